@@ -3,7 +3,9 @@ use crate::agent::dispatcher::{
 };
 use crate::agent::memory_loader::{DefaultMemoryLoader, MemoryLoader};
 use crate::agent::prompt::{PromptContext, SystemPromptBuilder};
+use crate::agent::prompt_templates::PromptVars;
 use crate::config::Config;
+use crate::error::ErrorCode;
 use crate::memory::{self, Memory, MemoryCategory};
 use crate::observability::{self, Observer, ObserverEvent};
 use crate::providers::{self, ChatMessage, ChatRequest, ConversationMessage, Provider};
@@ -35,6 +37,7 @@ pub struct Agent {
     history: Vec<ConversationMessage>,
     classification_config: crate::config::QueryClassificationConfig,
     available_hints: Vec<String>,
+    store: Option<Arc<crate::store::SharedStore>>,
 }
 
 pub struct AgentBuilder {
@@ -55,6 +58,7 @@ pub struct AgentBuilder {
     auto_save: Option<bool>,
     classification_config: Option<crate::config::QueryClassificationConfig>,
     available_hints: Option<Vec<String>>,
+    store: Option<Arc<crate::store::SharedStore>>,
 }
 
 impl AgentBuilder {
@@ -77,6 +81,7 @@ impl AgentBuilder {
             auto_save: None,
             classification_config: None,
             available_hints: None,
+            store: None,
         }
     }
 
@@ -171,6 +176,12 @@ impl AgentBuilder {
         self
     }
 
+    /// Store the per-user prompt template variables are read from.
+    pub fn store(mut self, store: Arc<crate::store::SharedStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
     pub fn build(self) -> Result<Agent> {
         let tools = self
             .tools
@@ -213,6 +224,7 @@ impl AgentBuilder {
             history: Vec::new(),
             classification_config: self.classification_config.unwrap_or_default(),
             available_hints: self.available_hints.unwrap_or_default(),
+            store: self.store,
         })
     }
 }
@@ -264,6 +276,7 @@ impl Agent {
             &security,
             runtime,
             memory.clone(),
+            memory.clone(),
             composio_key,
             composio_entity_id,
             &config.browser,
@@ -282,12 +295,13 @@ impl Agent {
             .unwrap_or("anthropic/claude-sonnet-4-20250514")
             .to_string();
 
+        let model_routes = providers::effective_model_routes(config);
         let provider: Box<dyn Provider> = providers::create_routed_provider(
             provider_name,
             config.api_key.as_deref(),
             config.api_url.as_deref(),
             &config.reliability,
-            &config.model_routes,
+            &model_routes,
             &model_name,
         )?;
 
@@ -299,8 +313,7 @@ impl Agent {
             _ => Box::new(XmlToolDispatcher),
         };
 
-        let available_hints: Vec<String> =
-            config.model_routes.iter().map(|r| r.hint.clone()).collect();
+        let available_hints: Vec<String> = model_routes.iter().map(|r| r.hint.clone()).collect();
 
         Agent::builder()
             .provider(provider)
@@ -319,6 +332,9 @@ impl Agent {
             .workspace_dir(config.workspace_dir.clone())
             .classification_config(config.query_classification.clone())
             .available_hints(available_hints)
+            .store(Arc::new(crate::store::SharedStore::new(
+                &config.zerobuild.db_path,
+            )))
             .identity_config(config.identity.clone())
             .skills(crate::skills::load_skills_with_config(
                 &config.workspace_dir,
@@ -358,6 +374,11 @@ impl Agent {
 
     fn build_system_prompt(&self) -> Result<String> {
         let instructions = self.tool_dispatcher.prompt_instructions(&self.tools);
+        let template_vars = self
+            .store
+            .as_deref()
+            .map(PromptVars::for_current_user)
+            .unwrap_or_default();
         let ctx = PromptContext {
             workspace_dir: &self.workspace_dir,
             model_name: &self.model_name,
@@ -366,6 +387,7 @@ impl Agent {
             skills_prompt_mode: self.skills_prompt_mode,
             identity_config: Some(&self.identity_config),
             dispatcher_instructions: &instructions,
+            template_vars: &template_vars,
         };
         self.prompt_builder.build(&ctx)
     }
@@ -384,7 +406,11 @@ impl Agent {
                     if r.success {
                         r.output
                     } else {
-                        format!("Error: {}", r.error.unwrap_or(r.output))
+                        let reason = r.error.unwrap_or(r.output);
+                        match r.error_code.or_else(|| ErrorCode::classify(&reason)) {
+                            Some(code) => format!("Error [{code}]: {reason}"),
+                            None => format!("Error: {reason}"),
+                        }
                     }
                 }
                 Err(e) => {
@@ -393,7 +419,11 @@ impl Agent {
                         duration: start.elapsed(),
                         success: false,
                     });
-                    format!("Error executing {}: {e}", call.name)
+                    format!(
+                        "Error executing {} [{}]: {e}",
+                        call.name,
+                        crate::error::code_of(&e)
+                    )
                 }
             }
         } else {
@@ -452,7 +482,17 @@ impl Agent {
                     | "file_edit"
                     | "shell"
                     | "sandbox_run_command"
+                    | "sandbox_shell"
+                    | "setup_project"
+                    | "ship_it"
                     | "sandbox_write_file"
+                    | "sandbox_write_files"
+                    | "sandbox_edit_file"
+                    | "sandbox_set_env"
+                    | "sandbox_move_file"
+                    | "sandbox_copy_file"
+                    | "sandbox_delete_path"
+                    | "generate_docs"
                     | "github_push"
             )
         });
@@ -482,13 +522,18 @@ impl Agent {
                         format!("Read file")
                     }
                 }
-                "shell" | "sandbox_run_command" => {
+                "shell" | "sandbox_run_command" | "sandbox_shell" => {
                     if let Some(cmd) = call.arguments.get("command").and_then(|v| v.as_str()) {
                         format!("Execute command: {}", cmd)
                     } else {
                         format!("Run command")
                     }
                 }
+                "setup_project" => {
+                    let name = call.arguments.get("name").and_then(|v| v.as_str());
+                    format!("Set up project: {}", name.unwrap_or("(unnamed)"))
+                }
+                "ship_it" => "Test, snapshot and deploy the project".to_string(),
                 "github_push" => "Push changes to GitHub".to_string(),
                 _ => format!("Call tool: {}", call.name),
             };
@@ -791,6 +836,7 @@ mod tests {
                 success: true,
                 output: "tool-out".into(),
                 error: None,
+                error_code: None,
                 error_hint: None,
             })
         }
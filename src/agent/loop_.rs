@@ -1,8 +1,9 @@
 use crate::approval::{ApprovalManager, ApprovalRequest, ApprovalResponse};
 use crate::config::Config;
+use crate::error::{self, ErrorCode};
 use crate::memory::{self, Memory, MemoryCategory};
 use crate::multimodal;
-use crate::observability::{self, runtime_trace, Observer, ObserverEvent};
+use crate::observability::{self, recording, runtime_trace, Observer, ObserverEvent};
 use crate::providers::{
     self, ChatMessage, ChatRequest, Provider, ProviderCapabilityError, ToolCall,
 };
@@ -18,11 +19,16 @@ use std::io::Write as _;
 use std::sync::{Arc, LazyLock};
 use std::time::{Duration, Instant};
 use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
 use uuid::Uuid;
 
 /// Minimum characters per chunk when relaying LLM text to a streaming draft.
 const STREAM_CHUNK_MIN_CHARS: usize = 80;
 
+/// Markers that start prompt-guided tool-call markup. Streamed text is not
+/// relayed past these so users never see raw tool-call syntax.
+const STREAM_TOOL_MARKUP_MARKERS: [&str; 4] = ["<tool_call", "<invoke", "<function", "```tool"];
+
 /// Default maximum agentic tool-use iterations per user message to prevent runaway loops.
 /// Used as a safe fallback when `max_tool_iterations` is unset or configured as zero.
 /// Increased from 10 to 25 to accommodate complex multi-step tasks while maintaining
@@ -257,7 +263,9 @@ async fn build_context(mem: &dyn Memory, user_msg: &str, min_relevance_score: f6
         if !relevant.is_empty() {
             context.push_str("[Memory context]\n");
             for entry in &relevant {
-                if memory::is_assistant_autosave_key(&entry.key) {
+                if memory::is_assistant_autosave_key(&entry.key)
+                    || crate::skills::learned::is_learned_skill_key(&entry.key)
+                {
                     continue;
                 }
                 let _ = writeln!(context, "- {}: {}", entry.key, entry.content);
@@ -270,6 +278,12 @@ async fn build_context(mem: &dyn Memory, user_msg: &str, min_relevance_score: f6
         }
     }
 
+    // Learned skills are rendered as few-shot guidance rather than raw entries.
+    context.push_str(
+        &crate::skills::learned::build_learned_skills_context(mem, user_msg, min_relevance_score)
+            .await,
+    );
+
     context
 }
 
@@ -1833,12 +1847,37 @@ fn is_github_tool(call_name: &str) -> bool {
     call_name.starts_with("github_") && call_name != "github_connect"
 }
 
+/// Run one tool call inside a `tool` span. Sandbox clients record the
+/// sandbox they used on it as `sandbox_id`.
 async fn execute_one_tool(
     call_name: &str,
     call_arguments: serde_json::Value,
     tools_registry: &[Box<dyn Tool>],
     observer: &dyn Observer,
     cancellation_token: Option<&CancellationToken>,
+) -> Result<ToolExecutionOutcome> {
+    let span = tracing::info_span!(
+        "tool",
+        tool = %call_name,
+        sandbox_id = tracing::field::Empty
+    );
+    run_tool_call(
+        call_name,
+        call_arguments,
+        tools_registry,
+        observer,
+        cancellation_token,
+    )
+    .instrument(span)
+    .await
+}
+
+async fn run_tool_call(
+    call_name: &str,
+    call_arguments: serde_json::Value,
+    tools_registry: &[Box<dyn Tool>],
+    observer: &dyn Observer,
+    cancellation_token: Option<&CancellationToken>,
 ) -> Result<ToolExecutionOutcome> {
     observer.record_event(&ObserverEvent::ToolCallStart {
         tool: call_name.to_string(),
@@ -1867,6 +1906,7 @@ async fn execute_one_tool(
                         output: format!("GitHub authentication required: {error_msg}"),
                         success: false,
                         error_reason: Some(scrub_credentials(&error_msg)),
+                        error_code: Some(ErrorCode::AuthFailed),
                         duration,
                     });
                 }
@@ -1894,6 +1934,7 @@ async fn execute_one_tool(
             output: reason.clone(),
             success: false,
             error_reason: Some(scrub_credentials(&reason)),
+            error_code: Some(ErrorCode::InvalidInput),
             duration,
         });
     };
@@ -1921,14 +1962,21 @@ async fn execute_one_tool(
                     output: scrub_credentials(&r.output),
                     success: true,
                     error_reason: None,
+                    error_code: None,
                     duration,
                 })
             } else {
                 let reason = r.error.unwrap_or(r.output);
+                let code = r.error_code.or_else(|| ErrorCode::classify(&reason));
+                let output = match code {
+                    Some(code) => format!("Error [{code}]: {reason}"),
+                    None => format!("Error: {reason}"),
+                };
                 Ok(ToolExecutionOutcome {
-                    output: format!("Error: {reason}"),
+                    output,
                     success: false,
                     error_reason: Some(scrub_credentials(&reason)),
+                    error_code: code,
                     duration,
                 })
             }
@@ -1940,11 +1988,13 @@ async fn execute_one_tool(
                 duration,
                 success: false,
             });
-            let reason = format!("Error executing {call_name}: {e}");
+            let code = error::code_of(&e);
+            let reason = format!("Error executing {call_name} [{code}]: {e}");
             Ok(ToolExecutionOutcome {
                 output: reason.clone(),
                 success: false,
                 error_reason: Some(scrub_credentials(&reason)),
+                error_code: Some(code),
                 duration,
             })
         }
@@ -1955,6 +2005,8 @@ struct ToolExecutionOutcome {
     output: String,
     success: bool,
     error_reason: Option<String>,
+    /// Category of the failure, shown to the model in `output`.
+    error_code: Option<ErrorCode>,
     duration: Duration,
 }
 
@@ -2036,6 +2088,169 @@ async fn execute_tools_sequential(
 //   • max_iterations is reached (runaway safety), or
 //   • the cancellation token fires (external abort).
 
+/// Length of the prefix of `text` that is safe to show while streaming:
+/// everything before tool-call markup, holding back a trailing `<`/backtick
+/// fragment that could still grow into a marker. The flag reports whether
+/// markup was found.
+fn streamable_prefix_len(text: &str) -> (usize, bool) {
+    if let Some(idx) = STREAM_TOOL_MARKUP_MARKERS
+        .iter()
+        .filter_map(|marker| text.find(marker))
+        .min()
+    {
+        return (idx, true);
+    }
+    let hold_back_from = text.len().saturating_sub(16);
+    let held = text
+        .char_indices()
+        .rev()
+        .take_while(|(idx, _)| *idx >= hold_back_from)
+        .filter(|(_, ch)| *ch == '<' || *ch == '`')
+        .last()
+        .map(|(idx, _)| idx);
+    (held.unwrap_or(text.len()), false)
+}
+
+/// Request a completion as a token stream, relaying visible text deltas to
+/// `on_delta` as they arrive. With `tools`, native tool calls are assembled
+/// from their streamed fragments. Usage comes from the final chunk.
+///
+/// Returns `Ok(None)` when the stream failed or produced nothing, so the
+/// caller can fall back to a regular request; any partial draft is cleared.
+async fn stream_chat_response(
+    provider: &dyn Provider,
+    messages: &[ChatMessage],
+    tools: Option<&[crate::tools::ToolSpec]>,
+    model: &str,
+    temperature: f64,
+    on_delta: &tokio::sync::mpsc::Sender<String>,
+) -> Result<Option<providers::ChatResponse>> {
+    use futures_util::StreamExt;
+
+    let options = providers::traits::StreamOptions::new(true);
+    let mut stream = match tools {
+        Some(tools) => {
+            provider.stream_chat_with_tools(messages, tools, model, temperature, options)
+        }
+        None => provider.stream_chat_with_history(messages, model, temperature, options),
+    };
+    let mut text = String::new();
+    let mut tool_calls = ToolCallAssembler::default();
+    let mut usage = None;
+    let mut relayed = 0;
+    let mut markup_seen = false;
+
+    while let Some(chunk) = stream.next().await {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                tracing::debug!("Streaming failed, falling back to a regular request: {e}");
+                if relayed > 0 {
+                    let _ = on_delta.send(DRAFT_CLEAR_SENTINEL.to_string()).await;
+                }
+                return Ok(None);
+            }
+        };
+        text.push_str(&chunk.delta);
+        tool_calls.push(chunk.tool_calls);
+        if chunk.usage.is_some() {
+            usage = chunk.usage;
+        }
+        if chunk.is_final {
+            break;
+        }
+        if markup_seen {
+            continue;
+        }
+        let (visible, found) = streamable_prefix_len(&text);
+        markup_seen = found;
+        if visible > relayed {
+            if relayed == 0 {
+                // Replace progress lines with the answer as it streams in.
+                let _ = on_delta.send(DRAFT_CLEAR_SENTINEL.to_string()).await;
+            }
+            let _ = on_delta.send(text[relayed..visible].to_string()).await;
+            relayed = visible;
+        }
+    }
+
+    let tool_calls = tool_calls.finish();
+    if text.trim().is_empty() && tool_calls.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(providers::ChatResponse {
+        text: Some(text).filter(|text| !text.is_empty()),
+        tool_calls,
+        usage,
+        ..providers::ChatResponse::default()
+    }))
+}
+
+/// Collects streamed native tool-call fragments into whole calls.
+#[derive(Default)]
+struct ToolCallAssembler {
+    /// index → (id, name, arguments)
+    calls: std::collections::BTreeMap<usize, (Option<String>, String, String)>,
+}
+
+impl ToolCallAssembler {
+    fn push(&mut self, fragments: Vec<providers::traits::ToolCallDelta>) {
+        for fragment in fragments {
+            let call = self.calls.entry(fragment.index).or_default();
+            if fragment.id.is_some() {
+                call.0 = fragment.id;
+            }
+            if let Some(name) = fragment.name {
+                call.1.push_str(&name);
+            }
+            call.2.push_str(&fragment.arguments);
+        }
+    }
+
+    /// Calls in index order. Calls without a name are dropped; arguments
+    /// that are not valid JSON become `{}`, as for non-streamed responses.
+    fn finish(self) -> Vec<providers::ToolCall> {
+        self.calls
+            .into_values()
+            .filter(|(_, name, _)| !name.is_empty())
+            .map(|(id, name, arguments)| {
+                let arguments = if arguments.trim().is_empty() {
+                    "{}".to_string()
+                } else if serde_json::from_str::<serde_json::Value>(&arguments).is_ok() {
+                    arguments
+                } else {
+                    tracing::warn!(
+                        function = %name,
+                        arguments = %arguments,
+                        "Invalid JSON in streamed tool-call arguments, using empty object"
+                    );
+                    "{}".to_string()
+                };
+                providers::ToolCall {
+                    id: id.unwrap_or_else(|| Uuid::new_v4().to_string()),
+                    name,
+                    arguments,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Header of the user message that carries images returned by tools.
+const TOOL_IMAGES_HEADER: &str = "[Tool images]";
+
+/// Turn image markers from earlier tool rounds into plain text so only the
+/// newest tool images are sent to the provider.
+fn retire_tool_images(history: &mut [ChatMessage]) {
+    for message in history.iter_mut().filter(|m| {
+        m.role == "user"
+            && (m.content.starts_with(TOOL_IMAGES_HEADER)
+                || m.content.starts_with("[Tool results]"))
+    }) {
+        message.content = multimodal::redact_image_markers(&message.content);
+    }
+}
+
 /// Execute a single turn of the agent loop: send messages, parse tool calls,
 /// execute tools, and loop until the LLM produces a final text response.
 #[allow(clippy::too_many_arguments)]
@@ -2101,10 +2316,12 @@ pub(crate) async fn run_tool_call_loop(
 
         // ── Progress: LLM thinking ────────────────────────────
         if let Some(ref tx) = on_delta {
+            let locale = crate::i18n::current();
             let phase = if iteration == 0 {
-                "\u{1f914} Thinking...\n".to_string()
+                crate::i18n::t(locale, "thinking", &[])
             } else {
-                format!("\u{1f914} Thinking (round {})...\n", iteration + 1)
+                let round = (iteration + 1).to_string();
+                crate::i18n::t(locale, "thinking_round", &[("round", &round)])
             };
             let _ = tx.send(phase).await;
         }
@@ -2137,20 +2354,46 @@ pub(crate) async fn run_tool_call_loop(
 
         // Unified path via Provider::chat so provider-specific native tool logic
         // (OpenAI/Anthropic/OpenRouter/compatible adapters) is honored.
+        // With `[agent.phases]`, only the current phase's schemas are sent.
+        let phase_specs = super::phases::exposed_specs(&tool_specs);
         let request_tools = if use_native_tools {
-            Some(tool_specs.as_slice())
+            Some(phase_specs.as_deref().unwrap_or(&tool_specs))
         } else {
             None
         };
 
-        let chat_future = provider.chat(
-            ChatRequest {
-                messages: &prepared_messages.messages,
-                tools: request_tools,
-            },
-            model,
-            temperature,
-        );
+        // Stream tokens to the draft; requests with native tool schemas need
+        // a provider that can stream tool calls.
+        let stream_tx = on_delta.as_ref().filter(|_| {
+            provider.supports_streaming()
+                && (request_tools.is_none() || provider.supports_streaming_tool_calls())
+        });
+        let chat_future = async {
+            if let Some(tx) = stream_tx {
+                if let Some(resp) = stream_chat_response(
+                    provider,
+                    &prepared_messages.messages,
+                    request_tools,
+                    model,
+                    temperature,
+                    tx,
+                )
+                .await?
+                {
+                    return Ok(resp);
+                }
+            }
+            provider
+                .chat(
+                    ChatRequest {
+                        messages: &prepared_messages.messages,
+                        tools: request_tools,
+                    },
+                    model,
+                    temperature,
+                )
+                .await
+        };
 
         let chat_result = if let Some(token) = cancellation_token.as_ref() {
             tokio::select! {
@@ -2170,11 +2413,11 @@ pub(crate) async fn run_tool_call_loop(
             reasoning_content,
         ) = match chat_result {
             Ok(resp) => {
-                let (resp_input_tokens, resp_output_tokens) = resp
+                let (resp_input_tokens, resp_output_tokens, resp_cached_tokens) = resp
                     .usage
                     .as_ref()
-                    .map(|u| (u.input_tokens, u.output_tokens))
-                    .unwrap_or((None, None));
+                    .map(|u| (u.input_tokens, u.output_tokens, u.cached_input_tokens))
+                    .unwrap_or((None, None, None));
 
                 observer.record_event(&ObserverEvent::LlmResponse {
                     provider: provider_name.to_string(),
@@ -2184,6 +2427,7 @@ pub(crate) async fn run_tool_call_loop(
                     error_message: None,
                     input_tokens: resp_input_tokens,
                     output_tokens: resp_output_tokens,
+                    cached_input_tokens: resp_cached_tokens,
                 });
 
                 let response_text = resp.text_or_empty().to_string();
@@ -2280,6 +2524,7 @@ pub(crate) async fn run_tool_call_loop(
                     error_message: Some(safe_error.clone()),
                     input_tokens: None,
                     output_tokens: None,
+                    cached_input_tokens: None,
                 });
                 runtime_trace::record_event(
                     "llm_response",
@@ -2308,10 +2553,13 @@ pub(crate) async fn run_tool_call_loop(
         if let Some(ref tx) = on_delta {
             let llm_secs = llm_started_at.elapsed().as_secs();
             if !tool_calls.is_empty() {
+                let count = tool_calls.len().to_string();
+                let secs = llm_secs.to_string();
                 let _ = tx
-                    .send(format!(
-                        "\u{1f4ac} Got {} tool call(s) ({llm_secs}s)\n",
-                        tool_calls.len()
+                    .send(crate::i18n::t(
+                        crate::i18n::current(),
+                        "tool_calls",
+                        &[("count", &count), ("secs", &secs)],
                     ))
                     .await;
             }
@@ -2398,6 +2646,7 @@ pub(crate) async fn run_tool_call_loop(
                 "[AGENT] Executing tool: {} with args: {:?}",
                 call.name, call.arguments
             );
+            super::phases::follow_call(&call.name);
 
             // ── Hook: before_tool_call (modifying) ──────────
             let mut tool_name = call.name.clone();
@@ -2431,6 +2680,7 @@ pub(crate) async fn run_tool_call_loop(
                                 output: cancelled,
                                 success: false,
                                 error_reason: Some(scrub_credentials(&reason)),
+                                error_code: None,
                                 duration: Duration::ZERO,
                             },
                         ));
@@ -2483,6 +2733,7 @@ pub(crate) async fn run_tool_call_loop(
                                 output: denied.clone(),
                                 success: false,
                                 error_reason: Some(denied),
+                                error_code: Some(ErrorCode::PermissionDenied),
                                 duration: Duration::ZERO,
                             },
                         ));
@@ -2518,6 +2769,7 @@ pub(crate) async fn run_tool_call_loop(
                         output: duplicate.clone(),
                         success: false,
                         error_reason: Some(duplicate),
+                        error_code: None,
                         duration: Duration::ZERO,
                     },
                 ));
@@ -2559,22 +2811,29 @@ pub(crate) async fn run_tool_call_loop(
             });
         }
 
-        let executed_outcomes = if allow_parallel_execution && executable_calls.len() > 1 {
-            execute_tools_parallel(
-                &executable_calls,
-                tools_registry,
-                observer,
-                cancellation_token.as_ref(),
-            )
-            .await?
-        } else {
-            execute_tools_sequential(
-                &executable_calls,
-                tools_registry,
-                observer,
-                cancellation_token.as_ref(),
-            )
-            .await?
+        let execution = async {
+            if allow_parallel_execution && executable_calls.len() > 1 {
+                execute_tools_parallel(
+                    &executable_calls,
+                    tools_registry,
+                    observer,
+                    cancellation_token.as_ref(),
+                )
+                .await
+            } else {
+                execute_tools_sequential(
+                    &executable_calls,
+                    tools_registry,
+                    observer,
+                    cancellation_token.as_ref(),
+                )
+                .await
+            }
+        };
+        // ── Progress: intermediate lines from long-running tools ──
+        let executed_outcomes = match on_delta.clone() {
+            Some(tx) => crate::tools::progress::with_tool_progress(tx, execution).await?,
+            None => execution.await?,
         };
 
         for ((idx, call), outcome) in executable_indices
@@ -2595,6 +2854,7 @@ pub(crate) async fn run_tool_call_loop(
                     "tool": call.name.clone(),
                     "duration_ms": outcome.duration.as_millis(),
                     "output": scrub_credentials(&outcome.output),
+                    "error_code": outcome.error_code,
                 }),
             );
 
@@ -2604,6 +2864,7 @@ pub(crate) async fn run_tool_call_loop(
                     success: outcome.success,
                     output: outcome.output.clone(),
                     error: None,
+                    error_code: outcome.error_code,
                     error_hint: None,
                 };
                 hooks
@@ -2650,14 +2911,34 @@ pub(crate) async fn run_tool_call_loop(
             ordered_results[*idx] = Some((call.name.clone(), call.tool_call_id.clone(), outcome));
         }
 
+        // Images referenced by tool output (e.g. screenshots) are shown to
+        // vision models; only the latest round's images are kept so repeated
+        // captures stay within `[multimodal]` limits.
+        let tool_image_refs: Vec<String> = ordered_results
+            .iter()
+            .flatten()
+            .flat_map(|(_, _, outcome)| multimodal::parse_image_markers(&outcome.output).1)
+            .collect();
+        let mut attach_tool_images = false;
+        if !tool_image_refs.is_empty() {
+            retire_tool_images(history);
+            let (max_images, _) = multimodal_config.effective_limits();
+            let room = max_images.saturating_sub(multimodal::count_image_markers(history));
+            attach_tool_images = provider.supports_vision() && tool_image_refs.len() <= room;
+        }
+
         for entry in ordered_results {
             if let Some((tool_name, tool_call_id, outcome)) = entry {
-                individual_results.push((tool_call_id, outcome.output.clone()));
+                let output = if attach_tool_images {
+                    outcome.output
+                } else {
+                    multimodal::redact_image_markers(&outcome.output)
+                };
                 let _ = writeln!(
                     tool_results,
-                    "<tool_result name=\"{}\">\n{}\n</tool_result>",
-                    tool_name, outcome.output
+                    "<tool_result name=\"{tool_name}\">\n{output}\n</tool_result>",
                 );
+                individual_results.push((tool_call_id, output));
             }
         }
 
@@ -2694,6 +2975,19 @@ pub(crate) async fn run_tool_call_loop(
                 history.push(ChatMessage::tool(tool_msg.to_string()));
             }
         }
+        // Tool messages are never parsed for image markers, so native-mode
+        // results get a follow-up user message carrying the images.
+        let results_in_tool_messages = history.last().is_some_and(|m| m.role == "tool");
+        if attach_tool_images && results_in_tool_messages {
+            let markers: Vec<String> = tool_image_refs
+                .iter()
+                .map(|reference| format!("[IMAGE:{reference}]"))
+                .collect();
+            history.push(ChatMessage::user(format!(
+                "{TOOL_IMAGES_HEADER}\n{}",
+                markers.join("\n")
+            )));
+        }
     }
 
     runtime_trace::record_event(
@@ -2758,6 +3052,25 @@ pub(crate) fn build_tool_budget_prompt(max_iterations: usize) -> String {
     )
 }
 
+/// Append the current user's stored preferences to the system prompt.
+/// Prompt template variables of the user being served.
+fn prompt_vars(config: &Config) -> crate::agent::prompt_templates::PromptVars {
+    crate::agent::prompt_templates::PromptVars::for_current_user(&crate::store::SharedStore::new(
+        &config.zerobuild.db_path,
+    ))
+}
+
+fn append_preferences(system_prompt: &mut String, config: &Config) {
+    let preferences = crate::store::preferences::load_prompt_section(
+        std::path::Path::new(&config.zerobuild.db_path),
+        &crate::store::preferences::current_user(),
+    );
+    if !preferences.is_empty() {
+        system_prompt.push_str("\n\n");
+        system_prompt.push_str(&preferences);
+    }
+}
+
 // ── CLI Entrypoint ───────────────────────────────────────────────────────
 // Wires up all subsystems (observer, runtime, security, memory, tools,
 // provider, hardware RAG, peripherals) and enters either single-shot or
@@ -2828,6 +3141,7 @@ pub async fn run(
         &security,
         runtime,
         mem.clone(),
+        mem.clone(),
         composio_key,
         composio_entity_id,
         &config.browser,
@@ -2861,18 +3175,35 @@ pub async fn run(
         zerobuild_dir: config.config_path.parent().map(std::path::PathBuf::from),
         secrets_encrypt: config.secrets.encrypt,
         reasoning_enabled: config.runtime.reasoning_enabled,
+        local_provider: config.local_provider.clone(),
+        prompt_caching: config.agent.prompt_caching,
     };
 
+    let model_routes = providers::effective_model_routes(&config);
     let provider: Box<dyn Provider> = providers::create_routed_provider_with_options(
         provider_name,
         config.api_key.as_deref(),
         config.api_url.as_deref(),
         &config.reliability,
-        &config.model_routes,
+        &model_routes,
         model_name,
         &provider_runtime_options,
     )?;
 
+    // ── Session recording (observability.record_sessions) ────────
+    let channel_name = if interactive { "cli" } else { "daemon" };
+    let recorder = recording::start_session(channel_name, &tools_registry);
+    let (provider, tools_registry) = match &recorder {
+        Some(recorder) => (
+            Box::new(recording::RecordingProvider::new(
+                Arc::from(provider),
+                Arc::clone(recorder),
+            )) as Box<dyn Provider>,
+            recording::record_tools(Arc::new(tools_registry), recorder),
+        ),
+        None => (provider, tools_registry),
+    };
+
     observer.record_event(&ObserverEvent::AgentStart {
         provider: provider_name.to_string(),
         model: model_name.to_string(),
@@ -2972,6 +3303,14 @@ pub async fn run(
         "model_routing_config",
         "Configure default model, scenario routing, and delegate agents. Use for natural-language requests like: 'set conversation to kimi and coding to gpt-5.3-codex'.",
     ));
+    tool_descs.push((
+        "model_routing",
+        "List, set or delete the user's stored per-hint model routes, which override config.toml routes. Use when the user wants a routing preference remembered.",
+    ));
+    tool_descs.push((
+        "preferences",
+        "List, set or delete the user's generation defaults (package manager, CSS framework, language, code style, license, deploy target). Use when the user states a lasting preference such as 'always use pnpm'.",
+    ));
     if !config.agents.is_empty() {
         tool_descs.push((
             "delegate",
@@ -3023,6 +3362,7 @@ pub async fn run(
         bootstrap_max_chars,
         native_tools,
         config.skills.prompt_injection_mode,
+        &prompt_vars(&config),
     );
 
     // Inject active project context into system prompt if available
@@ -3032,6 +3372,7 @@ pub async fn run(
         system_prompt.push_str(&project_context);
         system_prompt.push('\n');
     }
+    append_preferences(&mut system_prompt, &config);
 
     // Append structured tool-use instructions with schemas (only for non-native providers)
     if !native_tools {
@@ -3052,7 +3393,6 @@ pub async fn run(
     } else {
         None
     };
-    let channel_name = if interactive { "cli" } else { "daemon" };
 
     // ── Execute ──────────────────────────────────────────────────
     let start = Instant::now();
@@ -3088,7 +3428,17 @@ pub async fn run(
             ChatMessage::user(&enriched),
         ];
 
-        let response = run_tool_call_loop(
+        if let Some(recorder) = &recorder {
+            recorder.record_turn(
+                provider_name,
+                model_name,
+                temperature,
+                config.agent.max_tool_iterations,
+                &[],
+                &history,
+            );
+        }
+        let result = run_tool_call_loop(
             provider.as_ref(),
             &mut history,
             &tools_registry,
@@ -3106,7 +3456,11 @@ pub async fn run(
             None,
             &[],
         )
-        .await?;
+        .await;
+        if let Some(recorder) = &recorder {
+            recorder.record_turn_end(result.as_deref().map_err(|e| format!("{e:#}")));
+        }
+        let response = result?;
         final_output = response.clone();
         println!("{response}");
         observer.record_event(&ObserverEvent::TurnComplete);
@@ -3151,9 +3505,20 @@ pub async fn run(
                     println!("Available commands:");
                     println!("  /help        Show this help message");
                     println!("  /clear /new  Clear conversation history");
+                    println!("  /approve <code>  Allow one guardrail-blocked action");
                     println!("  /quit /exit  Exit interactive mode\n");
                     continue;
                 }
+                command if command.starts_with("/approve") => {
+                    let code = command.trim_start_matches("/approve").trim();
+                    let user = crate::store::preferences::current_user();
+                    if crate::security::guardrails::approve(&user, code) {
+                        println!("Approved. The blocked action can run once.\n");
+                    } else {
+                        println!("No pending action has approval code `{code}`.\n");
+                    }
+                    continue;
+                }
                 "/clear" | "/new" => {
                     println!(
                         "This will clear the current conversation and delete all session memory."
@@ -3218,7 +3583,17 @@ pub async fn run(
 
             history.push(ChatMessage::user(&enriched));
 
-            let response = match run_tool_call_loop(
+            if let Some(recorder) = &recorder {
+                recorder.record_turn(
+                    provider_name,
+                    model_name,
+                    temperature,
+                    config.agent.max_tool_iterations,
+                    &[],
+                    &history,
+                );
+            }
+            let result = run_tool_call_loop(
                 provider.as_ref(),
                 &mut history,
                 &tools_registry,
@@ -3236,8 +3611,11 @@ pub async fn run(
                 None,
                 &[],
             )
-            .await
-            {
+            .await;
+            if let Some(recorder) = &recorder {
+                recorder.record_turn_end(result.as_deref().map_err(|e| format!("{e:#}")));
+            }
+            let response = match result {
                 Ok(resp) => resp,
                 Err(e) => {
                     eprintln!("\nError: {e}\n");
@@ -3317,6 +3695,7 @@ pub async fn process_message(config: Config, message: &str) -> Result<String> {
         &security,
         runtime,
         mem.clone(),
+        mem.clone(),
         composio_key,
         composio_entity_id,
         &config.browser,
@@ -3335,15 +3714,17 @@ pub async fn process_message(config: Config, message: &str) -> Result<String> {
         .default_model
         .clone()
         .unwrap_or_else(|| "anthropic/claude-sonnet-4-20250514".into());
+    let model_routes = providers::effective_model_routes(&config);
 
     // ── Model recommendation based on task type ────────────────────────
     if let Some(task_type) = crate::agent::model_advisor::detect_task_type(message) {
         if !crate::agent::model_advisor::is_model_suitable(&model_name, task_type) {
-            let has_routing = !config.model_routes.is_empty();
+            let has_routing = !model_routes.is_empty();
             let recommendation = crate::agent::model_advisor::generate_recommendation(
                 &model_name,
                 task_type,
                 has_routing,
+                providers::local::is_local_provider(provider_name),
             );
 
             // Log recommendation (in interactive mode, this could be shown to user)
@@ -3366,16 +3747,29 @@ pub async fn process_message(config: Config, message: &str) -> Result<String> {
         zerobuild_dir: config.config_path.parent().map(std::path::PathBuf::from),
         secrets_encrypt: config.secrets.encrypt,
         reasoning_enabled: config.runtime.reasoning_enabled,
+        local_provider: config.local_provider.clone(),
+        prompt_caching: config.agent.prompt_caching,
     };
     let provider: Box<dyn Provider> = providers::create_routed_provider_with_options(
         provider_name,
         config.api_key.as_deref(),
         config.api_url.as_deref(),
         &config.reliability,
-        &config.model_routes,
+        &model_routes,
         &model_name,
         &provider_runtime_options,
     )?;
+    let recorder = recording::start_session("daemon", &tools_registry);
+    let (provider, tools_registry) = match &recorder {
+        Some(recorder) => (
+            Box::new(recording::RecordingProvider::new(
+                Arc::from(provider),
+                Arc::clone(recorder),
+            )) as Box<dyn Provider>,
+            recording::record_tools(Arc::new(tools_registry), recorder),
+        ),
+        None => (provider, tools_registry),
+    };
 
     let hardware_rag: Option<crate::rag::HardwareRag> = config
         .peripherals
@@ -3404,6 +3798,8 @@ pub async fn process_message(config: Config, message: &str) -> Result<String> {
             "model_routing_config",
             "Configure default model, scenario routing, and delegate agents.",
         ),
+        ("model_routing", "Store the user's per-hint model routes."),
+        ("preferences", "Store the user's generation defaults."),
         ("screenshot", "Capture a screenshot."),
         ("image_info", "Read image metadata."),
     ];
@@ -3455,7 +3851,9 @@ pub async fn process_message(config: Config, message: &str) -> Result<String> {
         bootstrap_max_chars,
         native_tools,
         config.skills.prompt_injection_mode,
+        &prompt_vars(&config),
     );
+    append_preferences(&mut system_prompt, &config);
     if !native_tools {
         system_prompt.push_str(&build_tool_instructions(&tools_registry));
     }
@@ -3486,7 +3884,17 @@ pub async fn process_message(config: Config, message: &str) -> Result<String> {
         ChatMessage::user(&enriched),
     ];
 
-    agent_turn(
+    if let Some(recorder) = &recorder {
+        recorder.record_turn(
+            provider_name,
+            &model_name,
+            config.default_temperature,
+            config.agent.max_tool_iterations,
+            &[],
+            &history,
+        );
+    }
+    let result = agent_turn(
         provider.as_ref(),
         &mut history,
         &tools_registry,
@@ -3498,7 +3906,11 @@ pub async fn process_message(config: Config, message: &str) -> Result<String> {
         &config.multimodal,
         config.agent.max_tool_iterations,
     )
-    .await
+    .await;
+    if let Some(recorder) = &recorder {
+        recorder.record_turn_end(result.as_deref().map_err(|e| format!("{e:#}")));
+    }
+    result
 }
 
 #[cfg(test)]
@@ -3563,6 +3975,7 @@ mod tests {
             ProviderCapabilities {
                 native_tool_calling: false,
                 vision: true,
+                max_context_tokens: None,
             }
         }
 
@@ -3662,6 +4075,110 @@ mod tests {
         }
     }
 
+    /// Provider that streams a fixed list of deltas and never answers `chat`.
+    struct StreamingProvider {
+        deltas: Vec<&'static str>,
+    }
+
+    #[async_trait]
+    impl Provider for StreamingProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            anyhow::bail!("streaming provider only streams");
+        }
+
+        fn supports_streaming(&self) -> bool {
+            true
+        }
+
+        fn stream_chat_with_history(
+            &self,
+            _messages: &[ChatMessage],
+            _model: &str,
+            _temperature: f64,
+            _options: providers::traits::StreamOptions,
+        ) -> futures_util::stream::BoxStream<
+            'static,
+            providers::traits::StreamResult<providers::traits::StreamChunk>,
+        > {
+            use futures_util::StreamExt;
+            let mut chunks: Vec<_> = self
+                .deltas
+                .iter()
+                .map(|d| Ok(providers::traits::StreamChunk::delta(*d)))
+                .collect();
+            chunks.push(Ok(providers::traits::StreamChunk::final_chunk()));
+            futures_util::stream::iter(chunks).boxed()
+        }
+    }
+
+    /// Provider that streams native tool calls: each request takes the next
+    /// scripted round of chunks.
+    struct NativeStreamingProvider {
+        rounds: Mutex<Vec<Vec<providers::traits::StreamChunk>>>,
+    }
+
+    #[async_trait]
+    impl Provider for NativeStreamingProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            anyhow::bail!("streaming provider only streams");
+        }
+
+        fn supports_native_tools(&self) -> bool {
+            true
+        }
+
+        fn supports_streaming(&self) -> bool {
+            true
+        }
+
+        fn supports_streaming_tool_calls(&self) -> bool {
+            true
+        }
+
+        fn stream_chat_with_tools(
+            &self,
+            _messages: &[ChatMessage],
+            _tools: &[crate::tools::ToolSpec],
+            _model: &str,
+            _temperature: f64,
+            _options: providers::traits::StreamOptions,
+        ) -> futures_util::stream::BoxStream<
+            'static,
+            providers::traits::StreamResult<providers::traits::StreamChunk>,
+        > {
+            use futures_util::StreamExt;
+            let round = self.rounds.lock().unwrap().remove(0);
+            futures_util::stream::iter(round.into_iter().map(Ok)).boxed()
+        }
+    }
+
+    fn tool_call_fragment(
+        id: Option<&str>,
+        name: Option<&str>,
+        arguments: &str,
+    ) -> providers::traits::StreamChunk {
+        let mut chunk = providers::traits::StreamChunk::delta("");
+        chunk.tool_calls = vec![providers::traits::ToolCallDelta {
+            index: 0,
+            id: id.map(str::to_string),
+            name: name.map(str::to_string),
+            arguments: arguments.to_string(),
+        }];
+        chunk
+    }
+
     struct CountingTool {
         name: String,
         invocations: Arc<AtomicUsize>,
@@ -3708,6 +4225,7 @@ mod tests {
                 success: true,
                 output: format!("counted:{value}"),
                 error: None,
+                error_code: None,
                 error_hint: None,
             })
         }
@@ -3777,11 +4295,94 @@ mod tests {
                 success: true,
                 output: format!("ok:{value}"),
                 error: None,
+                error_code: None,
+                error_hint: None,
+            })
+        }
+    }
+
+    struct FailingTool {
+        name: &'static str,
+    }
+
+    #[async_trait]
+    impl Tool for FailingTool {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn description(&self) -> &str {
+            "Fails for error-code tests"
+        }
+
+        fn parameters_schema(&self) -> serde_json::Value {
+            serde_json::json!({ "type": "object" })
+        }
+
+        async fn execute(
+            &self,
+            _args: serde_json::Value,
+        ) -> anyhow::Result<crate::tools::ToolResult> {
+            if self.name == "typed" {
+                return Err(crate::error::ZerobuildError::SandboxExpired("sbx-1".into()).into());
+            }
+            Ok(crate::tools::ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some("GitHub API returned 429 Too Many Requests".into()),
+                error_code: None,
                 error_hint: None,
             })
         }
     }
 
+    #[tokio::test]
+    async fn failed_tool_calls_carry_error_codes() {
+        let tools_registry: Vec<Box<dyn Tool>> = vec![
+            Box::new(FailingTool { name: "typed" }),
+            Box::new(FailingTool { name: "untyped" }),
+        ];
+        let observer = NoopObserver;
+
+        let typed = execute_one_tool(
+            "typed",
+            serde_json::json!({}),
+            &tools_registry,
+            &observer,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(typed.error_code, Some(ErrorCode::SandboxExpired));
+        assert!(typed.output.contains("[sandbox_expired]"));
+
+        let untyped = execute_one_tool(
+            "untyped",
+            serde_json::json!({}),
+            &tools_registry,
+            &observer,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(untyped.error_code, Some(ErrorCode::RateLimited));
+        assert!(untyped.output.starts_with("Error [rate_limited]: "));
+    }
+
+    #[test]
+    fn retire_tool_images_keeps_user_images() {
+        let mut history = vec![
+            ChatMessage::user("Clone this [IMAGE:/tmp/mockup.png]"),
+            ChatMessage::user(format!("{TOOL_IMAGES_HEADER}\n[IMAGE:/tmp/shot1.png]")),
+            ChatMessage::user("[Tool results]\n[IMAGE:/tmp/shot2.png]"),
+        ];
+
+        retire_tool_images(&mut history);
+
+        assert_eq!(crate::multimodal::count_image_markers(&history), 1);
+        assert!(history[1].content.contains("[image: /tmp/shot1.png]"));
+    }
+
     #[tokio::test]
     async fn run_tool_call_loop_returns_structured_error_for_non_vision_provider() {
         let calls = Arc::new(AtomicUsize::new(0));
@@ -3839,6 +4440,7 @@ mod tests {
             max_images: 4,
             max_image_size_mb: 1,
             allow_remote_fetch: false,
+            ..crate::config::MultimodalConfig::default()
         };
 
         let err = run_tool_call_loop(
@@ -3966,6 +4568,131 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn streamable_prefix_stops_at_tool_markup_and_holds_partial_tags() {
+        assert_eq!(streamable_prefix_len("plain answer"), (12, false));
+        assert_eq!(
+            streamable_prefix_len("Creating it now <tool_call>{}"),
+            (16, true)
+        );
+        // A trailing `<tool` fragment could still become markup.
+        assert_eq!(streamable_prefix_len("Let me check <tool"), (13, false));
+    }
+
+    #[tokio::test]
+    async fn run_tool_call_loop_streams_tokens_to_draft() {
+        let provider = StreamingProvider {
+            deltas: vec!["Your shop ", "is ready."],
+        };
+        let tools_registry: Vec<Box<dyn Tool>> = Vec::new();
+        let mut history = vec![ChatMessage::user("build a shop")];
+        let (tx, mut rx) = tokio::sync::mpsc::channel::<String>(64);
+
+        let result = run_tool_call_loop(
+            &provider,
+            &mut history,
+            &tools_registry,
+            &NoopObserver,
+            "mock-provider",
+            "mock-model",
+            0.0,
+            true,
+            None,
+            "telegram",
+            &crate::config::MultimodalConfig::default(),
+            3,
+            None,
+            Some(tx),
+            None,
+            &[],
+        )
+        .await
+        .expect("streamed turn should succeed");
+        assert_eq!(result, "Your shop is ready.");
+
+        let mut deltas = Vec::new();
+        while let Ok(delta) = rx.try_recv() {
+            deltas.push(delta);
+        }
+        assert!(
+            deltas.iter().any(|d| d == "Your shop "),
+            "first token batch should be relayed before the turn completes: {deltas:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn run_tool_call_loop_streams_native_tool_calls_and_usage() {
+        use providers::traits::StreamChunk;
+        let provider = || {
+            let usage = providers::traits::TokenUsage {
+                input_tokens: Some(40),
+                output_tokens: Some(7),
+                cached_input_tokens: None,
+            };
+            NativeStreamingProvider {
+                rounds: Mutex::new(vec![
+                    vec![
+                        tool_call_fragment(Some("call_1"), Some("count_tool"), "{\"val"),
+                        tool_call_fragment(None, None, "ue\":\"A\"}"),
+                        StreamChunk::final_chunk().with_usage(Some(usage.clone())),
+                    ],
+                    vec![
+                        StreamChunk::delta("Counted."),
+                        StreamChunk::final_chunk().with_usage(Some(usage)),
+                    ],
+                ]),
+            }
+        };
+        let invocations = Arc::new(AtomicUsize::new(0));
+        let tools_registry: Vec<Box<dyn Tool>> = vec![Box::new(CountingTool::new(
+            "count_tool",
+            Arc::clone(&invocations),
+        ))];
+        let (tx, _rx) = tokio::sync::mpsc::channel::<String>(64);
+
+        let specs: Vec<_> = tools_registry.iter().map(|t| t.spec()).collect();
+        let streamed = stream_chat_response(
+            &provider(),
+            &[ChatMessage::user("count")],
+            Some(&specs),
+            "mock-model",
+            0.0,
+            &tx,
+        )
+        .await
+        .unwrap()
+        .expect("stream should produce a tool call");
+        assert_eq!(streamed.tool_calls.len(), 1);
+        assert_eq!(streamed.tool_calls[0].id, "call_1");
+        assert_eq!(streamed.tool_calls[0].name, "count_tool");
+        assert_eq!(streamed.tool_calls[0].arguments, r#"{"value":"A"}"#);
+        assert_eq!(streamed.usage.unwrap().input_tokens, Some(40));
+
+        let mut history = vec![ChatMessage::user("count")];
+        let result = run_tool_call_loop(
+            &provider(),
+            &mut history,
+            &tools_registry,
+            &NoopObserver,
+            "mock-provider",
+            "mock-model",
+            0.0,
+            true,
+            None,
+            "telegram",
+            &crate::config::MultimodalConfig::default(),
+            3,
+            None,
+            Some(tx),
+            None,
+            &[],
+        )
+        .await
+        .expect("streamed tool turn should succeed");
+        assert_eq!(result, "Counted.");
+        assert_eq!(invocations.load(Ordering::SeqCst), 1);
+    }
+
     #[tokio::test]
     async fn run_tool_call_loop_executes_multiple_tools_with_ordered_results() {
         let provider = ScriptedProvider::from_text_responses(vec![
@@ -4744,6 +5471,27 @@ Tail"#;
         assert!(!context.contains("fabricated event"));
     }
 
+    #[tokio::test]
+    async fn build_context_renders_learned_skills_as_guidance() {
+        let tmp = TempDir::new().unwrap();
+        let mem = SqliteMemory::new(tmp.path()).unwrap();
+        let skill = crate::skills::learned::LearnedSkill {
+            name: "Tailwind setup".into(),
+            description: "Add tailwind to a Next.js project".into(),
+            parameters: Vec::new(),
+            steps: vec!["sandbox_run_command: npm install -D tailwindcss".into()],
+            tags: vec!["tailwind".into()],
+        };
+        crate::skills::learned::save_learned_skill(&mem, &skill)
+            .await
+            .unwrap();
+
+        let context = build_context(&mem, "tailwind", 0.0).await;
+        assert!(context.contains("[Learned skills]"));
+        assert!(context.contains("1. sandbox_run_command: npm install -D tailwindcss"));
+        assert!(!context.contains("- skill:tailwind-setup"));
+    }
+
     // ═══════════════════════════════════════════════════════════════════════
     // Recovery Tests - Tool Call Parsing Edge Cases
     // ═══════════════════════════════════════════════════════════════════════
@@ -5314,6 +6062,7 @@ Let me check the result."#;
             None, // no bootstrap_max_chars
             true, // native_tools
             crate::config::SkillsPromptInjectionMode::Full,
+            &crate::agent::prompt_templates::PromptVars::default(),
         );
 
         // Must contain zero XML protocol artifacts
@@ -45,7 +45,9 @@ impl MemoryLoader for DefaultMemoryLoader {
 
         let mut context = String::from("[Memory context]\n");
         for entry in entries {
-            if memory::is_assistant_autosave_key(&entry.key) {
+            if memory::is_assistant_autosave_key(&entry.key)
+                || crate::skills::learned::is_learned_skill_key(&entry.key)
+            {
                 continue;
             }
             if let Some(score) = entry.score {
@@ -56,12 +58,21 @@ impl MemoryLoader for DefaultMemoryLoader {
             let _ = writeln!(context, "- {}: {}", entry.key, entry.content);
         }
 
-        // If all entries were below threshold, return empty
+        // If all entries were below threshold, drop the memory header
         if context == "[Memory context]\n" {
-            return Ok(String::new());
-        }
-
-        context.push('\n');
+            context.clear();
+        } else {
+            context.push('\n');
+        }
+
+        context.push_str(
+            &crate::skills::learned::build_learned_skills_context(
+                memory,
+                user_message,
+                self.min_relevance_score,
+            )
+            .await,
+        );
         Ok(context)
     }
 }
@@ -5,7 +5,10 @@ pub mod dispatcher;
 pub mod loop_;
 pub mod memory_loader;
 pub mod model_advisor;
+pub mod phases;
 pub mod prompt;
+pub mod prompt_templates;
+pub mod replay;
 
 #[cfg(test)]
 mod tests;
@@ -294,8 +294,61 @@ pub fn get_recommended_models(task: TaskType) -> Vec<(&'static str, &'static str
     }
 }
 
+/// Get recommended models that run on a local Ollama server.
+/// Returns vec of (provider, model, description) tuples.
+pub fn get_local_recommended_models(
+    task: TaskType,
+) -> Vec<(&'static str, &'static str, &'static str)> {
+    match task {
+        TaskType::Coding | TaskType::WebDev => vec![
+            (
+                "ollama",
+                "qwen2.5-coder:14b",
+                "Qwen2.5 Coder 14B — Strong local coding, native tool calling",
+            ),
+            (
+                "ollama",
+                "devstral:24b",
+                "Devstral 24B — Agentic coding, needs ~16 GB VRAM",
+            ),
+        ],
+        TaskType::SystemAdmin | TaskType::Reasoning => vec![
+            (
+                "ollama",
+                "qwen3:14b",
+                "Qwen3 14B — Local reasoning with tool calling",
+            ),
+            (
+                "ollama",
+                "deepseek-r1:14b",
+                "DeepSeek R1 14B — Step-by-step reasoning, no tool calling",
+            ),
+        ],
+        TaskType::Creative | TaskType::Chat => vec![
+            (
+                "ollama",
+                "llama3.1:8b",
+                "Llama 3.1 8B — Fast on consumer hardware",
+            ),
+            (
+                "ollama",
+                "mistral-nemo:12b",
+                "Mistral Nemo 12B — Fluent writing, 128K context",
+            ),
+        ],
+    }
+}
+
 /// Generate recommendation message for user.
-pub fn generate_recommendation(current_model: &str, task: TaskType, has_routing: bool) -> String {
+///
+/// With `local_only` (the current provider runs locally) only local models
+/// are suggested; otherwise local options are listed after the cloud ones.
+pub fn generate_recommendation(
+    current_model: &str,
+    task: TaskType,
+    has_routing: bool,
+    local_only: bool,
+) -> String {
     let reason = task.recommendation_reason();
     let hint = task.hint();
 
@@ -311,20 +364,31 @@ pub fn generate_recommendation(current_model: &str, task: TaskType, has_routing:
 
     if has_routing {
         msg.push_str(&format!(
-            "You can use `model_routing_config` to set up automatic routing for '{}' tasks.\n\n",
+            "You can use `model_routing` to set up automatic routing for '{}' tasks.\n\n",
             task.description()
         ));
     } else {
         msg.push_str(&format!(
             "Consider switching to a model optimized for {}.\n\
-            Use `model_routing_config` to set up automatic routing for '{}' tasks.\n\n",
+            Use `model_routing` to set up automatic routing for '{}' tasks.\n\n",
             task.description(),
             hint
         ));
     }
 
-    msg.push_str("Recommended models:\n");
-    for (i, (provider, model, desc)) in get_recommended_models(task).iter().enumerate() {
+    let cloud = if local_only {
+        Vec::new()
+    } else {
+        get_recommended_models(task)
+    };
+    let local = get_local_recommended_models(task);
+    if !cloud.is_empty() {
+        msg.push_str("Recommended models:\n");
+    }
+    for (i, (provider, model, desc)) in cloud.iter().chain(local.iter()).enumerate() {
+        if i == cloud.len() {
+            msg.push_str("Local models (no cloud API needed):\n");
+        }
         msg.push_str(&format!(
             "  {}. {}: `{}` — {}\n",
             i + 1,
@@ -351,12 +415,15 @@ pub fn is_model_suitable(current_model: &str, task: TaskType) -> bool {
                 || model_lower.contains("gpt-4")
                 || model_lower.contains("code")
                 || model_lower.contains("kimi")
+                || model_lower.contains("devstral")
         }
         TaskType::Reasoning | TaskType::SystemAdmin => {
             model_lower.contains("opus")
                 || model_lower.contains("o1")
                 || model_lower.contains("reasoning")
                 || model_lower.contains("sonnet")
+                || model_lower.contains("qwen3")
+                || model_lower.contains("-r1")
         }
         TaskType::Creative => {
             model_lower.contains("gpt-4")
@@ -431,18 +498,27 @@ pub fn default_model_routes() -> Vec<ModelRouteConfig> {
             provider: "anthropic".to_string(),
             model: "claude-sonnet-4-20250514".to_string(),
             api_key: None,
+            temperature: None,
+            top_p: None,
+            seed: None,
         },
         ModelRouteConfig {
             hint: "reasoning".to_string(),
             provider: "openrouter".to_string(),
             model: "anthropic/claude-opus-4".to_string(),
             api_key: None,
+            temperature: None,
+            top_p: None,
+            seed: None,
         },
         ModelRouteConfig {
             hint: "fast".to_string(),
             provider: "openrouter".to_string(),
             model: "anthropic/claude-haiku".to_string(),
             api_key: None,
+            temperature: None,
+            top_p: None,
+            seed: None,
         },
     ]
 }
@@ -495,6 +571,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn recommendation_lists_local_models() {
+        let both = generate_recommendation("llama3", TaskType::Coding, false, false);
+        assert!(both.contains("anthropic"));
+        assert!(both.contains("Local models"));
+        assert!(both.contains("qwen2.5-coder:14b"));
+
+        let local = generate_recommendation("llama3", TaskType::Coding, false, true);
+        assert!(!local.contains("anthropic"));
+        assert!(local.contains("1. ollama: `qwen2.5-coder:14b`"));
+    }
+
+    #[test]
+    fn local_models_count_as_suitable() {
+        assert!(is_model_suitable("qwen2.5-coder:14b", TaskType::Coding));
+        assert!(is_model_suitable("deepseek-r1:14b", TaskType::Reasoning));
+        assert!(!is_model_suitable("llama3.1:8b", TaskType::Coding));
+    }
+
     #[test]
     fn chat_not_detected() {
         // Generic chat shouldn't be detected as a specific task
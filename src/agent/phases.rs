@@ -0,0 +1,228 @@
+//! Phase-aware tool exposure (`[agent.phases]`).
+//!
+//! Sending every tool schema on every turn wastes context. With phases
+//! enabled, each session is in one phase — planning, build or delivery —
+//! and the tool loop only sends the schemas of that phase's tools, plus the
+//! tools no phase lists. The model moves between phases with the
+//! `switch_phase` tool; the loop also follows it into a phase when it calls
+//! a tool that only another phase exposes.
+//!
+//! The phase of a session is kept per user
+//! ([`crate::security::users::current_user`]), so it carries over between
+//! the turns of a channel conversation.
+
+use crate::config::{AgentPhasesConfig, SessionPhase};
+use crate::tools::ToolSpec;
+use parking_lot::{Mutex, RwLock};
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock};
+
+/// Name of the tool the model switches phases with.
+pub const SWITCH_PHASE_TOOL: &str = "switch_phase";
+
+/// Session of turns made outside a user scope.
+const DEFAULT_SESSION: &str = "default";
+
+static POLICY: RwLock<Option<Arc<PhasePolicy>>> = RwLock::new(None);
+
+static SESSIONS: LazyLock<Mutex<HashMap<String, SessionPhase>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Tool patterns of every phase.
+#[derive(Debug, Clone)]
+pub struct PhasePolicy {
+    config: AgentPhasesConfig,
+}
+
+fn matches(pattern: &str, tool: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => tool.starts_with(prefix),
+        None => pattern == tool,
+    }
+}
+
+impl PhasePolicy {
+    pub fn new(config: &AgentPhasesConfig) -> Self {
+        Self {
+            config: config.clone(),
+        }
+    }
+
+    /// Phases listing `tool`; empty for tools exposed in every phase.
+    pub fn phases_of(&self, tool: &str) -> Vec<SessionPhase> {
+        SessionPhase::ALL
+            .into_iter()
+            .filter(|phase| {
+                self.config
+                    .tools(*phase)
+                    .iter()
+                    .any(|pattern| matches(pattern, tool))
+            })
+            .collect()
+    }
+
+    /// Whether the model sees `tool` in `phase`.
+    pub fn exposes(&self, phase: SessionPhase, tool: &str) -> bool {
+        if tool == SWITCH_PHASE_TOOL {
+            return true;
+        }
+        let phases = self.phases_of(tool);
+        phases.is_empty() || phases.contains(&phase)
+    }
+
+    /// The schemas of `specs` exposed in `phase`.
+    pub fn filter(&self, phase: SessionPhase, specs: &[ToolSpec]) -> Vec<ToolSpec> {
+        specs
+            .iter()
+            .filter(|spec| self.exposes(phase, &spec.name))
+            .cloned()
+            .collect()
+    }
+
+    /// Phase the session should move to for a call to `tool`: `None` when
+    /// `phase` already exposes it.
+    pub fn follow(&self, phase: SessionPhase, tool: &str) -> Option<SessionPhase> {
+        if self.exposes(phase, tool) {
+            return None;
+        }
+        self.phases_of(tool).first().copied()
+    }
+
+    fn current_in(&self, session: &str) -> SessionPhase {
+        SESSIONS
+            .lock()
+            .get(session)
+            .copied()
+            .unwrap_or(self.config.initial)
+    }
+}
+
+/// Turn phase-aware exposure on (or off) for the process.
+pub fn init_from_config(config: &AgentPhasesConfig) {
+    *POLICY.write() = config.enabled.then(|| Arc::new(PhasePolicy::new(config)));
+}
+
+/// Phase policy in effect, `None` when phases are disabled.
+pub fn policy() -> Option<Arc<PhasePolicy>> {
+    POLICY.read().clone()
+}
+
+fn current_session() -> String {
+    crate::security::users::current_user().unwrap_or_else(|| DEFAULT_SESSION.to_string())
+}
+
+/// Phase of the current session.
+pub fn current(policy: &PhasePolicy) -> SessionPhase {
+    policy.current_in(&current_session())
+}
+
+/// Move the current session to `phase`; returns the phase it left.
+pub fn switch(policy: &PhasePolicy, phase: SessionPhase) -> SessionPhase {
+    let session = current_session();
+    let previous = policy.current_in(&session);
+    SESSIONS.lock().insert(session, phase);
+    previous
+}
+
+/// Tool schemas to send this step: all of `specs` without phases, else
+/// those of the current session's phase.
+pub fn exposed_specs(specs: &[ToolSpec]) -> Option<Vec<ToolSpec>> {
+    let policy = policy()?;
+    Some(policy.filter(current(&policy), specs))
+}
+
+/// Follow the model into the phase of `tool` when the current phase hides
+/// it, so the phase's other tools are sent from the next step on.
+pub fn follow_call(tool: &str) {
+    let Some(policy) = policy() else {
+        return;
+    };
+    let phase = current(&policy);
+    if let Some(next) = policy.follow(phase, tool) {
+        switch(&policy, next);
+        tracing::info!(
+            tool,
+            from = phase.as_str(),
+            to = next.as_str(),
+            "Session phase switched to follow a tool call"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(name: &str) -> ToolSpec {
+        ToolSpec {
+            name: name.into(),
+            description: String::new(),
+            parameters: serde_json::json!({}),
+        }
+    }
+
+    #[test]
+    fn phases_expose_their_tools_and_unlisted_ones() {
+        let policy = PhasePolicy::new(&AgentPhasesConfig::default());
+        let specs: Vec<_> = [
+            "think",
+            "sandbox_run_command",
+            "github_push",
+            "memory_recall",
+            SWITCH_PHASE_TOOL,
+        ]
+        .into_iter()
+        .map(spec)
+        .collect();
+        let names = |phase| {
+            policy
+                .filter(phase, &specs)
+                .into_iter()
+                .map(|s| s.name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names(SessionPhase::Planning),
+            ["think", "memory_recall", SWITCH_PHASE_TOOL]
+        );
+        assert_eq!(
+            names(SessionPhase::Build),
+            ["sandbox_run_command", "memory_recall", SWITCH_PHASE_TOOL]
+        );
+        assert_eq!(
+            names(SessionPhase::Delivery),
+            ["github_push", "memory_recall", SWITCH_PHASE_TOOL]
+        );
+    }
+
+    #[test]
+    fn calls_to_hidden_tools_follow_their_phase() {
+        let policy = PhasePolicy::new(&AgentPhasesConfig::default());
+        assert_eq!(
+            policy.follow(SessionPhase::Planning, "sandbox_create"),
+            Some(SessionPhase::Build)
+        );
+        assert_eq!(policy.follow(SessionPhase::Build, "sandbox_create"), None);
+        // Snapshots belong to build and delivery; delivery keeps them.
+        assert_eq!(
+            policy.follow(SessionPhase::Delivery, "sandbox_save_snapshot"),
+            None
+        );
+        assert_eq!(policy.follow(SessionPhase::Planning, "memory_store"), None);
+    }
+
+    #[tokio::test]
+    async fn sessions_keep_their_own_phase() {
+        let policy = PhasePolicy::new(&AgentPhasesConfig::default());
+        crate::security::users::with_user("phases-test:a".into(), async {
+            assert_eq!(current(&policy), SessionPhase::Planning);
+            assert_eq!(switch(&policy, SessionPhase::Build), SessionPhase::Planning);
+            assert_eq!(current(&policy), SessionPhase::Build);
+        })
+        .await;
+        crate::security::users::with_user("phases-test:b".into(), async {
+            assert_eq!(current(&policy), SessionPhase::Planning);
+        })
+        .await;
+    }
+}
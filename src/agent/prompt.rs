@@ -1,3 +1,4 @@
+use crate::agent::prompt_templates::{PromptTemplates, PromptVars};
 use crate::config::IdentityConfig;
 use crate::identity;
 use crate::skills::Skill;
@@ -9,6 +10,8 @@ use std::path::Path;
 
 const BOOTSTRAP_MAX_CHARS: usize = 20_000;
 
+const SAFETY_PROMPT: &str = "## Safety\n\n- Do not exfiltrate private data.\n- Do not run destructive commands without asking.\n- Do not bypass oversight or approval mechanisms.\n- Prefer `trash` over `rm`.\n- When in doubt, ask before acting externally.";
+
 pub struct PromptContext<'a> {
     pub workspace_dir: &'a Path,
     pub model_name: &'a str,
@@ -17,6 +20,8 @@ pub struct PromptContext<'a> {
     pub skills_prompt_mode: crate::config::SkillsPromptInjectionMode,
     pub identity_config: Option<&'a IdentityConfig>,
     pub dispatcher_instructions: &'a str,
+    /// Per-user template variables (channel, stack, preferences).
+    pub template_vars: &'a PromptVars,
 }
 
 pub trait PromptSection: Send + Sync {
@@ -34,6 +39,7 @@ impl SystemPromptBuilder {
         Self {
             sections: vec![
                 Box::new(IdentitySection),
+                Box::new(InstructionsSection),
                 Box::new(ToolsSection),
                 Box::new(SafetySection),
                 Box::new(SkillsSection),
@@ -64,6 +70,7 @@ impl SystemPromptBuilder {
 }
 
 pub struct IdentitySection;
+pub struct InstructionsSection;
 pub struct ToolsSection;
 pub struct SafetySection;
 pub struct SkillsSection;
@@ -114,6 +121,32 @@ impl PromptSection for IdentitySection {
     }
 }
 
+/// Template variables for the prompt context: the caller's per-user
+/// variables plus the model, tools and (if unset) the workspace name.
+fn template_vars(ctx: &PromptContext<'_>) -> PromptVars {
+    let mut vars = ctx.template_vars.clone();
+    if vars.project_name.is_none() {
+        vars.project_name = ctx
+            .workspace_dir
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string());
+    }
+    vars.model = Some(ctx.model_name.to_string());
+    vars.tools = ctx.tools.iter().map(|t| t.name().to_string()).collect();
+    vars
+}
+
+impl PromptSection for InstructionsSection {
+    fn name(&self) -> &str {
+        "instructions"
+    }
+
+    /// Optional operator instructions from `prompts/system.md`.
+    fn build(&self, ctx: &PromptContext<'_>) -> Result<String> {
+        Ok(PromptTemplates::for_workspace(ctx.workspace_dir).render("system", &template_vars(ctx)))
+    }
+}
+
 impl PromptSection for ToolsSection {
     fn name(&self) -> &str {
         "tools"
@@ -143,8 +176,15 @@ impl PromptSection for SafetySection {
         "safety"
     }
 
-    fn build(&self, _ctx: &PromptContext<'_>) -> Result<String> {
-        Ok("## Safety\n\n- Do not exfiltrate private data.\n- Do not run destructive commands without asking.\n- Do not bypass oversight or approval mechanisms.\n- Prefer `trash` over `rm`.\n- When in doubt, ask before acting externally.".into())
+    /// The baseline wording unless the workspace overrides `safety`.
+    fn build(&self, ctx: &PromptContext<'_>) -> Result<String> {
+        let templates = PromptTemplates::for_workspace(ctx.workspace_dir);
+        match templates.render_override("safety", &template_vars(ctx)) {
+            Ok(Some(rendered)) => return Ok(rendered),
+            Ok(None) => {}
+            Err(e) => tracing::warn!("{e:#}; using built-in prompt"),
+        }
+        Ok(SAFETY_PROMPT.into())
     }
 }
 
@@ -197,6 +237,9 @@ impl PromptSection for DateTimeSection {
     }
 
     fn build(&self, _ctx: &PromptContext<'_>) -> Result<String> {
+        if crate::providers::sampling::is_deterministic() {
+            return Ok(String::new());
+        }
         let now = Local::now();
         Ok(format!(
             "## Current Date & Time\n\n{} ({})",
@@ -270,6 +313,7 @@ mod tests {
                 success: true,
                 output: "ok".into(),
                 error: None,
+                error_code: None,
                 error_hint: None,
             })
         }
@@ -301,6 +345,7 @@ mod tests {
             skills_prompt_mode: crate::config::SkillsPromptInjectionMode::Full,
             identity_config: Some(&identity_config),
             dispatcher_instructions: "",
+            template_vars: &PromptVars::default(),
         };
 
         let section = IdentitySection;
@@ -329,6 +374,7 @@ mod tests {
             skills_prompt_mode: crate::config::SkillsPromptInjectionMode::Full,
             identity_config: None,
             dispatcher_instructions: "instr",
+            template_vars: &PromptVars::default(),
         };
         let prompt = SystemPromptBuilder::with_defaults().build(&ctx).unwrap();
         assert!(prompt.contains("## Tools"));
@@ -336,6 +382,37 @@ mod tests {
         assert!(prompt.contains("instr"));
     }
 
+    #[test]
+    fn instructions_section_renders_workspace_template() {
+        let workspace = tempfile::TempDir::new().unwrap();
+        let prompts = workspace.path().join("prompts");
+        std::fs::create_dir_all(&prompts).unwrap();
+        std::fs::write(
+            prompts.join("system.md"),
+            "Tools: {{ tools | join(', ') }} on {{ model }}",
+        )
+        .unwrap();
+
+        let tools: Vec<Box<dyn Tool>> = vec![Box::new(TestTool)];
+        let ctx = PromptContext {
+            workspace_dir: workspace.path(),
+            model_name: "test-model",
+            tools: &tools,
+            skills: &[],
+            skills_prompt_mode: crate::config::SkillsPromptInjectionMode::Full,
+            identity_config: None,
+            dispatcher_instructions: "",
+            template_vars: &PromptVars::default(),
+        };
+
+        let output = InstructionsSection.build(&ctx).unwrap();
+        assert_eq!(output, "Tools: test_tool on test-model");
+        assert_eq!(
+            SafetySection.build(&ctx).unwrap(),
+            "## Safety\n\n- Do not exfiltrate private data.\n- Do not run destructive commands without asking.\n- Do not bypass oversight or approval mechanisms.\n- Prefer `trash` over `rm`.\n- When in doubt, ask before acting externally."
+        );
+    }
+
     #[test]
     fn skills_section_includes_instructions_and_tools() {
         let tools: Vec<Box<dyn Tool>> = vec![];
@@ -364,6 +441,7 @@ mod tests {
             skills_prompt_mode: crate::config::SkillsPromptInjectionMode::Full,
             identity_config: None,
             dispatcher_instructions: "",
+            template_vars: &PromptVars::default(),
         };
 
         let output = SkillsSection.build(&ctx).unwrap();
@@ -402,6 +480,7 @@ mod tests {
             skills_prompt_mode: crate::config::SkillsPromptInjectionMode::Compact,
             identity_config: None,
             dispatcher_instructions: "",
+            template_vars: &PromptVars::default(),
         };
 
         let output = SkillsSection.build(&ctx).unwrap();
@@ -423,6 +502,7 @@ mod tests {
             skills_prompt_mode: crate::config::SkillsPromptInjectionMode::Full,
             identity_config: None,
             dispatcher_instructions: "instr",
+            template_vars: &PromptVars::default(),
         };
 
         let rendered = DateTimeSection.build(&ctx).unwrap();
@@ -461,6 +541,7 @@ mod tests {
             skills_prompt_mode: crate::config::SkillsPromptInjectionMode::Full,
             identity_config: None,
             dispatcher_instructions: "",
+            template_vars: &PromptVars::default(),
         };
 
         let prompt = SystemPromptBuilder::with_defaults().build(&ctx).unwrap();
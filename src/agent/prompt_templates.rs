@@ -0,0 +1,329 @@
+//! Prompt templates for system and factory stage prompts.
+//!
+//! Templates are [minijinja](https://docs.rs/minijinja) files in
+//! `<workspace>/prompts/`. For a template named `safety` rendered for the
+//! `telegram` channel the lookup order is:
+//!
+//! 1. `prompts/safety.telegram.md`
+//! 2. `prompts/safety.md`
+//! 3. the built-in default for the channel, then the generic one (if any)
+//!
+//! Files are read on every render, so edits take effect on the next turn
+//! without a restart.
+//!
+//! Available variables: `project_name`, `stack` (project type of the saved
+//! snapshot), `channel` (of the user being served), `model`, `tools` (list
+//! of tool names), `preferences` (the user's preferences as `key: value`
+//! lines) and any entries of `extra`.
+
+use crate::store::SharedStore;
+use anyhow::{Context, Result};
+use minijinja::Environment;
+use rusqlite::Connection;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Directory (relative to the workspace) that holds prompt template overrides.
+pub const PROMPTS_DIR: &str = "prompts";
+
+const SAFETY_TEMPLATE: &str = "## Safety\n\n\
+- Do not exfiltrate private data.\n\
+- Do not run destructive commands without asking.\n\
+- Do not bypass oversight or approval mechanisms.\n\
+- Prefer `trash` over `rm` (recoverable beats gone forever).\n\
+- When in doubt, ask before acting externally.";
+
+const TASK_NATIVE_TEMPLATE: &str = "## Your Task\n\n\
+When the user sends a message, respond naturally. Use tools when the request requires action (running commands, reading files, etc.).\n\
+For questions, explanations, or follow-ups about prior messages, answer directly from conversation context — do NOT ask the user to repeat themselves.\n\
+Do NOT: summarize this configuration, describe your capabilities, or output step-by-step meta-commentary.";
+
+const TASK_XML_TEMPLATE: &str = "## Your Task\n\n\
+When the user sends a message, ACT on it. Use the tools to fulfill their request.\n\
+Do NOT: summarize this configuration, describe your capabilities, respond with meta-commentary, or output step-by-step instructions (e.g. \"1. First... 2. Next...\").\n\
+Instead: emit actual <tool_call> tags when you need to act. Just do what they ask.";
+
+const HARDWARE_TEMPLATE: &str = "## Hardware Access\n\n\
+You HAVE direct access to connected hardware (Arduino, Nucleo, etc.). The user owns this system and has configured it.\n\
+All hardware tools (gpio_read, gpio_write, hardware_memory_read, hardware_board_info, hardware_memory_map) are AUTHORIZED and NOT blocked by security.\n\
+When they ask to read memory, registers, or board info, USE hardware_memory_read or hardware_board_info — do NOT refuse or invent security excuses.\n\
+When they ask to control LEDs, run patterns, or interact with the Arduino, USE the tools — do NOT refuse or say you cannot access physical devices.\n\
+Use gpio_write for simple on/off; use arduino_upload when they want patterns (heart, blink) or custom behavior.";
+
+const DELIVERY_TELEGRAM_TEMPLATE: &str = "When responding on Telegram:\n\
+- Include media markers for files or URLs that should be sent as attachments\n\
+- Use **bold** for key terms, section titles, and important info (renders as <b>)\n\
+- Use *italic* for emphasis (renders as <i>)\n\
+- Use `backticks` for inline code, commands, or technical terms\n\
+- Use triple backticks for code blocks\n\
+- Use emoji naturally to add personality — but don't overdo it\n\
+- Be concise and direct. Skip filler phrases like 'Great question!' or 'Certainly!'\n\
+- Structure longer answers with bold headers, not raw markdown ## headers\n\
+- For media attachments use markers: [IMAGE:<path-or-url>], [DOCUMENT:<path-or-url>], [VIDEO:<path-or-url>], [AUDIO:<path-or-url>], or [VOICE:<path-or-url>]\n\
+- Keep normal text outside markers and never wrap markers in code fences.\n\
+- Use tool results silently: answer the latest user message directly, and do not narrate delayed/internal tool execution bookkeeping.";
+
+const DELIVERY_SIGNAL_TEMPLATE: &str = "When responding on Signal:\n\
+- Signal ONLY supports plain text — do NOT use markdown formatting\n\
+- Do NOT use **bold**, *italic*, `backticks`, lists (dash/bullet), or code blocks\n\
+- Use plain text only — write naturally without any formatting markers\n\
+- For emphasis, use CAPITALIZATION or quotes instead of markdown\n\
+- For lists, use simple numbers like 1. 2. 3. or write as flowing text\n\
+- Be concise and direct. Skip filler phrases like 'Great question!' or 'Certainly!'\n\
+- Use tool results silently: answer the latest user message directly, and do not narrate delayed/internal tool execution bookkeeping.";
+
+/// Built-in default for a template name, used when no override file exists.
+/// Channel-specific defaults are named `<name>.<channel>`.
+fn builtin_template(name: &str) -> Option<&'static str> {
+    match name {
+        "safety" => Some(SAFETY_TEMPLATE),
+        "task_native" => Some(TASK_NATIVE_TEMPLATE),
+        "task_xml" => Some(TASK_XML_TEMPLATE),
+        "hardware" => Some(HARDWARE_TEMPLATE),
+        "delivery.telegram" => Some(DELIVERY_TELEGRAM_TEMPLATE),
+        "delivery.signal" => Some(DELIVERY_SIGNAL_TEMPLATE),
+        _ => None,
+    }
+}
+
+/// Variables available to prompt templates.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PromptVars {
+    pub project_name: Option<String>,
+    pub stack: Option<String>,
+    pub channel: Option<String>,
+    pub model: Option<String>,
+    pub tools: Vec<String>,
+    pub preferences: Vec<String>,
+    pub extra: BTreeMap<String, String>,
+}
+
+impl PromptVars {
+    /// `channel`, `stack` and `preferences` of `user` (`<channel>:<sender>`,
+    /// see [`crate::security::users`]), plus `project_name` when the saved
+    /// snapshot records one. Values the store can't provide stay unset.
+    pub fn for_user(conn: &Connection, user: &str) -> Self {
+        let load = || -> Result<Self> {
+            Ok(Self {
+                project_name: crate::store::snapshot::load_project(conn)?,
+                stack: crate::store::snapshot::load_project_type(conn)?,
+                preferences: crate::store::preferences::list(conn, user)?
+                    .into_iter()
+                    .map(|pref| format!("{}: {}", pref.key, pref.value))
+                    .collect(),
+                ..Self::default()
+            })
+        };
+        let mut vars = load().unwrap_or_else(|e| {
+            tracing::debug!("Failed to load prompt variables: {e}");
+            Self::default()
+        });
+        vars.channel = user.split_once(':').map(|(channel, _)| channel.to_string());
+        vars
+    }
+
+    /// [`Self::for_user`] for the user being served (see
+    /// [`crate::store::preferences::current_user`]).
+    pub fn for_current_user(store: &SharedStore) -> Self {
+        let user = crate::store::preferences::current_user();
+        store
+            .with(|conn| Ok(Self::for_user(conn, &user)))
+            .unwrap_or_else(|e| {
+                tracing::debug!("Failed to open store for prompt variables: {e}");
+                Self::default()
+            })
+    }
+}
+
+/// Loader/renderer for prompt templates rooted at one directory.
+#[derive(Clone)]
+pub struct PromptTemplates {
+    dir: PathBuf,
+    store: Option<Arc<SharedStore>>,
+}
+
+impl PromptTemplates {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            store: None,
+        }
+    }
+
+    /// Templates in `<workspace_dir>/prompts/`.
+    pub fn for_workspace(workspace_dir: &Path) -> Self {
+        Self::new(workspace_dir.join(PROMPTS_DIR))
+    }
+
+    /// Read [`Self::user_vars`] from `store`.
+    pub fn with_store(mut self, store: Arc<SharedStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Variables of the user being served ([`PromptVars::for_current_user`]);
+    /// defaults when no store was set.
+    pub fn user_vars(&self) -> PromptVars {
+        self.store
+            .as_deref()
+            .map(PromptVars::for_current_user)
+            .unwrap_or_default()
+    }
+
+    /// Read the most specific override file for `name`, if one exists.
+    fn load_override(&self, name: &str, vars: &PromptVars) -> Option<(PathBuf, String)> {
+        let mut candidates = Vec::with_capacity(2);
+        if let Some(channel) = vars.channel.as_deref().filter(|c| !c.is_empty()) {
+            candidates.push(self.dir.join(format!("{name}.{channel}.md")));
+        }
+        candidates.push(self.dir.join(format!("{name}.md")));
+
+        candidates.into_iter().find_map(|path| {
+            std::fs::read_to_string(&path)
+                .ok()
+                .map(|source| (path, source))
+        })
+    }
+
+    /// Render only a user-provided override for `name`. Returns `Ok(None)`
+    /// when no override file exists.
+    pub fn render_override(&self, name: &str, vars: &PromptVars) -> Result<Option<String>> {
+        let Some((path, source)) = self.load_override(name, vars) else {
+            return Ok(None);
+        };
+        render_source(&source, vars)
+            .with_context(|| format!("Failed to render prompt template {}", path.display()))
+            .map(Some)
+    }
+
+    /// Render `name`, falling back to its built-in default. Unknown names
+    /// without an override render to an empty string.
+    ///
+    /// A broken override is logged and the built-in default is used instead,
+    /// so a typo in a template never leaves the agent without a prompt.
+    pub fn render(&self, name: &str, vars: &PromptVars) -> String {
+        match self.render_override(name, vars) {
+            Ok(Some(rendered)) => return rendered,
+            Ok(None) => {}
+            Err(e) => tracing::warn!("{e:#}; using built-in prompt"),
+        }
+        vars.channel
+            .as_deref()
+            .and_then(|channel| builtin_template(&format!("{name}.{channel}")))
+            .or_else(|| builtin_template(name))
+            .and_then(|source| render_source(source, vars).ok())
+            .unwrap_or_default()
+    }
+}
+
+fn render_source(source: &str, vars: &PromptVars) -> Result<String> {
+    let mut env = Environment::new();
+    env.set_keep_trailing_newline(true);
+    env.add_template("prompt", source)?;
+    let rendered = env.get_template("prompt")?.render(vars)?;
+    Ok(rendered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn vars() -> PromptVars {
+        PromptVars {
+            project_name: Some("shop".into()),
+            channel: Some("telegram".into()),
+            tools: vec!["shell".into(), "file_read".into()],
+            ..PromptVars::default()
+        }
+    }
+
+    #[test]
+    fn builtin_used_without_override() {
+        let tmp = TempDir::new().unwrap();
+        let templates = PromptTemplates::for_workspace(tmp.path());
+        assert!(templates.render("safety", &vars()).starts_with("## Safety"));
+        assert!(templates.render("unknown", &vars()).is_empty());
+    }
+
+    #[test]
+    fn channel_override_wins_over_generic_file() {
+        let tmp = TempDir::new().unwrap();
+        let dir = tmp.path().join(PROMPTS_DIR);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("system.md"), "generic {{ project_name }}").unwrap();
+        std::fs::write(
+            dir.join("system.telegram.md"),
+            "tg {{ project_name }}: {{ tools | join(', ') }}",
+        )
+        .unwrap();
+
+        let templates = PromptTemplates::for_workspace(tmp.path());
+        assert_eq!(
+            templates.render("system", &vars()),
+            "tg shop: shell, file_read"
+        );
+
+        let mut cli = vars();
+        cli.channel = None;
+        assert_eq!(templates.render("system", &cli), "generic shop");
+    }
+
+    #[test]
+    fn edits_are_picked_up_without_reload() {
+        let tmp = TempDir::new().unwrap();
+        let dir = tmp.path().join(PROMPTS_DIR);
+        std::fs::create_dir_all(&dir).unwrap();
+        let templates = PromptTemplates::for_workspace(tmp.path());
+
+        std::fs::write(dir.join("safety.md"), "v1").unwrap();
+        assert_eq!(templates.render("safety", &vars()), "v1");
+        std::fs::write(dir.join("safety.md"), "v2").unwrap();
+        assert_eq!(templates.render("safety", &vars()), "v2");
+    }
+
+    #[test]
+    fn channel_builtin_wins_over_generic_builtin() {
+        let tmp = TempDir::new().unwrap();
+        let templates = PromptTemplates::for_workspace(tmp.path());
+        assert!(templates
+            .render("delivery", &vars())
+            .starts_with("When responding on Telegram"));
+        let mut cli = vars();
+        cli.channel = None;
+        assert!(templates.render("delivery", &cli).is_empty());
+    }
+
+    #[test]
+    fn user_vars_come_from_the_store() {
+        let tmp = TempDir::new().unwrap();
+        let conn = crate::store::init_db(&tmp.path().join("zerobuild.db")).unwrap();
+        crate::store::snapshot::save_snapshot(
+            &conn,
+            &std::collections::HashMap::default(),
+            Some("nextjs"),
+        )
+        .unwrap();
+        crate::store::preferences::save(&conn, "telegram:42", "license", "mit").unwrap();
+
+        let vars = PromptVars::for_user(&conn, "telegram:42");
+        assert_eq!(vars.channel.as_deref(), Some("telegram"));
+        assert_eq!(vars.stack.as_deref(), Some("nextjs"));
+        assert_eq!(vars.preferences, vec!["license: MIT".to_string()]);
+        assert!(PromptVars::for_user(&conn, "local").channel.is_none());
+    }
+
+    #[test]
+    fn broken_override_falls_back_to_builtin() {
+        let tmp = TempDir::new().unwrap();
+        let dir = tmp.path().join(PROMPTS_DIR);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("safety.md"), "{% if %}").unwrap();
+
+        let templates = PromptTemplates::for_workspace(tmp.path());
+        assert!(templates.render_override("safety", &vars()).is_err());
+        assert!(templates.render("safety", &vars()).starts_with("## Safety"));
+    }
+}
@@ -0,0 +1,755 @@
+//! Replay of session recordings (see [`crate::observability::recording`]).
+//!
+//! Each recorded turn is run through the agent loop again with its recorded
+//! history. The provider answers with the recorded LLM responses in order and
+//! every tool answers with its recorded result, so GitHub and other network
+//! tools never run. With [`ReplayOptions::sandbox`], sandbox tools run for
+//! real in a fresh local sandbox instead, which reproduces build and command
+//! failures. Anything that happens differently from the recording — other
+//! tool calls or arguments, a different result or reply — is reported as a
+//! divergence, so a recording of a fixed bug doubles as a regression test.
+
+use crate::agent::loop_::run_tool_call_loop;
+use crate::config::Config;
+use crate::observability::recording::{self, RecordedEvent};
+use crate::observability::NoopObserver;
+use crate::providers::traits::{ChatMessage, ChatRequest, ChatResponse, TokenUsage};
+use crate::providers::Provider;
+use crate::tools::traits::{Tool, ToolResult, ToolSpec};
+use anyhow::Result;
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Sandbox tools that stay mocked in sandbox mode because they reach the network.
+const MOCKED_SANDBOX_TOOLS: &[&str] = &["sandbox_get_public_url"];
+/// Longest tool output quoted in a divergence.
+const DIVERGENCE_PREVIEW_CHARS: usize = 200;
+
+/// How to replay a recording.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReplayOptions {
+    /// Run sandbox tools in a fresh local sandbox instead of returning their
+    /// recorded results.
+    pub sandbox: bool,
+}
+
+/// What a replay did and where it diverged from the recording.
+#[derive(Debug, Default)]
+pub struct ReplayReport {
+    pub turns: usize,
+    pub llm_calls: usize,
+    pub tool_calls: usize,
+    /// Tool calls run in the live sandbox.
+    pub live_tool_calls: usize,
+    pub divergences: Vec<String>,
+}
+
+impl ReplayReport {
+    /// Whether the replay matched the recording.
+    pub fn reproduced(&self) -> bool {
+        self.divergences.is_empty()
+    }
+
+    /// Human-readable summary.
+    pub fn render(&self) -> String {
+        let mut out = format!(
+            "Turns: {}, LLM calls: {}, tool calls: {} ({} in the live sandbox)\n",
+            self.turns, self.llm_calls, self.tool_calls, self.live_tool_calls
+        );
+        if self.reproduced() {
+            out.push_str("Replay matched the recording.\n");
+        } else {
+            let _ = writeln!(out, "{} divergence(s):", self.divergences.len());
+            for divergence in &self.divergences {
+                let _ = writeln!(out, "  - {divergence}");
+            }
+        }
+        out
+    }
+}
+
+/// State shared by the replay provider and tools.
+#[derive(Default)]
+struct ReplayState {
+    turn: AtomicUsize,
+    llm_calls: AtomicUsize,
+    tool_calls: AtomicUsize,
+    live_tool_calls: AtomicUsize,
+    divergences: Mutex<Vec<String>>,
+}
+
+impl ReplayState {
+    fn diverge(&self, what: String) {
+        let turn = self.turn.load(Ordering::Relaxed);
+        self.divergences.lock().push(format!("turn {turn}: {what}"));
+    }
+}
+
+/// A recorded `chat` request and its response.
+struct Exchange {
+    request_len: usize,
+    response: Result<ChatResponse, String>,
+}
+
+/// Provider answering with the recorded responses in order.
+struct ReplayProvider {
+    exchanges: Mutex<VecDeque<Exchange>>,
+    native_tools: AtomicBool,
+    state: Arc<ReplayState>,
+}
+
+#[async_trait]
+impl Provider for ReplayProvider {
+    async fn chat_with_system(
+        &self,
+        _system_prompt: Option<&str>,
+        _message: &str,
+        _model: &str,
+        _temperature: f64,
+    ) -> Result<String> {
+        anyhow::bail!("Only agent loop requests are recorded")
+    }
+
+    async fn chat(
+        &self,
+        request: ChatRequest<'_>,
+        _model: &str,
+        _temperature: f64,
+    ) -> Result<ChatResponse> {
+        let call = self.state.llm_calls.fetch_add(1, Ordering::Relaxed) + 1;
+        let Some(exchange) = self.exchanges.lock().pop_front() else {
+            self.state
+                .diverge(format!("LLM call {call} is not in the recording"));
+            anyhow::bail!("Recording has no more LLM responses");
+        };
+        if exchange.request_len != request.messages.len() {
+            self.state.diverge(format!(
+                "LLM call {call} sent {} messages, the recording has {}",
+                request.messages.len(),
+                exchange.request_len
+            ));
+        }
+        exchange.response.map_err(|error| anyhow::anyhow!(error))
+    }
+
+    fn supports_native_tools(&self) -> bool {
+        self.native_tools.load(Ordering::Relaxed)
+    }
+}
+
+/// A recorded tool call.
+struct RecordedCall {
+    args: Value,
+    result: Option<ToolResult>,
+    error: Option<String>,
+}
+
+/// Tool answering with its recorded results, or running `live` and
+/// comparing the outcome with them.
+struct ReplayTool {
+    spec: ToolSpec,
+    calls: Arc<Mutex<VecDeque<RecordedCall>>>,
+    live: Option<Box<dyn Tool>>,
+    state: Arc<ReplayState>,
+}
+
+fn preview(text: &str) -> String {
+    crate::util::truncate_with_ellipsis(text.trim(), DIVERGENCE_PREVIEW_CHARS)
+}
+
+#[async_trait]
+impl Tool for ReplayTool {
+    fn name(&self) -> &str {
+        &self.spec.name
+    }
+
+    fn description(&self) -> &str {
+        &self.spec.description
+    }
+
+    fn parameters_schema(&self) -> Value {
+        self.spec.parameters.clone()
+    }
+
+    async fn execute(&self, args: Value) -> Result<ToolResult> {
+        self.state.tool_calls.fetch_add(1, Ordering::Relaxed);
+        let name = &self.spec.name;
+        // Parallel calls can finish in any order, so take the recorded call
+        // with the same arguments first.
+        let recorded = {
+            let mut calls = self.calls.lock();
+            let index = calls.iter().position(|call| call.args == args).unwrap_or(0);
+            calls.remove(index)
+        };
+        let Some(recorded) = recorded else {
+            self.state.diverge(format!(
+                "`{name}` was called with {args} but not in the recording"
+            ));
+            anyhow::bail!("`{name}` call is not in the recording");
+        };
+        if recorded.args != args {
+            self.state.diverge(format!(
+                "`{name}` was called with {args}, the recording has {}",
+                recorded.args
+            ));
+        }
+
+        let Some(live) = &self.live else {
+            return match (recorded.result, recorded.error) {
+                (Some(result), _) => Ok(result),
+                (None, error) => Err(anyhow::anyhow!(error.unwrap_or_default())),
+            };
+        };
+        self.state.live_tool_calls.fetch_add(1, Ordering::Relaxed);
+        let result = live.execute(args).await;
+        let recorded_success = recorded.result.as_ref().is_some_and(|r| r.success);
+        let live_success = result.as_ref().is_ok_and(|r| r.success);
+        if recorded_success != live_success {
+            let outcome = match &result {
+                Ok(r) => preview(r.error.as_deref().unwrap_or(&r.output)),
+                Err(e) => preview(&format!("{e:#}")),
+            };
+            self.state.diverge(format!(
+                "`{name}` {} in the sandbox but {} in the recording: {outcome}",
+                if live_success { "succeeded" } else { "failed" },
+                if recorded_success {
+                    "succeeded"
+                } else {
+                    "failed"
+                },
+            ));
+        }
+        result
+    }
+}
+
+/// A recorded turn and how it ended.
+struct Turn {
+    provider: String,
+    model: String,
+    temperature: f64,
+    max_tool_iterations: usize,
+    excluded_tools: Vec<String>,
+    history: Vec<ChatMessage>,
+    native_tools: bool,
+    end: Option<Result<String, String>>,
+}
+
+/// Replay the recording at `path`.
+pub async fn replay_file(
+    config: &Config,
+    path: &Path,
+    options: ReplayOptions,
+) -> Result<ReplayReport> {
+    replay(config, recording::load(path)?, options).await
+}
+
+/// Replay recorded `events`.
+pub async fn replay(
+    config: &Config,
+    events: Vec<RecordedEvent>,
+    options: ReplayOptions,
+) -> Result<ReplayReport> {
+    let state = Arc::new(ReplayState::default());
+    let mut channel = String::from("cli");
+    let mut specs = Vec::new();
+    let mut turns: Vec<Turn> = Vec::new();
+    let mut exchanges = VecDeque::new();
+    let mut calls: Vec<(String, RecordedCall)> = Vec::new();
+    let mut pending_request = None;
+
+    for event in events {
+        match event {
+            RecordedEvent::Session {
+                channel: session_channel,
+                tools,
+                ..
+            } => {
+                channel = session_channel;
+                specs = tools;
+            }
+            RecordedEvent::Turn {
+                provider,
+                model,
+                temperature,
+                max_tool_iterations,
+                excluded_tools,
+                history,
+                ..
+            } => turns.push(Turn {
+                provider,
+                model,
+                temperature,
+                max_tool_iterations,
+                excluded_tools,
+                history,
+                native_tools: false,
+                end: None,
+            }),
+            RecordedEvent::LlmRequest {
+                messages,
+                native_tools,
+            } => {
+                if let Some(turn) = turns.last_mut().filter(|t| t.end.is_none()) {
+                    turn.native_tools |= native_tools;
+                }
+                pending_request = Some(messages.len());
+            }
+            RecordedEvent::LlmResponse {
+                text,
+                tool_calls,
+                reasoning_content,
+                input_tokens,
+                output_tokens,
+                error,
+                ..
+            } => {
+                let response = match error {
+                    Some(error) => Err(error),
+                    None => Ok(ChatResponse {
+                        text,
+                        tool_calls,
+                        usage: Some(TokenUsage {
+                            input_tokens,
+                            output_tokens,
+                            cached_input_tokens: None,
+                        }),
+                        reasoning_content,
+                    }),
+                };
+                exchanges.push_back(Exchange {
+                    request_len: pending_request.take().unwrap_or_default(),
+                    response,
+                });
+            }
+            RecordedEvent::ToolCall {
+                name,
+                args,
+                result,
+                error,
+                ..
+            } => calls.push((
+                name,
+                RecordedCall {
+                    args,
+                    result,
+                    error,
+                },
+            )),
+            RecordedEvent::TurnEnd {
+                response, error, ..
+            } => {
+                if let Some(turn) = turns.last_mut() {
+                    turn.end = Some(match error {
+                        Some(error) => Err(error),
+                        None => Ok(response.unwrap_or_default()),
+                    });
+                }
+            }
+        }
+    }
+    if specs.is_empty() && turns.is_empty() {
+        anyhow::bail!("Not a session recording: no session or turn events");
+    }
+
+    let scratch = tempfile::TempDir::new()?;
+    let sandbox = crate::tools::sandbox_client();
+    let mut live_tools: Vec<Box<dyn Tool>> = if options.sandbox {
+        let mut zerobuild = config.zerobuild.clone();
+        zerobuild.db_path = scratch.path().join("replay.db").display().to_string();
+        crate::tools::sandbox_tools(
+            Arc::clone(&sandbox),
+            Arc::new(zerobuild),
+            String::new(),
+            Arc::new(crate::memory::embeddings::NoopEmbedding),
+            None,
+        )
+        .into_iter()
+        .filter(|tool| {
+            (tool.name().starts_with("sandbox_") || tool.name() == "project_info")
+                && !MOCKED_SANDBOX_TOOLS.contains(&tool.name())
+        })
+        .collect()
+    } else {
+        Vec::new()
+    };
+
+    let mut queues = Vec::with_capacity(specs.len());
+    let tools: Vec<Box<dyn Tool>> = specs
+        .into_iter()
+        .map(|spec| {
+            let mut queue = VecDeque::new();
+            calls.retain_mut(|(name, call)| {
+                if *name != spec.name {
+                    return true;
+                }
+                queue.push_back(RecordedCall {
+                    args: std::mem::take(&mut call.args),
+                    result: call.result.take(),
+                    error: call.error.take(),
+                });
+                false
+            });
+            let queue = Arc::new(Mutex::new(queue));
+            queues.push((spec.name.clone(), Arc::clone(&queue)));
+            let live = live_tools
+                .iter()
+                .position(|tool| tool.name() == spec.name)
+                .map(|index| live_tools.swap_remove(index));
+            Box::new(ReplayTool {
+                spec,
+                calls: queue,
+                live,
+                state: Arc::clone(&state),
+            }) as Box<dyn Tool>
+        })
+        .collect();
+    for (name, _) in &calls {
+        state.diverge(format!("recorded call to unknown tool `{name}`"));
+    }
+
+    let provider = ReplayProvider {
+        exchanges: Mutex::new(exchanges),
+        native_tools: AtomicBool::new(false),
+        state: Arc::clone(&state),
+    };
+    let turn_count = turns.len();
+    for (index, turn) in turns.into_iter().enumerate() {
+        state.turn.store(index + 1, Ordering::Relaxed);
+        provider
+            .native_tools
+            .store(turn.native_tools, Ordering::Relaxed);
+        let mut history = turn.history;
+        let result = run_tool_call_loop(
+            &provider,
+            &mut history,
+            &tools,
+            &NoopObserver,
+            &turn.provider,
+            &turn.model,
+            turn.temperature,
+            true,
+            None,
+            &channel,
+            &config.multimodal,
+            turn.max_tool_iterations,
+            None,
+            None,
+            None,
+            &turn.excluded_tools,
+        )
+        .await
+        .map_err(|e| format!("{e:#}"));
+        match (&turn.end, &result) {
+            (Some(Ok(expected)), Ok(actual)) if expected != actual => state.diverge(format!(
+                "replied \"{}\", the recording replied \"{}\"",
+                preview(actual),
+                preview(expected)
+            )),
+            (Some(Ok(_)), Err(error)) => {
+                state.diverge(format!(
+                    "failed ({}), the recording replied",
+                    preview(error)
+                ));
+            }
+            (Some(Err(expected)), Ok(_)) => state.diverge(format!(
+                "replied, the recording failed ({})",
+                preview(expected)
+            )),
+            _ => {}
+        }
+    }
+    if options.sandbox {
+        let _ = sandbox.kill_sandbox().await;
+    }
+
+    state.turn.store(turn_count, Ordering::Relaxed);
+    let unused_responses = provider.exchanges.lock().len();
+    if unused_responses > 0 {
+        state.diverge(format!(
+            "{unused_responses} recorded LLM response(s) were never requested"
+        ));
+    }
+    for (name, queue) in &queues {
+        let unused = queue.lock().len();
+        if unused > 0 {
+            state.diverge(format!(
+                "{unused} recorded `{name}` call(s) were never made"
+            ));
+        }
+    }
+
+    let divergences = std::mem::take(&mut *state.divergences.lock());
+    Ok(ReplayReport {
+        turns: turn_count,
+        llm_calls: state.llm_calls.load(Ordering::Relaxed),
+        tool_calls: state.tool_calls.load(Ordering::Relaxed),
+        live_tool_calls: state.live_tool_calls.load(Ordering::Relaxed),
+        divergences,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::observability::recording::{record_tools, RecordingProvider, SessionRecorder};
+    use crate::providers::traits::{ProviderCapabilities, ToolCall};
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    struct ScriptedProvider {
+        responses: Mutex<VecDeque<ChatResponse>>,
+    }
+
+    #[async_trait]
+    impl Provider for ScriptedProvider {
+        fn capabilities(&self) -> ProviderCapabilities {
+            ProviderCapabilities {
+                native_tool_calling: true,
+                vision: false,
+                max_context_tokens: None,
+            }
+        }
+
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> Result<String> {
+            Ok(String::new())
+        }
+
+        async fn chat(
+            &self,
+            _request: ChatRequest<'_>,
+            _model: &str,
+            _temperature: f64,
+        ) -> Result<ChatResponse> {
+            self.responses
+                .lock()
+                .pop_front()
+                .ok_or_else(|| anyhow::anyhow!("script exhausted"))
+        }
+    }
+
+    struct EchoTool;
+
+    #[async_trait]
+    impl Tool for EchoTool {
+        fn name(&self) -> &str {
+            "echo"
+        }
+
+        fn description(&self) -> &str {
+            "Echo the value"
+        }
+
+        fn parameters_schema(&self) -> Value {
+            json!({ "type": "object", "properties": { "value": { "type": "string" } } })
+        }
+
+        async fn execute(&self, args: Value) -> Result<ToolResult> {
+            Ok(ToolResult {
+                success: true,
+                output: args["value"].as_str().unwrap_or_default().to_string(),
+                error: None,
+                error_code: None,
+                error_hint: None,
+            })
+        }
+    }
+
+    fn tool_call(name: &str, arguments: Value) -> ChatResponse {
+        ChatResponse {
+            text: None,
+            tool_calls: vec![ToolCall {
+                id: format!("call-{name}"),
+                name: name.into(),
+                arguments: arguments.to_string(),
+            }],
+            usage: None,
+            reasoning_content: None,
+        }
+    }
+
+    fn reply(text: &str) -> ChatResponse {
+        ChatResponse {
+            text: Some(text.into()),
+            tool_calls: Vec::new(),
+            usage: None,
+            reasoning_content: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn recorded_session_replays_and_reports_divergences() {
+        let tmp = TempDir::new().unwrap();
+        let tools: Vec<Box<dyn Tool>> = vec![Box::new(EchoTool)];
+        let recorder = Arc::new(SessionRecorder::create(tmp.path(), "telegram", &tools).unwrap());
+        let provider = RecordingProvider::new(
+            Arc::new(ScriptedProvider {
+                responses: Mutex::new(VecDeque::from([
+                    tool_call("echo", json!({ "value": "hi" })),
+                    reply("The tool said hi."),
+                ])),
+            }),
+            Arc::clone(&recorder),
+        );
+        let tools = record_tools(Arc::new(tools), &recorder);
+
+        let mut history = vec![ChatMessage::system("sys"), ChatMessage::user("say hi")];
+        recorder.record_turn("openai", "gpt-4o", 0.7, 10, &[], &history);
+        let result = run_tool_call_loop(
+            &provider,
+            &mut history,
+            &tools,
+            &NoopObserver,
+            "openai",
+            "gpt-4o",
+            0.7,
+            true,
+            None,
+            "telegram",
+            &crate::config::MultimodalConfig::default(),
+            10,
+            None,
+            None,
+            None,
+            &[],
+        )
+        .await;
+        recorder.record_turn_end(result.as_deref().map_err(|e| format!("{e:#}")));
+        assert_eq!(result.unwrap(), "The tool said hi.");
+
+        let config = Config::default();
+        let report = replay_file(&config, recorder.path(), ReplayOptions::default())
+            .await
+            .unwrap();
+        assert!(report.reproduced(), "{}", report.render());
+        assert_eq!(
+            (report.turns, report.llm_calls, report.tool_calls),
+            (1, 2, 1)
+        );
+
+        let mut events = recording::load(recorder.path()).unwrap();
+        for event in &mut events {
+            match event {
+                RecordedEvent::ToolCall { args, .. } => *args = json!({ "value": "bye" }),
+                RecordedEvent::TurnEnd { response, .. } => *response = Some("Other.".into()),
+                _ => {}
+            }
+        }
+        let report = replay(&config, events, ReplayOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(report.divergences.len(), 2, "{}", report.render());
+        assert!(report.divergences[0].contains("`echo` was called with {\"value\":\"hi\"}"));
+        assert!(report.divergences[1].starts_with("turn 1: replied \"The tool said hi.\""));
+    }
+
+    #[tokio::test]
+    async fn sandbox_mode_reruns_sandbox_tools() {
+        let sandbox_tools = crate::tools::sandbox_tools(
+            crate::tools::sandbox_client(),
+            Arc::new(crate::config::ZerobuildConfig::default()),
+            String::new(),
+            Arc::new(crate::memory::embeddings::NoopEmbedding),
+            None,
+        );
+        let specs = sandbox_tools
+            .iter()
+            .filter(|tool| ["sandbox_create", "sandbox_run_command"].contains(&tool.name()))
+            .map(|tool| tool.spec())
+            .collect();
+        let ok = |output: &str| ToolResult {
+            success: true,
+            output: output.into(),
+            error: None,
+            error_code: None,
+            error_hint: None,
+        };
+        let response = |response: ChatResponse| RecordedEvent::LlmResponse {
+            text: response.text,
+            tool_calls: response.tool_calls,
+            reasoning_content: None,
+            input_tokens: None,
+            output_tokens: None,
+            error: None,
+            at: None,
+            duration_ms: None,
+        };
+        let request = |len: usize| RecordedEvent::LlmRequest {
+            messages: vec![ChatMessage::user("x"); len],
+            native_tools: true,
+        };
+        let create_args = json!({ "reset": true });
+        let command_args = json!({ "command": "echo oops >&2; exit 3" });
+        let events = vec![
+            RecordedEvent::Session {
+                id: "s".into(),
+                started_at: String::new(),
+                channel: "cli".into(),
+                tools: specs,
+            },
+            RecordedEvent::Turn {
+                provider: "openai".into(),
+                model: "gpt-4o".into(),
+                temperature: 0.7,
+                max_tool_iterations: 10,
+                excluded_tools: Vec::new(),
+                history: vec![ChatMessage::user("build it")],
+                at: None,
+            },
+            request(1),
+            response(tool_call("sandbox_create", create_args.clone())),
+            RecordedEvent::ToolCall {
+                name: "sandbox_create".into(),
+                args: create_args,
+                result: Some(ok("Sandbox created.")),
+                error: None,
+                at: None,
+                duration_ms: None,
+            },
+            request(3),
+            response(tool_call("sandbox_run_command", command_args.clone())),
+            RecordedEvent::ToolCall {
+                name: "sandbox_run_command".into(),
+                args: command_args,
+                result: Some(ok("done")),
+                error: None,
+                at: None,
+                duration_ms: None,
+            },
+            request(5),
+            response(reply("Built.")),
+            RecordedEvent::TurnEnd {
+                response: Some("Built.".into()),
+                error: None,
+                at: None,
+            },
+        ];
+
+        let mocked = replay(&Config::default(), events.clone(), ReplayOptions::default())
+            .await
+            .unwrap();
+        assert!(mocked.reproduced(), "{}", mocked.render());
+        assert_eq!(mocked.live_tool_calls, 0);
+
+        let live = replay(&Config::default(), events, ReplayOptions { sandbox: true })
+            .await
+            .unwrap();
+        assert_eq!(live.live_tool_calls, 2);
+        assert_eq!(live.divergences.len(), 1, "{}", live.render());
+        assert!(live.divergences[0].contains(
+            "`sandbox_run_command` failed in the sandbox but succeeded in the recording"
+        ));
+    }
+}
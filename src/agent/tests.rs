@@ -158,6 +158,7 @@ impl Tool for EchoTool {
             success: true,
             output: msg,
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
@@ -185,6 +186,7 @@ impl Tool for FailingTool {
             success: false,
             output: String::new(),
             error: Some("intentional failure".into()),
+            error_code: None,
             error_hint: None,
         })
     }
@@ -250,6 +252,7 @@ impl Tool for CountingTool {
             success: true,
             output: format!("call #{}", *c),
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
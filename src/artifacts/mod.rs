@@ -0,0 +1,445 @@
+//! Build artifacts: snapshot versions, screenshots, and full command logs
+//! (`[zerobuild.artifacts]`).
+//!
+//! Artifacts are written to an [`ArtifactStorage`] backend — a local
+//! directory or an S3-compatible bucket — and indexed in the ZeroBuild
+//! database under the project they belong to. Two retention limits keep them
+//! bounded:
+//!
+//! - `max_age_days`: the daemon's cleanup task deletes older artifacts every
+//!   `cleanup_interval_minutes`;
+//! - `max_project_mb`: saving an artifact deletes the oldest ones of its
+//!   project until the project fits again.
+//!
+//! `zerobuild artifacts usage` and `GET /api/artifacts/usage` report the
+//! storage used per project.
+
+pub mod storage;
+
+pub use storage::{ArtifactStorage, LocalStorage, S3Storage};
+
+use crate::config::{ArtifactBackend, ArtifactsConfig, Config, ZerobuildConfig};
+use crate::store::{self, artifacts::ArtifactRecord, artifacts::ProjectUsage};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Project of artifacts saved before any snapshot named one.
+pub const DEFAULT_PROJECT: &str = "default";
+
+/// What an artifact is; the second segment of its key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArtifactKind {
+    /// Files of a `sandbox_save_snapshot`, as JSON.
+    Snapshot,
+    Screenshot,
+    /// Full output of a truncated sandbox command.
+    Log,
+}
+
+impl ArtifactKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Snapshot => "snapshot",
+            Self::Screenshot => "screenshot",
+            Self::Log => "log",
+        }
+    }
+}
+
+/// What a cleanup deleted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Cleanup {
+    pub removed: usize,
+    pub freed_bytes: u64,
+}
+
+/// `name` reduced to characters that are safe in keys and file names.
+fn key_segment(name: &str) -> String {
+    let segment: String = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let segment = segment.trim_matches(['.', '-']);
+    if segment.is_empty() {
+        "artifact".into()
+    } else {
+        segment.into()
+    }
+}
+
+/// Oldest of `records` (oldest first) to delete so the rest fit in
+/// `max_bytes`.
+fn over_quota(records: Vec<ArtifactRecord>, max_bytes: u64) -> Vec<ArtifactRecord> {
+    let mut total: u64 = records.iter().map(|r| r.size).sum();
+    records
+        .into_iter()
+        .take_while(|record| {
+            if total <= max_bytes {
+                return false;
+            }
+            total = total.saturating_sub(record.size);
+            true
+        })
+        .collect()
+}
+
+/// Directory of the `local` backend: `dir`, or `artifacts/` next to the
+/// database.
+pub fn local_dir(config: &ZerobuildConfig) -> PathBuf {
+    let dir = config.artifacts.dir.trim();
+    if dir.is_empty() {
+        PathBuf::from(&config.db_path)
+            .parent()
+            .map_or_else(|| PathBuf::from("artifacts"), |p| p.join("artifacts"))
+    } else {
+        PathBuf::from(shellexpand::tilde(dir).as_ref())
+    }
+}
+
+/// Saves artifacts and applies the retention policy.
+pub struct Artifacts {
+    storage: Arc<dyn ArtifactStorage>,
+    db_path: PathBuf,
+    max_age: Option<chrono::Duration>,
+    max_project_bytes: Option<u64>,
+}
+
+impl Artifacts {
+    pub fn new(
+        storage: Arc<dyn ArtifactStorage>,
+        db_path: impl Into<PathBuf>,
+        config: &ArtifactsConfig,
+    ) -> Self {
+        Self {
+            storage,
+            db_path: db_path.into(),
+            max_age: i64::try_from(config.max_age_days)
+                .ok()
+                .filter(|days| *days > 0)
+                .and_then(chrono::Duration::try_days),
+            max_project_bytes: (config.max_project_mb > 0)
+                .then(|| config.max_project_mb.saturating_mul(1024 * 1024)),
+        }
+    }
+
+    /// Artifacts in the backend selected by `[zerobuild.artifacts]`.
+    pub fn from_config(config: &ZerobuildConfig) -> Self {
+        let storage: Arc<dyn ArtifactStorage> = match config.artifacts.backend {
+            ArtifactBackend::Local => Arc::new(LocalStorage::new(local_dir(config))),
+            ArtifactBackend::S3 => Arc::new(S3Storage::new(config.artifacts.s3.clone())),
+        };
+        Self::new(storage, &config.db_path, &config.artifacts)
+    }
+
+    /// Name of the storage backend.
+    pub fn backend(&self) -> &str {
+        self.storage.name()
+    }
+
+    /// Store `bytes` as a `kind` artifact called `name` of `project`, or of
+    /// the project of the last snapshot when `project` is `None`. Older
+    /// artifacts of the project are deleted when it exceeds its size limit.
+    pub async fn save(
+        &self,
+        project: Option<&str>,
+        kind: ArtifactKind,
+        name: &str,
+        bytes: Vec<u8>,
+    ) -> Result<ArtifactRecord> {
+        let project = self.project_key(project)?;
+        let now = Utc::now();
+        let key = format!(
+            "{project}/{}/{}-{}-{}",
+            kind.as_str(),
+            now.format("%Y%m%d-%H%M%S"),
+            &uuid::Uuid::new_v4().simple().to_string()[..8],
+            key_segment(name)
+        );
+        let record = ArtifactRecord {
+            key,
+            project,
+            kind: kind.as_str().to_string(),
+            size: u64::try_from(bytes.len()).unwrap_or(u64::MAX),
+            created_at: now.to_rfc3339(),
+        };
+
+        self.storage.put(&record.key, bytes).await?;
+        let conn = store::init_db(&self.db_path)?;
+        store::artifacts::insert(&conn, &record)?;
+
+        if let Some(max_bytes) = self.max_project_bytes {
+            let doomed = over_quota(store::artifacts::list(&conn, &record.project)?, max_bytes);
+            drop(conn);
+            let cleanup = self.remove(doomed).await;
+            if cleanup.removed > 0 {
+                tracing::info!(
+                    project = %record.project,
+                    "Deleted {} old artifacts over the project size limit",
+                    cleanup.removed
+                );
+            }
+        }
+        Ok(record)
+    }
+
+    /// Key segment of `project`, or of the project of the last snapshot when
+    /// `None`.
+    fn project_key(&self, project: Option<&str>) -> Result<String> {
+        let project = match project {
+            Some(project) => Some(project.to_string()),
+            None => store::snapshot::load_project(&store::init_db(&self.db_path)?)?,
+        };
+        Ok(project.map_or_else(|| DEFAULT_PROJECT.to_string(), |p| key_segment(&p)))
+    }
+
+    /// `kind` artifacts of `project` (of the last snapshot when `None`),
+    /// newest first.
+    pub fn versions(
+        &self,
+        project: Option<&str>,
+        kind: ArtifactKind,
+    ) -> Result<Vec<ArtifactRecord>> {
+        let project = self.project_key(project)?;
+        let conn = store::init_db(&self.db_path)?;
+        let mut records: Vec<ArtifactRecord> = store::artifacts::list(&conn, &project)?
+            .into_iter()
+            .filter(|record| record.kind == kind.as_str())
+            .collect();
+        records.reverse();
+        Ok(records)
+    }
+
+    /// Bytes of the artifact stored under `key`.
+    pub async fn load(&self, key: &str) -> Result<Vec<u8>> {
+        self.storage.get(key).await
+    }
+
+    /// Storage used per project, largest first.
+    pub fn usage(&self) -> Result<Vec<ProjectUsage>> {
+        store::artifacts::usage(&store::init_db(&self.db_path)?)
+    }
+
+    /// Delete artifacts older than the age limit as of `now`, then the
+    /// oldest of each project over the size limit.
+    pub async fn cleanup(&self, now: DateTime<Utc>) -> Result<Cleanup> {
+        let conn = store::init_db(&self.db_path)?;
+        let cutoff = self.max_age.and_then(|age| now.checked_sub_signed(age));
+        let mut doomed = match cutoff {
+            Some(cutoff) => store::artifacts::created_before(&conn, &cutoff.to_rfc3339())?,
+            None => Vec::new(),
+        };
+        if let Some(max_bytes) = self.max_project_bytes {
+            for usage in store::artifacts::usage(&conn)? {
+                if usage.bytes <= max_bytes {
+                    continue;
+                }
+                let remaining = store::artifacts::list(&conn, &usage.project)?
+                    .into_iter()
+                    .filter(|record| !doomed.contains(record))
+                    .collect();
+                doomed.extend(over_quota(remaining, max_bytes));
+            }
+        }
+        drop(conn);
+        Ok(self.remove(doomed).await)
+    }
+
+    /// Delete `records` from the backend and the index. Artifacts the
+    /// backend fails to delete stay indexed, so the next cleanup retries.
+    async fn remove(&self, records: Vec<ArtifactRecord>) -> Cleanup {
+        let mut cleanup = Cleanup::default();
+        for record in records {
+            if let Err(e) = self.storage.delete(&record.key).await {
+                tracing::warn!(key = %record.key, "Failed to delete artifact: {e:#}");
+                continue;
+            }
+            let unindexed = store::init_db(&self.db_path)
+                .and_then(|conn| store::artifacts::remove(&conn, &record.key));
+            if let Err(e) = unindexed {
+                tracing::warn!(key = %record.key, "Failed to unindex artifact: {e:#}");
+                continue;
+            }
+            cleanup.removed += 1;
+            cleanup.freed_bytes += record.size;
+        }
+        cleanup
+    }
+}
+
+/// Apply the retention policy every `cleanup_interval_minutes`.
+pub async fn run_cleanup_worker(config: ZerobuildConfig) -> Result<()> {
+    let minutes = config.artifacts.cleanup_interval_minutes;
+    if minutes == 0 {
+        return Ok(());
+    }
+
+    let artifacts = Artifacts::from_config(&config);
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(minutes * 60));
+    loop {
+        interval.tick().await;
+        let cleanup = artifacts.cleanup(Utc::now()).await?;
+        if cleanup.removed > 0 {
+            tracing::info!(
+                "artifact cleanup removed {} artifacts ({})",
+                cleanup.removed,
+                format_bytes(cleanup.freed_bytes)
+            );
+        }
+    }
+}
+
+/// `bytes` as e.g. `34.2 MB`.
+fn format_bytes(bytes: u64) -> String {
+    let value = bytes as f64;
+    if value >= 1e9 {
+        format!("{:.1} GB", value / 1e9)
+    } else if value >= 1e6 {
+        format!("{:.1} MB", value / 1e6)
+    } else {
+        format!("{:.0} kB", value / 1e3)
+    }
+}
+
+/// Handle `zerobuild artifacts <subcommand>`.
+pub async fn handle_command(command: crate::ArtifactCommands, config: &Config) -> Result<()> {
+    let artifacts = Artifacts::from_config(&config.zerobuild);
+    match command {
+        crate::ArtifactCommands::Usage => {
+            let usage = artifacts.usage()?;
+            if usage.is_empty() {
+                println!("No artifacts stored ({} backend).", artifacts.backend());
+                return Ok(());
+            }
+            println!("Artifacts ({} backend):", artifacts.backend());
+            for project in &usage {
+                let kinds: Vec<String> = project
+                    .kinds
+                    .iter()
+                    .map(|(kind, bytes)| format!("{kind} {}", format_bytes(*bytes)))
+                    .collect();
+                println!(
+                    "  {:<24} {:>4} files {:>10}  ({}; oldest {})",
+                    project.project,
+                    project.count,
+                    format_bytes(project.bytes),
+                    kinds.join(", "),
+                    project.oldest.as_deref().unwrap_or("-")
+                );
+            }
+            Ok(())
+        }
+        crate::ArtifactCommands::Cleanup => {
+            let cleanup = artifacts.cleanup(Utc::now()).await?;
+            println!(
+                "Removed {} artifacts, freed {}.",
+                cleanup.removed,
+                format_bytes(cleanup.freed_bytes)
+            );
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn artifacts(tmp: &TempDir, max_age_days: u64, max_project_mb: u64) -> Artifacts {
+        Artifacts::new(
+            Arc::new(LocalStorage::new(tmp.path().join("artifacts"))),
+            tmp.path().join("zerobuild.db"),
+            &ArtifactsConfig {
+                max_age_days,
+                max_project_mb,
+                ..ArtifactsConfig::default()
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn artifacts_are_filed_under_the_snapshot_project() {
+        let tmp = TempDir::new().unwrap();
+        let artifacts = artifacts(&tmp, 30, 0);
+
+        let first = artifacts
+            .save(None, ArtifactKind::Log, "log-1.log", b"out".to_vec())
+            .await
+            .unwrap();
+        assert!(first.key.starts_with("default/log/"));
+        assert!(first.key.ends_with("-log-1.log"));
+
+        let conn = store::init_db(&tmp.path().join("zerobuild.db")).unwrap();
+        store::snapshot::save_project(&conn, "Todo App").unwrap();
+        let second = artifacts
+            .save(None, ArtifactKind::Screenshot, "home page.png", vec![0; 4])
+            .await
+            .unwrap();
+        assert_eq!(second.project, "Todo-App");
+        assert!(second.key.ends_with("-home-page.png"));
+        assert_eq!(artifacts.load(&second.key).await.unwrap(), [0; 4]);
+
+        let usage = artifacts.usage().unwrap();
+        assert_eq!(usage.len(), 2);
+        assert_eq!(usage[0].project, "Todo-App");
+        assert_eq!(usage[0].bytes, 4);
+    }
+
+    #[tokio::test]
+    async fn cleanup_deletes_expired_artifacts() {
+        let tmp = TempDir::new().unwrap();
+        let artifacts = artifacts(&tmp, 7, 0);
+        let record = artifacts
+            .save(
+                Some("todo"),
+                ArtifactKind::Snapshot,
+                "snapshot.json",
+                b"{}".to_vec(),
+            )
+            .await
+            .unwrap();
+
+        let now = Utc::now();
+        assert_eq!(artifacts.cleanup(now).await.unwrap(), Cleanup::default());
+        let cleanup = artifacts
+            .cleanup(now + chrono::Duration::days(8))
+            .await
+            .unwrap();
+        assert_eq!(
+            cleanup,
+            Cleanup {
+                removed: 1,
+                freed_bytes: 2
+            }
+        );
+        assert!(artifacts.usage().unwrap().is_empty());
+        assert!(artifacts.load(&record.key).await.is_err());
+    }
+
+    #[test]
+    fn oldest_artifacts_go_first_when_over_quota() {
+        let record = |key: &str, size| ArtifactRecord {
+            key: key.into(),
+            project: "todo".into(),
+            kind: "log".into(),
+            size,
+            created_at: String::new(),
+        };
+        let records = vec![record("a", 4), record("b", 4), record("c", 4)];
+        let keys = |records: Vec<ArtifactRecord>| -> Vec<String> {
+            records.into_iter().map(|r| r.key).collect()
+        };
+        assert_eq!(keys(over_quota(records.clone(), 8)), ["a"]);
+        assert_eq!(keys(over_quota(records.clone(), 5)), ["a", "b"]);
+        assert!(over_quota(records, 12).is_empty());
+    }
+}
@@ -0,0 +1,179 @@
+//! Artifact storage backends (`[zerobuild.artifacts] backend`).
+
+use crate::config::ImageS3Config;
+use crate::tools::image_hosting::s3_request;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use std::path::{Component, Path, PathBuf};
+
+/// Where artifact bytes are kept. Keys are `/`-separated relative paths.
+#[async_trait]
+pub trait ArtifactStorage: Send + Sync {
+    /// Backend name for logs and usage reports.
+    fn name(&self) -> &str;
+
+    async fn put(&self, key: &str, bytes: Vec<u8>) -> Result<()>;
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>>;
+
+    /// Delete `key`. Deleting a missing key succeeds.
+    async fn delete(&self, key: &str) -> Result<()>;
+}
+
+/// Artifacts as files under a directory.
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf> {
+        let relative = Path::new(key);
+        if key.is_empty()
+            || !relative
+                .components()
+                .all(|c| matches!(c, Component::Normal(_)))
+        {
+            anyhow::bail!("Invalid artifact key '{key}'");
+        }
+        Ok(self.root.join(relative))
+    }
+}
+
+#[async_trait]
+impl ArtifactStorage for LocalStorage {
+    fn name(&self) -> &str {
+        "local"
+    }
+
+    async fn put(&self, key: &str, bytes: Vec<u8>) -> Result<()> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(&path, bytes)
+            .await
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>> {
+        let path = self.path(key)?;
+        tokio::fs::read(&path)
+            .await
+            .with_context(|| format!("Failed to read {}", path.display()))
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        let path = self.path(key)?;
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to delete {}", path.display()))
+            }
+        }
+        // Drop directories the deletion emptied; `remove_dir` refuses
+        // non-empty ones.
+        let mut dir = path.parent();
+        while let Some(parent) = dir.filter(|d| *d != self.root) {
+            if tokio::fs::remove_dir(parent).await.is_err() {
+                break;
+            }
+            dir = parent.parent();
+        }
+        Ok(())
+    }
+}
+
+/// Artifacts as objects in an S3-compatible bucket, under the configured
+/// key prefix.
+pub struct S3Storage {
+    client: reqwest::Client,
+    config: ImageS3Config,
+}
+
+impl S3Storage {
+    pub fn new(config: ImageS3Config) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            config,
+        }
+    }
+
+    async fn send(&self, method: reqwest::Method, key: &str, body: Vec<u8>) -> Result<Vec<u8>> {
+        let key = format!("{}{key}", self.config.prefix.trim_start_matches('/'));
+        let content_type = (method == reqwest::Method::PUT).then_some("application/octet-stream");
+        let (url, headers) = s3_request(
+            &self.config,
+            method.as_str(),
+            &key,
+            &body,
+            content_type,
+            chrono::Utc::now(),
+        )?;
+        let mut request = self.client.request(method, &url);
+        for (name, value) in headers.into_iter().filter(|(name, _)| name != "host") {
+            request = request.header(name, value);
+        }
+        let resp = request.body(body).send().await?;
+        let status = resp.status();
+        let bytes = resp.bytes().await?;
+        if !status.is_success() {
+            anyhow::bail!(
+                "bucket returned {status} for {key}: {}",
+                String::from_utf8_lossy(&bytes)
+            );
+        }
+        Ok(bytes.to_vec())
+    }
+}
+
+#[async_trait]
+impl ArtifactStorage for S3Storage {
+    fn name(&self) -> &str {
+        "s3"
+    }
+
+    async fn put(&self, key: &str, bytes: Vec<u8>) -> Result<()> {
+        self.send(reqwest::Method::PUT, key, bytes).await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>> {
+        self.send(reqwest::Method::GET, key, Vec::new()).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        self.send(reqwest::Method::DELETE, key, Vec::new()).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn local_storage_roundtrips_and_prunes_empty_directories() {
+        let tmp = TempDir::new().unwrap();
+        let storage = LocalStorage::new(tmp.path());
+
+        storage
+            .put("todo/log/a.log", b"out".to_vec())
+            .await
+            .unwrap();
+        assert_eq!(storage.get("todo/log/a.log").await.unwrap(), b"out");
+
+        storage.delete("todo/log/a.log").await.unwrap();
+        storage.delete("todo/log/a.log").await.unwrap();
+        assert!(!tmp.path().join("todo").exists());
+        assert!(tmp.path().exists());
+
+        assert!(storage.put("../escape", Vec::new()).await.is_err());
+        assert!(storage.get("/etc/passwd").await.is_err());
+    }
+}
@@ -1,9 +1,12 @@
 use super::traits::{Channel, ChannelMessage, SendMessage};
+use crate::config::StreamMode;
+use crate::tools::progress::BuildStatus;
 use async_trait::async_trait;
 use futures_util::{SinkExt, StreamExt};
 use parking_lot::Mutex;
 use serde_json::json;
 use std::collections::HashMap;
+use std::time::{Duration, Instant};
 use tokio_tungstenite::tungstenite::Message;
 use uuid::Uuid;
 
@@ -15,6 +18,92 @@ pub struct DiscordChannel {
     listen_to_bots: bool,
     mention_only: bool,
     typing_handles: Mutex<HashMap<String, tokio::task::JoinHandle<()>>>,
+    stream_mode: StreamMode,
+    /// Build threads by the ID of the message that started the build.
+    build_threads: Mutex<HashMap<String, BuildThread>>,
+}
+
+/// Build threads are forgotten this long after they were opened.
+const BUILD_THREAD_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Discord's limit on thread names.
+const MAX_THREAD_NAME_CHARS: usize = 100;
+
+/// A thread opened for a build, with its pinned status message.
+#[derive(Debug, Clone)]
+struct BuildThread {
+    thread_id: String,
+    status_message_id: String,
+    started: Instant,
+    stage: Option<String>,
+    preview_url: Option<String>,
+    /// Outcome and duration once the build finished.
+    finished: Option<(bool, Duration)>,
+}
+
+impl BuildThread {
+    fn new(thread_id: String, status_message_id: String) -> Self {
+        Self {
+            thread_id,
+            status_message_id,
+            started: Instant::now(),
+            stage: None,
+            preview_url: None,
+            finished: None,
+        }
+    }
+
+    fn apply(&mut self, status: &BuildStatus) {
+        match status {
+            BuildStatus::Stage(stage) => self.stage = Some(stage.clone()),
+            BuildStatus::Preview(url) => self.preview_url = Some(url.clone()),
+            BuildStatus::Finished { success } => {
+                self.finished = Some((*success, self.started.elapsed()));
+            }
+        }
+    }
+
+    /// The status message after `elapsed` of building.
+    fn render(&self, elapsed: Duration) -> String {
+        let (title, elapsed) = match self.finished {
+            Some((true, took)) => ("✅ **Build finished**", took),
+            Some((false, took)) => ("❌ **Build failed**", took),
+            None => ("🏗️ **Building**", elapsed),
+        };
+        let mut text = format!(
+            "{title}\nStage: {}\nElapsed: {}",
+            self.stage.as_deref().unwrap_or("starting"),
+            format_elapsed(elapsed)
+        );
+        if let Some(url) = &self.preview_url {
+            text.push_str("\nPreview: ");
+            text.push_str(url);
+        }
+        text
+    }
+}
+
+/// `duration` as e.g. `4m05s` or `40s`.
+fn format_elapsed(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs >= 60 {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{secs}s")
+    }
+}
+
+/// Thread name for a build requested with `content`: its first line, cut
+/// to Discord's limit.
+fn build_thread_name(content: &str) -> String {
+    let first_line = content.lines().find(|line| !line.trim().is_empty());
+    let name = format!("Build: {}", first_line.unwrap_or("project").trim());
+    if name.chars().count() <= MAX_THREAD_NAME_CHARS {
+        return name;
+    }
+    let mut cut: String = name.chars().take(MAX_THREAD_NAME_CHARS - 1).collect();
+    cut.push('…');
+    cut
 }
 
 impl DiscordChannel {
@@ -32,13 +121,148 @@ impl DiscordChannel {
             listen_to_bots,
             mention_only,
             typing_handles: Mutex::new(HashMap::new()),
+            stream_mode: StreamMode::Off,
+            build_threads: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Edit an in-progress reply as the response streams in.
+    pub fn with_streaming(mut self, stream_mode: StreamMode) -> Self {
+        self.stream_mode = stream_mode;
+        self
+    }
+
+    fn message_url(channel_id: &str, message_id: &str) -> String {
+        format!("https://discord.com/api/v10/channels/{channel_id}/messages/{message_id}")
+    }
+
+    async fn edit_message(
+        &self,
+        channel_id: &str,
+        message_id: &str,
+        text: &str,
+    ) -> anyhow::Result<()> {
+        let resp = self
+            .http_client()
+            .patch(Self::message_url(channel_id, message_id))
+            .header("Authorization", format!("Bot {}", self.bot_token))
+            .json(&json!({ "content": text }))
+            .send()
+            .await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let err = resp
+                .text()
+                .await
+                .unwrap_or_else(|e| format!("<failed to read response body: {e}>"));
+            anyhow::bail!("Discord edit message failed ({status}): {err}");
         }
+        Ok(())
     }
 
     fn http_client(&self) -> reqwest::Client {
         crate::config::build_runtime_proxy_client("channel.discord")
     }
 
+    /// `POST` `body` to the Discord API `path` and return the `id` of what
+    /// it created.
+    async fn create(&self, path: &str, body: serde_json::Value) -> anyhow::Result<String> {
+        let resp = self
+            .http_client()
+            .post(format!("https://discord.com/api/v10/{path}"))
+            .header("Authorization", format!("Bot {}", self.bot_token))
+            .json(&body)
+            .send()
+            .await?;
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let err = resp
+                .text()
+                .await
+                .unwrap_or_else(|e| format!("<failed to read response body: {e}>"));
+            anyhow::bail!("Discord POST {path} failed ({status}): {err}");
+        }
+        let created: serde_json::Value = resp.json().await?;
+        created
+            .get("id")
+            .and_then(serde_json::Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| anyhow::anyhow!("Discord POST {path} returned no id"))
+    }
+
+    /// Open a thread on the message `request_id` that started a build and
+    /// post its pinned status message. When no thread can be opened (the
+    /// request was sent in a thread already) the status message is posted
+    /// where the request was.
+    async fn open_build_thread(
+        &self,
+        message: &ChannelMessage,
+        request_id: &str,
+    ) -> anyhow::Result<BuildThread> {
+        let thread_id = match self
+            .create(
+                &format!(
+                    "channels/{}/messages/{request_id}/threads",
+                    message.reply_target
+                ),
+                json!({
+                    "name": build_thread_name(&message.content),
+                    "auto_archive_duration": 1440,
+                }),
+            )
+            .await
+        {
+            Ok(thread_id) => thread_id,
+            Err(e) => {
+                tracing::debug!("Discord: no thread for the build, staying in place: {e}");
+                message.reply_target.clone()
+            }
+        };
+        let thread = BuildThread::new(thread_id, String::new());
+        let status_message_id = self
+            .create(
+                &format!("channels/{}/messages", thread.thread_id),
+                json!({ "content": thread.render(Duration::ZERO) }),
+            )
+            .await?;
+
+        let pin = self
+            .http_client()
+            .put(format!(
+                "https://discord.com/api/v10/channels/{}/pins/{status_message_id}",
+                thread.thread_id
+            ))
+            .header("Authorization", format!("Bot {}", self.bot_token))
+            .send()
+            .await;
+        match pin {
+            Ok(resp) if !resp.status().is_success() => {
+                tracing::debug!(
+                    "Discord: pinning the build status failed ({})",
+                    resp.status()
+                );
+            }
+            Err(e) => tracing::debug!("Discord: pinning the build status failed: {e}"),
+            Ok(_) => {}
+        }
+
+        Ok(BuildThread {
+            status_message_id,
+            ..thread
+        })
+    }
+
+    /// Where replies to the message `thread_ts` go: its build thread, if a
+    /// build opened one.
+    fn build_thread_for(&self, thread_ts: Option<&str>) -> Option<String> {
+        let request_id = thread_ts?;
+        self.build_threads
+            .lock()
+            .get(request_id)
+            .map(|thread| thread.thread_id.clone())
+    }
+
     /// Check if a Discord user ID is in the allowlist.
     /// Empty list means deny everyone until explicitly configured.
     /// `"*"` means allow everyone.
@@ -257,12 +481,12 @@ impl Channel for DiscordChannel {
     async fn send(&self, message: &SendMessage) -> anyhow::Result<()> {
         let content = super::strip_tool_call_tags(&message.content);
         let chunks = split_message_for_discord(&content);
+        let recipient = self
+            .build_thread_for(message.thread_ts.as_deref())
+            .unwrap_or_else(|| message.recipient.clone());
 
         for (i, chunk) in chunks.iter().enumerate() {
-            let url = format!(
-                "https://discord.com/api/v10/channels/{}/messages",
-                message.recipient
-            );
+            let url = format!("https://discord.com/api/v10/channels/{recipient}/messages");
 
             let body = json!({ "content": chunk });
 
@@ -477,6 +701,8 @@ impl Channel for DiscordChannel {
 
                     let message_id = d.get("id").and_then(|i| i.as_str()).unwrap_or("");
                     let channel_id = d.get("channel_id").and_then(|c| c.as_str()).unwrap_or("").to_string();
+                    // Guild messages can get a build thread; replies follow it.
+                    let in_guild = d.get("guild_id").is_some_and(|g| !g.is_null());
 
                     let channel_msg = ChannelMessage {
                         id: if message_id.is_empty() {
@@ -496,7 +722,8 @@ impl Channel for DiscordChannel {
                             .duration_since(std::time::UNIX_EPOCH)
                             .unwrap_or_default()
                             .as_secs(),
-                        thread_ts: None,
+                        thread_ts: (in_guild && !message_id.is_empty())
+                            .then(|| message_id.to_string()),
                     };
 
                     if tx.send(channel_msg).await.is_err() {
@@ -509,6 +736,125 @@ impl Channel for DiscordChannel {
         Ok(())
     }
 
+    fn supports_draft_updates(&self) -> bool {
+        self.stream_mode != StreamMode::Off
+    }
+
+    async fn send_draft(&self, message: &SendMessage) -> anyhow::Result<Option<String>> {
+        if self.stream_mode == StreamMode::Off {
+            return Ok(None);
+        }
+
+        let initial_text = if message.content.is_empty() {
+            "..."
+        } else {
+            message.content.as_str()
+        };
+        let resp = self
+            .http_client()
+            .post(format!(
+                "https://discord.com/api/v10/channels/{}/messages",
+                message.recipient
+            ))
+            .header("Authorization", format!("Bot {}", self.bot_token))
+            .json(&json!({ "content": initial_text }))
+            .send()
+            .await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let err = resp
+                .text()
+                .await
+                .unwrap_or_else(|e| format!("<failed to read response body: {e}>"));
+            anyhow::bail!("Discord send draft failed ({status}): {err}");
+        }
+
+        let body: serde_json::Value = resp.json().await?;
+        Ok(body
+            .get("id")
+            .and_then(|id| id.as_str())
+            .map(str::to_string))
+    }
+
+    async fn update_draft(
+        &self,
+        recipient: &str,
+        message_id: &str,
+        text: &str,
+    ) -> anyhow::Result<()> {
+        // Mid-stream edits show the head of the reply; the full text is
+        // delivered (chunked if needed) by finalize_draft.
+        let display: String = text.chars().take(DISCORD_MAX_MESSAGE_LENGTH).collect();
+        self.edit_message(recipient, message_id, &display).await
+    }
+
+    async fn finalize_draft(
+        &self,
+        recipient: &str,
+        message_id: &str,
+        text: &str,
+    ) -> anyhow::Result<()> {
+        let content = super::strip_tool_call_tags(text);
+        if content.chars().count() <= DISCORD_MAX_MESSAGE_LENGTH
+            && self
+                .edit_message(recipient, message_id, &content)
+                .await
+                .is_ok()
+        {
+            return Ok(());
+        }
+
+        // Too long (or the edit failed): replace the draft with a normal send.
+        let _ = self.cancel_draft(recipient, message_id).await;
+        self.send(&SendMessage::new(content, recipient)).await
+    }
+
+    async fn cancel_draft(&self, recipient: &str, message_id: &str) -> anyhow::Result<()> {
+        self.http_client()
+            .delete(Self::message_url(recipient, message_id))
+            .header("Authorization", format!("Bot {}", self.bot_token))
+            .send()
+            .await?;
+        Ok(())
+    }
+
+    fn supports_build_status(&self) -> bool {
+        true
+    }
+
+    async fn update_build_status(
+        &self,
+        message: &ChannelMessage,
+        status: &BuildStatus,
+    ) -> anyhow::Result<()> {
+        // DMs have no threads; their builds report in the conversation.
+        let Some(request_id) = message.thread_ts.as_deref() else {
+            return Ok(());
+        };
+        if self.build_thread_for(Some(request_id)).is_none() {
+            let thread = self.open_build_thread(message, request_id).await?;
+            let mut threads = self.build_threads.lock();
+            threads.retain(|_, thread| thread.started.elapsed() < BUILD_THREAD_TTL);
+            threads.insert(request_id.to_string(), thread);
+        }
+
+        let (thread_id, status_message_id, text) = {
+            let mut threads = self.build_threads.lock();
+            let Some(thread) = threads.get_mut(request_id) else {
+                return Ok(());
+            };
+            thread.apply(status);
+            (
+                thread.thread_id.clone(),
+                thread.status_message_id.clone(),
+                thread.render(thread.started.elapsed()),
+            )
+        };
+        self.edit_message(&thread_id, &status_message_id, &text)
+            .await
+    }
+
     async fn health_check(&self) -> bool {
         self.http_client()
             .get("https://discord.com/api/v10/users/@me")
@@ -897,6 +1243,56 @@ mod tests {
         assert_eq!(reconstructed, msg);
     }
 
+    #[test]
+    fn build_status_renders_stage_elapsed_and_preview() {
+        let mut thread = BuildThread::new("thread_1".into(), "status_1".into());
+        assert_eq!(
+            thread.render(Duration::ZERO),
+            "🏗️ **Building**\nStage: starting\nElapsed: 0s"
+        );
+
+        thread.apply(&BuildStatus::Stage(
+            "Integration Loop — tests failing".into(),
+        ));
+        thread.apply(&BuildStatus::Preview("https://preview.example.com".into()));
+        assert_eq!(
+            thread.render(Duration::from_secs(252)),
+            "🏗️ **Building**\nStage: Integration Loop — tests failing\nElapsed: 4m12s\n\
+             Preview: https://preview.example.com"
+        );
+
+        thread.apply(&BuildStatus::Finished { success: false });
+        assert!(thread
+            .render(Duration::from_secs(600))
+            .starts_with("❌ **Build failed**\nStage: Integration Loop"));
+    }
+
+    #[test]
+    fn build_thread_names_use_the_first_line_within_the_limit() {
+        assert_eq!(
+            build_thread_name("\nbuild a todo app\nwith dark mode"),
+            "Build: build a todo app"
+        );
+        let long = build_thread_name(&"x".repeat(300));
+        assert_eq!(long.chars().count(), MAX_THREAD_NAME_CHARS);
+        assert!(long.ends_with('…'));
+    }
+
+    #[test]
+    fn replies_to_a_build_request_go_to_its_thread() {
+        let ch = DiscordChannel::new("fake".into(), None, vec!["*".into()], false, false);
+        ch.build_threads.lock().insert(
+            "111".into(),
+            BuildThread::new("thread_9".into(), "status_1".into()),
+        );
+        assert_eq!(
+            ch.build_thread_for(Some("111")).as_deref(),
+            Some("thread_9")
+        );
+        assert_eq!(ch.build_thread_for(Some("222")), None);
+        assert_eq!(ch.build_thread_for(None), None);
+    }
+
     #[test]
     fn typing_handles_start_empty() {
         let ch = DiscordChannel::new("fake".into(), None, vec![], false, false);
@@ -904,6 +1300,26 @@ mod tests {
         assert!(guard.is_empty());
     }
 
+    #[test]
+    fn supports_draft_updates_respects_stream_mode() {
+        let off = DiscordChannel::new("fake".into(), None, vec![], false, false);
+        assert!(!off.supports_draft_updates());
+
+        let partial = DiscordChannel::new("fake".into(), None, vec![], false, false)
+            .with_streaming(StreamMode::Partial);
+        assert!(partial.supports_draft_updates());
+    }
+
+    #[tokio::test]
+    async fn send_draft_returns_none_when_stream_mode_off() {
+        let ch = DiscordChannel::new("fake".into(), None, vec![], false, false);
+        let id = ch
+            .send_draft(&SendMessage::new("...", "123"))
+            .await
+            .unwrap();
+        assert!(id.is_none());
+    }
+
     #[tokio::test]
     async fn start_typing_sets_handle() {
         let ch = DiscordChannel::new("fake".into(), None, vec![], false, false);
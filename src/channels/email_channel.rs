@@ -22,7 +22,7 @@ use rustls::{ClientConfig, RootCertStore};
 use rustls_pki_types::DnsName;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::net::TcpStream;
@@ -39,6 +39,7 @@ use super::traits::{Channel, ChannelMessage, SendMessage};
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct EmailConfig {
     /// IMAP server hostname
+    #[serde(default)]
     pub imap_host: String,
     /// IMAP server port (default: 993 for TLS)
     #[serde(default = "default_imap_port")]
@@ -67,6 +68,14 @@ pub struct EmailConfig {
     /// Allowed sender addresses/domains (empty = deny all, ["*"] = allow all)
     #[serde(default)]
     pub allowed_senders: Vec<String>,
+    /// Where incoming mail comes from: "imap" (IDLE on `imap_folder`) or
+    /// "webhook" (the gateway's `/email` endpoint, fed by SES or Mailgun)
+    #[serde(default = "default_inbound")]
+    pub inbound: String,
+    /// Secret the inbound webhook must present, as the `X-Webhook-Secret`
+    /// header or the `token` query parameter (required for "webhook")
+    #[serde(default)]
+    pub webhook_secret: Option<String>,
 }
 
 impl crate::config::traits::ChannelConfig for EmailConfig {
@@ -93,6 +102,9 @@ fn default_idle_timeout() -> u64 {
 fn default_true() -> bool {
     true
 }
+fn default_inbound() -> String {
+    "imap".into()
+}
 
 impl Default for EmailConfig {
     fn default() -> Self {
@@ -108,16 +120,302 @@ impl Default for EmailConfig {
             from_address: String::new(),
             idle_timeout_secs: default_idle_timeout(),
             allowed_senders: Vec::new(),
+            inbound: default_inbound(),
+            webhook_secret: None,
         }
     }
 }
 
 type ImapSession = Session<TlsStream<TcpStream>>;
 
-/// Email channel — IMAP IDLE for instant push notifications, SMTP for outbound
+/// Threads remembered for replies; the least recently active is dropped
+/// beyond this.
+const MAX_TRACKED_THREADS: usize = 1000;
+
+/// Inbound mail handed over by the gateway's `/email` endpoint to the
+/// channel listening in webhook mode.
+static INBOUND_WEBHOOK: parking_lot::Mutex<Option<mpsc::Sender<InboundEmail>>> =
+    parking_lot::Mutex::new(None);
+
+/// Pass an email received by the gateway to the email channel. Returns
+/// `false` when no email channel is listening in webhook mode.
+pub fn deliver_inbound(email: InboundEmail) -> bool {
+    let tx = INBOUND_WEBHOOK.lock().clone();
+    tx.is_some_and(|tx| tx.try_send(email).is_ok())
+}
+
+/// An incoming email, from IMAP or the inbound webhook.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InboundEmail {
+    pub message_id: String,
+    pub sender: String,
+    pub subject: String,
+    pub body: String,
+    /// Ids of the messages this one follows up on, oldest first
+    /// (`References`, then `In-Reply-To`), without angle brackets.
+    pub references: Vec<String>,
+    pub timestamp: u64,
+}
+
+impl InboundEmail {
+    /// Parse a raw RFC 5322 message.
+    pub fn from_mime(raw: &[u8]) -> Option<Self> {
+        let parsed = MessageParser::default().parse(raw)?;
+        let mut references = header_ids(parsed.references());
+        for id in header_ids(parsed.in_reply_to()) {
+            if !references.contains(&id) {
+                references.push(id);
+            }
+        }
+
+        #[allow(clippy::cast_sign_loss)]
+        let timestamp = parsed
+            .date()
+            .map(|d| {
+                let naive = chrono::NaiveDate::from_ymd_opt(
+                    d.year as i32,
+                    u32::from(d.month),
+                    u32::from(d.day),
+                )
+                .and_then(|date| {
+                    date.and_hms_opt(u32::from(d.hour), u32::from(d.minute), u32::from(d.second))
+                });
+                naive.map_or(0, |n| n.and_utc().timestamp() as u64)
+            })
+            .unwrap_or_else(now_secs);
+
+        Some(Self {
+            message_id: parsed
+                .message_id()
+                .map(|s| s.to_string())
+                .unwrap_or_else(|| format!("gen-{}", Uuid::new_v4())),
+            sender: EmailChannel::extract_sender(&parsed),
+            subject: parsed.subject().unwrap_or("(no subject)").to_string(),
+            body: EmailChannel::extract_text(&parsed),
+            references,
+            timestamp,
+        })
+    }
+
+    /// Parse the fields of a Mailgun route post: the raw message in
+    /// `body-mime` when there is one, otherwise the parsed fields,
+    /// preferring the text without quoted replies.
+    fn from_mailgun(fields: &HashMap<String, String>) -> Option<Self> {
+        if let Some(mime) = fields.get("body-mime") {
+            return Self::from_mime(mime.as_bytes());
+        }
+        let field = |name: &str| {
+            fields
+                .get(name)
+                .map(|value| value.trim())
+                .filter(|value| !value.is_empty())
+        };
+        let sender = field("sender")?;
+        let mut references = message_ids(field("References").unwrap_or_default());
+        for id in message_ids(field("In-Reply-To").unwrap_or_default()) {
+            if !references.contains(&id) {
+                references.push(id);
+            }
+        }
+        let body = field("stripped-text")
+            .or_else(|| field("body-plain"))
+            .map(ToString::to_string)
+            .or_else(|| field("body-html").map(EmailChannel::strip_html))
+            .unwrap_or_else(|| "(no readable content)".to_string());
+
+        Some(Self {
+            message_id: field("Message-Id")
+                .and_then(|id| message_ids(id).into_iter().next())
+                .unwrap_or_else(|| format!("gen-{}", Uuid::new_v4())),
+            sender: sender.to_string(),
+            subject: field("subject").unwrap_or("(no subject)").to_string(),
+            body,
+            references,
+            timestamp: field("timestamp")
+                .and_then(|t| t.parse().ok())
+                .unwrap_or_else(now_secs),
+        })
+    }
+
+    /// Id of the thread the email belongs to: the first message of the
+    /// conversation, or the email itself when it starts one.
+    pub fn thread_id(&self) -> String {
+        self.references
+            .first()
+            .cloned()
+            .unwrap_or_else(|| self.message_id.clone())
+    }
+}
+
+/// What the gateway's `/email` endpoint received.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InboundWebhook {
+    /// An email for the channel.
+    Email(InboundEmail),
+    /// An SNS subscription, confirmed by fetching this URL.
+    ConfirmSubscription(String),
+    /// A notification without an email (e.g. an SNS unsubscribe notice).
+    Ignored,
+}
+
+/// Parse a post to the inbound webhook: an SES receipt through SNS (JSON),
+/// a Mailgun route (form data) or a raw message (anything else).
+pub fn parse_inbound_webhook(content_type: &str, body: &[u8]) -> Result<InboundWebhook> {
+    let unparsable = || anyhow!("not a parsable email");
+    let mime_type = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+
+    // SNS posts JSON as text/plain, so it is recognized by its content.
+    if body.trim_ascii_start().starts_with(b"{") {
+        let notice: serde_json::Value = serde_json::from_slice(body)?;
+        return match notice.get("Type").and_then(|t| t.as_str()) {
+            Some("SubscriptionConfirmation") => notice
+                .get("SubscribeURL")
+                .and_then(|u| u.as_str())
+                .map(|url| InboundWebhook::ConfirmSubscription(url.to_string()))
+                .ok_or_else(|| anyhow!("SNS subscription confirmation without SubscribeURL")),
+            Some("Notification") => {
+                let message: serde_json::Value = serde_json::from_str(
+                    notice
+                        .get("Message")
+                        .and_then(|m| m.as_str())
+                        .unwrap_or_default(),
+                )?;
+                let Some(content) = message.get("content").and_then(|c| c.as_str()) else {
+                    // Bounce/complaint notices and receipts without the message
+                    return Ok(InboundWebhook::Ignored);
+                };
+                let encoding = message
+                    .pointer("/receipt/action/encoding")
+                    .and_then(|e| e.as_str())
+                    .unwrap_or_default();
+                let raw = if encoding.eq_ignore_ascii_case("BASE64") {
+                    use base64::Engine;
+                    base64::engine::general_purpose::STANDARD.decode(content.trim())?
+                } else {
+                    content.as_bytes().to_vec()
+                };
+                InboundEmail::from_mime(&raw)
+                    .map(InboundWebhook::Email)
+                    .ok_or_else(unparsable)
+            }
+            _ => Ok(InboundWebhook::Ignored),
+        };
+    }
+
+    if mime_type == "application/x-www-form-urlencoded" || mime_type == "multipart/form-data" {
+        return InboundEmail::from_mailgun(&form_fields(content_type, body))
+            .map(InboundWebhook::Email)
+            .ok_or_else(unparsable);
+    }
+
+    InboundEmail::from_mime(body)
+        .map(InboundWebhook::Email)
+        .ok_or_else(unparsable)
+}
+
+/// Text fields of an `application/x-www-form-urlencoded` or
+/// `multipart/form-data` body. File parts are left out.
+fn form_fields(content_type: &str, body: &[u8]) -> HashMap<String, String> {
+    if !content_type
+        .trim_start()
+        .to_ascii_lowercase()
+        .starts_with("multipart/")
+    {
+        let decode = |value: &str| {
+            urlencoding::decode(&value.replace('+', " "))
+                .ok()
+                .map(|value| value.into_owned())
+        };
+        return String::from_utf8_lossy(body)
+            .split('&')
+            .filter_map(|pair| {
+                let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+                Some((decode(name)?, decode(value)?))
+            })
+            .collect();
+    }
+
+    // A multipart body is a MIME message without its headers.
+    let mut raw = format!("Content-Type: {content_type}\r\n\r\n").into_bytes();
+    raw.extend_from_slice(body);
+    let Some(parsed) = MessageParser::default().parse(&raw) else {
+        return HashMap::new();
+    };
+    parsed
+        .parts
+        .iter()
+        .filter_map(|part| {
+            let disposition = MimeHeaders::content_disposition(part)?;
+            if disposition.attribute("filename").is_some() {
+                return None;
+            }
+            let name = disposition.attribute("name")?;
+            let value = std::str::from_utf8(part.contents()).ok()?;
+            Some((name.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// Message ids of a `Message-ID`, `In-Reply-To` or `References` value.
+fn message_ids(value: &str) -> Vec<String> {
+    value
+        .split_whitespace()
+        .map(|id| id.trim_matches(|c| c == '<' || c == '>' || c == ','))
+        .filter(|id| !id.is_empty())
+        .map(ToString::to_string)
+        .collect()
+}
+
+fn header_ids(value: &mail_parser::HeaderValue) -> Vec<String> {
+    match value {
+        mail_parser::HeaderValue::Text(id) => message_ids(id),
+        mail_parser::HeaderValue::TextList(ids) => {
+            ids.iter().flat_map(|id| message_ids(id)).collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Subject of a reply in a thread started with `subject`.
+fn reply_subject(subject: &str) -> String {
+    if subject
+        .get(..3)
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case("re:"))
+    {
+        subject.to_string()
+    } else {
+        format!("Re: {subject}")
+    }
+}
+
+/// A conversation replies are threaded into.
+#[derive(Debug, Clone)]
+struct EmailThread {
+    subject: String,
+    /// Message ids of the thread, oldest first, ending with the latest one.
+    references: Vec<String>,
+    last_active: u64,
+}
+
+/// Email channel — IMAP IDLE (or the gateway's inbound webhook) for
+/// incoming mail, SMTP for outbound. Each email thread is its own
+/// conversation, and replies are threaded with `In-Reply-To`/`References`.
 pub struct EmailChannel {
     pub config: EmailConfig,
     seen_messages: Arc<Mutex<HashSet<String>>>,
+    threads: Mutex<HashMap<String, EmailThread>>,
 }
 
 impl EmailChannel {
@@ -125,6 +423,7 @@ impl EmailChannel {
         Self {
             config,
             seen_messages: Arc::new(Mutex::new(HashSet::new())),
+            threads: Mutex::new(HashMap::new()),
         }
     }
 
@@ -238,7 +537,7 @@ impl EmailChannel {
     }
 
     /// Fetch and process unseen messages from the selected mailbox
-    async fn fetch_unseen(&self, session: &mut ImapSession) -> Result<Vec<ParsedEmail>> {
+    async fn fetch_unseen(&self, session: &mut ImapSession) -> Result<Vec<InboundEmail>> {
         // Search for unseen messages
         let uids = session.uid_search("UNSEEN").await?;
         if uids.is_empty() {
@@ -259,51 +558,8 @@ impl EmailChannel {
         let messages: Vec<Fetch> = messages.try_collect().await?;
 
         for msg in messages {
-            let uid = msg.uid.unwrap_or(0);
-            if let Some(body) = msg.body() {
-                if let Some(parsed) = MessageParser::default().parse(body) {
-                    let sender = Self::extract_sender(&parsed);
-                    let subject = parsed.subject().unwrap_or("(no subject)").to_string();
-                    let body_text = Self::extract_text(&parsed);
-                    let content = format!("Subject: {}\n\n{}", subject, body_text);
-                    let msg_id = parsed
-                        .message_id()
-                        .map(|s| s.to_string())
-                        .unwrap_or_else(|| format!("gen-{}", Uuid::new_v4()));
-
-                    #[allow(clippy::cast_sign_loss)]
-                    let ts = parsed
-                        .date()
-                        .map(|d| {
-                            let naive = chrono::NaiveDate::from_ymd_opt(
-                                d.year as i32,
-                                u32::from(d.month),
-                                u32::from(d.day),
-                            )
-                            .and_then(|date| {
-                                date.and_hms_opt(
-                                    u32::from(d.hour),
-                                    u32::from(d.minute),
-                                    u32::from(d.second),
-                                )
-                            });
-                            naive.map_or(0, |n| n.and_utc().timestamp() as u64)
-                        })
-                        .unwrap_or_else(|| {
-                            SystemTime::now()
-                                .duration_since(UNIX_EPOCH)
-                                .map(|d| d.as_secs())
-                                .unwrap_or(0)
-                        });
-
-                    results.push(ParsedEmail {
-                        _uid: uid,
-                        msg_id,
-                        sender,
-                        content,
-                        timestamp: ts,
-                    });
-                }
+            if let Some(email) = msg.body().and_then(InboundEmail::from_mime) {
+                results.push(email);
             }
         }
 
@@ -438,36 +694,88 @@ impl EmailChannel {
         let messages = self.fetch_unseen(session).await?;
 
         for email in messages {
-            // Check allowlist
-            if !self.is_sender_allowed(&email.sender) {
-                warn!("Blocked email from {}", email.sender);
+            let Some(msg) = self.accept(email).await else {
                 continue;
+            };
+            if tx.send(msg).await.is_err() {
+                // Channel closed, exit cleanly
+                return Ok(());
             }
+        }
 
-            let is_new = {
-                let mut seen = self.seen_messages.lock().await;
-                seen.insert(email.msg_id.clone())
-            };
-            if !is_new {
-                continue;
+        Ok(())
+    }
+
+    /// Turn an incoming email into a channel message, dropping senders that
+    /// are not allowed and emails already seen. Remembers the thread so
+    /// replies continue it.
+    async fn accept(&self, email: InboundEmail) -> Option<ChannelMessage> {
+        if !self.is_sender_allowed(&email.sender) {
+            warn!("Blocked email from {}", email.sender);
+            return None;
+        }
+
+        let is_new = {
+            let mut seen = self.seen_messages.lock().await;
+            seen.insert(email.message_id.clone())
+        };
+        if !is_new {
+            return None;
+        }
+
+        let thread_id = email.thread_id();
+        {
+            let mut threads = self.threads.lock().await;
+            if threads.len() >= MAX_TRACKED_THREADS && !threads.contains_key(&thread_id) {
+                let oldest = threads
+                    .iter()
+                    .min_by_key(|(_, thread)| thread.last_active)
+                    .map(|(id, _)| id.clone());
+                if let Some(oldest) = oldest {
+                    threads.remove(&oldest);
+                }
             }
+            let mut references = email.references.clone();
+            references.push(email.message_id.clone());
+            let subject = threads
+                .get(&thread_id)
+                .map_or_else(|| email.subject.clone(), |thread| thread.subject.clone());
+            threads.insert(
+                thread_id.clone(),
+                EmailThread {
+                    subject,
+                    references,
+                    last_active: now_secs(),
+                },
+            );
+        }
 
-            let msg = ChannelMessage {
-                id: email.msg_id,
-                reply_target: email.sender.clone(),
-                sender: email.sender,
-                content: email.content,
-                channel: "email".to_string(),
-                timestamp: email.timestamp,
-                thread_ts: None,
-            };
+        Some(ChannelMessage {
+            id: email.message_id,
+            reply_target: email.sender.clone(),
+            sender: email.sender,
+            content: format!("Subject: {}\n\n{}", email.subject, email.body),
+            channel: "email".to_string(),
+            timestamp: email.timestamp,
+            thread_ts: Some(thread_id),
+        })
+    }
+
+    /// Receive mail handed over by the gateway's `/email` endpoint.
+    async fn listen_webhook(&self, tx: mpsc::Sender<ChannelMessage>) -> Result<()> {
+        let (inbound_tx, mut inbound_rx) = mpsc::channel(64);
+        *INBOUND_WEBHOOK.lock() = Some(inbound_tx);
+        info!("Email channel receiving mail on the gateway's /email endpoint");
 
+        while let Some(email) = inbound_rx.recv().await {
+            let Some(msg) = self.accept(email).await else {
+                continue;
+            };
             if tx.send(msg).await.is_err() {
-                // Channel closed, exit cleanly
-                return Ok(());
+                break;
             }
         }
-
+        *INBOUND_WEBHOOK.lock() = None;
         Ok(())
     }
 
@@ -488,15 +796,6 @@ impl EmailChannel {
     }
 }
 
-/// Internal struct for parsed email data
-struct ParsedEmail {
-    _uid: u32,
-    msg_id: String,
-    sender: String,
-    content: String,
-    timestamp: u64,
-}
-
 /// Result from waiting on IDLE
 enum IdleWaitResult {
     NewMail,
@@ -511,24 +810,50 @@ impl Channel for EmailChannel {
     }
 
     async fn send(&self, message: &SendMessage) -> Result<()> {
-        // Use explicit subject if provided, otherwise fall back to legacy parsing or default
+        let thread = match message.thread_ts.as_deref() {
+            Some(thread_id) => self.threads.lock().await.get(thread_id).cloned(),
+            None => None,
+        };
+
+        // Use explicit subject if provided, otherwise fall back to legacy parsing,
+        // the thread's subject or default
         let (subject, body) = if let Some(ref subj) = message.subject {
-            (subj.as_str(), message.content.as_str())
+            (subj.clone(), message.content.as_str())
         } else if message.content.starts_with("Subject: ") {
             if let Some(pos) = message.content.find('\n') {
-                (&message.content[9..pos], message.content[pos + 1..].trim())
+                (
+                    message.content[9..pos].to_string(),
+                    message.content[pos + 1..].trim(),
+                )
             } else {
-                ("ZeroBuild Message", message.content.as_str())
+                ("ZeroBuild Message".to_string(), message.content.as_str())
             }
+        } else if let Some(ref thread) = thread {
+            (reply_subject(&thread.subject), message.content.as_str())
         } else {
-            ("ZeroBuild Message", message.content.as_str())
+            ("ZeroBuild Message".to_string(), message.content.as_str())
         };
 
-        let email = Message::builder()
+        let mut builder = Message::builder()
             .from(self.config.from_address.parse()?)
             .to(message.recipient.parse()?)
-            .subject(subject)
-            .singlepart(SinglePart::plain(body.to_string()))?;
+            .subject(subject);
+        if let Some(thread_id) = message.thread_ts.as_deref() {
+            // Without the thread's history (e.g. after a restart) the reply
+            // still points at the message that started it.
+            let references = thread.map_or_else(|| vec![thread_id.to_string()], |t| t.references);
+            if let Some(latest) = references.last() {
+                builder = builder.in_reply_to(format!("<{latest}>"));
+            }
+            builder = builder.references(
+                references
+                    .iter()
+                    .map(|id| format!("<{id}>"))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            );
+        }
+        let email = builder.singlepart(SinglePart::plain(body.to_string()))?;
 
         let transport = self.create_smtp_transport()?;
         transport.send(&email)?;
@@ -537,6 +862,9 @@ impl Channel for EmailChannel {
     }
 
     async fn listen(&self, tx: mpsc::Sender<ChannelMessage>) -> Result<()> {
+        if self.config.inbound == "webhook" {
+            return self.listen_webhook(tx).await;
+        }
         info!(
             "Starting email channel with IDLE support on {}",
             self.config.imap_folder
@@ -545,6 +873,18 @@ impl Channel for EmailChannel {
     }
 
     async fn health_check(&self) -> bool {
+        // Webhook mode has no mailbox to reach; check the SMTP server instead
+        if self.config.inbound == "webhook" {
+            let Ok(transport) = self.create_smtp_transport() else {
+                return false;
+            };
+            let check = tokio::task::spawn_blocking(move || transport.test_connection());
+            return matches!(
+                timeout(Duration::from_secs(10), check).await,
+                Ok(Ok(Ok(true)))
+            );
+        }
+
         // Fully async health check - attempt IMAP connection
         match timeout(Duration::from_secs(10), self.connect_imap()).await {
             Ok(Ok(mut session)) => {
@@ -635,6 +975,8 @@ mod tests {
             from_address: "bot@example.com".to_string(),
             idle_timeout_secs: 1200,
             allowed_senders: vec!["allowed@example.com".to_string()],
+            inbound: default_inbound(),
+            webhook_secret: None,
         };
         assert_eq!(config.imap_host, "imap.example.com");
         assert_eq!(config.imap_folder, "Archive");
@@ -655,6 +997,8 @@ mod tests {
             from_address: "bot@test.com".to_string(),
             idle_timeout_secs: 1740,
             allowed_senders: vec!["*".to_string()],
+            inbound: default_inbound(),
+            webhook_secret: None,
         };
         let cloned = config.clone();
         assert_eq!(cloned.imap_host, config.imap_host);
@@ -900,6 +1244,8 @@ mod tests {
             from_address: "bot@example.com".to_string(),
             idle_timeout_secs: 1740,
             allowed_senders: vec!["allowed@example.com".to_string()],
+            inbound: default_inbound(),
+            webhook_secret: None,
         };
 
         let json = serde_json::to_string(&config).unwrap();
@@ -974,4 +1320,130 @@ mod tests {
         let debug_str = format!("{:?}", config);
         assert!(debug_str.contains("imap.debug.com"));
     }
+
+    // Inbound and threading tests
+
+    const REPLY_MIME: &str = "From: Alice <alice@example.com>\r\n\
+To: bot@example.com\r\n\
+Subject: Re: Bakery site\r\n\
+Message-ID: <c@mail.example.com>\r\n\
+In-Reply-To: <b@mail.example.com>\r\n\
+References: <a@mail.example.com> <b@mail.example.com>\r\n\
+Date: Tue, 1 Jul 2025 10:00:00 +0000\r\n\
+\r\n\
+Add a contact form.\r\n";
+
+    #[test]
+    fn inbound_mime_keeps_the_thread() {
+        let email = InboundEmail::from_mime(REPLY_MIME.as_bytes()).unwrap();
+        assert_eq!(email.message_id, "c@mail.example.com");
+        assert_eq!(email.sender, "alice@example.com");
+        assert_eq!(email.subject, "Re: Bakery site");
+        assert_eq!(email.body.trim(), "Add a contact form.");
+        assert_eq!(
+            email.references,
+            vec!["a@mail.example.com", "b@mail.example.com"]
+        );
+        assert_eq!(email.thread_id(), "a@mail.example.com");
+        assert_eq!(email.timestamp, 1_751_364_000);
+    }
+
+    #[test]
+    fn inbound_webhook_reads_mailgun_and_ses() {
+        let form = "sender=alice%40example.com&subject=Bakery+site\
+&stripped-text=Build+me+a+site&body-plain=Build+me+a+site%0A%3E+quoted\
+&Message-Id=%3Ca%40mail%3E&timestamp=1700000000";
+        let InboundWebhook::Email(email) =
+            parse_inbound_webhook("application/x-www-form-urlencoded", form.as_bytes()).unwrap()
+        else {
+            panic!("expected an email");
+        };
+        assert_eq!(email.sender, "alice@example.com");
+        assert_eq!(email.subject, "Bakery site");
+        assert_eq!(email.body, "Build me a site");
+        assert_eq!(email.thread_id(), "a@mail");
+        assert_eq!(email.timestamp, 1_700_000_000);
+
+        let multipart = "--XyZ\r\n\
+Content-Disposition: form-data; name=\"sender\"\r\n\r\n\
+bob@example.com\r\n\
+--XyZ\r\n\
+Content-Disposition: form-data; name=\"subject\"\r\n\r\n\
+Portfolio\r\n\
+--XyZ\r\n\
+Content-Disposition: form-data; name=\"body-plain\"\r\n\r\n\
+A portfolio please\r\n\
+--XyZ--\r\n";
+        let InboundWebhook::Email(email) =
+            parse_inbound_webhook("multipart/form-data; boundary=XyZ", multipart.as_bytes())
+                .unwrap()
+        else {
+            panic!("expected an email");
+        };
+        assert_eq!(email.sender, "bob@example.com");
+        assert_eq!(email.subject, "Portfolio");
+        assert_eq!(email.body, "A portfolio please");
+
+        use base64::Engine;
+        let ses_message = serde_json::json!({
+            "receipt": { "action": { "type": "SNS", "encoding": "BASE64" } },
+            "content": base64::engine::general_purpose::STANDARD.encode(REPLY_MIME),
+        });
+        let notification = serde_json::json!({
+            "Type": "Notification",
+            "TopicArn": "arn:aws:sns:us-east-1:123:inbound",
+            "Message": ses_message.to_string(),
+        });
+        let parsed = parse_inbound_webhook(
+            "text/plain; charset=UTF-8",
+            notification.to_string().as_bytes(),
+        )
+        .unwrap();
+        assert!(
+            matches!(parsed, InboundWebhook::Email(ref e) if e.message_id == "c@mail.example.com")
+        );
+
+        let subscription = serde_json::json!({
+            "Type": "SubscriptionConfirmation",
+            "SubscribeURL": "https://sns.us-east-1.amazonaws.com/?Action=ConfirmSubscription",
+        });
+        assert_eq!(
+            parse_inbound_webhook("text/plain", subscription.to_string().as_bytes()).unwrap(),
+            InboundWebhook::ConfirmSubscription(
+                "https://sns.us-east-1.amazonaws.com/?Action=ConfirmSubscription".into()
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn accepted_emails_are_threaded_and_deduplicated() {
+        let channel = EmailChannel::new(EmailConfig {
+            allowed_senders: vec!["example.com".to_string()],
+            ..Default::default()
+        });
+        let email = InboundEmail::from_mime(REPLY_MIME.as_bytes()).unwrap();
+
+        let msg = channel.accept(email.clone()).await.unwrap();
+        assert_eq!(msg.thread_ts.as_deref(), Some("a@mail.example.com"));
+        assert_eq!(msg.reply_target, "alice@example.com");
+        assert!(msg.content.starts_with("Subject: Re: Bakery site\n\n"));
+        assert!(channel.accept(email.clone()).await.is_none());
+
+        let threads = channel.threads.lock().await;
+        let thread = &threads["a@mail.example.com"];
+        assert_eq!(reply_subject(&thread.subject), "Re: Bakery site");
+        assert_eq!(
+            thread.references.last().map(String::as_str),
+            Some("c@mail.example.com")
+        );
+        drop(threads);
+
+        let stranger = InboundEmail {
+            sender: "eve@elsewhere.test".into(),
+            message_id: "d@mail".into(),
+            ..email
+        };
+        assert!(channel.accept(stranger).await.is_none());
+        assert_eq!(reply_subject("Bakery site"), "Re: Bakery site");
+    }
 }
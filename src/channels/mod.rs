@@ -32,10 +32,12 @@ pub mod mattermost;
 pub mod napcat;
 pub mod nextcloud_talk;
 pub mod nostr;
+pub mod observers;
 pub mod qq;
 pub mod signal;
 pub mod slack;
 pub mod telegram;
+pub mod telegram_choices;
 pub mod traits;
 pub mod transcription;
 pub mod whatsapp;
@@ -75,10 +77,11 @@ use crate::agent::loop_::{build_tool_instructions, run_tool_call_loop, scrub_cre
 use crate::config::Config;
 use crate::identity;
 use crate::memory::{self, Memory};
-use crate::observability::{self, runtime_trace, Observer};
+use crate::observability::{self, recording, report, runtime_trace, Observer};
 use crate::providers::{self, ChatMessage, Provider};
 use crate::runtime;
 use crate::security::SecurityPolicy;
+use crate::tools::progress::{with_build_status, BuildStatus};
 use crate::tools::{self, Tool};
 use crate::util::truncate_with_ellipsis;
 use anyhow::{Context, Result};
@@ -91,6 +94,7 @@ use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex, OnceLock};
 use std::time::{Duration, Instant, SystemTime};
 use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
 
 /// Per-sender conversation history for channel messages.
 type ConversationHistoryMap = Arc<Mutex<HashMap<String, Vec<ChatMessage>>>>;
@@ -116,6 +120,8 @@ const CHANNEL_PARALLELISM_PER_CHANNEL: usize = 4;
 const CHANNEL_MIN_IN_FLIGHT_MESSAGES: usize = 8;
 const CHANNEL_MAX_IN_FLIGHT_MESSAGES: usize = 64;
 const CHANNEL_TYPING_REFRESH_INTERVAL_SECS: u64 = 4;
+/// Minimum spacing between in-progress draft edits while a reply streams.
+const DRAFT_UPDATE_INTERVAL: Duration = Duration::from_secs(1);
 const CHANNEL_HEALTH_HEARTBEAT_SECS: u64 = 30;
 const MODEL_CACHE_FILE: &str = "models_cache.json";
 const MODEL_CACHE_PREVIEW_LIMIT: usize = 10;
@@ -147,6 +153,8 @@ fn channel_message_timeout_budget_secs(
 struct ChannelRouteSelection {
     provider: String,
     model: String,
+    /// Config profile selected with `/profile`
+    profile: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -155,6 +163,32 @@ enum ChannelRuntimeCommand {
     SetProvider(String),
     ShowModel,
     SetModel(String),
+    ShowProfiles,
+    SetProfile(String),
+    ShowPreferences,
+    SetPreference(String, String),
+    ClearPreference(String),
+    ShowLanguage,
+    SetLanguage(String),
+    User(UserCommand),
+    /// `/approve <code>`: allow one guardrail-blocked call
+    Approve(String),
+}
+
+/// `/user` subcommands. Everyone may show their own role and usage; the
+/// rest need the admin role.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum UserCommand {
+    /// `/user [<channel>:<sender>]`
+    Show(Option<String>),
+    /// `/user list`
+    List,
+    /// `/user <id> role <admin|builder|viewer|default>`
+    SetRole(String, String),
+    /// `/user <id> quota <builds|sandbox_minutes|deploys> <limit|default>`
+    SetQuota(String, String, String),
+    /// `/user <id> reset`: zero today's usage
+    Reset(String),
 }
 
 #[derive(Debug, Clone, Default, Deserialize)]
@@ -209,6 +243,8 @@ struct ChannelRuntimeContext {
     tools_registry: Arc<Vec<Box<dyn Tool>>>,
     observer: Arc<dyn Observer>,
     system_prompt: Arc<String>,
+    /// Rebuilds `system_prompt` per message when set.
+    prompt_recipe: Option<Arc<SystemPromptRecipe>>,
     model: Arc<String>,
     temperature: f64,
     auto_save_memory: bool,
@@ -227,6 +263,59 @@ struct ChannelRuntimeContext {
     multimodal: crate::config::MultimodalConfig,
     hooks: Option<Arc<crate::hooks::HookRunner>>,
     non_cli_excluded_tools: Arc<Vec<String>>,
+    /// ZeroBuild store used to mirror conversations for checkpoints, to
+    /// index messages for `history_search`, and for user preferences. Opened
+    /// once and shared by every message.
+    store: Option<Arc<crate::store::SharedStore>>,
+    /// Config that `/profile` resolves named profiles against.
+    profile_base: Option<Arc<Config>>,
+    /// Model prices for the session report posted when a build finishes;
+    /// `None` unless `observability.post_build_reports` is on.
+    build_report_prices: Option<Arc<HashMap<String, crate::config::ModelPricing>>>,
+    /// Locale settings for status and error messages.
+    i18n: Arc<crate::config::I18nConfig>,
+    /// Screens messages before the agent sees them; `None` unless
+    /// `[moderation]` is enabled.
+    moderation: Option<Arc<crate::security::moderation::Moderation>>,
+}
+
+/// What the channel system prompt is built from. The prompt is rebuilt per
+/// message so prompt templates see the sender's channel, stack and
+/// preferences, and template edits apply without a restart.
+struct SystemPromptRecipe {
+    workspace: PathBuf,
+    model: String,
+    tools: Vec<(String, String)>,
+    skills: Vec<crate::skills::Skill>,
+    identity: crate::config::IdentityConfig,
+    bootstrap_max_chars: Option<usize>,
+    native_tools: bool,
+    skills_prompt_mode: crate::config::SkillsPromptInjectionMode,
+    /// Tool-call protocol for providers without native tool calling.
+    tool_instructions: String,
+}
+
+impl SystemPromptRecipe {
+    fn build(&self, vars: &crate::agent::prompt_templates::PromptVars) -> String {
+        let tools: Vec<(&str, &str)> = self
+            .tools
+            .iter()
+            .map(|(name, desc)| (name.as_str(), desc.as_str()))
+            .collect();
+        let mut prompt = build_system_prompt_with_mode(
+            &self.workspace,
+            &self.model,
+            &tools,
+            &self.skills,
+            Some(&self.identity),
+            self.bootstrap_max_chars,
+            self.native_tools,
+            self.skills_prompt_mode,
+            vars,
+        );
+        prompt.push_str(&self.tool_instructions);
+        prompt
+    }
 }
 
 #[derive(Clone)]
@@ -266,8 +355,23 @@ fn conversation_memory_key(msg: &traits::ChannelMessage) -> String {
     format!("{}_{}_{}", msg.channel, msg.sender, msg.id)
 }
 
+/// User ID that `[users]` roles and quotas are keyed by: `<channel>:<sender>`.
+fn channel_user_id(msg: &traits::ChannelMessage) -> String {
+    format!("{}:{}", msg.channel, msg.sender)
+}
+
 fn conversation_history_key(msg: &traits::ChannelMessage) -> String {
-    format!("{}_{}", msg.channel, msg.sender)
+    match (msg.channel.as_str(), msg.thread_ts.as_deref()) {
+        // Each email thread is its own conversation.
+        ("email", Some(thread)) => format!("{}_{}_{}", msg.channel, msg.sender, thread),
+        _ => format!("{}_{}", msg.channel, msg.sender),
+    }
+}
+
+/// Per-user memory store id: channel + sender, encoded by
+/// [`memory::user_scoped::memory_user_id`].
+fn memory_user_id(msg: &traits::ChannelMessage) -> String {
+    memory::user_scoped::memory_user_id(&msg.channel, &msg.sender)
 }
 
 fn interruption_scope_key(msg: &traits::ChannelMessage) -> String {
@@ -390,45 +494,20 @@ fn strip_tool_call_tags(message: &str) -> String {
     result.trim().to_string()
 }
 
-fn channel_delivery_instructions(channel_name: &str) -> Option<&'static str> {
-    match channel_name {
-        "telegram" => Some(
-            "When responding on Telegram:\n\
-             - Include media markers for files or URLs that should be sent as attachments\n\
-             - Use **bold** for key terms, section titles, and important info (renders as <b>)\n\
-             - Use *italic* for emphasis (renders as <i>)\n\
-             - Use `backticks` for inline code, commands, or technical terms\n\
-             - Use triple backticks for code blocks\n\
-             - Use emoji naturally to add personality — but don't overdo it\n\
-             - Be concise and direct. Skip filler phrases like 'Great question!' or 'Certainly!'\n\
-             - Structure longer answers with bold headers, not raw markdown ## headers\n\
-             - For media attachments use markers: [IMAGE:<path-or-url>], [DOCUMENT:<path-or-url>], [VIDEO:<path-or-url>], [AUDIO:<path-or-url>], or [VOICE:<path-or-url>]\n\
-             - Keep normal text outside markers and never wrap markers in code fences.\n\
-             - Use tool results silently: answer the latest user message directly, and do not narrate delayed/internal tool execution bookkeeping.",
-        ),
-        "signal" => Some(
-            "When responding on Signal:\n\
-             - Signal ONLY supports plain text — do NOT use markdown formatting\n\
-             - Do NOT use **bold**, *italic*, `backticks`, lists (dash/bullet), or code blocks\n\
-             - Use plain text only — write naturally without any formatting markers\n\
-             - For emphasis, use CAPITALIZATION or quotes instead of markdown\n\
-             - For lists, use simple numbers like 1. 2. 3. or write as flowing text\n\
-             - Be concise and direct. Skip filler phrases like 'Great question!' or 'Certainly!'\n\
-             - Use tool results silently: answer the latest user message directly, and do not narrate delayed/internal tool execution bookkeeping.",
-        ),
-        _ => None,
-    }
-}
-
-fn build_channel_system_prompt(base_prompt: &str, channel_name: &str) -> String {
-    if let Some(instructions) = channel_delivery_instructions(channel_name) {
-        if base_prompt.is_empty() {
-            instructions.to_string()
-        } else {
-            format!("{base_prompt}\n\n{instructions}")
-        }
-    } else {
+/// `base_prompt` plus the `delivery` template for `vars.channel` (built in
+/// for Telegram and Signal), e.g. which formatting the channel renders.
+fn build_channel_system_prompt(
+    base_prompt: &str,
+    templates: &crate::agent::prompt_templates::PromptTemplates,
+    vars: &crate::agent::prompt_templates::PromptVars,
+) -> String {
+    let instructions = templates.render("delivery", vars);
+    if instructions.is_empty() {
         base_prompt.to_string()
+    } else if base_prompt.is_empty() {
+        instructions
+    } else {
+        format!("{base_prompt}\n\n{instructions}")
     }
 }
 
@@ -470,10 +549,6 @@ fn supports_runtime_model_switch(channel_name: &str) -> bool {
 }
 
 fn parse_runtime_command(channel_name: &str, content: &str) -> Option<ChannelRuntimeCommand> {
-    if !supports_runtime_model_switch(channel_name) {
-        return None;
-    }
-
     let trimmed = content.trim();
     if !trimmed.starts_with('/') {
         return None;
@@ -487,6 +562,51 @@ fn parse_runtime_command(channel_name: &str, content: &str) -> Option<ChannelRun
         .unwrap_or(command_token)
         .to_ascii_lowercase();
 
+    // Preferences and languages are not tied to a provider, so every
+    // channel gets them.
+    if matches!(base_command.as_str(), "/prefs" | "/preferences") {
+        return Some(match (parts.next(), parts.next()) {
+            (Some("clear"), Some(key)) => ChannelRuntimeCommand::ClearPreference(key.to_string()),
+            (Some(key), Some(first)) => {
+                let value = std::iter::once(first).chain(parts).collect::<Vec<_>>();
+                ChannelRuntimeCommand::SetPreference(key.to_string(), value.join(" "))
+            }
+            _ => ChannelRuntimeCommand::ShowPreferences,
+        });
+    }
+    if base_command == "/lang" {
+        return Some(match parts.next() {
+            Some(code) => ChannelRuntimeCommand::SetLanguage(code.to_string()),
+            None => ChannelRuntimeCommand::ShowLanguage,
+        });
+    }
+    if base_command == "/approve" {
+        return Some(ChannelRuntimeCommand::Approve(
+            parts.next().unwrap_or_default().to_string(),
+        ));
+    }
+    if base_command == "/user" {
+        let args = parts.map(str::to_string).collect::<Vec<_>>();
+        let command = match args.as_slice() {
+            [list] if list == "list" => UserCommand::List,
+            [user] => UserCommand::Show(Some(user.clone())),
+            [user, reset] if reset == "reset" => UserCommand::Reset(user.clone()),
+            [user, role, name] if role == "role" => {
+                UserCommand::SetRole(user.clone(), name.clone())
+            }
+            [user, quota, name, limit] if quota == "quota" => {
+                UserCommand::SetQuota(user.clone(), name.clone(), limit.clone())
+            }
+            // No or unknown arguments show the sender's own role and usage
+            _ => UserCommand::Show(None),
+        };
+        return Some(ChannelRuntimeCommand::User(command));
+    }
+
+    if !supports_runtime_model_switch(channel_name) {
+        return None;
+    }
+
     match base_command.as_str() {
         "/models" => {
             if let Some(provider) = parts.next() {
@@ -497,18 +617,67 @@ fn parse_runtime_command(channel_name: &str, content: &str) -> Option<ChannelRun
                 Some(ChannelRuntimeCommand::ShowProviders)
             }
         }
-        "/model" => {
-            let model = parts.collect::<Vec<_>>().join(" ").trim().to_string();
-            if model.is_empty() {
-                Some(ChannelRuntimeCommand::ShowModel)
-            } else {
-                Some(ChannelRuntimeCommand::SetModel(model))
-            }
-        }
+        "/model" => match (parts.next(), parts.next()) {
+            (None, _) => Some(ChannelRuntimeCommand::ShowModel),
+            (Some(model), None) => Some(ChannelRuntimeCommand::SetModel(model.to_string())),
+            // `/model <model> <message>` is a one-turn override, see
+            // `parse_turn_model_override`
+            (Some(_), Some(_)) => None,
+        },
+        "/profile" => match parts.next() {
+            Some(profile) => Some(ChannelRuntimeCommand::SetProfile(profile.to_string())),
+            None => Some(ChannelRuntimeCommand::ShowProfiles),
+        },
         _ => None,
     }
 }
 
+/// `/model <model> <message>`: the model spec and the message to answer
+/// with it, leaving the sender's route unchanged.
+fn parse_turn_model_override(channel_name: &str, content: &str) -> Option<(String, String)> {
+    if !supports_runtime_model_switch(channel_name) {
+        return None;
+    }
+
+    let (command_token, rest) = content.trim().split_once(char::is_whitespace)?;
+    let base_command = command_token.split('@').next().unwrap_or(command_token);
+    if !base_command.eq_ignore_ascii_case("/model") {
+        return None;
+    }
+    let (spec, message) = rest.trim_start().split_once(char::is_whitespace)?;
+    let message = message.trim();
+    (!message.is_empty()).then(|| (spec.to_string(), message.to_string()))
+}
+
+/// Provider and model named by a `/model` argument or the webhook `model`
+/// field: `<provider>:<model-id>`, or a model of `current_provider`. When a
+/// model catalog has been cached for the provider, the model must be in it.
+pub(crate) fn resolve_model_override(
+    workspace_dir: &Path,
+    current_provider: &str,
+    spec: &str,
+) -> Result<(String, String), String> {
+    let spec = spec.trim().trim_matches('`');
+    let (provider, model) = spec
+        .split_once(':')
+        .and_then(|(prefix, model)| resolve_provider_alias(prefix).map(|p| (p, model.trim())))
+        .unwrap_or_else(|| (current_provider.to_string(), spec));
+    if model.is_empty() {
+        return Err(
+            "Model ID cannot be empty. Use `/model <model-id>` or `/model <provider>:<model-id>`."
+                .to_string(),
+        );
+    }
+
+    let catalog = load_cached_models(workspace_dir, &provider);
+    if !catalog.is_empty() && !catalog.iter().any(|known| known == model) {
+        return Err(format!(
+            "Model `{model}` is not in the model catalog of `{provider}`. Use `/model` to list cached model IDs."
+        ));
+    }
+    Ok((provider, model.to_string()))
+}
+
 fn resolve_provider_alias(name: &str) -> Option<String> {
     let candidate = name.trim();
     if candidate.is_empty() {
@@ -700,6 +869,7 @@ fn default_route_selection(ctx: &ChannelRuntimeContext) -> ChannelRouteSelection
     ChannelRouteSelection {
         provider: defaults.default_provider,
         model: defaults.model,
+        profile: None,
     }
 }
 
@@ -767,7 +937,15 @@ fn compact_sender_history(ctx: &ChannelRuntimeContext, sender_key: &str) -> bool
     true
 }
 
-fn append_sender_turn(ctx: &ChannelRuntimeContext, sender_key: &str, turn: ChatMessage) {
+/// Add a turn in reply to `msg` to `sender_key`'s history, and record it
+/// for `history_search` and checkpoints.
+fn append_sender_turn(
+    ctx: &ChannelRuntimeContext,
+    msg: &traits::ChannelMessage,
+    sender_key: &str,
+    turn: ChatMessage,
+) {
+    record_history_turn(ctx, &memory_user_id(msg), &turn);
     let mut histories = ctx
         .conversation_histories
         .lock()
@@ -777,6 +955,71 @@ fn append_sender_turn(ctx: &ChannelRuntimeContext, sender_key: &str, turn: ChatM
     while turns.len() > MAX_CHANNEL_HISTORY {
         turns.remove(0);
     }
+    mirror_checkpoint_conversation(ctx, &channel_user_id(msg), turns);
+}
+
+/// Add a user/assistant turn to `memory_user`'s `history_search` index.
+fn record_history_turn(ctx: &ChannelRuntimeContext, memory_user: &str, turn: &ChatMessage) {
+    let Some(store) = ctx.store.as_deref() else {
+        return;
+    };
+    if turn.role != "user" && turn.role != "assistant" {
+        return;
+    }
+    if let Err(e) = store.with(|conn| {
+        crate::store::history::record_message(conn, memory_user, &turn.role, &turn.content)
+    }) {
+        tracing::debug!("Failed to index conversation turn: {e}");
+    }
+}
+
+/// Project of the saved snapshot, whose memory every sender shares.
+fn memory_project_id(ctx: &ChannelRuntimeContext) -> Option<String> {
+    let store = ctx.store.as_deref()?;
+    match store.with(crate::store::snapshot::load_project) {
+        Ok(project) => project.map(|name| memory::user_scoped::project_id(&name)),
+        Err(e) => {
+            tracing::debug!("Failed to load snapshot project: {e}");
+            None
+        }
+    }
+}
+
+/// Record `user`'s working conversation so `checkpoint create` can capture
+/// it.
+fn mirror_checkpoint_conversation(ctx: &ChannelRuntimeContext, user: &str, turns: &[ChatMessage]) {
+    let Some(store) = ctx.store.as_deref() else {
+        return;
+    };
+    if let Err(e) =
+        store.with(|conn| crate::store::checkpoint::save_conversation(conn, user, turns))
+    {
+        tracing::debug!("Failed to mirror conversation for checkpoints: {e}");
+    }
+}
+
+/// Replace the sender's history with the conversation of a checkpoint that
+/// `user` switched to since their last message. Returns `true` when rewound.
+fn apply_pending_checkpoint(ctx: &ChannelRuntimeContext, user: &str, sender_key: &str) -> bool {
+    let Some(store) = ctx.store.as_deref() else {
+        return false;
+    };
+    let pending =
+        match store.with(|conn| crate::store::checkpoint::take_pending_conversation(conn, user)) {
+            Ok(pending) => pending,
+            Err(e) => {
+                tracing::debug!("Failed to check for pending checkpoint switch: {e}");
+                return false;
+            }
+        };
+    let Some(turns) = pending else {
+        return false;
+    };
+    ctx.conversation_histories
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(sender_key.to_string(), turns);
+    true
 }
 
 fn rollback_orphan_user_turn(
@@ -807,7 +1050,7 @@ fn rollback_orphan_user_turn(
 }
 
 fn should_skip_memory_context_entry(key: &str, content: &str) -> bool {
-    if memory::is_assistant_autosave_key(key) {
+    if memory::is_assistant_autosave_key(key) || crate::skills::learned::is_learned_skill_key(key) {
         return true;
     }
 
@@ -834,7 +1077,8 @@ fn is_context_window_overflow_error(err: &anyhow::Error) -> bool {
     .any(|hint| lower.contains(hint))
 }
 
-fn load_cached_model_preview(workspace_dir: &Path, provider_name: &str) -> Vec<String> {
+/// Model IDs cached for `provider_name` by `zerobuild models refresh`.
+fn load_cached_models(workspace_dir: &Path, provider_name: &str) -> Vec<String> {
     let cache_path = workspace_dir.join("state").join(MODEL_CACHE_FILE);
     let Ok(raw) = std::fs::read_to_string(cache_path) else {
         return Vec::new();
@@ -847,16 +1091,16 @@ fn load_cached_model_preview(workspace_dir: &Path, provider_name: &str) -> Vec<S
         .entries
         .into_iter()
         .find(|entry| entry.provider == provider_name)
-        .map(|entry| {
-            entry
-                .models
-                .into_iter()
-                .take(MODEL_CACHE_PREVIEW_LIMIT)
-                .collect::<Vec<_>>()
-        })
+        .map(|entry| entry.models)
         .unwrap_or_default()
 }
 
+fn load_cached_model_preview(workspace_dir: &Path, provider_name: &str) -> Vec<String> {
+    let mut models = load_cached_models(workspace_dir, provider_name);
+    models.truncate(MODEL_CACHE_PREVIEW_LIMIT);
+    models
+}
+
 async fn get_or_create_provider(
     ctx: &ChannelRuntimeContext,
     provider_name: &str,
@@ -931,7 +1175,10 @@ fn build_models_help_response(current: &ChannelRouteSelection, workspace_dir: &P
         "Current provider: `{}`\nCurrent model: `{}`",
         current.provider, current.model
     );
-    response.push_str("\nSwitch model with `/model <model-id>`.\n");
+    response.push_str(
+        "\nSwitch model with `/model <model-id>` or `/model <provider>:<model-id>`.\n\
+         Add a message after it to answer only that message with the model.\n",
+    );
 
     let cached_models = load_cached_model_preview(workspace_dir, &current.provider);
     if cached_models.is_empty() {
@@ -979,6 +1226,275 @@ fn build_providers_help_response(current: &ChannelRouteSelection) -> String {
     response
 }
 
+fn build_profiles_help_response(
+    ctx: &ChannelRuntimeContext,
+    current: &ChannelRouteSelection,
+) -> String {
+    let profiles = ctx
+        .profile_base
+        .as_deref()
+        .map(|base| &base.profiles)
+        .filter(|profiles| !profiles.is_empty());
+    let Some(profiles) = profiles else {
+        return "No config profiles are configured. Add `[profiles.<name>]` to config.toml."
+            .to_string();
+    };
+
+    let mut response = String::new();
+    let _ = writeln!(
+        response,
+        "Current profile: `{}`\nSwitch with `/profile <name>` (starts a new session).\n\nAvailable profiles:",
+        current.profile.as_deref().unwrap_or("none")
+    );
+    let mut names: Vec<&String> = profiles.keys().collect();
+    names.sort();
+    for name in names {
+        match profiles[name].description.as_deref() {
+            Some(description) => {
+                let _ = writeln!(response, "- {name}: {description}");
+            }
+            None => {
+                let _ = writeln!(response, "- {name}");
+            }
+        }
+    }
+    response
+}
+
+/// Apply a named profile (plus the snapshot project's override file) to the
+/// sender's route and start a fresh conversation.
+async fn switch_profile(
+    ctx: &ChannelRuntimeContext,
+    sender_key: &str,
+    current: &mut ChannelRouteSelection,
+    name: &str,
+) -> String {
+    let Some(base) = ctx.profile_base.as_deref() else {
+        return "Config profiles are not available on this channel.".to_string();
+    };
+    let project = memory_project_id(ctx);
+    let resolved = match crate::config::profiles::resolve(base, Some(name), project.as_deref()) {
+        Ok(resolved) => resolved,
+        Err(err) => return format!("Profile unchanged: {err:#}"),
+    };
+
+    let provider = resolved
+        .default_provider
+        .clone()
+        .unwrap_or_else(|| current.provider.clone());
+    if let Err(err) = get_or_create_provider(ctx, &provider).await {
+        let safe_err = providers::sanitize_api_error(&err.to_string());
+        return format!(
+            "Failed to initialize provider `{provider}` for profile `{name}`. Profile unchanged.\nDetails: {safe_err}"
+        );
+    }
+    current.provider = provider;
+    if let Some(model) = resolved.default_model {
+        current.model = model;
+    }
+    current.profile = Some(name.to_string());
+    set_route_selection(ctx, sender_key, current.clone());
+    clear_sender_history(ctx, sender_key);
+
+    format!(
+        "Profile `{name}` active for this sender session: provider `{}`, model `{}`.",
+        current.provider, current.model
+    )
+}
+
+fn build_preferences_response(ctx: &ChannelRuntimeContext, user: &str) -> String {
+    let Some(store) = ctx.store.as_deref() else {
+        return "Preferences are unavailable: no ZeroBuild store is configured.".to_string();
+    };
+    let prefs = match store.with(|conn| crate::store::preferences::list(conn, user)) {
+        Ok(prefs) => prefs,
+        Err(e) => return format!("Failed to load preferences: {e}"),
+    };
+
+    let mut response = if prefs.is_empty() {
+        "No preferences set.\n".to_string()
+    } else {
+        format!(
+            "Preferences:\n{}",
+            crate::store::preferences::format_lines(&prefs)
+        )
+    };
+    response.push_str(
+        "\nSet one with `/prefs <key> <value>`, remove it with `/prefs clear <key>`. Keys:\n",
+    );
+    for (key, _, values) in crate::store::preferences::PREFERENCES {
+        let _ = writeln!(response, "- `{key}`: {values}");
+    }
+    response
+}
+
+/// Set (`Some`) or clear (`None`) `user`'s preference `key`.
+fn update_preference(
+    ctx: &ChannelRuntimeContext,
+    user: &str,
+    key: &str,
+    value: Option<&str>,
+) -> String {
+    let Some(store) = ctx.store.as_deref() else {
+        return "Preferences are unavailable: no ZeroBuild store is configured.".to_string();
+    };
+    let result = store.with(|conn| match value {
+        Some(value) => crate::store::preferences::save(conn, user, key, value)
+            .map(|(key, value)| format!("Preference `{key}` set to `{value}`.")),
+        None => crate::store::preferences::delete(conn, user, key).map(|deleted| {
+            if deleted {
+                format!("Preference `{key}` cleared.")
+            } else {
+                format!("Preference `{key}` is not set.")
+            }
+        }),
+    });
+    result.unwrap_or_else(|e| e.to_string())
+}
+
+/// The user's `/lang` choice, if they made one.
+fn chosen_locale(ctx: &ChannelRuntimeContext, user: &str) -> Option<crate::i18n::Locale> {
+    let store = ctx.store.as_deref()?;
+    match store.with(|conn| crate::store::locales::load(conn, user)) {
+        Ok(code) => code.as_deref().and_then(crate::i18n::Locale::parse),
+        Err(e) => {
+            tracing::debug!("Failed to load message language: {e}");
+            None
+        }
+    }
+}
+
+/// Language of status and error messages sent in reply to `msg`.
+fn message_locale(
+    ctx: &ChannelRuntimeContext,
+    msg: &traits::ChannelMessage,
+) -> crate::i18n::Locale {
+    crate::i18n::resolve(
+        &ctx.i18n,
+        &msg.channel,
+        chosen_locale(ctx, &channel_user_id(msg)),
+        crate::i18n::detected(&msg.channel, &msg.sender),
+    )
+}
+
+/// `/lang <code>` picks a language for the sender; `/lang auto` goes back
+/// to detection.
+fn set_language(ctx: &ChannelRuntimeContext, msg: &traits::ChannelMessage, code: &str) -> String {
+    use crate::i18n::{t, Locale};
+
+    let Some(store) = ctx.store.as_deref() else {
+        return t(message_locale(ctx, msg), "language_unavailable", &[]);
+    };
+    let user = channel_user_id(msg);
+    let clear = code.eq_ignore_ascii_case("auto");
+    let chosen = Locale::parse(code);
+    if !clear && chosen.is_none() {
+        return t(
+            message_locale(ctx, msg),
+            "language_unknown",
+            &[("code", code), ("available", &Locale::available())],
+        );
+    }
+    let saved = store.with(|conn| match chosen {
+        Some(locale) if !clear => crate::store::locales::save(conn, &user, locale.code()),
+        _ => crate::store::locales::clear(conn, &user),
+    });
+    if let Err(e) = saved {
+        return e.to_string();
+    }
+    let locale = message_locale(ctx, msg);
+    let key = if clear {
+        "language_auto"
+    } else {
+        "language_set"
+    };
+    t(locale, key, &[("language", locale.native_name())])
+}
+
+/// Answer a `/user` command. Only admins may look at or change other users.
+fn handle_user_command(
+    ctx: &ChannelRuntimeContext,
+    msg: &traits::ChannelMessage,
+    command: UserCommand,
+) -> String {
+    use crate::security::users::UserPolicy;
+    use crate::store::users::{self as user_store, Quota};
+
+    let Some(config) = ctx.profile_base.as_deref().filter(|c| c.users.enabled) else {
+        return "User management is off: set `[users] enabled = true`.".to_string();
+    };
+    let policy = UserPolicy::new(&config.users, &config.zerobuild.db_path);
+    let sender = channel_user_id(msg);
+    if command == UserCommand::Show(None) {
+        return policy.describe(&sender);
+    }
+    if policy.role(&sender) != crate::config::UserRole::Admin {
+        return "Only admins can look at or change other users.".to_string();
+    }
+
+    let result =
+        crate::store::init_db(std::path::Path::new(&config.zerobuild.db_path)).and_then(|conn| {
+            match command {
+                UserCommand::Show(user) => Ok(policy.describe(user.as_deref().unwrap_or(&sender))),
+                UserCommand::List => {
+                    let records = user_store::list(&conn)?;
+                    if records.is_empty() {
+                        return Ok("No user has a role or quota override.".to_string());
+                    }
+                    let mut out = "Users with overrides:\n".to_string();
+                    for record in records {
+                        let _ = writeln!(
+                            out,
+                            "- `{}`: {}",
+                            record.user,
+                            policy.role(&record.user).as_str()
+                        );
+                    }
+                    Ok(out)
+                }
+                UserCommand::SetRole(user, role) => {
+                    if role == "default" {
+                        user_store::set_role(&conn, &user, None)?;
+                    } else {
+                        let Some(role) = crate::config::UserRole::parse(&role) else {
+                            return Ok(format!(
+                            "Unknown role `{role}`: use `admin`, `builder`, `viewer` or `default`."
+                        ));
+                        };
+                        user_store::set_role(&conn, &user, Some(role.as_str()))?;
+                    }
+                    Ok(format!("`{user}` is now {}.", policy.role(&user).as_str()))
+                }
+                UserCommand::SetQuota(user, name, limit) => {
+                    let Some(quota) = Quota::parse(&name) else {
+                        return Ok(format!(
+                            "Unknown quota `{name}`: use `builds`, `sandbox_minutes` or `deploys`."
+                        ));
+                    };
+                    let limit = if limit == "default" {
+                        None
+                    } else {
+                        match limit.parse::<u32>() {
+                            Ok(limit) => Some(limit),
+                            Err(_) => {
+                                return Ok(format!(
+                            "Invalid limit `{limit}`: use a number (`0` = unlimited) or `default`."
+                        ))
+                            }
+                        }
+                    };
+                    user_store::set_quota(&conn, &user, quota, limit)?;
+                    Ok(policy.describe(&user))
+                }
+                UserCommand::Reset(user) => {
+                    user_store::reset_usage(&conn, &user, &user_store::today())?;
+                    Ok(format!("Today's usage of `{user}` reset."))
+                }
+            }
+        });
+    result.unwrap_or_else(|e| format!("User command failed: {e}"))
+}
+
 async fn handle_runtime_command_if_needed(
     ctx: &ChannelRuntimeContext,
     msg: &traits::ChannelMessage,
@@ -1028,18 +1544,63 @@ async fn handle_runtime_command_if_needed(
             build_models_help_response(&current, ctx.workspace_dir.as_path())
         }
         ChannelRuntimeCommand::SetModel(raw_model) => {
-            let model = raw_model.trim().trim_matches('`').to_string();
-            if model.is_empty() {
-                "Model ID cannot be empty. Use `/model <model-id>`.".to_string()
-            } else {
-                current.model = model.clone();
-                set_route_selection(ctx, &sender_key, current.clone());
-                clear_sender_history(ctx, &sender_key);
+            match resolve_model_override(ctx.workspace_dir.as_path(), &current.provider, &raw_model)
+            {
+                Ok((provider_name, model)) => {
+                    match get_or_create_provider(ctx, &provider_name).await {
+                        Ok(_) => {
+                            current.provider = provider_name;
+                            current.model = model.clone();
+                            set_route_selection(ctx, &sender_key, current.clone());
+                            clear_sender_history(ctx, &sender_key);
 
-                format!(
-                    "Model switched to `{model}` for provider `{}` in this sender session.",
-                    current.provider
-                )
+                            format!(
+                                "Model switched to `{model}` for provider `{}` in this sender session.",
+                                current.provider
+                            )
+                        }
+                        Err(err) => {
+                            let safe_err = providers::sanitize_api_error(&err.to_string());
+                            format!(
+                                "Failed to initialize provider `{provider_name}`. Route unchanged.\nDetails: {safe_err}"
+                            )
+                        }
+                    }
+                }
+                Err(err) => err,
+            }
+        }
+        ChannelRuntimeCommand::ShowProfiles => build_profiles_help_response(ctx, &current),
+        ChannelRuntimeCommand::SetProfile(name) => {
+            switch_profile(ctx, &sender_key, &mut current, &name).await
+        }
+        ChannelRuntimeCommand::ShowPreferences => {
+            build_preferences_response(ctx, &channel_user_id(msg))
+        }
+        ChannelRuntimeCommand::SetPreference(key, value) => {
+            update_preference(ctx, &channel_user_id(msg), &key, Some(&value))
+        }
+        ChannelRuntimeCommand::ClearPreference(key) => {
+            update_preference(ctx, &channel_user_id(msg), &key, None)
+        }
+        ChannelRuntimeCommand::ShowLanguage => {
+            let locale = message_locale(ctx, msg);
+            crate::i18n::t(
+                locale,
+                "language_show",
+                &[
+                    ("language", locale.native_name()),
+                    ("available", &crate::i18n::Locale::available()),
+                ],
+            )
+        }
+        ChannelRuntimeCommand::SetLanguage(code) => set_language(ctx, msg, &code),
+        ChannelRuntimeCommand::User(command) => handle_user_command(ctx, msg, command),
+        ChannelRuntimeCommand::Approve(code) => {
+            if crate::security::guardrails::approve(&channel_user_id(msg), &code) {
+                "Approved. The blocked action can run once.".to_string()
+            } else {
+                format!("No pending action of yours has approval code `{code}`.")
             }
         }
     };
@@ -1106,6 +1667,11 @@ async fn build_memory_context(
         }
     }
 
+    context.push_str(
+        &crate::skills::learned::build_learned_skills_context(mem, user_msg, min_relevance_score)
+            .await,
+    );
+
     context
 }
 
@@ -1424,13 +1990,16 @@ fn spawn_supervised_listener_with_health_interval(
 
             match result {
                 Ok(()) => {
-                    tracing::warn!("Channel {} exited unexpectedly; restarting", ch.name());
+                    tracing::warn!(
+                        channel = ch.name(),
+                        "Channel exited unexpectedly; restarting"
+                    );
                     crate::health::mark_component_error(&component, "listener exited unexpectedly");
                     // Clean exit — reset backoff since the listener ran successfully
                     backoff = initial_backoff_secs.max(1);
                 }
                 Err(e) => {
-                    tracing::error!("Channel {} error: {e}; restarting", ch.name());
+                    tracing::error!(channel = ch.name(), error = %e, "Channel error; restarting");
                     crate::health::mark_component_error(&component, e.to_string());
                 }
             }
@@ -1454,7 +2023,7 @@ fn compute_max_in_flight_messages(channel_count: usize) -> usize {
 
 fn log_worker_join_result(result: Result<(), tokio::task::JoinError>) {
     if let Err(error) = result {
-        tracing::error!("Channel message worker crashed: {error}");
+        tracing::error!(%error, "Channel message worker crashed");
     }
 }
 
@@ -1474,20 +2043,47 @@ fn spawn_scoped_typing_task(
                 () = stop_signal.cancelled() => break,
                 _ = interval.tick() => {
                     if let Err(e) = channel.start_typing(&recipient).await {
-                        tracing::debug!("Failed to start typing on {}: {e}", channel.name());
+                        tracing::debug!(channel = channel.name(), error = %e, "Failed to start typing");
                     }
                 }
             }
         }
 
         if let Err(e) = channel.stop_typing(&recipient).await {
-            tracing::debug!("Failed to stop typing on {}: {e}", channel.name());
+            tracing::debug!(channel = channel.name(), error = %e, "Failed to stop typing");
         }
     });
 
     handle
 }
 
+/// Post the session report after a turn that finished a `factory_build` or
+/// `ship_it`.
+async fn post_build_report(
+    channel: &dyn Channel,
+    msg: &traits::ChannelMessage,
+    recorder: &recording::SessionRecorder,
+    prices: &HashMap<String, crate::config::ModelPricing>,
+) {
+    let events = match recording::load(recorder.path()) {
+        Ok(events) => events,
+        Err(e) => {
+            tracing::warn!("Failed to read session recording for the build report: {e:#}");
+            return;
+        }
+    };
+    if !report::last_turn_built(&events) {
+        return;
+    }
+    let report = report::SessionReport::from_events(&events, prices).to_markdown();
+    if let Err(e) = channel
+        .send(&SendMessage::new(report, &msg.reply_target).in_thread(msg.thread_ts.clone()))
+        .await
+    {
+        tracing::warn!(channel = channel.name(), "Failed to post build report: {e}");
+    }
+}
+
 async fn process_channel_message(
     ctx: Arc<ChannelRuntimeContext>,
     msg: traits::ChannelMessage,
@@ -1534,22 +2130,63 @@ async fn process_channel_message(
 
     let target_channel = ctx.channels_by_name.get(&msg.channel).cloned();
     if let Err(err) = maybe_apply_runtime_config_update(ctx.as_ref()).await {
-        tracing::warn!("Failed to apply runtime config update: {err}");
+        tracing::warn!(error = %err, "Failed to apply runtime config update");
     }
     if handle_runtime_command_if_needed(ctx.as_ref(), &msg, target_channel.as_ref()).await {
         return;
     }
 
+    // ── Moderation: screened before the agent can act on the request ──
+    if let Some(moderation) = &ctx.moderation {
+        if let Some(refusal) = moderation
+            .screen(&msg.channel, &msg.sender, &msg.content)
+            .await
+        {
+            if let Some(channel) = target_channel.as_ref() {
+                let _ = channel
+                    .send(
+                        &SendMessage::new(refusal, &msg.reply_target)
+                            .in_thread(msg.thread_ts.clone()),
+                    )
+                    .await;
+            }
+            return;
+        }
+    }
+
     let history_key = conversation_history_key(&msg);
-    let route = get_route_selection(ctx.as_ref(), &history_key);
+    let locale = message_locale(ctx.as_ref(), &msg);
+    let mut route = get_route_selection(ctx.as_ref(), &history_key);
+    let mut msg = msg;
+    if let Some((spec, content)) = parse_turn_model_override(&msg.channel, &msg.content) {
+        match resolve_model_override(ctx.workspace_dir.as_path(), &route.provider, &spec) {
+            Ok((provider, model)) => {
+                route.provider = provider;
+                route.model = model;
+                msg.content = content;
+            }
+            Err(err) => {
+                if let Some(channel) = target_channel.as_ref() {
+                    let _ = channel
+                        .send(
+                            &SendMessage::new(err, &msg.reply_target)
+                                .in_thread(msg.thread_ts.clone()),
+                        )
+                        .await;
+                }
+                return;
+            }
+        }
+    }
     let runtime_defaults = runtime_defaults_snapshot(ctx.as_ref());
     let active_provider = match get_or_create_provider(ctx.as_ref(), &route.provider).await {
         Ok(provider) => provider,
         Err(err) => {
             let safe_err = providers::sanitize_api_error(&err.to_string());
-            let message = format!(
-                "⚠️ Failed to initialize provider `{}`. Please run `/models` to choose another provider.\nDetails: {safe_err}",
-                route.provider
+            let message = crate::i18n::t(
+                locale,
+                "provider_failed",
+                &[("provider", &route.provider), ("details", &safe_err)],
             );
             if let Some(channel) = target_channel.as_ref() {
                 let _ = channel
@@ -1578,6 +2215,10 @@ async fn process_channel_message(
     println!("  ⏳ Processing message...");
     let started_at = Instant::now();
 
+    if apply_pending_checkpoint(ctx.as_ref(), &channel_user_id(&msg), &history_key) {
+        tracing::info!("Conversation rewound to the active checkpoint");
+    }
+
     let had_prior_history = ctx
         .conversation_histories
         .lock()
@@ -1586,7 +2227,12 @@ async fn process_channel_message(
         .is_some_and(|turns| !turns.is_empty());
 
     // Preserve user turn before the LLM call so interrupted requests keep context.
-    append_sender_turn(ctx.as_ref(), &history_key, ChatMessage::user(&msg.content));
+    append_sender_turn(
+        ctx.as_ref(),
+        &msg,
+        &history_key,
+        ChatMessage::user(&msg.content),
+    );
 
     // Build history from per-sender conversation cache.
     let prior_turns_raw = ctx
@@ -1610,7 +2256,44 @@ async fn process_channel_message(
         }
     }
 
-    let system_prompt = build_channel_system_prompt(ctx.system_prompt.as_str(), &msg.channel);
+    let user_id = channel_user_id(&msg);
+    let mut template_vars = ctx
+        .store
+        .as_deref()
+        .and_then(|store| {
+            store
+                .with(|conn| {
+                    Ok(crate::agent::prompt_templates::PromptVars::for_user(
+                        conn, &user_id,
+                    ))
+                })
+                .ok()
+        })
+        .unwrap_or_default();
+    template_vars.channel = Some(msg.channel.clone());
+    let base_prompt = ctx.prompt_recipe.as_ref().map_or_else(
+        || ctx.system_prompt.to_string(),
+        |recipe| recipe.build(&template_vars),
+    );
+    let mut system_prompt = build_channel_system_prompt(
+        &base_prompt,
+        &crate::agent::prompt_templates::PromptTemplates::for_workspace(&ctx.workspace_dir),
+        &template_vars,
+    );
+    // Read per turn so `/prefs` and the `preferences` tool apply right away.
+    if let Some(store) = ctx.store.as_deref() {
+        let preferences = store
+            .with(|conn| crate::store::preferences::list(conn, &user_id))
+            .map(|prefs| crate::store::preferences::prompt_section(&prefs))
+            .unwrap_or_else(|e| {
+                tracing::debug!("Failed to load user preferences: {e}");
+                String::new()
+            });
+        if !preferences.is_empty() {
+            system_prompt.push_str("\n\n");
+            system_prompt.push_str(&preferences);
+        }
+    }
     let mut history = vec![ChatMessage::system(system_prompt)];
     history.extend(prior_turns);
     let use_streaming = target_channel
@@ -1656,7 +2339,7 @@ async fn process_channel_message(
                     let send_msg =
                         SendMessage::new(&progress, &reply_target).in_thread(thread_ts.clone());
                     if let Err(e) = channel.send(&send_msg).await {
-                        tracing::debug!("Progress send failed: {e}");
+                        tracing::debug!(error = %e, "Progress send failed");
                     }
                 }
             }))
@@ -1674,7 +2357,7 @@ async fn process_channel_message(
             {
                 Ok(id) => id,
                 Err(e) => {
-                    tracing::debug!("Failed to send draft on {}: {e}", channel.name());
+                    tracing::debug!(channel = channel.name(), error = %e, "Failed to send draft");
                     None
                 }
             }
@@ -1694,18 +2377,35 @@ async fn process_channel_message(
         let reply_target = msg.reply_target.clone();
         let draft_id = draft_id_ref.to_string();
         Some(tokio::spawn(async move {
+            // Coalesce streamed deltas into at most one draft edit per
+            // interval; the final text is delivered by finalize_draft.
             let mut accumulated = String::new();
-            while let Some(delta) = rx.recv().await {
-                if delta == crate::agent::loop_::DRAFT_CLEAR_SENTINEL {
-                    accumulated.clear();
-                    continue;
-                }
-                accumulated.push_str(&delta);
-                if let Err(e) = channel
-                    .update_draft(&reply_target, &draft_id, &accumulated)
-                    .await
-                {
-                    tracing::debug!("Draft update failed: {e}");
+            let mut dirty = false;
+            let mut ticker = tokio::time::interval(DRAFT_UPDATE_INTERVAL);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                tokio::select! {
+                    delta = rx.recv() => {
+                        let Some(delta) = delta else { break };
+                        if delta == crate::agent::loop_::DRAFT_CLEAR_SENTINEL {
+                            accumulated.clear();
+                        } else {
+                            accumulated.push_str(&delta);
+                        }
+                        dirty = true;
+                    }
+                    _ = ticker.tick() => {
+                        if !dirty || accumulated.is_empty() {
+                            continue;
+                        }
+                        dirty = false;
+                        if let Err(e) = channel
+                            .update_draft(&reply_target, &draft_id, &accumulated)
+                            .await
+                        {
+                            tracing::debug!(error = %e, "Draft update failed");
+                        }
+                    }
                 }
             }
         }))
@@ -1713,13 +2413,32 @@ async fn process_channel_message(
         None
     };
 
+    // Channels that show build status (Discord build threads) get the stage
+    // changes of factory builds started by this message.
+    let (build_status_tx, build_status_task) = match target_channel.as_ref() {
+        Some(channel) if channel.supports_build_status() => {
+            let (tx, mut rx) = tokio::sync::mpsc::channel::<BuildStatus>(16);
+            let channel = Arc::clone(channel);
+            let msg = msg.clone();
+            let task = tokio::spawn(async move {
+                while let Some(status) = rx.recv().await {
+                    if let Err(e) = channel.update_build_status(&msg, &status).await {
+                        tracing::debug!(channel = channel.name(), error = %e, "Build status update failed");
+                    }
+                }
+            });
+            (Some(tx), Some(task))
+        }
+        _ => (None, None),
+    };
+
     // React with 👀 to acknowledge the incoming message
     if let Some(channel) = target_channel.as_ref() {
         if let Err(e) = channel
             .add_reaction(&msg.reply_target, &msg.id, "\u{1F440}")
             .await
         {
-            tracing::debug!("Failed to add reaction: {e}");
+            tracing::debug!(error = %e, "Failed to add reaction");
         }
     }
 
@@ -1741,16 +2460,45 @@ async fn process_channel_message(
         Cancelled,
     }
 
+    let excluded_tools: &[String] = if msg.channel == "cli" {
+        &[]
+    } else {
+        ctx.non_cli_excluded_tools.as_ref()
+    };
+    let recorder = recording::conversation_session(
+        &history_key,
+        msg.channel.as_str(),
+        ctx.tools_registry.as_ref(),
+    );
+    let recorded = recorder.as_ref().map(|recorder| {
+        recorder.record_turn(
+            &route.provider,
+            &route.model,
+            runtime_defaults.temperature,
+            ctx.max_tool_iterations,
+            excluded_tools,
+            &history,
+        );
+        (
+            recording::RecordingProvider::new(Arc::clone(&active_provider), Arc::clone(recorder)),
+            recording::record_tools(Arc::clone(&ctx.tools_registry), recorder),
+        )
+    });
+    let (loop_provider, loop_tools): (&dyn Provider, &[Box<dyn Tool>]) = match &recorded {
+        Some((provider, tools)) => (provider, tools),
+        None => (active_provider.as_ref(), ctx.tools_registry.as_ref()),
+    };
+
     let timeout_budget_secs =
         channel_message_timeout_budget_secs(ctx.message_timeout_secs, ctx.max_tool_iterations);
     let llm_result = tokio::select! {
         () = cancellation_token.cancelled() => LlmExecutionResult::Cancelled,
         result = tokio::time::timeout(
             Duration::from_secs(timeout_budget_secs),
-            run_tool_call_loop(
-                active_provider.as_ref(),
+            with_build_status(build_status_tx, crate::i18n::with_locale(locale, run_tool_call_loop(
+                loop_provider,
                 &mut history,
-                ctx.tools_registry.as_ref(),
+                loop_tools,
                 ctx.observer.as_ref(),
                 route.provider.as_str(),
                 route.model.as_str(),
@@ -1763,18 +2511,26 @@ async fn process_channel_message(
                 Some(cancellation_token.clone()),
                 delta_tx,
                 ctx.hooks.as_deref(),
-                if msg.channel == "cli" {
-                    &[]
-                } else {
-                    ctx.non_cli_excluded_tools.as_ref()
-                },
-            ),
+                excluded_tools,
+            ))),
         ) => LlmExecutionResult::Completed(result),
     };
+    if let Some(recorder) = &recorder {
+        recorder.record_turn_end(match &llm_result {
+            LlmExecutionResult::Completed(Ok(Ok(response))) => Ok(response.as_str()),
+            LlmExecutionResult::Completed(Ok(Err(e))) => Err(format!("{e:#}")),
+            LlmExecutionResult::Completed(Err(_)) => Err("timed out".to_string()),
+            LlmExecutionResult::Cancelled => Err("cancelled".to_string()),
+        });
+    }
 
     if let Some(handle) = draft_updater {
         let _ = handle.await;
     }
+    // Replies go to the build thread once the status task has opened it.
+    if let Some(handle) = build_status_task {
+        let _ = handle.await;
+    }
 
     if let Some(token) = typing_cancellation.as_ref() {
         token.cancel();
@@ -1812,7 +2568,7 @@ async fn process_channel_message(
                 (target_channel.as_ref(), draft_message_id.as_deref())
             {
                 if let Err(err) = channel.cancel_draft(&msg.reply_target, draft_id).await {
-                    tracing::debug!("Failed to cancel draft on {}: {err}", channel.name());
+                    tracing::debug!(channel = channel.name(), error = %err, "Failed to cancel draft");
                 }
             }
         }
@@ -1911,6 +2667,7 @@ async fn process_channel_message(
 
             append_sender_turn(
                 ctx.as_ref(),
+                &msg,
                 &history_key,
                 ChatMessage::assistant(&history_response),
             );
@@ -1925,7 +2682,7 @@ async fn process_channel_message(
                         .finalize_draft(&msg.reply_target, draft_id, &delivered_response)
                         .await
                     {
-                        tracing::warn!("Failed to finalize draft: {e}; sending as new message");
+                        tracing::warn!(error = %e, "Failed to finalize draft; sending as new message");
                         let _ = channel
                             .send(
                                 &SendMessage::new(&delivered_response, &msg.reply_target)
@@ -1935,13 +2692,19 @@ async fn process_channel_message(
                     }
                 } else if let Err(e) = channel
                     .send(
-                        &SendMessage::new(delivered_response, &msg.reply_target)
+                        &SendMessage::new(&delivered_response, &msg.reply_target)
                             .in_thread(msg.thread_ts.clone()),
                     )
                     .await
                 {
                     eprintln!("  ❌ Failed to reply on {}: {e}", channel.name());
                 }
+                if let Err(e) = channel.follow_up_reply(&msg, &delivered_response).await {
+                    tracing::warn!(error = %e, "Failed to follow up the reply");
+                }
+                if let (Some(recorder), Some(prices)) = (&recorder, &ctx.build_report_prices) {
+                    post_build_report(channel.as_ref(), &msg, recorder, prices).await;
+                }
             }
         }
         LlmExecutionResult::Completed(Ok(Err(e))) => {
@@ -1969,16 +2732,20 @@ async fn process_channel_message(
                     (target_channel.as_ref(), draft_message_id.as_deref())
                 {
                     if let Err(err) = channel.cancel_draft(&msg.reply_target, draft_id).await {
-                        tracing::debug!("Failed to cancel draft on {}: {err}", channel.name());
+                        tracing::debug!(channel = channel.name(), error = %err, "Failed to cancel draft");
                     }
                 }
             } else if is_context_window_overflow_error(&e) {
                 let compacted = compact_sender_history(ctx.as_ref(), &history_key);
-                let error_text = if compacted {
-                    "⚠️ Context window exceeded for this conversation. I compacted recent history and kept the latest context. Please resend your last message."
-                } else {
-                    "⚠️ Context window exceeded for this conversation. Please resend your last message."
-                };
+                let error_text = crate::i18n::t(
+                    locale,
+                    if compacted {
+                        "context_compacted"
+                    } else {
+                        "context_exceeded"
+                    },
+                    &[],
+                );
                 eprintln!(
                     "  ⚠️ Context window exceeded after {}ms; sender history compacted={}",
                     started_at.elapsed().as_millis(),
@@ -2001,7 +2768,7 @@ async fn process_channel_message(
                 if let Some(channel) = target_channel.as_ref() {
                     if let Some(ref draft_id) = draft_message_id {
                         let _ = channel
-                            .finalize_draft(&msg.reply_target, draft_id, error_text)
+                            .finalize_draft(&msg.reply_target, draft_id, &error_text)
                             .await;
                     } else {
                         let _ = channel
@@ -2042,19 +2809,21 @@ async fn process_channel_message(
                     // inherit this failed request as unfinished context.
                     append_sender_turn(
                         ctx.as_ref(),
+                        &msg,
                         &history_key,
                         ChatMessage::assistant("[Task failed — not continuing this request]"),
                     );
                 }
                 if let Some(channel) = target_channel.as_ref() {
+                    let error_text = crate::i18n::t(locale, "error", &[("error", &e.to_string())]);
                     if let Some(ref draft_id) = draft_message_id {
                         let _ = channel
-                            .finalize_draft(&msg.reply_target, draft_id, &format!("⚠️ Error: {e}"))
+                            .finalize_draft(&msg.reply_target, draft_id, &error_text)
                             .await;
                     } else {
                         let _ = channel
                             .send(
-                                &SendMessage::new(format!("⚠️ Error: {e}"), &msg.reply_target)
+                                &SendMessage::new(error_text, &msg.reply_target)
                                     .in_thread(msg.thread_ts.clone()),
                             )
                             .await;
@@ -2089,15 +2858,15 @@ async fn process_channel_message(
             // inherit this timed-out request as unfinished context.
             append_sender_turn(
                 ctx.as_ref(),
+                &msg,
                 &history_key,
                 ChatMessage::assistant("[Task timed out — not continuing this request]"),
             );
             if let Some(channel) = target_channel.as_ref() {
-                let error_text =
-                    "⚠️ Request timed out while waiting for the model. Please try again.";
+                let error_text = crate::i18n::t(locale, "timeout", &[]);
                 if let Some(ref draft_id) = draft_message_id {
                     let _ = channel
-                        .finalize_draft(&msg.reply_target, draft_id, error_text)
+                        .finalize_draft(&msg.reply_target, draft_id, &error_text)
                         .await;
                 } else {
                     let _ = channel
@@ -2177,7 +2946,26 @@ async fn run_message_dispatch_loop(
                 }
             }
 
-            process_channel_message(worker_ctx, msg, cancellation_token).await;
+            let memory_user = memory_user_id(&msg);
+            let memory_project = memory_project_id(&worker_ctx).unwrap_or_default();
+            let user = channel_user_id(&msg);
+            let session = observability::logging::session_span(
+                &conversation_history_key(&msg),
+                &msg.sender,
+                &msg.channel,
+            );
+            crate::security::users::with_user(
+                user,
+                memory::user_scoped::with_memory_user(
+                    memory_user,
+                    memory::user_scoped::with_memory_project(
+                        memory_project,
+                        Box::pin(process_channel_message(worker_ctx, msg, cancellation_token)),
+                    ),
+                ),
+            )
+            .instrument(session)
+            .await;
 
             if interrupt_enabled {
                 let mut active = in_flight.lock().await;
@@ -2261,9 +3049,14 @@ pub fn build_system_prompt(
         bootstrap_max_chars,
         false,
         crate::config::SkillsPromptInjectionMode::Full,
+        &crate::agent::prompt_templates::PromptVars::default(),
     )
 }
 
+/// Build the system prompt. `template_vars` supplies what the caller knows
+/// about the user being served (channel, stack, preferences) to prompt
+/// templates; the project name, model and tools are filled in here.
+#[allow(clippy::too_many_arguments)]
 pub fn build_system_prompt_with_mode(
     workspace_dir: &std::path::Path,
     model_name: &str,
@@ -2273,9 +3066,28 @@ pub fn build_system_prompt_with_mode(
     bootstrap_max_chars: Option<usize>,
     native_tools: bool,
     skills_prompt_mode: crate::config::SkillsPromptInjectionMode,
+    template_vars: &crate::agent::prompt_templates::PromptVars,
 ) -> String {
     use std::fmt::Write;
     let mut prompt = String::with_capacity(8192);
+    let templates = crate::agent::prompt_templates::PromptTemplates::for_workspace(workspace_dir);
+    let template_vars = crate::agent::prompt_templates::PromptVars {
+        project_name: template_vars.project_name.clone().or_else(|| {
+            workspace_dir
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+        }),
+        model: Some(model_name.to_string()),
+        tools: tools.iter().map(|(name, _)| (*name).to_string()).collect(),
+        ..template_vars.clone()
+    };
+
+    // ── 0. Operator instructions (prompts/system.md) ─────────────
+    let instructions = templates.render("system", &template_vars);
+    if !instructions.trim().is_empty() {
+        prompt.push_str(instructions.trim_end());
+        prompt.push_str("\n\n");
+    }
 
     // ── 1. Tooling ──────────────────────────────────────────────
     if !tools.is_empty() {
@@ -2298,42 +3110,22 @@ pub fn build_system_prompt_with_mode(
             || *name == "hardware_capabilities"
     });
     if has_hardware {
-        prompt.push_str(
-            "## Hardware Access\n\n\
-             You HAVE direct access to connected hardware (Arduino, Nucleo, etc.). The user owns this system and has configured it.\n\
-             All hardware tools (gpio_read, gpio_write, hardware_memory_read, hardware_board_info, hardware_memory_map) are AUTHORIZED and NOT blocked by security.\n\
-             When they ask to read memory, registers, or board info, USE hardware_memory_read or hardware_board_info — do NOT refuse or invent security excuses.\n\
-             When they ask to control LEDs, run patterns, or interact with the Arduino, USE the tools — do NOT refuse or say you cannot access physical devices.\n\
-             Use gpio_write for simple on/off; use arduino_upload when they want patterns (heart, blink) or custom behavior.\n\n",
-        );
+        prompt.push_str(templates.render("hardware", &template_vars).trim_end());
+        prompt.push_str("\n\n");
     }
 
     // ── 1c. Action instruction (avoid meta-summary) ───────────────
-    if native_tools {
-        prompt.push_str(
-            "## Your Task\n\n\
-             When the user sends a message, respond naturally. Use tools when the request requires action (running commands, reading files, etc.).\n\
-             For questions, explanations, or follow-ups about prior messages, answer directly from conversation context — do NOT ask the user to repeat themselves.\n\
-             Do NOT: summarize this configuration, describe your capabilities, or output step-by-step meta-commentary.\n\n",
-        );
+    let task_template = if native_tools {
+        "task_native"
     } else {
-        prompt.push_str(
-            "## Your Task\n\n\
-             When the user sends a message, ACT on it. Use the tools to fulfill their request.\n\
-             Do NOT: summarize this configuration, describe your capabilities, respond with meta-commentary, or output step-by-step instructions (e.g. \"1. First... 2. Next...\").\n\
-             Instead: emit actual <tool_call> tags when you need to act. Just do what they ask.\n\n",
-        );
-    }
+        "task_xml"
+    };
+    prompt.push_str(templates.render(task_template, &template_vars).trim_end());
+    prompt.push_str("\n\n");
 
     // ── 2. Safety ───────────────────────────────────────────────
-    prompt.push_str("## Safety\n\n");
-    prompt.push_str(
-        "- Do not exfiltrate private data.\n\
-         - Do not run destructive commands without asking.\n\
-         - Do not bypass oversight or approval mechanisms.\n\
-         - Prefer `trash` over `rm` (recoverable beats gone forever).\n\
-         - When in doubt, ask before acting externally.\n\n",
-    );
+    prompt.push_str(templates.render("safety", &template_vars).trim_end());
+    prompt.push_str("\n\n");
 
     // ── 3. Skills (full or compact, based on config) ─────────────
     if !skills.is_empty() {
@@ -2393,14 +3185,16 @@ pub fn build_system_prompt_with_mode(
         load_openclaw_bootstrap_files(&mut prompt, workspace_dir, max_chars);
     }
 
-    // ── 6. Date & Time ──────────────────────────────────────────
-    let now = chrono::Local::now();
-    let _ = writeln!(
-        prompt,
-        "## Current Date & Time\n\n{} ({})\n",
-        now.format("%Y-%m-%d %H:%M:%S"),
-        now.format("%Z")
-    );
+    // ── 6. Date & Time (left out in deterministic mode) ─────────
+    if !crate::providers::sampling::is_deterministic() {
+        let now = chrono::Local::now();
+        let _ = writeln!(
+            prompt,
+            "## Current Date & Time\n\n{} ({})\n",
+            now.format("%Y-%m-%d %H:%M:%S"),
+            now.format("%Z")
+        );
+    }
 
     // ── 7. Runtime ──────────────────────────────────────────────
     let host =
@@ -2711,7 +3505,8 @@ fn collect_configured_channels(
                 )
                 .with_streaming(tg.stream_mode, tg.draft_update_interval_ms)
                 .with_transcription(config.transcription.clone())
-                .with_workspace_dir(config.workspace_dir.clone()),
+                .with_workspace_dir(config.workspace_dir.clone())
+                .with_preferences_db(PathBuf::from(&config.zerobuild.db_path)),
             ),
         });
     }
@@ -2719,13 +3514,16 @@ fn collect_configured_channels(
     if let Some(ref dc) = config.channels_config.discord {
         channels.push(ConfiguredChannel {
             display_name: "Discord",
-            channel: Arc::new(DiscordChannel::new(
-                dc.bot_token.clone(),
-                dc.guild_id.clone(),
-                dc.allowed_users.clone(),
-                dc.listen_to_bots,
-                dc.mention_only,
-            )),
+            channel: Arc::new(
+                DiscordChannel::new(
+                    dc.bot_token.clone(),
+                    dc.guild_id.clone(),
+                    dc.allowed_users.clone(),
+                    dc.listen_to_bots,
+                    dc.mention_only,
+                )
+                .with_streaming(dc.stream_mode),
+            ),
         });
     }
 
@@ -2810,14 +3608,18 @@ fn collect_configured_channels(
             "cloud" => {
                 // Cloud API mode: requires phone_number_id, access_token, verify_token
                 if wa.is_cloud_config() {
+                    let mut channel = WhatsAppChannel::new(
+                        wa.access_token.clone().unwrap_or_default(),
+                        wa.phone_number_id.clone().unwrap_or_default(),
+                        wa.verify_token.clone().unwrap_or_default(),
+                        wa.allowed_numbers.clone(),
+                    );
+                    if let Some((name, language)) = wa.reply_template_with_language() {
+                        channel = channel.with_reply_template(name, language);
+                    }
                     channels.push(ConfiguredChannel {
                         display_name: "WhatsApp",
-                        channel: Arc::new(WhatsAppChannel::new(
-                            wa.access_token.clone().unwrap_or_default(),
-                            wa.phone_number_id.clone().unwrap_or_default(),
-                            wa.verify_token.clone().unwrap_or_default(),
-                            wa.allowed_numbers.clone(),
-                        )),
+                        channel: Arc::new(channel),
                     });
                 } else {
                     tracing::warn!("WhatsApp Cloud API configured but missing required fields (phone_number_id, access_token, verify_token)");
@@ -3044,6 +3846,8 @@ pub async fn start_channels(config: Config) -> Result<()> {
         zerobuild_dir: config.config_path.parent().map(std::path::PathBuf::from),
         secrets_encrypt: config.secrets.encrypt,
         reasoning_enabled: config.runtime.reasoning_enabled,
+        local_provider: config.local_provider.clone(),
+        prompt_caching: config.agent.prompt_caching,
     };
     let provider: Arc<dyn Provider> = Arc::from(
         create_resilient_provider_nonblocking(
@@ -3086,12 +3890,22 @@ pub async fn start_channels(config: Config) -> Result<()> {
     ));
     let model = resolved_default_model(&config);
     let temperature = config.default_temperature;
-    let mem: Arc<dyn Memory> = Arc::from(memory::create_memory_with_storage(
+    let workspace_mem: Arc<dyn Memory> = Arc::from(memory::create_memory_with_storage(
         &config.memory,
         Some(&config.storage.provider.config),
         &config.workspace_dir,
         config.api_key.as_deref(),
     )?);
+    // Memory tools, autosave and recall context resolve to the sender's own
+    // store (falling back to the workspace memory for shared entries).
+    let mem: Arc<dyn Memory> = Arc::new(memory::user_scoped::CurrentUserMemory::new(Arc::new(
+        memory::user_scoped::UserScopedMemory::with_global(
+            &config.workspace_dir,
+            &config.memory,
+            config.api_key.as_deref(),
+            Arc::clone(&workspace_mem),
+        ),
+    )));
     let (composio_key, composio_entity_id) = if config.composio.enabled {
         (
             config.composio.api_key.as_deref(),
@@ -3107,6 +3921,8 @@ pub async fn start_channels(config: Config) -> Result<()> {
         &security,
         runtime,
         Arc::clone(&mem),
+        // Learned skills are shared, so they skip the per-sender routing.
+        workspace_mem,
         composio_key,
         composio_entity_id,
         &config.browser,
@@ -3135,15 +3951,27 @@ pub async fn start_channels(config: Config) -> Result<()> {
         ),
         (
             "memory_store",
-            "Save to memory. Use when: preserving durable preferences, decisions, key context. Don't use when: information is transient/noisy/sensitive without need.",
+            "Save to the current user's memory. Use when: preserving durable preferences (\"always use pnpm\", brand colors), decisions, key context. Don't use when: information is transient/noisy/sensitive without need.",
         ),
         (
             "memory_recall",
-            "Search memory. Use when: retrieving prior decisions, user preferences, historical context. Don't use when: answer is already in current context.",
+            "Search the current user's memory (plus shared entries). Use when: retrieving prior decisions, user preferences, historical context. Don't use when: answer is already in current context.",
         ),
         (
             "memory_forget",
-            "Delete a memory entry. Use when: memory is incorrect/stale or explicitly requested for removal. Don't use when: impact is uncertain.",
+            "Delete an entry from the current user's memory. Use when: memory is incorrect/stale or the user asks to forget it. Don't use when: impact is uncertain.",
+        ),
+        (
+            "history_search",
+            "Exact-term search over past messages in this conversation and memory. Use when: the user refers to something from an earlier session (an error, a decision, a URL). Don't use when: it is still in the current context.",
+        ),
+        (
+            "preferences",
+            "List, set or delete the user's generation defaults (package manager, CSS framework, language, code style, license, deploy target). Use when: the user states a lasting preference (\"always use pnpm\"). Don't use when: it only applies to the current request.",
+        ),
+        (
+            "memory_stats",
+            "Memory statistics and per-key inspection (where a fact is stored, when written, how often recalled). Use when: the user asks what is remembered or why a wrong fact keeps coming back. Don't use when: you just need the fact itself (use memory_recall).",
         ),
     ];
 
@@ -3187,19 +4015,25 @@ pub async fn start_channels(config: Config) -> Result<()> {
         None
     };
     let native_tools = provider.supports_native_tools();
-    let mut system_prompt = build_system_prompt_with_mode(
-        &workspace,
-        &model,
-        &tool_descs,
-        &skills,
-        Some(&config.identity),
+    let prompt_recipe = Arc::new(SystemPromptRecipe {
+        workspace: workspace.clone(),
+        model: model.clone(),
+        tools: tool_descs
+            .iter()
+            .map(|(name, desc)| ((*name).to_string(), (*desc).to_string()))
+            .collect(),
+        skills: skills.clone(),
+        identity: config.identity.clone(),
         bootstrap_max_chars,
         native_tools,
-        config.skills.prompt_injection_mode,
-    );
-    if !native_tools {
-        system_prompt.push_str(&build_tool_instructions(tools_registry.as_ref()));
-    }
+        skills_prompt_mode: config.skills.prompt_injection_mode,
+        tool_instructions: if native_tools {
+            String::new()
+        } else {
+            build_tool_instructions(tools_registry.as_ref())
+        },
+    });
+    let system_prompt = prompt_recipe.build(&crate::agent::prompt_templates::PromptVars::default());
 
     if !skills.is_empty() {
         println!(
@@ -3286,6 +4120,21 @@ pub async fn start_channels(config: Config) -> Result<()> {
     );
     let max_in_flight_messages = compute_max_in_flight_messages(channels.len());
 
+    // Observer chats only receive activity; their messages never reach the agent.
+    let observers = config.channels_config.observers.clone();
+    let rx = if observers.is_empty() {
+        rx
+    } else {
+        println!("  👁  Observer channels: {}", observers.len());
+        handles.push(observers::spawn_broadcaster(
+            observers.clone(),
+            Arc::clone(&channels_by_name),
+        ));
+        let (filtered, filter) = observers::filter_messages(rx, observers);
+        handles.push(filter);
+        filtered
+    };
+
     println!("  🚦 In-flight message limit: {max_in_flight_messages}");
 
     let mut provider_cache_seed: HashMap<String, Arc<dyn Provider>> = HashMap::new();
@@ -3297,6 +4146,26 @@ pub async fn start_channels(config: Config) -> Result<()> {
         .telegram
         .as_ref()
         .is_some_and(|tg| tg.interrupt_on_new_message);
+    if config.observability.post_build_reports && !config.observability.record_sessions {
+        tracing::warn!(
+            "observability.post_build_reports needs record_sessions; no build reports will be posted"
+        );
+    }
+
+    let moderation = if config.moderation.enabled {
+        match crate::security::moderation::Moderation::from_config(
+            &config.moderation,
+            config.config_path.parent(),
+        ) {
+            Ok(moderation) => Some(Arc::new(moderation)),
+            Err(e) => {
+                tracing::error!("Moderation disabled: {e:#}");
+                None
+            }
+        }
+    } else {
+        None
+    };
 
     let runtime_ctx = Arc::new(ChannelRuntimeContext {
         channels_by_name,
@@ -3306,6 +4175,7 @@ pub async fn start_channels(config: Config) -> Result<()> {
         tools_registry: Arc::clone(&tools_registry),
         observer,
         system_prompt: Arc::new(system_prompt),
+        prompt_recipe: Some(prompt_recipe),
         model: Arc::new(model.clone()),
         temperature,
         auto_save_memory: config.memory.auto_save,
@@ -3332,6 +4202,16 @@ pub async fn start_channels(config: Config) -> Result<()> {
             None
         },
         non_cli_excluded_tools: Arc::new(config.autonomy.non_cli_excluded_tools.clone()),
+        store: Some(Arc::new(crate::store::SharedStore::new(
+            &config.zerobuild.db_path,
+        ))),
+        profile_base: Some(Arc::new(config.clone())),
+        build_report_prices: config
+            .observability
+            .post_build_reports
+            .then(|| Arc::new(config.cost.prices.clone())),
+        i18n: Arc::new(config.i18n.clone()),
+        moderation,
     });
 
     run_message_dispatch_loop(rx, runtime_ctx, max_in_flight_messages).await;
@@ -3531,6 +4411,7 @@ mod tests {
             tools_registry: Arc::new(vec![]),
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("system".to_string()),
+            prompt_recipe: None,
             model: Arc::new("test-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
@@ -3549,6 +4430,11 @@ mod tests {
             workspace_dir: Arc::new(std::env::temp_dir()),
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             non_cli_excluded_tools: Arc::new(Vec::new()),
+            store: None,
+            profile_base: None,
+            build_report_prices: None,
+            i18n: Arc::new(crate::config::I18nConfig::default()),
+            moderation: None,
         };
 
         assert!(compact_sender_history(&ctx, &sender));
@@ -3580,6 +4466,7 @@ mod tests {
             tools_registry: Arc::new(vec![]),
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("system".to_string()),
+            prompt_recipe: None,
             model: Arc::new("test-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
@@ -3598,9 +4485,23 @@ mod tests {
             workspace_dir: Arc::new(std::env::temp_dir()),
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             non_cli_excluded_tools: Arc::new(Vec::new()),
+            store: None,
+            profile_base: None,
+            build_report_prices: None,
+            i18n: Arc::new(crate::config::I18nConfig::default()),
+            moderation: None,
         };
 
-        append_sender_turn(&ctx, &sender, ChatMessage::user("hello"));
+        let msg = traits::ChannelMessage {
+            id: "msg_1".into(),
+            sender: "u2".into(),
+            reply_target: "u2".into(),
+            content: "hello".into(),
+            channel: "telegram".into(),
+            timestamp: 1,
+            thread_ts: None,
+        };
+        append_sender_turn(&ctx, &msg, &sender, ChatMessage::user("hello"));
 
         let histories = ctx
             .conversation_histories
@@ -3632,6 +4533,7 @@ mod tests {
             tools_registry: Arc::new(vec![]),
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("system".to_string()),
+            prompt_recipe: None,
             model: Arc::new("test-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
@@ -3650,6 +4552,11 @@ mod tests {
             workspace_dir: Arc::new(std::env::temp_dir()),
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             non_cli_excluded_tools: Arc::new(Vec::new()),
+            store: None,
+            profile_base: None,
+            build_report_prices: None,
+            i18n: Arc::new(crate::config::I18nConfig::default()),
+            moderation: None,
         };
 
         assert!(rollback_orphan_user_turn(&ctx, &sender, "pending"));
@@ -4080,6 +4987,7 @@ BTC is currently around $65,000 based on latest tool output."#
                     success: false,
                     output: String::new(),
                     error: Some("unexpected symbol".to_string()),
+                    error_code: None,
                     error_hint: None,
                 });
             }
@@ -4088,6 +4996,7 @@ BTC is currently around $65,000 based on latest tool output."#
                 success: true,
                 output: r#"{"symbol":"BTC","price_usd":65000}"#.to_string(),
                 error: None,
+                error_code: None,
                 error_hint: None,
             })
         }
@@ -4109,6 +5018,7 @@ BTC is currently around $65,000 based on latest tool output."#
             tools_registry: Arc::new(vec![Box::new(MockPriceTool)]),
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("test-system-prompt".to_string()),
+            prompt_recipe: None,
             model: Arc::new("test-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
@@ -4125,6 +5035,11 @@ BTC is currently around $65,000 based on latest tool output."#
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             interrupt_on_new_message: false,
             non_cli_excluded_tools: Arc::new(Vec::new()),
+            store: None,
+            profile_base: None,
+            build_report_prices: None,
+            i18n: Arc::new(crate::config::I18nConfig::default()),
+            moderation: None,
             multimodal: crate::config::MultimodalConfig::default(),
             hooks: None,
         });
@@ -4168,6 +5083,7 @@ BTC is currently around $65,000 based on latest tool output."#
             tools_registry: Arc::new(vec![Box::new(MockPriceTool)]),
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("test-system-prompt".to_string()),
+            prompt_recipe: None,
             model: Arc::new("test-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
@@ -4184,6 +5100,11 @@ BTC is currently around $65,000 based on latest tool output."#
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             interrupt_on_new_message: false,
             non_cli_excluded_tools: Arc::new(Vec::new()),
+            store: None,
+            profile_base: None,
+            build_report_prices: None,
+            i18n: Arc::new(crate::config::I18nConfig::default()),
+            moderation: None,
             multimodal: crate::config::MultimodalConfig::default(),
             hooks: None,
         });
@@ -4241,6 +5162,7 @@ BTC is currently around $65,000 based on latest tool output."#
             tools_registry: Arc::new(vec![Box::new(MockPriceTool)]),
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("test-system-prompt".to_string()),
+            prompt_recipe: None,
             model: Arc::new("test-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
@@ -4259,6 +5181,11 @@ BTC is currently around $65,000 based on latest tool output."#
             multimodal: crate::config::MultimodalConfig::default(),
             hooks: None,
             non_cli_excluded_tools: Arc::new(Vec::new()),
+            store: None,
+            profile_base: None,
+            build_report_prices: None,
+            i18n: Arc::new(crate::config::I18nConfig::default()),
+            moderation: None,
         });
 
         process_channel_message(
@@ -4300,6 +5227,7 @@ BTC is currently around $65,000 based on latest tool output."#
             tools_registry: Arc::new(vec![Box::new(MockPriceTool)]),
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("test-system-prompt".to_string()),
+            prompt_recipe: None,
             model: Arc::new("test-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
@@ -4318,6 +5246,11 @@ BTC is currently around $65,000 based on latest tool output."#
             multimodal: crate::config::MultimodalConfig::default(),
             hooks: None,
             non_cli_excluded_tools: Arc::new(Vec::new()),
+            store: None,
+            profile_base: None,
+            build_report_prices: None,
+            i18n: Arc::new(crate::config::I18nConfig::default()),
+            moderation: None,
         });
 
         process_channel_message(
@@ -4368,6 +5301,7 @@ BTC is currently around $65,000 based on latest tool output."#
             tools_registry: Arc::new(vec![]),
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("test-system-prompt".to_string()),
+            prompt_recipe: None,
             model: Arc::new("default-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
@@ -4386,6 +5320,11 @@ BTC is currently around $65,000 based on latest tool output."#
             multimodal: crate::config::MultimodalConfig::default(),
             hooks: None,
             non_cli_excluded_tools: Arc::new(Vec::new()),
+            store: None,
+            profile_base: None,
+            build_report_prices: None,
+            i18n: Arc::new(crate::config::I18nConfig::default()),
+            moderation: None,
         });
 
         process_channel_message(
@@ -4422,6 +5361,100 @@ BTC is currently around $65,000 based on latest tool output."#
         assert_eq!(fallback_provider_impl.call_count.load(Ordering::SeqCst), 0);
     }
 
+    #[tokio::test]
+    async fn process_channel_message_handles_profile_command() {
+        let channel_impl = Arc::new(TelegramRecordingChannel::default());
+        let channel: Arc<dyn Channel> = channel_impl.clone();
+
+        let mut channels_by_name = HashMap::new();
+        channels_by_name.insert(channel.name().to_string(), channel);
+
+        let default_provider_impl = Arc::new(ModelCaptureProvider::default());
+        let default_provider: Arc<dyn Provider> = default_provider_impl.clone();
+        let mut provider_cache_seed: HashMap<String, Arc<dyn Provider>> = HashMap::new();
+        provider_cache_seed.insert("test-provider".to_string(), Arc::clone(&default_provider));
+        provider_cache_seed.insert("openrouter".to_string(), Arc::clone(&default_provider));
+
+        let mut base = Config::default();
+        base.profiles.insert(
+            "fast-cheap".to_string(),
+            crate::config::ConfigProfile {
+                description: Some("Quick drafts".to_string()),
+                default_provider: Some("openrouter".to_string()),
+                default_model: Some("cheap-model".to_string()),
+                ..crate::config::ConfigProfile::default()
+            },
+        );
+
+        let runtime_ctx = Arc::new(ChannelRuntimeContext {
+            channels_by_name: Arc::new(channels_by_name),
+            provider: Arc::clone(&default_provider),
+            default_provider: Arc::new("test-provider".to_string()),
+            memory: Arc::new(NoopMemory),
+            tools_registry: Arc::new(vec![]),
+            observer: Arc::new(NoopObserver),
+            system_prompt: Arc::new("test-system-prompt".to_string()),
+            prompt_recipe: None,
+            model: Arc::new("default-model".to_string()),
+            temperature: 0.0,
+            auto_save_memory: false,
+            max_tool_iterations: 5,
+            min_relevance_score: 0.0,
+            conversation_histories: Arc::new(Mutex::new(HashMap::new())),
+            provider_cache: Arc::new(Mutex::new(provider_cache_seed)),
+            route_overrides: Arc::new(Mutex::new(HashMap::new())),
+            api_key: None,
+            api_url: None,
+            reliability: Arc::new(crate::config::ReliabilityConfig::default()),
+            provider_runtime_options: providers::ProviderRuntimeOptions::default(),
+            workspace_dir: Arc::new(std::env::temp_dir()),
+            message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
+            interrupt_on_new_message: false,
+            multimodal: crate::config::MultimodalConfig::default(),
+            hooks: None,
+            non_cli_excluded_tools: Arc::new(Vec::new()),
+            store: None,
+            profile_base: Some(Arc::new(base)),
+            build_report_prices: None,
+            i18n: Arc::new(crate::config::I18nConfig::default()),
+            moderation: None,
+        });
+
+        for (id, content) in [("msg-1", "/profile"), ("msg-2", "/profile fast-cheap")] {
+            process_channel_message(
+                runtime_ctx.clone(),
+                traits::ChannelMessage {
+                    id: id.to_string(),
+                    sender: "alice".to_string(),
+                    reply_target: "chat-1".to_string(),
+                    content: content.to_string(),
+                    channel: "telegram".to_string(),
+                    timestamp: 1,
+                    thread_ts: None,
+                },
+                CancellationToken::new(),
+            )
+            .await;
+        }
+
+        let sent = channel_impl.sent_messages.lock().await;
+        assert_eq!(sent.len(), 2);
+        assert!(sent[0].contains("- fast-cheap: Quick drafts"));
+        assert!(sent[1].contains("Profile `fast-cheap` active"));
+
+        let route = runtime_ctx
+            .route_overrides
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get("telegram_alice")
+            .cloned()
+            .expect("route should be stored for sender");
+        assert_eq!(route.provider, "openrouter");
+        assert_eq!(route.model, "cheap-model");
+        assert_eq!(route.profile.as_deref(), Some("fast-cheap"));
+        assert_eq!(default_provider_impl.call_count.load(Ordering::SeqCst), 0);
+    }
+
     #[tokio::test]
     async fn process_channel_message_uses_route_override_provider_and_model() {
         let channel_impl = Arc::new(TelegramRecordingChannel::default());
@@ -4446,6 +5479,7 @@ BTC is currently around $65,000 based on latest tool output."#
             ChannelRouteSelection {
                 provider: "openrouter".to_string(),
                 model: "route-model".to_string(),
+                profile: None,
             },
         );
 
@@ -4457,6 +5491,7 @@ BTC is currently around $65,000 based on latest tool output."#
             tools_registry: Arc::new(vec![]),
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("test-system-prompt".to_string()),
+            prompt_recipe: None,
             model: Arc::new("default-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
@@ -4475,6 +5510,11 @@ BTC is currently around $65,000 based on latest tool output."#
             multimodal: crate::config::MultimodalConfig::default(),
             hooks: None,
             non_cli_excluded_tools: Arc::new(Vec::new()),
+            store: None,
+            profile_base: None,
+            build_report_prices: None,
+            i18n: Arc::new(crate::config::I18nConfig::default()),
+            moderation: None,
         });
 
         process_channel_message(
@@ -4504,6 +5544,211 @@ BTC is currently around $65,000 based on latest tool output."#
         );
     }
 
+    #[test]
+    fn model_override_accepts_provider_prefix_and_checks_catalog() {
+        let tmp = TempDir::new().unwrap();
+        assert_eq!(
+            resolve_model_override(tmp.path(), "openrouter", "openai:gpt-4o"),
+            Ok(("openai".to_string(), "gpt-4o".to_string()))
+        );
+        assert_eq!(
+            resolve_model_override(tmp.path(), "ollama", "llama3:8b"),
+            Ok(("ollama".to_string(), "llama3:8b".to_string()))
+        );
+
+        std::fs::create_dir_all(tmp.path().join("state")).unwrap();
+        std::fs::write(
+            tmp.path().join("state").join(MODEL_CACHE_FILE),
+            r#"{"entries":[{"provider":"openrouter","models":["openai/gpt-4o"]}]}"#,
+        )
+        .unwrap();
+        assert!(resolve_model_override(tmp.path(), "openrouter", "openai/gpt-4o").is_ok());
+        let err = resolve_model_override(tmp.path(), "openai", "openrouter:gpt-4o").unwrap_err();
+        assert!(err.contains("not in the model catalog of `openrouter`"));
+
+        assert_eq!(
+            parse_turn_model_override("telegram", "/model openai:o3  why is this slow?"),
+            Some(("openai:o3".to_string(), "why is this slow?".to_string()))
+        );
+        assert_eq!(
+            parse_turn_model_override("telegram", "/model openai:o3"),
+            None
+        );
+        assert!(matches!(
+            parse_runtime_command("telegram", "/model openai:o3"),
+            Some(ChannelRuntimeCommand::SetModel(_))
+        ));
+        assert!(parse_runtime_command("telegram", "/model openai:o3 hi").is_none());
+    }
+
+    #[test]
+    fn preference_commands_parse_on_every_channel() {
+        assert_eq!(
+            parse_runtime_command("slack", "/prefs"),
+            Some(ChannelRuntimeCommand::ShowPreferences)
+        );
+        assert_eq!(
+            parse_runtime_command(
+                "slack",
+                "/preferences code_style functional, 2-space indent"
+            ),
+            Some(ChannelRuntimeCommand::SetPreference(
+                "code_style".into(),
+                "functional, 2-space indent".into()
+            ))
+        );
+        assert_eq!(
+            parse_runtime_command("telegram", "/prefs@bot clear license"),
+            Some(ChannelRuntimeCommand::ClearPreference("license".into()))
+        );
+        assert_eq!(
+            parse_runtime_command("slack", "/approve a1b2c3"),
+            Some(ChannelRuntimeCommand::Approve("a1b2c3".into()))
+        );
+        assert!(parse_runtime_command("slack", "/model gpt-4o").is_none());
+    }
+
+    #[test]
+    fn language_commands_parse_on_every_channel() {
+        assert_eq!(
+            parse_runtime_command("discord", "/lang"),
+            Some(ChannelRuntimeCommand::ShowLanguage)
+        );
+        assert_eq!(
+            parse_runtime_command("telegram", "/lang@bot pt-BR"),
+            Some(ChannelRuntimeCommand::SetLanguage("pt-BR".into()))
+        );
+        assert_eq!(
+            parse_runtime_command("slack", "/lang auto"),
+            Some(ChannelRuntimeCommand::SetLanguage("auto".into()))
+        );
+    }
+
+    #[test]
+    fn user_commands_parse_on_every_channel() {
+        assert_eq!(
+            parse_runtime_command("slack", "/user"),
+            Some(ChannelRuntimeCommand::User(UserCommand::Show(None)))
+        );
+        assert_eq!(
+            parse_runtime_command("telegram", "/user@bot discord:42 role viewer"),
+            Some(ChannelRuntimeCommand::User(UserCommand::SetRole(
+                "discord:42".into(),
+                "viewer".into()
+            )))
+        );
+        assert_eq!(
+            parse_runtime_command("discord", "/user discord:42 quota builds 10"),
+            Some(ChannelRuntimeCommand::User(UserCommand::SetQuota(
+                "discord:42".into(),
+                "builds".into(),
+                "10".into()
+            )))
+        );
+        assert_eq!(
+            parse_runtime_command("slack", "/user list"),
+            Some(ChannelRuntimeCommand::User(UserCommand::List))
+        );
+    }
+
+    #[tokio::test]
+    async fn process_channel_message_applies_one_turn_model_override() {
+        let channel_impl = Arc::new(TelegramRecordingChannel::default());
+        let channel: Arc<dyn Channel> = channel_impl.clone();
+
+        let mut channels_by_name = HashMap::new();
+        channels_by_name.insert(channel.name().to_string(), channel);
+
+        let default_provider_impl = Arc::new(ModelCaptureProvider::default());
+        let default_provider: Arc<dyn Provider> = default_provider_impl.clone();
+        let escalated_provider_impl = Arc::new(ModelCaptureProvider::default());
+        let escalated_provider: Arc<dyn Provider> = escalated_provider_impl.clone();
+
+        let mut provider_cache_seed: HashMap<String, Arc<dyn Provider>> = HashMap::new();
+        provider_cache_seed.insert("test-provider".to_string(), Arc::clone(&default_provider));
+        provider_cache_seed.insert("openrouter".to_string(), escalated_provider);
+
+        let workspace = TempDir::new().unwrap();
+        let runtime_ctx = Arc::new(ChannelRuntimeContext {
+            channels_by_name: Arc::new(channels_by_name),
+            provider: Arc::clone(&default_provider),
+            default_provider: Arc::new("test-provider".to_string()),
+            memory: Arc::new(NoopMemory),
+            tools_registry: Arc::new(vec![]),
+            observer: Arc::new(NoopObserver),
+            system_prompt: Arc::new("test-system-prompt".to_string()),
+            prompt_recipe: None,
+            model: Arc::new("default-model".to_string()),
+            temperature: 0.0,
+            auto_save_memory: false,
+            max_tool_iterations: 5,
+            min_relevance_score: 0.0,
+            conversation_histories: Arc::new(Mutex::new(HashMap::new())),
+            provider_cache: Arc::new(Mutex::new(provider_cache_seed)),
+            route_overrides: Arc::new(Mutex::new(HashMap::new())),
+            api_key: None,
+            api_url: None,
+            reliability: Arc::new(crate::config::ReliabilityConfig::default()),
+            provider_runtime_options: providers::ProviderRuntimeOptions::default(),
+            workspace_dir: Arc::new(workspace.path().to_path_buf()),
+            message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
+            interrupt_on_new_message: false,
+            multimodal: crate::config::MultimodalConfig::default(),
+            hooks: None,
+            non_cli_excluded_tools: Arc::new(Vec::new()),
+            store: None,
+            profile_base: None,
+            build_report_prices: None,
+            i18n: Arc::new(crate::config::I18nConfig::default()),
+            moderation: None,
+        });
+
+        for (id, content) in [
+            (
+                "msg-turn-1",
+                "/model openrouter:strong-model fix this tricky step",
+            ),
+            ("msg-turn-2", "thanks"),
+        ] {
+            process_channel_message(
+                runtime_ctx.clone(),
+                traits::ChannelMessage {
+                    id: id.to_string(),
+                    sender: "alice".to_string(),
+                    reply_target: "chat-1".to_string(),
+                    content: content.to_string(),
+                    channel: "telegram".to_string(),
+                    timestamp: 1,
+                    thread_ts: None,
+                },
+                CancellationToken::new(),
+            )
+            .await;
+        }
+
+        assert_eq!(
+            escalated_provider_impl
+                .models
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .as_slice(),
+            &["strong-model".to_string()]
+        );
+        assert_eq!(
+            default_provider_impl
+                .models
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .as_slice(),
+            &["default-model".to_string()]
+        );
+        assert!(runtime_ctx
+            .route_overrides
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .is_empty());
+    }
+
     #[tokio::test]
     async fn process_channel_message_prefers_cached_default_provider_instance() {
         let channel_impl = Arc::new(TelegramRecordingChannel::default());
@@ -4528,6 +5773,7 @@ BTC is currently around $65,000 based on latest tool output."#
             tools_registry: Arc::new(vec![]),
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("test-system-prompt".to_string()),
+            prompt_recipe: None,
             model: Arc::new("default-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
@@ -4546,6 +5792,11 @@ BTC is currently around $65,000 based on latest tool output."#
             multimodal: crate::config::MultimodalConfig::default(),
             hooks: None,
             non_cli_excluded_tools: Arc::new(Vec::new()),
+            store: None,
+            profile_base: None,
+            build_report_prices: None,
+            i18n: Arc::new(crate::config::I18nConfig::default()),
+            moderation: None,
         });
 
         process_channel_message(
@@ -4611,6 +5862,7 @@ BTC is currently around $65,000 based on latest tool output."#
             tools_registry: Arc::new(vec![]),
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("test-system-prompt".to_string()),
+            prompt_recipe: None,
             model: Arc::new("startup-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
@@ -4632,6 +5884,11 @@ BTC is currently around $65,000 based on latest tool output."#
             multimodal: crate::config::MultimodalConfig::default(),
             hooks: None,
             non_cli_excluded_tools: Arc::new(Vec::new()),
+            store: None,
+            profile_base: None,
+            build_report_prices: None,
+            i18n: Arc::new(crate::config::I18nConfig::default()),
+            moderation: None,
         });
 
         process_channel_message(
@@ -4685,6 +5942,7 @@ BTC is currently around $65,000 based on latest tool output."#
             tools_registry: Arc::new(vec![Box::new(MockPriceTool)]),
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("test-system-prompt".to_string()),
+            prompt_recipe: None,
             model: Arc::new("test-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
@@ -4703,6 +5961,11 @@ BTC is currently around $65,000 based on latest tool output."#
             multimodal: crate::config::MultimodalConfig::default(),
             hooks: None,
             non_cli_excluded_tools: Arc::new(Vec::new()),
+            store: None,
+            profile_base: None,
+            build_report_prices: None,
+            i18n: Arc::new(crate::config::I18nConfig::default()),
+            moderation: None,
         });
 
         process_channel_message(
@@ -4745,6 +6008,7 @@ BTC is currently around $65,000 based on latest tool output."#
             tools_registry: Arc::new(vec![Box::new(MockPriceTool)]),
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("test-system-prompt".to_string()),
+            prompt_recipe: None,
             model: Arc::new("test-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
@@ -4763,6 +6027,11 @@ BTC is currently around $65,000 based on latest tool output."#
             multimodal: crate::config::MultimodalConfig::default(),
             hooks: None,
             non_cli_excluded_tools: Arc::new(Vec::new()),
+            store: None,
+            profile_base: None,
+            build_report_prices: None,
+            i18n: Arc::new(crate::config::I18nConfig::default()),
+            moderation: None,
         });
 
         process_channel_message(
@@ -4916,6 +6185,7 @@ BTC is currently around $65,000 based on latest tool output."#
             tools_registry: Arc::new(vec![]),
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("test-system-prompt".to_string()),
+            prompt_recipe: None,
             model: Arc::new("test-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
@@ -4934,6 +6204,11 @@ BTC is currently around $65,000 based on latest tool output."#
             multimodal: crate::config::MultimodalConfig::default(),
             hooks: None,
             non_cli_excluded_tools: Arc::new(Vec::new()),
+            store: None,
+            profile_base: None,
+            build_report_prices: None,
+            i18n: Arc::new(crate::config::I18nConfig::default()),
+            moderation: None,
         });
 
         let (tx, rx) = tokio::sync::mpsc::channel::<traits::ChannelMessage>(4);
@@ -4996,6 +6271,7 @@ BTC is currently around $65,000 based on latest tool output."#
             tools_registry: Arc::new(vec![]),
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("test-system-prompt".to_string()),
+            prompt_recipe: None,
             model: Arc::new("test-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
@@ -5014,6 +6290,11 @@ BTC is currently around $65,000 based on latest tool output."#
             multimodal: crate::config::MultimodalConfig::default(),
             hooks: None,
             non_cli_excluded_tools: Arc::new(Vec::new()),
+            store: None,
+            profile_base: None,
+            build_report_prices: None,
+            i18n: Arc::new(crate::config::I18nConfig::default()),
+            moderation: None,
         });
 
         let (tx, rx) = tokio::sync::mpsc::channel::<traits::ChannelMessage>(8);
@@ -5088,6 +6369,7 @@ BTC is currently around $65,000 based on latest tool output."#
             tools_registry: Arc::new(vec![]),
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("test-system-prompt".to_string()),
+            prompt_recipe: None,
             model: Arc::new("test-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
@@ -5106,6 +6388,11 @@ BTC is currently around $65,000 based on latest tool output."#
             multimodal: crate::config::MultimodalConfig::default(),
             hooks: None,
             non_cli_excluded_tools: Arc::new(Vec::new()),
+            store: None,
+            profile_base: None,
+            build_report_prices: None,
+            i18n: Arc::new(crate::config::I18nConfig::default()),
+            moderation: None,
         });
 
         let (tx, rx) = tokio::sync::mpsc::channel::<traits::ChannelMessage>(8);
@@ -5162,6 +6449,7 @@ BTC is currently around $65,000 based on latest tool output."#
             tools_registry: Arc::new(vec![]),
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("test-system-prompt".to_string()),
+            prompt_recipe: None,
             model: Arc::new("test-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
@@ -5180,6 +6468,11 @@ BTC is currently around $65,000 based on latest tool output."#
             multimodal: crate::config::MultimodalConfig::default(),
             hooks: None,
             non_cli_excluded_tools: Arc::new(Vec::new()),
+            store: None,
+            profile_base: None,
+            build_report_prices: None,
+            i18n: Arc::new(crate::config::I18nConfig::default()),
+            moderation: None,
         });
 
         process_channel_message(
@@ -5221,6 +6514,7 @@ BTC is currently around $65,000 based on latest tool output."#
             tools_registry: Arc::new(vec![]),
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("test-system-prompt".to_string()),
+            prompt_recipe: None,
             model: Arc::new("test-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
@@ -5239,6 +6533,11 @@ BTC is currently around $65,000 based on latest tool output."#
             multimodal: crate::config::MultimodalConfig::default(),
             hooks: None,
             non_cli_excluded_tools: Arc::new(Vec::new()),
+            store: None,
+            profile_base: None,
+            build_report_prices: None,
+            i18n: Arc::new(crate::config::I18nConfig::default()),
+            moderation: None,
         });
 
         process_channel_message(
@@ -5495,6 +6794,7 @@ BTC is currently around $65,000 based on latest tool output."#
             None,
             false,
             crate::config::SkillsPromptInjectionMode::Compact,
+            &crate::agent::prompt_templates::PromptVars::default(),
         );
 
         assert!(prompt.contains("<available_skills>"), "missing skills XML");
@@ -5659,6 +6959,44 @@ BTC is currently around $65,000 based on latest tool output."#
         );
     }
 
+    #[test]
+    fn email_threads_are_separate_conversations() {
+        let mut msg = traits::ChannelMessage {
+            id: "b@mail".into(),
+            sender: "alice@example.com".into(),
+            reply_target: "alice@example.com".into(),
+            content: "Subject: Landing page\n\nMake it blue".into(),
+            channel: "email".into(),
+            timestamp: 1,
+            thread_ts: Some("a@mail".into()),
+        };
+        let first = conversation_history_key(&msg);
+        msg.thread_ts = Some("c@mail".into());
+        assert_ne!(conversation_history_key(&msg), first);
+        assert_eq!(memory_user_id(&msg), "email_alice%40example%2Ecom");
+
+        msg.channel = "slack".into();
+        assert_eq!(conversation_history_key(&msg), "slack_alice@example.com");
+    }
+
+    #[test]
+    fn memory_user_id_is_path_safe() {
+        let msg = traits::ChannelMessage {
+            id: "msg_1".into(),
+            sender: "../@alice:matrix.org".into(),
+            reply_target: "C456".into(),
+            content: "hello".into(),
+            channel: "matrix".into(),
+            timestamp: 1,
+            thread_ts: None,
+        };
+
+        assert_eq!(
+            memory_user_id(&msg),
+            "matrix_%2E%2E%2F%40alice%3Amatrix%2Eorg"
+        );
+    }
+
     #[tokio::test]
     async fn autosave_keys_preserve_multiple_conversation_facts() {
         let tmp = TempDir::new().unwrap();
@@ -5737,6 +7075,7 @@ BTC is currently around $65,000 based on latest tool output."#
             tools_registry: Arc::new(vec![]),
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("test-system-prompt".to_string()),
+            prompt_recipe: None,
             model: Arc::new("test-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
@@ -5755,6 +7094,11 @@ BTC is currently around $65,000 based on latest tool output."#
             multimodal: crate::config::MultimodalConfig::default(),
             hooks: None,
             non_cli_excluded_tools: Arc::new(Vec::new()),
+            store: None,
+            profile_base: None,
+            build_report_prices: None,
+            i18n: Arc::new(crate::config::I18nConfig::default()),
+            moderation: None,
         });
 
         process_channel_message(
@@ -5822,6 +7166,7 @@ BTC is currently around $65,000 based on latest tool output."#
             tools_registry: Arc::new(vec![]),
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("test-system-prompt".to_string()),
+            prompt_recipe: None,
             model: Arc::new("test-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
@@ -5840,6 +7185,11 @@ BTC is currently around $65,000 based on latest tool output."#
             multimodal: crate::config::MultimodalConfig::default(),
             hooks: None,
             non_cli_excluded_tools: Arc::new(Vec::new()),
+            store: None,
+            profile_base: None,
+            build_report_prices: None,
+            i18n: Arc::new(crate::config::I18nConfig::default()),
+            moderation: None,
         });
 
         process_channel_message(
@@ -5907,6 +7257,7 @@ BTC is currently around $65,000 based on latest tool output."#
             tools_registry: Arc::new(vec![]),
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("test-system-prompt".to_string()),
+            prompt_recipe: None,
             model: Arc::new("test-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
@@ -5925,6 +7276,11 @@ BTC is currently around $65,000 based on latest tool output."#
             multimodal: crate::config::MultimodalConfig::default(),
             hooks: None,
             non_cli_excluded_tools: Arc::new(Vec::new()),
+            store: None,
+            profile_base: None,
+            build_report_prices: None,
+            i18n: Arc::new(crate::config::I18nConfig::default()),
+            moderation: None,
         });
 
         process_channel_message(
@@ -6456,6 +7812,7 @@ This is an example JSON object for profile settings."#;
             tools_registry: Arc::new(vec![]),
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("You are a helpful assistant.".to_string()),
+            prompt_recipe: None,
             model: Arc::new("test-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
@@ -6474,6 +7831,11 @@ This is an example JSON object for profile settings."#;
             multimodal: crate::config::MultimodalConfig::default(),
             hooks: None,
             non_cli_excluded_tools: Arc::new(Vec::new()),
+            store: None,
+            profile_base: None,
+            build_report_prices: None,
+            i18n: Arc::new(crate::config::I18nConfig::default()),
+            moderation: None,
         });
 
         // Simulate a photo attachment message with [IMAGE:] marker.
@@ -6522,6 +7884,7 @@ This is an example JSON object for profile settings."#;
             tools_registry: Arc::new(vec![]),
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("You are a helpful assistant.".to_string()),
+            prompt_recipe: None,
             model: Arc::new("test-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
@@ -6540,6 +7903,11 @@ This is an example JSON object for profile settings."#;
             multimodal: crate::config::MultimodalConfig::default(),
             hooks: None,
             non_cli_excluded_tools: Arc::new(Vec::new()),
+            store: None,
+            profile_base: None,
+            build_report_prices: None,
+            i18n: Arc::new(crate::config::I18nConfig::default()),
+            moderation: None,
         });
 
         process_channel_message(
@@ -0,0 +1,232 @@
+//! Read-only observer channels (`[[channels_config.observers]]`).
+//!
+//! An observer is one chat of a configured channel, e.g. a public Discord
+//! channel. Build status, snapshot diffs and deploys published as
+//! [`Activity`] are posted there, while its messages are dropped before they
+//! reach the agent, so the community can follow a build without being able
+//! to steer it.
+
+use super::traits::{Channel, ChannelMessage, SendMessage};
+use crate::config::{ObserverChannelConfig, ObserverEvent};
+use crate::tools::progress::{self, Activity, BuildStatus};
+use crate::util::truncate_with_ellipsis;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// Longest post, so it fits in any channel's message limit.
+const MAX_POST_CHARS: usize = 1500;
+
+/// Whether `msg` was sent in an observer chat.
+pub fn is_observer(observers: &[ObserverChannelConfig], msg: &ChannelMessage) -> bool {
+    observers
+        .iter()
+        .any(|o| o.channel == msg.channel && o.target == msg.reply_target)
+}
+
+/// The kind of `event` and its post.
+pub fn describe(event: &Activity) -> (ObserverEvent, String) {
+    let text = match event {
+        Activity::Status(BuildStatus::Stage(stage)) => format!("🔨 {stage}"),
+        Activity::Status(BuildStatus::Preview(url)) => format!("👀 Preview: {url}"),
+        Activity::Status(BuildStatus::Finished { success: true }) => {
+            "✅ Build finished".to_string()
+        }
+        Activity::Status(BuildStatus::Finished { success: false }) => "❌ Build failed".to_string(),
+        Activity::Diff { project, changes } => format!("📝 {project} changed: {changes}"),
+        Activity::Deploy { target, url } => format!("🚀 Deployed to {target}: {url}"),
+    };
+    let kind = match event {
+        Activity::Status(_) => ObserverEvent::Status,
+        Activity::Diff { .. } => ObserverEvent::Diff,
+        Activity::Deploy { .. } => ObserverEvent::Deploy,
+    };
+    (
+        kind,
+        truncate_with_ellipsis(text.trim_end(), MAX_POST_CHARS),
+    )
+}
+
+/// Forward the messages of `rx` except those of observer chats.
+pub fn filter_messages(
+    mut rx: mpsc::Receiver<ChannelMessage>,
+    observers: Vec<ObserverChannelConfig>,
+) -> (mpsc::Receiver<ChannelMessage>, JoinHandle<()>) {
+    let (tx, filtered) = mpsc::channel(100);
+    let handle = tokio::spawn(async move {
+        while let Some(msg) = rx.recv().await {
+            if is_observer(&observers, &msg) {
+                tracing::debug!(
+                    "Ignoring message in observer chat {}:{}",
+                    msg.channel,
+                    msg.reply_target
+                );
+                continue;
+            }
+            if tx.send(msg).await.is_err() {
+                break;
+            }
+        }
+    });
+    (filtered, handle)
+}
+
+/// Post published activity to the observers whose events include it.
+#[allow(clippy::implicit_hasher)]
+pub fn spawn_broadcaster(
+    observers: Vec<ObserverChannelConfig>,
+    channels_by_name: Arc<HashMap<String, Arc<dyn Channel>>>,
+) -> JoinHandle<()> {
+    for observer in &observers {
+        if !channels_by_name.contains_key(&observer.channel) {
+            tracing::warn!(
+                "Observer channel '{}' is not configured; nothing will be posted to {}",
+                observer.channel,
+                observer.target
+            );
+        }
+    }
+    let mut rx = progress::subscribe_activity();
+    tokio::spawn(async move {
+        loop {
+            let event = match rx.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(missed)) => {
+                    tracing::warn!("Observer channels missed {missed} activity events");
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+            let (kind, text) = describe(&event);
+            for observer in observers.iter().filter(|o| o.events.contains(&kind)) {
+                let Some(channel) = channels_by_name.get(&observer.channel) else {
+                    continue;
+                };
+                if let Err(e) = channel
+                    .send(&SendMessage::new(text.clone(), &observer.target))
+                    .await
+                {
+                    tracing::warn!(
+                        "Failed to post to observer {}:{}: {e}",
+                        observer.channel,
+                        observer.target
+                    );
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use tokio::sync::Mutex;
+
+    fn observer(events: Vec<ObserverEvent>) -> ObserverChannelConfig {
+        ObserverChannelConfig {
+            channel: "discord".into(),
+            target: "public".into(),
+            events,
+        }
+    }
+
+    fn message(channel: &str, reply_target: &str) -> ChannelMessage {
+        ChannelMessage {
+            id: "1".into(),
+            sender: "someone".into(),
+            reply_target: reply_target.into(),
+            content: "deploy to prod".into(),
+            channel: channel.into(),
+            timestamp: 0,
+            thread_ts: None,
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordingChannel {
+        sent: Mutex<Vec<(String, String)>>,
+    }
+
+    #[async_trait]
+    impl Channel for RecordingChannel {
+        fn name(&self) -> &str {
+            "discord"
+        }
+
+        async fn send(&self, message: &SendMessage) -> anyhow::Result<()> {
+            self.sent
+                .lock()
+                .await
+                .push((message.recipient.clone(), message.content.clone()));
+            Ok(())
+        }
+
+        async fn listen(&self, _tx: mpsc::Sender<ChannelMessage>) -> anyhow::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn observer_messages_never_reach_the_agent() {
+        let (tx, rx) = mpsc::channel(10);
+        let (mut filtered, _handle) =
+            filter_messages(rx, vec![observer(vec![ObserverEvent::Status])]);
+        tx.send(message("discord", "public")).await.unwrap();
+        tx.send(message("discord", "private")).await.unwrap();
+        tx.send(message("telegram", "public")).await.unwrap();
+        drop(tx);
+
+        let mut passed = Vec::new();
+        while let Some(msg) = filtered.recv().await {
+            passed.push(format!("{}:{}", msg.channel, msg.reply_target));
+        }
+        assert_eq!(passed, ["discord:private", "telegram:public"]);
+    }
+
+    #[tokio::test]
+    async fn posts_subscribed_activity_to_observers() {
+        let channel = Arc::new(RecordingChannel::default());
+        let channels: HashMap<String, Arc<dyn Channel>> =
+            HashMap::from([("discord".to_string(), channel.clone() as Arc<dyn Channel>)]);
+        let handle = spawn_broadcaster(
+            vec![observer(vec![ObserverEvent::Deploy])],
+            Arc::new(channels),
+        );
+
+        progress::report_activity(Activity::Status(BuildStatus::Stage("Testing".into())));
+        progress::report_activity(Activity::Deploy {
+            target: "Vercel".into(),
+            url: "https://observed-shop.vercel.app".into(),
+        });
+        let expected = (
+            "public".to_string(),
+            "🚀 Deployed to Vercel: https://observed-shop.vercel.app".to_string(),
+        );
+        for _ in 0..100 {
+            if channel.sent.lock().await.contains(&expected) {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        handle.abort();
+
+        // Other tests publish on the same process-wide bus, so only check
+        // that this deploy arrived and that no status was posted.
+        let sent = channel.sent.lock().await;
+        assert!(sent.contains(&expected), "{sent:?}");
+        assert!(sent.iter().all(|(_, text)| text.starts_with("🚀")));
+    }
+
+    #[test]
+    fn long_diffs_are_cut() {
+        let (kind, text) = describe(&Activity::Diff {
+            project: "shop".into(),
+            changes: "x".repeat(5000),
+        });
+        assert_eq!(kind, ObserverEvent::Diff);
+        assert!(text.chars().count() <= MAX_POST_CHARS + 3);
+    }
+}
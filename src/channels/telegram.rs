@@ -1,3 +1,4 @@
+use super::telegram_choices::{self, ChoiceFlow};
 use super::traits::{Channel, ChannelMessage, SendMessage};
 use crate::config::{Config, StreamMode};
 use crate::security::pairing::PairingGuard;
@@ -35,6 +36,20 @@ enum IncomingAttachmentKind {
 }
 const TELEGRAM_BIND_COMMAND: &str = "/bind";
 
+/// A setup keyboard flow waiting for answers, keyed by reply target.
+struct PendingChoice {
+    message: ChannelMessage,
+    flow: ChoiceFlow,
+    /// The message that carries the keyboard.
+    message_id: i64,
+}
+
+/// What a setup button press leads to.
+enum ChoiceStep {
+    Ask(i64, String, serde_json::Value),
+    Done(PendingChoice),
+}
+
 /// Split a message into chunks that respect Telegram's 4096 character limit.
 /// Tries to split at word boundaries when possible, and handles continuation.
 /// The effective per-chunk limit is reduced to leave room for continuation markers.
@@ -172,6 +187,12 @@ fn parse_path_only_attachment(message: &str) -> Option<TelegramAttachment> {
     })
 }
 
+/// Owner of the preferences the setup keyboards of `msg` read and save,
+/// keyed as `<channel>:<sender>` like the channel runtime's `/prefs`.
+fn preference_user(msg: &ChannelMessage) -> String {
+    format!("{}:{}", msg.channel, msg.sender)
+}
+
 /// Delegate to the shared `strip_tool_call_tags` in the parent module.
 fn strip_tool_call_tags(message: &str) -> String {
     super::strip_tool_call_tags(message)
@@ -243,7 +264,12 @@ pub struct TelegramChannel {
     api_base: String,
     transcription: Option<crate::config::TranscriptionConfig>,
     voice_transcriptions: Mutex<std::collections::HashMap<String, String>>,
+    /// Ids of voice messages awaiting a spoken summary of their reply.
+    pending_voice_replies: Mutex<std::collections::HashSet<String>>,
     workspace_dir: Option<std::path::PathBuf>,
+    choice_flows: Mutex<std::collections::HashMap<String, PendingChoice>>,
+    /// ZeroBuild store the setup keyboards read and save preferences in.
+    preferences_db: Option<std::path::PathBuf>,
 }
 
 impl TelegramChannel {
@@ -274,7 +300,10 @@ impl TelegramChannel {
             api_base: "https://api.telegram.org".to_string(),
             transcription: None,
             voice_transcriptions: Mutex::new(std::collections::HashMap::new()),
+            pending_voice_replies: Mutex::new(std::collections::HashSet::new()),
             workspace_dir: None,
+            choice_flows: Mutex::new(std::collections::HashMap::new()),
+            preferences_db: None,
         }
     }
 
@@ -284,6 +313,13 @@ impl TelegramChannel {
         self
     }
 
+    /// Read and save the setup keyboards' preferences in the ZeroBuild store
+    /// at `db_path`.
+    pub fn with_preferences_db(mut self, db_path: std::path::PathBuf) -> Self {
+        self.preferences_db = Some(db_path);
+        self
+    }
+
     /// Configure streaming mode for progressive draft updates.
     pub fn with_streaming(
         mut self,
@@ -922,6 +958,7 @@ Allowlist Telegram username (without '@') or numeric user ID.",
 
         // CRITICAL: Use numeric chat_id as sender for ZeroBuild create_job compatibility.
         // The chat_id is always numeric and matches Telegram's user/chat ID for API calls.
+        Self::remember_language(message, &chat_id);
         Some(ChannelMessage {
             id: format!("telegram_{chat_id}_{message_id}"),
             sender: chat_id.clone(),
@@ -1010,14 +1047,18 @@ Allowlist Telegram username (without '@') or numeric user ID.",
             }
         };
 
-        let text =
-            match super::transcription::transcribe_audio(audio_data, &file_name, config).await {
-                Ok(t) => t,
-                Err(e) => {
-                    tracing::warn!("Voice transcription failed: {e}");
-                    return None;
-                }
-            };
+        let transcription = match super::transcription::transcribe_audio_with_language(
+            audio_data, &file_name, config,
+        )
+        .await
+        {
+            Ok(t) => t,
+            Err(e) => {
+                tracing::warn!("Voice transcription failed: {e}");
+                return None;
+            }
+        };
+        let text = transcription.text;
 
         if text.trim().is_empty() {
             tracing::info!("Voice transcription returned empty text, skipping");
@@ -1033,15 +1074,33 @@ Allowlist Telegram username (without '@') or numeric user ID.",
             cache.insert(format!("{chat_id}:{message_id}"), text.clone());
         }
 
+        // The spoken language is named so the agent answers in it
+        let tag = match &transcription.language {
+            Some(language) => format!("[Voice, language: {language}]"),
+            None => "[Voice]".to_string(),
+        };
         let content = if let Some(quote) = self.extract_reply_context(message) {
-            format!("{quote}\n\n[Voice] {text}")
+            format!("{quote}\n\n{tag} {text}")
         } else {
-            format!("[Voice] {text}")
+            format!("{tag} {text}")
         };
 
         // CRITICAL: Use numeric chat_id as sender for ZeroBuild create_job compatibility.
+        Self::remember_language(message, &chat_id);
+        // What the user speaks outranks the client's interface language
+        if let Some(language) = &transcription.language {
+            crate::i18n::remember_detected("telegram", &chat_id, language);
+        }
+        let id = format!("telegram_{chat_id}_{message_id}");
+        if config.voice_replies {
+            let mut pending = self.pending_voice_replies.lock();
+            if pending.len() >= 100 {
+                pending.clear();
+            }
+            pending.insert(id.clone());
+        }
         Some(ChannelMessage {
-            id: format!("telegram_{chat_id}_{message_id}"),
+            id,
             sender: chat_id.clone(),
             reply_target,
             content,
@@ -1054,6 +1113,18 @@ Allowlist Telegram username (without '@') or numeric user ID.",
         })
     }
 
+    /// Remember the language the sender's Telegram client reports so replies
+    /// to `chat_id` can be translated (see [`crate::i18n`]).
+    fn remember_language(message: &serde_json::Value, chat_id: &str) {
+        if let Some(code) = message
+            .get("from")
+            .and_then(|from| from.get("language_code"))
+            .and_then(serde_json::Value::as_str)
+        {
+            crate::i18n::remember_detected("telegram", chat_id, code);
+        }
+    }
+
     /// Extract sender username and display identity from a Telegram message object.
     fn extract_sender_info(message: &serde_json::Value) -> (String, Option<String>, String) {
         let username = message
@@ -1198,6 +1269,7 @@ Allowlist Telegram username (without '@') or numeric user ID.",
 
         // CRITICAL: Use numeric chat_id as sender for ZeroBuild create_job compatibility.
         // The chat_id is always numeric and matches Telegram's user/chat ID for API calls.
+        Self::remember_language(message, &chat_id);
         Some(ChannelMessage {
             id: format!("telegram_{chat_id}_{message_id}"),
             sender: chat_id.clone(),
@@ -1212,6 +1284,198 @@ Allowlist Telegram username (without '@') or numeric user ID.",
         })
     }
 
+    /// Preference keys `msg`'s sender already stored, so the setup keyboards
+    /// skip them.
+    fn stored_preference_keys(&self, msg: &ChannelMessage) -> Vec<String> {
+        let Some(db_path) = self.preferences_db.as_deref() else {
+            return Vec::new();
+        };
+        let user = preference_user(msg);
+        match crate::store::init_db(db_path)
+            .and_then(|conn| crate::store::preferences::list(&conn, &user))
+        {
+            Ok(prefs) => prefs.into_iter().map(|pref| pref.key).collect(),
+            Err(e) => {
+                tracing::debug!("Failed to load preferences for the setup keyboards: {e}");
+                Vec::new()
+            }
+        }
+    }
+
+    /// Ask for template, styling and deploy target with inline keyboards
+    /// when `msg` is a vague build request. Returns `true` when the flow
+    /// started and the message is held back until it is answered.
+    async fn start_choice_flow(&self, msg: &ChannelMessage) -> bool {
+        // A new message replaces any unanswered flow in the chat.
+        self.choice_flows.lock().remove(&msg.reply_target);
+        if !telegram_choices::is_vague_build_request(&msg.content) {
+            return false;
+        }
+        let Some(flow) = ChoiceFlow::start(&msg.content, self.stored_preference_keys(msg)) else {
+            return false;
+        };
+
+        let (chat_id, thread_id) = Self::parse_reply_target(&msg.reply_target);
+        let (text, keyboard) = flow.prompt();
+        let mut body = serde_json::json!({
+            "chat_id": chat_id,
+            "text": text,
+            "reply_markup": keyboard,
+        });
+        if let Some(tid) = thread_id {
+            body["message_thread_id"] = serde_json::Value::String(tid);
+        }
+        let message_id = match self.call_api("sendMessage", &body).await {
+            Ok(result) => result
+                .get("message_id")
+                .and_then(serde_json::Value::as_i64)
+                .unwrap_or_default(),
+            Err(e) => {
+                tracing::warn!("Failed to send the setup keyboard: {e}");
+                return false;
+            }
+        };
+
+        self.choice_flows.lock().insert(
+            msg.reply_target.clone(),
+            PendingChoice {
+                message: msg.clone(),
+                flow,
+                message_id,
+            },
+        );
+        true
+    }
+
+    /// Record a setup keyboard button press. Returns the held-back request,
+    /// with the selections appended, once the last question is answered.
+    async fn handle_callback_query(&self, update: &serde_json::Value) -> Option<ChannelMessage> {
+        let query = update.get("callback_query")?;
+        let query_id = query.get("id").and_then(serde_json::Value::as_str)?;
+        let data = query
+            .get("data")
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or_default();
+
+        let (username, sender_id, _) = Self::extract_sender_info(query);
+        let mut identities = vec![username.as_str()];
+        if let Some(id) = sender_id.as_deref() {
+            identities.push(id);
+        }
+        let message = query.get("message");
+        let chat_id = message
+            .and_then(|m| m.get("chat"))
+            .and_then(|chat| chat.get("id"))
+            .and_then(serde_json::Value::as_i64)
+            .map(|id| id.to_string());
+        let step = match chat_id.as_deref() {
+            Some(chat_id)
+                if telegram_choices::is_flow_callback(data)
+                    && self.is_any_user_allowed(identities.iter().copied()) =>
+            {
+                let reply_target = match message
+                    .and_then(|m| m.get("message_thread_id"))
+                    .and_then(serde_json::Value::as_i64)
+                {
+                    Some(tid) => format!("{chat_id}:{tid}"),
+                    None => chat_id.to_string(),
+                };
+                let mut flows = self.choice_flows.lock();
+                match flows.get_mut(&reply_target) {
+                    Some(pending) => {
+                        if !pending.flow.answer(data) {
+                            None
+                        } else if pending.flow.is_done() {
+                            flows.remove(&reply_target).map(ChoiceStep::Done)
+                        } else {
+                            let (text, keyboard) = pending.flow.prompt();
+                            Some(ChoiceStep::Ask(pending.message_id, text, keyboard))
+                        }
+                    }
+                    None => None,
+                }
+            }
+            _ => None,
+        };
+
+        let mut answer = serde_json::json!({ "callback_query_id": query_id });
+        if step.is_none() {
+            answer["text"] = serde_json::Value::String("This choice has expired.".into());
+        }
+        if let Err(e) = self.call_api("answerCallbackQuery", &answer).await {
+            tracing::debug!("Telegram answerCallbackQuery failed: {e}");
+        }
+
+        let chat_id = chat_id?;
+        let (message_id, text, keyboard, request) = match step? {
+            ChoiceStep::Ask(message_id, text, keyboard) => (message_id, text, Some(keyboard), None),
+            ChoiceStep::Done(pending) => {
+                self.save_choice_preferences(&pending.message, &pending.flow);
+                let request = ChannelMessage {
+                    content: pending.flow.message(),
+                    ..pending.message
+                };
+                (
+                    pending.message_id,
+                    pending.flow.summary(),
+                    None,
+                    Some(request),
+                )
+            }
+        };
+        let mut edit = serde_json::json!({
+            "chat_id": chat_id,
+            "message_id": message_id,
+            "text": text,
+        });
+        if let Some(keyboard) = keyboard {
+            edit["reply_markup"] = keyboard;
+        }
+        if let Err(e) = self.call_api("editMessageText", &edit).await {
+            tracing::debug!("Failed to update the setup keyboard: {e}");
+        }
+        request
+    }
+
+    fn save_choice_preferences(&self, msg: &ChannelMessage, flow: &ChoiceFlow) {
+        let Some(db_path) = self.preferences_db.as_deref() else {
+            return;
+        };
+        let user = preference_user(msg);
+        let saved = crate::store::init_db(db_path).and_then(|conn| {
+            flow.preferences().into_iter().try_for_each(|(key, value)| {
+                crate::store::preferences::save(&conn, &user, key, value).map(|_| ())
+            })
+        });
+        if let Err(e) = saved {
+            tracing::warn!("Failed to save the setup keyboard preferences: {e}");
+        }
+    }
+
+    /// POST `body` to the Bot API `method` and return its `result`.
+    async fn call_api(
+        &self,
+        method: &str,
+        body: &serde_json::Value,
+    ) -> anyhow::Result<serde_json::Value> {
+        let resp = self
+            .http_client()
+            .post(self.api_url(method))
+            .json(body)
+            .send()
+            .await?;
+        let status = resp.status();
+        let data: serde_json::Value = resp.json().await.unwrap_or_default();
+        if !status.is_success() {
+            let description = data
+                .get("description")
+                .and_then(serde_json::Value::as_str)
+                .unwrap_or("unknown error");
+            anyhow::bail!("Telegram {method} failed ({status}): {description}");
+        }
+        Ok(data.get("result").cloned().unwrap_or_default())
+    }
+
     /// Download a Telegram photo by file_id, resize to fit within 1024px, and return as base64 data URI.
     async fn resolve_photo_data_uri(&self, file_id: &str) -> anyhow::Result<String> {
         use base64::Engine as _;
@@ -1885,6 +2149,19 @@ Allowlist Telegram username (without '@') or numeric user ID.",
             .unwrap_or("voice.ogg");
 
         let file_bytes = tokio::fs::read(file_path).await?;
+        self.send_voice_bytes(chat_id, thread_id, file_bytes, file_name, caption)
+            .await
+    }
+
+    /// Send in-memory audio as a voice message to a Telegram chat
+    async fn send_voice_bytes(
+        &self,
+        chat_id: &str,
+        thread_id: Option<&str>,
+        file_bytes: Vec<u8>,
+        file_name: &str,
+        caption: Option<&str>,
+    ) -> anyhow::Result<()> {
         let part = Part::bytes(file_bytes).file_name(file_name.to_string());
 
         let mut form = Form::new()
@@ -2330,7 +2607,7 @@ impl Channel for TelegramChannel {
             let body = serde_json::json!({
                 "offset": offset,
                 "timeout": 30,
-                "allowed_updates": ["message"]
+                "allowed_updates": ["message", "callback_query"]
             });
 
             let resp = match self.http_client().post(&url).json(&body).send().await {
@@ -2388,7 +2665,19 @@ Ensure only one `zerobuild` process is using this bot token."
                         offset = uid + 1;
                     }
 
+                    if update.get("callback_query").is_some() {
+                        if let Some(msg) = self.handle_callback_query(update).await {
+                            if tx.send(msg).await.is_err() {
+                                return Ok(());
+                            }
+                        }
+                        continue;
+                    }
+
                     let msg = if let Some(m) = self.parse_update_message(update) {
+                        if self.start_choice_flow(&m).await {
+                            continue;
+                        }
                         m
                     } else if let Some(m) = self.try_parse_voice_message(update).await {
                         m
@@ -2472,6 +2761,25 @@ Ensure only one `zerobuild` process is using this bot token."
         }
         Ok(())
     }
+
+    async fn follow_up_reply(&self, message: &ChannelMessage, reply: &str) -> anyhow::Result<()> {
+        if !self.pending_voice_replies.lock().remove(&message.id) {
+            return Ok(());
+        }
+        let Some(config) = self.transcription.as_ref() else {
+            return Ok(());
+        };
+        let Some(summary) =
+            super::transcription::spoken_summary(reply, config.voice_summary_max_chars)
+        else {
+            return Ok(());
+        };
+
+        let audio = super::transcription::synthesize_speech(&summary, config).await?;
+        let (chat_id, thread_id) = Self::parse_reply_target(&message.reply_target);
+        self.send_voice_bytes(&chat_id, thread_id.as_deref(), audio, "summary.ogg", None)
+            .await
+    }
 }
 
 #[cfg(test)]
@@ -2804,6 +3112,98 @@ mod tests {
         assert_eq!(msg.id, "telegram_-100200300_33");
     }
 
+    #[tokio::test]
+    async fn callback_queries_answer_the_choice_flow() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let db_path = tmp.path().join("zerobuild.db");
+        let ch = TelegramChannel::new("token".into(), vec!["alice".into()], false)
+            .with_api_base("http://127.0.0.1:9".into())
+            .with_preferences_db(db_path.clone());
+        let request = ChannelMessage {
+            id: "telegram_42_7".into(),
+            sender: "42".into(),
+            reply_target: "42".into(),
+            content: "build me a website".into(),
+            channel: "telegram".into(),
+            timestamp: 0,
+            thread_ts: None,
+        };
+        ch.choice_flows.lock().insert(
+            "42".into(),
+            PendingChoice {
+                flow: ChoiceFlow::start(&request.content, vec!["css_framework".into()]).unwrap(),
+                message: request,
+                message_id: 8,
+            },
+        );
+        let press = |from: &str, data: &str| {
+            serde_json::json!({
+                "update_id": 2,
+                "callback_query": {
+                    "id": "q1",
+                    "from": {"id": 555, "username": from},
+                    "message": {"message_id": 8, "chat": {"id": 42}},
+                    "data": data
+                }
+            })
+        };
+
+        assert!(ch
+            .handle_callback_query(&press("mallory", "zbc:0:1"))
+            .await
+            .is_none());
+        assert!(ch
+            .handle_callback_query(&press("alice", "zbc:0:1"))
+            .await
+            .is_none());
+        let msg = ch
+            .handle_callback_query(&press("alice", "zbc:2:0"))
+            .await
+            .expect("the last answer releases the request");
+
+        assert_eq!(msg.id, "telegram_42_7");
+        assert!(msg.content.starts_with("build me a website\n\n"));
+        assert!(msg
+            .content
+            .ends_with("- Template: vite-react\n- Deploy target: vercel"));
+        assert!(ch.choice_flows.lock().is_empty());
+        let conn = crate::store::init_db(&db_path).unwrap();
+        assert_eq!(
+            crate::store::preferences::get(&conn, "telegram:42", "deploy_target")
+                .unwrap()
+                .as_deref(),
+            Some("vercel")
+        );
+    }
+
+    #[test]
+    fn parse_update_message_remembers_client_language() {
+        let ch = TelegramChannel::new("token".into(), vec!["*".into()], false);
+        let update = serde_json::json!({
+            "update_id": 1,
+            "message": {
+                "message_id": 34,
+                "text": "olá",
+                "from": {
+                    "id": 556,
+                    "username": "bruna",
+                    "language_code": "pt-br"
+                },
+                "chat": {
+                    "id": 556
+                }
+            }
+        });
+
+        ch.parse_update_message(&update)
+            .expect("message should parse");
+
+        assert_eq!(
+            crate::i18n::detected("telegram", "556"),
+            Some(crate::i18n::Locale::Pt)
+        );
+    }
+
     #[test]
     fn parse_update_message_allows_numeric_id_without_username() {
         let ch = TelegramChannel::new("token".into(), vec!["555".into()], false);
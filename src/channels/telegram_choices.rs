@@ -0,0 +1,367 @@
+//! Inline keyboard flow for vague build requests on Telegram.
+//!
+//! A short request such as "build me a website" names no template, styling
+//! or hosting. Instead of letting the agent ask about each in turn, the
+//! channel asks with inline keyboards: one question per message edit, each
+//! with a "Decide for me" button. Questions whose preference is already
+//! stored are skipped. When the last one is answered the request is passed
+//! to the agent with the selections appended, and the styling and deploy
+//! choices are saved as preferences.
+
+use crate::sandbox::PROJECT_TEMPLATES;
+use std::fmt::Write;
+
+/// Prefix of the `callback_data` of flow buttons.
+const CALLBACK_PREFIX: &str = "zbc";
+
+/// Answer of a "Decide for me" button.
+pub const AUTO: &str = "auto";
+
+/// Longest request, in words, that can count as vague.
+const MAX_VAGUE_WORDS: usize = 10;
+
+/// Words that make a request a build request.
+const BUILD_VERBS: &[&str] = &["build", "make", "create", "generate", "develop", "start"];
+
+/// Words that name a generic kind of project.
+const GENERIC_PROJECTS: &[&str] = &[
+    "website",
+    "site",
+    "webpage",
+    "page",
+    "webapp",
+    "app",
+    "application",
+    "project",
+];
+
+/// Words that already pin down a stack or a host.
+const STACK_WORDS: &[&str] = &[
+    "next",
+    "nextjs",
+    "next.js",
+    "react",
+    "vite",
+    "vue",
+    "svelte",
+    "angular",
+    "astro",
+    "html",
+    "node",
+    "express",
+    "api",
+    "tailwind",
+    "bootstrap",
+    "css",
+    "vercel",
+    "netlify",
+    "pages",
+    "django",
+    "flask",
+    "rails",
+    "template",
+];
+
+/// A question of the flow: the preference it fills (or `template`), the
+/// label shown with the answer, the question and its `(value, button)`
+/// options.
+pub struct Question {
+    pub key: &'static str,
+    pub label: &'static str,
+    pub prompt: &'static str,
+    pub options: Vec<(&'static str, &'static str)>,
+}
+
+/// The flow's questions, in order.
+pub fn questions() -> Vec<Question> {
+    vec![
+        Question {
+            key: "template",
+            label: "Template",
+            prompt: "Which template should the project start from?",
+            options: PROJECT_TEMPLATES.to_vec(),
+        },
+        Question {
+            key: "css_framework",
+            label: "Styling",
+            prompt: "How should it be styled?",
+            options: vec![
+                ("tailwind", "Tailwind CSS"),
+                ("css-modules", "CSS Modules"),
+                ("bootstrap", "Bootstrap"),
+                ("plain-css", "Plain CSS"),
+            ],
+        },
+        Question {
+            key: "deploy_target",
+            label: "Deploy target",
+            prompt: "Where should it be deployed?",
+            options: vec![
+                ("vercel", "Vercel"),
+                ("netlify", "Netlify"),
+                ("github-pages", "GitHub Pages"),
+                ("none", "Don't deploy"),
+            ],
+        },
+    ]
+}
+
+/// Whether `text` asks for a project without saying what to build it with.
+pub fn is_vague_build_request(text: &str) -> bool {
+    let words: Vec<String> = text
+        .split_whitespace()
+        .map(|word| {
+            word.trim_matches(|c: char| !c.is_alphanumeric() && c != '.')
+                .trim_end_matches('.')
+                .to_lowercase()
+        })
+        .filter(|word| !word.is_empty())
+        .collect();
+    if words.is_empty() || words.len() > MAX_VAGUE_WORDS || text.starts_with('/') {
+        return false;
+    }
+    let has = |list: &[&str]| words.iter().any(|word| list.contains(&word.as_str()));
+    has(BUILD_VERBS) && has(GENERIC_PROJECTS) && !has(STACK_WORDS)
+}
+
+/// A flow in progress in one chat.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChoiceFlow {
+    /// The request the flow was started for.
+    pub request: String,
+    /// Index of the question being asked.
+    pub step: usize,
+    /// `(key, value)` answers so far; `value` is [`AUTO`] for "Decide for me".
+    pub answers: Vec<(&'static str, String)>,
+    /// Preference keys that are already stored and are not asked.
+    pub known: Vec<String>,
+}
+
+impl ChoiceFlow {
+    /// Start a flow for `request`, skipping the questions for `known`
+    /// preference keys. `None` when nothing is left to ask.
+    pub fn start(request: &str, known: Vec<String>) -> Option<Self> {
+        let mut flow = Self {
+            request: request.to_string(),
+            step: 0,
+            answers: Vec::new(),
+            known,
+        };
+        flow.skip_known();
+        (flow.step < questions().len()).then_some(flow)
+    }
+
+    fn skip_known(&mut self) {
+        let questions = questions();
+        while questions
+            .get(self.step)
+            .is_some_and(|question| self.known.iter().any(|key| key == question.key))
+        {
+            self.step += 1;
+        }
+    }
+
+    /// The question being asked, with its text and keyboard.
+    pub fn prompt(&self) -> (String, serde_json::Value) {
+        let questions = questions();
+        let question = &questions[self.step];
+        let mut text = String::new();
+        for (key, value) in &self.answers {
+            let _ = writeln!(text, "✓ {}: {}", label(key), display_value(key, value));
+        }
+        if !text.is_empty() {
+            text.push('\n');
+        }
+        text.push_str(question.prompt);
+        (text, keyboard(self.step, question))
+    }
+
+    /// Record the answer in a button's `callback_data`. Returns `false`
+    /// when the data is not for the current question.
+    pub fn answer(&mut self, data: &str) -> bool {
+        let Some((step, choice)) = parse_callback(data) else {
+            return false;
+        };
+        if step != self.step {
+            return false;
+        }
+        let questions = questions();
+        let question = &questions[step];
+        let value = if choice == AUTO {
+            AUTO
+        } else {
+            match choice
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| question.options.get(index))
+            {
+                Some((value, _)) => value,
+                None => return false,
+            }
+        };
+        self.answers.push((question.key, value.to_string()));
+        self.step += 1;
+        self.skip_known();
+        true
+    }
+
+    /// Whether every question has been answered.
+    pub fn is_done(&self) -> bool {
+        self.step >= questions().len()
+    }
+
+    /// The final text of the flow's message.
+    pub fn summary(&self) -> String {
+        self.answers
+            .iter()
+            .map(|(key, value)| format!("✓ {}: {}", label(key), display_value(key, value)))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Answers to store as preferences: the ones that are preference keys,
+    /// without "Decide for me" answers.
+    pub fn preferences(&self) -> Vec<(&'static str, &str)> {
+        self.answers
+            .iter()
+            .filter(|(key, value)| *key != "template" && value != AUTO)
+            .map(|(key, value)| (*key, value.as_str()))
+            .collect()
+    }
+
+    /// The request as passed to the agent, with the selections appended.
+    pub fn message(&self) -> String {
+        let mut out = format!(
+            "{}\n\n[Selected with the setup buttons — use these, don't ask about them again]",
+            self.request
+        );
+        for (key, value) in &self.answers {
+            if value == AUTO {
+                let _ = write!(out, "\n- {}: your choice", label(key));
+            } else {
+                let _ = write!(out, "\n- {}: {}", label(key), value);
+            }
+        }
+        out
+    }
+}
+
+fn label(key: &str) -> &'static str {
+    questions()
+        .iter()
+        .find(|question| question.key == key)
+        .map_or("Choice", |question| question.label)
+}
+
+fn display_value(key: &str, value: &str) -> String {
+    if value == AUTO {
+        return "decide for me".to_string();
+    }
+    questions()
+        .iter()
+        .find(|question| question.key == key)
+        .and_then(|question| question.options.iter().find(|(v, _)| *v == value))
+        .map_or_else(|| value.to_string(), |(_, button)| (*button).to_string())
+}
+
+/// `callback_data` of the button for option `choice` (an index or [`AUTO`])
+/// of question `step`.
+fn callback_data(step: usize, choice: &str) -> String {
+    format!("{CALLBACK_PREFIX}:{step}:{choice}")
+}
+
+/// `(step, choice)` of a flow button's `callback_data`.
+pub fn parse_callback(data: &str) -> Option<(usize, &str)> {
+    let rest = data.strip_prefix(CALLBACK_PREFIX)?.strip_prefix(':')?;
+    let (step, choice) = rest.split_once(':')?;
+    Some((step.parse().ok()?, choice))
+}
+
+/// Whether `data` belongs to a flow button.
+pub fn is_flow_callback(data: &str) -> bool {
+    parse_callback(data).is_some()
+}
+
+/// Inline keyboard with two options per row and "Decide for me" last.
+fn keyboard(step: usize, question: &Question) -> serde_json::Value {
+    let mut rows: Vec<Vec<serde_json::Value>> = question
+        .options
+        .chunks(2)
+        .enumerate()
+        .map(|(row, options)| {
+            options
+                .iter()
+                .enumerate()
+                .map(|(column, (_, button))| {
+                    serde_json::json!({
+                        "text": button,
+                        "callback_data": callback_data(step, &(row * 2 + column).to_string()),
+                    })
+                })
+                .collect()
+        })
+        .collect();
+    rows.push(vec![serde_json::json!({
+        "text": "Decide for me",
+        "callback_data": callback_data(step, AUTO),
+    })]);
+    serde_json::json!({ "inline_keyboard": rows })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_short_stackless_build_requests_are_vague() {
+        assert!(is_vague_build_request("build me a website"));
+        assert!(is_vague_build_request("Can you make an app?"));
+        assert!(!is_vague_build_request("build me a website with Next.js"));
+        assert!(!is_vague_build_request("deploy it to vercel"));
+        assert!(!is_vague_build_request("what is a website?"));
+        assert!(!is_vague_build_request("/build a website"));
+        assert!(!is_vague_build_request(
+            "build a website for my bakery that lists the cakes, opening hours, prices and a contact form"
+        ));
+    }
+
+    #[test]
+    fn flow_asks_unknown_questions_and_records_answers() {
+        let mut flow =
+            ChoiceFlow::start("build me a website", vec!["css_framework".into()]).unwrap();
+        let (text, keyboard) = flow.prompt();
+        assert_eq!(text, "Which template should the project start from?");
+        assert_eq!(
+            keyboard["inline_keyboard"][0][1]["callback_data"],
+            "zbc:0:1"
+        );
+        assert_eq!(
+            keyboard["inline_keyboard"][3][0]["callback_data"],
+            "zbc:0:auto"
+        );
+
+        assert!(!flow.answer("zbc:1:0"));
+        assert!(!flow.answer("zbc:0:99"));
+        assert!(flow.answer("zbc:0:0"));
+        // The stored CSS framework is not asked again.
+        assert_eq!(flow.step, 2);
+        assert!(flow
+            .prompt()
+            .0
+            .starts_with("✓ Template: Next.js + Tailwind CSS web app\n\n"));
+        assert!(flow.answer("zbc:2:auto"));
+        assert!(flow.is_done());
+
+        assert_eq!(
+            flow.summary(),
+            "✓ Template: Next.js + Tailwind CSS web app\n✓ Deploy target: decide for me"
+        );
+        assert!(flow.preferences().is_empty());
+        assert!(flow
+            .message()
+            .ends_with("- Template: nextjs\n- Deploy target: your choice"));
+
+        let known = questions().iter().map(|q| q.key.to_string()).collect();
+        assert!(ChoiceFlow::start("build an app", known).is_none());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::tools::progress::BuildStatus;
 use async_trait::async_trait;
 
 /// A message received from or sent to a channel
@@ -142,6 +143,27 @@ pub trait Channel: Send + Sync {
     ) -> anyhow::Result<()> {
         Ok(())
     }
+
+    /// Whether this channel shows the status of builds started from its messages.
+    fn supports_build_status(&self) -> bool {
+        false
+    }
+
+    /// Show a status change of the build started by `message` (e.g. by editing
+    /// a pinned status message in a thread opened for the build).
+    async fn update_build_status(
+        &self,
+        _message: &ChannelMessage,
+        _status: &BuildStatus,
+    ) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Follow up the reply delivered for `message` (e.g. with a spoken
+    /// summary when the message was a voice note).
+    async fn follow_up_reply(&self, _message: &ChannelMessage, _reply: &str) -> anyhow::Result<()> {
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -31,6 +31,47 @@ fn normalize_audio_filename(file_name: &str) -> String {
     }
 }
 
+/// Text of a transcription and the language it was spoken in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transcription {
+    pub text: String,
+    /// ISO-639-1 code: the configured hint, or the language the API detected.
+    pub language: Option<String>,
+}
+
+/// ISO-639-1 code of a language as Whisper-compatible APIs report it
+/// (a code, or an English name such as "portuguese").
+fn language_code(language: &str) -> Option<String> {
+    const NAMES: &[(&str, &str)] = &[
+        ("arabic", "ar"),
+        ("chinese", "zh"),
+        ("dutch", "nl"),
+        ("english", "en"),
+        ("french", "fr"),
+        ("german", "de"),
+        ("hindi", "hi"),
+        ("indonesian", "id"),
+        ("italian", "it"),
+        ("japanese", "ja"),
+        ("korean", "ko"),
+        ("polish", "pl"),
+        ("portuguese", "pt"),
+        ("russian", "ru"),
+        ("spanish", "es"),
+        ("turkish", "tr"),
+        ("ukrainian", "uk"),
+        ("vietnamese", "vi"),
+    ];
+    let language = language.trim().to_ascii_lowercase();
+    if language.len() == 2 && language.chars().all(|c| c.is_ascii_lowercase()) {
+        return Some(language);
+    }
+    NAMES
+        .iter()
+        .find(|(name, _)| *name == language)
+        .map(|(_, code)| (*code).to_string())
+}
+
 /// Transcribe audio bytes via a Whisper-compatible transcription API.
 ///
 /// Returns the transcribed text on success.  Requires `GROQ_API_KEY` in the
@@ -41,6 +82,20 @@ pub async fn transcribe_audio(
     file_name: &str,
     config: &TranscriptionConfig,
 ) -> Result<String> {
+    Ok(
+        transcribe_audio_with_language(audio_data, file_name, config)
+            .await?
+            .text,
+    )
+}
+
+/// Like [`transcribe_audio`], also returning the spoken language. Without
+/// a configured language hint the API detects it.
+pub async fn transcribe_audio_with_language(
+    audio_data: Vec<u8>,
+    file_name: &str,
+    config: &TranscriptionConfig,
+) -> Result<Transcription> {
     if audio_data.len() > MAX_AUDIO_BYTES {
         bail!(
             "Audio file too large ({} bytes, max {MAX_AUDIO_BYTES})",
@@ -69,10 +124,18 @@ pub async fn transcribe_audio(
         .file_name(normalized_name)
         .mime_str(mime)?;
 
+    // verbose_json carries the detected language
     let mut form = Form::new()
         .part("file", file_part)
         .text("model", config.model.clone())
-        .text("response_format", "json");
+        .text(
+            "response_format",
+            if config.language.is_some() {
+                "json"
+            } else {
+                "verbose_json"
+            },
+        );
 
     if let Some(ref lang) = config.language {
         form = form.text("language", lang.clone());
@@ -100,9 +163,97 @@ pub async fn transcribe_audio(
     let text = body["text"]
         .as_str()
         .context("Transcription response missing 'text' field")?
+        .trim()
         .to_string();
+    let language = config
+        .language
+        .as_deref()
+        .or_else(|| body["language"].as_str())
+        .and_then(language_code);
 
-    Ok(text)
+    Ok(Transcription { text, language })
+}
+
+/// A reply shortened for speaking: code blocks, links and markdown removed,
+/// whole sentences up to `max_chars`. `None` when nothing speakable is left.
+pub fn spoken_summary(reply: &str, max_chars: usize) -> Option<String> {
+    let mut prose = String::new();
+    let mut in_code = false;
+    for line in reply.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+        for word in line.split_whitespace() {
+            if word.contains("://") || word.starts_with('`') {
+                // Keep the end of the sentence a link or code span closes
+                if word.ends_with(['.', '!', '?']) && !prose.ends_with(['.', '!', '?']) {
+                    prose.push('.');
+                }
+                continue;
+            }
+            let word = word.trim_matches(|c| matches!(c, '*' | '_' | '#' | '>' | '|' | '`'));
+            if word.is_empty() || word == "-" {
+                continue;
+            }
+            if !prose.is_empty() {
+                prose.push(' ');
+            }
+            prose.push_str(word);
+        }
+    }
+
+    let mut summary = String::new();
+    for sentence in prose.split_inclusive(['.', '!', '?']) {
+        if summary.chars().count() + sentence.chars().count() > max_chars {
+            break;
+        }
+        summary.push_str(sentence);
+    }
+    if summary.trim().is_empty() {
+        // One long sentence: cut it at a word boundary
+        for word in prose.split(' ') {
+            if summary.chars().count() + word.chars().count() + 1 > max_chars {
+                break;
+            }
+            if !summary.is_empty() {
+                summary.push(' ');
+            }
+            summary.push_str(word);
+        }
+    }
+    let summary = summary.trim();
+    (!summary.is_empty()).then(|| summary.to_string())
+}
+
+/// Speak `text` via an OpenAI-compatible speech API. Returns Ogg/Opus audio,
+/// which Telegram plays as a voice message. Requires `OPENAI_API_KEY`.
+pub async fn synthesize_speech(text: &str, config: &TranscriptionConfig) -> Result<Vec<u8>> {
+    let api_key = std::env::var("OPENAI_API_KEY")
+        .context("OPENAI_API_KEY environment variable is not set — required for voice replies")?;
+
+    let resp = crate::config::build_runtime_proxy_client("transcription.tts")
+        .post(&config.tts_api_url)
+        .bearer_auth(&api_key)
+        .json(&serde_json::json!({
+            "model": config.tts_model,
+            "voice": config.tts_voice,
+            "input": text,
+            "response_format": "opus",
+        }))
+        .send()
+        .await
+        .context("Failed to send speech request")?;
+
+    let status = resp.status();
+    if !status.is_success() {
+        let body = resp.text().await.unwrap_or_default();
+        bail!("Speech API error ({status}): {body}");
+    }
+    Ok(resp.bytes().await?.to_vec())
 }
 
 #[cfg(test)]
@@ -215,4 +366,31 @@ mod tests {
             "error should mention the rejected extension, got: {msg}"
         );
     }
+
+    #[test]
+    fn language_code_accepts_codes_and_names() {
+        assert_eq!(language_code("de").as_deref(), Some("de"));
+        assert_eq!(language_code("Spanish").as_deref(), Some("es"));
+        assert_eq!(language_code("klingon"), None);
+    }
+
+    #[test]
+    fn spoken_summary_skips_code_and_links() {
+        let reply = "**Done!** Your site is ready.\nhttps://example.com\n\
+                     ```\nnpm run dev\n```\n\
+                     - Added a contact form. Next I can add a blog.";
+        assert_eq!(
+            spoken_summary(reply, 400).as_deref(),
+            Some("Done! Your site is ready. Added a contact form. Next I can add a blog.")
+        );
+        assert_eq!(
+            spoken_summary(reply, 30).as_deref(),
+            Some("Done! Your site is ready.")
+        );
+        assert_eq!(
+            spoken_summary("one two three four", 9).as_deref(),
+            Some("one two")
+        );
+        assert_eq!(spoken_summary("```\ncode\n```", 400), None);
+    }
 }
@@ -1,7 +1,24 @@
 use super::traits::{Channel, ChannelMessage, SendMessage};
+use anyhow::Context;
 use async_trait::async_trait;
+use std::path::PathBuf;
 use uuid::Uuid;
 
+const GRAPH_API_BASE: &str = "https://graph.facebook.com/v18.0";
+
+/// Message types whose media is downloaded and passed to the agent.
+const MEDIA_KINDS: &[&str] = &["image", "document", "audio", "video"];
+
+/// Largest attachment downloaded from the Cloud API.
+const WHATSAPP_MAX_MEDIA_BYTES: u64 = 20 * 1024 * 1024;
+
+/// Cloud API error code for a free-form message sent outside the 24-hour
+/// customer service window.
+const OUTSIDE_SERVICE_WINDOW: i64 = 131_047;
+
+/// Longest text a template body variable takes.
+const MAX_TEMPLATE_PARAM_CHARS: usize = 1024;
+
 /// `WhatsApp` channel — uses `WhatsApp` Business Cloud API
 ///
 /// This channel operates in webhook mode (push-based) rather than polling.
@@ -22,11 +39,133 @@ fn ensure_https(url: &str) -> anyhow::Result<()> {
 ///
 /// This Cloud API channel is automatically selected when `phone_number_id` is set in the config.
 /// Use `WhatsAppWebChannel` (with `session_path`) for native Web mode.
+///
+/// # Replies and attachments
+///
+/// Replies are sent as session (free-form) messages. When Meta rejects one
+/// because the 24-hour customer service window has closed, the reply is
+/// sent again through the configured `reply_template`. Incoming images,
+/// documents, audio and video are downloaded to
+/// `{workspace_dir}/whatsapp_files/`.
 pub struct WhatsAppChannel {
     access_token: String,
     endpoint_id: String,
     verify_token: String,
     allowed_numbers: Vec<String>,
+    /// `(name, language)` of the template for replies outside the window.
+    reply_template: Option<(String, String)>,
+    workspace_dir: Option<PathBuf>,
+}
+
+/// Media attached to an incoming message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct IncomingMedia {
+    id: String,
+    kind: &'static str,
+    mime_type: Option<String>,
+    file_name: Option<String>,
+    caption: Option<String>,
+}
+
+impl IncomingMedia {
+    fn parse(msg: &serde_json::Value) -> Option<Self> {
+        let kind = msg.get("type").and_then(|t| t.as_str())?;
+        let kind = *MEDIA_KINDS.iter().find(|k| **k == kind)?;
+        let media = msg.get(kind)?;
+        let field = |name: &str| {
+            media
+                .get(name)
+                .and_then(|v| v.as_str())
+                .filter(|v| !v.is_empty())
+                .map(String::from)
+        };
+        Some(Self {
+            id: field("id")?,
+            kind,
+            mime_type: field("mime_type"),
+            file_name: field("filename"),
+            caption: field("caption"),
+        })
+    }
+
+    /// Name of the downloaded file: the sender's file name without any
+    /// directories, or one derived from the media id and MIME type.
+    fn local_file_name(&self) -> String {
+        let sent_name = self
+            .file_name
+            .as_deref()
+            .and_then(|name| std::path::Path::new(name).file_name())
+            .and_then(|name| name.to_str());
+        if let Some(name) = sent_name {
+            return format!("{}_{name}", self.id);
+        }
+        let extension = self
+            .mime_type
+            .as_deref()
+            .and_then(|mime| mime.split(';').next())
+            .and_then(|mime| mime.split_once('/'))
+            .map_or("bin", |(_, subtype)| subtype.trim());
+        format!("{}_{}.{extension}", self.kind, self.id)
+    }
+
+    /// Message content for the downloaded file. Images use the `[IMAGE:]`
+    /// marker so the multimodal pipeline handles them.
+    fn content(&self, path: &std::path::Path) -> String {
+        let mut content = match self.kind {
+            "image" => format!("[IMAGE:{}]", path.display()),
+            kind => {
+                let label = match kind {
+                    "audio" => "Audio",
+                    "video" => "Video",
+                    _ => "Document",
+                };
+                let name = path.file_name().map_or_else(
+                    || self.id.clone(),
+                    |name| name.to_string_lossy().into_owned(),
+                );
+                format!("[{label}: {name}] {}", path.display())
+            }
+        };
+        if let Some(caption) = &self.caption {
+            content.push_str("\n\n");
+            content.push_str(caption);
+        }
+        content
+    }
+}
+
+/// A template message with `text` as its single body variable.
+fn template_message(to: &str, name: &str, language: &str, text: &str) -> serde_json::Value {
+    serde_json::json!({
+        "messaging_product": "whatsapp",
+        "recipient_type": "individual",
+        "to": to,
+        "type": "template",
+        "template": {
+            "name": name,
+            "language": { "code": language },
+            "components": [{
+                "type": "body",
+                "parameters": [{ "type": "text", "text": template_parameter(text) }]
+            }]
+        }
+    })
+}
+
+/// The `error.code` of a Graph API error body.
+fn graph_error_code(body: &str) -> Option<i64> {
+    serde_json::from_str::<serde_json::Value>(body)
+        .ok()?
+        .get("error")?
+        .get("code")?
+        .as_i64()
+}
+
+/// A reply as a template body variable: on one line (Meta rejects newlines,
+/// tabs and long runs of spaces there) and within the length limit.
+fn template_parameter(text: &str) -> String {
+    let flat = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    crate::util::truncate_with_ellipsis(&flat, MAX_TEMPLATE_PARAM_CHARS - 3)
 }
 
 impl WhatsAppChannel {
@@ -41,9 +180,24 @@ impl WhatsAppChannel {
             endpoint_id,
             verify_token,
             allowed_numbers,
+            reply_template: None,
+            workspace_dir: None,
         }
     }
 
+    /// Send replies outside the 24-hour window with the approved template
+    /// `name` in `language`.
+    pub fn with_reply_template(mut self, name: String, language: String) -> Self {
+        self.reply_template = Some((name, language));
+        self
+    }
+
+    /// Download incoming media into `dir`.
+    pub fn with_workspace_dir(mut self, dir: PathBuf) -> Self {
+        self.workspace_dir = Some(dir);
+        self
+    }
+
     fn http_client(&self) -> reqwest::Client {
         crate::config::build_runtime_proxy_client("channel.whatsapp")
     }
@@ -58,8 +212,146 @@ impl WhatsAppChannel {
         &self.verify_token
     }
 
-    /// Parse an incoming webhook payload from Meta and extract messages
+    /// Parse an incoming webhook payload from Meta and extract text messages
     pub fn parse_webhook_payload(&self, payload: &serde_json::Value) -> Vec<ChannelMessage> {
+        self.parse_inbound(payload)
+            .into_iter()
+            .filter(|(_, media)| media.is_none())
+            .map(|(msg, _)| msg)
+            .collect()
+    }
+
+    /// Extract the messages of a webhook payload, downloading the media of
+    /// image, document, audio and video messages. Media that cannot be
+    /// downloaded is skipped.
+    pub async fn receive_webhook_payload(
+        &self,
+        payload: &serde_json::Value,
+    ) -> Vec<ChannelMessage> {
+        let mut messages = Vec::new();
+        for (mut msg, media) in self.parse_inbound(payload) {
+            if let Some(media) = media {
+                match self.download_media(&media).await {
+                    Ok(path) => msg.content = media.content(&path),
+                    Err(e) => {
+                        tracing::warn!(
+                            "WhatsApp: skipping {} from {}: {e:#}",
+                            media.kind,
+                            msg.sender
+                        );
+                        continue;
+                    }
+                }
+            }
+            messages.push(msg);
+        }
+        messages
+    }
+
+    /// POST a message to the Cloud API.
+    async fn post_message(&self, body: &serde_json::Value) -> anyhow::Result<reqwest::Response> {
+        // WhatsApp Cloud API: POST to /v18.0/{phone_number_id}/messages
+        let url = format!("{GRAPH_API_BASE}/{}/messages", self.endpoint_id);
+        ensure_https(&url)?;
+
+        Ok(self
+            .http_client()
+            .post(&url)
+            .bearer_auth(&self.access_token)
+            .header("Content-Type", "application/json")
+            .json(body)
+            .send()
+            .await?)
+    }
+
+    /// Send `text` as the body variable of the template `name`.
+    async fn send_template(
+        &self,
+        to: &str,
+        name: &str,
+        language: &str,
+        text: &str,
+    ) -> anyhow::Result<()> {
+        let resp = self
+            .post_message(&template_message(to, name, language, text))
+            .await?;
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let error_body = resp.text().await.unwrap_or_default();
+            tracing::error!("WhatsApp template send failed: {status} — {error_body}");
+            anyhow::bail!("WhatsApp API error: {status}");
+        }
+        Ok(())
+    }
+
+    /// Download a media object to `{workspace_dir}/whatsapp_files/`.
+    async fn download_media(&self, media: &IncomingMedia) -> anyhow::Result<PathBuf> {
+        let workspace = self
+            .workspace_dir
+            .as_ref()
+            .context("workspace_dir not configured")?;
+
+        // The media id resolves to a short-lived URL that needs the same token.
+        let lookup_url = format!("{GRAPH_API_BASE}/{}", media.id);
+        ensure_https(&lookup_url)?;
+        let lookup: serde_json::Value = self
+            .http_client()
+            .get(&lookup_url)
+            .bearer_auth(&self.access_token)
+            .send()
+            .await?
+            .error_for_status()
+            .context("WhatsApp media lookup failed")?
+            .json()
+            .await?;
+        let size = lookup
+            .get("file_size")
+            .and_then(|v| v.as_u64().or_else(|| v.as_str()?.parse().ok()));
+        if size.is_some_and(|size| size > WHATSAPP_MAX_MEDIA_BYTES) {
+            anyhow::bail!(
+                "file exceeds the {} MB limit",
+                WHATSAPP_MAX_MEDIA_BYTES / (1024 * 1024)
+            );
+        }
+        let url = lookup
+            .get("url")
+            .and_then(|u| u.as_str())
+            .context("WhatsApp media lookup: missing url")?;
+        ensure_https(url)?;
+
+        let data = self
+            .http_client()
+            .get(url)
+            .bearer_auth(&self.access_token)
+            .send()
+            .await?
+            .error_for_status()
+            .context("WhatsApp media download failed")?
+            .bytes()
+            .await?;
+        if data.len() as u64 > WHATSAPP_MAX_MEDIA_BYTES {
+            anyhow::bail!(
+                "file exceeds the {} MB limit",
+                WHATSAPP_MAX_MEDIA_BYTES / (1024 * 1024)
+            );
+        }
+
+        let save_dir = workspace.join("whatsapp_files");
+        tokio::fs::create_dir_all(&save_dir).await?;
+        let path = save_dir.join(media.local_file_name());
+        tokio::fs::write(&path, &data)
+            .await
+            .with_context(|| format!("failed to save {}", path.display()))?;
+        Ok(path)
+    }
+
+    /// Messages of a webhook payload from allowed numbers, each with its
+    /// media when it is a media message (its content is filled in once the
+    /// media is downloaded).
+    fn parse_inbound(
+        &self,
+        payload: &serde_json::Value,
+    ) -> Vec<(ChannelMessage, Option<IncomingMedia>)> {
         let mut messages = Vec::new();
 
         // WhatsApp Cloud API webhook structure:
@@ -105,20 +397,23 @@ impl WhatsAppChannel {
                         continue;
                     }
 
-                    // Extract text content (support text messages only for now)
+                    // Text messages carry their content; media is downloaded later
+                    let media = IncomingMedia::parse(msg);
                     let content = if let Some(text_obj) = msg.get("text") {
                         text_obj
                             .get("body")
                             .and_then(|b| b.as_str())
                             .unwrap_or("")
                             .to_string()
+                    } else if media.is_some() {
+                        String::new()
                     } else {
-                        // Could be image, audio, etc. — skip for now
-                        tracing::debug!("WhatsApp: skipping non-text message from {from}");
+                        // Stickers, locations, contacts, reactions, ...
+                        tracing::debug!("WhatsApp: skipping unsupported message from {from}");
                         continue;
                     };
 
-                    if content.is_empty() {
+                    if content.is_empty() && media.is_none() {
                         continue;
                     }
 
@@ -134,15 +429,18 @@ impl WhatsAppChannel {
                                 .as_secs()
                         });
 
-                    messages.push(ChannelMessage {
-                        id: Uuid::new_v4().to_string(),
-                        reply_target: normalized_from.clone(),
-                        sender: normalized_from,
-                        content,
-                        channel: "whatsapp".to_string(),
-                        timestamp,
-                        thread_ts: None,
-                    });
+                    messages.push((
+                        ChannelMessage {
+                            id: Uuid::new_v4().to_string(),
+                            reply_target: normalized_from.clone(),
+                            sender: normalized_from,
+                            content,
+                            channel: "whatsapp".to_string(),
+                            timestamp,
+                            thread_ts: None,
+                        },
+                        media,
+                    ));
                 }
             }
         }
@@ -158,12 +456,6 @@ impl Channel for WhatsAppChannel {
     }
 
     async fn send(&self, message: &SendMessage) -> anyhow::Result<()> {
-        // WhatsApp Cloud API: POST to /v18.0/{phone_number_id}/messages
-        let url = format!(
-            "https://graph.facebook.com/v18.0/{}/messages",
-            self.endpoint_id
-        );
-
         // Normalize recipient (remove leading + if present for API)
         let to = message
             .recipient
@@ -181,25 +473,28 @@ impl Channel for WhatsAppChannel {
             }
         });
 
-        ensure_https(&url)?;
-
-        let resp = self
-            .http_client()
-            .post(&url)
-            .bearer_auth(&self.access_token)
-            .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
-            .await?;
-
-        if !resp.status().is_success() {
-            let status = resp.status();
-            let error_body = resp.text().await.unwrap_or_default();
-            tracing::error!("WhatsApp send failed: {status} — {error_body}");
-            anyhow::bail!("WhatsApp API error: {status}");
+        let resp = self.post_message(&body).await?;
+        if resp.status().is_success() {
+            return Ok(());
         }
 
-        Ok(())
+        let status = resp.status();
+        let error_body = resp.text().await.unwrap_or_default();
+        if graph_error_code(&error_body) == Some(OUTSIDE_SERVICE_WINDOW) {
+            if let Some((name, language)) = &self.reply_template {
+                tracing::info!(
+                    "WhatsApp: service window closed for {to}, replying with template {name}"
+                );
+                return self
+                    .send_template(to, name, language, &message.content)
+                    .await;
+            }
+            tracing::warn!(
+                "WhatsApp: service window closed for {to}; set channels_config.whatsapp.reply_template to reply outside it"
+            );
+        }
+        tracing::error!("WhatsApp send failed: {status} — {error_body}");
+        anyhow::bail!("WhatsApp API error: {status}");
     }
 
     async fn listen(&self, _tx: tokio::sync::mpsc::Sender<ChannelMessage>) -> anyhow::Result<()> {
@@ -219,7 +514,7 @@ impl Channel for WhatsAppChannel {
 
     async fn health_check(&self) -> bool {
         // Check if we can reach the WhatsApp API
-        let url = format!("https://graph.facebook.com/v18.0/{}", self.endpoint_id);
+        let url = format!("{GRAPH_API_BASE}/{}", self.endpoint_id);
 
         if ensure_https(&url).is_err() {
             return false;
@@ -1135,4 +1430,94 @@ mod tests {
             "<script>alert('xss')</script> & \"quotes\" 'apostrophe'"
         );
     }
+
+    #[test]
+    fn whatsapp_media_names_and_content() {
+        let doc = IncomingMedia::parse(&serde_json::json!({
+            "type": "document",
+            "document": {
+                "id": "doc123",
+                "filename": "../../etc/brief.pdf",
+                "mime_type": "application/pdf",
+                "caption": "The brief"
+            }
+        }))
+        .unwrap();
+        assert_eq!(doc.local_file_name(), "doc123_brief.pdf");
+        assert_eq!(
+            doc.content(std::path::Path::new("/ws/whatsapp_files/doc123_brief.pdf")),
+            "[Document: doc123_brief.pdf] /ws/whatsapp_files/doc123_brief.pdf\n\nThe brief"
+        );
+
+        let voice = IncomingMedia::parse(&serde_json::json!({
+            "type": "audio",
+            "audio": { "id": "aud1", "mime_type": "audio/ogg; codecs=opus" }
+        }))
+        .unwrap();
+        assert_eq!(voice.local_file_name(), "audio_aud1.ogg");
+
+        let image = IncomingMedia::parse(&serde_json::json!({
+            "type": "image",
+            "image": { "id": "img123", "mime_type": "image/jpeg" }
+        }))
+        .unwrap();
+        assert_eq!(
+            image.content(std::path::Path::new("/ws/image_img123.jpeg")),
+            "[IMAGE:/ws/image_img123.jpeg]"
+        );
+
+        assert!(IncomingMedia::parse(&serde_json::json!({
+            "type": "sticker",
+            "sticker": { "id": "stk1" }
+        }))
+        .is_none());
+    }
+
+    #[tokio::test]
+    async fn whatsapp_receive_skips_media_that_cannot_be_saved() {
+        // No workspace_dir: the image is dropped before any request is made.
+        let ch = make_channel();
+        let payload = serde_json::json!({
+            "entry": [{
+                "changes": [{
+                    "value": {
+                        "messages": [
+                            { "from": "1234567890", "type": "image", "image": { "id": "img123" } },
+                            { "from": "1234567890", "type": "text", "text": { "body": "hi" } }
+                        ]
+                    }
+                }]
+            }]
+        });
+        let msgs = ch.receive_webhook_payload(&payload).await;
+        assert_eq!(msgs.len(), 1);
+        assert_eq!(msgs[0].content, "hi");
+    }
+
+    #[test]
+    fn whatsapp_template_reply_is_flattened() {
+        assert_eq!(
+            graph_error_code(r#"{"error":{"message":"Re-engagement message","code":131047}}"#),
+            Some(OUTSIDE_SERVICE_WINDOW)
+        );
+        assert_eq!(graph_error_code("not json"), None);
+
+        let body = template_message(
+            "123",
+            "build_update",
+            "en_US",
+            "Done!\n\nPreview:   https://x.dev",
+        );
+        assert_eq!(body["type"], "template");
+        assert_eq!(body["template"]["name"], "build_update");
+        assert_eq!(body["template"]["language"]["code"], "en_US");
+        assert_eq!(
+            body["template"]["components"][0]["parameters"][0]["text"],
+            "Done! Preview: https://x.dev"
+        );
+        assert_eq!(
+            template_parameter(&"a".repeat(2000)).chars().count(),
+            MAX_TEMPLATE_PARAM_CHARS
+        );
+    }
 }
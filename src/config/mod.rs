@@ -1,3 +1,5 @@
+pub mod profiles;
+pub mod reload;
 pub mod schema;
 pub mod traits;
 
@@ -5,18 +7,29 @@ pub mod traits;
 pub use schema::{
     apply_runtime_proxy_to_builder, build_runtime_proxy_client,
     build_runtime_proxy_client_with_timeouts, runtime_proxy_config, set_runtime_proxy_config,
-    AgentConfig, AuditConfig, AutonomyConfig, BrowserComputerUseConfig, BrowserConfig,
-    BuiltinHooksConfig, ChannelsConfig, ClassificationRule, ComposioConfig, Config, CostConfig,
-    CronConfig, DelegateAgentConfig, DiscordConfig, DockerRuntimeConfig, EmbeddingRouteConfig,
-    FactoryConfig, GatewayConfig, HardwareConfig, HardwareTransport, HeartbeatConfig, HooksConfig,
-    HttpRequestConfig, IMessageConfig, IdentityConfig, LarkConfig, MatrixConfig, MemoryConfig,
-    ModelRouteConfig, MultimodalConfig, NextcloudTalkConfig, ObservabilityConfig,
-    PeripheralBoardConfig, PeripheralsConfig, ProxyConfig, ProxyScope, QueryClassificationConfig,
-    ReliabilityConfig, ResourceLimitsConfig, RuntimeConfig, SandboxBackend, SandboxConfig,
-    SchedulerConfig, SecretsConfig, SecurityConfig, SkillsConfig, SkillsPromptInjectionMode,
-    SlackConfig, SopConfig, SopExecutionMode, StorageConfig, StorageProviderConfig,
-    StorageProviderSection, StreamMode, TelegramConfig, TranscriptionConfig, TunnelConfig,
-    WebSearchConfig, WebhookConfig, ZerobuildConfig,
+    A2aPeerConfig, AgentConfig, AgentPhasesConfig, ArtifactBackend, ArtifactsConfig, AuditConfig,
+    AutonomyConfig, BrowserComputerUseConfig, BrowserConfig, BuiltinHooksConfig,
+    CategoryRetentionConfig, ChangelogConfig, ChannelsConfig, ClassificationRule, ComposioConfig,
+    Config, ConfigProfile, CostConfig, CronConfig, DelegateAgentConfig, DiscordConfig,
+    DockerRuntimeConfig, EmbeddingRouteConfig, FactoryA2aConfig, FactoryBusConfig, FactoryConfig,
+    FactoryIssuesConfig, FactoryMemoryConfig, FactoryQueueConfig, FactoryReviewConfig,
+    FactoryStagesConfig, FactoryTriageConfig, FirecrackerSandboxConfig, FlySandboxConfig,
+    GatewayConfig, GuardrailsConfig, HardwareConfig, HardwareTransport, HeartbeatConfig,
+    HooksConfig, HttpRequestConfig, I18nConfig, IMessageConfig, IdentityConfig, ImageBackend,
+    ImageHostingConfig, ImageS3Config, KubernetesSandboxConfig, LarkConfig, LicenseConfig,
+    LocalProviderConfig, LoggingConfig, MaintenanceConfig, MatrixConfig, MemoryConfig,
+    MemoryConsolidationConfig, MemoryEvictionPolicy, MemoryNamespace, MemoryNamespaceAccess,
+    MemoryRetentionConfig, ModelPricing, ModelRouteConfig, ModerationAction, ModerationConfig,
+    ModerationProvider, MultimodalConfig, NextcloudTalkConfig, ObservabilityConfig,
+    ObserverChannelConfig, ObserverEvent, PeripheralBoardConfig, PeripheralsConfig,
+    ProviderRateLimitConfig, ProxyConfig, ProxyScope, QueryClassificationConfig, RecoveryConfig,
+    ReliabilityConfig, RepoAccessConfig, ResourceLimitsConfig, ReviewRepoConfig, ReviewSeverity,
+    RuntimeConfig, SandboxBackend, SandboxConfig, SandboxLimitsConfig, SandboxTemplateConfig,
+    SchedulerConfig, SecretsConfig, SecurityConfig, SessionPhase, SkillsConfig,
+    SkillsPromptInjectionMode, SlackConfig, SopConfig, SopExecutionMode, StorageConfig,
+    StorageProviderConfig, StorageProviderSection, StreamMode, TelegramConfig,
+    ToolReliabilityConfig, TranscriptionConfig, TunnelConfig, UserQuotasConfig, UserRole,
+    UsersConfig, VercelConfig, WebSearchConfig, WebhookConfig, ZerobuildConfig,
 };
 
 pub fn name_and_presence<T: traits::ChannelConfig>(channel: &Option<T>) -> (&'static str, bool) {
@@ -53,6 +66,7 @@ mod tests {
             allowed_users: vec![],
             listen_to_bots: false,
             mention_only: false,
+            stream_mode: StreamMode::default(),
         };
 
         let lark = LarkConfig {
@@ -0,0 +1,229 @@
+//! Config profiles and per-project overrides.
+//!
+//! A session's effective config is the base config, then the selected
+//! `[profiles.<name>]` entry, then the project's override file
+//! (`projects/<project-id>.toml` next to `config.toml`). A project file may
+//! name a profile of its own, used when the session did not select one.
+
+use super::{Config, ConfigProfile};
+use anyhow::{bail, Context, Result};
+use std::path::{Path, PathBuf};
+
+/// Directory of per-project override files, relative to the config dir.
+pub const PROJECTS_DIR: &str = "projects";
+
+impl ConfigProfile {
+    /// Apply the fields this profile sets to `config`.
+    pub fn apply_to(&self, config: &mut Config) {
+        if let Some(provider) = &self.default_provider {
+            config.default_provider = Some(provider.clone());
+        }
+        if let Some(model) = &self.default_model {
+            config.default_model = Some(model.clone());
+        }
+        if let Some(temperature) = self.default_temperature {
+            config.default_temperature = temperature;
+        }
+        for route in &self.model_routes {
+            match config
+                .model_routes
+                .iter_mut()
+                .find(|r| r.hint == route.hint)
+            {
+                Some(existing) => *existing = route.clone(),
+                None => config.model_routes.push(route.clone()),
+            }
+        }
+        if let Some(sandbox) = &self.sandbox {
+            config.zerobuild.sandbox = sandbox.clone();
+        }
+        if let Some(stages) = &self.factory_stages {
+            config.factory.stages = stages.clone();
+        }
+        if let Some(iterations) = self.max_ping_pong_iterations {
+            config.factory.max_ping_pong_iterations = iterations;
+        }
+        if let Some(iterations) = self.max_tool_iterations {
+            config.agent.max_tool_iterations = iterations;
+        }
+    }
+}
+
+/// Profile names, sorted.
+pub fn profile_names(config: &Config) -> Vec<String> {
+    let mut names: Vec<String> = config.profiles.keys().cloned().collect();
+    names.sort();
+    names
+}
+
+/// Path of the override file for `project`.
+pub fn project_override_path(config: &Config, project: &str) -> PathBuf {
+    config
+        .config_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(PROJECTS_DIR)
+        .join(format!(
+            "{}.toml",
+            crate::memory::user_scoped::project_id(project)
+        ))
+}
+
+/// Read the override file for `project`, if there is one.
+pub fn load_project_override(config: &Config, project: &str) -> Result<Option<ConfigProfile>> {
+    let path = project_override_path(config, project);
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => {
+            return Err(e).with_context(|| format!("Failed to read {}", path.display()));
+        }
+    };
+    let overrides = toml::from_str(&contents)
+        .with_context(|| format!("Invalid project override file {}", path.display()))?;
+    Ok(Some(overrides))
+}
+
+fn named_profile<'a>(config: &'a Config, name: &str) -> Result<&'a ConfigProfile> {
+    match config.profiles.get(name) {
+        Some(profile) => Ok(profile),
+        None => {
+            let names = profile_names(config);
+            if names.is_empty() {
+                bail!("Unknown profile '{name}': no [profiles] are configured");
+            }
+            bail!("Unknown profile '{name}'. Available: {}", names.join(", "));
+        }
+    }
+}
+
+/// Effective config for a session using `profile` and/or `project`.
+pub fn resolve(base: &Config, profile: Option<&str>, project: Option<&str>) -> Result<Config> {
+    let overrides = match project {
+        Some(project) => load_project_override(base, project)?,
+        None => None,
+    };
+    let profile = profile
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .or_else(|| overrides.as_ref().and_then(|o| o.profile.as_deref()));
+
+    let mut config = base.clone();
+    if let Some(name) = profile {
+        named_profile(base, name)?.apply_to(&mut config);
+    }
+    if let Some(overrides) = &overrides {
+        overrides.apply_to(&mut config);
+    }
+    config
+        .validate()
+        .context("Profile produced an invalid config")?;
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ModelRouteConfig;
+    use tempfile::TempDir;
+
+    fn base(tmp: &TempDir) -> Config {
+        let mut config: Config = toml::from_str(
+            r#"
+default_temperature = 0.7
+default_model = "big-model"
+
+[[model_routes]]
+hint = "fast"
+provider = "openrouter"
+model = "small-model"
+
+[profiles.fast-cheap]
+description = "Quick drafts"
+default_model = "cheap-model"
+max_ping_pong_iterations = 1
+factory_stages = { integration_loop = true, deployment = false }
+
+[profiles.production-quality]
+default_temperature = 0.2
+sandbox = { command_timeout_secs = 900 }
+"#,
+        )
+        .unwrap();
+        config.config_path = tmp.path().join("config.toml");
+        config
+    }
+
+    #[test]
+    fn named_profile_overrides_only_its_fields() {
+        let tmp = TempDir::new().unwrap();
+        let base = base(&tmp);
+
+        let cheap = resolve(&base, Some("fast-cheap"), None).unwrap();
+        assert_eq!(cheap.default_model.as_deref(), Some("cheap-model"));
+        assert_eq!(cheap.factory.max_ping_pong_iterations, 1);
+        assert!(!cheap.factory.stages.deployment);
+        assert!((cheap.default_temperature - 0.7).abs() < f64::EPSILON);
+
+        let prod = resolve(&base, Some("production-quality"), None).unwrap();
+        assert_eq!(prod.default_model.as_deref(), Some("big-model"));
+        assert_eq!(prod.zerobuild.sandbox.command_timeout_secs, 900);
+
+        let err = resolve(&base, Some("turbo"), None).unwrap_err();
+        assert!(err.to_string().contains("fast-cheap, production-quality"));
+    }
+
+    #[test]
+    fn project_file_layers_on_top_of_its_profile() {
+        let tmp = TempDir::new().unwrap();
+        let base = base(&tmp);
+        std::fs::create_dir_all(tmp.path().join(PROJECTS_DIR)).unwrap();
+        std::fs::write(
+            tmp.path().join(PROJECTS_DIR).join("landing-page.toml"),
+            r#"
+profile = "fast-cheap"
+max_tool_iterations = 3
+
+[[model_routes]]
+hint = "fast"
+provider = "ollama"
+model = "local-model"
+"#,
+        )
+        .unwrap();
+
+        let config = resolve(&base, None, Some("Landing Page")).unwrap();
+        assert_eq!(config.default_model.as_deref(), Some("cheap-model"));
+        assert_eq!(config.agent.max_tool_iterations, 3);
+        let routes: Vec<&ModelRouteConfig> = config.model_routes.iter().collect();
+        assert_eq!(routes.len(), 1);
+        assert_eq!(routes[0].model, "local-model");
+
+        // An explicit profile wins over the project's own.
+        let config = resolve(&base, Some("production-quality"), Some("landing page")).unwrap();
+        assert_eq!(config.default_model.as_deref(), Some("big-model"));
+        assert_eq!(config.agent.max_tool_iterations, 3);
+
+        // Projects without a file use the base config.
+        let config = resolve(&base, None, Some("other")).unwrap();
+        assert_eq!(
+            config.agent.max_tool_iterations,
+            base.agent.max_tool_iterations
+        );
+    }
+
+    #[test]
+    fn invalid_project_file_is_rejected() {
+        let tmp = TempDir::new().unwrap();
+        let base = base(&tmp);
+        std::fs::create_dir_all(tmp.path().join(PROJECTS_DIR)).unwrap();
+        std::fs::write(
+            tmp.path().join(PROJECTS_DIR).join("demo.toml"),
+            "default_modle = \"typo\"\n",
+        )
+        .unwrap();
+
+        let err = resolve(&base, None, Some("demo")).unwrap_err();
+        assert!(format!("{err:#}").contains("default_modle"));
+    }
+}
@@ -0,0 +1,458 @@
+//! Config hot reload.
+//!
+//! [`ConfigReloader`] re-reads `config.toml` when its mtime changes (or on
+//! `SIGHUP`), validates it exactly like startup does, and publishes the new
+//! config. Sections in [`LIVE_SECTIONS`] are picked up by running components
+//! without a restart; changes anywhere else are reported as restart-required
+//! and only take effect on the next start. An invalid file never replaces
+//! the running config.
+
+use super::Config;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::Serialize;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::watch;
+
+/// Top-level sections that are read per request and can change live.
+pub const LIVE_SECTIONS: &[&str] = &[
+    "api_key",
+    "api_url",
+    "default_provider",
+    "default_model",
+    "default_temperature",
+    "reliability",
+    "model_routes",
+    "embedding_routes",
+    "query_classification",
+    "autonomy",
+    "guardrails",
+    "agent",
+    "identity",
+    "multimodal",
+    "profiles",
+];
+
+/// Fields inside [`LIVE_SECTIONS`] that are only read at startup.
+pub const RESTART_ONLY_FIELDS: &[&str] = &[
+    "agent.prompt_caching",
+    "reliability.channel_initial_backoff_secs",
+    "reliability.channel_max_backoff_secs",
+    "reliability.scheduler_poll_secs",
+    "reliability.scheduler_retries",
+    "reliability.tools",
+];
+
+/// How often the watcher checks the config file's mtime.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Changed fields between two configs, as dotted paths (at most two levels
+/// deep, e.g. `autonomy.level`, `model_routes`).
+#[derive(Debug, Clone, Default, Serialize, PartialEq, Eq)]
+pub struct ConfigDiff {
+    /// Changes applied live
+    pub live: Vec<String>,
+    /// Changes that need a restart to take effect
+    pub restart_required: Vec<String>,
+}
+
+impl ConfigDiff {
+    pub fn is_empty(&self) -> bool {
+        self.live.is_empty() && self.restart_required.is_empty()
+    }
+}
+
+/// Outcome of one reload attempt.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReloadReport {
+    pub checked_at: Option<DateTime<Utc>>,
+    /// Fields now in effect
+    pub applied: Vec<String>,
+    /// Fields changed on disk that only take effect after a restart
+    pub restart_required: Vec<String>,
+    /// Unknown keys and other non-fatal findings
+    pub warnings: Vec<String>,
+    /// Why the file was rejected; the previous config stays active
+    pub error: Option<String>,
+}
+
+/// Compare two configs and classify every changed field.
+pub fn diff_config(old: &Config, new: &Config) -> ConfigDiff {
+    let (Ok(old), Ok(new)) = (serde_json::to_value(old), serde_json::to_value(new)) else {
+        return ConfigDiff::default();
+    };
+    let mut changed = Vec::new();
+    diff_values("", &old, &new, 0, &mut changed);
+
+    let mut diff = ConfigDiff::default();
+    for path in changed {
+        let section = path.split('.').next().unwrap_or_default();
+        if LIVE_SECTIONS.contains(&section) && !RESTART_ONLY_FIELDS.contains(&path.as_str()) {
+            diff.live.push(path);
+        } else {
+            diff.restart_required.push(path);
+        }
+    }
+    diff
+}
+
+fn diff_values(prefix: &str, old: &Value, new: &Value, depth: usize, out: &mut Vec<String>) {
+    if old == new {
+        return;
+    }
+    match (old, new) {
+        (Value::Object(a), Value::Object(b)) if depth < 2 => {
+            let mut keys: Vec<&String> = a.keys().chain(b.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                diff_values(
+                    &path,
+                    a.get(key).unwrap_or(&Value::Null),
+                    b.get(key).unwrap_or(&Value::Null),
+                    depth + 1,
+                    out,
+                );
+            }
+        }
+        _ => out.push(prefix.to_string()),
+    }
+}
+
+/// Copy the [`LIVE_SECTIONS`] of `new` into `target` (minus
+/// [`RESTART_ONLY_FIELDS`]), leaving everything else as it was loaded at
+/// startup.
+pub fn apply_live(target: &mut Config, new: &Config) {
+    target.api_key.clone_from(&new.api_key);
+    target.api_url.clone_from(&new.api_url);
+    target.default_provider.clone_from(&new.default_provider);
+    target.default_model.clone_from(&new.default_model);
+    target.default_temperature = new.default_temperature;
+    let reliability = &mut target.reliability;
+    reliability.provider_retries = new.reliability.provider_retries;
+    reliability.provider_backoff_ms = new.reliability.provider_backoff_ms;
+    reliability
+        .fallback_providers
+        .clone_from(&new.reliability.fallback_providers);
+    reliability.api_keys.clone_from(&new.reliability.api_keys);
+    reliability
+        .model_fallbacks
+        .clone_from(&new.reliability.model_fallbacks);
+    target.model_routes.clone_from(&new.model_routes);
+    target.embedding_routes.clone_from(&new.embedding_routes);
+    target
+        .query_classification
+        .clone_from(&new.query_classification);
+    target.autonomy.clone_from(&new.autonomy);
+    target.guardrails.clone_from(&new.guardrails);
+    target.agent.clone_from(&new.agent);
+    target.identity.clone_from(&new.identity);
+    target.multimodal.clone_from(&new.multimodal);
+    target.profiles.clone_from(&new.profiles);
+}
+
+/// Keys in the raw TOML that the schema does not know. Serde silently drops
+/// them, which usually means a typo.
+pub fn unknown_keys(raw: &str, parsed: &Config) -> Vec<String> {
+    let (Ok(toml::Value::Table(raw)), Ok(toml::Value::Table(known))) = (
+        toml::from_str::<toml::Value>(raw),
+        toml::Value::try_from(parsed),
+    ) else {
+        return Vec::new();
+    };
+    let mut unknown = Vec::new();
+    for (key, value) in &raw {
+        match (value, known.get(key)) {
+            (_, None) => unknown.push(key.clone()),
+            (toml::Value::Table(section), Some(toml::Value::Table(known_section))) => {
+                unknown.extend(
+                    section
+                        .keys()
+                        .filter(|k| !known_section.contains_key(*k))
+                        .map(|k| format!("{key}.{k}")),
+                );
+            }
+            _ => {}
+        }
+    }
+    unknown
+}
+
+fn file_stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let meta = std::fs::metadata(path).ok()?;
+    Some((meta.modified().ok()?, meta.len()))
+}
+
+/// Watches one config file and publishes validated reloads.
+pub struct ConfigReloader {
+    config_path: PathBuf,
+    workspace_dir: PathBuf,
+    /// Config as last read from disk (including restart-only changes)
+    on_disk: Mutex<Config>,
+    /// Config in effect: startup config with live sections applied
+    tx: watch::Sender<Arc<Config>>,
+    stamp: Mutex<Option<(SystemTime, u64)>>,
+    last_report: Mutex<ReloadReport>,
+}
+
+impl ConfigReloader {
+    pub fn new(config: Config) -> Self {
+        let stamp = file_stamp(&config.config_path);
+        Self {
+            config_path: config.config_path.clone(),
+            workspace_dir: config.workspace_dir.clone(),
+            on_disk: Mutex::new(config.clone()),
+            tx: watch::channel(Arc::new(config)).0,
+            stamp: Mutex::new(stamp),
+            last_report: Mutex::new(ReloadReport::default()),
+        }
+    }
+
+    /// Receive the effective config after every applied reload.
+    pub fn subscribe(&self) -> watch::Receiver<Arc<Config>> {
+        self.tx.subscribe()
+    }
+
+    /// The config currently in effect.
+    pub fn current(&self) -> Arc<Config> {
+        self.tx.borrow().clone()
+    }
+
+    pub fn last_report(&self) -> ReloadReport {
+        self.last_report.lock().clone()
+    }
+
+    /// Reload only if the file changed since the last check.
+    pub async fn reload_if_changed(&self) -> Option<ReloadReport> {
+        let stamp = file_stamp(&self.config_path);
+        if stamp.is_none() || *self.stamp.lock() == stamp {
+            return None;
+        }
+        Some(self.reload().await)
+    }
+
+    /// Read, validate and apply the config file now.
+    pub async fn reload(&self) -> ReloadReport {
+        *self.stamp.lock() = file_stamp(&self.config_path);
+        let mut report = ReloadReport {
+            checked_at: Some(Utc::now()),
+            ..ReloadReport::default()
+        };
+
+        let loaded = Config::load_from_file(&self.config_path, self.workspace_dir.clone()).await;
+        let new = match loaded {
+            Ok(new) => new,
+            Err(e) => {
+                let error = format!("{e:#}");
+                tracing::warn!(
+                    path = %self.config_path.display(),
+                    "Config reload rejected, keeping the running config: {error}"
+                );
+                report.error = Some(error);
+                *self.last_report.lock() = report.clone();
+                return report;
+            }
+        };
+        if let Ok(raw) = tokio::fs::read_to_string(&self.config_path).await {
+            report.warnings = unknown_keys(&raw, &new)
+                .into_iter()
+                .map(|key| format!("unknown key '{key}' ignored"))
+                .collect();
+        }
+
+        let diff = diff_config(&self.on_disk.lock(), &new);
+        if !diff.live.is_empty() {
+            let mut effective = (*self.current()).clone();
+            apply_live(&mut effective, &new);
+            self.tx.send_replace(Arc::new(effective));
+        }
+        // Restart-only changes stay pending until the process restarts, so
+        // keep reporting them against the config the process started with.
+        report.restart_required = diff_config(&self.current(), &new).restart_required;
+        report.applied = diff.live;
+        *self.on_disk.lock() = new;
+
+        for warning in &report.warnings {
+            tracing::warn!("Config reload: {warning}");
+        }
+        if !report.applied.is_empty() {
+            tracing::info!(fields = ?report.applied, "Config reloaded");
+        }
+        if !report.restart_required.is_empty() {
+            tracing::warn!(
+                fields = ?report.restart_required,
+                "Config changes need a restart to take effect"
+            );
+        }
+        *self.last_report.lock() = report.clone();
+        report
+    }
+}
+
+/// Poll the config file for changes and reload on `SIGHUP` until the
+/// process exits.
+pub async fn run_config_watcher(reloader: Arc<ConfigReloader>, poll_interval: Duration) {
+    let mut interval = tokio::time::interval(poll_interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    #[cfg(unix)]
+    let mut hangup = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()) {
+        Ok(signal) => Some(signal),
+        Err(e) => {
+            tracing::warn!("SIGHUP config reload unavailable: {e}");
+            None
+        }
+    };
+
+    loop {
+        #[cfg(unix)]
+        {
+            let hup = async {
+                match hangup.as_mut() {
+                    Some(signal) => signal.recv().await,
+                    None => std::future::pending().await,
+                }
+            };
+            tokio::select! {
+                _ = interval.tick() => {
+                    reloader.reload_if_changed().await;
+                }
+                _ = hup => {
+                    tracing::info!("SIGHUP received, reloading config");
+                    reloader.reload().await;
+                }
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            interval.tick().await;
+            reloader.reload_if_changed().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    async fn reloader_with(toml: &str) -> (TempDir, ConfigReloader) {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("config.toml");
+        tokio::fs::write(&path, toml).await.unwrap();
+        let config = Config::load_from_file(&path, tmp.path().join("workspace"))
+            .await
+            .unwrap();
+        (tmp, ConfigReloader::new(config))
+    }
+
+    #[test]
+    fn diff_splits_live_and_restart_fields() {
+        let old = Config::default();
+        let mut new = old.clone();
+        new.default_model = Some("other-model".into());
+        new.autonomy.max_actions_per_hour += 1;
+        new.gateway.port += 1;
+
+        let diff = diff_config(&old, &new);
+        assert_eq!(
+            diff.live,
+            ["autonomy.max_actions_per_hour", "default_model"]
+        );
+        assert_eq!(diff.restart_required, ["gateway.port"]);
+    }
+
+    #[test]
+    fn apply_live_covers_every_live_section() {
+        let old = Config::default();
+        let mut new = old.clone();
+        new.api_key = Some("key".into());
+        new.api_url = Some("http://localhost:1".into());
+        new.default_provider = Some("ollama".into());
+        new.default_model = Some("m".into());
+        new.default_temperature = 0.1;
+        new.reliability.provider_retries += 1;
+        new.model_routes.push(crate::config::ModelRouteConfig {
+            hint: "fast".into(),
+            provider: "ollama".into(),
+            model: "m".into(),
+            api_key: None,
+            temperature: None,
+            top_p: None,
+            seed: None,
+        });
+        new.query_classification.enabled = !old.query_classification.enabled;
+        new.autonomy.max_actions_per_hour += 1;
+        new.agent.max_tool_iterations += 1;
+        new.multimodal.max_images += 1;
+        new.profiles
+            .insert("fast-cheap".into(), crate::config::ConfigProfile::default());
+        new.gateway.port += 1;
+        new.reliability.scheduler_poll_secs += 1;
+
+        let mut target = old.clone();
+        apply_live(&mut target, &new);
+        let remaining = diff_config(&target, &new);
+        assert!(remaining.live.is_empty(), "{:?}", remaining.live);
+        assert_eq!(
+            remaining.restart_required,
+            ["gateway.port", "reliability.scheduler_poll_secs"]
+        );
+    }
+
+    #[test]
+    fn unknown_keys_are_reported() {
+        let raw = "default_temperature = 0.7\ndefault_temprature = 0.5\n[gateway]\nport = 4242\nprot = 4243\n";
+        let parsed: Config = toml::from_str(raw).unwrap();
+        assert_eq!(
+            unknown_keys(raw, &parsed),
+            ["default_temprature", "gateway.prot"]
+        );
+    }
+
+    #[tokio::test]
+    async fn reload_applies_live_changes_and_reports_restart_fields() {
+        let (tmp, reloader) = reloader_with("default_temperature = 0.7\n").await;
+        let mut rx = reloader.subscribe();
+        tokio::fs::write(
+            tmp.path().join("config.toml"),
+            "default_temperature = 0.2\n[gateway]\nport = 4242\n",
+        )
+        .await
+        .unwrap();
+
+        let report = reloader.reload().await;
+        assert!(report.error.is_none());
+        assert_eq!(report.applied, ["default_temperature"]);
+        assert_eq!(report.restart_required, ["gateway.port"]);
+        assert!(rx.has_changed().unwrap());
+        let current = rx.borrow_and_update().clone();
+        assert!((current.default_temperature - 0.2).abs() < f64::EPSILON);
+        assert_ne!(current.gateway.port, 4242);
+    }
+
+    #[tokio::test]
+    async fn invalid_config_keeps_running_config() {
+        let (tmp, reloader) = reloader_with("default_temperature = 0.7\n").await;
+        tokio::fs::write(
+            tmp.path().join("config.toml"),
+            "default_temperature = 0.2\n[gateway]\nhost = \"\"\n",
+        )
+        .await
+        .unwrap();
+
+        let report = reloader.reload().await;
+        let error = report.error.unwrap();
+        assert!(error.contains("gateway.host"), "{error}");
+        assert!((reloader.current().default_temperature - 0.7).abs() < f64::EPSILON);
+        assert!(reloader.last_report().error.is_some());
+    }
+}
@@ -1,5 +1,6 @@
 use crate::config::traits::ChannelConfig;
 use crate::providers::{is_glm_alias, is_zai_alias};
+use crate::security::guardrails::GuardrailAction;
 use crate::security::AutonomyLevel;
 use anyhow::{Context, Result};
 use directories::UserDirs;
@@ -25,6 +26,7 @@ const SUPPORTED_PROXY_SERVICE_KEYS: &[&str] = &[
     "provider.openrouter",
     "channel.dingtalk",
     "channel.discord",
+    "channel.email",
     "channel.lark",
     "channel.matrix",
     "channel.mattermost",
@@ -34,13 +36,17 @@ const SUPPORTED_PROXY_SERVICE_KEYS: &[&str] = &[
     "channel.slack",
     "channel.telegram",
     "channel.whatsapp",
+    "tool.a2a",
     "tool.browser",
     "tool.composio",
     "tool.http_request",
     "tool.pushover",
+    "tool.vercel",
     "memory.embeddings",
     "tunnel.custom",
     "transcription.groq",
+    "transcription.tts",
+    "moderation.openai",
 ];
 
 const SUPPORTED_PROXY_SERVICE_SELECTORS: &[&str] = &[
@@ -50,6 +56,7 @@ const SUPPORTED_PROXY_SERVICE_SELECTORS: &[&str] = &[
     "memory.*",
     "tunnel.*",
     "transcription.*",
+    "moderation.*",
 ];
 
 static RUNTIME_PROXY_CONFIG: OnceLock<RwLock<ProxyConfig>> = OnceLock::new();
@@ -84,6 +91,10 @@ pub struct Config {
     #[serde(default)]
     pub observability: ObservabilityConfig,
 
+    /// Log level, format and file rotation (`[logging]`).
+    #[serde(default)]
+    pub logging: LoggingConfig,
+
     /// Autonomy and security policy configuration (`[autonomy]`).
     #[serde(default)]
     pub autonomy: AutonomyConfig,
@@ -92,6 +103,10 @@ pub struct Config {
     #[serde(default)]
     pub runtime: RuntimeConfig,
 
+    /// Capability overrides for local model servers (`[local_provider]`).
+    #[serde(default)]
+    pub local_provider: LocalProviderConfig,
+
     /// Reliability settings: retries, fallback providers, backoff (`[reliability]`).
     #[serde(default)]
     pub reliability: ReliabilityConfig,
@@ -168,6 +183,10 @@ pub struct Config {
     #[serde(default)]
     pub multimodal: MultimodalConfig,
 
+    /// Language of user-facing channel messages (`[i18n]`).
+    #[serde(default)]
+    pub i18n: I18nConfig,
+
     /// Web search tool configuration (`[web_search]`).
     #[serde(default)]
     pub web_search: WebSearchConfig,
@@ -215,6 +234,23 @@ pub struct Config {
     /// ZeroBuild multi-tenant orchestration configuration (`[zerobuild]`).
     #[serde(default)]
     pub zerobuild: ZerobuildConfig,
+
+    /// Tool-argument guardrails (`[guardrails]`).
+    #[serde(default)]
+    pub guardrails: GuardrailsConfig,
+
+    /// Roles and daily quotas of channel and gateway users (`[users]`).
+    #[serde(default)]
+    pub users: UsersConfig,
+
+    /// Screening of inbound requests before the agent acts on them
+    /// (`[moderation]`).
+    #[serde(default)]
+    pub moderation: ModerationConfig,
+
+    /// Named config profiles (`[profiles.<name>]`), selected per session.
+    #[serde(default)]
+    pub profiles: HashMap<String, ConfigProfile>,
 }
 
 // ── Delegate Agents ──────────────────────────────────────────────
@@ -223,8 +259,10 @@ pub struct Config {
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct DelegateAgentConfig {
     /// Provider name (e.g. "ollama", "openrouter", "anthropic")
+    #[serde(default)]
     pub provider: String,
     /// Model name
+    #[serde(default)]
     pub model: String,
     /// Optional system prompt for the sub-agent
     #[serde(default)]
@@ -235,6 +273,12 @@ pub struct DelegateAgentConfig {
     /// Temperature override
     #[serde(default)]
     pub temperature: Option<f64>,
+    /// Nucleus sampling (`top_p`) override
+    #[serde(default)]
+    pub top_p: Option<f64>,
+    /// Sampling seed, for providers that support one
+    #[serde(default)]
+    pub seed: Option<u64>,
     /// Max recursion depth for nested delegation
     #[serde(default = "default_max_depth")]
     pub max_depth: u32,
@@ -286,6 +330,40 @@ pub struct FactoryConfig {
     /// Workspace isolation configuration for per-agent workspace management.
     #[serde(default)]
     pub workspace: WorkspaceIsolationConfig,
+    /// Shared memory namespaces across factory agents (`[factory.memory]`).
+    #[serde(default)]
+    pub memory: FactoryMemoryConfig,
+    /// Optional pipeline stages (`[factory.stages]`).
+    #[serde(default)]
+    pub stages: FactoryStagesConfig,
+    /// Queued builds processed by a worker pool (`[factory.queue]`).
+    #[serde(default)]
+    pub queue: FactoryQueueConfig,
+    /// Builds started from labeled GitHub issues (`[factory.issues]`).
+    #[serde(default)]
+    pub issues: FactoryIssuesConfig,
+    /// Triage of new issues in existing repositories (`[factory.triage]`).
+    #[serde(default)]
+    pub triage: FactoryTriageConfig,
+    /// Reviews of new pull requests in existing repositories
+    /// (`[factory.review]`).
+    #[serde(default)]
+    pub review: FactoryReviewConfig,
+    /// Ask the user to confirm a `factory_build` whose estimated cost is
+    /// above this many USD, priced with `[cost.prices]`. `0` never asks.
+    /// Default: `1.0`.
+    #[serde(default = "default_factory_confirm_above_usd")]
+    pub confirm_above_usd: f64,
+    /// Tasks delegated to and from other ZeroBuild instances (`[factory.a2a]`).
+    #[serde(default)]
+    pub a2a: FactoryA2aConfig,
+    /// Coordination bus shared by several ZeroBuild processes (`[factory.bus]`).
+    #[serde(default)]
+    pub bus: FactoryBusConfig,
+}
+
+fn default_factory_confirm_above_usd() -> f64 {
+    1.0
 }
 
 impl Default for FactoryConfig {
@@ -296,6 +374,414 @@ impl Default for FactoryConfig {
             provider_overrides: HashMap::new(),
             enable_streaming: true,
             workspace: WorkspaceIsolationConfig::default(),
+            memory: FactoryMemoryConfig::default(),
+            stages: FactoryStagesConfig::default(),
+            queue: FactoryQueueConfig::default(),
+            issues: FactoryIssuesConfig::default(),
+            triage: FactoryTriageConfig::default(),
+            review: FactoryReviewConfig::default(),
+            confirm_above_usd: default_factory_confirm_above_usd(),
+            a2a: FactoryA2aConfig::default(),
+            bus: FactoryBusConfig::default(),
+        }
+    }
+}
+
+/// Agent-to-agent protocol between ZeroBuild instances (`[factory.a2a]`).
+/// An instance serves delegated tasks when `enabled`, and delegates its own
+/// to the listed `peers` with the `a2a_delegate` tool.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct FactoryA2aConfig {
+    /// Accept tasks from peers on the gateway's `/a2a/*` routes.
+    /// Default: `false`.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Bearer token peers must send. Required when `enabled`.
+    #[serde(default)]
+    pub token: Option<String>,
+    /// Instances tasks can be delegated to, by name
+    /// (`[factory.a2a.peers.<name>]`).
+    #[serde(default)]
+    pub peers: HashMap<String, A2aPeerConfig>,
+    /// Longest wait for a delegated task, in seconds. Default: `3600`.
+    #[serde(default = "default_a2a_task_timeout_secs")]
+    pub task_timeout_secs: u64,
+}
+
+fn default_a2a_task_timeout_secs() -> u64 {
+    3600
+}
+
+impl Default for FactoryA2aConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            token: None,
+            peers: HashMap::new(),
+            task_timeout_secs: default_a2a_task_timeout_secs(),
+        }
+    }
+}
+
+/// A ZeroBuild instance that accepts delegated tasks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct A2aPeerConfig {
+    /// Base URL of the peer's gateway, e.g. `https://api-builder.example.com`.
+    pub url: String,
+    /// The peer's `[factory.a2a] token`.
+    pub token: String,
+    /// What the peer is for, shown to the agent (e.g. "backend APIs").
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Build job queue (`[factory.queue]`). Jobs are persisted in the ZeroBuild
+/// store and survive restarts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct FactoryQueueConfig {
+    /// Register the `build_queue` tool and start the worker pool.
+    /// Default: `false`.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Builds processed concurrently. Default: `2`.
+    #[serde(default = "default_factory_queue_workers")]
+    pub workers: usize,
+    /// Queued plus running jobs allowed per user. Default: `5`.
+    #[serde(default = "default_factory_queue_max_pending_per_user")]
+    pub max_pending_per_user: usize,
+}
+
+fn default_factory_queue_workers() -> usize {
+    2
+}
+
+fn default_factory_queue_max_pending_per_user() -> usize {
+    5
+}
+
+impl Default for FactoryQueueConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            workers: default_factory_queue_workers(),
+            max_pending_per_user: default_factory_queue_max_pending_per_user(),
+        }
+    }
+}
+
+/// Coordination bus (`[factory.bus]`). With a Redis or NATS backend,
+/// several ZeroBuild processes share the factory blackboard, and processes
+/// running stage workers pick up the completion stages of builds started
+/// anywhere.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct FactoryBusConfig {
+    /// `"memory"` (this process only), `"redis"` (Redis Streams, needs the
+    /// `bus-redis` feature) or `"nats"` (NATS JetStream, needs the
+    /// `bus-nats` feature). Default: `"memory"`.
+    #[serde(default = "default_factory_bus_backend")]
+    pub backend: String,
+    /// Server URL, e.g. `redis://127.0.0.1:6379` or `nats://127.0.0.1:4222`.
+    /// Required for the redis and nats backends.
+    #[serde(default)]
+    pub url: Option<String>,
+    /// Prefix of every key, stream and bucket, so deployments can share a
+    /// server. Letters, digits, `-` and `_`. Default: `"zerobuild"`.
+    #[serde(default = "default_factory_bus_namespace")]
+    pub namespace: String,
+    /// Send this process's completion stages (PRD, design, tests, reviews)
+    /// to the stage queue instead of running them here. Tool-using stages
+    /// always run next to the sandbox. Default: `false`.
+    #[serde(default)]
+    pub distribute_stages: bool,
+    /// Stage workers this process runs for the queue. Default: `0`.
+    #[serde(default)]
+    pub workers: usize,
+    /// Seconds a distributed stage may wait in the queue and run before the
+    /// build gives up on it. Default: `900`.
+    #[serde(default = "default_factory_bus_stage_timeout_secs")]
+    pub stage_timeout_secs: u64,
+}
+
+fn default_factory_bus_backend() -> String {
+    "memory".into()
+}
+
+fn default_factory_bus_namespace() -> String {
+    "zerobuild".into()
+}
+
+fn default_factory_bus_stage_timeout_secs() -> u64 {
+    900
+}
+
+impl Default for FactoryBusConfig {
+    fn default() -> Self {
+        Self {
+            backend: default_factory_bus_backend(),
+            url: None,
+            namespace: default_factory_bus_namespace(),
+            distribute_stages: false,
+            workers: 0,
+            stage_timeout_secs: default_factory_bus_stage_timeout_secs(),
+        }
+    }
+}
+
+/// Issue-to-build trigger (`[factory.issues]`). Open issues carrying the
+/// label in the listed repositories are queued as factory builds; needs
+/// `[factory.queue]` and a connected GitHub account.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct FactoryIssuesConfig {
+    /// Watch the repositories for labeled issues. Default: `false`.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Repositories to watch, as `owner/repo`. Default: empty.
+    #[serde(default)]
+    pub repos: Vec<String>,
+    /// Label that turns an issue into a build. Default: `"zerobuild"`.
+    #[serde(default = "default_factory_issues_label")]
+    pub label: String,
+    /// Seconds between polls of the repositories. Default: `120`.
+    #[serde(default = "default_factory_issues_poll_secs")]
+    pub poll_secs: u64,
+}
+
+fn default_factory_issues_label() -> String {
+    "zerobuild".into()
+}
+
+fn default_factory_issues_poll_secs() -> u64 {
+    120
+}
+
+impl Default for FactoryIssuesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            repos: Vec::new(),
+            label: default_factory_issues_label(),
+            poll_secs: default_factory_issues_poll_secs(),
+        }
+    }
+}
+
+/// Issue triage for existing repositories (`[factory.triage]`). New issues
+/// in the listed repositories are classified, labeled and, when a bug report
+/// lacks reproduction details, asked for them; needs a connected GitHub
+/// account.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct FactoryTriageConfig {
+    /// Triage new issues of the repositories. Default: `false`.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Repositories to watch, as `owner/repo`. Default: empty.
+    #[serde(default)]
+    pub repos: Vec<String>,
+    /// Seconds between polls of the repositories. Default: `120`.
+    #[serde(default = "default_factory_issues_poll_secs")]
+    pub poll_secs: u64,
+    /// Model that classifies issues. Default: `default_model`.
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Comment on bug reports that lack reproduction details, asking for
+    /// them. Default: `true`.
+    #[serde(default = "default_true")]
+    pub ask_for_details: bool,
+    /// Add the `[factory.issues]` label to complete bug reports with a
+    /// trivially-scoped fix, so the issue-to-build trigger drafts the fix in
+    /// a sandbox and opens a pull request. Default: `false`.
+    #[serde(default)]
+    pub draft_fixes: bool,
+}
+
+impl Default for FactoryTriageConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            repos: Vec::new(),
+            poll_secs: default_factory_issues_poll_secs(),
+            model: None,
+            ask_for_details: true,
+            draft_fixes: false,
+        }
+    }
+}
+
+/// Pull request review bot (`[factory.review]`). New pull requests of the
+/// listed repositories get a checklist review from a model, posted with
+/// inline comments on the diff; needs a connected GitHub account.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct FactoryReviewConfig {
+    /// Review new pull requests of the repositories. Default: `false`.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Repositories to review, keyed by `owner/repo`. Default: empty.
+    #[serde(default)]
+    pub repos: HashMap<String, ReviewRepoConfig>,
+    /// Seconds between polls of the repositories. Default: `120`.
+    #[serde(default = "default_factory_issues_poll_secs")]
+    pub poll_secs: u64,
+    /// Model that reviews the diffs. Default: `default_model`.
+    #[serde(default)]
+    pub model: Option<String>,
+}
+
+impl Default for FactoryReviewConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            repos: HashMap::new(),
+            poll_secs: default_factory_issues_poll_secs(),
+            model: None,
+        }
+    }
+}
+
+/// How the pull requests of one repository are reviewed
+/// (`[factory.review.repos."owner/repo"]`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ReviewRepoConfig {
+    /// Least severe finding posted as an inline comment. Default: `"medium"`.
+    #[serde(default = "default_review_comment_at")]
+    pub comment_at: ReviewSeverity,
+    /// Least severe finding that makes the review request changes; reviews
+    /// without one only comment. Default: `"high"`.
+    #[serde(default = "default_review_request_changes_at")]
+    pub request_changes_at: ReviewSeverity,
+    /// Checklist hashtags every review answers. Default: `#correctness`,
+    /// `#security`, `#tests`, `#performance`.
+    #[serde(default = "default_review_checklist")]
+    pub checklist: Vec<String>,
+}
+
+fn default_review_comment_at() -> ReviewSeverity {
+    ReviewSeverity::Medium
+}
+
+fn default_review_request_changes_at() -> ReviewSeverity {
+    ReviewSeverity::High
+}
+
+fn default_review_checklist() -> Vec<String> {
+    ["#correctness", "#security", "#tests", "#performance"]
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
+
+impl Default for ReviewRepoConfig {
+    fn default() -> Self {
+        Self {
+            comment_at: default_review_comment_at(),
+            request_changes_at: default_review_request_changes_at(),
+            checklist: default_review_checklist(),
+        }
+    }
+}
+
+/// Severity of a review finding, least severe first.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum ReviewSeverity {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl ReviewSeverity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+            Self::Critical => "critical",
+        }
+    }
+}
+
+/// Optional factory pipeline stages (`[factory.stages]`). Analysis and the
+/// parallel build always run.
+// One independent on/off switch per stage: these are the keys of the
+// `[factory.stages]` table that profiles and project overrides set.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct FactoryStagesConfig {
+    /// Run the developer-tester loop until tests pass. Default: `true`.
+    #[serde(default = "default_true")]
+    pub integration_loop: bool,
+    /// Let the DevOps agent push the result to GitHub. Default: `true`.
+    #[serde(default = "default_true")]
+    pub deployment: bool,
+    /// Generate README.md, docs/API.md and docs/ARCHITECTURE.md from the
+    /// source before deployment. Default: `true`.
+    #[serde(default = "default_true")]
+    pub docs: bool,
+    /// Review the project against an OWASP-style security checklist before
+    /// deployment; failed items block the push. Default: `false`.
+    #[serde(default)]
+    pub security_review: bool,
+    /// Audit the running app with axe-core through the `browser` tool and
+    /// have the developer fix the violations. Default: `false`.
+    #[serde(default)]
+    pub accessibility: bool,
+}
+
+impl Default for FactoryStagesConfig {
+    fn default() -> Self {
+        Self {
+            integration_loop: true,
+            deployment: true,
+            docs: true,
+            security_review: false,
+            accessibility: false,
+        }
+    }
+}
+
+/// Memory namespace shared between factory agents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MemoryNamespace {
+    /// Visible for the current `factory_build` run only.
+    Session,
+    /// Persists across builds of the same project.
+    Project,
+    /// Persists across all projects of the requesting user.
+    User,
+    /// Shared by every build.
+    Global,
+}
+
+/// Namespaces a factory role may read and write.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct MemoryNamespaceAccess {
+    #[serde(default)]
+    pub read: Vec<MemoryNamespace>,
+    #[serde(default)]
+    pub write: Vec<MemoryNamespace>,
+}
+
+/// Factory shared memory (`[factory.memory]`).
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FactoryMemoryConfig {
+    /// Give factory agents the `factory_memory` tool and inject readable
+    /// facts into their prompts. Default: `true`.
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Per-role access overrides keyed by role name (e.g. `"tester"`).
+    /// Roles without an entry use the built-in defaults.
+    #[serde(default)]
+    pub permissions: HashMap<String, MemoryNamespaceAccess>,
+}
+
+impl Default for FactoryMemoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            permissions: HashMap::new(),
         }
     }
 }
@@ -504,6 +990,22 @@ fn default_transcription_max_duration_secs() -> u64 {
     120
 }
 
+fn default_tts_api_url() -> String {
+    "https://api.openai.com/v1/audio/speech".into()
+}
+
+fn default_tts_model() -> String {
+    "gpt-4o-mini-tts".into()
+}
+
+fn default_tts_voice() -> String {
+    "alloy".into()
+}
+
+fn default_voice_summary_max_chars() -> usize {
+    400
+}
+
 /// Voice transcription configuration (Whisper API via Groq).
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TranscriptionConfig {
@@ -522,6 +1024,22 @@ pub struct TranscriptionConfig {
     /// Maximum voice duration in seconds (messages longer than this are skipped).
     #[serde(default = "default_transcription_max_duration_secs")]
     pub max_duration_secs: u64,
+    /// Answer voice messages with a short spoken summary after the text reply
+    /// (Telegram). Uses an OpenAI-compatible speech API and `OPENAI_API_KEY`.
+    #[serde(default)]
+    pub voice_replies: bool,
+    /// Speech API endpoint URL for voice replies.
+    #[serde(default = "default_tts_api_url")]
+    pub tts_api_url: String,
+    /// Speech model for voice replies.
+    #[serde(default = "default_tts_model")]
+    pub tts_model: String,
+    /// Voice for voice replies.
+    #[serde(default = "default_tts_voice")]
+    pub tts_voice: String,
+    /// Longest spoken summary, in characters.
+    #[serde(default = "default_voice_summary_max_chars")]
+    pub voice_summary_max_chars: usize,
 }
 
 impl Default for TranscriptionConfig {
@@ -532,12 +1050,18 @@ impl Default for TranscriptionConfig {
             model: default_transcription_model(),
             language: None,
             max_duration_secs: default_transcription_max_duration_secs(),
+            voice_replies: false,
+            tts_api_url: default_tts_api_url(),
+            tts_model: default_tts_model(),
+            tts_voice: default_tts_voice(),
+            voice_summary_max_chars: default_voice_summary_max_chars(),
         }
     }
 }
 
 /// Agent orchestration configuration (`[agent]` section).
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct AgentConfig {
     /// When true: bootstrap_max_chars=6000, rag_chunk_limit=2. Use for 13B or smaller models.
     #[serde(default)]
@@ -568,6 +1092,143 @@ pub struct AgentConfig {
     /// Tool dispatch strategy (e.g. `"auto"`). Default: `"auto"`.
     #[serde(default = "default_agent_tool_dispatcher")]
     pub tool_dispatcher: String,
+    /// Mark the system prompt, tool schemas and conversation tail as
+    /// cacheable for providers with explicit prompt caching (Anthropic), so
+    /// the static prefix is not billed in full every turn. Default: `true`.
+    #[serde(default = "default_true")]
+    pub prompt_caching: bool,
+    /// Deterministic mode: pin the sampling seed of every request that has
+    /// none and leave the current date and time out of system prompts, so
+    /// runs can be reproduced. Default: `false`.
+    #[serde(default)]
+    pub deterministic: bool,
+    /// Phase-aware tool exposure (`[agent.phases]`).
+    #[serde(default)]
+    pub phases: AgentPhasesConfig,
+}
+
+/// Phase of a session; decides which tool schemas the model is sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionPhase {
+    /// Thinking, research and planning.
+    #[default]
+    Planning,
+    /// Sandbox and file work.
+    Build,
+    /// GitHub and deployment.
+    Delivery,
+}
+
+impl SessionPhase {
+    pub const ALL: [Self; 3] = [Self::Planning, Self::Build, Self::Delivery];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Planning => "planning",
+            Self::Build => "build",
+            Self::Delivery => "delivery",
+        }
+    }
+
+    pub fn parse(raw: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|phase| phase.as_str().eq_ignore_ascii_case(raw.trim()))
+    }
+}
+
+/// Tools exposed per session phase (`[agent.phases]`). Entries are tool
+/// names; a trailing `*` matches a prefix. Tools no phase lists are exposed
+/// in every phase.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct AgentPhasesConfig {
+    /// Send only the tool schemas of the session's phase. Default: `false`.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Phase new sessions start in. Default: `planning`.
+    #[serde(default)]
+    pub initial: SessionPhase,
+    /// Tools of the planning phase.
+    #[serde(default = "default_planning_phase_tools")]
+    pub planning: Vec<String>,
+    /// Tools of the build phase.
+    #[serde(default = "default_build_phase_tools")]
+    pub build: Vec<String>,
+    /// Tools of the delivery phase.
+    #[serde(default = "default_delivery_phase_tools")]
+    pub delivery: Vec<String>,
+}
+
+impl AgentPhasesConfig {
+    /// Tool patterns of `phase`.
+    pub fn tools(&self, phase: SessionPhase) -> &[String] {
+        match phase {
+            SessionPhase::Planning => &self.planning,
+            SessionPhase::Build => &self.build,
+            SessionPhase::Delivery => &self.delivery,
+        }
+    }
+}
+
+fn default_planning_phase_tools() -> Vec<String> {
+    [
+        "think",
+        "web_search_tool",
+        "web_fetch",
+        "product_advisor",
+        "github_read_repo",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
+fn default_build_phase_tools() -> Vec<String> {
+    [
+        "sandbox_*",
+        "setup_project",
+        "project_info",
+        "codebase_search",
+        "checkpoint",
+        "get_log",
+        "generate_docs",
+        "shell",
+        "file_*",
+        "apply_patch",
+        "glob_search",
+        "content_search",
+        "git_operations",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
+fn default_delivery_phase_tools() -> Vec<String> {
+    [
+        "github_*",
+        "deploy_changes",
+        "ship_it",
+        "sandbox_save_snapshot",
+        "sandbox_get_preview_url",
+        "sandbox_get_public_url",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
+impl Default for AgentPhasesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            initial: SessionPhase::default(),
+            planning: default_planning_phase_tools(),
+            build: default_build_phase_tools(),
+            delivery: default_delivery_phase_tools(),
+        }
+    }
 }
 
 fn default_agent_max_tool_iterations() -> usize {
@@ -590,6 +1251,9 @@ impl Default for AgentConfig {
             max_history_messages: default_agent_max_history_messages(),
             parallel_tools: false,
             tool_dispatcher: default_agent_tool_dispatcher(),
+            prompt_caching: true,
+            deterministic: false,
+            phases: AgentPhasesConfig::default(),
         }
     }
 }
@@ -640,6 +1304,36 @@ impl Default for SkillsConfig {
     }
 }
 
+/// Language of user-facing channel messages (`[i18n]` section).
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct I18nConfig {
+    /// Locale used when neither the user nor the channel has one: `en`,
+    /// `es`, `pt`, `ru`, `zh` or `vi`.
+    #[serde(default = "default_i18n_locale")]
+    pub default_locale: String,
+    /// Per-channel locale overriding `default_locale`, e.g. `telegram = "ru"`.
+    #[serde(default)]
+    pub channels: HashMap<String, String>,
+    /// Use the language the user's client reports (Telegram `language_code`)
+    /// when the user hasn't picked one with `/lang`.
+    #[serde(default = "default_true")]
+    pub detect: bool,
+}
+
+fn default_i18n_locale() -> String {
+    "en".into()
+}
+
+impl Default for I18nConfig {
+    fn default() -> Self {
+        Self {
+            default_locale: default_i18n_locale(),
+            channels: HashMap::new(),
+            detect: true,
+        }
+    }
+}
+
 /// Multimodal (image) handling configuration (`[multimodal]` section).
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct MultimodalConfig {
@@ -652,12 +1346,29 @@ pub struct MultimodalConfig {
     /// Allow fetching remote image URLs (http/https). Disabled by default.
     #[serde(default)]
     pub allow_remote_fetch: bool,
+    /// Longest edge in pixels; larger PNG/JPEG images are downscaled before
+    /// sending. `0` disables resizing.
+    #[serde(default = "default_multimodal_max_dimension")]
+    pub max_dimension: u32,
+    /// Approximate token budget shared by all images in one request. Each
+    /// image is downscaled to its share (estimated at width × height / 750).
+    /// `0` disables the budget.
+    #[serde(default = "default_multimodal_max_image_tokens")]
+    pub max_image_tokens: usize,
 }
 
 fn default_multimodal_max_images() -> usize {
     4
 }
 
+fn default_multimodal_max_dimension() -> u32 {
+    1568
+}
+
+fn default_multimodal_max_image_tokens() -> usize {
+    6400
+}
+
 fn default_multimodal_max_image_size_mb() -> usize {
     5
 }
@@ -677,6 +1388,8 @@ impl Default for MultimodalConfig {
             max_images: default_multimodal_max_images(),
             max_image_size_mb: default_multimodal_max_image_size_mb(),
             allow_remote_fetch: false,
+            max_dimension: default_multimodal_max_dimension(),
+            max_image_tokens: default_multimodal_max_image_tokens(),
         }
     }
 }
@@ -1856,10 +2569,207 @@ pub struct MemoryConfig {
     /// None = wait indefinitely (default). Recommended max: 300.
     #[serde(default)]
     pub sqlite_open_timeout_secs: Option<u64>,
+
+    // ── User-scoped memory limits ──────────────────────────────
+    /// Max per-user memory backends kept open at once; least recently used
+    /// users are closed beyond this (default: 128)
+    #[serde(default = "default_max_resident_users")]
+    pub max_resident_users: usize,
+    /// Max entries stored per user before eviction (0 = unlimited, default: 500)
+    #[serde(default = "default_user_max_entries")]
+    pub user_max_entries: usize,
+    /// Max content bytes stored per user before eviction (0 = unlimited, default: 8 MiB)
+    #[serde(default = "default_user_max_bytes")]
+    pub user_max_bytes: usize,
+    /// Which entries to evict when a per-user quota is exceeded
+    #[serde(default)]
+    pub user_eviction_policy: MemoryEvictionPolicy,
+
+    // ── TTL and relevance decay ────────────────────────────────
+    /// Per-category expiry and recall decay (`[memory.retention]`)
+    #[serde(default)]
+    pub retention: MemoryRetentionConfig,
+
+    // ── Consolidation ──────────────────────────────────────────
+    /// Periodic merging of near-duplicate entries (`[memory.consolidation]`)
+    #[serde(default)]
+    pub consolidation: MemoryConsolidationConfig,
 }
 
-fn default_embedding_provider() -> String {
-    "none".into()
+/// Memory consolidation job (`[memory.consolidation]`).
+///
+/// Clusters near-duplicate entries by embedding similarity (token overlap
+/// when no embedding provider is configured), asks a model to merge each
+/// cluster into one canonical entry, and archives the originals under
+/// `memory/archive/`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct MemoryConsolidationConfig {
+    /// Run consolidation periodically from the daemon. Default: `false`.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Hours between consolidation passes. Default: `24`.
+    #[serde(default = "default_consolidation_interval_hours")]
+    pub interval_hours: u64,
+    /// Minimum similarity (0.0–1.0) for two entries to share a cluster. Default: `0.85`.
+    #[serde(default = "default_consolidation_similarity")]
+    pub similarity_threshold: f64,
+    /// Smallest cluster worth merging. Default: `3`.
+    #[serde(default = "default_consolidation_min_cluster")]
+    pub min_cluster_size: usize,
+    /// Model used to write canonical entries; supports `hint:<name>` routes.
+    /// Defaults to `default_model` — point this at a cheap model.
+    #[serde(default)]
+    pub model: Option<String>,
+}
+
+fn default_consolidation_interval_hours() -> u64 {
+    24
+}
+fn default_consolidation_similarity() -> f64 {
+    0.85
+}
+fn default_consolidation_min_cluster() -> usize {
+    3
+}
+
+impl Default for MemoryConsolidationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_hours: default_consolidation_interval_hours(),
+            similarity_threshold: default_consolidation_similarity(),
+            min_cluster_size: default_consolidation_min_cluster(),
+            model: None,
+        }
+    }
+}
+
+/// Expiry and decay for one memory category.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct CategoryRetentionConfig {
+    /// Entries expire this many days after their last write (0 = never)
+    #[serde(default)]
+    pub ttl_days: u32,
+    /// Recall score halves every this many days since last write (0 = no decay)
+    #[serde(default)]
+    pub half_life_days: f64,
+}
+
+impl CategoryRetentionConfig {
+    const fn new(ttl_days: u32, half_life_days: f64) -> Self {
+        Self {
+            ttl_days,
+            half_life_days,
+        }
+    }
+}
+
+/// Memory TTL and relevance decay configuration (`[memory.retention]`).
+///
+/// Stale facts (old preferences, dead sandbox IDs) lose recall score as they
+/// age and are deleted once their TTL passes. Applies to sqlite-based backends.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct MemoryRetentionConfig {
+    /// Long-term facts. Default: never expire, no decay.
+    #[serde(default = "default_core_retention")]
+    pub core: CategoryRetentionConfig,
+    /// Daily session logs. Default: no TTL, 14-day half-life.
+    #[serde(default = "default_daily_retention")]
+    pub daily: CategoryRetentionConfig,
+    /// Conversation context. Default: no TTL, 7-day half-life.
+    #[serde(default = "default_conversation_retention")]
+    pub conversation: CategoryRetentionConfig,
+    /// All custom categories. Default: no TTL, 30-day half-life.
+    #[serde(default = "default_custom_retention")]
+    pub custom: CategoryRetentionConfig,
+    /// Learned skills (the `skill` category), kept apart from `custom` so
+    /// proven procedures do not fade. Default: never expire, no decay.
+    #[serde(default = "default_skill_retention")]
+    pub skill: CategoryRetentionConfig,
+    /// Minutes between background compaction passes that delete expired
+    /// entries when the daemon is running (0 = disabled). Default: 60.
+    #[serde(default = "default_compaction_interval_minutes")]
+    pub compaction_interval_minutes: u64,
+}
+
+impl MemoryRetentionConfig {
+    /// Retention settings for the given category.
+    pub fn for_category(
+        &self,
+        category: &crate::memory::MemoryCategory,
+    ) -> CategoryRetentionConfig {
+        use crate::memory::MemoryCategory;
+        match category {
+            MemoryCategory::Core => self.core,
+            MemoryCategory::Daily => self.daily,
+            MemoryCategory::Conversation => self.conversation,
+            MemoryCategory::Custom(name)
+                if name == crate::skills::learned::LEARNED_SKILL_CATEGORY =>
+            {
+                self.skill
+            }
+            MemoryCategory::Custom(_) => self.custom,
+        }
+    }
+}
+
+fn default_core_retention() -> CategoryRetentionConfig {
+    CategoryRetentionConfig::new(0, 0.0)
+}
+fn default_daily_retention() -> CategoryRetentionConfig {
+    CategoryRetentionConfig::new(0, 14.0)
+}
+fn default_conversation_retention() -> CategoryRetentionConfig {
+    CategoryRetentionConfig::new(0, 7.0)
+}
+fn default_custom_retention() -> CategoryRetentionConfig {
+    CategoryRetentionConfig::new(0, 30.0)
+}
+fn default_skill_retention() -> CategoryRetentionConfig {
+    CategoryRetentionConfig::new(0, 0.0)
+}
+fn default_compaction_interval_minutes() -> u64 {
+    60
+}
+
+impl Default for MemoryRetentionConfig {
+    fn default() -> Self {
+        Self {
+            core: default_core_retention(),
+            daily: default_daily_retention(),
+            conversation: default_conversation_retention(),
+            custom: default_custom_retention(),
+            skill: default_skill_retention(),
+            compaction_interval_minutes: default_compaction_interval_minutes(),
+        }
+    }
+}
+
+/// Eviction order for per-user memory quotas (`[memory].user_eviction_policy`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MemoryEvictionPolicy {
+    /// Evict the oldest entries (by creation time) first.
+    #[default]
+    Oldest,
+    /// Evict the lowest-scoring entries first. The score is a category
+    /// weight (conversation 1, daily 2, custom 4, core 8) decayed by age with
+    /// the category's `[memory.retention]` half-life.
+    LowestScore,
+}
+
+fn default_max_resident_users() -> usize {
+    128
+}
+fn default_user_max_entries() -> usize {
+    500
+}
+fn default_user_max_bytes() -> usize {
+    8 * 1024 * 1024
+}
+
+fn default_embedding_provider() -> String {
+    "none".into()
 }
 fn default_hygiene_enabled() -> bool {
     true
@@ -1925,6 +2835,12 @@ impl Default for MemoryConfig {
             snapshot_on_hygiene: false,
             auto_hydrate: true,
             sqlite_open_timeout_secs: None,
+            max_resident_users: default_max_resident_users(),
+            user_max_entries: default_user_max_entries(),
+            user_max_bytes: default_user_max_bytes(),
+            user_eviction_policy: MemoryEvictionPolicy::default(),
+            retention: MemoryRetentionConfig::default(),
+            consolidation: MemoryConsolidationConfig::default(),
         }
     }
 }
@@ -1957,6 +2873,20 @@ pub struct ObservabilityConfig {
     /// Maximum entries retained when runtime_trace_mode = "rolling".
     #[serde(default = "default_runtime_trace_max_entries")]
     pub runtime_trace_max_entries: usize,
+
+    /// Record each agent session (LLM requests and responses, tool calls) as
+    /// a replayable JSONL file for `zerobuild replay`.
+    #[serde(default)]
+    pub record_sessions: bool,
+
+    /// Session recordings directory. Relative paths are resolved under workspace_dir.
+    #[serde(default = "default_recordings_dir")]
+    pub recordings_dir: String,
+
+    /// Post a Markdown report of the session to the channel when a
+    /// `factory_build` call finishes. Needs `record_sessions`.
+    #[serde(default)]
+    pub post_build_reports: bool,
 }
 
 impl Default for ObservabilityConfig {
@@ -1968,6 +2898,9 @@ impl Default for ObservabilityConfig {
             runtime_trace_mode: default_runtime_trace_mode(),
             runtime_trace_path: default_runtime_trace_path(),
             runtime_trace_max_entries: default_runtime_trace_max_entries(),
+            record_sessions: false,
+            recordings_dir: default_recordings_dir(),
+            post_build_reports: false,
         }
     }
 }
@@ -1984,6 +2917,47 @@ fn default_runtime_trace_max_entries() -> usize {
     200
 }
 
+fn default_recordings_dir() -> String {
+    "state/recordings".to_string()
+}
+
+// ── Logging ──────────────────────────────────────────────────────
+
+/// Log output (`[logging]`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct LoggingConfig {
+    /// Filter used when `RUST_LOG` is unset, e.g. `info` or
+    /// `zerobuild::agent=debug,info`. Default: `info`.
+    pub level: String,
+
+    /// `text` (human readable) or `json` (one object per line, with the
+    /// fields of the enclosing session and tool spans). Default: `text`.
+    pub format: String,
+
+    /// Directory for log files. Relative paths are resolved under
+    /// `workspace_dir`. Empty (default) logs to stdout only.
+    pub dir: String,
+
+    /// Log file rotation: `hourly`, `daily` or `never`. Default: `daily`.
+    pub rotation: String,
+
+    /// Rotated log files to keep; `0` keeps all. Default: `7`.
+    pub max_files: usize,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            level: "info".into(),
+            format: "text".into(),
+            dir: String::new(),
+            rotation: "daily".into(),
+            max_files: 7,
+        }
+    }
+}
+
 // ── Hooks ────────────────────────────────────────────────────────
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -2177,6 +3151,16 @@ pub struct DockerRuntimeConfig {
     #[serde(default = "default_docker_image")]
     pub image: String,
 
+    /// Per-architecture images (`amd64`, `arm64`, ...) used instead of
+    /// `image` when the container platform matches.
+    #[serde(default)]
+    pub images: HashMap<String, String>,
+
+    /// Container platform passed to `docker run --platform`, e.g.
+    /// `linux/arm64`. Empty = the host's native platform.
+    #[serde(default)]
+    pub platform: String,
+
     /// Docker network mode (`none`, `bridge`, etc.).
     #[serde(default = "default_docker_network")]
     pub network: String,
@@ -2226,6 +3210,8 @@ impl Default for DockerRuntimeConfig {
     fn default() -> Self {
         Self {
             image: default_docker_image(),
+            images: HashMap::new(),
+            platform: String::new(),
             network: default_docker_network(),
             memory_limit_mb: default_docker_memory_limit_mb(),
             cpu_limit: default_docker_cpu_limit(),
@@ -2246,6 +3232,31 @@ impl Default for RuntimeConfig {
     }
 }
 
+// ── Local provider ───────────────────────────────────────────────
+
+/// Capability overrides for local model servers (`[local_provider]` section).
+///
+/// Applies to providers that run on the user's machine (`local`, `ollama`,
+/// `llamacpp`, `lmstudio`, `vllm`, `sglang`, `osaurus`). What a local server
+/// can do depends on the model it has loaded, so each flag left unset keeps
+/// the provider's built-in default.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct LocalProviderConfig {
+    /// Whether the loaded model supports native tool calling. When `false`,
+    /// tools are described in the system prompt instead.
+    #[serde(default)]
+    pub tool_calling: Option<bool>,
+
+    /// Whether the loaded model accepts image inputs.
+    #[serde(default)]
+    pub vision: Option<bool>,
+
+    /// Context window of the loaded model in tokens. Ollama also receives it
+    /// as `num_ctx`.
+    #[serde(default)]
+    pub max_context_tokens: Option<usize>,
+}
+
 // ── Reliability / supervision ────────────────────────────────────
 
 /// Reliability and supervision configuration (`[reliability]` section).
@@ -2282,6 +3293,129 @@ pub struct ReliabilityConfig {
     /// Max retries for cron job execution attempts.
     #[serde(default = "default_scheduler_retries")]
     pub scheduler_retries: u32,
+    /// Tool execution timeouts and circuit breaker (`[reliability.tools]`).
+    #[serde(default)]
+    pub tools: ToolReliabilityConfig,
+    /// Automatic recovery from known tool failures (`[reliability.recovery]`).
+    #[serde(default)]
+    pub recovery: RecoveryConfig,
+    /// Provider request rate limits shared by all sessions
+    /// (`[reliability.rate_limit]`).
+    #[serde(default)]
+    pub rate_limit: ProviderRateLimitConfig,
+}
+
+/// Tool execution limits (`[reliability.tools]`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ToolReliabilityConfig {
+    /// Timeout for a single tool call in seconds; `0` disables it.
+    /// Default: `600`.
+    #[serde(default = "default_tool_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Per-tool timeouts in seconds keyed by tool name (`0` = no limit).
+    /// Overrides `timeout_secs` and the built-in limits for sandbox commands
+    /// and sub-agents.
+    #[serde(default)]
+    pub timeouts: HashMap<String, u64>,
+    /// Consecutive timeouts or execution errors that disable a tool;
+    /// `0` disables the circuit breaker. Default: `3`.
+    #[serde(default = "default_tool_failure_threshold")]
+    pub failure_threshold: u32,
+    /// How long a tripped tool stays disabled, in seconds. Default: `120`.
+    #[serde(default = "default_tool_cooldown_secs")]
+    pub cooldown_secs: u64,
+}
+
+fn default_tool_timeout_secs() -> u64 {
+    600
+}
+
+fn default_tool_failure_threshold() -> u32 {
+    3
+}
+
+fn default_tool_cooldown_secs() -> u64 {
+    120
+}
+
+/// Automatic recovery playbooks (`[reliability.recovery]`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct RecoveryConfig {
+    /// Run a playbook before a failure with a known error code reaches the
+    /// model. Default: `true`.
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Error codes whose playbooks are skipped (e.g. `["auth_failed"]`).
+    #[serde(default)]
+    pub disabled: Vec<crate::error::ErrorCode>,
+}
+
+/// Per-provider token buckets shared by every concurrent session
+/// (`[reliability.rate_limit]`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ProviderRateLimitConfig {
+    /// Queue provider requests instead of letting concurrent sessions hit
+    /// the provider's rate limit together. Default: `false`.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Requests per minute allowed per provider. Default: `60`.
+    #[serde(default = "default_rate_limit_requests_per_minute")]
+    pub requests_per_minute: u32,
+    /// Requests that may be sent at once after an idle period. Default: `10`.
+    #[serde(default = "default_rate_limit_burst")]
+    pub burst: u32,
+    /// Per-provider `requests_per_minute` keyed by provider name (as in
+    /// `default_provider` or `fallback_providers`); `0` = unlimited.
+    #[serde(default)]
+    pub providers: HashMap<String, u32>,
+    /// Seconds a queued request waits before it is served ahead of the
+    /// round-robin order. Default: `30`.
+    #[serde(default = "default_rate_limit_starvation_secs")]
+    pub starvation_secs: u64,
+}
+
+fn default_rate_limit_requests_per_minute() -> u32 {
+    60
+}
+
+fn default_rate_limit_burst() -> u32 {
+    10
+}
+
+fn default_rate_limit_starvation_secs() -> u64 {
+    30
+}
+
+impl Default for ProviderRateLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            requests_per_minute: default_rate_limit_requests_per_minute(),
+            burst: default_rate_limit_burst(),
+            providers: HashMap::new(),
+            starvation_secs: default_rate_limit_starvation_secs(),
+        }
+    }
+}
+
+impl Default for RecoveryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            disabled: Vec::new(),
+        }
+    }
+}
+
+impl Default for ToolReliabilityConfig {
+    fn default() -> Self {
+        Self {
+            timeout_secs: default_tool_timeout_secs(),
+            timeouts: HashMap::new(),
+            failure_threshold: default_tool_failure_threshold(),
+            cooldown_secs: default_tool_cooldown_secs(),
+        }
+    }
 }
 
 fn default_provider_retries() -> u32 {
@@ -2320,6 +3454,9 @@ impl Default for ReliabilityConfig {
             channel_max_backoff_secs: default_channel_backoff_max_secs(),
             scheduler_poll_secs: default_scheduler_poll_secs(),
             scheduler_retries: default_scheduler_retries(),
+            tools: ToolReliabilityConfig::default(),
+            recovery: RecoveryConfig::default(),
+            rate_limit: ProviderRateLimitConfig::default(),
         }
     }
 }
@@ -2390,6 +3527,15 @@ pub struct ModelRouteConfig {
     /// Optional API key override for this route's provider
     #[serde(default)]
     pub api_key: Option<String>,
+    /// Temperature for requests on this route, overriding the caller's
+    #[serde(default)]
+    pub temperature: Option<f64>,
+    /// Nucleus sampling (`top_p`) for requests on this route
+    #[serde(default)]
+    pub top_p: Option<f64>,
+    /// Sampling seed for requests on this route, for providers that support one
+    #[serde(default)]
+    pub seed: Option<u64>,
 }
 
 // ── Embedding routing ───────────────────────────────────────────
@@ -2652,6 +3798,46 @@ pub struct ChannelsConfig {
     /// Default: 300s for on-device LLMs (Ollama) which are slower than cloud APIs.
     #[serde(default = "default_channel_message_timeout_secs")]
     pub message_timeout_secs: u64,
+    /// Read-only channels that receive live build activity
+    /// (`[[channels_config.observers]]`).
+    #[serde(default)]
+    pub observers: Vec<ObserverChannelConfig>,
+}
+
+/// A read-only "observer" chat (`[[channels_config.observers]]`): it
+/// receives build status, diffs and deploy notifications of every build,
+/// and its messages are never handed to the agent, so a public community
+/// channel can follow the work while control stays in a private one.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct ObserverChannelConfig {
+    /// Name of a configured channel, e.g. `"discord"`, `"telegram"`, `"slack"`.
+    pub channel: String,
+    /// Chat, channel or room id the activity is posted to (what the channel
+    /// uses as reply target).
+    pub target: String,
+    /// Activity posted. Default: all of `status`, `diff` and `deploy`.
+    #[serde(default = "default_observer_events")]
+    pub events: Vec<ObserverEvent>,
+}
+
+/// Kind of activity posted to an observer channel.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ObserverEvent {
+    /// Build stages, preview URLs and the build result.
+    Status,
+    /// Files changed since the previous snapshot.
+    Diff,
+    /// Pushes to GitHub and deployments with their URLs.
+    Deploy,
+}
+
+fn default_observer_events() -> Vec<ObserverEvent> {
+    vec![
+        ObserverEvent::Status,
+        ObserverEvent::Diff,
+        ObserverEvent::Deploy,
+    ]
 }
 
 impl ChannelsConfig {
@@ -2770,6 +3956,7 @@ impl Default for ChannelsConfig {
             nostr: None,
             clawdtalk: None,
             message_timeout_secs: default_channel_message_timeout_secs(),
+            observers: Vec::new(),
         }
     }
 }
@@ -2839,6 +4026,9 @@ pub struct DiscordConfig {
     /// Other messages in the guild are silently ignored.
     #[serde(default)]
     pub mention_only: bool,
+    /// Streaming mode: `partial` edits an in-progress reply as tokens arrive.
+    #[serde(default)]
+    pub stream_mode: StreamMode,
 }
 
 impl ChannelConfig for DiscordConfig {
@@ -3035,6 +4225,14 @@ pub struct WhatsAppConfig {
     /// Allowed phone numbers (E.164 format: +1234567890) or "*" for all
     #[serde(default)]
     pub allowed_numbers: Vec<String>,
+    /// Approved message template used for replies outside the 24-hour
+    /// customer service window (Cloud API mode). Its body must have one
+    /// variable, `{{1}}`, which receives the reply text.
+    #[serde(default)]
+    pub reply_template: Option<String>,
+    /// Language code of `reply_template` (default: `en_US`)
+    #[serde(default)]
+    pub reply_template_language: Option<String>,
 }
 
 impl ChannelConfig for WhatsAppConfig {
@@ -3109,6 +4307,18 @@ impl WhatsAppConfig {
         }
     }
 
+    /// `(name, language)` of the reply template, when one is configured.
+    pub fn reply_template_with_language(&self) -> Option<(String, String)> {
+        let name = self.reply_template.as_deref().map(str::trim)?;
+        let language = self
+            .reply_template_language
+            .as_deref()
+            .map(str::trim)
+            .filter(|language| !language.is_empty())
+            .unwrap_or("en_US");
+        (!name.is_empty()).then(|| (name.to_string(), language.to_string()))
+    }
+
     /// Check if this is a valid Cloud API config
     pub fn is_cloud_config(&self) -> bool {
         self.phone_number_id.is_some() && self.access_token.is_some() && self.verify_token.is_some()
@@ -3371,88 +4581,356 @@ impl Default for AuditConfig {
     }
 }
 
-/// DingTalk configuration for Stream Mode messaging
+// ── Guardrails ───────────────────────────────────────────────────
+
+/// Guardrails checked against tool arguments before every tool call.
+///
+/// Example `config.toml` section:
+/// ```toml
+/// [guardrails]
+/// command_action = "deny"
+/// blocked_command_patterns = ["\\bgit\\s+push\\s+--force\\b"]
+/// write_path_allowlist = ["project/**"]
+/// deploy_repo_allowlist = ["my-org/*"]
+/// deploy_action = "ask"
+/// ```
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
-pub struct DingTalkConfig {
-    /// Client ID (AppKey) from DingTalk developer console
-    pub client_id: String,
-    /// Client Secret (AppSecret) from DingTalk developer console
-    pub client_secret: String,
-    /// Allowed user IDs (staff IDs). Empty = deny all, "*" = allow all
-    #[serde(default)]
-    pub allowed_users: Vec<String>,
+#[serde(default)]
+pub struct GuardrailsConfig {
+    /// Enable guardrail checks. Default: `true`.
+    pub enabled: bool,
+
+    /// Extra regexes for blocked shell commands, checked in addition to the
+    /// built-in patterns (`curl | sh`, `rm -rf /`, fork bombs, disk wipes).
+    pub blocked_command_patterns: Vec<String>,
+
+    /// Action for blocked commands: `allow`, `deny`, or `ask`. Default: `deny`.
+    pub command_action: GuardrailAction,
+
+    /// Glob patterns that file-writing tools may write to. Empty = no restriction.
+    pub write_path_allowlist: Vec<String>,
+
+    /// Action for writes outside `write_path_allowlist`. Default: `deny`.
+    pub path_action: GuardrailAction,
+
+    /// Repositories deploy tools may push to: `repo`, `owner/repo`, or
+    /// `owner/*`. Empty = no restriction.
+    pub deploy_repo_allowlist: Vec<String>,
+
+    /// Action for deploys outside `deploy_repo_allowlist`. Default: `deny`.
+    pub deploy_action: GuardrailAction,
 }
 
-impl ChannelConfig for DingTalkConfig {
-    fn name() -> &'static str {
-        "DingTalk"
-    }
-    fn desc() -> &'static str {
-        "DingTalk Stream Mode"
+impl Default for GuardrailsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            blocked_command_patterns: Vec::new(),
+            command_action: GuardrailAction::Deny,
+            write_path_allowlist: Vec::new(),
+            path_action: GuardrailAction::Deny,
+            deploy_repo_allowlist: Vec::new(),
+            deploy_action: GuardrailAction::Deny,
+        }
     }
 }
 
-/// Napcat (OneBot v11 over WebSocket) channel configuration.
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
-pub struct NapcatConfig {
-    /// Napcat WebSocket endpoint (e.g. `ws://127.0.0.1:3001`)
-    #[serde(alias = "ws_url")]
-    pub websocket_url: String,
-    /// Optional HTTP API base URL. If omitted, derived from websocket_url.
-    #[serde(default)]
-    pub api_base_url: String,
-    /// Optional access token (Authorization Bearer token)
-    pub access_token: Option<String>,
-    /// Allowed user IDs. Empty = deny all, "*" = allow all
-    #[serde(default)]
-    pub allowed_users: Vec<String>,
+// ── Users ────────────────────────────────────────────────────────
+
+/// What a channel or gateway user may do.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum UserRole {
+    /// Every tool, no quotas; may change other users with `/user`.
+    Admin,
+    /// Build, sandbox and deploy tools within the daily quotas.
+    #[default]
+    Builder,
+    /// Chat and read-only tools; no builds, sandboxes or deploys.
+    Viewer,
 }
 
-impl ChannelConfig for NapcatConfig {
-    fn name() -> &'static str {
-        "Napcat"
+impl UserRole {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Admin => "admin",
+            Self::Builder => "builder",
+            Self::Viewer => "viewer",
+        }
     }
-    fn desc() -> &'static str {
-        "QQ via Napcat (OneBot)"
+
+    /// The role named `name`, case-insensitively.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "admin" => Some(Self::Admin),
+            "builder" => Some(Self::Builder),
+            "viewer" => Some(Self::Viewer),
+            _ => None,
+        }
     }
 }
 
-/// GitHub channel configuration (webhook + REST API).
-#[derive(Clone, Serialize, Deserialize, JsonSchema)]
-pub struct GitHubConfig {
-    /// GitHub token used for outbound API calls.
-    ///
-    /// Supports fine-grained PAT or installation token with `issues:write` / `pull_requests:write`.
-    pub access_token: String,
-    /// Optional webhook secret to verify `X-Hub-Signature-256`.
+/// Roles and daily quotas of users talking to the bot over channels and
+/// gateway-hosted channels, for bots open to a community.
+///
+/// Example `config.toml` section:
+/// ```toml
+/// [users]
+/// enabled = true
+/// default_role = "viewer"
+/// admins = ["telegram:123456789"]
+///
+/// [users.quotas]
+/// builds_per_day = 3
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct UsersConfig {
+    /// Enforce roles and quotas. Default: `false`.
     #[serde(default)]
-    pub webhook_secret: Option<String>,
-    /// Optional GitHub API base URL (for GHES).
-    /// Defaults to `https://api.github.com` when omitted.
+    pub enabled: bool,
+    /// Role of users no admin has assigned one. Default: `"builder"`.
     #[serde(default)]
-    pub api_base_url: Option<String>,
-    /// Allowed repositories (`owner/repo`), `owner/*`, or `*`.
-    /// Empty list denies all repositories.
+    pub default_role: UserRole,
+    /// Users that are always admins, as `<channel>:<sender>`.
     #[serde(default)]
-    pub allowed_repos: Vec<String>,
+    pub admins: Vec<String>,
+    /// Daily limits of builders (`[users.quotas]`).
+    #[serde(default)]
+    pub quotas: UserQuotasConfig,
 }
 
-impl std::fmt::Debug for GitHubConfig {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("GitHubConfig")
-            .field("access_token", &"[REDACTED]")
-            .field(
-                "webhook_secret",
-                &self.webhook_secret.as_ref().map(|_| "[REDACTED]"),
-            )
-            .field("api_base_url", &self.api_base_url)
-            .field("allowed_repos", &self.allowed_repos)
-            .finish()
+impl Default for UsersConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            default_role: UserRole::Builder,
+            admins: Vec::new(),
+            quotas: UserQuotasConfig::default(),
+        }
     }
 }
 
-impl ChannelConfig for GitHubConfig {
-    fn name() -> &'static str {
+/// Daily limits of a builder, reset at midnight UTC; `0` = unlimited.
+/// Admins can override them per user with `/user`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct UserQuotasConfig {
+    /// Successful `factory_build` runs and queued builds. Default: `5`.
+    #[serde(default = "default_user_builds_per_day")]
+    pub builds_per_day: u32,
+    /// Minutes spent in sandbox tools. Default: `120`.
+    #[serde(default = "default_user_sandbox_minutes_per_day")]
+    pub sandbox_minutes_per_day: u32,
+    /// Successful `github_push` and `ship_it` calls. Default: `3`.
+    #[serde(default = "default_user_deploys_per_day")]
+    pub deploys_per_day: u32,
+}
+
+fn default_user_builds_per_day() -> u32 {
+    5
+}
+
+fn default_user_sandbox_minutes_per_day() -> u32 {
+    120
+}
+
+fn default_user_deploys_per_day() -> u32 {
+    3
+}
+
+impl Default for UserQuotasConfig {
+    fn default() -> Self {
+        Self {
+            builds_per_day: default_user_builds_per_day(),
+            sandbox_minutes_per_day: default_user_sandbox_minutes_per_day(),
+            deploys_per_day: default_user_deploys_per_day(),
+        }
+    }
+}
+
+// ── Moderation ───────────────────────────────────────────────────
+
+/// Classifier that scores inbound requests (`[moderation] provider`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ModerationProvider {
+    /// Built-in patterns plus `blocked_patterns`, scored in-process.
+    #[default]
+    Local,
+    /// An OpenAI-compatible `/moderations` endpoint.
+    OpenAi,
+}
+
+/// What happens to a request that scores at or above its threshold.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ModerationAction {
+    /// Refuse the request; the agent never sees it.
+    #[default]
+    Block,
+    /// Let the request through and only record it in the audit log.
+    Flag,
+}
+
+/// Screening of inbound channel and gateway requests for abuse and illegal
+/// requests, before any sandbox or GitHub action.
+///
+/// Example `config.toml` section:
+/// ```toml
+/// [moderation]
+/// enabled = true
+/// provider = "openai"
+/// threshold = 0.7
+///
+/// [moderation.category_thresholds]
+/// "violence" = 0.9
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ModerationConfig {
+    /// Screen inbound requests. Default: `false`.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Classifier: `local` or `openai`. Default: `"local"`.
+    #[serde(default)]
+    pub provider: ModerationProvider,
+    /// API key of the `openai` provider; falls back to `OPENAI_API_KEY`.
+    /// Encrypted at rest like other secrets.
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// Base URL of the `openai` provider. Default: `"https://api.openai.com/v1"`.
+    #[serde(default = "default_moderation_api_url")]
+    pub api_url: String,
+    /// Model of the `openai` provider. Default: `"omni-moderation-latest"`.
+    #[serde(default = "default_moderation_model")]
+    pub model: String,
+    /// Score (`0.0`–`1.0`) at which a category flags a request.
+    /// Default: `0.5`.
+    #[serde(default = "default_moderation_threshold")]
+    pub threshold: f64,
+    /// Per-category thresholds overriding `threshold`, e.g. `"violence" = 0.9`.
+    #[serde(default)]
+    pub category_thresholds: HashMap<String, f64>,
+    /// Extra regexes the `local` provider flags as the `custom` category.
+    #[serde(default)]
+    pub blocked_patterns: Vec<String>,
+    /// `block` or `flag` (audit only). Default: `"block"`.
+    #[serde(default)]
+    pub action: ModerationAction,
+    /// Let requests through when the provider fails. Default: `true`.
+    #[serde(default = "default_true")]
+    pub fail_open: bool,
+}
+
+fn default_moderation_api_url() -> String {
+    "https://api.openai.com/v1".into()
+}
+
+fn default_moderation_model() -> String {
+    "omni-moderation-latest".into()
+}
+
+fn default_moderation_threshold() -> f64 {
+    0.5
+}
+
+impl Default for ModerationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            provider: ModerationProvider::Local,
+            api_key: None,
+            api_url: default_moderation_api_url(),
+            model: default_moderation_model(),
+            threshold: default_moderation_threshold(),
+            category_thresholds: HashMap::new(),
+            blocked_patterns: Vec::new(),
+            action: ModerationAction::Block,
+            fail_open: true,
+        }
+    }
+}
+
+/// DingTalk configuration for Stream Mode messaging
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DingTalkConfig {
+    /// Client ID (AppKey) from DingTalk developer console
+    pub client_id: String,
+    /// Client Secret (AppSecret) from DingTalk developer console
+    pub client_secret: String,
+    /// Allowed user IDs (staff IDs). Empty = deny all, "*" = allow all
+    #[serde(default)]
+    pub allowed_users: Vec<String>,
+}
+
+impl ChannelConfig for DingTalkConfig {
+    fn name() -> &'static str {
+        "DingTalk"
+    }
+    fn desc() -> &'static str {
+        "DingTalk Stream Mode"
+    }
+}
+
+/// Napcat (OneBot v11 over WebSocket) channel configuration.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NapcatConfig {
+    /// Napcat WebSocket endpoint (e.g. `ws://127.0.0.1:3001`)
+    #[serde(alias = "ws_url")]
+    pub websocket_url: String,
+    /// Optional HTTP API base URL. If omitted, derived from websocket_url.
+    #[serde(default)]
+    pub api_base_url: String,
+    /// Optional access token (Authorization Bearer token)
+    pub access_token: Option<String>,
+    /// Allowed user IDs. Empty = deny all, "*" = allow all
+    #[serde(default)]
+    pub allowed_users: Vec<String>,
+}
+
+impl ChannelConfig for NapcatConfig {
+    fn name() -> &'static str {
+        "Napcat"
+    }
+    fn desc() -> &'static str {
+        "QQ via Napcat (OneBot)"
+    }
+}
+
+/// GitHub channel configuration (webhook + REST API).
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GitHubConfig {
+    /// GitHub token used for outbound API calls.
+    ///
+    /// Supports fine-grained PAT or installation token with `issues:write` / `pull_requests:write`.
+    pub access_token: String,
+    /// Optional webhook secret to verify `X-Hub-Signature-256`.
+    #[serde(default)]
+    pub webhook_secret: Option<String>,
+    /// Optional GitHub API base URL (for GHES).
+    /// Defaults to `https://api.github.com` when omitted.
+    #[serde(default)]
+    pub api_base_url: Option<String>,
+    /// Allowed repositories (`owner/repo`), `owner/*`, or `*`.
+    /// Empty list denies all repositories.
+    #[serde(default)]
+    pub allowed_repos: Vec<String>,
+}
+
+impl std::fmt::Debug for GitHubConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GitHubConfig")
+            .field("access_token", &"[REDACTED]")
+            .field(
+                "webhook_secret",
+                &self.webhook_secret.as_ref().map(|_| "[REDACTED]"),
+            )
+            .field("api_base_url", &self.api_base_url)
+            .field("allowed_repos", &self.allowed_repos)
+            .finish()
+    }
+}
+
+impl ChannelConfig for GitHubConfig {
+    fn name() -> &'static str {
         "GitHub"
     }
     fn desc() -> &'static str {
@@ -3574,6 +5052,8 @@ impl Default for Config {
             default_model: Some("anthropic/claude-sonnet-4.6".to_string()),
             default_temperature: 0.7,
             observability: ObservabilityConfig::default(),
+            logging: LoggingConfig::default(),
+            local_provider: LocalProviderConfig::default(),
             autonomy: AutonomyConfig::default(),
             runtime: RuntimeConfig::default(),
             reliability: ReliabilityConfig::default(),
@@ -3594,6 +5074,7 @@ impl Default for Config {
             browser: BrowserConfig::default(),
             http_request: HttpRequestConfig::default(),
             multimodal: MultimodalConfig::default(),
+            i18n: I18nConfig::default(),
             web_search: WebSearchConfig::default(),
             proxy: ProxyConfig::default(),
             identity: IdentityConfig::default(),
@@ -3607,6 +5088,10 @@ impl Default for Config {
             sop: SopConfig::default(),
             factory: FactoryConfig::default(),
             zerobuild: ZerobuildConfig::default(),
+            guardrails: GuardrailsConfig::default(),
+            users: UsersConfig::default(),
+            moderation: ModerationConfig::default(),
+            profiles: HashMap::new(),
         }
     }
 }
@@ -3939,6 +5424,105 @@ fn has_ollama_cloud_credential(config_api_key: Option<&str>) -> bool {
 }
 
 impl Config {
+    /// Read, decrypt and validate the config file at `config_path`
+    /// (env overrides applied). Used at startup and by hot reload.
+    pub async fn load_from_file(config_path: &Path, workspace_dir: PathBuf) -> Result<Self> {
+        let contents = fs::read_to_string(&config_path)
+            .await
+            .context("Failed to read config file")?;
+        let mut config: Config = match crate::security::sops::decrypt_document(&contents)
+            .context("Failed to decrypt config file")?
+        {
+            Some(document) => toml::Value::Table(document)
+                .try_into()
+                .context("Failed to parse config file")?,
+            None => toml::from_str(&contents).context("Failed to parse config file")?,
+        };
+        // Set computed paths that are skipped during serialization
+        config.config_path = config_path.to_path_buf();
+        config.workspace_dir = workspace_dir;
+        let zerobuild_dir = config_path.parent().unwrap_or_else(|| Path::new("."));
+        let store = crate::security::SecretStore::new(zerobuild_dir, config.secrets.encrypt);
+        decrypt_optional_secret(&store, &mut config.api_key, "config.api_key")?;
+        decrypt_optional_secret(
+            &store,
+            &mut config.composio.api_key,
+            "config.composio.api_key",
+        )?;
+
+        decrypt_optional_secret(
+            &store,
+            &mut config.browser.computer_use.api_key,
+            "config.browser.computer_use.api_key",
+        )?;
+
+        decrypt_optional_secret(
+            &store,
+            &mut config.web_search.brave_api_key,
+            "config.web_search.brave_api_key",
+        )?;
+
+        decrypt_optional_secret(
+            &store,
+            &mut config.storage.provider.config.db_url,
+            "config.storage.provider.config.db_url",
+        )?;
+
+        decrypt_optional_secret(
+            &store,
+            &mut config.zerobuild.images.s3.secret_access_key,
+            "config.zerobuild.images.s3.secret_access_key",
+        )?;
+
+        decrypt_optional_secret(
+            &store,
+            &mut config.zerobuild.artifacts.s3.secret_access_key,
+            "config.zerobuild.artifacts.s3.secret_access_key",
+        )?;
+
+        decrypt_optional_secret(
+            &store,
+            &mut config.zerobuild.vercel.token,
+            "config.zerobuild.vercel.token",
+        )?;
+
+        decrypt_optional_secret(
+            &store,
+            &mut config.zerobuild.sandbox.kubernetes.token,
+            "config.zerobuild.sandbox.kubernetes.token",
+        )?;
+
+        decrypt_optional_secret(
+            &store,
+            &mut config.zerobuild.sandbox.fly.token,
+            "config.zerobuild.sandbox.fly.token",
+        )?;
+
+        decrypt_optional_secret(
+            &store,
+            &mut config.moderation.api_key,
+            "config.moderation.api_key",
+        )?;
+
+        for agent in config.agents.values_mut() {
+            decrypt_optional_secret(&store, &mut agent.api_key, "config.agents.*.api_key")?;
+        }
+
+        if let Some(ref mut ns) = config.channels_config.nostr {
+            decrypt_secret(
+                &store,
+                &mut ns.private_key,
+                "config.channels_config.nostr.private_key",
+            )?;
+        }
+
+        config.apply_env_overrides();
+        config.share_repo_allowlist();
+        config.validate()?;
+        crate::security::redaction::register_config_secrets(&config);
+        Ok(config)
+    }
+
     pub async fn load_or_init() -> Result<Self> {
         let (default_zerobuild_dir, default_workspace_dir) = default_config_and_workspace_dirs()?;
 
@@ -3972,54 +5556,7 @@ impl Config {
                 }
             }
 
-            let contents = fs::read_to_string(&config_path)
-                .await
-                .context("Failed to read config file")?;
-            let mut config: Config =
-                toml::from_str(&contents).context("Failed to parse config file")?;
-            // Set computed paths that are skipped during serialization
-            config.config_path = config_path.clone();
-            config.workspace_dir = workspace_dir;
-            let store = crate::security::SecretStore::new(&zerobuild_dir, config.secrets.encrypt);
-            decrypt_optional_secret(&store, &mut config.api_key, "config.api_key")?;
-            decrypt_optional_secret(
-                &store,
-                &mut config.composio.api_key,
-                "config.composio.api_key",
-            )?;
-
-            decrypt_optional_secret(
-                &store,
-                &mut config.browser.computer_use.api_key,
-                "config.browser.computer_use.api_key",
-            )?;
-
-            decrypt_optional_secret(
-                &store,
-                &mut config.web_search.brave_api_key,
-                "config.web_search.brave_api_key",
-            )?;
-
-            decrypt_optional_secret(
-                &store,
-                &mut config.storage.provider.config.db_url,
-                "config.storage.provider.config.db_url",
-            )?;
-
-            for agent in config.agents.values_mut() {
-                decrypt_optional_secret(&store, &mut agent.api_key, "config.agents.*.api_key")?;
-            }
-
-            if let Some(ref mut ns) = config.channels_config.nostr {
-                decrypt_secret(
-                    &store,
-                    &mut ns.private_key,
-                    "config.channels_config.nostr.private_key",
-                )?;
-            }
-
-            config.apply_env_overrides();
-            config.validate()?;
+            let config = Self::load_from_file(&config_path, workspace_dir).await?;
             tracing::info!(
                 path = %config.config_path.display(),
                 workspace = %config.workspace_dir.display(),
@@ -4042,6 +5579,7 @@ impl Config {
             }
 
             config.apply_env_overrides();
+            config.share_repo_allowlist();
             config.validate()?;
             tracing::info!(
                 path = %config.config_path.display(),
@@ -4064,6 +5602,10 @@ impl Config {
             anyhow::bail!("gateway.host must not be empty");
         }
 
+        // Runtime
+        crate::runtime::platform::validate_platform(&self.runtime.docker.platform)
+            .context("runtime.docker.platform")?;
+
         // Autonomy
         if self.autonomy.max_actions_per_hour == 0 {
             anyhow::bail!("autonomy.max_actions_per_hour must be greater than 0");
@@ -4133,48 +5675,463 @@ impl Config {
             }
         }
 
-        // Proxy (delegate to existing validation)
-        self.proxy.validate()?;
-
-        Ok(())
-    }
+        // Logging
+        crate::observability::logging::validate(&self.logging)?;
 
-    /// Apply environment variable overrides to config
-    pub fn apply_env_overrides(&mut self) {
-        // API Key: ZEROBUILD_API_KEY or API_KEY (generic)
-        if let Ok(key) = std::env::var("ZEROBUILD_API_KEY").or_else(|_| std::env::var("API_KEY")) {
-            if !key.is_empty() {
-                self.api_key = Some(key);
+        // Message languages
+        let locales = std::iter::once(("default_locale", &self.i18n.default_locale)).chain(
+            self.i18n
+                .channels
+                .iter()
+                .map(|(channel, locale)| (channel.as_str(), locale)),
+        );
+        for (field, locale) in locales {
+            if crate::i18n::Locale::parse(locale).is_none() {
+                anyhow::bail!(
+                    "i18n.{field} '{locale}' is not supported. Available: {}",
+                    crate::i18n::Locale::available()
+                );
             }
         }
-        // API Key: GLM_API_KEY overrides when provider is a GLM/Zhipu variant.
-        if self.default_provider.as_deref().is_some_and(is_glm_alias) {
-            if let Ok(key) = std::env::var("GLM_API_KEY") {
-                if !key.is_empty() {
-                    self.api_key = Some(key);
-                }
+
+        // Default project template
+        let template = self.zerobuild.default_template.trim();
+        if !template.is_empty() && crate::sandbox::project_template(template).is_none() {
+            let names: Vec<&str> = crate::sandbox::PROJECT_TEMPLATES
+                .iter()
+                .map(|(name, _)| *name)
+                .collect();
+            anyhow::bail!(
+                "zerobuild.default_template '{template}' is unknown. Available: {}",
+                names.join(", ")
+            );
+        }
+        for (name, template) in &self.zerobuild.templates {
+            if crate::sandbox::project_template(name).is_none() {
+                anyhow::bail!("zerobuild.templates.{name} is not a project template");
+            }
+            let prebuilt_dir = template.prebuilt_dir.trim();
+            if !prebuilt_dir.is_empty() && !std::path::Path::new(prebuilt_dir).is_absolute() {
+                anyhow::bail!("zerobuild.templates.{name}.prebuilt_dir must be an absolute path");
             }
         }
 
-        // API Key: ZAI_API_KEY overrides when provider is a Z.AI variant.
-        if self.default_provider.as_deref().is_some_and(is_zai_alias) {
-            if let Ok(key) = std::env::var("ZAI_API_KEY") {
-                if !key.is_empty() {
-                    self.api_key = Some(key);
-                }
+        let maintenance = &self.zerobuild.maintenance;
+        if maintenance.enabled {
+            if maintenance.interval_hours == 0 {
+                anyhow::bail!("zerobuild.maintenance.interval_hours must be greater than 0");
+            }
+            if maintenance.branch.trim().is_empty() {
+                anyhow::bail!("zerobuild.maintenance.branch must not be empty");
             }
         }
 
-        // Provider override precedence:
-        // 1) ZEROBUILD_PROVIDER always wins when set.
-        // 2) Legacy PROVIDER is only honored when config still uses the
-        //    default provider (openrouter) or provider is unset. This prevents
-        //    container defaults from overriding explicit custom providers.
-        if let Ok(provider) = std::env::var("ZEROBUILD_PROVIDER") {
-            if !provider.is_empty() {
-                self.default_provider = Some(provider);
+        // Sandbox providers
+        let providers = &self.zerobuild.sandbox.providers;
+        if providers.is_empty() {
+            anyhow::bail!("zerobuild.sandbox.providers must name at least one provider");
+        }
+        for (i, name) in providers.iter().enumerate() {
+            if !crate::sandbox::PROVIDERS.contains(&name.as_str()) {
+                anyhow::bail!(
+                    "zerobuild.sandbox.providers: '{name}' is unknown. Available: {}",
+                    crate::sandbox::PROVIDERS.join(", ")
+                );
             }
-        } else if let Ok(provider) = std::env::var("PROVIDER") {
+            if providers[..i].contains(name) {
+                anyhow::bail!("zerobuild.sandbox.providers lists '{name}' twice");
+            }
+        }
+        if self.zerobuild.sandbox.failover_after_errors == 0 {
+            anyhow::bail!("zerobuild.sandbox.failover_after_errors must be at least 1");
+        }
+        if providers.iter().any(|name| name == "kubernetes") {
+            let kube = &self.zerobuild.sandbox.kubernetes;
+            let api_url = kube.api_url.trim();
+            if !api_url.is_empty()
+                && !api_url.starts_with("https://")
+                && !api_url.starts_with("http://")
+            {
+                anyhow::bail!("zerobuild.sandbox.kubernetes.api_url must be an http(s) URL");
+            }
+            if !kube.namespace.is_empty()
+                && !kube
+                    .namespace
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+            {
+                anyhow::bail!(
+                    "zerobuild.sandbox.kubernetes.namespace must be lowercase letters, digits and '-'"
+                );
+            }
+            if kube.image.trim().is_empty() {
+                anyhow::bail!("zerobuild.sandbox.kubernetes.image must not be empty");
+            }
+            if kube.preview_domain.contains(['/', ':']) {
+                anyhow::bail!(
+                    "zerobuild.sandbox.kubernetes.preview_domain must be a bare domain, e.g. \
+                     preview.example.com"
+                );
+            }
+        }
+        if providers.iter().any(|name| name == "firecracker") {
+            let vm = &self.zerobuild.sandbox.firecracker;
+            if !matches!(vm.hypervisor.as_str(), "firecracker" | "cloud-hypervisor") {
+                anyhow::bail!(
+                    "zerobuild.sandbox.firecracker.hypervisor must be 'firecracker' or 'cloud-hypervisor'"
+                );
+            }
+            if vm.kernel.trim().is_empty() || vm.rootfs.trim().is_empty() {
+                anyhow::bail!(
+                    "zerobuild.sandbox.firecracker.kernel and rootfs must name the guest kernel and root filesystem image"
+                );
+            }
+            if vm.vcpus == 0 || vm.memory_mib < 128 {
+                anyhow::bail!(
+                    "zerobuild.sandbox.firecracker needs at least 1 vcpu and 128 MiB of memory"
+                );
+            }
+            if vm.tap_device.trim().is_empty() {
+                anyhow::bail!("zerobuild.sandbox.firecracker.tap_device must not be empty");
+            }
+            let host = vm.host_ip.parse::<std::net::Ipv4Addr>();
+            let guest = vm.guest_ip.parse::<std::net::Ipv4Addr>();
+            match (host, guest) {
+                (Ok(host), Ok(guest))
+                    if host != guest && host.octets()[..3] == guest.octets()[..3] => {}
+                _ => anyhow::bail!(
+                    "zerobuild.sandbox.firecracker.host_ip and guest_ip must be two IPv4 addresses \
+                     in one /24, e.g. 172.16.0.1 and 172.16.0.2"
+                ),
+            }
+        }
+
+        if providers.iter().any(|name| name == "fly") {
+            let fly = &self.zerobuild.sandbox.fly;
+            if !fly.api_url.starts_with("https://") && !fly.api_url.starts_with("http://") {
+                anyhow::bail!("zerobuild.sandbox.fly.api_url must be an http(s) URL");
+            }
+            if fly.org.trim().is_empty() || fly.image.trim().is_empty() {
+                anyhow::bail!("zerobuild.sandbox.fly.org and image must not be empty");
+            }
+            if !matches!(fly.cpu_kind.as_str(), "shared" | "performance") {
+                anyhow::bail!("zerobuild.sandbox.fly.cpu_kind must be 'shared' or 'performance'");
+            }
+            if fly.cpus == 0 || fly.memory_mb < 256 {
+                anyhow::bail!("zerobuild.sandbox.fly needs at least 1 CPU and 256 MB of memory");
+            }
+            let ports = &fly.preview_ports;
+            if ports.is_empty() || ports.contains(&0) {
+                anyhow::bail!("zerobuild.sandbox.fly.preview_ports must list at least one port");
+            }
+            for (i, port) in ports.iter().enumerate() {
+                if ports[..i].contains(port) {
+                    anyhow::bail!("zerobuild.sandbox.fly.preview_ports lists {port} twice");
+                }
+                // The first port is served on 80 and 443 of the app's hostname.
+                if i > 0 && matches!(port, 80 | 443) {
+                    anyhow::bail!(
+                        "zerobuild.sandbox.fly.preview_ports can list port {port} only first"
+                    );
+                }
+            }
+        }
+
+        // Deploy license
+        let spdx = self.zerobuild.license.spdx.trim();
+        if !spdx.is_empty() && crate::tools::licensing::license_key(spdx).is_none() {
+            anyhow::bail!(
+                "zerobuild.license.spdx '{spdx}' is not supported. Available: {}",
+                crate::tools::licensing::SUPPORTED_LICENSES.join(", ")
+            );
+        }
+
+        // Image hosting
+        let images = &self.zerobuild.images;
+        match images.backend {
+            ImageBackend::S3 => {
+                let s3 = &images.s3;
+                if s3.endpoint.trim().is_empty()
+                    || s3.bucket.trim().is_empty()
+                    || s3.access_key_id.trim().is_empty()
+                    || s3.secret_access_key.as_deref().is_none_or(str::is_empty)
+                {
+                    anyhow::bail!(
+                        "zerobuild.images.backend = \"s3\" needs endpoint, bucket, access_key_id \
+                         and secret_access_key in [zerobuild.images.s3]"
+                    );
+                }
+            }
+            ImageBackend::Imgur if images.imgur_client_id.trim().is_empty() => {
+                anyhow::bail!("zerobuild.images.backend = \"imgur\" needs imgur_client_id");
+            }
+            _ => {}
+        }
+
+        // Artifact storage
+        let artifacts = &self.zerobuild.artifacts;
+        if artifacts.backend == ArtifactBackend::S3 {
+            let s3 = &artifacts.s3;
+            if s3.endpoint.trim().is_empty()
+                || s3.bucket.trim().is_empty()
+                || s3.access_key_id.trim().is_empty()
+                || s3.secret_access_key.as_deref().is_none_or(str::is_empty)
+            {
+                anyhow::bail!(
+                    "zerobuild.artifacts.backend = \"s3\" needs endpoint, bucket, access_key_id \
+                     and secret_access_key in [zerobuild.artifacts.s3]"
+                );
+            }
+        }
+
+        // Issue-to-build trigger
+        if !self.factory.confirm_above_usd.is_finite() || self.factory.confirm_above_usd < 0.0 {
+            anyhow::bail!("factory.confirm_above_usd must be a non-negative number");
+        }
+        if self.factory.issues.enabled {
+            if let Some(repo) = self.factory.issues.repos.iter().find(|repo| {
+                let mut parts = repo.split('/');
+                !matches!(
+                    (parts.next(), parts.next(), parts.next()),
+                    (Some(owner), Some(name), None) if !owner.is_empty() && !name.is_empty()
+                )
+            }) {
+                anyhow::bail!("factory.issues.repos entry '{repo}' must be 'owner/repo'");
+            }
+            if self.factory.issues.label.trim().is_empty() {
+                anyhow::bail!("factory.issues.label must not be empty");
+            }
+            if self.factory.issues.poll_secs < 10 {
+                anyhow::bail!("factory.issues.poll_secs must be at least 10");
+            }
+        }
+        let triage = &self.factory.triage;
+        if triage.enabled {
+            if let Some(repo) = triage.repos.iter().find(|repo| {
+                let mut parts = repo.split('/');
+                !matches!(
+                    (parts.next(), parts.next(), parts.next()),
+                    (Some(owner), Some(name), None) if !owner.is_empty() && !name.is_empty()
+                )
+            }) {
+                anyhow::bail!("factory.triage.repos entry '{repo}' must be 'owner/repo'");
+            }
+            if triage.poll_secs < 10 {
+                anyhow::bail!("factory.triage.poll_secs must be at least 10");
+            }
+            if triage.draft_fixes && !self.factory.issues.enabled {
+                anyhow::bail!("factory.triage.draft_fixes needs [factory.issues] enabled");
+            }
+        }
+        for (i, observer) in self.channels_config.observers.iter().enumerate() {
+            if observer.channel.trim().is_empty() || observer.target.trim().is_empty() {
+                anyhow::bail!("channels_config.observers[{i}] needs a channel and a target");
+            }
+            if observer.events.is_empty() {
+                anyhow::bail!("channels_config.observers[{i}].events must not be empty");
+            }
+        }
+        let review = &self.factory.review;
+        if review.enabled {
+            if review.repos.is_empty() {
+                anyhow::bail!("factory.review.repos must list at least one repository");
+            }
+            for (repo, settings) in &review.repos {
+                let mut parts = repo.split('/');
+                if !matches!(
+                    (parts.next(), parts.next(), parts.next()),
+                    (Some(owner), Some(name), None) if !owner.is_empty() && !name.is_empty()
+                ) {
+                    anyhow::bail!("factory.review.repos key '{repo}' must be 'owner/repo'");
+                }
+                if settings.checklist.iter().any(|item| {
+                    let tag = item.trim().trim_start_matches('#');
+                    tag.is_empty() || tag.contains(char::is_whitespace)
+                }) {
+                    anyhow::bail!(
+                        "factory.review.repos.\"{repo}\".checklist entries must be single hashtags"
+                    );
+                }
+            }
+            if review.poll_secs < 10 {
+                anyhow::bail!("factory.review.poll_secs must be at least 10");
+            }
+        }
+
+        // Agent-to-agent protocol
+        let a2a = &self.factory.a2a;
+        if a2a.enabled
+            && a2a
+                .token
+                .as_deref()
+                .is_none_or(|token| token.trim().is_empty())
+        {
+            anyhow::bail!("factory.a2a.token is required when factory.a2a.enabled = true");
+        }
+        for (name, peer) in &a2a.peers {
+            let url = peer.url.trim();
+            if !url.starts_with("https://") && !url.starts_with("http://") {
+                anyhow::bail!("factory.a2a.peers.{name}.url must be an http(s) URL");
+            }
+            if peer.token.trim().is_empty() {
+                anyhow::bail!("factory.a2a.peers.{name}.token must not be empty");
+            }
+        }
+
+        // Coordination bus
+        let bus = &self.factory.bus;
+        match bus.backend.trim() {
+            "memory" => {
+                if bus.distribute_stages || bus.workers > 0 {
+                    anyhow::bail!(
+                        "factory.bus.distribute_stages and factory.bus.workers need a shared \
+                         backend (\"redis\" or \"nats\")"
+                    );
+                }
+            }
+            "redis" | "nats" => {
+                if bus.url.as_deref().is_none_or(|url| url.trim().is_empty()) {
+                    anyhow::bail!(
+                        "factory.bus.url is required for the \"{}\" backend",
+                        bus.backend.trim()
+                    );
+                }
+            }
+            other => anyhow::bail!(
+                "factory.bus.backend \"{other}\" is unknown; use \"memory\", \"redis\" or \"nats\""
+            ),
+        }
+        if bus.namespace.is_empty()
+            || !bus
+                .namespace
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            anyhow::bail!("factory.bus.namespace must be letters, digits, '-' or '_'");
+        }
+        if bus.stage_timeout_secs == 0 {
+            anyhow::bail!("factory.bus.stage_timeout_secs must be greater than 0");
+        }
+
+        // Provider rate limits
+        if self.reliability.rate_limit.burst == 0 {
+            anyhow::bail!("reliability.rate_limit.burst must be greater than 0");
+        }
+
+        // Moderation
+        let moderation = &self.moderation;
+        let thresholds = std::iter::once(("threshold".to_string(), moderation.threshold)).chain(
+            moderation
+                .category_thresholds
+                .iter()
+                .map(|(category, value)| (format!("category_thresholds.{category}"), *value)),
+        );
+        for (key, value) in thresholds {
+            if !(0.0..=1.0).contains(&value) {
+                anyhow::bail!("moderation.{key} must be between 0.0 and 1.0");
+            }
+        }
+        for pattern in &moderation.blocked_patterns {
+            if let Err(e) = regex::Regex::new(pattern) {
+                anyhow::bail!("moderation.blocked_patterns entry '{pattern}' is invalid: {e}");
+            }
+        }
+
+        // Repository deny list
+        for entry in &self.zerobuild.repos.deny {
+            let valid = match entry.trim().split_once('/') {
+                None => !entry.trim().is_empty(),
+                Some((owner, repo)) => !owner.is_empty() && !repo.is_empty() && !repo.contains('/'),
+            };
+            if !valid {
+                anyhow::bail!(
+                    "zerobuild.repos.deny entry '{entry}' must be `owner`, `owner/*` or `owner/repo`"
+                );
+            }
+        }
+
+        // Email channel
+        if let Some(email) = &self.channels_config.email {
+            match email.inbound.as_str() {
+                "imap" => {}
+                "webhook" => {
+                    if email
+                        .webhook_secret
+                        .as_deref()
+                        .is_none_or(|secret| secret.trim().is_empty())
+                    {
+                        anyhow::bail!(
+                            "channels_config.email.webhook_secret is required when inbound = \"webhook\""
+                        );
+                    }
+                }
+                other => anyhow::bail!(
+                    "channels_config.email.inbound must be \"imap\" or \"webhook\", got '{other}'"
+                ),
+            }
+        }
+
+        // Guardrails: patterns must compile so a typo never silently disables a rule
+        if self.guardrails.enabled {
+            crate::security::guardrails::GuardrailPolicy::from_config(&self.guardrails)?;
+        }
+
+        // Proxy (delegate to existing validation)
+        self.proxy.validate()?;
+
+        Ok(())
+    }
+
+    /// Make `[guardrails].deploy_repo_allowlist` the allow list of every
+    /// GitHub request ([`RepoAccessConfig::allow`]) while guardrails are on
+    /// and `deploy_action = "deny"`. With `ask` or `allow` the list only
+    /// governs deploys, through the guardrail.
+    pub fn share_repo_allowlist(&mut self) {
+        let guardrails = &self.guardrails;
+        self.zerobuild.repos.allow =
+            if guardrails.enabled && guardrails.deploy_action == GuardrailAction::Deny {
+                guardrails.deploy_repo_allowlist.clone()
+            } else {
+                Vec::new()
+            };
+    }
+
+    /// Apply environment variable overrides to config
+    pub fn apply_env_overrides(&mut self) {
+        // API Key: ZEROBUILD_API_KEY or API_KEY (generic)
+        if let Ok(key) = std::env::var("ZEROBUILD_API_KEY").or_else(|_| std::env::var("API_KEY")) {
+            if !key.is_empty() {
+                self.api_key = Some(key);
+            }
+        }
+        // API Key: GLM_API_KEY overrides when provider is a GLM/Zhipu variant.
+        if self.default_provider.as_deref().is_some_and(is_glm_alias) {
+            if let Ok(key) = std::env::var("GLM_API_KEY") {
+                if !key.is_empty() {
+                    self.api_key = Some(key);
+                }
+            }
+        }
+
+        // API Key: ZAI_API_KEY overrides when provider is a Z.AI variant.
+        if self.default_provider.as_deref().is_some_and(is_zai_alias) {
+            if let Ok(key) = std::env::var("ZAI_API_KEY") {
+                if !key.is_empty() {
+                    self.api_key = Some(key);
+                }
+            }
+        }
+
+        // Provider override precedence:
+        // 1) ZEROBUILD_PROVIDER always wins when set.
+        // 2) Legacy PROVIDER is only honored when config still uses the
+        //    default provider (openrouter) or provider is unset. This prevents
+        //    container defaults from overriding explicit custom providers.
+        if let Ok(provider) = std::env::var("ZEROBUILD_PROVIDER") {
+            if !provider.is_empty() {
+                self.default_provider = Some(provider);
+            }
+        } else if let Ok(provider) = std::env::var("PROVIDER") {
             let should_apply_legacy_provider =
                 self.default_provider.as_deref().map_or(true, |configured| {
                     configured.trim().eq_ignore_ascii_case("openrouter")
@@ -4421,6 +6378,19 @@ impl Config {
     }
 
     pub async fn save(&self) -> Result<()> {
+        // A SOPS-encrypted file would be overwritten with plaintext values
+        if let Ok(existing) = fs::read_to_string(&self.config_path).await {
+            if toml::from_str::<toml::Table>(&existing)
+                .is_ok_and(|document| crate::security::sops::is_encrypted(&document))
+            {
+                anyhow::bail!(
+                    "{} is SOPS-encrypted and can't be changed here; decrypt it with \
+                     `zerobuild config decrypt`, edit it and encrypt it again",
+                    self.config_path.display()
+                );
+            }
+        }
+
         // Encrypt secrets before serialization
         let mut config_to_save = self.clone();
         let zerobuild_dir = self
@@ -4454,6 +6424,42 @@ impl Config {
             "config.storage.provider.config.db_url",
         )?;
 
+        encrypt_optional_secret(
+            &store,
+            &mut config_to_save.zerobuild.images.s3.secret_access_key,
+            "config.zerobuild.images.s3.secret_access_key",
+        )?;
+
+        encrypt_optional_secret(
+            &store,
+            &mut config_to_save.zerobuild.artifacts.s3.secret_access_key,
+            "config.zerobuild.artifacts.s3.secret_access_key",
+        )?;
+
+        encrypt_optional_secret(
+            &store,
+            &mut config_to_save.zerobuild.vercel.token,
+            "config.zerobuild.vercel.token",
+        )?;
+
+        encrypt_optional_secret(
+            &store,
+            &mut config_to_save.zerobuild.sandbox.kubernetes.token,
+            "config.zerobuild.sandbox.kubernetes.token",
+        )?;
+
+        encrypt_optional_secret(
+            &store,
+            &mut config_to_save.zerobuild.sandbox.fly.token,
+            "config.zerobuild.sandbox.fly.token",
+        )?;
+
+        encrypt_optional_secret(
+            &store,
+            &mut config_to_save.moderation.api_key,
+            "config.moderation.api_key",
+        )?;
+
         for agent in config_to_save.agents.values_mut() {
             encrypt_optional_secret(&store, &mut agent.api_key, "config.agents.*.api_key")?;
         }
@@ -4510,152 +6516,923 @@ impl Config {
             .context("Failed to fsync temporary config file")?;
         drop(temp_file);
 
-        let had_existing_config = self.config_path.exists();
-        if had_existing_config {
-            fs::copy(&self.config_path, &backup_path)
-                .await
-                .with_context(|| {
-                    format!(
-                        "Failed to create config backup before atomic replace: {}",
-                        backup_path.display()
-                    )
-                })?;
-        }
+        let had_existing_config = self.config_path.exists();
+        if had_existing_config {
+            fs::copy(&self.config_path, &backup_path)
+                .await
+                .with_context(|| {
+                    format!(
+                        "Failed to create config backup before atomic replace: {}",
+                        backup_path.display()
+                    )
+                })?;
+        }
+
+        if let Err(e) = fs::rename(&temp_path, &self.config_path).await {
+            let _ = fs::remove_file(&temp_path).await;
+            if had_existing_config && backup_path.exists() {
+                fs::copy(&backup_path, &self.config_path)
+                    .await
+                    .context("Failed to restore config backup")?;
+            }
+            anyhow::bail!("Failed to atomically replace config file: {e}");
+        }
+
+        sync_directory(parent_dir).await?;
+
+        if had_existing_config {
+            let _ = fs::remove_file(&backup_path).await;
+        }
+
+        Ok(())
+    }
+}
+
+async fn sync_directory(path: &Path) -> Result<()> {
+    #[cfg(unix)]
+    {
+        let dir = File::open(path)
+            .await
+            .with_context(|| format!("Failed to open directory for fsync: {}", path.display()))?;
+        dir.sync_all()
+            .await
+            .with_context(|| format!("Failed to fsync directory metadata: {}", path.display()))?;
+        return Ok(());
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        Ok(())
+    }
+}
+
+// ── ZeroBuild ────────────────────────────────────────────────────────────────
+
+/// SOP execution mode for the engine.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SopExecutionMode {
+    /// Execute all steps without human approval.
+    Auto,
+    /// Request approval before starting, then execute all steps.
+    #[default]
+    Supervised,
+    /// Request approval before each step.
+    StepByStep,
+    /// Critical/High → Auto, Normal/Low → Supervised.
+    PriorityBased,
+}
+
+impl fmt::Display for SopExecutionMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Auto => write!(f, "auto"),
+            Self::Supervised => write!(f, "supervised"),
+            Self::StepByStep => write!(f, "step_by_step"),
+            Self::PriorityBased => write!(f, "priority_based"),
+        }
+    }
+}
+
+/// SOP engine configuration (`[sop]`).
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct SopConfig {
+    /// Directory containing SOP definitions (relative to workspace or absolute).
+    pub sops_dir: Option<String>,
+    /// Default execution mode for SOPs without explicit mode.
+    pub default_execution_mode: SopExecutionMode,
+    /// Maximum concurrent SOP runs allowed.
+    pub max_concurrent_total: usize,
+    /// Timeout in seconds for approval prompts.
+    pub approval_timeout_secs: u64,
+    /// Maximum number of finished runs to keep in memory.
+    pub max_finished_runs: usize,
+}
+
+impl Default for SopConfig {
+    fn default() -> Self {
+        Self {
+            sops_dir: Some("sops".to_string()),
+            default_execution_mode: SopExecutionMode::Auto,
+            max_concurrent_total: 10,
+            approval_timeout_secs: 300,
+            max_finished_runs: 100,
+        }
+    }
+}
+
+/// Example `config.toml` section:
+/// ```toml
+/// [zerobuild]
+/// role = "master"
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct ZerobuildConfig {
+    /// GitHub OAuth client ID for the GitHub connector (`/auth/github` flow).
+    /// If not set, ZeroBuild will use the official OAuth Proxy for seamless connection.
+    pub github_client_id: String,
+
+    /// GitHub OAuth client secret for the GitHub connector.
+    /// If not set, ZeroBuild will use the official OAuth Proxy for seamless connection.
+    pub github_client_secret: String,
+
+    /// OAuth Proxy URL for the GitHub connector.
+    /// Used when user doesn't have their own GitHub OAuth App.
+    /// Default: "https://zerobuild-oauth-proxy.githubz.workers.dev" (official proxy)
+    #[serde(default = "default_github_oauth_proxy")]
+    pub github_oauth_proxy: String,
+
+    /// Path to the ZeroBuild SQLite database.
+    /// Default: `~/.zerobuild/zerobuild.db`.
+    #[serde(default = "default_db_path")]
+    pub db_path: String,
+
+    /// Project template new sandboxes start from (see
+    /// [`crate::sandbox::PROJECT_TEMPLATES`]). Empty lets the agent choose.
+    pub default_template: String,
+
+    /// Sandbox command limits (`[zerobuild.sandbox]`).
+    pub sandbox: SandboxLimitsConfig,
+
+    /// License and attribution for deployed repos (`[zerobuild.license]`).
+    pub license: LicenseConfig,
+
+    /// Where `github_upload_image` stores images (`[zerobuild.images]`).
+    pub images: ImageHostingConfig,
+
+    /// Storage and retention of build artifacts (`[zerobuild.artifacts]`).
+    pub artifacts: ArtifactsConfig,
+
+    /// Vercel deployments of `ship_it` (`[zerobuild.vercel]`).
+    pub vercel: VercelConfig,
+
+    /// Repositories and organizations the GitHub tools may never touch
+    /// (`[zerobuild.repos]`).
+    pub repos: RepoAccessConfig,
+
+    /// Sandbox image and prebuilt project per project template
+    /// (`[zerobuild.templates.<name>]`, see [`crate::sandbox::templates`]).
+    pub templates: HashMap<String, SandboxTemplateConfig>,
+
+    /// Dependency refresh of deployed projects (`[zerobuild.maintenance]`).
+    pub maintenance: MaintenanceConfig,
+
+    /// Changelog entries and release notes of deploys
+    /// (`[zerobuild.changelog]`).
+    pub changelog: ChangelogConfig,
+}
+
+/// Dependency refresh job for deployed projects (`[zerobuild.maintenance]`).
+///
+/// The daemon restores each project `github_push` deployed into a sandbox,
+/// updates its dependencies, runs its tests and opens a pull request with the
+/// bumps (see [`crate::factory::maintenance`]).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct MaintenanceConfig {
+    /// Run the refresh job. Default: `false`.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Hours between refreshes of one project, counted from its last deploy
+    /// or refresh. Default: `24`.
+    #[serde(default = "default_maintenance_interval_hours")]
+    pub interval_hours: u64,
+    /// Deployed repositories (`owner/repo`) to refresh. Empty = all of them.
+    #[serde(default)]
+    pub repos: Vec<String>,
+    /// Branch the bumps are pushed to; one pull request from it stays open
+    /// and is updated by later refreshes. Default: `"zerobuild/dependency-refresh"`.
+    #[serde(default = "default_maintenance_branch")]
+    pub branch: String,
+    /// Only open a pull request when the tests pass after the update.
+    /// Default: `true`.
+    #[serde(default = "default_true")]
+    pub require_passing_tests: bool,
+}
+
+fn default_maintenance_interval_hours() -> u64 {
+    24
+}
+
+fn default_maintenance_branch() -> String {
+    "zerobuild/dependency-refresh".into()
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_hours: default_maintenance_interval_hours(),
+            repos: Vec::new(),
+            branch: default_maintenance_branch(),
+            require_passing_tests: true,
+        }
+    }
+}
+
+/// Changelog of deployed projects (`[zerobuild.changelog]`).
+///
+/// `github_push` remembers the files of every deploy so `deploy_changes`
+/// can show what changed since the last one. A `changelog` given to
+/// `github_push` becomes a new `CHANGELOG.md` entry and the notes of a
+/// GitHub release of the deployed commit.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ChangelogConfig {
+    /// Add the `changelog` given to `github_push` to `CHANGELOG.md`; the
+    /// factory's DevOps agent writes one on every deploy. Default: `true`.
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Also publish the entry as a GitHub release. Default: `true`.
+    #[serde(default = "default_true")]
+    pub releases: bool,
+}
+
+impl Default for ChangelogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            releases: true,
+        }
+    }
+}
+
+/// Sandbox image and prebuilt project of one project template
+/// (`[zerobuild.templates.<name>]`).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct SandboxTemplateConfig {
+    /// Image container-based providers start the sandbox from, ideally one
+    /// with the stack's dependencies pre-installed. Empty keeps the
+    /// built-in base image. Default: empty.
+    #[serde(default)]
+    pub image: String,
+    /// Absolute path of a prebuilt project with its dependencies installed
+    /// (on the host for the local provider, inside `image` otherwise);
+    /// `setup_project` copies it instead of running the generator. Empty
+    /// scaffolds from scratch. Default: empty.
+    #[serde(default)]
+    pub prebuilt_dir: String,
+}
+
+impl Default for ZerobuildConfig {
+    fn default() -> Self {
+        Self {
+            github_client_id: String::new(),
+            github_client_secret: String::new(),
+            github_oauth_proxy: default_github_oauth_proxy(),
+            db_path: default_db_path(),
+            default_template: String::new(),
+            sandbox: SandboxLimitsConfig::default(),
+            license: LicenseConfig::default(),
+            images: ImageHostingConfig::default(),
+            artifacts: ArtifactsConfig::default(),
+            vercel: VercelConfig::default(),
+            repos: RepoAccessConfig::default(),
+            templates: HashMap::new(),
+            maintenance: MaintenanceConfig::default(),
+            changelog: ChangelogConfig::default(),
+        }
+    }
+}
+
+/// Repositories and organizations the GitHub tools and `github_push` may
+/// touch. The deny list is `[zerobuild.repos]`; the allow list is
+/// `[guardrails].deploy_repo_allowlist`. Matching ignores case.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct RepoAccessConfig {
+    /// Never touched, even when allowed: `owner/repo`, or `owner` (also
+    /// written `owner/*`) for every repository of a user or organization.
+    #[serde(default)]
+    pub deny: Vec<String>,
+    /// Only these may be touched (empty = any repository not denied).
+    /// Not read from `[zerobuild.repos]`: [`Config::share_repo_allowlist`]
+    /// copies in `[guardrails].deploy_repo_allowlist` when the config loads.
+    #[serde(skip)]
+    pub allow: Vec<String>,
+}
+
+/// Vercel account `ship_it` deploys to (`[zerobuild.vercel]`).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct VercelConfig {
+    /// Access token. `ship_it` deploys to Vercel only when it is set;
+    /// encrypted at rest like other secrets.
+    #[serde(default)]
+    pub token: Option<String>,
+    /// Team ID (`team_...`) or slug deployments belong to. Empty uses the
+    /// token's personal account. Default: empty.
+    #[serde(default)]
+    pub team: String,
+}
+
+/// License added to deployed repos (`[zerobuild.license]`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct LicenseConfig {
+    /// SPDX identifier of the license `github_push` adds to projects without
+    /// a LICENSE file (see [`crate::tools::licensing::SUPPORTED_LICENSES`]).
+    /// Empty disables it. Default: `"MIT"`.
+    #[serde(default = "default_license_spdx")]
+    pub spdx: String,
+    /// Copyright holder in the LICENSE file and package `author`. Empty
+    /// uses the repository owner. Default: empty.
+    #[serde(default)]
+    pub holder: String,
+    /// Fill missing `license`, `repository` and `author` fields of
+    /// `package.json` and `Cargo.toml`. Default: `true`.
+    #[serde(default = "default_true")]
+    pub fill_metadata: bool,
+    /// Report files with third-party copyright or license notices that the
+    /// project's NOTICE or README doesn't credit. Default: `true`.
+    #[serde(default = "default_true")]
+    pub check_attribution: bool,
+}
+
+fn default_license_spdx() -> String {
+    "MIT".into()
+}
+
+impl Default for LicenseConfig {
+    fn default() -> Self {
+        Self {
+            spdx: default_license_spdx(),
+            holder: String::new(),
+            fill_metadata: true,
+            check_attribution: true,
+        }
+    }
+}
+
+/// Where `github_upload_image` stores images (`[zerobuild.images]`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageBackend {
+    /// Commit the image to `.github/assets/` of the repository.
+    #[default]
+    Repo,
+    /// Upload to an S3-compatible bucket (`[zerobuild.images.s3]`).
+    S3,
+    /// Upload anonymously to Imgur; needs `imgur_client_id`.
+    Imgur,
+}
+
+/// Image hosting for issues and pull requests (`[zerobuild.images]`).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ImageHostingConfig {
+    /// Default backend. Default: `"repo"`.
+    #[serde(default)]
+    pub backend: ImageBackend,
+    /// Imgur API client ID. Imgur is only used when this is set. Default:
+    /// empty.
+    #[serde(default)]
+    pub imgur_client_id: String,
+    /// S3-compatible bucket (`[zerobuild.images.s3]`).
+    #[serde(default)]
+    pub s3: ImageS3Config,
+}
+
+/// S3-compatible bucket for images (`[zerobuild.images.s3]`): AWS S3,
+/// Cloudflare R2, MinIO, ...
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ImageS3Config {
+    /// Endpoint URL, e.g. `https://s3.us-east-1.amazonaws.com` or
+    /// `https://<account>.r2.cloudflarestorage.com`. Default: empty.
+    #[serde(default)]
+    pub endpoint: String,
+    /// Bucket name. Default: empty.
+    #[serde(default)]
+    pub bucket: String,
+    /// Signing region. Default: `"us-east-1"` (R2 accepts `"auto"`).
+    #[serde(default = "default_image_s3_region")]
+    pub region: String,
+    /// Access key ID. Default: empty.
+    #[serde(default)]
+    pub access_key_id: String,
+    /// Secret access key; encrypted at rest like other secrets.
+    #[serde(default)]
+    pub secret_access_key: Option<String>,
+    /// Public base URL of the bucket for image links. Empty uses
+    /// `<endpoint>/<bucket>`. Default: empty.
+    #[serde(default)]
+    pub public_url: String,
+    /// Key prefix of uploaded images. Default: `"zerobuild/"`.
+    #[serde(default = "default_image_s3_prefix")]
+    pub prefix: String,
+}
+
+fn default_image_s3_region() -> String {
+    "us-east-1".into()
+}
+
+fn default_image_s3_prefix() -> String {
+    "zerobuild/".into()
+}
+
+impl Default for ImageS3Config {
+    fn default() -> Self {
+        Self {
+            endpoint: String::new(),
+            bucket: String::new(),
+            region: default_image_s3_region(),
+            access_key_id: String::new(),
+            secret_access_key: None,
+            public_url: String::new(),
+            prefix: default_image_s3_prefix(),
+        }
+    }
+}
+
+/// Where build artifacts are stored (`[zerobuild.artifacts]`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ArtifactBackend {
+    /// Files under `dir`.
+    #[default]
+    Local,
+    /// An S3-compatible bucket (`[zerobuild.artifacts.s3]`).
+    S3,
+}
+
+/// Build artifacts — snapshot versions, screenshots, and full command logs —
+/// and how long they are kept (`[zerobuild.artifacts]`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ArtifactsConfig {
+    /// Storage backend. Default: `"local"`.
+    #[serde(default)]
+    pub backend: ArtifactBackend,
+    /// Directory of the `local` backend. Empty uses `artifacts/` next to
+    /// `db_path`. Default: empty.
+    #[serde(default)]
+    pub dir: String,
+    /// Bucket of the `s3` backend (`[zerobuild.artifacts.s3]`); same keys as
+    /// `[zerobuild.images.s3]`, `public_url` is unused.
+    #[serde(default)]
+    pub s3: ImageS3Config,
+    /// Artifacts older than this are deleted. `0` keeps them forever.
+    /// Default: `30`.
+    #[serde(default = "default_artifact_max_age_days")]
+    pub max_age_days: u64,
+    /// Largest total size of one project's artifacts; the oldest are deleted
+    /// first. `0` disables the limit. Default: `1024`.
+    #[serde(default = "default_artifact_max_project_mb")]
+    pub max_project_mb: u64,
+    /// How often the daemon applies the retention policy. `0` disables the
+    /// cleanup task. Default: `60`.
+    #[serde(default = "default_artifact_cleanup_interval_minutes")]
+    pub cleanup_interval_minutes: u64,
+}
+
+fn default_artifact_max_age_days() -> u64 {
+    30
+}
+
+fn default_artifact_max_project_mb() -> u64 {
+    1024
+}
+
+fn default_artifact_cleanup_interval_minutes() -> u64 {
+    60
+}
+
+impl Default for ArtifactsConfig {
+    fn default() -> Self {
+        Self {
+            backend: ArtifactBackend::default(),
+            dir: String::new(),
+            s3: ImageS3Config::default(),
+            max_age_days: default_artifact_max_age_days(),
+            max_project_mb: default_artifact_max_project_mb(),
+            cleanup_interval_minutes: default_artifact_cleanup_interval_minutes(),
+        }
+    }
+}
+
+/// Time limits for sandbox commands (`[zerobuild.sandbox]`).
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct SandboxLimitsConfig {
+    /// Timeout for `sandbox_run_command` when the agent passes none. Default: `300`.
+    #[serde(default = "default_sandbox_command_timeout_secs")]
+    pub command_timeout_secs: u64,
+    /// Upper bound for agent-requested command timeouts. Default: `1800`.
+    #[serde(default = "default_sandbox_max_command_timeout_secs")]
+    pub max_command_timeout_secs: u64,
+    /// Timeout for creating a sandbox. Default: `600`.
+    #[serde(default = "default_sandbox_create_timeout_secs")]
+    pub create_timeout_secs: u64,
+    /// Rewrite generic `npm install` / `npm run` commands to the project's
+    /// package manager with a frozen lockfile. Default: `true`.
+    #[serde(default = "default_true")]
+    pub rewrite_package_commands: bool,
+    /// Run `sandbox_run_command` commands under a pseudo-terminal unless the
+    /// agent passes `pty`. Default: `false`.
+    #[serde(default)]
+    pub pty: bool,
+    /// Lines kept from the start of long `sandbox_run_command` output; the
+    /// full output is stored for `get_log`. Default: `80`.
+    #[serde(default = "default_sandbox_output_head_lines")]
+    pub output_head_lines: usize,
+    /// Lines kept from the end of long `sandbox_run_command` output. Default: `80`.
+    #[serde(default = "default_sandbox_output_tail_lines")]
+    pub output_tail_lines: usize,
+    /// Seconds between progress messages to the channel while a dependency
+    /// install runs; `0` disables them. Default: `20`.
+    #[serde(default = "default_sandbox_install_heartbeat_secs")]
+    pub install_heartbeat_secs: u64,
+    /// Sandbox providers in failover order (see
+    /// [`crate::sandbox::PROVIDERS`]). Default: `["local"]`.
+    #[serde(default = "default_sandbox_providers")]
+    pub providers: Vec<String>,
+    /// Provider errors in a row (network, rate limit, credentials) after
+    /// which the next provider takes over with the latest snapshot.
+    /// Default: `3`.
+    #[serde(default = "default_sandbox_failover_after_errors")]
+    pub failover_after_errors: u32,
+    /// Offer `sandbox_shell`, whose named sessions keep the working
+    /// directory, exported variables and activated tools (nvm, venvs)
+    /// between commands. Default: `true`.
+    #[serde(default = "default_true")]
+    pub shell_sessions: bool,
+    /// Commands kept in the history `sandbox_command_history` reads from.
+    /// Default: `500`.
+    #[serde(default = "default_sandbox_command_history_limit")]
+    pub command_history_limit: usize,
+    /// Return preview URLs through a proxy on the ZeroBuild host that records
+    /// the app's requests and responses for `sandbox_http_log`.
+    /// Default: `false`.
+    #[serde(default)]
+    pub http_capture: bool,
+    /// Cluster the `kubernetes` provider creates sandbox pods in
+    /// (`[zerobuild.sandbox.kubernetes]`). Boxed to keep [`Config`] small,
+    /// as most setups never use it.
+    #[serde(default)]
+    pub kubernetes: Box<KubernetesSandboxConfig>,
+    /// MicroVMs of the `firecracker` provider
+    /// (`[zerobuild.sandbox.firecracker]`). Boxed like `kubernetes`.
+    #[serde(default)]
+    pub firecracker: Box<FirecrackerSandboxConfig>,
+    /// Fly.io Machines of the `fly` provider (`[zerobuild.sandbox.fly]`).
+    /// Boxed like `kubernetes`.
+    #[serde(default)]
+    pub fly: Box<FlySandboxConfig>,
+}
+
+/// Cluster of the `kubernetes` sandbox provider
+/// (`[zerobuild.sandbox.kubernetes]`): one pod per sandbox, commands run
+/// through the exec API and preview ports exposed by a Service and, with
+/// `preview_domain`, an Ingress. Empty connection settings use the pod's
+/// service account when ZeroBuild runs in the cluster.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct KubernetesSandboxConfig {
+    /// API server URL, e.g. `https://10.0.0.1:6443`. Empty uses
+    /// `KUBERNETES_SERVICE_HOST`/`KUBERNETES_SERVICE_PORT`. Default: empty.
+    #[serde(default)]
+    pub api_url: String,
+    /// Bearer token; encrypted at rest like other secrets. Unset reads the
+    /// service account token.
+    #[serde(default)]
+    pub token: Option<String>,
+    /// PEM file of the API server's CA. Empty uses the service account's
+    /// `ca.crt` when present, else the public roots. Default: empty.
+    #[serde(default)]
+    pub ca_cert: String,
+    /// Namespace of the sandbox pods. Empty uses the service account's
+    /// namespace, else `default`. Default: empty.
+    #[serde(default)]
+    pub namespace: String,
+    /// Image of sandboxes whose template sets none. Default: `"node:20-bookworm"`.
+    #[serde(default = "default_kubernetes_sandbox_image")]
+    pub image: String,
+    /// CPU limit of a sandbox pod. Default: `"2"`.
+    #[serde(default = "default_kubernetes_sandbox_cpu")]
+    pub cpu: String,
+    /// Memory limit of a sandbox pod. Default: `"4Gi"`.
+    #[serde(default = "default_kubernetes_sandbox_memory")]
+    pub memory: String,
+    /// Wildcard domain previews are served on: port 3000 of pod `zb-abc`
+    /// becomes `https://3000-zb-abc.<preview_domain>` through an Ingress.
+    /// Empty returns the Service's in-cluster URL. Default: empty.
+    #[serde(default)]
+    pub preview_domain: String,
+    /// `ingressClassName` of preview Ingresses. Empty uses the cluster
+    /// default. Default: empty.
+    #[serde(default)]
+    pub ingress_class: String,
+}
+
+fn default_kubernetes_sandbox_image() -> String {
+    "node:20-bookworm".into()
+}
 
-        if let Err(e) = fs::rename(&temp_path, &self.config_path).await {
-            let _ = fs::remove_file(&temp_path).await;
-            if had_existing_config && backup_path.exists() {
-                fs::copy(&backup_path, &self.config_path)
-                    .await
-                    .context("Failed to restore config backup")?;
-            }
-            anyhow::bail!("Failed to atomically replace config file: {e}");
-        }
+fn default_kubernetes_sandbox_cpu() -> String {
+    "2".into()
+}
 
-        sync_directory(parent_dir).await?;
+fn default_kubernetes_sandbox_memory() -> String {
+    "4Gi".into()
+}
 
-        if had_existing_config {
-            let _ = fs::remove_file(&backup_path).await;
+impl Default for KubernetesSandboxConfig {
+    fn default() -> Self {
+        Self {
+            api_url: String::new(),
+            token: None,
+            ca_cert: String::new(),
+            namespace: String::new(),
+            image: default_kubernetes_sandbox_image(),
+            cpu: default_kubernetes_sandbox_cpu(),
+            memory: default_kubernetes_sandbox_memory(),
+            preview_domain: String::new(),
+            ingress_class: String::new(),
         }
-
-        Ok(())
     }
 }
 
-async fn sync_directory(path: &Path) -> Result<()> {
-    #[cfg(unix)]
-    {
-        let dir = File::open(path)
-            .await
-            .with_context(|| format!("Failed to open directory for fsync: {}", path.display()))?;
-        dir.sync_all()
-            .await
-            .with_context(|| format!("Failed to fsync directory metadata: {}", path.display()))?;
-        return Ok(());
-    }
+/// MicroVMs of the `firecracker` sandbox provider
+/// (`[zerobuild.sandbox.firecracker]`): one VM per sandbox booted from
+/// `kernel` and a copy of `rootfs`, reached over a tap device through the
+/// guest agent (`zerobuild sandbox-agent`) the rootfs starts at boot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct FirecrackerSandboxConfig {
+    /// VMM running the VMs: `"firecracker"` or `"cloud-hypervisor"`.
+    /// Default: `"firecracker"`.
+    #[serde(default = "default_firecracker_hypervisor")]
+    pub hypervisor: String,
+    /// Path of the VMM binary. Empty looks `hypervisor` up on `PATH`.
+    /// Default: empty.
+    #[serde(default)]
+    pub binary: String,
+    /// Uncompressed guest kernel (`vmlinux`) built with `CONFIG_IP_PNP`, as
+    /// the guest's address is set on its command line. Required.
+    #[serde(default)]
+    pub kernel: String,
+    /// ext4 root filesystem image each sandbox boots a copy of. It must
+    /// start `zerobuild sandbox-agent` at boot and contain `/workspace`.
+    /// Required.
+    #[serde(default)]
+    pub rootfs: String,
+    /// Kernel command line, before the root device and address arguments
+    /// ZeroBuild appends. Default: `"console=ttyS0 reboot=k panic=1"`.
+    #[serde(default = "default_firecracker_boot_args")]
+    pub boot_args: String,
+    /// vCPUs of a sandbox VM. Default: `2`.
+    #[serde(default = "default_firecracker_vcpus")]
+    pub vcpus: u8,
+    /// Memory of a sandbox VM in MiB. Default: `2048`.
+    #[serde(default = "default_firecracker_memory_mib")]
+    pub memory_mib: u32,
+    /// Tap device the VM's network interface uses; it must exist with
+    /// `host_ip` assigned. One VM runs on it at a time. Default: `"zb-tap0"`.
+    #[serde(default = "default_firecracker_tap_device")]
+    pub tap_device: String,
+    /// Address of the host on the tap device. Default: `"172.16.0.1"`.
+    #[serde(default = "default_firecracker_host_ip")]
+    pub host_ip: String,
+    /// Address given to the guest, in the `/24` of `host_ip`.
+    /// Default: `"172.16.0.2"`.
+    #[serde(default = "default_firecracker_guest_ip")]
+    pub guest_ip: String,
+    /// Port the guest agent listens on. Default: `7000`.
+    #[serde(default = "default_firecracker_agent_port")]
+    pub agent_port: u16,
+    /// Directory of the VMs' disks, configs and console logs. Empty uses
+    /// `~/.zerobuild/firecracker`. Default: empty.
+    #[serde(default)]
+    pub state_dir: String,
+}
+
+fn default_firecracker_hypervisor() -> String {
+    "firecracker".into()
+}
+
+fn default_firecracker_boot_args() -> String {
+    "console=ttyS0 reboot=k panic=1".into()
+}
+
+fn default_firecracker_vcpus() -> u8 {
+    2
+}
 
-    #[cfg(not(unix))]
-    {
-        let _ = path;
-        Ok(())
-    }
+fn default_firecracker_memory_mib() -> u32 {
+    2048
 }
 
-// ── ZeroBuild ────────────────────────────────────────────────────────────────
+fn default_firecracker_tap_device() -> String {
+    "zb-tap0".into()
+}
 
-/// SOP execution mode for the engine.
-#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
-#[serde(rename_all = "lowercase")]
-pub enum SopExecutionMode {
-    /// Execute all steps without human approval.
-    Auto,
-    /// Request approval before starting, then execute all steps.
-    #[default]
-    Supervised,
-    /// Request approval before each step.
-    StepByStep,
-    /// Critical/High → Auto, Normal/Low → Supervised.
-    PriorityBased,
+fn default_firecracker_host_ip() -> String {
+    "172.16.0.1".into()
 }
 
-impl fmt::Display for SopExecutionMode {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Self::Auto => write!(f, "auto"),
-            Self::Supervised => write!(f, "supervised"),
-            Self::StepByStep => write!(f, "step_by_step"),
-            Self::PriorityBased => write!(f, "priority_based"),
+fn default_firecracker_guest_ip() -> String {
+    "172.16.0.2".into()
+}
+
+fn default_firecracker_agent_port() -> u16 {
+    crate::sandbox::guest_agent::DEFAULT_PORT
+}
+
+impl Default for FirecrackerSandboxConfig {
+    fn default() -> Self {
+        Self {
+            hypervisor: default_firecracker_hypervisor(),
+            binary: String::new(),
+            kernel: String::new(),
+            rootfs: String::new(),
+            boot_args: default_firecracker_boot_args(),
+            vcpus: default_firecracker_vcpus(),
+            memory_mib: default_firecracker_memory_mib(),
+            tap_device: default_firecracker_tap_device(),
+            host_ip: default_firecracker_host_ip(),
+            guest_ip: default_firecracker_guest_ip(),
+            agent_port: default_firecracker_agent_port(),
+            state_dir: String::new(),
         }
     }
 }
 
-/// SOP engine configuration (`[sop]`).
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
-#[serde(default)]
-pub struct SopConfig {
-    /// Directory containing SOP definitions (relative to workspace or absolute).
-    pub sops_dir: Option<String>,
-    /// Default execution mode for SOPs without explicit mode.
-    pub default_execution_mode: SopExecutionMode,
-    /// Maximum concurrent SOP runs allowed.
-    pub max_concurrent_total: usize,
-    /// Timeout in seconds for approval prompts.
-    pub approval_timeout_secs: u64,
-    /// Maximum number of finished runs to keep in memory.
-    pub max_finished_runs: usize,
+/// Fly.io account of the `fly` sandbox provider (`[zerobuild.sandbox.fly]`):
+/// one Fly app with a single Machine per sandbox, so previews get a public
+/// `https://<app>.fly.dev` URL. Commands run through the Machines exec API.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct FlySandboxConfig {
+    /// Machines API URL. Default: `"https://api.machines.dev"`.
+    #[serde(default = "default_fly_api_url")]
+    pub api_url: String,
+    /// API token allowed to create apps in `org` (`fly tokens create org`);
+    /// encrypted at rest like other secrets. Unset reads `FLY_API_TOKEN`.
+    #[serde(default)]
+    pub token: Option<String>,
+    /// Organization slug the sandbox apps are created in. Default: `"personal"`.
+    #[serde(default = "default_fly_org")]
+    pub org: String,
+    /// Region of the sandbox Machines, e.g. `"ams"`. Empty lets Fly pick
+    /// the one closest to ZeroBuild. Default: empty.
+    #[serde(default)]
+    pub region: String,
+    /// Image of sandboxes whose template sets none. Default: `"node:20-bookworm"`.
+    #[serde(default = "default_kubernetes_sandbox_image")]
+    pub image: String,
+    /// `"shared"` or `"performance"` CPUs. Default: `"shared"`.
+    #[serde(default = "default_fly_cpu_kind")]
+    pub cpu_kind: String,
+    /// CPUs of a sandbox Machine. Default: `2`.
+    #[serde(default = "default_fly_cpus")]
+    pub cpus: u8,
+    /// Memory of a sandbox Machine in MB. Default: `2048`.
+    #[serde(default = "default_fly_memory_mb")]
+    pub memory_mb: u32,
+    /// Ports previews are served from. The first is served on
+    /// `https://<app>.fly.dev`, the others on their own port of that
+    /// hostname. Default: `[3000, 5173, 8080]`.
+    #[serde(default = "default_fly_preview_ports")]
+    pub preview_ports: Vec<u16>,
 }
 
-impl Default for SopConfig {
+fn default_fly_api_url() -> String {
+    "https://api.machines.dev".into()
+}
+
+fn default_fly_org() -> String {
+    "personal".into()
+}
+
+fn default_fly_cpu_kind() -> String {
+    "shared".into()
+}
+
+fn default_fly_cpus() -> u8 {
+    2
+}
+
+fn default_fly_memory_mb() -> u32 {
+    2048
+}
+
+fn default_fly_preview_ports() -> Vec<u16> {
+    vec![3000, 5173, 8080]
+}
+
+impl Default for FlySandboxConfig {
     fn default() -> Self {
         Self {
-            sops_dir: Some("sops".to_string()),
-            default_execution_mode: SopExecutionMode::Auto,
-            max_concurrent_total: 10,
-            approval_timeout_secs: 300,
-            max_finished_runs: 100,
+            api_url: default_fly_api_url(),
+            token: None,
+            org: default_fly_org(),
+            region: String::new(),
+            image: default_kubernetes_sandbox_image(),
+            cpu_kind: default_fly_cpu_kind(),
+            cpus: default_fly_cpus(),
+            memory_mb: default_fly_memory_mb(),
+            preview_ports: default_fly_preview_ports(),
         }
     }
 }
 
-/// Example `config.toml` section:
-/// ```toml
-/// [zerobuild]
-/// role = "master"
-/// ```
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
-#[serde(default)]
-pub struct ZerobuildConfig {
-    /// GitHub OAuth client ID for the GitHub connector (`/auth/github` flow).
-    /// If not set, ZeroBuild will use the official OAuth Proxy for seamless connection.
-    pub github_client_id: String,
+fn default_sandbox_command_timeout_secs() -> u64 {
+    300
+}
 
-    /// GitHub OAuth client secret for the GitHub connector.
-    /// If not set, ZeroBuild will use the official OAuth Proxy for seamless connection.
-    pub github_client_secret: String,
+fn default_sandbox_max_command_timeout_secs() -> u64 {
+    1800
+}
 
-    /// OAuth Proxy URL for the GitHub connector.
-    /// Used when user doesn't have their own GitHub OAuth App.
-    /// Default: "https://zerobuild-oauth-proxy.githubz.workers.dev" (official proxy)
-    #[serde(default = "default_github_oauth_proxy")]
-    pub github_oauth_proxy: String,
+fn default_sandbox_create_timeout_secs() -> u64 {
+    600
+}
 
-    /// Path to the ZeroBuild SQLite database.
-    /// Default: `~/.zerobuild/zerobuild.db`.
-    #[serde(default = "default_db_path")]
-    pub db_path: String,
+fn default_sandbox_output_head_lines() -> usize {
+    80
 }
 
-impl Default for ZerobuildConfig {
+fn default_sandbox_output_tail_lines() -> usize {
+    80
+}
+
+fn default_sandbox_install_heartbeat_secs() -> u64 {
+    20
+}
+
+fn default_sandbox_providers() -> Vec<String> {
+    vec!["local".into()]
+}
+
+fn default_sandbox_failover_after_errors() -> u32 {
+    3
+}
+
+fn default_sandbox_command_history_limit() -> usize {
+    500
+}
+
+impl Default for SandboxLimitsConfig {
     fn default() -> Self {
         Self {
-            github_client_id: String::new(),
-            github_client_secret: String::new(),
-            github_oauth_proxy: default_github_oauth_proxy(),
-            db_path: default_db_path(),
+            command_timeout_secs: default_sandbox_command_timeout_secs(),
+            max_command_timeout_secs: default_sandbox_max_command_timeout_secs(),
+            create_timeout_secs: default_sandbox_create_timeout_secs(),
+            rewrite_package_commands: true,
+            pty: false,
+            output_head_lines: default_sandbox_output_head_lines(),
+            output_tail_lines: default_sandbox_output_tail_lines(),
+            install_heartbeat_secs: default_sandbox_install_heartbeat_secs(),
+            providers: default_sandbox_providers(),
+            failover_after_errors: default_sandbox_failover_after_errors(),
+            shell_sessions: true,
+            command_history_limit: default_sandbox_command_history_limit(),
+            http_capture: false,
+            kubernetes: Box::default(),
+            firecracker: Box::default(),
+            fly: Box::default(),
         }
     }
 }
 
+// ── Config Profiles ─────────────────────────────────────────────
+
+/// Named config profile (`[profiles.<name>]`) or per-project override file
+/// (`projects/<project>.toml` next to `config.toml`). Unset fields keep the
+/// base config's value; see [`crate::config::profiles`].
+///
+/// ```toml
+/// [profiles.fast-cheap]
+/// description = "Quick drafts"
+/// default_model = "anthropic/claude-haiku-4-5"
+/// max_ping_pong_iterations = 2
+/// factory_stages = { integration_loop = true, deployment = false }
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigProfile {
+    /// Shown by the `/profile` chat command.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Named profile to apply first (project override files only).
+    #[serde(default)]
+    pub profile: Option<String>,
+    #[serde(default)]
+    pub default_provider: Option<String>,
+    #[serde(default)]
+    pub default_model: Option<String>,
+    #[serde(default)]
+    pub default_temperature: Option<f64>,
+    /// Routes added to `[[model_routes]]`, replacing routes with the same hint.
+    #[serde(default)]
+    pub model_routes: Vec<ModelRouteConfig>,
+    /// Replaces `[zerobuild.sandbox]`.
+    #[serde(default)]
+    pub sandbox: Option<SandboxLimitsConfig>,
+    /// Replaces `[factory.stages]`.
+    #[serde(default)]
+    pub factory_stages: Option<FactoryStagesConfig>,
+    /// Overrides `factory.max_ping_pong_iterations`.
+    #[serde(default)]
+    pub max_ping_pong_iterations: Option<usize>,
+    /// Overrides `agent.max_tool_iterations`.
+    #[serde(default)]
+    pub max_tool_iterations: Option<usize>,
+}
+
 fn default_db_path() -> String {
     let home = UserDirs::new().map_or_else(|| PathBuf::from("."), |u| u.home_dir().to_path_buf());
     home.join(".zerobuild")
@@ -4868,6 +7645,8 @@ default_temperature = 0.7
                 backend: "log".into(),
                 ..ObservabilityConfig::default()
             },
+            logging: LoggingConfig::default(),
+            local_provider: LocalProviderConfig::default(),
             autonomy: AutonomyConfig {
                 level: AutonomyLevel::Full,
                 workspace_only: false,
@@ -4929,6 +7708,7 @@ default_temperature = 0.7
                 nostr: None,
                 clawdtalk: None,
                 message_timeout_secs: 300,
+                observers: Vec::new(),
             },
             memory: MemoryConfig::default(),
             storage: StorageConfig::default(),
@@ -4939,6 +7719,7 @@ default_temperature = 0.7
             browser: BrowserConfig::default(),
             http_request: HttpRequestConfig::default(),
             multimodal: MultimodalConfig::default(),
+            i18n: I18nConfig::default(),
             web_search: WebSearchConfig::default(),
             proxy: ProxyConfig::default(),
             agent: AgentConfig::default(),
@@ -4952,6 +7733,10 @@ default_temperature = 0.7
             sop: SopConfig::default(),
             factory: FactoryConfig::default(),
             zerobuild: ZerobuildConfig::default(),
+            guardrails: GuardrailsConfig::default(),
+            users: UsersConfig::default(),
+            moderation: ModerationConfig::default(),
+            profiles: HashMap::new(),
         };
 
         let toml_str = toml::to_string_pretty(&config).unwrap();
@@ -5095,6 +7880,8 @@ tool_dispatcher = "xml"
             default_model: Some("test-model".into()),
             default_temperature: 0.9,
             observability: ObservabilityConfig::default(),
+            logging: LoggingConfig::default(),
+            local_provider: LocalProviderConfig::default(),
             autonomy: AutonomyConfig::default(),
             runtime: RuntimeConfig::default(),
             reliability: ReliabilityConfig::default(),
@@ -5115,6 +7902,7 @@ tool_dispatcher = "xml"
             browser: BrowserConfig::default(),
             http_request: HttpRequestConfig::default(),
             multimodal: MultimodalConfig::default(),
+            i18n: I18nConfig::default(),
             web_search: WebSearchConfig::default(),
             proxy: ProxyConfig::default(),
             agent: AgentConfig::default(),
@@ -5128,6 +7916,10 @@ tool_dispatcher = "xml"
             sop: SopConfig::default(),
             factory: FactoryConfig::default(),
             zerobuild: ZerobuildConfig::default(),
+            guardrails: GuardrailsConfig::default(),
+            users: UsersConfig::default(),
+            moderation: ModerationConfig::default(),
+            profiles: HashMap::new(),
         };
 
         config.save().await.unwrap();
@@ -5148,6 +7940,38 @@ tool_dispatcher = "xml"
         let _ = fs::remove_dir_all(&dir).await;
     }
 
+    #[tokio::test]
+    async fn config_load_decrypts_sops_values() {
+        use age::secrecy::ExposeSecret;
+
+        let _env_guard = env_override_lock().await;
+        let dir = tempfile::TempDir::new().unwrap();
+        let identity = age::x25519::Identity::generate();
+        let document: toml::Table = toml::from_str(
+            r#"
+            api_key = "sk-sops"
+            default_model = "test-model"
+            default_temperature = 0.7
+            "#,
+        )
+        .unwrap();
+        let encrypted =
+            crate::security::sops::encrypt(document, &[identity.to_public()], Some("^api_key$"))
+                .unwrap();
+        let config_path = dir.path().join("config.toml");
+        std::fs::write(&config_path, toml::to_string_pretty(&encrypted).unwrap()).unwrap();
+
+        std::env::set_var("SOPS_AGE_KEY", identity.to_string().expose_secret());
+        let loaded = Config::load_from_file(&config_path, dir.path().join("workspace")).await;
+        std::env::remove_var("SOPS_AGE_KEY");
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded.api_key.as_deref(), Some("sk-sops"));
+        assert_eq!(loaded.default_model.as_deref(), Some("test-model"));
+
+        let err = loaded.save().await.unwrap_err();
+        assert!(err.to_string().contains("SOPS-encrypted"), "{err}");
+    }
+
     #[tokio::test]
     async fn config_save_encrypts_nested_credentials() {
         let dir = std::env::temp_dir().join(format!(
@@ -5173,6 +7997,8 @@ tool_dispatcher = "xml"
                 system_prompt: None,
                 api_key: Some("agent-credential".into()),
                 temperature: None,
+                top_p: None,
+                seed: None,
                 max_depth: 3,
                 agentic: false,
                 allowed_tools: Vec::new(),
@@ -5302,6 +8128,7 @@ tool_dispatcher = "xml"
             allowed_users: vec![],
             listen_to_bots: false,
             mention_only: false,
+            stream_mode: StreamMode::default(),
         };
         let json = serde_json::to_string(&dc).unwrap();
         let parsed: DiscordConfig = serde_json::from_str(&json).unwrap();
@@ -5317,6 +8144,7 @@ tool_dispatcher = "xml"
             allowed_users: vec![],
             listen_to_bots: false,
             mention_only: false,
+            stream_mode: StreamMode::default(),
         };
         let json = serde_json::to_string(&dc).unwrap();
         let parsed: DiscordConfig = serde_json::from_str(&json).unwrap();
@@ -5490,6 +8318,7 @@ allowed_users = ["@ops:matrix.org"]
             nostr: None,
             clawdtalk: None,
             message_timeout_secs: 300,
+            observers: Vec::new(),
         };
         let toml_str = toml::to_string_pretty(&c).unwrap();
         let parsed: ChannelsConfig = toml::from_str(&toml_str).unwrap();
@@ -5587,6 +8416,8 @@ channel_id = "C123"
             pair_phone: None,
             pair_code: None,
             allowed_numbers: vec!["+1234567890".into(), "+9876543210".into()],
+            reply_template: None,
+            reply_template_language: None,
         };
         let json = serde_json::to_string(&wc).unwrap();
         let parsed: WhatsAppConfig = serde_json::from_str(&json).unwrap();
@@ -5607,6 +8438,8 @@ channel_id = "C123"
             pair_phone: None,
             pair_code: None,
             allowed_numbers: vec!["+1".into()],
+            reply_template: None,
+            reply_template_language: None,
         };
         let toml_str = toml::to_string(&wc).unwrap();
         let parsed: WhatsAppConfig = toml::from_str(&toml_str).unwrap();
@@ -5614,6 +8447,19 @@ channel_id = "C123"
         assert_eq!(parsed.allowed_numbers, vec!["+1"]);
     }
 
+    #[test]
+    async fn whatsapp_config_reply_template_defaults_language() {
+        let json = r#"{"access_token":"tok","phone_number_id":"123","verify_token":"ver","reply_template":"build_update"}"#;
+        let parsed: WhatsAppConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            parsed.reply_template_with_language(),
+            Some(("build_update".into(), "en_US".into()))
+        );
+        let json = r#"{"access_token":"tok","phone_number_id":"123","verify_token":"ver"}"#;
+        let parsed: WhatsAppConfig = serde_json::from_str(json).unwrap();
+        assert!(parsed.reply_template_with_language().is_none());
+    }
+
     #[test]
     async fn whatsapp_config_deserializes_without_allowed_numbers() {
         let json = r#"{"access_token":"tok","phone_number_id":"123","verify_token":"ver"}"#;
@@ -5632,6 +8478,8 @@ channel_id = "C123"
             pair_phone: None,
             pair_code: None,
             allowed_numbers: vec!["*".into()],
+            reply_template: None,
+            reply_template_language: None,
         };
         let toml_str = toml::to_string(&wc).unwrap();
         let parsed: WhatsAppConfig = toml::from_str(&toml_str).unwrap();
@@ -5649,6 +8497,8 @@ channel_id = "C123"
             pair_phone: None,
             pair_code: None,
             allowed_numbers: vec!["+1".into()],
+            reply_template: None,
+            reply_template_language: None,
         };
         assert!(wc.is_ambiguous_config());
         assert_eq!(wc.backend_type(), "cloud");
@@ -5665,6 +8515,8 @@ channel_id = "C123"
             pair_phone: None,
             pair_code: None,
             allowed_numbers: vec![],
+            reply_template: None,
+            reply_template_language: None,
         };
         assert!(!wc.is_ambiguous_config());
         assert_eq!(wc.backend_type(), "web");
@@ -5691,6 +8543,8 @@ channel_id = "C123"
                 pair_phone: None,
                 pair_code: None,
                 allowed_numbers: vec!["+1".into()],
+                reply_template: None,
+                reply_template_language: None,
             }),
             linq: None,
             nextcloud_talk: None,
@@ -5705,6 +8559,7 @@ channel_id = "C123"
             nostr: None,
             clawdtalk: None,
             message_timeout_secs: 300,
+            observers: Vec::new(),
         };
         let toml_str = toml::to_string_pretty(&c).unwrap();
         let parsed: ChannelsConfig = toml::from_str(&toml_str).unwrap();
@@ -6262,6 +9117,413 @@ default_temperature = 0.7
         assert!(result.is_ok(), "expected validation to pass: {result:?}");
     }
 
+    #[test]
+    async fn validate_sandbox_providers_are_known_and_unique() {
+        let mut config = Config::default();
+        assert!(config.validate().is_ok());
+        config.zerobuild.sandbox.providers = vec!["local".into(), "e2b".into()];
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("'e2b' is unknown"));
+        config.zerobuild.sandbox.providers = vec!["local".into(), "local".into()];
+        assert!(config.validate().unwrap_err().to_string().contains("twice"));
+        config.zerobuild.sandbox.providers = Vec::new();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    async fn validate_kubernetes_sandbox() {
+        let mut config = Config::default();
+        config.zerobuild.sandbox.providers = vec!["kubernetes".into(), "local".into()];
+        assert!(config.validate().is_ok());
+        config.zerobuild.sandbox.kubernetes.preview_domain = "https://preview.example.com".into();
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("preview_domain"));
+        config.zerobuild.sandbox.kubernetes.preview_domain = "preview.example.com".into();
+        config.zerobuild.sandbox.kubernetes.namespace = "Build_Sandboxes".into();
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("namespace"));
+        config.zerobuild.sandbox.kubernetes.namespace = "build-sandboxes".into();
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    async fn validate_firecracker_sandbox() {
+        let mut config = Config::default();
+        config.zerobuild.sandbox.providers = vec!["firecracker".into()];
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("kernel and rootfs"));
+        config.zerobuild.sandbox.firecracker.kernel = "/var/lib/zerobuild/vmlinux".into();
+        config.zerobuild.sandbox.firecracker.rootfs = "/var/lib/zerobuild/rootfs.ext4".into();
+        assert!(config.validate().is_ok());
+        config.zerobuild.sandbox.firecracker.guest_ip = "10.0.0.2".into();
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("guest_ip"));
+        config.zerobuild.sandbox.firecracker.guest_ip = "172.16.0.2".into();
+        config.zerobuild.sandbox.firecracker.hypervisor = "qemu".into();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    async fn validate_fly_sandbox() {
+        let mut config = Config::default();
+        config.zerobuild.sandbox.providers = vec!["fly".into(), "local".into()];
+        assert!(config.validate().is_ok());
+        config.zerobuild.sandbox.fly.preview_ports = vec![3000, 443];
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("only first"));
+        config.zerobuild.sandbox.fly.preview_ports = vec![3000, 3000];
+        assert!(config.validate().unwrap_err().to_string().contains("twice"));
+        config.zerobuild.sandbox.fly.preview_ports = vec![443, 3000];
+        assert!(config.validate().is_ok());
+        config.zerobuild.sandbox.fly.cpu_kind = "dedicated".into();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    async fn validate_a2a_needs_a_token_and_valid_peers() {
+        let mut config = Config::default();
+        config.factory.a2a.enabled = true;
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("factory.a2a.token"));
+        config.factory.a2a.token = Some("t0ken".into());
+        assert!(config.validate().is_ok());
+
+        config.factory.a2a.peers.insert(
+            "api".into(),
+            A2aPeerConfig {
+                url: "api.example.com".into(),
+                token: "t".into(),
+                description: None,
+            },
+        );
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("factory.a2a.peers.api.url"));
+        config.factory.a2a.peers.get_mut("api").unwrap().url = "https://api.example.com".into();
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    async fn validate_bus_needs_a_shared_backend_for_workers() {
+        let mut config = Config::default();
+        assert_eq!(config.factory.bus.backend, "memory");
+        config.factory.bus.workers = 2;
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("shared backend"));
+
+        config.factory.bus.backend = "redis".into();
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("factory.bus.url"));
+        config.factory.bus.url = Some("redis://127.0.0.1:6379".into());
+        assert!(config.validate().is_ok());
+
+        config.factory.bus.namespace = "zero build".into();
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("factory.bus.namespace"));
+        config.factory.bus.namespace = "zerobuild".into();
+        config.factory.bus.backend = "kafka".into();
+        assert!(config.validate().unwrap_err().to_string().contains("kafka"));
+    }
+
+    #[test]
+    async fn validate_sandbox_templates() {
+        let mut config = Config::default();
+        config.zerobuild.templates.insert(
+            "nextjs".into(),
+            SandboxTemplateConfig {
+                image: "ghcr.io/acme/next-prebuilt:15".into(),
+                prebuilt_dir: "/opt/prebuilt/nextjs".into(),
+            },
+        );
+        assert!(config.validate().is_ok());
+        config.zerobuild.templates.insert(
+            "fastapi".into(),
+            SandboxTemplateConfig {
+                prebuilt_dir: "prebuilt/fastapi".into(),
+                ..SandboxTemplateConfig::default()
+            },
+        );
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("zerobuild.templates.fastapi.prebuilt_dir"));
+        config.zerobuild.templates.remove("fastapi");
+        config
+            .zerobuild
+            .templates
+            .insert("cobol".into(), SandboxTemplateConfig::default());
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("zerobuild.templates.cobol"));
+    }
+
+    #[test]
+    async fn validate_channel_observers() {
+        let mut config = Config::default();
+        config.channels_config = toml::from_str(
+            r#"
+            cli = true
+            [[observers]]
+            channel = "discord"
+            target = "1234"
+            [[observers]]
+            channel = "telegram"
+            target = "-100200"
+            events = ["deploy"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.channels_config.observers[0].events,
+            [
+                ObserverEvent::Status,
+                ObserverEvent::Diff,
+                ObserverEvent::Deploy
+            ]
+        );
+        assert!(config.validate().is_ok());
+        config.channels_config.observers[1].events.clear();
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("observers[1].events"));
+        config.channels_config.observers[1].target = " ".into();
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("needs a channel and a target"));
+    }
+
+    #[test]
+    async fn validate_factory_review() {
+        let mut config = Config::default();
+        config.factory.review.enabled = true;
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("factory.review.repos must list"));
+        config
+            .factory
+            .review
+            .repos
+            .insert("acme/shop".into(), ReviewRepoConfig::default());
+        assert!(config.validate().is_ok());
+        config
+            .factory
+            .review
+            .repos
+            .get_mut("acme/shop")
+            .unwrap()
+            .checklist = vec!["#tests".into(), "#error handling".into()];
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("single hashtags"));
+
+        let parsed: FactoryReviewConfig = toml::from_str(
+            r#"
+            enabled = true
+            [repos."acme/shop"]
+            comment_at = "low"
+            "#,
+        )
+        .unwrap();
+        let shop = &parsed.repos["acme/shop"];
+        assert_eq!(shop.comment_at, ReviewSeverity::Low);
+        assert_eq!(shop.request_changes_at, ReviewSeverity::High);
+        assert!(ReviewSeverity::Critical > ReviewSeverity::High);
+    }
+
+    #[test]
+    async fn validate_factory_triage() {
+        let mut config = Config::default();
+        config.factory.triage.enabled = true;
+        config.factory.triage.repos = vec!["acme/shop".into()];
+        assert!(config.validate().is_ok());
+        config.factory.triage.repos.push("shop".into());
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("factory.triage.repos entry 'shop'"));
+        config.factory.triage.repos.pop();
+        config.factory.triage.draft_fixes = true;
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("factory.triage.draft_fixes"));
+    }
+
+    #[test]
+    async fn validate_maintenance() {
+        let mut config = Config::default();
+        config.zerobuild.maintenance.interval_hours = 0;
+        assert!(config.validate().is_ok());
+        config.zerobuild.maintenance.enabled = true;
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("zerobuild.maintenance.interval_hours"));
+        config.zerobuild.maintenance.interval_hours = 24;
+        config.zerobuild.maintenance.branch = " ".into();
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("zerobuild.maintenance.branch"));
+    }
+
+    #[test]
+    async fn validate_rejects_zero_rate_limit_burst() {
+        let mut config = Config::default();
+        config.reliability.rate_limit.burst = 0;
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("reliability.rate_limit.burst"));
+    }
+
+    #[test]
+    async fn validate_moderation_thresholds_and_patterns() {
+        let mut config = Config::default();
+        config
+            .moderation
+            .category_thresholds
+            .insert("violence".into(), 1.5);
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("moderation.category_thresholds.violence"));
+        config.moderation.category_thresholds.clear();
+        config.moderation.blocked_patterns = vec!["(".into()];
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("moderation.blocked_patterns"));
+    }
+
+    #[test]
+    async fn validate_repo_access_entries() {
+        let mut config = Config::default();
+        config.zerobuild.repos.deny = vec!["acme".into(), "acme/*".into(), "me/site".into()];
+        assert!(config.validate().is_ok());
+        config.zerobuild.repos.deny = vec!["acme/a/b".into()];
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("zerobuild.repos.deny"));
+    }
+
+    #[test]
+    async fn deploy_allowlist_limits_github_access_only_when_denying() {
+        let mut config = Config::default();
+        config.guardrails.deploy_repo_allowlist = vec!["acme/*".into()];
+        config.share_repo_allowlist();
+        assert_eq!(config.zerobuild.repos.allow, vec!["acme/*".to_string()]);
+
+        config.guardrails.deploy_action = GuardrailAction::Ask;
+        config.share_repo_allowlist();
+        assert!(config.zerobuild.repos.allow.is_empty());
+    }
+
+    #[test]
+    async fn validate_email_webhook_needs_a_secret() {
+        let mut config = Config::default();
+        config.channels_config.email = Some(crate::channels::email_channel::EmailConfig {
+            inbound: "webhook".into(),
+            ..Default::default()
+        });
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("webhook_secret"));
+        if let Some(email) = config.channels_config.email.as_mut() {
+            email.webhook_secret = Some("s3cret".into());
+        }
+        assert!(config.validate().is_ok());
+        if let Some(email) = config.channels_config.email.as_mut() {
+            email.inbound = "jmap".into();
+        }
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    async fn validate_image_backends_need_their_settings() {
+        let mut config = Config::default();
+        config.zerobuild.images.backend = ImageBackend::Imgur;
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("imgur_client_id"));
+
+        config.zerobuild.images.backend = ImageBackend::S3;
+        config.zerobuild.images.s3 = ImageS3Config {
+            endpoint: "https://s3.us-east-1.amazonaws.com".into(),
+            bucket: "shots".into(),
+            access_key_id: "AKID".into(),
+            ..ImageS3Config::default()
+        };
+        assert!(config.validate().is_err());
+        config.zerobuild.images.s3.secret_access_key = Some("secret".into());
+        assert!(config.validate().is_ok());
+
+        config.zerobuild.artifacts.backend = ArtifactBackend::S3;
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("[zerobuild.artifacts.s3]"));
+        config.zerobuild.artifacts.s3 = config.zerobuild.images.s3.clone();
+        assert!(config.validate().is_ok());
+    }
+
     #[test]
     async fn env_override_model_fallback() {
         let _env_guard = env_override_lock().await;
@@ -7124,6 +10386,9 @@ default_model = "legacy-model"
         assert_eq!(tc.model, "whisper-large-v3-turbo");
         assert!(tc.language.is_none());
         assert_eq!(tc.max_duration_secs, 120);
+        assert!(!tc.voice_replies);
+        assert_eq!(tc.tts_voice, "alloy");
+        assert_eq!(tc.voice_summary_max_chars, 400);
     }
 
     #[test]
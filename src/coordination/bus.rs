@@ -0,0 +1,337 @@
+//! Pluggable coordination bus shared by factory processes.
+//!
+//! A [`CoordinationBus`] carries two kinds of traffic:
+//!
+//! - **Versioned context**: keys written with an expected version, so
+//!   concurrent writers cannot silently overwrite each other. The factory
+//!   blackboard stores its artifacts here.
+//! - **Work queues**: messages enqueued once and claimed by exactly one
+//!   consumer, then acknowledged. Factory stage workers pick up stages here.
+//!
+//! [`LocalBus`] keeps everything in process on top of [`InMemoryMessageBus`].
+//! The Redis Streams (`bus-redis` feature) and NATS JetStream (`bus-nats`
+//! feature) backends let several ZeroBuild processes share one bus; pick one
+//! with `[factory.bus]`.
+
+use super::{
+    CoordinationEnvelope, CoordinationError, CoordinationPayload, InMemoryMessageBus,
+    SharedContextEntry,
+};
+use crate::config::FactoryBusConfig;
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+
+#[cfg(feature = "bus-nats")]
+mod nats_bus;
+#[cfg(feature = "bus-redis")]
+mod redis_bus;
+
+/// Conversation id of envelopes written by [`LocalBus`].
+const LOCAL_CONVERSATION_ID: &str = "coordination_bus";
+/// Topic of context patches written by [`LocalBus`].
+const LOCAL_TOPIC: &str = "context";
+
+/// A versioned context value as stored on a bus.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContextEntry {
+    pub value: Value,
+    pub version: u64,
+    pub updated_by: String,
+}
+
+impl From<SharedContextEntry> for ContextEntry {
+    fn from(entry: SharedContextEntry) -> Self {
+        Self {
+            value: entry.value,
+            version: entry.version,
+            updated_by: entry.updated_by,
+        }
+    }
+}
+
+/// A message claimed from a work queue, pending acknowledgement.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueuedMessage {
+    pub id: String,
+    pub payload: Value,
+}
+
+/// Shared context and work queues, in process or across processes.
+#[async_trait]
+pub trait CoordinationBus: Send + Sync {
+    /// Backend name, as written in `[factory.bus] backend`.
+    fn backend(&self) -> &'static str;
+
+    /// Read the current entry for `key`.
+    async fn context_entry(&self, key: &str) -> anyhow::Result<Option<ContextEntry>>;
+
+    /// Write `value` to `key` if its current version is `expected_version`
+    /// (`0` for a key that does not exist yet) and return the new version.
+    ///
+    /// Fails with [`CoordinationError::ContextVersionMismatch`] when another
+    /// writer got there first.
+    async fn patch_context(
+        &self,
+        key: &str,
+        expected_version: u64,
+        value: Value,
+        updated_by: &str,
+    ) -> anyhow::Result<u64>;
+
+    /// Append `payload` to `queue` and return its message id.
+    async fn enqueue(&self, queue: &str, payload: Value) -> anyhow::Result<String>;
+
+    /// Claim the next message of `queue` for `consumer`, waiting up to
+    /// `wait` for one to arrive.
+    async fn claim(
+        &self,
+        queue: &str,
+        consumer: &str,
+        wait: Duration,
+    ) -> anyhow::Result<Option<QueuedMessage>>;
+
+    /// Acknowledge a claimed message so it is not handed out again.
+    async fn ack(&self, queue: &str, id: &str) -> anyhow::Result<()>;
+}
+
+/// Open the bus selected by `[factory.bus]`. Network backends connect on
+/// first use, so this never blocks.
+pub fn connect(config: &FactoryBusConfig) -> anyhow::Result<Arc<dyn CoordinationBus>> {
+    match config.backend.trim() {
+        "memory" => Ok(Arc::new(LocalBus::new())),
+        #[cfg(feature = "bus-redis")]
+        "redis" => Ok(Arc::new(redis_bus::RedisBus::new(
+            config.url.as_deref().unwrap_or_default(),
+            &config.namespace,
+        )?)),
+        #[cfg(feature = "bus-nats")]
+        "nats" => Ok(Arc::new(nats_bus::NatsBus::new(
+            config.url.as_deref().unwrap_or_default(),
+            &config.namespace,
+        ))),
+        #[cfg(not(feature = "bus-redis"))]
+        "redis" => anyhow::bail!(
+            "[factory.bus] backend \"redis\" needs ZeroBuild built with --features bus-redis"
+        ),
+        #[cfg(not(feature = "bus-nats"))]
+        "nats" => anyhow::bail!(
+            "[factory.bus] backend \"nats\" needs ZeroBuild built with --features bus-nats"
+        ),
+        other => anyhow::bail!("unknown [factory.bus] backend \"{other}\""),
+    }
+}
+
+#[derive(Default)]
+struct LocalQueue {
+    ready: VecDeque<QueuedMessage>,
+    claimed: HashMap<String, QueuedMessage>,
+}
+
+/// In-process bus: context on an [`InMemoryMessageBus`], queues in memory.
+/// Nothing is shared with other processes.
+#[derive(Default)]
+pub struct LocalBus {
+    context: InMemoryMessageBus,
+    queues: Mutex<HashMap<String, LocalQueue>>,
+    wake: Notify,
+}
+
+impl LocalBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn try_claim(&self, queue: &str) -> Option<QueuedMessage> {
+        let mut queues = self.queues.lock().unwrap_or_else(|e| e.into_inner());
+        let queue = queues.get_mut(queue)?;
+        let message = queue.ready.pop_front()?;
+        queue.claimed.insert(message.id.clone(), message.clone());
+        Some(message)
+    }
+}
+
+#[async_trait]
+impl CoordinationBus for LocalBus {
+    fn backend(&self) -> &'static str {
+        "memory"
+    }
+
+    async fn context_entry(&self, key: &str) -> anyhow::Result<Option<ContextEntry>> {
+        Ok(self.context.context_entry(key).map(ContextEntry::from))
+    }
+
+    async fn patch_context(
+        &self,
+        key: &str,
+        expected_version: u64,
+        value: Value,
+        updated_by: &str,
+    ) -> anyhow::Result<u64> {
+        let envelope = CoordinationEnvelope::new_broadcast(
+            updated_by,
+            LOCAL_CONVERSATION_ID,
+            LOCAL_TOPIC,
+            CoordinationPayload::ContextPatch {
+                key: key.to_string(),
+                expected_version,
+                value,
+            },
+        );
+        self.context.publish(envelope)?;
+        Ok(expected_version + 1)
+    }
+
+    async fn enqueue(&self, queue: &str, payload: Value) -> anyhow::Result<String> {
+        let id = uuid::Uuid::new_v4().to_string();
+        self.queues
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(queue.to_string())
+            .or_default()
+            .ready
+            .push_back(QueuedMessage {
+                id: id.clone(),
+                payload,
+            });
+        self.wake.notify_waiters();
+        Ok(id)
+    }
+
+    async fn claim(
+        &self,
+        queue: &str,
+        _consumer: &str,
+        wait: Duration,
+    ) -> anyhow::Result<Option<QueuedMessage>> {
+        let deadline = tokio::time::Instant::now() + wait;
+        loop {
+            // Register interest before checking, so an enqueue in between
+            // still wakes us.
+            let notified = self.wake.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            if let Some(message) = self.try_claim(queue) {
+                return Ok(Some(message));
+            }
+            if tokio::time::timeout_at(deadline, notified).await.is_err() {
+                return Ok(self.try_claim(queue));
+            }
+        }
+    }
+
+    async fn ack(&self, queue: &str, id: &str) -> anyhow::Result<()> {
+        if let Some(queue) = self
+            .queues
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get_mut(queue)
+        {
+            queue.claimed.remove(id);
+        }
+        Ok(())
+    }
+}
+
+/// Error for a lost compare-and-set, shared by the network backends.
+#[cfg_attr(
+    not(any(feature = "bus-redis", feature = "bus-nats")),
+    allow(dead_code)
+)]
+fn version_mismatch(key: &str, expected: u64, actual: u64) -> anyhow::Error {
+    CoordinationError::ContextVersionMismatch {
+        key: key.to_string(),
+        expected,
+        actual,
+    }
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn local_bus_patches_context_with_versions() {
+        let bus = LocalBus::new();
+        assert_eq!(bus.context_entry("k").await.unwrap(), None);
+
+        assert_eq!(bus.patch_context("k", 0, json!(1), "a").await.unwrap(), 1);
+        assert_eq!(bus.patch_context("k", 1, json!(2), "b").await.unwrap(), 2);
+        let entry = bus.context_entry("k").await.unwrap().unwrap();
+        assert_eq!(entry.value, json!(2));
+        assert_eq!(entry.version, 2);
+        assert_eq!(entry.updated_by, "b");
+
+        let stale = bus.patch_context("k", 1, json!(3), "c").await.unwrap_err();
+        assert!(matches!(
+            stale.downcast_ref::<CoordinationError>(),
+            Some(CoordinationError::ContextVersionMismatch { actual: 2, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn local_bus_hands_each_message_to_one_consumer() {
+        let bus = LocalBus::new();
+        let first = bus.enqueue("stages", json!("a")).await.unwrap();
+        bus.enqueue("stages", json!("b")).await.unwrap();
+
+        let claimed = bus
+            .claim("stages", "w1", Duration::ZERO)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(claimed.id, first);
+        assert_eq!(claimed.payload, json!("a"));
+        bus.ack("stages", &claimed.id).await.unwrap();
+
+        let second = bus
+            .claim("stages", "w2", Duration::ZERO)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(second.payload, json!("b"));
+        assert!(bus
+            .claim("stages", "w1", Duration::from_millis(10))
+            .await
+            .unwrap()
+            .is_none());
+        assert!(bus
+            .claim("other", "w1", Duration::ZERO)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn local_bus_claim_waits_for_an_enqueue() {
+        let bus = Arc::new(LocalBus::new());
+        let waiter = {
+            let bus = Arc::clone(&bus);
+            tokio::spawn(async move { bus.claim("stages", "w1", Duration::from_secs(5)).await })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        bus.enqueue("stages", json!("late")).await.unwrap();
+
+        let claimed = waiter.await.unwrap().unwrap().unwrap();
+        assert_eq!(claimed.payload, json!("late"));
+    }
+
+    #[test]
+    fn connect_rejects_unknown_backends() {
+        let config = FactoryBusConfig {
+            backend: "kafka".into(),
+            ..FactoryBusConfig::default()
+        };
+        let err = connect(&config).err().unwrap();
+        assert!(err.to_string().contains("kafka"));
+        assert_eq!(
+            connect(&FactoryBusConfig::default()).unwrap().backend(),
+            "memory"
+        );
+    }
+}
@@ -0,0 +1,252 @@
+//! NATS backend: context keys live in a JetStream key-value bucket updated
+//! by revision, queues are subjects of one work-queue stream with a durable
+//! pull consumer each.
+
+use super::{version_mismatch, ContextEntry, CoordinationBus, QueuedMessage};
+use async_nats::jetstream::{self, consumer::pull, kv, stream};
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::OnceCell;
+
+/// How long a claimed message may stay unacknowledged before JetStream
+/// hands it to another worker, e.g. after its worker died.
+const ACK_WAIT: Duration = Duration::from_secs(60 * 60);
+
+/// Stored form of a context entry; the bucket revision guards the update.
+#[derive(Serialize, Deserialize)]
+struct StoredEntry {
+    value: Value,
+    version: u64,
+    updated_by: String,
+}
+
+struct Connection {
+    jetstream: jetstream::Context,
+    context: kv::Store,
+    queues: stream::Stream,
+}
+
+pub(super) struct NatsBus {
+    url: String,
+    namespace: String,
+    conn: OnceCell<Connection>,
+    /// Claimed messages by id, kept until they are acknowledged.
+    pending: Mutex<HashMap<String, jetstream::Message>>,
+}
+
+impl NatsBus {
+    pub(super) fn new(url: &str, namespace: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            namespace: namespace.to_string(),
+            conn: OnceCell::new(),
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    async fn conn(&self) -> anyhow::Result<&Connection> {
+        self.conn
+            .get_or_try_init(|| async {
+                let client = async_nats::connect(self.url.as_str()).await?;
+                let jetstream = jetstream::new(client);
+
+                let bucket = format!("{}_context", self.namespace);
+                let context = match jetstream.get_key_value(bucket.as_str()).await {
+                    Ok(store) => store,
+                    Err(_) => {
+                        jetstream
+                            .create_key_value(kv::Config {
+                                bucket,
+                                history: 1,
+                                ..kv::Config::default()
+                            })
+                            .await?
+                    }
+                };
+                let queues = jetstream
+                    .get_or_create_stream(stream::Config {
+                        name: format!("{}_queues", self.namespace),
+                        subjects: vec![format!("{}.queue.>", self.namespace)],
+                        retention: stream::RetentionPolicy::WorkQueue,
+                        ..stream::Config::default()
+                    })
+                    .await?;
+                anyhow::Ok(Connection {
+                    jetstream,
+                    context,
+                    queues,
+                })
+            })
+            .await
+    }
+
+    fn subject(&self, queue: &str) -> String {
+        format!("{}.queue.{}", self.namespace, kv_key(queue))
+    }
+
+    async fn stored(&self, key: &str) -> anyhow::Result<Option<(StoredEntry, u64)>> {
+        let conn = self.conn().await?;
+        match conn.context.entry(kv_key(key)).await? {
+            Some(entry) if entry.operation == kv::Operation::Put => Ok(Some((
+                serde_json::from_slice(&entry.value)?,
+                entry.revision,
+            ))),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Key-value keys allow `[-/_=.a-zA-Z0-9]`; escape anything else as `=XX`.
+fn kv_key(key: &str) -> String {
+    let mut escaped = String::with_capacity(key.len());
+    for byte in key.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'/' | b'_' | b'.') {
+            escaped.push(char::from(byte));
+        } else {
+            let _ = write!(escaped, "={byte:02X}");
+        }
+    }
+    escaped
+}
+
+#[async_trait]
+impl CoordinationBus for NatsBus {
+    fn backend(&self) -> &'static str {
+        "nats"
+    }
+
+    async fn context_entry(&self, key: &str) -> anyhow::Result<Option<ContextEntry>> {
+        Ok(self.stored(key).await?.map(|(entry, _)| ContextEntry {
+            value: entry.value,
+            version: entry.version,
+            updated_by: entry.updated_by,
+        }))
+    }
+
+    async fn patch_context(
+        &self,
+        key: &str,
+        expected_version: u64,
+        value: Value,
+        updated_by: &str,
+    ) -> anyhow::Result<u64> {
+        let conn = self.conn().await?;
+        let current = self.stored(key).await?;
+        let actual = current.as_ref().map_or(0, |(entry, _)| entry.version);
+        if actual != expected_version {
+            return Err(version_mismatch(key, expected_version, actual));
+        }
+        let version = expected_version + 1;
+        let body = serde_json::to_vec(&StoredEntry {
+            value,
+            version,
+            updated_by: updated_by.to_string(),
+        })?;
+        let written = match current {
+            Some((_, revision)) => conn
+                .context
+                .update(kv_key(key), body.into(), revision)
+                .await
+                .map_err(anyhow::Error::from),
+            None => conn
+                .context
+                .create(kv_key(key), body.into())
+                .await
+                .map_err(anyhow::Error::from),
+        };
+        if let Err(e) = written {
+            // Lost the race to another writer between the read and the write.
+            let actual = self
+                .stored(key)
+                .await?
+                .map_or(0, |(entry, _)| entry.version);
+            if actual != expected_version {
+                return Err(version_mismatch(key, expected_version, actual));
+            }
+            return Err(e);
+        }
+        Ok(version)
+    }
+
+    async fn enqueue(&self, queue: &str, payload: Value) -> anyhow::Result<String> {
+        let conn = self.conn().await?;
+        let ack = conn
+            .jetstream
+            .publish(self.subject(queue), payload.to_string().into())
+            .await?
+            .await?;
+        Ok(ack.sequence.to_string())
+    }
+
+    async fn claim(
+        &self,
+        queue: &str,
+        _consumer: &str,
+        wait: Duration,
+    ) -> anyhow::Result<Option<QueuedMessage>> {
+        let conn = self.conn().await?;
+        let durable = format!("{}_workers", kv_key(queue).replace(['/', '.', '='], "_"));
+        let consumer: jetstream::consumer::PullConsumer = conn
+            .queues
+            .get_or_create_consumer(
+                &durable,
+                pull::Config {
+                    durable_name: Some(durable.clone()),
+                    filter_subject: self.subject(queue),
+                    ack_wait: ACK_WAIT,
+                    ..pull::Config::default()
+                },
+            )
+            .await?;
+        let mut batch = consumer
+            .batch()
+            .max_messages(1)
+            .expires(wait.max(Duration::from_secs(1)))
+            .messages()
+            .await?;
+        let Some(message) = batch.next().await else {
+            return Ok(None);
+        };
+        let message = message.map_err(|e| anyhow::anyhow!(e))?;
+        let id = message
+            .info()
+            .map_err(|e| anyhow::anyhow!(e))?
+            .stream_sequence
+            .to_string();
+        let payload = serde_json::from_slice(&message.payload)?;
+        self.pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(id.clone(), message);
+        Ok(Some(QueuedMessage { id, payload }))
+    }
+
+    async fn ack(&self, _queue: &str, id: &str) -> anyhow::Result<()> {
+        let message = self
+            .pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(id);
+        match message {
+            Some(message) => message.ack().await.map_err(|e| anyhow::anyhow!(e)),
+            None => anyhow::bail!("message {id} was not claimed by this process"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kv_keys_escape_disallowed_characters() {
+        assert_eq!(kv_key("artifact:prd"), "artifact=3Aprd");
+        assert_eq!(kv_key("factory/ab-12/stage.x"), "factory/ab-12/stage.x");
+    }
+}
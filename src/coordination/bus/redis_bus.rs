@@ -0,0 +1,193 @@
+//! Redis backend: context keys are hashes updated by a compare-and-set
+//! script, queues are streams read through one consumer group.
+
+use super::{version_mismatch, ContextEntry, CoordinationBus, QueuedMessage};
+use ::redis::aio::ConnectionManager;
+use ::redis::streams::{StreamReadOptions, StreamReadReply};
+use ::redis::{AsyncCommands, Client, Script};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::OnceCell;
+
+/// Consumer group shared by every worker reading a queue.
+const GROUP: &str = "workers";
+
+/// Bump the version of `KEYS[1]` from `ARGV[1]` and store the value and
+/// writer; returns `{1, new_version}`, or `{0, current_version}` when the
+/// expected version is stale.
+const PATCH_SCRIPT: &str = r"
+local current = tonumber(redis.call('HGET', KEYS[1], 'version') or '0')
+if current ~= tonumber(ARGV[1]) then
+  return {0, current}
+end
+redis.call('HSET', KEYS[1], 'value', ARGV[2], 'version', current + 1, 'updated_by', ARGV[3])
+return {1, current + 1}
+";
+
+pub(super) struct RedisBus {
+    client: Client,
+    namespace: String,
+    conn: OnceCell<ConnectionManager>,
+    patch: Script,
+    groups: Mutex<HashSet<String>>,
+}
+
+impl RedisBus {
+    pub(super) fn new(url: &str, namespace: &str) -> anyhow::Result<Self> {
+        Ok(Self {
+            client: Client::open(url)?,
+            namespace: namespace.to_string(),
+            conn: OnceCell::new(),
+            patch: Script::new(PATCH_SCRIPT),
+            groups: Mutex::new(HashSet::new()),
+        })
+    }
+
+    async fn conn(&self) -> anyhow::Result<ConnectionManager> {
+        let conn = self
+            .conn
+            .get_or_try_init(|| ConnectionManager::new(self.client.clone()))
+            .await?;
+        Ok(conn.clone())
+    }
+
+    fn context_key(&self, key: &str) -> String {
+        format!("{}:context:{key}", self.namespace)
+    }
+
+    fn stream_key(&self, queue: &str) -> String {
+        format!("{}:queue:{queue}", self.namespace)
+    }
+
+    /// Create the consumer group of `stream` once per process. Messages
+    /// enqueued before any worker started are still delivered.
+    async fn ensure_group(&self, stream: &str) -> anyhow::Result<()> {
+        if self
+            .groups
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .contains(stream)
+        {
+            return Ok(());
+        }
+        let mut conn = self.conn().await?;
+        let created: Result<(), _> = conn.xgroup_create_mkstream(stream, GROUP, "0").await;
+        match created {
+            Ok(()) => {}
+            Err(e) if e.code() == Some("BUSYGROUP") => {}
+            Err(e) => return Err(e.into()),
+        }
+        self.groups
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(stream.to_string());
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CoordinationBus for RedisBus {
+    fn backend(&self) -> &'static str {
+        "redis"
+    }
+
+    async fn context_entry(&self, key: &str) -> anyhow::Result<Option<ContextEntry>> {
+        let mut conn = self.conn().await?;
+        let fields: HashMap<String, String> = conn.hgetall(self.context_key(key)).await?;
+        let Some(value) = fields.get("value") else {
+            return Ok(None);
+        };
+        Ok(Some(ContextEntry {
+            value: serde_json::from_str(value)?,
+            version: fields
+                .get("version")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0),
+            updated_by: fields.get("updated_by").cloned().unwrap_or_default(),
+        }))
+    }
+
+    async fn patch_context(
+        &self,
+        key: &str,
+        expected_version: u64,
+        value: Value,
+        updated_by: &str,
+    ) -> anyhow::Result<u64> {
+        let mut conn = self.conn().await?;
+        let (applied, version): (u8, u64) = self
+            .patch
+            .key(self.context_key(key))
+            .arg(expected_version)
+            .arg(value.to_string())
+            .arg(updated_by)
+            .invoke_async(&mut conn)
+            .await?;
+        if applied == 1 {
+            Ok(version)
+        } else {
+            Err(version_mismatch(key, expected_version, version))
+        }
+    }
+
+    async fn enqueue(&self, queue: &str, payload: Value) -> anyhow::Result<String> {
+        let mut conn = self.conn().await?;
+        let id: Option<String> = conn
+            .xadd(
+                self.stream_key(queue),
+                "*",
+                &[("payload", payload.to_string())],
+            )
+            .await?;
+        id.ok_or_else(|| anyhow::anyhow!("Redis did not return a stream id"))
+    }
+
+    async fn claim(
+        &self,
+        queue: &str,
+        consumer: &str,
+        wait: Duration,
+    ) -> anyhow::Result<Option<QueuedMessage>> {
+        let stream = self.stream_key(queue);
+        self.ensure_group(&stream).await?;
+        // A blocking read holds its connection, so it gets its own instead
+        // of stalling the shared one.
+        let mut conn = self.client.get_multiplexed_async_connection().await?;
+        let block_ms = usize::try_from(wait.as_millis())
+            .unwrap_or(usize::MAX)
+            .max(1);
+        let options = StreamReadOptions::default()
+            .group(GROUP, consumer)
+            .count(1)
+            .block(block_ms);
+        let reply: Option<StreamReadReply> = conn
+            .xread_options(&[stream.as_str()], &[">"], &options)
+            .await?;
+        let Some(entry) = reply
+            .into_iter()
+            .flat_map(|reply| reply.keys)
+            .flat_map(|key| key.ids)
+            .next()
+        else {
+            return Ok(None);
+        };
+        let payload: String = entry
+            .get("payload")
+            .ok_or_else(|| anyhow::anyhow!("stream entry {} has no payload", entry.id))?;
+        Ok(Some(QueuedMessage {
+            id: entry.id,
+            payload: serde_json::from_str(&payload)?,
+        }))
+    }
+
+    async fn ack(&self, queue: &str, id: &str) -> anyhow::Result<()> {
+        let stream = self.stream_key(queue);
+        let mut conn = self.conn().await?;
+        let _: usize = conn.xack(&stream, GROUP, &[id]).await?;
+        let _: usize = conn.xdel(&stream, &[id]).await?;
+        Ok(())
+    }
+}
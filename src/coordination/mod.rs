@@ -1,3 +1,5 @@
+pub mod bus;
+
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::{HashMap, HashSet, VecDeque};
@@ -49,7 +49,7 @@ pub async fn run(config: Config, host: String, port: u16) -> Result<()> {
                 max_backoff,
                 move || {
                     let cfg = channels_cfg.clone();
-                    async move { crate::channels::start_channels(cfg).await }
+                    async move { Box::pin(crate::channels::start_channels(cfg)).await }
                 },
             ));
         } else {
@@ -87,6 +87,88 @@ pub async fn run(config: Config, host: String, port: u16) -> Result<()> {
         tracing::info!("Cron disabled; scheduler supervisor not started");
     }
 
+    if config.memory.retention.compaction_interval_minutes > 0 {
+        let memory_cfg = config.memory.clone();
+        let workspace_dir = config.workspace_dir.clone();
+        handles.push(spawn_component_supervisor(
+            "memory-compaction",
+            initial_backoff,
+            max_backoff,
+            move || {
+                let cfg = memory_cfg.clone();
+                let dir = workspace_dir.clone();
+                async move { crate::memory::decay::run_compaction_worker(cfg, dir).await }
+            },
+        ));
+    }
+
+    if config.memory.consolidation.enabled {
+        let consolidation_cfg = config.clone();
+        handles.push(spawn_component_supervisor(
+            "memory-consolidation",
+            initial_backoff,
+            max_backoff,
+            move || {
+                let cfg = consolidation_cfg.clone();
+                async move {
+                    Box::pin(crate::memory::consolidation::run_consolidation_worker(cfg)).await
+                }
+            },
+        ));
+    }
+
+    if config.zerobuild.artifacts.cleanup_interval_minutes > 0 {
+        let zerobuild_cfg = config.zerobuild.clone();
+        handles.push(spawn_component_supervisor(
+            "artifact-cleanup",
+            initial_backoff,
+            max_backoff,
+            move || {
+                let cfg = zerobuild_cfg.clone();
+                async move { crate::artifacts::run_cleanup_worker(cfg).await }
+            },
+        ));
+    }
+
+    if config.zerobuild.maintenance.enabled {
+        let zerobuild_cfg = config.zerobuild.clone();
+        handles.push(spawn_component_supervisor(
+            "dependency-refresh",
+            initial_backoff,
+            max_backoff,
+            move || {
+                let cfg = zerobuild_cfg.clone();
+                async move { crate::factory::maintenance::run_refresh_worker(cfg).await }
+            },
+        ));
+    }
+
+    if config.factory.triage.enabled {
+        let triage_cfg = config.clone();
+        handles.push(spawn_component_supervisor(
+            "issue-triage",
+            initial_backoff,
+            max_backoff,
+            move || {
+                let cfg = triage_cfg.clone();
+                async move { Box::pin(crate::factory::triage::run_triage_worker(cfg)).await }
+            },
+        ));
+    }
+
+    if config.factory.review.enabled {
+        let review_cfg = config.clone();
+        handles.push(spawn_component_supervisor(
+            "pr-review",
+            initial_backoff,
+            max_backoff,
+            move || {
+                let cfg = review_cfg.clone();
+                async move { Box::pin(crate::factory::review::run_review_worker(cfg)).await }
+            },
+        ));
+    }
+
     println!("🧠 ZeroBuild daemon started");
     println!("   Gateway:  http://{host}:{port}");
     println!("   Components: gateway, channels, heartbeat, scheduler");
@@ -196,7 +278,7 @@ async fn run_heartbeat_worker(config: Config) -> Result<()> {
         for task in tasks {
             let prompt = format!("[Heartbeat Task] {task}");
             let temp = config.default_temperature;
-            if let Err(e) = crate::agent::run(
+            if let Err(e) = Box::pin(crate::agent::run(
                 config.clone(),
                 Some(prompt),
                 None,
@@ -204,7 +286,7 @@ async fn run_heartbeat_worker(config: Config) -> Result<()> {
                 temp,
                 vec![],
                 false,
-            )
+            ))
             .await
             {
                 crate::health::mark_component_error("heartbeat", e.to_string());
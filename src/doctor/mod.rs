@@ -561,12 +561,15 @@ fn provider_validation_error(name: &str) -> Option<String> {
 
 fn embedding_provider_validation_error(name: &str) -> Option<String> {
     let normalized = name.trim();
-    if normalized.eq_ignore_ascii_case("none") || normalized.eq_ignore_ascii_case("openai") {
+    if ["none", "openai", "openrouter", "local", "fastembed"]
+        .iter()
+        .any(|known| normalized.eq_ignore_ascii_case(known))
+    {
         return None;
     }
 
     let Some(url) = normalized.strip_prefix("custom:") else {
-        return Some("supported values: none, openai, custom:<url>".into());
+        return Some("supported values: none, openai, openrouter, local, custom:<url>".into());
     };
 
     let url = url.trim();
@@ -1097,6 +1100,9 @@ mod tests {
             provider: "groq".into(),
             model: String::new(),
             api_key: None,
+            temperature: None,
+            top_p: None,
+            seed: None,
         }];
         let mut items = Vec::new();
         check_config_semantics(&config, &mut items);
@@ -1208,6 +1214,8 @@ mod tests {
                 system_prompt: None,
                 api_key: None,
                 temperature: None,
+                top_p: None,
+                seed: None,
                 max_depth: 3,
                 agentic: false,
                 allowed_tools: Vec::new(),
@@ -1222,6 +1230,8 @@ mod tests {
                 system_prompt: None,
                 api_key: None,
                 temperature: None,
+                top_p: None,
+                seed: None,
                 max_depth: 3,
                 agentic: false,
                 allowed_tools: Vec::new(),
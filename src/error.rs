@@ -0,0 +1,290 @@
+//! Error taxonomy shared by sandboxes, tools, and the agent loop.
+//!
+//! Code that knows why it failed returns a [`ZerobuildError`]; everything
+//! else still returns plain `anyhow` errors, which [`code_of`] and
+//! [`ErrorCode::classify`] map onto the same categories from their message.
+//! Tool failures carry the resulting [`ErrorCode`] in
+//! `ToolResult::error_code`, and the agent loop shows it to the model next
+//! to the error text.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Machine-readable failure category.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// No sandbox has been created for the session.
+    SandboxMissing,
+    /// The session's sandbox existed but is gone.
+    SandboxExpired,
+    FileNotFound,
+    NotFound,
+    RateLimited,
+    /// Credentials are missing, expired, or rejected.
+    AuthFailed,
+    PermissionDenied,
+    Timeout,
+    DiskFull,
+    Network,
+    Conflict,
+    InvalidInput,
+    Internal,
+}
+
+impl ErrorCode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::SandboxMissing => "sandbox_missing",
+            Self::SandboxExpired => "sandbox_expired",
+            Self::FileNotFound => "file_not_found",
+            Self::NotFound => "not_found",
+            Self::RateLimited => "rate_limited",
+            Self::AuthFailed => "auth_failed",
+            Self::PermissionDenied => "permission_denied",
+            Self::Timeout => "timeout",
+            Self::DiskFull => "disk_full",
+            Self::Network => "network",
+            Self::Conflict => "conflict",
+            Self::InvalidInput => "invalid_input",
+            Self::Internal => "internal",
+        }
+    }
+
+    /// Whether retrying the same call later can succeed without changes.
+    pub fn is_transient(self) -> bool {
+        matches!(self, Self::RateLimited | Self::Timeout | Self::Network)
+    }
+
+    /// Best-effort category of an error message, for errors that were not
+    /// raised as a [`ZerobuildError`].
+    pub fn classify(message: &str) -> Option<Self> {
+        const RULES: &[(&[&str], ErrorCode)] = &[
+            (
+                &["no active sandbox", "no active local sandbox"],
+                ErrorCode::SandboxMissing,
+            ),
+            (
+                &["sandbox expired", "sandbox has expired"],
+                ErrorCode::SandboxExpired,
+            ),
+            (&["enospc", "no space left on device"], ErrorCode::DiskFull),
+            (
+                &["rate limit", "too many requests", "quota exceeded"],
+                ErrorCode::RateLimited,
+            ),
+            (
+                &[
+                    "unauthorized",
+                    "bad credentials",
+                    "authentication required",
+                    "invalid api key",
+                    "token expired",
+                ],
+                ErrorCode::AuthFailed,
+            ),
+            (
+                &["forbidden", "permission denied", "eacces", "not allowed"],
+                ErrorCode::PermissionDenied,
+            ),
+            (
+                &["no such file", "enoent", "file not found"],
+                ErrorCode::FileNotFound,
+            ),
+            (&["not found"], ErrorCode::NotFound),
+            (&["timed out", "timeout"], ErrorCode::Timeout),
+            (&["conflict", "already exists"], ErrorCode::Conflict),
+            (
+                &[
+                    "connection refused",
+                    "connection reset",
+                    "dns error",
+                    "error sending request",
+                ],
+                ErrorCode::Network,
+            ),
+            (
+                &[
+                    "missing required parameter",
+                    "invalid argument",
+                    "invalid parameter",
+                ],
+                ErrorCode::InvalidInput,
+            ),
+        ];
+
+        let message = message.to_ascii_lowercase();
+        RULES
+            .iter()
+            .find(|(needles, _)| needles.iter().any(|n| message.contains(n)))
+            .map(|(_, code)| *code)
+    }
+
+    /// Category of a failed HTTP response, or `None` for success statuses.
+    pub fn from_http(status: u16, body: &str) -> Option<Self> {
+        match status {
+            200..=399 => None,
+            401 => Some(Self::AuthFailed),
+            403 if body.to_ascii_lowercase().contains("rate limit") => Some(Self::RateLimited),
+            403 => Some(Self::PermissionDenied),
+            404 | 410 => Some(Self::NotFound),
+            408 | 504 => Some(Self::Timeout),
+            409 => Some(Self::Conflict),
+            400 | 422 => Some(Self::InvalidInput),
+            429 => Some(Self::RateLimited),
+            502 | 503 => Some(Self::Network),
+            _ => Some(Self::Internal),
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An error whose category is known where it is raised.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum ZerobuildError {
+    #[error("No active sandbox. Call sandbox_create first.")]
+    SandboxMissing,
+
+    #[error("Sandbox expired: {0}. Call sandbox_create to start a new one.")]
+    SandboxExpired(String),
+
+    #[error("File not found: {0}")]
+    FileNotFound(String),
+
+    #[error("Not found: {0}")]
+    NotFound(String),
+
+    #[error("Rate limited: {0}")]
+    RateLimited(String),
+
+    #[error("Authentication failed: {0}")]
+    AuthFailed(String),
+
+    #[error("Permission denied: {0}")]
+    PermissionDenied(String),
+
+    #[error("Timed out: {0}")]
+    Timeout(String),
+
+    #[error("No space left on device: {0}")]
+    DiskFull(String),
+
+    #[error("Network error: {0}")]
+    Network(String),
+
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+}
+
+impl ZerobuildError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::SandboxMissing => ErrorCode::SandboxMissing,
+            Self::SandboxExpired(_) => ErrorCode::SandboxExpired,
+            Self::FileNotFound(_) => ErrorCode::FileNotFound,
+            Self::NotFound(_) => ErrorCode::NotFound,
+            Self::RateLimited(_) => ErrorCode::RateLimited,
+            Self::AuthFailed(_) => ErrorCode::AuthFailed,
+            Self::PermissionDenied(_) => ErrorCode::PermissionDenied,
+            Self::Timeout(_) => ErrorCode::Timeout,
+            Self::DiskFull(_) => ErrorCode::DiskFull,
+            Self::Network(_) => ErrorCode::Network,
+            Self::Conflict(_) => ErrorCode::Conflict,
+            Self::InvalidInput(_) => ErrorCode::InvalidInput,
+        }
+    }
+
+    /// Typed form of an I/O error on `path`, for the kinds that have one.
+    pub fn from_io(path: &str, err: &std::io::Error) -> Option<Self> {
+        let detail = format!("{path}: {err}");
+        match err.kind() {
+            std::io::ErrorKind::NotFound => Some(Self::FileNotFound(path.to_string())),
+            std::io::ErrorKind::PermissionDenied => Some(Self::PermissionDenied(detail)),
+            std::io::ErrorKind::StorageFull => Some(Self::DiskFull(detail)),
+            std::io::ErrorKind::TimedOut => Some(Self::Timeout(detail)),
+            std::io::ErrorKind::AlreadyExists => Some(Self::Conflict(detail)),
+            _ => None,
+        }
+    }
+}
+
+/// Category of `err`: the code of the first [`ZerobuildError`] in its chain,
+/// else whatever [`ErrorCode::classify`] makes of the message, else
+/// [`ErrorCode::Internal`].
+pub fn code_of(err: &anyhow::Error) -> ErrorCode {
+    err.chain()
+        .find_map(|cause| cause.downcast_ref::<ZerobuildError>())
+        .map(ZerobuildError::code)
+        .or_else(|| ErrorCode::classify(&format!("{err:#}")))
+        .unwrap_or(ErrorCode::Internal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typed_errors_keep_their_code_through_context() {
+        let err = anyhow::Error::new(ZerobuildError::SandboxExpired("sbx-1".into()))
+            .context("writing src/main.rs");
+        assert_eq!(code_of(&err), ErrorCode::SandboxExpired);
+
+        let err = anyhow::anyhow!("GitHub API returned 429 Too Many Requests: slow down");
+        assert_eq!(code_of(&err), ErrorCode::RateLimited);
+        assert_eq!(code_of(&anyhow::anyhow!("boom")), ErrorCode::Internal);
+    }
+
+    #[test]
+    fn classify_distinguishes_common_failures() {
+        let cases = [
+            (
+                "No active sandbox. Call sandbox_create first.",
+                Some(ErrorCode::SandboxMissing),
+            ),
+            (
+                "npm ERR! code ENOSPC: no space left on device",
+                Some(ErrorCode::DiskFull),
+            ),
+            (
+                "Failed to read file 'a.txt': No such file or directory (os error 2)",
+                Some(ErrorCode::FileNotFound),
+            ),
+            (
+                "GitHub API returned 401 Unauthorized: Bad credentials",
+                Some(ErrorCode::AuthFailed),
+            ),
+            ("Command timed out after 120s", Some(ErrorCode::Timeout)),
+            ("Command exited with code 1", None),
+        ];
+        for (message, expected) in cases {
+            assert_eq!(ErrorCode::classify(message), expected, "{message}");
+        }
+    }
+
+    #[test]
+    fn http_statuses_map_to_codes() {
+        assert_eq!(ErrorCode::from_http(200, ""), None);
+        assert_eq!(ErrorCode::from_http(401, ""), Some(ErrorCode::AuthFailed));
+        assert_eq!(
+            ErrorCode::from_http(403, "API rate limit exceeded"),
+            Some(ErrorCode::RateLimited)
+        );
+        assert_eq!(
+            ErrorCode::from_http(403, "Resource not accessible"),
+            Some(ErrorCode::PermissionDenied)
+        );
+        assert_eq!(
+            serde_json::to_value(ErrorCode::SandboxExpired).unwrap(),
+            serde_json::json!("sandbox_expired")
+        );
+    }
+}
@@ -0,0 +1,864 @@
+//! A/B evaluation of two model routes on canned build tasks.
+//!
+//! `zerobuild eval --a <route> --b <route>` runs every task of a suite once
+//! per route, each in a fresh local sandbox with the Developer role's
+//! sandbox tools. A task passes when its check command (or the project's
+//! `.zerobuild.toml` test command) exits 0 afterwards. The report compares
+//! pass rate, wall time and estimated cost per route and recommends one, so
+//! routing defaults can be chosen from measurements instead of guesses.
+//!
+//! A suite file lists tasks as TOML:
+//!
+//! ```toml
+//! [[task]]
+//! name = "health-api"
+//! template = "node-api"
+//! prompt = "Build an HTTP API with GET /health ..."
+//! check = "npm test"
+//! ```
+
+use crate::agent::loop_::{build_tool_instructions, run_tool_call_loop};
+use crate::config::Config;
+use crate::factory::{AgentRole, RoleConfig};
+use crate::observability::traits::{Observer, ObserverEvent, ObserverMetric};
+use crate::providers::{ChatMessage, Provider};
+use crate::sandbox::project_config::{ProjectConfig, PROJECT_DIR};
+use crate::sandbox::SandboxClient;
+use crate::tools::Tool;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::fmt::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Sandbox lifetime requested for each run.
+const SANDBOX_TIMEOUT_MS: u64 = 3_600_000;
+/// Time limit for a task's check command.
+const CHECK_TIMEOUT_MS: u64 = 600_000;
+/// USD per 1M input/output tokens for models without a `[cost.prices]` entry.
+const DEFAULT_PRICES: (f64, f64) = (3.0, 15.0);
+
+const EVAL_PROMPT: &str = "\
+You are a Developer agent being evaluated on a build task. \
+Work autonomously: there is no user to answer questions, so make reasonable choices and finish the task.\n\
+\n\
+Rules:\n\
+- Call sandbox_create first, then build the project in the `project/` directory of the sandbox\n\
+- Write several files at once with sandbox_write_files when scaffolding\n\
+- Install dependencies and run commands with sandbox_run_command\n\
+- Make sure the project builds and its tests pass before you finish\n\
+- End with a short summary of what you built";
+
+/// A canned build task.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EvalTask {
+    pub name: String,
+    /// Project template the sandbox starts from (see
+    /// [`crate::sandbox::PROJECT_TEMPLATES`]); empty lets the agent choose.
+    #[serde(default)]
+    pub template: String,
+    pub prompt: String,
+    /// Shell command run in the project directory after the agent finishes;
+    /// exit 0 means the task passed. Defaults to the project's test command.
+    #[serde(default)]
+    pub check: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SuiteFile {
+    #[serde(default, rename = "task")]
+    tasks: Vec<EvalTask>,
+}
+
+/// Tasks used when no suite file is given, one per project template.
+pub fn builtin_suite() -> Vec<EvalTask> {
+    let task = |name: &str, template: &str, prompt: &str, check: &str| EvalTask {
+        name: name.into(),
+        template: template.into(),
+        prompt: prompt.into(),
+        check: Some(check.into()),
+    };
+    vec![
+        task(
+            "landing-page",
+            "static",
+            "Build a landing page for a coffee shop called Bean There with a hero section, \
+             a menu of at least three drinks with prices and a contact form. Use plain HTML, \
+             CSS and JavaScript with index.html at the project root.",
+            "test -f index.html && grep -qi 'bean there' index.html && grep -qi '<form' index.html",
+        ),
+        task(
+            "health-api",
+            "node-api",
+            "Build a Node.js HTTP API with GET /health returning {\"status\":\"ok\"} and \
+             GET /todos returning a JSON array. Add tests for both endpoints using the \
+             built-in node:test runner and make `npm test` run them.",
+            "npm test",
+        ),
+        task(
+            "todo-app",
+            "vite-react",
+            "Build a Vite + React todo app where users can add, complete and delete todos, \
+             with the list kept in localStorage. `npm run build` must succeed.",
+            "npm run build",
+        ),
+        task(
+            "blog",
+            "nextjs",
+            "Build a Next.js + Tailwind CSS blog with a home page listing three posts and a \
+             page for each post. `npm run build` must succeed.",
+            "npm run build",
+        ),
+    ]
+}
+
+/// Parse a suite file's contents.
+pub fn parse_suite(text: &str) -> Result<Vec<EvalTask>> {
+    let suite: SuiteFile = toml::from_str(text).context("Invalid eval suite")?;
+    if suite.tasks.is_empty() {
+        anyhow::bail!("Eval suite has no [[task]] entries");
+    }
+    for (i, task) in suite.tasks.iter().enumerate() {
+        if task.name.trim().is_empty() || task.prompt.trim().is_empty() {
+            anyhow::bail!("Eval task #{} needs a name and a prompt", i + 1);
+        }
+        if suite.tasks[..i].iter().any(|t| t.name == task.name) {
+            anyhow::bail!("Duplicate eval task '{}'", task.name);
+        }
+        if !task.template.is_empty() && crate::sandbox::project_template(&task.template).is_none() {
+            anyhow::bail!(
+                "Eval task '{}' uses unknown template '{}'",
+                task.name,
+                task.template
+            );
+        }
+    }
+    Ok(suite.tasks)
+}
+
+/// A provider and model under evaluation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvalRoute {
+    /// The spec the route was given as, for the report.
+    pub label: String,
+    pub provider: String,
+    pub model: String,
+    pub api_key: Option<String>,
+}
+
+/// Resolve `spec`: `hint:<name>` for a configured or stored model route, or
+/// `<provider>:<model>`. For `custom:<url>` providers the model follows the
+/// last `:`.
+pub fn resolve_route(config: &Config, spec: &str) -> Result<EvalRoute> {
+    let spec = spec.trim();
+    if let Some(hint) = spec.strip_prefix("hint:") {
+        let route = crate::providers::effective_model_routes(config)
+            .into_iter()
+            .find(|route| route.hint == hint)
+            .ok_or_else(|| anyhow::anyhow!("No model route for hint '{hint}'"))?;
+        let api_key = route
+            .api_key
+            .clone()
+            .or_else(|| default_key(config, &route.provider));
+        return Ok(EvalRoute {
+            label: spec.to_string(),
+            provider: route.provider,
+            model: route.model,
+            api_key,
+        });
+    }
+
+    let split = if spec.starts_with("custom:") || spec.starts_with("anthropic-custom:") {
+        spec.rsplit_once(':')
+    } else {
+        spec.split_once(':')
+    };
+    let (raw_provider, model) = split
+        .map(|(provider, model)| (provider.trim(), model.trim()))
+        .filter(|(provider, model)| !provider.is_empty() && !model.is_empty())
+        .ok_or_else(|| {
+            anyhow::anyhow!("Route '{spec}' must be <provider>:<model> or hint:<name>")
+        })?;
+    let provider = crate::providers::resolve_provider_name(raw_provider)
+        .ok_or_else(|| anyhow::anyhow!("Unknown provider '{raw_provider}'"))?;
+    let api_key = default_key(config, &provider);
+    Ok(EvalRoute {
+        label: spec.to_string(),
+        provider,
+        model: model.to_string(),
+        api_key,
+    })
+}
+
+/// The configured API key, when it belongs to `provider`.
+fn default_key(config: &Config, provider: &str) -> Option<String> {
+    let default_provider = config.default_provider.as_deref().unwrap_or("openrouter");
+    if default_provider == provider {
+        config.api_key.clone()
+    } else {
+        None
+    }
+}
+
+/// Outcome of one task on one route.
+#[derive(Debug, Clone)]
+pub struct RunResult {
+    pub task: String,
+    pub passed: bool,
+    pub duration: Duration,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: f64,
+    /// Why the run failed, empty when it passed.
+    pub note: String,
+}
+
+/// Observer summing the token usage of successful LLM calls.
+#[derive(Default)]
+struct UsageObserver {
+    input_tokens: AtomicU64,
+    output_tokens: AtomicU64,
+}
+
+impl Observer for UsageObserver {
+    fn record_event(&self, event: &ObserverEvent) {
+        if let ObserverEvent::LlmResponse {
+            success: true,
+            input_tokens,
+            output_tokens,
+            ..
+        } = event
+        {
+            self.input_tokens
+                .fetch_add(input_tokens.unwrap_or(0), Ordering::Relaxed);
+            self.output_tokens
+                .fetch_add(output_tokens.unwrap_or(0), Ordering::Relaxed);
+        }
+    }
+
+    fn record_metric(&self, _metric: &ObserverMetric) {}
+
+    fn name(&self) -> &str {
+        "eval-usage"
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+/// USD per 1M input and output tokens for `route` from `[cost.prices]`,
+/// keyed by `provider/model` or `model`.
+fn route_prices(config: &Config, route: &EvalRoute) -> (f64, f64) {
+    let prices = &config.cost.prices;
+    prices
+        .get(&format!("{}/{}", route.provider, route.model))
+        .or_else(|| prices.get(&route.model))
+        .map_or(DEFAULT_PRICES, |pricing| (pricing.input, pricing.output))
+}
+
+/// Check command for the finished project: the task's own, else the
+/// project's configured test command.
+fn check_command(task: &EvalTask, project: Option<&ProjectConfig>) -> Option<String> {
+    task.check
+        .clone()
+        .or_else(|| project.and_then(|p| p.commands.test.clone()))
+}
+
+/// Run `task` with `provider` in a fresh sandbox on `sandbox` and check the
+/// result. The sandbox is removed afterwards.
+pub async fn run_task(
+    config: &Config,
+    route: &EvalRoute,
+    provider: &dyn Provider,
+    sandbox: Arc<dyn SandboxClient>,
+    task: &EvalTask,
+    time_limit: Duration,
+) -> RunResult {
+    let observer = UsageObserver::default();
+    let started = Instant::now();
+    let outcome = run_and_check(
+        config, route, provider, &sandbox, task, time_limit, &observer,
+    )
+    .await;
+    let duration = started.elapsed();
+    let _ = sandbox.kill_sandbox().await;
+
+    let input_tokens = observer.input_tokens.load(Ordering::Relaxed);
+    let output_tokens = observer.output_tokens.load(Ordering::Relaxed);
+    let (input_price, output_price) = route_prices(config, route);
+    let cost_usd = crate::cost::TokenUsage::new(
+        &route.model,
+        input_tokens,
+        output_tokens,
+        input_price,
+        output_price,
+    )
+    .cost();
+
+    RunResult {
+        task: task.name.clone(),
+        passed: outcome.is_ok(),
+        duration,
+        input_tokens,
+        output_tokens,
+        cost_usd,
+        note: outcome.err().map(|e| format!("{e:#}")).unwrap_or_default(),
+    }
+}
+
+async fn run_and_check(
+    config: &Config,
+    route: &EvalRoute,
+    provider: &dyn Provider,
+    sandbox: &Arc<dyn SandboxClient>,
+    task: &EvalTask,
+    time_limit: Duration,
+    observer: &dyn Observer,
+) -> Result<()> {
+    sandbox
+        .create_sandbox(true, &task.template, SANDBOX_TIMEOUT_MS)
+        .await
+        .context("Failed to create sandbox")?;
+
+    let mut zerobuild = config.zerobuild.clone();
+    zerobuild.default_template.clone_from(&task.template);
+    let role = RoleConfig::default_for(AgentRole::Developer).delegate_config;
+    let tools: Vec<Box<dyn Tool>> = crate::tools::sandbox_tools(
+        Arc::clone(sandbox),
+        Arc::new(zerobuild),
+        String::new(),
+        Arc::new(crate::memory::embeddings::NoopEmbedding),
+        None,
+    )
+    .into_iter()
+    .filter(|tool| role.allowed_tools.iter().any(|name| name == tool.name()))
+    .collect();
+
+    let mut system_prompt = EVAL_PROMPT.to_string();
+    if !provider.supports_native_tools() {
+        system_prompt.push_str(&build_tool_instructions(&tools));
+    }
+    let mut history = vec![
+        ChatMessage::system(system_prompt),
+        ChatMessage::user(task.prompt.clone()),
+    ];
+    tokio::time::timeout(
+        time_limit,
+        run_tool_call_loop(
+            provider,
+            &mut history,
+            &tools,
+            observer,
+            &route.provider,
+            &route.model,
+            role.temperature.unwrap_or(0.3),
+            true,
+            None,
+            "eval",
+            &config.multimodal,
+            config.agent.max_tool_iterations,
+            None,
+            None,
+            None,
+            &[],
+        ),
+    )
+    .await
+    .map_err(|_| anyhow::anyhow!("Timed out after {}", format_duration(time_limit)))?
+    .context("Agent failed")?;
+
+    let project = ProjectConfig::load(sandbox.as_ref()).await.ok().flatten();
+    let command = check_command(task, project.as_ref())
+        .ok_or_else(|| anyhow::anyhow!("No check command and no test command in the project"))?;
+    let workdir = project.map_or_else(|| PROJECT_DIR.to_string(), |p| p.workdir());
+    let output = sandbox
+        .run_command(&command, &workdir, CHECK_TIMEOUT_MS)
+        .await
+        .with_context(|| format!("Failed to run `{command}`"))?;
+    if output.exit_code != 0 {
+        let detail = output
+            .stderr
+            .lines()
+            .chain(output.stdout.lines())
+            .rfind(|line| !line.trim().is_empty())
+            .unwrap_or("")
+            .trim()
+            .to_string();
+        anyhow::bail!("`{command}` exited with {}: {detail}", output.exit_code);
+    }
+    Ok(())
+}
+
+/// Totals of one route's runs.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Summary {
+    passed: usize,
+    total: usize,
+    duration: Duration,
+    input_tokens: u64,
+    output_tokens: u64,
+    cost_usd: f64,
+}
+
+fn summarize(results: &[RunResult]) -> Summary {
+    results.iter().fold(Summary::default(), |mut sum, run| {
+        sum.passed += usize::from(run.passed);
+        sum.total += 1;
+        sum.duration += run.duration;
+        sum.input_tokens += run.input_tokens;
+        sum.output_tokens += run.output_tokens;
+        sum.cost_usd += run.cost_usd;
+        sum
+    })
+}
+
+/// `duration` as e.g. `2m05s` or `40s`.
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs >= 60 {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{secs}s")
+    }
+}
+
+fn format_run(run: &RunResult) -> String {
+    let verdict = if run.passed { "pass" } else { "FAIL" };
+    format!(
+        "{verdict} · {} · ${:.3}",
+        format_duration(run.duration),
+        run.cost_usd
+    )
+}
+
+/// Which route the results favour: more passed tasks, then lower cost,
+/// then less time. `None` when neither passed anything or they tie.
+fn preferred(a: &Summary, b: &Summary) -> Option<bool> {
+    if a.passed == 0 && b.passed == 0 {
+        return None;
+    }
+    if a.passed != b.passed {
+        return Some(a.passed > b.passed);
+    }
+    let cheaper = a.cost_usd.min(b.cost_usd);
+    if (a.cost_usd - b.cost_usd).abs() > cheaper * 0.1 {
+        return Some(a.cost_usd < b.cost_usd);
+    }
+    if a.duration != b.duration {
+        return Some(a.duration < b.duration);
+    }
+    None
+}
+
+/// Markdown report comparing the runs of routes `a` and `b` task by task.
+pub fn render_report(
+    a: &EvalRoute,
+    b: &EvalRoute,
+    results_a: &[RunResult],
+    results_b: &[RunResult],
+) -> String {
+    let (sum_a, sum_b) = (summarize(results_a), summarize(results_b));
+    let mut report = String::from("# Model route eval\n\n");
+    let _ = writeln!(report, "- A: `{}` ({} / {})", a.label, a.provider, a.model);
+    let _ = writeln!(
+        report,
+        "- B: `{}` ({} / {})\n",
+        b.label, b.provider, b.model
+    );
+
+    report.push_str("| Task | A | B |\n|------|---|---|\n");
+    for (run_a, run_b) in results_a.iter().zip(results_b) {
+        let _ = writeln!(
+            report,
+            "| {} | {} | {} |",
+            run_a.task,
+            format_run(run_a),
+            format_run(run_b)
+        );
+    }
+
+    report.push_str("\n| Route | Passed | Time | Tokens (in / out) | Est. cost |\n");
+    report.push_str("|-------|--------|------|-------------------|-----------|\n");
+    for (name, sum) in [("A", &sum_a), ("B", &sum_b)] {
+        let _ = writeln!(
+            report,
+            "| {name} | {}/{} | {} | {} / {} | ${:.3} |",
+            sum.passed,
+            sum.total,
+            format_duration(sum.duration),
+            sum.input_tokens,
+            sum.output_tokens,
+            sum.cost_usd
+        );
+    }
+
+    let failures: Vec<(&str, &RunResult)> = results_a
+        .iter()
+        .map(|run| ("A", run))
+        .chain(results_b.iter().map(|run| ("B", run)))
+        .filter(|(_, run)| !run.passed)
+        .collect();
+    if !failures.is_empty() {
+        report.push_str("\n## Failures\n\n");
+        for (name, run) in failures {
+            let _ = writeln!(report, "- {name} / {}: {}", run.task, run.note);
+        }
+    }
+
+    report.push_str("\n## Recommendation\n\n");
+    match preferred(&sum_a, &sum_b) {
+        Some(prefer_a) => {
+            let (name, route) = if prefer_a { ("A", a) } else { ("B", b) };
+            let _ = writeln!(
+                report,
+                "Prefer {name} (`{}`). Make it the route for build work with the \
+                 `model_routing` tool, e.g. hint `code` → {} / {}.",
+                route.label, route.provider, route.model
+            );
+        }
+        None => report.push_str("No clear winner; keep the current routing.\n"),
+    }
+    report.push_str(
+        "\nCosts use `[cost.prices]`, or $3 / $15 per 1M input / output tokens for \
+         models without an entry.\n",
+    );
+    report
+}
+
+/// Run the suite (`suite` file or the built-in one, filtered to `only` task
+/// names when given) against routes `a` and `b` and print the report,
+/// also writing it to `output` when set.
+pub async fn run(
+    config: &Config,
+    a: &str,
+    b: &str,
+    suite: Option<&Path>,
+    only: &[String],
+    time_limit: Duration,
+    output: Option<&Path>,
+) -> Result<()> {
+    let route_a = resolve_route(config, a)?;
+    let route_b = resolve_route(config, b)?;
+    let mut tasks = match suite {
+        Some(path) => parse_suite(
+            &std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?,
+        )?,
+        None => builtin_suite(),
+    };
+    if !only.is_empty() {
+        if let Some(unknown) = only
+            .iter()
+            .find(|name| !tasks.iter().any(|t| &t.name == *name))
+        {
+            anyhow::bail!("No eval task named '{unknown}'");
+        }
+        tasks.retain(|task| only.contains(&task.name));
+    }
+
+    let options = crate::providers::ProviderRuntimeOptions {
+        auth_profile_override: None,
+        zerobuild_dir: config.config_path.parent().map(std::path::PathBuf::from),
+        secrets_encrypt: config.secrets.encrypt,
+        reasoning_enabled: config.runtime.reasoning_enabled,
+        local_provider: config.local_provider.clone(),
+        prompt_caching: config.agent.prompt_caching,
+    };
+    let mut providers = Vec::with_capacity(2);
+    for route in [&route_a, &route_b] {
+        let provider = crate::providers::create_resilient_provider_with_options(
+            &route.provider,
+            route.api_key.as_deref(),
+            None,
+            &config.reliability,
+            &options,
+        )
+        .with_context(|| format!("Failed to initialize provider `{}`", route.provider))?;
+        providers.push(provider);
+    }
+
+    let (mut results_a, mut results_b) = (Vec::new(), Vec::new());
+    for task in &tasks {
+        for (name, route, provider, results) in [
+            ("A", &route_a, &providers[0], &mut results_a),
+            ("B", &route_b, &providers[1], &mut results_b),
+        ] {
+            println!(
+                "▶ {} on {name} ({} / {})",
+                task.name, route.provider, route.model
+            );
+            let result = run_task(
+                config,
+                route,
+                provider.as_ref(),
+                crate::tools::sandbox_client(),
+                task,
+                time_limit,
+            )
+            .await;
+            println!("  {}", format_run(&result));
+            results.push(result);
+        }
+    }
+
+    let report = render_report(&route_a, &route_b, &results_a, &results_b);
+    println!("\n{report}");
+    if let Some(path) = output {
+        std::fs::write(path, &report)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        println!("Report written to {}", path.display());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::schema::ModelPricing;
+    use crate::config::ModelRouteConfig;
+    use crate::providers::{ChatRequest, ChatResponse};
+    use async_trait::async_trait;
+    use tempfile::TempDir;
+
+    fn test_config(tmp: &TempDir) -> Config {
+        let mut config = Config {
+            workspace_dir: tmp.path().join("workspace"),
+            config_path: tmp.path().join("config.toml"),
+            default_provider: Some("openrouter".into()),
+            api_key: Some("sk-test".into()),
+            model_routes: vec![ModelRouteConfig {
+                hint: "code".into(),
+                provider: "anthropic".into(),
+                model: "claude-sonnet-4".into(),
+                api_key: Some("sk-route".into()),
+                temperature: None,
+                top_p: None,
+                seed: None,
+            }],
+            ..Config::default()
+        };
+        config.zerobuild.db_path = tmp.path().join("zerobuild.db").display().to_string();
+        config
+    }
+
+    fn run(task: &str, passed: bool, secs: u64, cost_usd: f64) -> RunResult {
+        RunResult {
+            task: task.into(),
+            passed,
+            duration: Duration::from_secs(secs),
+            input_tokens: 1000,
+            output_tokens: 200,
+            cost_usd,
+            note: if passed {
+                String::new()
+            } else {
+                "`npm test` exited with 1: boom".into()
+            },
+        }
+    }
+
+    #[test]
+    fn routes_resolve_from_specs_and_hints() {
+        let tmp = TempDir::new().unwrap();
+        let config = test_config(&tmp);
+
+        let route = resolve_route(&config, "OpenRouter:openai/gpt-4o").unwrap();
+        assert_eq!(route.provider, "openrouter");
+        assert_eq!(route.model, "openai/gpt-4o");
+        assert_eq!(route.api_key.as_deref(), Some("sk-test"));
+
+        let route = resolve_route(&config, "hint:code").unwrap();
+        assert_eq!(
+            (route.provider.as_str(), route.model.as_str()),
+            ("anthropic", "claude-sonnet-4")
+        );
+        assert_eq!(route.api_key.as_deref(), Some("sk-route"));
+
+        let route = resolve_route(&config, "custom:http://localhost:8080/v1:llama3").unwrap();
+        assert_eq!(route.provider, "custom:http://localhost:8080/v1");
+        assert_eq!(route.model, "llama3");
+        assert!(route.api_key.is_none());
+
+        assert!(resolve_route(&config, "gpt-4o").is_err());
+        assert!(resolve_route(&config, "nope:model").is_err());
+        assert!(resolve_route(&config, "hint:missing").is_err());
+    }
+
+    #[test]
+    fn suites_parse_and_reject_bad_tasks() {
+        let tasks = parse_suite(
+            r#"
+[[task]]
+name = "api"
+template = "node-api"
+prompt = "Build an API"
+check = "npm test"
+
+[[task]]
+name = "page"
+prompt = "Build a page"
+"#,
+        )
+        .unwrap();
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[1].template, "");
+        assert!(tasks[1].check.is_none());
+
+        assert!(parse_suite("").is_err());
+        assert!(
+            parse_suite("[[task]]\nname = \"a\"\nprompt = \"x\"\ntemplate = \"rails\"").is_err()
+        );
+        assert!(parse_suite(
+            "[[task]]\nname = \"a\"\nprompt = \"x\"\n[[task]]\nname = \"a\"\nprompt = \"y\""
+        )
+        .is_err());
+
+        for task in builtin_suite() {
+            assert!(crate::sandbox::project_template(&task.template).is_some());
+            assert!(task.check.is_some());
+        }
+    }
+
+    #[test]
+    fn report_prefers_more_passes_then_lower_cost() {
+        let tmp = TempDir::new().unwrap();
+        let config = test_config(&tmp);
+        let a = resolve_route(&config, "openrouter:openai/gpt-4o").unwrap();
+        let b = resolve_route(&config, "hint:code").unwrap();
+
+        let report = render_report(
+            &a,
+            &b,
+            &[run("api", true, 90, 0.20), run("page", false, 30, 0.05)],
+            &[run("api", true, 120, 0.50), run("page", true, 45, 0.10)],
+        );
+        assert!(report.contains("| api | pass · 1m30s · $0.200 | pass · 2m00s · $0.500 |"));
+        assert!(report.contains("| A | 1/2 | 2m00s | 2000 / 400 | $0.250 |"));
+        assert!(report.contains("- A / page: `npm test` exited with 1: boom"));
+        assert!(report.contains("Prefer B (`hint:code`)"));
+
+        let report = render_report(
+            &a,
+            &b,
+            &[run("api", true, 90, 0.20)],
+            &[run("api", true, 60, 0.50)],
+        );
+        assert!(report.contains("Prefer A"));
+
+        let report = render_report(
+            &a,
+            &b,
+            &[run("api", false, 9, 0.0)],
+            &[run("api", false, 9, 0.0)],
+        );
+        assert!(report.contains("No clear winner"));
+    }
+
+    #[test]
+    fn prices_come_from_cost_config() {
+        let tmp = TempDir::new().unwrap();
+        let mut config = test_config(&tmp);
+        config.cost.prices.insert(
+            "anthropic/claude-sonnet-4".into(),
+            ModelPricing {
+                input: 3.0,
+                output: 15.0,
+            },
+        );
+        config.cost.prices.insert(
+            "gpt-4o-mini".into(),
+            ModelPricing {
+                input: 0.15,
+                output: 0.6,
+            },
+        );
+        let route = |model: &str| EvalRoute {
+            label: model.into(),
+            provider: "openai".into(),
+            model: model.into(),
+            api_key: None,
+        };
+        assert_eq!(route_prices(&config, &route("gpt-4o-mini")), (0.15, 0.6));
+        assert_eq!(route_prices(&config, &route("o3")), DEFAULT_PRICES);
+        let hinted = resolve_route(&config, "hint:code").unwrap();
+        assert_eq!(route_prices(&config, &hinted), (3.0, 15.0));
+    }
+
+    struct FinishingProvider;
+
+    #[async_trait]
+    impl Provider for FinishingProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            Ok("done".into())
+        }
+
+        async fn chat(
+            &self,
+            _request: ChatRequest<'_>,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<ChatResponse> {
+            Ok(ChatResponse {
+                text: Some("Built it.".into()),
+                tool_calls: Vec::new(),
+                usage: Some(crate::providers::traits::TokenUsage {
+                    input_tokens: Some(2_000_000),
+                    output_tokens: Some(100_000),
+                    cached_input_tokens: None,
+                }),
+                reasoning_content: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn run_task_scores_with_the_check_command_and_meters_usage() {
+        let tmp = TempDir::new().unwrap();
+        let config = test_config(&tmp);
+        let route = resolve_route(&config, "openai:o3").unwrap();
+        let task = |check: &str| EvalTask {
+            name: "check".into(),
+            template: "static".into(),
+            prompt: "Build a page".into(),
+            check: Some(check.into()),
+        };
+        let sandbox = crate::tools::sandbox_client();
+
+        let passed = run_task(
+            &config,
+            &route,
+            &FinishingProvider,
+            Arc::clone(&sandbox),
+            &task("true"),
+            Duration::from_secs(60),
+        )
+        .await;
+        assert!(passed.passed, "{}", passed.note);
+        assert_eq!(passed.input_tokens, 2_000_000);
+        assert!((passed.cost_usd - 7.5).abs() < 1e-9);
+        assert!(sandbox.current_id().is_none());
+
+        let failed = run_task(
+            &config,
+            &route,
+            &FinishingProvider,
+            sandbox,
+            &task("echo missing index.html >&2; exit 3"),
+            Duration::from_secs(60),
+        )
+        .await;
+        assert!(!failed.passed);
+        assert!(failed.note.contains("exited with 3: missing index.html"));
+    }
+}
@@ -0,0 +1,703 @@
+//! Agent-to-agent (A2A) protocol between ZeroBuild instances
+//! (`[factory.a2a]`), so one factory can hand a sub-task — "build the API"
+//! while it builds the frontend — to an instance on another machine.
+//!
+//! A peer serves three gateway routes, all behind
+//! `Authorization: Bearer <[factory.a2a] token>`:
+//!
+//! - `GET  /a2a/card` — protocol version and how busy the instance is;
+//! - `POST /a2a/tasks` — submit a [`TaskRequest`]: the task, its
+//!   requirements and optionally the files to start from;
+//! - `GET  /a2a/tasks/{id}` — the [`Task`]: its status and, once finished,
+//!   the agent's summary and the files of the snapshot it saved.
+//!
+//! A task runs as a full agent turn with every tool, `factory_build`
+//! included. An instance has one sandbox and one snapshot, so it runs one
+//! task at a time and queues the rest; the local snapshot is put back when a
+//! task ends, the sandbox is not. Tasks are kept in memory; a restart
+//! forgets them. The `a2a_delegate` tool is the requesting side.
+
+use crate::config::{A2aPeerConfig, Config, FactoryA2aConfig};
+use crate::sandbox::SandboxClient;
+use crate::store;
+use crate::tools::progress::report_progress;
+use crate::tools::traits::{Tool, ToolResult};
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+
+/// Protocol version reported by `/a2a/card`.
+pub const PROTOCOL: &str = "zerobuild-a2a/1";
+
+/// Finished tasks kept for their requesters to collect.
+const MAX_FINISHED_TASKS: usize = 100;
+
+/// How often the requester checks on a delegated task.
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The process-wide task board served by the gateway.
+static BOARD: OnceLock<Arc<TaskBoard>> = OnceLock::new();
+
+/// A task submitted by a peer.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskRequest {
+    /// What to build.
+    pub task: String,
+    /// Requirements and context shared by the requester: API contracts,
+    /// stack, conventions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requirements: Option<String>,
+    /// Project name, for the peer's project memory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    /// Files to start from, as `path → content` relative to the project root.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub files: HashMap<String, String>,
+}
+
+/// Lifecycle of a task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+}
+
+impl TaskStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Queued => "queued",
+            Self::Running => "running",
+            Self::Succeeded => "succeeded",
+            Self::Failed => "failed",
+        }
+    }
+
+    pub fn is_finished(self) -> bool {
+        matches!(self, Self::Succeeded | Self::Failed)
+    }
+}
+
+/// A task and, once it finished, its result.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Task {
+    pub id: String,
+    pub status: TaskStatus,
+    /// The agent's final summary.
+    #[serde(default)]
+    pub output: Option<String>,
+    #[serde(default)]
+    pub error: Option<String>,
+    /// Files of the snapshot the task saved; empty when it saved none.
+    #[serde(default)]
+    pub files: HashMap<String, String>,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+/// Tasks submitted to this instance, run one at a time.
+pub struct TaskBoard {
+    tasks: Mutex<HashMap<String, Task>>,
+    slot: Semaphore,
+}
+
+impl TaskBoard {
+    fn new() -> Self {
+        Self {
+            tasks: Mutex::new(HashMap::new()),
+            slot: Semaphore::new(1),
+        }
+    }
+
+    /// The board the gateway's `/a2a/*` routes serve.
+    pub fn global() -> Arc<Self> {
+        Arc::clone(BOARD.get_or_init(|| Arc::new(Self::new())))
+    }
+
+    /// Queue `request` and run it as an agent turn with `config` once the
+    /// tasks before it finished.
+    pub fn submit(self: &Arc<Self>, config: Config, request: TaskRequest) -> Task {
+        self.submit_with(run_task(config, request))
+    }
+
+    fn submit_with<F>(self: &Arc<Self>, run: F) -> Task
+    where
+        F: Future<Output = Result<(String, HashMap<String, String>)>> + Send + 'static,
+    {
+        let task = Task {
+            id: uuid::Uuid::new_v4().to_string(),
+            status: TaskStatus::Queued,
+            output: None,
+            error: None,
+            files: HashMap::new(),
+            created_at: Utc::now(),
+            finished_at: None,
+        };
+        self.insert(task.clone());
+
+        let board = Arc::clone(self);
+        let id = task.id.clone();
+        tokio::spawn(async move {
+            let Ok(_permit) = board.slot.acquire().await else {
+                return;
+            };
+            board.update(&id, |task| task.status = TaskStatus::Running);
+            tracing::info!(task_id = %id, "Running A2A task");
+            let result = run.await;
+            board.update(&id, |task| {
+                match result {
+                    Ok((output, files)) => {
+                        task.status = TaskStatus::Succeeded;
+                        task.output = Some(output);
+                        task.files = files;
+                    }
+                    Err(e) => {
+                        task.status = TaskStatus::Failed;
+                        task.error = Some(format!("{e:#}"));
+                    }
+                }
+                task.finished_at = Some(Utc::now());
+            });
+            tracing::info!(task_id = %id, "A2A task finished");
+        });
+        task
+    }
+
+    pub fn get(&self, id: &str) -> Option<Task> {
+        self.tasks.lock().get(id).cloned()
+    }
+
+    /// Number of queued and of running tasks.
+    pub fn load(&self) -> (usize, usize) {
+        let tasks = self.tasks.lock();
+        let count = |status| tasks.values().filter(|t| t.status == status).count();
+        (count(TaskStatus::Queued), count(TaskStatus::Running))
+    }
+
+    fn insert(&self, task: Task) {
+        let mut tasks = self.tasks.lock();
+        tasks.insert(task.id.clone(), task);
+        let mut finished: Vec<(DateTime<Utc>, String)> = tasks
+            .values()
+            .filter_map(|t| Some((t.finished_at?, t.id.clone())))
+            .collect();
+        if finished.len() > MAX_FINISHED_TASKS {
+            finished.sort();
+            for (_, id) in &finished[..finished.len() - MAX_FINISHED_TASKS] {
+                tasks.remove(id);
+            }
+        }
+    }
+
+    fn update(&self, id: &str, change: impl FnOnce(&mut Task)) {
+        if let Some(task) = self.tasks.lock().get_mut(id) {
+            change(task);
+        }
+    }
+}
+
+/// Run a peer's task as an agent turn. Returns the agent's reply and the
+/// files of the snapshot saved during the turn.
+///
+/// The task works on this instance's snapshot, so the local one is set
+/// aside first and put back when the task ends, whether it succeeded or not.
+async fn run_task(
+    config: Config,
+    request: TaskRequest,
+) -> Result<(String, HashMap<String, String>)> {
+    let db_path = PathBuf::from(&config.zerobuild.db_path);
+    let (stashed, before) = {
+        let conn = store::init_db(&db_path)?;
+        let stashed = store::snapshot::stash(&conn)?;
+        let before = load_task_snapshot(&conn, &request)
+            .and_then(|()| store::snapshot::snapshot_saved_at(&conn));
+        match before {
+            Ok(before) => (stashed, before),
+            Err(e) => {
+                store::snapshot::restore_stash(&conn, &stashed)?;
+                return Err(e);
+            }
+        }
+    };
+
+    let output = Box::pin(crate::agent::process_message(
+        config,
+        &task_prompt(&request),
+    ))
+    .await;
+
+    let conn = store::init_db(&db_path)?;
+    let files = if output.is_ok() && store::snapshot::snapshot_saved_at(&conn)? != before {
+        store::snapshot::load_snapshot(&conn)?
+            .map(|(files, _)| files)
+            .unwrap_or_default()
+    } else {
+        HashMap::new()
+    };
+    store::snapshot::restore_stash(&conn, &stashed)?;
+    Ok((output?, files))
+}
+
+/// Save the requester's files and project as the snapshot the task starts
+/// from.
+fn load_task_snapshot(conn: &rusqlite::Connection, request: &TaskRequest) -> Result<()> {
+    if !request.files.is_empty() {
+        store::snapshot::save_snapshot(conn, &request.files, None)?;
+    }
+    if let Some(project) = request.project.as_deref().filter(|p| !p.trim().is_empty()) {
+        store::snapshot::save_project(conn, project)?;
+    }
+    Ok(())
+}
+
+/// The message a peer's task is run with.
+fn task_prompt(request: &TaskRequest) -> String {
+    let mut prompt = format!(
+        "[Task delegated by another ZeroBuild instance]\n\n{}",
+        request.task.trim()
+    );
+    if let Some(requirements) = request
+        .requirements
+        .as_deref()
+        .map(str::trim)
+        .filter(|r| !r.is_empty())
+    {
+        let _ = write!(prompt, "\n\nRequirements:\n{requirements}");
+    }
+    if !request.files.is_empty() {
+        let _ = write!(
+            prompt,
+            "\n\nThe requester's {} project files are saved as the snapshot: restore it \
+             with sandbox_restore_snapshot and build on them.",
+            request.files.len()
+        );
+    }
+    prompt.push_str(
+        "\n\nNobody can answer questions during this task: decide on your own and list your \
+         assumptions in your final reply. When the work is done, save it with \
+         sandbox_save_snapshot — the saved files are sent back to the requester.",
+    );
+    prompt
+}
+
+/// Client for a peer's `/a2a/*` routes.
+pub struct PeerClient {
+    url: String,
+    token: String,
+    client: reqwest::Client,
+}
+
+impl PeerClient {
+    pub fn new(peer: &A2aPeerConfig) -> Self {
+        Self {
+            url: peer.url.trim().trim_end_matches('/').to_string(),
+            token: peer.token.trim().to_string(),
+            client: crate::config::build_runtime_proxy_client("tool.a2a"),
+        }
+    }
+
+    /// Submit a task; the peer answers with it queued.
+    pub async fn submit(&self, request: &TaskRequest) -> Result<Task> {
+        let resp = self
+            .client
+            .post(format!("{}/a2a/tasks", self.url))
+            .bearer_auth(&self.token)
+            .json(request)
+            .send()
+            .await?;
+        read_task(resp).await
+    }
+
+    pub async fn task(&self, id: &str) -> Result<Task> {
+        let resp = self
+            .client
+            .get(format!("{}/a2a/tasks/{id}", self.url))
+            .bearer_auth(&self.token)
+            .send()
+            .await?;
+        read_task(resp).await
+    }
+}
+
+async fn read_task(resp: reqwest::Response) -> Result<Task> {
+    let status = resp.status();
+    if !status.is_success() {
+        let body = resp.text().await.unwrap_or_default();
+        anyhow::bail!("peer returned {status}: {body}");
+    }
+    Ok(resp.json().await?)
+}
+
+/// `a2a_delegate` tool: hand a sub-task to a peer, wait for it and write
+/// the files it built into the sandbox.
+pub struct A2aDelegateTool {
+    peers: HashMap<String, A2aPeerConfig>,
+    timeout: Duration,
+    sandbox: Arc<dyn SandboxClient>,
+    db_path: PathBuf,
+    description: String,
+}
+
+impl A2aDelegateTool {
+    pub fn new(
+        config: &FactoryA2aConfig,
+        sandbox: Arc<dyn SandboxClient>,
+        db_path: impl Into<PathBuf>,
+    ) -> Self {
+        let mut names: Vec<&String> = config.peers.keys().collect();
+        names.sort();
+        let mut description = String::from(
+            "Delegate a sub-task to another ZeroBuild instance and wait for it, e.g. have a \
+             peer build the API while you build the frontend. Returns the peer's summary and \
+             writes the files it built into the sandbox. Peers:",
+        );
+        for name in names {
+            let _ = match &config.peers[name].description {
+                Some(about) => write!(description, "\n- {name}: {about}"),
+                None => write!(description, "\n- {name}"),
+            };
+        }
+        Self {
+            peers: config.peers.clone(),
+            timeout: Duration::from_secs(config.task_timeout_secs.max(1)),
+            sandbox,
+            db_path: db_path.into(),
+            description,
+        }
+    }
+
+    /// Files to send with a task: the sandbox project, else the saved snapshot.
+    async fn project_files(&self) -> HashMap<String, String> {
+        if self.sandbox.require_id().is_ok() {
+            match self.sandbox.collect_snapshot_files("project").await {
+                Ok(files) if !files.is_empty() => return files,
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Failed to collect project files for A2A: {e:#}"),
+            }
+        }
+        store::init_db(&self.db_path)
+            .and_then(|conn| store::snapshot::load_snapshot(&conn))
+            .ok()
+            .flatten()
+            .map(|(files, _)| files)
+            .unwrap_or_default()
+    }
+
+    /// Write a finished task's files under `project/<target_dir>`.
+    async fn write_files(&self, task: &Task, target_dir: Option<&str>) -> String {
+        if task.files.is_empty() {
+            return "The peer saved no snapshot, so no files came back.".into();
+        }
+        if self.sandbox.require_id().is_err() {
+            return format!(
+                "No active sandbox: the {} returned files were not written. Create a sandbox \
+                 and call a2a_delegate again with task_id \"{}\" to write them.",
+                task.files.len(),
+                task.id
+            );
+        }
+        let base = match target_dir {
+            Some(dir) => format!("project/{dir}"),
+            None => "project".to_string(),
+        };
+        let mut skipped = 0;
+        let files: Vec<(String, String)> = task
+            .files
+            .iter()
+            .filter_map(|(path, content)| {
+                let path = path.trim_start_matches('/');
+                if is_safe_relative_path(path) {
+                    Some((format!("{base}/{path}"), content.clone()))
+                } else {
+                    skipped += 1;
+                    None
+                }
+            })
+            .collect();
+        let mut failed = 0;
+        for ((dest, _), result) in files.iter().zip(self.sandbox.write_files(&files).await) {
+            if let Err(e) = result {
+                tracing::warn!("Failed to write {dest}: {e}");
+                failed += 1;
+            }
+        }
+        let mut report = format!(
+            "Wrote {}/{} returned files to '{base}'.",
+            files.len() - failed,
+            task.files.len()
+        );
+        if skipped > 0 {
+            let _ = write!(report, " Skipped {skipped} with unsafe paths.");
+        }
+        report
+    }
+}
+
+/// Whether `path` stays inside the directory it is joined to.
+fn is_safe_relative_path(path: &str) -> bool {
+    !path.is_empty()
+        && !path.starts_with('/')
+        && path
+            .split(['/', '\\'])
+            .all(|segment| !segment.is_empty() && segment != "." && segment != "..")
+}
+
+fn failure(error: impl Into<String>) -> ToolResult {
+    ToolResult {
+        success: false,
+        output: String::new(),
+        error: Some(error.into()),
+        error_code: None,
+        error_hint: None,
+    }
+}
+
+#[async_trait]
+impl Tool for A2aDelegateTool {
+    fn name(&self) -> &str {
+        "a2a_delegate"
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        let mut names: Vec<&String> = self.peers.keys().collect();
+        names.sort();
+        json!({
+            "type": "object",
+            "additionalProperties": false,
+            "properties": {
+                "peer": {
+                    "type": "string",
+                    "enum": names,
+                    "description": "Instance to delegate to"
+                },
+                "task": {
+                    "type": "string",
+                    "description": "What the peer should build, e.g. 'Build the REST API for the todo app'"
+                },
+                "requirements": {
+                    "type": "string",
+                    "description": "Everything the peer must know: API contract, stack, conventions, what you build yourself"
+                },
+                "project": {
+                    "type": "string",
+                    "description": "Optional project name for the peer's project memory"
+                },
+                "send_files": {
+                    "type": "boolean",
+                    "description": "Send the current project files for the peer to build on. Default: false"
+                },
+                "target_dir": {
+                    "type": "string",
+                    "description": "Directory under the project root for the returned files, e.g. 'api'. Default: the project root"
+                },
+                "task_id": {
+                    "type": "string",
+                    "description": "Resume waiting for a task submitted earlier instead of submitting a new one"
+                }
+            },
+            "required": ["peer"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let name = args["peer"].as_str().unwrap_or_default();
+        let Some(peer) = self.peers.get(name) else {
+            let mut names: Vec<&str> = self.peers.keys().map(String::as_str).collect();
+            names.sort_unstable();
+            return Ok(failure(format!(
+                "Unknown peer '{name}'. Configured peers: {}",
+                names.join(", ")
+            )));
+        };
+        let target_dir = args["target_dir"]
+            .as_str()
+            .map(|dir| dir.trim().trim_matches('/'))
+            .filter(|dir| !dir.is_empty());
+        if target_dir.is_some_and(|dir| !is_safe_relative_path(dir)) {
+            return Ok(failure(
+                "'target_dir' must be a relative path inside the project",
+            ));
+        }
+        let client = PeerClient::new(peer);
+
+        let submitted = match args["task_id"].as_str().filter(|id| !id.is_empty()) {
+            Some(id) => client.task(id).await,
+            None => {
+                let Some(task) = args["task"]
+                    .as_str()
+                    .map(str::trim)
+                    .filter(|t| !t.is_empty())
+                else {
+                    return Ok(failure("'task' is required unless 'task_id' is given"));
+                };
+                let files = if args["send_files"].as_bool().unwrap_or(false) {
+                    self.project_files().await
+                } else {
+                    HashMap::new()
+                };
+                client
+                    .submit(&TaskRequest {
+                        task: task.to_string(),
+                        requirements: args["requirements"].as_str().map(str::to_string),
+                        project: args["project"].as_str().map(str::to_string),
+                        files,
+                    })
+                    .await
+            }
+        };
+        let mut task = match submitted {
+            Ok(task) => task,
+            Err(e) => return Ok(failure(format!("Peer '{name}' unreachable: {e:#}"))),
+        };
+
+        let deadline = Instant::now() + self.timeout;
+        let mut last_status = None;
+        while !task.status.is_finished() {
+            if last_status != Some(task.status) {
+                report_progress(format!("🤝 {name}: task {}", task.status.as_str()));
+                last_status = Some(task.status);
+            }
+            if Instant::now() >= deadline {
+                return Ok(failure(format!(
+                    "Task {} on '{name}' is still {} after {}s. Call a2a_delegate again \
+                     with task_id \"{}\" to keep waiting.",
+                    task.id,
+                    task.status.as_str(),
+                    self.timeout.as_secs(),
+                    task.id
+                )));
+            }
+            tokio::time::sleep(POLL_INTERVAL.min(self.timeout)).await;
+            task = match client.task(&task.id).await {
+                Ok(task) => task,
+                Err(e) => {
+                    return Ok(failure(format!(
+                        "Lost track of task {} on '{name}': {e:#}. Call a2a_delegate again \
+                         with task_id \"{}\" to resume.",
+                        task.id, task.id
+                    )))
+                }
+            };
+        }
+
+        if task.status == TaskStatus::Failed {
+            return Ok(failure(format!(
+                "Peer '{name}' failed the task: {}",
+                task.error.as_deref().unwrap_or("no error given")
+            )));
+        }
+        let written = self.write_files(&task, target_dir).await;
+        Ok(ToolResult {
+            success: true,
+            output: format!(
+                "Peer '{name}' finished task {}.\n\n{}\n\n{written}",
+                task.id,
+                task.output.as_deref().unwrap_or_default().trim()
+            ),
+            error: None,
+            error_code: None,
+            error_hint: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn board_runs_tasks_one_at_a_time() {
+        let board = Arc::new(TaskBoard::new());
+        let (release, wait) = tokio::sync::oneshot::channel::<()>();
+        let first = board.submit_with(async move {
+            let _ = wait.await;
+            Ok((
+                "built".to_string(),
+                HashMap::from([("a.ts".into(), "x".into())]),
+            ))
+        });
+        let second = board.submit_with(async { anyhow::bail!("no provider") });
+        assert_eq!(first.status, TaskStatus::Queued);
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(board.load(), (1, 1));
+        assert_eq!(board.get(&second.id).unwrap().status, TaskStatus::Queued);
+
+        release.send(()).unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let first = board.get(&first.id).unwrap();
+        assert_eq!(first.status, TaskStatus::Succeeded);
+        assert_eq!(first.output.as_deref(), Some("built"));
+        assert_eq!(first.files["a.ts"], "x");
+        let second = board.get(&second.id).unwrap();
+        assert_eq!(second.status, TaskStatus::Failed);
+        assert_eq!(second.error.as_deref(), Some("no provider"));
+        assert!(board.get("missing").is_none());
+    }
+
+    #[test]
+    fn task_prompt_carries_requirements_and_files() {
+        let prompt = task_prompt(&TaskRequest {
+            task: "Build the todo API".into(),
+            requirements: Some("REST, GET /todos returns JSON".into()),
+            project: None,
+            files: HashMap::from([("openapi.yaml".into(), String::new())]),
+        });
+        assert!(prompt.contains("Build the todo API\n\nRequirements:\nREST, GET /todos"));
+        assert!(prompt.contains("1 project files are saved as the snapshot"));
+        assert!(prompt.contains("sandbox_save_snapshot"));
+    }
+
+    #[tokio::test]
+    async fn delegate_rejects_unknown_peers_and_unsafe_paths() {
+        let config = FactoryA2aConfig {
+            peers: HashMap::from([(
+                "api".to_string(),
+                A2aPeerConfig {
+                    url: "http://127.0.0.1:9".into(),
+                    token: "t".into(),
+                    description: Some("backend APIs".into()),
+                },
+            )]),
+            ..FactoryA2aConfig::default()
+        };
+        let tool = A2aDelegateTool::new(
+            &config,
+            Arc::new(crate::sandbox::mock::MockSandboxClient::new()),
+            "unused.db",
+        );
+        assert!(tool.description().ends_with("Peers:\n- api: backend APIs"));
+
+        let result = tool
+            .execute(json!({"peer": "web", "task": "x"}))
+            .await
+            .unwrap();
+        assert!(result.error.unwrap().contains("Configured peers: api"));
+        let result = tool
+            .execute(json!({"peer": "api", "task": "x", "target_dir": "../etc"}))
+            .await
+            .unwrap();
+        assert!(result.error.unwrap().contains("relative path"));
+
+        assert!(is_safe_relative_path("src/index.ts"));
+        assert!(!is_safe_relative_path("src/../../x"));
+        assert!(!is_safe_relative_path("/etc/passwd"));
+    }
+}
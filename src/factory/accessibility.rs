@@ -0,0 +1,234 @@
+//! Accessibility audit stage (`[factory.stages] accessibility`).
+//!
+//! The workflow opens the app's preview URL with the `browser` tool and runs
+//! its `a11y_audit` action, which injects axe-core into the page. The
+//! violations are grouped by severity with the offending selectors and handed
+//! to the developer as fixes.
+
+use anyhow::{bail, Result};
+use serde_json::Value;
+use std::fmt::{self, Write};
+
+/// Most selectors listed per violation.
+const MAX_SELECTORS: usize = 5;
+
+/// axe-core impact levels, most severe first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Impact {
+    Critical,
+    Serious,
+    Moderate,
+    Minor,
+}
+
+impl Impact {
+    fn parse(raw: &str) -> Self {
+        match raw {
+            "critical" => Self::Critical,
+            "serious" => Self::Serious,
+            "moderate" => Self::Moderate,
+            _ => Self::Minor,
+        }
+    }
+}
+
+impl fmt::Display for Impact {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Critical => "critical",
+            Self::Serious => "serious",
+            Self::Moderate => "moderate",
+            Self::Minor => "minor",
+        })
+    }
+}
+
+/// An axe-core rule the page violates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    pub id: String,
+    pub impact: Impact,
+    pub help: String,
+    pub help_url: String,
+    /// CSS selectors of the offending elements.
+    pub selectors: Vec<String>,
+}
+
+/// Violations in the output of the browser tool's `a11y_audit` action.
+///
+/// Backends wrap the script result differently (a JSON string under `data`,
+/// `result` or `output`), so the first array of rule results anywhere in the
+/// output is taken.
+pub fn parse_violations(output: &str) -> Result<Vec<Violation>> {
+    let value: Value =
+        serde_json::from_str(output.trim()).unwrap_or_else(|_| Value::String(output.to_string()));
+    let Some(results) = find_results(&value)? else {
+        bail!("No axe-core results in the audit output");
+    };
+
+    let mut violations: Vec<Violation> = results
+        .iter()
+        .map(|result| Violation {
+            id: result["id"].as_str().unwrap_or_default().to_string(),
+            impact: Impact::parse(result["impact"].as_str().unwrap_or_default()),
+            help: result["help"].as_str().unwrap_or_default().to_string(),
+            help_url: result["helpUrl"].as_str().unwrap_or_default().to_string(),
+            selectors: result["nodes"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|node| selector(&node["target"]))
+                .collect(),
+        })
+        .collect();
+    violations.sort_by_key(|violation| violation.impact);
+    Ok(violations)
+}
+
+/// The first array of axe rule results (objects with an `id`) in `value`,
+/// looking inside JSON-encoded strings. An `error` the script reported is
+/// returned as an error.
+fn find_results(value: &Value) -> Result<Option<Vec<Value>>> {
+    match value {
+        Value::Array(items) if items.iter().all(|item| item.get("id").is_some()) => {
+            Ok(Some(items.clone()))
+        }
+        Value::String(text) => match serde_json::from_str::<Value>(text) {
+            Ok(parsed @ (Value::Array(_) | Value::Object(_))) => find_results(&parsed),
+            _ => Ok(None),
+        },
+        Value::Object(map) => {
+            if let Some(error) = map.get("error").and_then(Value::as_str) {
+                bail!("axe-core audit failed: {error}");
+            }
+            for field in map.values() {
+                if let Some(results) = find_results(field)? {
+                    return Ok(Some(results));
+                }
+            }
+            Ok(None)
+        }
+        _ => Ok(None),
+    }
+}
+
+/// An axe `target`: a list of selectors, nested for iframes and shadow DOM.
+fn selector(target: &Value) -> Option<String> {
+    let parts: Vec<String> = target
+        .as_array()?
+        .iter()
+        .filter_map(|part| match part {
+            Value::String(selector) => Some(selector.clone()),
+            Value::Array(_) => selector(part),
+            _ => None,
+        })
+        .collect();
+    (!parts.is_empty()).then(|| parts.join(" >>> "))
+}
+
+/// Violations grouped by severity, with up to [`MAX_SELECTORS`] offending
+/// selectors each.
+pub fn summarize(violations: &[Violation]) -> String {
+    if violations.is_empty() {
+        return "No accessibility violations found.".to_string();
+    }
+
+    let counts: Vec<String> = [
+        Impact::Critical,
+        Impact::Serious,
+        Impact::Moderate,
+        Impact::Minor,
+    ]
+    .into_iter()
+    .filter_map(|impact| {
+        let count = violations.iter().filter(|v| v.impact == impact).count();
+        (count > 0).then(|| format!("{count} {impact}"))
+    })
+    .collect();
+    let mut out = format!(
+        "{} accessibility violations ({}):",
+        violations.len(),
+        counts.join(", ")
+    );
+
+    let mut current = None;
+    for violation in violations {
+        if current != Some(violation.impact) {
+            current = Some(violation.impact);
+            let _ = write!(out, "\n[{}]", violation.impact);
+        }
+        let _ = write!(out, "\n- {}: {}", violation.id, violation.help);
+        if !violation.help_url.is_empty() {
+            let _ = write!(out, " ({})", violation.help_url);
+        }
+        for selector in violation.selectors.iter().take(MAX_SELECTORS) {
+            let _ = write!(out, "\n    {selector}");
+        }
+        if violation.selectors.len() > MAX_SELECTORS {
+            let _ = write!(
+                out,
+                "\n    … {} more elements",
+                violation.selectors.len() - MAX_SELECTORS
+            );
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_wrapped_results_and_groups_by_severity() {
+        let violations = json!([
+            {
+                "id": "color-contrast",
+                "impact": "serious",
+                "help": "Elements must meet minimum color contrast ratio thresholds",
+                "helpUrl": "https://dequeuniversity.com/rules/axe/4.8/color-contrast",
+                "nodes": (0..7).map(|i| json!({"target": [format!(".btn-{i}")]})).collect::<Vec<_>>()
+            },
+            {
+                "id": "image-alt",
+                "impact": "critical",
+                "help": "Images must have alternate text",
+                "helpUrl": "",
+                "nodes": [{"target": ["img.hero"]}, {"target": [["#widget", "img"]]}]
+            }
+        ]);
+        let output = serde_json::to_string_pretty(&json!({
+            "backend": "rust_native",
+            "action": "a11y_audit",
+            "data": violations.to_string(),
+        }))
+        .unwrap();
+
+        let parsed = parse_violations(&output).unwrap();
+        assert_eq!(parsed[0].id, "image-alt");
+        assert_eq!(parsed[0].selectors, ["img.hero", "#widget >>> img"]);
+        assert_eq!(parsed[1].impact, Impact::Serious);
+
+        let summary = summarize(&parsed);
+        assert!(summary.starts_with("2 accessibility violations (1 critical, 1 serious):"));
+        assert!(summary.contains(
+            "[critical]\n- image-alt: Images must have alternate text\n    img.hero\n    #widget >>> img"
+        ));
+        assert!(summary.contains("    .btn-4\n    … 2 more elements"));
+    }
+
+    #[test]
+    fn reports_empty_and_failed_audits() {
+        let clean = parse_violations(r#"{"result": "[]"}"#).unwrap();
+        assert!(clean.is_empty());
+        assert_eq!(summarize(&clean), "No accessibility violations found.");
+
+        let failed = parse_violations(r#"{"data": "{\"error\": \"failed to load axe-core\"}"}"#);
+        assert!(failed
+            .unwrap_err()
+            .to_string()
+            .contains("failed to load axe-core"));
+        assert!(parse_violations("page not loaded").is_err());
+    }
+}
@@ -1,19 +1,28 @@
 //! Shared state management for the multi-agent factory.
 //!
 //! The Blackboard provides typed artifact storage for inter-agent communication
-//! during factory workflow execution. Internally backed by [`InMemoryMessageBus`]
-//! from the coordination module, using `ContextPatch` envelopes for versioned writes.
-
-use crate::coordination::{CoordinationEnvelope, CoordinationPayload, InMemoryMessageBus};
+//! during factory workflow execution. Internally backed by a [`CoordinationBus`]
+//! from the coordination module, using versioned context writes. The default
+//! [`LocalBus`] keeps artifacts in process; a shared Redis or NATS bus makes
+//! them visible to every ZeroBuild process on it.
+//!
+//! With a [`BlackboardJournal`], every publish and read and every workflow
+//! phase is also recorded in the store ([`store::blackboard_log`]), so the
+//! blackboard of a past build can be reconstructed at any point.
+
+use crate::coordination::bus::{CoordinationBus, LocalBus};
+use crate::coordination::CoordinationError;
+use crate::store::{self, blackboard_log::EventKind};
+use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::path::PathBuf;
+use std::sync::Arc;
 
-/// The agent identity used for all blackboard-originated envelopes.
+/// The agent identity used for all blackboard-originated writes.
 const ORCHESTRATOR_AGENT: &str = "factory_orchestrator";
-/// Conversation id shared across all blackboard traffic.
-const CONVERSATION_ID: &str = "factory_blackboard";
-/// Topic used for artifact context patches.
-const TOPIC: &str = "artifact";
+/// Attempts to publish when other writers keep bumping the version.
+const PUBLISH_ATTEMPTS: usize = 5;
 
 /// Artifact types produced and consumed by factory agents.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -31,6 +40,12 @@ pub enum Artifact {
     TestResults,
     /// Deployment configuration.
     DeployConfig,
+    /// UI/UX comparison of the rendered app against user-provided mockups.
+    DesignReview,
+    /// Security checklist from the security review stage.
+    SecurityReport,
+    /// Accessibility violations left after the accessibility audit stage.
+    AccessibilityReport,
 }
 
 impl Artifact {
@@ -43,8 +58,16 @@ impl Artifact {
             Artifact::TestCases => "artifact:test_cases",
             Artifact::TestResults => "artifact:test_results",
             Artifact::DeployConfig => "artifact:deploy_config",
+            Artifact::DesignReview => "artifact:design_review",
+            Artifact::SecurityReport => "artifact:security_report",
+            Artifact::AccessibilityReport => "artifact:accessibility_report",
         }
     }
+
+    /// Name of the artifact in the journal, e.g. `prd`.
+    pub fn name(&self) -> &'static str {
+        self.context_key().trim_start_matches("artifact:")
+    }
 }
 
 /// A versioned artifact entry on the blackboard.
@@ -55,71 +78,190 @@ pub struct ArtifactEntry {
     pub updated_by: String,
 }
 
+/// Journal of one build's blackboard in the ZeroBuild store.
+#[derive(Debug)]
+pub struct BlackboardJournal {
+    db_path: PathBuf,
+    workflow_id: String,
+}
+
+impl BlackboardJournal {
+    /// Journal build `workflow_id` of `idea` in the store at `db_path`.
+    pub fn start(db_path: impl Into<PathBuf>, workflow_id: &str, idea: &str) -> Self {
+        let journal = Self {
+            db_path: db_path.into(),
+            workflow_id: workflow_id.to_string(),
+        };
+        if let Err(e) = store::init_db(&journal.db_path)
+            .and_then(|conn| store::blackboard_log::start_build(&conn, workflow_id, idea))
+        {
+            tracing::warn!("Failed to start the blackboard journal: {e:#}");
+        }
+        journal
+    }
+
+    /// Record an event; failures are logged, never fail the build.
+    fn record(
+        &self,
+        kind: EventKind,
+        artifact: &str,
+        agent: Option<&str>,
+        version: Option<u64>,
+        value: Option<&Value>,
+    ) {
+        if let Err(e) = store::init_db(&self.db_path).and_then(|conn| {
+            store::blackboard_log::record(
+                &conn,
+                &self.workflow_id,
+                kind,
+                artifact,
+                agent,
+                version,
+                value,
+            )
+        }) {
+            tracing::warn!("Failed to journal blackboard {artifact}: {e:#}");
+        }
+    }
+}
+
 /// Shared state layer for inter-agent communication in the factory workflow.
 ///
 /// Thread-safe, cloneable. Agents publish artifacts via `publish_artifact()` and
-/// read them via `read_artifact()`. Internally delegates to [`InMemoryMessageBus`].
-#[derive(Debug, Clone)]
+/// read them via `read_artifact()`. Internally delegates to a [`CoordinationBus`].
+#[derive(Clone)]
 pub struct Blackboard {
-    bus: InMemoryMessageBus,
+    bus: Arc<dyn CoordinationBus>,
+    /// Prefix keeping one build's artifacts apart from others on a shared bus.
+    scope: String,
+    journal: Option<Arc<BlackboardJournal>>,
+}
+
+impl std::fmt::Debug for Blackboard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Blackboard")
+            .field("backend", &self.bus.backend())
+            .field("scope", &self.scope)
+            .field("journal", &self.journal)
+            .finish()
+    }
 }
 
 impl Blackboard {
     /// Create a new empty Blackboard.
     pub fn new() -> Self {
-        let bus = InMemoryMessageBus::new();
-        bus.register_agent(ORCHESTRATOR_AGENT)
-            .expect("register factory_orchestrator agent");
-        Self { bus }
+        Self {
+            bus: Arc::new(LocalBus::new()),
+            scope: String::new(),
+            journal: None,
+        }
+    }
+
+    /// Keep artifacts on `bus` under `scope`, usually the workflow id.
+    pub fn with_bus(bus: Arc<dyn CoordinationBus>, scope: &str) -> Self {
+        Self {
+            bus,
+            scope: format!("factory/{scope}/"),
+            journal: None,
+        }
+    }
+
+    /// Record publishes, reads and phases in `journal`.
+    pub fn with_journal(mut self, journal: Arc<BlackboardJournal>) -> Self {
+        self.journal = Some(journal);
+        self
+    }
+
+    /// The journal events are recorded in, if any.
+    pub fn journal(&self) -> Option<&Arc<BlackboardJournal>> {
+        self.journal.as_ref()
+    }
+
+    /// Note in the journal that the workflow entered `phase`, so later
+    /// reads can be told apart by stage.
+    pub fn mark_phase(&self, phase: &str) {
+        if let Some(journal) = &self.journal {
+            journal.record(EventKind::Phase, phase, None, None, None);
+        }
+    }
+
+    fn key(&self, artifact: &Artifact) -> String {
+        format!("{}{}", self.scope, artifact.context_key())
     }
 
     /// Publish an artifact to the blackboard.
     ///
     /// Overwrites any existing value for the same artifact type.
-    /// The version is incremented automatically via `ContextPatch`.
-    pub fn publish_artifact(&self, artifact: Artifact, value: Value, _from: &str) {
-        let key = artifact.context_key();
-        let expected_version = self.bus.context_entry(key).map(|e| e.version).unwrap_or(0);
-
-        let envelope = CoordinationEnvelope::new_broadcast(
-            ORCHESTRATOR_AGENT,
-            CONVERSATION_ID,
-            TOPIC,
-            CoordinationPayload::ContextPatch {
-                key: key.to_string(),
-                expected_version,
-                value,
-            },
-        );
-
-        self.bus
-            .publish(envelope)
-            .expect("blackboard publish must succeed");
+    /// The version is incremented automatically on every write.
+    pub async fn publish_artifact(
+        &self,
+        artifact: Artifact,
+        value: Value,
+        from: &str,
+    ) -> Result<()> {
+        let key = self.key(&artifact);
+        let mut attempt = 0;
+        loop {
+            let expected_version = self.bus.context_entry(&key).await?.map_or(0, |e| e.version);
+            match self
+                .bus
+                .patch_context(&key, expected_version, value.clone(), ORCHESTRATOR_AGENT)
+                .await
+            {
+                Ok(version) => {
+                    if let Some(journal) = &self.journal {
+                        journal.record(
+                            EventKind::Publish,
+                            artifact.name(),
+                            Some(from),
+                            Some(version),
+                            Some(&value),
+                        );
+                    }
+                    return Ok(());
+                }
+                Err(e)
+                    if attempt + 1 < PUBLISH_ATTEMPTS
+                        && matches!(
+                            e.downcast_ref::<CoordinationError>(),
+                            Some(CoordinationError::ContextVersionMismatch { .. })
+                        ) =>
+                {
+                    attempt += 1;
+                }
+                Err(e) => return Err(e.context(format!("failed to publish {key}"))),
+            }
+        }
     }
 
     /// Read an artifact value from the blackboard.
     ///
     /// Returns `None` if the artifact has not been published yet.
-    pub fn read_artifact(&self, artifact: &Artifact) -> Option<Value> {
-        self.bus
-            .context_entry(artifact.context_key())
-            .map(|e| e.value)
+    pub async fn read_artifact(&self, artifact: &Artifact) -> Result<Option<Value>> {
+        Ok(self.read_entry(artifact).await?.map(|e| e.value))
     }
 
     /// Read a full artifact entry (includes version metadata).
-    pub fn read_entry(&self, artifact: &Artifact) -> Option<ArtifactEntry> {
-        self.bus
-            .context_entry(artifact.context_key())
+    pub async fn read_entry(&self, artifact: &Artifact) -> Result<Option<ArtifactEntry>> {
+        let entry = self
+            .bus
+            .context_entry(&self.key(artifact))
+            .await?
             .map(|e| ArtifactEntry {
                 value: e.value,
                 version: e.version,
                 updated_by: e.updated_by,
-            })
+            });
+        if let Some(journal) = &self.journal {
+            let version = entry.as_ref().map(|e| e.version);
+            journal.record(EventKind::Read, artifact.name(), None, version, None);
+        }
+        Ok(entry)
     }
 
     /// Check if an artifact has been published.
-    pub fn has_artifact(&self, artifact: &Artifact) -> bool {
-        self.bus.context_entry(artifact.context_key()).is_some()
+    pub async fn has_artifact(&self, artifact: &Artifact) -> Result<bool> {
+        Ok(self.read_entry(artifact).await?.is_some())
     }
 }
 
@@ -134,79 +276,156 @@ mod tests {
     use super::*;
     use serde_json::json;
 
-    #[test]
-    fn publish_and_read_artifact() {
+    #[tokio::test]
+    async fn publish_and_read_artifact() {
         let board = Blackboard::new();
         let prd = json!({
             "title": "Todo App",
             "features": ["add tasks", "delete tasks"]
         });
 
-        board.publish_artifact(Artifact::Prd, prd.clone(), "business_analyst");
+        board
+            .publish_artifact(Artifact::Prd, prd.clone(), "business_analyst")
+            .await
+            .unwrap();
 
-        let read = board.read_artifact(&Artifact::Prd);
+        let read = board.read_artifact(&Artifact::Prd).await.unwrap();
         assert_eq!(read, Some(prd));
     }
 
-    #[test]
-    fn read_missing_artifact_returns_none() {
+    #[tokio::test]
+    async fn read_missing_artifact_returns_none() {
         let board = Blackboard::new();
-        assert!(board.read_artifact(&Artifact::DesignSpec).is_none());
+        assert!(board
+            .read_artifact(&Artifact::DesignSpec)
+            .await
+            .unwrap()
+            .is_none());
     }
 
-    #[test]
-    fn has_artifact_tracks_publication() {
+    #[tokio::test]
+    async fn has_artifact_tracks_publication() {
         let board = Blackboard::new();
-        assert!(!board.has_artifact(&Artifact::TestResults));
+        assert!(!board.has_artifact(&Artifact::TestResults).await.unwrap());
 
-        board.publish_artifact(Artifact::TestResults, json!({"passed": true}), "tester");
+        board
+            .publish_artifact(Artifact::TestResults, json!({"passed": true}), "tester")
+            .await
+            .unwrap();
 
-        assert!(board.has_artifact(&Artifact::TestResults));
+        assert!(board.has_artifact(&Artifact::TestResults).await.unwrap());
     }
 
-    #[test]
-    fn overwrite_artifact_increments_version() {
+    #[tokio::test]
+    async fn overwrite_artifact_increments_version() {
         let board = Blackboard::new();
 
-        board.publish_artifact(Artifact::SourceCode, json!({"v": 1}), "developer");
-        let entry1 = board.read_entry(&Artifact::SourceCode).unwrap();
+        board
+            .publish_artifact(Artifact::SourceCode, json!({"v": 1}), "developer")
+            .await
+            .unwrap();
+        let entry1 = board
+            .read_entry(&Artifact::SourceCode)
+            .await
+            .unwrap()
+            .unwrap();
         assert_eq!(entry1.version, 1);
 
-        board.publish_artifact(Artifact::SourceCode, json!({"v": 2}), "developer");
-        let entry2 = board.read_entry(&Artifact::SourceCode).unwrap();
+        board
+            .publish_artifact(Artifact::SourceCode, json!({"v": 2}), "developer")
+            .await
+            .unwrap();
+        let entry2 = board
+            .read_entry(&Artifact::SourceCode)
+            .await
+            .unwrap()
+            .unwrap();
         assert_eq!(entry2.version, 2);
         assert_eq!(entry2.value, json!({"v": 2}));
     }
 
-    #[test]
-    fn artifact_keys_are_distinct() {
+    #[tokio::test]
+    async fn artifact_keys_are_distinct() {
         let board = Blackboard::new();
 
-        board.publish_artifact(Artifact::Prd, json!("prd"), "ba");
-        board.publish_artifact(Artifact::DesignSpec, json!("design"), "uiux");
+        board
+            .publish_artifact(Artifact::Prd, json!("prd"), "ba")
+            .await
+            .unwrap();
+        board
+            .publish_artifact(Artifact::DesignSpec, json!("design"), "uiux")
+            .await
+            .unwrap();
 
-        assert_eq!(board.read_artifact(&Artifact::Prd), Some(json!("prd")));
         assert_eq!(
-            board.read_artifact(&Artifact::DesignSpec),
+            board.read_artifact(&Artifact::Prd).await.unwrap(),
+            Some(json!("prd"))
+        );
+        assert_eq!(
+            board.read_artifact(&Artifact::DesignSpec).await.unwrap(),
             Some(json!("design"))
         );
     }
 
-    #[test]
-    fn entry_tracks_author() {
+    #[tokio::test]
+    async fn entry_tracks_author() {
         let board = Blackboard::new();
-        board.publish_artifact(Artifact::TestCases, json!("tests"), "tester");
-
-        let entry = board.read_entry(&Artifact::TestCases).unwrap();
+        board
+            .publish_artifact(Artifact::TestCases, json!("tests"), "tester")
+            .await
+            .unwrap();
+
+        let entry = board
+            .read_entry(&Artifact::TestCases)
+            .await
+            .unwrap()
+            .unwrap();
         assert_eq!(entry.updated_by, ORCHESTRATOR_AGENT);
     }
 
-    #[test]
-    fn thread_safe_clone() {
+    #[tokio::test]
+    async fn journal_records_publishes_reads_and_phases() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let db_path = tmp.path().join("zerobuild.db");
+        let journal = Arc::new(BlackboardJournal::start(&db_path, "wf-1", "todo app"));
+        let board = Blackboard::new().with_journal(journal);
+
+        board.mark_phase("parallel_build");
+        assert!(board.read_artifact(&Artifact::Prd).await.unwrap().is_none());
+        board
+            .publish_artifact(Artifact::Prd, json!("prd"), "business_analyst")
+            .await
+            .unwrap();
+
+        let conn = store::init_db(&db_path).unwrap();
+        let events = store::blackboard_log::events(&conn, "wf-1", None).unwrap();
+        let kinds: Vec<_> = events
+            .iter()
+            .map(|e| (e.kind, e.artifact.as_str(), e.version))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                (EventKind::Phase, "parallel_build", None),
+                (EventKind::Read, "prd", None),
+                (EventKind::Publish, "prd", Some(1)),
+            ]
+        );
+        assert_eq!(events[2].agent.as_deref(), Some("business_analyst"));
+    }
+
+    #[tokio::test]
+    async fn thread_safe_clone() {
         let board = Blackboard::new();
         let board2 = board.clone();
 
-        board.publish_artifact(Artifact::Prd, json!("shared"), "ba");
-        assert_eq!(board2.read_artifact(&Artifact::Prd), Some(json!("shared")));
+        board
+            .publish_artifact(Artifact::Prd, json!("shared"), "ba")
+            .await
+            .unwrap();
+        assert_eq!(
+            board2.read_artifact(&Artifact::Prd).await.unwrap(),
+            Some(json!("shared"))
+        );
     }
 }
@@ -0,0 +1,270 @@
+//! Factory stages spread over several ZeroBuild processes.
+//!
+//! With `[factory.bus] distribute_stages = true`, a build enqueues its
+//! completion stages (PRD, design, test cases, reviews) on a shared
+//! [`CoordinationBus`] instead of calling the model itself. Any process on
+//! the bus with `workers > 0` claims the stage, runs it with its own
+//! provider settings and writes the outcome to the build's blackboard scope,
+//! where the waiting build picks it up. Tool-using stages stay in the
+//! process that owns the sandbox.
+
+use super::orchestrator_tool::FactoryOrchestratorTool;
+use super::roles::AgentRole;
+use crate::coordination::bus::{CoordinationBus, QueuedMessage};
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Queue the stage jobs go through.
+pub const STAGE_QUEUE: &str = "factory_stages";
+
+/// How long an idle worker blocks on the queue before checking again.
+const IDLE_WAIT: Duration = Duration::from_secs(5);
+
+/// How often a build checks the blackboard for a dispatched stage's outcome.
+const RESULT_POLL: Duration = Duration::from_secs(2);
+
+/// Set once the process-wide stage workers are running.
+static WORKERS: OnceLock<()> = OnceLock::new();
+
+/// A stage waiting on the queue.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StageJob {
+    pub workflow_id: String,
+    pub role: AgentRole,
+    /// The build's idea, for prompt templates that use `{{idea}}`.
+    pub idea: String,
+    /// The full prompt, shared memory context included.
+    pub prompt: String,
+    /// Blackboard key the outcome is written to.
+    pub result_key: String,
+    /// Unix time after which the build stopped waiting.
+    pub deadline: i64,
+}
+
+/// What a worker wrote back for a stage.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StageOutcome {
+    pub output: Option<String>,
+    pub error: Option<String>,
+    pub provider: String,
+    pub model: String,
+    pub worker: String,
+}
+
+/// Sends a build's stages to the queue and waits for their outcomes.
+#[derive(Clone)]
+pub struct StageDispatch {
+    bus: Arc<dyn CoordinationBus>,
+    timeout: Duration,
+}
+
+impl StageDispatch {
+    pub fn new(bus: Arc<dyn CoordinationBus>, timeout: Duration) -> Self {
+        Self { bus, timeout }
+    }
+
+    /// Enqueue `prompt` for `role` and wait until a worker finished it.
+    pub async fn run(
+        &self,
+        workflow_id: Uuid,
+        role: AgentRole,
+        idea: &str,
+        prompt: &str,
+    ) -> Result<StageOutcome> {
+        let result_key = format!("factory/{workflow_id}/stage/{}", Uuid::new_v4());
+        let timeout = chrono::Duration::from_std(self.timeout).unwrap_or(chrono::Duration::MAX);
+        let job = StageJob {
+            workflow_id: workflow_id.to_string(),
+            role,
+            idea: idea.to_string(),
+            prompt: prompt.to_string(),
+            result_key: result_key.clone(),
+            deadline: (chrono::Utc::now() + timeout).timestamp(),
+        };
+        self.bus.enqueue(STAGE_QUEUE, json!(job)).await?;
+        tracing::debug!(%role, %workflow_id, "Stage queued for a worker");
+
+        let deadline = tokio::time::Instant::now() + self.timeout;
+        loop {
+            if let Some(entry) = self.bus.context_entry(&result_key).await? {
+                return Ok(serde_json::from_value(entry.value)?);
+            }
+            if tokio::time::Instant::now() >= deadline {
+                bail!(
+                    "Agent {role} timed out: no stage worker finished it within {}s",
+                    self.timeout.as_secs()
+                );
+            }
+            tokio::time::sleep(RESULT_POLL).await;
+        }
+    }
+}
+
+/// Workers claiming stages from [`STAGE_QUEUE`].
+pub struct StageWorkers;
+
+impl StageWorkers {
+    /// Start `workers` worker loops running stages with `factory`'s provider
+    /// settings, once per process.
+    pub fn start_global(
+        bus: Arc<dyn CoordinationBus>,
+        workers: usize,
+        factory: Arc<FactoryOrchestratorTool>,
+    ) -> Result<()> {
+        if WORKERS.get().is_some() {
+            return Ok(());
+        }
+        let handle = tokio::runtime::Handle::try_current()
+            .map_err(|_| anyhow::anyhow!("stage workers need a Tokio runtime"))?;
+        if WORKERS.set(()).is_err() {
+            return Ok(());
+        }
+        let process = Uuid::new_v4().simple().to_string();
+        for worker in 0..workers {
+            let consumer = format!("{}-{worker}", &process[..8]);
+            handle.spawn(worker_loop(
+                Arc::clone(&bus),
+                consumer,
+                Arc::clone(&factory),
+            ));
+        }
+        tracing::info!(
+            workers,
+            backend = bus.backend(),
+            "Factory stage workers started"
+        );
+        Ok(())
+    }
+}
+
+async fn worker_loop(
+    bus: Arc<dyn CoordinationBus>,
+    consumer: String,
+    factory: Arc<FactoryOrchestratorTool>,
+) {
+    loop {
+        match bus.claim(STAGE_QUEUE, &consumer, IDLE_WAIT).await {
+            Ok(Some(message)) => run_job(bus.as_ref(), &consumer, &factory, message).await,
+            Ok(None) => {}
+            Err(e) => {
+                tracing::warn!(consumer, "Failed to claim a factory stage: {e:#}");
+                tokio::time::sleep(IDLE_WAIT).await;
+            }
+        }
+    }
+}
+
+async fn run_job(
+    bus: &dyn CoordinationBus,
+    consumer: &str,
+    factory: &FactoryOrchestratorTool,
+    message: QueuedMessage,
+) {
+    match serde_json::from_value::<StageJob>(message.payload) {
+        Ok(job) if job.deadline < chrono::Utc::now().timestamp() => {
+            tracing::info!(
+                role = %job.role,
+                workflow_id = job.workflow_id,
+                "Skipping a stage its build stopped waiting for"
+            );
+        }
+        Ok(job) => {
+            tracing::info!(
+                consumer,
+                role = %job.role,
+                workflow_id = job.workflow_id,
+                "Running factory stage"
+            );
+            let outcome = run_stage(factory, &job, consumer).await;
+            if let Err(e) = bus
+                .patch_context(&job.result_key, 0, json!(outcome), consumer)
+                .await
+            {
+                tracing::warn!(consumer, "Failed to report factory stage: {e:#}");
+            }
+        }
+        Err(e) => tracing::warn!(consumer, "Dropping malformed factory stage: {e}"),
+    }
+    if let Err(e) = bus.ack(STAGE_QUEUE, &message.id).await {
+        tracing::warn!(consumer, "Failed to acknowledge factory stage: {e:#}");
+    }
+}
+
+async fn run_stage(
+    factory: &FactoryOrchestratorTool,
+    job: &StageJob,
+    consumer: &str,
+) -> StageOutcome {
+    let workflow = factory.stage_workflow(job.idea.clone());
+    let (provider, model) = workflow.stage_model(job.role);
+    let (output, error) = match workflow.complete(job.role, job.prompt.clone()).await {
+        Ok(output) => (Some(output), None),
+        Err(e) => (None, Some(format!("{e:#}"))),
+    };
+    StageOutcome {
+        output,
+        error,
+        provider,
+        model,
+        worker: consumer.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coordination::bus::LocalBus;
+
+    #[tokio::test]
+    async fn dispatch_waits_for_the_outcome_a_worker_writes() {
+        let bus: Arc<dyn CoordinationBus> = Arc::new(LocalBus::new());
+        let dispatch = StageDispatch::new(Arc::clone(&bus), Duration::from_secs(30));
+        let workflow_id = Uuid::new_v4();
+        let waiting = tokio::spawn(async move {
+            dispatch
+                .run(workflow_id, AgentRole::Tester, "todo app", "write tests")
+                .await
+        });
+
+        let message = bus
+            .claim(STAGE_QUEUE, "w1", Duration::from_secs(5))
+            .await
+            .unwrap()
+            .unwrap();
+        let job: StageJob = serde_json::from_value(message.payload).unwrap();
+        assert_eq!(job.role, AgentRole::Tester);
+        assert_eq!(job.prompt, "write tests");
+        assert!(job
+            .result_key
+            .starts_with(&format!("factory/{workflow_id}/stage/")));
+
+        let outcome = StageOutcome {
+            output: Some("tests".into()),
+            error: None,
+            provider: "openrouter".into(),
+            model: "m".into(),
+            worker: "w1".into(),
+        };
+        bus.patch_context(&job.result_key, 0, json!(outcome), "w1")
+            .await
+            .unwrap();
+        bus.ack(STAGE_QUEUE, &message.id).await.unwrap();
+
+        assert_eq!(waiting.await.unwrap().unwrap(), outcome);
+    }
+
+    #[tokio::test]
+    async fn dispatch_gives_up_after_its_timeout() {
+        let bus: Arc<dyn CoordinationBus> = Arc::new(LocalBus::new());
+        let dispatch = StageDispatch::new(bus, Duration::ZERO);
+        let err = dispatch
+            .run(Uuid::new_v4(), AgentRole::Tester, "idea", "prompt")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("timed out"));
+    }
+}
@@ -0,0 +1,407 @@
+//! Cost estimate for a factory build (`[factory] confirm_above_usd`).
+//!
+//! Before a build starts, its planned agent calls are priced from the size
+//! of the request and each stage's model. Once builds have finished, the
+//! estimate is scaled by how far earlier baselines were off (see
+//! [`crate::store::build_usage`]). While the build runs, a [`UsageMeter`]
+//! counts the tokens the agents actually used, so the final report can put
+//! the estimate next to the actuals.
+
+use super::roles::AgentRole;
+use crate::config::ModelPricing;
+use crate::observability::traits::{Observer, ObserverEvent, ObserverMetric};
+use crate::store::build_usage::BuildUsage;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Input and output tokens of a single-shot stage call, before the request.
+const SIMPLE_CALL_TOKENS: (u64, u64) = (3_000, 2_000);
+
+/// Input and output tokens of an agentic stage run. Every tool iteration
+/// resends the conversation, so input dominates.
+const AGENTIC_CALL_TOKENS: (u64, u64) = (60_000, 8_000);
+
+/// Wall time of a single-shot and an agentic call, in seconds.
+const SIMPLE_CALL_SECS: u64 = 40;
+const AGENTIC_CALL_SECS: u64 = 240;
+
+/// Times an agentic run resends the request with its growing history.
+const AGENTIC_REQUEST_REPEATS: u64 = 4;
+
+/// Price (USD per 1M input and output tokens) assumed for models without
+/// configured pricing; the conservative default of the cost tracker.
+const DEFAULT_PRICE: (f64, f64) = (3.0, 15.0);
+
+/// Finished builds used to calibrate the estimate.
+pub const CALIBRATION_BUILDS: usize = 20;
+
+/// Calibration factors are kept within this range so one odd build can't
+/// skew every estimate.
+const CALIBRATION_RANGE: (f64, f64) = (0.25, 4.0);
+
+/// An agent call the build is expected to make.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedCall {
+    pub role: AgentRole,
+    /// `provider/model` the stage runs on.
+    pub model: String,
+    /// Runs a tool loop rather than a single completion.
+    pub agentic: bool,
+    /// Runs alongside the previous call, adding no wall time of its own.
+    pub concurrent: bool,
+}
+
+/// Token, time and cost estimate for a build.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BuildEstimate {
+    pub calls: usize,
+    /// Heuristic estimate before calibration, recorded with the actuals.
+    pub baseline_tokens: u64,
+    pub baseline_secs: u64,
+    pub tokens: u64,
+    pub secs: u64,
+    pub cost_usd: f64,
+    /// Past builds the estimate was calibrated on.
+    pub calibrated_on: usize,
+}
+
+/// Estimate the planned calls for a request of `request_chars` characters,
+/// calibrated by `history` (most recent builds).
+#[allow(
+    clippy::implicit_hasher,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+pub fn estimate(
+    request_chars: usize,
+    plan: &[PlannedCall],
+    prices: &HashMap<String, ModelPricing>,
+    history: &[BuildUsage],
+) -> BuildEstimate {
+    let request_tokens = request_chars as u64 / 4;
+    let (token_factor, time_factor, calibrated_on) = calibration(history);
+
+    let mut baseline_tokens = 0;
+    let mut baseline_secs = 0;
+    let mut cost_usd = 0.0;
+    for call in plan {
+        let ((input, output), secs) = if call.agentic {
+            let (input, output) = AGENTIC_CALL_TOKENS;
+            (
+                (input + request_tokens * AGENTIC_REQUEST_REPEATS, output),
+                AGENTIC_CALL_SECS,
+            )
+        } else {
+            let (input, output) = SIMPLE_CALL_TOKENS;
+            ((input + request_tokens, output), SIMPLE_CALL_SECS)
+        };
+        baseline_tokens += input + output;
+        if !call.concurrent {
+            baseline_secs += secs;
+        }
+        let (input_price, output_price) = price(prices, &call.model);
+        cost_usd += (input as f64 * input_price + output as f64 * output_price) / 1_000_000.0;
+    }
+
+    BuildEstimate {
+        calls: plan.len(),
+        baseline_tokens,
+        baseline_secs,
+        tokens: (baseline_tokens as f64 * token_factor).round() as u64,
+        secs: (baseline_secs as f64 * time_factor).round() as u64,
+        cost_usd: cost_usd * token_factor,
+        calibrated_on,
+    }
+}
+
+/// Token and time factors from how far past baselines were off, and the
+/// number of builds they are based on.
+fn calibration(history: &[BuildUsage]) -> (f64, f64, usize) {
+    let measured: Vec<&BuildUsage> = history
+        .iter()
+        .filter(|build| build.actual_tokens > 0 && build.estimated_tokens > 0)
+        .collect();
+    if measured.is_empty() {
+        return (1.0, 1.0, 0);
+    }
+    let ratio = |actual: u64, estimated: u64| {
+        if estimated == 0 {
+            1.0
+        } else {
+            (actual as f64 / estimated as f64).clamp(CALIBRATION_RANGE.0, CALIBRATION_RANGE.1)
+        }
+    };
+    let tokens = ratio(
+        measured.iter().map(|build| build.actual_tokens).sum(),
+        measured.iter().map(|build| build.estimated_tokens).sum(),
+    );
+    let time = ratio(
+        measured.iter().map(|build| build.actual_secs).sum(),
+        measured.iter().map(|build| build.estimated_secs).sum(),
+    );
+    (tokens, time, measured.len())
+}
+
+/// Input and output price for `provider/model`, by full name or model name.
+fn price(prices: &HashMap<String, ModelPricing>, model: &str) -> (f64, f64) {
+    let short = model.split_once('/').map_or(model, |(_, rest)| rest);
+    prices
+        .get(model)
+        .or_else(|| prices.get(short))
+        .map_or(DEFAULT_PRICE, |pricing| (pricing.input, pricing.output))
+}
+
+/// `1234567` as `1.2M`, `45600` as `46k`.
+fn tokens_label(tokens: u64) -> String {
+    if tokens >= 1_000_000 {
+        format!("{:.1}M", tokens as f64 / 1_000_000.0)
+    } else if tokens >= 1_000 {
+        format!("{}k", (tokens as f64 / 1_000.0).round())
+    } else {
+        tokens.to_string()
+    }
+}
+
+fn duration_label(secs: u64) -> String {
+    if secs >= 60 {
+        format!("{} min", (secs as f64 / 60.0).round())
+    } else {
+        format!("{secs} s")
+    }
+}
+
+/// `actual` relative to `estimated`, e.g. `+12%`.
+fn deviation(actual: f64, estimated: f64) -> String {
+    if estimated <= 0.0 {
+        return "n/a".to_string();
+    }
+    format!("{:+.0}%", (actual / estimated - 1.0) * 100.0)
+}
+
+impl BuildEstimate {
+    /// One-paragraph estimate shown before the build.
+    pub fn preview(&self) -> String {
+        let basis = match self.calibrated_on {
+            0 => "rough heuristic; no finished builds to calibrate on yet".to_string(),
+            1 => "calibrated on the last build".to_string(),
+            n => format!("calibrated on the last {n} builds"),
+        };
+        format!(
+            "Estimated factory build: {} agent calls, ~{} tokens, ~{}, ~${:.2} ({basis}).",
+            self.calls,
+            tokens_label(self.tokens),
+            duration_label(self.secs),
+            self.cost_usd
+        )
+    }
+
+    /// Estimate next to what the build used.
+    pub fn compare(&self, actual: &MeteredUsage, actual_secs: u64) -> String {
+        let tokens = actual.tokens();
+        let mut out = format!(
+            "Estimate vs actual: {} calls vs {}; ~{} vs {} tokens ({}); ~{} vs {} ({}); ~${:.2} vs ${:.2}",
+            self.calls,
+            actual.calls,
+            tokens_label(self.tokens),
+            tokens_label(tokens),
+            deviation(tokens as f64, self.tokens as f64),
+            duration_label(self.secs),
+            duration_label(actual_secs),
+            deviation(actual_secs as f64, self.secs as f64),
+            self.cost_usd,
+            actual.cost_usd,
+        );
+        if actual.unreported_calls > 0 {
+            let _ = write!(
+                out,
+                " ({} of the calls reported no token usage)",
+                actual.unreported_calls
+            );
+        }
+        out
+    }
+}
+
+/// Tokens, calls and cost a build used so far.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MeteredUsage {
+    pub calls: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: f64,
+    /// Calls whose provider didn't report usage.
+    pub unreported_calls: u64,
+}
+
+impl MeteredUsage {
+    pub fn tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens
+    }
+}
+
+/// Counts the tokens of a build's LLM calls: agentic runs report through
+/// [`Observer`] events, single-shot calls through [`UsageMeter::record`].
+#[derive(Default)]
+pub struct UsageMeter {
+    prices: HashMap<String, ModelPricing>,
+    usage: Mutex<MeteredUsage>,
+}
+
+impl UsageMeter {
+    pub fn new(prices: HashMap<String, ModelPricing>) -> Self {
+        Self {
+            prices,
+            usage: Mutex::new(MeteredUsage::default()),
+        }
+    }
+
+    /// Count a call on `provider/model`.
+    pub fn record(&self, provider: &str, model: &str, input_tokens: u64, output_tokens: u64) {
+        let (input_price, output_price) = price(&self.prices, &format!("{provider}/{model}"));
+        let mut usage = self.usage.lock();
+        usage.calls += 1;
+        usage.input_tokens += input_tokens;
+        usage.output_tokens += output_tokens;
+        usage.cost_usd +=
+            (input_tokens as f64 * input_price + output_tokens as f64 * output_price) / 1_000_000.0;
+    }
+
+    /// Count a call whose provider reported no usage, from the length of
+    /// its messages.
+    pub fn record_unreported(&self, provider: &str, model: &str, input: &str, output: &str) {
+        self.record(
+            provider,
+            model,
+            input.len() as u64 / 4,
+            output.len() as u64 / 4,
+        );
+        self.usage.lock().unreported_calls += 1;
+    }
+
+    pub fn usage(&self) -> MeteredUsage {
+        *self.usage.lock()
+    }
+}
+
+impl Observer for UsageMeter {
+    fn record_event(&self, event: &ObserverEvent) {
+        if let ObserverEvent::LlmResponse {
+            provider,
+            model,
+            success: true,
+            input_tokens,
+            output_tokens,
+            ..
+        } = event
+        {
+            if input_tokens.is_none() && output_tokens.is_none() {
+                let mut usage = self.usage.lock();
+                usage.calls += 1;
+                usage.unreported_calls += 1;
+                return;
+            }
+            self.record(
+                provider,
+                model,
+                input_tokens.unwrap_or(0),
+                output_tokens.unwrap_or(0),
+            );
+        }
+    }
+
+    fn record_metric(&self, _metric: &ObserverMetric) {}
+
+    fn name(&self) -> &str {
+        "factory_usage"
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan() -> Vec<PlannedCall> {
+        let call = |role, agentic, concurrent| PlannedCall {
+            role,
+            model: "anthropic/claude-sonnet-4".into(),
+            agentic,
+            concurrent,
+        };
+        vec![
+            call(AgentRole::BusinessAnalyst, false, false),
+            call(AgentRole::Developer, true, false),
+            call(AgentRole::Tester, false, true),
+        ]
+    }
+
+    #[test]
+    fn estimates_from_plan_and_calibrates_on_history() {
+        let prices = HashMap::from([(
+            "claude-sonnet-4".to_string(),
+            ModelPricing {
+                input: 3.0,
+                output: 15.0,
+            },
+        )]);
+        let baseline = estimate(4_000, &plan(), &prices, &[]);
+        // BA: 3000+1000 in, 2000 out; Dev: 60000+4000 in, 8000 out; Tester like BA.
+        assert_eq!(baseline.baseline_tokens, 6_000 + 72_000 + 6_000);
+        assert_eq!(baseline.secs, SIMPLE_CALL_SECS + AGENTIC_CALL_SECS);
+        assert!((baseline.cost_usd - 0.396).abs() < 1e-9);
+        assert!(baseline.preview().contains("rough heuristic"));
+
+        let history = [
+            BuildUsage {
+                estimated_tokens: 100_000,
+                actual_tokens: 150_000,
+                estimated_secs: 600,
+                actual_secs: 300,
+            },
+            BuildUsage {
+                estimated_tokens: 100_000,
+                actual_tokens: 0,
+                estimated_secs: 600,
+                actual_secs: 9_999,
+            },
+        ];
+        let calibrated = estimate(4_000, &plan(), &prices, &history);
+        assert_eq!(calibrated.calibrated_on, 1);
+        assert_eq!(calibrated.baseline_tokens, baseline.baseline_tokens);
+        assert_eq!(calibrated.tokens, 126_000);
+        assert_eq!(calibrated.secs, 140);
+        assert!(calibrated
+            .preview()
+            .contains("calibrated on the last build"));
+    }
+
+    #[test]
+    fn meter_counts_reported_and_unreported_calls() {
+        let meter = UsageMeter::default();
+        meter.record_event(&ObserverEvent::LlmResponse {
+            provider: "openrouter".into(),
+            model: "x".into(),
+            duration: std::time::Duration::from_secs(1),
+            success: true,
+            error_message: None,
+            input_tokens: Some(1_000_000),
+            output_tokens: Some(0),
+            cached_input_tokens: None,
+        });
+        meter.record_unreported("openrouter", "x", &"a".repeat(400), &"b".repeat(40));
+
+        let usage = meter.usage();
+        assert_eq!(usage.calls, 2);
+        assert_eq!(usage.tokens(), 1_000_110);
+        assert_eq!(usage.unreported_calls, 1);
+        assert!((usage.cost_usd - 3.00045).abs() < 1e-9);
+
+        let report = estimate(0, &plan(), &HashMap::new(), &[]).compare(&usage, 600);
+        assert!(report.starts_with("Estimate vs actual: 3 calls vs 2;"));
+        assert!(report.ends_with("(1 of the calls reported no token usage)"));
+    }
+}
@@ -0,0 +1,493 @@
+//! Issue-to-build trigger: open GitHub issues carrying the configured label
+//! (`[factory.issues]`) become queued factory builds.
+//!
+//! The watcher polls the listed repositories with the connected GitHub
+//! account. Each new labeled issue is queued through [`BuildQueue`] with the
+//! issue as the requirement and instructions to push the result to
+//! `zerobuild/issue-<number>`. One status comment on the issue follows the
+//! job through the queue and its stages; when the build succeeds the watcher
+//! opens a pull request from that branch that closes the issue and links it
+//! in the comment. Each issue is built once. Repositories the GitHub tools
+//! may not touch ([`RepoAccess`]) are neither polled nor written to.
+
+use super::queue::BuildQueue;
+use crate::config::FactoryIssuesConfig;
+use crate::store::{self, issues, jobs};
+use crate::tools::github_access::RepoAccess;
+use serde_json::json;
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+const GITHUB_API_BASE: &str = "https://api.github.com";
+
+/// Branch builds of issue `n` are pushed to: `zerobuild/issue-n`.
+pub const BRANCH_PREFIX: &str = "zerobuild/issue-";
+
+/// Issues picked up per repository and poll.
+const MAX_ISSUES_PER_POLL: usize = 20;
+
+/// Build output quoted in the final comment.
+const MAX_OUTPUT_CHARS: usize = 3000;
+
+/// Set once the process-wide watcher runs.
+static WATCHER: OnceLock<()> = OnceLock::new();
+
+/// Polls repositories for labeled issues and reports on their builds.
+pub struct IssueWatcher {
+    db_path: PathBuf,
+    config: FactoryIssuesConfig,
+    queue: Arc<BuildQueue>,
+    access: RepoAccess,
+    client: reqwest::Client,
+}
+
+/// Branch a build of issue `number` pushes to.
+pub fn issue_branch(number: u64) -> String {
+    format!("{BRANCH_PREFIX}{number}")
+}
+
+/// Build requirement for an issue.
+fn issue_idea(repo: &str, number: u64, title: &str, body: &str) -> String {
+    let (owner, name) = repo.split_once('/').unwrap_or((repo, repo));
+    let branch = issue_branch(number);
+    format!(
+        "{title}\n\n{body}\n\n---\n\
+         Requested in GitHub issue {repo}#{number}. The repository already exists: load its \
+         code with github_read_repo and change it rather than starting over. Deploy with \
+         github_push using owner \"{owner}\", project_name \"{name}\" and branch \"{branch}\"; \
+         never push to the default branch. A pull request that closes the issue is opened \
+         from that branch after the build.",
+        body = body.trim()
+    )
+}
+
+/// The status comment for a job: `(key, markdown)`. The key changes
+/// exactly when the comment should be rewritten.
+fn status_comment(
+    job: &jobs::BuildJob,
+    number: u64,
+    position: Option<usize>,
+    stage: Option<&str>,
+    pr_url: Option<&str>,
+) -> (String, String) {
+    let header = format!("🏗️ **ZeroBuild build #{}**", job.id);
+    match job.status {
+        jobs::JobStatus::Queued => (
+            "queued".into(),
+            match position {
+                Some(position) => format!("{header} is queued (position {position})."),
+                None => format!("{header} is queued."),
+            },
+        ),
+        jobs::JobStatus::Running => {
+            let stage = stage.unwrap_or("starting");
+            (
+                format!("running:{stage}"),
+                format!("{header} is running.\n\nCurrent stage: {stage}"),
+            )
+        }
+        jobs::JobStatus::Succeeded => {
+            let mut text = match pr_url {
+                Some(url) => format!("✅ {header} finished. Pull request: {url}"),
+                None => format!(
+                    "✅ {header} finished, but nothing was pushed to `{}`, so no pull request \
+                     was opened.",
+                    issue_branch(number)
+                ),
+            };
+            if let Some(output) = job.output.as_deref().filter(|o| !o.trim().is_empty()) {
+                let mut output: String = output.trim().chars().take(MAX_OUTPUT_CHARS).collect();
+                if output.len() < job.output.as_deref().unwrap_or_default().trim().len() {
+                    output.push('…');
+                }
+                let _ = write!(
+                    text,
+                    "\n\n<details><summary>Build summary</summary>\n\n{output}\n\n</details>"
+                );
+            }
+            ("succeeded".into(), text)
+        }
+        jobs::JobStatus::Failed => (
+            "failed".into(),
+            format!(
+                "❌ {header} failed: {}",
+                job.error.as_deref().unwrap_or("unknown error")
+            ),
+        ),
+        jobs::JobStatus::Cancelled => ("cancelled".into(), format!("{header} was cancelled.")),
+    }
+}
+
+impl IssueWatcher {
+    /// Start the process-wide watcher polling every `poll_secs`. Later calls
+    /// do nothing.
+    pub fn start_global(
+        db_path: PathBuf,
+        config: FactoryIssuesConfig,
+        queue: Arc<BuildQueue>,
+        access: RepoAccess,
+    ) -> anyhow::Result<()> {
+        if WATCHER.get().is_some() {
+            return Ok(());
+        }
+        let handle = tokio::runtime::Handle::try_current()
+            .map_err(|_| anyhow::anyhow!("the issue watcher needs a Tokio runtime"))?;
+        if WATCHER.set(()).is_err() {
+            return Ok(());
+        }
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .user_agent("ZeroBuild/0.1")
+            .build()?;
+        let watcher = Self {
+            db_path,
+            config,
+            queue,
+            access,
+            client,
+        };
+        tracing::info!(repos = ?watcher.config.repos, label = %watcher.config.label, "Issue watcher started");
+        handle.spawn(watcher.poll_loop());
+        Ok(())
+    }
+
+    async fn poll_loop(self) {
+        let mut ticker = tokio::time::interval(Duration::from_secs(self.config.poll_secs.max(10)));
+        loop {
+            ticker.tick().await;
+            if let Err(e) = self.poll().await {
+                tracing::warn!("Issue watcher poll failed: {e:#}");
+            }
+        }
+    }
+
+    async fn poll(&self) -> anyhow::Result<()> {
+        let Some(token) = store::tokens::load_github_token(&store::init_db(&self.db_path)?)? else {
+            tracing::debug!("Issue watcher idle: GitHub is not connected");
+            return Ok(());
+        };
+        for repo in &self.config.repos {
+            if let Err(e) = self.pick_up(&token.token, repo).await {
+                tracing::warn!(repo, "Failed to pick up labeled issues: {e:#}");
+            }
+        }
+        self.report(&token.token).await
+    }
+
+    /// Queue builds for labeled issues not seen before.
+    async fn pick_up(&self, token: &str, repo: &str) -> anyhow::Result<()> {
+        self.check_repo(repo)?;
+        let url = format!("{GITHUB_API_BASE}/repos/{repo}/issues");
+        let resp = self
+            .request(reqwest::Method::GET, &url, token)
+            .query(&[
+                ("labels", self.config.label.as_str()),
+                ("state", "open"),
+                ("sort", "created"),
+                ("direction", "asc"),
+                ("per_page", "50"),
+            ])
+            .send()
+            .await?
+            .error_for_status()?;
+        let listed: Vec<serde_json::Value> = resp.json().await?;
+
+        let mut picked = 0;
+        for issue in listed.iter().filter(|i| i.get("pull_request").is_none()) {
+            let Some(number) = issue["number"].as_u64() else {
+                continue;
+            };
+            let conn = store::init_db(&self.db_path)?;
+            if issues::get(&conn, repo, number)?.is_some() {
+                continue;
+            }
+            if picked == MAX_ISSUES_PER_POLL {
+                break;
+            }
+            picked += 1;
+
+            let author = issue["user"]["login"].as_str().unwrap_or("unknown");
+            let idea = issue_idea(
+                repo,
+                number,
+                issue["title"].as_str().unwrap_or_default(),
+                issue["body"].as_str().unwrap_or_default(),
+            );
+            let user = format!("github:{author}");
+            let job_id = jobs::enqueue_job(
+                &conn,
+                &jobs::NewJob {
+                    user: &user,
+                    idea: &idea,
+                    project: repo.split_once('/').map(|(_, name)| name),
+                    ..jobs::NewJob::default()
+                },
+            )?;
+            issues::track(&conn, repo, number, job_id)?;
+            self.queue.notify();
+            tracing::info!(repo, number, job_id, "Queued build for labeled issue");
+        }
+        Ok(())
+    }
+
+    /// Bring the status comments of active builds up to date.
+    async fn report(&self, token: &str) -> anyhow::Result<()> {
+        let conn = store::init_db(&self.db_path)?;
+        for build in issues::active(&conn)? {
+            let Some(job) = jobs::get_job(&conn, build.job_id)? else {
+                issues::update(&conn, &build.repo, build.number, build.comment_id, "", true)?;
+                continue;
+            };
+            let position = jobs::queue_position(&conn, job.id)?;
+            let stage = jobs::progress(&conn, job.id)?;
+            let done = matches!(
+                job.status,
+                jobs::JobStatus::Succeeded | jobs::JobStatus::Failed | jobs::JobStatus::Cancelled
+            );
+            let pr_url = if job.status == jobs::JobStatus::Succeeded {
+                match self
+                    .open_pull_request(token, &build.repo, build.number, job.id)
+                    .await
+                {
+                    Ok(url) => url,
+                    Err(e) => {
+                        tracing::warn!(repo = %build.repo, number = build.number, "Failed to open pull request: {e:#}");
+                        continue;
+                    }
+                }
+            } else {
+                None
+            };
+            let (key, body) = status_comment(
+                &job,
+                build.number,
+                position,
+                stage.as_deref(),
+                pr_url.as_deref(),
+            );
+            if key == build.reported {
+                continue;
+            }
+            match self
+                .write_comment(token, &build.repo, build.number, build.comment_id, &body)
+                .await
+            {
+                Ok(comment_id) => {
+                    issues::update(
+                        &conn,
+                        &build.repo,
+                        build.number,
+                        Some(comment_id),
+                        &key,
+                        done,
+                    )?;
+                }
+                Err(e) => {
+                    tracing::warn!(repo = %build.repo, number = build.number, "Failed to update issue comment: {e:#}");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Post the status comment, or edit it once posted. Returns its id.
+    async fn write_comment(
+        &self,
+        token: &str,
+        repo: &str,
+        number: u64,
+        comment_id: Option<u64>,
+        body: &str,
+    ) -> anyhow::Result<u64> {
+        self.check_repo(repo)?;
+        let request = match comment_id {
+            Some(id) => self.request(
+                reqwest::Method::PATCH,
+                &format!("{GITHUB_API_BASE}/repos/{repo}/issues/comments/{id}"),
+                token,
+            ),
+            None => self.request(
+                reqwest::Method::POST,
+                &format!("{GITHUB_API_BASE}/repos/{repo}/issues/{number}/comments"),
+                token,
+            ),
+        };
+        let resp = request
+            .json(&json!({ "body": body }))
+            .send()
+            .await?
+            .error_for_status()?;
+        let comment: serde_json::Value = resp.json().await?;
+        comment["id"]
+            .as_u64()
+            .ok_or_else(|| anyhow::anyhow!("comment response without id"))
+    }
+
+    /// Open (or find) the pull request from the issue branch that closes
+    /// the issue. `Ok(None)` when the build pushed no branch.
+    async fn open_pull_request(
+        &self,
+        token: &str,
+        repo: &str,
+        number: u64,
+        job_id: i64,
+    ) -> anyhow::Result<Option<String>> {
+        self.check_repo(repo)?;
+        let branch = issue_branch(number);
+        let ref_url = format!("{GITHUB_API_BASE}/repos/{repo}/git/ref/heads/{branch}");
+        let resp = self
+            .request(reqwest::Method::GET, &ref_url, token)
+            .send()
+            .await?;
+        if resp.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        resp.error_for_status()?;
+
+        let owner = repo.split_once('/').map_or(repo, |(owner, _)| owner);
+        let pulls_url = format!("{GITHUB_API_BASE}/repos/{repo}/pulls");
+        let existing: Vec<serde_json::Value> = self
+            .request(reqwest::Method::GET, &pulls_url, token)
+            .query(&[
+                ("head", format!("{owner}:{branch}")),
+                ("state", "all".into()),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        if let Some(url) = existing.first().and_then(|pr| pr["html_url"].as_str()) {
+            return Ok(Some(url.to_string()));
+        }
+
+        let repo_info: serde_json::Value = self
+            .request(
+                reqwest::Method::GET,
+                &format!("{GITHUB_API_BASE}/repos/{repo}"),
+                token,
+            )
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let issue: serde_json::Value = self
+            .request(
+                reqwest::Method::GET,
+                &format!("{GITHUB_API_BASE}/repos/{repo}/issues/{number}"),
+                token,
+            )
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let title = issue["title"].as_str().unwrap_or("ZeroBuild build");
+        let created: serde_json::Value = self
+            .request(reqwest::Method::POST, &pulls_url, token)
+            .json(&json!({
+                "title": title,
+                "head": branch,
+                "base": repo_info["default_branch"].as_str().unwrap_or("main"),
+                "body": format!(
+                    "Closes #{number}\n\nBuilt by ZeroBuild from the issue description (build job #{job_id})."
+                ),
+            }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(created["html_url"].as_str().map(str::to_string))
+    }
+
+    /// Refuse `owner/name` unless [`RepoAccess`] admits it.
+    fn check_repo(&self, repo: &str) -> anyhow::Result<()> {
+        let (owner, name) = repo
+            .split_once('/')
+            .ok_or_else(|| anyhow::anyhow!("invalid repository '{repo}'"))?;
+        self.access
+            .check(owner, Some(name))
+            .map_err(anyhow::Error::msg)
+    }
+
+    fn request(&self, method: reqwest::Method, url: &str, token: &str) -> reqwest::RequestBuilder {
+        self.client
+            .request(method, url)
+            .header("Authorization", format!("Bearer {token}"))
+            .header("Accept", "application/vnd.github+json")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(status: jobs::JobStatus) -> jobs::BuildJob {
+        jobs::BuildJob {
+            id: 4,
+            user: "github:alice".into(),
+            idea: "todo app".into(),
+            project_type: None,
+            project: Some("shop".into()),
+            priority: 0,
+            status,
+            output: None,
+            error: None,
+            created_at: String::new(),
+            started_at: None,
+            finished_at: None,
+        }
+    }
+
+    #[test]
+    fn idea_points_the_deploy_at_the_issue_branch() {
+        let idea = issue_idea("acme/shop", 12, "Add a cart", "Users need a cart.\n");
+        assert!(idea.starts_with("Add a cart\n\nUsers need a cart.\n\n---"));
+        assert!(idea.contains("acme/shop#12"));
+        assert!(idea
+            .contains("owner \"acme\", project_name \"shop\" and branch \"zerobuild/issue-12\""));
+    }
+
+    #[test]
+    fn status_comment_follows_the_job() {
+        let (key, body) = status_comment(&job(jobs::JobStatus::Queued), 12, Some(2), None, None);
+        assert_eq!(key, "queued");
+        assert!(body.contains("build #4** is queued (position 2)"));
+
+        let running = job(jobs::JobStatus::Running);
+        let (first, _) = status_comment(
+            &running,
+            12,
+            None,
+            Some("Business Analyst creating PRD"),
+            None,
+        );
+        let (second, body) = status_comment(&running, 12, None, Some("Deploying to GitHub"), None);
+        assert_ne!(first, second);
+        assert!(body.contains("Current stage: Deploying to GitHub"));
+
+        let mut done = job(jobs::JobStatus::Succeeded);
+        done.output = Some("Deployed 12 files".into());
+        let (_, body) = status_comment(
+            &done,
+            12,
+            None,
+            None,
+            Some("https://github.com/acme/shop/pull/5"),
+        );
+        assert!(body.contains("Pull request: https://github.com/acme/shop/pull/5"));
+        assert!(body.contains("Deployed 12 files"));
+        let (_, body) = status_comment(&done, 12, None, None, None);
+        assert!(body.contains("nothing was pushed to `zerobuild/issue-12`"));
+
+        let mut failed = job(jobs::JobStatus::Failed);
+        failed.error = Some("tests failed".into());
+        assert!(status_comment(&failed, 12, None, None, None)
+            .1
+            .contains("failed: tests failed"));
+    }
+}
@@ -0,0 +1,671 @@
+//! Dependency refresh of deployed projects (`[zerobuild.maintenance]`).
+//!
+//! ZeroBuild keeps maintaining what it shipped. Every `interval_hours` after
+//! a project's last deploy or refresh, the daemon restores the project's
+//! latest snapshot version into a sandbox of its own, updates the
+//! dependencies (`[commands] update` of `.zerobuild.toml`, else the package
+//! manager's update for `package.json` and `cargo update` for `Cargo.toml`)
+//! and runs the tests. The changed manifests and lockfiles are pushed to the
+//! configured branch of the deployed repository with a pull request listing
+//! the bumps; one pull request stays open per repository and later refreshes
+//! update it. The user's own sandbox and snapshot are never touched.
+//!
+//! Deployed projects are the ones `github_push` recorded in
+//! [`store::deployments`].
+
+use crate::artifacts::Artifacts;
+use crate::config::ZerobuildConfig;
+use crate::sandbox::package_manager::detect_project_package_manager;
+use crate::sandbox::project_config::{ProjectConfig, PROJECT_DIR};
+use crate::sandbox::SandboxClient;
+use crate::store::{self, deployments::Deployment};
+use crate::tools::github_access::RepoAccess;
+use crate::tools::sandbox::SandboxRestoreSnapshotTool;
+use crate::tools::Tool;
+use anyhow::{bail, Context};
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Write;
+use std::sync::Arc;
+use std::time::Duration;
+
+const GITHUB_API_BASE: &str = "https://api.github.com";
+
+/// How often the worker looks for projects that are due.
+const CHECK_INTERVAL_SECS: u64 = 600;
+
+/// Output lines of a failed command kept in the result.
+const OUTPUT_TAIL_LINES: usize = 20;
+
+/// Bumps listed in the pull request.
+const MAX_BUMPS_LISTED: usize = 50;
+
+const PULL_REQUEST_TITLE: &str = "Refresh dependencies";
+
+/// A dependency whose locked version changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bump {
+    pub name: String,
+    /// `None` for a new dependency.
+    pub from: Option<String>,
+    /// `None` for a removed dependency.
+    pub to: Option<String>,
+}
+
+/// How the tests went after the update.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Tests {
+    NotRun,
+    Passed(String),
+    Failed { command: String, output: String },
+}
+
+/// What updating the dependencies in the sandbox produced.
+#[derive(Debug)]
+struct Update {
+    command: String,
+    /// Changed files by path relative to the project.
+    changed: BTreeMap<String, String>,
+    bumps: Vec<Bump>,
+    tests: Tests,
+}
+
+/// Whether `deployment` was last deployed or refreshed `interval_hours` ago.
+fn is_due(deployment: &Deployment, now: DateTime<Utc>, interval_hours: u64) -> bool {
+    let last = std::iter::once(deployment.deployed_at.as_str())
+        .chain(deployment.refreshed_at.as_deref())
+        .filter_map(|at| DateTime::parse_from_rfc3339(at).ok())
+        .map(|at| at.with_timezone(&Utc))
+        .max();
+    let interval = chrono::Duration::hours(i64::try_from(interval_hours).unwrap_or(i64::MAX));
+    last.is_none_or(|last| now - last >= interval)
+}
+
+/// The last `OUTPUT_TAIL_LINES` lines of a command's stdout and stderr.
+fn output_tail(output: &crate::sandbox::CommandOutput) -> String {
+    let combined = format!("{}\n{}", output.stdout, output.stderr);
+    let lines: Vec<&str> = combined.trim().lines().collect();
+    lines[lines.len().saturating_sub(OUTPUT_TAIL_LINES)..].join("\n")
+}
+
+/// Files of `after` that are new or differ from `before`.
+fn changed_files(
+    before: &HashMap<String, String>,
+    after: HashMap<String, String>,
+) -> BTreeMap<String, String> {
+    after
+        .into_iter()
+        .filter(|(path, content)| before.get(path) != Some(content))
+        .collect()
+}
+
+/// Locked versions by package name of a `package-lock.json` or
+/// `Cargo.lock`; `None` for other files.
+fn locked_versions(path: &str, content: &str) -> Option<BTreeMap<String, String>> {
+    match path.rsplit('/').next().unwrap_or(path) {
+        "package-lock.json" => {
+            let lock: serde_json::Value = serde_json::from_str(content).ok()?;
+            Some(
+                lock["packages"]
+                    .as_object()?
+                    .iter()
+                    .filter_map(|(key, package)| {
+                        let name = key.strip_prefix("node_modules/")?;
+                        if name.contains("/node_modules/") {
+                            return None;
+                        }
+                        Some((name.to_string(), package["version"].as_str()?.to_string()))
+                    })
+                    .collect(),
+            )
+        }
+        "Cargo.lock" => {
+            let lock: toml::Value = toml::from_str(content).ok()?;
+            let mut versions: BTreeMap<String, String> = BTreeMap::new();
+            for package in lock.get("package")?.as_array()? {
+                let (Some(name), Some(version)) = (
+                    package.get("name").and_then(toml::Value::as_str),
+                    package.get("version").and_then(toml::Value::as_str),
+                ) else {
+                    continue;
+                };
+                versions
+                    .entry(name.to_string())
+                    .and_modify(|listed| {
+                        listed.push_str(", ");
+                        listed.push_str(version);
+                    })
+                    .or_insert_with(|| version.to_string());
+            }
+            Some(versions)
+        }
+        _ => None,
+    }
+}
+
+/// Dependencies whose locked version differs between two versions of the
+/// lockfile at `path`.
+pub fn bumps(path: &str, before: Option<&str>, after: &str) -> Vec<Bump> {
+    let Some(after) = locked_versions(path, after) else {
+        return Vec::new();
+    };
+    let before = before
+        .and_then(|before| locked_versions(path, before))
+        .unwrap_or_default();
+    let names: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+    names
+        .into_iter()
+        .filter(|name| before.get(*name) != after.get(*name))
+        .map(|name| Bump {
+            name: name.clone(),
+            from: before.get(name).cloned(),
+            to: after.get(name).cloned(),
+        })
+        .collect()
+}
+
+/// Pull request description of `update` for `project`.
+fn pull_request_body(project: &str, update: &Update) -> String {
+    let mut body = format!(
+        "ZeroBuild refreshed the dependencies of `{project}` with `{}`.\n",
+        update.command
+    );
+    if !update.bumps.is_empty() {
+        body.push_str("\n| Dependency | From | To |\n|---|---|---|\n");
+        for bump in update.bumps.iter().take(MAX_BUMPS_LISTED) {
+            let _ = writeln!(
+                body,
+                "| `{}` | {} | {} |",
+                bump.name,
+                bump.from.as_deref().unwrap_or("—"),
+                bump.to.as_deref().unwrap_or("removed")
+            );
+        }
+        if update.bumps.len() > MAX_BUMPS_LISTED {
+            let _ = writeln!(
+                body,
+                "\n…and {} more.",
+                update.bumps.len() - MAX_BUMPS_LISTED
+            );
+        }
+    }
+    let files: Vec<String> = update
+        .changed
+        .keys()
+        .map(|path| format!("`{path}`"))
+        .collect();
+    let _ = write!(body, "\nChanged files: {}\n\n", files.join(", "));
+    match &update.tests {
+        Tests::NotRun => body.push_str("Tests: not run, the project has no test command."),
+        Tests::Passed(command) => {
+            let _ = write!(body, "Tests: ✅ `{command}` passed.");
+        }
+        Tests::Failed { command, output } => {
+            let _ = write!(
+                body,
+                "Tests: ❌ `{command}` failed after the update:\n\n```\n{output}\n```"
+            );
+        }
+    }
+    body
+}
+
+/// Refreshes the dependencies of deployed projects.
+pub struct DependencyRefresher {
+    config: ZerobuildConfig,
+    artifacts: Arc<Artifacts>,
+    http: reqwest::Client,
+}
+
+impl DependencyRefresher {
+    pub fn new(config: ZerobuildConfig) -> anyhow::Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .user_agent("ZeroBuild/0.1")
+            .build()?;
+        Ok(Self {
+            artifacts: Arc::new(Artifacts::from_config(&config)),
+            config,
+            http,
+        })
+    }
+
+    /// Whether `[zerobuild.maintenance] repos` selects `repo`.
+    fn selected(&self, repo: &str) -> bool {
+        let repos = &self.config.maintenance.repos;
+        repos.is_empty() || repos.iter().any(|r| r.trim().eq_ignore_ascii_case(repo))
+    }
+
+    /// Refresh every selected deployed project that is due.
+    pub async fn refresh_due(&self) -> anyhow::Result<()> {
+        let conn = store::init_db(std::path::Path::new(&self.config.db_path))?;
+        let Some(token) = store::tokens::load_github_token(&conn)? else {
+            tracing::debug!("Dependency refresh idle: GitHub is not connected");
+            return Ok(());
+        };
+        let now = Utc::now();
+        for deployment in store::deployments::list(&conn)? {
+            if !self.selected(&deployment.repo)
+                || !is_due(&deployment, now, self.config.maintenance.interval_hours)
+            {
+                continue;
+            }
+            let result = match self.refresh(&token.token, &deployment).await {
+                Ok(result) => result,
+                Err(e) => format!("Failed: {e:#}"),
+            };
+            tracing::info!(repo = %deployment.repo, %result, "Dependency refresh finished");
+            store::deployments::mark_refreshed(&conn, &deployment.repo, &result)?;
+        }
+        Ok(())
+    }
+
+    /// Refresh one project; returns what happened.
+    async fn refresh(&self, token: &str, deployment: &Deployment) -> anyhow::Result<String> {
+        let Some((owner, name)) = deployment.repo.split_once('/') else {
+            bail!("invalid repository '{}'", deployment.repo);
+        };
+        RepoAccess::from_config(&self.config.repos)
+            .check(owner, Some(name))
+            .map_err(anyhow::Error::msg)?;
+
+        let client = crate::tools::configured_sandbox_client(&self.config);
+        let update = self.update(client.clone(), deployment).await;
+        if let Err(e) = client.kill_sandbox().await {
+            tracing::warn!("Failed to remove the refresh sandbox: {e:#}");
+        }
+        let update = update?;
+
+        if update.changed.is_empty() {
+            return Ok("Dependencies are up to date".to_string());
+        }
+        if let Tests::Failed { command, .. } = &update.tests {
+            if self.config.maintenance.require_passing_tests {
+                return Ok(format!(
+                    "{} dependency bumps found, but `{command}` failed after the update; no \
+                     pull request opened",
+                    update.bumps.len()
+                ));
+            }
+        }
+        let url = self.push(token, deployment, &update).await?;
+        Ok(format!(
+            "Pull request with {} dependency bumps: {url}",
+            update.bumps.len()
+        ))
+    }
+
+    /// Restore the project into a fresh sandbox, update its dependencies and
+    /// run its tests.
+    async fn update(
+        &self,
+        client: Arc<dyn SandboxClient>,
+        deployment: &Deployment,
+    ) -> anyhow::Result<Update> {
+        let restored = SandboxRestoreSnapshotTool::new(client.clone(), &self.config.db_path)
+            .with_artifacts(self.artifacts.clone())
+            .with_limits(&self.config.sandbox)
+            .execute(json!({"project": deployment.project, "fresh": true}))
+            .await?;
+        if !restored.success {
+            bail!(
+                "restoring {} failed: {}",
+                deployment.project,
+                restored.error.unwrap_or_default()
+            );
+        }
+
+        let client = client.as_ref();
+        let project = ProjectConfig::load(client).await?.unwrap_or_default();
+        let workdir = project.workdir();
+        let timeout_ms = self
+            .config
+            .sandbox
+            .max_command_timeout_secs
+            .saturating_mul(1000);
+        let before = client.collect_snapshot_files(PROJECT_DIR).await?;
+        let command = match &project.commands.update {
+            Some(command) => command.clone(),
+            None if client
+                .read_file(&format!("{workdir}/package.json"))
+                .await
+                .is_ok() =>
+            {
+                detect_project_package_manager(client, &workdir)
+                    .await
+                    .manager
+                    .update_cmd()
+                    .to_string()
+            }
+            None if client
+                .read_file(&format!("{workdir}/Cargo.toml"))
+                .await
+                .is_ok() =>
+            {
+                "cargo update".to_string()
+            }
+            None => bail!("{workdir} has no package.json, Cargo.toml or update command"),
+        };
+        let output = client.run_command(&command, &workdir, timeout_ms).await?;
+        if output.exit_code != 0 {
+            bail!("`{command}` failed:\n{}", output_tail(&output));
+        }
+        // Collected before the tests, which may leave files behind
+        let changed = changed_files(&before, client.collect_snapshot_files(PROJECT_DIR).await?);
+        let bumps = changed
+            .iter()
+            .flat_map(|(path, after)| bumps(path, before.get(path).map(String::as_str), after))
+            .collect();
+
+        let test = match crate::tools::ship_it::test_command(client, &project).await {
+            Some(test) => Some(test),
+            None => client
+                .read_file(&format!("{workdir}/Cargo.toml"))
+                .await
+                .is_ok()
+                .then(|| "cargo test".to_string()),
+        };
+        let tests = match test {
+            _ if changed.is_empty() => Tests::NotRun,
+            None => Tests::NotRun,
+            Some(test) => {
+                let output = client.run_command(&test, &workdir, timeout_ms).await?;
+                if output.exit_code == 0 {
+                    Tests::Passed(test)
+                } else {
+                    Tests::Failed {
+                        command: test,
+                        output: output_tail(&output),
+                    }
+                }
+            }
+        };
+        Ok(Update {
+            command,
+            changed,
+            bumps,
+            tests,
+        })
+    }
+
+    /// Commit the changed files on top of the deployed branch, force them to
+    /// the refresh branch and open (or reuse) its pull request. Returns the
+    /// pull request's URL.
+    async fn push(
+        &self,
+        token: &str,
+        deployment: &Deployment,
+        update: &Update,
+    ) -> anyhow::Result<String> {
+        let repo = &deployment.repo;
+        let branch = self.config.maintenance.branch.trim();
+        let repo_url = format!("{GITHUB_API_BASE}/repos/{repo}");
+
+        let base: serde_json::Value = self
+            .get(
+                token,
+                &format!("{repo_url}/git/ref/heads/{}", deployment.branch),
+            )
+            .await?;
+        let base_sha = base["object"]["sha"]
+            .as_str()
+            .context("deployed branch has no commit")?;
+        let base_commit: serde_json::Value = self
+            .get(token, &format!("{repo_url}/git/commits/{base_sha}"))
+            .await?;
+
+        let prefix = deployment
+            .path
+            .as_deref()
+            .map(|path| format!("{}/", path.trim_matches('/')))
+            .unwrap_or_default();
+        let entries: Vec<serde_json::Value> = update
+            .changed
+            .iter()
+            .map(|(path, content)| {
+                json!({
+                    "path": format!("{prefix}{path}"),
+                    "mode": "100644",
+                    "type": "blob",
+                    "content": content,
+                })
+            })
+            .collect();
+        let tree = self
+            .post(
+                token,
+                &format!("{repo_url}/git/trees"),
+                &json!({"base_tree": base_commit["tree"]["sha"], "tree": entries}),
+            )
+            .await?;
+        let commit = self
+            .post(
+                token,
+                &format!("{repo_url}/git/commits"),
+                &json!({
+                    "message": format!("Refresh dependencies with `{}`", update.command),
+                    "tree": tree["sha"],
+                    "parents": [base_sha],
+                }),
+            )
+            .await?;
+        let commit_sha = commit["sha"].as_str().context("commit has no sha")?;
+
+        let moved = self
+            .request(
+                reqwest::Method::PATCH,
+                &format!("{repo_url}/git/refs/heads/{branch}"),
+                token,
+            )
+            .json(&json!({"sha": commit_sha, "force": true}))
+            .send()
+            .await?;
+        if !moved.status().is_success() {
+            self.post(
+                token,
+                &format!("{repo_url}/git/refs"),
+                &json!({"ref": format!("refs/heads/{branch}"), "sha": commit_sha}),
+            )
+            .await?;
+        }
+
+        let body = pull_request_body(&deployment.project, update);
+        let owner = repo
+            .split_once('/')
+            .map_or(repo.as_str(), |(owner, _)| owner);
+        let open: Vec<serde_json::Value> = self
+            .request(reqwest::Method::GET, &format!("{repo_url}/pulls"), token)
+            .query(&[
+                ("head", format!("{owner}:{branch}")),
+                ("state", "open".into()),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        if let Some(pull) = open.first() {
+            let number = pull["number"].as_u64().unwrap_or_default();
+            self.request(
+                reqwest::Method::PATCH,
+                &format!("{repo_url}/pulls/{number}"),
+                token,
+            )
+            .json(&json!({"body": body}))
+            .send()
+            .await?
+            .error_for_status()?;
+            return Ok(pull["html_url"].as_str().unwrap_or_default().to_string());
+        }
+        let created = self
+            .post(
+                token,
+                &format!("{repo_url}/pulls"),
+                &json!({
+                    "title": PULL_REQUEST_TITLE,
+                    "head": branch,
+                    "base": deployment.branch,
+                    "body": body,
+                }),
+            )
+            .await?;
+        Ok(created["html_url"].as_str().unwrap_or_default().to_string())
+    }
+
+    async fn get(&self, token: &str, url: &str) -> anyhow::Result<serde_json::Value> {
+        Ok(self
+            .request(reqwest::Method::GET, url, token)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+
+    async fn post(
+        &self,
+        token: &str,
+        url: &str,
+        body: &serde_json::Value,
+    ) -> anyhow::Result<serde_json::Value> {
+        Ok(self
+            .request(reqwest::Method::POST, url, token)
+            .json(body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+
+    fn request(&self, method: reqwest::Method, url: &str, token: &str) -> reqwest::RequestBuilder {
+        self.http
+            .request(method, url)
+            .header("Authorization", format!("Bearer {token}"))
+            .header("Accept", "application/vnd.github+json")
+    }
+}
+
+/// Look for due projects every few minutes and refresh them.
+pub async fn run_refresh_worker(config: ZerobuildConfig) -> anyhow::Result<()> {
+    let refresher = DependencyRefresher::new(config)?;
+    let mut interval = tokio::time::interval(Duration::from_secs(CHECK_INTERVAL_SECS));
+    loop {
+        interval.tick().await;
+        if let Err(e) = refresher.refresh_due().await {
+            tracing::warn!("Dependency refresh failed: {e:#}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deployment(deployed_at: &str, refreshed_at: Option<&str>) -> Deployment {
+        Deployment {
+            repo: "acme/shop".into(),
+            project: "shop".into(),
+            branch: "main".into(),
+            deployed_at: deployed_at.into(),
+            refreshed_at: refreshed_at.map(str::to_string),
+            ..Deployment::default()
+        }
+    }
+
+    #[test]
+    fn projects_are_due_an_interval_after_their_last_deploy_or_refresh() {
+        let now = DateTime::parse_from_rfc3339("2026-03-10T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert!(is_due(&deployment("2026-03-09T11:00:00Z", None), now, 24));
+        assert!(!is_due(&deployment("2026-03-10T01:00:00Z", None), now, 24));
+        assert!(!is_due(
+            &deployment("2026-03-01T00:00:00Z", Some("2026-03-10T02:00:00Z")),
+            now,
+            24
+        ));
+        assert!(is_due(&deployment("not a date", None), now, 24));
+    }
+
+    #[test]
+    fn lockfile_bumps_list_changed_top_level_packages() {
+        let before = r#"{"packages": {
+            "": {"name": "shop"},
+            "node_modules/react": {"version": "18.2.0"},
+            "node_modules/left-pad": {"version": "1.3.0"},
+            "node_modules/a/node_modules/react": {"version": "17.0.0"}
+        }}"#;
+        let after = r#"{"packages": {
+            "": {"name": "shop"},
+            "node_modules/react": {"version": "18.3.1"},
+            "node_modules/zod": {"version": "3.23.8"},
+            "node_modules/a/node_modules/react": {"version": "17.0.2"}
+        }}"#;
+        assert_eq!(
+            bumps("package-lock.json", Some(before), after),
+            [
+                Bump {
+                    name: "left-pad".into(),
+                    from: Some("1.3.0".into()),
+                    to: None,
+                },
+                Bump {
+                    name: "react".into(),
+                    from: Some("18.2.0".into()),
+                    to: Some("18.3.1".into()),
+                },
+                Bump {
+                    name: "zod".into(),
+                    from: None,
+                    to: Some("3.23.8".into()),
+                },
+            ]
+        );
+        assert!(bumps("package.json", Some(before), after).is_empty());
+
+        let cargo = |serde: &str| {
+            format!(
+                "version = 3\n\n[[package]]\nname = \"serde\"\nversion = \"{serde}\"\n\n\
+                 [[package]]\nname = \"shop\"\nversion = \"0.1.0\"\n"
+            )
+        };
+        assert_eq!(
+            bumps("api/Cargo.lock", Some(&cargo("1.0.200")), &cargo("1.0.210")),
+            [Bump {
+                name: "serde".into(),
+                from: Some("1.0.200".into()),
+                to: Some("1.0.210".into()),
+            }]
+        );
+    }
+
+    #[test]
+    fn pull_request_lists_bumps_files_and_tests() {
+        let before = HashMap::from([
+            ("package.json".to_string(), "{}".to_string()),
+            ("src/index.js".to_string(), "run()".to_string()),
+        ]);
+        let after = HashMap::from([
+            ("package.json".to_string(), "{\"v\": 2}".to_string()),
+            ("src/index.js".to_string(), "run()".to_string()),
+        ]);
+        let update = Update {
+            command: "npm update".into(),
+            changed: changed_files(&before, after),
+            bumps: vec![Bump {
+                name: "react".into(),
+                from: Some("18.2.0".into()),
+                to: Some("18.3.1".into()),
+            }],
+            tests: Tests::Passed("npm test".into()),
+        };
+        assert_eq!(update.changed.keys().collect::<Vec<_>>(), ["package.json"]);
+        let body = pull_request_body("shop", &update);
+        assert!(body.contains("`shop` with `npm update`"));
+        assert!(body.contains("| `react` | 18.2.0 | 18.3.1 |"));
+        assert!(body.contains("Changed files: `package.json`"));
+        assert!(body.contains("✅ `npm test` passed"));
+    }
+}
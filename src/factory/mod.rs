@@ -13,6 +13,17 @@
 //! - [`workflow`]: Workflow state machine with phased execution
 //! - [`orchestrator_tool`]: `factory_build` tool implementing the `Tool` trait
 //! - [`progress`]: Real-time progress streaming system
+//! - [`shared_memory`]: Permissioned memory namespaces shared across agents and builds
+//! - [`queue`]: Persisted build queue processed by a worker pool (`build_queue` tool)
+//! - [`issues`]: Builds queued from labeled GitHub issues, reported back on the issue
+//! - [`maintenance`]: Dependency refresh pull requests for deployed projects
+//! - [`review`]: Checklist reviews with inline comments for new pull requests
+//! - [`triage`]: Classification, labels and follow-up questions for new issues
+//! - [`estimate`]: Token, time and cost estimate of a build, compared with its actual usage
+//! - [`security`]: OWASP-style checklist for the security review stage
+//! - [`accessibility`]: axe-core audit summaries for the accessibility stage
+//! - [`a2a`]: Sub-tasks delegated to and from other ZeroBuild instances
+//! - [`distributed`]: Completion stages run by workers on a shared coordination bus
 //!
 //! ## Workspace Isolation Modules (Phase B)
 //! - [`workspace`]: Per-agent workspace management with isolation
@@ -29,16 +40,30 @@
 //! is true, the `factory_build` tool is available and agents can autonomously decide
 //! when to use it based on task complexity.
 
+pub mod a2a;
+pub mod accessibility;
 pub mod blackboard;
+pub mod distributed;
+pub mod estimate;
+pub mod issues;
+pub mod maintenance;
 pub mod orchestrator_tool;
 pub mod pool;
 pub mod progress;
 pub mod protocol;
+pub mod queue;
+pub mod review;
 pub mod roles;
+pub mod security;
+pub mod shared_memory;
+pub mod triage;
 pub mod workflow;
 pub mod workspace;
 
+pub use a2a::A2aDelegateTool;
 pub use blackboard::{Artifact, ArtifactEntry, Blackboard};
+pub use distributed::StageWorkers;
+pub use issues::IssueWatcher;
 pub use orchestrator_tool::FactoryOrchestratorTool;
 pub use pool::{
     AgentInstance, AgentInstanceId, AgentPool, AgentState as PoolAgentState, PoolConfig, PoolStats,
@@ -51,7 +76,9 @@ pub use protocol::{
     AgentMessage, MessageBus, MessageContent, MessageHandler, MessageHandlerRegistry,
     MessageHeader, MessageId, MessagePriority, ProtocolError,
 };
+pub use queue::{BuildQueue, BuildQueueTool};
 pub use roles::{AgentRole, RoleConfig};
+pub use shared_memory::{FactoryMemoryTool, SharedFact, SharedMemory};
 pub use workflow::{FactoryWorkflow, WorkflowPhase};
 pub use workspace::{
     AgentConfig, AgentWorkspace, WorkspaceConfig, WorkspaceId, WorkspaceManager, WorkspacePaths,
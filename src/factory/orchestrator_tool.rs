@@ -3,14 +3,33 @@
 //! Implements the [`Tool`] trait, registered conditionally when
 //! `factory.enabled = true` in the config.
 
+use super::estimate::{BuildEstimate, CALIBRATION_BUILDS};
+use super::shared_memory::SharedMemory;
 use super::workflow::FactoryWorkflow;
-use crate::config::DelegateAgentConfig;
+use crate::agent::prompt_templates::PromptTemplates;
+use crate::config::{
+    DelegateAgentConfig, FactoryBusConfig, FactoryMemoryConfig, FactoryStagesConfig, ModelPricing,
+};
+use crate::coordination::bus::CoordinationBus;
+use crate::memory::Memory;
 use crate::providers;
+use crate::sandbox::SandboxClient;
+use crate::store::{self, build_usage};
+use crate::tools::progress::{report_build_status, BuildStatus};
 use crate::tools::traits::{Tool, ToolResult};
 use async_trait::async_trait;
 use serde_json::json;
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Instant;
+
+/// Pricing and history for the pre-build cost estimate.
+struct CostEstimate {
+    prices: HashMap<String, ModelPricing>,
+    confirm_above_usd: f64,
+    db_path: PathBuf,
+}
 
 /// Tool that orchestrates the full multi-agent factory workflow.
 ///
@@ -32,6 +51,14 @@ pub struct FactoryOrchestratorTool {
     parent_tools: Arc<Vec<Arc<dyn Tool>>>,
     multimodal_config: crate::config::MultimodalConfig,
     enable_streaming: bool,
+    prompt_templates: Option<PromptTemplates>,
+    shared_memory: Option<(Arc<dyn Memory>, FactoryMemoryConfig)>,
+    stages: FactoryStagesConfig,
+    sandbox: Option<Arc<dyn SandboxClient>>,
+    cost_estimate: Option<CostEstimate>,
+    bus: Option<(Arc<dyn CoordinationBus>, FactoryBusConfig)>,
+    /// Store the blackboard journal of every build goes to.
+    journal_db: Option<PathBuf>,
 }
 
 impl FactoryOrchestratorTool {
@@ -58,7 +85,130 @@ impl FactoryOrchestratorTool {
             parent_tools,
             multimodal_config,
             enable_streaming,
+            prompt_templates: None,
+            shared_memory: None,
+            stages: FactoryStagesConfig::default(),
+            sandbox: None,
+            cost_estimate: None,
+            bus: None,
+            journal_db: None,
+        }
+    }
+
+    /// Use `prompts/<role>.md` templates for factory stage prompts.
+    pub fn with_prompt_templates(mut self, templates: PromptTemplates) -> Self {
+        self.prompt_templates = Some(templates);
+        self
+    }
+
+    /// Select which optional pipeline stages run.
+    pub fn with_stages(mut self, stages: FactoryStagesConfig) -> Self {
+        self.stages = stages;
+        self
+    }
+
+    /// Read the project's `.zerobuild.toml` from `sandbox` for stage prompts.
+    pub fn with_sandbox(mut self, sandbox: Arc<dyn SandboxClient>) -> Self {
+        self.sandbox = Some(sandbox);
+        self
+    }
+
+    /// Estimate each build's tokens, time and cost with `prices`, calibrated
+    /// by the builds recorded in the store at `db_path`, and ask for
+    /// confirmation when the cost exceeds `confirm_above_usd` (`0` never asks).
+    pub fn with_cost_estimate(
+        mut self,
+        prices: HashMap<String, ModelPricing>,
+        confirm_above_usd: f64,
+        db_path: impl Into<PathBuf>,
+    ) -> Self {
+        self.cost_estimate = Some(CostEstimate {
+            prices,
+            confirm_above_usd,
+            db_path: db_path.into(),
+        });
+        self
+    }
+
+    /// Journal every build's blackboard in the store at `db_path`, for
+    /// `blackboard_history`.
+    pub fn with_journal(mut self, db_path: impl Into<PathBuf>) -> Self {
+        self.journal_db = Some(db_path.into());
+        self
+    }
+
+    /// Keep build blackboards on `bus`, configured by `[factory.bus]`.
+    pub fn with_bus(mut self, bus: Arc<dyn CoordinationBus>, config: FactoryBusConfig) -> Self {
+        self.bus = Some((bus, config));
+        self
+    }
+
+    /// A workflow with this tool's provider settings, stages, sandbox and
+    /// prompt templates, for a build or for a stage picked up from the queue.
+    pub(crate) fn stage_workflow(&self, idea: String) -> FactoryWorkflow {
+        let mut workflow = FactoryWorkflow::new(
+            idea,
+            self.max_ping_pong,
+            self.role_overrides.clone(),
+            self.provider_runtime_options.clone(),
+            self.fallback_credential.clone(),
+            self.default_provider.clone(),
+            self.default_model.clone(),
+            self.parent_tools.clone(),
+            self.multimodal_config.clone(),
+            self.enable_streaming,
+        )
+        .with_stages(self.stages.clone());
+        if let Some(sandbox) = &self.sandbox {
+            workflow = workflow.with_sandbox(Arc::clone(sandbox));
+        }
+        if let Some(templates) = &self.prompt_templates {
+            workflow = workflow.with_prompt_templates(templates.clone());
         }
+        workflow
+    }
+
+    /// Back factory agents' shared memory namespaces with `memory`.
+    pub fn with_shared_memory(
+        mut self,
+        memory: Arc<dyn Memory>,
+        config: FactoryMemoryConfig,
+    ) -> Self {
+        if config.enabled {
+            self.shared_memory = Some((memory, config));
+        }
+        self
+    }
+
+    /// Shared memory for one run. The project is the explicit `project`
+    /// argument, else the snapshot's project, else the active project, else
+    /// derived from the idea.
+    async fn shared_memory_for(
+        &self,
+        project: Option<&str>,
+        idea: &str,
+    ) -> Option<Arc<SharedMemory>> {
+        let (memory, config) = self.shared_memory.as_ref()?;
+        let project = match project
+            .filter(|p| !p.trim().is_empty())
+            .map(str::to_string)
+            .or_else(crate::memory::user_scoped::current_memory_project)
+        {
+            Some(project) => project,
+            None => match crate::memory::load_project_context(memory.as_ref()).await {
+                Ok(Some(active)) => active.name,
+                _ => idea.to_string(),
+            },
+        };
+        Some(Arc::new(
+            SharedMemory::new(
+                Arc::clone(memory),
+                uuid::Uuid::new_v4().to_string(),
+                &project,
+            )
+            .with_user(crate::memory::user_scoped::current_memory_user())
+            .with_permissions(config.permissions.clone()),
+        ))
     }
 }
 
@@ -88,6 +238,14 @@ impl Tool for FactoryOrchestratorTool {
                     "type": "string",
                     "enum": ["web", "api", "cli", "library", "mobile"],
                     "description": "Optional project type hint for the factory agents"
+                },
+                "project": {
+                    "type": "string",
+                    "description": "Optional project name; agents share project memory across builds of the same project"
+                },
+                "confirm": {
+                    "type": "boolean",
+                    "description": "Set to true only after the user approved the cost estimate of a previous call"
                 }
             },
             "required": ["idea"]
@@ -106,6 +264,7 @@ impl Tool for FactoryOrchestratorTool {
                 success: false,
                 output: String::new(),
                 error: Some("'idea' parameter must not be empty".into()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -121,40 +280,109 @@ impl Tool for FactoryOrchestratorTool {
             format!("[Project type: {project_type}]\n\n{idea}")
         };
 
-        let mut workflow = FactoryWorkflow::new(
-            full_idea,
-            self.max_ping_pong,
-            self.role_overrides.clone(),
-            self.provider_runtime_options.clone(),
-            self.fallback_credential.clone(),
-            self.default_provider.clone(),
-            self.default_model.clone(),
-            self.parent_tools.clone(),
-            self.multimodal_config.clone(),
-            self.enable_streaming,
-        );
+        let mut workflow = self.stage_workflow(full_idea);
+        if let Some(db_path) = &self.journal_db {
+            workflow = workflow.with_journal(db_path);
+        }
+        if let Some((bus, config)) = &self.bus {
+            workflow = workflow.with_bus(Arc::clone(bus), config);
+        }
+        let project = args.get("project").and_then(|v| v.as_str());
+        if let Some(shared) = self.shared_memory_for(project, idea).await {
+            workflow = workflow.with_shared_memory(shared);
+        }
+
+        let mut estimate = None;
+        if let Some(settings) = &self.cost_estimate {
+            workflow = workflow.with_pricing(settings.prices.clone());
+            let history = store::init_db(&settings.db_path)
+                .and_then(|conn| build_usage::recent(&conn, CALIBRATION_BUILDS))
+                .unwrap_or_else(|e| {
+                    tracing::warn!("Failed to read build usage history: {e:#}");
+                    Vec::new()
+                });
+            let built = workflow.estimate(&history);
+            let confirmed = args["confirm"].as_bool().unwrap_or(false);
+            if !confirmed
+                && settings.confirm_above_usd > 0.0
+                && built.cost_usd > settings.confirm_above_usd
+            {
+                return Ok(confirmation_request(&built, settings.confirm_above_usd));
+            }
+            workflow = workflow.with_estimate(built.clone());
+            estimate = Some(built);
+        }
+
+        let started = Instant::now();
+        let result = workflow.run().await;
+        report_build_status(BuildStatus::Finished {
+            success: result.is_ok(),
+        });
+        if let (Some(settings), Some(estimate), Ok(_)) = (&self.cost_estimate, &estimate, &result) {
+            let usage = build_usage::BuildUsage {
+                estimated_tokens: estimate.baseline_tokens,
+                actual_tokens: workflow.usage().tokens(),
+                estimated_secs: estimate.baseline_secs,
+                actual_secs: started.elapsed().as_secs(),
+            };
+            if let Err(e) = store::init_db(&settings.db_path)
+                .and_then(|conn| build_usage::record(&conn, &usage))
+            {
+                tracing::warn!("Failed to record build usage: {e:#}");
+            }
+        }
 
-        match workflow.run().await {
+        match result {
             Ok(summary) => Ok(ToolResult {
                 success: true,
                 output: summary,
                 error: None,
+                error_code: None,
                 error_hint: None,
             }),
             Err(e) => Ok(ToolResult {
                 success: false,
                 output: String::new(),
                 error: Some(format!("Factory build failed: {e}")),
-                error_hint: Some(
+                error_code: None,
+                error_hint: Some(if self.journal_db.is_some() {
+                    format!(
+                        "Check provider configuration and ensure factory agents have valid \
+                         provider/model settings. blackboard_history with workflow_id {} shows \
+                         what each stage saw.",
+                        workflow.id()
+                    )
+                } else {
                     "Check provider configuration and ensure factory agents \
                      have valid provider/model settings."
-                        .into(),
-                ),
+                        .into()
+                }),
             }),
         }
     }
 }
 
+/// Stop before the build and show its estimate to the user.
+fn confirmation_request(estimate: &BuildEstimate, threshold: f64) -> ToolResult {
+    ToolResult {
+        success: false,
+        output: format!(
+            "{}\n\nThis is above the ${threshold:.2} confirmation threshold \
+             (factory.confirm_above_usd). Reply \"build it\" to start, or \"cancel\".",
+            estimate.preview()
+        ),
+        error: Some(
+            "⏳ COST ESTIMATE — build not started. Waiting for the user to confirm.".to_string(),
+        ),
+        error_code: None,
+        error_hint: Some(
+            "Show the estimate to the user. Call factory_build again with confirm: true only \
+             after they approve."
+                .to_string(),
+        ),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -234,4 +462,26 @@ mod tests {
         let result = tool.execute(json!({})).await;
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn expensive_build_waits_for_confirmation() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let tool = FactoryOrchestratorTool::new(
+            5,
+            HashMap::new(),
+            providers::ProviderRuntimeOptions::default(),
+            None,
+            "openrouter".into(),
+            "test-model".into(),
+            Arc::new(Vec::new()),
+            crate::config::MultimodalConfig::default(),
+            true,
+        )
+        .with_cost_estimate(HashMap::new(), 0.01, tmp.path().join("zerobuild.db"));
+
+        let result = tool.execute(json!({"idea": "todo app"})).await.unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("COST ESTIMATE"));
+        assert!(result.output.contains("$0.01 confirmation threshold"));
+    }
 }
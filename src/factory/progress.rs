@@ -5,6 +5,7 @@
 use crate::factory::blackboard::Artifact;
 use crate::factory::roles::AgentRole;
 use crate::factory::workflow::WorkflowPhase;
+use crate::tools::progress::BuildStatus;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::time::Duration;
@@ -233,15 +234,25 @@ impl ProgressTracker {
     }
 
     /// Track phase start
+    ///
+    /// The message also goes to the caller's tool progress and build status
+    /// (see [`crate::tools::progress`]), so channels and queued builds see
+    /// which stage is running.
     pub fn phase_started(&mut self, phase: WorkflowPhase, message: impl Into<String>) {
         let now = chrono::Utc::now();
+        let message = message.into();
+        crate::tools::progress::report_progress(&message);
+        crate::tools::progress::report_build_status(BuildStatus::Stage(format!(
+            "{} — {message}",
+            phase_to_string(&phase)
+        )));
         self.current_phase = Some(phase);
         self.phase_start_time = Some(now);
         self.broadcaster
             .broadcast(FactoryProgressEvent::PhaseStarted {
                 phase,
                 timestamp: now,
-                message: message.into(),
+                message,
             });
     }
 
@@ -407,6 +418,8 @@ impl ProgressFormatter {
                     WorkflowPhase::Analysis => "📋",
                     WorkflowPhase::ParallelBuild => "🔨",
                     WorkflowPhase::IntegrationLoop => "🔄",
+                    WorkflowPhase::AccessibilityAudit => "♿",
+                    WorkflowPhase::SecurityReview => "🛡️",
                     WorkflowPhase::Deployment => "🚀",
                     WorkflowPhase::Completed => "✅",
                     WorkflowPhase::Failed => "❌",
@@ -516,6 +529,8 @@ fn phase_to_string(phase: &WorkflowPhase) -> &'static str {
         WorkflowPhase::Analysis => "Analysis",
         WorkflowPhase::ParallelBuild => "Parallel Build",
         WorkflowPhase::IntegrationLoop => "Integration Loop",
+        WorkflowPhase::AccessibilityAudit => "Accessibility Audit",
+        WorkflowPhase::SecurityReview => "Security Review",
         WorkflowPhase::Deployment => "Deployment",
         WorkflowPhase::Completed => "Completed",
         WorkflowPhase::Failed => "Failed",
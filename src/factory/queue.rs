@@ -0,0 +1,428 @@
+//! Build queue: `factory_build` requests enqueued in the store and processed
+//! by a pool of workers, so several ideas from one team channel run side by
+//! side instead of trampling one shared agent.
+//!
+//! Claim order (priority, then per-user fairness) lives in
+//! [`crate::store::jobs`]. The `build_queue` tool enqueues jobs and reports
+//! their status; workers run each job through the `factory_build` tool in the
+//! requesting user's memory scope.
+
+use crate::config::FactoryQueueConfig;
+use crate::memory::user_scoped::{current_memory_user, with_memory_user};
+use crate::store::preferences::LOCAL_USER;
+use crate::store::{self, jobs};
+use crate::tools::progress::with_tool_progress;
+use crate::tools::traits::{Tool, ToolResult};
+use async_trait::async_trait;
+use serde_json::json;
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// How often idle workers re-check the store for jobs enqueued by another
+/// process.
+const IDLE_POLL: Duration = Duration::from_secs(5);
+
+/// The process-wide queue; workers are started once per process.
+static QUEUE: OnceLock<Arc<BuildQueue>> = OnceLock::new();
+
+/// Worker pool draining the `build_jobs` table.
+pub struct BuildQueue {
+    db_path: PathBuf,
+    wake: Notify,
+}
+
+impl BuildQueue {
+    /// Return the process-wide queue, starting `workers` worker loops that
+    /// run jobs through `builder` on first use. Jobs interrupted by a
+    /// previous shutdown are requeued.
+    pub fn start_global(
+        db_path: PathBuf,
+        workers: usize,
+        builder: Arc<dyn Tool>,
+    ) -> anyhow::Result<Arc<Self>> {
+        if let Some(queue) = QUEUE.get() {
+            return Ok(Arc::clone(queue));
+        }
+        let handle = tokio::runtime::Handle::try_current()
+            .map_err(|_| anyhow::anyhow!("build queue workers need a Tokio runtime"))?;
+
+        let requeued = jobs::requeue_interrupted(&store::init_db(&db_path)?)?;
+        if requeued > 0 {
+            tracing::info!(requeued, "Requeued interrupted build jobs");
+        }
+
+        let queue = Arc::clone(QUEUE.get_or_init(|| {
+            Arc::new(Self {
+                db_path,
+                wake: Notify::new(),
+            })
+        }));
+        for worker in 0..workers.max(1) {
+            handle.spawn(Arc::clone(&queue).worker_loop(worker, Arc::clone(&builder)));
+        }
+        tracing::info!(workers = workers.max(1), "Build queue workers started");
+        Ok(queue)
+    }
+
+    /// Wake an idle worker after a job was enqueued.
+    pub fn notify(&self) {
+        self.wake.notify_one();
+    }
+
+    async fn worker_loop(self: Arc<Self>, worker: usize, builder: Arc<dyn Tool>) {
+        loop {
+            let db_path = self.db_path.clone();
+            let claimed = tokio::task::spawn_blocking(move || {
+                jobs::claim_next_job(&mut store::init_db(&db_path)?)
+            })
+            .await
+            .unwrap_or_else(|e| Err(e.into()));
+
+            match claimed {
+                Ok(Some(job)) => self.run_job(worker, job, builder.as_ref()).await,
+                Ok(None) => {
+                    let _ = tokio::time::timeout(IDLE_POLL, self.wake.notified()).await;
+                }
+                Err(e) => {
+                    tracing::warn!(worker, "Failed to claim build job: {e:#}");
+                    tokio::time::sleep(IDLE_POLL).await;
+                }
+            }
+        }
+    }
+
+    async fn run_job(&self, worker: usize, job: jobs::BuildJob, builder: &dyn Tool) {
+        tracing::info!(worker, job_id = job.id, user = %job.user, "Starting build job");
+        let args = json!({
+            "idea": job.idea,
+            "project_type": job.project_type,
+            "project": job.project,
+            // Queued builds run unattended; nobody is there to confirm a cost estimate.
+            "confirm": true,
+        });
+        // Keep the latest progress line in the store for `build_queue status`
+        // and the issue watcher.
+        let (tx, mut rx) = tokio::sync::mpsc::channel::<String>(32);
+        let db_path = self.db_path.clone();
+        let job_id = job.id;
+        let recorder = tokio::spawn(async move {
+            while let Some(line) = rx.recv().await {
+                let line = line.trim().to_string();
+                let db_path = db_path.clone();
+                let _ = tokio::task::spawn_blocking(move || {
+                    jobs::set_progress(&store::init_db(&db_path)?, job_id, &line)
+                })
+                .await;
+            }
+        });
+        let run = with_memory_user(job.user.clone(), builder.execute(args));
+        let result = with_tool_progress(tx, run).await;
+        let _ = recorder.await;
+        let outcome = match result {
+            Ok(result) if result.success => Ok(result.output),
+            Ok(result) => Err(result.error.unwrap_or_else(|| "build failed".into())),
+            Err(e) => Err(format!("{e:#}")),
+        };
+        if let Err(e) = store::init_db(&self.db_path).and_then(|conn| {
+            jobs::finish_job(&conn, job.id, outcome.as_deref().map_err(String::as_str))
+        }) {
+            tracing::warn!(job_id = job.id, "Failed to record build job result: {e:#}");
+        }
+        tracing::info!(
+            worker,
+            job_id = job.id,
+            ok = outcome.is_ok(),
+            "Build job finished"
+        );
+    }
+}
+
+/// `build_queue` tool: enqueue builds and query or cancel them.
+pub struct BuildQueueTool {
+    db_path: PathBuf,
+    queue: Option<Arc<BuildQueue>>,
+    max_pending_per_user: usize,
+}
+
+impl BuildQueueTool {
+    pub fn new(
+        db_path: PathBuf,
+        queue: Option<Arc<BuildQueue>>,
+        config: &FactoryQueueConfig,
+    ) -> Self {
+        Self {
+            db_path,
+            queue,
+            max_pending_per_user: config.max_pending_per_user.max(1),
+        }
+    }
+
+    fn enqueue(&self, user: &str, args: &serde_json::Value) -> anyhow::Result<ToolResult> {
+        let Some(idea) = args
+            .get("idea")
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|idea| !idea.is_empty())
+        else {
+            return Ok(failure("'idea' is required for enqueue"));
+        };
+        let priority = match args.get("priority").and_then(|v| v.as_str()) {
+            Some("high") => 1,
+            Some("low") => -1,
+            None | Some("normal") => 0,
+            Some(other) => return Ok(failure(&format!("Unknown priority '{other}'"))),
+        };
+
+        let conn = store::init_db(&self.db_path)?;
+        if jobs::pending_count_for_user(&conn, user)? >= self.max_pending_per_user {
+            return Ok(failure(&format!(
+                "You already have {} builds queued or running; wait for one to finish or cancel one",
+                self.max_pending_per_user
+            )));
+        }
+        let id = jobs::enqueue_job(
+            &conn,
+            &jobs::NewJob {
+                user,
+                idea,
+                project_type: args.get("project_type").and_then(|v| v.as_str()),
+                project: args.get("project").and_then(|v| v.as_str()),
+                priority,
+            },
+        )?;
+        let position = jobs::queue_position(&conn, id)?.unwrap_or(1);
+        if let Some(queue) = &self.queue {
+            queue.notify();
+        }
+
+        Ok(success(format!(
+            "Build job #{id} queued (position {position}). Use action 'status' with job_id {id} to follow it."
+        )))
+    }
+
+    fn status(&self, user: &str, args: &serde_json::Value) -> anyhow::Result<ToolResult> {
+        let Some(id) = args.get("job_id").and_then(serde_json::Value::as_i64) else {
+            return Ok(failure("'job_id' is required for status"));
+        };
+        let conn = store::init_db(&self.db_path)?;
+        let Some(job) = jobs::get_job(&conn, id)?.filter(|job| job.user == user) else {
+            return Ok(failure(&format!("No build job #{id} for you")));
+        };
+
+        let mut output = format!(
+            "Job #{}: {}\nIdea: {}",
+            job.id,
+            job.status.as_str(),
+            job.idea
+        );
+        if let Some(position) = jobs::queue_position(&conn, id)? {
+            let _ = write!(output, "\nQueue position: {position}");
+        }
+        if job.status == jobs::JobStatus::Running {
+            if let Some(line) = jobs::progress(&conn, id)? {
+                let _ = write!(output, "\nStage: {line}");
+            }
+        }
+        if let Some(started) = &job.started_at {
+            let _ = write!(output, "\nStarted: {started}");
+        }
+        if let Some(finished) = &job.finished_at {
+            let _ = write!(output, "\nFinished: {finished}");
+        }
+        if let Some(result) = &job.output {
+            let _ = write!(output, "\n\n{result}");
+        }
+        if let Some(error) = &job.error {
+            let _ = write!(output, "\nError: {error}");
+        }
+        Ok(success(output))
+    }
+
+    fn list(&self, user: &str) -> anyhow::Result<ToolResult> {
+        let conn = store::init_db(&self.db_path)?;
+        let listed = jobs::list_jobs(&conn, Some(user), 20)?;
+        if listed.is_empty() {
+            return Ok(success("No build jobs.".into()));
+        }
+        let mut output = String::new();
+        for job in listed {
+            let idea: String = job.idea.chars().take(60).collect();
+            let _ = writeln!(output, "#{} [{}] {idea}", job.id, job.status.as_str());
+        }
+        Ok(success(output))
+    }
+
+    fn cancel(&self, user: &str, args: &serde_json::Value) -> anyhow::Result<ToolResult> {
+        let Some(id) = args.get("job_id").and_then(serde_json::Value::as_i64) else {
+            return Ok(failure("'job_id' is required for cancel"));
+        };
+        let conn = store::init_db(&self.db_path)?;
+        if jobs::cancel_job(&conn, id, user)? {
+            Ok(success(format!("Build job #{id} cancelled.")))
+        } else {
+            Ok(failure(&format!(
+                "Build job #{id} is not queued for you (running and finished jobs can't be cancelled)"
+            )))
+        }
+    }
+}
+
+fn success(output: String) -> ToolResult {
+    ToolResult {
+        success: true,
+        output,
+        error: None,
+        error_code: None,
+        error_hint: None,
+    }
+}
+
+fn failure(error: &str) -> ToolResult {
+    ToolResult {
+        success: false,
+        output: String::new(),
+        error: Some(error.to_string()),
+        error_code: None,
+        error_hint: None,
+    }
+}
+
+#[async_trait]
+impl Tool for BuildQueueTool {
+    fn name(&self) -> &str {
+        "build_queue"
+    }
+
+    fn description(&self) -> &str {
+        "Queue factory builds to run in the background and check on them. \
+         Use 'enqueue' when the user asks for several projects at once or doesn't need to wait; \
+         each job runs the full factory_build pipeline. 'status' and 'list' report progress, \
+         'cancel' removes a job that hasn't started."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "additionalProperties": false,
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["enqueue", "status", "list", "cancel"]
+                },
+                "idea": {
+                    "type": "string",
+                    "description": "Project idea to build (enqueue)"
+                },
+                "project_type": {
+                    "type": "string",
+                    "enum": ["web", "api", "cli", "library", "mobile"],
+                    "description": "Optional project type hint (enqueue)"
+                },
+                "project": {
+                    "type": "string",
+                    "description": "Optional project name for shared project memory (enqueue)"
+                },
+                "priority": {
+                    "type": "string",
+                    "enum": ["low", "normal", "high"],
+                    "description": "Higher priority jobs start first (enqueue, default normal)"
+                },
+                "job_id": {
+                    "type": "integer",
+                    "description": "Job to inspect or cancel (status, cancel)"
+                }
+            },
+            "required": ["action"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let user = current_memory_user().unwrap_or_else(|| LOCAL_USER.to_string());
+        match args.get("action").and_then(|v| v.as_str()) {
+            Some("enqueue") => self.enqueue(&user, &args),
+            Some("status") => self.status(&user, &args),
+            Some("list") => self.list(&user),
+            Some("cancel") => self.cancel(&user, &args),
+            Some(other) => Ok(failure(&format!("Unknown action '{other}'"))),
+            None => Ok(failure("'action' is required")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn tool(tmp: &TempDir, max_pending_per_user: usize) -> BuildQueueTool {
+        BuildQueueTool::new(
+            tmp.path().join("zerobuild.db"),
+            None,
+            &FactoryQueueConfig {
+                enabled: true,
+                workers: 1,
+                max_pending_per_user,
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn enqueue_status_and_cancel_are_scoped_to_the_user() {
+        let tmp = TempDir::new().unwrap();
+        let tool = tool(&tmp, 5);
+
+        let queued = with_memory_user(
+            "alice".into(),
+            tool.execute(json!({"action": "enqueue", "idea": "todo app", "priority": "high"})),
+        )
+        .await
+        .unwrap();
+        assert!(queued.success, "{:?}", queued.error);
+        assert!(queued.output.contains("#1 queued (position 1)"));
+
+        let status = with_memory_user(
+            "alice".into(),
+            tool.execute(json!({"action": "status", "job_id": 1})),
+        )
+        .await
+        .unwrap();
+        assert!(status.output.contains("Job #1: queued"));
+
+        let foreign = with_memory_user(
+            "bob".into(),
+            tool.execute(json!({"action": "cancel", "job_id": 1})),
+        )
+        .await
+        .unwrap();
+        assert!(!foreign.success);
+
+        let cancelled = with_memory_user(
+            "alice".into(),
+            tool.execute(json!({"action": "cancel", "job_id": 1})),
+        )
+        .await
+        .unwrap();
+        assert!(cancelled.success);
+    }
+
+    #[tokio::test]
+    async fn enqueue_enforces_per_user_limit() {
+        let tmp = TempDir::new().unwrap();
+        let tool = tool(&tmp, 1);
+
+        let first = tool
+            .execute(json!({"action": "enqueue", "idea": "blog"}))
+            .await
+            .unwrap();
+        assert!(first.success);
+        let second = tool
+            .execute(json!({"action": "enqueue", "idea": "shop"}))
+            .await
+            .unwrap();
+        assert!(!second.success);
+        assert!(second.error.unwrap().contains("already have 1 builds"));
+    }
+}
@@ -0,0 +1,545 @@
+//! Pull request review bot for existing repositories (`[factory.review]`).
+//!
+//! The watcher polls the configured repositories for pull requests opened
+//! since it started watching them. For each one that is ready for review it
+//! fetches the diff with `github_get_pr_diff` and has a model review it: one
+//! `#tag STATUS - note` line per checklist item of the repository, the format
+//! `github_review_pr_with_checklist` posts, and findings on changed lines,
+//! each with a severity. `github_post_inline_comments` then posts the review:
+//! findings at or above `comment_at` become inline comments (or are listed in
+//! the summary when their line is outside the diff), and a finding at or
+//! above `request_changes_at` makes it request changes.
+//!
+//! Draft pull requests wait until they are ready. Each pull request is
+//! reviewed once.
+
+use crate::config::{Config, FactoryReviewConfig, ReviewRepoConfig, ReviewSeverity};
+use crate::providers::Provider;
+use crate::store::{self, reviews::ReviewedPr};
+use crate::tools::github_ops::{
+    checklist_review_body, GitHubGetPRDiffTool, GitHubListPRsTool, GitHubPostInlineCommentsTool,
+};
+use crate::tools::Tool;
+use anyhow::{bail, Context};
+use chrono::DateTime;
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Pull requests reviewed per repository and poll.
+const MAX_REVIEWS_PER_POLL: usize = 5;
+
+/// Characters of the diff the model sees.
+const MAX_DIFF_CHARS: usize = 60_000;
+
+/// Characters of the pull request description the model sees.
+const MAX_DESCRIPTION_CHARS: usize = 4000;
+
+const REVIEW_SYSTEM_PROMPT: &str = "You review GitHub pull requests. Read the diff and \
+answer with JSON only:\n\
+{\"summary\": \"...\", \
+\"checklist\": [\"#security PASS - no auth changes\", \"#tests NEEDS_WORK - add a test for the empty cart\"], \
+\"findings\": [{\"path\": \"src/cart.ts\", \"line\": 42, \"severity\": \"high\", \"comment\": \"...\"}]}\n\n\
+- summary: two or three sentences on what the pull request does and how it looks overall.\n\
+- checklist: exactly one line per checklist hashtag you are given, with the status PASS, \
+NEEDS_WORK, FAIL or SKIP and a short note.\n\
+- findings: concrete problems only, each on a line the diff adds or keeps (the line number \
+in the new version of the file). severity is low (style, naming), medium (maintainability, \
+missing edge cases), high (bugs, missing tests for changed behavior) or critical (security \
+holes, data loss, crashes). Explain the problem and how to fix it. No praise, no nitpicks \
+the checklist already covers.";
+
+/// What the model made of a pull request.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Review {
+    #[serde(default)]
+    pub summary: String,
+    #[serde(default)]
+    pub checklist: Vec<String>,
+    #[serde(default)]
+    pub findings: Vec<Finding>,
+}
+
+/// A problem on one line of the diff.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Finding {
+    pub path: String,
+    pub line: u64,
+    pub severity: ReviewSeverity,
+    pub comment: String,
+}
+
+/// The review in a model reply, which may wrap the JSON in prose or a code
+/// fence.
+pub fn parse_review(reply: &str) -> Option<Review> {
+    let start = reply.find('{')?;
+    let end = reply.rfind('}')?;
+    serde_json::from_str(reply.get(start..=end)?).ok()
+}
+
+/// Lines of the new version of each file that a review can comment on,
+/// read from `github_get_pr_diff` output: the added and context lines of
+/// every hunk.
+pub fn commentable_lines(diff: &str) -> BTreeMap<String, BTreeSet<u64>> {
+    let mut lines: BTreeMap<String, BTreeSet<u64>> = BTreeMap::new();
+    let mut file: Option<String> = None;
+    let mut next_line = 0;
+    for line in diff.lines() {
+        if let Some(header) = line
+            .strip_prefix("=== ")
+            .and_then(|h| h.strip_suffix(" ==="))
+        {
+            file = header.rfind(" [").map(|end| header[..end].to_string());
+            continue;
+        }
+        let Some(path) = &file else {
+            continue;
+        };
+        if let Some(hunk) = line.strip_prefix("@@ ") {
+            // `@@ -a,b +c,d @@`: the new file's lines start at `c`.
+            next_line = hunk
+                .split_whitespace()
+                .find_map(|range| range.strip_prefix('+'))
+                .and_then(|range| range.split(',').next()?.parse().ok())
+                .unwrap_or(0);
+        } else if line.starts_with('+') || line.starts_with(' ') {
+            if next_line > 0 {
+                lines.entry(path.clone()).or_default().insert(next_line);
+                next_line += 1;
+            }
+        } else if !line.starts_with('-') && !line.starts_with('\\') {
+            // Anything else ends the patch of the file.
+            file = None;
+        }
+    }
+    lines
+}
+
+/// A review ready to post.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedReview {
+    pub body: String,
+    /// `COMMENT` or `REQUEST_CHANGES`.
+    pub event: &'static str,
+    /// Inline comments in the `github_post_inline_comments` format.
+    pub comments: Vec<serde_json::Value>,
+}
+
+/// Turn a model review into what gets posted under the thresholds of
+/// `settings`. GitHub refuses to let authors request changes on their own
+/// pull requests, so `own_pull_request` reviews only comment.
+pub fn plan_review(
+    review: &Review,
+    settings: &ReviewRepoConfig,
+    commentable: &BTreeMap<String, BTreeSet<u64>>,
+    own_pull_request: bool,
+) -> PlannedReview {
+    let summary = (!review.summary.trim().is_empty()).then_some(review.summary.as_str());
+    let mut body = checklist_review_body(summary, &review.checklist.join("\n"));
+
+    let mut comments = Vec::new();
+    let mut outside_diff = Vec::new();
+    for finding in review
+        .findings
+        .iter()
+        .filter(|f| f.severity >= settings.comment_at)
+    {
+        let comment = format!(
+            "**{}**: {}",
+            finding.severity.as_str(),
+            finding.comment.trim()
+        );
+        if commentable
+            .get(&finding.path)
+            .is_some_and(|lines| lines.contains(&finding.line))
+        {
+            comments.push(json!({
+                "path": finding.path,
+                "line": finding.line,
+                "body": comment,
+            }));
+        } else {
+            outside_diff.push(format!("- `{}:{}` {comment}", finding.path, finding.line));
+        }
+    }
+    if !outside_diff.is_empty() {
+        body.push_str("\n### Other findings\n\n");
+        body.push_str(&outside_diff.join("\n"));
+        body.push('\n');
+    }
+    let _ = write!(
+        body,
+        "\n_Reviewed automatically by ZeroBuild; findings below `{}` are not shown._",
+        settings.comment_at.as_str()
+    );
+
+    let blocking = review
+        .findings
+        .iter()
+        .any(|f| f.severity >= settings.request_changes_at);
+    PlannedReview {
+        body,
+        event: if blocking && !own_pull_request {
+            "REQUEST_CHANGES"
+        } else {
+            "COMMENT"
+        },
+        comments,
+    }
+}
+
+/// Polls repositories for new pull requests and reviews them.
+pub struct ReviewWatcher {
+    config: FactoryReviewConfig,
+    db_path: PathBuf,
+    provider: Box<dyn Provider>,
+    model: String,
+    list_prs: GitHubListPRsTool,
+    get_diff: GitHubGetPRDiffTool,
+    post_review: GitHubPostInlineCommentsTool,
+}
+
+impl ReviewWatcher {
+    pub fn new(config: &Config) -> anyhow::Result<Self> {
+        let provider_name = config.default_provider.as_deref().unwrap_or("openrouter");
+        let model = config
+            .factory
+            .review
+            .model
+            .clone()
+            .or_else(|| config.default_model.clone())
+            .unwrap_or_else(|| "anthropic/claude-sonnet-4".into());
+        let provider = crate::providers::create_routed_provider(
+            provider_name,
+            config.api_key.as_deref(),
+            config.api_url.as_deref(),
+            &config.reliability,
+            &crate::providers::effective_model_routes(config),
+            &model,
+        )?;
+        Ok(Self::with_provider(config, provider, model))
+    }
+
+    fn with_provider(config: &Config, provider: Box<dyn Provider>, model: String) -> Self {
+        let zerobuild = Arc::new(config.zerobuild.clone());
+        Self {
+            config: config.factory.review.clone(),
+            db_path: PathBuf::from(&config.zerobuild.db_path),
+            provider,
+            model,
+            list_prs: GitHubListPRsTool::new(zerobuild.clone()),
+            get_diff: GitHubGetPRDiffTool::new(zerobuild.clone()),
+            post_review: GitHubPostInlineCommentsTool::new(zerobuild),
+        }
+    }
+
+    async fn poll(&self) -> anyhow::Result<()> {
+        let Some(token) = store::tokens::load_github_token(&store::init_db(&self.db_path)?)? else {
+            tracing::debug!("Pull request reviews idle: GitHub is not connected");
+            return Ok(());
+        };
+        let mut repos: Vec<_> = self.config.repos.iter().collect();
+        repos.sort_by_key(|(repo, _)| *repo);
+        for (repo, settings) in repos {
+            if let Err(e) = self
+                .review_repo(repo, settings, token.username.as_deref())
+                .await
+            {
+                tracing::warn!(repo, "Failed to review new pull requests: {e:#}");
+            }
+        }
+        Ok(())
+    }
+
+    /// Review the pull requests of `repo` opened since it is watched.
+    async fn review_repo(
+        &self,
+        repo: &str,
+        settings: &ReviewRepoConfig,
+        username: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let since = store::reviews::watched_since(&store::init_db(&self.db_path)?, repo)?;
+        let watched_since = DateTime::parse_from_rfc3339(&since)?;
+        let (owner, name) = repo.split_once('/').unwrap_or((repo, ""));
+        let listed = self
+            .list_prs
+            .execute(json!({"owner": owner, "repo": name, "state": "open", "limit": 50}))
+            .await?;
+        if !listed.success {
+            bail!("{}", listed.error.unwrap_or_default());
+        }
+        let pulls: Vec<serde_json::Value> = serde_json::from_str(&listed.output)?;
+
+        let mut reviewed = 0;
+        for pull in &pulls {
+            let Some(number) = pull["number"].as_u64() else {
+                continue;
+            };
+            let opened = pull["created_at"]
+                .as_str()
+                .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+                .is_some_and(|at| at >= watched_since);
+            let draft = pull["draft"].as_bool().unwrap_or(false);
+            let conn = store::init_db(&self.db_path)?;
+            if !opened || draft || store::reviews::is_reviewed(&conn, repo, number)? {
+                continue;
+            }
+            if reviewed == MAX_REVIEWS_PER_POLL {
+                break;
+            }
+            reviewed += 1;
+            let own_pull_request = username.is_some_and(|u| pull["user"]["login"] == u);
+            let outcome = self
+                .review(repo, settings, pull, own_pull_request)
+                .await
+                .with_context(|| format!("pull request #{number}"))?;
+            store::reviews::record(&conn, &outcome)?;
+            tracing::info!(
+                repo,
+                number,
+                event = %outcome.event,
+                comments = outcome.comments,
+                "Reviewed pull request"
+            );
+        }
+        Ok(())
+    }
+
+    async fn review(
+        &self,
+        repo: &str,
+        settings: &ReviewRepoConfig,
+        pull: &serde_json::Value,
+        own_pull_request: bool,
+    ) -> anyhow::Result<ReviewedPr> {
+        let (owner, name) = repo.split_once('/').unwrap_or((repo, ""));
+        let number = pull["number"].as_u64().unwrap_or_default();
+        let head_sha = pull["head"]["sha"]
+            .as_str()
+            .context("pull request without a head commit")?;
+        let diff = self
+            .get_diff
+            .execute(json!({"owner": owner, "repo": name, "pr_number": number}))
+            .await?;
+        if !diff.success {
+            bail!("{}", diff.error.unwrap_or_default());
+        }
+
+        let description: String = pull["body"]
+            .as_str()
+            .unwrap_or_default()
+            .chars()
+            .take(MAX_DESCRIPTION_CHARS)
+            .collect();
+        let shown_diff: String = diff.output.chars().take(MAX_DIFF_CHARS).collect();
+        let prompt = format!(
+            "Repository: {repo}\nPull request #{number}: {}\n\n{description}\n\n\
+             Checklist: {}\n\nDiff:\n{shown_diff}",
+            pull["title"].as_str().unwrap_or_default(),
+            settings.checklist.join(" ")
+        );
+        let reply = self
+            .provider
+            .chat_with_system(Some(REVIEW_SYSTEM_PROMPT), &prompt, &self.model, 0.0)
+            .await?;
+        let review = parse_review(&reply).with_context(|| format!("unreadable review: {reply}"))?;
+
+        let planned = plan_review(
+            &review,
+            settings,
+            &commentable_lines(&diff.output),
+            own_pull_request,
+        );
+        let posted = self
+            .post_review
+            .execute(json!({
+                "owner": owner,
+                "repo": name,
+                "pr_number": number,
+                "commit_id": head_sha,
+                "body": planned.body,
+                "event": planned.event,
+                "comments": planned.comments,
+            }))
+            .await?;
+        if !posted.success {
+            bail!("{}", posted.error.unwrap_or_default());
+        }
+        Ok(ReviewedPr {
+            repo: repo.to_string(),
+            number,
+            head_sha: head_sha.to_string(),
+            event: planned.event.to_string(),
+            comments: planned.comments.len(),
+        })
+    }
+}
+
+/// Review new pull requests every `[factory.review] poll_secs`.
+pub async fn run_review_worker(config: Config) -> anyhow::Result<()> {
+    let watcher = ReviewWatcher::new(&config)?;
+    tracing::info!(
+        repos = watcher.config.repos.len(),
+        "Pull request reviews started"
+    );
+    let mut ticker = tokio::time::interval(Duration::from_secs(watcher.config.poll_secs.max(10)));
+    loop {
+        ticker.tick().await;
+        if let Err(e) = watcher.poll().await {
+            tracing::warn!("Pull request review poll failed: {e:#}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::github_transport::{self, MockGitHubTransport};
+    use async_trait::async_trait;
+    use tempfile::TempDir;
+
+    const DIFF: &str = "=== src/cart.ts [modified] (+2 / -1) ===\n\
+                        @@ -10,3 +10,4 @@ export function total(cart) {\n\
+                        \x20 let sum = 0;\n\
+                        -  for (const item of cart) sum += item.price;\n\
+                        +  for (const item of cart.items) sum += item.price;\n\
+                        +  return sum;\n\
+                        \x20}\n\n\
+                        === logo.png [added] (+0 / -0) ===\n\
+                        [binary or too large to show]";
+
+    const REPLY: &str = r##"```json
+{"summary": "Fixes the cart total.",
+ "checklist": ["#correctness NEEDS_WORK - totals ignore quantities", "#tests FAIL - no test"],
+ "findings": [
+   {"path": "src/cart.ts", "line": 11, "severity": "high", "comment": "Multiply by item.quantity."},
+   {"path": "src/cart.ts", "line": 40, "severity": "medium", "comment": "Round the total."},
+   {"path": "src/cart.ts", "line": 12, "severity": "low", "comment": "Name it total."}
+ ]}
+```"##;
+
+    struct ScriptedProvider;
+
+    #[async_trait]
+    impl Provider for ScriptedProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            assert!(message.contains("Checklist: #correctness #security #tests #performance"));
+            assert!(message.contains("cart.items"));
+            Ok(REPLY.into())
+        }
+    }
+
+    #[test]
+    fn commentable_lines_follow_the_hunks() {
+        let lines = commentable_lines(DIFF);
+        assert_eq!(
+            lines["src/cart.ts"].iter().copied().collect::<Vec<_>>(),
+            [10, 11, 12, 13]
+        );
+        assert!(!lines.contains_key("logo.png"));
+    }
+
+    #[test]
+    fn findings_are_filtered_by_severity_and_placed_on_the_diff() {
+        let review = parse_review(REPLY).unwrap();
+        let settings = ReviewRepoConfig::default();
+        let planned = plan_review(&review, &settings, &commentable_lines(DIFF), false);
+        assert_eq!(planned.event, "REQUEST_CHANGES");
+        assert_eq!(planned.comments.len(), 1);
+        assert_eq!(planned.comments[0]["line"], 11);
+        assert!(planned
+            .body
+            .contains("⚠️ **correctness** – totals ignore quantities"));
+        assert!(planned
+            .body
+            .contains("- `src/cart.ts:40` **medium**: Round the total."));
+        assert!(!planned.body.contains("Name it total"));
+
+        let lenient = ReviewRepoConfig {
+            comment_at: ReviewSeverity::Low,
+            request_changes_at: ReviewSeverity::Critical,
+            ..ReviewRepoConfig::default()
+        };
+        let planned = plan_review(&review, &lenient, &commentable_lines(DIFF), false);
+        assert_eq!(planned.event, "COMMENT");
+        assert_eq!(planned.comments.len(), 2);
+        let own = plan_review(&review, &settings, &commentable_lines(DIFF), true);
+        assert_eq!(own.event, "COMMENT");
+    }
+
+    #[tokio::test]
+    async fn new_pull_requests_are_reviewed_once() {
+        let tmp = TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.zerobuild.db_path = tmp.path().join("zerobuild.db").display().to_string();
+        config
+            .factory
+            .review
+            .repos
+            .insert("acme/shop".into(), ReviewRepoConfig::default());
+        let conn = store::init_db(&PathBuf::from(&config.zerobuild.db_path)).unwrap();
+        store::tokens::save_github_token(&conn, "gho_test", Some("zerobuild-bot")).unwrap();
+        let since = store::reviews::watched_since(&conn, "acme/shop").unwrap();
+        let opened = (DateTime::parse_from_rfc3339(&since).unwrap() + chrono::Duration::minutes(5))
+            .to_rfc3339();
+
+        let github = Arc::new(MockGitHubTransport::new());
+        github.on(
+            reqwest::Method::GET,
+            "/repos/acme/shop/pulls",
+            200,
+            json!([
+                {"number": 7, "title": "Fix totals", "created_at": opened,
+                 "user": {"login": "alice"}, "head": {"sha": "abc123"}},
+                {"number": 8, "title": "WIP", "created_at": opened, "draft": true,
+                 "user": {"login": "alice"}, "head": {"sha": "def456"}},
+                {"number": 3, "title": "Old", "created_at": "2020-01-01T00:00:00Z",
+                 "user": {"login": "alice"}, "head": {"sha": "0ld"}}
+            ]),
+        );
+        github.on(
+            reqwest::Method::GET,
+            "/repos/acme/shop/pulls/7/files",
+            200,
+            json!([{"filename": "src/cart.ts", "status": "modified", "additions": 2,
+                    "deletions": 1, "patch": DIFF.lines().skip(1).take(6).collect::<Vec<_>>().join("\n")}]),
+        );
+        github.on(
+            reqwest::Method::POST,
+            "/repos/acme/shop/pulls/7/reviews",
+            200,
+            json!({"id": 1, "state": "CHANGES_REQUESTED"}),
+        );
+
+        let watcher =
+            ReviewWatcher::with_provider(&config, Box::new(ScriptedProvider), "test".into());
+        github_transport::with_transport(github.clone(), async {
+            watcher.poll().await.unwrap();
+            watcher.poll().await.unwrap();
+        })
+        .await;
+
+        let posted: Vec<_> = github
+            .requests()
+            .into_iter()
+            .filter(|r| r.method == reqwest::Method::POST)
+            .collect();
+        assert_eq!(posted.len(), 1);
+        let body = posted[0].body.as_ref().unwrap();
+        assert_eq!(body["commit_id"], "abc123");
+        assert_eq!(body["event"], "REQUEST_CHANGES");
+        assert_eq!(body["comments"][0]["path"], "src/cart.ts");
+        assert!(store::reviews::is_reviewed(&conn, "acme/shop", 7).unwrap());
+        assert!(!store::reviews::is_reviewed(&conn, "acme/shop", 8).unwrap());
+    }
+}
@@ -20,6 +20,8 @@ pub enum AgentRole {
     Tester,
     /// Handles deployment configuration and GitHub push.
     DevOps,
+    /// Reviews the project against the security checklist before deployment.
+    SecurityReviewer,
 }
 
 impl fmt::Display for AgentRole {
@@ -31,6 +33,7 @@ impl fmt::Display for AgentRole {
             Self::Developer => write!(f, "developer"),
             Self::Tester => write!(f, "tester"),
             Self::DevOps => write!(f, "devops"),
+            Self::SecurityReviewer => write!(f, "security_reviewer"),
         }
     }
 }
@@ -65,10 +68,23 @@ impl RoleConfig {
                 DEV_PROMPT.to_string(),
                 vec![
                     "sandbox_create".to_string(),
+                    "setup_project".to_string(),
                     "sandbox_run_command".to_string(),
+                    "sandbox_shell".to_string(),
                     "sandbox_write_file".to_string(),
+                    "sandbox_write_files".to_string(),
+                    "sandbox_edit_file".to_string(),
+                    "sandbox_set_env".to_string(),
                     "sandbox_read_file".to_string(),
                     "sandbox_list_files".to_string(),
+                    "sandbox_move_file".to_string(),
+                    "sandbox_copy_file".to_string(),
+                    "sandbox_delete_path".to_string(),
+                    "codebase_search".to_string(),
+                    "project_info".to_string(),
+                    "get_log".to_string(),
+                    "sandbox_command_history".to_string(),
+                    "generate_docs".to_string(),
                 ],
                 0.3,
                 20,
@@ -79,7 +95,12 @@ impl RoleConfig {
                     "sandbox_run_command".to_string(),
                     "sandbox_read_file".to_string(),
                     "sandbox_write_file".to_string(),
+                    "sandbox_write_files".to_string(),
+                    "sandbox_edit_file".to_string(),
                     "sandbox_list_files".to_string(),
+                    "codebase_search".to_string(),
+                    "get_log".to_string(),
+                    "sandbox_command_history".to_string(),
                 ],
                 0.3,
                 15,
@@ -90,11 +111,24 @@ impl RoleConfig {
                     "sandbox_run_command".to_string(),
                     "sandbox_read_file".to_string(),
                     "sandbox_write_file".to_string(),
+                    "generate_docs".to_string(),
+                    "deploy_changes".to_string(),
                     "github_push".to_string(),
                 ],
                 0.3,
                 10,
             ),
+            AgentRole::SecurityReviewer => (
+                SECURITY_PROMPT.to_string(),
+                vec![
+                    "sandbox_read_file".to_string(),
+                    "sandbox_list_files".to_string(),
+                    "codebase_search".to_string(),
+                    "project_info".to_string(),
+                ],
+                0.2,
+                15,
+            ),
         };
 
         Self {
@@ -105,6 +139,8 @@ impl RoleConfig {
                 system_prompt: Some(system_prompt),
                 api_key: None,
                 temperature: Some(temperature),
+                top_p: None,
+                seed: None,
                 max_depth: 1,
                 agentic: !allowed_tools.is_empty(),
                 allowed_tools,
@@ -132,6 +168,12 @@ impl RoleConfig {
         if overrides.temperature.is_some() {
             self.delegate_config.temperature = overrides.temperature;
         }
+        if overrides.top_p.is_some() {
+            self.delegate_config.top_p = overrides.top_p;
+        }
+        if overrides.seed.is_some() {
+            self.delegate_config.seed = overrides.seed;
+        }
         if !overrides.allowed_tools.is_empty() {
             self.delegate_config.allowed_tools = overrides.allowed_tools.clone();
         }
@@ -171,6 +213,11 @@ Your design spec must include:\n\
 4. Visual Guidelines — color scheme, typography, spacing recommendations\n\
 5. Responsive Breakpoints — mobile, tablet, desktop considerations\n\
 \n\
+When the user attaches reference mockups, they are the source of truth: reproduce their \
+layout, spacing, colors and typography rather than inventing a new style. When asked to \
+review the running app against the mockups, compare a screenshot of the render with each \
+mockup and list concrete differences.\n\
+\n\
 Output ONLY the design specification as structured text. Be specific about component names and layout.";
 
 const DEV_PROMPT: &str = "\
@@ -179,10 +226,15 @@ Your task is to implement source code based on the PRD and design specification.
 \n\
 Rules:\n\
 - Use sandbox tools (sandbox_create, sandbox_write_file, sandbox_run_command) to build\n\
+- Start a new project with setup_project (sandbox, scaffold, install, git and first snapshot in one call) unless the stack has no template\n\
+- Write several files at once with sandbox_write_files when scaffolding, instead of one call per file\n\
+- To change part of a large file, read just the lines you need (sandbox_read_file with start_line/end_line) and edit them with sandbox_edit_file instead of rewriting the whole file\n\
+- Rename, copy or remove files with sandbox_move_file, sandbox_copy_file and sandbox_delete_path, not mv/cp/rm in sandbox_run_command\n\
 - Follow the design spec for component structure and layout\n\
 - Write clean, production-quality code\n\
-- Install all dependencies via sandbox_run_command\n\
-- Ensure the project builds without errors\n\
+- Install all dependencies via sandbox_run_command; call project_info first in an existing project to see its stack, entry points and package manager\n\
+- Ensure the project builds without errors; when build output is truncated, read the omitted errors with get_log\n\
+- After adding or changing routes, pages or the project structure, refresh README.md and docs/ with generate_docs\n\
 - All file paths are relative to the sandbox root (use 'project/' prefix)";
 
 const TESTER_PROMPT: &str = "\
@@ -192,7 +244,7 @@ Your task is to write and execute test cases based on the PRD and source code. \
 Rules:\n\
 - Read the source code via sandbox_read_file to understand what to test\n\
 - Write test files via sandbox_write_file\n\
-- Run tests via sandbox_run_command\n\
+- Run tests via sandbox_run_command; use get_log with the returned log_id to read truncated test output\n\
 - Report test results clearly: which tests passed, which failed, and why\n\
 - Focus on functional correctness and edge cases from the PRD\n\
 - Output structured test results at the end";
@@ -205,8 +257,25 @@ Rules:\n\
 - Read project files to understand the structure\n\
 - Ensure build scripts and configurations are correct\n\
 - Create any missing deployment files (Dockerfile, CI config, etc.) if appropriate\n\
-- Push the code to GitHub using github_push tool\n\
-- Report the deployment result (repo URL, branch, commit SHA)";
+- Make sure README.md and docs/ describe the current code; run generate_docs if they are missing or stale\n\
+- Before pushing, call deploy_changes with the same project_name and owner, and pass the user-visible changes it shows (features, fixes, removals; not refactors or formatting) as a short Markdown bullet list in github_push's changelog\n\
+- Push the code to GitHub using github_push tool; it adds a LICENSE and manifest license fields when missing, and a CHANGELOG.md entry and release for the changelog\n\
+- Report the deployment result (repo URL, branch, commit SHA, license, release) and any attribution warnings";
+
+const SECURITY_PROMPT: &str = "\
+You are a Security Reviewer agent in the ZeroBuild factory. \
+Your task is to review the project for vulnerabilities before it is deployed, following an \
+OWASP-style checklist. \
+\n\
+Rules:\n\
+- Start from the code quoted under each checklist item, then read around it with sandbox_read_file and search for related code with codebase_search\n\
+- Injection: queries and shell commands must use parameters, never string concatenation with request data\n\
+- Auth: every route that reads private data or changes state must check who the caller is and what they may do\n\
+- CORS: no wildcard origin on APIs that use cookies or credentials\n\
+- XSS: user content must be escaped; flag raw HTML rendering of untrusted input\n\
+- Secrets: credentials belong in environment variables, not in the code\n\
+- Do not modify files — report findings with file:line and the fix\n\
+- Mark an item FAIL only for a concrete, exploitable problem; use NEEDS_WORK for hardening";
 
 #[cfg(test)]
 mod tests {
@@ -221,6 +290,7 @@ mod tests {
             AgentRole::Developer,
             AgentRole::Tester,
             AgentRole::DevOps,
+            AgentRole::SecurityReviewer,
         ];
 
         for role in roles {
@@ -273,6 +343,8 @@ mod tests {
             system_prompt: None,
             api_key: Some("test-key".to_string()),
             temperature: Some(0.1),
+            top_p: Some(0.8),
+            seed: Some(7),
             max_depth: 1,
             agentic: true,
             allowed_tools: Vec::new(),
@@ -286,6 +358,8 @@ mod tests {
         assert!(merged.delegate_config.system_prompt.is_some());
         assert_eq!(merged.delegate_config.api_key.as_deref(), Some("test-key"));
         assert_eq!(merged.delegate_config.temperature, Some(0.1));
+        assert_eq!(merged.delegate_config.top_p, Some(0.8));
+        assert_eq!(merged.delegate_config.seed, Some(7));
     }
 
     #[test]
@@ -299,6 +373,8 @@ mod tests {
             system_prompt: None,
             api_key: None,
             temperature: None,
+            top_p: None,
+            seed: None,
             max_depth: 1,
             agentic: false,
             allowed_tools: Vec::new(),
@@ -0,0 +1,453 @@
+//! Namespaced memory shared between factory agents.
+//!
+//! The blackboard only lives for one `factory_build` run. Facts that later
+//! stages or later builds need — a quirk the Tester found, a deploy setting
+//! DevOps settled on — are written here instead, into one of four
+//! [`MemoryNamespace`]s:
+//!
+//! - `session`: this run only
+//! - `project`: every build of the same project
+//! - `user`: every build requested by the same user
+//! - `global`: every build
+//!
+//! Each role has explicit read/write access per namespace (see
+//! [`default_access`]), overridable via `[factory.memory.permissions]`.
+//! Entries are stored in the workspace memory under
+//! `factory:<namespace>:<scope>:<key>` with category `factory_<namespace>`.
+
+use super::roles::AgentRole;
+use crate::config::{MemoryNamespace, MemoryNamespaceAccess};
+use crate::memory::user_scoped::{project_id, without_memory_user};
+use crate::memory::{Memory, MemoryCategory};
+use crate::tools::traits::{Tool, ToolResult};
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use serde_json::json;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::Arc;
+
+const ALL_NAMESPACES: [MemoryNamespace; 4] = [
+    MemoryNamespace::Session,
+    MemoryNamespace::Project,
+    MemoryNamespace::User,
+    MemoryNamespace::Global,
+];
+
+/// Facts per namespace injected into an agent prompt.
+const CONTEXT_ENTRIES_PER_NAMESPACE: usize = 20;
+
+fn namespace_name(namespace: MemoryNamespace) -> &'static str {
+    match namespace {
+        MemoryNamespace::Session => "session",
+        MemoryNamespace::Project => "project",
+        MemoryNamespace::User => "user",
+        MemoryNamespace::Global => "global",
+    }
+}
+
+fn parse_namespace(name: &str) -> Option<MemoryNamespace> {
+    ALL_NAMESPACES
+        .into_iter()
+        .find(|ns| namespace_name(*ns) == name.trim().to_ascii_lowercase())
+}
+
+/// Built-in access for a role: everyone reads everything; stage agents
+/// write session and project facts, DevOps may also record global deploy
+/// knowledge, and only the orchestrator writes user preferences.
+pub fn default_access(role: AgentRole) -> MemoryNamespaceAccess {
+    use MemoryNamespace::{Global, Project, Session, User};
+    let write = match role {
+        AgentRole::Orchestrator => vec![Session, Project, User, Global],
+        AgentRole::DevOps => vec![Session, Project, Global],
+        AgentRole::BusinessAnalyst
+        | AgentRole::UiUxDesigner
+        | AgentRole::Developer
+        | AgentRole::Tester
+        | AgentRole::SecurityReviewer => vec![Session, Project],
+    };
+    MemoryNamespaceAccess {
+        read: ALL_NAMESPACES.to_vec(),
+        write,
+    }
+}
+
+/// A fact read from a namespace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SharedFact {
+    pub namespace: MemoryNamespace,
+    pub key: String,
+    pub content: String,
+    pub timestamp: String,
+}
+
+/// Namespaced, permission-checked view over the workspace memory for one
+/// factory run.
+pub struct SharedMemory {
+    memory: Arc<dyn Memory>,
+    session_id: String,
+    project: String,
+    user_id: Option<String>,
+    permissions: HashMap<String, MemoryNamespaceAccess>,
+}
+
+impl SharedMemory {
+    pub fn new(memory: Arc<dyn Memory>, session_id: impl Into<String>, project: &str) -> Self {
+        Self {
+            memory,
+            session_id: session_id.into(),
+            project: project_id(project),
+            user_id: None,
+            permissions: HashMap::new(),
+        }
+    }
+
+    /// Enable the `user` namespace for the requesting user.
+    pub fn with_user(mut self, user_id: Option<String>) -> Self {
+        self.user_id = user_id;
+        self
+    }
+
+    /// Per-role overrides of [`default_access`], keyed by role name.
+    pub fn with_permissions(mut self, permissions: HashMap<String, MemoryNamespaceAccess>) -> Self {
+        self.permissions = permissions;
+        self
+    }
+
+    pub fn project(&self) -> &str {
+        &self.project
+    }
+
+    pub fn access(&self, role: AgentRole) -> MemoryNamespaceAccess {
+        self.permissions
+            .get(&role.to_string())
+            .cloned()
+            .unwrap_or_else(|| default_access(role))
+    }
+
+    fn scope(&self, namespace: MemoryNamespace) -> Option<&str> {
+        match namespace {
+            MemoryNamespace::Session => Some(&self.session_id),
+            MemoryNamespace::Project => Some(&self.project),
+            MemoryNamespace::User => self.user_id.as_deref(),
+            MemoryNamespace::Global => Some("all"),
+        }
+    }
+
+    fn prefix(&self, namespace: MemoryNamespace) -> Option<String> {
+        self.scope(namespace)
+            .map(|scope| format!("factory:{}:{scope}:", namespace_name(namespace)))
+    }
+
+    fn category(namespace: MemoryNamespace) -> MemoryCategory {
+        MemoryCategory::Custom(format!("factory_{}", namespace_name(namespace)))
+    }
+
+    /// Write a fact, failing if `role` may not write `namespace`.
+    pub async fn remember(
+        &self,
+        role: AgentRole,
+        namespace: MemoryNamespace,
+        key: &str,
+        content: &str,
+    ) -> Result<()> {
+        if !self.access(role).write.contains(&namespace) {
+            bail!(
+                "{role} may not write the '{}' memory namespace",
+                namespace_name(namespace)
+            );
+        }
+        let Some(prefix) = self.prefix(namespace) else {
+            bail!("no user is associated with this build");
+        };
+        let key = key.trim();
+        if key.is_empty() {
+            bail!("memory key must not be empty");
+        }
+        without_memory_user(self.memory.store(
+            &format!("{prefix}{key}"),
+            content,
+            Self::category(namespace),
+            None,
+        ))
+        .await
+    }
+
+    /// Facts in `namespace` visible to `role`, newest first.
+    pub async fn facts(
+        &self,
+        role: AgentRole,
+        namespace: MemoryNamespace,
+    ) -> Result<Vec<SharedFact>> {
+        if !self.access(role).read.contains(&namespace) {
+            bail!(
+                "{role} may not read the '{}' memory namespace",
+                namespace_name(namespace)
+            );
+        }
+        let Some(prefix) = self.prefix(namespace) else {
+            return Ok(Vec::new());
+        };
+        let category = Self::category(namespace);
+        let entries = without_memory_user(self.memory.list(Some(&category), None)).await?;
+        let mut facts: Vec<SharedFact> = entries
+            .into_iter()
+            .filter_map(|entry| {
+                let key = entry.key.strip_prefix(&prefix)?.to_string();
+                Some(SharedFact {
+                    namespace,
+                    key,
+                    content: entry.content,
+                    timestamp: entry.timestamp,
+                })
+            })
+            .collect();
+        facts.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        Ok(facts)
+    }
+
+    /// Prompt preamble listing the facts `role` can read (empty if none).
+    pub async fn context_for(&self, role: AgentRole) -> String {
+        let mut out = String::new();
+        for namespace in self.access(role).read {
+            let facts = match self.facts(role, namespace).await {
+                Ok(facts) => facts,
+                Err(e) => {
+                    tracing::debug!("factory memory read skipped: {e}");
+                    continue;
+                }
+            };
+            for fact in facts.iter().take(CONTEXT_ENTRIES_PER_NAMESPACE) {
+                let _ = writeln!(
+                    out,
+                    "- [{}] {}: {}",
+                    namespace_name(namespace),
+                    fact.key,
+                    fact.content
+                );
+            }
+        }
+        if out.is_empty() {
+            return out;
+        }
+        format!(
+            "[Shared factory memory for project '{}']\n{out}\n",
+            self.project
+        )
+    }
+}
+
+/// `factory_memory` tool bound to one agent role.
+pub struct FactoryMemoryTool {
+    shared: Arc<SharedMemory>,
+    role: AgentRole,
+}
+
+impl FactoryMemoryTool {
+    pub fn new(shared: Arc<SharedMemory>, role: AgentRole) -> Self {
+        Self { shared, role }
+    }
+}
+
+#[async_trait]
+impl Tool for FactoryMemoryTool {
+    fn name(&self) -> &str {
+        "factory_memory"
+    }
+
+    fn description(&self) -> &str {
+        "Share facts with other factory agents and future builds. 'remember' stores a fact in a namespace \
+         (session: this build, project: all builds of this project, user: this user's builds, global: every build); \
+         'list' shows facts in a namespace. Record quirks, workarounds and decisions later stages need."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["remember", "list"]
+                },
+                "namespace": {
+                    "type": "string",
+                    "enum": ["session", "project", "user", "global"],
+                    "description": "Namespace to write or read (default: project)"
+                },
+                "key": {
+                    "type": "string",
+                    "description": "Short identifier for the fact (remember only)"
+                },
+                "content": {
+                    "type": "string",
+                    "description": "The fact to store (remember only)"
+                }
+            },
+            "required": ["action"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let action = args
+            .get("action")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'action' parameter"))?;
+        let namespace_arg = args
+            .get("namespace")
+            .and_then(|v| v.as_str())
+            .unwrap_or("project");
+        let Some(namespace) = parse_namespace(namespace_arg) else {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(format!("Unknown namespace '{namespace_arg}'")),
+                error_code: None,
+                error_hint: None,
+            });
+        };
+
+        let result = match action {
+            "remember" => {
+                let key = args.get("key").and_then(|v| v.as_str()).unwrap_or("");
+                let content = args.get("content").and_then(|v| v.as_str()).unwrap_or("");
+                if content.trim().is_empty() {
+                    return Ok(ToolResult {
+                        success: false,
+                        output: String::new(),
+                        error: Some("'content' must not be empty".into()),
+                        error_code: None,
+                        error_hint: None,
+                    });
+                }
+                self.shared
+                    .remember(self.role, namespace, key, content)
+                    .await
+                    .map(|()| format!("Remembered '{key}' in {namespace_arg} memory."))
+            }
+            "list" => self.shared.facts(self.role, namespace).await.map(|facts| {
+                if facts.is_empty() {
+                    return format!("No facts in {namespace_arg} memory.");
+                }
+                let mut out = format!("{} facts in {namespace_arg} memory:\n", facts.len());
+                for fact in facts {
+                    let _ = writeln!(out, "- {}: {}", fact.key, fact.content);
+                }
+                out
+            }),
+            other => Err(anyhow::anyhow!("Unknown action '{other}'")),
+        };
+
+        Ok(match result {
+            Ok(output) => ToolResult {
+                success: true,
+                output,
+                error: None,
+                error_code: None,
+                error_hint: None,
+            },
+            Err(e) => ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(e.to_string()),
+                error_code: None,
+                error_hint: None,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::SqliteMemory;
+    use tempfile::TempDir;
+
+    fn shared(memory: Arc<dyn Memory>, session: &str) -> SharedMemory {
+        SharedMemory::new(memory, session, "Todo App").with_user(Some("alice".into()))
+    }
+
+    #[tokio::test]
+    async fn project_facts_outlive_the_session_but_session_facts_do_not() {
+        let tmp = TempDir::new().unwrap();
+        let memory: Arc<dyn Memory> = Arc::new(SqliteMemory::new(tmp.path()).unwrap());
+
+        let first = shared(memory.clone(), "run-1");
+        first
+            .remember(
+                AgentRole::Tester,
+                MemoryNamespace::Project,
+                "jest_esm",
+                "jest needs --experimental-vm-modules",
+            )
+            .await
+            .unwrap();
+        first
+            .remember(
+                AgentRole::Tester,
+                MemoryNamespace::Session,
+                "flaky",
+                "login test",
+            )
+            .await
+            .unwrap();
+
+        let second = shared(memory, "run-2");
+        let context = second.context_for(AgentRole::DevOps).await;
+        assert!(context.contains("project 'todo-app'"));
+        assert!(context.contains("jest_esm"));
+        assert!(!context.contains("flaky"));
+    }
+
+    #[tokio::test]
+    async fn permissions_are_enforced_and_overridable() {
+        let tmp = TempDir::new().unwrap();
+        let memory: Arc<dyn Memory> = Arc::new(SqliteMemory::new(tmp.path()).unwrap());
+
+        let mem = shared(memory.clone(), "run-1");
+        assert!(mem
+            .remember(AgentRole::Tester, MemoryNamespace::Global, "k", "v")
+            .await
+            .is_err());
+        assert!(mem
+            .remember(AgentRole::Orchestrator, MemoryNamespace::User, "pm", "pnpm")
+            .await
+            .is_ok());
+
+        let locked = shared(memory, "run-1").with_permissions(HashMap::from([(
+            "developer".to_string(),
+            MemoryNamespaceAccess {
+                read: vec![MemoryNamespace::Session],
+                write: Vec::new(),
+            },
+        )]));
+        assert!(locked
+            .facts(AgentRole::Developer, MemoryNamespace::User)
+            .await
+            .is_err());
+        assert!(locked.context_for(AgentRole::Developer).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn tool_remembers_and_lists() {
+        let tmp = TempDir::new().unwrap();
+        let memory: Arc<dyn Memory> = Arc::new(SqliteMemory::new(tmp.path()).unwrap());
+        let tool = FactoryMemoryTool::new(Arc::new(shared(memory, "run-1")), AgentRole::Developer);
+
+        let stored = tool
+            .execute(json!({"action": "remember", "key": "orm", "content": "use drizzle"}))
+            .await
+            .unwrap();
+        assert!(stored.success, "{:?}", stored.error);
+
+        let listed = tool.execute(json!({"action": "list"})).await.unwrap();
+        assert!(listed.output.contains("orm: use drizzle"));
+
+        let denied = tool
+            .execute(
+                json!({"action": "remember", "namespace": "global", "key": "x", "content": "y"}),
+            )
+            .await
+            .unwrap();
+        assert!(!denied.success);
+    }
+}
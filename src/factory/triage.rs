@@ -0,0 +1,355 @@
+//! Issue triage for existing repositories (`[factory.triage]`).
+//!
+//! The watcher polls the listed repositories for issues opened since it
+//! started watching them. A model classifies each new issue as a bug, a
+//! feature request or a question and answers with hashtags, which become
+//! labels the way `github_create_issue_with_hashtags` extracts them. Bug
+//! reports that lack what it takes to reproduce them get a comment asking
+//! for it. With `draft_fixes`, complete bug reports with a trivially-scoped
+//! fix get the `[factory.issues]` label, so the issue-to-build trigger
+//! ([`super::issues`]) drafts the fix in a sandbox and opens a pull request.
+//!
+//! Pull requests and issues that already carry labels are left alone. Each
+//! issue is triaged once.
+
+use crate::config::{Config, FactoryTriageConfig};
+use crate::providers::Provider;
+use crate::store::{self, triage::TriagedIssue};
+use crate::tools::github_access::RepoAccess;
+use crate::tools::github_ops::{extract_hashtags, sanitize_labels};
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::json;
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+
+const GITHUB_API_BASE: &str = "https://api.github.com";
+
+/// Issues triaged per repository and poll.
+const MAX_ISSUES_PER_POLL: usize = 10;
+
+/// Characters of the issue body the model sees.
+const MAX_BODY_CHARS: usize = 6000;
+
+const TRIAGE_SYSTEM_PROMPT: &str = "You triage new GitHub issues. Classify the issue as \
+a bug report, a feature request or a question, and answer with JSON only:\n\
+{\"kind\": \"bug\" | \"feature\" | \"question\", \
+\"hashtags\": \"#bug #ui\", \
+\"missing\": [\"...\"], \
+\"trivial\": false}\n\n\
+- hashtags: the type hashtag (#bug, #feature, #docs, #perf, #security, ...) followed by \
+scope hashtags the issue clearly touches (#ui, #api, #database, #auth, ...).\n\
+- missing: for bug reports only, what a maintainer needs to reproduce the bug and the \
+report does not give: steps to reproduce, expected and actual behavior, version, \
+environment, error output. Empty when the report is complete or not a bug.\n\
+- trivial: true only for a bug whose fix is obviously small and local, such as a typo, \
+a wrong constant or a missing null check.";
+
+/// What the model made of an issue.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Classification {
+    pub kind: IssueKind,
+    #[serde(default)]
+    pub hashtags: String,
+    #[serde(default)]
+    pub missing: Vec<String>,
+    #[serde(default)]
+    pub trivial: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IssueKind {
+    Bug,
+    Feature,
+    Question,
+}
+
+impl IssueKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Bug => "bug",
+            Self::Feature => "feature",
+            Self::Question => "question",
+        }
+    }
+}
+
+/// The classification in a model reply, which may wrap the JSON in prose or
+/// a code fence.
+pub fn parse_classification(reply: &str) -> Option<Classification> {
+    let start = reply.find('{')?;
+    let end = reply.rfind('}')?;
+    serde_json::from_str(reply.get(start..=end)?).ok()
+}
+
+/// Labels for a classification: the kind first, then the hashtags that
+/// survive the label rules of the issue tools.
+pub fn labels_for(classification: &Classification) -> Vec<String> {
+    let mut labels = vec![classification.kind.as_str().to_string()];
+    for label in sanitize_labels(&extract_hashtags(&classification.hashtags)) {
+        if !labels.contains(&label) {
+            labels.push(label);
+        }
+    }
+    labels
+}
+
+/// Comment asking the reporter of a bug for what is `missing`.
+fn details_comment(missing: &[String]) -> String {
+    let mut comment = String::from(
+        "👋 Thanks for the report! To reproduce the problem we need a bit more information:\n\n",
+    );
+    for item in missing {
+        let _ = writeln!(comment, "- {}", item.trim());
+    }
+    comment.push_str("\n_Triaged automatically by ZeroBuild._");
+    comment
+}
+
+/// Polls repositories for new issues and triages them.
+pub struct TriageWatcher {
+    config: FactoryTriageConfig,
+    /// `[factory.issues]` label that turns an issue into a build.
+    build_label: String,
+    access: RepoAccess,
+    db_path: PathBuf,
+    provider: Box<dyn Provider>,
+    model: String,
+    client: reqwest::Client,
+}
+
+impl TriageWatcher {
+    pub fn new(config: &Config) -> anyhow::Result<Self> {
+        let provider_name = config.default_provider.as_deref().unwrap_or("openrouter");
+        let model = config
+            .factory
+            .triage
+            .model
+            .clone()
+            .or_else(|| config.default_model.clone())
+            .unwrap_or_else(|| "anthropic/claude-sonnet-4".into());
+        let provider = crate::providers::create_routed_provider(
+            provider_name,
+            config.api_key.as_deref(),
+            config.api_url.as_deref(),
+            &config.reliability,
+            &crate::providers::effective_model_routes(config),
+            &model,
+        )?;
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .user_agent("ZeroBuild/0.1")
+            .build()?;
+        Ok(Self {
+            config: config.factory.triage.clone(),
+            build_label: config.factory.issues.label.clone(),
+            access: RepoAccess::from_config(&config.zerobuild.repos),
+            db_path: PathBuf::from(&config.zerobuild.db_path),
+            provider,
+            model,
+            client,
+        })
+    }
+
+    async fn poll(&self) -> anyhow::Result<()> {
+        let Some(token) = store::tokens::load_github_token(&store::init_db(&self.db_path)?)? else {
+            tracing::debug!("Issue triage idle: GitHub is not connected");
+            return Ok(());
+        };
+        for repo in &self.config.repos {
+            let (owner, name) = repo.split_once('/').unwrap_or((repo, ""));
+            if let Err(reason) = self.access.check(owner, Some(name)) {
+                tracing::warn!(repo, "Issue triage skipped: {reason}");
+                continue;
+            }
+            if let Err(e) = self.triage_repo(&token.token, repo).await {
+                tracing::warn!(repo, "Failed to triage new issues: {e:#}");
+            }
+        }
+        Ok(())
+    }
+
+    /// Triage the issues of `repo` opened since it is watched.
+    async fn triage_repo(&self, token: &str, repo: &str) -> anyhow::Result<()> {
+        let since = store::triage::watched_since(&store::init_db(&self.db_path)?, repo)?;
+        let watched_since = DateTime::parse_from_rfc3339(&since)?.with_timezone(&Utc);
+        let url = format!("{GITHUB_API_BASE}/repos/{repo}/issues");
+        let listed: Vec<serde_json::Value> = self
+            .request(reqwest::Method::GET, &url, token)
+            .query(&[
+                ("state", "open"),
+                ("since", since.as_str()),
+                ("sort", "created"),
+                ("direction", "asc"),
+                ("per_page", "50"),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let mut triaged = 0;
+        for issue in listed.iter().filter(|i| i.get("pull_request").is_none()) {
+            let Some(number) = issue["number"].as_u64() else {
+                continue;
+            };
+            let opened = issue["created_at"]
+                .as_str()
+                .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+                .is_some_and(|at| at >= watched_since);
+            let conn = store::init_db(&self.db_path)?;
+            if !opened || store::triage::is_triaged(&conn, repo, number)? {
+                continue;
+            }
+            if triaged == MAX_ISSUES_PER_POLL {
+                break;
+            }
+            triaged += 1;
+            let outcome = self.triage(token, repo, number, issue).await?;
+            store::triage::record(&conn, &outcome)?;
+            tracing::info!(
+                repo,
+                number,
+                kind = %outcome.kind,
+                labels = %outcome.labels,
+                asked = outcome.asked,
+                drafted = outcome.drafted,
+                "Triaged issue"
+            );
+        }
+        Ok(())
+    }
+
+    async fn triage(
+        &self,
+        token: &str,
+        repo: &str,
+        number: u64,
+        issue: &serde_json::Value,
+    ) -> anyhow::Result<TriagedIssue> {
+        let mut outcome = TriagedIssue {
+            repo: repo.to_string(),
+            number,
+            kind: "skipped".into(),
+            ..TriagedIssue::default()
+        };
+        if issue["labels"].as_array().is_some_and(|l| !l.is_empty()) {
+            return Ok(outcome);
+        }
+
+        let body: String = issue["body"]
+            .as_str()
+            .unwrap_or_default()
+            .chars()
+            .take(MAX_BODY_CHARS)
+            .collect();
+        let prompt = format!(
+            "Repository: {repo}\nTitle: {}\n\n{body}",
+            issue["title"].as_str().unwrap_or_default()
+        );
+        let reply = self
+            .provider
+            .chat_with_system(Some(TRIAGE_SYSTEM_PROMPT), &prompt, &self.model, 0.0)
+            .await?;
+        let classification = parse_classification(&reply)
+            .with_context(|| format!("unreadable classification: {reply}"))?;
+
+        let issue_url = format!("{GITHUB_API_BASE}/repos/{repo}/issues/{number}");
+        let labels = labels_for(&classification);
+        self.post(
+            token,
+            &format!("{issue_url}/labels"),
+            json!({"labels": labels}),
+        )
+        .await?;
+        outcome.kind = classification.kind.as_str().to_string();
+        outcome.labels = labels.join(",");
+
+        let is_bug = classification.kind == IssueKind::Bug;
+        if is_bug && self.config.ask_for_details && !classification.missing.is_empty() {
+            self.post(
+                token,
+                &format!("{issue_url}/comments"),
+                json!({"body": details_comment(&classification.missing)}),
+            )
+            .await?;
+            outcome.asked = true;
+        }
+        if is_bug
+            && self.config.draft_fixes
+            && classification.trivial
+            && classification.missing.is_empty()
+        {
+            self.post(
+                token,
+                &format!("{issue_url}/labels"),
+                json!({"labels": [self.build_label]}),
+            )
+            .await?;
+            outcome.drafted = true;
+        }
+        Ok(outcome)
+    }
+
+    async fn post(&self, token: &str, url: &str, body: serde_json::Value) -> anyhow::Result<()> {
+        self.request(reqwest::Method::POST, url, token)
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    fn request(&self, method: reqwest::Method, url: &str, token: &str) -> reqwest::RequestBuilder {
+        self.client
+            .request(method, url)
+            .header("Authorization", format!("Bearer {token}"))
+            .header("Accept", "application/vnd.github+json")
+    }
+}
+
+/// Triage new issues every `[factory.triage] poll_secs`.
+pub async fn run_triage_worker(config: Config) -> anyhow::Result<()> {
+    let watcher = TriageWatcher::new(&config)?;
+    tracing::info!(repos = ?watcher.config.repos, "Issue triage started");
+    let mut ticker = tokio::time::interval(Duration::from_secs(watcher.config.poll_secs.max(10)));
+    loop {
+        ticker.tick().await;
+        if let Err(e) = watcher.poll().await {
+            tracing::warn!("Issue triage poll failed: {e:#}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classification_is_read_from_fenced_replies() {
+        let reply = "Here you go:\n```json\n{\"kind\": \"bug\", \"hashtags\": \"#bug #ui\", \
+                     \"missing\": [\"steps to reproduce\"], \"trivial\": false}\n```";
+        let classification = parse_classification(reply).unwrap();
+        assert_eq!(classification.kind, IssueKind::Bug);
+        assert_eq!(classification.missing, ["steps to reproduce"]);
+        assert!(parse_classification("It's a bug.").is_none());
+        assert!(parse_classification("{\"kind\": \"rant\"}").is_none());
+    }
+
+    #[test]
+    fn labels_start_with_the_kind_and_follow_the_hashtag_rules() {
+        let classification = Classification {
+            kind: IssueKind::Question,
+            hashtags: "#docs, #api and #docs again".into(),
+            missing: Vec::new(),
+            trivial: false,
+        };
+        assert_eq!(labels_for(&classification), ["question", "docs", "api"]);
+        let comment = details_comment(&["Steps to reproduce".into(), " Browser version ".into()]);
+        assert!(comment.contains("- Steps to reproduce\n- Browser version\n"));
+    }
+}
@@ -1,7 +1,8 @@
 //! Workflow state machine for the multi-agent factory.
 //!
 //! Defines the phased execution model: Analysis → ParallelBuild →
-//! IntegrationLoop → Deployment → Completed/Failed.
+//! IntegrationLoop → AccessibilityAudit → SecurityReview → Deployment →
+//! Completed/Failed.
 //!
 //! Features:
 //! - Intent classification for dynamic agent spawning
@@ -10,24 +11,57 @@
 //! - Optional learning from past builds
 //! - Optional sub-agent spawning for complex tasks
 
-use super::blackboard::{Artifact, Blackboard};
+use super::accessibility;
+use super::blackboard::{Artifact, Blackboard, BlackboardJournal};
+use super::distributed::StageDispatch;
+use super::estimate::{self, BuildEstimate, MeteredUsage, PlannedCall, UsageMeter};
 use super::pool::{AgentPool, PoolConfig};
 use super::progress::{
     AgentStatus, ProgressBroadcaster, ProgressTracker, TestStatus, WorkflowCompletionStatus,
 };
 use super::roles::{AgentRole, RoleConfig};
+use super::security::{self, SecurityReport};
+use super::shared_memory::{FactoryMemoryTool, SharedMemory};
 use super::workspace::{AgentConfig as WorkspaceAgentConfig, WorkspaceConfig, WorkspaceManager};
-use crate::config::DelegateAgentConfig;
-use crate::providers::{self, Provider};
+use crate::agent::prompt_templates::{PromptTemplates, PromptVars};
+use crate::config::{DelegateAgentConfig, FactoryBusConfig, FactoryStagesConfig, ModelPricing};
+use crate::coordination::bus::CoordinationBus;
+use crate::multimodal;
+use crate::providers::sampling::{self, SamplingParams};
+use crate::providers::{self, ChatMessage, Provider};
+use crate::sandbox::docs::DocStatus;
+use crate::sandbox::project_config::{ProjectConfig, CONFIG_FILE, PROJECT_DIR};
+use crate::sandbox::SandboxClient;
+use crate::store::build_usage::BuildUsage;
+use crate::tools::progress::{report_build_status, BuildStatus};
 use crate::tools::traits::Tool;
 use anyhow::{bail, Result};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use uuid::Uuid;
 
+/// Maximum UI/UX review rounds against user-provided mockups.
+const DESIGN_REVIEW_ROUNDS: usize = 2;
+
+/// Maximum accessibility audit rounds, each followed by a developer fix
+/// round while violations remain.
+const ACCESSIBILITY_AUDIT_ROUNDS: usize = 2;
+
+/// Port of the dev server the accessibility audit opens.
+const PREVIEW_PORT: u16 = 3000;
+
+/// Maximum security review rounds, each followed by a developer fix round
+/// while checklist items fail.
+const SECURITY_REVIEW_ROUNDS: usize = 2;
+
+/// Tools the UI/UX designer may use to render the app during design review.
+const DESIGN_REVIEW_TOOLS: &[&str] = &["sandbox_get_preview_url", "browser", "screenshot"];
+
 /// Workflow phases in the factory pipeline.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -40,6 +74,10 @@ pub enum WorkflowPhase {
     ParallelBuild,
     /// Developer-Tester ping-pong loop until tests pass.
     IntegrationLoop,
+    /// axe-core audit of the running app, with developer fixes.
+    AccessibilityAudit,
+    /// Security reviewer checks the project before deployment.
+    SecurityReview,
     /// DevOps agent deploys the project.
     Deployment,
     /// Workflow completed successfully.
@@ -56,6 +94,8 @@ pub struct FactoryWorkflow {
     workflow_id: Uuid,
     blackboard: Blackboard,
     idea: String,
+    /// Reference images (`[IMAGE:...]` markers) attached to the idea.
+    mockups: Vec<String>,
     max_ping_pong: usize,
     phase: WorkflowPhase,
     // Dynamic spawning flags (set by LLM analysis)
@@ -75,6 +115,20 @@ pub struct FactoryWorkflow {
     workspace_manager: Option<Arc<WorkspaceManager>>,
     agent_pool: Option<AgentPool>,
     use_workspace_isolation: bool,
+    prompt_templates: Option<PromptTemplates>,
+    shared_memory: Option<Arc<SharedMemory>>,
+    stages: FactoryStagesConfig,
+    /// Sandbox holding the project, for its `.zerobuild.toml`.
+    sandbox: Option<Arc<dyn SandboxClient>>,
+    /// Model prices for the cost estimate and the usage meter.
+    pricing: HashMap<String, ModelPricing>,
+    /// Tokens the agents used so far.
+    usage: Arc<UsageMeter>,
+    /// Estimate made before the run, compared with the actuals at the end.
+    estimate: Option<BuildEstimate>,
+    started: Option<Instant>,
+    /// Completion stages go to the shared stage queue when set.
+    stage_dispatch: Option<StageDispatch>,
 }
 
 impl FactoryWorkflow {
@@ -94,11 +148,16 @@ impl FactoryWorkflow {
     ) -> Self {
         let broadcaster = ProgressBroadcaster::new();
         let progress = ProgressTracker::new(broadcaster);
+        let (mut idea, mockups) = multimodal::parse_image_markers(&idea);
+        if idea.is_empty() && !mockups.is_empty() {
+            idea = "Recreate the design shown in the attached mockup.".into();
+        }
 
         Self {
             workflow_id: Uuid::new_v4(),
             blackboard: Blackboard::new(),
             idea,
+            mockups,
             max_ping_pong,
             spawn_ui_ux: true,
             spawn_devops: true,
@@ -116,7 +175,154 @@ impl FactoryWorkflow {
             workspace_manager: None,
             agent_pool: None,
             use_workspace_isolation: false,
+            prompt_templates: None,
+            shared_memory: None,
+            stages: FactoryStagesConfig::default(),
+            sandbox: None,
+            pricing: HashMap::new(),
+            usage: Arc::new(UsageMeter::default()),
+            estimate: None,
+            started: None,
+            stage_dispatch: None,
+        }
+    }
+
+    /// Keep the blackboard on a shared `bus`, and send completion stages to
+    /// its stage workers when `[factory.bus] distribute_stages` is set.
+    pub fn with_bus(mut self, bus: Arc<dyn CoordinationBus>, config: &FactoryBusConfig) -> Self {
+        let blackboard = Blackboard::with_bus(Arc::clone(&bus), &self.workflow_id.to_string());
+        self.blackboard = match self.blackboard.journal() {
+            Some(journal) => blackboard.with_journal(Arc::clone(journal)),
+            None => blackboard,
+        };
+        if config.distribute_stages {
+            self.stage_dispatch = Some(StageDispatch::new(
+                bus,
+                Duration::from_secs(config.stage_timeout_secs),
+            ));
+        }
+        self
+    }
+
+    /// Let `prompts/<role>.md` templates override the built-in stage prompts.
+    pub fn with_prompt_templates(mut self, templates: PromptTemplates) -> Self {
+        self.prompt_templates = Some(templates);
+        self
+    }
+
+    /// Journal the blackboard in the store at `db_path`, for
+    /// `blackboard_history`.
+    pub fn with_journal(mut self, db_path: impl Into<PathBuf>) -> Self {
+        let journal = BlackboardJournal::start(db_path, &self.workflow_id.to_string(), &self.idea);
+        self.blackboard = self.blackboard.clone().with_journal(Arc::new(journal));
+        self
+    }
+
+    /// ID of this build, as shown by `blackboard_history`.
+    pub fn id(&self) -> Uuid {
+        self.workflow_id
+    }
+
+    /// Move to `phase`, marking it in the blackboard journal.
+    fn enter_phase(&mut self, phase: WorkflowPhase) {
+        self.phase = phase;
+        if let Ok(serde_json::Value::String(name)) = serde_json::to_value(phase) {
+            self.blackboard.mark_phase(&name);
+        }
+    }
+
+    /// Give agents namespaced memory that outlives this run.
+    pub fn with_shared_memory(mut self, shared: Arc<SharedMemory>) -> Self {
+        self.shared_memory = Some(shared);
+        self
+    }
+
+    /// Select which optional pipeline stages run.
+    pub fn with_stages(mut self, stages: FactoryStagesConfig) -> Self {
+        self.spawn_devops &= stages.deployment;
+        self.stages = stages;
+        self
+    }
+
+    /// Follow the `.zerobuild.toml` of the project in `sandbox` in the
+    /// Developer, Tester and DevOps stages.
+    pub fn with_sandbox(mut self, sandbox: Arc<dyn SandboxClient>) -> Self {
+        self.sandbox = Some(sandbox);
+        self
+    }
+
+    /// Price agent calls with `prices` (USD per 1M tokens, keyed by
+    /// `provider/model` or model name).
+    pub fn with_pricing(mut self, prices: HashMap<String, ModelPricing>) -> Self {
+        self.usage = Arc::new(UsageMeter::new(prices.clone()));
+        self.pricing = prices;
+        self
+    }
+
+    /// Report `estimate` next to the actual usage in the final summary.
+    pub fn with_estimate(mut self, estimate: BuildEstimate) -> Self {
+        self.estimate = Some(estimate);
+        self
+    }
+
+    /// Estimate tokens, wall time and cost of running this workflow,
+    /// calibrated by the most recent finished builds in `history`.
+    pub fn estimate(&self, history: &[BuildUsage]) -> BuildEstimate {
+        estimate::estimate(
+            self.idea.len(),
+            &self.planned_calls(),
+            &self.pricing,
+            history,
+        )
+    }
+
+    /// Tokens, calls and cost the agents used so far.
+    pub fn usage(&self) -> MeteredUsage {
+        self.usage.usage()
+    }
+
+    /// The agent calls a run is expected to make: every enabled stage once,
+    /// and two rounds of the integration loop.
+    fn planned_calls(&self) -> Vec<PlannedCall> {
+        let call = |role: AgentRole, agentic: bool, concurrent: bool| {
+            let config = self.resolve_config(role);
+            PlannedCall {
+                role,
+                model: format!("{}/{}", config.provider, config.model),
+                agentic: agentic && !config.allowed_tools.is_empty(),
+                concurrent,
+            }
+        };
+
+        let mut plan = Vec::new();
+        if self.enable_dynamic_spawning {
+            plan.push(call(AgentRole::Orchestrator, false, false));
+        }
+        plan.push(call(AgentRole::BusinessAnalyst, false, false));
+        plan.push(call(AgentRole::Developer, true, false));
+        if self.spawn_ui_ux {
+            plan.push(call(AgentRole::UiUxDesigner, false, true));
         }
+        plan.push(call(AgentRole::Tester, false, true));
+        if self.stages.integration_loop {
+            let rounds = self.max_ping_pong.min(2);
+            for round in 1..=rounds {
+                plan.push(call(AgentRole::Tester, true, false));
+                if round < rounds {
+                    plan.push(call(AgentRole::Developer, true, false));
+                }
+            }
+        }
+        if self.stages.accessibility {
+            plan.push(call(AgentRole::Developer, true, false));
+        }
+        if self.stages.security_review {
+            plan.push(call(AgentRole::SecurityReviewer, true, false));
+        }
+        if self.spawn_devops {
+            plan.push(call(AgentRole::DevOps, true, false));
+        }
+        plan
     }
 
     /// Enable workspace isolation for this workflow
@@ -149,6 +355,8 @@ impl FactoryWorkflow {
 
     /// Execute the full factory workflow, returning a summary of the result.
     pub async fn run(&mut self) -> Result<String> {
+        self.started = Some(Instant::now());
+
         // Start workflow
         if self.enable_streaming {
             self.progress.workflow_started();
@@ -163,8 +371,32 @@ impl FactoryWorkflow {
         // Phase 1-4: Execute main workflow
         self.execute_phase_1_analysis().await?;
         self.execute_phase_2_parallel_build().await?;
-        self.execute_phase_3_integration_loop().await?;
-        let result = self.execute_phase_4_deployment().await;
+        if let Some(sandbox) = &self.sandbox {
+            if let Ok(url) = sandbox.get_preview_url(PREVIEW_PORT).await {
+                report_build_status(BuildStatus::Preview(url));
+            }
+        }
+        if self.stages.integration_loop {
+            self.execute_phase_3_integration_loop().await?;
+        }
+        if self.spawn_ui_ux && !self.mockups.is_empty() {
+            self.review_design_against_mockups().await?;
+        }
+        if self.stages.accessibility {
+            self.execute_accessibility_audit().await?;
+        }
+        if self.stages.docs {
+            self.generate_docs().await;
+        }
+        if self.stages.security_review {
+            self.execute_security_review().await?;
+        }
+        let result = if self.stages.deployment {
+            self.execute_phase_4_deployment().await
+        } else {
+            self.enter_phase(WorkflowPhase::Completed);
+            self.summary("skipped (disabled for this session)").await
+        };
 
         // Complete workflow
         if self.enable_streaming {
@@ -236,7 +468,7 @@ impl FactoryWorkflow {
 
     /// Phase 0: Intent Analysis using LLM
     async fn classify_intent(&mut self) -> Result<()> {
-        self.phase = WorkflowPhase::IntentAnalysis;
+        self.enter_phase(WorkflowPhase::IntentAnalysis);
 
         if !self.enable_dynamic_spawning {
             return Ok(());
@@ -288,8 +520,8 @@ Rules:
                 .contains("\"needs_backend\": true");
 
         // Store classification for later phases
-        self.spawn_ui_ux = needs_ui_ux;
-        self.spawn_devops = needs_devops;
+        self.spawn_ui_ux = needs_ui_ux || !self.mockups.is_empty();
+        self.spawn_devops = needs_devops && self.stages.deployment;
 
         if self.enable_streaming {
             self.progress.progress_update(
@@ -307,7 +539,7 @@ Rules:
 
     /// Phase 1: Analysis
     async fn execute_phase_1_analysis(&mut self) -> Result<()> {
-        self.phase = WorkflowPhase::Analysis;
+        self.enter_phase(WorkflowPhase::Analysis);
 
         if self.enable_streaming {
             self.progress
@@ -339,8 +571,14 @@ Rules:
             .run_agent_simple(
                 AgentRole::BusinessAnalyst,
                 &format!(
-                    "Analyze the following project idea and produce a comprehensive PRD:\n\n{}",
-                    self.idea
+                    "Analyze the following project idea and produce a comprehensive PRD:\n\n{}{}",
+                    self.idea,
+                    if self.mockups.is_empty() {
+                        ""
+                    } else {
+                        "\n\nThe user attached reference mockups; the UI/UX designer will \
+                         match the visual design to them."
+                    }
                 ),
             )
             .await?;
@@ -354,7 +592,8 @@ Rules:
         }
 
         self.blackboard
-            .publish_artifact(Artifact::Prd, json!(prd), "business_analyst");
+            .publish_artifact(Artifact::Prd, json!(prd), "business_analyst")
+            .await?;
 
         if self.enable_streaming {
             self.progress.phase_completed(WorkflowPhase::Analysis);
@@ -365,7 +604,7 @@ Rules:
 
     /// Phase 2: Parallel Build
     async fn execute_phase_2_parallel_build(&mut self) -> Result<()> {
-        self.phase = WorkflowPhase::ParallelBuild;
+        self.enter_phase(WorkflowPhase::ParallelBuild);
 
         if self.enable_streaming {
             self.progress.phase_started(
@@ -380,10 +619,21 @@ Rules:
         let prd = self
             .blackboard
             .read_artifact(&Artifact::Prd)
+            .await?
             .unwrap_or(json!(""))
             .to_string();
 
-        let design_prompt = format!("Based on this PRD, produce a design specification:\n\n{prd}");
+        let mut design_prompt =
+            format!("Based on this PRD, produce a design specification:\n\n{prd}");
+        if !self.mockups.is_empty() {
+            let _ = write!(
+                design_prompt,
+                "\n\nReference mockups from the user. Match their layout, spacing, colors \
+                 and typography, and describe them precisely so the developer can \
+                 reproduce them:\n{}",
+                self.mockup_markers()
+            );
+        }
         let dev_prompt = format!("Based on this PRD, implement the project:\n\n{prd}");
         let test_prompt = format!("Based on this PRD, write comprehensive test cases:\n\n{prd}");
 
@@ -425,11 +675,9 @@ Rules:
         // Process results
         if spawn_ui_ux {
             let design_spec = design_result?;
-            self.blackboard.publish_artifact(
-                Artifact::DesignSpec,
-                json!(design_spec),
-                "ui_ux_designer",
-            );
+            self.blackboard
+                .publish_artifact(Artifact::DesignSpec, json!(design_spec), "ui_ux_designer")
+                .await?;
             if self.enable_streaming {
                 self.progress.agent_completed(
                     AgentRole::UiUxDesigner,
@@ -441,7 +689,8 @@ Rules:
 
         let dev_output = dev_result?;
         self.blackboard
-            .publish_artifact(Artifact::SourceCode, json!(dev_output), "developer");
+            .publish_artifact(Artifact::SourceCode, json!(dev_output), "developer")
+            .await?;
         if self.enable_streaming {
             self.progress.agent_completed(
                 AgentRole::Developer,
@@ -452,7 +701,8 @@ Rules:
 
         let test_cases = test_result?;
         self.blackboard
-            .publish_artifact(Artifact::TestCases, json!(test_cases), "tester");
+            .publish_artifact(Artifact::TestCases, json!(test_cases), "tester")
+            .await?;
         if self.enable_streaming {
             self.progress.agent_completed(
                 AgentRole::Tester,
@@ -472,7 +722,7 @@ Rules:
 
     /// Phase 3: Integration Loop
     async fn execute_phase_3_integration_loop(&mut self) -> Result<()> {
-        self.phase = WorkflowPhase::IntegrationLoop;
+        self.enter_phase(WorkflowPhase::IntegrationLoop);
 
         if self.enable_streaming {
             self.progress.phase_started(
@@ -484,6 +734,7 @@ Rules:
         let test_cases = self
             .blackboard
             .read_artifact(&Artifact::TestCases)
+            .await?
             .unwrap_or(json!(""))
             .to_string();
 
@@ -527,7 +778,8 @@ Rules:
             }
 
             self.blackboard
-                .publish_artifact(Artifact::TestResults, json!(test_result), "tester");
+                .publish_artifact(Artifact::TestResults, json!(test_result), "tester")
+                .await?;
 
             if test_result.to_uppercase().contains("ALL TESTS PASSED") {
                 if self.enable_streaming {
@@ -567,11 +819,9 @@ Rules:
                 let fix_output = self
                     .run_agent_agentic(AgentRole::Developer, &fix_prompt)
                     .await?;
-                self.blackboard.publish_artifact(
-                    Artifact::SourceCode,
-                    json!(fix_output),
-                    "developer",
-                );
+                self.blackboard
+                    .publish_artifact(Artifact::SourceCode, json!(fix_output), "developer")
+                    .await?;
 
                 if self.enable_streaming {
                     self.progress.agent_completed(
@@ -595,9 +845,355 @@ Rules:
         Ok(())
     }
 
+    /// Compare the rendered app with the user's mockups and let the
+    /// developer close the gaps, for up to [`DESIGN_REVIEW_ROUNDS`] rounds.
+    ///
+    /// Skipped when the UI/UX designer's model has no vision input.
+    async fn review_design_against_mockups(&mut self) -> Result<()> {
+        let mut config = self.resolve_config(AgentRole::UiUxDesigner);
+        if !self.create_provider(&config)?.supports_vision() {
+            tracing::warn!(
+                provider = %config.provider,
+                "Skipping mockup design review: UI/UX designer model has no vision input"
+            );
+            return Ok(());
+        }
+        config.allowed_tools = DESIGN_REVIEW_TOOLS
+            .iter()
+            .filter(|name| self.parent_tools.iter().any(|tool| tool.name() == **name))
+            .map(ToString::to_string)
+            .collect();
+        config.agentic = !config.allowed_tools.is_empty();
+
+        for round in 1..=DESIGN_REVIEW_ROUNDS {
+            if self.enable_streaming {
+                self.progress.agent_started(
+                    AgentRole::UiUxDesigner,
+                    format!("Comparing render against mockups (round {round})"),
+                );
+            }
+
+            let render_step = if config.agentic {
+                "Get the app's preview URL with sandbox_get_preview_url, open it with the \
+                 browser tool and take a screenshot of each page in the mockups."
+            } else {
+                "No rendering tools are available, so review the current source code \
+                 instead of a screenshot."
+            };
+            let source = self
+                .blackboard
+                .read_artifact(&Artifact::SourceCode)
+                .await?
+                .unwrap_or(json!(""))
+                .to_string();
+            let review_prompt = format!(
+                "Review the implemented UI against the user's reference mockups. {render_step} \
+                 Compare layout, spacing, colors, typography and copy. If the render \
+                 matches closely, say 'DESIGN MATCHES'. Otherwise list concrete fixes \
+                 for the developer, most visible first.\n\n\
+                 Current implementation:\n{source}\n\n\
+                 Reference mockups:\n{}\n\n\
+                 Round {round}/{DESIGN_REVIEW_ROUNDS}.",
+                self.mockup_markers(),
+            );
+
+            let start = Instant::now();
+            let review = if config.agentic {
+                self.run_delegate(AgentRole::UiUxDesigner, config.clone(), &review_prompt)
+                    .await?
+            } else {
+                self.run_agent_simple(AgentRole::UiUxDesigner, &review_prompt)
+                    .await?
+            };
+            self.blackboard
+                .publish_artifact(Artifact::DesignReview, json!(review), "ui_ux_designer")
+                .await?;
+
+            if self.enable_streaming {
+                self.progress.agent_completed(
+                    AgentRole::UiUxDesigner,
+                    start.elapsed(),
+                    AgentStatus::Success,
+                );
+            }
+
+            if review.to_uppercase().contains("DESIGN MATCHES") || round == DESIGN_REVIEW_ROUNDS {
+                break;
+            }
+
+            if self.enable_streaming {
+                self.progress
+                    .agent_started(AgentRole::Developer, "Matching the design to the mockups");
+            }
+            let fix_output = self
+                .run_agent_agentic(
+                    AgentRole::Developer,
+                    &format!(
+                        "The UI/UX designer compared the app with the user's mockups. \
+                         Apply these fixes without breaking passing tests:\n\n{review}"
+                    ),
+                )
+                .await?;
+            self.blackboard
+                .publish_artifact(Artifact::SourceCode, json!(fix_output), "developer")
+                .await?;
+            if self.enable_streaming {
+                self.progress.agent_completed(
+                    AgentRole::Developer,
+                    start.elapsed(),
+                    AgentStatus::Success,
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Audit the running app with axe-core and let the developer fix the
+    /// violations, for up to [`ACCESSIBILITY_AUDIT_ROUNDS`] rounds.
+    ///
+    /// Skipped with a warning when the `browser` tool is unavailable or the
+    /// app cannot be opened.
+    async fn execute_accessibility_audit(&mut self) -> Result<()> {
+        let browser = self
+            .parent_tools
+            .iter()
+            .find(|tool| tool.name() == "browser")
+            .cloned();
+        let (Some(browser), Some(sandbox)) = (browser, self.sandbox.clone()) else {
+            tracing::warn!("Skipping accessibility audit: browser tool or sandbox unavailable");
+            return Ok(());
+        };
+        self.enter_phase(WorkflowPhase::AccessibilityAudit);
+
+        if self.enable_streaming {
+            self.progress.phase_started(
+                WorkflowPhase::AccessibilityAudit,
+                "Auditing the app with axe-core",
+            );
+        }
+
+        for round in 1..=ACCESSIBILITY_AUDIT_ROUNDS {
+            let violations = match audit_preview(sandbox.as_ref(), browser.as_ref()).await {
+                Ok(violations) => violations,
+                Err(e) => {
+                    tracing::warn!("Accessibility audit skipped: {e:#}");
+                    break;
+                }
+            };
+            let summary = accessibility::summarize(&violations);
+            self.blackboard
+                .publish_artifact(
+                    Artifact::AccessibilityReport,
+                    json!(summary),
+                    "accessibility_audit",
+                )
+                .await?;
+            if self.enable_streaming {
+                self.progress
+                    .progress_update(format!("Accessibility (round {round}): {summary}"), 85);
+            }
+            if violations.is_empty() || round == ACCESSIBILITY_AUDIT_ROUNDS {
+                break;
+            }
+
+            if self.enable_streaming {
+                self.progress
+                    .agent_started(AgentRole::Developer, "Fixing accessibility violations");
+            }
+            let start = Instant::now();
+            let fix_output = self
+                .run_agent_agentic(
+                    AgentRole::Developer,
+                    &format!(
+                        "An axe-core audit of the running app found these accessibility \
+                         violations, most severe first. Fix them in the source (labels, alt \
+                         text, contrast, roles, heading order) without breaking passing tests \
+                         or changing the design:\n\n{summary}"
+                    ),
+                )
+                .await?;
+            self.blackboard
+                .publish_artifact(Artifact::SourceCode, json!(fix_output), "developer")
+                .await?;
+            if self.enable_streaming {
+                self.progress.agent_completed(
+                    AgentRole::Developer,
+                    start.elapsed(),
+                    AgentStatus::Success,
+                );
+            }
+        }
+
+        if self.enable_streaming {
+            self.progress
+                .phase_completed(WorkflowPhase::AccessibilityAudit);
+        }
+        Ok(())
+    }
+
+    /// The mockups as `[IMAGE:...]` markers, one per line.
+    fn mockup_markers(&self) -> String {
+        self.mockups
+            .iter()
+            .map(|reference| format!("[IMAGE:{reference}]"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Write or refresh the project docs before deployment. Best effort: a
+    /// failure is logged and the build carries on.
+    async fn generate_docs(&self) {
+        let Some(sandbox) = &self.sandbox else {
+            return;
+        };
+        if sandbox.require_id().is_err() {
+            return;
+        }
+        let workdir = match ProjectConfig::load(sandbox.as_ref()).await {
+            Ok(Some(config)) => config.workdir(),
+            _ => PROJECT_DIR.to_string(),
+        };
+        match crate::sandbox::docs::update_project_docs(sandbox.as_ref(), &workdir).await {
+            Ok(report) => {
+                let changed = report
+                    .iter()
+                    .filter(|(_, status)| matches!(status, DocStatus::Created | DocStatus::Updated))
+                    .count();
+                if self.enable_streaming && changed > 0 {
+                    self.progress
+                        .progress_update(format!("Generated {changed} documentation files"), 90);
+                }
+            }
+            Err(e) => tracing::warn!("Generating project docs failed: {e:#}"),
+        }
+    }
+
+    /// Review the project against the security checklist and let the
+    /// developer fix failed items, for up to [`SECURITY_REVIEW_ROUNDS`]
+    /// rounds. Items still failing block deployment.
+    async fn execute_security_review(&mut self) -> Result<()> {
+        self.enter_phase(WorkflowPhase::SecurityReview);
+
+        if self.enable_streaming {
+            self.progress.phase_started(
+                WorkflowPhase::SecurityReview,
+                "Security reviewer checking the project",
+            );
+        }
+
+        let mut report = None;
+        for round in 1..=SECURITY_REVIEW_ROUNDS {
+            if self.enable_streaming {
+                self.progress.agent_started(
+                    AgentRole::SecurityReviewer,
+                    format!("Reviewing the security checklist (round {round})"),
+                );
+            }
+
+            let evidence = self.security_evidence().await;
+            let start = Instant::now();
+            let review = self
+                .run_agent_agentic(
+                    AgentRole::SecurityReviewer,
+                    &security::review_prompt(&evidence, round, SECURITY_REVIEW_ROUNDS),
+                )
+                .await?;
+            let parsed = SecurityReport::parse(&review);
+            self.blackboard
+                .publish_artifact(
+                    Artifact::SecurityReport,
+                    json!(parsed.checklist()),
+                    "security_reviewer",
+                )
+                .await?;
+
+            let failures: Vec<String> = parsed
+                .failures()
+                .iter()
+                .map(|item| format!("#{} {}", item.tag, item.note))
+                .collect();
+            if self.enable_streaming {
+                let status = if failures.is_empty() {
+                    AgentStatus::Success
+                } else {
+                    AgentStatus::Failed
+                };
+                self.progress
+                    .agent_completed(AgentRole::SecurityReviewer, start.elapsed(), status);
+            }
+            report = Some(parsed);
+            if failures.is_empty() || round == SECURITY_REVIEW_ROUNDS {
+                break;
+            }
+
+            if self.enable_streaming {
+                self.progress
+                    .agent_started(AgentRole::Developer, "Fixing security review findings");
+            }
+            let start = Instant::now();
+            let fix_output = self
+                .run_agent_agentic(
+                    AgentRole::Developer,
+                    &format!(
+                        "The security reviewer found these vulnerabilities. Fix each one \
+                         without breaking passing tests:\n\n{}",
+                        failures.join("\n")
+                    ),
+                )
+                .await?;
+            self.blackboard
+                .publish_artifact(Artifact::SourceCode, json!(fix_output), "developer")
+                .await?;
+            if self.enable_streaming {
+                self.progress.agent_completed(
+                    AgentRole::Developer,
+                    start.elapsed(),
+                    AgentStatus::Success,
+                );
+            }
+        }
+
+        if self.enable_streaming {
+            self.progress.phase_completed(WorkflowPhase::SecurityReview);
+        }
+
+        if let Some(report) = report.filter(|report| !report.failures().is_empty()) {
+            if self.stages.deployment {
+                self.enter_phase(WorkflowPhase::Failed);
+                bail!(
+                    "Security review failed; deployment blocked until these items pass:\n{}",
+                    report.checklist()
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Checklist evidence from the project in the sandbox.
+    async fn security_evidence(&self) -> String {
+        let Some(sandbox) = self.sandbox.as_ref().filter(|s| s.require_id().is_ok()) else {
+            return "No sandbox is active; find the relevant code with codebase_search.\n"
+                .to_string();
+        };
+        let workdir = match ProjectConfig::load(sandbox.as_ref()).await {
+            Ok(Some(config)) => config.workdir(),
+            _ => PROJECT_DIR.to_string(),
+        };
+        match sandbox.collect_snapshot_files(&workdir).await {
+            Ok(files) => security::retrieve(&files.into_iter().collect()),
+            Err(e) => {
+                tracing::warn!("Collecting files for the security review failed: {e:#}");
+                "The project files could not be listed; find the relevant code with \
+                 codebase_search.\n"
+                    .to_string()
+            }
+        }
+    }
+
     /// Phase 4: Deployment
     async fn execute_phase_4_deployment(&mut self) -> Result<String> {
-        self.phase = WorkflowPhase::Deployment;
+        self.enter_phase(WorkflowPhase::Deployment);
 
         if self.enable_streaming {
             self.progress
@@ -612,7 +1208,8 @@ Rules:
             .await?;
 
         self.blackboard
-            .publish_artifact(Artifact::DeployConfig, json!(deploy_result), "devops");
+            .publish_artifact(Artifact::DeployConfig, json!(deploy_result), "devops")
+            .await?;
 
         if self.enable_streaming {
             use super::progress::DeployTarget;
@@ -621,29 +1218,63 @@ Rules:
             self.progress.phase_completed(WorkflowPhase::Deployment);
         }
 
-        self.phase = WorkflowPhase::Completed;
+        self.enter_phase(WorkflowPhase::Completed);
+
+        self.summary(&deploy_result).await
+    }
 
-        // Build summary
+    /// Final summary of a completed build.
+    async fn summary(&self, deployment: &str) -> Result<String> {
         let prd = self
             .blackboard
             .read_artifact(&Artifact::Prd)
+            .await?
             .unwrap_or(json!(""))
             .to_string();
         let design = self
             .blackboard
             .read_artifact(&Artifact::DesignSpec)
+            .await?
             .unwrap_or(json!(""))
             .to_string();
 
-        Ok(format!(
+        let mut summary = format!(
             "Factory build completed successfully!\n\n\
              PRD: {}\n\n\
              Design: {}\n\n\
              Deployment: {}",
             prd.chars().take(200).collect::<String>(),
             design.chars().take(200).collect::<String>(),
-            deploy_result
-        ))
+            deployment
+        );
+        if let (Some(estimate), Some(started)) = (&self.estimate, self.started) {
+            let _ = write!(
+                summary,
+                "\n\n{}",
+                estimate.compare(&self.usage.usage(), started.elapsed().as_secs())
+            );
+        }
+        if let Some(report) = self
+            .blackboard
+            .read_artifact(&Artifact::AccessibilityReport)
+            .await?
+            .and_then(|value| value.as_str().map(str::to_string))
+        {
+            let _ = write!(summary, "\n\nAccessibility: {report}");
+        }
+        if let Some(checklist) = self
+            .blackboard
+            .read_artifact(&Artifact::SecurityReport)
+            .await?
+            .and_then(|value| value.as_str().map(str::to_string))
+        {
+            let _ = write!(
+                summary,
+                "\n\nSecurity review (post it on the PR with \
+                 github_review_pr_with_checklist):\n{checklist}"
+            );
+        }
+        Ok(summary)
     }
 
     /// Current workflow phase.
@@ -653,23 +1284,111 @@ Rules:
 
     // ── Agent execution helpers ──────────────────────────────────
 
+    /// Prefix `prompt` with the shared facts `role` may read.
+    async fn with_shared_context(&self, role: AgentRole, prompt: &str) -> String {
+        match &self.shared_memory {
+            Some(shared) => format!("{}{prompt}", shared.context_for(role).await),
+            None => prompt.to_string(),
+        }
+    }
+
     async fn run_agent_simple(&self, role: AgentRole, prompt: &str) -> Result<String> {
+        let prompt = self.with_shared_context(role, prompt).await;
+        // Reference images may be local files, so those stages stay here.
+        let has_images = !multimodal::parse_image_markers(&prompt).1.is_empty();
+        match &self.stage_dispatch {
+            Some(dispatch) if !has_images => {
+                let outcome = dispatch
+                    .run(self.workflow_id, role, &self.idea, &prompt)
+                    .await?;
+                match outcome.output {
+                    Some(output) => {
+                        self.usage.record_unreported(
+                            &outcome.provider,
+                            &outcome.model,
+                            &prompt,
+                            &output,
+                        );
+                        Ok(output)
+                    }
+                    None => bail!(
+                        "Agent {role} failed on worker {}: {}",
+                        outcome.worker,
+                        outcome.error.unwrap_or_default()
+                    ),
+                }
+            }
+            _ => self.complete(role, prompt).await,
+        }
+    }
+
+    /// Provider and model `role` runs with in this process.
+    pub(crate) fn stage_model(&self, role: AgentRole) -> (String, String) {
+        let config = self.resolve_config(role);
+        (config.provider, config.model)
+    }
+
+    /// Run `role` as a single completion of `prompt` in this process.
+    pub(crate) async fn complete(&self, role: AgentRole, mut prompt: String) -> Result<String> {
         let config = self.resolve_config(role);
         let provider = self.create_provider(&config)?;
+        let temperature = config.temperature.unwrap_or(0.7);
+        let params = SamplingParams {
+            top_p: config.top_p,
+            seed: config.seed,
+        };
 
-        let result = tokio::time::timeout(
-            Duration::from_secs(120),
-            provider.chat_with_system(
-                config.system_prompt.as_deref(),
-                prompt,
-                &config.model,
-                config.temperature.unwrap_or(0.7),
-            ),
-        )
-        .await;
+        let has_images = !multimodal::parse_image_markers(&prompt).1.is_empty();
+        if has_images && !provider.supports_vision() {
+            tracing::warn!(%role, provider = %config.provider, "Model has no vision input; sending image references as text");
+            prompt = multimodal::redact_image_markers(&prompt);
+        }
+
+        // Providers report no usage for single completions; the meter
+        // counts them from the message length.
+        let input = format!(
+            "{}{prompt}",
+            config.system_prompt.as_deref().unwrap_or_default()
+        );
+        let result = if has_images && provider.supports_vision() {
+            let mut messages = Vec::with_capacity(2);
+            if let Some(system_prompt) = &config.system_prompt {
+                messages.push(ChatMessage::system(system_prompt.clone()));
+            }
+            messages.push(ChatMessage::user(prompt));
+            let prepared =
+                multimodal::prepare_messages_for_provider(&messages, &self.multimodal_config)
+                    .await?;
+            tokio::time::timeout(
+                Duration::from_secs(120),
+                sampling::with_sampling(
+                    params,
+                    provider.chat_with_history(&prepared.messages, &config.model, temperature),
+                ),
+            )
+            .await
+        } else {
+            tokio::time::timeout(
+                Duration::from_secs(120),
+                sampling::with_sampling(
+                    params,
+                    provider.chat_with_system(
+                        config.system_prompt.as_deref(),
+                        &prompt,
+                        &config.model,
+                        temperature,
+                    ),
+                ),
+            )
+            .await
+        };
 
         match result {
-            Ok(Ok(response)) => Ok(response),
+            Ok(Ok(response)) => {
+                self.usage
+                    .record_unreported(&config.provider, &config.model, &input, &response);
+                Ok(response)
+            }
             Ok(Err(e)) => bail!("Agent {role} failed: {e}"),
             Err(_) => bail!("Agent {role} timed out"),
         }
@@ -677,11 +1396,51 @@ Rules:
 
     async fn run_agent_agentic(&self, role: AgentRole, prompt: &str) -> Result<String> {
         let config = self.resolve_config(role);
+        let prompt = format!("{}{prompt}", self.project_rules(role).await);
 
         if config.allowed_tools.is_empty() {
-            return self.run_agent_simple(role, prompt).await;
+            return self.run_agent_simple(role, &prompt).await;
         }
 
+        self.run_delegate(role, config, &prompt).await
+    }
+
+    /// The project's `.zerobuild.toml` settings that concern `role`, read
+    /// fresh for every stage since a restored snapshot or cloned repo may
+    /// bring one along.
+    async fn project_rules(&self, role: AgentRole) -> String {
+        let Some(sandbox) = &self.sandbox else {
+            return String::new();
+        };
+        if sandbox.require_id().is_err() {
+            return String::new();
+        }
+        match ProjectConfig::load(sandbox.as_ref()).await {
+            Ok(Some(config)) => project_rules(&config, role),
+            Ok(None) => String::new(),
+            Err(e) => {
+                tracing::warn!("{e:#}; ignoring it for the {role} stage");
+                String::new()
+            }
+        }
+    }
+
+    /// Run `role` as a tool-using sub-agent with an already resolved `config`.
+    async fn run_delegate(
+        &self,
+        role: AgentRole,
+        mut config: DelegateAgentConfig,
+        prompt: &str,
+    ) -> Result<String> {
+        let mut parent_tools = self.parent_tools.clone();
+        if let Some(shared) = &self.shared_memory {
+            let mut tools = self.parent_tools.as_ref().clone();
+            tools.push(Arc::new(FactoryMemoryTool::new(Arc::clone(shared), role)));
+            parent_tools = Arc::new(tools);
+            config.allowed_tools.push("factory_memory".into());
+        }
+        let prompt = self.with_shared_context(role, prompt).await;
+
         let role_name = role.to_string();
         let mut agents = HashMap::new();
         agents.insert(role_name.clone(), config);
@@ -693,8 +1452,9 @@ Rules:
             security,
             self.provider_runtime_options.clone(),
         )
-        .with_parent_tools(self.parent_tools.clone())
-        .with_multimodal_config(self.multimodal_config.clone());
+        .with_parent_tools(parent_tools)
+        .with_multimodal_config(self.multimodal_config.clone())
+        .with_observer(self.usage.clone());
 
         let result = delegate
             .execute(json!({
@@ -727,6 +1487,19 @@ Rules:
         if config.api_key.is_none() {
             config.api_key = self.fallback_credential.clone();
         }
+        if let Some(templates) = &self.prompt_templates {
+            let vars = PromptVars {
+                model: Some(config.model.clone()),
+                tools: config.allowed_tools.clone(),
+                extra: BTreeMap::from([("idea".to_string(), self.idea.clone())]),
+                ..templates.user_vars()
+            };
+            match templates.render_override(&role.to_string(), &vars) {
+                Ok(Some(prompt)) => config.system_prompt = Some(prompt),
+                Ok(None) => {}
+                Err(e) => tracing::warn!("{e:#}; using built-in {role} prompt"),
+            }
+        }
 
         config
     }
@@ -740,6 +1513,76 @@ Rules:
     }
 }
 
+/// Open the app's preview URL with `browser` and run its axe-core audit.
+async fn audit_preview(
+    sandbox: &dyn SandboxClient,
+    browser: &dyn Tool,
+) -> Result<Vec<accessibility::Violation>> {
+    let url = sandbox.get_preview_url(PREVIEW_PORT).await?;
+    let opened = browser
+        .execute(json!({"action": "open", "url": url}))
+        .await?;
+    if !opened.success {
+        bail!("opening {url} failed: {}", opened.error.unwrap_or_default());
+    }
+    let audit = browser.execute(json!({"action": "a11y_audit"})).await?;
+    if !audit.success {
+        bail!("axe-core audit failed: {}", audit.error.unwrap_or_default());
+    }
+    accessibility::parse_violations(&audit.output)
+}
+
+/// Prompt preamble listing the `.zerobuild.toml` settings `role` must
+/// follow; empty for roles that don't touch the project.
+fn project_rules(config: &ProjectConfig, role: AgentRole) -> String {
+    let commands = &config.commands;
+    let lines: Vec<(&str, &Option<String>)> = match role {
+        AgentRole::Developer => vec![
+            ("Install dependencies with", &commands.install),
+            ("Verify the build with", &commands.build),
+            ("Run the tests with", &commands.test),
+            ("Lint with", &commands.lint),
+            ("Start the app with", &commands.start),
+        ],
+        AgentRole::Tester => vec![
+            ("Run the tests with", &commands.test),
+            ("Lint with", &commands.lint),
+        ],
+        AgentRole::DevOps => vec![
+            ("Build with", &commands.build),
+            ("Deploy target:", &config.deploy.target),
+            ("Push to repository", &config.deploy.repo),
+            ("Push to branch", &config.deploy.branch),
+            (
+                "Push into repository directory (github_push path)",
+                &config.deploy.path,
+            ),
+            (
+                "Create a new repository from template",
+                &config.deploy.template,
+            ),
+        ],
+        _ => return String::new(),
+    };
+
+    let mut out = format!(
+        "Project settings from {PROJECT_DIR}/{CONFIG_FILE} (set by the user; follow them):\n\
+         - Work in `{}`; sandbox_run_command runs there by default\n",
+        config.workdir()
+    );
+    for (label, value) in lines {
+        if let Some(value) = value {
+            let _ = writeln!(out, "- {label} `{value}`");
+        }
+    }
+    let _ = writeln!(
+        out,
+        "- Never modify these paths; the sandbox tools refuse to: {}\n",
+        config.protected_list()
+    );
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -775,4 +1618,81 @@ mod tests {
         assert_eq!(config.api_key.as_deref(), Some("test-key"));
         assert!(config.system_prompt.is_some());
     }
+
+    #[test]
+    fn project_rules_follow_role() {
+        let config = ProjectConfig::parse(
+            r#"
+            workdir = "web"
+            protected = ["contracts/**"]
+            [commands]
+            build = "pnpm build"
+            test = "pnpm test"
+            [deploy]
+            target = "vercel"
+            path = "apps/web"
+            "#,
+        )
+        .unwrap();
+
+        let tester = project_rules(&config, AgentRole::Tester);
+        assert!(tester.contains("- Work in `project/web`"));
+        assert!(tester.contains("- Run the tests with `pnpm test`"));
+        assert!(!tester.contains("pnpm build"));
+        assert!(tester.contains(".zerobuild.toml, contracts/**"));
+
+        let devops = project_rules(&config, AgentRole::DevOps);
+        assert!(devops.contains("- Build with `pnpm build`"));
+        assert!(devops.contains("- Deploy target: `vercel`"));
+        assert!(devops.contains("- Push into repository directory (github_push path) `apps/web`"));
+
+        assert!(project_rules(&config, AgentRole::BusinessAnalyst).is_empty());
+    }
+
+    #[test]
+    fn image_markers_in_idea_become_mockups() {
+        let wf = FactoryWorkflow::new(
+            "Clone this landing page [IMAGE:/tmp/mockup.png]".into(),
+            5,
+            HashMap::new(),
+            providers::ProviderRuntimeOptions::default(),
+            None,
+            "openrouter".into(),
+            "test-model".into(),
+            Arc::new(Vec::new()),
+            crate::config::MultimodalConfig::default(),
+            false,
+        );
+
+        assert_eq!(wf.idea, "Clone this landing page");
+        assert_eq!(wf.mockups, vec!["/tmp/mockup.png".to_string()]);
+        assert_eq!(wf.mockup_markers(), "[IMAGE:/tmp/mockup.png]");
+    }
+
+    #[test]
+    fn resolve_config_applies_stage_prompt_template() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let prompts = tmp.path().join("prompts");
+        std::fs::create_dir_all(&prompts).unwrap();
+        std::fs::write(prompts.join("developer.md"), "Build: {{ extra.idea }}").unwrap();
+
+        let wf = FactoryWorkflow::new(
+            "todo app".into(),
+            5,
+            HashMap::new(),
+            providers::ProviderRuntimeOptions::default(),
+            None,
+            "openrouter".into(),
+            "test-model".into(),
+            Arc::new(Vec::new()),
+            crate::config::MultimodalConfig::default(),
+            true,
+        )
+        .with_prompt_templates(PromptTemplates::for_workspace(tmp.path()));
+
+        let config = wf.resolve_config(AgentRole::Developer);
+        assert_eq!(config.system_prompt.as_deref(), Some("Build: todo app"));
+        let tester = wf.resolve_config(AgentRole::Tester);
+        assert_ne!(tester.system_prompt.as_deref(), Some("Build: todo app"));
+    }
 }
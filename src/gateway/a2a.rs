@@ -0,0 +1,108 @@
+//! Agent-to-agent routes: `/a2a/card`, `/a2a/tasks` and `/a2a/tasks/{id}`.
+//!
+//! Other ZeroBuild instances submit sub-tasks here and collect their results;
+//! see [`crate::factory::a2a`] for the protocol. The routes answer 404 unless
+//! `[factory.a2a] enabled = true`, and require the configured token as a
+//! bearer token.
+
+use super::AppState;
+use crate::factory::a2a::{TaskBoard, TaskRequest, PROTOCOL};
+use crate::security::pairing::constant_time_eq;
+use axum::{
+    extract::{rejection::JsonRejection, Path, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Json},
+};
+
+type Rejection = (StatusCode, Json<serde_json::Value>);
+
+fn rejection(status: StatusCode, error: &str) -> Rejection {
+    (status, Json(serde_json::json!({ "error": error })))
+}
+
+/// Check the bearer token against the live `[factory.a2a]` config.
+fn authorize(state: &AppState, headers: &HeaderMap) -> Result<(), Rejection> {
+    let a2a = state.config.lock().factory.a2a.clone();
+    if !a2a.enabled {
+        return Err(rejection(
+            StatusCode::NOT_FOUND,
+            "A2A is not enabled on this instance",
+        ));
+    }
+    let expected = a2a.token.as_deref().map(str::trim).unwrap_or_default();
+    let presented = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|auth| auth.strip_prefix("Bearer "))
+        .map(str::trim)
+        .unwrap_or_default();
+    if expected.is_empty() || !constant_time_eq(presented, expected) {
+        tracing::warn!("A2A: rejected request — invalid or missing bearer token");
+        return Err(rejection(
+            StatusCode::UNAUTHORIZED,
+            "Unauthorized — send Authorization: Bearer <[factory.a2a] token>",
+        ));
+    }
+    Ok(())
+}
+
+/// GET /a2a/card — protocol version and load of this instance
+pub async fn handle_a2a_card(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Err(e) = authorize(&state, &headers) {
+        return e.into_response();
+    }
+    let factory = state.config.lock().factory.enabled;
+    let (queued, running) = TaskBoard::global().load();
+    Json(serde_json::json!({
+        "protocol": PROTOCOL,
+        "version": env!("CARGO_PKG_VERSION"),
+        "factory": factory,
+        "queued": queued,
+        "running": running,
+    }))
+    .into_response()
+}
+
+/// POST /a2a/tasks — queue a task from a peer
+pub async fn handle_a2a_submit(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Result<Json<TaskRequest>, JsonRejection>,
+) -> impl IntoResponse {
+    if let Err(e) = authorize(&state, &headers) {
+        return e.into_response();
+    }
+    let Ok(Json(request)) = body else {
+        return rejection(
+            StatusCode::BAD_REQUEST,
+            "Invalid JSON body. Expected: {\"task\": \"...\"}",
+        )
+        .into_response();
+    };
+    if request.task.trim().is_empty() {
+        return rejection(StatusCode::BAD_REQUEST, "'task' must not be empty").into_response();
+    }
+
+    let config = state.config.lock().clone();
+    let task = TaskBoard::global().submit(config, request);
+    tracing::info!(task_id = %task.id, "A2A task queued");
+    (StatusCode::ACCEPTED, Json(task)).into_response()
+}
+
+/// GET /a2a/tasks/{id} — status and result of a task
+pub async fn handle_a2a_task(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    if let Err(e) = authorize(&state, &headers) {
+        return e.into_response();
+    }
+    match TaskBoard::global().get(&id) {
+        Some(task) => Json(task).into_response(),
+        None => rejection(StatusCode::NOT_FOUND, "Unknown task").into_response(),
+    }
+}
@@ -3,6 +3,7 @@
 //! All `/api/*` routes require bearer token authentication (PairingGuard).
 
 use super::AppState;
+use crate::observability::{recording, report, timeline};
 use axum::{
     extract::{Path, Query, State},
     http::{header, HeaderMap, StatusCode},
@@ -50,6 +51,48 @@ pub struct MemoryQuery {
     pub category: Option<String>,
 }
 
+#[derive(Deserialize)]
+pub struct HistorySearchQuery {
+    pub q: String,
+    pub conversation: Option<String>,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Deserialize)]
+pub struct LogDownloadQuery {
+    /// `stdout` or `stderr`; both when omitted
+    pub stream: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct SessionReportQuery {
+    /// `md` (default) or `html`
+    pub format: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct SessionTimelineQuery {
+    /// `json` (default) or `html`
+    pub format: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct MemoryExportQuery {
+    /// `json` (default) or `markdown`
+    pub format: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct MemoryBrowseQuery {
+    pub category: Option<String>,
+    /// Substring matched against key and content
+    pub q: Option<String>,
+    pub offset: Option<usize>,
+    pub limit: Option<usize>,
+}
+
 #[derive(Deserialize)]
 pub struct MemoryStoreBody {
     pub key: String,
@@ -64,6 +107,22 @@ pub struct CronAddBody {
     pub command: String,
 }
 
+#[derive(Deserialize)]
+pub struct CheckpointCreateBody {
+    pub name: String,
+    pub note: Option<String>,
+    /// `<channel>:<sender>` whose conversation to capture; the local user
+    /// when omitted
+    pub user: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct CheckpointUserQuery {
+    /// `<channel>:<sender>` whose checkpoints to list or switch to; the
+    /// local user when omitted
+    pub user: Option<String>,
+}
+
 // ── Handlers ────────────────────────────────────────────────────
 
 /// GET /api/status — system status overview
@@ -84,10 +143,11 @@ pub async fn handle_api_status(
         channels.insert(channel.name().to_string(), serde_json::Value::Bool(present));
     }
 
+    let chat = state.chat();
     let body = serde_json::json!({
         "provider": config.default_provider,
-        "model": state.model,
-        "temperature": state.temperature,
+        "model": chat.model,
+        "temperature": chat.temperature,
         "uptime_seconds": health.uptime_seconds,
         "gateway_port": config.gateway.port,
         "locale": "en",
@@ -95,6 +155,8 @@ pub async fn handle_api_status(
         "paired": state.pairing.is_paired(),
         "channels": channels,
         "health": health,
+        "provider_rate_limits": crate::providers::throttle::stats(),
+        "memory_evictions": crate::memory::user_scoped::eviction_stats(),
     });
 
     Json(body).into_response()
@@ -144,7 +206,7 @@ pub async fn handle_api_config_put(
     }
 
     // Parse the incoming TOML
-    let new_config: crate::config::Config = match toml::from_str(&body) {
+    let mut new_config: crate::config::Config = match toml::from_str(&body) {
         Ok(c) => c,
         Err(e) => {
             return (
@@ -154,6 +216,18 @@ pub async fn handle_api_config_put(
                 .into_response();
         }
     };
+    {
+        let current = state.config.lock();
+        new_config.config_path.clone_from(&current.config_path);
+        new_config.workspace_dir.clone_from(&current.workspace_dir);
+    }
+    if let Err(e) = new_config.validate() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": format!("Invalid config: {e}")})),
+        )
+            .into_response();
+    }
 
     // Save to disk
     if let Err(e) = new_config.save().await {
@@ -164,12 +238,210 @@ pub async fn handle_api_config_put(
             .into_response();
     }
 
+    // Apply through the reloader so only live sections change and the
+    // caller learns which fields need a restart.
+    if let Some(reloader) = &state.config_reloader {
+        let report = reloader.reload().await;
+        crate::config::reload::apply_live(&mut state.config.lock(), &reloader.current());
+        return Json(serde_json::json!({"status": "ok", "reload": report})).into_response();
+    }
+
     // Update in-memory config
     *state.config.lock() = new_config;
 
     Json(serde_json::json!({"status": "ok"})).into_response()
 }
 
+/// GET /api/config/reload — outcome of the last config reload
+pub async fn handle_api_config_reload_status(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Err(e) = require_auth(&state, &headers) {
+        return e.into_response();
+    }
+
+    let Some(reloader) = &state.config_reloader else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({"error": "Config reload is not available"})),
+        )
+            .into_response();
+    };
+    Json(serde_json::json!({"reload": reloader.last_report()})).into_response()
+}
+
+/// POST /api/config/reload — re-read config.toml now
+pub async fn handle_api_config_reload(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Err(e) = require_auth(&state, &headers) {
+        return e.into_response();
+    }
+
+    let Some(reloader) = &state.config_reloader else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({"error": "Config reload is not available"})),
+        )
+            .into_response();
+    };
+    let report = reloader.reload().await;
+    crate::config::reload::apply_live(&mut state.config.lock(), &reloader.current());
+    let status = if report.error.is_some() {
+        StatusCode::UNPROCESSABLE_ENTITY
+    } else {
+        StatusCode::OK
+    };
+    (status, Json(serde_json::json!({"reload": report}))).into_response()
+}
+
+/// GET /api/init — environment checks and current choices for the setup page
+pub async fn handle_api_init_status(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Err(e) = require_auth(&state, &headers) {
+        return e.into_response();
+    }
+
+    let config = state.config.lock().clone();
+    let checks = crate::onboard::init::detect_environment().await;
+    let templates: Vec<serde_json::Value> = crate::sandbox::PROJECT_TEMPLATES
+        .iter()
+        .map(|(name, description)| serde_json::json!({"name": name, "description": description}))
+        .collect();
+
+    Json(serde_json::json!({
+        "checks": checks,
+        "templates": templates,
+        "github_connected": crate::onboard::init::github_connected(&config),
+        "current": {
+            "provider": config.default_provider,
+            "model": config.default_model,
+            "api_url": config.api_url,
+            "has_api_key": config.api_key.is_some(),
+            "template": config.zerobuild.default_template,
+            "use_docker": config.runtime.kind == "docker",
+        },
+    }))
+    .into_response()
+}
+
+#[derive(Deserialize)]
+pub struct InitTestBody {
+    pub provider: String,
+    pub api_key: Option<String>,
+    pub api_url: Option<String>,
+    pub model: Option<String>,
+}
+
+/// POST /api/init/test — send one short request to test a provider key
+pub async fn handle_api_init_test(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<InitTestBody>,
+) -> impl IntoResponse {
+    if let Err(e) = require_auth(&state, &headers) {
+        return e.into_response();
+    }
+
+    let provider = body.provider.trim();
+    let api_url = body
+        .api_url
+        .as_deref()
+        .map(str::trim)
+        .filter(|url| !url.is_empty());
+    let api_key = match body.api_key.filter(|key| !key.trim().is_empty()) {
+        Some(key) => Some(key),
+        // A blank key tests the key already in config.toml, but only against
+        // the configured endpoint or a built-in provider's own URL; the
+        // stored key must never be sent to a caller-chosen host.
+        None => {
+            let config = state.config.lock();
+            if !may_use_stored_key(&config, provider, api_url) {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(serde_json::json!({
+                        "error": "api_key is required to test a custom endpoint"
+                    })),
+                )
+                    .into_response();
+            }
+            config.api_key.clone()
+        }
+    };
+    let model = body
+        .model
+        .filter(|model| !model.trim().is_empty())
+        .unwrap_or_else(|| crate::onboard::wizard::default_model_for_provider(provider));
+    let check =
+        crate::onboard::init::test_provider_key(provider, api_key.as_deref(), api_url, &model)
+            .await;
+    Json(serde_json::json!({"check": check})).into_response()
+}
+
+/// Whether testing `provider` at `api_url` may fall back to the stored API
+/// key: the endpoint must be the configured one, or a built-in provider with
+/// no URL override.
+fn may_use_stored_key(
+    config: &crate::config::Config,
+    provider: &str,
+    api_url: Option<&str>,
+) -> bool {
+    let configured_url = config
+        .api_url
+        .as_deref()
+        .map(str::trim)
+        .filter(|url| !url.is_empty());
+    if config.default_provider.as_deref() == Some(provider) && api_url == configured_url {
+        return true;
+    }
+    let custom_endpoint =
+        provider.starts_with("custom:") || provider.starts_with("anthropic-custom:");
+    api_url.is_none() && !custom_endpoint
+}
+
+/// POST /api/init — apply setup choices, write and validate config.toml
+pub async fn handle_api_init(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(answers): Json<crate::onboard::init::InitAnswers>,
+) -> impl IntoResponse {
+    if let Err(e) = require_auth(&state, &headers) {
+        return e.into_response();
+    }
+
+    let mut config = state.config.lock().clone();
+    if let Err(e) = crate::onboard::init::apply_answers(&mut config, &answers) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": format!("Invalid setup: {e:#}")})),
+        )
+            .into_response();
+    }
+    let written = match crate::onboard::init::write_validated(&config).await {
+        Ok(written) => written,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": format!("{e:#}")})),
+            )
+                .into_response();
+        }
+    };
+
+    if let Some(reloader) = &state.config_reloader {
+        let report = reloader.reload().await;
+        crate::config::reload::apply_live(&mut state.config.lock(), &reloader.current());
+        return Json(serde_json::json!({"status": "ok", "reload": report})).into_response();
+    }
+
+    *state.config.lock() = written;
+    Json(serde_json::json!({"status": "ok"})).into_response()
+}
+
 /// GET /api/tools — list registered tool specs
 pub async fn handle_api_tools(
     State(state): State<AppState>,
@@ -286,6 +558,306 @@ pub async fn handle_api_cron_delete(
     }
 }
 
+/// Open the ZeroBuild store configured for this gateway.
+fn open_store(state: &AppState) -> anyhow::Result<rusqlite::Connection> {
+    let db_path = std::path::PathBuf::from(&state.config.lock().zerobuild.db_path);
+    crate::store::init_db(&db_path)
+}
+
+/// GET /api/checkpoints?user= — list a user's conversation checkpoints
+pub async fn handle_api_checkpoints_list(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<CheckpointUserQuery>,
+) -> impl IntoResponse {
+    if let Err(e) = require_auth(&state, &headers) {
+        return e.into_response();
+    }
+
+    let user = params
+        .user
+        .as_deref()
+        .unwrap_or(crate::store::preferences::LOCAL_USER);
+    match open_store(&state)
+        .and_then(|conn| crate::store::checkpoint::list_checkpoints(&conn, user))
+    {
+        Ok(checkpoints) => Json(serde_json::json!({"checkpoints": checkpoints})).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to list checkpoints: {e}")})),
+        )
+            .into_response(),
+    }
+}
+
+/// POST /api/checkpoints — capture the current snapshot and conversation
+pub async fn handle_api_checkpoints_create(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<CheckpointCreateBody>,
+) -> impl IntoResponse {
+    if let Err(e) = require_auth(&state, &headers) {
+        return e.into_response();
+    }
+
+    match open_store(&state).and_then(|conn| {
+        crate::store::checkpoint::create_checkpoint(
+            &conn,
+            body.user
+                .as_deref()
+                .unwrap_or(crate::store::preferences::LOCAL_USER),
+            &body.name,
+            body.note.as_deref(),
+        )
+    }) {
+        Ok(checkpoint) => {
+            Json(serde_json::json!({"status": "ok", "checkpoint": checkpoint})).into_response()
+        }
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": format!("Failed to create checkpoint: {e}")})),
+        )
+            .into_response(),
+    }
+}
+
+/// POST /api/checkpoints/:name/switch?user= — branch from a checkpoint
+pub async fn handle_api_checkpoints_switch(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+    Query(params): Query<CheckpointUserQuery>,
+) -> impl IntoResponse {
+    if let Err(e) = require_auth(&state, &headers) {
+        return e.into_response();
+    }
+
+    let user = params
+        .user
+        .as_deref()
+        .unwrap_or(crate::store::preferences::LOCAL_USER);
+    match open_store(&state)
+        .and_then(|conn| crate::store::checkpoint::switch_checkpoint(&conn, user, &name))
+    {
+        Ok(checkpoint) => Json(serde_json::json!({
+            "status": "ok",
+            "active": checkpoint.name,
+            "files": checkpoint.files.len(),
+            "messages": checkpoint.conversation.len(),
+        }))
+        .into_response(),
+        Err(e) => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({"error": format!("Failed to switch checkpoint: {e}")})),
+        )
+            .into_response(),
+    }
+}
+
+/// GET /api/logs/:id — download the full output of a truncated sandbox command
+pub async fn handle_api_log_download(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Query(params): Query<LogDownloadQuery>,
+) -> impl IntoResponse {
+    if let Err(e) = require_auth(&state, &headers) {
+        return e.into_response();
+    }
+
+    let log = match open_store(&state).and_then(|conn| crate::store::logs::load_log(&conn, &id)) {
+        Ok(Some(log)) => log,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({"error": format!("No log '{id}'")})),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": format!("Failed to load log: {e}")})),
+            )
+                .into_response()
+        }
+    };
+
+    let (body, filename) = match params.stream.as_deref() {
+        Some(stream) => match log.stream(stream) {
+            Ok(text) => (text.to_string(), format!("{}-{stream}.log", log.id)),
+            Err(e) => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(serde_json::json!({"error": e.to_string()})),
+                )
+                    .into_response()
+            }
+        },
+        None => (
+            format!(
+                "$ {}\n# workdir: {}, exit code: {}, {}\n\n--- stdout ---\n{}\n--- stderr ---\n{}",
+                log.command, log.workdir, log.exit_code, log.created_at, log.stdout, log.stderr
+            ),
+            format!("{}.log", log.id),
+        ),
+    };
+    (
+        [
+            (
+                header::CONTENT_TYPE,
+                "text/plain; charset=utf-8".to_string(),
+            ),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{filename}\""),
+            ),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+/// GET /api/artifacts/usage — storage used by each project's build artifacts
+pub async fn handle_api_artifacts_usage(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Err(e) = require_auth(&state, &headers) {
+        return e.into_response();
+    }
+
+    let zerobuild = state.config.lock().zerobuild.clone();
+    let artifacts = crate::artifacts::Artifacts::from_config(&zerobuild);
+    match artifacts.usage() {
+        Ok(projects) => Json(serde_json::json!({
+            "backend": artifacts.backend(),
+            "max_age_days": zerobuild.artifacts.max_age_days,
+            "max_project_mb": zerobuild.artifacts.max_project_mb,
+            "projects": projects,
+        }))
+        .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Artifact usage failed: {e}")})),
+        )
+            .into_response(),
+    }
+}
+
+/// GET /api/sessions/{id}/report — download a recorded session as a report
+pub async fn handle_api_session_report(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Query(params): Query<SessionReportQuery>,
+) -> impl IntoResponse {
+    if let Err(e) = require_auth(&state, &headers) {
+        return e.into_response();
+    }
+
+    let format = match params.format.as_deref() {
+        None => report::ReportFormat::Markdown,
+        Some(name) => match report::ReportFormat::from_name(name) {
+            Some(format) => format,
+            None => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(serde_json::json!({"error": format!("Unknown report format '{name}'; use md or html")})),
+                )
+                    .into_response()
+            }
+        },
+    };
+
+    let prices = state.config.lock().cost.prices.clone();
+    let events = match load_session(&state, &id) {
+        Ok(events) => events,
+        Err(e) => return e.into_response(),
+    };
+
+    let report = report::SessionReport::from_events(&events, &prices);
+    let short_id: String = report.id.chars().take(8).collect();
+    (
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!(
+                    "attachment; filename=\"session-{short_id}.{}\"",
+                    format.extension()
+                ),
+            ),
+        ],
+        report.render(format),
+    )
+        .into_response()
+}
+
+/// GET /api/v1/sessions/{id}/timeline — stages, calls, durations, tokens
+/// and errors of a recorded session, as JSON or an HTML chart
+pub async fn handle_api_session_timeline(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Query(params): Query<SessionTimelineQuery>,
+) -> impl IntoResponse {
+    if let Err(e) = require_auth(&state, &headers) {
+        return e.into_response();
+    }
+
+    let html = match params.format.as_deref().map(str::to_ascii_lowercase).as_deref() {
+        None | Some("json") => false,
+        Some("html") => true,
+        Some(name) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({"error": format!("Unknown timeline format '{name}'; use json or html")})),
+            )
+                .into_response()
+        }
+    };
+    let events = match load_session(&state, &id) {
+        Ok(events) => events,
+        Err(e) => return e.into_response(),
+    };
+
+    let timeline = timeline::SessionTimeline::from_events(&events);
+    if html {
+        (
+            [(header::CONTENT_TYPE, "text/html; charset=utf-8")],
+            timeline.to_html(),
+        )
+            .into_response()
+    } else {
+        Json(timeline).into_response()
+    }
+}
+
+/// Events of the recorded session whose ID starts with `id`.
+fn load_session(
+    state: &AppState,
+    id: &str,
+) -> Result<Vec<recording::RecordedEvent>, (StatusCode, Json<serde_json::Value>)> {
+    let dir = {
+        let config = state.config.lock();
+        recording::resolve_recordings_dir(&config.observability, &config.workspace_dir)
+    };
+    match recording::find(&dir, id)
+        .and_then(|path| path.map(|path| recording::load(&path)).transpose())
+    {
+        Ok(Some(events)) => Ok(events),
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({"error": format!("No recorded session '{id}'")})),
+        )),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to load session: {e}")})),
+        )),
+    }
+}
+
 /// GET /api/integrations — list all integrations with status
 pub async fn handle_api_integrations(
     State(state): State<AppState>,
@@ -447,6 +1019,263 @@ pub async fn handle_api_memory_delete(
     }
 }
 
+fn invalid_user_id(user_id: &str) -> Option<axum::response::Response> {
+    crate::memory::user_scoped::validate_user_id(user_id)
+        .err()
+        .map(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({"error": e.to_string()})),
+            )
+                .into_response()
+        })
+}
+
+/// GET /api/memory/users/:user_id/export — export a user's memory as JSON or Markdown
+pub async fn handle_api_memory_user_export(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(user_id): Path<String>,
+    Query(params): Query<MemoryExportQuery>,
+) -> impl IntoResponse {
+    if let Err(e) = require_auth(&state, &headers) {
+        return e.into_response();
+    }
+    if let Some(resp) = invalid_user_id(&user_id) {
+        return resp;
+    }
+
+    let scoped = &state.user_memory;
+    if !scoped.user_exists(&user_id) {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({"error": format!("No memory stored for user '{user_id}'")})),
+        )
+            .into_response();
+    }
+    match scoped.export_user(&user_id).await {
+        Ok(export) if params.format.as_deref() == Some("markdown") => (
+            [(header::CONTENT_TYPE, "text/markdown; charset=utf-8")],
+            crate::memory::export::render_markdown(&export),
+        )
+            .into_response(),
+        Ok(export) => Json(export).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Memory export failed: {e}")})),
+        )
+            .into_response(),
+    }
+}
+
+/// POST /api/memory/users/:user_id/import — load a JSON export into a user's memory
+pub async fn handle_api_memory_user_import(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(user_id): Path<String>,
+    Json(export): Json<crate::memory::export::MemoryExport>,
+) -> impl IntoResponse {
+    if let Err(e) = require_auth(&state, &headers) {
+        return e.into_response();
+    }
+    if let Some(resp) = invalid_user_id(&user_id) {
+        return resp;
+    }
+
+    match state.user_memory.import_user(&user_id, &export).await {
+        Ok(imported) => {
+            Json(serde_json::json!({"status": "ok", "imported": imported})).into_response()
+        }
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": format!("Memory import failed: {e}")})),
+        )
+            .into_response(),
+    }
+}
+
+/// DELETE /api/memory/users/:user_id — irreversibly wipe a user's memory and stored data
+pub async fn handle_api_memory_user_wipe(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(user_id): Path<String>,
+) -> impl IntoResponse {
+    if let Err(e) = require_auth(&state, &headers) {
+        return e.into_response();
+    }
+    if let Some(resp) = invalid_user_id(&user_id) {
+        return resp;
+    }
+    let Some((channel, sender)) = crate::memory::user_scoped::parse_memory_user_id(&user_id) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "error": format!("{user_id:?} is not a channel user id (<channel>_<sender>)")
+            })),
+        )
+            .into_response();
+    };
+
+    let memory_deleted = match state.user_memory.wipe_user(&user_id) {
+        Ok(deleted) => deleted,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": format!("Memory wipe failed: {e}")})),
+            )
+                .into_response()
+        }
+    };
+    match open_store(&state)
+        .and_then(|mut conn| crate::store::user_data::wipe_user(&mut conn, &channel, &sender))
+    {
+        Ok(rows_deleted) => Json(serde_json::json!({
+            "status": "ok",
+            "memory_deleted": memory_deleted,
+            "rows_deleted": rows_deleted,
+        }))
+        .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Store wipe failed: {e}")})),
+        )
+            .into_response(),
+    }
+}
+
+/// GET /api/memory/stats — entries per store and category, sizes, last write/access
+pub async fn handle_api_memory_stats(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Err(e) = require_auth(&state, &headers) {
+        return e.into_response();
+    }
+
+    let workspace_dir = state.config.lock().workspace_dir.clone();
+    let stats =
+        tokio::task::spawn_blocking(move || crate::memory::stats::collect_stats(&workspace_dir))
+            .await;
+    match stats {
+        Ok(stats) => Json(stats).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Memory stats failed: {e}")})),
+        )
+            .into_response(),
+    }
+}
+
+/// GET /api/memory/stores/:store/entries — browse a store's entries with metadata
+pub async fn handle_api_memory_browse(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(store): Path<String>,
+    Query(params): Query<MemoryBrowseQuery>,
+) -> impl IntoResponse {
+    if let Err(e) = require_auth(&state, &headers) {
+        return e.into_response();
+    }
+
+    let workspace_dir = state.config.lock().workspace_dir.clone();
+    let entries = tokio::task::spawn_blocking(move || {
+        let query = crate::memory::stats::BrowseQuery {
+            category: params.category.as_deref(),
+            search: params.q.as_deref(),
+            offset: params.offset.unwrap_or(0),
+            limit: params.limit.unwrap_or(50).min(500),
+        };
+        crate::memory::stats::browse(&workspace_dir, &store, &query)
+    })
+    .await;
+    match entries {
+        Ok(Ok(entries)) => Json(serde_json::json!({"entries": entries})).into_response(),
+        Ok(Err(e)) => memory_store_error("Memory browse failed", &e),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Memory browse failed: {e}")})),
+        )
+            .into_response(),
+    }
+}
+
+/// GET /api/memory/stores/:store/entries/:key — one entry with its write/access history
+pub async fn handle_api_memory_inspect(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((store, key)): Path<(String, String)>,
+) -> impl IntoResponse {
+    if let Err(e) = require_auth(&state, &headers) {
+        return e.into_response();
+    }
+
+    let workspace_dir = state.config.lock().workspace_dir.clone();
+    let entry = {
+        let (store, key) = (store.clone(), key.clone());
+        tokio::task::spawn_blocking(move || {
+            crate::memory::stats::inspect(&workspace_dir, &store, &key)
+        })
+        .await
+    };
+    match entry {
+        Ok(Ok(Some(entry))) => Json(entry).into_response(),
+        Ok(Ok(None)) => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({"error": format!("No entry '{key}' in store '{store}'")})),
+        )
+            .into_response(),
+        Ok(Err(e)) => memory_store_error("Memory inspect failed", &e),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Memory inspect failed: {e}")})),
+        )
+            .into_response(),
+    }
+}
+
+/// 500 for SQLite failures of a memory store; 404 for a missing store or
+/// an invalid store name.
+fn memory_store_error(what: &str, e: &anyhow::Error) -> axum::response::Response {
+    let status = if e.downcast_ref::<rusqlite::Error>().is_some() {
+        StatusCode::INTERNAL_SERVER_ERROR
+    } else {
+        StatusCode::NOT_FOUND
+    };
+    (
+        status,
+        Json(serde_json::json!({"error": format!("{what}: {e}")})),
+    )
+        .into_response()
+}
+
+/// GET /api/history/search — full-text search over conversation history
+pub async fn handle_api_history_search(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<HistorySearchQuery>,
+) -> impl IntoResponse {
+    if let Err(e) = require_auth(&state, &headers) {
+        return e.into_response();
+    }
+
+    let filter = crate::store::history::HistoryQuery {
+        conversation: params.conversation.as_deref(),
+        since: params.since.as_deref(),
+        until: params.until.as_deref(),
+        limit: params.limit.unwrap_or(50).min(500),
+    };
+    match open_store(&state)
+        .and_then(|conn| crate::store::history::search_history(&conn, &params.q, &filter))
+    {
+        Ok(messages) => Json(serde_json::json!({"messages": messages})).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": format!("History search failed: {e}")})),
+        )
+            .into_response(),
+    }
+}
+
 /// GET /api/cost — cost summary
 pub async fn handle_api_cost(
     State(state): State<AppState>,
@@ -7,6 +7,7 @@
 //! - Request timeouts (30s) to prevent slow-loris attacks
 //! - Header sanitization (handled by axum/hyper)
 
+pub mod a2a;
 pub mod api;
 pub mod oauth;
 pub mod sse;
@@ -14,8 +15,10 @@ pub mod static_files;
 pub mod ws;
 
 use crate::channels::{Channel, LinqChannel, NextcloudTalkChannel, SendMessage, WhatsAppChannel};
+use crate::config::reload::{run_config_watcher, ConfigReloader};
 use crate::config::Config;
 use crate::cost::CostTracker;
+use crate::memory::user_scoped::UserScopedMemory;
 use crate::memory::{self, Memory, MemoryCategory};
 use crate::providers::{self, ChatMessage, Provider};
 use crate::runtime;
@@ -33,17 +36,23 @@ use axum::{
     routing::{delete, get, post, put},
     Router,
 };
-use parking_lot::Mutex;
+use parking_lot::{Mutex, RwLock};
 use std::collections::HashMap;
+use std::fmt::Write;
 use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tower_http::limit::RequestBodyLimitLayer;
 use tower_http::timeout::TimeoutLayer;
+use tracing::Instrument;
 use uuid::Uuid;
 
 /// Maximum request body size (64KB) — prevents memory exhaustion
 pub const MAX_BODY_SIZE: usize = 65_536;
+/// Maximum inbound email size on `/email` (1MB)
+pub const MAX_EMAIL_BODY_SIZE: usize = 1_048_576;
+/// Maximum A2A task submission size on `/a2a/tasks` (32MB), for project files
+pub const MAX_A2A_BODY_SIZE: usize = 33_554_432;
 /// Request timeout (30s) — prevents slow-loris attacks
 pub const REQUEST_TIMEOUT_SECS: u64 = 30;
 /// Sliding window used by gateway rate limiting.
@@ -272,14 +281,67 @@ fn normalize_max_keys(configured: usize, fallback: usize) -> usize {
     }
 }
 
-/// Shared state for all axum handlers
+/// Provider, model and temperature the gateway chats with by default.
 #[derive(Clone)]
-pub struct AppState {
-    pub config: Arc<Mutex<Config>>,
+pub struct GatewayChat {
     pub provider: Arc<dyn Provider>,
     pub model: String,
     pub temperature: f64,
+}
+
+impl GatewayChat {
+    /// Chat settings from `config`'s defaults.
+    pub fn from_config(config: &Config) -> Result<Self> {
+        Ok(Self {
+            provider: create_chat_provider(config)?,
+            model: chat_model(config),
+            temperature: config.default_temperature,
+        })
+    }
+
+    /// Chat settings after a live config change from `old` to `new`. The
+    /// provider is only rebuilt when the settings it is built from changed;
+    /// if that fails the previous provider is kept.
+    fn reload(&self, old: &Config, new: &Config) -> Self {
+        let provider_settings = |c: &Config| {
+            serde_json::to_value((&c.default_provider, &c.api_key, &c.api_url, &c.reliability)).ok()
+        };
+        let provider = if provider_settings(old) == provider_settings(new) {
+            Arc::clone(&self.provider)
+        } else {
+            create_chat_provider(new).unwrap_or_else(|e| {
+                tracing::warn!("Keeping the previous gateway provider: {e}");
+                Arc::clone(&self.provider)
+            })
+        };
+        Self {
+            provider,
+            model: chat_model(new),
+            temperature: new.default_temperature,
+        }
+    }
+}
+
+/// Apply the live sections of a reloaded config to the gateway's `config`
+/// and default `chat` settings.
+fn apply_live_config(config: &Mutex<Config>, chat: &RwLock<GatewayChat>, new: &Config) {
+    let mut config = config.lock();
+    let old = config.clone();
+    crate::config::reload::apply_live(&mut config, new);
+    let reloaded = chat.read().reload(&old, &config);
+    *chat.write() = reloaded;
+}
+
+/// Shared state for all axum handlers
+#[derive(Clone)]
+pub struct AppState {
+    pub config: Arc<Mutex<Config>>,
+    /// Default chat settings, replaced when a config reload changes them
+    pub chat: Arc<RwLock<GatewayChat>>,
     pub mem: Arc<dyn Memory>,
+    /// Per-user view over `mem`, shared so its resident-user cache and
+    /// eviction counters persist across requests
+    pub user_memory: Arc<UserScopedMemory>,
     pub auto_save: bool,
     /// SHA-256 hash of `X-Webhook-Secret` (hex-encoded), never plaintext.
     pub webhook_secret_hash: Option<Arc<str>>,
@@ -304,6 +366,78 @@ pub struct AppState {
     pub cost_tracker: Option<Arc<CostTracker>>,
     /// SSE broadcast channel for real-time events
     pub event_tx: tokio::sync::broadcast::Sender<serde_json::Value>,
+    /// Config file watcher (`None` when the gateway runs without one)
+    pub config_reloader: Option<Arc<ConfigReloader>>,
+}
+
+impl AppState {
+    /// The default chat settings in effect now.
+    pub fn chat(&self) -> GatewayChat {
+        self.chat.read().clone()
+    }
+}
+
+/// Runtime options for gateway chat providers.
+fn chat_provider_options(config: &Config) -> providers::ProviderRuntimeOptions {
+    providers::ProviderRuntimeOptions {
+        auth_profile_override: None,
+        zerobuild_dir: config.config_path.parent().map(std::path::PathBuf::from),
+        secrets_encrypt: config.secrets.encrypt,
+        reasoning_enabled: config.runtime.reasoning_enabled,
+        local_provider: config.local_provider.clone(),
+        prompt_caching: config.agent.prompt_caching,
+    }
+}
+
+/// Provider for gateway chat built from `config`'s defaults.
+pub(crate) fn create_chat_provider(config: &Config) -> Result<Arc<dyn Provider>> {
+    Ok(Arc::from(
+        providers::create_resilient_provider_with_options(
+            config.default_provider.as_deref().unwrap_or("openrouter"),
+            config.api_key.as_deref(),
+            config.api_url.as_deref(),
+            &config.reliability,
+            &chat_provider_options(config),
+        )?,
+    ))
+}
+
+/// Provider, provider name and model for the `model` field of a webhook
+/// request. A provider other than the default one is created for the
+/// request.
+fn resolve_webhook_model(
+    config: &Config,
+    default: &Arc<dyn Provider>,
+    default_provider: &str,
+    spec: &str,
+) -> std::result::Result<(Arc<dyn Provider>, String, String), String> {
+    let (name, model) =
+        crate::channels::resolve_model_override(&config.workspace_dir, default_provider, spec)?;
+    if name == default_provider {
+        return Ok((Arc::clone(default), name, model));
+    }
+    let provider = providers::create_resilient_provider_with_options(
+        &name,
+        config.api_key.as_deref(),
+        None,
+        &config.reliability,
+        &chat_provider_options(config),
+    )
+    .map_err(|e| {
+        format!(
+            "Failed to initialize provider `{name}`: {}",
+            providers::sanitize_api_error(&e.to_string())
+        )
+    })?;
+    Ok((Arc::from(provider), name, model))
+}
+
+/// Model for gateway chat from `config`'s defaults.
+pub(crate) fn chat_model(config: &Config) -> String {
+    config
+        .default_model
+        .clone()
+        .unwrap_or_else(|| "anthropic/claude-sonnet-4".into())
 }
 
 /// Run the HTTP gateway using axum with proper HTTP/1.1 compliance.
@@ -319,6 +453,28 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
         );
     }
     let config_state = Arc::new(Mutex::new(config.clone()));
+    let chat = Arc::new(RwLock::new(GatewayChat::from_config(&config)?));
+
+    // ── Config hot reload ──────────────────────────────────────────
+    let config_reloader = Arc::new(ConfigReloader::new(config.clone()));
+    let config_watcher = {
+        let reloader = Arc::clone(&config_reloader);
+        let config_state = Arc::clone(&config_state);
+        let chat = Arc::clone(&chat);
+        let mut updates = reloader.subscribe();
+        tokio::spawn(async move {
+            let apply = async move {
+                while updates.changed().await.is_ok() {
+                    let effective = updates.borrow_and_update().clone();
+                    apply_live_config(&config_state, &chat, &effective);
+                }
+            };
+            tokio::join!(
+                run_config_watcher(reloader, crate::config::reload::DEFAULT_POLL_INTERVAL),
+                apply
+            );
+        })
+    };
 
     // ── Hooks ──────────────────────────────────────────────────────
     let hooks: Option<std::sync::Arc<crate::hooks::HookRunner>> = if config.hooks.enabled {
@@ -332,23 +488,6 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
     let actual_port = listener.local_addr()?.port();
     let display_addr = format!("{host}:{actual_port}");
 
-    let provider: Arc<dyn Provider> = Arc::from(providers::create_resilient_provider_with_options(
-        config.default_provider.as_deref().unwrap_or("openrouter"),
-        config.api_key.as_deref(),
-        config.api_url.as_deref(),
-        &config.reliability,
-        &providers::ProviderRuntimeOptions {
-            auth_profile_override: None,
-            zerobuild_dir: config.config_path.parent().map(std::path::PathBuf::from),
-            secrets_encrypt: config.secrets.encrypt,
-            reasoning_enabled: config.runtime.reasoning_enabled,
-        },
-    )?);
-    let model = config
-        .default_model
-        .clone()
-        .unwrap_or_else(|| "anthropic/claude-sonnet-4".into());
-    let temperature = config.default_temperature;
     let mem: Arc<dyn Memory> = Arc::from(memory::create_memory_with_storage(
         &config.memory,
         Some(&config.storage.provider.config),
@@ -376,6 +515,7 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
         &security,
         runtime,
         Arc::clone(&mem),
+        Arc::clone(&mem),
         composio_key,
         composio_entity_id,
         &config.browser,
@@ -420,12 +560,17 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
         .as_ref()
         .filter(|wa| wa.is_cloud_config())
         .map(|wa| {
-            Arc::new(WhatsAppChannel::new(
+            let mut channel = WhatsAppChannel::new(
                 wa.access_token.clone().unwrap_or_default(),
                 wa.phone_number_id.clone().unwrap_or_default(),
                 wa.verify_token.clone().unwrap_or_default(),
                 wa.allowed_numbers.clone(),
-            ))
+            )
+            .with_workspace_dir(config.workspace_dir.clone());
+            if let Some((name, language)) = wa.reply_template_with_language() {
+                channel = channel.with_reply_template(name, language);
+            }
+            Arc::new(channel)
         });
 
     // WhatsApp app secret for webhook signature verification
@@ -567,6 +712,17 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
     if nextcloud_talk_channel.is_some() {
         println!("  POST /nextcloud-talk — Nextcloud Talk bot webhook");
     }
+    if config
+        .channels_config
+        .email
+        .as_ref()
+        .is_some_and(|email| email.inbound == "webhook")
+    {
+        println!("  POST /email     — inbound email (SES via SNS, Mailgun, raw MIME)");
+    }
+    if config.factory.a2a.enabled {
+        println!("  POST /a2a/tasks — tasks delegated by other ZeroBuild instances");
+    }
     println!("  GET  /api/*     — REST API (bearer token required)");
     println!("  GET  /ws/chat   — WebSocket agent chat");
     println!("  GET  /health    — health check");
@@ -599,12 +755,17 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
             event_tx.clone(),
         ));
 
+    let user_memory = Arc::new(UserScopedMemory::with_global(
+        &config.workspace_dir,
+        &config.memory,
+        config.api_key.as_deref(),
+        Arc::clone(&mem),
+    ));
     let state = AppState {
         config: config_state,
-        provider,
-        model,
-        temperature,
+        chat,
         mem,
+        user_memory,
         auto_save: config.memory.auto_save,
         webhook_secret_hash,
         pairing,
@@ -621,6 +782,7 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
         tools_registry,
         cost_tracker,
         event_tx,
+        config_reloader: Some(config_reloader),
     };
 
     // Config PUT needs larger body limit (1MB)
@@ -628,6 +790,20 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
         .route("/api/config", put(api::handle_api_config_put))
         .layer(RequestBodyLimitLayer::new(1_048_576));
 
+    // Inbound email is merged after the default body limit so whole messages fit
+    let email_router = Router::new()
+        .route("/email", post(handle_email_webhook))
+        .with_state(state.clone())
+        .layer(RequestBodyLimitLayer::new(MAX_EMAIL_BODY_SIZE));
+
+    // A2A task submissions carry project files
+    let a2a_router = Router::new()
+        .route("/a2a/card", get(a2a::handle_a2a_card))
+        .route("/a2a/tasks", post(a2a::handle_a2a_submit))
+        .route("/a2a/tasks/{id}", get(a2a::handle_a2a_task))
+        .with_state(state.clone())
+        .layer(RequestBodyLimitLayer::new(MAX_A2A_BODY_SIZE));
+
     // Build router with middleware
     let app = Router::new()
         // ── Existing routes ──
@@ -642,15 +818,61 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
         // ── Web Dashboard API routes ──
         .route("/api/status", get(api::handle_api_status))
         .route("/api/config", get(api::handle_api_config_get))
+        .route(
+            "/api/config/reload",
+            get(api::handle_api_config_reload_status),
+        )
+        .route("/api/config/reload", post(api::handle_api_config_reload))
+        .route("/api/init", get(api::handle_api_init_status))
+        .route("/api/init", post(api::handle_api_init))
+        .route("/api/init/test", post(api::handle_api_init_test))
         .route("/api/tools", get(api::handle_api_tools))
         .route("/api/cron", get(api::handle_api_cron_list))
         .route("/api/cron", post(api::handle_api_cron_add))
         .route("/api/cron/{id}", delete(api::handle_api_cron_delete))
+        .route("/api/checkpoints", get(api::handle_api_checkpoints_list))
+        .route("/api/checkpoints", post(api::handle_api_checkpoints_create))
+        .route(
+            "/api/checkpoints/{name}/switch",
+            post(api::handle_api_checkpoints_switch),
+        )
+        .route("/api/logs/{id}", get(api::handle_api_log_download))
+        .route(
+            "/api/sessions/{id}/report",
+            get(api::handle_api_session_report),
+        )
+        .route(
+            "/api/v1/sessions/{id}/timeline",
+            get(api::handle_api_session_timeline),
+        )
+        .route("/api/artifacts/usage", get(api::handle_api_artifacts_usage))
         .route("/api/integrations", get(api::handle_api_integrations))
         .route("/api/doctor", post(api::handle_api_doctor))
         .route("/api/memory", get(api::handle_api_memory_list))
         .route("/api/memory", post(api::handle_api_memory_store))
         .route("/api/memory/{key}", delete(api::handle_api_memory_delete))
+        .route("/api/memory/stats", get(api::handle_api_memory_stats))
+        .route(
+            "/api/memory/stores/{store}/entries",
+            get(api::handle_api_memory_browse),
+        )
+        .route(
+            "/api/memory/stores/{store}/entries/{key}",
+            get(api::handle_api_memory_inspect),
+        )
+        .route(
+            "/api/memory/users/{user_id}",
+            delete(api::handle_api_memory_user_wipe),
+        )
+        .route(
+            "/api/memory/users/{user_id}/export",
+            get(api::handle_api_memory_user_export),
+        )
+        .route(
+            "/api/memory/users/{user_id}/import",
+            post(api::handle_api_memory_user_import),
+        )
+        .route("/api/history/search", get(api::handle_api_history_search))
         .route("/api/cost", get(api::handle_api_cost))
         .route("/api/cli-tools", get(api::handle_api_cli_tools))
         .route("/api/health", get(api::handle_api_health))
@@ -667,6 +889,8 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
         .merge(config_put_router)
         .with_state(state)
         .layer(RequestBodyLimitLayer::new(MAX_BODY_SIZE))
+        .merge(email_router)
+        .merge(a2a_router)
         .layer(TimeoutLayer::with_status_code(
             StatusCode::REQUEST_TIMEOUT,
             Duration::from_secs(REQUEST_TIMEOUT_SECS),
@@ -675,11 +899,13 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
         .fallback(get(static_files::handle_spa_fallback));
 
     // Run the server
-    axum::serve(
+    let served = axum::serve(
         listener,
         app.into_make_service_with_connect_info::<SocketAddr>(),
     )
-    .await?;
+    .await;
+    config_watcher.abort();
+    served?;
 
     Ok(())
 }
@@ -701,6 +927,34 @@ async fn handle_health(State(state): State<AppState>) -> impl IntoResponse {
 /// Prometheus content type for text exposition format.
 const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
 
+/// Prometheus text for the user memory eviction counters.
+fn memory_eviction_metrics(stats: crate::memory::user_scoped::EvictionStats) -> String {
+    let mut out = String::new();
+    for (name, help, value) in [
+        (
+            "zerobuild_memory_resident_evictions_total",
+            "User memory backends closed by the resident-user LRU",
+            stats.resident_evictions,
+        ),
+        (
+            "zerobuild_memory_entry_evictions_total",
+            "User memory entries deleted to satisfy per-user quotas",
+            stats.entry_evictions,
+        ),
+        (
+            "zerobuild_memory_evicted_bytes_total",
+            "Content bytes freed by user memory entry evictions",
+            stats.bytes_evicted,
+        ),
+    ] {
+        let _ = write!(
+            out,
+            "# HELP {name} {help}\n# TYPE {name} counter\n{name} {value}\n"
+        );
+    }
+    out
+}
+
 /// GET /metrics — Prometheus text exposition format
 async fn handle_metrics(State(state): State<AppState>) -> impl IntoResponse {
     let body = if let Some(prom) = state
@@ -709,7 +963,11 @@ async fn handle_metrics(State(state): State<AppState>) -> impl IntoResponse {
         .as_any()
         .downcast_ref::<crate::observability::PrometheusObserver>()
     {
-        prom.encode()
+        let mut body = prom.encode();
+        body.push_str(&memory_eviction_metrics(
+            crate::memory::user_scoped::eviction_stats(),
+        ));
+        body
     } else {
         String::from("# Prometheus backend not enabled. Set [observability] backend = \"prometheus\" in config.\n")
     };
@@ -800,22 +1058,51 @@ async fn persist_pairing_tokens(config: Arc<Mutex<Config>>, pairing: &PairingGua
     Ok(())
 }
 
+/// Session span for a message from a gateway-hosted channel.
+fn channel_session_span(msg: &crate::channels::traits::ChannelMessage) -> tracing::Span {
+    crate::observability::logging::session_span(
+        &format!("{}_{}", msg.channel, msg.sender),
+        &msg.sender,
+        &msg.channel,
+    )
+}
+
+/// `prompt` with the current user's stored preferences appended.
+pub(crate) fn with_preferences(mut prompt: String, db_path: &str) -> String {
+    let preferences = crate::store::preferences::load_prompt_section(
+        std::path::Path::new(db_path),
+        &crate::store::preferences::current_user(),
+    );
+    if !preferences.is_empty() {
+        prompt.push_str("\n\n");
+        prompt.push_str(&preferences);
+    }
+    prompt
+}
+
 /// Simple chat for webhook endpoint (no tools, for backward compatibility and testing).
-async fn run_gateway_chat_simple(state: &AppState, message: &str) -> anyhow::Result<String> {
+async fn run_gateway_chat_simple(
+    state: &AppState,
+    provider: &dyn Provider,
+    model: &str,
+    temperature: f64,
+    message: &str,
+) -> anyhow::Result<String> {
     let user_messages = vec![ChatMessage::user(message)];
 
     // Keep webhook/gateway prompts aligned with channel behavior by injecting
     // workspace-aware system context before model invocation.
     let system_prompt = {
         let config_guard = state.config.lock();
-        crate::channels::build_system_prompt(
+        let prompt = crate::channels::build_system_prompt(
             &config_guard.workspace_dir,
-            &state.model,
+            model,
             &[], // tools - empty for simple chat
             &[], // skills
             Some(&config_guard.identity),
             None, // bootstrap_max_chars - use default
-        )
+        );
+        with_preferences(prompt, &config_guard.zerobuild.db_path)
     };
 
     let mut messages = Vec::with_capacity(1 + user_messages.len());
@@ -826,22 +1113,52 @@ async fn run_gateway_chat_simple(state: &AppState, message: &str) -> anyhow::Res
     let prepared =
         crate::multimodal::prepare_messages_for_provider(&messages, &multimodal_config).await?;
 
-    state
-        .provider
-        .chat_with_history(&prepared.messages, &state.model, state.temperature)
+    provider
+        .chat_with_history(&prepared.messages, model, temperature)
         .await
 }
 
 /// Full-featured chat with tools for channel handlers (WhatsApp, Linq, Nextcloud Talk).
+/// With `[moderation]` enabled, a flagged message is answered with the
+/// refusal and never reaches the agent.
 async fn run_gateway_chat_with_tools(state: &AppState, message: &str) -> anyhow::Result<String> {
     let config = state.config.lock().clone();
-    crate::agent::process_message(config, message).await
+    if config.moderation.enabled {
+        let moderation = crate::security::moderation::Moderation::from_config(
+            &config.moderation,
+            config.config_path.parent(),
+        )?;
+        let sender = crate::security::users::current_user().unwrap_or_else(|| "unknown".into());
+        if let Some(refusal) = moderation.screen("gateway", &sender, message).await {
+            return Ok(refusal);
+        }
+    }
+    Box::pin(crate::agent::process_message(config, message)).await
+}
+
+/// Agent turn with tools for a message from a gateway-hosted channel, on
+/// behalf of its sender (see [`crate::security::users`]).
+async fn run_channel_chat(
+    state: &AppState,
+    msg: &crate::channels::traits::ChannelMessage,
+) -> anyhow::Result<String> {
+    crate::security::users::with_user(
+        format!("{}:{}", msg.channel, msg.sender),
+        Box::pin(run_gateway_chat_with_tools(state, &msg.content)),
+    )
+    .instrument(channel_session_span(msg))
+    .await
 }
 
 /// Webhook request body
 #[derive(serde::Deserialize)]
 pub struct WebhookBody {
     pub message: String,
+    /// Model for this request only: `<provider>:<model-id>` or a model of the
+    /// default provider. Must be in the provider's cached model catalog when
+    /// there is one.
+    #[serde(default)]
+    pub model: Option<String>,
 }
 
 /// POST /webhook — main webhook endpoint
@@ -936,13 +1253,28 @@ async fn handle_webhook(
             .await;
     }
 
-    let provider_label = state
-        .config
-        .lock()
-        .default_provider
-        .clone()
-        .unwrap_or_else(|| "unknown".to_string());
-    let model_label = state.model.clone();
+    let config = state.config.lock().clone();
+    let chat = state.chat();
+    let default_provider = config.default_provider.as_deref().unwrap_or("openrouter");
+    let (provider, provider_label, model_label) = match webhook_body.model.as_deref() {
+        None => (
+            Arc::clone(&chat.provider),
+            config
+                .default_provider
+                .clone()
+                .unwrap_or_else(|| "unknown".to_string()),
+            chat.model.clone(),
+        ),
+        Some(spec) => {
+            match resolve_webhook_model(&config, &chat.provider, default_provider, spec) {
+                Ok(resolved) => resolved,
+                Err(e) => {
+                    let err = serde_json::json!({ "error": e });
+                    return (StatusCode::BAD_REQUEST, Json(err));
+                }
+            }
+        }
+    };
     let started_at = Instant::now();
 
     state
@@ -959,7 +1291,21 @@ async fn handle_webhook(
             messages_count: 1,
         });
 
-    match run_gateway_chat_simple(&state, message).await {
+    let session = crate::observability::logging::session_span(
+        &crate::observability::logging::new_session_id(),
+        &rate_key,
+        "webhook",
+    );
+    match run_gateway_chat_simple(
+        &state,
+        provider.as_ref(),
+        &model_label,
+        chat.temperature,
+        message,
+    )
+    .instrument(session)
+    .await
+    {
         Ok(response) => {
             let duration = started_at.elapsed();
             state
@@ -972,6 +1318,7 @@ async fn handle_webhook(
                     error_message: None,
                     input_tokens: None,
                     output_tokens: None,
+                    cached_input_tokens: None,
                 });
             state.observer.record_metric(
                 &crate::observability::traits::ObserverMetric::RequestLatency(duration),
@@ -980,13 +1327,13 @@ async fn handle_webhook(
                 .observer
                 .record_event(&crate::observability::ObserverEvent::AgentEnd {
                     provider: provider_label,
-                    model: model_label,
+                    model: model_label.clone(),
                     duration,
                     tokens_used: None,
                     cost_usd: None,
                 });
 
-            let body = serde_json::json!({"response": response, "model": state.model});
+            let body = serde_json::json!({"response": response, "model": model_label});
             (StatusCode::OK, Json(body))
         }
         Err(e) => {
@@ -1003,6 +1350,7 @@ async fn handle_webhook(
                     error_message: Some(sanitized.clone()),
                     input_tokens: None,
                     output_tokens: None,
+                    cached_input_tokens: None,
                 });
             state.observer.record_metric(
                 &crate::observability::traits::ObserverMetric::RequestLatency(duration),
@@ -1138,8 +1486,8 @@ async fn handle_whatsapp_message(
         );
     };
 
-    // Parse messages from the webhook payload
-    let messages = wa.parse_webhook_payload(&payload);
+    // Parse messages from the webhook payload, downloading attached media
+    let messages = wa.receive_webhook_payload(&payload).await;
 
     if messages.is_empty() {
         // Acknowledge the webhook even if no messages (could be status updates)
@@ -1149,9 +1497,9 @@ async fn handle_whatsapp_message(
     // Process each message
     for msg in &messages {
         tracing::info!(
-            "WhatsApp message from {}: {}",
-            msg.sender,
-            truncate_with_ellipsis(&msg.content, 50)
+            sender = %msg.sender,
+            preview = %truncate_with_ellipsis(&msg.content, 50),
+            "WhatsApp message received"
         );
 
         // Auto-save to memory
@@ -1163,7 +1511,7 @@ async fn handle_whatsapp_message(
                 .await;
         }
 
-        match run_gateway_chat_with_tools(&state, &msg.content).await {
+        match run_channel_chat(&state, msg).await {
             Ok(response) => {
                 // Send reply via WhatsApp
                 if let Err(e) = wa
@@ -1271,7 +1619,7 @@ async fn handle_linq_webhook(
         }
 
         // Call the LLM
-        match run_gateway_chat_with_tools(&state, &msg.content).await {
+        match run_channel_chat(&state, msg).await {
             Ok(response) => {
                 // Send reply via Linq
                 if let Err(e) = linq
@@ -1375,7 +1723,7 @@ async fn handle_nextcloud_talk_webhook(
                 .await;
         }
 
-        match run_gateway_chat_with_tools(&state, &msg.content).await {
+        match run_channel_chat(&state, msg).await {
             Ok(response) => {
                 if let Err(e) = nextcloud_talk
                     .send(&SendMessage::new(response, &msg.reply_target))
@@ -1399,6 +1747,120 @@ async fn handle_nextcloud_talk_webhook(
     (StatusCode::OK, Json(serde_json::json!({"status": "ok"})))
 }
 
+/// `/email` query parameters
+#[derive(serde::Deserialize)]
+pub struct EmailWebhookQuery {
+    /// The inbound secret, for senders that cannot set headers
+    pub token: Option<String>,
+}
+
+/// Whether `url` is an SNS endpoint, the only place subscriptions are
+/// confirmed at.
+fn is_sns_url(url: &str) -> bool {
+    reqwest::Url::parse(url).is_ok_and(|url| {
+        url.scheme() == "https"
+            && url
+                .host_str()
+                .is_some_and(|host| host.starts_with("sns.") && host.ends_with(".amazonaws.com"))
+    })
+}
+
+/// POST /email — inbound email for the email channel in webhook mode (SES
+/// receipts through SNS, Mailgun routes or a raw message)
+async fn handle_email_webhook(
+    State(state): State<AppState>,
+    Query(query): Query<EmailWebhookQuery>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> impl IntoResponse {
+    use crate::channels::email_channel::{deliver_inbound, parse_inbound_webhook, InboundWebhook};
+
+    let secret = state
+        .config
+        .lock()
+        .channels_config
+        .email
+        .as_ref()
+        .filter(|email| email.inbound == "webhook")
+        .and_then(|email| email.webhook_secret.clone())
+        .filter(|secret| !secret.trim().is_empty());
+    let Some(secret) = secret else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({"error": "Inbound email not configured"})),
+        );
+    };
+
+    // ── Security: the shared secret, as a header or in the URL ──
+    let presented = headers
+        .get("X-Webhook-Secret")
+        .and_then(|v| v.to_str().ok())
+        .or(query.token.as_deref())
+        .map_or("", str::trim);
+    if !constant_time_eq(presented, secret.trim()) {
+        tracing::warn!("Email webhook: rejected request — invalid or missing secret");
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(serde_json::json!({"error": "Invalid secret"})),
+        );
+    }
+
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+    match parse_inbound_webhook(content_type, &body) {
+        Ok(InboundWebhook::Email(email)) => {
+            tracing::info!(
+                sender = %email.sender,
+                subject = %truncate_with_ellipsis(&email.subject, 50),
+                "Email received"
+            );
+            if !deliver_inbound(email) {
+                // Non-2xx makes SES and Mailgun retry later
+                return (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    Json(serde_json::json!({"error": "Email channel is not running"})),
+                );
+            }
+        }
+        Ok(InboundWebhook::ConfirmSubscription(url)) => {
+            if !is_sns_url(&url) {
+                tracing::warn!("Email webhook: refusing to confirm a subscription at {url}");
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(serde_json::json!({"error": "SubscribeURL is not an SNS endpoint"})),
+                );
+            }
+            let confirmed = crate::config::build_runtime_proxy_client("channel.email")
+                .get(&url)
+                .send()
+                .await
+                .and_then(reqwest::Response::error_for_status);
+            match confirmed {
+                Ok(_) => tracing::info!("Email webhook: SNS subscription confirmed"),
+                Err(e) => {
+                    tracing::error!("Email webhook: SNS subscription confirmation failed: {e}");
+                    return (
+                        StatusCode::BAD_GATEWAY,
+                        Json(serde_json::json!({"error": "Subscription confirmation failed"})),
+                    );
+                }
+            }
+        }
+        Ok(InboundWebhook::Ignored) => {}
+        Err(e) => {
+            tracing::warn!("Email webhook: unreadable payload: {e:#}");
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({"error": "Unreadable email payload"})),
+            );
+        }
+    }
+
+    (StatusCode::OK, Json(serde_json::json!({"status": "ok"})))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1450,6 +1912,23 @@ mod tests {
         assert!(q.mode.is_none());
     }
 
+    fn test_chat(provider: Arc<dyn Provider>) -> Arc<RwLock<GatewayChat>> {
+        Arc::new(RwLock::new(GatewayChat {
+            provider,
+            model: "test-model".into(),
+            temperature: 0.0,
+        }))
+    }
+
+    fn test_user_memory(mem: Arc<dyn Memory>) -> Arc<UserScopedMemory> {
+        Arc::new(UserScopedMemory::with_global(
+            &std::env::temp_dir().join("zerobuild-gateway-test"),
+            &crate::config::MemoryConfig::default(),
+            None,
+            mem,
+        ))
+    }
+
     #[test]
     fn app_state_is_clone() {
         fn assert_clone<T: Clone>() {}
@@ -1460,10 +1939,9 @@ mod tests {
     async fn metrics_endpoint_returns_hint_when_prometheus_is_disabled() {
         let state = AppState {
             config: Arc::new(Mutex::new(Config::default())),
-            provider: Arc::new(MockProvider::default()),
-            model: "test-model".into(),
-            temperature: 0.0,
+            chat: test_chat(Arc::new(MockProvider::default())),
             mem: Arc::new(MockMemory),
+            user_memory: test_user_memory(Arc::new(MockMemory)),
             auto_save: false,
             webhook_secret_hash: None,
             pairing: Arc::new(PairingGuard::new(false, &[])),
@@ -1480,6 +1958,7 @@ mod tests {
             tools_registry: Arc::new(Vec::new()),
             cost_tracker: None,
             event_tx: tokio::sync::broadcast::channel(16).0,
+            config_reloader: None,
         };
 
         let response = handle_metrics(State(state)).await.into_response();
@@ -1508,10 +1987,9 @@ mod tests {
         let observer: Arc<dyn crate::observability::Observer> = prom;
         let state = AppState {
             config: Arc::new(Mutex::new(Config::default())),
-            provider: Arc::new(MockProvider::default()),
-            model: "test-model".into(),
-            temperature: 0.0,
+            chat: test_chat(Arc::new(MockProvider::default())),
             mem: Arc::new(MockMemory),
+            user_memory: test_user_memory(Arc::new(MockMemory)),
             auto_save: false,
             webhook_secret_hash: None,
             pairing: Arc::new(PairingGuard::new(false, &[])),
@@ -1528,6 +2006,7 @@ mod tests {
             tools_registry: Arc::new(Vec::new()),
             cost_tracker: None,
             event_tx: tokio::sync::broadcast::channel(16).0,
+            config_reloader: None,
         };
 
         let response = handle_metrics(State(state)).await.into_response();
@@ -1536,6 +2015,7 @@ mod tests {
         let body = response.into_body().collect().await.unwrap().to_bytes();
         let text = String::from_utf8(body.to_vec()).unwrap();
         assert!(text.contains("zerobuild_heartbeat_ticks_total 1"));
+        assert!(text.contains("# TYPE zerobuild_memory_entry_evictions_total counter"));
     }
 
     #[test]
@@ -1788,6 +2268,7 @@ mod tests {
     #[derive(Default)]
     struct MockProvider {
         calls: AtomicUsize,
+        models: Mutex<Vec<String>>,
     }
 
     #[async_trait]
@@ -1796,10 +2277,11 @@ mod tests {
             &self,
             _system_prompt: Option<&str>,
             _message: &str,
-            _model: &str,
+            model: &str,
             _temperature: f64,
         ) -> anyhow::Result<String> {
             self.calls.fetch_add(1, Ordering::SeqCst);
+            self.models.lock().push(model.to_string());
             Ok("ok".into())
         }
     }
@@ -1873,9 +2355,8 @@ mod tests {
 
         let state = AppState {
             config: Arc::new(Mutex::new(Config::default())),
-            provider,
-            model: "test-model".into(),
-            temperature: 0.0,
+            chat: test_chat(provider),
+            user_memory: test_user_memory(Arc::clone(&memory)),
             mem: memory,
             auto_save: false,
             webhook_secret_hash: None,
@@ -1893,6 +2374,7 @@ mod tests {
             tools_registry: Arc::new(Vec::new()),
             cost_tracker: None,
             event_tx: tokio::sync::broadcast::channel(16).0,
+            config_reloader: None,
         };
 
         let mut headers = HeaderMap::new();
@@ -1900,6 +2382,7 @@ mod tests {
 
         let body = Ok(Json(WebhookBody {
             message: "hello".into(),
+            model: None,
         }));
         let first = handle_webhook(
             State(state.clone()),
@@ -1913,6 +2396,7 @@ mod tests {
 
         let body = Ok(Json(WebhookBody {
             message: "hello".into(),
+            model: None,
         }));
         let second = handle_webhook(State(state), test_connect_info(), headers, body)
             .await
@@ -1926,6 +2410,63 @@ mod tests {
         assert_eq!(provider_impl.calls.load(Ordering::SeqCst), 1);
     }
 
+    #[tokio::test]
+    async fn webhook_uses_the_model_of_a_reloaded_config() {
+        let provider_impl = Arc::new(MockProvider::default());
+        let provider: Arc<dyn Provider> = provider_impl.clone();
+        let config = Config {
+            default_model: Some("old-model".into()),
+            ..Config::default()
+        };
+
+        let state = AppState {
+            config: Arc::new(Mutex::new(config.clone())),
+            chat: test_chat(provider),
+            mem: Arc::new(MockMemory),
+            user_memory: test_user_memory(Arc::new(MockMemory)),
+            auto_save: false,
+            webhook_secret_hash: None,
+            pairing: Arc::new(PairingGuard::new(false, &[])),
+            trust_forwarded_headers: false,
+            rate_limiter: Arc::new(GatewayRateLimiter::new(100, 100, 100)),
+            idempotency_store: Arc::new(IdempotencyStore::new(Duration::from_secs(300), 1000)),
+            whatsapp: None,
+            whatsapp_app_secret: None,
+            linq: None,
+            linq_signing_secret: None,
+            nextcloud_talk: None,
+            nextcloud_talk_webhook_secret: None,
+            observer: Arc::new(crate::observability::NoopObserver),
+            tools_registry: Arc::new(Vec::new()),
+            cost_tracker: None,
+            event_tx: tokio::sync::broadcast::channel(16).0,
+            config_reloader: None,
+        };
+
+        let reloaded = Config {
+            default_model: Some("new-model".into()),
+            default_temperature: 0.2,
+            ..config
+        };
+        apply_live_config(&state.config, &state.chat, &reloaded);
+
+        let body = Ok(Json(WebhookBody {
+            message: "hello".into(),
+            model: None,
+        }));
+        let response = handle_webhook(
+            State(state.clone()),
+            test_connect_info(),
+            HeaderMap::new(),
+            body,
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(*provider_impl.models.lock(), ["new-model"]);
+        assert!((state.chat().temperature - 0.2).abs() < f64::EPSILON);
+    }
+
     #[tokio::test]
     async fn webhook_autosave_stores_distinct_keys_per_request() {
         let provider_impl = Arc::new(MockProvider::default());
@@ -1936,9 +2477,8 @@ mod tests {
 
         let state = AppState {
             config: Arc::new(Mutex::new(Config::default())),
-            provider,
-            model: "test-model".into(),
-            temperature: 0.0,
+            chat: test_chat(provider),
+            user_memory: test_user_memory(Arc::clone(&memory)),
             mem: memory,
             auto_save: true,
             webhook_secret_hash: None,
@@ -1956,12 +2496,14 @@ mod tests {
             tools_registry: Arc::new(Vec::new()),
             cost_tracker: None,
             event_tx: tokio::sync::broadcast::channel(16).0,
+            config_reloader: None,
         };
 
         let headers = HeaderMap::new();
 
         let body1 = Ok(Json(WebhookBody {
             message: "hello one".into(),
+            model: None,
         }));
         let first = handle_webhook(
             State(state.clone()),
@@ -1975,6 +2517,7 @@ mod tests {
 
         let body2 = Ok(Json(WebhookBody {
             message: "hello two".into(),
+            model: None,
         }));
         let second = handle_webhook(State(state), test_connect_info(), headers, body2)
             .await
@@ -1989,6 +2532,76 @@ mod tests {
         assert_eq!(provider_impl.calls.load(Ordering::SeqCst), 2);
     }
 
+    #[tokio::test]
+    async fn webhook_model_field_overrides_model_checked_against_catalog() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(tmp.path().join("state")).unwrap();
+        std::fs::write(
+            tmp.path().join("state").join("models_cache.json"),
+            r#"{"entries":[{"provider":"openrouter","models":["openai/gpt-4o"]}]}"#,
+        )
+        .unwrap();
+        let provider_impl = Arc::new(MockProvider::default());
+        let provider: Arc<dyn Provider> = provider_impl.clone();
+
+        let state = AppState {
+            config: Arc::new(Mutex::new(Config {
+                workspace_dir: tmp.path().to_path_buf(),
+                ..Config::default()
+            })),
+            chat: test_chat(provider),
+            mem: Arc::new(MockMemory),
+            user_memory: test_user_memory(Arc::new(MockMemory)),
+            auto_save: false,
+            webhook_secret_hash: None,
+            pairing: Arc::new(PairingGuard::new(false, &[])),
+            trust_forwarded_headers: false,
+            rate_limiter: Arc::new(GatewayRateLimiter::new(100, 100, 100)),
+            idempotency_store: Arc::new(IdempotencyStore::new(Duration::from_secs(300), 1000)),
+            whatsapp: None,
+            whatsapp_app_secret: None,
+            linq: None,
+            linq_signing_secret: None,
+            nextcloud_talk: None,
+            nextcloud_talk_webhook_secret: None,
+            observer: Arc::new(crate::observability::NoopObserver),
+            tools_registry: Arc::new(Vec::new()),
+            cost_tracker: None,
+            event_tx: tokio::sync::broadcast::channel(16).0,
+            config_reloader: None,
+        };
+
+        let response = handle_webhook(
+            State(state.clone()),
+            test_connect_info(),
+            HeaderMap::new(),
+            Ok(Json(WebhookBody {
+                message: "hello".into(),
+                model: Some("openrouter:openai/gpt-4o".into()),
+            })),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let payload = response.into_body().collect().await.unwrap().to_bytes();
+        let parsed: serde_json::Value = serde_json::from_slice(&payload).unwrap();
+        assert_eq!(parsed["model"], "openai/gpt-4o");
+
+        let response = handle_webhook(
+            State(state),
+            test_connect_info(),
+            HeaderMap::new(),
+            Ok(Json(WebhookBody {
+                message: "hello".into(),
+                model: Some("not-a-model".into()),
+            })),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(provider_impl.calls.load(Ordering::SeqCst), 1);
+    }
+
     #[test]
     fn webhook_secret_hash_is_deterministic_and_nonempty() {
         let secret_a = generate_test_secret();
@@ -2011,9 +2624,8 @@ mod tests {
 
         let state = AppState {
             config: Arc::new(Mutex::new(Config::default())),
-            provider,
-            model: "test-model".into(),
-            temperature: 0.0,
+            chat: test_chat(provider),
+            user_memory: test_user_memory(Arc::clone(&memory)),
             mem: memory,
             auto_save: false,
             webhook_secret_hash: Some(Arc::from(hash_webhook_secret(&secret))),
@@ -2031,6 +2643,7 @@ mod tests {
             tools_registry: Arc::new(Vec::new()),
             cost_tracker: None,
             event_tx: tokio::sync::broadcast::channel(16).0,
+            config_reloader: None,
         };
 
         let response = handle_webhook(
@@ -2039,6 +2652,7 @@ mod tests {
             HeaderMap::new(),
             Ok(Json(WebhookBody {
                 message: "hello".into(),
+                model: None,
             })),
         )
         .await
@@ -2058,9 +2672,8 @@ mod tests {
 
         let state = AppState {
             config: Arc::new(Mutex::new(Config::default())),
-            provider,
-            model: "test-model".into(),
-            temperature: 0.0,
+            chat: test_chat(provider),
+            user_memory: test_user_memory(Arc::clone(&memory)),
             mem: memory,
             auto_save: false,
             webhook_secret_hash: Some(Arc::from(hash_webhook_secret(&valid_secret))),
@@ -2078,6 +2691,7 @@ mod tests {
             tools_registry: Arc::new(Vec::new()),
             cost_tracker: None,
             event_tx: tokio::sync::broadcast::channel(16).0,
+            config_reloader: None,
         };
 
         let mut headers = HeaderMap::new();
@@ -2092,6 +2706,7 @@ mod tests {
             headers,
             Ok(Json(WebhookBody {
                 message: "hello".into(),
+                model: None,
             })),
         )
         .await
@@ -2110,9 +2725,8 @@ mod tests {
 
         let state = AppState {
             config: Arc::new(Mutex::new(Config::default())),
-            provider,
-            model: "test-model".into(),
-            temperature: 0.0,
+            chat: test_chat(provider),
+            user_memory: test_user_memory(Arc::clone(&memory)),
             mem: memory,
             auto_save: false,
             webhook_secret_hash: Some(Arc::from(hash_webhook_secret(&secret))),
@@ -2130,6 +2744,7 @@ mod tests {
             tools_registry: Arc::new(Vec::new()),
             cost_tracker: None,
             event_tx: tokio::sync::broadcast::channel(16).0,
+            config_reloader: None,
         };
 
         let mut headers = HeaderMap::new();
@@ -2141,6 +2756,7 @@ mod tests {
             headers,
             Ok(Json(WebhookBody {
                 message: "hello".into(),
+                model: None,
             })),
         )
         .await
@@ -2150,6 +2766,68 @@ mod tests {
         assert_eq!(provider_impl.calls.load(Ordering::SeqCst), 1);
     }
 
+    #[test]
+    fn email_webhook_confirms_only_sns_subscriptions() {
+        assert!(is_sns_url(
+            "https://sns.us-east-1.amazonaws.com/?Action=ConfirmSubscription&Token=abc"
+        ));
+        assert!(!is_sns_url("http://sns.us-east-1.amazonaws.com/"));
+        assert!(!is_sns_url(
+            "https://sns.us-east-1.amazonaws.com.evil.test/"
+        ));
+        assert!(!is_sns_url("https://example.com/"));
+    }
+
+    #[tokio::test]
+    async fn a2a_routes_need_a2a_enabled_and_its_token() {
+        let mut config = Config::default();
+        let state = AppState {
+            config: Arc::new(Mutex::new(config.clone())),
+            chat: test_chat(Arc::new(MockProvider::default())),
+            mem: Arc::new(MockMemory),
+            user_memory: test_user_memory(Arc::new(MockMemory)),
+            auto_save: false,
+            webhook_secret_hash: None,
+            pairing: Arc::new(PairingGuard::new(false, &[])),
+            trust_forwarded_headers: false,
+            rate_limiter: Arc::new(GatewayRateLimiter::new(100, 100, 100)),
+            idempotency_store: Arc::new(IdempotencyStore::new(Duration::from_secs(300), 1000)),
+            whatsapp: None,
+            whatsapp_app_secret: None,
+            linq: None,
+            linq_signing_secret: None,
+            nextcloud_talk: None,
+            nextcloud_talk_webhook_secret: None,
+            observer: Arc::new(crate::observability::NoopObserver),
+            tools_registry: Arc::new(Vec::new()),
+            cost_tracker: None,
+            event_tx: tokio::sync::broadcast::channel(16).0,
+            config_reloader: None,
+        };
+        let task = |state: AppState, token: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                header::AUTHORIZATION,
+                HeaderValue::from_str(&format!("Bearer {token}")).unwrap(),
+            );
+            async move {
+                a2a::handle_a2a_task(State(state), headers, axum::extract::Path("missing".into()))
+                    .await
+                    .into_response()
+                    .status()
+            }
+        };
+
+        assert_eq!(task(state.clone(), "").await, StatusCode::NOT_FOUND);
+
+        config.factory.a2a.enabled = true;
+        config.factory.a2a.token = Some("peer-token".into());
+        *state.config.lock() = config;
+        assert_eq!(task(state.clone(), "wrong").await, StatusCode::UNAUTHORIZED);
+        // Authorized, but no such task
+        assert_eq!(task(state, "peer-token").await, StatusCode::NOT_FOUND);
+    }
+
     fn compute_nextcloud_signature_hex(secret: &str, random: &str, body: &str) -> String {
         use hmac::{Hmac, Mac};
         use sha2::Sha256;
@@ -2167,9 +2845,8 @@ mod tests {
 
         let state = AppState {
             config: Arc::new(Mutex::new(Config::default())),
-            provider,
-            model: "test-model".into(),
-            temperature: 0.0,
+            chat: test_chat(provider),
+            user_memory: test_user_memory(Arc::clone(&memory)),
             mem: memory,
             auto_save: false,
             webhook_secret_hash: None,
@@ -2187,6 +2864,7 @@ mod tests {
             tools_registry: Arc::new(Vec::new()),
             cost_tracker: None,
             event_tx: tokio::sync::broadcast::channel(16).0,
+            config_reloader: None,
         };
 
         let response = handle_nextcloud_talk_webhook(
@@ -2220,9 +2898,8 @@ mod tests {
 
         let state = AppState {
             config: Arc::new(Mutex::new(Config::default())),
-            provider,
-            model: "test-model".into(),
-            temperature: 0.0,
+            chat: test_chat(provider),
+            user_memory: test_user_memory(Arc::clone(&memory)),
             mem: memory,
             auto_save: false,
             webhook_secret_hash: None,
@@ -2240,6 +2917,7 @@ mod tests {
             tools_registry: Arc::new(Vec::new()),
             cost_tracker: None,
             event_tx: tokio::sync::broadcast::channel(16).0,
+            config_reloader: None,
         };
 
         let mut headers = HeaderMap::new();
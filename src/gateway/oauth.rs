@@ -11,12 +11,14 @@
 //! The proxy service securely stores the CLIENT_SECRET and handles the OAuth exchange.
 
 use super::AppState;
+use crate::i18n::{self, Locale};
 use axum::{
     extract::{Query, State},
-    http::{header, StatusCode},
+    http::{header, HeaderMap, StatusCode},
     response::IntoResponse,
 };
 use serde::Deserialize;
+use std::fmt::Write;
 
 /// Query parameters returned by OAuth redirect.
 /// Can come from GitHub directly or from the OAuth Proxy.
@@ -40,11 +42,10 @@ pub async fn handle_github_auth(State(state): State<AppState>) -> impl IntoRespo
 
     // If user has their own OAuth app configured, use direct GitHub OAuth
     if !cfg.github_client_id.is_empty() {
-        let scope = "repo,read:user,user:email";
         let auth_url = format!(
             "https://github.com/login/oauth/authorize?client_id={client_id}&scope={scope}",
             client_id = cfg.github_client_id,
-            scope = urlencoding::encode(scope),
+            scope = urlencoding::encode(&crate::tools::github_scopes::requested_scopes()),
         );
         return (StatusCode::FOUND, [(header::LOCATION, auth_url)]).into_response();
     }
@@ -68,10 +69,16 @@ pub async fn handle_github_auth(State(state): State<AppState>) -> impl IntoRespo
 /// Two possible flows:
 /// 1. Direct GitHub OAuth: receives `code`, exchange for token using client_secret
 /// 2. OAuth Proxy: receives `token` directly (proxy already exchanged the code)
+///
+/// The success page is in the browser's `Accept-Language`, falling back to
+/// `[i18n].default_locale`.
 pub async fn handle_github_callback(
     State(state): State<AppState>,
     Query(params): Query<OAuthCallbackQuery>,
+    headers: HeaderMap,
 ) -> impl IntoResponse {
+    let locale = page_locale(&state, &headers);
+
     // Handle error from either flow
     if let Some(err) = params.error {
         let desc = params
@@ -88,7 +95,7 @@ pub async fn handle_github_callback(
     // Check if this is a callback from the OAuth Proxy (token provided directly)
     if let Some(token) = params.token {
         let username = params.username;
-        return save_token_and_respond(state, token, username).await;
+        return save_token_and_respond(state, token, username, locale).await;
     }
 
     // Otherwise, this is a direct GitHub OAuth callback (code exchange required)
@@ -184,7 +191,16 @@ pub async fn handle_github_callback(
     // Fetch the authenticated user's login name
     let username = fetch_github_username(&client, &access_token).await;
 
-    save_token_and_respond(state, access_token, username).await
+    save_token_and_respond(state, access_token, username, locale).await
+}
+
+/// Locale of the success page.
+fn page_locale(state: &AppState, headers: &HeaderMap) -> Locale {
+    let detected = headers
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(i18n::from_accept_language);
+    i18n::resolve(&state.config.lock().i18n, "", None, detected)
 }
 
 /// Save token to database and return success response.
@@ -192,11 +208,13 @@ async fn save_token_and_respond(
     state: AppState,
     token: String,
     username: Option<String>,
+    locale: Locale,
 ) -> axum::response::Response {
     let cfg = state.config.lock().zerobuild.clone();
     let db_path = std::path::PathBuf::from(&cfg.db_path);
 
-    match crate::store::init_db(&db_path) {
+    let scopes = fetch_scopes(&token).await;
+    let missing = match crate::store::init_db(&db_path) {
         Ok(conn) => {
             if let Err(e) =
                 crate::store::tokens::save_github_token(&conn, &token, username.as_deref())
@@ -207,6 +225,7 @@ async fn save_token_and_respond(
                 )
                     .into_response();
             }
+            record_scopes(&conn, scopes.as_ref())
         }
         Err(e) => {
             return (
@@ -215,24 +234,81 @@ async fn save_token_and_respond(
             )
                 .into_response();
         }
-    }
+    };
 
     let display_name = username.as_deref().unwrap_or("unknown user");
     tracing::info!("GitHub OAuth: connected as {}", display_name);
 
+    // Features the granted scopes don't cover
+    let missing_html = if missing.is_empty() {
+        String::new()
+    } else {
+        let mut items = String::new();
+        for scope in &missing {
+            let purpose = crate::tools::github_scopes::REQUESTED
+                .iter()
+                .find(|(requested, _)| requested == scope)
+                .map_or("", |(_, purpose)| purpose);
+            let _ = write!(items, "<li><code>{scope}</code>: needed to {purpose}</li>");
+        }
+        format!(
+            "<p>{}</p><ul style='display:inline-block;text-align:left'>{items}</ul>",
+            i18n::t(locale, "oauth_missing_scopes", &[])
+        )
+    };
+
     // Return a simple success page
+    let user = format!("<strong>{display_name}</strong>");
     let html = format!(
-        "<!DOCTYPE html><html><body style='font-family:sans-serif;text-align:center;padding:40px'>
-        <h2>✅ GitHub Connected!</h2>
-        <p>Connected as <strong>{}</strong></p>
-        <p>You can close this window and return to your chat.</p>
+        "<!DOCTYPE html><html lang='{locale}'><head><meta charset='utf-8'></head>
+        <body style='font-family:sans-serif;text-align:center;padding:40px'>
+        <h2>{}</h2>
+        <p>{}</p>
+        {missing_html}
+        <p>{}</p>
         </body></html>",
-        display_name
+        i18n::t(locale, "oauth_title", &[]),
+        i18n::t(locale, "oauth_connected_as", &[("user", &user)]),
+        i18n::t(locale, "oauth_close", &[]),
     );
 
     (StatusCode::OK, [(header::CONTENT_TYPE, "text/html")], html).into_response()
 }
 
+/// Scopes GitHub reports for a new token; `None` when it reports none or
+/// the check fails (logged), leaving the verdict to GitHub.
+async fn fetch_scopes(token: &str) -> Option<Vec<String>> {
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(15))
+        .user_agent("ZeroBuild/0.1")
+        .build()
+        .ok()?;
+    match crate::tools::github_scopes::fetch_scopes(&client, token).await {
+        Ok(scopes) => scopes,
+        Err(e) => {
+            tracing::warn!("GitHub OAuth: failed to verify token scopes: {e:#}");
+            None
+        }
+    }
+}
+
+/// Store the scopes of the saved token and return the requested ones it
+/// lacks.
+fn record_scopes(conn: &rusqlite::Connection, scopes: Option<&Vec<String>>) -> Vec<&'static str> {
+    match crate::tools::github_scopes::record(conn, scopes.map(Vec::as_slice)) {
+        Ok(missing) => {
+            if !missing.is_empty() {
+                tracing::warn!("GitHub OAuth: token lacks scopes {}", missing.join(", "));
+            }
+            missing
+        }
+        Err(e) => {
+            tracing::warn!("GitHub OAuth: failed to store token scopes: {e:#}");
+            Vec::new()
+        }
+    }
+}
+
 async fn fetch_github_username(client: &reqwest::Client, token: &str) -> Option<String> {
     let resp = client
         .get("https://api.github.com/user")
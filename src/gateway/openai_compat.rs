@@ -4,7 +4,7 @@
 //! OpenAI API, enabling any OpenAI-compatible client (e.g., `openai` Python
 //! library, `curl`, Aura) to send chat requests through the gateway.
 
-use super::AppState;
+use super::{AppState, GatewayChat};
 use crate::providers::traits::{ChatMessage, StreamOptions};
 use axum::{
     body::Body,
@@ -201,13 +201,15 @@ pub async fn handle_v1_chat_completions(
         return (StatusCode::BAD_REQUEST, Json(err)).into_response();
     }
 
-    let model = request
-        .model
-        .as_deref()
-        .filter(|m| !m.is_empty())
-        .unwrap_or(&state.model)
-        .to_string();
-    let temperature = request.temperature.unwrap_or(state.temperature);
+    let default = state.chat();
+    let chat = GatewayChat {
+        model: request
+            .model
+            .filter(|m| !m.is_empty())
+            .unwrap_or(default.model),
+        temperature: request.temperature.unwrap_or(default.temperature),
+        provider: default.provider,
+    };
     let stream = request.stream.unwrap_or(false);
 
     // Convert messages to provider format
@@ -232,31 +234,16 @@ pub async fn handle_v1_chat_completions(
         .observer
         .record_event(&crate::observability::ObserverEvent::LlmRequest {
             provider: provider_label.clone(),
-            model: model.clone(),
+            model: chat.model.clone(),
             messages_count: messages.len(),
         });
 
     if stream {
-        handle_streaming(
-            state,
-            messages,
-            model,
-            temperature,
-            provider_label,
-            started_at,
-        )
-        .into_response()
+        handle_streaming(state, messages, chat, provider_label, started_at).into_response()
     } else {
-        handle_non_streaming(
-            state,
-            messages,
-            model,
-            temperature,
-            provider_label,
-            started_at,
-        )
-        .await
-        .into_response()
+        handle_non_streaming(state, messages, chat, provider_label, started_at)
+            .await
+            .into_response()
     }
 }
 
@@ -264,13 +251,16 @@ pub async fn handle_v1_chat_completions(
 async fn handle_non_streaming(
     state: AppState,
     messages: Vec<ChatMessage>,
-    model: String,
-    temperature: f64,
+    chat: GatewayChat,
     provider_label: String,
     started_at: Instant,
 ) -> impl IntoResponse {
-    match state
-        .provider
+    let GatewayChat {
+        provider,
+        model,
+        temperature,
+    } = chat;
+    match provider
         .chat_with_history(&messages, &model, temperature)
         .await
     {
@@ -331,15 +321,19 @@ async fn handle_non_streaming(
 fn handle_streaming(
     state: AppState,
     messages: Vec<ChatMessage>,
-    model: String,
-    temperature: f64,
+    chat: GatewayChat,
     provider_label: String,
     started_at: Instant,
 ) -> impl IntoResponse {
+    let GatewayChat {
+        provider,
+        model,
+        temperature,
+    } = chat;
     let request_id = format!("chatcmpl-{}", Uuid::new_v4());
     let created = unix_timestamp();
 
-    if !state.provider.supports_streaming() {
+    if !provider.supports_streaming() {
         // Provider doesn't support streaming — fall back to a single-chunk response
         let model_clone = model.clone();
         let id = request_id.clone();
@@ -396,12 +390,8 @@ fn handle_streaming(
     }
 
     // Provider supports native streaming
-    let provider_stream = state.provider.stream_chat_with_history(
-        &messages,
-        &model,
-        temperature,
-        StreamOptions::new(true),
-    );
+    let provider_stream =
+        provider.stream_chat_with_history(&messages, &model, temperature, StreamOptions::new(true));
 
     let model_for_stream = model.clone();
     let state_for_stream = state.clone();
@@ -504,7 +494,7 @@ pub async fn handle_v1_models(
     let response = ModelsResponse {
         object: "list",
         data: vec![ModelObject {
-            id: state.model.clone(),
+            id: state.chat().model,
             object: "model",
             created: unix_timestamp(),
             owned_by: "zerobuild".to_string(),
@@ -544,6 +534,7 @@ fn record_success(
             error_message: None,
             input_tokens: None,
             output_tokens: None,
+            cached_input_tokens: None,
         });
     state
         .observer
@@ -567,6 +558,7 @@ fn record_failure(
             error_message: Some(error_message.to_string()),
             input_tokens: None,
             output_tokens: None,
+            cached_input_tokens: None,
         });
     state
         .observer
@@ -168,7 +168,7 @@ pub async fn handle_api_chat(
         .default_provider
         .clone()
         .unwrap_or_else(|| "unknown".to_string());
-    let model_label = state.model.clone();
+    let model_label = state.chat().model;
     let started_at = Instant::now();
 
     state
@@ -202,6 +202,7 @@ pub async fn handle_api_chat(
                     error_message: None,
                     input_tokens: None,
                     output_tokens: None,
+                    cached_input_tokens: None,
                 });
             state.observer.record_metric(
                 &crate::observability::traits::ObserverMetric::RequestLatency(duration),
@@ -218,7 +219,7 @@ pub async fn handle_api_chat(
 
             let body = serde_json::json!({
                 "reply": safe_response,
-                "model": state.model,
+                "model": model_label,
                 "session_id": chat_body.session_id,
             });
             (StatusCode::OK, Json(body))
@@ -237,6 +238,7 @@ pub async fn handle_api_chat(
                     error_message: Some(sanitized.clone()),
                     input_tokens: None,
                     output_tokens: None,
+                    cached_input_tokens: None,
                 });
             state.observer.record_metric(
                 &crate::observability::traits::ObserverMetric::RequestLatency(duration),
@@ -534,7 +536,7 @@ pub async fn handle_v1_chat_completions_with_tools(
         .default_provider
         .clone()
         .unwrap_or_else(|| "unknown".to_string());
-    let model_label = state.model.clone();
+    let model_label = state.chat().model;
     let started_at = Instant::now();
 
     state
@@ -573,6 +575,7 @@ pub async fn handle_v1_chat_completions_with_tools(
                     error_message: None,
                     input_tokens: None,
                     output_tokens: None,
+                    cached_input_tokens: None,
                 });
             state.observer.record_metric(
                 &crate::observability::traits::ObserverMetric::RequestLatency(duration),
@@ -603,6 +606,7 @@ pub async fn handle_v1_chat_completions_with_tools(
                     error_message: Some(sanitized.clone()),
                     input_tokens: None,
                     output_tokens: None,
+                    cached_input_tokens: None,
                 });
             state.observer.record_metric(
                 &crate::observability::traits::ObserverMetric::RequestLatency(duration),
@@ -629,7 +633,7 @@ pub async fn handle_v1_chat_completions_with_tools(
         }
     };
 
-    let model_name = request.model.unwrap_or_else(|| state.model.clone());
+    let model_name = request.model.unwrap_or_else(|| state.chat().model);
 
     #[allow(clippy::cast_possible_truncation)]
     let prompt_tokens = (enriched_message.len() / 4) as u32;
@@ -8,8 +8,17 @@
 //! Server -> Client: {"type":"tool_result","name":"shell","output":"..."}
 //! Server -> Client: {"type":"done","full_response":"..."}
 //! ```
+//!
+//! `?profile=<name>` and `?project=<name>` on the upgrade request pick the
+//! config profile and project overrides (provider, model, temperature) for
+//! the whole socket session.
+//!
+//! When the provider supports streaming, `chunk` messages carry the reply as
+//! it is generated (coalesced to roughly one per second) and are mirrored to
+//! `/api/events` as `agent_chunk` events.
 
-use super::AppState;
+use super::{with_preferences, AppState};
+use crate::providers::Provider;
 use axum::{
     extract::{
         ws::{Message, WebSocket},
@@ -19,10 +28,51 @@ use axum::{
 };
 use futures_util::{SinkExt, StreamExt};
 use serde::Deserialize;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::Instrument;
+
+/// Minimum spacing between streamed `chunk` messages.
+const CHUNK_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
 
 #[derive(Deserialize)]
 pub struct WsQuery {
     pub token: Option<String>,
+    /// Config profile for this session (`[profiles.<name>]`)
+    pub profile: Option<String>,
+    /// Project whose override file applies to this session
+    pub project: Option<String>,
+}
+
+/// Provider settings of one socket session.
+struct WsSession {
+    provider: Arc<dyn Provider>,
+    model: String,
+    temperature: f64,
+}
+
+impl WsSession {
+    fn for_request(state: &AppState, params: &WsQuery) -> anyhow::Result<Self> {
+        if params.profile.is_none() && params.project.is_none() {
+            let chat = state.chat();
+            return Ok(Self {
+                provider: chat.provider,
+                model: chat.model,
+                temperature: chat.temperature,
+            });
+        }
+        let base = state.config.lock().clone();
+        let config = crate::config::profiles::resolve(
+            &base,
+            params.profile.as_deref(),
+            params.project.as_deref(),
+        )?;
+        Ok(Self {
+            provider: super::create_chat_provider(&config)?,
+            model: super::chat_model(&config),
+            temperature: config.default_temperature,
+        })
+    }
 }
 
 /// GET /ws/chat — WebSocket upgrade for agent chat
@@ -43,11 +93,23 @@ pub async fn handle_ws_chat(
         }
     }
 
-    ws.on_upgrade(move |socket| handle_socket(socket, state))
+    let session = match WsSession::for_request(&state, &params) {
+        Ok(session) => session,
+        Err(e) => {
+            return (axum::http::StatusCode::BAD_REQUEST, format!("{e:#}")).into_response();
+        }
+    };
+
+    let span = crate::observability::logging::session_span(
+        &crate::observability::logging::new_session_id(),
+        "dashboard",
+        "ws",
+    );
+    ws.on_upgrade(move |socket| handle_socket(socket, state, session).instrument(span))
         .into_response()
 }
 
-async fn handle_socket(socket: WebSocket, state: AppState) {
+async fn handle_socket(socket: WebSocket, state: AppState, session: WsSession) {
     let (mut sender, mut receiver) = socket.split();
 
     while let Some(msg) = receiver.next().await {
@@ -90,20 +152,21 @@ async fn handle_socket(socket: WebSocket, state: AppState) {
         let _ = state.event_tx.send(serde_json::json!({
             "type": "agent_start",
             "provider": provider_label,
-            "model": state.model,
+            "model": session.model,
         }));
 
-        // Simple single-turn chat (no streaming for now — use provider.chat_with_system)
+        // Single-turn chat without tools
         let system_prompt = {
             let config_guard = state.config.lock();
-            crate::channels::build_system_prompt(
+            let prompt = crate::channels::build_system_prompt(
                 &config_guard.workspace_dir,
-                &state.model,
+                &session.model,
                 &[],
                 &[],
                 Some(&config_guard.identity),
                 None,
-            )
+            );
+            with_preferences(prompt, &config_guard.zerobuild.db_path)
         };
 
         let messages = vec![
@@ -127,11 +190,16 @@ async fn handle_socket(socket: WebSocket, state: AppState) {
                 }
             };
 
-        match state
-            .provider
-            .chat_with_history(&prepared.messages, &state.model, state.temperature)
-            .await
-        {
+        let result = if session.provider.supports_streaming() {
+            stream_reply(&state, &session, &mut sender, &prepared.messages).await
+        } else {
+            session
+                .provider
+                .chat_with_history(&prepared.messages, &session.model, session.temperature)
+                .await
+        };
+
+        match result {
             Ok(response) => {
                 // Send the full response as a done message
                 let done = serde_json::json!({
@@ -144,7 +212,7 @@ async fn handle_socket(socket: WebSocket, state: AppState) {
                 let _ = state.event_tx.send(serde_json::json!({
                     "type": "agent_end",
                     "provider": provider_label,
-                    "model": state.model,
+                    "model": session.model,
                 }));
             }
             Err(e) => {
@@ -165,3 +233,60 @@ async fn handle_socket(socket: WebSocket, state: AppState) {
         }
     }
 }
+
+/// Stream a reply from the provider, forwarding coalesced `chunk` messages to
+/// the socket and `agent_chunk` events to SSE subscribers. Returns the full
+/// response text.
+async fn stream_reply<S>(
+    state: &AppState,
+    session: &WsSession,
+    sender: &mut S,
+    messages: &[crate::providers::ChatMessage],
+) -> anyhow::Result<String>
+where
+    S: SinkExt<Message> + Unpin,
+{
+    let mut stream = session.provider.stream_chat_with_history(
+        messages,
+        &session.model,
+        session.temperature,
+        crate::providers::traits::StreamOptions::new(true),
+    );
+
+    let mut full_response = String::new();
+    let mut pending = String::new();
+    let mut last_flush = Instant::now();
+
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| anyhow::anyhow!("{e}"))?;
+        full_response.push_str(&chunk.delta);
+        pending.push_str(&chunk.delta);
+
+        if !pending.is_empty() && (chunk.is_final || last_flush.elapsed() >= CHUNK_FLUSH_INTERVAL) {
+            flush_chunk(state, session, sender, &mut pending).await;
+            last_flush = Instant::now();
+        }
+        if chunk.is_final {
+            break;
+        }
+    }
+    if !pending.is_empty() {
+        flush_chunk(state, session, sender, &mut pending).await;
+    }
+
+    Ok(full_response)
+}
+
+async fn flush_chunk<S>(state: &AppState, session: &WsSession, sender: &mut S, pending: &mut String)
+where
+    S: SinkExt<Message> + Unpin,
+{
+    let content = std::mem::take(pending);
+    let chunk = serde_json::json!({"type": "chunk", "content": content});
+    let _ = sender.send(Message::Text(chunk.to_string().into())).await;
+    let _ = state.event_tx.send(serde_json::json!({
+        "type": "agent_chunk",
+        "content": content,
+        "model": session.model,
+    }));
+}
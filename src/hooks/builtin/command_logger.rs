@@ -57,6 +57,7 @@ mod tests {
             success: true,
             output: "ok".into(),
             error: None,
+            error_code: None,
             error_hint: None,
         };
         hook.on_after_tool_call("shell", &result, Duration::from_millis(42))
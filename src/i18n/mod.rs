@@ -0,0 +1,289 @@
+//! Translations of user-facing channel messages (`[i18n]`).
+//!
+//! Status updates, errors, and the GitHub OAuth success page are looked up
+//! in the catalogs under `catalogs/` by key. A message's locale is, in
+//! order: the one the user picked with `/lang`, the language their client
+//! reports (Telegram `language_code`, a browser's `Accept-Language`), the
+//! channel's locale from `[i18n.channels]`, and `[i18n].default_locale`.
+//! Keys missing from a catalog fall back to English.
+//!
+//! The agent loop has no user context, so channels run it inside
+//! [`with_locale`] and the loop reads the locale with [`current`].
+
+use crate::config::I18nConfig;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::LazyLock;
+
+/// Most client-reported locales remembered; the map is reset beyond it.
+const MAX_DETECTED: usize = 10_000;
+
+/// Supported message languages.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Locale {
+    #[default]
+    En,
+    Es,
+    Pt,
+    Ru,
+    Zh,
+    Vi,
+}
+
+impl Locale {
+    pub const ALL: [Self; 6] = [Self::En, Self::Es, Self::Pt, Self::Ru, Self::Zh, Self::Vi];
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::En => "en",
+            Self::Es => "es",
+            Self::Pt => "pt",
+            Self::Ru => "ru",
+            Self::Zh => "zh",
+            Self::Vi => "vi",
+        }
+    }
+
+    /// The language's name in itself.
+    pub fn native_name(self) -> &'static str {
+        match self {
+            Self::En => "English",
+            Self::Es => "Español",
+            Self::Pt => "Português",
+            Self::Ru => "Русский",
+            Self::Zh => "中文",
+            Self::Vi => "Tiếng Việt",
+        }
+    }
+
+    /// Parse a language tag (`pt-BR`, `zh_Hans`, `RU`) by its primary
+    /// subtag.
+    pub fn parse(tag: &str) -> Option<Self> {
+        let primary = tag.trim().split(['-', '_']).next()?.to_ascii_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|locale| locale.code() == primary)
+    }
+
+    /// Comma-separated `code (name)` list of the supported locales.
+    pub fn available() -> String {
+        Self::ALL
+            .iter()
+            .map(|locale| format!("{} ({})", locale.code(), locale.native_name()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn catalog_source(self) -> &'static str {
+        match self {
+            Self::En => include_str!("catalogs/en.toml"),
+            Self::Es => include_str!("catalogs/es.toml"),
+            Self::Pt => include_str!("catalogs/pt.toml"),
+            Self::Ru => include_str!("catalogs/ru.toml"),
+            Self::Zh => include_str!("catalogs/zh.toml"),
+            Self::Vi => include_str!("catalogs/vi.toml"),
+        }
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+type Catalog = HashMap<String, String>;
+
+static CATALOGS: LazyLock<HashMap<Locale, Catalog>> = LazyLock::new(|| {
+    Locale::ALL
+        .into_iter()
+        .map(|locale| {
+            let catalog = toml::from_str(locale.catalog_source()).unwrap_or_else(|e| {
+                tracing::error!("Invalid {locale} message catalog: {e}");
+                Catalog::new()
+            });
+            (locale, catalog)
+        })
+        .collect()
+});
+
+/// The `key` message in `locale` with each `{name}` placeholder replaced
+/// by its value from `args`.
+pub fn t(locale: Locale, key: &str, args: &[(&str, &str)]) -> String {
+    let template = CATALOGS
+        .get(&locale)
+        .and_then(|catalog| catalog.get(key))
+        .or_else(|| CATALOGS.get(&Locale::En)?.get(key))
+        .map_or(key, String::as_str);
+    let mut message = template.to_string();
+    for (name, value) in args {
+        message = message.replace(&format!("{{{name}}}"), value);
+    }
+    message
+}
+
+tokio::task_local! {
+    static CURRENT: Locale;
+}
+
+/// Run `fut` with [`current`] returning `locale`.
+pub async fn with_locale<F: Future>(locale: Locale, fut: F) -> F::Output {
+    CURRENT.scope(locale, fut).await
+}
+
+/// Locale of the message being handled, English outside [`with_locale`].
+pub fn current() -> Locale {
+    CURRENT.try_with(|locale| *locale).unwrap_or_default()
+}
+
+static DETECTED: LazyLock<Mutex<HashMap<String, Locale>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Remember the language `sender`'s client reports on `channel`. Tags of
+/// unsupported languages are ignored.
+pub fn remember_detected(channel: &str, sender: &str, tag: &str) {
+    let Some(locale) = Locale::parse(tag) else {
+        return;
+    };
+    let mut detected = DETECTED.lock();
+    if detected.len() >= MAX_DETECTED {
+        detected.clear();
+    }
+    detected.insert(format!("{channel}_{sender}"), locale);
+}
+
+/// The language `sender`'s client last reported on `channel`.
+pub fn detected(channel: &str, sender: &str) -> Option<Locale> {
+    DETECTED.lock().get(&format!("{channel}_{sender}")).copied()
+}
+
+/// First supported language of an `Accept-Language` header, by weight.
+pub fn from_accept_language(header: &str) -> Option<Locale> {
+    let mut ranges: Vec<(f32, Locale)> = header
+        .split(',')
+        .filter_map(|range| {
+            let mut parts = range.split(';');
+            let locale = Locale::parse(parts.next()?)?;
+            let weight = parts
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .and_then(|q| q.parse().ok())
+                .unwrap_or(1.0);
+            Some((weight, locale))
+        })
+        .collect();
+    // Stable, so equal weights keep header order.
+    ranges.sort_by(|a, b| b.0.total_cmp(&a.0));
+    ranges.first().map(|(_, locale)| *locale)
+}
+
+/// Locale for a message on `channel`: the user's `chosen` one, then the
+/// `detected` one when detection is on, then the channel's, then the
+/// default.
+pub fn resolve(
+    config: &I18nConfig,
+    channel: &str,
+    chosen: Option<Locale>,
+    detected: Option<Locale>,
+) -> Locale {
+    chosen
+        .or(detected.filter(|_| config.detect))
+        .or_else(|| {
+            config
+                .channels
+                .get(channel)
+                .and_then(|tag| Locale::parse(tag))
+        })
+        .or_else(|| Locale::parse(&config.default_locale))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use regex::Regex;
+    use std::collections::BTreeSet;
+
+    fn placeholders(message: &str) -> BTreeSet<String> {
+        Regex::new(r"\{[a-z_]+\}")
+            .unwrap()
+            .find_iter(message)
+            .map(|m| m.as_str().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn catalogs_translate_every_key_with_the_same_placeholders() {
+        let english = &CATALOGS[&Locale::En];
+        assert!(!english.is_empty());
+        for locale in Locale::ALL {
+            let catalog = &CATALOGS[&locale];
+            for (key, message) in english {
+                let translated = catalog
+                    .get(key)
+                    .unwrap_or_else(|| panic!("{locale} catalog lacks '{key}'"));
+                assert_eq!(
+                    placeholders(translated),
+                    placeholders(message),
+                    "{locale} '{key}'"
+                );
+            }
+            assert_eq!(catalog.len(), english.len(), "{locale} has extra keys");
+        }
+    }
+
+    #[test]
+    fn messages_fill_placeholders_and_fall_back_to_english() {
+        assert_eq!(
+            t(Locale::En, "error", &[("error", "boom")]),
+            "⚠️ Error: boom"
+        );
+        assert_eq!(
+            t(Locale::Ru, "error", &[("error", "boom")]),
+            "⚠️ Ошибка: boom"
+        );
+        assert_eq!(t(Locale::Es, "no_such_key", &[]), "no_such_key");
+    }
+
+    #[test]
+    fn locales_resolve_in_priority_order() {
+        let mut config = I18nConfig::default();
+        config.channels.insert("telegram".into(), "vi".into());
+        assert_eq!(resolve(&config, "slack", None, None), Locale::En);
+        assert_eq!(resolve(&config, "telegram", None, None), Locale::Vi);
+        assert_eq!(
+            resolve(&config, "telegram", None, Some(Locale::Ru)),
+            Locale::Ru
+        );
+        assert_eq!(
+            resolve(&config, "telegram", Some(Locale::Pt), Some(Locale::Ru)),
+            Locale::Pt
+        );
+        config.detect = false;
+        assert_eq!(
+            resolve(&config, "telegram", None, Some(Locale::Ru)),
+            Locale::Vi
+        );
+
+        assert_eq!(Locale::parse("pt-BR"), Some(Locale::Pt));
+        assert_eq!(Locale::parse("de"), None);
+        assert_eq!(
+            from_accept_language("de-DE,de;q=0.9,es;q=0.8,en;q=0.7"),
+            Some(Locale::Es)
+        );
+        assert_eq!(from_accept_language("fr"), None);
+
+        remember_detected("telegram", "42", "zh-hans");
+        remember_detected("telegram", "43", "de");
+        assert_eq!(detected("telegram", "42"), Some(Locale::Zh));
+        assert_eq!(detected("telegram", "43"), None);
+    }
+
+    #[tokio::test]
+    async fn current_locale_is_scoped() {
+        assert_eq!(current(), Locale::En);
+        let inner = with_locale(Locale::Vi, async { current() }).await;
+        assert_eq!(inner, Locale::Vi);
+    }
+}
@@ -40,6 +40,7 @@ use serde::{Deserialize, Serialize};
 
 pub mod agent;
 pub(crate) mod approval;
+pub mod artifacts;
 pub(crate) mod auth;
 pub mod channels;
 pub mod config;
@@ -48,12 +49,15 @@ pub(crate) mod cost;
 pub(crate) mod cron;
 pub(crate) mod daemon;
 pub(crate) mod doctor;
+pub mod error;
+pub(crate) mod eval;
 pub mod factory;
 pub mod gateway;
 pub(crate) mod hardware;
 pub(crate) mod health;
 pub(crate) mod heartbeat;
 pub mod hooks;
+pub(crate) mod i18n;
 pub(crate) mod identity;
 pub(crate) mod integrations;
 pub mod memory;
@@ -143,6 +147,15 @@ Examples:
     },
 }
 
+/// Build artifact subcommands
+#[derive(Subcommand, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ArtifactCommands {
+    /// Show the storage used by each project's artifacts
+    Usage,
+    /// Apply the retention policy now
+    Cleanup,
+}
+
 /// Skills management subcommands
 #[derive(Subcommand, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum SkillCommands {
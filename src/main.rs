@@ -32,13 +32,12 @@
     dead_code
 )]
 
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
 use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
 use dialoguer::{Input, Password};
 use serde::{Deserialize, Serialize};
 use std::io::Write;
-use tracing::{info, warn};
-use tracing_subscriber::{fmt, EnvFilter};
+use tracing::{info, warn, Instrument};
 
 fn parse_temperature(s: &str) -> std::result::Result<f64, String> {
     let t: f64 = s.parse().map_err(|e| format!("{e}"))?;
@@ -50,6 +49,7 @@ fn parse_temperature(s: &str) -> std::result::Result<f64, String> {
 
 mod agent;
 mod approval;
+mod artifacts;
 mod auth;
 mod channels;
 mod rag {
@@ -61,12 +61,15 @@ mod cost;
 mod cron;
 mod daemon;
 mod doctor;
+mod error;
+mod eval;
 mod factory;
 mod gateway;
 mod hardware;
 mod health;
 mod heartbeat;
 mod hooks;
+mod i18n;
 mod identity;
 mod integrations;
 mod memory;
@@ -91,8 +94,8 @@ use config::Config;
 
 // Re-export so binary modules can use crate::<CommandEnum> while keeping a single source of truth.
 pub use zerobuild::{
-    ChannelCommands, CronCommands, HardwareCommands, IntegrationCommands, MigrateCommands,
-    PeripheralCommands, ServiceCommands, SkillCommands, SopCommands,
+    ArtifactCommands, ChannelCommands, CronCommands, HardwareCommands, IntegrationCommands,
+    MigrateCommands, PeripheralCommands, ServiceCommands, SkillCommands, SopCommands,
 };
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
@@ -154,6 +157,25 @@ enum Commands {
         memory: Option<String>,
     },
 
+    /// Guided setup: check Docker/E2B, test your provider key, connect GitHub and write a validated config
+    #[command(long_about = "\
+Guided first-run setup.
+
+Detects Docker and E2B, sends one short request to test the chosen \
+provider key, optionally connects GitHub, picks a default project \
+template and writes config.toml. The written file is loaded back and \
+validated before setup reports success. The gateway dashboard offers \
+the same flow on its Setup page.
+
+Examples:
+  zerobuild init
+  zerobuild init --force   # overwrite an existing config.toml")]
+    Init {
+        /// Overwrite existing config without confirmation
+        #[arg(long)]
+        force: bool,
+    },
+
     /// Start the AI agent loop
     #[command(long_about = "\
 Start the AI agent loop.
@@ -179,13 +201,21 @@ Examples:
         #[arg(long)]
         model: Option<String>,
 
-        /// Temperature (0.0 - 2.0)
-        #[arg(short, long, default_value = "0.7", value_parser = parse_temperature)]
-        temperature: f64,
+        /// Temperature (0.0 - 2.0); defaults to 0.7, or the profile's temperature
+        #[arg(short, long, value_parser = parse_temperature)]
+        temperature: Option<f64>,
 
         /// Attach a peripheral (board:path, e.g. nucleo-f401re:/dev/ttyACM0)
         #[arg(long)]
         peripheral: Vec<String>,
+
+        /// Config profile for this session (`[profiles.<name>]`)
+        #[arg(long)]
+        profile: Option<String>,
+
+        /// Project whose override file (`projects/<project>.toml`) applies
+        #[arg(long)]
+        project: Option<String>,
     },
 
     /// Start the gateway server (webhooks, websockets)
@@ -256,6 +286,99 @@ Examples:
     /// Show system status (full details)
     Status,
 
+    /// Compare two model routes on a suite of build tasks
+    #[command(long_about = "\
+Compare two model routes on a suite of build tasks.
+
+Runs every task once per route, each in a fresh local sandbox, and \
+scores it by running the task's check command (or the project's \
+test command) when the agent finishes. The report compares pass \
+rate, time and estimated cost and recommends a route.
+
+Routes are <provider>:<model> or hint:<name> for a model route. \
+Without --suite the built-in suite runs one task per project template.
+
+Examples:
+  zerobuild eval --a openrouter:anthropic/claude-sonnet-4 --b openai:gpt-4o
+  zerobuild eval --a hint:code --b groq:llama-3.3-70b-versatile --task health-api
+  zerobuild eval --a hint:code --b hint:fast --suite evals.toml -o report.md")]
+    Eval {
+        /// First route (<provider>:<model> or hint:<name>)
+        #[arg(long)]
+        a: String,
+
+        /// Second route (<provider>:<model> or hint:<name>)
+        #[arg(long)]
+        b: String,
+
+        /// TOML suite file with [[task]] entries; defaults to the built-in suite
+        #[arg(long)]
+        suite: Option<std::path::PathBuf>,
+
+        /// Only run the named task (repeatable)
+        #[arg(long)]
+        task: Vec<String>,
+
+        /// Time limit per run in seconds
+        #[arg(long, default_value_t = 900)]
+        timeout_secs: u64,
+
+        /// Also write the markdown report to this file
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+    },
+
+    /// Replay a recorded session against its recorded tool results
+    #[command(long_about = "\
+Replay a recorded session against its recorded tool results.
+
+Sessions are recorded when [observability] record_sessions is on. \
+The replay feeds the recorded LLM responses and tool results back \
+through the agent loop, so it needs no API keys or network access, \
+and reports every point where the run differs from the recording. \
+With --sandbox, sandbox tools run for real in a fresh local sandbox \
+to reproduce build and command failures.
+
+Exits with an error when the replay diverges, so a recording of a \
+fixed bug can run as a regression test.
+
+Examples:
+  zerobuild replay state/recordings/20260101-120000-telegram-1a2b3c4d.jsonl
+  zerobuild replay session.jsonl --sandbox")]
+    Replay {
+        /// Recording file (JSONL)
+        file: std::path::PathBuf,
+
+        /// Run sandbox tools in a fresh local sandbox
+        #[arg(long)]
+        sandbox: bool,
+    },
+
+    /// Render a recorded session as a Markdown or HTML report
+    #[command(long_about = "\
+Render a recorded session as a Markdown or HTML report.
+
+The report lists the requirements, the plan, the tool calls that \
+changed something, file diffs, test results, deploy links and the \
+token cost, priced with [cost.prices]. The gateway serves the same \
+report at /api/sessions/<id>/report.
+
+Examples:
+  zerobuild report state/recordings/20260101-120000-telegram-1a2b3c4d.jsonl
+  zerobuild report session.jsonl --format html -o report.html")]
+    Report {
+        /// Recording file (JSONL)
+        file: std::path::PathBuf,
+
+        /// md or html
+        #[arg(long, default_value = "md")]
+        format: String,
+
+        /// Write the report to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+    },
+
     /// Configure and manage scheduled tasks
     #[command(long_about = "\
 Configure and manage scheduled tasks.
@@ -388,17 +511,38 @@ Examples:
         memory_command: MemoryCommands,
     },
 
+    /// Manage build artifacts
+    #[command(long_about = "\
+Manage build artifacts.
+
+Snapshot versions, screenshots, and full command logs are kept in \
+the backend configured in [zerobuild.artifacts] and deleted by its \
+retention policy (max_age_days, max_project_mb). \
+The daemon applies the policy periodically; 'cleanup' applies it now.
+
+Examples:
+  zerobuild artifacts usage
+  zerobuild artifacts cleanup")]
+    Artifacts {
+        #[command(subcommand)]
+        artifact_command: ArtifactCommands,
+    },
+
     /// Manage configuration
     #[command(long_about = "\
 Manage ZeroBuild configuration.
 
 Inspect and export configuration settings. Use 'schema' to dump \
 the full JSON Schema for the config file, which documents every \
-available key, type, and default value.
+available key, type, and default value. 'encrypt' and 'decrypt' \
+convert a config file (or a project's .zerobuild.toml) to and from \
+SOPS/age encryption, so it can be committed to a private repository.
 
 Examples:
   zerobuild config schema              # print JSON Schema to stdout
-  zerobuild config schema > schema.json")]
+  zerobuild config schema > schema.json
+  zerobuild config encrypt --age age1... --encrypted-regex 'key|token|secret' --in-place
+  zerobuild config decrypt --file project/.zerobuild.toml")]
     Config {
         #[command(subcommand)]
         config_command: ConfigCommands,
@@ -419,12 +563,50 @@ Examples:
         #[arg(value_enum)]
         shell: CompletionShell,
     },
+
+    /// Run the agent of a microVM sandbox (inside the guest)
+    #[command(long_about = "\
+Run the guest agent of the firecracker sandbox provider.
+
+The rootfs image of [zerobuild.sandbox.firecracker] starts this at boot; \
+ZeroBuild on the host sends it the commands and file reads and writes of \
+the sandbox over TCP. It needs no config file.
+
+Examples:
+  zerobuild sandbox-agent
+  zerobuild sandbox-agent --listen 0.0.0.0:7000")]
+    SandboxAgent {
+        /// Address to listen on
+        #[arg(long, default_value = "0.0.0.0:7000")]
+        listen: String,
+    },
 }
 
 #[derive(Subcommand, Debug)]
 enum ConfigCommands {
     /// Dump the full configuration JSON Schema to stdout
     Schema,
+    /// Encrypt a config file's values for age recipients (SOPS format)
+    Encrypt {
+        /// age recipient (`age1...`) that can decrypt the file; repeatable
+        #[arg(long = "age", required = true)]
+        recipients: Vec<String>,
+        /// Only encrypt values under keys matching this regex (default: all values)
+        #[arg(long)]
+        encrypted_regex: Option<String>,
+        /// File to encrypt (default: the active config.toml)
+        #[arg(long)]
+        file: Option<std::path::PathBuf>,
+        /// Replace the file instead of printing the result
+        #[arg(long)]
+        in_place: bool,
+    },
+    /// Print a SOPS-encrypted config file decrypted
+    Decrypt {
+        /// File to decrypt (default: the active config.toml)
+        #[arg(long)]
+        file: Option<std::path::PathBuf>,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -610,14 +792,9 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
-    // Initialize logging - respects RUST_LOG env var, defaults to INFO
-    let subscriber = fmt::Subscriber::builder()
-        .with_env_filter(
-            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
-        )
-        .finish();
-
-    tracing::subscriber::set_global_default(subscriber).expect("setting default subscriber failed");
+    // Initialize logging - respects RUST_LOG env var, defaults to INFO until
+    // the config's [logging] section is applied below.
+    observability::logging::init();
 
     // Onboard runs quick setup by default, or the interactive wizard with --interactive.
     // The onboard wizard uses reqwest::blocking internally, which creates its own
@@ -654,9 +831,9 @@ async fn main() -> Result<()> {
         }
 
         let config = if channels_only {
-            onboard::run_channels_repair_wizard().await
+            Box::pin(onboard::run_channels_repair_wizard()).await
         } else if interactive {
-            onboard::run_wizard(force).await
+            Box::pin(onboard::run_wizard(force)).await
         } else {
             onboard::run_quick_setup(
                 api_key.as_deref(),
@@ -669,19 +846,39 @@ async fn main() -> Result<()> {
         }?;
         // Auto-start channels if user said yes during wizard
         if std::env::var("ZEROBUILD_AUTOSTART_CHANNELS").as_deref() == Ok("1") {
-            channels::start_channels(config).await?;
+            Box::pin(channels::start_channels(config)).await?;
         }
         return Ok(());
     }
 
+    if let Commands::Init { force } = &cli.command {
+        Box::pin(onboard::run_init(*force)).await?;
+        return Ok(());
+    }
+
+    // The guest of a microVM sandbox has no ZeroBuild config.
+    if let Commands::SandboxAgent { listen } = &cli.command {
+        let listener = tokio::net::TcpListener::bind(listen)
+            .await
+            .with_context(|| format!("Failed to listen on {listen}"))?;
+        info!("Sandbox agent listening on {listen}");
+        return sandbox::guest_agent::serve(listener).await;
+    }
+
     // All other commands need config loaded first
     let mut config = Config::load_or_init().await?;
     config.apply_env_overrides();
+    if let Err(e) = observability::logging::apply(&config.logging, &config.workspace_dir) {
+        tracing::warn!(error = %e, "Failed to apply [logging] config; keeping default logger");
+    }
     observability::runtime_trace::init_from_config(&config.observability, &config.workspace_dir);
+    observability::recording::init_from_config(&config.observability, &config.workspace_dir);
+    providers::sampling::set_deterministic(config.agent.deterministic);
+    agent::phases::init_from_config(&config.agent.phases);
 
     match cli.command {
-        Commands::Onboard { .. } => unreachable!(),
-        Commands::Completions { .. } => unreachable!(),
+        Commands::Onboard { .. } | Commands::Init { .. } => unreachable!(),
+        Commands::Completions { .. } | Commands::SandboxAgent { .. } => unreachable!(),
 
         Commands::Agent {
             message,
@@ -689,17 +886,38 @@ async fn main() -> Result<()> {
             model,
             temperature,
             peripheral,
-        } => agent::run(
-            config,
-            message,
-            provider,
-            model,
-            temperature,
-            peripheral,
-            true,
-        )
-        .await
-        .map(|_| ()),
+            profile,
+            project,
+        } => {
+            let profiled = profile.is_some() || project.is_some();
+            let config = if profiled {
+                config::profiles::resolve(&config, profile.as_deref(), project.as_deref())?
+            } else {
+                config
+            };
+            let temperature = temperature.unwrap_or(if profiled {
+                config.default_temperature
+            } else {
+                0.7
+            });
+            let session = observability::logging::session_span(
+                &observability::logging::new_session_id(),
+                &std::env::var("USER").unwrap_or_else(|_| "cli".into()),
+                "cli",
+            );
+            Box::pin(agent::run(
+                config,
+                message,
+                provider,
+                model,
+                temperature,
+                peripheral,
+                true,
+            ))
+            .instrument(session)
+            .await
+            .map(|_| ())
+        }
 
         Commands::Gateway { port, host } => {
             let port = port.unwrap_or(config.gateway.port);
@@ -884,9 +1102,66 @@ async fn main() -> Result<()> {
             None => doctor::run(&config),
         },
 
+        Commands::Eval {
+            a,
+            b,
+            suite,
+            task,
+            timeout_secs,
+            output,
+        } => {
+            eval::run(
+                &config,
+                &a,
+                &b,
+                suite.as_deref(),
+                &task,
+                std::time::Duration::from_secs(timeout_secs),
+                output.as_deref(),
+            )
+            .await
+        }
+
+        Commands::Replay { file, sandbox } => {
+            let report = agent::replay::replay_file(
+                &config,
+                &file,
+                agent::replay::ReplayOptions { sandbox },
+            )
+            .await?;
+            print!("{}", report.render());
+            if !report.reproduced() {
+                bail!("Replay diverged from {}", file.display());
+            }
+            Ok(())
+        }
+
+        Commands::Report {
+            file,
+            format,
+            output,
+        } => {
+            let Some(format) = observability::report::ReportFormat::from_name(&format) else {
+                bail!("Unknown report format '{format}'; use md or html");
+            };
+            let events = observability::recording::load(&file)?;
+            let report =
+                observability::report::SessionReport::from_events(&events, &config.cost.prices)
+                    .render(format);
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, report)
+                        .map_err(|e| anyhow::anyhow!("Failed to write {}: {e}", path.display()))?;
+                    println!("Report written to {}", path.display());
+                }
+                None => print!("{report}"),
+            }
+            Ok(())
+        }
+
         Commands::Channel { channel_command } => match channel_command {
-            ChannelCommands::Start => channels::start_channels(config).await,
-            ChannelCommands::Doctor => channels::doctor_channels(config).await,
+            ChannelCommands::Start => Box::pin(channels::start_channels(config)).await,
+            ChannelCommands::Doctor => Box::pin(channels::doctor_channels(config)).await,
             other => channels::handle_command(other, &config).await,
         },
 
@@ -904,6 +1179,10 @@ async fn main() -> Result<()> {
             memory::cli::handle_command(memory_command, &config).await
         }
 
+        Commands::Artifacts { artifact_command } => {
+            artifacts::handle_command(artifact_command, &config).await
+        }
+
         Commands::Auth { auth_command } => handle_auth_command(auth_command, &config).await,
 
         Commands::Hardware { hardware_command } => {
@@ -923,6 +1202,47 @@ async fn main() -> Result<()> {
                 );
                 Ok(())
             }
+            ConfigCommands::Encrypt {
+                recipients,
+                encrypted_regex,
+                file,
+                in_place,
+            } => {
+                let path = file.unwrap_or_else(|| config.config_path.clone());
+                let text = std::fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read {}", path.display()))?;
+                let recipients = recipients
+                    .iter()
+                    .map(|r| {
+                        r.parse::<age::x25519::Recipient>()
+                            .map_err(|e| anyhow::anyhow!("Invalid age recipient '{r}': {e}"))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                let document = security::sops::encrypt(
+                    toml::from_str(&text)?,
+                    &recipients,
+                    encrypted_regex.as_deref(),
+                )?;
+                let encrypted = toml::to_string_pretty(&document)?;
+                if in_place {
+                    std::fs::write(&path, encrypted)
+                        .with_context(|| format!("Failed to write {}", path.display()))?;
+                    println!("Encrypted {}", path.display());
+                } else {
+                    print!("{encrypted}");
+                }
+                Ok(())
+            }
+            ConfigCommands::Decrypt { file } => {
+                let path = file.unwrap_or_else(|| config.config_path.clone());
+                let text = std::fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read {}", path.display()))?;
+                match security::sops::decrypt_document(&text)? {
+                    Some(document) => print!("{}", toml::to_string_pretty(&document)?),
+                    None => bail!("{} is not SOPS-encrypted", path.display()),
+                }
+                Ok(())
+            }
         },
     }
 }
@@ -195,6 +195,21 @@ async fn handle_stats(config: &Config) -> Result<()> {
         }
     }
 
+    let stats = super::stats::collect_stats(&config.workspace_dir);
+    if !stats.stores.is_empty() {
+        println!("\n  Stores:");
+        for store in &stats.stores {
+            println!(
+                "    {:<20} {:>6} entries {:>10} bytes  last write {}  last access {}",
+                store.store,
+                store.entries,
+                store.disk_bytes,
+                store.last_write.as_deref().unwrap_or("-"),
+                store.last_access.as_deref().unwrap_or("-")
+            );
+        }
+    }
+
     Ok(())
 }
 
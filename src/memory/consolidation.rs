@@ -0,0 +1,462 @@
+//! Memory consolidation.
+//!
+//! Long-lived stores accumulate near-duplicate entries ("user prefers dark
+//! mode", "user likes dark themes", ...). [`consolidate_memory`] clusters
+//! entries of the same category by embedding similarity — falling back to
+//! token overlap when no embedding provider is configured — asks a model to
+//! merge each cluster into one canonical entry, and archives the originals to
+//! `memory/archive/consolidated-YYYY-MM-DD.jsonl` before removing them. The
+//! daemon runs it periodically via [`run_consolidation_worker`] when
+//! `[memory.consolidation].enabled = true`.
+
+use super::embeddings::EmbeddingProvider;
+use super::traits::{Memory, MemoryCategory, MemoryEntry};
+use super::vector::cosine_similarity;
+use super::ACTIVE_PROJECT_KEY;
+use crate::config::{Config, MemoryConsolidationConfig};
+use crate::providers::Provider;
+use crate::skills::learned::{LEARNED_SKILL_CATEGORY, LEARNED_SKILL_KEY_PREFIX};
+use anyhow::Result;
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const CONSOLIDATION_SYSTEM_PROMPT: &str = "You merge near-duplicate memory entries into a single \
+canonical entry. Keep every distinct fact, drop repetition, and when entries conflict prefer the \
+most recent one (entries are listed oldest first). Reply with the merged entry text only — no \
+preamble, no bullet list unless the facts are genuinely separate.";
+
+/// Outcome of one consolidation pass.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ConsolidationReport {
+    /// Clusters merged into a canonical entry.
+    pub clusters: usize,
+    /// Original entries archived (and removed, except the one whose key the
+    /// canonical entry reuses).
+    pub archived: usize,
+}
+
+/// Merge clusters of near-duplicate entries in `memory`.
+///
+/// `memory_dir` is the store's `memory/` directory; originals are appended
+/// to `archive/consolidated-<date>.jsonl` inside it. Conversation entries,
+/// learned skills and the active-project record are never touched.
+pub async fn consolidate_memory(
+    memory: &dyn Memory,
+    embedder: &dyn EmbeddingProvider,
+    provider: &dyn Provider,
+    model: &str,
+    settings: &MemoryConsolidationConfig,
+    memory_dir: &Path,
+) -> Result<ConsolidationReport> {
+    let mut report = ConsolidationReport::default();
+    let min_cluster = settings.min_cluster_size.max(2);
+
+    let entries: Vec<MemoryEntry> = memory
+        .list(None, None)
+        .await?
+        .into_iter()
+        .filter(is_consolidatable)
+        .collect();
+
+    let mut categories: Vec<MemoryCategory> = Vec::new();
+    for entry in &entries {
+        if !categories.contains(&entry.category) {
+            categories.push(entry.category.clone());
+        }
+    }
+
+    for category in categories {
+        let mut group: Vec<&MemoryEntry> =
+            entries.iter().filter(|e| e.category == category).collect();
+        if group.len() < min_cluster {
+            continue;
+        }
+        group.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+
+        let texts: Vec<&str> = group.iter().map(|e| e.content.as_str()).collect();
+        let vectors = match embedder.embed(&texts).await {
+            Ok(v) if v.len() == texts.len() && v.iter().all(|vec| !vec.is_empty()) => Some(v),
+            Ok(_) => None,
+            Err(e) => {
+                tracing::warn!("memory consolidation: embedding failed, using token overlap: {e}");
+                None
+            }
+        };
+
+        for cluster in cluster_indices(&texts, vectors.as_deref(), settings.similarity_threshold) {
+            if cluster.len() < min_cluster {
+                continue;
+            }
+            let members: Vec<&MemoryEntry> = cluster.iter().map(|&i| group[i]).collect();
+            match merge_cluster(memory, provider, model, &members, memory_dir).await {
+                Ok(()) => {
+                    report.clusters += 1;
+                    report.archived += members.len();
+                }
+                Err(e) => tracing::warn!("memory consolidation: cluster skipped: {e}"),
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Conversation context, the active-project record and learned skills are
+/// left alone: skills are structured JSON looked up by key, so merging them
+/// would corrupt them.
+fn is_consolidatable(entry: &MemoryEntry) -> bool {
+    let is_skill = entry.key.starts_with(LEARNED_SKILL_KEY_PREFIX)
+        || matches!(&entry.category, MemoryCategory::Custom(name) if name == LEARNED_SKILL_CATEGORY);
+    entry.category != MemoryCategory::Conversation && entry.key != ACTIVE_PROJECT_KEY && !is_skill
+}
+
+/// Greedy single-pass clustering: each unassigned entry seeds a cluster and
+/// absorbs every later unassigned entry at least `threshold` similar to it.
+fn cluster_indices(
+    texts: &[&str],
+    vectors: Option<&[Vec<f32>]>,
+    threshold: f64,
+) -> Vec<Vec<usize>> {
+    let token_sets: Vec<HashSet<String>> = if vectors.is_none() {
+        texts.iter().map(|t| tokenize(t)).collect()
+    } else {
+        Vec::new()
+    };
+    let similarity = |a: usize, b: usize| match vectors {
+        Some(v) => f64::from(cosine_similarity(&v[a], &v[b])),
+        None => jaccard(&token_sets[a], &token_sets[b]),
+    };
+
+    let mut assigned = vec![false; texts.len()];
+    let mut clusters = Vec::new();
+    for seed in 0..texts.len() {
+        if assigned[seed] {
+            continue;
+        }
+        assigned[seed] = true;
+        let mut cluster = vec![seed];
+        for (other, taken) in assigned.iter_mut().enumerate().skip(seed + 1) {
+            if !*taken && similarity(seed, other) >= threshold {
+                *taken = true;
+                cluster.push(other);
+            }
+        }
+        clusters.push(cluster);
+    }
+    clusters
+}
+
+fn tokenize(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    #[allow(clippy::cast_precision_loss)]
+    let score = a.intersection(b).count() as f64 / union as f64;
+    score
+}
+
+/// Summarize `members` (oldest first), archive them, then replace them with
+/// one entry stored under the newest member's key.
+async fn merge_cluster(
+    memory: &dyn Memory,
+    provider: &dyn Provider,
+    model: &str,
+    members: &[&MemoryEntry],
+    memory_dir: &Path,
+) -> Result<()> {
+    let mut prompt = String::from("Entries:\n");
+    for (i, entry) in members.iter().enumerate() {
+        let _ = writeln!(prompt, "{}. [{}] {}", i + 1, entry.timestamp, entry.content);
+    }
+
+    let merged = provider
+        .chat_with_system(Some(CONSOLIDATION_SYSTEM_PROMPT), &prompt, model, 0.2)
+        .await?;
+    let merged = merged.trim();
+    if merged.is_empty() {
+        anyhow::bail!("model returned an empty summary");
+    }
+
+    archive_entries(memory_dir, members)?;
+
+    let canonical = members[members.len() - 1];
+    memory
+        .store(
+            &canonical.key,
+            merged,
+            canonical.category.clone(),
+            canonical.session_id.as_deref(),
+        )
+        .await?;
+    for entry in &members[..members.len() - 1] {
+        if entry.key != canonical.key {
+            memory.forget(&entry.key).await?;
+        }
+    }
+    Ok(())
+}
+
+fn archive_entries(memory_dir: &Path, entries: &[&MemoryEntry]) -> Result<()> {
+    let archive_dir = memory_dir.join("archive");
+    std::fs::create_dir_all(&archive_dir)?;
+    let path = archive_dir.join(format!(
+        "consolidated-{}.jsonl",
+        chrono::Local::now().format("%Y-%m-%d")
+    ));
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    for entry in entries {
+        writeln!(file, "{}", serde_json::to_string(entry)?)?;
+    }
+    Ok(())
+}
+
+/// Workspace roots of every sqlite store: the workspace itself plus each
+/// scoped store (`memory/global`, `memory/user_<id>`, ...).
+fn store_roots(workspace_dir: &Path) -> Vec<PathBuf> {
+    let mut roots = vec![workspace_dir.to_path_buf()];
+    if let Ok(entries) = std::fs::read_dir(workspace_dir.join("memory")) {
+        for entry in entries.flatten() {
+            if entry.path().is_dir() && entry.file_name() != "archive" {
+                roots.push(entry.path());
+            }
+        }
+    }
+    roots.retain(|root| root.join("memory").join("brain.db").is_file());
+    roots
+}
+
+/// Background loop consolidating every sqlite memory store in the workspace
+/// every `[memory.consolidation].interval_hours`.
+pub async fn run_consolidation_worker(config: Config) -> Result<()> {
+    let settings = config.memory.consolidation.clone();
+    if !settings.enabled || settings.interval_hours == 0 {
+        return Ok(());
+    }
+
+    let provider_name = config.default_provider.as_deref().unwrap_or("openrouter");
+    let model = settings
+        .model
+        .clone()
+        .or_else(|| config.default_model.clone())
+        .unwrap_or_else(|| "anthropic/claude-sonnet-4".into());
+    let provider = crate::providers::create_routed_provider(
+        provider_name,
+        config.api_key.as_deref(),
+        config.api_url.as_deref(),
+        &config.reliability,
+        &crate::providers::effective_model_routes(&config),
+        &model,
+    )?;
+
+    let resolved = super::resolve_embedding_config(
+        &config.memory,
+        &config.embedding_routes,
+        config.api_key.as_deref(),
+    );
+    let embedder: Arc<dyn EmbeddingProvider> =
+        Arc::from(super::embeddings::create_embedding_provider(
+            &resolved.provider,
+            resolved.api_key.as_deref(),
+            &resolved.model,
+            resolved.dimensions,
+        ));
+
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(
+        settings.interval_hours * 3_600,
+    ));
+    loop {
+        interval.tick().await;
+        for root in store_roots(&config.workspace_dir) {
+            #[allow(clippy::cast_possible_truncation)]
+            let memory = match super::SqliteMemory::with_embedder(
+                &root,
+                embedder.clone(),
+                config.memory.vector_weight as f32,
+                config.memory.keyword_weight as f32,
+                config.memory.embedding_cache_size,
+                config.memory.sqlite_open_timeout_secs,
+            ) {
+                Ok(memory) => memory.with_retention(config.memory.retention.clone()),
+                Err(e) => {
+                    tracing::warn!("memory consolidation skipped {}: {e}", root.display());
+                    continue;
+                }
+            };
+            let report = match consolidate_memory(
+                &memory,
+                embedder.as_ref(),
+                provider.as_ref(),
+                &model,
+                &settings,
+                &root.join("memory"),
+            )
+            .await
+            {
+                Ok(report) => report,
+                Err(e) => {
+                    tracing::warn!("memory consolidation failed for {}: {e}", root.display());
+                    continue;
+                }
+            };
+            if report.clusters > 0 {
+                tracing::info!(
+                    "memory consolidation merged {} entries into {} in {}",
+                    report.archived,
+                    report.clusters,
+                    root.display()
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::embeddings::NoopEmbedding;
+    use crate::memory::SqliteMemory;
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+    use tempfile::TempDir;
+
+    struct SummaryProvider {
+        prompts: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl Provider for SummaryProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            self.prompts.lock().unwrap().push(message.to_string());
+            Ok("User prefers dark mode in every editor".into())
+        }
+    }
+
+    #[test]
+    fn clustering_groups_overlapping_texts() {
+        let texts = [
+            "user prefers dark mode",
+            "the user prefers dark mode",
+            "deploy target is vercel",
+            "user prefers dark mode always",
+        ];
+        let clusters = cluster_indices(&texts, None, 0.6);
+        assert_eq!(clusters, vec![vec![0, 1, 3], vec![2]]);
+
+        let vectors = vec![vec![1.0, 0.0], vec![0.0, 1.0], vec![0.99, 0.05]];
+        let clusters = cluster_indices(&texts[..3], Some(&vectors), 0.9);
+        assert_eq!(clusters, vec![vec![0, 2], vec![1]]);
+    }
+
+    #[tokio::test]
+    async fn consolidation_skips_learned_skills() {
+        let tmp = TempDir::new().unwrap();
+        let mem = SqliteMemory::new(tmp.path()).unwrap();
+        let skill = MemoryCategory::Custom("skill".into());
+        for key in ["nextjs_1", "nextjs_2", "nextjs_3"] {
+            mem.store(key, "set up nextjs with tailwind", skill.clone(), None)
+                .await
+                .unwrap();
+        }
+        for key in ["skill:a", "skill:b", "skill:c"] {
+            mem.store(key, "user prefers dark mode", MemoryCategory::Core, None)
+                .await
+                .unwrap();
+        }
+
+        let provider = SummaryProvider {
+            prompts: Mutex::new(Vec::new()),
+        };
+        let settings = MemoryConsolidationConfig {
+            similarity_threshold: 0.6,
+            ..MemoryConsolidationConfig::default()
+        };
+        let report = consolidate_memory(
+            &mem,
+            &NoopEmbedding,
+            &provider,
+            "cheap-model",
+            &settings,
+            &tmp.path().join("memory"),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(report, ConsolidationReport::default());
+        assert!(provider.prompts.lock().unwrap().is_empty());
+        assert_eq!(mem.count().await.unwrap(), 6);
+    }
+
+    #[tokio::test]
+    async fn consolidation_merges_cluster_and_archives_originals() {
+        let tmp = TempDir::new().unwrap();
+        let mem = SqliteMemory::new(tmp.path()).unwrap();
+        for (key, content) in [
+            ("pref_1", "user prefers dark mode"),
+            ("pref_2", "the user prefers dark mode"),
+            ("pref_3", "user prefers dark mode always"),
+            ("deploy", "deploy target is vercel"),
+        ] {
+            mem.store(key, content, MemoryCategory::Core, None)
+                .await
+                .unwrap();
+        }
+
+        let provider = SummaryProvider {
+            prompts: Mutex::new(Vec::new()),
+        };
+        let settings = MemoryConsolidationConfig {
+            similarity_threshold: 0.6,
+            ..MemoryConsolidationConfig::default()
+        };
+        let memory_dir = tmp.path().join("memory");
+        let report = consolidate_memory(
+            &mem,
+            &NoopEmbedding,
+            &provider,
+            "cheap-model",
+            &settings,
+            &memory_dir,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            report,
+            ConsolidationReport {
+                clusters: 1,
+                archived: 3
+            }
+        );
+        assert_eq!(provider.prompts.lock().unwrap().len(), 1);
+        assert_eq!(mem.count().await.unwrap(), 2);
+        assert!(mem.get("deploy").await.unwrap().is_some());
+
+        let archive = std::fs::read_dir(memory_dir.join("archive"))
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .path();
+        assert_eq!(std::fs::read_to_string(archive).unwrap().lines().count(), 3);
+    }
+}
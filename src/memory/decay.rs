@@ -0,0 +1,300 @@
+//! Memory TTL and relevance decay.
+//!
+//! Entries carry an optional `expires_at` (set from the category TTL in
+//! `[memory.retention]` or an explicit per-entry TTL). Expired entries are
+//! hidden from reads immediately and deleted by [`compact_workspace`], which
+//! the daemon runs periodically via [`run_compaction_worker`]. Recall scores
+//! are multiplied by [`decay_factor`] so stale facts sink below fresher ones.
+
+use crate::config::{MemoryConfig, MemoryRetentionConfig};
+use crate::memory::MemoryCategory;
+use anyhow::Result;
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use rusqlite::{params, Connection};
+use std::path::{Path, PathBuf};
+
+/// Current time in the fixed-width UTC format used for `expires_at`, so
+/// expiry checks can compare strings in SQL.
+pub fn now_timestamp() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// `expires_at` value for an entry written now with the given TTL.
+pub fn expiry_timestamp(ttl: std::time::Duration) -> String {
+    let ttl = Duration::from_std(ttl).unwrap_or(Duration::MAX);
+    Utc::now()
+        .checked_add_signed(ttl)
+        .unwrap_or(DateTime::<Utc>::MAX_UTC)
+        .to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// `expires_at` from a category TTL in days (`None` when `ttl_days` is 0).
+pub fn category_expiry(ttl_days: u32) -> Option<String> {
+    (ttl_days > 0).then(|| {
+        expiry_timestamp(std::time::Duration::from_secs(
+            u64::from(ttl_days) * 24 * 60 * 60,
+        ))
+    })
+}
+
+/// Exponential decay: `0.5^(age / half_life)`. Returns 1.0 when decay is
+/// disabled (`half_life_days <= 0`) or the age is unknown/negative.
+pub fn decay_factor(age_days: f64, half_life_days: f64) -> f64 {
+    if half_life_days <= 0.0 || !age_days.is_finite() || age_days <= 0.0 {
+        return 1.0;
+    }
+    0.5_f64.powf(age_days / half_life_days)
+}
+
+/// Age in days of an RFC 3339 timestamp (`None` if unparseable).
+pub fn age_days(timestamp: &str) -> Option<f64> {
+    let age = Utc::now().signed_duration_since(parse_utc(timestamp)?);
+    #[allow(clippy::cast_precision_loss)]
+    Some(age.num_seconds() as f64 / 86_400.0)
+}
+
+/// Delete expired entries from every sqlite memory database in the workspace
+/// (the shared `brain.db` plus global and per-user stores). Returns the
+/// number of rows removed.
+pub fn compact_workspace(workspace_dir: &Path, retention: &MemoryRetentionConfig) -> Result<u64> {
+    let mut removed = 0;
+    for db_path in memory_databases(workspace_dir) {
+        match compact_database(&db_path, retention) {
+            Ok(count) => removed += count,
+            Err(e) => tracing::warn!("memory compaction skipped {}: {e}", db_path.display()),
+        }
+    }
+    Ok(removed)
+}
+
+fn memory_databases(workspace_dir: &Path) -> Vec<PathBuf> {
+    let memory_dir = workspace_dir.join("memory");
+    let mut paths = vec![memory_dir.join("brain.db")];
+    if let Ok(entries) = std::fs::read_dir(&memory_dir) {
+        for entry in entries.flatten() {
+            let scoped = entry.path().join("memory").join("brain.db");
+            if entry.path().is_dir() {
+                paths.push(scoped);
+            }
+        }
+    }
+    paths.retain(|p| p.is_file());
+    paths
+}
+
+fn compact_database(db_path: &Path, retention: &MemoryRetentionConfig) -> Result<u64> {
+    let conn = Connection::open(db_path)?;
+    conn.execute_batch("PRAGMA journal_mode = WAL; PRAGMA synchronous = NORMAL;")?;
+
+    let has_expiry: bool = conn
+        .prepare("SELECT sql FROM sqlite_master WHERE type='table' AND name='memories'")?
+        .query_row([], |row| row.get::<_, String>(0))?
+        .contains("expires_at");
+    if !has_expiry {
+        // Not yet migrated by SqliteMemory; nothing carries an expiry.
+        return Ok(0);
+    }
+
+    // Timestamps are parsed and compared in UTC rather than as strings:
+    // `updated_at` carries the writer's local offset, which changes with DST
+    // and between hosts sharing a workspace.
+    let now = Utc::now();
+    let expired: Vec<String> = {
+        let mut stmt = conn.prepare("SELECT id, category, updated_at, expires_at FROM memories")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+            ))
+        })?;
+        let mut expired = Vec::new();
+        for row in rows {
+            let (id, category, updated_at, expires_at) = row?;
+            if is_expired(
+                retention,
+                &category,
+                &updated_at,
+                expires_at.as_deref(),
+                now,
+            ) {
+                expired.push(id);
+            }
+        }
+        expired
+    };
+
+    let tx = conn.unchecked_transaction()?;
+    let mut removed = 0;
+    for id in &expired {
+        removed += tx.execute("DELETE FROM memories WHERE id = ?1", params![id])?;
+    }
+    tx.commit()?;
+
+    Ok(u64::try_from(removed).unwrap_or(0))
+}
+
+/// Whether an entry is past its explicit `expires_at` or, lacking one, past
+/// its category TTL measured from `updated_at`. Unparseable timestamps never
+/// expire an entry.
+fn is_expired(
+    retention: &MemoryRetentionConfig,
+    category: &str,
+    updated_at: &str,
+    expires_at: Option<&str>,
+    now: DateTime<Utc>,
+) -> bool {
+    if let Some(expires_at) = expires_at {
+        return parse_utc(expires_at).is_some_and(|at| at <= now);
+    }
+    // Entries written before a category TTL was configured have no
+    // `expires_at`; age them out by last write instead.
+    let category = match category {
+        "core" => MemoryCategory::Core,
+        "daily" => MemoryCategory::Daily,
+        "conversation" => MemoryCategory::Conversation,
+        other => MemoryCategory::Custom(other.to_string()),
+    };
+    let ttl_days = retention.for_category(&category).ttl_days;
+    ttl_days > 0
+        && parse_utc(updated_at)
+            .is_some_and(|written| written < now - Duration::days(i64::from(ttl_days)))
+}
+
+/// An RFC 3339 timestamp in UTC, whatever offset it was written with.
+pub(crate) fn parse_utc(timestamp: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|ts| ts.with_timezone(&Utc))
+}
+
+/// Background loop deleting expired memory entries every
+/// `retention.compaction_interval_minutes`. Returns immediately when the
+/// interval is 0.
+pub async fn run_compaction_worker(config: MemoryConfig, workspace_dir: PathBuf) -> Result<()> {
+    let minutes = config.retention.compaction_interval_minutes;
+    if minutes == 0 {
+        return Ok(());
+    }
+
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(minutes * 60));
+    loop {
+        interval.tick().await;
+        let retention = config.retention.clone();
+        let dir = workspace_dir.clone();
+        let removed =
+            tokio::task::spawn_blocking(move || compact_workspace(&dir, &retention)).await??;
+        if removed > 0 {
+            tracing::info!("memory compaction removed {removed} expired entries");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::{Memory, MemoryCategory, SqliteMemory};
+    use tempfile::TempDir;
+
+    #[test]
+    fn decay_halves_every_half_life() {
+        assert!((decay_factor(0.0, 7.0) - 1.0).abs() < f64::EPSILON);
+        assert!((decay_factor(7.0, 7.0) - 0.5).abs() < 1e-9);
+        assert!((decay_factor(14.0, 7.0) - 0.25).abs() < 1e-9);
+        assert!((decay_factor(365.0, 0.0) - 1.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn expiry_timestamps_compare_as_strings() {
+        let soon = expiry_timestamp(std::time::Duration::from_secs(60));
+        let later = expiry_timestamp(std::time::Duration::from_secs(3_600));
+        assert!(now_timestamp() < soon);
+        assert!(soon < later);
+        assert!(category_expiry(0).is_none());
+        assert!(age_days("not a timestamp").is_none());
+    }
+
+    #[test]
+    fn ttl_compares_timestamps_in_utc() {
+        let mut retention = MemoryRetentionConfig::default();
+        retention.daily.ttl_days = 30;
+        let now = Utc::now();
+        let offset = chrono::FixedOffset::east_opt(14 * 3600).unwrap();
+        // 29 days old, written with a +14:00 offset: lexically older than a
+        // UTC cutoff string would suggest, but still within the TTL.
+        let recent = (now - Duration::days(29))
+            .with_timezone(&offset)
+            .to_rfc3339();
+        let stale = (now - Duration::days(31))
+            .with_timezone(&offset)
+            .to_rfc3339();
+        assert!(!is_expired(&retention, "daily", &recent, None, now));
+        assert!(is_expired(&retention, "daily", &stale, None, now));
+        assert!(!is_expired(&retention, "daily", "garbage", None, now));
+        assert!(is_expired(
+            &retention,
+            "core",
+            &recent,
+            Some("2000-01-01T05:00:00+05:00"),
+            now
+        ));
+    }
+
+    #[test]
+    fn skills_are_exempt_from_custom_decay_and_ttl() {
+        let mut retention = MemoryRetentionConfig::default();
+        retention.custom.ttl_days = 1;
+        let skill = MemoryCategory::Custom("skill".into());
+        assert!(retention.for_category(&skill).half_life_days <= 0.0);
+        assert!(
+            retention
+                .for_category(&MemoryCategory::Custom("notes".into()))
+                .half_life_days
+                > 0.0
+        );
+        let old = (Utc::now() - Duration::days(365)).to_rfc3339();
+        assert!(!is_expired(&retention, "skill", &old, None, Utc::now()));
+        assert!(is_expired(&retention, "notes", &old, None, Utc::now()));
+    }
+
+    #[tokio::test]
+    async fn compaction_deletes_expired_and_category_ttl_entries() {
+        let tmp = TempDir::new().unwrap();
+        let mem = SqliteMemory::new(tmp.path()).unwrap();
+        mem.store("keep", "fresh", MemoryCategory::Core, None)
+            .await
+            .unwrap();
+        mem.store("stale_daily", "old log", MemoryCategory::Daily, None)
+            .await
+            .unwrap();
+        mem.store_with_ttl(
+            "sandbox_id",
+            "sbx-123",
+            MemoryCategory::Core,
+            None,
+            std::time::Duration::from_secs(3_600),
+        )
+        .await
+        .unwrap();
+
+        let conn = Connection::open(tmp.path().join("memory").join("brain.db")).unwrap();
+        conn.execute(
+            "UPDATE memories SET expires_at = '2000-01-01T00:00:00Z' WHERE key = 'sandbox_id'",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "UPDATE memories SET updated_at = '2000-01-01T00:00:00+00:00' WHERE key = 'stale_daily'",
+            [],
+        )
+        .unwrap();
+
+        let mut retention = MemoryRetentionConfig::default();
+        retention.daily.ttl_days = 30;
+        assert_eq!(compact_workspace(tmp.path(), &retention).unwrap(), 2);
+        assert!(mem.get("keep").await.unwrap().is_some());
+        assert!(mem.get("stale_daily").await.unwrap().is_none());
+        assert_eq!(mem.count().await.unwrap(), 1);
+    }
+}
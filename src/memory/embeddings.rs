@@ -1,4 +1,6 @@
 use async_trait::async_trait;
+#[cfg(feature = "memory-fastembed")]
+use std::sync::Arc;
 
 /// Trait for embedding providers — convert text to vectors
 #[async_trait]
@@ -154,6 +156,113 @@ impl EmbeddingProvider for OpenAiEmbedding {
     }
 }
 
+// ── Local embedding provider (fastembed / ONNX) ──────────────
+
+/// Default local model: small, English, 384 dimensions.
+pub const DEFAULT_LOCAL_EMBEDDING_MODEL: &str = "AllMiniLML6V2";
+
+/// Runs a bundled ONNX embedding model in-process, so vector recall works
+/// without any embedding API key. Weights are downloaded on first use into
+/// `~/.zerobuild/models/fastembed` (or `FASTEMBED_CACHE_DIR` when set).
+#[cfg(feature = "memory-fastembed")]
+pub struct LocalEmbedding {
+    model: fastembed::EmbeddingModel,
+    dims: usize,
+    engine: Arc<std::sync::Mutex<Option<fastembed::TextEmbedding>>>,
+}
+
+#[cfg(feature = "memory-fastembed")]
+impl LocalEmbedding {
+    /// Resolve `model` by fastembed variant name (`BGESmallENV15`) or by
+    /// Hugging Face model code suffix (`bge-small-en-v1.5`). Unknown or
+    /// remote-only names (e.g. `text-embedding-3-small`) fall back to
+    /// [`DEFAULT_LOCAL_EMBEDDING_MODEL`].
+    pub fn new(model: &str) -> Self {
+        let info = Self::resolve_model(model).unwrap_or_else(|| {
+            tracing::warn!(
+                model,
+                "Unknown local embedding model; using {DEFAULT_LOCAL_EMBEDDING_MODEL}"
+            );
+            Self::resolve_model(DEFAULT_LOCAL_EMBEDDING_MODEL)
+                .expect("default local embedding model is supported")
+        });
+        Self {
+            model: info.model,
+            dims: info.dim,
+            engine: Arc::new(std::sync::Mutex::new(None)),
+        }
+    }
+
+    fn resolve_model(name: &str) -> Option<fastembed::ModelInfo<fastembed::EmbeddingModel>> {
+        let name = name.trim();
+        if name.is_empty() {
+            return None;
+        }
+        fastembed::TextEmbedding::list_supported_models()
+            .into_iter()
+            .find(|info| {
+                format!("{:?}", info.model).eq_ignore_ascii_case(name)
+                    || info.model_code.eq_ignore_ascii_case(name)
+                    || info
+                        .model_code
+                        .rsplit('/')
+                        .next()
+                        .is_some_and(|suffix| suffix.eq_ignore_ascii_case(name))
+            })
+    }
+
+    fn cache_dir() -> std::path::PathBuf {
+        if let Ok(dir) = std::env::var("FASTEMBED_CACHE_DIR") {
+            return std::path::PathBuf::from(dir);
+        }
+        directories::UserDirs::new()
+            .map(|dirs| dirs.home_dir().join(".zerobuild"))
+            .unwrap_or_else(|| std::path::PathBuf::from(".zerobuild"))
+            .join("models")
+            .join("fastembed")
+    }
+}
+
+#[cfg(feature = "memory-fastembed")]
+#[async_trait]
+impl EmbeddingProvider for LocalEmbedding {
+    fn name(&self) -> &str {
+        "local"
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dims
+    }
+
+    async fn embed(&self, texts: &[&str]) -> anyhow::Result<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let owned: Vec<String> = texts.iter().map(|t| (*t).to_string()).collect();
+        let engine = Arc::clone(&self.engine);
+        let model = self.model.clone();
+
+        // ONNX inference (and the first-use download) is blocking work.
+        tokio::task::spawn_blocking(move || {
+            let mut guard = engine
+                .lock()
+                .map_err(|_| anyhow::anyhow!("local embedding engine lock poisoned"))?;
+            if guard.is_none() {
+                let options = fastembed::TextInitOptions::new(model)
+                    .with_cache_dir(Self::cache_dir())
+                    .with_show_download_progress(false);
+                *guard = Some(fastembed::TextEmbedding::try_new(options)?);
+            }
+            let engine = guard
+                .as_mut()
+                .ok_or_else(|| anyhow::anyhow!("local embedding engine unavailable"))?;
+            engine.embed(owned, None)
+        })
+        .await?
+    }
+}
+
 // ── Factory ──────────────────────────────────────────────────
 
 pub fn create_embedding_provider(
@@ -186,10 +295,24 @@ pub fn create_embedding_provider(
             let key = api_key.unwrap_or("");
             Box::new(OpenAiEmbedding::new(base_url, key, model, dims))
         }
+        "local" | "fastembed" => create_local_embedding_provider(model),
         _ => Box::new(NoopEmbedding),
     }
 }
 
+#[cfg(feature = "memory-fastembed")]
+fn create_local_embedding_provider(model: &str) -> Box<dyn EmbeddingProvider> {
+    Box::new(LocalEmbedding::new(model))
+}
+
+#[cfg(not(feature = "memory-fastembed"))]
+fn create_local_embedding_provider(_model: &str) -> Box<dyn EmbeddingProvider> {
+    tracing::warn!(
+        "embedding_provider 'local' requested but this build was compiled without `memory-fastembed`; rebuild with `--features memory-fastembed`. Falling back to keyword-only recall"
+    );
+    Box::new(NoopEmbedding)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -276,6 +399,24 @@ mod tests {
         assert_eq!(p.name(), "none");
     }
 
+    #[cfg(not(feature = "memory-fastembed"))]
+    #[test]
+    fn factory_local_without_feature_returns_noop() {
+        let p = create_embedding_provider("local", None, DEFAULT_LOCAL_EMBEDDING_MODEL, 384);
+        assert_eq!(p.name(), "none");
+    }
+
+    #[cfg(feature = "memory-fastembed")]
+    #[test]
+    fn local_model_resolves_by_code_suffix_and_falls_back() {
+        let p = LocalEmbedding::new("bge-small-en-v1.5");
+        assert_eq!(p.model, fastembed::EmbeddingModel::BGESmallENV15);
+        assert_eq!(p.dimensions(), 384);
+
+        let fallback = LocalEmbedding::new("text-embedding-3-small");
+        assert_eq!(fallback.model, fastembed::EmbeddingModel::AllMiniLML6V2);
+    }
+
     #[test]
     fn factory_custom_empty_url() {
         // "custom:" with no URL — should still construct without panic
@@ -0,0 +1,179 @@
+//! Portable memory export/import.
+//!
+//! [`export_memory`] dumps every entry of a backend into a versioned
+//! [`MemoryExport`] that [`import_memory`] can load into any other backend or
+//! instance. [`render_markdown`] produces a human-readable copy for data
+//! access requests. Per-user wipes live on
+//! [`UserScopedMemory::wipe_user`](super::user_scoped::UserScopedMemory::wipe_user).
+
+use super::traits::{Memory, MemoryCategory, MemoryEntry};
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt::Write;
+
+/// Current [`MemoryExport::version`].
+pub const EXPORT_VERSION: u32 = 1;
+
+/// Serialized memory of one store.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryExport {
+    pub version: u32,
+    /// Owner of the exported store (`None` for workspace/global memory).
+    pub user_id: Option<String>,
+    pub exported_at: String,
+    pub entries: Vec<MemoryEntry>,
+}
+
+impl MemoryExport {
+    /// Fail if the export was written by a newer, incompatible version.
+    pub fn ensure_supported(&self) -> Result<()> {
+        if self.version > EXPORT_VERSION {
+            bail!(
+                "memory export version {} is newer than supported version {EXPORT_VERSION}",
+                self.version
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Collect every entry of `memory`.
+///
+/// Backends cap `list` results, so each category is listed separately to
+/// reach past the cap on large stores.
+pub async fn export_memory(memory: &dyn Memory, user_id: Option<&str>) -> Result<MemoryExport> {
+    let mut entries = memory.list(None, None).await?;
+    let mut categories = vec![
+        MemoryCategory::Core,
+        MemoryCategory::Daily,
+        MemoryCategory::Conversation,
+    ];
+    for entry in &entries {
+        if !categories.contains(&entry.category) {
+            categories.push(entry.category.clone());
+        }
+    }
+    for category in &categories {
+        entries.extend(memory.list(Some(category), None).await?);
+    }
+
+    let mut seen = HashSet::new();
+    entries.retain(|entry| seen.insert(entry.key.clone()));
+    entries.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+    for entry in &mut entries {
+        entry.score = None;
+    }
+
+    Ok(MemoryExport {
+        version: EXPORT_VERSION,
+        user_id: user_id.map(str::to_string),
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        entries,
+    })
+}
+
+/// Store every entry of `export` into `memory`, overwriting entries with the
+/// same key. Returns the number of entries imported.
+pub async fn import_memory(memory: &dyn Memory, export: &MemoryExport) -> Result<usize> {
+    export.ensure_supported()?;
+    for entry in &export.entries {
+        memory
+            .store(
+                &entry.key,
+                &entry.content,
+                entry.category.clone(),
+                entry.session_id.as_deref(),
+            )
+            .await?;
+    }
+    Ok(export.entries.len())
+}
+
+/// Markdown rendering of an export, grouped by category.
+pub fn render_markdown(export: &MemoryExport) -> String {
+    let mut out = String::from("# Memory export\n\n");
+    if let Some(user_id) = &export.user_id {
+        let _ = writeln!(out, "- User: `{user_id}`");
+    }
+    let _ = writeln!(out, "- Exported: {}", export.exported_at);
+    let _ = writeln!(out, "- Entries: {}", export.entries.len());
+
+    let mut categories: Vec<String> = Vec::new();
+    for entry in &export.entries {
+        let name = entry.category.to_string();
+        if !categories.contains(&name) {
+            categories.push(name);
+        }
+    }
+    for category in categories {
+        let _ = write!(out, "\n## {category}\n\n");
+        for entry in export
+            .entries
+            .iter()
+            .filter(|e| e.category.to_string() == category)
+        {
+            let _ = writeln!(
+                out,
+                "- **{}** ({}): {}",
+                entry.key,
+                entry.timestamp,
+                entry.content.replace('\n', " ")
+            );
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::{MarkdownMemory, SqliteMemory};
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn export_roundtrips_into_another_backend() {
+        let src_dir = TempDir::new().unwrap();
+        let src = SqliteMemory::new(src_dir.path()).unwrap();
+        src.store("pkg", "always use pnpm", MemoryCategory::Core, None)
+            .await
+            .unwrap();
+        src.store(
+            "brand",
+            "brand color #FF5733",
+            MemoryCategory::Custom("design".into()),
+            None,
+        )
+        .await
+        .unwrap();
+
+        let export = export_memory(&src, Some("alice")).await.unwrap();
+        assert_eq!(export.entries.len(), 2);
+        let json = serde_json::to_string(&export).unwrap();
+        let parsed: MemoryExport = serde_json::from_str(&json).unwrap();
+
+        let dst_dir = TempDir::new().unwrap();
+        let dst = SqliteMemory::new(dst_dir.path()).unwrap();
+        assert_eq!(import_memory(&dst, &parsed).await.unwrap(), 2);
+        let brand = dst.get("brand").await.unwrap().unwrap();
+        assert_eq!(brand.category, MemoryCategory::Custom("design".into()));
+
+        let markdown = render_markdown(&parsed);
+        assert!(markdown.contains("- User: `alice`"));
+        assert!(markdown.contains("## design"));
+        assert!(markdown.contains("**pkg**"));
+    }
+
+    #[tokio::test]
+    async fn import_rejects_newer_versions() {
+        let tmp = TempDir::new().unwrap();
+        let mem = MarkdownMemory::new(tmp.path());
+        let export = MemoryExport {
+            version: EXPORT_VERSION + 1,
+            user_id: None,
+            exported_at: String::new(),
+            entries: Vec::new(),
+        };
+        assert!(import_memory(&mem, &export).await.is_err());
+    }
+}
@@ -1,7 +1,10 @@
 pub mod backend;
 pub mod chunker;
 pub mod cli;
+pub mod consolidation;
+pub mod decay;
 pub mod embeddings;
+pub mod export;
 pub mod hygiene;
 pub mod lucid;
 pub mod markdown;
@@ -11,7 +14,9 @@ pub mod postgres;
 pub mod response_cache;
 pub mod snapshot;
 pub mod sqlite;
+pub mod stats;
 pub mod traits;
+pub mod user_scoped;
 pub mod vector;
 
 #[allow(unused_imports)]
@@ -258,7 +263,8 @@ pub fn create_memory_with_storage_and_routes(
             config.keyword_weight as f32,
             config.embedding_cache_size,
             config.sqlite_open_timeout_secs,
-        )?;
+        )?
+        .with_retention(config.retention.clone());
         Ok(mem)
     }
 
@@ -1,6 +1,8 @@
+use super::decay;
 use super::embeddings::EmbeddingProvider;
 use super::traits::{Memory, MemoryCategory, MemoryEntry};
 use super::vector;
+use crate::config::MemoryRetentionConfig;
 use anyhow::Context;
 use async_trait::async_trait;
 use chrono::Local;
@@ -25,6 +27,7 @@ const SQLITE_OPEN_TIMEOUT_CAP_SECS: u64 = 300;
 /// - **Hybrid Merge**: weighted fusion of vector + keyword results
 /// - **Embedding Cache**: LRU-evicted cache to avoid redundant API calls
 /// - **Safe Reindex**: temp DB → seed → sync → atomic swap → rollback
+/// - **TTL + Decay**: expired entries are hidden; recall scores decay with age
 pub struct SqliteMemory {
     conn: Arc<Mutex<Connection>>,
     db_path: PathBuf,
@@ -32,6 +35,7 @@ pub struct SqliteMemory {
     vector_weight: f32,
     keyword_weight: f32,
     cache_max: usize,
+    retention: MemoryRetentionConfig,
 }
 
 impl SqliteMemory {
@@ -90,9 +94,26 @@ impl SqliteMemory {
             vector_weight,
             keyword_weight,
             cache_max,
+            retention: MemoryRetentionConfig::default(),
         })
     }
 
+    /// Apply per-category TTL and decay settings.
+    pub fn with_retention(mut self, retention: MemoryRetentionConfig) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    /// Recall score multiplier for an entry last written at `updated_at`.
+    fn decay_for(
+        retention: &MemoryRetentionConfig,
+        category: &MemoryCategory,
+        updated_at: &str,
+    ) -> f64 {
+        let half_life = retention.for_category(category).half_life_days;
+        decay::age_days(updated_at).map_or(1.0, |age| decay::decay_factor(age, half_life))
+    }
+
     /// Open SQLite connection, optionally with a timeout (for locked/slow storage).
     fn open_connection(
         db_path: &Path,
@@ -183,9 +204,46 @@ impl SqliteMemory {
             )?;
         }
 
+        // Migration: add expires_at column (UTC, second precision) for TTLs
+        let has_expires_at: bool = conn
+            .prepare("SELECT sql FROM sqlite_master WHERE type='table' AND name='memories'")?
+            .query_row([], |row| row.get::<_, String>(0))?
+            .contains("expires_at");
+        if !has_expires_at {
+            conn.execute_batch(
+                "ALTER TABLE memories ADD COLUMN expires_at TEXT;
+                 CREATE INDEX IF NOT EXISTS idx_memories_expires ON memories(expires_at);",
+            )?;
+        }
+
+        // Migration: track reads (get/recall hits) for `memory::stats`
+        let has_accessed_at: bool = conn
+            .prepare("SELECT sql FROM sqlite_master WHERE type='table' AND name='memories'")?
+            .query_row([], |row| row.get::<_, String>(0))?
+            .contains("accessed_at");
+        if !has_accessed_at {
+            conn.execute_batch(
+                "ALTER TABLE memories ADD COLUMN accessed_at TEXT;
+                 ALTER TABLE memories ADD COLUMN access_count INTEGER NOT NULL DEFAULT 0;",
+            )?;
+        }
+
         Ok(())
     }
 
+    /// Record that entries were returned by a read.
+    fn touch(conn: &Connection, ids: &[&str], now: &str) {
+        for id in ids {
+            if let Err(e) = conn.execute(
+                "UPDATE memories SET accessed_at = ?1, access_count = access_count + 1
+                 WHERE id = ?2",
+                params![now, id],
+            ) {
+                tracing::debug!("Failed to record memory access: {e}");
+            }
+        }
+    }
+
     fn category_to_str(cat: &MemoryCategory) -> String {
         match cat {
             MemoryCategory::Core => "core".into(),
@@ -376,6 +434,49 @@ impl SqliteMemory {
         Ok(scored)
     }
 
+    /// Upsert an entry with an optional `expires_at` (see [`decay`]).
+    async fn store_entry(
+        &self,
+        key: &str,
+        content: &str,
+        category: MemoryCategory,
+        session_id: Option<&str>,
+        expires_at: Option<String>,
+    ) -> anyhow::Result<()> {
+        // Compute embedding (async, before blocking work)
+        let embedding_bytes = self
+            .get_or_compute_embedding(content)
+            .await?
+            .map(|emb| vector::vec_to_bytes(&emb));
+
+        let conn = self.conn.clone();
+        let key = key.to_string();
+        let content = content.to_string();
+        let sid = session_id.map(String::from);
+
+        tokio::task::spawn_blocking(move || -> anyhow::Result<()> {
+            let conn = conn.lock();
+            let now = Local::now().to_rfc3339();
+            let cat = Self::category_to_str(&category);
+            let id = Uuid::new_v4().to_string();
+
+            conn.execute(
+                "INSERT INTO memories (id, key, content, category, embedding, created_at, updated_at, session_id, expires_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+                 ON CONFLICT(key) DO UPDATE SET
+                    content = excluded.content,
+                    category = excluded.category,
+                    embedding = excluded.embedding,
+                    updated_at = excluded.updated_at,
+                    session_id = excluded.session_id,
+                    expires_at = excluded.expires_at",
+                params![id, key, content, cat, embedding_bytes, now, now, sid, expires_at],
+            )?;
+            Ok(())
+        })
+        .await?
+    }
+
     /// Safe reindex: rebuild FTS5 + embeddings with rollback on failure
     #[allow(dead_code)]
     pub async fn reindex(&self) -> anyhow::Result<usize> {
@@ -443,37 +544,22 @@ impl Memory for SqliteMemory {
         category: MemoryCategory,
         session_id: Option<&str>,
     ) -> anyhow::Result<()> {
-        // Compute embedding (async, before blocking work)
-        let embedding_bytes = self
-            .get_or_compute_embedding(content)
-            .await?
-            .map(|emb| vector::vec_to_bytes(&emb));
-
-        let conn = self.conn.clone();
-        let key = key.to_string();
-        let content = content.to_string();
-        let sid = session_id.map(String::from);
-
-        tokio::task::spawn_blocking(move || -> anyhow::Result<()> {
-            let conn = conn.lock();
-            let now = Local::now().to_rfc3339();
-            let cat = Self::category_to_str(&category);
-            let id = Uuid::new_v4().to_string();
+        let expires_at = decay::category_expiry(self.retention.for_category(&category).ttl_days);
+        self.store_entry(key, content, category, session_id, expires_at)
+            .await
+    }
 
-            conn.execute(
-                "INSERT INTO memories (id, key, content, category, embedding, created_at, updated_at, session_id)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
-                 ON CONFLICT(key) DO UPDATE SET
-                    content = excluded.content,
-                    category = excluded.category,
-                    embedding = excluded.embedding,
-                    updated_at = excluded.updated_at,
-                    session_id = excluded.session_id",
-                params![id, key, content, cat, embedding_bytes, now, now, sid],
-            )?;
-            Ok(())
-        })
-        .await?
+    async fn store_with_ttl(
+        &self,
+        key: &str,
+        content: &str,
+        category: MemoryCategory,
+        session_id: Option<&str>,
+        ttl: Duration,
+    ) -> anyhow::Result<()> {
+        let expires_at = Some(decay::expiry_timestamp(ttl));
+        self.store_entry(key, content, category, session_id, expires_at)
+            .await
     }
 
     async fn recall(
@@ -494,6 +580,8 @@ impl Memory for SqliteMemory {
         let sid = session_id.map(String::from);
         let vector_weight = self.vector_weight;
         let keyword_weight = self.keyword_weight;
+        let retention = self.retention.clone();
+        let now = decay::now_timestamp();
 
         tokio::task::spawn_blocking(move || -> anyhow::Result<Vec<MemoryEntry>> {
             let conn = conn.lock();
@@ -539,14 +627,17 @@ impl Memory for SqliteMemory {
                     .collect::<Vec<_>>()
                     .join(", ");
                 let sql = format!(
-                    "SELECT id, key, content, category, created_at, session_id \
-                     FROM memories WHERE id IN ({placeholders})"
+                    "SELECT id, key, content, category, created_at, session_id, updated_at \
+                     FROM memories WHERE id IN ({placeholders}) \
+                     AND (expires_at IS NULL OR expires_at > ?{})",
+                    merged.len() + 1
                 );
                 let mut stmt = conn.prepare(&sql)?;
-                let id_params: Vec<Box<dyn rusqlite::types::ToSql>> = merged
+                let mut id_params: Vec<Box<dyn rusqlite::types::ToSql>> = merged
                     .iter()
                     .map(|s| Box::new(s.id.clone()) as Box<dyn rusqlite::types::ToSql>)
                     .collect();
+                id_params.push(Box::new(now.clone()));
                 let params_ref: Vec<&dyn rusqlite::types::ToSql> =
                     id_params.iter().map(AsRef::as_ref).collect();
                 let rows = stmt.query_map(params_ref.as_slice(), |row| {
@@ -557,25 +648,30 @@ impl Memory for SqliteMemory {
                         row.get::<_, String>(3)?,
                         row.get::<_, String>(4)?,
                         row.get::<_, Option<String>>(5)?,
+                        row.get::<_, String>(6)?,
                     ))
                 })?;
 
                 let mut entry_map = std::collections::HashMap::new();
                 for row in rows {
-                    let (id, key, content, cat, ts, sid) = row?;
-                    entry_map.insert(id, (key, content, cat, ts, sid));
+                    let (id, key, content, cat, ts, sid, updated_at) = row?;
+                    entry_map.insert(id, (key, content, cat, ts, sid, updated_at));
                 }
 
                 for scored in &merged {
-                    if let Some((key, content, cat, ts, sid)) = entry_map.remove(&scored.id) {
+                    if let Some((key, content, cat, ts, sid, updated_at)) =
+                        entry_map.remove(&scored.id)
+                    {
+                        let category = Self::str_to_category(&cat);
+                        let decay = Self::decay_for(&retention, &category, &updated_at);
                         let entry = MemoryEntry {
                             id: scored.id.clone(),
                             key,
                             content,
-                            category: Self::str_to_category(&cat),
+                            category,
                             timestamp: ts,
                             session_id: sid,
-                            score: Some(f64::from(scored.final_score)),
+                            score: Some(f64::from(scored.final_score) * decay),
                         };
                         if let Some(filter_sid) = session_ref {
                             if entry.session_id.as_deref() != Some(filter_sid) {
@@ -607,11 +703,14 @@ impl Memory for SqliteMemory {
                         .collect();
                     let where_clause = conditions.join(" OR ");
                     let sql = format!(
-                        "SELECT id, key, content, category, created_at, session_id FROM memories
-                         WHERE {where_clause}
+                        "SELECT id, key, content, category, created_at, session_id, updated_at
+                         FROM memories
+                         WHERE ({where_clause})
+                           AND (expires_at IS NULL OR expires_at > ?{})
                          ORDER BY updated_at DESC
                          LIMIT ?{}",
-                        keywords.len() * 2 + 1
+                        keywords.len() * 2 + 1,
+                        keywords.len() * 2 + 2
                     );
                     let mut stmt = conn.prepare(&sql)?;
                     let mut param_values: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
@@ -619,19 +718,23 @@ impl Memory for SqliteMemory {
                         param_values.push(Box::new(kw.clone()));
                         param_values.push(Box::new(kw.clone()));
                     }
+                    param_values.push(Box::new(now.clone()));
                     #[allow(clippy::cast_possible_wrap)]
                     param_values.push(Box::new(limit as i64));
                     let params_ref: Vec<&dyn rusqlite::types::ToSql> =
                         param_values.iter().map(AsRef::as_ref).collect();
                     let rows = stmt.query_map(params_ref.as_slice(), |row| {
+                        let category = Self::str_to_category(&row.get::<_, String>(3)?);
+                        let decay =
+                            Self::decay_for(&retention, &category, &row.get::<_, String>(6)?);
                         Ok(MemoryEntry {
                             id: row.get(0)?,
                             key: row.get(1)?,
                             content: row.get(2)?,
-                            category: Self::str_to_category(&row.get::<_, String>(3)?),
+                            category,
                             timestamp: row.get(4)?,
                             session_id: row.get(5)?,
-                            score: Some(1.0),
+                            score: Some(decay),
                         })
                     })?;
                     for row in rows {
@@ -646,7 +749,15 @@ impl Memory for SqliteMemory {
                 }
             }
 
+            // Decay can reorder hybrid results; keep best-first.
+            results.sort_by(|a, b| {
+                b.score
+                    .partial_cmp(&a.score)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
             results.truncate(limit);
+            let ids: Vec<&str> = results.iter().map(|e| e.id.as_str()).collect();
+            Self::touch(&conn, &ids, &now);
             Ok(results)
         })
         .await?
@@ -655,14 +766,16 @@ impl Memory for SqliteMemory {
     async fn get(&self, key: &str) -> anyhow::Result<Option<MemoryEntry>> {
         let conn = self.conn.clone();
         let key = key.to_string();
+        let now = decay::now_timestamp();
 
         tokio::task::spawn_blocking(move || -> anyhow::Result<Option<MemoryEntry>> {
             let conn = conn.lock();
             let mut stmt = conn.prepare(
-                "SELECT id, key, content, category, created_at, session_id FROM memories WHERE key = ?1",
+                "SELECT id, key, content, category, created_at, session_id FROM memories
+                 WHERE key = ?1 AND (expires_at IS NULL OR expires_at > ?2)",
             )?;
 
-            let mut rows = stmt.query_map(params![key], |row| {
+            let mut rows = stmt.query_map(params![key, now], |row| {
                 Ok(MemoryEntry {
                     id: row.get(0)?,
                     key: row.get(1)?,
@@ -675,7 +788,12 @@ impl Memory for SqliteMemory {
             })?;
 
             match rows.next() {
-                Some(Ok(entry)) => Ok(Some(entry)),
+                Some(Ok(entry)) => {
+                    drop(rows);
+                    drop(stmt);
+                    Self::touch(&conn, &[entry.id.as_str()], &now);
+                    Ok(Some(entry))
+                }
                 _ => Ok(None),
             }
         })
@@ -692,6 +810,7 @@ impl Memory for SqliteMemory {
         let conn = self.conn.clone();
         let category = category.cloned();
         let sid = session_id.map(String::from);
+        let now = decay::now_timestamp();
 
         tokio::task::spawn_blocking(move || -> anyhow::Result<Vec<MemoryEntry>> {
             let conn = conn.lock();
@@ -714,9 +833,10 @@ impl Memory for SqliteMemory {
                 let cat_str = Self::category_to_str(cat);
                 let mut stmt = conn.prepare(
                     "SELECT id, key, content, category, created_at, session_id FROM memories
-                     WHERE category = ?1 ORDER BY updated_at DESC LIMIT ?2",
+                     WHERE category = ?1 AND (expires_at IS NULL OR expires_at > ?3)
+                     ORDER BY updated_at DESC LIMIT ?2",
                 )?;
-                let rows = stmt.query_map(params![cat_str, DEFAULT_LIST_LIMIT], row_mapper)?;
+                let rows = stmt.query_map(params![cat_str, DEFAULT_LIST_LIMIT, now], row_mapper)?;
                 for row in rows {
                     let entry = row?;
                     if let Some(sid) = session_ref {
@@ -729,9 +849,10 @@ impl Memory for SqliteMemory {
             } else {
                 let mut stmt = conn.prepare(
                     "SELECT id, key, content, category, created_at, session_id FROM memories
+                     WHERE expires_at IS NULL OR expires_at > ?2
                      ORDER BY updated_at DESC LIMIT ?1",
                 )?;
-                let rows = stmt.query_map(params![DEFAULT_LIST_LIMIT], row_mapper)?;
+                let rows = stmt.query_map(params![DEFAULT_LIST_LIMIT, now], row_mapper)?;
                 for row in rows {
                     let entry = row?;
                     if let Some(sid) = session_ref {
@@ -762,11 +883,15 @@ impl Memory for SqliteMemory {
 
     async fn count(&self) -> anyhow::Result<usize> {
         let conn = self.conn.clone();
+        let now = decay::now_timestamp();
 
         tokio::task::spawn_blocking(move || -> anyhow::Result<usize> {
             let conn = conn.lock();
-            let count: i64 =
-                conn.query_row("SELECT COUNT(*) FROM memories", [], |row| row.get(0))?;
+            let count: i64 = conn.query_row(
+                "SELECT COUNT(*) FROM memories WHERE expires_at IS NULL OR expires_at > ?1",
+                params![now],
+                |row| row.get(0),
+            )?;
             #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
             Ok(count as usize)
         })
@@ -1055,6 +1180,103 @@ mod tests {
         assert!(results[0].content.contains("quick"));
     }
 
+    // ── TTL and decay ──────────────────────────────────────────
+
+    #[tokio::test]
+    async fn expired_entries_are_hidden_from_reads() {
+        let (_tmp, mem) = temp_sqlite();
+        mem.store("live", "sandbox alive", MemoryCategory::Core, None)
+            .await
+            .unwrap();
+        mem.store_with_ttl(
+            "dead",
+            "sandbox gone",
+            MemoryCategory::Core,
+            None,
+            Duration::from_secs(3600),
+        )
+        .await
+        .unwrap();
+        assert!(mem.get("dead").await.unwrap().is_some());
+
+        mem.conn
+            .lock()
+            .execute(
+                "UPDATE memories SET expires_at = '2000-01-01T00:00:00Z' WHERE key = 'dead'",
+                [],
+            )
+            .unwrap();
+
+        assert!(mem.get("dead").await.unwrap().is_none());
+        assert_eq!(mem.count().await.unwrap(), 1);
+        assert_eq!(mem.list(None, None).await.unwrap().len(), 1);
+        let recalled = mem.recall("sandbox", 10, None).await.unwrap();
+        assert_eq!(recalled.len(), 1);
+        assert_eq!(recalled[0].key, "live");
+    }
+
+    #[tokio::test]
+    async fn category_ttl_sets_expiry_on_store() {
+        let tmp = TempDir::new().unwrap();
+        let mut retention = MemoryRetentionConfig::default();
+        retention.conversation.ttl_days = 3;
+        let mem = SqliteMemory::new(tmp.path())
+            .unwrap()
+            .with_retention(retention);
+        mem.store("chat", "hello", MemoryCategory::Conversation, None)
+            .await
+            .unwrap();
+        mem.store("fact", "likes tea", MemoryCategory::Core, None)
+            .await
+            .unwrap();
+
+        let expiry = |key: &str| -> Option<String> {
+            mem.conn
+                .lock()
+                .query_row(
+                    "SELECT expires_at FROM memories WHERE key = ?1",
+                    params![key],
+                    |row| row.get(0),
+                )
+                .unwrap()
+        };
+        assert!(expiry("chat").is_some());
+        assert!(expiry("fact").is_none());
+    }
+
+    #[tokio::test]
+    async fn recall_decays_stale_entries_below_fresh_ones() {
+        let (_tmp, mem) = temp_sqlite();
+        mem.store(
+            "old_pref",
+            "preferred editor vim",
+            MemoryCategory::Daily,
+            None,
+        )
+        .await
+        .unwrap();
+        mem.store(
+            "new_pref",
+            "preferred editor helix",
+            MemoryCategory::Daily,
+            None,
+        )
+        .await
+        .unwrap();
+        mem.conn
+            .lock()
+            .execute(
+                "UPDATE memories SET updated_at = '2020-01-01T00:00:00+00:00' WHERE key = 'old_pref'",
+                [],
+            )
+            .unwrap();
+
+        let results = mem.recall("preferred editor", 10, None).await.unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].key, "new_pref");
+        assert!(results[1].score.unwrap() < 0.01);
+    }
+
     #[tokio::test]
     async fn recall_empty_query_returns_empty() {
         let (_tmp, mem) = temp_sqlite();
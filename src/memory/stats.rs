@@ -0,0 +1,357 @@
+//! Memory statistics and inspection for operators.
+//!
+//! Reads every sqlite store in the workspace (the workspace store plus each
+//! scoped store under `memory/`: `global`, `user_<id>`, `project_<id>`)
+//! directly, so counts are exact regardless of backend `list` caps.
+//! Entries carry their read history (`accessed_at`, `access_count`, updated
+//! by `get`/`recall` hits) to help explain why the agent keeps recalling
+//! something.
+
+use super::decay;
+use anyhow::{bail, Result};
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Name of the workspace-level store in [`StoreStats::store`].
+pub const WORKSPACE_STORE: &str = "workspace";
+
+/// Entry count and content size of one category.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct CategoryStats {
+    pub category: String,
+    pub entries: usize,
+    pub bytes: u64,
+}
+
+/// Statistics of one memory store.
+#[derive(Debug, Clone, Serialize)]
+pub struct StoreStats {
+    /// `workspace`, or the directory name under `memory/` (`user_<id>`, ...)
+    pub store: String,
+    /// Live (unexpired) entries
+    pub entries: usize,
+    /// Expired entries awaiting compaction
+    pub expired: usize,
+    pub content_bytes: u64,
+    /// Size of the database files on disk
+    pub disk_bytes: u64,
+    pub categories: Vec<CategoryStats>,
+    pub last_write: Option<String>,
+    pub last_access: Option<String>,
+}
+
+/// Statistics of every store in a workspace.
+#[derive(Debug, Clone, Serialize)]
+pub struct MemoryStats {
+    pub stores: Vec<StoreStats>,
+    pub total_entries: usize,
+    pub total_disk_bytes: u64,
+}
+
+/// A memory entry with its storage metadata.
+#[derive(Debug, Clone, Serialize)]
+pub struct InspectedEntry {
+    pub store: String,
+    pub id: String,
+    pub key: String,
+    pub content: String,
+    pub category: String,
+    pub session_id: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub expires_at: Option<String>,
+    pub accessed_at: Option<String>,
+    pub access_count: u64,
+    pub has_embedding: bool,
+}
+
+/// Filters for [`browse`].
+#[derive(Debug, Clone, Default)]
+pub struct BrowseQuery<'a> {
+    pub category: Option<&'a str>,
+    /// Substring matched against key and content
+    pub search: Option<&'a str>,
+    pub offset: usize,
+    pub limit: usize,
+}
+
+/// Names of the sqlite stores in `workspace_dir`, workspace store first.
+pub fn store_names(workspace_dir: &Path) -> Vec<String> {
+    let mut names = Vec::new();
+    if store_root(workspace_dir, WORKSPACE_STORE)
+        .join("memory")
+        .join("brain.db")
+        .is_file()
+    {
+        names.push(WORKSPACE_STORE.to_string());
+    }
+    let mut scoped: Vec<String> = std::fs::read_dir(workspace_dir.join("memory"))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().join("memory").join("brain.db").is_file())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect();
+    scoped.sort();
+    names.extend(scoped);
+    names
+}
+
+fn store_root(workspace_dir: &Path, store: &str) -> PathBuf {
+    if store == WORKSPACE_STORE {
+        workspace_dir.to_path_buf()
+    } else {
+        workspace_dir.join("memory").join(store)
+    }
+}
+
+fn open_store(workspace_dir: &Path, store: &str) -> Result<Connection> {
+    if store != WORKSPACE_STORE {
+        super::user_scoped::validate_user_id(store)?;
+    }
+    let db_path = store_root(workspace_dir, store)
+        .join("memory")
+        .join("brain.db");
+    if !db_path.is_file() {
+        bail!("memory store '{store}' not found");
+    }
+    Ok(Connection::open_with_flags(
+        &db_path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )?)
+}
+
+/// Stores written before access tracking lack the access columns until
+/// they are next opened by the memory backend.
+fn has_access_columns(conn: &Connection) -> Result<bool> {
+    let sql: String = conn.query_row(
+        "SELECT sql FROM sqlite_master WHERE type='table' AND name='memories'",
+        [],
+        |row| row.get(0),
+    )?;
+    Ok(sql.contains("accessed_at"))
+}
+
+fn disk_bytes(workspace_dir: &Path, store: &str) -> u64 {
+    std::fs::read_dir(store_root(workspace_dir, store).join("memory"))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("brain.db"))
+        .filter_map(|entry| entry.metadata().ok())
+        .map(|meta| meta.len())
+        .sum()
+}
+
+/// Statistics of one store.
+pub fn store_stats(workspace_dir: &Path, store: &str) -> Result<StoreStats> {
+    let conn = open_store(workspace_dir, store)?;
+    let now = decay::now_timestamp();
+    let live = "(expires_at IS NULL OR expires_at > ?1)";
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT category, COUNT(*), COALESCE(SUM(LENGTH(CAST(content AS BLOB))), 0)
+         FROM memories WHERE {live} GROUP BY category ORDER BY COUNT(*) DESC, category"
+    ))?;
+    let categories = stmt
+        .query_map(params![now], |row| {
+            Ok(CategoryStats {
+                category: row.get(0)?,
+                entries: usize::try_from(row.get::<_, i64>(1)?).unwrap_or_default(),
+                bytes: u64::try_from(row.get::<_, i64>(2)?).unwrap_or_default(),
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let expired: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM memories WHERE expires_at IS NOT NULL AND expires_at <= ?1",
+        params![now],
+        |row| row.get(0),
+    )?;
+    let last_write: Option<String> = conn.query_row(
+        &format!("SELECT MAX(updated_at) FROM memories WHERE {live}"),
+        params![now],
+        |row| row.get(0),
+    )?;
+    let last_access: Option<String> = if has_access_columns(&conn)? {
+        conn.query_row(
+            &format!("SELECT MAX(accessed_at) FROM memories WHERE {live}"),
+            params![now],
+            |row| row.get(0),
+        )?
+    } else {
+        None
+    };
+
+    Ok(StoreStats {
+        store: store.to_string(),
+        entries: categories.iter().map(|c| c.entries).sum(),
+        expired: usize::try_from(expired).unwrap_or_default(),
+        content_bytes: categories.iter().map(|c| c.bytes).sum(),
+        disk_bytes: disk_bytes(workspace_dir, store),
+        categories,
+        last_write,
+        last_access,
+    })
+}
+
+/// Statistics of every store in the workspace. Unreadable stores are
+/// skipped with a warning.
+pub fn collect_stats(workspace_dir: &Path) -> MemoryStats {
+    let stores: Vec<StoreStats> = store_names(workspace_dir)
+        .iter()
+        .filter_map(|store| match store_stats(workspace_dir, store) {
+            Ok(stats) => Some(stats),
+            Err(e) => {
+                tracing::warn!(store = %store, "Skipping memory store in stats: {e}");
+                None
+            }
+        })
+        .collect();
+    MemoryStats {
+        total_entries: stores.iter().map(|s| s.entries).sum(),
+        total_disk_bytes: stores.iter().map(|s| s.disk_bytes).sum(),
+        stores,
+    }
+}
+
+fn select_entries(conn: &Connection) -> Result<String> {
+    let access = if has_access_columns(conn)? {
+        "accessed_at, access_count"
+    } else {
+        "NULL, 0"
+    };
+    Ok(format!(
+        "SELECT id, key, content, category, session_id, created_at, updated_at, expires_at,
+                {access}, embedding IS NOT NULL
+         FROM memories"
+    ))
+}
+
+fn map_entry(store: &str, row: &rusqlite::Row<'_>) -> rusqlite::Result<InspectedEntry> {
+    Ok(InspectedEntry {
+        store: store.to_string(),
+        id: row.get(0)?,
+        key: row.get(1)?,
+        content: row.get(2)?,
+        category: row.get(3)?,
+        session_id: row.get(4)?,
+        created_at: row.get(5)?,
+        updated_at: row.get(6)?,
+        expires_at: row.get(7)?,
+        accessed_at: row.get(8)?,
+        access_count: u64::try_from(row.get::<_, i64>(9)?).unwrap_or_default(),
+        has_embedding: row.get(10)?,
+    })
+}
+
+/// Page through a store's entries (including expired ones), most recently
+/// written first.
+pub fn browse(
+    workspace_dir: &Path,
+    store: &str,
+    query: &BrowseQuery<'_>,
+) -> Result<Vec<InspectedEntry>> {
+    let conn = open_store(workspace_dir, store)?;
+    let sql = format!(
+        "{} WHERE (?1 IS NULL OR category = ?1)
+           AND (?2 IS NULL OR instr(key, ?2) > 0 OR instr(content, ?2) > 0)
+         ORDER BY updated_at DESC LIMIT ?3 OFFSET ?4",
+        select_entries(&conn)?
+    );
+    let limit = i64::try_from(query.limit.max(1)).unwrap_or(i64::MAX);
+    let offset = i64::try_from(query.offset).unwrap_or(i64::MAX);
+    let mut stmt = conn.prepare(&sql)?;
+    let entries = stmt
+        .query_map(
+            params![query.category, query.search, limit, offset],
+            |row| map_entry(store, row),
+        )?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(entries)
+}
+
+/// One entry of a store by key, with its metadata.
+pub fn inspect(workspace_dir: &Path, store: &str, key: &str) -> Result<Option<InspectedEntry>> {
+    let conn = open_store(workspace_dir, store)?;
+    let sql = format!("{} WHERE key = ?1", select_entries(&conn)?);
+    Ok(conn
+        .query_row(&sql, params![key], |row| map_entry(store, row))
+        .optional()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::{Memory, MemoryCategory, SqliteMemory};
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn stats_cover_every_store_and_track_access() {
+        let tmp = TempDir::new().unwrap();
+        let workspace = SqliteMemory::new(tmp.path()).unwrap();
+        workspace
+            .store("pkg", "always use pnpm", MemoryCategory::Core, None)
+            .await
+            .unwrap();
+        workspace
+            .store("standup", "fixed login bug", MemoryCategory::Daily, None)
+            .await
+            .unwrap();
+        let alice = SqliteMemory::new(&tmp.path().join("memory").join("user_alice")).unwrap();
+        alice
+            .store("editor", "prefers vim", MemoryCategory::Core, None)
+            .await
+            .unwrap();
+
+        workspace.get("pkg").await.unwrap();
+        workspace.recall("pnpm", 5, None).await.unwrap();
+
+        let stats = collect_stats(tmp.path());
+        assert_eq!(stats.total_entries, 3);
+        let names: Vec<&str> = stats.stores.iter().map(|s| s.store.as_str()).collect();
+        assert_eq!(names, ["workspace", "user_alice"]);
+        let ws = &stats.stores[0];
+        assert_eq!(ws.categories.len(), 2);
+        assert!(ws.disk_bytes > 0);
+        assert!(ws.last_access.is_some());
+        assert!(stats.stores[1].last_access.is_none());
+
+        let pkg = inspect(tmp.path(), WORKSPACE_STORE, "pkg")
+            .unwrap()
+            .unwrap();
+        assert_eq!(pkg.access_count, 2);
+        assert_eq!(pkg.category, "core");
+
+        let daily = browse(
+            tmp.path(),
+            WORKSPACE_STORE,
+            &BrowseQuery {
+                category: Some("daily"),
+                limit: 10,
+                ..BrowseQuery::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(daily.len(), 1);
+        let searched = browse(
+            tmp.path(),
+            "user_alice",
+            &BrowseQuery {
+                search: Some("vim"),
+                limit: 10,
+                ..BrowseQuery::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(searched[0].key, "editor");
+    }
+
+    #[test]
+    fn unknown_or_unsafe_stores_are_rejected() {
+        let tmp = TempDir::new().unwrap();
+        assert!(store_stats(tmp.path(), "user_nobody").is_err());
+        assert!(inspect(tmp.path(), "..", "key").is_err());
+    }
+}
@@ -66,6 +66,20 @@ pub trait Memory: Send + Sync {
         session_id: Option<&str>,
     ) -> anyhow::Result<()>;
 
+    /// Store a memory entry that expires after `ttl`, overriding the
+    /// category's configured TTL. Backends without expiry support store the
+    /// entry without one.
+    async fn store_with_ttl(
+        &self,
+        key: &str,
+        content: &str,
+        category: MemoryCategory,
+        session_id: Option<&str>,
+        _ttl: std::time::Duration,
+    ) -> anyhow::Result<()> {
+        self.store(key, content, category, session_id).await
+    }
+
     /// Recall memories matching a query (keyword search), optionally scoped to a session
     async fn recall(
         &self,
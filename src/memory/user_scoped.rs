@@ -7,13 +7,25 @@
 //! - Each user gets their own memory instance (SQLite/Markdown)
 //! - Global memory for shared data (skills, tools, system patterns)
 //! - Automatic user memory creation on first access
-//! - LRU cache to limit memory usage
+//! - LRU cache of open user backends (`max_resident_users`)
+//! - Per-user entry/byte quotas enforced on store (`user_max_entries`,
+//!   `user_max_bytes`, `user_eviction_policy`)
+//! - [`CurrentUserMemory`] routes a shared `Arc<dyn Memory>` (e.g. the one
+//!   handed to the memory tools) to the user set via [`with_memory_user`]
+//! - Per-project memory under `memory/project_<id>/`, shared by every user
+//!   working on the project set via [`with_memory_project`]; entries in the
+//!   [`PROJECT_CATEGORY`] category are routed there
 
+use super::export::{export_memory, MemoryExport};
 use super::traits::{Memory, MemoryCategory, MemoryEntry};
 use super::{create_memory, MemoryConfig};
+use crate::config::{MemoryEvictionPolicy, MemoryRetentionConfig};
 use async_trait::async_trait;
 use std::collections::HashMap;
+use std::fmt::Write;
+use std::future::Future;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 
 /// Memory scope for operations
@@ -23,10 +35,147 @@ pub enum MemoryScope {
     User,
     /// Global/shared memory only
     Global,
+    /// Memory of the active project, shared by all users
+    Project,
     /// Both user and global (default)
     Both,
 }
 
+/// Category whose entries a [`UserMemorySession`] with an active project
+/// stores in project memory (architecture decisions, chosen libraries,
+/// known issues).
+pub const PROJECT_CATEGORY: &str = "project";
+
+fn is_project_category(category: &MemoryCategory) -> bool {
+    matches!(category, MemoryCategory::Custom(name) if name == PROJECT_CATEGORY)
+}
+
+/// Directory-safe project id: lowercase, dash-separated words of `name`.
+pub fn project_id(name: &str) -> String {
+    let id = name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .take(8)
+        .map(str::to_ascii_lowercase)
+        .collect::<Vec<_>>()
+        .join("-");
+    if id.is_empty() {
+        "default".into()
+    } else {
+        id
+    }
+}
+
+/// Memory user id of `sender` on `channel`, safe as a directory name.
+///
+/// Bytes outside `[A-Za-z0-9-]` in either part are percent-encoded, so the
+/// `_` joining the parts is unambiguous and no two senders share a store.
+/// Plain ids such as `telegram_42` are unchanged.
+pub fn memory_user_id(channel: &str, sender: &str) -> String {
+    format!("{}_{}", encode_id_part(channel), encode_id_part(sender))
+}
+
+/// The channel and sender a [`memory_user_id`] was built from, or `None`
+/// if `id` isn't one.
+pub fn parse_memory_user_id(id: &str) -> Option<(String, String)> {
+    let (channel, sender) = id.split_once('_')?;
+    Some((decode_id_part(channel)?, decode_id_part(sender)?))
+}
+
+fn decode_id_part(part: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(part.len());
+    let mut rest = part.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        match byte {
+            b'%' => {
+                let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+                bytes.push(u8::from_str_radix(hex, 16).ok()?);
+                rest = &tail[2..];
+            }
+            b'_' => return None,
+            _ => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+fn encode_id_part(part: &str) -> String {
+    let mut out = String::with_capacity(part.len());
+    for byte in part.bytes() {
+        if byte.is_ascii_alphanumeric() || byte == b'-' {
+            out.push(char::from(byte));
+        } else {
+            let _ = write!(out, "%{byte:02X}");
+        }
+    }
+    out
+}
+
+/// Eviction counters for user-scoped memory.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+pub struct EvictionStats {
+    /// User backends closed by the resident-user LRU
+    pub resident_evictions: u64,
+    /// Entries deleted to satisfy per-user quotas
+    pub entry_evictions: u64,
+    /// Content bytes freed by entry evictions
+    pub bytes_evicted: u64,
+}
+
+#[derive(Default)]
+struct EvictionCounters {
+    resident_evictions: AtomicU64,
+    entry_evictions: AtomicU64,
+    bytes_evicted: AtomicU64,
+}
+
+/// Evictions of every [`UserScopedMemory`] in the process.
+static TOTAL_EVICTIONS: EvictionCounters = EvictionCounters {
+    resident_evictions: AtomicU64::new(0),
+    entry_evictions: AtomicU64::new(0),
+    bytes_evicted: AtomicU64::new(0),
+};
+
+impl EvictionCounters {
+    /// Count evictions here and in the process-wide totals.
+    fn record(&self, resident_evictions: u64, entry_evictions: u64, bytes_evicted: u64) {
+        for counters in [self, &TOTAL_EVICTIONS] {
+            counters
+                .resident_evictions
+                .fetch_add(resident_evictions, Ordering::Relaxed);
+            counters
+                .entry_evictions
+                .fetch_add(entry_evictions, Ordering::Relaxed);
+            counters
+                .bytes_evicted
+                .fetch_add(bytes_evicted, Ordering::Relaxed);
+        }
+    }
+
+    fn snapshot(&self) -> EvictionStats {
+        EvictionStats {
+            resident_evictions: self.resident_evictions.load(Ordering::Relaxed),
+            entry_evictions: self.entry_evictions.load(Ordering::Relaxed),
+            bytes_evicted: self.bytes_evicted.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Eviction counters of all user-scoped memories since startup (channels,
+/// gateway and agents share one process in daemon mode).
+pub fn eviction_stats() -> EvictionStats {
+    TOTAL_EVICTIONS.snapshot()
+}
+
+/// An open per-user backend and the LRU tick of its last access.
+struct ResidentUser {
+    memory: Arc<dyn Memory>,
+    last_access: u64,
+}
+
 /// User-scoped memory manager
 pub struct UserScopedMemory {
     /// Base workspace directory
@@ -35,8 +184,14 @@ pub struct UserScopedMemory {
     config_template: MemoryConfig,
     /// Global shared memory
     global_memory: Arc<dyn Memory>,
-    /// Per-user memory instances (user_id -> memory)
-    user_memories: Mutex<HashMap<String, Arc<dyn Memory>>>,
+    /// Per-user memory instances (user_id -> memory), bounded by `max_resident_users`
+    user_memories: Mutex<HashMap<String, ResidentUser>>,
+    /// Per-project memory instances (project id -> memory)
+    project_memories: Mutex<HashMap<String, Arc<dyn Memory>>>,
+    /// Monotonic access counter driving LRU order
+    access_tick: AtomicU64,
+    /// Eviction metrics
+    evictions: EvictionCounters,
     /// API key for embedding (if needed)
     api_key: Option<String>,
 }
@@ -59,22 +214,46 @@ impl UserScopedMemory {
         };
         let global_memory = create_memory(&global_config, &global_dir, api_key)?;
 
-        Ok(Self {
+        Ok(Self::with_global(
+            workspace_dir,
+            config,
+            api_key,
+            Arc::from(global_memory),
+        ))
+    }
+
+    /// Use an existing backend as the global store, e.g. the workspace
+    /// memory, so entries written before per-user scoping stay visible.
+    pub fn with_global(
+        workspace_dir: &Path,
+        config: &MemoryConfig,
+        api_key: Option<&str>,
+        global_memory: Arc<dyn Memory>,
+    ) -> Self {
+        Self {
             workspace_dir: workspace_dir.to_path_buf(),
             config_template: config.clone(),
-            global_memory: Arc::from(global_memory),
+            global_memory,
             user_memories: Mutex::new(HashMap::new()),
+            project_memories: Mutex::new(HashMap::new()),
+            access_tick: AtomicU64::new(0),
+            evictions: EvictionCounters::default(),
             api_key: api_key.map(String::from),
-        })
+        }
+    }
+
+    fn next_tick(&self) -> u64 {
+        self.access_tick.fetch_add(1, Ordering::Relaxed)
     }
 
     /// Get or create user-specific memory (public for access)
     pub fn get_user_memory(&self, user_id: &str) -> anyhow::Result<Arc<dyn Memory>> {
         // Check cache first
         {
-            let memories = self.user_memories.lock().unwrap();
-            if let Some(memory) = memories.get(user_id) {
-                return Ok(memory.clone());
+            let mut memories = self.user_memories.lock().unwrap();
+            if let Some(resident) = memories.get_mut(user_id) {
+                resident.last_access = self.next_tick();
+                return Ok(resident.memory.clone());
             }
         }
 
@@ -89,19 +268,181 @@ impl UserScopedMemory {
             backend: self.config_template.backend.clone(),
             ..self.config_template.clone()
         };
-        
-        let user_memory: Arc<dyn Memory> = Arc::from(
-            create_memory(&user_config, &user_dir, self.api_key.as_deref())?
-        );
 
-        // Store in cache
-        {
+        let user_memory: Arc<dyn Memory> = Arc::from(create_memory(
+            &user_config,
+            &user_dir,
+            self.api_key.as_deref(),
+        )?);
+
+        // Store in cache; a concurrent caller may have opened it first.
+        let memory = {
             let mut memories = self.user_memories.lock().unwrap();
-            memories.insert(user_id.to_string(), user_memory.clone());
-        }
+            let tick = self.next_tick();
+            let memory = memories
+                .entry(user_id.to_string())
+                .or_insert(ResidentUser {
+                    memory: user_memory,
+                    last_access: tick,
+                })
+                .memory
+                .clone();
+            self.evict_resident_users(&mut memories, user_id);
+            memory
+        };
 
         tracing::info!("Created user-scoped memory for user_id={}", user_id);
-        Ok(user_memory)
+        Ok(memory)
+    }
+
+    /// Get or create the memory of a project (see [`project_id`]).
+    pub fn get_project_memory(&self, project: &str) -> anyhow::Result<Arc<dyn Memory>> {
+        let project = project_id(project);
+        if let Some(memory) = self.project_memories.lock().unwrap().get(&project) {
+            return Ok(memory.clone());
+        }
+
+        let project_dir = self.project_memory_path(&project);
+        std::fs::create_dir_all(&project_dir)?;
+        let memory: Arc<dyn Memory> = Arc::from(create_memory(
+            &self.config_template,
+            &project_dir,
+            self.api_key.as_deref(),
+        )?);
+
+        let memory = self
+            .project_memories
+            .lock()
+            .unwrap()
+            .entry(project.clone())
+            .or_insert(memory)
+            .clone();
+        tracing::info!(project = %project, "Opened project-scoped memory");
+        Ok(memory)
+    }
+
+    /// Get project memory directory path
+    pub fn project_memory_path(&self, project: &str) -> PathBuf {
+        self.workspace_dir
+            .join("memory")
+            .join(format!("project_{}", project_id(project)))
+    }
+
+    /// Store into a project's memory
+    pub async fn store_for_project(
+        &self,
+        project: &str,
+        key: &str,
+        content: &str,
+        category: MemoryCategory,
+        session_id: Option<&str>,
+    ) -> anyhow::Result<()> {
+        self.get_project_memory(project)?
+            .store(key, content, category, session_id)
+            .await
+    }
+
+    /// Recall from a project's memory
+    pub async fn recall_for_project(
+        &self,
+        project: &str,
+        query: &str,
+        limit: usize,
+        session_id: Option<&str>,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        self.get_project_memory(project)?
+            .recall(query, limit, session_id)
+            .await
+    }
+
+    /// Close least recently used user backends beyond `max_resident_users`.
+    /// Their data stays on disk and is reopened on next access.
+    fn evict_resident_users(&self, memories: &mut HashMap<String, ResidentUser>, keep: &str) {
+        let max = self.config_template.max_resident_users.max(1);
+        while memories.len() > max {
+            let Some(lru) = memories
+                .iter()
+                .filter(|(user_id, _)| user_id.as_str() != keep)
+                .min_by_key(|(_, resident)| resident.last_access)
+                .map(|(user_id, _)| user_id.clone())
+            else {
+                break;
+            };
+            memories.remove(&lru);
+            self.evictions.record(1, 0, 0);
+            tracing::debug!(user_id = %lru, "Evicted user-scoped memory from resident cache");
+        }
+    }
+
+    /// Delete entries from a user's memory until it fits `user_max_entries`
+    /// and `user_max_bytes`, in `user_eviction_policy` order. The entry
+    /// stored under `keep_key` is never evicted.
+    async fn enforce_user_quota(
+        &self,
+        user_id: &str,
+        memory: &dyn Memory,
+        keep_key: &str,
+    ) -> anyhow::Result<()> {
+        let max_entries = self.config_template.user_max_entries;
+        let max_bytes = self.config_template.user_max_bytes;
+        if max_entries == 0 && max_bytes == 0 {
+            return Ok(());
+        }
+
+        // Backends may cap `list` (sqlite returns the newest 1000), so take the
+        // count from `count` and pick eviction candidates from what is listed.
+        let entries = memory.list(None, None).await?;
+        let mut remaining = memory.count().await?.max(entries.len());
+        let mut bytes: usize = entries.iter().map(|e| e.content.len()).sum();
+        let over_quota = |remaining: usize, bytes: usize| {
+            (max_entries > 0 && remaining > max_entries) || (max_bytes > 0 && bytes > max_bytes)
+        };
+        if !over_quota(remaining, bytes) {
+            return Ok(());
+        }
+
+        let candidates = eviction_order(
+            &entries,
+            keep_key,
+            self.config_template.user_eviction_policy,
+            &self.config_template.retention,
+        );
+        let mut evicted = 0u64;
+        let mut freed = 0u64;
+        for entry in candidates {
+            if !over_quota(remaining, bytes) {
+                break;
+            }
+            if memory.forget(&entry.key).await? {
+                remaining -= 1;
+                bytes = bytes.saturating_sub(entry.content.len());
+                evicted += 1;
+                freed += entry.content.len() as u64;
+            }
+        }
+
+        if evicted > 0 {
+            self.evictions.record(0, evicted, freed);
+            tracing::info!(
+                user_id,
+                evicted,
+                remaining,
+                "Evicted user memory entries over quota"
+            );
+        }
+        if over_quota(remaining, bytes) {
+            tracing::warn!(
+                user_id,
+                backend = memory.name(),
+                "User memory still over quota after eviction"
+            );
+        }
+        Ok(())
+    }
+
+    /// Snapshot of this instance's eviction counters since creation.
+    pub fn eviction_stats(&self) -> EvictionStats {
+        self.evictions.snapshot()
     }
 
     /// Store with explicit user_id
@@ -114,7 +455,25 @@ impl UserScopedMemory {
         session_id: Option<&str>,
     ) -> anyhow::Result<()> {
         let memory = self.get_user_memory(user_id)?;
-        memory.store(key, content, category, session_id).await
+        memory.store(key, content, category, session_id).await?;
+        self.enforce_user_quota(user_id, memory.as_ref(), key).await
+    }
+
+    /// Store with explicit user_id and a per-entry TTL
+    pub async fn store_for_user_with_ttl(
+        &self,
+        user_id: &str,
+        key: &str,
+        content: &str,
+        category: MemoryCategory,
+        session_id: Option<&str>,
+        ttl: std::time::Duration,
+    ) -> anyhow::Result<()> {
+        let memory = self.get_user_memory(user_id)?;
+        memory
+            .store_with_ttl(key, content, category, session_id, ttl)
+            .await?;
+        self.enforce_user_quota(user_id, memory.as_ref(), key).await
     }
 
     /// Recall with explicit user_id
@@ -180,11 +539,7 @@ impl UserScopedMemory {
     }
 
     /// Forget from user memory
-    pub async fn forget_for_user(
-        &self,
-        user_id: &str,
-        key: &str,
-    ) -> anyhow::Result<bool> {
+    pub async fn forget_for_user(&self, user_id: &str, key: &str) -> anyhow::Result<bool> {
         let memory = self.get_user_memory(user_id)?;
         memory.forget(key).await
     }
@@ -237,6 +592,96 @@ impl UserScopedMemory {
         let memories = self.user_memories.lock().unwrap();
         memories.contains_key(user_id) || self.user_memory_path(user_id).exists()
     }
+
+    /// Export everything stored for a user (see [`super::export`]).
+    pub async fn export_user(&self, user_id: &str) -> anyhow::Result<MemoryExport> {
+        validate_user_id(user_id)?;
+        let memory = self.get_user_memory(user_id)?;
+        export_memory(memory.as_ref(), Some(user_id)).await
+    }
+
+    /// Load an export into a user's store. Quotas apply as for normal writes.
+    pub async fn import_user(&self, user_id: &str, export: &MemoryExport) -> anyhow::Result<usize> {
+        validate_user_id(user_id)?;
+        export.ensure_supported()?;
+        for entry in &export.entries {
+            self.store_for_user(
+                user_id,
+                &entry.key,
+                &entry.content,
+                entry.category.clone(),
+                entry.session_id.as_deref(),
+            )
+            .await?;
+        }
+        Ok(export.entries.len())
+    }
+
+    /// Irreversibly delete a user's memory: closes the cached backend and
+    /// removes the user's directory. Returns `false` if nothing was stored.
+    pub fn wipe_user(&self, user_id: &str) -> anyhow::Result<bool> {
+        validate_user_id(user_id)?;
+        self.clear_user_cache(user_id);
+        let path = self.user_memory_path(user_id);
+        if !path.exists() {
+            return Ok(false);
+        }
+        std::fs::remove_dir_all(&path)?;
+        tracing::info!(user_id, "Wiped user memory");
+        Ok(true)
+    }
+}
+
+/// Reject user ids that [`memory_user_id`] can't produce, which includes
+/// anything that could escape `memory/` when used as a directory name.
+pub fn validate_user_id(user_id: &str) -> anyhow::Result<()> {
+    let valid = !user_id.is_empty()
+        && user_id.len() <= 256
+        && user_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '%');
+    if !valid {
+        anyhow::bail!("invalid user id: {user_id:?}");
+    }
+    Ok(())
+}
+
+/// Value of an entry for [`MemoryEvictionPolicy::LowestScore`]: a category
+/// weight (conversation 1, daily 2, custom 4, core 8) decayed by age with
+/// the category's `[memory.retention]` half-life.
+fn retention_score(entry: &MemoryEntry, retention: &MemoryRetentionConfig) -> f64 {
+    let weight = match entry.category {
+        MemoryCategory::Conversation => 1.0,
+        MemoryCategory::Daily => 2.0,
+        MemoryCategory::Custom(_) => 4.0,
+        MemoryCategory::Core => 8.0,
+    };
+    let half_life = retention.for_category(&entry.category).half_life_days;
+    weight
+        * super::decay::age_days(&entry.timestamp)
+            .map_or(1.0, |age| super::decay::decay_factor(age, half_life))
+}
+
+/// Order entries for quota eviction, most evictable first.
+fn eviction_order<'a>(
+    entries: &'a [MemoryEntry],
+    keep_key: &str,
+    policy: MemoryEvictionPolicy,
+    retention: &MemoryRetentionConfig,
+) -> Vec<&'a MemoryEntry> {
+    let mut candidates: Vec<&MemoryEntry> = entries.iter().filter(|e| e.key != keep_key).collect();
+    // Backends write RFC 3339 timestamps with the local offset of the time of
+    // writing, so compare them in UTC; unparseable ones go first.
+    let written = |entry: &MemoryEntry| super::decay::parse_utc(&entry.timestamp);
+    match policy {
+        MemoryEvictionPolicy::Oldest => candidates.sort_by_cached_key(|e| written(e)),
+        MemoryEvictionPolicy::LowestScore => candidates.sort_by(|a, b| {
+            retention_score(a, retention)
+                .total_cmp(&retention_score(b, retention))
+                .then_with(|| written(a).cmp(&written(b)))
+        }),
+    }
+    candidates
 }
 
 // Implement Memory trait for UserScopedMemory (delegates to global memory)
@@ -254,7 +699,9 @@ impl Memory for UserScopedMemory {
         category: MemoryCategory,
         session_id: Option<&str>,
     ) -> anyhow::Result<()> {
-        self.global_memory.store(key, content, category, session_id).await
+        self.global_memory
+            .store(key, content, category, session_id)
+            .await
     }
 
     async fn recall(
@@ -296,11 +743,33 @@ impl Memory for UserScopedMemory {
 pub struct UserMemorySession {
     user_id: String,
     scoped: Arc<UserScopedMemory>,
+    project: Option<String>,
 }
 
 impl UserMemorySession {
     pub fn new(user_id: String, scoped: Arc<UserScopedMemory>) -> Self {
-        Self { user_id, scoped }
+        Self {
+            user_id,
+            scoped,
+            project: None,
+        }
+    }
+
+    /// Also read the project's memory, and store [`PROJECT_CATEGORY`]
+    /// entries there instead of in the user's memory.
+    pub fn with_project(mut self, project: Option<String>) -> Self {
+        self.project = project;
+        self
+    }
+
+    /// Project memory when `category` belongs to the active project.
+    fn project_target(&self, category: &MemoryCategory) -> anyhow::Result<Option<Arc<dyn Memory>>> {
+        match &self.project {
+            Some(project) if is_project_category(category) => {
+                self.scoped.get_project_memory(project).map(Some)
+            }
+            _ => Ok(None),
+        }
     }
 }
 
@@ -317,24 +786,69 @@ impl Memory for UserMemorySession {
         category: MemoryCategory,
         session_id: Option<&str>,
     ) -> anyhow::Result<()> {
+        if let Some(project) = self.project_target(&category)? {
+            return project.store(key, content, category, session_id).await;
+        }
         self.scoped
             .store_for_user(&self.user_id, key, content, category, session_id)
             .await
     }
 
+    async fn store_with_ttl(
+        &self,
+        key: &str,
+        content: &str,
+        category: MemoryCategory,
+        session_id: Option<&str>,
+        ttl: std::time::Duration,
+    ) -> anyhow::Result<()> {
+        if let Some(project) = self.project_target(&category)? {
+            return project
+                .store_with_ttl(key, content, category, session_id, ttl)
+                .await;
+        }
+        self.scoped
+            .store_for_user_with_ttl(&self.user_id, key, content, category, session_id, ttl)
+            .await
+    }
+
     async fn recall(
         &self,
         query: &str,
         limit: usize,
         session_id: Option<&str>,
     ) -> anyhow::Result<Vec<MemoryEntry>> {
-        self.scoped
+        let mut results = self
+            .scoped
             .recall_for_user(&self.user_id, query, limit, session_id, MemoryScope::Both)
-            .await
+            .await?;
+        if let Some(project) = &self.project {
+            match self
+                .scoped
+                .recall_for_project(project, query, limit, session_id)
+                .await
+            {
+                Ok(project_results) => results.extend(project_results),
+                Err(e) => tracing::warn!("Failed to recall project memory for {project}: {e}"),
+            }
+            results.sort_by(|a, b| {
+                b.score
+                    .partial_cmp(&a.score)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+            results.truncate(limit);
+        }
+        Ok(results)
     }
 
     async fn get(&self, key: &str) -> anyhow::Result<Option<MemoryEntry>> {
-        self.scoped.get_for_user(&self.user_id, key).await
+        if let Some(entry) = self.scoped.get_for_user(&self.user_id, key).await? {
+            return Ok(Some(entry));
+        }
+        match &self.project {
+            Some(project) => self.scoped.get_project_memory(project)?.get(key).await,
+            None => Ok(None),
+        }
     }
 
     async fn list(
@@ -342,15 +856,35 @@ impl Memory for UserMemorySession {
         category: Option<&MemoryCategory>,
         session_id: Option<&str>,
     ) -> anyhow::Result<Vec<MemoryEntry>> {
-        self.scoped.list_for_user(&self.user_id, category, session_id).await
+        let mut entries = self
+            .scoped
+            .list_for_user(&self.user_id, category, session_id)
+            .await?;
+        if let Some(project) = &self.project {
+            if category.is_none_or(is_project_category) {
+                let project = self.scoped.get_project_memory(project)?;
+                entries.extend(project.list(category, session_id).await?);
+            }
+        }
+        Ok(entries)
     }
 
     async fn forget(&self, key: &str) -> anyhow::Result<bool> {
-        self.scoped.forget_for_user(&self.user_id, key).await
+        if self.scoped.forget_for_user(&self.user_id, key).await? {
+            return Ok(true);
+        }
+        match &self.project {
+            Some(project) => self.scoped.get_project_memory(project)?.forget(key).await,
+            None => Ok(false),
+        }
     }
 
     async fn count(&self) -> anyhow::Result<usize> {
-        self.scoped.count_for_user(&self.user_id).await
+        let mut count = self.scoped.count_for_user(&self.user_id).await?;
+        if let Some(project) = &self.project {
+            count += self.scoped.get_project_memory(project)?.count().await?;
+        }
+        Ok(count)
     }
 
     async fn health_check(&self) -> bool {
@@ -361,11 +895,159 @@ impl Memory for UserMemorySession {
     }
 }
 
+tokio::task_local! {
+    static CURRENT_MEMORY_USER: String;
+    static CURRENT_MEMORY_PROJECT: String;
+}
+
+/// Run `fut` with `user_id` as the user that [`CurrentUserMemory`] routes to.
+pub async fn with_memory_user<F: Future>(user_id: String, fut: F) -> F::Output {
+    CURRENT_MEMORY_USER.scope(user_id, fut).await
+}
+
+/// Run `fut` against shared memory even inside a user's [`with_memory_user`]
+/// scope, e.g. for facts that must outlive one user's conversation.
+pub async fn without_memory_user<F: Future>(fut: F) -> F::Output {
+    CURRENT_MEMORY_USER.scope(String::new(), fut).await
+}
+
+/// User set by the enclosing [`with_memory_user`], if any.
+pub fn current_memory_user() -> Option<String> {
+    CURRENT_MEMORY_USER
+        .try_with(Clone::clone)
+        .ok()
+        .filter(|user| !user.is_empty())
+}
+
+/// Run `fut` with `project` as the project whose memory [`CurrentUserMemory`]
+/// also reads and writes.
+pub async fn with_memory_project<F: Future>(project: String, fut: F) -> F::Output {
+    CURRENT_MEMORY_PROJECT.scope(project, fut).await
+}
+
+/// Project set by the enclosing [`with_memory_project`], if any.
+pub fn current_memory_project() -> Option<String> {
+    CURRENT_MEMORY_PROJECT
+        .try_with(Clone::clone)
+        .ok()
+        .filter(|project| !project.is_empty())
+}
+
+/// Memory that resolves the user per call: inside [`with_memory_user`] it
+/// behaves like a [`UserMemorySession`] for that user (and the project set
+/// via [`with_memory_project`]), otherwise it falls back to the global
+/// store. Lets a tool registry built once serve every sender without
+/// leaking one user's memories to another.
+pub struct CurrentUserMemory {
+    scoped: Arc<UserScopedMemory>,
+}
+
+impl CurrentUserMemory {
+    pub fn new(scoped: Arc<UserScopedMemory>) -> Self {
+        Self { scoped }
+    }
+
+    fn target(&self) -> Arc<dyn Memory> {
+        match current_memory_user() {
+            Some(user_id) => Arc::new(
+                UserMemorySession::new(user_id, self.scoped.clone())
+                    .with_project(current_memory_project()),
+            ),
+            None => self.scoped.global(),
+        }
+    }
+}
+
+#[async_trait]
+impl Memory for CurrentUserMemory {
+    fn name(&self) -> &str {
+        "user_scoped"
+    }
+
+    async fn store(
+        &self,
+        key: &str,
+        content: &str,
+        category: MemoryCategory,
+        session_id: Option<&str>,
+    ) -> anyhow::Result<()> {
+        self.target()
+            .store(key, content, category, session_id)
+            .await
+    }
+
+    async fn store_with_ttl(
+        &self,
+        key: &str,
+        content: &str,
+        category: MemoryCategory,
+        session_id: Option<&str>,
+        ttl: std::time::Duration,
+    ) -> anyhow::Result<()> {
+        self.target()
+            .store_with_ttl(key, content, category, session_id, ttl)
+            .await
+    }
+
+    async fn recall(
+        &self,
+        query: &str,
+        limit: usize,
+        session_id: Option<&str>,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        self.target().recall(query, limit, session_id).await
+    }
+
+    async fn get(&self, key: &str) -> anyhow::Result<Option<MemoryEntry>> {
+        self.target().get(key).await
+    }
+
+    async fn list(
+        &self,
+        category: Option<&MemoryCategory>,
+        session_id: Option<&str>,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        self.target().list(category, session_id).await
+    }
+
+    async fn forget(&self, key: &str) -> anyhow::Result<bool> {
+        self.target().forget(key).await
+    }
+
+    async fn count(&self) -> anyhow::Result<usize> {
+        self.target().count().await
+    }
+
+    async fn health_check(&self) -> bool {
+        self.target().health_check().await
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use tempfile::TempDir;
 
+    #[test]
+    fn memory_user_ids_are_injective_and_valid() {
+        assert_eq!(memory_user_id("telegram", "42"), "telegram_42");
+        assert_ne!(
+            memory_user_id("email", "a.b@x.io"),
+            memory_user_id("email", "a_b@x.io")
+        );
+        assert_ne!(memory_user_id("a_b", "c"), memory_user_id("a", "b_c"));
+        let id = memory_user_id("matrix", "../@alice:matrix.org");
+        assert!(validate_user_id(&id).is_ok());
+        assert_eq!(
+            parse_memory_user_id(&id),
+            Some(("matrix".into(), "../@alice:matrix.org".into()))
+        );
+        assert_eq!(parse_memory_user_id("alice"), None);
+        assert_eq!(parse_memory_user_id("a_b_c"), None);
+        assert!(validate_user_id("..").is_err());
+        assert!(validate_user_id("a/b").is_err());
+    }
+
     fn test_config() -> MemoryConfig {
         MemoryConfig {
             backend: "markdown".into(),
@@ -452,4 +1134,242 @@ mod tests {
 
         assert_eq!(session.name(), "user_scoped");
     }
+
+    fn sqlite_config() -> MemoryConfig {
+        MemoryConfig {
+            backend: "sqlite".into(),
+            hygiene_enabled: false,
+            auto_hydrate: false,
+            ..MemoryConfig::default()
+        }
+    }
+
+    fn entry(key: &str, category: MemoryCategory, timestamp: &str) -> MemoryEntry {
+        MemoryEntry {
+            id: key.into(),
+            key: key.into(),
+            content: String::new(),
+            category,
+            timestamp: timestamp.into(),
+            session_id: None,
+            score: None,
+        }
+    }
+
+    #[test]
+    fn resident_users_are_bounded_by_lru() {
+        let tmp = TempDir::new().unwrap();
+        let config = MemoryConfig {
+            max_resident_users: 2,
+            ..test_config()
+        };
+        let scoped = UserScopedMemory::new(tmp.path(), &config, None).unwrap();
+
+        scoped.get_user_memory("a").unwrap();
+        scoped.get_user_memory("b").unwrap();
+        // Touch "a" so "b" becomes least recently used.
+        scoped.get_user_memory("a").unwrap();
+        scoped.get_user_memory("c").unwrap();
+
+        let mut active = scoped.active_users();
+        active.sort();
+        assert_eq!(active, vec!["a".to_string(), "c".to_string()]);
+        assert_eq!(scoped.eviction_stats().resident_evictions, 1);
+        assert!(eviction_stats().resident_evictions >= 1);
+        // Evicted users keep their data on disk.
+        assert!(scoped.user_exists("b"));
+    }
+
+    #[tokio::test]
+    async fn store_evicts_oldest_entries_over_quota() {
+        let tmp = TempDir::new().unwrap();
+        let config = MemoryConfig {
+            user_max_entries: 2,
+            ..sqlite_config()
+        };
+        let scoped = UserScopedMemory::new(tmp.path(), &config, None).unwrap();
+
+        for key in ["first", "second", "third"] {
+            scoped
+                .store_for_user("u", key, "value", MemoryCategory::Core, None)
+                .await
+                .unwrap();
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+
+        assert_eq!(scoped.count_for_user("u").await.unwrap(), 2);
+        assert!(scoped.get_for_user("u", "first").await.unwrap().is_none());
+        assert!(scoped.get_for_user("u", "third").await.unwrap().is_some());
+        let stats = scoped.eviction_stats();
+        assert_eq!(stats.entry_evictions, 1);
+        assert_eq!(stats.bytes_evicted, 5);
+    }
+
+    #[tokio::test]
+    async fn store_enforces_byte_quota_but_keeps_new_entry() {
+        let tmp = TempDir::new().unwrap();
+        let config = MemoryConfig {
+            user_max_entries: 0,
+            user_max_bytes: 10,
+            ..sqlite_config()
+        };
+        let scoped = UserScopedMemory::new(tmp.path(), &config, None).unwrap();
+
+        scoped
+            .store_for_user("u", "small", "12345", MemoryCategory::Core, None)
+            .await
+            .unwrap();
+        scoped
+            .store_for_user("u", "large", "0123456789ab", MemoryCategory::Core, None)
+            .await
+            .unwrap();
+
+        assert!(scoped.get_for_user("u", "small").await.unwrap().is_none());
+        assert!(scoped.get_for_user("u", "large").await.unwrap().is_some());
+    }
+
+    #[test]
+    fn lowest_score_policy_evicts_conversation_before_core() {
+        let entries = vec![
+            entry("core_old", MemoryCategory::Core, "2026-01-01T00:00:00Z"),
+            entry(
+                "conv_new",
+                MemoryCategory::Conversation,
+                "2026-03-01T00:00:00Z",
+            ),
+            entry("daily", MemoryCategory::Daily, "2026-02-01T00:00:00Z"),
+            entry("kept", MemoryCategory::Conversation, "2025-01-01T00:00:00Z"),
+        ];
+
+        let keys = |entries: &[MemoryEntry], policy| -> Vec<String> {
+            eviction_order(entries, "kept", policy, &MemoryRetentionConfig::default())
+                .into_iter()
+                .map(|e| e.key.clone())
+                .collect()
+        };
+        assert_eq!(
+            keys(&entries, MemoryEvictionPolicy::LowestScore),
+            vec!["conv_new", "daily", "core_old"]
+        );
+        assert_eq!(
+            keys(&entries, MemoryEvictionPolicy::Oldest),
+            vec!["core_old", "daily", "conv_new"]
+        );
+
+        // Offsets change with DST; order by the instant, not the string.
+        let entries = vec![
+            entry("summer", MemoryCategory::Core, "2026-03-29T09:00:00+02:00"),
+            entry("winter", MemoryCategory::Core, "2026-03-29T08:30:00+01:00"),
+            entry("garbled", MemoryCategory::Core, "yesterday"),
+        ];
+        assert_eq!(
+            keys(&entries, MemoryEvictionPolicy::Oldest),
+            vec!["garbled", "summer", "winter"]
+        );
+
+        // Score decays with age: a long-stale custom fact goes before a
+        // conversation entry written just now.
+        let now = super::super::decay::now_timestamp();
+        let entries = vec![
+            entry("conv_fresh", MemoryCategory::Conversation, &now),
+            entry(
+                "custom_stale",
+                MemoryCategory::Custom("notes".into()),
+                "2024-01-01T00:00:00Z",
+            ),
+        ];
+        assert_eq!(
+            keys(&entries, MemoryEvictionPolicy::LowestScore),
+            vec!["custom_stale", "conv_fresh"]
+        );
+    }
+
+    #[tokio::test]
+    async fn current_user_memory_routes_by_task_local_user() {
+        let tmp = TempDir::new().unwrap();
+        let scoped = Arc::new(UserScopedMemory::new(tmp.path(), &test_config(), None).unwrap());
+        let routed = CurrentUserMemory::new(scoped.clone());
+
+        with_memory_user("alice".into(), async {
+            routed
+                .store("pkg", "always use pnpm", MemoryCategory::Core, None)
+                .await
+                .unwrap();
+        })
+        .await;
+
+        let alice = with_memory_user("alice".into(), routed.get("pkg")).await;
+        let bob = with_memory_user("bob".into(), routed.get("pkg")).await;
+        assert!(alice.unwrap().is_some());
+        assert!(bob.unwrap().is_none());
+        assert!(routed.get("pkg").await.unwrap().is_none());
+        assert!(current_memory_user().is_none());
+    }
+
+    #[tokio::test]
+    async fn project_memory_is_shared_across_users_of_the_project() {
+        let tmp = TempDir::new().unwrap();
+        let scoped = Arc::new(UserScopedMemory::new(tmp.path(), &sqlite_config(), None).unwrap());
+        let routed = CurrentUserMemory::new(scoped.clone());
+        let project_category = MemoryCategory::Custom(PROJECT_CATEGORY.into());
+
+        with_memory_user(
+            "alice".into(),
+            with_memory_project(
+                project_id("Todo App"),
+                routed.store("orm", "chose drizzle over prisma", project_category, None),
+            ),
+        )
+        .await
+        .unwrap();
+        assert!(tmp.path().join("memory").join("project_todo-app").exists());
+        assert!(scoped.get_for_user("alice", "orm").await.unwrap().is_none());
+
+        let bob = with_memory_user(
+            "bob".into(),
+            with_memory_project("todo-app".into(), routed.recall("drizzle", 5, None)),
+        )
+        .await
+        .unwrap();
+        assert!(bob.iter().any(|entry| entry.key == "orm"));
+
+        let other_project = with_memory_user(
+            "bob".into(),
+            with_memory_project("invoices".into(), routed.get("orm")),
+        )
+        .await
+        .unwrap();
+        assert!(other_project.is_none());
+        assert!(current_memory_project().is_none());
+    }
+
+    #[tokio::test]
+    async fn export_import_and_wipe_user() {
+        let tmp = TempDir::new().unwrap();
+        let scoped = UserScopedMemory::new(tmp.path(), &test_config(), None).unwrap();
+        scoped
+            .store_for_user(
+                "alice",
+                "pkg",
+                "always use pnpm",
+                MemoryCategory::Core,
+                None,
+            )
+            .await
+            .unwrap();
+
+        let export = scoped.export_user("alice").await.unwrap();
+        assert_eq!(export.entries.len(), 1);
+        assert_eq!(scoped.import_user("bob", &export).await.unwrap(), 1);
+        assert!(scoped.get_for_user("bob", "pkg").await.unwrap().is_some());
+
+        assert!(scoped.wipe_user("alice").unwrap());
+        assert!(!scoped.user_exists("alice"));
+        assert!(!scoped.wipe_user("alice").unwrap());
+        assert!(scoped.get_for_user("alice", "pkg").await.unwrap().is_none());
+
+        assert!(scoped.wipe_user("../global").is_err());
+        assert!(validate_user_id("..").is_err());
+        assert!(validate_user_id("telegram_12345").is_ok());
+    }
 }
@@ -85,6 +85,36 @@ pub fn parse_image_markers(content: &str) -> (String, Vec<String>) {
     (cleaned.trim().to_string(), refs)
 }
 
+/// Replace `[IMAGE:...]` markers with plain text naming the source, so the
+/// reference stays readable without being sent as image input.
+pub fn redact_image_markers(content: &str) -> String {
+    let mut redacted = String::with_capacity(content.len());
+    let mut cursor = 0usize;
+
+    while let Some(rel_start) = content[cursor..].find(IMAGE_MARKER_PREFIX) {
+        let start = cursor + rel_start;
+        let marker_start = start + IMAGE_MARKER_PREFIX.len();
+        let Some(rel_end) = content[marker_start..].find(']') else {
+            break;
+        };
+        let end = marker_start + rel_end;
+        redacted.push_str(&content[cursor..start]);
+
+        let candidate = content[marker_start..end].trim();
+        if candidate.starts_with("data:") {
+            redacted.push_str("[image omitted]");
+        } else {
+            redacted.push_str("[image: ");
+            redacted.push_str(candidate);
+            redacted.push(']');
+        }
+        cursor = end + 1;
+    }
+
+    redacted.push_str(&content[cursor..]);
+    redacted
+}
+
 pub fn count_image_markers(messages: &[ChatMessage]) -> usize {
     messages
         .iter()
@@ -136,6 +166,9 @@ pub async fn prepare_messages_for_provider(
     }
 
     let remote_client = build_runtime_proxy_client_with_timeouts("provider.ollama", 30, 10);
+    // Split the token budget evenly so one huge image can't crowd out the rest.
+    let max_pixels = (config.max_image_tokens > 0)
+        .then(|| (config.max_image_tokens / found_images) as u64 * 750);
 
     let mut normalized_messages = Vec::with_capacity(messages.len());
     for message in messages {
@@ -152,8 +185,14 @@ pub async fn prepare_messages_for_provider(
 
         let mut normalized_refs = Vec::with_capacity(refs.len());
         for reference in refs {
-            let data_uri =
-                normalize_image_reference(&reference, config, max_bytes, &remote_client).await?;
+            let data_uri = normalize_image_reference(
+                &reference,
+                config,
+                max_bytes,
+                max_pixels,
+                &remote_client,
+            )
+            .await?;
             normalized_refs.push(data_uri);
         }
 
@@ -195,13 +234,12 @@ async fn normalize_image_reference(
     source: &str,
     config: &MultimodalConfig,
     max_bytes: usize,
+    max_pixels: Option<u64>,
     remote_client: &Client,
 ) -> anyhow::Result<String> {
-    if source.starts_with("data:") {
-        return normalize_data_uri(source, max_bytes);
-    }
-
-    if source.starts_with("http://") || source.starts_with("https://") {
+    let (mime, bytes) = if source.starts_with("data:") {
+        normalize_data_uri(source, max_bytes)?
+    } else if source.starts_with("http://") || source.starts_with("https://") {
         if !config.allow_remote_fetch {
             return Err(MultimodalError::RemoteFetchDisabled {
                 input: source.to_string(),
@@ -209,13 +247,104 @@ async fn normalize_image_reference(
             .into());
         }
 
-        return normalize_remote_image(source, max_bytes, remote_client).await;
+        normalize_remote_image(source, max_bytes, remote_client).await?
+    } else {
+        normalize_local_image(source, max_bytes).await?
+    };
+
+    let max_dimension = config.max_dimension;
+    let (mime, bytes) = tokio::task::spawn_blocking(move || {
+        downscale_image(mime, bytes, max_dimension, max_pixels)
+    })
+    .await?;
+
+    Ok(format!("data:{mime};base64,{}", STANDARD.encode(bytes)))
+}
+
+/// Estimated prompt tokens for an image of `width` × `height` pixels.
+///
+/// Vision APIs bill roughly one token per 750 pixels once the image fits
+/// their size limits; this is close enough for budgeting across providers.
+pub fn estimate_image_tokens(width: u32, height: u32) -> u64 {
+    (u64::from(width) * u64::from(height)).div_ceil(750)
+}
+
+/// Downscale PNG/JPEG images so the longest edge fits `max_dimension` and the
+/// pixel count fits `max_pixels`. Other formats, undecodable payloads and
+/// images already within limits are returned unchanged.
+fn downscale_image(
+    mime: String,
+    bytes: Vec<u8>,
+    max_dimension: u32,
+    max_pixels: Option<u64>,
+) -> (String, Vec<u8>) {
+    let format = match mime.as_str() {
+        "image/png" => image::ImageFormat::Png,
+        "image/jpeg" => image::ImageFormat::Jpeg,
+        _ => return (mime, bytes),
+    };
+    let Ok(decoded) = image::load_from_memory_with_format(&bytes, format) else {
+        return (mime, bytes);
+    };
+
+    let (width, height) = (decoded.width(), decoded.height());
+    let Some((target_width, target_height)) =
+        fit_dimensions(width, height, max_dimension, max_pixels)
+    else {
+        return (mime, bytes);
+    };
+
+    let resized = decoded.resize(
+        target_width,
+        target_height,
+        image::imageops::FilterType::Triangle,
+    );
+    let mut out = std::io::Cursor::new(Vec::new());
+    if resized.write_to(&mut out, format).is_err() {
+        return (mime, bytes);
+    }
+    tracing::debug!(
+        from = %format!("{width}x{height}"),
+        to = %format!("{}x{}", resized.width(), resized.height()),
+        "Downscaled image for vision provider"
+    );
+    (mime, out.into_inner())
+}
+
+/// Target size for an image, or `None` when it already fits.
+fn fit_dimensions(
+    width: u32,
+    height: u32,
+    max_dimension: u32,
+    max_pixels: Option<u64>,
+) -> Option<(u32, u32)> {
+    if width == 0 || height == 0 {
+        return None;
+    }
+
+    let mut scale = 1.0_f64;
+    let longest = width.max(height);
+    if max_dimension > 0 && longest > max_dimension {
+        scale = f64::from(max_dimension) / f64::from(longest);
+    }
+    if let Some(max_pixels) = max_pixels {
+        let pixels = u64::from(width) * u64::from(height);
+        if pixels > max_pixels {
+            #[allow(clippy::cast_precision_loss)]
+            let pixel_scale = (max_pixels as f64 / pixels as f64).sqrt();
+            scale = scale.min(pixel_scale);
+        }
+    }
+    if scale >= 1.0 {
+        return None;
     }
 
-    normalize_local_image(source, max_bytes).await
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let scaled = |value: u32| ((f64::from(value) * scale).floor() as u32).max(1);
+    Some((scaled(width), scaled(height)))
 }
 
-fn normalize_data_uri(source: &str, max_bytes: usize) -> anyhow::Result<String> {
+fn normalize_data_uri(source: &str, max_bytes: usize) -> anyhow::Result<(String, Vec<u8>)> {
     let Some(comma_idx) = source.find(',') else {
         return Err(MultimodalError::InvalidMarker {
             input: source.to_string(),
@@ -254,14 +383,14 @@ fn normalize_data_uri(source: &str, max_bytes: usize) -> anyhow::Result<String>
 
     validate_size(source, decoded.len(), max_bytes)?;
 
-    Ok(format!("data:{mime};base64,{}", STANDARD.encode(decoded)))
+    Ok((mime, decoded))
 }
 
 async fn normalize_remote_image(
     source: &str,
     max_bytes: usize,
     remote_client: &Client,
-) -> anyhow::Result<String> {
+) -> anyhow::Result<(String, Vec<u8>)> {
     let response = remote_client.get(source).send().await.map_err(|error| {
         MultimodalError::RemoteFetchFailed {
             input: source.to_string(),
@@ -308,10 +437,13 @@ async fn normalize_remote_image(
 
     validate_mime(source, &mime)?;
 
-    Ok(format!("data:{mime};base64,{}", STANDARD.encode(bytes)))
+    Ok((mime, bytes.to_vec()))
 }
 
-async fn normalize_local_image(source: &str, max_bytes: usize) -> anyhow::Result<String> {
+async fn normalize_local_image(
+    source: &str,
+    max_bytes: usize,
+) -> anyhow::Result<(String, Vec<u8>)> {
     let path = Path::new(source);
     if !path.exists() || !path.is_file() {
         return Err(MultimodalError::ImageSourceNotFound {
@@ -351,7 +483,7 @@ async fn normalize_local_image(source: &str, max_bytes: usize) -> anyhow::Result
 
     validate_mime(source, &mime)?;
 
-    Ok(format!("data:{mime};base64,{}", STANDARD.encode(bytes)))
+    Ok((mime, bytes))
 }
 
 fn validate_size(source: &str, size_bytes: usize, max_bytes: usize) -> anyhow::Result<()> {
@@ -467,6 +599,14 @@ mod tests {
         assert!(refs.is_empty());
     }
 
+    #[test]
+    fn redact_image_markers_keeps_path_and_drops_payloads() {
+        let redacted =
+            redact_image_markers("Saved [IMAGE:/tmp/a.png] and [IMAGE:data:image/png;base64,AAAA]");
+        assert_eq!(redacted, "Saved [image: /tmp/a.png] and [image omitted]");
+        assert!(parse_image_markers(&redacted).1.is_empty());
+    }
+
     #[tokio::test]
     async fn prepare_messages_normalizes_local_image_to_data_uri() {
         let temp = tempfile::tempdir().unwrap();
@@ -507,6 +647,7 @@ mod tests {
             max_images: 1,
             max_image_size_mb: 5,
             allow_remote_fetch: false,
+            ..MultimodalConfig::default()
         };
 
         let error = prepare_messages_for_provider(&messages, &config)
@@ -549,6 +690,7 @@ mod tests {
             max_images: 4,
             max_image_size_mb: 1,
             allow_remote_fetch: false,
+            ..MultimodalConfig::default()
         };
 
         let error = prepare_messages_for_provider(&messages, &config)
@@ -560,6 +702,39 @@ mod tests {
             .contains("multimodal image size limit exceeded"));
     }
 
+    #[tokio::test]
+    async fn prepare_messages_downscales_large_images() {
+        let temp = tempfile::tempdir().unwrap();
+        let image_path = temp.path().join("mockup.png");
+        image::RgbImage::new(3200, 1600).save(&image_path).unwrap();
+
+        let messages = vec![ChatMessage::user(format!(
+            "Clone this [IMAGE:{}]",
+            image_path.display()
+        ))];
+        let prepared = prepare_messages_for_provider(&messages, &MultimodalConfig::default())
+            .await
+            .unwrap();
+
+        let (_, refs) = parse_image_markers(&prepared.messages[0].content);
+        let payload = extract_ollama_image_payload(&refs[0]).unwrap();
+        let decoded = image::load_from_memory(&STANDARD.decode(payload).unwrap()).unwrap();
+        assert_eq!(decoded.width(), 1568);
+        assert_eq!(decoded.height(), 784);
+        assert!(estimate_image_tokens(decoded.width(), decoded.height()) <= 6400);
+    }
+
+    #[test]
+    fn fit_dimensions_applies_dimension_and_token_budget() {
+        assert_eq!(fit_dimensions(800, 600, 1568, None), None);
+        assert_eq!(fit_dimensions(3136, 1000, 1568, None), Some((1568, 500)));
+        // 1000x1000 at a 750-token budget (562_500 px) scales by 0.75.
+        assert_eq!(
+            fit_dimensions(1000, 1000, 0, Some(562_500)),
+            Some((750, 750))
+        );
+    }
+
     #[test]
     fn extract_ollama_image_payload_supports_data_uris() {
         let payload = extract_ollama_image_payload("data:image/png;base64,abcd==")
@@ -166,6 +166,7 @@ mod tests {
             error_message: None,
             input_tokens: Some(1000),
             output_tokens: Some(500),
+            cached_input_tokens: None,
         });
 
         let summary = tracker.get_summary().unwrap();
@@ -187,6 +188,7 @@ mod tests {
             error_message: Some("API error".into()),
             input_tokens: Some(1000),
             output_tokens: Some(500),
+            cached_input_tokens: None,
         });
 
         let summary = tracker.get_summary().unwrap();
@@ -206,6 +208,7 @@ mod tests {
             error_message: None,
             input_tokens: None,
             output_tokens: None,
+            cached_input_tokens: None,
         });
 
         let summary = tracker.get_summary().unwrap();
@@ -225,6 +228,7 @@ mod tests {
             error_message: None,
             input_tokens: Some(1_000_000), // 1M tokens
             output_tokens: Some(1_000_000),
+            cached_input_tokens: None,
         });
 
         let summary = tracker.get_summary().unwrap();
@@ -256,6 +260,7 @@ mod tests {
             error_message: None,
             input_tokens: Some(1_000_000),
             output_tokens: Some(0),
+            cached_input_tokens: None,
         });
 
         let summary = tracker.get_summary().unwrap();
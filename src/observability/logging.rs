@@ -0,0 +1,505 @@
+//! Process-wide log output: level, `text`/`json` format and rotated files.
+//!
+//! [`init`] installs a stdout text logger before the config is read; [`apply`]
+//! then switches to the `[logging]` settings. Work for one conversation runs
+//! inside a [`session_span`] and each tool call inside a `tool` span, so in
+//! JSON output every event carries `session_id`, `user_id`, `tool` and
+//! `sandbox_id` of the spans it happened in. Every output is passed through
+//! [`crate::security::redaction::redact`] so credentials never reach a log.
+
+use crate::config::LoggingConfig;
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use parking_lot::Mutex;
+use serde_json::{Map, Value};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::{Context as LayerContext, Layered, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, reload, EnvFilter, Layer, Registry};
+
+/// File name prefix of log files written to `[logging] dir`.
+pub const LOG_FILE_PREFIX: &str = "zerobuild";
+
+type Base = Layered<reload::Layer<EnvFilter, Registry>, Registry>;
+type OutputLayer = Box<dyn Layer<Base> + Send + Sync>;
+
+struct Handles {
+    filter: reload::Handle<EnvFilter, Registry>,
+    output: reload::Handle<OutputLayer, Base>,
+}
+
+static HANDLES: OnceLock<Handles> = OnceLock::new();
+
+/// How often a new log file is started.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    Hourly,
+    Daily,
+    Never,
+}
+
+impl Rotation {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "hourly" => Some(Self::Hourly),
+            "daily" => Some(Self::Daily),
+            "never" => Some(Self::Never),
+            _ => None,
+        }
+    }
+
+    fn file_name(self, now: DateTime<Utc>) -> String {
+        match self {
+            Self::Hourly => format!("{LOG_FILE_PREFIX}.{}.log", now.format("%Y-%m-%d-%H")),
+            Self::Daily => format!("{LOG_FILE_PREFIX}.{}.log", now.format("%Y-%m-%d")),
+            Self::Never => format!("{LOG_FILE_PREFIX}.log"),
+        }
+    }
+}
+
+/// Check `[logging]` values.
+pub fn validate(config: &LoggingConfig) -> Result<()> {
+    if !matches!(config.format.as_str(), "text" | "json") {
+        bail!(
+            "logging.format must be 'text' or 'json', got '{}'",
+            config.format
+        );
+    }
+    if Rotation::parse(&config.rotation).is_none() {
+        bail!(
+            "logging.rotation must be 'hourly', 'daily' or 'never', got '{}'",
+            config.rotation
+        );
+    }
+    EnvFilter::try_new(&config.level)
+        .with_context(|| format!("logging.level '{}' is not a valid filter", config.level))?;
+    Ok(())
+}
+
+fn env_filter(default_level: &str) -> EnvFilter {
+    EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default_level))
+}
+
+/// Install the default logger: text on stdout, `RUST_LOG` or `info`.
+pub fn init() {
+    let (filter, filter_handle) = reload::Layer::new(env_filter("info"));
+    let console: OutputLayer = Box::new(fmt::layer().with_writer(Redacted(io::stdout)));
+    let (output, output_handle) = reload::Layer::new(console);
+    if tracing_subscriber::registry()
+        .with(filter)
+        .with(output)
+        .try_init()
+        .is_ok()
+    {
+        let _ = HANDLES.set(Handles {
+            filter: filter_handle,
+            output: output_handle,
+        });
+    }
+}
+
+fn output_layer(config: &LoggingConfig, workspace_dir: &Path) -> Result<OutputLayer> {
+    let json = config.format == "json";
+    let console: OutputLayer = if json {
+        Box::new(JsonLayer::new(Redacted(io::stdout)))
+    } else {
+        Box::new(fmt::layer().with_writer(Redacted(io::stdout)))
+    };
+    if config.dir.trim().is_empty() {
+        return Ok(console);
+    }
+
+    let dir = PathBuf::from(shellexpand::tilde(config.dir.trim()).as_ref());
+    let dir = if dir.is_absolute() {
+        dir
+    } else {
+        workspace_dir.join(dir)
+    };
+    let rotation = Rotation::parse(&config.rotation).unwrap_or(Rotation::Daily);
+    let file = Redacted(RollingFile::new(dir, rotation, config.max_files)?);
+    let file: OutputLayer = if json {
+        Box::new(JsonLayer::new(file))
+    } else {
+        Box::new(fmt::layer().with_ansi(false).with_writer(file))
+    };
+    Ok(Box::new(vec![console, file]))
+}
+
+/// Switch the logger installed by [`init`] to `config`.
+pub fn apply(config: &LoggingConfig, workspace_dir: &Path) -> Result<()> {
+    validate(config)?;
+    let Some(handles) = HANDLES.get() else {
+        return Ok(());
+    };
+    let output = output_layer(config, workspace_dir)?;
+    handles
+        .filter
+        .reload(env_filter(&config.level))
+        .context("Failed to update log filter")?;
+    handles
+        .output
+        .reload(output)
+        .context("Failed to update log output")?;
+    Ok(())
+}
+
+/// Span for one conversation; events inside it carry these ids.
+pub fn session_span(session_id: &str, user_id: &str, channel: &str) -> tracing::Span {
+    tracing::info_span!(
+        "session",
+        session_id = %session_id,
+        user_id = %user_id,
+        channel = %channel
+    )
+}
+
+/// Fresh id for a session that has no natural key (CLI run, web socket).
+pub fn new_session_id() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+// ── JSON output ──────────────────────────────────────────────────
+
+/// Fields recorded on a span, kept in its extensions.
+struct SpanFields(Map<String, Value>);
+
+struct JsonVisitor<'a>(&'a mut Map<String, Value>);
+
+impl Visit for JsonVisitor<'_> {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), Value::from(format!("{value:?}")));
+    }
+}
+
+/// Writes each event as one JSON object per line. Fields of the enclosing
+/// spans are merged in (innermost wins), then the event's own fields.
+pub struct JsonLayer<W> {
+    make_writer: W,
+}
+
+impl<W> JsonLayer<W> {
+    pub fn new(make_writer: W) -> Self {
+        Self { make_writer }
+    }
+}
+
+impl<S, W> Layer<S> for JsonLayer<W>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'a> MakeWriter<'a> + 'static,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: LayerContext<'_, S>) {
+        let Some(span) = ctx.span(id) else { return };
+        let mut extensions = span.extensions_mut();
+        // Console and file output may both be JSON layers; record once.
+        if extensions.get_mut::<SpanFields>().is_none() {
+            let mut fields = Map::new();
+            attrs.record(&mut JsonVisitor(&mut fields));
+            extensions.insert(SpanFields(fields));
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: LayerContext<'_, S>) {
+        let Some(span) = ctx.span(id) else { return };
+        let mut extensions = span.extensions_mut();
+        if let Some(fields) = extensions.get_mut::<SpanFields>() {
+            values.record(&mut JsonVisitor(&mut fields.0));
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: LayerContext<'_, S>) {
+        let meta = event.metadata();
+        let mut line = Map::new();
+        line.insert(
+            "timestamp".into(),
+            Value::from(Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)),
+        );
+        line.insert("level".into(), Value::from(meta.level().as_str()));
+        line.insert("target".into(), Value::from(meta.target()));
+
+        if let Some(scope) = ctx.event_scope(event) {
+            let mut names = Vec::new();
+            for span in scope.from_root() {
+                names.push(span.name());
+                if let Some(fields) = span.extensions().get::<SpanFields>() {
+                    for (key, value) in &fields.0 {
+                        line.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+            line.insert("spans".into(), Value::from(names.join(":")));
+        }
+        event.record(&mut JsonVisitor(&mut line));
+
+        let Ok(mut buf) = serde_json::to_vec(&Value::Object(line)) else {
+            return;
+        };
+        buf.push(b'\n');
+        let _ = self.make_writer.make_writer_for(meta).write_all(&buf);
+    }
+}
+
+// ── Rotated log files ────────────────────────────────────────────
+
+/// Log file writer that starts a new file each period and keeps the newest
+/// `max_files`.
+pub struct RollingFile {
+    dir: PathBuf,
+    rotation: Rotation,
+    max_files: usize,
+    current: Mutex<Option<(String, File)>>,
+}
+
+impl RollingFile {
+    pub fn new(dir: PathBuf, rotation: Rotation, max_files: usize) -> Result<Self> {
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create log dir {}", dir.display()))?;
+        Ok(Self {
+            dir,
+            rotation,
+            max_files,
+            current: Mutex::new(None),
+        })
+    }
+
+    fn write_at(&self, now: DateTime<Utc>, buf: &[u8]) -> io::Result<()> {
+        let name = self.rotation.file_name(now);
+        let mut current = self.current.lock();
+        let file = match &mut *current {
+            Some((open, file)) if *open == name => file,
+            slot => {
+                let file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(self.dir.join(&name))?;
+                self.prune();
+                &mut slot.insert((name, file)).1
+            }
+        };
+        file.write_all(buf)
+    }
+
+    /// Remove the oldest log files beyond `max_files`.
+    fn prune(&self) {
+        if self.max_files == 0 {
+            return;
+        }
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return;
+        };
+        let mut files: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| {
+                        name.starts_with(&format!("{LOG_FILE_PREFIX}.")) && name.ends_with(".log")
+                    })
+            })
+            .collect();
+        // Date-stamped names sort oldest first.
+        files.sort();
+        let excess = files.len().saturating_sub(self.max_files);
+        for path in files.into_iter().take(excess) {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+pub struct RollingFileWriter<'a>(&'a RollingFile);
+
+impl Write for RollingFileWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write_at(Utc::now(), buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for RollingFile {
+    type Writer = RollingFileWriter<'a>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RollingFileWriter(self)
+    }
+}
+
+// ── Secret redaction ─────────────────────────────────────────────
+
+/// Wraps a writer factory so every formatted event is redacted before it is
+/// written. Layers write each event with a single `write_all`, so secrets are
+/// never split across calls.
+pub struct Redacted<M>(pub M);
+
+pub struct RedactedWriter<W>(W);
+
+impl<W: Write> Write for RedactedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let text = String::from_utf8_lossy(buf);
+        self.0
+            .write_all(crate::security::redaction::redact(&text).as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl<'a, M: MakeWriter<'a>> MakeWriter<'a> for Redacted<M> {
+    type Writer = RedactedWriter<M::Writer>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RedactedWriter(self.0.make_writer())
+    }
+
+    fn make_writer_for(&'a self, meta: &tracing::Metadata<'_>) -> Self::Writer {
+        RedactedWriter(self.0.make_writer_for(meta))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for Captured {
+        type Writer = Captured;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    #[test]
+    fn json_events_carry_session_and_tool_fields() {
+        let captured = Captured::default();
+        let subscriber = tracing_subscriber::registry().with(JsonLayer::new(captured.clone()));
+        tracing::subscriber::with_default(subscriber, || {
+            let session = session_span("telegram_alice", "alice", "telegram");
+            let _session = session.enter();
+            let tool = tracing::info_span!(
+                "tool",
+                tool = "sandbox_run_command",
+                sandbox_id = tracing::field::Empty
+            );
+            let _tool = tool.enter();
+            tool.record("sandbox_id", "sbx-1");
+            tracing::info!(duration_ms = 42_u64, "tool.call");
+        });
+
+        let output = String::from_utf8(captured.0.lock().clone()).unwrap();
+        let line: Value = serde_json::from_str(output.trim()).unwrap();
+        assert_eq!(line["session_id"], "telegram_alice");
+        assert_eq!(line["user_id"], "alice");
+        assert_eq!(line["tool"], "sandbox_run_command");
+        assert_eq!(line["sandbox_id"], "sbx-1");
+        assert_eq!(line["duration_ms"], 42);
+        assert_eq!(line["message"], "tool.call");
+        assert_eq!(line["spans"], "session:tool");
+        assert_eq!(line["level"], "INFO");
+    }
+
+    #[test]
+    fn redacted_output_hides_credentials() {
+        let captured = Captured::default();
+        let subscriber =
+            tracing_subscriber::registry().with(JsonLayer::new(Redacted(captured.clone())));
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::warn!(
+                error = "401: Authorization: Bearer abcdef0123456789",
+                "provider call failed"
+            );
+        });
+
+        let output = String::from_utf8(captured.0.lock().clone()).unwrap();
+        let line: Value = serde_json::from_str(output.trim()).unwrap();
+        assert_eq!(line["error"], "401: Authorization: Bearer [REDACTED]");
+    }
+
+    #[test]
+    fn rolling_file_rotates_and_keeps_newest() {
+        let tmp = TempDir::new().unwrap();
+        let file = RollingFile::new(tmp.path().to_path_buf(), Rotation::Daily, 2).unwrap();
+        for day in 1..=3 {
+            let now = Utc.with_ymd_and_hms(2026, 3, day, 12, 0, 0).unwrap();
+            file.write_at(now, format!("day {day}\n").as_bytes())
+                .unwrap();
+        }
+
+        let mut names: Vec<String> = std::fs::read_dir(tmp.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec!["zerobuild.2026-03-02.log", "zerobuild.2026-03-03.log"]
+        );
+        let newest = std::fs::read_to_string(tmp.path().join("zerobuild.2026-03-03.log")).unwrap();
+        assert_eq!(newest, "day 3\n");
+    }
+
+    #[test]
+    fn validate_rejects_unknown_values() {
+        assert!(validate(&LoggingConfig::default()).is_ok());
+        let bad_format = LoggingConfig {
+            format: "yaml".into(),
+            ..LoggingConfig::default()
+        };
+        assert!(validate(&bad_format).is_err());
+        let bad_rotation = LoggingConfig {
+            rotation: "weekly".into(),
+            ..LoggingConfig::default()
+        };
+        assert!(validate(&bad_rotation).is_err());
+    }
+}
@@ -1,10 +1,14 @@
 pub mod log;
+pub mod logging;
 pub mod multi;
 pub mod noop;
 #[cfg(feature = "observability-otel")]
 pub mod otel;
 pub mod prometheus;
+pub mod recording;
+pub mod report;
 pub mod runtime_trace;
+pub mod timeline;
 pub mod traits;
 pub mod verbose;
 
@@ -197,6 +197,7 @@ impl Observer for OtelObserver {
                 error_message: _,
                 input_tokens: _,
                 output_tokens: _,
+                cached_input_tokens: _,
             } => {
                 let secs = duration.as_secs_f64();
                 let attrs = [
@@ -417,6 +418,7 @@ mod tests {
             error_message: None,
             input_tokens: Some(100),
             output_tokens: Some(50),
+            cached_input_tokens: None,
         });
         obs.record_event(&ObserverEvent::AgentEnd {
             provider: "openrouter".into(),
@@ -497,6 +499,7 @@ mod tests {
             error_message: Some("404 Not Found".into()),
             input_tokens: None,
             output_tokens: None,
+            cached_input_tokens: None,
         });
     }
 
@@ -12,6 +12,7 @@ pub struct PrometheusObserver {
     llm_requests: IntCounterVec,
     tokens_input_total: IntCounterVec,
     tokens_output_total: IntCounterVec,
+    tokens_cached_input_total: IntCounterVec,
     tool_calls: IntCounterVec,
     channel_messages: IntCounterVec,
     heartbeat_ticks: prometheus::IntCounter,
@@ -65,6 +66,15 @@ impl PrometheusObserver {
         )
         .expect("valid metric");
 
+        let tokens_cached_input_total = IntCounterVec::new(
+            prometheus::Opts::new(
+                "zerobuild_tokens_cached_input_total",
+                "Total input tokens served from provider prompt caches",
+            ),
+            &["provider", "model"],
+        )
+        .expect("valid metric");
+
         let tool_calls = IntCounterVec::new(
             prometheus::Opts::new("zerobuild_tool_calls_total", "Total tool calls"),
             &["tool", "success"],
@@ -141,6 +151,9 @@ impl PrometheusObserver {
         registry
             .register(Box::new(tokens_output_total.clone()))
             .ok();
+        registry
+            .register(Box::new(tokens_cached_input_total.clone()))
+            .ok();
         registry.register(Box::new(tool_calls.clone())).ok();
         registry.register(Box::new(channel_messages.clone())).ok();
         registry.register(Box::new(heartbeat_ticks.clone())).ok();
@@ -158,6 +171,7 @@ impl PrometheusObserver {
             llm_requests,
             tokens_input_total,
             tokens_output_total,
+            tokens_cached_input_total,
             tool_calls,
             channel_messages,
             heartbeat_ticks,
@@ -210,6 +224,7 @@ impl Observer for PrometheusObserver {
                 success,
                 input_tokens,
                 output_tokens,
+                cached_input_tokens,
                 ..
             } => {
                 let success_str = if *success { "true" } else { "false" };
@@ -226,6 +241,11 @@ impl Observer for PrometheusObserver {
                         .with_label_values(&[provider.as_str(), model.as_str()])
                         .inc_by(*output);
                 }
+                if let Some(cached) = cached_input_tokens {
+                    self.tokens_cached_input_total
+                        .with_label_values(&[provider.as_str(), model.as_str()])
+                        .inc_by(*cached);
+                }
             }
             ObserverEvent::ToolCallStart { tool: _ }
             | ObserverEvent::TurnComplete
@@ -454,6 +474,7 @@ mod tests {
             error_message: None,
             input_tokens: Some(100),
             output_tokens: Some(50),
+            cached_input_tokens: None,
         });
         obs.record_event(&ObserverEvent::LlmResponse {
             provider: "openrouter".into(),
@@ -463,6 +484,7 @@ mod tests {
             error_message: None,
             input_tokens: Some(200),
             output_tokens: Some(80),
+            cached_input_tokens: Some(1024),
         });
 
         let output = obs.encode();
@@ -475,6 +497,9 @@ mod tests {
         assert!(output.contains(
             r#"zerobuild_tokens_output_total{model="claude-sonnet",provider="openrouter"} 130"#
         ));
+        assert!(output.contains(
+            r#"zerobuild_tokens_cached_input_total{model="claude-sonnet",provider="openrouter"} 1024"#
+        ));
     }
 
     #[test]
@@ -489,6 +514,7 @@ mod tests {
             error_message: Some("timeout".into()),
             input_tokens: None,
             output_tokens: None,
+            cached_input_tokens: None,
         });
 
         let output = obs.encode();
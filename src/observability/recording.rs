@@ -0,0 +1,604 @@
+//! Replayable session recordings.
+//!
+//! With `observability.record_sessions = true`, every agent session writes
+//! a JSONL file under `observability.recordings_dir` holding each LLM
+//! request and response and each tool call with its result, in order. The
+//! recording wraps the session's provider ([`RecordingProvider`]) and tools
+//! ([`record_tools`]), so the agent loop itself is unchanged.
+//!
+//! `zerobuild replay <file>` feeds a recording back through the agent loop
+//! (see [`crate::agent::replay`]) to reproduce a reported failure.
+
+use crate::config::ObservabilityConfig;
+use crate::providers::traits::{ChatMessage, ChatRequest, ChatResponse, ToolCall};
+use crate::providers::Provider;
+use crate::tools::traits::{Tool, ToolResult, ToolSpec};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, RwLock};
+use std::time::Instant;
+use uuid::Uuid;
+
+const DEFAULT_RECORDINGS_REL_DIR: &str = "state/recordings";
+
+/// One line of a recording.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum RecordedEvent {
+    /// First line: the session and the tools it had.
+    Session {
+        id: String,
+        started_at: String,
+        channel: String,
+        tools: Vec<ToolSpec>,
+    },
+    /// A user turn starts with `history` handed to the agent loop.
+    Turn {
+        provider: String,
+        model: String,
+        temperature: f64,
+        max_tool_iterations: usize,
+        #[serde(default)]
+        excluded_tools: Vec<String>,
+        history: Vec<ChatMessage>,
+        /// When the turn started (RFC 3339); absent in older recordings.
+        #[serde(default)]
+        at: Option<String>,
+    },
+    LlmRequest {
+        messages: Vec<ChatMessage>,
+        /// Whether tool schemas were sent natively with the request.
+        native_tools: bool,
+    },
+    LlmResponse {
+        #[serde(default)]
+        text: Option<String>,
+        #[serde(default)]
+        tool_calls: Vec<ToolCall>,
+        #[serde(default)]
+        reasoning_content: Option<String>,
+        #[serde(default)]
+        input_tokens: Option<u64>,
+        #[serde(default)]
+        output_tokens: Option<u64>,
+        /// Set when the request failed.
+        #[serde(default)]
+        error: Option<String>,
+        /// When the response arrived (RFC 3339).
+        #[serde(default)]
+        at: Option<String>,
+        #[serde(default)]
+        duration_ms: Option<u64>,
+    },
+    ToolCall {
+        name: String,
+        args: Value,
+        /// `None` when the tool returned an error instead of a result.
+        #[serde(default)]
+        result: Option<ToolResult>,
+        #[serde(default)]
+        error: Option<String>,
+        /// When the call returned (RFC 3339).
+        #[serde(default)]
+        at: Option<String>,
+        #[serde(default)]
+        duration_ms: Option<u64>,
+    },
+    /// The turn's final reply, or why it failed.
+    TurnEnd {
+        #[serde(default)]
+        response: Option<String>,
+        #[serde(default)]
+        error: Option<String>,
+        #[serde(default)]
+        at: Option<String>,
+    },
+}
+
+/// Appends the events of one session to its recording file.
+pub struct SessionRecorder {
+    id: String,
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl SessionRecorder {
+    /// Start a recording in `dir` for a session on `channel` with `tools`.
+    pub fn create(dir: &Path, channel: &str, tools: &[Box<dyn Tool>]) -> Result<Self> {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        let id = Uuid::new_v4().to_string();
+        let started_at = Utc::now();
+        let slug: String = channel
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect();
+        let path = dir.join(format!(
+            "{}-{slug}-{}.jsonl",
+            started_at.format("%Y%m%d-%H%M%S"),
+            &id[..8]
+        ));
+
+        let mut options = OpenOptions::new();
+        options.create_new(true).append(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let file = options
+            .open(&path)
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+
+        let recorder = Self {
+            id: id.clone(),
+            path,
+            file: Mutex::new(file),
+        };
+        recorder.append(&RecordedEvent::Session {
+            id,
+            started_at: started_at.to_rfc3339(),
+            channel: channel.to_string(),
+            tools: tools.iter().map(|tool| tool.spec()).collect(),
+        });
+        Ok(recorder)
+    }
+
+    /// The session ID.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// The recording file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append `event`. Failures are logged; they never fail the session.
+    pub fn append(&self, event: &RecordedEvent) {
+        let result = serde_json::to_string(event)
+            .map_err(anyhow::Error::from)
+            .and_then(|line| Ok(writeln!(self.file.lock(), "{line}")?));
+        if let Err(e) = result {
+            tracing::warn!(path = %self.path.display(), "Failed to write session recording: {e}");
+        }
+    }
+
+    /// Record the start of a turn run with these agent loop arguments.
+    pub fn record_turn(
+        &self,
+        provider: &str,
+        model: &str,
+        temperature: f64,
+        max_tool_iterations: usize,
+        excluded_tools: &[String],
+        history: &[ChatMessage],
+    ) {
+        self.append(&RecordedEvent::Turn {
+            provider: provider.to_string(),
+            model: model.to_string(),
+            temperature,
+            max_tool_iterations,
+            excluded_tools: excluded_tools.to_vec(),
+            history: history.to_vec(),
+            at: Some(Utc::now().to_rfc3339()),
+        });
+    }
+
+    /// Record how the current turn ended.
+    pub fn record_turn_end(&self, outcome: Result<&str, String>) {
+        let (response, error) = match outcome {
+            Ok(response) => (Some(response.to_string()), None),
+            Err(error) => (None, Some(error)),
+        };
+        self.append(&RecordedEvent::TurnEnd {
+            response,
+            error,
+            at: Some(Utc::now().to_rfc3339()),
+        });
+    }
+}
+
+/// Now and the milliseconds since `started`, for events that took a while.
+fn timing(started: Instant) -> (Option<String>, Option<u64>) {
+    (
+        Some(Utc::now().to_rfc3339()),
+        Some(u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX)),
+    )
+}
+
+/// Read the events of the recording at `path`.
+pub fn load(path: &Path) -> Result<Vec<RecordedEvent>> {
+    let raw =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    raw.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line)
+                .with_context(|| format!("{}:{}: invalid recording event", path.display(), i + 1))
+        })
+        .collect()
+}
+
+/// The recording of session `id` in `dir`. The ID may be shortened, down to
+/// the 8 characters that appear in recording file names.
+pub fn find(dir: &Path, id: &str) -> Result<Option<PathBuf>> {
+    let id = id.trim().to_ascii_lowercase();
+    if id.len() < 8 || !id.chars().all(|c| c.is_ascii_hexdigit() || c == '-') {
+        return Ok(None);
+    }
+    let suffix = format!("-{}.jsonl", &id[..8]);
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", dir.display())),
+    };
+    for entry in entries {
+        let path = entry?.path();
+        if !path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.ends_with(&suffix))
+        {
+            continue;
+        }
+        let first = fs::read_to_string(&path)
+            .ok()
+            .and_then(|raw| raw.lines().next().map(str::to_string))
+            .and_then(|line| serde_json::from_str::<RecordedEvent>(&line).ok());
+        if matches!(first, Some(RecordedEvent::Session { id: session, .. }) if session.starts_with(&id))
+        {
+            return Ok(Some(path));
+        }
+    }
+    Ok(None)
+}
+
+/// Provider that records each `chat` request and response before handing
+/// them on. Streaming is reported as unsupported so every completion goes
+/// through `chat` and is recorded whole.
+pub struct RecordingProvider {
+    inner: Arc<dyn Provider>,
+    recorder: Arc<SessionRecorder>,
+}
+
+impl RecordingProvider {
+    pub fn new(inner: Arc<dyn Provider>, recorder: Arc<SessionRecorder>) -> Self {
+        Self { inner, recorder }
+    }
+}
+
+#[async_trait]
+impl Provider for RecordingProvider {
+    fn capabilities(&self) -> crate::providers::traits::ProviderCapabilities {
+        self.inner.capabilities()
+    }
+
+    fn convert_tools(&self, tools: &[ToolSpec]) -> crate::providers::traits::ToolsPayload {
+        self.inner.convert_tools(tools)
+    }
+
+    async fn chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        temperature: f64,
+    ) -> Result<String> {
+        self.inner
+            .chat_with_system(system_prompt, message, model, temperature)
+            .await
+    }
+
+    async fn chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> Result<String> {
+        self.inner
+            .chat_with_history(messages, model, temperature)
+            .await
+    }
+
+    async fn chat(
+        &self,
+        request: ChatRequest<'_>,
+        model: &str,
+        temperature: f64,
+    ) -> Result<ChatResponse> {
+        self.recorder.append(&RecordedEvent::LlmRequest {
+            messages: request.messages.to_vec(),
+            native_tools: request.tools.is_some(),
+        });
+        let started = Instant::now();
+        let result = self.inner.chat(request, model, temperature).await;
+        let (at, duration_ms) = timing(started);
+        let event = match &result {
+            Ok(response) => RecordedEvent::LlmResponse {
+                text: response.text.clone(),
+                tool_calls: response.tool_calls.clone(),
+                reasoning_content: response.reasoning_content.clone(),
+                input_tokens: response.usage.as_ref().and_then(|u| u.input_tokens),
+                output_tokens: response.usage.as_ref().and_then(|u| u.output_tokens),
+                error: None,
+                at,
+                duration_ms,
+            },
+            Err(e) => RecordedEvent::LlmResponse {
+                text: None,
+                tool_calls: Vec::new(),
+                reasoning_content: None,
+                input_tokens: None,
+                output_tokens: None,
+                error: Some(crate::providers::sanitize_api_error(&format!("{e:#}"))),
+                at,
+                duration_ms,
+            },
+        };
+        self.recorder.append(&event);
+        result
+    }
+
+    fn supports_native_tools(&self) -> bool {
+        self.inner.supports_native_tools()
+    }
+
+    fn supports_vision(&self) -> bool {
+        self.inner.supports_vision()
+    }
+
+    async fn warmup(&self) -> Result<()> {
+        self.inner.warmup().await
+    }
+}
+
+/// Tool at `index` of a shared registry whose calls are recorded.
+struct RecordingTool {
+    tools: Arc<Vec<Box<dyn Tool>>>,
+    index: usize,
+    recorder: Arc<SessionRecorder>,
+}
+
+#[async_trait]
+impl Tool for RecordingTool {
+    fn name(&self) -> &str {
+        self.tools[self.index].name()
+    }
+
+    fn description(&self) -> &str {
+        self.tools[self.index].description()
+    }
+
+    fn parameters_schema(&self) -> Value {
+        self.tools[self.index].parameters_schema()
+    }
+
+    async fn execute(&self, args: Value) -> Result<ToolResult> {
+        let started = Instant::now();
+        let result = self.tools[self.index].execute(args.clone()).await;
+        let (at, duration_ms) = timing(started);
+        let scrub = |text: &str| crate::agent::loop_::scrub_credentials(text);
+        let (recorded, error) = match &result {
+            Ok(result) => (
+                Some(ToolResult {
+                    success: result.success,
+                    output: scrub(&result.output),
+                    error: result.error.as_deref().map(scrub),
+                    error_code: result.error_code,
+                    error_hint: result.error_hint.clone(),
+                }),
+                None,
+            ),
+            Err(e) => (None, Some(scrub(&format!("{e:#}")))),
+        };
+        self.recorder.append(&RecordedEvent::ToolCall {
+            name: self.name().to_string(),
+            args,
+            result: recorded,
+            error,
+            at,
+            duration_ms,
+        });
+        result
+    }
+}
+
+/// `tools` wrapped so that each call is recorded by `recorder`.
+pub fn record_tools(
+    tools: Arc<Vec<Box<dyn Tool>>>,
+    recorder: &Arc<SessionRecorder>,
+) -> Vec<Box<dyn Tool>> {
+    (0..tools.len())
+        .map(|index| {
+            Box::new(RecordingTool {
+                tools: Arc::clone(&tools),
+                index,
+                recorder: Arc::clone(recorder),
+            }) as Box<dyn Tool>
+        })
+        .collect()
+}
+
+/// Recordings directory, or `None` while recording is off.
+static RECORDINGS_DIR: LazyLock<RwLock<Option<PathBuf>>> = LazyLock::new(|| RwLock::new(None));
+
+/// Recorders of channel conversations, by conversation key.
+static CONVERSATIONS: LazyLock<Mutex<HashMap<String, Arc<SessionRecorder>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Resolve the recordings directory from config.
+pub fn resolve_recordings_dir(config: &ObservabilityConfig, workspace_dir: &Path) -> PathBuf {
+    let raw = config.recordings_dir.trim();
+    if raw.is_empty() {
+        return workspace_dir.join(DEFAULT_RECORDINGS_REL_DIR);
+    }
+    let configured = PathBuf::from(raw);
+    if configured.is_absolute() {
+        configured
+    } else {
+        workspace_dir.join(configured)
+    }
+}
+
+/// Enable (or disable) session recording.
+pub fn init_from_config(config: &ObservabilityConfig, workspace_dir: &Path) {
+    let dir = config
+        .record_sessions
+        .then(|| resolve_recordings_dir(config, workspace_dir));
+    *RECORDINGS_DIR.write().unwrap_or_else(|e| e.into_inner()) = dir;
+}
+
+fn recordings_dir() -> Option<PathBuf> {
+    RECORDINGS_DIR
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+}
+
+/// Start recording a new session, when recording is enabled.
+pub fn start_session(channel: &str, tools: &[Box<dyn Tool>]) -> Option<Arc<SessionRecorder>> {
+    let dir = recordings_dir()?;
+    match SessionRecorder::create(&dir, channel, tools) {
+        Ok(recorder) => {
+            tracing::info!(path = %recorder.path().display(), "Recording session");
+            Some(Arc::new(recorder))
+        }
+        Err(e) => {
+            tracing::warn!("Failed to start session recording: {e:#}");
+            None
+        }
+    }
+}
+
+/// Recorder of the channel conversation `key`, started on its first turn,
+/// when recording is enabled.
+pub fn conversation_session(
+    key: &str,
+    channel: &str,
+    tools: &[Box<dyn Tool>],
+) -> Option<Arc<SessionRecorder>> {
+    recordings_dir()?;
+    if let Some(recorder) = CONVERSATIONS.lock().get(key) {
+        return Some(Arc::clone(recorder));
+    }
+    let recorder = start_session(channel, tools)?;
+    CONVERSATIONS
+        .lock()
+        .insert(key.to_string(), Arc::clone(&recorder));
+    Some(recorder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    struct EchoTool;
+
+    #[async_trait]
+    impl Tool for EchoTool {
+        fn name(&self) -> &str {
+            "echo"
+        }
+
+        fn description(&self) -> &str {
+            "Echo the input"
+        }
+
+        fn parameters_schema(&self) -> Value {
+            serde_json::json!({ "type": "object" })
+        }
+
+        async fn execute(&self, args: Value) -> Result<ToolResult> {
+            if args.get("fail").is_some() {
+                anyhow::bail!("boom");
+            }
+            Ok(ToolResult {
+                success: true,
+                output: format!("token=ghp_abcdefghijklmnop {args}"),
+                error: None,
+                error_code: None,
+                error_hint: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn tool_calls_are_recorded_with_scrubbed_output() {
+        let tmp = TempDir::new().unwrap();
+        let tools: Vec<Box<dyn Tool>> = vec![Box::new(EchoTool)];
+        let recorder = Arc::new(SessionRecorder::create(tmp.path(), "cli", &tools).unwrap());
+        let recorded = record_tools(Arc::new(tools), &recorder);
+
+        recorder.record_turn("openai", "gpt-4o", 0.7, 10, &[], &[ChatMessage::user("hi")]);
+        let result = recorded[0]
+            .execute(serde_json::json!({ "x": 1 }))
+            .await
+            .unwrap();
+        assert!(result.output.contains("ghp_abcdefghijklmnop"));
+        assert!(recorded[0]
+            .execute(serde_json::json!({ "fail": true }))
+            .await
+            .is_err());
+        recorder.record_turn_end(Ok("done"));
+
+        let events = load(recorder.path()).unwrap();
+        assert_eq!(events.len(), 5);
+        assert!(
+            matches!(&events[0], RecordedEvent::Session { channel, tools, .. }
+            if channel == "cli" && tools[0].name == "echo")
+        );
+        assert!(matches!(&events[1], RecordedEvent::Turn { history, .. } if history.len() == 1));
+        match &events[2] {
+            RecordedEvent::ToolCall {
+                name,
+                result: Some(result),
+                ..
+            } => {
+                assert_eq!(name, "echo");
+                assert!(!result.output.contains("ghp_abcdefghijklmnop"));
+            }
+            other => panic!("unexpected event {other:?}"),
+        }
+        assert!(
+            matches!(&events[3], RecordedEvent::ToolCall { result: None, error: Some(e), .. }
+            if e == "boom")
+        );
+        assert!(
+            matches!(&events[4], RecordedEvent::TurnEnd { response: Some(r), .. } if r == "done")
+        );
+
+        assert_eq!(
+            find(tmp.path(), &recorder.id()[..8]).unwrap().as_deref(),
+            Some(recorder.path())
+        );
+        assert_eq!(find(tmp.path(), "00000000").unwrap(), None);
+        assert_eq!(find(tmp.path(), "../../etc").unwrap(), None);
+    }
+
+    #[test]
+    fn recordings_dir_resolves_under_workspace() {
+        let config = ObservabilityConfig::default();
+        assert_eq!(
+            resolve_recordings_dir(&config, Path::new("/ws")),
+            PathBuf::from("/ws/state/recordings")
+        );
+        let config = ObservabilityConfig {
+            recordings_dir: "/var/rec".into(),
+            ..ObservabilityConfig::default()
+        };
+        assert_eq!(
+            resolve_recordings_dir(&config, Path::new("/ws")),
+            PathBuf::from("/var/rec")
+        );
+    }
+}
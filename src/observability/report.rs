@@ -0,0 +1,823 @@
+//! Shareable reports of recorded sessions.
+//!
+//! [`SessionReport::from_events`] condenses a session recording (see
+//! [`super::recording`]) into what a reviewer wants to see: what was asked,
+//! the plan, the tool calls that changed something, file diffs, test runs,
+//! deploy links and the token cost. Reports render as Markdown or as a
+//! standalone HTML page. The gateway serves them at
+//! `GET /api/sessions/{id}/report`, and with
+//! `observability.post_build_reports = true` channels post one when a
+//! `factory_build` call finishes.
+
+use super::recording::RecordedEvent;
+use crate::config::ModelPricing;
+use crate::tools::traits::ToolResult;
+use crate::util::truncate_with_ellipsis;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Tools that only look around; left out of the tool call list.
+const READ_ONLY_TOOLS: &[&str] = &[
+    "sandbox_read_file",
+    "sandbox_list_files",
+    "sandbox_get_package_manager",
+    "codebase_search",
+    "get_log",
+    "sandbox_command_history",
+    "project_info",
+    "task_plan",
+    "think",
+    "memory_recall",
+    "file_read",
+    "glob_search",
+    "content_search",
+    "github_connect",
+];
+
+/// Tools besides `github_*` whose output may hold preview or deploy URLs.
+const LINK_TOOLS: &[&str] = &[
+    "factory_build",
+    "sandbox_get_preview_url",
+    "sandbox_get_public_url",
+];
+
+/// Command fragments that mark a test, type-check, lint or build run.
+const CHECK_COMMANDS: &[&str] = &[
+    "test",
+    "vitest",
+    "jest",
+    "pytest",
+    "tsc",
+    "lint",
+    "build",
+    "cargo check",
+];
+
+/// Diff lines kept per file; the rest are counted.
+const MAX_DIFF_LINES: usize = 60;
+
+/// Output format of a report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+impl ReportFormat {
+    /// Format named `md`/`markdown` or `html`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "md" | "markdown" => Some(Self::Markdown),
+            "html" => Some(Self::Html),
+            _ => None,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Html => "html",
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Markdown => "text/markdown; charset=utf-8",
+            Self::Html => "text/html; charset=utf-8",
+        }
+    }
+}
+
+/// One `task_plan` task.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlanItem {
+    pub title: String,
+    /// `pending`, `in_progress` or `completed`.
+    pub status: String,
+}
+
+/// A tool call that changed something.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolCallSummary {
+    pub name: String,
+    /// The command, path or title the call was about.
+    pub detail: String,
+    /// Why the call failed, or `None` when it succeeded.
+    pub error: Option<String>,
+}
+
+/// Lines a file change removed (`-`) and added (`+`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileDiff {
+    pub path: String,
+    pub diff: String,
+}
+
+impl FileDiff {
+    fn new(path: impl Into<String>, removed: &str, added: &str) -> Self {
+        let mut lines: Vec<String> = removed
+            .lines()
+            .map(|line| format!("-{line}"))
+            .chain(added.lines().map(|line| format!("+{line}")))
+            .collect();
+        if lines.len() > MAX_DIFF_LINES {
+            let more = lines.len() - MAX_DIFF_LINES;
+            lines.truncate(MAX_DIFF_LINES);
+            lines.push(format!("… {more} more lines"));
+        }
+        Self {
+            path: path.into(),
+            diff: lines.join("\n"),
+        }
+    }
+}
+
+/// A test, type-check, lint or build command and how it went.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckRun {
+    pub command: String,
+    pub passed: bool,
+    /// Last line of output, or the error.
+    pub summary: String,
+}
+
+/// Summary of one recorded session.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SessionReport {
+    pub id: String,
+    pub channel: String,
+    pub started_at: String,
+    /// `provider/model` of each turn, first use first.
+    pub models: Vec<String>,
+    /// The user message of each turn.
+    pub requirements: Vec<String>,
+    pub plan: Vec<PlanItem>,
+    pub tool_calls: Vec<ToolCallSummary>,
+    pub diffs: Vec<FileDiff>,
+    pub checks: Vec<CheckRun>,
+    pub links: Vec<String>,
+    /// Final reply of the last turn that produced one.
+    pub outcome: Option<String>,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// `None` when a model that used tokens has no price in `[cost.prices]`.
+    pub cost_usd: Option<f64>,
+}
+
+impl SessionReport {
+    /// Report of the recording `events`, priced with `prices` (keyed by
+    /// `provider/model` or model, like `[cost.prices]`).
+    pub fn from_events(events: &[RecordedEvent], prices: &HashMap<String, ModelPricing>) -> Self {
+        let mut report = Self {
+            cost_usd: Some(0.0),
+            ..Self::default()
+        };
+        let mut model = String::new();
+        let mut price = None;
+        for event in events {
+            match event {
+                RecordedEvent::Session {
+                    id,
+                    started_at,
+                    channel,
+                    ..
+                } => {
+                    report.id.clone_from(id);
+                    report.started_at.clone_from(started_at);
+                    report.channel.clone_from(channel);
+                }
+                RecordedEvent::Turn {
+                    provider,
+                    model: turn_model,
+                    history,
+                    ..
+                } => {
+                    model = format!("{provider}/{turn_model}");
+                    price = prices
+                        .get(&model)
+                        .or_else(|| prices.get(turn_model))
+                        .map(|pricing| (pricing.input, pricing.output));
+                    if !report.models.contains(&model) {
+                        report.models.push(model.clone());
+                    }
+                    let request = history
+                        .iter()
+                        .rev()
+                        .find(|message| message.role == "user")
+                        .map(|message| message.content.trim())
+                        .filter(|text| !text.is_empty());
+                    if let Some(text) = request {
+                        if report.requirements.last().map(String::as_str) != Some(text) {
+                            report.requirements.push(text.to_string());
+                        }
+                    }
+                }
+                RecordedEvent::LlmResponse {
+                    input_tokens,
+                    output_tokens,
+                    ..
+                } => {
+                    let (input, output) = (input_tokens.unwrap_or(0), output_tokens.unwrap_or(0));
+                    report.input_tokens += input;
+                    report.output_tokens += output;
+                    if input + output > 0 {
+                        report.cost_usd = match (report.cost_usd, price) {
+                            (Some(total), Some((input_price, output_price))) => Some(
+                                total
+                                    + crate::cost::TokenUsage::new(
+                                        &model,
+                                        input,
+                                        output,
+                                        input_price,
+                                        output_price,
+                                    )
+                                    .cost(),
+                            ),
+                            _ => None,
+                        };
+                    }
+                }
+                RecordedEvent::ToolCall {
+                    name,
+                    args,
+                    result,
+                    error,
+                    ..
+                } => report.add_tool_call(name, args, result.as_ref(), error.as_deref()),
+                RecordedEvent::TurnEnd {
+                    response: Some(response),
+                    ..
+                } => report.outcome = Some(response.clone()),
+                RecordedEvent::TurnEnd { .. } | RecordedEvent::LlmRequest { .. } => {}
+            }
+        }
+        report
+    }
+
+    fn add_tool_call(
+        &mut self,
+        name: &str,
+        args: &Value,
+        result: Option<&ToolResult>,
+        error: Option<&str>,
+    ) {
+        let succeeded = result.is_some_and(|result| result.success);
+        let failure = (!succeeded).then(|| {
+            result
+                .and_then(|result| result.error.clone())
+                .or_else(|| error.map(str::to_string))
+                .unwrap_or_else(|| "failed".to_string())
+        });
+        let text = |key: &str| args[key].as_str().unwrap_or_default();
+
+        match name {
+            "task_plan" if succeeded => self.apply_plan(args),
+            "sandbox_write_file" if succeeded => {
+                self.diffs
+                    .push(FileDiff::new(text("path"), "", text("content")));
+            }
+            "sandbox_write_files" if succeeded => {
+                for file in args["files"].as_array().into_iter().flatten() {
+                    let path = file["path"].as_str().unwrap_or_default();
+                    let content = file["content"].as_str().unwrap_or_default();
+                    self.diffs.push(FileDiff::new(path, "", content));
+                }
+            }
+            "sandbox_edit_file" if succeeded => {
+                let diff = match args["start_line"].as_u64() {
+                    Some(start) => FileDiff::new(
+                        format!(
+                            "{} (lines {start}-{})",
+                            text("path"),
+                            args["end_line"].as_u64().unwrap_or(start)
+                        ),
+                        "",
+                        text("new_content"),
+                    ),
+                    None => FileDiff::new(text("path"), text("old_string"), text("new_string")),
+                };
+                self.diffs.push(diff);
+            }
+            "sandbox_run_command" | "sandbox_shell" | "shell" => {
+                let command = text("command");
+                if CHECK_COMMANDS.iter().any(|check| command.contains(check)) {
+                    let summary = match (&failure, result) {
+                        (None, Some(result)) => last_line(&result.output),
+                        (Some(error), _) => error.clone(),
+                        (None, None) => String::new(),
+                    };
+                    self.checks.push(CheckRun {
+                        command: command.to_string(),
+                        passed: succeeded,
+                        summary: truncate_with_ellipsis(&summary, 160),
+                    });
+                }
+            }
+            _ => {}
+        }
+
+        if let Some(result) = result.filter(|_| succeeded) {
+            if name.starts_with("github_") || LINK_TOOLS.contains(&name) {
+                self.push_links(&result.output);
+            }
+        }
+        if !READ_ONLY_TOOLS.contains(&name) {
+            self.tool_calls.push(ToolCallSummary {
+                name: name.to_string(),
+                detail: call_detail(args),
+                error: failure.map(|error| truncate_with_ellipsis(&error, 200)),
+            });
+        }
+    }
+
+    /// Follow `task_plan` `create`, `add` and `update` calls.
+    fn apply_plan(&mut self, args: &Value) {
+        match args["action"].as_str().unwrap_or_default() {
+            "create" => {
+                self.plan = args["tasks"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .map(|task| PlanItem {
+                        title: task["title"].as_str().unwrap_or_default().to_string(),
+                        status: task["status"].as_str().unwrap_or("pending").to_string(),
+                    })
+                    .collect();
+            }
+            "add" => self.plan.push(PlanItem {
+                title: args["title"].as_str().unwrap_or_default().to_string(),
+                status: "pending".to_string(),
+            }),
+            "update" => {
+                let index = args["id"]
+                    .as_u64()
+                    .and_then(|id| usize::try_from(id).ok())
+                    .and_then(|id| id.checked_sub(1));
+                if let (Some(task), Some(status)) = (
+                    index.and_then(|index| self.plan.get_mut(index)),
+                    args["status"].as_str(),
+                ) {
+                    task.status = status.to_string();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn push_links(&mut self, text: &str) {
+        for word in text.split_whitespace() {
+            let Some(start) = word.find("https://").or_else(|| word.find("http://")) else {
+                continue;
+            };
+            let url = word[start..].trim_end_matches(|c: char| {
+                matches!(c, '.' | ',' | ';' | ')' | ']' | '>' | '"' | '\'' | '`')
+            });
+            if url.starts_with("https://api.github.com") || self.links.iter().any(|l| l == url) {
+                continue;
+            }
+            self.links.push(url.to_string());
+        }
+    }
+
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Markdown => self.to_markdown(),
+            ReportFormat::Html => self.to_html(),
+        }
+    }
+
+    fn cost_line(&self) -> String {
+        match self.cost_usd {
+            Some(cost) => format!("${cost:.4}"),
+            None => "unknown (a model has no price in [cost.prices])".to_string(),
+        }
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::from("# Session report\n\n");
+        let _ = writeln!(out, "- **Session:** `{}`", self.id);
+        let _ = writeln!(out, "- **Channel:** {}", self.channel);
+        let _ = writeln!(out, "- **Started:** {}", self.started_at);
+        if !self.models.is_empty() {
+            let _ = writeln!(out, "- **Models:** {}", self.models.join(", "));
+        }
+        let _ = writeln!(
+            out,
+            "- **Tokens:** {} in / {} out",
+            self.input_tokens, self.output_tokens
+        );
+        let _ = writeln!(out, "- **Cost:** {}", self.cost_line());
+
+        if !self.requirements.is_empty() {
+            out.push_str("\n## Requirements\n");
+            for request in &self.requirements {
+                out.push('\n');
+                for line in request.lines() {
+                    let _ = writeln!(out, "> {line}");
+                }
+            }
+        }
+
+        if !self.plan.is_empty() {
+            out.push_str("\n## Plan\n\n");
+            for task in &self.plan {
+                let mark = if task.status == "completed" { "x" } else { " " };
+                let note = if task.status == "in_progress" {
+                    " _(in progress)_"
+                } else {
+                    ""
+                };
+                let _ = writeln!(out, "- [{mark}] {}{note}", task.title);
+            }
+        }
+
+        if !self.tool_calls.is_empty() {
+            out.push_str("\n## Tool calls\n\n| # | Tool | Details | Result |\n|---|---|---|---|\n");
+            for (i, call) in self.tool_calls.iter().enumerate() {
+                let result = match &call.error {
+                    None => "ok".to_string(),
+                    Some(error) => format!("failed: {}", table_cell(error)),
+                };
+                let detail = if call.detail.is_empty() {
+                    String::new()
+                } else {
+                    format!("`{}`", table_cell(&call.detail))
+                };
+                let _ = writeln!(out, "| {} | `{}` | {detail} | {result} |", i + 1, call.name);
+            }
+        }
+
+        if !self.diffs.is_empty() {
+            out.push_str("\n## Changes\n");
+            for diff in &self.diffs {
+                let _ = write!(
+                    out,
+                    "\n### `{}`\n\n```diff\n{}\n```\n",
+                    diff.path, diff.diff
+                );
+            }
+        }
+
+        if !self.checks.is_empty() {
+            out.push_str("\n## Tests and checks\n\n");
+            for check in &self.checks {
+                let mark = if check.passed { "passed" } else { "FAILED" };
+                let _ = write!(out, "- {mark}: `{}`", check.command);
+                if !check.summary.is_empty() {
+                    let _ = write!(out, " — {}", check.summary);
+                }
+                out.push('\n');
+            }
+        }
+
+        if !self.links.is_empty() {
+            out.push_str("\n## Links\n\n");
+            for link in &self.links {
+                let _ = writeln!(out, "- <{link}>");
+            }
+        }
+
+        if let Some(outcome) = &self.outcome {
+            let _ = write!(out, "\n## Outcome\n\n{}\n", outcome.trim());
+        }
+        out
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut out = String::from(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>Session report</title>\n<style>\n\
+             body{font-family:system-ui,sans-serif;max-width:960px;margin:2em auto;padding:0 1em;color:#222}\n\
+             table{border-collapse:collapse;width:100%}td,th{border:1px solid #ddd;padding:4px 8px;text-align:left}\n\
+             pre{background:#f6f8fa;padding:8px;overflow-x:auto}.add{color:#116329}.del{color:#82071e}\n\
+             .failed{color:#82071e}blockquote{border-left:4px solid #ddd;margin:0;padding:0 1em;white-space:pre-wrap}\n\
+             </style>\n</head>\n<body>\n<h1>Session report</h1>\n<ul>\n",
+        );
+        let _ = writeln!(
+            out,
+            "<li><b>Session:</b> <code>{}</code></li>",
+            escape(&self.id)
+        );
+        let _ = writeln!(out, "<li><b>Channel:</b> {}</li>", escape(&self.channel));
+        let _ = writeln!(out, "<li><b>Started:</b> {}</li>", escape(&self.started_at));
+        if !self.models.is_empty() {
+            let _ = writeln!(
+                out,
+                "<li><b>Models:</b> {}</li>",
+                escape(&self.models.join(", "))
+            );
+        }
+        let _ = writeln!(
+            out,
+            "<li><b>Tokens:</b> {} in / {} out</li>",
+            self.input_tokens, self.output_tokens
+        );
+        let _ = writeln!(
+            out,
+            "<li><b>Cost:</b> {}</li>\n</ul>",
+            escape(&self.cost_line())
+        );
+
+        if !self.requirements.is_empty() {
+            out.push_str("<h2>Requirements</h2>\n");
+            for request in &self.requirements {
+                let _ = writeln!(out, "<blockquote>{}</blockquote>", escape(request));
+            }
+        }
+
+        if !self.plan.is_empty() {
+            out.push_str("<h2>Plan</h2>\n<ul>\n");
+            for task in &self.plan {
+                let mark = if task.status == "completed" {
+                    "&#9745;"
+                } else {
+                    "&#9744;"
+                };
+                let note = if task.status == "in_progress" {
+                    " <i>(in progress)</i>"
+                } else {
+                    ""
+                };
+                let _ = writeln!(out, "<li>{mark} {}{note}</li>", escape(&task.title));
+            }
+            out.push_str("</ul>\n");
+        }
+
+        if !self.tool_calls.is_empty() {
+            out.push_str(
+                "<h2>Tool calls</h2>\n<table>\n<tr><th>#</th><th>Tool</th><th>Details</th><th>Result</th></tr>\n",
+            );
+            for (i, call) in self.tool_calls.iter().enumerate() {
+                let result = match &call.error {
+                    None => "ok".to_string(),
+                    Some(error) => {
+                        format!("<span class=\"failed\">failed: {}</span>", escape(error))
+                    }
+                };
+                let _ = writeln!(
+                    out,
+                    "<tr><td>{}</td><td><code>{}</code></td><td><code>{}</code></td><td>{result}</td></tr>",
+                    i + 1,
+                    escape(&call.name),
+                    escape(&call.detail)
+                );
+            }
+            out.push_str("</table>\n");
+        }
+
+        if !self.diffs.is_empty() {
+            out.push_str("<h2>Changes</h2>\n");
+            for diff in &self.diffs {
+                let _ = write!(out, "<h3><code>{}</code></h3>\n<pre>", escape(&diff.path));
+                for line in diff.diff.lines() {
+                    let class = match line.chars().next() {
+                        Some('+') => "add",
+                        Some('-') => "del",
+                        _ => "",
+                    };
+                    let _ = writeln!(out, "<span class=\"{class}\">{}</span>", escape(line));
+                }
+                out.push_str("</pre>\n");
+            }
+        }
+
+        if !self.checks.is_empty() {
+            out.push_str("<h2>Tests and checks</h2>\n<ul>\n");
+            for check in &self.checks {
+                let mark = if check.passed {
+                    "passed".to_string()
+                } else {
+                    "<span class=\"failed\">FAILED</span>".to_string()
+                };
+                let _ = write!(out, "<li>{mark}: <code>{}</code>", escape(&check.command));
+                if !check.summary.is_empty() {
+                    let _ = write!(out, " — {}", escape(&check.summary));
+                }
+                out.push_str("</li>\n");
+            }
+            out.push_str("</ul>\n");
+        }
+
+        if !self.links.is_empty() {
+            out.push_str("<h2>Links</h2>\n<ul>\n");
+            for link in &self.links {
+                let link = escape(link);
+                let _ = writeln!(out, "<li><a href=\"{link}\">{link}</a></li>");
+            }
+            out.push_str("</ul>\n");
+        }
+
+        if let Some(outcome) = &self.outcome {
+            let _ = writeln!(
+                out,
+                "<h2>Outcome</h2>\n<blockquote>{}</blockquote>",
+                escape(outcome.trim())
+            );
+        }
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+}
+
+/// Whether the last turn of `events` finished a build: a `factory_build` or
+/// `ship_it` call that succeeded.
+pub fn last_turn_built(events: &[RecordedEvent]) -> bool {
+    let turn_start = events
+        .iter()
+        .rposition(|event| matches!(event, RecordedEvent::Turn { .. }))
+        .unwrap_or(0);
+    events[turn_start..].iter().any(|event| {
+        matches!(
+            event,
+            RecordedEvent::ToolCall { name, result: Some(result), .. }
+                if (name == "factory_build" || name == "ship_it") && result.success
+        )
+    })
+}
+
+/// The argument a call was mostly about.
+pub(super) fn call_detail(args: &Value) -> String {
+    ["command", "path", "title", "idea", "repo", "url"]
+        .iter()
+        .find_map(|key| args[*key].as_str())
+        .map(|detail| truncate_with_ellipsis(detail.lines().next().unwrap_or_default(), 120))
+        .unwrap_or_default()
+}
+
+fn last_line(output: &str) -> String {
+    output
+        .lines()
+        .rev()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn table_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+pub(super) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::ChatMessage;
+    use serde_json::json;
+
+    fn tool_call(name: &str, args: Value, success: bool, output: &str) -> RecordedEvent {
+        RecordedEvent::ToolCall {
+            name: name.to_string(),
+            args,
+            result: Some(ToolResult {
+                success,
+                output: output.to_string(),
+                error: (!success).then(|| "Command exited with code 1".to_string()),
+                error_code: None,
+                error_hint: None,
+            }),
+            error: None,
+            at: None,
+            duration_ms: None,
+        }
+    }
+
+    fn build_session() -> Vec<RecordedEvent> {
+        vec![
+            RecordedEvent::Session {
+                id: "1a2b3c4d-0000-0000-0000-000000000000".into(),
+                started_at: "2026-10-17T10:00:00+00:00".into(),
+                channel: "telegram".into(),
+                tools: Vec::new(),
+            },
+            RecordedEvent::Turn {
+                provider: "openrouter".into(),
+                model: "anthropic/claude-sonnet-4".into(),
+                temperature: 0.7,
+                max_tool_iterations: 10,
+                excluded_tools: Vec::new(),
+                history: vec![
+                    ChatMessage::system("You are ZeroBuild"),
+                    ChatMessage::user("Build a todo app"),
+                ],
+                at: None,
+            },
+            RecordedEvent::LlmResponse {
+                text: None,
+                tool_calls: Vec::new(),
+                reasoning_content: None,
+                input_tokens: Some(1_000_000),
+                output_tokens: Some(100_000),
+                error: None,
+                at: None,
+                duration_ms: None,
+            },
+            tool_call(
+                "task_plan",
+                json!({"action": "create", "tasks": [{"title": "Scaffold"}, {"title": "Add tests"}]}),
+                true,
+                "",
+            ),
+            tool_call(
+                "task_plan",
+                json!({"action": "update", "id": 1, "status": "completed"}),
+                true,
+                "",
+            ),
+            tool_call(
+                "sandbox_edit_file",
+                json!({"path": "project/src/App.tsx", "old_string": "<h1>Hi</h1>", "new_string": "<h1>Todos</h1>"}),
+                true,
+                "Edited",
+            ),
+            tool_call(
+                "sandbox_read_file",
+                json!({"path": "project/package.json"}),
+                true,
+                "{}",
+            ),
+            tool_call(
+                "sandbox_run_command",
+                json!({"command": "npm test"}),
+                false,
+                "exit_code: 1\n\nstdout:\nTests: 1 failed, 4 passed\n",
+            ),
+            tool_call(
+                "github_push",
+                json!({"repo": "alice/todo"}),
+                true,
+                "Pushed to https://github.com/alice/todo.",
+            ),
+            RecordedEvent::TurnEnd {
+                response: Some("Your app is live.".into()),
+                error: None,
+                at: None,
+            },
+        ]
+    }
+
+    #[test]
+    fn report_collects_plan_changes_checks_links_and_cost() {
+        let prices = HashMap::from([(
+            "anthropic/claude-sonnet-4".to_string(),
+            ModelPricing {
+                input: 3.0,
+                output: 15.0,
+            },
+        )]);
+        let report = SessionReport::from_events(&build_session(), &prices);
+
+        assert_eq!(report.requirements, ["Build a todo app"]);
+        assert_eq!(report.plan[0].status, "completed");
+        assert_eq!(report.plan[1].status, "pending");
+        let tools: Vec<&str> = report.tool_calls.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(
+            tools,
+            ["sandbox_edit_file", "sandbox_run_command", "github_push"]
+        );
+        assert_eq!(report.diffs[0].diff, "-<h1>Hi</h1>\n+<h1>Todos</h1>");
+        assert_eq!(
+            report.checks,
+            [CheckRun {
+                command: "npm test".into(),
+                passed: false,
+                summary: "Command exited with code 1".into(),
+            }]
+        );
+        assert_eq!(report.links, ["https://github.com/alice/todo"]);
+        assert!((report.cost_usd.unwrap() - 4.5).abs() < 1e-9);
+        assert!(!last_turn_built(&build_session()));
+
+        let markdown = report.to_markdown();
+        assert!(markdown.contains("- [x] Scaffold"));
+        assert!(markdown.contains("```diff\n-<h1>Hi</h1>\n+<h1>Todos</h1>\n```"));
+        assert!(markdown.contains("- **Cost:** $4.5000"));
+        let html = report.to_html();
+        assert!(html.contains("<span class=\"del\">-&lt;h1&gt;Hi&lt;/h1&gt;</span>"));
+        assert!(html.contains("<a href=\"https://github.com/alice/todo\">"));
+    }
+
+    #[test]
+    fn unpriced_models_leave_cost_unknown_and_builds_are_detected() {
+        let mut events = build_session();
+        events.insert(
+            events.len() - 1,
+            tool_call("factory_build", json!({"idea": "todo app"}), true, "done"),
+        );
+        let report = SessionReport::from_events(&events, &HashMap::new());
+        assert_eq!(report.cost_usd, None);
+        assert!(report.to_markdown().contains("unknown"));
+        assert!(last_turn_built(&events));
+        assert_eq!(
+            ReportFormat::from_name("HTML").map(ReportFormat::extension),
+            Some("html")
+        );
+    }
+}
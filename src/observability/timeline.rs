@@ -0,0 +1,503 @@
+//! Per-session timelines.
+//!
+//! [`SessionTimeline::from_events`] lays a session recording (see
+//! [`super::recording`]) out over time: each turn is a stage holding its LLM
+//! calls and tool calls with when they started, how long they took, their
+//! token usage and errors. The gateway serves it as JSON at
+//! `GET /api/v1/sessions/{id}/timeline`, or with `?format=html` as a page
+//! with a bar per call, to show where a slow build spent its time.
+//! Recordings made before calls were timed have no offsets or durations.
+
+use super::recording::RecordedEvent;
+use super::report::{call_detail, escape};
+use crate::util::truncate_with_ellipsis;
+use chrono::{DateTime, FixedOffset};
+use serde::Serialize;
+use std::fmt::Write;
+
+/// Calls listed under "Slowest steps" in the HTML page.
+const SLOWEST_STEPS: usize = 5;
+
+/// What a span of the timeline did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpanKind {
+    Llm,
+    Tool,
+}
+
+/// One LLM call or tool call.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TimelineSpan {
+    pub kind: SpanKind,
+    /// The tool, or the model for LLM calls.
+    pub name: String,
+    /// The command, path or title a tool call was about.
+    pub detail: String,
+    /// Milliseconds from the session start to the call.
+    pub offset_ms: Option<u64>,
+    pub duration_ms: Option<u64>,
+    pub input_tokens: Option<u64>,
+    pub output_tokens: Option<u64>,
+    pub error: Option<String>,
+}
+
+/// One turn: a user message and everything the agent did for it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct TimelineStage {
+    /// The user message that started the turn.
+    pub request: String,
+    /// `provider/model` of the turn.
+    pub model: String,
+    pub offset_ms: Option<u64>,
+    pub duration_ms: Option<u64>,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Why the turn failed.
+    pub error: Option<String>,
+    pub spans: Vec<TimelineSpan>,
+}
+
+/// Timeline of one recorded session.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SessionTimeline {
+    pub id: String,
+    pub channel: String,
+    pub started_at: String,
+    /// Milliseconds from the session start to the end of its last turn.
+    pub duration_ms: Option<u64>,
+    /// Time spent waiting on the model and running tools. Tool calls made
+    /// in parallel both count.
+    pub llm_ms: u64,
+    pub tool_ms: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Failed calls and turns.
+    pub errors: usize,
+    pub stages: Vec<TimelineStage>,
+}
+
+impl SessionTimeline {
+    /// Timeline of the recording `events`.
+    pub fn from_events(events: &[RecordedEvent]) -> Self {
+        let mut timeline = Self::default();
+        let mut start = None;
+        let mut model = String::new();
+        // Milliseconds from the session start to `at`.
+        let offset = |start: Option<DateTime<FixedOffset>>, at: Option<&String>| {
+            let at = DateTime::parse_from_rfc3339(at?).ok()?;
+            u64::try_from((at - start?).num_milliseconds()).ok()
+        };
+
+        for event in events {
+            match event {
+                RecordedEvent::Session {
+                    id,
+                    started_at,
+                    channel,
+                    ..
+                } => {
+                    timeline.id.clone_from(id);
+                    timeline.channel.clone_from(channel);
+                    timeline.started_at.clone_from(started_at);
+                    start = DateTime::parse_from_rfc3339(started_at).ok();
+                }
+                RecordedEvent::Turn {
+                    provider,
+                    model: turn_model,
+                    history,
+                    at,
+                    ..
+                } => {
+                    model = format!("{provider}/{turn_model}");
+                    let request = history
+                        .iter()
+                        .rev()
+                        .find(|message| message.role == "user")
+                        .map(|message| truncate_with_ellipsis(message.content.trim(), 200))
+                        .unwrap_or_default();
+                    timeline.stages.push(TimelineStage {
+                        request,
+                        model: model.clone(),
+                        offset_ms: offset(start, at.as_ref()),
+                        ..TimelineStage::default()
+                    });
+                }
+                RecordedEvent::LlmResponse {
+                    input_tokens,
+                    output_tokens,
+                    error,
+                    at,
+                    duration_ms,
+                    ..
+                } => {
+                    timeline.input_tokens += input_tokens.unwrap_or(0);
+                    timeline.output_tokens += output_tokens.unwrap_or(0);
+                    timeline.llm_ms += duration_ms.unwrap_or(0);
+                    let stage = timeline.current_stage(&model);
+                    stage.input_tokens += input_tokens.unwrap_or(0);
+                    stage.output_tokens += output_tokens.unwrap_or(0);
+                    stage.spans.push(TimelineSpan {
+                        kind: SpanKind::Llm,
+                        name: model.clone(),
+                        detail: String::new(),
+                        offset_ms: span_start(offset(start, at.as_ref()), *duration_ms),
+                        duration_ms: *duration_ms,
+                        input_tokens: *input_tokens,
+                        output_tokens: *output_tokens,
+                        error: error.as_deref().map(|e| truncate_with_ellipsis(e, 200)),
+                    });
+                }
+                RecordedEvent::ToolCall {
+                    name,
+                    args,
+                    result,
+                    error,
+                    at,
+                    duration_ms,
+                } => {
+                    timeline.tool_ms += duration_ms.unwrap_or(0);
+                    let failure = match result {
+                        Some(result) if result.success => None,
+                        Some(result) => Some(result.error.as_deref().unwrap_or("failed")),
+                        None => Some(error.as_deref().unwrap_or("failed")),
+                    };
+                    timeline.current_stage(&model).spans.push(TimelineSpan {
+                        kind: SpanKind::Tool,
+                        name: name.clone(),
+                        detail: call_detail(args),
+                        offset_ms: span_start(offset(start, at.as_ref()), *duration_ms),
+                        duration_ms: *duration_ms,
+                        input_tokens: None,
+                        output_tokens: None,
+                        error: failure.map(|e| truncate_with_ellipsis(e, 200)),
+                    });
+                }
+                RecordedEvent::TurnEnd { error, at, .. } => {
+                    let end = offset(start, at.as_ref());
+                    let stage = timeline.current_stage(&model);
+                    stage.error = error.as_deref().map(|e| truncate_with_ellipsis(e, 200));
+                    stage.duration_ms = end
+                        .zip(stage.offset_ms)
+                        .map(|(end, begin)| end.saturating_sub(begin));
+                }
+                RecordedEvent::LlmRequest { .. } => {}
+            }
+        }
+
+        for stage in &mut timeline.stages {
+            // A turn cut short has no end; it lasted until its last call.
+            if stage.duration_ms.is_none() {
+                stage.duration_ms = stage
+                    .spans
+                    .iter()
+                    .filter_map(|span| Some(span.offset_ms? + span.duration_ms?))
+                    .max()
+                    .zip(stage.offset_ms)
+                    .map(|(end, begin)| end.saturating_sub(begin));
+            }
+            timeline.errors += stage.spans.iter().filter(|s| s.error.is_some()).count()
+                + usize::from(stage.error.is_some());
+        }
+        timeline.duration_ms = timeline
+            .stages
+            .iter()
+            .filter_map(|stage| Some(stage.offset_ms? + stage.duration_ms?))
+            .max();
+        timeline
+    }
+
+    /// The stage calls belong to; recordings cut before their first turn
+    /// get one.
+    fn current_stage(&mut self, model: &str) -> &mut TimelineStage {
+        if self.stages.is_empty() {
+            self.stages.push(TimelineStage {
+                model: model.to_string(),
+                ..TimelineStage::default()
+            });
+        }
+        let last = self.stages.len() - 1;
+        &mut self.stages[last]
+    }
+
+    /// The calls that took longest, longest first.
+    pub fn slowest(&self, count: usize) -> Vec<&TimelineSpan> {
+        let mut spans: Vec<&TimelineSpan> = self
+            .stages
+            .iter()
+            .flat_map(|stage| &stage.spans)
+            .filter(|span| span.duration_ms.is_some())
+            .collect();
+        spans.sort_by_key(|span| std::cmp::Reverse(span.duration_ms));
+        spans.truncate(count);
+        spans
+    }
+
+    /// A standalone page with the summary, the slowest calls and a bar
+    /// chart of each turn.
+    pub fn to_html(&self) -> String {
+        let mut out = String::from(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>Session timeline</title>\n<style>\n\
+             body{font-family:system-ui,sans-serif;max-width:1100px;margin:2em auto;padding:0 1em;color:#222}\n\
+             table{border-collapse:collapse;width:100%}td,th{border:1px solid #ddd;padding:4px 8px;text-align:left}\n\
+             td.track{width:40%;padding:4px 0}.bar{height:12px;min-width:2px;border-radius:2px}\n\
+             .llm{background:#6f42c1}.tool{background:#0969da}.error{background:#cf222e}\n\
+             .failed{color:#82071e}blockquote{border-left:4px solid #ddd;margin:0;padding:0 1em;white-space:pre-wrap}\n\
+             </style>\n</head>\n<body>\n<h1>Session timeline</h1>\n<ul>\n",
+        );
+        let _ = writeln!(
+            out,
+            "<li><b>Session:</b> <code>{}</code></li>",
+            escape(&self.id)
+        );
+        let _ = writeln!(out, "<li><b>Channel:</b> {}</li>", escape(&self.channel));
+        let _ = writeln!(out, "<li><b>Started:</b> {}</li>", escape(&self.started_at));
+        let _ = writeln!(
+            out,
+            "<li><b>Duration:</b> {} (model {}, tools {})</li>",
+            format_ms(self.duration_ms),
+            format_ms(Some(self.llm_ms)),
+            format_ms(Some(self.tool_ms))
+        );
+        let _ = writeln!(
+            out,
+            "<li><b>Tokens:</b> {} in / {} out</li>",
+            self.input_tokens, self.output_tokens
+        );
+        let _ = writeln!(out, "<li><b>Errors:</b> {}</li>\n</ul>", self.errors);
+
+        let slowest = self.slowest(SLOWEST_STEPS);
+        if !slowest.is_empty() {
+            out.push_str(
+                "<h2>Slowest steps</h2>\n<table>\n<tr><th>Step</th><th>Details</th><th>Duration</th></tr>\n",
+            );
+            for span in slowest {
+                let _ = writeln!(
+                    out,
+                    "<tr><td><code>{}</code></td><td><code>{}</code></td><td>{}</td></tr>",
+                    escape(&span.name),
+                    escape(&span.detail),
+                    format_ms(span.duration_ms)
+                );
+            }
+            out.push_str("</table>\n");
+        }
+
+        for (i, stage) in self.stages.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "<h2>Turn {} — {} <small>({})</small></h2>",
+                i + 1,
+                format_ms(stage.duration_ms),
+                escape(&stage.model)
+            );
+            if !stage.request.is_empty() {
+                let _ = writeln!(out, "<blockquote>{}</blockquote>", escape(&stage.request));
+            }
+            if let Some(error) = &stage.error {
+                let _ = writeln!(out, "<p class=\"failed\">Failed: {}</p>", escape(error));
+            }
+            if stage.spans.is_empty() {
+                continue;
+            }
+            out.push_str(
+                "<table>\n<tr><th>Step</th><th>Details</th><th>Time</th><th>Duration</th><th>Tokens</th></tr>\n",
+            );
+            let begin = stage.offset_ms.unwrap_or(0);
+            let length = stage.duration_ms.unwrap_or(0).max(1) as f64;
+            for span in &stage.spans {
+                let class = match (&span.error, span.kind) {
+                    (Some(_), _) => "error",
+                    (None, SpanKind::Llm) => "llm",
+                    (None, SpanKind::Tool) => "tool",
+                };
+                let bar = match (span.offset_ms, span.duration_ms) {
+                    (Some(offset), Some(duration)) => {
+                        let left = offset.saturating_sub(begin) as f64 / length * 100.0;
+                        let width = duration as f64 / length * 100.0;
+                        format!(
+                            "<div class=\"bar {class}\" style=\"margin-left:{:.1}%;width:{:.1}%\"></div>",
+                            left.min(100.0),
+                            width.min(100.0 - left.min(100.0))
+                        )
+                    }
+                    _ => String::new(),
+                };
+                let name = match span.kind {
+                    SpanKind::Llm => "LLM".to_string(),
+                    SpanKind::Tool => format!("<code>{}</code>", escape(&span.name)),
+                };
+                let mut detail = escape(&span.detail);
+                if let Some(error) = &span.error {
+                    let _ = write!(detail, " <span class=\"failed\">{}</span>", escape(error));
+                }
+                let tokens = match (span.input_tokens, span.output_tokens) {
+                    (None, None) => String::new(),
+                    (input, output) => {
+                        format!("{} / {}", input.unwrap_or(0), output.unwrap_or(0))
+                    }
+                };
+                let _ = writeln!(
+                    out,
+                    "<tr><td>{name}</td><td>{detail}</td><td class=\"track\">{bar}</td><td>{}</td><td>{tokens}</td></tr>",
+                    format_ms(span.duration_ms)
+                );
+            }
+            out.push_str("</table>\n");
+        }
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+}
+
+/// When a call that ended at `end` after `duration` milliseconds started.
+fn span_start(end: Option<u64>, duration: Option<u64>) -> Option<u64> {
+    Some(end?.saturating_sub(duration.unwrap_or(0)))
+}
+
+/// `ms` as e.g. `850 ms`, `12.3 s` or `14m 02s`.
+fn format_ms(ms: Option<u64>) -> String {
+    match ms {
+        None => "unknown".to_string(),
+        Some(ms) if ms < 1_000 => format!("{ms} ms"),
+        Some(ms) if ms < 60_000 => format!("{:.1} s", ms as f64 / 1_000.0),
+        Some(ms) => format!("{}m {:02}s", ms / 60_000, ms % 60_000 / 1_000),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::ChatMessage;
+    use crate::tools::traits::ToolResult;
+    use serde_json::json;
+
+    fn build_session() -> Vec<RecordedEvent> {
+        let llm = |at: &str, duration_ms: u64| RecordedEvent::LlmResponse {
+            text: None,
+            tool_calls: Vec::new(),
+            reasoning_content: None,
+            input_tokens: Some(1_000),
+            output_tokens: Some(100),
+            error: None,
+            at: Some(at.into()),
+            duration_ms: Some(duration_ms),
+        };
+        vec![
+            RecordedEvent::Session {
+                id: "1a2b3c4d".into(),
+                started_at: "2026-10-17T10:00:00+00:00".into(),
+                channel: "telegram".into(),
+                tools: Vec::new(),
+            },
+            RecordedEvent::Turn {
+                provider: "openrouter".into(),
+                model: "anthropic/claude-sonnet-4".into(),
+                temperature: 0.7,
+                max_tool_iterations: 10,
+                excluded_tools: Vec::new(),
+                history: vec![ChatMessage::user("Build a todo app")],
+                at: Some("2026-10-17T10:00:01+00:00".into()),
+            },
+            llm("2026-10-17T10:00:05+00:00", 4_000),
+            RecordedEvent::ToolCall {
+                name: "factory_build".into(),
+                args: json!({"idea": "todo app"}),
+                result: Some(ToolResult {
+                    success: true,
+                    output: "Built".into(),
+                    error: None,
+                    error_code: None,
+                    error_hint: None,
+                }),
+                error: None,
+                at: Some("2026-10-17T10:14:05+00:00".into()),
+                duration_ms: Some(840_000),
+            },
+            RecordedEvent::ToolCall {
+                name: "github_push".into(),
+                args: json!({"repo": "alice/todo"}),
+                result: None,
+                error: Some("GitHub is not connected".into()),
+                at: Some("2026-10-17T10:14:06+00:00".into()),
+                duration_ms: Some(1_000),
+            },
+            llm("2026-10-17T10:14:10+00:00", 4_000),
+            RecordedEvent::TurnEnd {
+                response: Some("Built, but the push failed.".into()),
+                error: None,
+                at: Some("2026-10-17T10:14:11+00:00".into()),
+            },
+        ]
+    }
+
+    #[test]
+    fn timeline_places_calls_and_totals_time_and_tokens() {
+        let timeline = SessionTimeline::from_events(&build_session());
+
+        assert_eq!(timeline.duration_ms, Some(851_000));
+        assert_eq!(timeline.llm_ms, 8_000);
+        assert_eq!(timeline.tool_ms, 841_000);
+        assert_eq!(
+            (timeline.input_tokens, timeline.output_tokens),
+            (2_000, 200)
+        );
+        assert_eq!(timeline.errors, 1);
+
+        let stage = &timeline.stages[0];
+        assert_eq!(stage.request, "Build a todo app");
+        assert_eq!(stage.model, "openrouter/anthropic/claude-sonnet-4");
+        assert_eq!(
+            (stage.offset_ms, stage.duration_ms),
+            (Some(1_000), Some(850_000))
+        );
+        let offsets: Vec<Option<u64>> = stage.spans.iter().map(|s| s.offset_ms).collect();
+        assert_eq!(
+            offsets,
+            [Some(1_000), Some(5_000), Some(845_000), Some(846_000)]
+        );
+        assert_eq!(stage.spans[1].detail, "todo app");
+        assert_eq!(
+            stage.spans[2].error.as_deref(),
+            Some("GitHub is not connected")
+        );
+        assert_eq!(timeline.slowest(1)[0].name, "factory_build");
+
+        let html = timeline.to_html();
+        assert!(html.contains("<b>Duration:</b> 14m 11s"));
+        assert!(html.contains(
+            "<td><code>factory_build</code></td><td><code>todo app</code></td><td>14m 00s</td>"
+        ));
+        assert!(html.contains("class=\"bar error\""));
+    }
+
+    #[test]
+    fn untimed_recordings_have_no_offsets() {
+        let events: Vec<RecordedEvent> = build_session()
+            .into_iter()
+            .map(|event| match event {
+                RecordedEvent::ToolCall {
+                    name,
+                    args,
+                    result,
+                    error,
+                    ..
+                } => RecordedEvent::ToolCall {
+                    name,
+                    args,
+                    result,
+                    error,
+                    at: None,
+                    duration_ms: None,
+                },
+                event => event,
+            })
+            .collect();
+        let timeline = SessionTimeline::from_events(&events);
+
+        assert_eq!(timeline.tool_ms, 0);
+        assert_eq!(timeline.stages[0].spans[1].offset_ms, None);
+        assert_eq!(timeline.stages[0].duration_ms, Some(850_000));
+    }
+}
@@ -28,6 +28,8 @@ pub enum ObserverEvent {
         error_message: Option<String>,
         input_tokens: Option<u64>,
         output_tokens: Option<u64>,
+        /// Prompt tokens served from the provider's prompt cache.
+        cached_input_tokens: Option<u64>,
     },
     /// The agent session has finished.
     ///
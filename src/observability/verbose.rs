@@ -89,6 +89,7 @@ mod tests {
             error_message: None,
             input_tokens: Some(50),
             output_tokens: Some(25),
+            cached_input_tokens: None,
         });
         obs.record_event(&ObserverEvent::ToolCallStart {
             tool: "shell".into(),
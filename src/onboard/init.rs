@@ -0,0 +1,427 @@
+//! `zerobuild init` — guided first-run setup.
+//!
+//! Checks the machine (Docker, E2B), tests the provider key with a real
+//! request, optionally connects GitHub, picks a default project template and
+//! writes a config that is read back and validated before setup reports
+//! success. The gateway's setup page drives the same steps through
+//! `/api/init`.
+
+use super::wizard::{
+    default_model_for_provider, ensure_onboard_overwrite_allowed,
+    memory_config_defaults_for_backend, persist_workspace_selection, print_bullet, print_step,
+    print_summary, run_github_oauth_flow, scaffold_workspace, setup_provider, setup_workspace,
+    suggested_fallback_providers, ProjectContext, BANNER,
+};
+use crate::config::Config;
+use crate::sandbox::PROJECT_TEMPLATES;
+use anyhow::{bail, Context, Result};
+use console::style;
+use dialoguer::{Confirm, Select};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+
+const DOCKER_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+const PROVIDER_CHECK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Outcome of one setup check.
+#[derive(Debug, Clone, Serialize)]
+pub struct InitCheck {
+    pub name: String,
+    pub ok: bool,
+    pub detail: String,
+}
+
+impl InitCheck {
+    fn new(name: &str, ok: bool, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            ok,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Choices collected by the CLI flow or posted by the setup page.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct InitAnswers {
+    pub provider: String,
+    #[serde(default)]
+    pub api_key: Option<String>,
+    #[serde(default)]
+    pub api_url: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub template: Option<String>,
+    #[serde(default)]
+    pub use_docker: bool,
+}
+
+/// Whether `program` can reach a running Docker daemon.
+async fn docker_check(program: &str) -> InitCheck {
+    let output = tokio::time::timeout(
+        DOCKER_CHECK_TIMEOUT,
+        tokio::process::Command::new(program)
+            .args(["info", "--format", "{{.ServerVersion}}"])
+            .output(),
+    )
+    .await;
+    match output {
+        Ok(Ok(output)) if output.status.success() => {
+            let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            InitCheck::new("docker", true, format!("daemon running (server {version})"))
+        }
+        Ok(Ok(_)) => InitCheck::new(
+            "docker",
+            false,
+            "installed, but the daemon is not reachable — start Docker to use the docker runtime",
+        ),
+        Ok(Err(_)) => InitCheck::new("docker", false, "not installed"),
+        Err(_) => InitCheck::new("docker", false, "`docker info` timed out"),
+    }
+}
+
+/// Docker availability, used to offer `runtime.kind = "docker"`.
+pub async fn detect_docker() -> InitCheck {
+    docker_check("docker").await
+}
+
+/// E2B availability. Sandboxes run as local processes, so E2B is optional.
+pub fn detect_e2b() -> InitCheck {
+    let has_key = std::env::var("E2B_API_KEY").is_ok_and(|key| !key.trim().is_empty());
+    if has_key {
+        InitCheck::new(
+            "e2b",
+            true,
+            "E2B_API_KEY is set; sandboxes still run as local processes",
+        )
+    } else {
+        InitCheck::new(
+            "e2b",
+            false,
+            "E2B_API_KEY not set — not needed, sandboxes run as local processes",
+        )
+    }
+}
+
+/// Environment checks shown before any question is asked.
+pub async fn detect_environment() -> Vec<InitCheck> {
+    vec![detect_docker().await, detect_e2b()]
+}
+
+/// Send one short request to `provider` to prove the key and model work.
+pub async fn test_provider_key(
+    provider: &str,
+    api_key: Option<&str>,
+    api_url: Option<&str>,
+    model: &str,
+) -> InitCheck {
+    let api_key = api_key.map(str::trim).filter(|key| !key.is_empty());
+    let client = match crate::providers::create_provider_with_url(provider, api_key, api_url) {
+        Ok(client) => client,
+        Err(e) => return InitCheck::new("provider", false, format!("{e:#}")),
+    };
+    let reply = tokio::time::timeout(
+        PROVIDER_CHECK_TIMEOUT,
+        client.chat_with_system(None, "Reply with the single word OK.", model, 0.0),
+    )
+    .await;
+    match reply {
+        Ok(Ok(_)) => InitCheck::new(
+            "provider",
+            true,
+            format!("{provider} answered with {model}"),
+        ),
+        Ok(Err(e)) => InitCheck::new("provider", false, format!("{e:#}")),
+        Err(_) => InitCheck::new(
+            "provider",
+            false,
+            format!("no answer within {}s", PROVIDER_CHECK_TIMEOUT.as_secs()),
+        ),
+    }
+}
+
+/// Whether a GitHub token is stored in the ZeroBuild database.
+pub fn github_connected(config: &Config) -> bool {
+    let db_path = PathBuf::from(&config.zerobuild.db_path);
+    if !db_path.exists() {
+        return false;
+    }
+    crate::store::init_db(&db_path)
+        .and_then(|conn| crate::store::tokens::load_github_token(&conn))
+        .is_ok_and(|token| token.is_some())
+}
+
+/// Apply `answers` to `config`.
+pub fn apply_answers(config: &mut Config, answers: &InitAnswers) -> Result<()> {
+    let provider = answers.provider.trim();
+    if provider.is_empty() {
+        bail!("A provider is required");
+    }
+    if config.default_provider.as_deref() != Some(provider) {
+        config.reliability.fallback_providers = suggested_fallback_providers(provider);
+    }
+    config.default_provider = Some(provider.to_string());
+    config.default_model = Some(
+        answers
+            .model
+            .as_deref()
+            .map(str::trim)
+            .filter(|model| !model.is_empty())
+            .map_or_else(|| default_model_for_provider(provider), str::to_string),
+    );
+    if let Some(key) = answers.api_key.as_deref().map(str::trim) {
+        if !key.is_empty() {
+            config.api_key = Some(key.to_string());
+        }
+    }
+    if let Some(url) = &answers.api_url {
+        let url = url.trim();
+        config.api_url = (!url.is_empty()).then(|| url.to_string());
+    }
+    if let Some(template) = &answers.template {
+        config.zerobuild.default_template = template.trim().to_string();
+    }
+    config.runtime.kind = if answers.use_docker {
+        "docker".to_string()
+    } else {
+        "native".to_string()
+    };
+    config.validate()
+}
+
+/// Save `config`, then load it back from disk and validate it.
+pub async fn write_validated(config: &Config) -> Result<Config> {
+    config
+        .validate()
+        .context("Refusing to write an invalid config")?;
+    config.save().await?;
+    Config::load_from_file(&config.config_path, config.workspace_dir.clone())
+        .await
+        .with_context(|| {
+            format!(
+                "Wrote {} but it did not load back cleanly",
+                config.config_path.display()
+            )
+        })
+}
+
+fn print_check(check: &InitCheck) {
+    let mark = if check.ok {
+        style("✓").green().bold()
+    } else {
+        style("·").yellow().bold()
+    };
+    println!(
+        "  {mark} {:<9} {}",
+        style(&check.name).white().bold(),
+        style(&check.detail).dim()
+    );
+}
+
+/// Interactive `zerobuild init`.
+#[allow(clippy::too_many_lines)]
+pub async fn run_init(force: bool) -> Result<Config> {
+    println!("{}", style(BANNER).cyan().bold());
+    println!(
+        "  {}",
+        style("Setup — checks your machine, tests your keys and writes a working config.")
+            .white()
+            .bold()
+    );
+    println!();
+
+    print_step(1, 5, "Environment");
+    let checks = detect_environment().await;
+    for check in &checks {
+        print_check(check);
+    }
+    let docker_ready = checks.iter().any(|c| c.name == "docker" && c.ok);
+    println!();
+
+    print_step(2, 5, "Workspace");
+    let (workspace_dir, config_path) = setup_workspace().await?;
+    ensure_onboard_overwrite_allowed(&config_path, force)?;
+
+    print_step(3, 5, "AI Provider & API Key");
+    let (provider, api_key, model, api_url) = loop {
+        let (provider, api_key, model, api_url) = setup_provider(&workspace_dir).await?;
+        print_bullet("Testing the provider with a one-word request...");
+        let check = test_provider_key(&provider, Some(&api_key), api_url.as_deref(), &model).await;
+        print_check(&check);
+        if check.ok {
+            break (provider, api_key, model, api_url);
+        }
+        let choice = Select::new()
+            .with_prompt("  The provider check failed")
+            .items(["Choose the provider again", "Keep these settings anyway"])
+            .default(0)
+            .interact()?;
+        if choice == 1 {
+            break (provider, api_key, model, api_url);
+        }
+    };
+
+    let mut config = Config {
+        workspace_dir: workspace_dir.clone(),
+        config_path,
+        memory: memory_config_defaults_for_backend(crate::memory::default_memory_backend_key()),
+        ..Config::default()
+    };
+    config.reliability.provider_retries = 2;
+    config.reliability.provider_backoff_ms = 500;
+
+    print_step(4, 5, "GitHub");
+    if github_connected(&config) {
+        println!(
+            "  {} GitHub is already connected",
+            style("✓").green().bold()
+        );
+    } else if Confirm::new()
+        .with_prompt("  Connect GitHub now? (can be done later via chat)")
+        .default(false)
+        .interact()?
+    {
+        match run_github_oauth_flow(&config.zerobuild).await {
+            Ok(()) => println!("  {} GitHub connected", style("✓").green().bold()),
+            Err(e) => println!(
+                "  {} {}",
+                style("⚠").yellow().bold(),
+                style(format!("Could not connect GitHub: {e}")).yellow()
+            ),
+        }
+    }
+
+    print_step(5, 5, "Default Project Template");
+    let mut items: Vec<String> = PROJECT_TEMPLATES
+        .iter()
+        .map(|(name, description)| format!("{name} — {description}"))
+        .collect();
+    items.push("No default — let the agent choose per project".to_string());
+    let choice = Select::new()
+        .with_prompt("  Template for new projects")
+        .items(&items)
+        .default(0)
+        .interact()?;
+    let template = PROJECT_TEMPLATES
+        .get(choice)
+        .map_or(String::new(), |(name, _)| (*name).to_string());
+
+    let use_docker = docker_ready
+        && Confirm::new()
+            .with_prompt("  Docker is running. Run shell commands in Docker containers?")
+            .default(false)
+            .interact()?;
+
+    apply_answers(
+        &mut config,
+        &InitAnswers {
+            provider,
+            api_key: Some(api_key),
+            api_url,
+            model: Some(model),
+            template: Some(template),
+            use_docker,
+        },
+    )?;
+    let config = write_validated(&config).await?;
+    persist_workspace_selection(&config.config_path).await?;
+
+    let ctx = ProjectContext {
+        user_name: std::env::var("USER").unwrap_or_else(|_| "User".into()),
+        timezone: "UTC".into(),
+        agent_name: "ZeroBuild".into(),
+        communication_style:
+            "Be warm, natural, and clear. Use occasional relevant emojis (1-2 max) and avoid robotic phrasing."
+                .into(),
+    };
+    scaffold_workspace(&workspace_dir, &ctx).await?;
+
+    println!(
+        "  {} Config written and validated: {}",
+        style("✓").green().bold(),
+        style(config.config_path.display()).green()
+    );
+    print_summary(&config);
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn config_in(tmp: &TempDir) -> Config {
+        Config {
+            workspace_dir: tmp.path().join("workspace"),
+            config_path: tmp.path().join("config.toml"),
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn apply_answers_sets_provider_template_and_runtime() {
+        let tmp = TempDir::new().unwrap();
+        let mut config = config_in(&tmp);
+        apply_answers(
+            &mut config,
+            &InitAnswers {
+                provider: "anthropic".into(),
+                api_key: Some(" sk-test ".into()),
+                template: Some("vite-react".into()),
+                use_docker: true,
+                ..InitAnswers::default()
+            },
+        )
+        .unwrap();
+
+        assert_eq!(config.default_provider.as_deref(), Some("anthropic"));
+        assert_eq!(
+            config.default_model,
+            Some(default_model_for_provider("anthropic"))
+        );
+        assert_eq!(config.api_key.as_deref(), Some("sk-test"));
+        assert_eq!(config.zerobuild.default_template, "vite-react");
+        assert_eq!(config.runtime.kind, "docker");
+
+        let err = apply_answers(
+            &mut config,
+            &InitAnswers {
+                provider: "anthropic".into(),
+                template: Some("rails".into()),
+                ..InitAnswers::default()
+            },
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("nextjs"));
+    }
+
+    #[tokio::test]
+    async fn write_validated_round_trips_through_disk() {
+        let tmp = TempDir::new().unwrap();
+        let mut config = config_in(&tmp);
+        apply_answers(
+            &mut config,
+            &InitAnswers {
+                provider: "ollama".into(),
+                model: Some("llama3.2".into()),
+                template: Some("static".into()),
+                ..InitAnswers::default()
+            },
+        )
+        .unwrap();
+
+        let loaded = write_validated(&config).await.unwrap();
+        assert_eq!(loaded.zerobuild.default_template, "static");
+        assert_eq!(loaded.runtime.kind, "native");
+        assert!(tmp.path().join("config.toml").exists());
+    }
+
+    #[tokio::test]
+    async fn missing_docker_binary_is_reported() {
+        let check = docker_check("zerobuild-no-such-docker").await;
+        assert!(!check.ok);
+        assert_eq!(check.detail, "not installed");
+    }
+}
@@ -1,7 +1,10 @@
+pub mod init;
 pub mod wizard;
 
 // Re-exported for CLI and external use
 #[allow(unused_imports)]
+pub use init::run_init;
+#[allow(unused_imports)]
 pub use wizard::{run_channels_repair_wizard, run_models_refresh, run_quick_setup, run_wizard};
 
 #[cfg(test)]
@@ -16,5 +19,6 @@ mod tests {
         assert_reexport_exists(run_channels_repair_wizard);
         assert_reexport_exists(run_quick_setup);
         assert_reexport_exists(run_models_refresh);
+        assert_reexport_exists(run_init);
     }
 }
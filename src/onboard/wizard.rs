@@ -46,7 +46,7 @@ pub struct ProjectContext {
 
 // ── Banner ───────────────────────────────────────────────────────
 
-const BANNER: &str = r"
+pub(super) const BANNER: &str = r"
     ⚡⚡⚡⚡⚡⚡⚡⚡⚡⚡⚡⚡⚡⚡⚡⚡⚡⚡⚡⚡⚡⚡⚡⚡⚡⚡⚡⚡⚡⚡⚡⚡⚡⚡⚡⚡
 
     ███████╗███████╗██████╗  ██████╗ ██████╗ ██╗   ██╗██╗██╗     ██████╗
@@ -154,6 +154,8 @@ pub async fn run_wizard(force: bool) -> Result<Config> {
         default_model: Some(model),
         default_temperature: 0.7,
         observability: ObservabilityConfig::default(),
+        logging: crate::config::LoggingConfig::default(),
+        local_provider: crate::config::LocalProviderConfig::default(),
         autonomy: AutonomyConfig::default(),
         runtime: RuntimeConfig::default(),
         reliability: reliability_config,
@@ -174,6 +176,7 @@ pub async fn run_wizard(force: bool) -> Result<Config> {
         browser: BrowserConfig::default(),
         http_request: crate::config::HttpRequestConfig::default(),
         multimodal: crate::config::MultimodalConfig::default(),
+        i18n: crate::config::I18nConfig::default(),
         web_search: crate::config::WebSearchConfig::default(),
         proxy: crate::config::ProxyConfig::default(),
         identity: crate::config::IdentityConfig::default(),
@@ -187,6 +190,10 @@ pub async fn run_wizard(force: bool) -> Result<Config> {
         sop: crate::config::SopConfig::default(),
         factory: crate::config::FactoryConfig::default(),
         zerobuild: zerobuild_config,
+        guardrails: crate::config::GuardrailsConfig::default(),
+        users: crate::config::UsersConfig::default(),
+        moderation: crate::config::ModerationConfig::default(),
+        profiles: std::collections::HashMap::new(),
     };
 
     println!(
@@ -379,7 +386,7 @@ fn backend_key_from_choice(choice: usize) -> &'static str {
         .map_or(default_memory_backend_key(), |backend| backend.key)
 }
 
-fn memory_config_defaults_for_backend(backend: &str) -> MemoryConfig {
+pub(super) fn memory_config_defaults_for_backend(backend: &str) -> MemoryConfig {
     let profile = memory_backend_profile(backend);
 
     MemoryConfig {
@@ -408,6 +415,12 @@ fn memory_config_defaults_for_backend(backend: &str) -> MemoryConfig {
         snapshot_on_hygiene: false,
         auto_hydrate: true,
         sqlite_open_timeout_secs: None,
+        max_resident_users: 128,
+        user_max_entries: 500,
+        user_max_bytes: 8 * 1024 * 1024,
+        user_eviction_policy: crate::config::MemoryEvictionPolicy::Oldest,
+        retention: crate::config::MemoryRetentionConfig::default(),
+        consolidation: crate::config::MemoryConsolidationConfig::default(),
     }
 }
 
@@ -515,6 +528,8 @@ async fn run_quick_setup_with_home(
         default_model: Some(model.clone()),
         default_temperature: 0.7,
         observability: ObservabilityConfig::default(),
+        logging: crate::config::LoggingConfig::default(),
+        local_provider: crate::config::LocalProviderConfig::default(),
         autonomy: AutonomyConfig::default(),
         runtime: RuntimeConfig::default(),
         reliability: reliability_config,
@@ -535,6 +550,7 @@ async fn run_quick_setup_with_home(
         browser: BrowserConfig::default(),
         http_request: crate::config::HttpRequestConfig::default(),
         multimodal: crate::config::MultimodalConfig::default(),
+        i18n: crate::config::I18nConfig::default(),
         web_search: crate::config::WebSearchConfig::default(),
         proxy: crate::config::ProxyConfig::default(),
         identity: crate::config::IdentityConfig::default(),
@@ -548,6 +564,10 @@ async fn run_quick_setup_with_home(
         sop: crate::config::SopConfig::default(),
         factory: crate::config::FactoryConfig::default(),
         zerobuild: crate::config::ZerobuildConfig::default(),
+        guardrails: crate::config::GuardrailsConfig::default(),
+        users: crate::config::UsersConfig::default(),
+        moderation: crate::config::ModerationConfig::default(),
+        profiles: std::collections::HashMap::new(),
     };
 
     config.save().await?;
@@ -691,7 +711,7 @@ const MINIMAX_ONBOARD_MODELS: [(&str, &str); 5] = [
     ("MiniMax-M2", "MiniMax M2 (legacy)"),
 ];
 
-fn default_model_for_provider(provider: &str) -> String {
+pub(crate) fn default_model_for_provider(provider: &str) -> String {
     match canonical_provider_name(provider) {
         "anthropic" => "claude-sonnet-4-5-20250929".into(),
         "openai" => "gpt-5.2".into(),
@@ -1776,7 +1796,7 @@ pub async fn run_models_refresh(
 
 // ── Step helpers ─────────────────────────────────────────────────
 
-fn print_step(current: u8, total: u8, title: &str) {
+pub(super) fn print_step(current: u8, total: u8, title: &str) {
     println!();
     println!(
         "  {} {}",
@@ -1786,7 +1806,7 @@ fn print_step(current: u8, total: u8, title: &str) {
     println!("  {}", style("─".repeat(50)).dim());
 }
 
-fn print_bullet(text: &str) {
+pub(super) fn print_bullet(text: &str) {
     println!("  {} {}", style("›").cyan(), text);
 }
 
@@ -1836,7 +1856,7 @@ fn resolve_interactive_onboarding_mode(
     }
 }
 
-fn ensure_onboard_overwrite_allowed(config_path: &Path, force: bool) -> Result<()> {
+pub(super) fn ensure_onboard_overwrite_allowed(config_path: &Path, force: bool) -> Result<()> {
     if !config_path.exists() {
         return Ok(());
     }
@@ -1872,7 +1892,7 @@ fn ensure_onboard_overwrite_allowed(config_path: &Path, force: bool) -> Result<(
     Ok(())
 }
 
-async fn persist_workspace_selection(config_path: &Path) -> Result<()> {
+pub(super) async fn persist_workspace_selection(config_path: &Path) -> Result<()> {
     let config_dir = config_path
         .parent()
         .context("Config path must have a parent directory")?;
@@ -1888,7 +1908,7 @@ async fn persist_workspace_selection(config_path: &Path) -> Result<()> {
 
 // ── Step 1: Workspace ────────────────────────────────────────────
 
-async fn setup_workspace() -> Result<(PathBuf, PathBuf)> {
+pub(super) async fn setup_workspace() -> Result<(PathBuf, PathBuf)> {
     // Debug: Show which env vars are affecting the path
     if let Ok(val) = std::env::var("ZEROBUILD_CONFIG_DIR") {
         print_bullet(&format!(
@@ -1976,7 +1996,9 @@ async fn setup_workspace() -> Result<(PathBuf, PathBuf)> {
 // ── Step 2: Provider & API Key ───────────────────────────────────
 
 #[allow(clippy::too_many_lines)]
-async fn setup_provider(workspace_dir: &Path) -> Result<(String, String, String, Option<String>)> {
+pub(super) async fn setup_provider(
+    workspace_dir: &Path,
+) -> Result<(String, String, String, Option<String>)> {
     // ── Tier selection ──
     let tiers = vec![
         "⭐ Recommended (OpenRouter, Venice, Anthropic, OpenAI, Gemini)",
@@ -2713,7 +2735,7 @@ fn local_provider_choices() -> Vec<(&'static str, &'static str)> {
 /// Suggest fallback providers based on the primary provider selection.
 /// This ensures that if the primary provider fails, ZeroBuild can automatically
 /// failover to alternative providers.
-fn suggested_fallback_providers(primary_provider: &str) -> Vec<String> {
+pub(super) fn suggested_fallback_providers(primary_provider: &str) -> Vec<String> {
     let canonical = canonical_provider_name(primary_provider);
 
     match canonical {
@@ -3065,7 +3087,7 @@ async fn setup_connectors() -> Result<crate::config::ZerobuildConfig> {
 }
 
 /// Run GitHub OAuth flow during onboarding
-async fn run_github_oauth_flow(config: &crate::config::ZerobuildConfig) -> Result<()> {
+pub(super) async fn run_github_oauth_flow(config: &crate::config::ZerobuildConfig) -> Result<()> {
     use std::sync::atomic::{AtomicBool, Ordering};
 
     // Find an available port
@@ -3886,6 +3908,7 @@ fn setup_channels() -> Result<ChannelsConfig> {
                     allowed_users,
                     listen_to_bots: false,
                     mention_only: false,
+                    stream_mode: crate::config::StreamMode::default(),
                 });
             }
             ChannelMenuChoice::Slack => {
@@ -4343,6 +4366,8 @@ fn setup_channels() -> Result<ChannelsConfig> {
                         pair_code: (!pair_code.trim().is_empty())
                             .then(|| pair_code.trim().to_string()),
                         allowed_numbers,
+                        reply_template: None,
+                        reply_template_language: None,
                     });
 
                     println!(
@@ -4444,6 +4469,8 @@ fn setup_channels() -> Result<ChannelsConfig> {
                     pair_phone: None,
                     pair_code: None,
                     allowed_numbers,
+                    reply_template: None,
+                    reply_template_language: None,
                 });
             }
             ChannelMenuChoice::Linq => {
@@ -5378,7 +5405,7 @@ fn setup_tunnel() -> Result<crate::config::TunnelConfig> {
 // ── Step 6: Scaffold workspace files ─────────────────────────────
 
 #[allow(clippy::too_many_lines)]
-async fn scaffold_workspace(workspace_dir: &Path, ctx: &ProjectContext) -> Result<()> {
+pub(super) async fn scaffold_workspace(workspace_dir: &Path, ctx: &ProjectContext) -> Result<()> {
     let agent = if ctx.agent_name.is_empty() {
         "ZeroBuild"
     } else {
@@ -5656,7 +5683,7 @@ async fn scaffold_workspace(workspace_dir: &Path, ctx: &ProjectContext) -> Resul
 // ── Final summary ────────────────────────────────────────────────
 
 #[allow(clippy::too_many_lines)]
-fn print_summary(config: &Config) {
+pub(super) fn print_summary(config: &Config) {
     let has_channels = has_launchable_channels(&config.channels_config);
 
     println!();
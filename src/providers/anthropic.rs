@@ -1,3 +1,4 @@
+use crate::providers::sampling;
 use crate::providers::traits::{
     ChatMessage, ChatRequest as ProviderChatRequest, ChatResponse as ProviderChatResponse,
     Provider, TokenUsage, ToolCall as ProviderToolCall,
@@ -10,6 +11,7 @@ use serde::{Deserialize, Serialize};
 pub struct AnthropicProvider {
     credential: Option<String>,
     base_url: String,
+    prompt_caching: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -20,6 +22,8 @@ struct ChatRequest {
     system: Option<String>,
     messages: Vec<Message>,
     temperature: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f64>,
 }
 
 #[derive(Debug, Serialize)]
@@ -50,6 +54,8 @@ struct NativeChatRequest<'a> {
     messages: Vec<NativeMessage>,
     temperature: f64,
     #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     tools: Option<Vec<NativeToolSpec<'a>>>,
 }
 
@@ -138,6 +144,8 @@ struct AnthropicUsage {
     input_tokens: Option<u64>,
     #[serde(default)]
     output_tokens: Option<u64>,
+    #[serde(default)]
+    cache_read_input_tokens: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -170,9 +178,17 @@ impl AnthropicProvider {
                 .filter(|k| !k.is_empty())
                 .map(ToString::to_string),
             base_url,
+            prompt_caching: true,
         }
     }
 
+    /// Enable or disable `cache_control` breakpoints on the system prompt,
+    /// tool definitions and conversation tail (enabled by default).
+    pub fn with_prompt_caching(mut self, enabled: bool) -> Self {
+        self.prompt_caching = enabled;
+        self
+    }
+
     fn is_setup_token(token: &str) -> bool {
         token.starts_with("sk-ant-oat01-")
     }
@@ -216,6 +232,21 @@ impl AnthropicProvider {
         }
     }
 
+    /// Remove the cache breakpoints `convert_messages`/`convert_tools` add.
+    fn strip_cache_control(
+        system: &mut Option<SystemPrompt>,
+        tools: Option<&mut [NativeToolSpec<'_>]>,
+    ) {
+        if let Some(SystemPrompt::Blocks(blocks)) = system {
+            for block in blocks {
+                block.cache_control = None;
+            }
+        }
+        for tool in tools.into_iter().flatten() {
+            tool.cache_control = None;
+        }
+    }
+
     fn convert_tools<'a>(tools: Option<&'a [ToolSpec]>) -> Option<Vec<NativeToolSpec<'a>>> {
         let items = tools?;
         if items.is_empty() {
@@ -375,6 +406,7 @@ impl AnthropicProvider {
         let usage = response.usage.map(|u| TokenUsage {
             input_tokens: u.input_tokens,
             output_tokens: u.output_tokens,
+            cached_input_tokens: u.cache_read_input_tokens,
         });
 
         for block in response.content {
@@ -445,6 +477,7 @@ impl Provider for AnthropicProvider {
                 content: message.to_string(),
             }],
             temperature,
+            top_p: sampling::current().top_p,
         };
 
         let mut request = self
@@ -478,11 +511,16 @@ impl Provider for AnthropicProvider {
             )
         })?;
 
-        let (system_prompt, mut messages) = Self::convert_messages(request.messages);
+        let (mut system_prompt, mut messages) = Self::convert_messages(request.messages);
+        let mut tools = Self::convert_tools(request.tools);
 
-        // Auto-cache last message if conversation is long
-        if Self::should_cache_conversation(request.messages) {
-            Self::apply_cache_to_last_message(&mut messages);
+        if self.prompt_caching {
+            // Auto-cache last message if conversation is long
+            if Self::should_cache_conversation(request.messages) {
+                Self::apply_cache_to_last_message(&mut messages);
+            }
+        } else {
+            Self::strip_cache_control(&mut system_prompt, tools.as_deref_mut());
         }
 
         let native_request = NativeChatRequest {
@@ -491,7 +529,8 @@ impl Provider for AnthropicProvider {
             system: system_prompt,
             messages,
             temperature,
-            tools: Self::convert_tools(request.tools),
+            top_p: sampling::current().top_p,
+            tools,
         };
 
         let req = self
@@ -725,6 +764,7 @@ mod tests {
                 content: "hello".to_string(),
             }],
             temperature: 0.7,
+            top_p: None,
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(
@@ -746,6 +786,7 @@ mod tests {
                 content: "hello".to_string(),
             }],
             temperature: 0.7,
+            top_p: None,
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(json.contains("\"system\":\"You are ZeroBuild\""));
@@ -786,6 +827,7 @@ mod tests {
                 system: None,
                 messages: vec![],
                 temperature: temp,
+                top_p: None,
             };
             let json = serde_json::to_string(&req).unwrap();
             assert!(json.contains(&format!("{temp}")));
@@ -1155,6 +1197,7 @@ mod tests {
                 }],
             }],
             temperature: 0.7,
+            top_p: None,
             tools: None,
         };
 
@@ -1245,6 +1288,7 @@ mod tests {
         let provider = AnthropicProvider {
             credential: Some("test-key".to_string()),
             base_url: format!("http://{addr}"),
+            prompt_caching: true,
         };
 
         // Multi-turn conversation: system → user (Go code) → assistant (code response) → user (follow-up)
@@ -1346,6 +1390,45 @@ mod tests {
         assert_eq!(usage.output_tokens, Some(75));
     }
 
+    #[test]
+    fn native_response_parses_cache_read_tokens() {
+        let json = r#"{
+            "content": [{"type": "text", "text": "Hello"}],
+            "usage": {"input_tokens": 40, "output_tokens": 10, "cache_read_input_tokens": 2048}
+        }"#;
+        let resp: NativeChatResponse = serde_json::from_str(json).unwrap();
+        let usage = AnthropicProvider::parse_native_response(resp)
+            .usage
+            .unwrap();
+        assert_eq!(usage.cached_input_tokens, Some(2048));
+    }
+
+    #[test]
+    fn strip_cache_control_clears_system_and_tool_breakpoints() {
+        let messages = vec![
+            ChatMessage::system("x".repeat(4000)),
+            ChatMessage::user("hi"),
+        ];
+        let (mut system, _) = AnthropicProvider::convert_messages(&messages);
+        let specs = vec![ToolSpec {
+            name: "shell".into(),
+            description: "Run".into(),
+            parameters: serde_json::json!({"type": "object"}),
+        }];
+        let mut tools = AnthropicProvider::convert_tools(Some(&specs));
+        assert!(serde_json::to_string(&system)
+            .unwrap()
+            .contains("cache_control"));
+
+        AnthropicProvider::strip_cache_control(&mut system, tools.as_deref_mut());
+        assert!(!serde_json::to_string(&system)
+            .unwrap()
+            .contains("cache_control"));
+        assert!(!serde_json::to_string(&tools)
+            .unwrap()
+            .contains("cache_control"));
+    }
+
     #[test]
     fn native_response_parses_without_usage() {
         let json = r#"{"content": [{"type": "text", "text": "Hello"}]}"#;
@@ -3,10 +3,11 @@
 //! This module provides a single implementation that works for all of them.
 
 use crate::multimodal;
+use crate::providers::sampling::{self, SamplingParams};
 use crate::providers::traits::{
     ChatMessage, ChatRequest as ProviderChatRequest, ChatResponse as ProviderChatResponse,
-    Provider, StreamChunk, StreamError, StreamOptions, StreamResult, TokenUsage,
-    ToolCall as ProviderToolCall,
+    PromptTokensDetails, Provider, StreamChunk, StreamError, StreamOptions, StreamResult,
+    TokenUsage, ToolCall as ProviderToolCall, ToolCallDelta,
 };
 use async_trait::async_trait;
 use futures_util::{stream, StreamExt};
@@ -306,14 +307,32 @@ struct ApiChatRequest {
     model: String,
     messages: Vec<Message>,
     temperature: f64,
+    #[serde(flatten)]
+    sampling: SamplingParams,
     #[serde(skip_serializing_if = "Option::is_none")]
     stream: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<StreamUsageOptions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     tools: Option<Vec<serde_json::Value>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tool_choice: Option<String>,
 }
 
+/// `stream_options` asking for a final usage chunk on streamed requests.
+#[derive(Debug, Serialize)]
+struct StreamUsageOptions {
+    include_usage: bool,
+}
+
+impl StreamUsageOptions {
+    fn for_stream(stream: bool) -> Option<Self> {
+        stream.then_some(Self {
+            include_usage: true,
+        })
+    }
+}
+
 #[derive(Debug, Serialize)]
 struct Message {
     role: String,
@@ -352,6 +371,8 @@ struct UsageInfo {
     prompt_tokens: Option<u64>,
     #[serde(default)]
     completion_tokens: Option<u64>,
+    #[serde(default)]
+    prompt_tokens_details: Option<PromptTokensDetails>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -498,9 +519,13 @@ struct NativeChatRequest {
     model: String,
     messages: Vec<NativeMessage>,
     temperature: f64,
+    #[serde(flatten)]
+    sampling: SamplingParams,
     #[serde(skip_serializing_if = "Option::is_none")]
     stream: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<StreamUsageOptions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     tools: Option<Vec<serde_json::Value>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tool_choice: Option<String>,
@@ -570,7 +595,11 @@ struct ResponsesContent {
 /// Server-Sent Event stream chunk for OpenAI-compatible streaming.
 #[derive(Debug, Deserialize)]
 struct StreamChunkResponse {
+    #[serde(default)]
     choices: Vec<StreamChoice>,
+    /// Sent on the last chunk when `stream_options.include_usage` is set.
+    #[serde(default)]
+    usage: Option<UsageInfo>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -586,11 +615,34 @@ struct StreamDelta {
     /// Reasoning/thinking models may stream output via `reasoning_content`.
     #[serde(default)]
     reasoning_content: Option<String>,
+    #[serde(default)]
+    tool_calls: Option<Vec<StreamToolCall>>,
+}
+
+/// Streamed tool-call fragment: `id` and `function.name` come with the first
+/// fragment of a call, `function.arguments` is split across fragments.
+#[derive(Debug, Deserialize)]
+struct StreamToolCall {
+    #[serde(default)]
+    index: usize,
+    #[serde(default)]
+    id: Option<String>,
+    #[serde(default)]
+    function: Option<StreamFunction>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamFunction {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    arguments: Option<String>,
 }
 
 /// Parse SSE (Server-Sent Events) stream from OpenAI-compatible providers.
-/// Handles the `data: {...}` format and `[DONE]` sentinel.
-fn parse_sse_line(line: &str) -> StreamResult<Option<String>> {
+/// Handles the `data: {...}` format and `[DONE]` sentinel. Returns the text,
+/// tool-call fragments and usage of one event, or `None` when it has none.
+fn parse_sse_line(line: &str) -> StreamResult<Option<StreamChunk>> {
     let line = line.trim();
 
     // Skip empty lines and comments
@@ -599,35 +651,59 @@ fn parse_sse_line(line: &str) -> StreamResult<Option<String>> {
     }
 
     // SSE format: "data: {...}"
-    if let Some(data) = line.strip_prefix("data:") {
-        let data = data.trim();
-
-        // Check for [DONE] sentinel
-        if data == "[DONE]" {
-            return Ok(None);
-        }
+    let Some(data) = line.strip_prefix("data:") else {
+        return Ok(None);
+    };
+    let data = data.trim();
 
-        // Parse JSON delta
-        let chunk: StreamChunkResponse = serde_json::from_str(data).map_err(StreamError::Json)?;
+    // Check for [DONE] sentinel
+    if data == "[DONE]" {
+        return Ok(None);
+    }
 
-        // Extract content from delta
-        if let Some(choice) = chunk.choices.first() {
-            if let Some(content) = &choice.delta.content {
-                if !content.is_empty() {
-                    return Ok(Some(content.clone()));
+    // Parse JSON delta
+    let response: StreamChunkResponse = serde_json::from_str(data).map_err(StreamError::Json)?;
+    let mut chunk = StreamChunk::delta("").with_usage(response.usage.map(|u| TokenUsage {
+        input_tokens: u.prompt_tokens,
+        output_tokens: u.completion_tokens,
+        cached_input_tokens: u.prompt_tokens_details.and_then(|d| d.cached_tokens),
+    }));
+
+    if let Some(choice) = response.choices.into_iter().next() {
+        let delta = choice.delta;
+        // Fall back to reasoning_content for thinking models
+        chunk.delta = delta
+            .content
+            .filter(|content| !content.is_empty())
+            .or(delta.reasoning_content)
+            .unwrap_or_default();
+        chunk.tool_calls = delta
+            .tool_calls
+            .unwrap_or_default()
+            .into_iter()
+            .map(|call| {
+                let (name, arguments) = call
+                    .function
+                    .map(|f| (f.name, f.arguments.unwrap_or_default()))
+                    .unwrap_or_default();
+                ToolCallDelta {
+                    index: call.index,
+                    id: call.id,
+                    name,
+                    arguments,
                 }
-            }
-            // Fallback to reasoning_content for thinking models
-            if let Some(reasoning) = &choice.delta.reasoning_content {
-                return Ok(Some(reasoning.clone()));
-            }
-        }
+            })
+            .collect();
     }
 
-    Ok(None)
+    if chunk.delta.is_empty() && chunk.tool_calls.is_empty() && chunk.usage.is_none() {
+        return Ok(None);
+    }
+    Ok(Some(chunk))
 }
 
-/// Convert SSE byte stream to text chunks.
+/// Convert SSE byte stream to chunks. Usage reported by the provider is
+/// carried on the final chunk.
 fn sse_bytes_to_chunks(
     response: reqwest::Response,
     count_tokens: bool,
@@ -636,8 +712,10 @@ fn sse_bytes_to_chunks(
     let (tx, rx) = tokio::sync::mpsc::channel::<StreamResult<StreamChunk>>(100);
 
     tokio::spawn(async move {
-        // Buffer for incomplete lines
-        let mut buffer = String::new();
+        // Buffer for incomplete lines; kept as bytes so a UTF-8 character
+        // split across network reads is decoded once it is complete.
+        let mut buffer = Vec::new();
+        let mut usage = None;
 
         // Get response body as bytes stream
         match response.error_for_status_ref() {
@@ -651,56 +729,56 @@ fn sse_bytes_to_chunks(
         let mut bytes_stream = response.bytes_stream();
 
         while let Some(item) = bytes_stream.next().await {
-            match item {
-                Ok(bytes) => {
-                    // Convert bytes to string and process line by line
-                    let text = match String::from_utf8(bytes.to_vec()) {
-                        Ok(t) => t,
-                        Err(e) => {
-                            let _ = tx
-                                .send(Err(StreamError::InvalidSse(format!(
-                                    "Invalid UTF-8: {}",
-                                    e
-                                ))))
-                                .await;
-                            break;
-                        }
-                    };
-
-                    buffer.push_str(&text);
-
-                    // Process complete lines
-                    while let Some(pos) = buffer.find('\n') {
-                        let line = buffer.drain(..=pos).collect::<String>();
-                        buffer = buffer[pos + 1..].to_string();
-
-                        match parse_sse_line(&line) {
-                            Ok(Some(content)) => {
-                                let mut chunk = StreamChunk::delta(content);
-                                if count_tokens {
-                                    chunk = chunk.with_token_estimate();
-                                }
-                                if tx.send(Ok(chunk)).await.is_err() {
-                                    return; // Receiver dropped
-                                }
-                            }
-                            Ok(None) => {}
-                            Err(e) => {
-                                let _ = tx.send(Err(e)).await;
-                                return;
-                            }
-                        }
-                    }
-                }
+            let bytes = match item {
+                Ok(bytes) => bytes,
                 Err(e) => {
                     let _ = tx.send(Err(StreamError::Http(e))).await;
                     break;
                 }
+            };
+            buffer.extend_from_slice(&bytes);
+
+            // Process complete lines
+            while let Some(pos) = buffer.iter().position(|b| *b == b'\n') {
+                let line = buffer.drain(..=pos).collect::<Vec<_>>();
+                let line = match String::from_utf8(line) {
+                    Ok(line) => line,
+                    Err(e) => {
+                        let _ = tx
+                            .send(Err(StreamError::InvalidSse(format!("Invalid UTF-8: {e}"))))
+                            .await;
+                        return;
+                    }
+                };
+
+                match parse_sse_line(&line) {
+                    Ok(Some(mut chunk)) => {
+                        if let Some(reported) = chunk.usage.take() {
+                            usage = Some(reported);
+                        }
+                        if chunk.delta.is_empty() && chunk.tool_calls.is_empty() {
+                            continue;
+                        }
+                        if count_tokens {
+                            chunk = chunk.with_token_estimate();
+                        }
+                        if tx.send(Ok(chunk)).await.is_err() {
+                            return; // Receiver dropped
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        let _ = tx.send(Err(e)).await;
+                        return;
+                    }
+                }
             }
         }
 
         // Send final chunk
-        let _ = tx.send(Ok(StreamChunk::final_chunk())).await;
+        let _ = tx
+            .send(Ok(StreamChunk::final_chunk().with_usage(usage)))
+            .await;
     });
 
     // Convert channel receiver to stream
@@ -1084,6 +1162,109 @@ impl OpenAiCompatibleProvider {
         .iter()
         .any(|hint| lower.contains(hint))
     }
+
+    /// Send a streaming chat completion request and expose the SSE body as
+    /// a chunk stream.
+    fn stream_messages(
+        &self,
+        messages: Vec<Message>,
+        model: &str,
+        temperature: f64,
+        options: StreamOptions,
+    ) -> stream::BoxStream<'static, StreamResult<StreamChunk>> {
+        let request = ApiChatRequest {
+            model: model.to_string(),
+            messages,
+            temperature,
+            sampling: sampling::current(),
+            stream: Some(options.enabled),
+            stream_options: StreamUsageOptions::for_stream(options.enabled),
+            tools: None,
+            tool_choice: None,
+        };
+        self.stream_request(request, options)
+    }
+
+    /// POST a streaming `request` body to the chat completions endpoint.
+    fn stream_request<R: Serialize + Send + 'static>(
+        &self,
+        request: R,
+        options: StreamOptions,
+    ) -> stream::BoxStream<'static, StreamResult<StreamChunk>> {
+        let credential = match self.credential.as_ref() {
+            Some(value) => value.clone(),
+            None => {
+                let provider_name = self.name.clone();
+                return stream::once(async move {
+                    Err(StreamError::Provider(format!(
+                        "{} API key not set",
+                        provider_name
+                    )))
+                })
+                .boxed();
+            }
+        };
+
+        let url = self.chat_completions_url();
+        let client = self.http_client();
+        let auth_header = self.auth_header.clone();
+
+        // Use a channel to bridge the async HTTP response to the stream
+        let (tx, rx) = tokio::sync::mpsc::channel::<StreamResult<StreamChunk>>(100);
+
+        tokio::spawn(async move {
+            // Build request with auth
+            let mut req_builder = client.post(&url).json(&request);
+
+            // Apply auth header
+            req_builder = match &auth_header {
+                AuthStyle::Bearer => {
+                    req_builder.header("Authorization", format!("Bearer {}", credential))
+                }
+                AuthStyle::XApiKey => req_builder.header("x-api-key", &credential),
+                AuthStyle::Custom(header) => req_builder.header(header, &credential),
+            };
+
+            // Set accept header for streaming
+            req_builder = req_builder.header("Accept", "text/event-stream");
+
+            // Send request
+            let response = match req_builder.send().await {
+                Ok(r) => r,
+                Err(e) => {
+                    let _ = tx.send(Err(StreamError::Http(e))).await;
+                    return;
+                }
+            };
+
+            // Check status
+            if !response.status().is_success() {
+                let status = response.status();
+                let error = match response.text().await {
+                    Ok(e) => e,
+                    Err(_) => format!("HTTP error: {}", status),
+                };
+                let _ = tx
+                    .send(Err(StreamError::Provider(format!("{}: {}", status, error))))
+                    .await;
+                return;
+            }
+
+            // Convert to chunk stream and forward to channel
+            let mut chunk_stream = sse_bytes_to_chunks(response, options.count_tokens);
+            while let Some(chunk) = chunk_stream.next().await {
+                if tx.send(chunk).await.is_err() {
+                    break; // Receiver dropped
+                }
+            }
+        });
+
+        // Convert channel receiver to stream
+        stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|chunk| (chunk, rx))
+        })
+        .boxed()
+    }
 }
 
 #[async_trait]
@@ -1092,6 +1273,7 @@ impl Provider for OpenAiCompatibleProvider {
         crate::providers::traits::ProviderCapabilities {
             native_tool_calling: true,
             vision: self.supports_vision,
+            max_context_tokens: None,
         }
     }
 
@@ -1137,7 +1319,9 @@ impl Provider for OpenAiCompatibleProvider {
             model: model.to_string(),
             messages,
             temperature,
+            sampling: sampling::current(),
             stream: Some(false),
+            stream_options: None,
             tools: None,
             tool_choice: None,
         };
@@ -1255,7 +1439,9 @@ impl Provider for OpenAiCompatibleProvider {
             model: model.to_string(),
             messages: api_messages,
             temperature,
+            sampling: sampling::current(),
             stream: Some(false),
+            stream_options: None,
             tools: None,
             tool_choice: None,
         };
@@ -1361,7 +1547,9 @@ impl Provider for OpenAiCompatibleProvider {
             model: model.to_string(),
             messages: api_messages,
             temperature,
+            sampling: sampling::current(),
             stream: Some(false),
+            stream_options: None,
             tools: if tools.is_empty() {
                 None
             } else {
@@ -1405,6 +1593,7 @@ impl Provider for OpenAiCompatibleProvider {
         let usage = chat_response.usage.map(|u| TokenUsage {
             input_tokens: u.prompt_tokens,
             output_tokens: u.completion_tokens,
+            cached_input_tokens: u.prompt_tokens_details.and_then(|d| d.cached_tokens),
         });
         let choice = chat_response
             .choices
@@ -1462,7 +1651,9 @@ impl Provider for OpenAiCompatibleProvider {
             model: model.to_string(),
             messages: Self::convert_messages_for_native(&effective_messages),
             temperature,
+            sampling: sampling::current(),
             stream: Some(false),
+            stream_options: None,
             tool_choice: tools.as_ref().map(|_| "auto".to_string()),
             tools,
             reasoning: None,
@@ -1546,6 +1737,7 @@ impl Provider for OpenAiCompatibleProvider {
         let usage = native_response.usage.map(|u| TokenUsage {
             input_tokens: u.prompt_tokens,
             output_tokens: u.completion_tokens,
+            cached_input_tokens: u.prompt_tokens_details.and_then(|d| d.cached_tokens),
         });
         let message = native_response
             .choices
@@ -1575,20 +1767,6 @@ impl Provider for OpenAiCompatibleProvider {
         temperature: f64,
         options: StreamOptions,
     ) -> stream::BoxStream<'static, StreamResult<StreamChunk>> {
-        let credential = match self.credential.as_ref() {
-            Some(value) => value.clone(),
-            None => {
-                let provider_name = self.name.clone();
-                return stream::once(async move {
-                    Err(StreamError::Provider(format!(
-                        "{} API key not set",
-                        provider_name
-                    )))
-                })
-                .boxed();
-            }
-        };
-
         let mut messages = Vec::new();
         if let Some(sys) = system_prompt {
             messages.push(Message {
@@ -1601,74 +1779,64 @@ impl Provider for OpenAiCompatibleProvider {
             content: Self::to_message_content("user", message),
         });
 
-        let request = ApiChatRequest {
-            model: model.to_string(),
-            messages,
-            temperature,
-            stream: Some(options.enabled),
-            tools: None,
-            tool_choice: None,
-        };
-
-        let url = self.chat_completions_url();
-        let client = self.http_client();
-        let auth_header = self.auth_header.clone();
-
-        // Use a channel to bridge the async HTTP response to the stream
-        let (tx, rx) = tokio::sync::mpsc::channel::<StreamResult<StreamChunk>>(100);
-
-        tokio::spawn(async move {
-            // Build request with auth
-            let mut req_builder = client.post(&url).json(&request);
-
-            // Apply auth header
-            req_builder = match &auth_header {
-                AuthStyle::Bearer => {
-                    req_builder.header("Authorization", format!("Bearer {}", credential))
-                }
-                AuthStyle::XApiKey => req_builder.header("x-api-key", &credential),
-                AuthStyle::Custom(header) => req_builder.header(header, &credential),
-            };
+        self.stream_messages(messages, model, temperature, options)
+    }
 
-            // Set accept header for streaming
-            req_builder = req_builder.header("Accept", "text/event-stream");
+    fn stream_chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+        options: StreamOptions,
+    ) -> stream::BoxStream<'static, StreamResult<StreamChunk>> {
+        let effective_messages = if self.merge_system_into_user {
+            Self::flatten_system_messages(messages)
+        } else {
+            messages.to_vec()
+        };
+        let api_messages = effective_messages
+            .iter()
+            .map(|m| Message {
+                role: m.role.clone(),
+                content: Self::to_message_content(&m.role, &m.content),
+            })
+            .collect();
 
-            // Send request
-            let response = match req_builder.send().await {
-                Ok(r) => r,
-                Err(e) => {
-                    let _ = tx.send(Err(StreamError::Http(e))).await;
-                    return;
-                }
-            };
+        self.stream_messages(api_messages, model, temperature, options)
+    }
 
-            // Check status
-            if !response.status().is_success() {
-                let status = response.status();
-                let error = match response.text().await {
-                    Ok(e) => e,
-                    Err(_) => format!("HTTP error: {}", status),
-                };
-                let _ = tx
-                    .send(Err(StreamError::Provider(format!("{}: {}", status, error))))
-                    .await;
-                return;
-            }
+    fn supports_streaming_tool_calls(&self) -> bool {
+        true
+    }
 
-            // Convert to chunk stream and forward to channel
-            let mut chunk_stream = sse_bytes_to_chunks(response, options.count_tokens);
-            while let Some(chunk) = chunk_stream.next().await {
-                if tx.send(chunk).await.is_err() {
-                    break; // Receiver dropped
-                }
-            }
-        });
+    fn stream_chat_with_tools(
+        &self,
+        messages: &[ChatMessage],
+        tools: &[crate::tools::ToolSpec],
+        model: &str,
+        temperature: f64,
+        options: StreamOptions,
+    ) -> stream::BoxStream<'static, StreamResult<StreamChunk>> {
+        // Same request as `chat`, streamed
+        let tools = Self::convert_tool_specs(Some(tools));
+        let effective_messages = if self.merge_system_into_user {
+            Self::flatten_system_messages(messages)
+        } else {
+            messages.to_vec()
+        };
+        let request = NativeChatRequest {
+            model: model.to_string(),
+            messages: Self::convert_messages_for_native(&effective_messages),
+            temperature,
+            sampling: sampling::current(),
+            stream: Some(options.enabled),
+            stream_options: StreamUsageOptions::for_stream(options.enabled),
+            tool_choice: tools.as_ref().map(|_| "auto".to_string()),
+            tools,
+            reasoning: None,
+        };
 
-        // Convert channel receiver to stream
-        stream::unfold(rx, |mut rx| async move {
-            rx.recv().await.map(|chunk| (chunk, rx))
-        })
-        .boxed()
+        self.stream_request(request, options)
     }
 
     async fn warmup(&self) -> anyhow::Result<()> {
@@ -1746,7 +1914,9 @@ mod tests {
                 },
             ],
             temperature: 0.4,
+            sampling: SamplingParams::default(),
             stream: Some(false),
+            stream_options: None,
             tools: None,
             tool_choice: None,
         };
@@ -2371,7 +2541,9 @@ mod tests {
                 content: MessageContent::Text("What is the weather?".to_string()),
             }],
             temperature: 0.7,
+            sampling: SamplingParams::default(),
             stream: Some(false),
+            stream_options: None,
             tools: Some(tools),
             tool_choice: Some("auto".to_string()),
         };
@@ -2621,21 +2793,21 @@ mod tests {
     #[test]
     fn parse_sse_line_with_content() {
         let line = r#"data: {"choices":[{"delta":{"content":"hello"}}]}"#;
-        let result = parse_sse_line(line).unwrap();
+        let result = parse_sse_line(line).unwrap().map(|chunk| chunk.delta);
         assert_eq!(result, Some("hello".to_string()));
     }
 
     #[test]
     fn parse_sse_line_with_reasoning_content() {
         let line = r#"data: {"choices":[{"delta":{"reasoning_content":"thinking..."}}]}"#;
-        let result = parse_sse_line(line).unwrap();
+        let result = parse_sse_line(line).unwrap().map(|chunk| chunk.delta);
         assert_eq!(result, Some("thinking...".to_string()));
     }
 
     #[test]
     fn parse_sse_line_with_both_prefers_content() {
         let line = r#"data: {"choices":[{"delta":{"content":"real answer","reasoning_content":"thinking..."}}]}"#;
-        let result = parse_sse_line(line).unwrap();
+        let result = parse_sse_line(line).unwrap().map(|chunk| chunk.delta);
         assert_eq!(result, Some("real answer".to_string()));
     }
 
@@ -2643,7 +2815,7 @@ mod tests {
     fn parse_sse_line_with_empty_content_falls_back_to_reasoning_content() {
         let line =
             r#"data: {"choices":[{"delta":{"content":"","reasoning_content":"thinking..."}}]}"#;
-        let result = parse_sse_line(line).unwrap();
+        let result = parse_sse_line(line).unwrap().map(|chunk| chunk.delta);
         assert_eq!(result, Some("thinking...".to_string()));
     }
 
@@ -2651,7 +2823,31 @@ mod tests {
     fn parse_sse_line_done_sentinel() {
         let line = "data: [DONE]";
         let result = parse_sse_line(line).unwrap();
-        assert_eq!(result, None);
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn parse_sse_line_with_tool_call_fragment() {
+        let line = r#"data: {"choices":[{"delta":{"tool_calls":[{"index":1,"id":"call_1","function":{"name":"shell","arguments":"{\"comm"}}]}}]}"#;
+        let chunk = parse_sse_line(line).unwrap().unwrap();
+        assert!(chunk.delta.is_empty());
+        assert_eq!(
+            chunk.tool_calls,
+            vec![ToolCallDelta {
+                index: 1,
+                id: Some("call_1".into()),
+                name: Some("shell".into()),
+                arguments: "{\"comm".into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_sse_line_with_usage_only_chunk() {
+        let line = r#"data: {"choices":[],"usage":{"prompt_tokens":12,"completion_tokens":5}}"#;
+        let usage = parse_sse_line(line).unwrap().unwrap().usage.unwrap();
+        assert_eq!(usage.input_tokens, Some(12));
+        assert_eq!(usage.output_tokens, Some(5));
     }
 
     #[test]
@@ -2666,6 +2862,17 @@ mod tests {
         assert_eq!(usage.completion_tokens, Some(60));
     }
 
+    #[test]
+    fn api_response_parses_cached_prompt_tokens() {
+        let json = r#"{
+            "choices": [{"message": {"content": "Hello"}}],
+            "usage": {"prompt_tokens": 1500, "completion_tokens": 60, "prompt_tokens_details": {"cached_tokens": 1280}}
+        }"#;
+        let resp: ApiChatResponse = serde_json::from_str(json).unwrap();
+        let details = resp.usage.unwrap().prompt_tokens_details.unwrap();
+        assert_eq!(details.cached_tokens, Some(1280));
+    }
+
     #[test]
     fn api_response_parses_without_usage() {
         let json = r#"{"choices": [{"message": {"content": "Hello"}}]}"#;
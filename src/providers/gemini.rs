@@ -5,6 +5,7 @@
 //! - Google Cloud ADC (`GOOGLE_APPLICATION_CREDENTIALS`)
 
 use crate::auth::AuthService;
+use crate::providers::sampling;
 use crate::providers::traits::{ChatMessage, ChatResponse, Provider, TokenUsage};
 use async_trait::async_trait;
 use directories::UserDirs;
@@ -143,6 +144,10 @@ struct GenerationConfig {
     temperature: f64,
     #[serde(rename = "maxOutputTokens")]
     max_output_tokens: u32,
+    #[serde(rename = "topP", skip_serializing_if = "Option::is_none")]
+    top_p: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -932,12 +937,15 @@ impl GeminiProvider {
             _ => (None, None),
         };
 
+        let sampling = sampling::current();
         let request = GenerateContentRequest {
             contents,
             system_instruction,
             generation_config: GenerationConfig {
                 temperature,
                 max_output_tokens: 8192,
+                top_p: sampling.top_p,
+                seed: sampling.seed,
             },
         };
 
@@ -1080,6 +1088,7 @@ impl GeminiProvider {
         let usage = result.usage_metadata.map(|u| TokenUsage {
             input_tokens: u.prompt_token_count,
             output_tokens: u.candidates_token_count,
+            cached_input_tokens: None,
         });
 
         let text = result
@@ -1400,6 +1409,8 @@ mod tests {
             generation_config: GenerationConfig {
                 temperature: 0.7,
                 max_output_tokens: 8192,
+                top_p: None,
+                seed: None,
             },
         };
 
@@ -1441,6 +1452,8 @@ mod tests {
             generation_config: GenerationConfig {
                 temperature: 0.7,
                 max_output_tokens: 8192,
+                top_p: None,
+                seed: None,
             },
         };
 
@@ -1485,6 +1498,8 @@ mod tests {
             generation_config: GenerationConfig {
                 temperature: 0.7,
                 max_output_tokens: 8192,
+                top_p: None,
+                seed: None,
             },
         };
 
@@ -1522,6 +1537,8 @@ mod tests {
             generation_config: GenerationConfig {
                 temperature: 0.7,
                 max_output_tokens: 8192,
+                top_p: None,
+                seed: None,
             },
         };
 
@@ -1551,6 +1568,8 @@ mod tests {
                 generation_config: Some(GenerationConfig {
                     temperature: 0.7,
                     max_output_tokens: 8192,
+                    top_p: None,
+                    seed: None,
                 }),
             },
         };
@@ -0,0 +1,320 @@
+//! Local model servers (Ollama, llama.cpp, LM Studio, vLLM, ...).
+//!
+//! What a local server supports depends on the model it has loaded rather
+//! than on the server software, so [`LocalProvider`] wraps the backend and
+//! reports the capabilities set in `[local_provider]`. When native tool
+//! calling is switched off, tools are described in the system prompt instead,
+//! which lets models without function calling run the full agent pipeline.
+
+use super::traits::{
+    build_tool_instructions_text, ChatMessage, ChatRequest, ChatResponse, ProviderCapabilities,
+    StreamChunk, StreamOptions, StreamResult, ToolsPayload,
+};
+use super::Provider;
+use crate::config::LocalProviderConfig;
+use crate::tools::ToolSpec;
+use async_trait::async_trait;
+use futures_util::stream;
+
+/// Endpoint used by the `local` provider when `api_url` is unset (Ollama's
+/// OpenAI-compatible API).
+pub const DEFAULT_LOCAL_URL: &str = "http://localhost:11434/v1";
+
+/// Whether `name` (canonical or alias) runs on the user's machine.
+pub fn is_local_provider(name: &str) -> bool {
+    super::list_providers()
+        .iter()
+        .any(|info| info.local && (info.name == name || info.aliases.contains(&name)))
+}
+
+/// Wrap `provider` with `[local_provider]` capability overrides when `name`
+/// is a local backend and any override is set.
+pub fn apply_overrides(
+    name: &str,
+    provider: Box<dyn Provider>,
+    config: &LocalProviderConfig,
+) -> Box<dyn Provider> {
+    if *config == LocalProviderConfig::default() || !is_local_provider(name) {
+        return provider;
+    }
+    Box::new(LocalProvider::new(provider, config))
+}
+
+/// A local backend with capabilities taken from `[local_provider]`.
+pub struct LocalProvider {
+    inner: Box<dyn Provider>,
+    capabilities: ProviderCapabilities,
+}
+
+impl LocalProvider {
+    pub fn new(inner: Box<dyn Provider>, config: &LocalProviderConfig) -> Self {
+        let capabilities = ProviderCapabilities {
+            native_tool_calling: config
+                .tool_calling
+                .unwrap_or_else(|| inner.supports_native_tools()),
+            vision: config.vision.unwrap_or_else(|| inner.supports_vision()),
+            max_context_tokens: config
+                .max_context_tokens
+                .or_else(|| inner.capabilities().max_context_tokens),
+        };
+        Self {
+            inner,
+            capabilities,
+        }
+    }
+}
+
+#[async_trait]
+impl Provider for LocalProvider {
+    fn capabilities(&self) -> ProviderCapabilities {
+        self.capabilities.clone()
+    }
+
+    fn convert_tools(&self, tools: &[ToolSpec]) -> ToolsPayload {
+        if self.capabilities.native_tool_calling {
+            self.inner.convert_tools(tools)
+        } else {
+            ToolsPayload::PromptGuided {
+                instructions: build_tool_instructions_text(tools),
+            }
+        }
+    }
+
+    async fn chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        self.inner
+            .chat_with_system(system_prompt, message, model, temperature)
+            .await
+    }
+
+    async fn chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        self.inner
+            .chat_with_history(messages, model, temperature)
+            .await
+    }
+
+    async fn chat(
+        &self,
+        request: ChatRequest<'_>,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatResponse> {
+        let tools = match request.tools {
+            Some(tools) if !tools.is_empty() && !self.capabilities.native_tool_calling => tools,
+            _ => return self.inner.chat(request, model, temperature).await,
+        };
+
+        let instructions = build_tool_instructions_text(tools);
+        let mut messages = request.messages.to_vec();
+        if let Some(system) = messages.iter_mut().find(|m| m.role == "system") {
+            if !system.content.is_empty() {
+                system.content.push_str("\n\n");
+            }
+            system.content.push_str(&instructions);
+        } else {
+            messages.insert(0, ChatMessage::system(instructions));
+        }
+        self.inner
+            .chat(
+                ChatRequest {
+                    messages: &messages,
+                    tools: None,
+                },
+                model,
+                temperature,
+            )
+            .await
+    }
+
+    async fn chat_with_tools(
+        &self,
+        messages: &[ChatMessage],
+        tools: &[serde_json::Value],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatResponse> {
+        if self.capabilities.native_tool_calling {
+            return self
+                .inner
+                .chat_with_tools(messages, tools, model, temperature)
+                .await;
+        }
+        let text = self
+            .inner
+            .chat_with_history(messages, model, temperature)
+            .await?;
+        Ok(ChatResponse {
+            text: Some(text),
+            tool_calls: Vec::new(),
+            usage: None,
+            reasoning_content: None,
+        })
+    }
+
+    async fn warmup(&self) -> anyhow::Result<()> {
+        self.inner.warmup().await
+    }
+
+    fn supports_streaming(&self) -> bool {
+        self.inner.supports_streaming()
+    }
+
+    fn stream_chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        temperature: f64,
+        options: StreamOptions,
+    ) -> stream::BoxStream<'static, StreamResult<StreamChunk>> {
+        self.inner
+            .stream_chat_with_system(system_prompt, message, model, temperature, options)
+    }
+
+    fn stream_chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+        options: StreamOptions,
+    ) -> stream::BoxStream<'static, StreamResult<StreamChunk>> {
+        self.inner
+            .stream_chat_with_history(messages, model, temperature, options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parking_lot::Mutex;
+    use std::sync::Arc;
+
+    #[derive(Default)]
+    struct NativeMock {
+        seen: Arc<Mutex<Vec<(usize, bool)>>>,
+    }
+
+    #[async_trait]
+    impl Provider for NativeMock {
+        fn capabilities(&self) -> ProviderCapabilities {
+            ProviderCapabilities {
+                native_tool_calling: true,
+                vision: true,
+                max_context_tokens: None,
+            }
+        }
+
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            Ok("ok".into())
+        }
+
+        async fn chat(
+            &self,
+            request: ChatRequest<'_>,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<ChatResponse> {
+            let system_len = request
+                .messages
+                .iter()
+                .find(|m| m.role == "system")
+                .map_or(0, |m| m.content.len());
+            self.seen.lock().push((system_len, request.tools.is_some()));
+            Ok(ChatResponse {
+                text: Some("ok".into()),
+                tool_calls: Vec::new(),
+                usage: None,
+                reasoning_content: None,
+            })
+        }
+    }
+
+    fn tool_spec() -> ToolSpec {
+        ToolSpec {
+            name: "shell".into(),
+            description: "Run a command".into(),
+            parameters: serde_json::json!({"type": "object"}),
+        }
+    }
+
+    #[test]
+    fn local_provider_names_include_aliases() {
+        assert!(is_local_provider("local"));
+        assert!(is_local_provider("ollama"));
+        assert!(is_local_provider("llama.cpp"));
+        assert!(is_local_provider("lm-studio"));
+        assert!(!is_local_provider("openrouter"));
+    }
+
+    #[test]
+    fn overrides_apply_only_to_local_providers_with_settings() {
+        let config = LocalProviderConfig {
+            tool_calling: Some(false),
+            vision: None,
+            max_context_tokens: Some(8192),
+        };
+
+        let cloud = apply_overrides("openrouter", Box::new(NativeMock::default()), &config);
+        assert!(cloud.supports_native_tools());
+
+        let unset = apply_overrides(
+            "ollama",
+            Box::new(NativeMock::default()),
+            &LocalProviderConfig::default(),
+        );
+        assert!(unset.supports_native_tools());
+
+        let local = apply_overrides("ollama", Box::new(NativeMock::default()), &config);
+        let caps = local.capabilities();
+        assert!(!caps.native_tool_calling);
+        assert!(caps.vision, "unset flags keep the backend default");
+        assert_eq!(caps.max_context_tokens, Some(8192));
+    }
+
+    #[tokio::test]
+    async fn tools_move_into_system_prompt_without_native_tool_calling() {
+        let mock = NativeMock::default();
+        let seen = Arc::clone(&mock.seen);
+        let provider = LocalProvider::new(
+            Box::new(mock),
+            &LocalProviderConfig {
+                tool_calling: Some(false),
+                ..LocalProviderConfig::default()
+            },
+        );
+        let tools = [tool_spec()];
+        let messages = [ChatMessage::system("be brief"), ChatMessage::user("hi")];
+
+        provider
+            .chat(
+                ChatRequest {
+                    messages: &messages,
+                    tools: Some(&tools),
+                },
+                "llama3",
+                0.7,
+            )
+            .await
+            .unwrap();
+
+        let (system_len, sent_tools) = seen.lock()[0];
+        assert!(!sent_tools);
+        assert!(system_len > "be brief".len());
+    }
+}
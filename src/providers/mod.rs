@@ -24,13 +24,16 @@ pub mod copilot;
 pub mod cursor;
 pub mod gemini;
 pub mod health;
+pub mod local;
 pub mod ollama;
 pub mod openai;
 pub mod openai_codex;
 pub mod openrouter;
 pub mod reliable;
 pub mod router;
+pub mod sampling;
 pub mod telnyx;
+pub mod throttle;
 pub mod traits;
 
 #[allow(unused_imports)]
@@ -40,6 +43,7 @@ pub use traits::{
 };
 
 use crate::auth::AuthService;
+use crate::config::LocalProviderConfig;
 use compatible::{AuthStyle, OpenAiCompatibleProvider};
 use reliable::ReliableProvider;
 use serde::Deserialize;
@@ -678,6 +682,8 @@ pub struct ProviderRuntimeOptions {
     pub zerobuild_dir: Option<PathBuf>,
     pub secrets_encrypt: bool,
     pub reasoning_enabled: Option<bool>,
+    pub local_provider: LocalProviderConfig,
+    pub prompt_caching: bool,
 }
 
 impl Default for ProviderRuntimeOptions {
@@ -687,6 +693,8 @@ impl Default for ProviderRuntimeOptions {
             zerobuild_dir: None,
             secrets_encrypt: true,
             reasoning_enabled: None,
+            local_provider: LocalProviderConfig::default(),
+            prompt_caching: true,
         }
     }
 }
@@ -934,12 +942,26 @@ pub fn create_provider_with_url(
 }
 
 /// Factory: create provider with optional base URL and runtime options.
-#[allow(clippy::too_many_lines)]
 fn create_provider_with_url_and_options(
     name: &str,
     api_key: Option<&str>,
     api_url: Option<&str>,
     options: &ProviderRuntimeOptions,
+) -> anyhow::Result<Box<dyn Provider>> {
+    let provider = create_backend_provider(name, api_key, api_url, options)?;
+    Ok(local::apply_overrides(
+        name,
+        provider,
+        &options.local_provider,
+    ))
+}
+
+#[allow(clippy::too_many_lines)]
+fn create_backend_provider(
+    name: &str,
+    api_key: Option<&str>,
+    api_url: Option<&str>,
+    options: &ProviderRuntimeOptions,
 ) -> anyhow::Result<Box<dyn Provider>> {
     let qwen_oauth_context = is_qwen_oauth_alias(name).then(|| resolve_qwen_oauth_context(api_key));
 
@@ -957,14 +979,33 @@ fn create_provider_with_url_and_options(
     match name {
         // ── Primary providers (custom implementations) ───────
         "openrouter" => Ok(Box::new(openrouter::OpenRouterProvider::new(key))),
-        "anthropic" => Ok(Box::new(anthropic::AnthropicProvider::new(key))),
+        "anthropic" => Ok(Box::new(
+            anthropic::AnthropicProvider::new(key).with_prompt_caching(options.prompt_caching),
+        )),
         "openai" => Ok(Box::new(openai::OpenAiProvider::with_base_url(api_url, key))),
         // Ollama uses api_url for custom base URL (e.g. remote Ollama instance)
-        "ollama" => Ok(Box::new(ollama::OllamaProvider::new_with_reasoning(
-            api_url,
-            key,
-            options.reasoning_enabled,
-        ))),
+        "ollama" => Ok(Box::new(
+            ollama::OllamaProvider::new_with_reasoning(api_url, key, options.reasoning_enabled)
+                .with_num_ctx(options.local_provider.max_context_tokens),
+        )),
+        // Any OpenAI-compatible server on the user's machine; defaults to
+        // Ollama's `/v1` endpoint.
+        "local" => {
+            let base_url = api_url
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .unwrap_or(local::DEFAULT_LOCAL_URL);
+            let local_key = key
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .unwrap_or("local");
+            Ok(Box::new(OpenAiCompatibleProvider::new(
+                "Local",
+                base_url,
+                Some(local_key),
+                AuthStyle::Bearer,
+            )))
+        }
         "gemini" | "google" | "google-gemini" => {
             let state_dir = options
                 .zerobuild_dir
@@ -1217,10 +1258,10 @@ fn create_provider_with_url_and_options(
                 "Anthropic-custom provider",
                 "anthropic-custom:https://your-api.com",
             )?;
-            Ok(Box::new(anthropic::AnthropicProvider::with_base_url(
-                key,
-                Some(&base_url),
-            )))
+            Ok(Box::new(
+                anthropic::AnthropicProvider::with_base_url(key, Some(&base_url))
+                    .with_prompt_caching(options.prompt_caching),
+            ))
         }
 
         _ => anyhow::bail!(
@@ -1317,6 +1358,17 @@ pub fn create_resilient_provider_with_options(
         }
     }
 
+    // Each provider draws from the bucket all sessions share, inside the
+    // retry loop so retries are paced as well.
+    let providers = providers
+        .into_iter()
+        .map(|(name, provider)| {
+            let provider =
+                throttle::ThrottledProvider::wrap(&name, provider, &reliability.rate_limit);
+            (name, provider)
+        })
+        .collect();
+
     let reliable = ReliableProvider::new(
         providers,
         reliability.provider_retries,
@@ -1328,6 +1380,49 @@ pub fn create_resilient_provider_with_options(
     Ok(Box::new(reliable))
 }
 
+/// Model routes in effect for `config`: `[[model_routes]]` with the routes
+/// the user stored through the `model_routing` tool applied on top. The
+/// store is only read when its database already exists; read errors are
+/// logged and leave the configured routes.
+pub fn effective_model_routes(
+    config: &crate::config::Config,
+) -> Vec<crate::config::ModelRouteConfig> {
+    let db_path = std::path::Path::new(&config.zerobuild.db_path);
+    if !db_path.exists() {
+        return config.model_routes.clone();
+    }
+    match crate::store::init_db(db_path).and_then(|conn| crate::store::routes::list_routes(&conn)) {
+        Ok(stored) => crate::store::routes::merge_routes(&config.model_routes, &stored),
+        Err(e) => {
+            tracing::warn!("Failed to load stored model routes: {e:#}");
+            config.model_routes.clone()
+        }
+    }
+}
+
+/// Canonical name of the provider called `name` (or one of its aliases);
+/// `custom:` and `anthropic-custom:` endpoints are kept as given. `None` for
+/// unknown providers.
+pub fn resolve_provider_name(name: &str) -> Option<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return None;
+    }
+    if name.starts_with("custom:") || name.starts_with("anthropic-custom:") {
+        return Some(name.to_string());
+    }
+    list_providers()
+        .into_iter()
+        .find(|provider| {
+            provider.name.eq_ignore_ascii_case(name)
+                || provider
+                    .aliases
+                    .iter()
+                    .any(|alias| alias.eq_ignore_ascii_case(name))
+        })
+        .map(|provider| provider.name.to_string())
+}
+
 /// Create a RouterProvider if model routes are configured, otherwise return a
 /// standard resilient provider. The router wraps individual providers per route,
 /// each with its own retry/fallback chain.
@@ -1416,6 +1511,11 @@ pub fn create_routed_provider_with_options(
                 router::Route {
                     provider_name: r.provider.clone(),
                     model: r.model.clone(),
+                    temperature: r.temperature,
+                    sampling: sampling::SamplingParams {
+                        top_p: r.top_p,
+                        seed: r.seed,
+                    },
                 },
             )
         })
@@ -1641,6 +1741,12 @@ pub fn list_providers() -> Vec<ProviderInfo> {
             aliases: &["github-copilot"],
             local: false,
         },
+        ProviderInfo {
+            name: "local",
+            display_name: "Local OpenAI-compatible server",
+            aliases: &[],
+            local: true,
+        },
         ProviderInfo {
             name: "lmstudio",
             display_name: "LM Studio",
@@ -2136,6 +2242,32 @@ mod tests {
         assert!(oauth_provider.supports_vision());
     }
 
+    #[test]
+    fn factory_local() {
+        assert!(create_provider("local", None).is_ok());
+        assert!(create_provider_with_url("local", None, Some("http://127.0.0.1:8080/v1")).is_ok());
+    }
+
+    #[test]
+    fn factory_local_applies_capability_overrides() {
+        let options = ProviderRuntimeOptions {
+            local_provider: LocalProviderConfig {
+                tool_calling: Some(false),
+                vision: Some(true),
+                max_context_tokens: Some(32_768),
+            },
+            ..ProviderRuntimeOptions::default()
+        };
+        let provider = create_provider_with_options("local", None, &options).unwrap();
+        let caps = provider.capabilities();
+        assert!(!caps.native_tool_calling);
+        assert!(caps.vision);
+        assert_eq!(caps.max_context_tokens, Some(32_768));
+
+        let cloud = create_provider_with_options("openrouter", Some("key"), &options).unwrap();
+        assert!(cloud.supports_native_tools());
+    }
+
     #[test]
     fn factory_lmstudio() {
         assert!(create_provider("lmstudio", Some("key")).is_ok());
@@ -2417,6 +2549,9 @@ mod tests {
             channel_max_backoff_secs: 60,
             scheduler_poll_secs: 15,
             scheduler_retries: 2,
+            tools: crate::config::ToolReliabilityConfig::default(),
+            recovery: crate::config::RecoveryConfig::default(),
+            rate_limit: crate::config::ProviderRateLimitConfig::default(),
         };
 
         let provider = create_resilient_provider(
@@ -2456,6 +2591,9 @@ mod tests {
             channel_max_backoff_secs: 60,
             scheduler_poll_secs: 15,
             scheduler_retries: 2,
+            tools: crate::config::ToolReliabilityConfig::default(),
+            recovery: crate::config::RecoveryConfig::default(),
+            rate_limit: crate::config::ProviderRateLimitConfig::default(),
         };
 
         // Primary uses a ZAI key; fallbacks (lmstudio, ollama) should NOT
@@ -2478,6 +2616,9 @@ mod tests {
             channel_max_backoff_secs: 60,
             scheduler_poll_secs: 15,
             scheduler_retries: 2,
+            tools: crate::config::ToolReliabilityConfig::default(),
+            recovery: crate::config::RecoveryConfig::default(),
+            rate_limit: crate::config::ProviderRateLimitConfig::default(),
         };
 
         let provider =
@@ -2504,6 +2645,9 @@ mod tests {
             channel_max_backoff_secs: 60,
             scheduler_poll_secs: 15,
             scheduler_retries: 2,
+            tools: crate::config::ToolReliabilityConfig::default(),
+            recovery: crate::config::RecoveryConfig::default(),
+            rate_limit: crate::config::ProviderRateLimitConfig::default(),
         };
 
         let provider = create_resilient_provider("zai", Some("zai-test-key"), None, &reliability);
@@ -2536,6 +2680,9 @@ mod tests {
             channel_max_backoff_secs: 60,
             scheduler_poll_secs: 15,
             scheduler_retries: 2,
+            tools: crate::config::ToolReliabilityConfig::default(),
+            recovery: crate::config::RecoveryConfig::default(),
+            rate_limit: crate::config::ProviderRateLimitConfig::default(),
         };
 
         let provider = create_resilient_provider("zai", Some("zai-test-key"), None, &reliability);
@@ -2828,6 +2975,9 @@ mod tests {
             channel_max_backoff_secs: 60,
             scheduler_poll_secs: 15,
             scheduler_retries: 2,
+            tools: crate::config::ToolReliabilityConfig::default(),
+            recovery: crate::config::RecoveryConfig::default(),
+            rate_limit: crate::config::ProviderRateLimitConfig::default(),
         };
 
         // openai-codex resolves its own OAuth credential; it should not
@@ -2857,6 +3007,9 @@ mod tests {
             channel_max_backoff_secs: 60,
             scheduler_poll_secs: 15,
             scheduler_retries: 2,
+            tools: crate::config::ToolReliabilityConfig::default(),
+            recovery: crate::config::RecoveryConfig::default(),
+            rate_limit: crate::config::ProviderRateLimitConfig::default(),
         };
 
         let provider = create_resilient_provider("ollama", None, None, &reliability);
@@ -1,4 +1,5 @@
 use crate::multimodal;
+use crate::providers::sampling::{self, SamplingParams};
 use crate::providers::traits::{
     ChatMessage, ChatResponse, Provider, ProviderCapabilities, TokenUsage, ToolCall,
 };
@@ -11,6 +12,7 @@ pub struct OllamaProvider {
     base_url: String,
     api_key: Option<String>,
     reasoning_enabled: Option<bool>,
+    num_ctx: Option<usize>,
 }
 
 // ─── Request Structures ───────────────────────────────────────────────────────
@@ -56,6 +58,10 @@ struct OutgoingFunction {
 #[derive(Debug, Serialize)]
 struct Options {
     temperature: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_ctx: Option<usize>,
+    #[serde(flatten)]
+    sampling: SamplingParams,
 }
 
 // ─── Response Structures ──────────────────────────────────────────────────────
@@ -127,9 +133,17 @@ impl OllamaProvider {
             base_url: Self::normalize_base_url(base_url.unwrap_or("http://localhost:11434")),
             api_key,
             reasoning_enabled,
+            num_ctx: None,
         }
     }
 
+    /// Request a context window of `num_ctx` tokens instead of the model
+    /// default.
+    pub fn with_num_ctx(mut self, num_ctx: Option<usize>) -> Self {
+        self.num_ctx = num_ctx;
+        self
+    }
+
     fn is_local_endpoint(&self) -> bool {
         reqwest::Url::parse(&self.base_url)
             .ok()
@@ -179,7 +193,11 @@ impl OllamaProvider {
             model: model.to_string(),
             messages,
             stream: false,
-            options: Options { temperature },
+            options: Options {
+                temperature,
+                num_ctx: self.num_ctx,
+                sampling: sampling::current(),
+            },
             think: self.reasoning_enabled,
             tools: tools.map(|t| t.to_vec()),
         }
@@ -460,6 +478,7 @@ impl Provider for OllamaProvider {
         ProviderCapabilities {
             native_tool_calling: true,
             vision: true,
+            max_context_tokens: self.num_ctx,
         }
     }
 
@@ -608,6 +627,7 @@ impl Provider for OllamaProvider {
             Some(TokenUsage {
                 input_tokens: response.prompt_eval_count,
                 output_tokens: response.eval_count,
+                cached_input_tokens: None,
             })
         } else {
             None
@@ -849,6 +869,21 @@ mod tests {
         assert_eq!(json.get("think"), Some(&serde_json::json!(false)));
     }
 
+    #[test]
+    fn request_includes_num_ctx_when_configured() {
+        let provider = OllamaProvider::new(None, None).with_num_ctx(Some(16_384));
+        let request = provider.build_chat_request(Vec::new(), "llama3", 0.7, None);
+
+        let json = serde_json::to_value(request).unwrap();
+        assert_eq!(json["options"]["num_ctx"], 16_384);
+        assert_eq!(provider.capabilities().max_context_tokens, Some(16_384));
+
+        let default =
+            OllamaProvider::new(None, None).build_chat_request(Vec::new(), "llama3", 0.7, None);
+        let json = serde_json::to_value(default).unwrap();
+        assert!(json["options"].get("num_ctx").is_none());
+    }
+
     #[test]
     fn response_deserializes() {
         let json = r#"{"message":{"role":"assistant","content":"Hello from Ollama!"}}"#;
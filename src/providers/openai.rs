@@ -1,6 +1,7 @@
+use crate::providers::sampling::{self, SamplingParams};
 use crate::providers::traits::{
     ChatMessage, ChatRequest as ProviderChatRequest, ChatResponse as ProviderChatResponse,
-    Provider, TokenUsage, ToolCall as ProviderToolCall,
+    PromptTokensDetails, Provider, TokenUsage, ToolCall as ProviderToolCall,
 };
 use crate::tools::ToolSpec;
 use async_trait::async_trait;
@@ -17,6 +18,8 @@ struct ChatRequest {
     model: String,
     messages: Vec<Message>,
     temperature: f64,
+    #[serde(flatten)]
+    sampling: SamplingParams,
 }
 
 #[derive(Debug, Serialize)]
@@ -58,6 +61,8 @@ struct NativeChatRequest {
     model: String,
     messages: Vec<NativeMessage>,
     temperature: f64,
+    #[serde(flatten)]
+    sampling: SamplingParams,
     #[serde(skip_serializing_if = "Option::is_none")]
     tools: Option<Vec<NativeToolSpec>>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -131,6 +136,8 @@ struct UsageInfo {
     prompt_tokens: Option<u64>,
     #[serde(default)]
     completion_tokens: Option<u64>,
+    #[serde(default)]
+    prompt_tokens_details: Option<PromptTokensDetails>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -314,6 +321,7 @@ impl Provider for OpenAiProvider {
             model: model.to_string(),
             messages,
             temperature,
+            sampling: sampling::current(),
         };
 
         let response = self
@@ -353,6 +361,7 @@ impl Provider for OpenAiProvider {
             model: model.to_string(),
             messages: Self::convert_messages(request.messages),
             temperature,
+            sampling: sampling::current(),
             tool_choice: tools.as_ref().map(|_| "auto".to_string()),
             tools,
         };
@@ -373,6 +382,7 @@ impl Provider for OpenAiProvider {
         let usage = native_response.usage.map(|u| TokenUsage {
             input_tokens: u.prompt_tokens,
             output_tokens: u.completion_tokens,
+            cached_input_tokens: u.prompt_tokens_details.and_then(|d| d.cached_tokens),
         });
         let message = native_response
             .choices
@@ -416,6 +426,7 @@ impl Provider for OpenAiProvider {
             model: model.to_string(),
             messages: Self::convert_messages(messages),
             temperature,
+            sampling: sampling::current(),
             tool_choice: native_tools.as_ref().map(|_| "auto".to_string()),
             tools: native_tools,
         };
@@ -436,6 +447,7 @@ impl Provider for OpenAiProvider {
         let usage = native_response.usage.map(|u| TokenUsage {
             input_tokens: u.prompt_tokens,
             output_tokens: u.completion_tokens,
+            cached_input_tokens: u.prompt_tokens_details.and_then(|d| d.cached_tokens),
         });
         let message = native_response
             .choices
@@ -515,6 +527,7 @@ mod tests {
                 },
             ],
             temperature: 0.7,
+            sampling: SamplingParams::default(),
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(json.contains("\"role\":\"system\""));
@@ -531,6 +544,7 @@ mod tests {
                 content: "hello".to_string(),
             }],
             temperature: 0.0,
+            sampling: SamplingParams::default(),
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(!json.contains("system"));
@@ -1,7 +1,8 @@
 use crate::multimodal;
+use crate::providers::sampling::{self, SamplingParams};
 use crate::providers::traits::{
     ChatMessage, ChatRequest as ProviderChatRequest, ChatResponse as ProviderChatResponse,
-    Provider, ProviderCapabilities, TokenUsage, ToolCall as ProviderToolCall,
+    PromptTokensDetails, Provider, ProviderCapabilities, TokenUsage, ToolCall as ProviderToolCall,
 };
 use crate::tools::ToolSpec;
 use async_trait::async_trait;
@@ -17,6 +18,8 @@ struct ChatRequest {
     model: String,
     messages: Vec<Message>,
     temperature: f64,
+    #[serde(flatten)]
+    sampling: SamplingParams,
 }
 
 #[derive(Debug, Serialize)]
@@ -64,6 +67,8 @@ struct NativeChatRequest {
     model: String,
     messages: Vec<NativeMessage>,
     temperature: f64,
+    #[serde(flatten)]
+    sampling: SamplingParams,
     #[serde(skip_serializing_if = "Option::is_none")]
     tools: Option<Vec<NativeToolSpec>>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -123,6 +128,8 @@ struct UsageInfo {
     prompt_tokens: Option<u64>,
     #[serde(default)]
     completion_tokens: Option<u64>,
+    #[serde(default)]
+    prompt_tokens_details: Option<PromptTokensDetails>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -292,6 +299,7 @@ impl Provider for OpenRouterProvider {
         ProviderCapabilities {
             native_tool_calling: true,
             vision: true,
+            max_context_tokens: None,
         }
     }
 
@@ -337,6 +345,7 @@ impl Provider for OpenRouterProvider {
             model: model.to_string(),
             messages,
             temperature,
+            sampling: sampling::current(),
         };
 
         let response = self
@@ -387,6 +396,7 @@ impl Provider for OpenRouterProvider {
             model: model.to_string(),
             messages: api_messages,
             temperature,
+            sampling: sampling::current(),
         };
 
         let response = self
@@ -433,6 +443,7 @@ impl Provider for OpenRouterProvider {
             model: model.to_string(),
             messages: Self::convert_messages(request.messages),
             temperature,
+            sampling: sampling::current(),
             tool_choice: tools.as_ref().map(|_| "auto".to_string()),
             tools,
         };
@@ -458,6 +469,7 @@ impl Provider for OpenRouterProvider {
         let usage = native_response.usage.map(|u| TokenUsage {
             input_tokens: u.prompt_tokens,
             output_tokens: u.completion_tokens,
+            cached_input_tokens: u.prompt_tokens_details.and_then(|d| d.cached_tokens),
         });
         let message = native_response
             .choices
@@ -527,6 +539,7 @@ impl Provider for OpenRouterProvider {
             model: model.to_string(),
             messages: native_messages,
             temperature,
+            sampling: sampling::current(),
             tool_choice: native_tools.as_ref().map(|_| "auto".to_string()),
             tools: native_tools,
         };
@@ -552,6 +565,7 @@ impl Provider for OpenRouterProvider {
         let usage = native_response.usage.map(|u| TokenUsage {
             input_tokens: u.prompt_tokens,
             output_tokens: u.completion_tokens,
+            cached_input_tokens: u.prompt_tokens_details.and_then(|d| d.cached_tokens),
         });
         let message = native_response
             .choices
@@ -648,6 +662,7 @@ mod tests {
                 },
             ],
             temperature: 0.5,
+            sampling: SamplingParams::default(),
         };
 
         let json = serde_json::to_string(&request).unwrap();
@@ -681,6 +696,7 @@ mod tests {
                 })
                 .collect(),
             temperature: 0.0,
+            sampling: SamplingParams::default(),
         };
 
         let json = serde_json::to_string(&request).unwrap();
@@ -2,6 +2,7 @@ use super::traits::{
     ChatMessage, ChatRequest, ChatResponse, StreamChunk, StreamOptions, StreamResult,
 };
 use super::Provider;
+use crate::tools::ToolSpec;
 use async_trait::async_trait;
 use futures_util::{stream, StreamExt};
 use std::collections::HashMap;
@@ -261,6 +262,58 @@ impl ReliableProvider {
         self
     }
 
+    /// Provider used for streaming: the first one that supports it.
+    fn streaming_provider(&self) -> Option<&(String, Box<dyn Provider>)> {
+        self.providers.iter().find(|(_, p)| p.supports_streaming())
+    }
+
+    /// Same policy as `stream_chat_with_system`: first streaming-capable
+    /// provider, first model in the chain, errors propagate to the caller.
+    fn stream_first(
+        &self,
+        model: &str,
+        options: StreamOptions,
+        start: impl FnOnce(&dyn Provider, &str) -> stream::BoxStream<'static, StreamResult<StreamChunk>>,
+    ) -> stream::BoxStream<'static, StreamResult<StreamChunk>> {
+        let Some((provider_name, provider)) = self.streaming_provider().filter(|_| options.enabled)
+        else {
+            return stream::once(async move {
+                Err(super::traits::StreamError::Provider(
+                    "No provider supports streaming".to_string(),
+                ))
+            })
+            .boxed();
+        };
+
+        let provider_clone = provider_name.clone();
+        let current_model = match self.model_chain(model).first() {
+            Some(m) => m.to_string(),
+            None => model.to_string(),
+        };
+        let mut stream = start(provider.as_ref(), &current_model);
+
+        let (tx, rx) = tokio::sync::mpsc::channel::<StreamResult<StreamChunk>>(100);
+        tokio::spawn(async move {
+            while let Some(chunk) = stream.next().await {
+                if let Err(ref e) = chunk {
+                    tracing::warn!(
+                        provider = provider_clone,
+                        model = current_model,
+                        "Streaming error: {e}"
+                    );
+                }
+                if tx.send(chunk).await.is_err() {
+                    break; // Receiver dropped
+                }
+            }
+        });
+
+        stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|chunk| (chunk, rx))
+        })
+        .boxed()
+    }
+
     /// Build the list of models to try: [original, fallback1, fallback2, ...]
     fn model_chain<'a>(&'a self, model: &'a str) -> Vec<&'a str> {
         let mut chain = vec![model];
@@ -852,6 +905,36 @@ impl Provider for ReliableProvider {
         })
         .boxed()
     }
+
+    fn stream_chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+        options: StreamOptions,
+    ) -> stream::BoxStream<'static, StreamResult<StreamChunk>> {
+        self.stream_first(model, options, |provider, model| {
+            provider.stream_chat_with_history(messages, model, temperature, options)
+        })
+    }
+
+    fn supports_streaming_tool_calls(&self) -> bool {
+        self.streaming_provider()
+            .is_some_and(|(_, p)| p.supports_streaming_tool_calls())
+    }
+
+    fn stream_chat_with_tools(
+        &self,
+        messages: &[ChatMessage],
+        tools: &[ToolSpec],
+        model: &str,
+        temperature: f64,
+        options: StreamOptions,
+    ) -> stream::BoxStream<'static, StreamResult<StreamChunk>> {
+        self.stream_first(model, options, |provider, model| {
+            provider.stream_chat_with_tools(messages, tools, model, temperature, options)
+        })
+    }
 }
 
 #[cfg(test)]
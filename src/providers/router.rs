@@ -1,3 +1,4 @@
+use super::sampling::{self, SamplingParams};
 use super::traits::{ChatMessage, ChatRequest, ChatResponse};
 use super::Provider;
 use async_trait::async_trait;
@@ -8,6 +9,18 @@ use std::collections::HashMap;
 pub struct Route {
     pub provider_name: String,
     pub model: String,
+    /// Temperature for requests on this route, overriding the caller's.
+    pub temperature: Option<f64>,
+    /// `top_p` and `seed` for requests on this route.
+    pub sampling: SamplingParams,
+}
+
+/// A route resolved to a provider index.
+struct ResolvedRoute {
+    index: usize,
+    model: String,
+    temperature: Option<f64>,
+    sampling: SamplingParams,
 }
 
 /// Multi-model router — routes requests to different provider+model combos
@@ -19,7 +32,7 @@ pub struct Route {
 ///
 /// This wraps multiple pre-created providers and selects the right one per request.
 pub struct RouterProvider {
-    routes: HashMap<String, (usize, String)>, // hint → (provider_index, model)
+    routes: HashMap<String, ResolvedRoute>,
     providers: Vec<(String, Box<dyn Provider>)>,
     default_index: usize,
     default_model: String,
@@ -43,12 +56,20 @@ impl RouterProvider {
             .collect();
 
         // Resolve routes to provider indices
-        let resolved_routes: HashMap<String, (usize, String)> = routes
+        let resolved_routes: HashMap<String, ResolvedRoute> = routes
             .into_iter()
             .filter_map(|(hint, route)| {
                 let index = name_to_index.get(route.provider_name.as_str()).copied();
                 match index {
-                    Some(i) => Some((hint, (i, route.model))),
+                    Some(index) => Some((
+                        hint,
+                        ResolvedRoute {
+                            index,
+                            model: route.model,
+                            temperature: route.temperature,
+                            sampling: route.sampling,
+                        },
+                    )),
                     None => {
                         tracing::warn!(
                             hint = hint,
@@ -69,15 +90,21 @@ impl RouterProvider {
         }
     }
 
-    /// Resolve a model parameter to a (provider, actual_model) pair.
+    /// Resolve a model parameter to the provider index, actual model,
+    /// temperature and sampling parameters for a request.
     ///
-    /// If the model starts with "hint:", look up the hint in the route table.
+    /// If the model starts with "hint:", look up the hint in the route table
+    /// and use the route's temperature and sampling parameters where set.
     /// Otherwise, use the default provider with the given model name.
-    /// Resolve a model parameter to a (provider_index, actual_model) pair.
-    fn resolve(&self, model: &str) -> (usize, String) {
+    fn resolve(&self, model: &str, temperature: f64) -> (usize, String, f64, SamplingParams) {
         if let Some(hint) = model.strip_prefix("hint:") {
-            if let Some((idx, resolved_model)) = self.routes.get(hint) {
-                return (*idx, resolved_model.clone());
+            if let Some(route) = self.routes.get(hint) {
+                return (
+                    route.index,
+                    route.model.clone(),
+                    route.temperature.unwrap_or(temperature),
+                    route.sampling,
+                );
             }
             tracing::warn!(
                 hint = hint,
@@ -86,7 +113,12 @@ impl RouterProvider {
         }
 
         // Not a hint or hint not found — use default provider with the model as-is
-        (self.default_index, model.to_string())
+        (
+            self.default_index,
+            model.to_string(),
+            temperature,
+            SamplingParams::default(),
+        )
     }
 }
 
@@ -99,7 +131,7 @@ impl Provider for RouterProvider {
         model: &str,
         temperature: f64,
     ) -> anyhow::Result<String> {
-        let (provider_idx, resolved_model) = self.resolve(model);
+        let (provider_idx, resolved_model, temperature, params) = self.resolve(model, temperature);
 
         let (provider_name, provider) = &self.providers[provider_idx];
         tracing::info!(
@@ -108,9 +140,11 @@ impl Provider for RouterProvider {
             "Router dispatching request"
         );
 
-        provider
-            .chat_with_system(system_prompt, message, &resolved_model, temperature)
-            .await
+        sampling::with_sampling(
+            params,
+            provider.chat_with_system(system_prompt, message, &resolved_model, temperature),
+        )
+        .await
     }
 
     async fn chat_with_history(
@@ -119,11 +153,13 @@ impl Provider for RouterProvider {
         model: &str,
         temperature: f64,
     ) -> anyhow::Result<String> {
-        let (provider_idx, resolved_model) = self.resolve(model);
+        let (provider_idx, resolved_model, temperature, params) = self.resolve(model, temperature);
         let (_, provider) = &self.providers[provider_idx];
-        provider
-            .chat_with_history(messages, &resolved_model, temperature)
-            .await
+        sampling::with_sampling(
+            params,
+            provider.chat_with_history(messages, &resolved_model, temperature),
+        )
+        .await
     }
 
     async fn chat(
@@ -132,9 +168,9 @@ impl Provider for RouterProvider {
         model: &str,
         temperature: f64,
     ) -> anyhow::Result<ChatResponse> {
-        let (provider_idx, resolved_model) = self.resolve(model);
+        let (provider_idx, resolved_model, temperature, params) = self.resolve(model, temperature);
         let (_, provider) = &self.providers[provider_idx];
-        provider.chat(request, &resolved_model, temperature).await
+        sampling::with_sampling(params, provider.chat(request, &resolved_model, temperature)).await
     }
 
     async fn chat_with_tools(
@@ -144,11 +180,13 @@ impl Provider for RouterProvider {
         model: &str,
         temperature: f64,
     ) -> anyhow::Result<ChatResponse> {
-        let (provider_idx, resolved_model) = self.resolve(model);
+        let (provider_idx, resolved_model, temperature, params) = self.resolve(model, temperature);
         let (_, provider) = &self.providers[provider_idx];
-        provider
-            .chat_with_tools(messages, tools, &resolved_model, temperature)
-            .await
+        sampling::with_sampling(
+            params,
+            provider.chat_with_tools(messages, tools, &resolved_model, temperature),
+        )
+        .await
     }
 
     fn supports_native_tools(&self) -> bool {
@@ -185,6 +223,7 @@ mod tests {
         calls: Arc<AtomicUsize>,
         response: &'static str,
         last_model: parking_lot::Mutex<String>,
+        last_sampling: parking_lot::Mutex<(f64, SamplingParams)>,
     }
 
     impl MockProvider {
@@ -193,6 +232,7 @@ mod tests {
                 calls: Arc::new(AtomicUsize::new(0)),
                 response,
                 last_model: parking_lot::Mutex::new(String::new()),
+                last_sampling: parking_lot::Mutex::new((0.0, SamplingParams::default())),
             }
         }
 
@@ -212,10 +252,11 @@ mod tests {
             _system_prompt: Option<&str>,
             _message: &str,
             model: &str,
-            _temperature: f64,
+            temperature: f64,
         ) -> anyhow::Result<String> {
             self.calls.fetch_add(1, Ordering::SeqCst);
             *self.last_model.lock() = model.to_string();
+            *self.last_sampling.lock() = (temperature, sampling::current());
             Ok(self.response.to_string())
         }
     }
@@ -248,6 +289,8 @@ mod tests {
                     Route {
                         provider_name: provider_name.to_string(),
                         model: model.to_string(),
+                        temperature: None,
+                        sampling: SamplingParams::default(),
                     },
                 )
             })
@@ -347,7 +390,7 @@ mod tests {
     fn resolve_preserves_model_for_non_hints() {
         let (router, _) = make_router(vec![("default", "ok")], vec![]);
 
-        let (idx, model) = router.resolve("gpt-4o");
+        let (idx, model, _, _) = router.resolve("gpt-4o", 0.7);
         assert_eq!(idx, 0);
         assert_eq!(model, "gpt-4o");
     }
@@ -359,11 +402,42 @@ mod tests {
             vec![("reasoning", "smart", "claude-opus")],
         );
 
-        let (idx, model) = router.resolve("hint:reasoning");
+        let (idx, model, _, _) = router.resolve("hint:reasoning", 0.7);
         assert_eq!(idx, 1);
         assert_eq!(model, "claude-opus");
     }
 
+    #[tokio::test]
+    async fn route_sampling_overrides_apply_only_to_that_route() {
+        let mock = Arc::new(MockProvider::new("ok"));
+        let params = SamplingParams {
+            top_p: Some(0.9),
+            seed: Some(7),
+        };
+        let router = RouterProvider::new(
+            vec![(
+                "default".into(),
+                Box::new(Arc::clone(&mock)) as Box<dyn Provider>,
+            )],
+            vec![(
+                "exact".into(),
+                Route {
+                    provider_name: "default".into(),
+                    model: "model-a".into(),
+                    temperature: Some(0.0),
+                    sampling: params,
+                },
+            )],
+            "model".into(),
+        );
+
+        router.simple_chat("hi", "hint:exact", 0.7).await.unwrap();
+        assert_eq!(*mock.last_sampling.lock(), (0.0, params));
+
+        router.simple_chat("hi", "model-b", 0.7).await.unwrap();
+        assert_eq!(*mock.last_sampling.lock(), (0.7, SamplingParams::default()));
+    }
+
     #[test]
     fn skips_routes_with_unknown_provider() {
         let (router, _) = make_router(
@@ -0,0 +1,105 @@
+//! Sampling parameters beyond temperature (`top_p`, `seed`).
+//!
+//! Model routes and factory stages run their provider calls inside
+//! [`with_sampling`]; providers that support the parameters read [`current`]
+//! when building a request body. In deterministic mode (`[agent]
+//! deterministic = true`) every request without an explicit seed uses
+//! [`DETERMINISTIC_SEED`].
+
+use serde::Serialize;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Seed pinned on every request in deterministic mode.
+pub const DETERMINISTIC_SEED: u64 = 42;
+
+static DETERMINISTIC: AtomicBool = AtomicBool::new(false);
+
+/// `top_p` and `seed` for a provider request; unset fields are left to the
+/// provider's defaults.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct SamplingParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+}
+
+impl SamplingParams {
+    /// These parameters, with unset fields taken from `fallback`.
+    pub fn or(self, fallback: Self) -> Self {
+        Self {
+            top_p: self.top_p.or(fallback.top_p),
+            seed: self.seed.or(fallback.seed),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.top_p.is_none() && self.seed.is_none()
+    }
+}
+
+tokio::task_local! {
+    static SAMPLING: SamplingParams;
+}
+
+/// Turn deterministic mode on or off for the process.
+pub fn set_deterministic(enabled: bool) {
+    DETERMINISTIC.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether deterministic mode is on: seeds are pinned and prompts leave out
+/// the current date and time.
+pub fn is_deterministic() -> bool {
+    DETERMINISTIC.load(Ordering::Relaxed)
+}
+
+/// Run `future` with `params` applied to its provider requests. Fields left
+/// unset keep the values of an enclosing [`with_sampling`].
+pub async fn with_sampling<F: Future>(params: SamplingParams, future: F) -> F::Output {
+    let params = params.or(SAMPLING.try_with(|outer| *outer).unwrap_or_default());
+    SAMPLING.scope(params, future).await
+}
+
+/// Parameters for a request made now.
+pub fn current() -> SamplingParams {
+    let mut params = SAMPLING.try_with(|params| *params).unwrap_or_default();
+    if params.seed.is_none() && is_deterministic() {
+        params.seed = Some(DETERMINISTIC_SEED);
+    }
+    params
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn nested_scopes_fill_unset_fields_from_outer_ones() {
+        assert_eq!(current(), SamplingParams::default());
+        let outer = SamplingParams {
+            top_p: Some(0.9),
+            seed: Some(7),
+        };
+        let inner = SamplingParams {
+            top_p: Some(0.5),
+            seed: None,
+        };
+        let params = with_sampling(outer, with_sampling(inner, async { current() })).await;
+        assert_eq!(
+            params,
+            SamplingParams {
+                top_p: Some(0.5),
+                seed: Some(7),
+            }
+        );
+        assert_eq!(
+            serde_json::to_value(params).unwrap(),
+            serde_json::json!({ "top_p": 0.5, "seed": 7 })
+        );
+        assert_eq!(
+            serde_json::to_value(SamplingParams::default()).unwrap(),
+            serde_json::json!({})
+        );
+    }
+}
@@ -0,0 +1,594 @@
+//! Provider token buckets shared across concurrent sessions
+//! (`[reliability.rate_limit]`).
+//!
+//! Every session builds its own provider chain, so without a shared limit
+//! several simultaneous builds hit a provider's rate limit together and all
+//! fail. [`ThrottledProvider`] takes a token from the process-wide bucket of
+//! its provider before each request. When the bucket is empty, requests
+//! queue per session and are served round-robin, so a session firing many
+//! requests cannot crowd out one waiting on a single request; a request
+//! queued longer than `starvation_secs` is served ahead of the rotation.
+//!
+//! Sessions are told apart by the user of the request
+//! ([`crate::security::users::current_user`]); requests without one share a
+//! session. [`stats`] reports the queue of every bucket.
+
+use super::traits::{
+    ChatMessage, ChatRequest, ChatResponse, ProviderCapabilities, StreamChunk, StreamOptions,
+    StreamResult, ToolsPayload,
+};
+use super::Provider;
+use crate::config::ProviderRateLimitConfig;
+use crate::tools::ToolSpec;
+use async_trait::async_trait;
+use futures_util::{stream, FutureExt, StreamExt};
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, LazyLock};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+
+/// Session of requests made outside a user scope.
+const DEFAULT_SESSION: &str = "default";
+
+static LIMITERS: LazyLock<Mutex<HashMap<String, Arc<ProviderLimiter>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Queue metrics of one provider bucket.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LimiterStats {
+    pub provider: String,
+    pub requests_per_minute: u32,
+    /// Whole tokens in the bucket right now.
+    pub available: u32,
+    /// Requests waiting for a token.
+    pub queued: usize,
+    /// Sessions with at least one waiting request.
+    pub waiting_sessions: usize,
+    /// Requests let through since startup.
+    pub granted: u64,
+    /// Requests served ahead of the rotation because they waited too long.
+    pub starved: u64,
+    pub avg_wait_ms: u64,
+    pub max_wait_ms: u64,
+}
+
+struct LimiterState {
+    requests_per_minute: u32,
+    capacity: f64,
+    tokens: f64,
+    refilled_at: Instant,
+    starvation: Duration,
+    next_ticket: u64,
+    /// Waiting tickets per session, oldest first.
+    queues: HashMap<String, VecDeque<(u64, Instant)>>,
+    /// Sessions with waiting tickets, in serving order.
+    rotation: VecDeque<String>,
+    granted: u64,
+    starved: u64,
+    waited: Duration,
+    max_wait: Duration,
+}
+
+impl LimiterState {
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let per_sec = f64::from(self.requests_per_minute) / 60.0;
+        let elapsed = now.duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * per_sec).min(self.capacity);
+        self.refilled_at = now;
+    }
+
+    /// Time until the bucket holds a whole token.
+    fn until_token(&self) -> Duration {
+        let per_sec = f64::from(self.requests_per_minute) / 60.0;
+        Duration::from_secs_f64(((1.0 - self.tokens) / per_sec).max(0.001))
+    }
+
+    /// Ticket to serve next and whether it jumps the rotation: the oldest
+    /// ticket once it has waited past the starvation limit, otherwise the
+    /// oldest ticket of the session at the front of the rotation.
+    fn next(&self) -> Option<(u64, bool)> {
+        let oldest = self
+            .queues
+            .values()
+            .filter_map(VecDeque::front)
+            .min_by_key(|(_, since)| *since);
+        if let Some((ticket, since)) = oldest {
+            if since.elapsed() >= self.starvation {
+                return Some((*ticket, true));
+            }
+        }
+        let session = self.rotation.front()?;
+        self.queues
+            .get(session)
+            .and_then(VecDeque::front)
+            .map(|(ticket, _)| (*ticket, false))
+    }
+
+    /// Drop `ticket` of `session`; a session that still waits moves to the
+    /// back of the rotation when `rotate` is set.
+    fn remove(&mut self, session: &str, ticket: u64, rotate: bool) -> Option<Instant> {
+        let queue = self.queues.get_mut(session)?;
+        let position = queue.iter().position(|(t, _)| *t == ticket)?;
+        let (_, since) = queue.remove(position)?;
+        let empty = queue.is_empty();
+        if empty {
+            self.queues.remove(session);
+        }
+        if empty || rotate {
+            self.rotation.retain(|s| s != session);
+            if !empty {
+                self.rotation.push_back(session.to_string());
+            }
+        }
+        Some(since)
+    }
+
+    fn record(&mut self, waited: Duration, starved: bool) {
+        self.granted += 1;
+        self.starved += u64::from(starved);
+        self.waited += waited;
+        self.max_wait = self.max_wait.max(waited);
+    }
+}
+
+/// Token bucket of one provider, shared by every session in the process.
+pub struct ProviderLimiter {
+    provider: String,
+    state: Mutex<LimiterState>,
+    notify: Notify,
+}
+
+/// Removes a ticket whose request was cancelled while waiting.
+struct Ticket<'a> {
+    limiter: &'a ProviderLimiter,
+    session: &'a str,
+    id: u64,
+    served: bool,
+}
+
+impl Drop for Ticket<'_> {
+    fn drop(&mut self) {
+        if !self.served {
+            self.limiter
+                .state
+                .lock()
+                .remove(self.session, self.id, false);
+            self.limiter.notify.notify_waiters();
+        }
+    }
+}
+
+impl ProviderLimiter {
+    fn new(provider: &str, requests_per_minute: u32, config: &ProviderRateLimitConfig) -> Self {
+        let capacity = f64::from(config.burst.max(1));
+        Self {
+            provider: provider.to_string(),
+            state: Mutex::new(LimiterState {
+                requests_per_minute,
+                capacity,
+                tokens: capacity,
+                refilled_at: Instant::now(),
+                starvation: Duration::from_secs(config.starvation_secs),
+                next_ticket: 0,
+                queues: HashMap::new(),
+                rotation: VecDeque::new(),
+                granted: 0,
+                starved: 0,
+                waited: Duration::ZERO,
+                max_wait: Duration::ZERO,
+            }),
+            notify: Notify::new(),
+        }
+    }
+
+    /// Apply changed limits (after a config reload) to the shared bucket.
+    fn update(&self, requests_per_minute: u32, config: &ProviderRateLimitConfig) {
+        let mut state = self.state.lock();
+        state.refill();
+        state.requests_per_minute = requests_per_minute;
+        state.capacity = f64::from(config.burst.max(1));
+        state.tokens = state.tokens.min(state.capacity);
+        state.starvation = Duration::from_secs(config.starvation_secs);
+    }
+
+    /// Wait for a token on behalf of `session`.
+    pub async fn acquire(&self, session: &str) {
+        let id = {
+            let mut state = self.state.lock();
+            state.refill();
+            if state.queues.is_empty() && state.tokens >= 1.0 {
+                state.tokens -= 1.0;
+                state.record(Duration::ZERO, false);
+                return;
+            }
+            let id = state.next_ticket;
+            state.next_ticket += 1;
+            let session = session.to_string();
+            if !state.queues.contains_key(&session) {
+                state.rotation.push_back(session.clone());
+            }
+            state
+                .queues
+                .entry(session)
+                .or_default()
+                .push_back((id, Instant::now()));
+            id
+        };
+        let mut ticket = Ticket {
+            limiter: self,
+            session,
+            id,
+            served: false,
+        };
+        tracing::debug!(provider = %self.provider, session, "Provider request queued for a rate-limit token");
+
+        loop {
+            let notified = self.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            let wait = {
+                let mut state = self.state.lock();
+                state.refill();
+                match state.next() {
+                    Some((next, starved)) if next == id => {
+                        if state.tokens >= 1.0 {
+                            state.tokens -= 1.0;
+                            let since =
+                                state.remove(session, id, true).unwrap_or_else(Instant::now);
+                            let waited = since.elapsed();
+                            state.record(waited, starved);
+                            ticket.served = true;
+                            drop(state);
+                            tracing::debug!(
+                                provider = %self.provider,
+                                session,
+                                waited_ms = u64::try_from(waited.as_millis()).unwrap_or(u64::MAX),
+                                starved,
+                                "Provider request released by the rate limiter"
+                            );
+                            self.notify.notify_waiters();
+                            return;
+                        }
+                        Some(state.until_token())
+                    }
+                    // Another ticket is due and a token is ready: make sure
+                    // its waiter is awake.
+                    _ if state.tokens >= 1.0 => {
+                        drop(state);
+                        self.notify.notify_waiters();
+                        None
+                    }
+                    _ => None,
+                }
+            };
+
+            match wait {
+                Some(wait) => {
+                    tokio::select! {
+                        () = &mut notified => {}
+                        () = tokio::time::sleep(wait) => {}
+                    }
+                }
+                None => notified.await,
+            }
+        }
+    }
+
+    pub fn stats(&self) -> LimiterStats {
+        let mut state = self.state.lock();
+        state.refill();
+        let millis = |d: Duration| u64::try_from(d.as_millis()).unwrap_or(u64::MAX);
+        LimiterStats {
+            provider: self.provider.clone(),
+            requests_per_minute: state.requests_per_minute,
+            // Whole tokens never exceed `burst`, a u32.
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            available: state.tokens.floor() as u32,
+            queued: state.queues.values().map(VecDeque::len).sum(),
+            waiting_sessions: state.queues.len(),
+            granted: state.granted,
+            starved: state.starved,
+            avg_wait_ms: millis(state.waited)
+                .checked_div(state.granted)
+                .unwrap_or_default(),
+            max_wait_ms: millis(state.max_wait),
+        }
+    }
+}
+
+/// Shared bucket of `provider`, or `None` when it is unlimited.
+pub fn limiter(provider: &str, config: &ProviderRateLimitConfig) -> Option<Arc<ProviderLimiter>> {
+    let requests_per_minute = config
+        .providers
+        .get(provider)
+        .copied()
+        .unwrap_or(config.requests_per_minute);
+    if !config.enabled || requests_per_minute == 0 {
+        return None;
+    }
+    let mut limiters = LIMITERS.lock();
+    let limiter = limiters
+        .entry(provider.to_string())
+        .or_insert_with(|| Arc::new(ProviderLimiter::new(provider, requests_per_minute, config)));
+    limiter.update(requests_per_minute, config);
+    Some(Arc::clone(limiter))
+}
+
+/// Queue metrics of every provider bucket, by provider name.
+pub fn stats() -> Vec<LimiterStats> {
+    let mut stats: Vec<_> = LIMITERS.lock().values().map(|l| l.stats()).collect();
+    stats.sort_by(|a, b| a.provider.cmp(&b.provider));
+    stats
+}
+
+fn current_session() -> String {
+    crate::security::users::current_user().unwrap_or_else(|| DEFAULT_SESSION.to_string())
+}
+
+/// Provider whose requests wait for a token of the shared bucket.
+pub struct ThrottledProvider {
+    inner: Box<dyn Provider>,
+    limiter: Arc<ProviderLimiter>,
+}
+
+impl ThrottledProvider {
+    /// `provider` behind the bucket of `name`; unchanged when the limiter is
+    /// disabled or the provider is unlimited.
+    pub fn wrap(
+        name: &str,
+        provider: Box<dyn Provider>,
+        config: &ProviderRateLimitConfig,
+    ) -> Box<dyn Provider> {
+        match limiter(name, config) {
+            Some(limiter) => Box::new(Self {
+                inner: provider,
+                limiter,
+            }),
+            None => provider,
+        }
+    }
+
+    async fn acquire(&self) {
+        self.limiter.acquire(&current_session()).await;
+    }
+
+    /// `stream` once a token is available.
+    fn throttled_stream(
+        &self,
+        stream: stream::BoxStream<'static, StreamResult<StreamChunk>>,
+    ) -> stream::BoxStream<'static, StreamResult<StreamChunk>> {
+        let limiter = Arc::clone(&self.limiter);
+        let session = current_session();
+        async move {
+            limiter.acquire(&session).await;
+            stream
+        }
+        .flatten_stream()
+        .boxed()
+    }
+}
+
+#[async_trait]
+impl Provider for ThrottledProvider {
+    fn capabilities(&self) -> ProviderCapabilities {
+        self.inner.capabilities()
+    }
+
+    fn convert_tools(&self, tools: &[ToolSpec]) -> ToolsPayload {
+        self.inner.convert_tools(tools)
+    }
+
+    async fn simple_chat(
+        &self,
+        message: &str,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        self.acquire().await;
+        self.inner.simple_chat(message, model, temperature).await
+    }
+
+    async fn chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        self.acquire().await;
+        self.inner
+            .chat_with_system(system_prompt, message, model, temperature)
+            .await
+    }
+
+    async fn chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        self.acquire().await;
+        self.inner
+            .chat_with_history(messages, model, temperature)
+            .await
+    }
+
+    async fn chat(
+        &self,
+        request: ChatRequest<'_>,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatResponse> {
+        self.acquire().await;
+        self.inner.chat(request, model, temperature).await
+    }
+
+    fn supports_native_tools(&self) -> bool {
+        self.inner.supports_native_tools()
+    }
+
+    fn supports_vision(&self) -> bool {
+        self.inner.supports_vision()
+    }
+
+    async fn warmup(&self) -> anyhow::Result<()> {
+        self.inner.warmup().await
+    }
+
+    async fn chat_with_tools(
+        &self,
+        messages: &[ChatMessage],
+        tools: &[serde_json::Value],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatResponse> {
+        self.acquire().await;
+        self.inner
+            .chat_with_tools(messages, tools, model, temperature)
+            .await
+    }
+
+    fn supports_streaming(&self) -> bool {
+        self.inner.supports_streaming()
+    }
+
+    fn stream_chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        temperature: f64,
+        options: StreamOptions,
+    ) -> stream::BoxStream<'static, StreamResult<StreamChunk>> {
+        self.throttled_stream(self.inner.stream_chat_with_system(
+            system_prompt,
+            message,
+            model,
+            temperature,
+            options,
+        ))
+    }
+
+    fn stream_chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+        options: StreamOptions,
+    ) -> stream::BoxStream<'static, StreamResult<StreamChunk>> {
+        self.throttled_stream(self.inner.stream_chat_with_history(
+            messages,
+            model,
+            temperature,
+            options,
+        ))
+    }
+
+    fn supports_streaming_tool_calls(&self) -> bool {
+        self.inner.supports_streaming_tool_calls()
+    }
+
+    fn stream_chat_with_tools(
+        &self,
+        messages: &[ChatMessage],
+        tools: &[ToolSpec],
+        model: &str,
+        temperature: f64,
+        options: StreamOptions,
+    ) -> stream::BoxStream<'static, StreamResult<StreamChunk>> {
+        self.throttled_stream(self.inner.stream_chat_with_tools(
+            messages,
+            tools,
+            model,
+            temperature,
+            options,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(requests_per_minute: u32, burst: u32) -> ProviderRateLimitConfig {
+        ProviderRateLimitConfig {
+            enabled: true,
+            requests_per_minute,
+            burst,
+            starvation_secs: 3600,
+            ..ProviderRateLimitConfig::default()
+        }
+    }
+
+    #[test]
+    fn disabled_or_unlimited_providers_have_no_bucket() {
+        let mut config = config(60, 1);
+        config.providers.insert("throttle-test-free".into(), 0);
+        assert!(limiter("throttle-test-free", &config).is_none());
+        config.enabled = false;
+        assert!(limiter("throttle-test-disabled", &config).is_none());
+    }
+
+    #[tokio::test]
+    async fn buckets_are_shared_by_provider_name() {
+        let config = config(60, 2);
+        let first = limiter("throttle-test-shared", &config).unwrap();
+        let second = limiter("throttle-test-shared", &config).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        first.acquire("a").await;
+        second.acquire("b").await;
+        let stats = first.stats();
+        assert_eq!(stats.granted, 2);
+        assert_eq!(stats.available, 0);
+    }
+
+    #[tokio::test]
+    async fn sessions_are_served_round_robin() {
+        let limiter = Arc::new(ProviderLimiter::new(
+            "throttle-test-fair",
+            60,
+            &config(60, 1),
+        ));
+        limiter.acquire("busy").await;
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut tasks = Vec::new();
+        for session in ["busy", "busy", "busy", "quiet"] {
+            let limiter = Arc::clone(&limiter);
+            let tx = tx.clone();
+            tasks.push(tokio::spawn(async move {
+                limiter.acquire(session).await;
+                tx.send(session).unwrap();
+            }));
+            // Queue in a fixed order.
+            tokio::task::yield_now().await;
+        }
+        for task in tasks {
+            task.await.unwrap();
+        }
+        drop(tx);
+
+        let mut order = Vec::new();
+        while let Some(session) = rx.recv().await {
+            order.push(session);
+        }
+        assert_eq!(order, ["busy", "quiet", "busy", "busy"]);
+        let stats = limiter.stats();
+        assert_eq!(stats.granted, 5);
+        assert_eq!(stats.queued, 0);
+    }
+
+    #[tokio::test]
+    async fn long_waiting_requests_jump_the_rotation() {
+        let mut config = config(6000, 1);
+        config.starvation_secs = 0;
+        let limiter = Arc::new(ProviderLimiter::new("throttle-test-starved", 6000, &config));
+        limiter.acquire("a").await;
+        limiter.acquire("b").await;
+        assert_eq!(limiter.stats().starved, 1);
+    }
+}
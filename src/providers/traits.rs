@@ -54,6 +54,15 @@ pub struct ToolCall {
 pub struct TokenUsage {
     pub input_tokens: Option<u64>,
     pub output_tokens: Option<u64>,
+    /// Prompt tokens served from the provider's prompt cache.
+    pub cached_input_tokens: Option<u64>,
+}
+
+/// OpenAI-style `usage.prompt_tokens_details`, reporting prompt-prefix reuse.
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct PromptTokensDetails {
+    #[serde(default)]
+    pub cached_tokens: Option<u64>,
 }
 
 /// An LLM response that may contain text, tool calls, or both.
@@ -131,6 +140,20 @@ pub struct StreamChunk {
     pub is_final: bool,
     /// Approximate token count for this chunk (estimated).
     pub token_count: usize,
+    /// Native tool-call fragments carried by this chunk.
+    pub tool_calls: Vec<ToolCallDelta>,
+    /// Token usage reported by the provider (usually on the final chunk).
+    pub usage: Option<TokenUsage>,
+}
+
+/// Fragment of a streamed native tool call. Fragments with the same `index`
+/// belong to one call: `id` and `name` arrive once, `arguments` is appended.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ToolCallDelta {
+    pub index: usize,
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub arguments: String,
 }
 
 impl StreamChunk {
@@ -140,27 +163,33 @@ impl StreamChunk {
             delta: text.into(),
             is_final: false,
             token_count: 0,
+            tool_calls: Vec::new(),
+            usage: None,
         }
     }
 
     /// Create a final chunk.
     pub fn final_chunk() -> Self {
         Self {
-            delta: String::new(),
             is_final: true,
-            token_count: 0,
+            ..Self::delta("")
         }
     }
 
     /// Create an error chunk.
     pub fn error(message: impl Into<String>) -> Self {
         Self {
-            delta: message.into(),
             is_final: true,
-            token_count: 0,
+            ..Self::delta(message)
         }
     }
 
+    /// Attach provider-reported token usage.
+    pub fn with_usage(mut self, usage: Option<TokenUsage>) -> Self {
+        self.usage = usage;
+        self
+    }
+
     /// Estimate tokens (rough approximation: ~4 chars per token).
     pub fn with_token_estimate(mut self) -> Self {
         self.token_count = self.delta.len().div_ceil(4);
@@ -239,6 +268,11 @@ pub struct ProviderCapabilities {
     pub native_tool_calling: bool,
     /// Whether the provider supports vision / image inputs.
     pub vision: bool,
+    /// Maximum context window in tokens, when known.
+    ///
+    /// `None` means the provider does not advertise a limit (most cloud APIs
+    /// negotiate this per model).
+    pub max_context_tokens: Option<usize>,
 }
 
 /// Provider-specific tool payload formats.
@@ -440,6 +474,32 @@ pub trait Provider: Send + Sync {
         stream::empty().boxed()
     }
 
+    /// Whether [`Provider::stream_chat_with_tools`] can stream native tool
+    /// calls. Default implementation returns false.
+    fn supports_streaming_tool_calls(&self) -> bool {
+        false
+    }
+
+    /// Streaming chat with native tool schemas. Text arrives in
+    /// [`StreamChunk::delta`], tool calls as [`StreamChunk::tool_calls`]
+    /// fragments, and usage on the final chunk.
+    /// Default implementation reports that tool calls cannot be streamed.
+    fn stream_chat_with_tools(
+        &self,
+        _messages: &[ChatMessage],
+        _tools: &[ToolSpec],
+        _model: &str,
+        _temperature: f64,
+        _options: StreamOptions,
+    ) -> stream::BoxStream<'static, StreamResult<StreamChunk>> {
+        stream::once(async {
+            Err(StreamError::Provider(
+                "provider does not stream tool calls".to_string(),
+            ))
+        })
+        .boxed()
+    }
+
     /// Streaming chat with history.
     /// Default implementation falls back to stream_chat_with_system with last user message.
     fn stream_chat_with_history(
@@ -504,6 +564,7 @@ mod tests {
             ProviderCapabilities {
                 native_tool_calling: true,
                 vision: true,
+                max_context_tokens: None,
             }
         }
 
@@ -574,6 +635,7 @@ mod tests {
             usage: Some(TokenUsage {
                 input_tokens: Some(100),
                 output_tokens: Some(50),
+                cached_input_tokens: None,
             }),
             reasoning_content: None,
         };
@@ -619,14 +681,17 @@ mod tests {
         let caps1 = ProviderCapabilities {
             native_tool_calling: true,
             vision: false,
+            max_context_tokens: None,
         };
         let caps2 = ProviderCapabilities {
             native_tool_calling: true,
             vision: false,
+            max_context_tokens: None,
         };
         let caps3 = ProviderCapabilities {
             native_tool_calling: false,
             vision: false,
+            max_context_tokens: None,
         };
 
         assert_eq!(caps1, caps2);
@@ -1,3 +1,4 @@
+use super::platform;
 use super::traits::RuntimeAdapter;
 use crate::config::DockerRuntimeConfig;
 use anyhow::{Context, Result};
@@ -14,6 +15,17 @@ impl DockerRuntime {
         Self { config }
     }
 
+    /// Platform containers run as: `platform` from config, or the host's.
+    pub fn platform(&self) -> String {
+        platform::resolve_platform(&self.config.platform)
+    }
+
+    /// Image for [`Self::platform`]: the matching `images` entry or `image`.
+    pub fn image(&self) -> String {
+        platform::image_for_platform(&self.config.image, &self.config.images, &self.platform())
+            .to_string()
+    }
+
     fn workspace_mount_path(&self, workspace_dir: &Path) -> Result<PathBuf> {
         let resolved = workspace_dir
             .canonicalize()
@@ -93,7 +105,9 @@ impl RuntimeAdapter for DockerRuntime {
             .arg("run")
             .arg("--rm")
             .arg("--init")
-            .arg("--interactive");
+            .arg("--interactive")
+            .arg("--platform")
+            .arg(self.platform());
 
         let network = self.config.network.trim();
         if !network.is_empty() {
@@ -127,11 +141,7 @@ impl RuntimeAdapter for DockerRuntime {
                 .arg("/workspace");
         }
 
-        process
-            .arg(self.config.image.trim())
-            .arg("sh")
-            .arg("-c")
-            .arg(command);
+        process.arg(self.image()).arg("sh").arg("-c").arg(command);
 
         Ok(process)
     }
@@ -140,6 +150,7 @@ impl RuntimeAdapter for DockerRuntime {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashMap;
 
     #[test]
     fn docker_runtime_name() {
@@ -159,6 +170,8 @@ mod tests {
     fn docker_build_shell_command_includes_runtime_flags() {
         let cfg = DockerRuntimeConfig {
             image: "alpine:3.20".into(),
+            images: HashMap::new(),
+            platform: String::new(),
             network: "none".into(),
             memory_limit_mb: Some(128),
             cpu_limit: Some(1.5),
@@ -272,4 +285,34 @@ mod tests {
             "should not include --memory when not configured"
         );
     }
+
+    #[test]
+    fn docker_platform_override_selects_arch_image() {
+        let cfg = DockerRuntimeConfig {
+            image: "node:20".into(),
+            images: HashMap::from([("arm64".to_string(), "node:20-arm64".to_string())]),
+            platform: "linux/arm64".into(),
+            ..DockerRuntimeConfig::default()
+        };
+        let runtime = DockerRuntime::new(cfg);
+        let workspace = std::env::temp_dir();
+        let debug = format!(
+            "{:?}",
+            runtime
+                .build_shell_command("echo hello", &workspace)
+                .unwrap()
+        );
+        assert!(debug.contains(r#""--platform" "linux/arm64""#));
+        assert!(debug.contains(r#""node:20-arm64" "sh""#));
+    }
+
+    #[test]
+    fn docker_defaults_to_host_platform() {
+        let runtime = DockerRuntime::new(DockerRuntimeConfig::default());
+        assert_eq!(
+            runtime.platform(),
+            platform::platform_for_arch(platform::host_arch())
+        );
+        assert_eq!(runtime.image(), "alpine:3.20");
+    }
 }
@@ -1,5 +1,6 @@
 pub mod docker;
 pub mod native;
+pub mod platform;
 pub mod traits;
 
 pub use docker::DockerRuntime;
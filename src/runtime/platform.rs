@@ -0,0 +1,153 @@
+//! Container platform selection for Docker-backed execution.
+//!
+//! Multi-arch images carry one variant per CPU architecture, and Docker runs
+//! a mismatched variant under (slow) emulation. Passing an explicit
+//! `--platform` for the host architecture makes Docker pull the native
+//! variant. On Apple Silicon the host is detected as `arm64` even when this
+//! binary itself is an x86_64 build running under Rosetta.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Docker architecture name (`amd64`, `arm64`, ...) for a Rust, Go or Docker
+/// spelling of it. Unknown names are returned lowercased.
+pub fn normalize_arch(arch: &str) -> String {
+    match arch.trim().to_ascii_lowercase().as_str() {
+        "x86_64" | "x64" | "amd64" => "amd64".into(),
+        "aarch64" | "arm64" | "arm64/v8" => "arm64".into(),
+        "arm" | "armv7" | "arm/v7" | "armhf" => "arm".into(),
+        "x86" | "i386" | "i686" | "386" => "386".into(),
+        other => other.into(),
+    }
+}
+
+/// Whether this process is an x86_64 build translated by Rosetta on an
+/// Apple Silicon Mac.
+#[cfg(target_os = "macos")]
+fn running_under_rosetta() -> bool {
+    std::process::Command::new("sysctl")
+        .args(["-n", "sysctl.proc_translated"])
+        .output()
+        .map(|out| String::from_utf8_lossy(&out.stdout).trim() == "1")
+        .unwrap_or(false)
+}
+
+#[cfg(not(target_os = "macos"))]
+fn running_under_rosetta() -> bool {
+    false
+}
+
+/// Docker architecture of the host machine.
+pub fn host_arch() -> &'static str {
+    static ARCH: OnceLock<String> = OnceLock::new();
+    ARCH.get_or_init(|| {
+        let arch = normalize_arch(std::env::consts::ARCH);
+        if arch == "amd64" && running_under_rosetta() {
+            "arm64".into()
+        } else {
+            arch
+        }
+    })
+}
+
+/// Docker `--platform` value for `arch`. Containers are Linux even on macOS
+/// and Windows hosts.
+pub fn platform_for_arch(arch: &str) -> String {
+    match normalize_arch(arch).as_str() {
+        "arm" => "linux/arm/v7".into(),
+        arch => format!("linux/{arch}"),
+    }
+}
+
+/// Architecture part of a `os/arch[/variant]` platform string.
+pub fn platform_arch(platform: &str) -> String {
+    normalize_arch(platform.split('/').nth(1).unwrap_or(platform))
+}
+
+/// Check a configured `platform` override: empty (auto-detect) or
+/// `os/arch[/variant]`.
+pub fn validate_platform(platform: &str) -> anyhow::Result<()> {
+    let platform = platform.trim();
+    if platform.is_empty() {
+        return Ok(());
+    }
+    let parts: Vec<&str> = platform.split('/').collect();
+    if !(2..=3).contains(&parts.len()) || parts.iter().any(|part| part.trim().is_empty()) {
+        anyhow::bail!(
+            "invalid platform '{platform}'; expected os/arch[/variant], e.g. linux/arm64"
+        );
+    }
+    Ok(())
+}
+
+/// The platform to run containers as: `platform` when set, otherwise the
+/// host's native platform.
+pub fn resolve_platform(platform: &str) -> String {
+    let platform = platform.trim();
+    if platform.is_empty() {
+        platform_for_arch(host_arch())
+    } else {
+        platform.to_string()
+    }
+}
+
+/// The image for `platform`: a per-architecture entry of `images` (keys in
+/// any spelling [`normalize_arch`] accepts) or `default`.
+#[allow(clippy::implicit_hasher)]
+pub fn image_for_platform<'a>(
+    default: &'a str,
+    images: &'a HashMap<String, String>,
+    platform: &str,
+) -> &'a str {
+    let arch = platform_arch(platform);
+    images
+        .iter()
+        .find(|(key, image)| normalize_arch(key) == arch && !image.trim().is_empty())
+        .map_or(default, |(_, image)| image.as_str())
+        .trim()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arch_names_normalize_to_docker_spelling() {
+        assert_eq!(normalize_arch("x86_64"), "amd64");
+        assert_eq!(normalize_arch("aarch64"), "arm64");
+        assert_eq!(normalize_arch("ARM64"), "arm64");
+        assert_eq!(platform_for_arch("aarch64"), "linux/arm64");
+        assert_eq!(platform_for_arch("arm"), "linux/arm/v7");
+        assert_eq!(platform_arch("linux/arm/v7"), "arm");
+    }
+
+    #[test]
+    fn explicit_platform_wins_and_picks_arch_image() {
+        let images = HashMap::from([
+            (
+                "aarch64".to_string(),
+                "ghcr.io/acme/node:20-arm64".to_string(),
+            ),
+            ("amd64".to_string(), String::new()),
+        ]);
+        let platform = resolve_platform(" linux/arm64 ");
+        assert_eq!(platform, "linux/arm64");
+        assert_eq!(
+            image_for_platform("node:20", &images, &platform),
+            "ghcr.io/acme/node:20-arm64"
+        );
+        assert_eq!(
+            image_for_platform("node:20", &images, "linux/amd64"),
+            "node:20"
+        );
+        assert_eq!(resolve_platform(""), platform_for_arch(host_arch()));
+    }
+
+    #[test]
+    fn validate_platform_rejects_malformed_values() {
+        assert!(validate_platform("").is_ok());
+        assert!(validate_platform("linux/arm/v7").is_ok());
+        assert!(validate_platform("arm64").is_err());
+        assert!(validate_platform("linux/").is_err());
+    }
+}
@@ -0,0 +1,108 @@
+//! Terminal output cleanup.
+//!
+//! Commands that think they write to a terminal (always under a PTY, and
+//! often anyway because `TERM` is set) color their output, redraw progress
+//! bars with `\r` and end lines with `\r\n`. [`normalize`] turns that into
+//! the plain text the model reads: escape sequences and stray control
+//! characters are dropped, `\r\n` becomes `\n`, a line redrawn with `\r`
+//! keeps only its last drawing and backspaces erase.
+
+/// Plain text of terminal output `text`.
+pub fn normalize(text: &str) -> String {
+    let stripped = strip_escapes(text);
+    let mut out = String::with_capacity(stripped.len());
+    for (i, line) in stripped.split('\n').enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        // What a terminal would show last: the final non-empty drawing.
+        let drawn = line
+            .split('\r')
+            .rev()
+            .find(|segment| !segment.is_empty())
+            .unwrap_or("");
+        let mut visible = String::with_capacity(drawn.len());
+        for c in drawn.chars() {
+            match c {
+                '\u{8}' => {
+                    visible.pop();
+                }
+                '\t' => visible.push(c),
+                c if c.is_control() => {}
+                c => visible.push(c),
+            }
+        }
+        out.push_str(&visible);
+    }
+    out
+}
+
+/// `text` without ANSI escape sequences (CSI, OSC, DCS and the other string
+/// sequences, and two- or three-character escapes).
+fn strip_escapes(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\u{1b}' => match chars.next() {
+                Some('[') => skip_csi(&mut chars),
+                Some(']' | 'P' | 'X' | '^' | '_') => skip_string(&mut chars),
+                // Character set designations carry one more character.
+                Some('(' | ')' | '*' | '+' | '-' | '.' | '/' | '#' | '%') => {
+                    chars.next();
+                }
+                _ => {}
+            },
+            '\u{9b}' => skip_csi(&mut chars),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Skip a CSI sequence's parameters up to and including its final byte.
+fn skip_csi(chars: &mut impl Iterator<Item = char>) {
+    for c in chars.by_ref() {
+        if ('\u{40}'..='\u{7e}').contains(&c) {
+            break;
+        }
+    }
+}
+
+/// Skip a string sequence up to its terminator: BEL or `ESC \`.
+fn skip_string(chars: &mut std::iter::Peekable<impl Iterator<Item = char>>) {
+    while let Some(c) = chars.next() {
+        match c {
+            '\u{7}' | '\u{9c}' => break,
+            '\u{1b}' if chars.peek() == Some(&'\\') => {
+                chars.next();
+                break;
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_colors_and_terminal_titles() {
+        assert_eq!(
+            normalize("\u{1b}[1;32m✓\u{1b}[0m Compiled \u{1b}]0;next dev\u{7}ok\r\n"),
+            "✓ Compiled ok\n"
+        );
+        assert_eq!(normalize("\u{1b}(Bplain\u{1b}[?25l"), "plain");
+    }
+
+    #[test]
+    fn keeps_the_last_drawing_of_redrawn_lines() {
+        assert_eq!(
+            normalize("Progress: 10%\rProgress: 55%\rProgress: 100%\r\ndone"),
+            "Progress: 100%\ndone"
+        );
+        assert_eq!(normalize("\u{1b}[2K\rbuilding\u{1b}[K\r"), "building");
+        assert_eq!(normalize("abx\u{8}c\tz\u{7}"), "abc\tz");
+    }
+}
@@ -0,0 +1,323 @@
+//! Dev container settings of an existing repository
+//! (`.devcontainer/devcontainer.json` or `.devcontainer.json`).
+//!
+//! `github_read_repo` reads them so the agent works in the environment the
+//! repository expects: on providers that run container images the sandbox
+//! is recreated from `image` before the files are written, then the
+//! lifecycle commands (`onCreateCommand`, `updateContentCommand`,
+//! `postCreateCommand`) run in the project and every `forwardPorts` entry
+//! gets a preview URL. Features and Dockerfile builds need the dev
+//! container CLI, so they are only reported for the agent to set up by
+//! hand.
+
+use super::shell_ops::quote;
+use super::SandboxClient;
+use crate::security::guardrails::GuardrailPolicy;
+use serde_json::{json, Value};
+use std::fmt::Write;
+
+/// Paths of the dev container file, relative to the repository root, in
+/// the order they are looked up.
+pub const PATHS: &[&str] = &[".devcontainer/devcontainer.json", ".devcontainer.json"];
+
+/// Tool whose guardrails the lifecycle commands are checked against.
+const RUN_TOOL: &str = "sandbox_run_command";
+
+/// Output lines of a failed lifecycle command shown in the report.
+const FAILURE_TAIL_LINES: usize = 20;
+
+/// The settings of a `devcontainer.json` a sandbox can honour.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DevContainer {
+    /// Image the container runs.
+    pub image: Option<String>,
+    /// Dockerfile or Compose file the container is built from instead.
+    pub build: Option<String>,
+    /// IDs of the features installed on top of the image.
+    pub features: Vec<String>,
+    /// Ports of the container to expose (`host:port` entries of other
+    /// Compose services are left out).
+    pub forward_ports: Vec<u16>,
+    /// Lifecycle commands in the order they run, as `sh` command lines.
+    pub setup_commands: Vec<String>,
+}
+
+/// `text` without the `//` and `/* */` comments and trailing commas JSON
+/// with Comments allows.
+fn strip_jsonc(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                '\\' => out.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                out.push(c);
+            }
+            ('/', Some('/')) => while chars.next_if(|&c| c != '\n').is_some() {},
+            ('/', Some('*')) => {
+                chars.next();
+                let mut last = ' ';
+                for c in chars.by_ref() {
+                    if last == '*' && c == '/' {
+                        break;
+                    }
+                    last = c;
+                }
+                out.push(' ');
+            }
+            (']' | '}', _) => {
+                let kept = out.trim_end().len();
+                if out[..kept].ends_with(',') {
+                    out.truncate(kept - 1);
+                }
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// A lifecycle command: a command line, an argv array, or an object of
+/// named commands of either kind.
+fn commands(value: &Value) -> Vec<String> {
+    match value {
+        Value::String(command) if !command.trim().is_empty() => vec![command.trim().to_string()],
+        Value::Array(argv) => {
+            let argv: Vec<String> = argv.iter().filter_map(Value::as_str).map(quote).collect();
+            if argv.is_empty() {
+                Vec::new()
+            } else {
+                vec![argv.join(" ")]
+            }
+        }
+        Value::Object(named) => named.values().flat_map(commands).collect(),
+        _ => Vec::new(),
+    }
+}
+
+impl DevContainer {
+    /// Parse the contents of a `devcontainer.json`.
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let json: Value = serde_json::from_str(&strip_jsonc(text))
+            .map_err(|e| anyhow::anyhow!("Invalid devcontainer.json: {e}"))?;
+        let text = |value: &Value| {
+            value
+                .as_str()
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+        };
+        let build = text(&json["build"]["dockerfile"])
+            .or_else(|| text(&json["dockerFile"]))
+            .or_else(|| match &json["dockerComposeFile"] {
+                Value::Array(files) => files.first().and_then(text),
+                file => text(file),
+            });
+        let forward_ports = json["forwardPorts"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|port| match port {
+                Value::Number(port) => port.as_u64().and_then(|p| u16::try_from(p).ok()),
+                Value::String(port) => port.parse().ok(),
+                _ => None,
+            })
+            .filter(|&port| port > 0)
+            .collect();
+        Ok(Self {
+            image: text(&json["image"]),
+            build,
+            features: json["features"]
+                .as_object()
+                .map(|features| features.keys().cloned().collect())
+                .unwrap_or_default(),
+            forward_ports,
+            setup_commands: [
+                "onCreateCommand",
+                "updateContentCommand",
+                "postCreateCommand",
+            ]
+            .iter()
+            .flat_map(|key| commands(&json[*key]))
+            .collect(),
+        })
+    }
+}
+
+/// Run the lifecycle commands of `container` in `workdir`, stopping at the
+/// first failure or the first command `guardrails` block, and get preview
+/// URLs for its forwarded ports. Commands are checked as the
+/// `sandbox_run_command` calls that would run them. Returns a report for
+/// the agent.
+pub async fn setup(
+    client: &dyn SandboxClient,
+    guardrails: Option<&GuardrailPolicy>,
+    workdir: &str,
+    container: &DevContainer,
+    timeout_ms: u64,
+) -> String {
+    let mut report = String::new();
+    if let Some(build) = &container.build {
+        let _ = writeln!(
+            report,
+            "The dev container is built from {build}, which sandboxes do not build; install \
+             what it sets up with sandbox_run_command."
+        );
+    }
+    if !container.features.is_empty() {
+        let _ = writeln!(
+            report,
+            "Dev container features not installed (install the tools the build needs): {}",
+            container.features.join(", ")
+        );
+    }
+    for command in &container.setup_commands {
+        let call = json!({"command": command, "workdir": workdir});
+        if let Some(Err(blocked)) = guardrails.map(|policy| policy.check(RUN_TOOL, &call)) {
+            let _ = writeln!(
+                report,
+                "`{command}` blocked by guardrail, later commands were skipped: {}",
+                blocked.detail
+            );
+            if let Some(code) = blocked.approval_code {
+                let _ = writeln!(
+                    report,
+                    "The user can allow it once by sending `/approve {code}`; then run it with \
+                     sandbox_run_command {call}, followed by the skipped commands."
+                );
+            }
+            break;
+        }
+        match client.run_command(command, workdir, timeout_ms).await {
+            Ok(output) if output.exit_code == 0 => {
+                let _ = writeln!(report, "Ran `{command}`.");
+            }
+            Ok(output) => {
+                let combined = format!("{}\n{}", output.stdout, output.stderr);
+                let lines: Vec<&str> = combined.lines().filter(|l| !l.trim().is_empty()).collect();
+                let tail = &lines[lines.len().saturating_sub(FAILURE_TAIL_LINES)..];
+                let _ = writeln!(
+                    report,
+                    "`{command}` failed with exit code {}; later commands were skipped:\n{}",
+                    output.exit_code,
+                    tail.join("\n")
+                );
+                break;
+            }
+            Err(e) => {
+                let _ = writeln!(
+                    report,
+                    "Failed to run `{command}`; later commands were skipped: {e}"
+                );
+                break;
+            }
+        }
+    }
+    for port in &container.forward_ports {
+        let _ = match client.get_preview_url(*port).await {
+            Ok(url) => writeln!(report, "Forwarded port {port}: {url}"),
+            Err(e) => writeln!(report, "Forwarded port {port} has no preview URL: {e}"),
+        };
+    }
+    report.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sandbox::mock::MockSandboxClient;
+
+    const DEVCONTAINER: &str = r#"{
+        // Node app with a Postgres sidecar
+        "name": "web // app",
+        "image": "mcr.microsoft.com/devcontainers/typescript-node:20",
+        "features": {
+            "ghcr.io/devcontainers/features/github-cli:1": {},
+        },
+        /* 5432 is the database container */
+        "forwardPorts": [3000, "6006", "db:5432"],
+        "onCreateCommand": ["npm", "ci"],
+        "postCreateCommand": {
+            "migrate": "npm run db:migrate",
+            "seed": "npm run seed",
+        },
+    }"#;
+
+    #[test]
+    fn parses_jsonc_and_every_command_form() {
+        let container = DevContainer::parse(DEVCONTAINER).unwrap();
+        assert_eq!(
+            container,
+            DevContainer {
+                image: Some("mcr.microsoft.com/devcontainers/typescript-node:20".into()),
+                build: None,
+                features: vec!["ghcr.io/devcontainers/features/github-cli:1".into()],
+                forward_ports: vec![3000, 6006],
+                setup_commands: vec![
+                    "'npm' 'ci'".into(),
+                    "npm run db:migrate".into(),
+                    "npm run seed".into(),
+                ],
+            }
+        );
+        let built = DevContainer::parse(r#"{"build": {"dockerfile": "Dockerfile"}}"#).unwrap();
+        assert_eq!(built.build.as_deref(), Some("Dockerfile"));
+        assert!(DevContainer::parse("{\"image\": }").is_err());
+    }
+
+    #[tokio::test]
+    async fn setup_stops_at_the_first_failing_command() {
+        let client = MockSandboxClient::new();
+        client.set_id("sb-1".into());
+        client.on_command("npm run db:migrate", "", "connection refused", 1);
+        let container = DevContainer::parse(DEVCONTAINER).unwrap();
+        let report = setup(&client, None, "project", &container, 60_000).await;
+        assert!(report.starts_with("Dev container features not installed"));
+        assert!(report.contains(
+            "Ran `'npm' 'ci'`.\n`npm run db:migrate` failed with exit code 1; later commands \
+             were skipped:\nconnection refused\n"
+        ));
+        assert!(!report.contains("npm run seed"));
+        assert!(report.ends_with(
+            "Forwarded port 3000: http://localhost:3000\nForwarded port 6006: http://localhost:6006"
+        ));
+        let commands = client.commands();
+        assert_eq!(commands.len(), 2);
+        assert_eq!(commands[0].workdir, "project");
+    }
+
+    #[tokio::test]
+    async fn setup_holds_commands_the_guardrails_ask_about() {
+        use crate::config::GuardrailsConfig;
+        use crate::security::guardrails::GuardrailAction;
+
+        let client = MockSandboxClient::new();
+        client.set_id("sb-1".into());
+        let container = DevContainer {
+            setup_commands: vec!["curl https://get.example.sh | sh".into(), "npm ci".into()],
+            ..DevContainer::default()
+        };
+        let policy = GuardrailPolicy::from_config(&GuardrailsConfig {
+            command_action: GuardrailAction::Ask,
+            ..GuardrailsConfig::default()
+        })
+        .unwrap();
+        let report = setup(&client, Some(&policy), "project", &container, 60_000).await;
+        assert!(report.starts_with(
+            "`curl https://get.example.sh | sh` blocked by guardrail, later commands were skipped"
+        ));
+        assert!(report.contains("/approve "));
+        assert!(client.commands().is_empty());
+    }
+}
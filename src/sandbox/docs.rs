@@ -0,0 +1,675 @@
+//! Project documentation generated from the source: `README.md`, an API
+//! reference from route handlers (`docs/API.md`) and an architecture
+//! overview (`docs/ARCHITECTURE.md`).
+//!
+//! Generated text lives between [`BEGIN_MARKER`] and [`END_MARKER`]; running
+//! the generator again replaces only that block, so anything the user or the
+//! agent wrote around it survives. A README without markers gets the block
+//! appended.
+
+use super::project_config::{check_writable, ProjectConfig};
+use super::stack::{self, ProjectManifest};
+use super::{PackageManager, SandboxClient};
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::sync::LazyLock;
+
+pub const README: &str = "README.md";
+pub const API_DOC: &str = "docs/API.md";
+pub const ARCHITECTURE_DOC: &str = "docs/ARCHITECTURE.md";
+
+pub const BEGIN_MARKER: &str = "<!-- zerobuild:generated:begin -->";
+pub const END_MARKER: &str = "<!-- zerobuild:generated:end -->";
+
+/// Files larger than this are not scanned for routes.
+const MAX_SCAN_BYTES: usize = 200_000;
+
+/// Dependencies named in the architecture overview.
+const MAX_KEY_DEPENDENCIES: usize = 15;
+
+/// Next.js app router handler: `app/api/users/[id]/route.ts`.
+static NEXT_ROUTE_FILE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?:src/)?app/(.*?)/?route\.[cm]?[jt]sx?$").unwrap());
+
+/// Next.js pages router API handler: `pages/api/users/[id].ts`.
+static NEXT_PAGES_API_FILE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?:src/)?pages/(api/.*?)\.[cm]?[jt]sx?$").unwrap());
+
+/// Next.js app router page: `app/(shop)/products/page.tsx`.
+static NEXT_PAGE_FILE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?:src/)?app/(.*?)/?page\.[cm]?[jt]sx?$").unwrap());
+
+/// Exported HTTP method handlers of an app router `route.ts`.
+static NEXT_METHOD_EXPORT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"export\s+(?:async\s+)?(?:function|const)\s+(GET|POST|PUT|PATCH|DELETE|HEAD|OPTIONS)\b",
+    )
+    .unwrap()
+});
+
+/// Express, Fastify, Hono and Koa style: `router.get('/users/:id', ...)`.
+static JS_ROUTE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"\b(?:app|router|server|api|fastify|routes)\.(get|post|put|patch|delete|all)\(\s*['"`](/[^'"`]*)['"`]"#,
+    )
+    .unwrap()
+});
+
+/// FastAPI style: `@app.get("/users/{id}")`.
+static PY_METHOD_ROUTE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"@\w+\.(get|post|put|patch|delete)\(\s*['"](/[^'"]*)['"]"#).unwrap()
+});
+
+/// Flask style: `@app.route("/users", methods=["GET", "POST"])`.
+static PY_ROUTE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"@\w+\.route\(\s*['"](/[^'"]*)['"](?:.*methods\s*=\s*[\[(]([^\])]*)[\])])?"#)
+        .unwrap()
+});
+
+/// Axum style: `.route("/users/:id", get(show).delete(remove))`.
+static AXUM_ROUTE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\.route\(\s*"(/[^"]*)"\s*,(.*)"#).unwrap());
+
+/// Method routers inside an axum `.route(...)` call.
+static AXUM_METHOD: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b(get|post|put|patch|delete)\(").unwrap());
+
+/// Actix and Rocket style: `#[get("/users/{id}")]`.
+static RUST_ATTR_ROUTE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"#\[(get|post|put|patch|delete)\(\s*"(/[^"]*)""#).unwrap());
+
+/// One HTTP endpoint found in the source.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Route {
+    pub path: String,
+    pub method: String,
+    /// `file:line` of the handler.
+    pub source: String,
+}
+
+/// Whether `path` is a test file, whose route-like calls are requests.
+fn is_test_file(path: &str) -> bool {
+    path.contains(".test.")
+        || path.contains(".spec.")
+        || path.contains("__tests__/")
+        || path.starts_with("tests/")
+        || path.starts_with("test/")
+        || path
+            .rsplit('/')
+            .next()
+            .is_some_and(|name| name.starts_with("test_"))
+}
+
+/// URL path of a Next.js file-system route: `[id]` becomes `:id`, route
+/// groups `(shop)` disappear.
+fn next_url(segments: &str) -> String {
+    let parts: Vec<String> = segments
+        .split('/')
+        .filter(|part| !(part.is_empty() || part.starts_with('(') && part.ends_with(')')))
+        .filter(|part| *part != "index")
+        .map(|part| {
+            part.strip_prefix("[...")
+                .or_else(|| part.strip_prefix("[[..."))
+                .map(|name| format!(":{}*", name.trim_end_matches(']')))
+                .or_else(|| {
+                    part.strip_prefix('[')
+                        .map(|name| format!(":{}", name.trim_end_matches(']')))
+                })
+                .unwrap_or_else(|| part.to_string())
+        })
+        .collect();
+    format!("/{}", parts.join("/"))
+}
+
+fn line_of(content: &str, offset: usize) -> usize {
+    content[..offset].matches('\n').count() + 1
+}
+
+/// HTTP endpoints declared in `files` (paths relative to the project).
+pub fn extract_routes(files: &BTreeMap<String, String>) -> Vec<Route> {
+    let mut routes = Vec::new();
+    for (path, content) in files {
+        if content.len() > MAX_SCAN_BYTES || is_test_file(path) {
+            continue;
+        }
+        let mut push = |method: &str, url: String, offset: usize| {
+            routes.push(Route {
+                path: url,
+                method: method.to_ascii_uppercase(),
+                source: format!("{path}:{}", line_of(content, offset)),
+            });
+        };
+
+        if let Some(caps) = NEXT_ROUTE_FILE.captures(path) {
+            let url = next_url(&caps[1]);
+            for export in NEXT_METHOD_EXPORT.captures_iter(content) {
+                push(
+                    &export[1],
+                    url.clone(),
+                    export.get(0).map_or(0, |m| m.start()),
+                );
+            }
+            continue;
+        }
+        if let Some(caps) = NEXT_PAGES_API_FILE.captures(path) {
+            push("ANY", next_url(&caps[1]), 0);
+            continue;
+        }
+
+        let extension = path.rsplit_once('.').map_or("", |(_, ext)| ext);
+        match extension {
+            "js" | "ts" | "mjs" | "cjs" | "jsx" | "tsx" => {
+                for caps in JS_ROUTE.captures_iter(content) {
+                    let method = if &caps[1] == "all" { "ANY" } else { &caps[1] };
+                    push(
+                        method,
+                        caps[2].to_string(),
+                        caps.get(0).map_or(0, |m| m.start()),
+                    );
+                }
+            }
+            "py" => {
+                for caps in PY_METHOD_ROUTE.captures_iter(content) {
+                    push(
+                        &caps[1],
+                        caps[2].to_string(),
+                        caps.get(0).map_or(0, |m| m.start()),
+                    );
+                }
+                for caps in PY_ROUTE.captures_iter(content) {
+                    let offset = caps.get(0).map_or(0, |m| m.start());
+                    let methods: Vec<String> = caps.get(2).map_or_else(
+                        || vec!["GET".to_string()],
+                        |list| {
+                            list.as_str()
+                                .split(',')
+                                .map(|m| m.trim().trim_matches(['"', '\'']).to_string())
+                                .filter(|m| !m.is_empty())
+                                .collect()
+                        },
+                    );
+                    for method in methods {
+                        push(&method, caps[1].to_string(), offset);
+                    }
+                }
+            }
+            "rs" => {
+                for caps in AXUM_ROUTE.captures_iter(content) {
+                    let offset = caps.get(0).map_or(0, |m| m.start());
+                    for method in AXUM_METHOD.captures_iter(&caps[2]) {
+                        push(&method[1], caps[1].to_string(), offset);
+                    }
+                }
+                for caps in RUST_ATTR_ROUTE.captures_iter(content) {
+                    push(
+                        &caps[1],
+                        caps[2].to_string(),
+                        caps.get(0).map_or(0, |m| m.start()),
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+    routes.sort();
+    routes.dedup_by(|a, b| a.path == b.path && a.method == b.method);
+    routes
+}
+
+/// Pages of a Next.js app as `(url, file)`.
+fn extract_pages(files: &BTreeMap<String, String>) -> Vec<(String, &str)> {
+    let mut pages: Vec<(String, &str)> = files
+        .keys()
+        .filter_map(|path| {
+            NEXT_PAGE_FILE
+                .captures(path)
+                .map(|caps| (next_url(&caps[1]), path.as_str()))
+        })
+        .collect();
+    pages.sort();
+    pages
+}
+
+/// Replace the generated block of `existing`, or create the file from
+/// `header` and the block. A file without markers gets the block appended.
+pub fn merge_generated(existing: Option<&str>, header: &str, body: &str) -> String {
+    let block = format!("{BEGIN_MARKER}\n{}\n{END_MARKER}", body.trim_end());
+    let Some(existing) = existing else {
+        return format!("{header}{block}\n");
+    };
+    match (existing.find(BEGIN_MARKER), existing.find(END_MARKER)) {
+        (Some(begin), Some(end)) if begin < end => format!(
+            "{}{block}{}",
+            &existing[..begin],
+            &existing[end + END_MARKER.len()..]
+        ),
+        _ => format!("{}\n\n{block}\n", existing.trim_end()),
+    }
+}
+
+/// Everything the generator knows about the project.
+pub struct DocsInput<'a> {
+    /// Project name for a new README; the manifest's name wins.
+    pub name: &'a str,
+    /// Source files keyed by path relative to the project directory.
+    pub files: &'a BTreeMap<String, String>,
+    pub config: Option<&'a ProjectConfig>,
+    /// Package manager of a JavaScript project.
+    pub package_manager: Option<PackageManager>,
+}
+
+impl DocsInput<'_> {
+    fn manifests(&self) -> Vec<ProjectManifest> {
+        stack::MANIFESTS
+            .iter()
+            .filter_map(|file| {
+                self.files
+                    .get(*file)
+                    .and_then(|text| stack::parse_manifest(file, text))
+            })
+            .collect()
+    }
+
+    /// How to install, run, build and test the project, configured
+    /// commands first.
+    fn commands(&self, manifest: Option<&ProjectManifest>) -> Vec<(&'static str, String)> {
+        let configured = self.config.map(|config| &config.commands);
+        let script = |name: &str| {
+            let manager = self.package_manager?;
+            manifest?
+                .scripts
+                .iter()
+                .any(|(script, _)| script == name)
+                .then(|| format!("{} {name}", manager.run_cmd()))
+        };
+        let kind = manifest.map_or("", |m| m.file);
+        let fallback = |js: Option<String>, cargo: &str, python: &str| match kind {
+            "package.json" => js,
+            "Cargo.toml" => Some(cargo.to_string()).filter(|c| !c.is_empty()),
+            "pyproject.toml" => Some(python.to_string()).filter(|c| !c.is_empty()),
+            _ => None,
+        };
+
+        let install = configured.and_then(|c| c.install.clone()).or_else(|| {
+            fallback(
+                self.package_manager.map(|m| m.install_cmd().to_string()),
+                "",
+                "pip install -e .",
+            )
+        });
+        let start = configured
+            .and_then(|c| c.start.clone())
+            .or_else(|| fallback(script("dev").or_else(|| script("start")), "cargo run", ""));
+        let build = configured
+            .and_then(|c| c.build.clone())
+            .or_else(|| fallback(script("build"), "cargo build --release", ""));
+        let test = configured
+            .and_then(|c| c.test.clone())
+            .or_else(|| fallback(script("test"), "cargo test", "pytest"));
+
+        [
+            ("Install dependencies", install),
+            ("Start", start),
+            ("Build", build),
+            ("Test", test),
+        ]
+        .into_iter()
+        .filter_map(|(label, command)| command.map(|command| (label, command)))
+        .collect()
+    }
+}
+
+/// Directories with their file count and most common extensions, one level
+/// deep (two under `src/`).
+fn directory_layout(files: &BTreeMap<String, String>) -> Vec<(String, usize, Vec<String>)> {
+    let mut dirs: BTreeMap<String, (usize, HashMap<String, usize>)> = BTreeMap::new();
+    for path in files.keys() {
+        let parts: Vec<&str> = path.split('/').collect();
+        let dir = match parts.as_slice() {
+            [_] | [] => continue,
+            ["src", _] => "src".to_string(),
+            ["src", sub, ..] => format!("src/{sub}"),
+            [top, ..] => (*top).to_string(),
+        };
+        let entry = dirs.entry(dir).or_default();
+        entry.0 += 1;
+        if let Some((_, ext)) = parts[parts.len() - 1].rsplit_once('.') {
+            *entry.1.entry(format!(".{ext}")).or_default() += 1;
+        }
+    }
+    dirs.into_iter()
+        .map(|(dir, (count, extensions))| {
+            let mut extensions: Vec<(String, usize)> = extensions.into_iter().collect();
+            extensions.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            let top = extensions.into_iter().take(3).map(|(ext, _)| ext).collect();
+            (dir, count, top)
+        })
+        .collect()
+}
+
+/// Generated `(path, full content)` for each doc file, given the current
+/// contents of existing ones. `docs/API.md` is only created when the
+/// project has routes.
+#[allow(clippy::implicit_hasher)]
+pub fn generate_docs(
+    input: &DocsInput<'_>,
+    existing: &HashMap<&str, String>,
+) -> Vec<(&'static str, String)> {
+    let manifests = input.manifests();
+    let main = manifests.first();
+    let name = main.and_then(|m| m.name.as_deref()).unwrap_or(input.name);
+    let routes = extract_routes(input.files);
+    let has_api = !routes.is_empty() || existing.contains_key(API_DOC);
+
+    let mut docs = Vec::new();
+
+    // README.md
+    let mut readme = String::from("## Overview\n\n");
+    if let Some(stack) = main.and_then(ProjectManifest::stack) {
+        let _ = writeln!(readme, "**Stack:** {stack}\n");
+    }
+    let commands = input.commands(main);
+    if !commands.is_empty() {
+        readme.push_str("## Getting started\n\n```sh\n");
+        if let Some(workdir) = input.config.and_then(|c| c.workdir.as_deref()) {
+            let _ = writeln!(readme, "cd {workdir}");
+        }
+        for (label, command) in &commands {
+            let _ = writeln!(readme, "# {label}\n{command}");
+        }
+        readme.push_str("```\n\n");
+    }
+    if let Some(main) = main.filter(|m| !m.scripts.is_empty()) {
+        readme.push_str("## Scripts\n\n| Script | Command |\n|---|---|\n");
+        for (script, command) in &main.scripts {
+            let _ = writeln!(readme, "| `{script}` | `{}` |", command.replace('|', "\\|"));
+        }
+        readme.push('\n');
+    }
+    readme.push_str("## Documentation\n\n");
+    if has_api {
+        let _ = writeln!(readme, "- [API reference]({API_DOC})");
+    }
+    let _ = writeln!(readme, "- [Architecture]({ARCHITECTURE_DOC})");
+    let description = main
+        .and(input.files.get("package.json"))
+        .and_then(|text| serde_json::from_str::<serde_json::Value>(text).ok())
+        .and_then(|json| json["description"].as_str().map(str::to_string))
+        .map_or_else(String::new, |d| format!("{d}\n\n"));
+    docs.push((
+        README,
+        merge_generated(
+            existing.get(README).map(String::as_str),
+            &format!("# {name}\n\n{description}"),
+            &readme,
+        ),
+    ));
+
+    // docs/API.md
+    if has_api {
+        let mut api = String::from(
+            "Generated from the route handlers; regenerate after changing routes.\n\n",
+        );
+        if routes.is_empty() {
+            api.push_str("No HTTP routes found.\n");
+        } else {
+            api.push_str("| Method | Path | Handler |\n|---|---|---|\n");
+            for route in &routes {
+                let _ = writeln!(
+                    api,
+                    "| {} | `{}` | `{}` |",
+                    route.method, route.path, route.source
+                );
+            }
+        }
+        docs.push((
+            API_DOC,
+            merge_generated(
+                existing.get(API_DOC).map(String::as_str),
+                &format!("# {name} API\n\n"),
+                &api,
+            ),
+        ));
+    }
+
+    // docs/ARCHITECTURE.md
+    let mut arch = String::new();
+    if let Some(main) = main {
+        arch.push_str("## Stack\n\n");
+        if let Some(stack) = main.stack() {
+            let _ = writeln!(arch, "- {stack}");
+        }
+        let deps: Vec<String> = main
+            .dependencies
+            .iter()
+            .take(MAX_KEY_DEPENDENCIES)
+            .map(|dep| format!("`{}`", dep.name))
+            .collect();
+        if !deps.is_empty() {
+            let _ = writeln!(arch, "- Dependencies: {}", deps.join(", "));
+        }
+        arch.push('\n');
+    }
+    let entries: Vec<&str> = stack::ENTRY_CANDIDATES
+        .iter()
+        .copied()
+        .filter(|path| input.files.contains_key(*path))
+        .chain(
+            manifests
+                .iter()
+                .flat_map(|m| m.entry_points.iter().map(String::as_str)),
+        )
+        .collect();
+    if !entries.is_empty() {
+        arch.push_str("## Entry points\n\n");
+        for entry in entries {
+            let _ = writeln!(arch, "- `{entry}`");
+        }
+        arch.push('\n');
+    }
+    let pages = extract_pages(input.files);
+    if !pages.is_empty() {
+        arch.push_str("## Pages\n\n| Route | File |\n|---|---|\n");
+        for (url, file) in &pages {
+            let _ = writeln!(arch, "| `{url}` | `{file}` |");
+        }
+        arch.push('\n');
+    }
+    if !routes.is_empty() {
+        let _ = writeln!(
+            arch,
+            "## API\n\n{} endpoints; see [API reference](API.md).\n",
+            routes.len()
+        );
+    }
+    let layout = directory_layout(input.files);
+    if !layout.is_empty() {
+        arch.push_str("## Directory layout\n\n| Directory | Files | Main types |\n|---|---|---|\n");
+        for (dir, count, extensions) in layout {
+            let _ = writeln!(arch, "| `{dir}/` | {count} | {} |", extensions.join(", "));
+        }
+    }
+    docs.push((
+        ARCHITECTURE_DOC,
+        merge_generated(
+            existing.get(ARCHITECTURE_DOC).map(String::as_str),
+            &format!("# {name} architecture\n\n"),
+            &arch,
+        ),
+    ));
+
+    docs
+}
+
+/// What [`update_project_docs`] did to one file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DocStatus {
+    Created,
+    Updated,
+    Unchanged,
+    /// Protected by `.zerobuild.toml`; left alone.
+    Skipped(String),
+}
+
+/// Generate the docs of the project in `workdir` and write the ones that
+/// changed. Returns each doc's path relative to the sandbox root and what
+/// happened to it.
+pub async fn update_project_docs(
+    client: &dyn SandboxClient,
+    workdir: &str,
+) -> anyhow::Result<Vec<(String, DocStatus)>> {
+    let dir = workdir.trim_end_matches('/');
+    let files: BTreeMap<String, String> = client
+        .collect_snapshot_files(dir)
+        .await?
+        .into_iter()
+        .filter(|(path, _)| ![README, API_DOC, ARCHITECTURE_DOC].contains(&path.as_str()))
+        .collect();
+    let mut existing = HashMap::new();
+    for doc in [README, API_DOC, ARCHITECTURE_DOC] {
+        if let Ok(text) = client.read_file(&format!("{dir}/{doc}")).await {
+            existing.insert(doc, text);
+        }
+    }
+    let config = ProjectConfig::load(client).await.ok().flatten();
+    let package_manager = if files.contains_key("package.json") {
+        Some(
+            super::package_manager::detect_project_package_manager(client, dir)
+                .await
+                .manager,
+        )
+    } else {
+        None
+    };
+    let name = dir.rsplit('/').next().unwrap_or(dir);
+
+    let input = DocsInput {
+        name,
+        files: &files,
+        config: config.as_ref(),
+        package_manager,
+    };
+    let mut report = Vec::new();
+    for (doc, content) in generate_docs(&input, &existing) {
+        let path = format!("{dir}/{doc}");
+        let status = match existing.get(doc) {
+            Some(current) if *current == content => DocStatus::Unchanged,
+            current => match check_writable(client, &[&path]).await {
+                Err(reason) => DocStatus::Skipped(reason),
+                Ok(()) => {
+                    client.write_file(&path, &content).await?;
+                    if current.is_some() {
+                        DocStatus::Updated
+                    } else {
+                        DocStatus::Created
+                    }
+                }
+            },
+        };
+        report.push((path, status));
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+        entries
+            .iter()
+            .map(|(path, content)| ((*path).to_string(), (*content).to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn extracts_routes_across_frameworks() {
+        let routes = extract_routes(&files(&[
+            (
+                "app/api/users/[id]/route.ts",
+                "export async function GET() {}\nexport const DELETE = handler;",
+            ),
+            ("app/(shop)/api/cart/route.ts", "export function POST() {}"),
+            ("pages/api/health.ts", "export default function handler() {}"),
+            ("server.js", "const app = express();\napp.get('/items/:id', show);"),
+            ("main.py", "@app.get(\"/todos/{id}\")\ndef read(id): ...\n@bp.route('/login', methods=['GET', 'POST'])"),
+            ("src/main.rs", "Router::new().route(\"/notes\", get(list).post(create))"),
+            ("src/api.test.ts", "app.get('/ignored', h)"),
+        ]));
+        let summary: Vec<String> = routes
+            .iter()
+            .map(|r| format!("{} {} {}", r.method, r.path, r.source))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                "POST /api/cart app/(shop)/api/cart/route.ts:1",
+                "ANY /api/health pages/api/health.ts:1",
+                "DELETE /api/users/:id app/api/users/[id]/route.ts:2",
+                "GET /api/users/:id app/api/users/[id]/route.ts:1",
+                "GET /items/:id server.js:2",
+                "GET /login main.py:3",
+                "POST /login main.py:3",
+                "GET /notes src/main.rs:1",
+                "POST /notes src/main.rs:1",
+                "GET /todos/{id} main.py:1",
+            ]
+        );
+    }
+
+    #[test]
+    fn merge_keeps_text_outside_the_generated_block() {
+        let created = merge_generated(None, "# App\n\n", "v1");
+        assert_eq!(
+            created,
+            format!("# App\n\n{BEGIN_MARKER}\nv1\n{END_MARKER}\n")
+        );
+
+        let edited = created.replace("# App\n", "# App\n\nHand-written intro.\n");
+        let updated = merge_generated(Some(&edited), "# ignored\n", "v2");
+        assert!(updated.contains("Hand-written intro."));
+        assert!(updated.contains(&format!("{BEGIN_MARKER}\nv2\n{END_MARKER}")));
+        assert!(!updated.contains("v1"));
+        assert!(!updated.contains("# ignored"));
+
+        let appended = merge_generated(Some("# Boilerplate\n"), "# App\n", "v1");
+        assert!(appended.starts_with("# Boilerplate\n\n<!--"));
+    }
+
+    #[test]
+    fn generates_readme_api_and_architecture() {
+        let files = files(&[
+            (
+                "package.json",
+                r#"{"name": "shop", "description": "A tiny shop.", "scripts": {"dev": "next dev", "build": "next build"}, "dependencies": {"next": "14.2.3", "react": "18.3.1"}}"#,
+            ),
+            ("app/page.tsx", "export default function Home() {}"),
+            ("app/products/[slug]/page.tsx", ""),
+            (
+                "app/api/products/route.ts",
+                "export async function GET() {}",
+            ),
+            ("components/Header.tsx", ""),
+        ]);
+        let input = DocsInput {
+            name: "project",
+            files: &files,
+            config: None,
+            package_manager: Some(PackageManager::Pnpm),
+        };
+        let docs: HashMap<&str, String> =
+            generate_docs(&input, &HashMap::new()).into_iter().collect();
+
+        let readme = &docs[README];
+        assert!(readme.starts_with("# shop\n\nA tiny shop.\n\n"));
+        assert!(readme.contains("**Stack:** Next.js 14.2.3 (React)"));
+        assert!(readme.contains("# Install dependencies\npnpm install\n# Start\npnpm dev"));
+        assert!(readme.contains("- [API reference](docs/API.md)"));
+        assert!(docs[API_DOC].contains("| GET | `/api/products` | `app/api/products/route.ts:1` |"));
+        let arch = &docs[ARCHITECTURE_DOC];
+        assert!(arch.contains("| `/products/:slug` | `app/products/[slug]/page.tsx` |"));
+        assert!(arch.contains("| `components/` | 1 | .tsx |"));
+        assert!(arch.contains("- `app/page.tsx`"));
+    }
+}
@@ -0,0 +1,431 @@
+//! Sandbox provider failover (`[zerobuild.sandbox] providers`).
+//!
+//! [`FailoverSandboxClient`] fronts the configured providers in order and
+//! sends every call to the active one. When that provider fails
+//! `failover_after_errors` calls in a row with a provider-level error (network
+//! failure, rate limit, rejected credentials), the client creates a sandbox
+//! with the next provider, writes the latest project snapshot into it, and
+//! retries the failed call there, so a build survives an outage of the
+//! provider it started on. Files written since the last snapshot are lost.
+
+use super::{
+    CommandOutput, ListeningPort, PackageManager, SandboxClient, SandboxProcess, SessionOutput,
+    ShellSessionInfo,
+};
+use crate::error::{code_of, ErrorCode};
+use crate::store;
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Project root snapshots are restored under, as in `sandbox_restore_snapshot`.
+const SNAPSHOT_WORKDIR: &str = "project";
+
+/// Whether `err` means the provider itself is failing, rather than the call.
+fn is_provider_error(err: &anyhow::Error) -> bool {
+    matches!(
+        code_of(err),
+        ErrorCode::Network | ErrorCode::RateLimited | ErrorCode::AuthFailed
+    )
+}
+
+struct FailoverState {
+    active: usize,
+    consecutive_errors: u32,
+    /// Template and timeout of the last `create_sandbox`, reused for the
+    /// replacement sandbox.
+    created_with: Option<(String, u64)>,
+}
+
+/// A [`SandboxClient`] that moves to the next provider when the active one
+/// keeps failing.
+pub struct FailoverSandboxClient {
+    providers: Vec<(String, Arc<dyn SandboxClient>)>,
+    failover_after_errors: u32,
+    db_path: PathBuf,
+    state: Mutex<FailoverState>,
+}
+
+impl FailoverSandboxClient {
+    /// Client over `providers` as `(name, client)`, in failover order, that
+    /// restores snapshots from the store at `db_path`.
+    pub fn new(
+        providers: Vec<(String, Arc<dyn SandboxClient>)>,
+        failover_after_errors: u32,
+        db_path: impl Into<PathBuf>,
+    ) -> Self {
+        assert!(!providers.is_empty(), "at least one sandbox provider");
+        Self {
+            providers,
+            failover_after_errors: failover_after_errors.max(1),
+            db_path: db_path.into(),
+            state: Mutex::new(FailoverState {
+                active: 0,
+                consecutive_errors: 0,
+                created_with: None,
+            }),
+        }
+    }
+
+    /// Name of the provider calls go to.
+    pub fn active_provider(&self) -> &str {
+        &self.providers[self.state.lock().active].0
+    }
+
+    fn active(&self) -> (usize, Arc<dyn SandboxClient>) {
+        let active = self.state.lock().active;
+        (active, Arc::clone(&self.providers[active].1))
+    }
+
+    /// Run `op` on the active provider. After enough provider errors in a
+    /// row, fail over and run it once more on the new provider.
+    async fn call<T, F, Fut>(&self, op: F) -> anyhow::Result<T>
+    where
+        F: Fn(Arc<dyn SandboxClient>) -> Fut,
+        Fut: Future<Output = anyhow::Result<T>>,
+    {
+        let (index, client) = self.active();
+        let result = op(client).await;
+        match &result {
+            Err(e) if is_provider_error(e) => {
+                if self.record_error(index) && self.fail_over(index).await.is_ok() {
+                    let (_, client) = self.active();
+                    return op(client).await;
+                }
+            }
+            _ => self.state.lock().consecutive_errors = 0,
+        }
+        result
+    }
+
+    /// Count a provider error of provider `index`; true when it is time to
+    /// fail over to the next one.
+    fn record_error(&self, index: usize) -> bool {
+        let mut state = self.state.lock();
+        if state.active != index {
+            // Another call already failed over.
+            return true;
+        }
+        state.consecutive_errors += 1;
+        state.consecutive_errors >= self.failover_after_errors && index + 1 < self.providers.len()
+    }
+
+    /// Make the provider after `from` active with a fresh sandbox holding
+    /// the latest snapshot.
+    async fn fail_over(&self, from: usize) -> anyhow::Result<()> {
+        let (template, timeout_ms) = {
+            let state = self.state.lock();
+            if state.active != from {
+                return Ok(());
+            }
+            state.created_with.clone().unwrap_or_default()
+        };
+        let mut last_error = None;
+        for next in from + 1..self.providers.len() {
+            let (name, client) = &self.providers[next];
+            tracing::warn!(
+                from = %self.providers[from].0,
+                to = %name,
+                "Sandbox provider keeps failing; failing over"
+            );
+            match self.prepare(client.as_ref(), &template, timeout_ms).await {
+                Ok(restored) => {
+                    tracing::info!(provider = %name, restored, "Sandbox failover complete");
+                    let mut state = self.state.lock();
+                    state.active = next;
+                    state.consecutive_errors = 0;
+                    return Ok(());
+                }
+                Err(e) => {
+                    tracing::warn!(provider = %name, "Sandbox failover failed: {e:#}");
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| anyhow::anyhow!("No sandbox provider left")))
+    }
+
+    /// Create a sandbox on `client` and restore the latest snapshot into
+    /// it, returning the number of files restored.
+    async fn prepare(
+        &self,
+        client: &dyn SandboxClient,
+        template: &str,
+        timeout_ms: u64,
+    ) -> anyhow::Result<usize> {
+        client.create_sandbox(true, template, timeout_ms).await?;
+        let snapshot =
+            store::init_db(&self.db_path).and_then(|conn| store::snapshot::load_snapshot(&conn))?;
+        let Some((files, _)) = snapshot else {
+            return Ok(0);
+        };
+        let files: Vec<(String, String)> = files
+            .into_iter()
+            .map(|(path, content)| (format!("{SNAPSHOT_WORKDIR}/{path}"), content))
+            .collect();
+        let results = client.write_files(&files).await;
+        if let Some(Err(e)) = results.into_iter().find(Result::is_err) {
+            anyhow::bail!("Failed to restore the snapshot: {e}");
+        }
+        client.detect_package_manager().await;
+        Ok(files.len())
+    }
+}
+
+#[async_trait]
+impl SandboxClient for FailoverSandboxClient {
+    async fn create_sandbox(
+        &self,
+        reset: bool,
+        template: &str,
+        timeout_ms: u64,
+    ) -> anyhow::Result<String> {
+        self.state.lock().created_with = Some((template.to_string(), timeout_ms));
+        self.call(|client| async move { client.create_sandbox(reset, template, timeout_ms).await })
+            .await
+    }
+
+    async fn create_sandbox_from_image(
+        &self,
+        image: &str,
+        timeout_ms: u64,
+    ) -> anyhow::Result<String> {
+        self.call(|client| async move { client.create_sandbox_from_image(image, timeout_ms).await })
+            .await
+    }
+
+    fn runs_images(&self) -> bool {
+        self.active().1.runs_images()
+    }
+
+    async fn kill_sandbox(&self) -> anyhow::Result<String> {
+        self.active().1.kill_sandbox().await
+    }
+
+    async fn run_command(
+        &self,
+        command: &str,
+        workdir: &str,
+        timeout_ms: u64,
+    ) -> anyhow::Result<CommandOutput> {
+        self.call(|client| async move { client.run_command(command, workdir, timeout_ms).await })
+            .await
+    }
+
+    async fn run_command_in_pty(
+        &self,
+        command: &str,
+        workdir: &str,
+        timeout_ms: u64,
+    ) -> anyhow::Result<CommandOutput> {
+        self.call(|client| async move {
+            client
+                .run_command_in_pty(command, workdir, timeout_ms)
+                .await
+        })
+        .await
+    }
+
+    async fn run_command_streaming(
+        &self,
+        command: &str,
+        workdir: &str,
+        timeout_ms: u64,
+        lines: tokio::sync::mpsc::UnboundedSender<String>,
+    ) -> anyhow::Result<CommandOutput> {
+        self.call(|client| {
+            let lines = lines.clone();
+            async move {
+                client
+                    .run_command_streaming(command, workdir, timeout_ms, lines)
+                    .await
+            }
+        })
+        .await
+    }
+
+    async fn write_file(&self, path: &str, content: &str) -> anyhow::Result<()> {
+        self.call(|client| async move { client.write_file(path, content).await })
+            .await
+    }
+
+    async fn write_files(&self, files: &[(String, String)]) -> Vec<anyhow::Result<()>> {
+        // One failure for the whole batch decides whether it is retried.
+        let batch = self
+            .call(|client| async move {
+                let mut results = client.write_files(files).await;
+                if !results.is_empty() && results.iter().all(Result::is_err) {
+                    return Err(results.swap_remove(0).expect_err("every write failed"));
+                }
+                Ok(results)
+            })
+            .await;
+        batch.unwrap_or_else(|e| {
+            let message = format!("{e:#}");
+            files
+                .iter()
+                .map(|_| Err(anyhow::anyhow!("{message}")))
+                .collect()
+        })
+    }
+
+    async fn move_path(&self, from: &str, to: &str, overwrite: bool) -> anyhow::Result<()> {
+        self.call(|client| async move { client.move_path(from, to, overwrite).await })
+            .await
+    }
+
+    async fn copy_path(&self, from: &str, to: &str, overwrite: bool) -> anyhow::Result<()> {
+        self.call(|client| async move { client.copy_path(from, to, overwrite).await })
+            .await
+    }
+
+    async fn delete_path(&self, path: &str, recursive: bool) -> anyhow::Result<()> {
+        self.call(|client| async move { client.delete_path(path, recursive).await })
+            .await
+    }
+
+    async fn read_file(&self, path: &str) -> anyhow::Result<String> {
+        self.call(|client| async move { client.read_file(path).await })
+            .await
+    }
+
+    async fn list_files(&self, path: &str) -> anyhow::Result<String> {
+        self.call(|client| async move { client.list_files(path).await })
+            .await
+    }
+
+    async fn get_preview_url(&self, port: u16) -> anyhow::Result<String> {
+        self.call(|client| async move { client.get_preview_url(port).await })
+            .await
+    }
+
+    async fn start_tunnel(&self, port: u16) -> anyhow::Result<String> {
+        self.call(|client| async move { client.start_tunnel(port).await })
+            .await
+    }
+
+    async fn run_in_session(
+        &self,
+        session: &str,
+        command: &str,
+        timeout_ms: u64,
+    ) -> anyhow::Result<SessionOutput> {
+        self.call(|client| async move { client.run_in_session(session, command, timeout_ms).await })
+            .await
+    }
+
+    async fn close_session(&self, session: &str) -> anyhow::Result<bool> {
+        self.active().1.close_session(session).await
+    }
+
+    async fn shell_sessions(&self) -> Vec<ShellSessionInfo> {
+        self.active().1.shell_sessions().await
+    }
+
+    async fn processes(&self) -> anyhow::Result<Vec<SandboxProcess>> {
+        self.call(|client| async move { client.processes().await })
+            .await
+    }
+
+    async fn listening_ports(&self) -> anyhow::Result<Vec<ListeningPort>> {
+        self.call(|client| async move { client.listening_ports().await })
+            .await
+    }
+
+    async fn collect_snapshot_files(
+        &self,
+        workdir: &str,
+    ) -> anyhow::Result<HashMap<String, String>> {
+        self.call(|client| async move { client.collect_snapshot_files(workdir).await })
+            .await
+    }
+
+    fn current_id(&self) -> Option<String> {
+        self.active().1.current_id()
+    }
+
+    fn set_id(&self, id: String) {
+        self.active().1.set_id(id);
+    }
+
+    fn clear_id(&self) {
+        self.active().1.clear_id();
+    }
+
+    fn package_manager(&self) -> PackageManager {
+        self.active().1.package_manager()
+    }
+
+    fn set_package_manager(&self, pm: PackageManager) {
+        self.active().1.set_package_manager(pm);
+    }
+
+    async fn detect_package_manager(&self) -> PackageManager {
+        self.active().1.detect_package_manager().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ZerobuildError;
+    use crate::sandbox::mock::MockSandboxClient;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn repeated_provider_errors_fail_over_with_the_snapshot() {
+        let tmp = TempDir::new().unwrap();
+        let db_path = tmp.path().join("zerobuild.db");
+        let conn = store::init_db(&db_path).unwrap();
+        let snapshot = HashMap::from([("index.html".to_string(), "<h1>Hi</h1>".to_string())]);
+        store::snapshot::save_snapshot(&conn, &snapshot, Some("static")).unwrap();
+
+        let primary = Arc::new(MockSandboxClient::new());
+        let backup = Arc::new(MockSandboxClient::new());
+        let client = FailoverSandboxClient::new(
+            vec![
+                ("primary".into(), primary.clone() as Arc<dyn SandboxClient>),
+                ("backup".into(), backup.clone() as Arc<dyn SandboxClient>),
+            ],
+            2,
+            &db_path,
+        );
+        client.create_sandbox(false, "static", 1000).await.unwrap();
+
+        // Errors of the call itself never count.
+        assert!(client.read_file("missing.txt").await.is_err());
+        assert!(client.read_file("missing.txt").await.is_err());
+        assert_eq!(client.active_provider(), "primary");
+
+        primary.set_outage(Some(ZerobuildError::Network("connection reset".into())));
+        assert!(client.run_command("ls", "project", 1000).await.is_err());
+        assert_eq!(client.active_provider(), "primary");
+        let output = client.run_command("ls", "project", 1000).await.unwrap();
+        assert_eq!(output.exit_code, 0);
+        assert_eq!(client.active_provider(), "backup");
+        assert_eq!(
+            client.read_file("project/index.html").await.unwrap(),
+            "<h1>Hi</h1>"
+        );
+        assert_eq!(backup.commands().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn the_last_provider_keeps_its_errors() {
+        let tmp = TempDir::new().unwrap();
+        let only = Arc::new(MockSandboxClient::new());
+        let client = FailoverSandboxClient::new(
+            vec![("only".into(), only.clone() as Arc<dyn SandboxClient>)],
+            1,
+            tmp.path().join("zerobuild.db"),
+        );
+        only.set_outage(Some(ZerobuildError::RateLimited("quota exhausted".into())));
+
+        let err = client.create_sandbox(true, "", 1000).await.unwrap_err();
+        assert_eq!(code_of(&err), ErrorCode::RateLimited);
+        assert_eq!(client.active_provider(), "only");
+    }
+}
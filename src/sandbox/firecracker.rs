@@ -0,0 +1,680 @@
+//! MicroVM sandbox provider (`firecracker` in `[zerobuild.sandbox]
+//! providers`), on Firecracker or Cloud Hypervisor.
+//!
+//! Every sandbox is a VM booted from `[zerobuild.sandbox.firecracker]
+//! kernel` and its own copy of `rootfs`, so a build gets a kernel of its own
+//! without a container runtime or cloud account. The VM's network interface
+//! is the configured tap device; the kernel command line gives the guest
+//! `guest_ip`. Commands and file reads and writes go to the
+//! [`guest_agent`](super::guest_agent) the rootfs starts at boot; the other
+//! file operations are [`shell_ops`] scripts run through it. A preview port
+//! is forwarded from the same port on the host's loopback (or a free one
+//! when it is taken) to the guest.
+
+use super::guest_agent::{AgentClient, Frame, Request};
+use super::shell_ops;
+use super::{CommandOutput, PackageManager, SandboxClient};
+use crate::config::FirecrackerSandboxConfig;
+use crate::error::ZerobuildError;
+use anyhow::Context;
+use async_trait::async_trait;
+use base64::Engine as _;
+use parking_lot::Mutex;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::net::Ipv4Addr;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Sandbox root inside the guest.
+const SANDBOX_ROOT: &str = "/workspace";
+
+/// Interval between agent pings while a VM boots.
+const BOOT_POLL: Duration = Duration::from_millis(250);
+
+/// Extra time a command gets on top of its timeout before the client stops
+/// waiting for the agent to end it.
+const EXEC_GRACE: Duration = Duration::from_secs(5);
+
+/// Lines of the console log quoted when a VM fails to boot.
+const LOG_TAIL_LINES: usize = 20;
+
+/// A running VM.
+struct Vm {
+    id: String,
+    dir: PathBuf,
+    process: tokio::process::Child,
+}
+
+/// A preview port forwarded from the host.
+struct Forward {
+    url: String,
+    task: tokio::task::JoinHandle<()>,
+}
+
+/// MAC address of the guest: `06:00` then the octets of its address.
+fn guest_mac(guest_ip: Ipv4Addr) -> String {
+    let [a, b, c, d] = guest_ip.octets();
+    format!("06:00:{a:02X}:{b:02X}:{c:02X}:{d:02X}")
+}
+
+/// Kernel command line: `boot_args`, then the guest's static address.
+fn kernel_args(config: &FirecrackerSandboxConfig, root_device: bool) -> String {
+    let mut args = config.boot_args.trim().to_string();
+    if root_device {
+        args.push_str(" root=/dev/vda rw");
+    }
+    let _ = write!(
+        args,
+        " ip={}::{}:255.255.255.0::eth0:off",
+        config.guest_ip, config.host_ip
+    );
+    args.trim_start().to_string()
+}
+
+/// Firecracker `--config-file` of a VM booting `rootfs`.
+fn firecracker_config(config: &FirecrackerSandboxConfig, rootfs: &Path) -> anyhow::Result<Value> {
+    let guest_ip: Ipv4Addr = config.guest_ip.parse()?;
+    Ok(json!({
+        "boot-source": {
+            "kernel_image_path": config.kernel,
+            "boot_args": kernel_args(config, false),
+        },
+        "drives": [{
+            "drive_id": "rootfs",
+            "path_on_host": rootfs,
+            "is_root_device": true,
+            "is_read_only": false,
+        }],
+        "machine-config": {
+            "vcpu_count": config.vcpus,
+            "mem_size_mib": config.memory_mib,
+        },
+        "network-interfaces": [{
+            "iface_id": "eth0",
+            "guest_mac": guest_mac(guest_ip),
+            "host_dev_name": config.tap_device,
+        }],
+    }))
+}
+
+/// Arguments of the VMM for a VM whose files are in `dir`.
+fn vmm_args(config: &FirecrackerSandboxConfig, dir: &Path) -> anyhow::Result<Vec<String>> {
+    let rootfs = dir.join("rootfs.ext4");
+    if config.hypervisor == "cloud-hypervisor" {
+        let guest_ip: Ipv4Addr = config.guest_ip.parse()?;
+        return Ok(vec![
+            "--kernel".into(),
+            config.kernel.clone(),
+            "--cmdline".into(),
+            kernel_args(config, true),
+            "--disk".into(),
+            format!("path={}", rootfs.display()),
+            "--cpus".into(),
+            format!("boot={}", config.vcpus),
+            "--memory".into(),
+            format!("size={}M", config.memory_mib),
+            "--net".into(),
+            format!("tap={},mac={}", config.tap_device, guest_mac(guest_ip)),
+            "--serial".into(),
+            "tty".into(),
+            "--console".into(),
+            "off".into(),
+        ]);
+    }
+    let config_file = dir.join("vm.json");
+    std::fs::write(
+        &config_file,
+        serde_json::to_vec_pretty(&firecracker_config(config, &rootfs)?)?,
+    )
+    .with_context(|| format!("Failed to write {}", config_file.display()))?;
+    Ok(vec![
+        "--no-api".into(),
+        "--config-file".into(),
+        config_file.display().to_string(),
+    ])
+}
+
+/// Last lines of the console log in `dir`.
+fn log_tail(dir: &Path) -> String {
+    let log = std::fs::read_to_string(dir.join("console.log")).unwrap_or_default();
+    let lines: Vec<&str> = log.lines().collect();
+    lines[lines.len().saturating_sub(LOG_TAIL_LINES)..].join("\n")
+}
+
+/// Sandbox provider running each sandbox in a microVM.
+pub struct FirecrackerSandboxClient {
+    config: FirecrackerSandboxConfig,
+    agent: AgentClient,
+    /// Sandbox root in the guest; tests point it at a directory on the host.
+    root: String,
+    vm: Mutex<Option<Vm>>,
+    id: Mutex<Option<String>>,
+    forwards: Mutex<HashMap<u16, Forward>>,
+    package_manager: Mutex<PackageManager>,
+}
+
+impl FirecrackerSandboxClient {
+    pub fn new(config: FirecrackerSandboxConfig) -> Self {
+        let agent = AgentClient::new(format!("{}:{}", config.guest_ip, config.agent_port));
+        Self {
+            config,
+            agent,
+            root: SANDBOX_ROOT.to_string(),
+            vm: Mutex::new(None),
+            id: Mutex::new(None),
+            forwards: Mutex::new(HashMap::new()),
+            package_manager: Mutex::new(PackageManager::default()),
+        }
+    }
+
+    fn state_dir(&self) -> anyhow::Result<PathBuf> {
+        if !self.config.state_dir.is_empty() {
+            return Ok(PathBuf::from(&self.config.state_dir));
+        }
+        let home = std::env::var("HOME")
+            .or_else(|_| std::env::var("USERPROFILE"))
+            .map_err(|_| anyhow::anyhow!("Unable to determine home directory"))?;
+        Ok(PathBuf::from(home).join(".zerobuild").join("firecracker"))
+    }
+
+    fn sandbox_path(&self, relative: &str) -> anyhow::Result<String> {
+        shell_ops::sandbox_path(&self.root, relative)
+    }
+
+    /// Whether the VM process of the active sandbox still runs.
+    fn vm_running(&self) -> bool {
+        self.vm
+            .lock()
+            .as_mut()
+            .is_some_and(|vm| matches!(vm.process.try_wait(), Ok(None)))
+    }
+
+    /// Stop the VM, its port forwards and delete its files.
+    async fn stop_vm(&self) {
+        for (_, forward) in self.forwards.lock().drain() {
+            forward.task.abort();
+        }
+        let vm = self.vm.lock().take();
+        if let Some(mut vm) = vm {
+            let _ = vm.process.start_kill();
+            let _ = vm.process.wait().await;
+            if let Err(e) = tokio::fs::remove_dir_all(&vm.dir).await {
+                tracing::warn!(vm = %vm.id, "Failed to delete the sandbox VM's files: {e}");
+            }
+        }
+    }
+
+    /// Send `request` to the agent of the active sandbox.
+    async fn call(&self, request: &Request, on_frame: impl FnMut(&Frame)) -> anyhow::Result<Frame> {
+        let id = self.require_id().map_err(|e| anyhow::anyhow!(e))?;
+        match self.agent.call(request, on_frame).await {
+            Ok(Frame::Error { message, .. }) if !matches!(request, Request::Read { .. }) => {
+                anyhow::bail!("Sandbox agent: {message}")
+            }
+            Ok(frame) => Ok(frame),
+            Err(_) if !self.vm_running() => Err(ZerobuildError::SandboxExpired(id).into()),
+            Err(e) => Err(ZerobuildError::Network(format!(
+                "Sandbox agent at {}: {e}",
+                self.agent.addr()
+            ))
+            .into()),
+        }
+    }
+
+    /// Run `script` in `dir` (absolute, in the guest), killed after
+    /// `timeout_ms`, sending output lines to `lines` as they arrive.
+    async fn exec(
+        &self,
+        script: &str,
+        dir: String,
+        timeout_ms: u64,
+        lines: Option<&tokio::sync::mpsc::UnboundedSender<String>>,
+    ) -> anyhow::Result<CommandOutput> {
+        let request = Request::Exec {
+            command: script.to_string(),
+            workdir: dir,
+            timeout_ms,
+        };
+        let mut stdout = String::new();
+        let mut stderr = String::new();
+        let call = self.call(&request, |frame| {
+            let (Frame::Stdout(chunk) | Frame::Stderr(chunk)) = frame else {
+                return;
+            };
+            if let Some(lines) = lines {
+                for line in chunk.split(['\r', '\n']) {
+                    let line = super::ansi::normalize(line);
+                    if !line.trim().is_empty() {
+                        let _ = lines.send(line);
+                    }
+                }
+            }
+            if matches!(frame, Frame::Stdout(_)) {
+                stdout.push_str(chunk);
+            } else {
+                stderr.push_str(chunk);
+            }
+        });
+        let limit = Duration::from_millis(timeout_ms) + EXEC_GRACE;
+        let exit_code = match tokio::time::timeout(limit, call).await {
+            Ok(Ok(Frame::Exit(code))) => code,
+            Ok(Ok(frame)) => anyhow::bail!("Unexpected answer from the sandbox agent: {frame:?}"),
+            Ok(Err(e)) => return Err(e),
+            Err(_) => {
+                return Ok(CommandOutput {
+                    stdout,
+                    stderr: format!("Command timed out after {timeout_ms}ms"),
+                    exit_code: -1,
+                })
+            }
+        };
+        Ok(CommandOutput {
+            stdout,
+            stderr,
+            exit_code,
+        }
+        .normalized())
+    }
+
+    /// Run a file operation `script` at the sandbox root, failing with its
+    /// stderr.
+    async fn file_op(&self, script: &str, what: &str) -> anyhow::Result<String> {
+        let output = self.exec(script, self.root.clone(), 60_000, None).await?;
+        if output.exit_code != 0 {
+            let detail = output.stderr.trim();
+            anyhow::bail!(
+                "{what}: {}",
+                if detail.is_empty() { "failed" } else { detail }
+            );
+        }
+        Ok(output.stdout)
+    }
+
+    async fn transfer(
+        &self,
+        verb: &str,
+        from: &str,
+        to: &str,
+        overwrite: bool,
+    ) -> anyhow::Result<()> {
+        let script = shell_ops::transfer_script(&self.root, verb, from, to, overwrite)?;
+        let action = if verb == "mv" { "move" } else { "copy" };
+        self.file_op(&script, &format!("Failed to {action} '{from}' to '{to}'"))
+            .await
+            .map(|_| ())
+    }
+}
+
+/// Forward connections accepted on `listener` to `target`.
+async fn forward(listener: tokio::net::TcpListener, target: String) {
+    while let Ok((mut inbound, _)) = listener.accept().await {
+        let target = target.clone();
+        tokio::spawn(async move {
+            match tokio::net::TcpStream::connect(&target).await {
+                Ok(mut outbound) => {
+                    let _ = tokio::io::copy_bidirectional(&mut inbound, &mut outbound).await;
+                }
+                Err(e) => tracing::debug!(%target, "Preview forward failed: {e}"),
+            }
+        });
+    }
+}
+
+#[async_trait]
+impl SandboxClient for FirecrackerSandboxClient {
+    async fn create_sandbox(
+        &self,
+        reset: bool,
+        _template: &str,
+        timeout_ms: u64,
+    ) -> anyhow::Result<String> {
+        if let Some(id) = self.current_id() {
+            if !reset && self.vm_running() && self.agent.ping().await {
+                tracing::Span::current().record("sandbox_id", id.as_str());
+                return Ok(id);
+            }
+            self.stop_vm().await;
+            self.clear_id();
+        }
+
+        let id = format!("fc-{}", &Uuid::new_v4().simple().to_string()[..12]);
+        let dir = self.state_dir()?.join(&id);
+        tokio::fs::create_dir_all(&dir)
+            .await
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+        // Reflinked or sparse where the filesystem allows, so a multi-GB
+        // image copies in moments.
+        let copied = tokio::process::Command::new("cp")
+            .args(["--reflink=auto", "--sparse=always"])
+            .arg(&self.config.rootfs)
+            .arg(dir.join("rootfs.ext4"))
+            .output()
+            .await
+            .context("Failed to run cp")?;
+        if !copied.status.success() {
+            let _ = tokio::fs::remove_dir_all(&dir).await;
+            anyhow::bail!(
+                "Failed to copy the sandbox rootfs {}: {}",
+                self.config.rootfs,
+                String::from_utf8_lossy(&copied.stderr).trim()
+            );
+        }
+
+        let binary = if self.config.binary.is_empty() {
+            &self.config.hypervisor
+        } else {
+            &self.config.binary
+        };
+        let log = std::fs::File::create(dir.join("console.log"))?;
+        let mut process = tokio::process::Command::new(binary)
+            .args(vmm_args(&self.config, &dir)?)
+            .stdin(Stdio::null())
+            .stdout(log.try_clone()?)
+            .stderr(log)
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to start {binary}"))?;
+
+        let deadline = tokio::time::Instant::now() + Duration::from_millis(timeout_ms);
+        loop {
+            if let Ok(Some(status)) = process.try_wait() {
+                let tail = log_tail(&dir);
+                let _ = tokio::fs::remove_dir_all(&dir).await;
+                anyhow::bail!("Sandbox VM {id} exited while booting ({status}):\n{tail}");
+            }
+            if self.agent.ping().await {
+                break;
+            }
+            if tokio::time::Instant::now() >= deadline {
+                let _ = process.kill().await;
+                let tail = log_tail(&dir);
+                let _ = tokio::fs::remove_dir_all(&dir).await;
+                return Err(ZerobuildError::Timeout(format!(
+                    "sandbox VM {id}'s agent did not answer within {timeout_ms}ms; console:\n{tail}"
+                ))
+                .into());
+            }
+            tokio::time::sleep(BOOT_POLL).await;
+        }
+
+        *self.vm.lock() = Some(Vm {
+            id: id.clone(),
+            dir,
+            process,
+        });
+        self.set_id(id.clone());
+        tracing::Span::current().record("sandbox_id", id.as_str());
+        tracing::info!(vm = %id, hypervisor = %self.config.hypervisor, "MicroVM sandbox created");
+        Ok(id)
+    }
+
+    async fn kill_sandbox(&self) -> anyhow::Result<String> {
+        let Some(id) = self.current_id() else {
+            return Ok("No active microVM sandbox to kill.".to_string());
+        };
+        self.stop_vm().await;
+        self.clear_id();
+        tracing::info!(vm = %id, "MicroVM sandbox stopped");
+        Ok(format!("MicroVM sandbox {id} stopped."))
+    }
+
+    async fn run_command(
+        &self,
+        command: &str,
+        workdir: &str,
+        timeout_ms: u64,
+    ) -> anyhow::Result<CommandOutput> {
+        let dir = self.sandbox_path(workdir)?;
+        self.exec(command, dir, timeout_ms, None).await
+    }
+
+    async fn run_command_streaming(
+        &self,
+        command: &str,
+        workdir: &str,
+        timeout_ms: u64,
+        lines: tokio::sync::mpsc::UnboundedSender<String>,
+    ) -> anyhow::Result<CommandOutput> {
+        let dir = self.sandbox_path(workdir)?;
+        self.exec(command, dir, timeout_ms, Some(&lines)).await
+    }
+
+    async fn write_file(&self, path: &str, content: &str) -> anyhow::Result<()> {
+        let request = Request::Write {
+            path: self.sandbox_path(path)?,
+            content: base64::engine::general_purpose::STANDARD.encode(content),
+        };
+        self.call(&request, |_| {})
+            .await
+            .with_context(|| format!("Failed to write file '{path}'"))
+            .map(|_| ())
+    }
+
+    async fn move_path(&self, from: &str, to: &str, overwrite: bool) -> anyhow::Result<()> {
+        self.transfer("mv", from, to, overwrite).await
+    }
+
+    async fn copy_path(&self, from: &str, to: &str, overwrite: bool) -> anyhow::Result<()> {
+        self.transfer("cp -R", from, to, overwrite).await
+    }
+
+    async fn delete_path(&self, path: &str, recursive: bool) -> anyhow::Result<()> {
+        let script = shell_ops::delete_script(&self.root, path, recursive)?;
+        self.file_op(&script, &format!("Failed to delete '{path}'"))
+            .await
+            .map(|_| ())
+    }
+
+    async fn read_file(&self, path: &str) -> anyhow::Result<String> {
+        let request = Request::Read {
+            path: self.sandbox_path(path)?,
+        };
+        match self.call(&request, |_| {}).await? {
+            Frame::Content(encoded) => {
+                let bytes = base64::engine::general_purpose::STANDARD.decode(encoded)?;
+                Ok(String::from_utf8_lossy(&bytes).into_owned())
+            }
+            Frame::Error {
+                not_found: true, ..
+            } => Err(ZerobuildError::FileNotFound(path.to_string()).into()),
+            Frame::Error { message, .. } => {
+                anyhow::bail!("Failed to read file '{path}': {message}")
+            }
+            frame => anyhow::bail!("Unexpected answer from the sandbox agent: {frame:?}"),
+        }
+    }
+
+    async fn list_files(&self, path: &str) -> anyhow::Result<String> {
+        let script = shell_ops::list_script(&self.root, path)?;
+        let output = self
+            .file_op(&script, &format!("Failed to list directory '{path}'"))
+            .await?;
+        Ok(shell_ops::list_entries(&output))
+    }
+
+    async fn get_preview_url(&self, port: u16) -> anyhow::Result<String> {
+        self.require_id().map_err(|e| anyhow::anyhow!(e))?;
+        if let Some(forward) = self.forwards.lock().get(&port) {
+            return Ok(forward.url.clone());
+        }
+        let listener = match tokio::net::TcpListener::bind(("127.0.0.1", port)).await {
+            Ok(listener) => listener,
+            Err(_) => tokio::net::TcpListener::bind(("127.0.0.1", 0))
+                .await
+                .with_context(|| format!("Failed to forward port {port}"))?,
+        };
+        let url = format!("http://localhost:{}", listener.local_addr()?.port());
+        let target = format!("{}:{port}", self.config.guest_ip);
+        let task = tokio::spawn(forward(listener, target));
+        let mut forwards = self.forwards.lock();
+        // Another call may have forwarded the port meanwhile.
+        if let Some(existing) = forwards.get(&port) {
+            task.abort();
+            return Ok(existing.url.clone());
+        }
+        forwards.insert(
+            port,
+            Forward {
+                url: url.clone(),
+                task,
+            },
+        );
+        Ok(url)
+    }
+
+    async fn collect_snapshot_files(
+        &self,
+        workdir: &str,
+    ) -> anyhow::Result<HashMap<String, String>> {
+        let script = shell_ops::snapshot_script(&self.root, workdir)?;
+        let output = self
+            .file_op(&script, &format!("Failed to collect files of '{workdir}'"))
+            .await?;
+        Ok(shell_ops::parse_snapshot(&output))
+    }
+
+    fn current_id(&self) -> Option<String> {
+        self.id.lock().clone()
+    }
+
+    fn set_id(&self, id: String) {
+        *self.id.lock() = Some(id);
+    }
+
+    fn clear_id(&self) {
+        *self.id.lock() = None;
+    }
+
+    fn package_manager(&self) -> PackageManager {
+        *self.package_manager.lock()
+    }
+
+    fn set_package_manager(&self, pm: PackageManager) {
+        *self.package_manager.lock() = pm;
+    }
+
+    /// Detect the package manager installed in the rootfs.
+    async fn detect_package_manager(&self) -> PackageManager {
+        let output = self
+            .exec(
+                shell_ops::DETECT_PACKAGE_MANAGER,
+                self.root.clone(),
+                30_000,
+                None,
+            )
+            .await;
+        let pm = match output {
+            Ok(output) => PackageManager::from_name(output.stdout.trim()).unwrap_or_default(),
+            Err(e) => {
+                tracing::warn!("Failed to detect the sandbox's package manager: {e:#}");
+                PackageManager::default()
+            }
+        };
+        self.set_package_manager(pm);
+        pm
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> FirecrackerSandboxConfig {
+        FirecrackerSandboxConfig {
+            kernel: "/images/vmlinux".into(),
+            rootfs: "/images/rootfs.ext4".into(),
+            ..FirecrackerSandboxConfig::default()
+        }
+    }
+
+    #[test]
+    fn vms_boot_with_a_static_guest_address() {
+        let dir = tempfile::tempdir().unwrap();
+        let args = vmm_args(&config(), dir.path()).unwrap();
+        assert_eq!(args[..2], ["--no-api", "--config-file"]);
+        let vm: Value =
+            serde_json::from_slice(&std::fs::read(dir.path().join("vm.json")).unwrap()).unwrap();
+        assert_eq!(
+            vm["boot-source"]["boot_args"],
+            "console=ttyS0 reboot=k panic=1 ip=172.16.0.2::172.16.0.1:255.255.255.0::eth0:off"
+        );
+        assert_eq!(
+            vm["network-interfaces"][0]["guest_mac"],
+            "06:00:AC:10:00:02"
+        );
+        assert_eq!(vm["network-interfaces"][0]["host_dev_name"], "zb-tap0");
+        assert_eq!(vm["machine-config"]["mem_size_mib"], 2048);
+
+        let cloud = FirecrackerSandboxConfig {
+            hypervisor: "cloud-hypervisor".into(),
+            ..config()
+        };
+        let args = vmm_args(&cloud, dir.path()).unwrap();
+        let cmdline = &args[args.iter().position(|a| a == "--cmdline").unwrap() + 1];
+        assert!(cmdline.contains(" root=/dev/vda rw ip=172.16.0.2::"));
+        assert!(args.contains(&"tap=zb-tap0,mac=06:00:AC:10:00:02".to_string()));
+    }
+
+    #[tokio::test]
+    async fn commands_and_files_go_through_the_agent() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let agent = listener.local_addr().unwrap();
+        tokio::spawn(super::super::guest_agent::serve(listener));
+        let root = tempfile::tempdir().unwrap();
+        let mut client = FirecrackerSandboxClient::new(FirecrackerSandboxConfig {
+            guest_ip: "127.0.0.1".into(),
+            ..config()
+        });
+        client.agent = AgentClient::new(agent.to_string());
+        client.root = root.path().to_string_lossy().into_owned();
+        assert!(client.run_command("true", ".", 1_000).await.is_err());
+        client.set_id("fc-test".into());
+
+        client
+            .write_file("app/index.js", "console.log('hi')")
+            .await
+            .unwrap();
+        let output = client
+            .run_command("cat index.js", "app", 10_000)
+            .await
+            .unwrap();
+        assert_eq!(output.stdout, "console.log('hi')");
+        client
+            .copy_path("app/index.js", "app/main.js", false)
+            .await
+            .unwrap();
+        assert_eq!(
+            client.list_files("app").await.unwrap(),
+            "file\tindex.js\nfile\tmain.js"
+        );
+        let missing = client.read_file("app/missing.js").await.unwrap_err();
+        assert!(matches!(
+            missing.downcast_ref::<ZerobuildError>(),
+            Some(ZerobuildError::FileNotFound(_))
+        ));
+        assert!(client.read_file("../etc/passwd").await.is_err());
+
+        // The preview port is forwarded to the guest address.
+        let app = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = app.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            axum::serve(
+                app,
+                axum::Router::new().route("/", axum::routing::get(|| async { "up" })),
+            )
+            .await
+            .unwrap();
+        });
+        let url = client.get_preview_url(port).await.unwrap();
+        assert_ne!(url, format!("http://localhost:{port}"));
+        assert_eq!(
+            reqwest::get(&url).await.unwrap().text().await.unwrap(),
+            "up"
+        );
+        assert_eq!(client.get_preview_url(port).await.unwrap(), url);
+    }
+}
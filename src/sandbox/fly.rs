@@ -0,0 +1,647 @@
+//! Fly.io sandbox provider (`fly` in `[zerobuild.sandbox] providers`).
+//!
+//! Every sandbox is a Fly app of its own (`zb-<id>`) with one Machine
+//! running the image of its template (`[zerobuild.templates.<name>] image`)
+//! or `[zerobuild.sandbox.fly] image`, with the sandbox root at
+//! `/workspace`. The app gets a shared IPv4 and an IPv6 address, so its
+//! `preview_ports` are public at `https://<app>.fly.dev` from the start,
+//! unlike the `local` provider's loopback previews. Commands go through the
+//! Machines exec API; file operations are [`shell_ops`] scripts run the
+//! same way. Deleting the app removes the Machine and its addresses.
+
+use super::shell_ops::{self, quote};
+use super::{CommandOutput, PackageManager, SandboxClient};
+use crate::config::{FlySandboxConfig, SandboxTemplateConfig};
+use crate::error::ZerobuildError;
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Sandbox root inside the Machine.
+const SANDBOX_ROOT: &str = "/workspace";
+
+/// GraphQL API, for the app addresses the Machines API doesn't allocate.
+const GRAPHQL_URL: &str = "https://api.fly.io/graphql";
+
+/// Allocates an address of `type` to the app `appId`.
+const ALLOCATE_IP: &str = "mutation($input: AllocateIPAddressInput!) { \
+    allocateIpAddress(input: $input) { app { name } } }";
+
+/// Interval between Machine state checks while a sandbox starts.
+const START_POLL: Duration = Duration::from_secs(1);
+
+/// Extra time a command gets on top of its timeout before the client stops
+/// waiting for `timeout(1)` in the Machine to end it.
+const EXEC_GRACE: Duration = Duration::from_secs(5);
+
+/// `Authorization` header of `token`: org and deploy tokens already carry
+/// their `FlyV1` scheme, personal tokens are bearer tokens.
+fn authorization(token: &str) -> String {
+    if token.starts_with("FlyV1 ") {
+        token.to_string()
+    } else {
+        format!("Bearer {token}")
+    }
+}
+
+/// The `error` of a Fly API error body, else the body itself.
+fn api_message(body: &str) -> String {
+    serde_json::from_str::<Value>(body)
+        .ok()
+        .and_then(|error| error["error"].as_str().map(str::to_string))
+        .unwrap_or_else(|| body.trim().to_string())
+}
+
+/// Machine of a sandbox: `image` idling until commands are exec'd into it,
+/// with the first preview port on 80 and 443 of the app's hostname and the
+/// others on their own port.
+fn machine_config(image: &str, config: &FlySandboxConfig) -> Value {
+    let services: Vec<Value> = config
+        .preview_ports
+        .iter()
+        .enumerate()
+        .map(|(i, &port)| {
+            let ports = if i == 0 {
+                json!([
+                    {"port": 443, "handlers": ["tls", "http"]},
+                    {"port": 80, "handlers": ["http"], "force_https": true},
+                ])
+            } else {
+                json!([{"port": port, "handlers": ["tls", "http"]}])
+            };
+            json!({"protocol": "tcp", "internal_port": port, "ports": ports})
+        })
+        .collect();
+    let mut body = json!({
+        "config": {
+            "image": image,
+            "init": {
+                "exec": ["/bin/sh", "-c", format!("mkdir -p {SANDBOX_ROOT} && exec sleep infinity")],
+            },
+            "env": {"HOME": SANDBOX_ROOT},
+            "guest": {
+                "cpu_kind": config.cpu_kind,
+                "cpus": config.cpus,
+                "memory_mb": config.memory_mb,
+            },
+            "services": services,
+            "restart": {"policy": "no"},
+        },
+    });
+    if !config.region.is_empty() {
+        body["region"] = json!(config.region);
+    }
+    body
+}
+
+/// Public URL of `port` of `app`, if it is one of `preview_ports`.
+fn preview_url(app: &str, port: u16, preview_ports: &[u16]) -> Option<String> {
+    match preview_ports.iter().position(|&p| p == port)? {
+        0 => Some(format!("https://{app}.fly.dev")),
+        _ => Some(format!("https://{app}.fly.dev:{port}")),
+    }
+}
+
+/// Output of an exec answer (`exit_code`, `stdout`, `stderr`).
+fn exec_output(answer: &Value, timeout_ms: u64) -> CommandOutput {
+    let text = |key: &str| answer[key].as_str().unwrap_or_default().to_string();
+    let mut stderr = text("stderr");
+    let exit_code = match answer["exit_code"].as_i64() {
+        // `timeout -s KILL` ends the command with 128 + SIGKILL.
+        Some(137) => {
+            stderr = format!("Command timed out after {timeout_ms}ms");
+            -1
+        }
+        Some(code) => code,
+        None => -1,
+    };
+    CommandOutput {
+        stdout: text("stdout"),
+        stderr,
+        exit_code,
+    }
+}
+
+/// `relative` inside the sandbox root, rejecting `..` components.
+fn sandbox_path(relative: &str) -> anyhow::Result<String> {
+    shell_ops::sandbox_path(SANDBOX_ROOT, relative)
+}
+
+/// Sandbox provider running each sandbox as a Fly Machine in an app of its
+/// own.
+pub struct FlySandboxClient {
+    config: FlySandboxConfig,
+    templates: HashMap<String, SandboxTemplateConfig>,
+    http: reqwest::Client,
+    /// Name of the active sandbox's app, which is its ID.
+    app: Mutex<Option<String>>,
+    /// Machine of the active app, looked up when the ID was restored.
+    machine: Mutex<Option<String>>,
+    package_manager: Mutex<PackageManager>,
+}
+
+impl FlySandboxClient {
+    pub fn new(
+        config: FlySandboxConfig,
+        templates: HashMap<String, SandboxTemplateConfig>,
+    ) -> Self {
+        Self {
+            config,
+            templates,
+            http: reqwest::Client::builder()
+                .connect_timeout(Duration::from_secs(10))
+                .timeout(Duration::from_secs(60))
+                .build()
+                .unwrap_or_default(),
+            app: Mutex::new(None),
+            machine: Mutex::new(None),
+            package_manager: Mutex::new(PackageManager::default()),
+        }
+    }
+
+    /// `path` of the Machines API, e.g. `apps/zb-1/machines`.
+    fn url(&self, path: &str) -> String {
+        format!("{}/v1/{path}", self.config.api_url.trim_end_matches('/'))
+    }
+
+    fn request(
+        &self,
+        method: reqwest::Method,
+        url: &str,
+    ) -> anyhow::Result<reqwest::RequestBuilder> {
+        let token = match self.config.token.as_deref().map(str::trim) {
+            Some(token) if !token.is_empty() => token.to_string(),
+            _ => std::env::var("FLY_API_TOKEN")
+                .ok()
+                .filter(|token| !token.trim().is_empty())
+                .ok_or_else(|| {
+                    ZerobuildError::AuthFailed(
+                        "Set [zerobuild.sandbox.fly] token or FLY_API_TOKEN".to_string(),
+                    )
+                })?,
+        };
+        Ok(self
+            .http
+            .request(method, url)
+            .header("Authorization", authorization(token.trim()))
+            .header("Accept", "application/json"))
+    }
+
+    /// Send `request`; `Ok(None)` when the app or Machine doesn't exist.
+    async fn send(&self, request: reqwest::RequestBuilder) -> anyhow::Result<Option<Value>> {
+        let response = request.send().await.map_err(|e| {
+            if e.is_timeout() {
+                ZerobuildError::Timeout(format!("Fly API: {e}"))
+            } else {
+                ZerobuildError::Network(format!("Fly API: {e}"))
+            }
+        })?;
+        let status = response.status().as_u16();
+        let text = response.text().await.unwrap_or_default();
+        if status == 404 {
+            return Ok(None);
+        }
+        if status >= 400 {
+            let detail = format!("Fly API returned {status}: {}", api_message(&text));
+            return Err(match status {
+                401 => ZerobuildError::AuthFailed(detail).into(),
+                403 => ZerobuildError::PermissionDenied(detail).into(),
+                409 => ZerobuildError::Conflict(detail).into(),
+                429 => ZerobuildError::RateLimited(detail).into(),
+                502..=504 => ZerobuildError::Network(detail).into(),
+                _ => anyhow::anyhow!(detail),
+            });
+        }
+        Ok(Some(serde_json::from_str(&text).unwrap_or(Value::Null)))
+    }
+
+    fn active_app(&self) -> anyhow::Result<String> {
+        let app = self
+            .app
+            .lock()
+            .clone()
+            .ok_or(ZerobuildError::SandboxMissing)?;
+        tracing::Span::current().record("sandbox_id", app.as_str());
+        Ok(app)
+    }
+
+    /// The active app and its Machine.
+    async fn active(&self) -> anyhow::Result<(String, String)> {
+        let app = self.active_app()?;
+        let cached = self.machine.lock().clone();
+        if let Some(machine) = cached {
+            return Ok((app, machine));
+        }
+        // The ID was restored with `set_id`; look the Machine up.
+        let machines = self.machines(&app).await?;
+        let machine = machines
+            .into_iter()
+            .next()
+            .ok_or_else(|| ZerobuildError::SandboxExpired(app.clone()))?;
+        *self.machine.lock() = Some(machine.clone());
+        Ok((app, machine))
+    }
+
+    /// IDs of the Machines of `app`; none when the app is gone.
+    async fn machines(&self, app: &str) -> anyhow::Result<Vec<String>> {
+        let url = self.url(&format!("apps/{app}/machines"));
+        let machines = self.send(self.request(reqwest::Method::GET, &url)?).await?;
+        Ok(machines
+            .as_ref()
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|machine| machine["id"].as_str().map(str::to_string))
+            .collect())
+    }
+
+    /// State of Machine `id` of `app` (e.g. `started`); `None` when it is
+    /// gone.
+    async fn machine_state(&self, app: &str, id: &str) -> anyhow::Result<Option<String>> {
+        let url = self.url(&format!("apps/{app}/machines/{id}"));
+        let machine = self.send(self.request(reqwest::Method::GET, &url)?).await?;
+        Ok(machine.map(|machine| machine["state"].as_str().unwrap_or("created").to_string()))
+    }
+
+    /// Destroy the Machines of `app`, then the app and its addresses.
+    async fn delete_app(&self, app: &str) -> anyhow::Result<()> {
+        for machine in self.machines(app).await? {
+            let url = self.url(&format!("apps/{app}/machines/{machine}?force=true"));
+            self.send(self.request(reqwest::Method::DELETE, &url)?)
+                .await?;
+        }
+        let url = self.url(&format!("apps/{app}"));
+        self.send(self.request(reqwest::Method::DELETE, &url)?)
+            .await?;
+        Ok(())
+    }
+
+    /// Give `app` a shared IPv4 and an IPv6 address, so `<app>.fly.dev`
+    /// resolves.
+    async fn allocate_ips(&self, app: &str) -> anyhow::Result<()> {
+        for kind in ["shared_v4", "v6"] {
+            let body = json!({
+                "query": ALLOCATE_IP,
+                "variables": {"input": {"appId": app, "type": kind}},
+            });
+            let answer = self
+                .send(
+                    self.request(reqwest::Method::POST, GRAPHQL_URL)?
+                        .json(&body),
+                )
+                .await?
+                .unwrap_or_default();
+            if let Some(error) = answer["errors"][0]["message"].as_str() {
+                anyhow::bail!("Failed to allocate a {kind} address to Fly app {app}: {error}");
+            }
+        }
+        Ok(())
+    }
+
+    /// Create the addresses and Machine of the new `app` and wait until
+    /// the Machine runs. Returns its ID.
+    async fn start_machine(
+        &self,
+        app: &str,
+        image: &str,
+        timeout_ms: u64,
+    ) -> anyhow::Result<String> {
+        self.allocate_ips(app).await?;
+        let url = self.url(&format!("apps/{app}/machines"));
+        let created = self
+            .send(
+                self.request(reqwest::Method::POST, &url)?
+                    .json(&machine_config(image, &self.config)),
+            )
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Fly app {app} was deleted while starting"))?;
+        let machine = created["id"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Fly API created no Machine for {app}"))?
+            .to_string();
+
+        let deadline = tokio::time::Instant::now() + Duration::from_millis(timeout_ms);
+        loop {
+            match self.machine_state(app, &machine).await?.as_deref() {
+                Some("started") => return Ok(machine),
+                Some(state @ ("stopping" | "stopped" | "failed" | "destroying" | "destroyed")) => {
+                    anyhow::bail!(
+                        "Sandbox Machine {machine} of {app} stopped while starting ({state}); \
+                         the image must provide /bin/sh and sleep"
+                    );
+                }
+                None => {
+                    anyhow::bail!("Sandbox Machine {machine} of {app} was deleted while starting")
+                }
+                Some(_) => {}
+            }
+            if tokio::time::Instant::now() >= deadline {
+                return Err(ZerobuildError::Timeout(format!(
+                    "sandbox Machine {machine} of {app} did not start within {timeout_ms}ms"
+                ))
+                .into());
+            }
+            tokio::time::sleep(START_POLL).await;
+        }
+    }
+
+    /// Run `script` under `sh -c` in the active Machine, killed after
+    /// `timeout_ms`.
+    async fn exec(&self, script: &str, timeout_ms: u64) -> anyhow::Result<CommandOutput> {
+        let (app, machine) = self.active().await?;
+        let timeout_secs = timeout_ms.div_ceil(1000).max(1).to_string();
+        let limit = Duration::from_millis(timeout_ms) + EXEC_GRACE;
+        let url = self.url(&format!("apps/{app}/machines/{machine}/exec"));
+        let request = self
+            .request(reqwest::Method::POST, &url)?
+            .timeout(limit)
+            .json(&json!({
+                "command": ["timeout", "-s", "KILL", timeout_secs, "sh", "-c", script],
+                "timeout": limit.as_secs(),
+            }));
+        match self.send(request).await {
+            Ok(Some(answer)) => Ok(exec_output(&answer, timeout_ms)),
+            Ok(None) => Err(ZerobuildError::SandboxExpired(app).into()),
+            Err(e) if matches!(e.downcast_ref(), Some(ZerobuildError::Timeout(_))) => {
+                Ok(CommandOutput {
+                    stdout: String::new(),
+                    stderr: format!("Command timed out after {timeout_ms}ms"),
+                    exit_code: -1,
+                })
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Run a file operation `script`, failing with its stderr.
+    async fn file_op(&self, script: &str, what: &str) -> anyhow::Result<String> {
+        let output = self.exec(script, 60_000).await?;
+        if output.exit_code != 0 {
+            let detail = output.stderr.trim();
+            anyhow::bail!(
+                "{what}: {}",
+                if detail.is_empty() { "failed" } else { detail }
+            );
+        }
+        Ok(output.stdout)
+    }
+
+    /// Check a move or copy like the local provider does, then run `verb`
+    /// (`mv` or `cp -R`).
+    async fn transfer(
+        &self,
+        verb: &str,
+        from: &str,
+        to: &str,
+        overwrite: bool,
+    ) -> anyhow::Result<()> {
+        let script = shell_ops::transfer_script(SANDBOX_ROOT, verb, from, to, overwrite)?;
+        let action = if verb == "mv" { "move" } else { "copy" };
+        self.file_op(&script, &format!("Failed to {action} '{from}' to '{to}'"))
+            .await
+            .map(|_| ())
+    }
+
+    /// Create (or reset) a sandbox Machine running `image`.
+    async fn create(&self, reset: bool, image: &str, timeout_ms: u64) -> anyhow::Result<String> {
+        let existing = self.app.lock().clone();
+        if let Some(app) = existing {
+            if !reset {
+                if let Ok((_, machine)) = self.active().await {
+                    if self.machine_state(&app, &machine).await?.as_deref() == Some("started") {
+                        tracing::Span::current().record("sandbox_id", app.as_str());
+                        return Ok(app);
+                    }
+                }
+            }
+            if let Err(e) = self.delete_app(&app).await {
+                tracing::warn!(app = %app, "Failed to delete the previous sandbox app: {e:#}");
+            }
+            self.clear_id();
+        }
+
+        let app = format!("zb-{}", &Uuid::new_v4().simple().to_string()[..12]);
+        let org = &self.config.org;
+        self.send(
+            self.request(reqwest::Method::POST, &self.url("apps"))?
+                .json(&json!({"app_name": app, "org_slug": org})),
+        )
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Fly organization '{org}' does not exist"))?;
+        let machine = match self.start_machine(&app, image, timeout_ms).await {
+            Ok(machine) => machine,
+            Err(e) => {
+                if let Err(cleanup) = self.delete_app(&app).await {
+                    tracing::warn!(app = %app, "Failed to delete the sandbox app: {cleanup:#}");
+                }
+                return Err(e);
+            }
+        };
+
+        *self.app.lock() = Some(app.clone());
+        *self.machine.lock() = Some(machine.clone());
+        tracing::Span::current().record("sandbox_id", app.as_str());
+        tracing::info!(app = %app, machine = %machine, "Fly sandbox created");
+        Ok(app)
+    }
+}
+
+#[async_trait]
+impl SandboxClient for FlySandboxClient {
+    async fn create_sandbox(
+        &self,
+        reset: bool,
+        template: &str,
+        timeout_ms: u64,
+    ) -> anyhow::Result<String> {
+        let image = self
+            .templates
+            .get(template)
+            .map(|t| t.image.as_str())
+            .filter(|image| !image.is_empty())
+            .unwrap_or(&self.config.image);
+        self.create(reset, image, timeout_ms).await
+    }
+
+    async fn create_sandbox_from_image(
+        &self,
+        image: &str,
+        timeout_ms: u64,
+    ) -> anyhow::Result<String> {
+        self.create(true, image, timeout_ms).await
+    }
+
+    fn runs_images(&self) -> bool {
+        true
+    }
+
+    async fn kill_sandbox(&self) -> anyhow::Result<String> {
+        let Some(app) = self.app.lock().clone() else {
+            return Ok("No active Fly sandbox to kill.".to_string());
+        };
+        self.delete_app(&app).await?;
+        self.clear_id();
+        tracing::info!(app = %app, "Fly sandbox deleted");
+        Ok(format!("Fly sandbox app {app} deleted."))
+    }
+
+    async fn run_command(
+        &self,
+        command: &str,
+        workdir: &str,
+        timeout_ms: u64,
+    ) -> anyhow::Result<CommandOutput> {
+        let dir = sandbox_path(workdir)?;
+        self.exec(&format!("cd {} && {command}", quote(&dir)), timeout_ms)
+            .await
+    }
+
+    async fn write_file(&self, path: &str, content: &str) -> anyhow::Result<()> {
+        for script in shell_ops::write_scripts(SANDBOX_ROOT, path, content)? {
+            self.file_op(&script, &format!("Failed to write file '{path}'"))
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn move_path(&self, from: &str, to: &str, overwrite: bool) -> anyhow::Result<()> {
+        self.transfer("mv", from, to, overwrite).await
+    }
+
+    async fn copy_path(&self, from: &str, to: &str, overwrite: bool) -> anyhow::Result<()> {
+        self.transfer("cp -R", from, to, overwrite).await
+    }
+
+    async fn delete_path(&self, path: &str, recursive: bool) -> anyhow::Result<()> {
+        let script = shell_ops::delete_script(SANDBOX_ROOT, path, recursive)?;
+        self.file_op(&script, &format!("Failed to delete '{path}'"))
+            .await
+            .map(|_| ())
+    }
+
+    async fn read_file(&self, path: &str) -> anyhow::Result<String> {
+        let script = shell_ops::read_script(SANDBOX_ROOT, path)?;
+        let output = self.exec(&script, 60_000).await?;
+        match output.exit_code {
+            0 => Ok(output.stdout),
+            shell_ops::READ_MISSING => Err(ZerobuildError::FileNotFound(path.to_string()).into()),
+            _ => anyhow::bail!("Failed to read file '{path}': {}", output.stderr.trim()),
+        }
+    }
+
+    async fn list_files(&self, path: &str) -> anyhow::Result<String> {
+        let script = shell_ops::list_script(SANDBOX_ROOT, path)?;
+        let output = self
+            .file_op(&script, &format!("Failed to list directory '{path}'"))
+            .await?;
+        Ok(shell_ops::list_entries(&output))
+    }
+
+    async fn get_preview_url(&self, port: u16) -> anyhow::Result<String> {
+        let app = self.active_app()?;
+        let ports = &self.config.preview_ports;
+        preview_url(&app, port, ports).ok_or_else(|| {
+            let published: Vec<String> = ports.iter().map(u16::to_string).collect();
+            anyhow::anyhow!(
+                "Port {port} is not published by the Fly sandbox; serve the app on {} or add \
+                 the port to [zerobuild.sandbox.fly] preview_ports",
+                published.join(", ")
+            )
+        })
+    }
+
+    async fn collect_snapshot_files(
+        &self,
+        workdir: &str,
+    ) -> anyhow::Result<HashMap<String, String>> {
+        let script = shell_ops::snapshot_script(SANDBOX_ROOT, workdir)?;
+        let output = self
+            .file_op(&script, &format!("Failed to collect files of '{workdir}'"))
+            .await?;
+        Ok(shell_ops::parse_snapshot(&output))
+    }
+
+    fn current_id(&self) -> Option<String> {
+        self.app.lock().clone()
+    }
+
+    fn set_id(&self, id: String) {
+        *self.app.lock() = Some(id);
+        *self.machine.lock() = None;
+    }
+
+    fn clear_id(&self) {
+        *self.app.lock() = None;
+        *self.machine.lock() = None;
+    }
+
+    fn package_manager(&self) -> PackageManager {
+        *self.package_manager.lock()
+    }
+
+    fn set_package_manager(&self, pm: PackageManager) {
+        *self.package_manager.lock() = pm;
+    }
+
+    /// Detect the package manager installed in the Machine's image.
+    async fn detect_package_manager(&self) -> PackageManager {
+        let pm = match self.exec(shell_ops::DETECT_PACKAGE_MANAGER, 30_000).await {
+            Ok(output) => PackageManager::from_name(output.stdout.trim()).unwrap_or_default(),
+            Err(e) => {
+                tracing::warn!("Failed to detect the sandbox's package manager: {e:#}");
+                PackageManager::default()
+            }
+        };
+        self.set_package_manager(pm);
+        pm
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_preview_port_gets_the_bare_hostname() {
+        let mut config = FlySandboxConfig::default();
+        config.region = "ams".into();
+        let machine = machine_config("node:20-bookworm", &config);
+        assert_eq!(machine["region"], "ams");
+        let services = machine["config"]["services"].as_array().unwrap();
+        assert_eq!(services.len(), 3);
+        assert_eq!(services[0]["internal_port"], 3000);
+        assert_eq!(services[0]["ports"][0]["port"], 443);
+        assert_eq!(services[1]["ports"][0]["port"], 5173);
+
+        let ports = &config.preview_ports;
+        assert_eq!(
+            preview_url("zb-1", 3000, ports).as_deref(),
+            Some("https://zb-1.fly.dev")
+        );
+        assert_eq!(
+            preview_url("zb-1", 8080, ports).as_deref(),
+            Some("https://zb-1.fly.dev:8080")
+        );
+        assert_eq!(preview_url("zb-1", 4000, ports), None);
+    }
+
+    #[test]
+    fn exec_answer_gives_the_output() {
+        let done = exec_output(
+            &json!({"exit_code": 2, "stdout": "out", "stderr": "err"}),
+            1000,
+        );
+        assert_eq!(
+            (done.stdout.as_str(), done.stderr.as_str(), done.exit_code),
+            ("out", "err", 2)
+        );
+        let killed = exec_output(&json!({"exit_code": 137, "stdout": "", "stderr": ""}), 1000);
+        assert_eq!(killed.exit_code, -1);
+        assert_eq!(killed.stderr, "Command timed out after 1000ms");
+        assert_eq!(authorization("FlyV1 fm2_abc"), "FlyV1 fm2_abc");
+        assert_eq!(authorization("abc"), "Bearer abc");
+    }
+}
@@ -0,0 +1,330 @@
+//! Agent inside a microVM sandbox (`zerobuild sandbox-agent`).
+//!
+//! The `firecracker` provider can't exec into its VMs the way Docker or
+//! Kubernetes can, so the guest image starts this agent at boot. It listens
+//! on TCP; every connection carries one JSON [`Request`] line and gets
+//! [`Frame`] lines back: output chunks then the exit code for
+//! [`Request::Exec`], a single frame for the others. The guest is only
+//! reachable from the host's tap device, so the agent has no
+//! authentication of its own.
+
+use base64::Engine as _;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Stdio;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+
+/// Default port of the agent (`[zerobuild.sandbox.firecracker] agent_port`).
+pub const DEFAULT_PORT: u16 = 7000;
+
+/// Bytes of output sent per frame.
+const CHUNK: usize = 16 * 1024;
+
+/// A request to the agent.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Request {
+    /// Answered with [`Frame::Done`] once the agent runs.
+    Ping,
+    /// Run `command` under `sh -c` in `workdir`, killed after `timeout_ms`.
+    Exec {
+        command: String,
+        workdir: String,
+        timeout_ms: u64,
+    },
+    /// Write `content` (base64) to `path`, creating parent directories.
+    Write { path: String, content: String },
+    /// Read `path`, answered with [`Frame::Content`] in base64.
+    Read { path: String },
+}
+
+/// A line of the agent's answer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Frame {
+    Stdout(String),
+    Stderr(String),
+    /// Last frame of an exec; `-1` when the command timed out or was killed.
+    Exit(i64),
+    Content(String),
+    Done,
+    Error {
+        message: String,
+        not_found: bool,
+    },
+}
+
+/// Accept connections on `listener` until it fails.
+pub async fn serve(listener: TcpListener) -> anyhow::Result<()> {
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        tokio::spawn(async move {
+            if let Err(e) = handle(stream).await {
+                tracing::debug!(%peer, "Sandbox agent connection failed: {e:#}");
+            }
+        });
+    }
+}
+
+async fn handle(stream: TcpStream) -> anyhow::Result<()> {
+    let (read, mut write) = stream.into_split();
+    let mut lines = BufReader::new(read).lines();
+    let Some(line) = lines.next_line().await? else {
+        return Ok(());
+    };
+    let request: Request = match serde_json::from_str(&line) {
+        Ok(request) => request,
+        Err(e) => {
+            let frame = Frame::Error {
+                message: format!("Invalid request: {e}"),
+                not_found: false,
+            };
+            return send(&mut write, &frame).await;
+        }
+    };
+
+    match request {
+        Request::Ping => send(&mut write, &Frame::Done).await,
+        Request::Exec {
+            command,
+            workdir,
+            timeout_ms,
+        } => exec(&mut write, &command, &workdir, timeout_ms).await,
+        Request::Write { path, content } => {
+            let frame = match write_file(&path, &content).await {
+                Ok(()) => Frame::Done,
+                Err(e) => Frame::Error {
+                    message: format!("{e:#}"),
+                    not_found: false,
+                },
+            };
+            send(&mut write, &frame).await
+        }
+        Request::Read { path } => {
+            let frame = match tokio::fs::read(&path).await {
+                Ok(bytes) => {
+                    Frame::Content(base64::engine::general_purpose::STANDARD.encode(bytes))
+                }
+                Err(e) => Frame::Error {
+                    message: e.to_string(),
+                    not_found: e.kind() == std::io::ErrorKind::NotFound,
+                },
+            };
+            send(&mut write, &frame).await
+        }
+    }
+}
+
+async fn send(write: &mut (impl AsyncWriteExt + Unpin), frame: &Frame) -> anyhow::Result<()> {
+    let mut line = serde_json::to_vec(frame)?;
+    line.push(b'\n');
+    write.write_all(&line).await?;
+    Ok(())
+}
+
+async fn write_file(path: &str, content: &str) -> anyhow::Result<()> {
+    let bytes = base64::engine::general_purpose::STANDARD.decode(content)?;
+    if let Some(parent) = Path::new(path).parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(path, bytes).await?;
+    Ok(())
+}
+
+/// Run `command`, sending its output as it arrives and then its exit code.
+async fn exec(
+    write: &mut (impl AsyncWriteExt + Unpin),
+    command: &str,
+    workdir: &str,
+    timeout_ms: u64,
+) -> anyhow::Result<()> {
+    let spawned = tokio::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .current_dir(workdir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn();
+    let mut child = match spawned {
+        Ok(child) => child,
+        Err(e) => {
+            let frame = Frame::Error {
+                message: format!("Failed to run the command in {workdir}: {e}"),
+                not_found: e.kind() == std::io::ErrorKind::NotFound,
+            };
+            return send(write, &frame).await;
+        }
+    };
+
+    let (frames, mut received) = mpsc::unbounded_channel();
+    let stdout = tokio::spawn(pump(child.stdout.take(), frames.clone(), Frame::Stdout));
+    let stderr = tokio::spawn(pump(child.stderr.take(), frames, Frame::Stderr));
+    let run = async {
+        while let Some(frame) = received.recv().await {
+            send(write, &frame).await?;
+        }
+        let _ = tokio::join!(stdout, stderr);
+        Ok::<_, anyhow::Error>(child.wait().await?.code().map_or(-1, i64::from))
+    };
+    let code = match tokio::time::timeout(std::time::Duration::from_millis(timeout_ms), run).await {
+        Ok(code) => code?,
+        Err(_) => {
+            let _ = child.start_kill();
+            send(
+                write,
+                &Frame::Stderr(format!("Command timed out after {timeout_ms}ms")),
+            )
+            .await?;
+            -1
+        }
+    };
+    send(write, &Frame::Exit(code)).await
+}
+
+/// Forward `pipe` as frames made by `frame` until it closes.
+async fn pump(
+    pipe: Option<impl AsyncRead + Unpin>,
+    frames: mpsc::UnboundedSender<Frame>,
+    frame: fn(String) -> Frame,
+) {
+    let Some(mut pipe) = pipe else { return };
+    let mut buf = vec![0u8; CHUNK];
+    let mut pending = Vec::new();
+    loop {
+        match pipe.read(&mut buf).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => {
+                pending.extend_from_slice(&buf[..n]);
+                // Hold back an incomplete UTF-8 sequence for the next read.
+                let valid = match std::str::from_utf8(&pending) {
+                    Err(e) if e.error_len().is_none() => e.valid_up_to(),
+                    _ => pending.len(),
+                };
+                let rest = pending.split_off(valid);
+                let _ = frames.send(frame(String::from_utf8_lossy(&pending).into_owned()));
+                pending = rest;
+            }
+        }
+    }
+    if !pending.is_empty() {
+        let _ = frames.send(frame(String::from_utf8_lossy(&pending).into_owned()));
+    }
+}
+
+/// Connection to an agent at `addr` (`host:port`).
+#[derive(Debug, Clone)]
+pub struct AgentClient {
+    addr: String,
+}
+
+impl AgentClient {
+    pub fn new(addr: impl Into<String>) -> Self {
+        Self { addr: addr.into() }
+    }
+
+    pub fn addr(&self) -> &str {
+        &self.addr
+    }
+
+    /// Send `request` and hand each frame of the answer to `on_frame` until
+    /// the last one, which is returned.
+    pub async fn call(
+        &self,
+        request: &Request,
+        mut on_frame: impl FnMut(&Frame),
+    ) -> std::io::Result<Frame> {
+        let stream = TcpStream::connect(&self.addr).await?;
+        let (read, mut write) = stream.into_split();
+        let mut line = serde_json::to_vec(request)?;
+        line.push(b'\n');
+        write.write_all(&line).await?;
+        let mut lines = BufReader::new(read).lines();
+        while let Some(line) = lines.next_line().await? {
+            let frame: Frame = serde_json::from_str(&line)?;
+            match frame {
+                Frame::Stdout(_) | Frame::Stderr(_) => on_frame(&frame),
+                frame => return Ok(frame),
+            }
+        }
+        Err(std::io::Error::new(
+            std::io::ErrorKind::UnexpectedEof,
+            "the sandbox agent closed the connection without an answer",
+        ))
+    }
+
+    /// Whether the agent answers.
+    pub async fn ping(&self) -> bool {
+        matches!(self.call(&Request::Ping, |_| {}).await, Ok(Frame::Done))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn agent() -> AgentClient {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        tokio::spawn(serve(listener));
+        AgentClient::new(addr)
+    }
+
+    #[tokio::test]
+    async fn runs_commands_and_transfers_files() {
+        let agent = agent().await;
+        assert!(agent.ping().await);
+        let dir = tempfile::tempdir().unwrap();
+        let workdir = dir.path().to_string_lossy().into_owned();
+
+        let mut output = String::new();
+        let exec = Request::Exec {
+            command: "echo out; echo err >&2; exit 3".into(),
+            workdir: workdir.clone(),
+            timeout_ms: 10_000,
+        };
+        let last = agent
+            .call(&exec, |frame| {
+                if let Frame::Stdout(s) | Frame::Stderr(s) = frame {
+                    output.push_str(s);
+                }
+            })
+            .await
+            .unwrap();
+        assert_eq!(last, Frame::Exit(3));
+        assert!(output.contains("out\n") && output.contains("err\n"));
+
+        let path = format!("{workdir}/src/app.js");
+        let content = base64::engine::general_purpose::STANDARD.encode("ok");
+        let write = Request::Write {
+            path: path.clone(),
+            content: content.clone(),
+        };
+        assert_eq!(agent.call(&write, |_| {}).await.unwrap(), Frame::Done);
+        let read = Request::Read { path };
+        assert_eq!(
+            agent.call(&read, |_| {}).await.unwrap(),
+            Frame::Content(content)
+        );
+        let missing = Request::Read {
+            path: format!("{workdir}/missing"),
+        };
+        assert!(matches!(
+            agent.call(&missing, |_| {}).await.unwrap(),
+            Frame::Error {
+                not_found: true,
+                ..
+            }
+        ));
+
+        let slow = Request::Exec {
+            command: "sleep 5".into(),
+            workdir,
+            timeout_ms: 100,
+        };
+        assert_eq!(agent.call(&slow, |_| {}).await.unwrap(), Frame::Exit(-1));
+    }
+}
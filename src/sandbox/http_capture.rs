@@ -0,0 +1,462 @@
+//! HTTP capture for sandbox previews (`[zerobuild.sandbox] http_capture`).
+//!
+//! [`HttpCapture::proxy`] puts a reverse proxy on the ZeroBuild host in
+//! front of a preview URL. Everything loaded through it — pages, assets and
+//! the frontend's API calls — is forwarded unchanged and recorded as an
+//! [`Exchange`] with its headers and (text) bodies, so the
+//! `sandbox_http_log` tool can show the agent what the app actually sent and
+//! got back. Only the last [`MAX_EXCHANGES`] exchanges are kept, in memory.
+//!
+//! WebSocket upgrades (dev server hot reload) are refused; pages keep
+//! working without hot reload.
+
+use axum::body::{to_bytes, Body};
+use axum::extract::{Request, State};
+use axum::http::{HeaderMap, HeaderName, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Router;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::task::JoinHandle;
+
+/// Exchanges kept; older ones are dropped.
+pub const MAX_EXCHANGES: usize = 200;
+
+/// Longest request or response body recorded.
+const MAX_BODY_CHARS: usize = 4000;
+
+/// Largest request body forwarded.
+const MAX_REQUEST_BYTES: usize = 10 * 1024 * 1024;
+
+/// Headers that only concern one connection and are never forwarded.
+const HOP_BY_HOP: &[&str] = &[
+    "connection",
+    "keep-alive",
+    "proxy-authenticate",
+    "proxy-authorization",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+    "host",
+    "content-length",
+];
+
+/// Headers whose values are never recorded.
+const SECRET_HEADERS: &[&str] = &[
+    "authorization",
+    "cookie",
+    "set-cookie",
+    "x-api-key",
+    "x-auth-token",
+];
+
+/// One request made through a capture proxy and its response.
+#[derive(Debug, Clone)]
+pub struct Exchange {
+    pub id: u64,
+    pub at: DateTime<Utc>,
+    /// Sandbox port the request went to.
+    pub port: u16,
+    pub method: String,
+    /// Path and query.
+    pub path: String,
+    pub request_headers: Vec<(String, String)>,
+    pub request_body: String,
+    /// `None` when the app could not be reached.
+    pub status: Option<u16>,
+    pub response_headers: Vec<(String, String)>,
+    pub response_body: String,
+    /// Why the app could not be reached.
+    pub error: Option<String>,
+    pub duration_ms: u64,
+}
+
+impl Exchange {
+    /// Whether the request failed or got a 4xx/5xx response.
+    pub fn failed(&self) -> bool {
+        self.status.is_none_or(|status| status >= 400)
+    }
+
+    /// Whether this loaded a static asset (script, style, image, font) rather
+    /// than a page or an API call.
+    pub fn is_asset(&self) -> bool {
+        let path = self.path.split(['?', '#']).next().unwrap_or_default();
+        const PREFIXES: &[&str] = &["/_next/static/", "/@vite/", "/@fs/", "/@react-refresh"];
+        const EXTENSIONS: &[&str] = &[
+            "js", "mjs", "cjs", "css", "map", "png", "jpg", "jpeg", "gif", "svg", "webp", "avif",
+            "ico", "woff", "woff2", "ttf", "otf", "tsx", "ts", "jsx",
+        ];
+        PREFIXES.iter().any(|p| path.starts_with(p))
+            || path
+                .rsplit_once('.')
+                .is_some_and(|(_, ext)| EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+    }
+}
+
+/// Recorded exchanges, shared by all proxies of one [`HttpCapture`].
+#[derive(Default)]
+struct Recorder {
+    exchanges: Mutex<VecDeque<Exchange>>,
+    next_id: AtomicU64,
+}
+
+impl Recorder {
+    fn push(&self, mut exchange: Exchange) {
+        exchange.id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let mut exchanges = self.exchanges.lock();
+        if exchanges.len() >= MAX_EXCHANGES {
+            exchanges.pop_front();
+        }
+        exchanges.push_back(exchange);
+    }
+}
+
+/// A running capture proxy.
+struct Proxy {
+    upstream: String,
+    url: String,
+    task: JoinHandle<()>,
+}
+
+impl Drop for Proxy {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Capture proxies in front of sandbox preview ports, one per port.
+pub struct HttpCapture {
+    recorder: Arc<Recorder>,
+    proxies: tokio::sync::Mutex<HashMap<u16, Proxy>>,
+    client: reqwest::Client,
+}
+
+impl Default for HttpCapture {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HttpCapture {
+    pub fn new() -> Self {
+        Self {
+            recorder: Arc::default(),
+            proxies: tokio::sync::Mutex::default(),
+            client: reqwest::Client::builder()
+                .redirect(reqwest::redirect::Policy::none())
+                .no_proxy()
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    /// URL of a capture proxy forwarding to `upstream`, the preview URL of
+    /// sandbox port `port`. The proxy of a port is reused while its
+    /// upstream stays the same.
+    pub async fn proxy(&self, port: u16, upstream: &str) -> anyhow::Result<String> {
+        let upstream = upstream.trim_end_matches('/').to_string();
+        let mut proxies = self.proxies.lock().await;
+        if let Some(proxy) = proxies.get(&port) {
+            if proxy.upstream == upstream && !proxy.task.is_finished() {
+                return Ok(proxy.url.clone());
+            }
+        }
+        if let Some(stale) = proxies.remove(&port) {
+            stale.task.abort();
+        }
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://localhost:{}", listener.local_addr()?.port());
+        let state = Arc::new(Upstream {
+            port,
+            base: upstream.clone(),
+            client: self.client.clone(),
+            recorder: Arc::clone(&self.recorder),
+        });
+        let app = Router::new().fallback(forward).with_state(state);
+        let task = tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, app).await {
+                tracing::warn!("HTTP capture proxy stopped: {e}");
+            }
+        });
+        proxies.insert(
+            port,
+            Proxy {
+                upstream,
+                url: url.clone(),
+                task,
+            },
+        );
+        Ok(url)
+    }
+
+    /// Recorded exchanges, oldest first.
+    pub fn exchanges(&self) -> Vec<Exchange> {
+        self.recorder.exchanges.lock().iter().cloned().collect()
+    }
+
+    /// Exchange `id`, if still kept.
+    pub fn exchange(&self, id: u64) -> Option<Exchange> {
+        self.recorder
+            .exchanges
+            .lock()
+            .iter()
+            .find(|e| e.id == id)
+            .cloned()
+    }
+
+    /// Forget all recorded exchanges.
+    pub fn clear(&self) {
+        self.recorder.exchanges.lock().clear();
+    }
+}
+
+/// Where one proxy forwards to.
+struct Upstream {
+    port: u16,
+    base: String,
+    client: reqwest::Client,
+    recorder: Arc<Recorder>,
+}
+
+async fn forward(State(upstream): State<Arc<Upstream>>, request: Request) -> Response {
+    if request.headers().contains_key("upgrade") {
+        return (
+            StatusCode::NOT_IMPLEMENTED,
+            "WebSocket connections are not proxied while HTTP capture is on",
+        )
+            .into_response();
+    }
+    let started = Instant::now();
+    let (parts, body) = request.into_parts();
+    let path = parts
+        .uri
+        .path_and_query()
+        .map_or_else(|| "/".to_string(), ToString::to_string);
+    let mut exchange = Exchange {
+        id: 0,
+        at: Utc::now(),
+        port: upstream.port,
+        method: parts.method.to_string(),
+        path: path.clone(),
+        request_headers: recorded_headers(&parts.headers),
+        request_body: String::new(),
+        status: None,
+        response_headers: Vec::new(),
+        response_body: String::new(),
+        error: None,
+        duration_ms: 0,
+    };
+
+    let body = match to_bytes(body, MAX_REQUEST_BYTES).await {
+        Ok(body) => body,
+        Err(e) => {
+            exchange.error = Some(format!("Could not read request body: {e}"));
+            upstream.recorder.push(exchange);
+            return (StatusCode::PAYLOAD_TOO_LARGE, "Request body too large").into_response();
+        }
+    };
+    exchange.request_body = body_text(&parts.headers, &body);
+
+    let mut outgoing = upstream
+        .client
+        .request(parts.method, format!("{}{path}", upstream.base))
+        .body(body);
+    for (name, value) in &parts.headers {
+        // Bodies come back uncompressed so they can be recorded.
+        if !is_hop_by_hop(name) && name != "accept-encoding" {
+            outgoing = outgoing.header(name, value);
+        }
+    }
+
+    let response = match outgoing.send().await {
+        Ok(response) => response,
+        Err(e) => {
+            let message = format!("App not reachable at {}: {e}", upstream.base);
+            exchange.error = Some(message.clone());
+            exchange.duration_ms = elapsed_ms(started);
+            upstream.recorder.push(exchange);
+            return (StatusCode::BAD_GATEWAY, message).into_response();
+        }
+    };
+    let status = response.status();
+    let headers = response.headers().clone();
+    exchange.status = Some(status.as_u16());
+    exchange.response_headers = recorded_headers(&headers);
+
+    let mut builder = Response::builder().status(status);
+    for (name, value) in &headers {
+        if !is_hop_by_hop(name) {
+            builder = builder.header(name, value);
+        }
+    }
+    let streamed = headers
+        .get("content-type")
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("text/event-stream"));
+    let body = if streamed {
+        exchange.response_body = "(event stream, not recorded)".into();
+        Body::from_stream(response.bytes_stream())
+    } else {
+        match response.bytes().await {
+            Ok(bytes) => {
+                exchange.response_body = body_text(&headers, &bytes);
+                Body::from(bytes)
+            }
+            Err(e) => {
+                exchange.error = Some(format!("Response body failed: {e}"));
+                Body::empty()
+            }
+        }
+    };
+    exchange.duration_ms = elapsed_ms(started);
+    upstream.recorder.push(exchange);
+    builder
+        .body(body)
+        .unwrap_or_else(|_| StatusCode::BAD_GATEWAY.into_response())
+}
+
+fn is_hop_by_hop(name: &HeaderName) -> bool {
+    HOP_BY_HOP.contains(&name.as_str())
+}
+
+fn recorded_headers(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if SECRET_HEADERS.contains(&name.as_str()) {
+                crate::security::redaction::REDACTED.to_string()
+            } else {
+                String::from_utf8_lossy(value.as_bytes()).into_owned()
+            };
+            (name.to_string(), value)
+        })
+        .collect()
+}
+
+/// `body` as recorded: its text when it is text, else a size summary.
+fn body_text(headers: &HeaderMap, body: &[u8]) -> String {
+    if body.is_empty() {
+        return String::new();
+    }
+    let content_type = headers
+        .get("content-type")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    let textual = content_type.is_empty()
+        || content_type.starts_with("text/")
+        || [
+            "json",
+            "xml",
+            "javascript",
+            "x-www-form-urlencoded",
+            "graphql",
+        ]
+        .iter()
+        .any(|t| content_type.contains(t));
+    match std::str::from_utf8(body) {
+        Ok(text) if textual => crate::util::truncate_with_ellipsis(text, MAX_BODY_CHARS),
+        _ => format!("({} bytes, {content_type})", body.len()),
+    }
+}
+
+fn elapsed_ms(started: Instant) -> u64 {
+    u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::{get, post};
+
+    async fn start_app() -> String {
+        let app = Router::new()
+            .route(
+                "/api/todos",
+                post(|body: String| async move {
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        [("content-type", "application/json")],
+                        format!("{{\"error\":\"cannot save {body}\"}}"),
+                    )
+                }),
+            )
+            .route("/app.js", get(|| async { "console.log(1)" }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://127.0.0.1:{}", listener.local_addr().unwrap().port());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        url
+    }
+
+    #[tokio::test]
+    async fn records_requests_through_the_proxy() {
+        let app = start_app().await;
+        let capture = HttpCapture::new();
+        let url = capture.proxy(3000, &app).await.unwrap();
+        assert_eq!(capture.proxy(3000, &app).await.unwrap(), url);
+
+        let client = reqwest::Client::new();
+        let response = client
+            .post(format!("{url}/api/todos?draft=1"))
+            .header("authorization", "Bearer secret")
+            .header("content-type", "application/json")
+            .body("{\"title\":\"milk\"}")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 500);
+        assert!(response.text().await.unwrap().contains("cannot save"));
+        client.get(format!("{url}/app.js")).send().await.unwrap();
+
+        let exchanges = capture.exchanges();
+        assert_eq!(exchanges.len(), 2);
+        let api = &exchanges[0];
+        assert_eq!((api.id, api.port), (1, 3000));
+        assert_eq!(api.path, "/api/todos?draft=1");
+        assert_eq!(api.status, Some(500));
+        assert!(api.failed() && !api.is_asset());
+        assert_eq!(api.request_body, "{\"title\":\"milk\"}");
+        assert!(api
+            .response_body
+            .contains("cannot save {\"title\":\"milk\"}"));
+        assert!(api
+            .request_headers
+            .contains(&("authorization".into(), "[REDACTED]".into())));
+        assert!(exchanges[1].is_asset());
+        assert_eq!(capture.exchange(2).unwrap().status, Some(200));
+
+        capture.clear();
+        assert!(capture.exchanges().is_empty());
+    }
+
+    #[tokio::test]
+    async fn new_upstream_stops_the_old_proxy() {
+        let capture = HttpCapture::new();
+        let old = capture.proxy(3000, &start_app().await).await.unwrap();
+        let new = capture.proxy(3000, &start_app().await).await.unwrap();
+        assert_ne!(old, new);
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+        assert!(reqwest::get(format!("{old}/app.js")).await.is_err());
+        let response = reqwest::get(format!("{new}/app.js")).await.unwrap();
+        assert_eq!(response.status(), 200);
+    }
+
+    #[tokio::test]
+    async fn records_unreachable_apps() {
+        let capture = HttpCapture::new();
+        let url = capture.proxy(5173, "http://127.0.0.1:9").await.unwrap();
+        let response = reqwest::get(format!("{url}/")).await.unwrap();
+        assert_eq!(response.status(), 502);
+
+        let exchange = capture.exchange(1).unwrap();
+        assert_eq!(exchange.status, None);
+        assert!(exchange.failed());
+        assert!(exchange.error.unwrap().contains("not reachable"));
+    }
+}
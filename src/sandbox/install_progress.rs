@@ -0,0 +1,304 @@
+//! Progress of dependency installs, parsed from the package manager's output.
+//!
+//! Installs of a fresh project can run for minutes with little output, long
+//! enough for chat users to assume the build hung and for webhook-driven
+//! channels to time out. `sandbox_run_command` feeds each output line of an
+//! install to [`InstallProgress::observe`] and periodically reports
+//! [`InstallProgress::summary`]: the install phase, packages resolved and
+//! downloaded, bytes fetched where the manager prints them, and how long ago
+//! the last output arrived.
+
+use regex::Regex;
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
+
+/// Install commands of npm, pnpm, yarn and bun (`yarn` alone installs too).
+static INSTALL_COMMAND: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)\b(?:(?:npm|pnpm|bun)\s+(?:install|i|ci|add)\b|yarn(?:\s+(?:install|add))?\s*(?:$|&&|;|\|))").unwrap()
+});
+
+/// pnpm: `Progress: resolved 412, reused 300, downloaded 112, added 90`.
+static PNPM_PROGRESS: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"resolved (\d+), reused (\d+), downloaded (\d+), added (\d+)(, done)?").unwrap()
+});
+
+/// pnpm: `Downloading next@14.2.3: 20.41 MB/20.41 MB, done`.
+static PNPM_DOWNLOAD_DONE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"Downloading \S+: [\d.]+ \w+/([\d.]+ \w+), done").unwrap());
+
+/// yarn berry: `YN0013: │ 412 packages were fetched (+ 34.2 MiB)` and similar.
+static YARN_FETCHED: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(\d+) packages? (?:were|was) (?:fetched|added)").unwrap());
+
+/// yarn berry size summary: `(+ 34.2 MiB)`.
+static PLUS_SIZE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\(\+ ([\d.]+ \w+)\)").unwrap());
+
+/// npm (`--loglevel http`): `npm http fetch GET 200 https://registry...`.
+static NPM_FETCH: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^npm (?:http )?fetch GET 200 ").unwrap());
+
+/// npm and bun final counts: `added 412 packages`, `412 packages installed`.
+static PACKAGES_ADDED: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:added (\d+) packages?|(\d+) packages? installed)").unwrap());
+
+/// bun: `Resolved, downloaded and extracted [412]`.
+static BUN_EXTRACTED: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"downloaded and extracted \[(\d+)\]").unwrap());
+
+/// Whether `command` installs dependencies.
+pub fn is_install_command(command: &str) -> bool {
+    INSTALL_COMMAND.is_match(command)
+}
+
+/// Short label for the install in `command`, e.g. `pnpm install`.
+pub fn install_label(command: &str) -> String {
+    INSTALL_COMMAND.find(command).map_or_else(
+        || "install".to_string(),
+        |m| {
+            m.as_str()
+                .trim_end_matches(['&', ';', '|'])
+                .trim()
+                .to_string()
+        },
+    )
+}
+
+/// Where an install is, as far as its output tells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstallPhase {
+    Starting,
+    Resolving,
+    Fetching,
+    Linking,
+    Building,
+    Done,
+}
+
+impl InstallPhase {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Starting => "starting",
+            Self::Resolving => "resolving packages",
+            Self::Fetching => "downloading packages",
+            Self::Linking => "linking",
+            Self::Building => "running build scripts",
+            Self::Done => "finishing",
+        }
+    }
+}
+
+/// Parse a size like `20.41 MB` or `3.2 MiB` into bytes.
+fn parse_size(size: &str) -> Option<u64> {
+    let (number, unit) = size.trim().split_once(' ')?;
+    let number: f64 = number.parse().ok()?;
+    let factor = match unit {
+        "B" => 1.0,
+        "kB" | "KB" => 1e3,
+        "KiB" => 1024.0,
+        "MB" => 1e6,
+        "MiB" => 1024.0 * 1024.0,
+        "GB" => 1e9,
+        "GiB" => 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let bytes = (number * factor) as u64;
+    Some(bytes)
+}
+
+/// `bytes` as e.g. `34.2 MB`.
+fn format_bytes(bytes: u64) -> String {
+    #[allow(clippy::cast_precision_loss)]
+    let value = bytes as f64;
+    if value >= 1e9 {
+        format!("{:.1} GB", value / 1e9)
+    } else if value >= 1e6 {
+        format!("{:.1} MB", value / 1e6)
+    } else {
+        format!("{:.0} kB", value / 1e3)
+    }
+}
+
+/// `duration` as e.g. `2m05s` or `40s`.
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs >= 60 {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{secs}s")
+    }
+}
+
+/// Install state accumulated from output lines.
+#[derive(Debug, Clone)]
+pub struct InstallProgress {
+    pub phase: InstallPhase,
+    pub resolved: u64,
+    pub downloaded: u64,
+    pub bytes: u64,
+    started: Instant,
+    last_output: Option<Instant>,
+}
+
+impl Default for InstallProgress {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InstallProgress {
+    pub fn new() -> Self {
+        Self {
+            phase: InstallPhase::Starting,
+            resolved: 0,
+            downloaded: 0,
+            bytes: 0,
+            started: Instant::now(),
+            last_output: None,
+        }
+    }
+
+    fn advance(&mut self, phase: InstallPhase) {
+        // Phases only move forward; managers repeat earlier step names in
+        // nested workspaces.
+        if phase as u8 > self.phase as u8 {
+            self.phase = phase;
+        }
+    }
+
+    /// Update the state from one line of install output.
+    pub fn observe(&mut self, line: &str) {
+        self.last_output = Some(Instant::now());
+        let line = line.trim();
+
+        if let Some(caps) = PNPM_PROGRESS.captures(line) {
+            self.resolved = caps[1].parse().unwrap_or(self.resolved);
+            self.downloaded = caps[3].parse().unwrap_or(self.downloaded);
+            self.advance(if caps.get(5).is_some() {
+                InstallPhase::Linking
+            } else {
+                InstallPhase::Fetching
+            });
+        } else if let Some(caps) = PNPM_DOWNLOAD_DONE.captures(line) {
+            self.bytes += parse_size(&caps[1]).unwrap_or(0);
+        } else if let Some(caps) = YARN_FETCHED.captures(line) {
+            self.downloaded = self.downloaded.max(caps[1].parse().unwrap_or(0));
+            if let Some(size) = PLUS_SIZE.captures(line) {
+                self.bytes = self.bytes.max(parse_size(&size[1]).unwrap_or(0));
+            }
+        } else if NPM_FETCH.is_match(line) {
+            self.downloaded += 1;
+            self.advance(InstallPhase::Fetching);
+        } else if let Some(caps) = BUN_EXTRACTED.captures(line) {
+            self.downloaded = caps[1].parse().unwrap_or(self.downloaded);
+            self.advance(InstallPhase::Linking);
+        } else if let Some(caps) = PACKAGES_ADDED.captures(line) {
+            let count = caps.get(1).or_else(|| caps.get(2));
+            self.resolved = count.map_or(self.resolved, |n| n.as_str().parse().unwrap_or(0));
+            self.advance(InstallPhase::Done);
+        }
+
+        let lower = line.to_ascii_lowercase();
+        if lower.contains("resolving") || lower.contains("resolution step") {
+            self.advance(InstallPhase::Resolving);
+        } else if lower.contains("fetching packages") || lower.contains("fetch step") {
+            self.advance(InstallPhase::Fetching);
+        } else if lower.contains("linking dependencies") || lower.contains("link step") {
+            self.advance(InstallPhase::Linking);
+        } else if lower.contains("building fresh packages") || lower.contains("postinstall") {
+            self.advance(InstallPhase::Building);
+        }
+    }
+
+    /// One progress line, e.g. `⏳ pnpm install: downloading packages —
+    /// 1m20s, 412 resolved, 120 downloaded (34.2 MB)`.
+    pub fn summary(&self, label: &str) -> String {
+        let mut parts = vec![format_duration(self.started.elapsed())];
+        if self.resolved > 0 {
+            parts.push(format!("{} resolved", self.resolved));
+        }
+        if self.downloaded > 0 {
+            parts.push(format!("{} downloaded", self.downloaded));
+        }
+        if self.bytes > 0 {
+            parts.push(format_bytes(self.bytes));
+        }
+        match self.last_output {
+            Some(last) if last.elapsed() >= Duration::from_secs(60) => {
+                parts.push(format!("no output for {}", format_duration(last.elapsed())));
+            }
+            None if self.started.elapsed() >= Duration::from_secs(60) => {
+                parts.push("no output yet".to_string());
+            }
+            _ => {}
+        }
+        format!(
+            "\u{23f3} {label}: {} \u{2014} {}",
+            self.phase.as_str(),
+            parts.join(", ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_install_commands() {
+        assert!(is_install_command("npm install"));
+        assert!(is_install_command("cd web && pnpm i --frozen-lockfile"));
+        assert!(is_install_command("yarn"));
+        assert!(is_install_command("yarn && yarn build"));
+        assert!(is_install_command("bun add zod"));
+        assert!(!is_install_command("npm run build"));
+        assert!(!is_install_command("yarn build"));
+        assert!(!is_install_command("npm init -y"));
+        assert_eq!(
+            install_label("cd web && pnpm install --frozen-lockfile"),
+            "pnpm install"
+        );
+        assert_eq!(install_label("yarn && yarn build"), "yarn");
+    }
+
+    #[test]
+    fn parses_pnpm_and_yarn_output() {
+        let mut pnpm = InstallProgress::new();
+        pnpm.observe("Packages: +412");
+        pnpm.observe("Progress: resolved 412, reused 300, downloaded 112, added 90");
+        pnpm.observe("Downloading next@14.2.3: 20.41 MB/20.41 MB, done");
+        assert_eq!(pnpm.phase, InstallPhase::Fetching);
+        assert_eq!((pnpm.resolved, pnpm.downloaded), (412, 112));
+        assert_eq!(
+            pnpm.summary("pnpm install"),
+            "\u{23f3} pnpm install: downloading packages \u{2014} 0s, 412 resolved, \
+             112 downloaded, 20.4 MB"
+        );
+        pnpm.observe("Progress: resolved 412, reused 300, downloaded 112, added 412, done");
+        assert_eq!(pnpm.phase, InstallPhase::Linking);
+
+        let mut yarn = InstallProgress::new();
+        yarn.observe("[1/4] Resolving packages...");
+        assert_eq!(yarn.phase, InstallPhase::Resolving);
+        yarn.observe("[3/4] Linking dependencies...");
+        yarn.observe("[1/4] Resolving packages...");
+        assert_eq!(yarn.phase, InstallPhase::Linking);
+        yarn.observe("YN0013: │ 87 packages were fetched (+ 12.5 MiB)");
+        assert_eq!(yarn.downloaded, 87);
+        assert_eq!(yarn.bytes, 13_107_200);
+    }
+
+    #[test]
+    fn parses_npm_and_bun_output() {
+        let mut npm = InstallProgress::new();
+        npm.observe("npm http fetch GET 200 https://registry.npmjs.org/react 120ms");
+        npm.observe("npm http fetch GET 200 https://registry.npmjs.org/vite 80ms");
+        assert_eq!((npm.phase, npm.downloaded), (InstallPhase::Fetching, 2));
+        npm.observe("added 412 packages, and audited 413 packages in 42s");
+        assert_eq!((npm.phase, npm.resolved), (InstallPhase::Done, 412));
+
+        let mut bun = InstallProgress::new();
+        bun.observe("Resolved, downloaded and extracted [233]");
+        assert_eq!((bun.phase, bun.downloaded), (InstallPhase::Linking, 233));
+    }
+}
@@ -0,0 +1,874 @@
+//! Kubernetes sandbox provider (`kubernetes` in `[zerobuild.sandbox]
+//! providers`).
+//!
+//! Every sandbox is a pod in the configured namespace, running the image of
+//! its template (`[zerobuild.templates.<name>] image`) or
+//! `[zerobuild.sandbox.kubernetes] image` with the sandbox root at
+//! `/workspace`. Commands and file operations go through the pod exec API
+//! (`v4.channel.k8s.io` over WebSocket). A preview port gets a Service and,
+//! with `preview_domain`, an Ingress; both are owned by the pod, so deleting
+//! the pod cleans them up.
+//!
+//! The client talks to the API server directly with a bearer token, by
+//! default the service account of the pod ZeroBuild runs in, so several
+//! users can share a cluster without E2B or a local Docker daemon.
+
+use super::shell_ops::{self, quote};
+use super::{CommandOutput, PackageManager, SandboxClient};
+use crate::config::{KubernetesSandboxConfig, SandboxTemplateConfig};
+use crate::error::{ErrorCode, ZerobuildError};
+use anyhow::Context;
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use parking_lot::Mutex;
+use rustls_pki_types::pem::PemObject;
+use rustls_pki_types::CertificateDer;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::Connector;
+use uuid::Uuid;
+
+/// Sandbox root inside the pod.
+const SANDBOX_ROOT: &str = "/workspace";
+
+/// Name of the sandbox container.
+const CONTAINER: &str = "sandbox";
+
+/// Label naming the sandbox a pod, Service or Ingress belongs to.
+const SANDBOX_LABEL: &str = "zerobuild.dev/sandbox";
+
+/// Mounted service account of a pod, used when the config names no cluster.
+const SERVICE_ACCOUNT_DIR: &str = "/var/run/secrets/kubernetes.io/serviceaccount";
+
+/// Exec subprotocol: binary frames prefixed with their stream (1 stdout,
+/// 2 stderr, 3 the final status).
+const EXEC_PROTOCOL: &str = "v4.channel.k8s.io";
+
+/// Interval between pod status checks while a sandbox starts.
+const START_POLL: Duration = Duration::from_secs(1);
+
+/// Extra time a command gets on top of its timeout before the client stops
+/// waiting for `timeout(1)` in the pod to end it.
+const EXEC_GRACE: Duration = Duration::from_secs(5);
+
+/// Connection to the API server.
+struct Cluster {
+    api_url: String,
+    token: String,
+    namespace: String,
+    http: reqwest::Client,
+    /// TLS settings of exec WebSockets: the public roots plus the cluster CA.
+    tls: Arc<rustls::ClientConfig>,
+}
+
+impl Cluster {
+    /// Connect with `config`, filling its empty settings from the service
+    /// account.
+    fn from_config(config: &KubernetesSandboxConfig) -> anyhow::Result<Self> {
+        let service_account = Path::new(SERVICE_ACCOUNT_DIR);
+        let api_url = if config.api_url.trim().is_empty() {
+            let host = std::env::var("KUBERNETES_SERVICE_HOST").map_err(|_| {
+                anyhow::anyhow!(
+                    "Set [zerobuild.sandbox.kubernetes] api_url, or run ZeroBuild inside the cluster"
+                )
+            })?;
+            let port =
+                std::env::var("KUBERNETES_SERVICE_PORT").unwrap_or_else(|_| "443".to_string());
+            if host.contains(':') {
+                format!("https://[{host}]:{port}")
+            } else {
+                format!("https://{host}:{port}")
+            }
+        } else {
+            config.api_url.trim().trim_end_matches('/').to_string()
+        };
+        let token = match config.token.as_deref().map(str::trim) {
+            Some(token) if !token.is_empty() => token.to_string(),
+            _ => std::fs::read_to_string(service_account.join("token"))
+                .map(|token| token.trim().to_string())
+                .context(
+                    "Set [zerobuild.sandbox.kubernetes] token, or run ZeroBuild with a service account",
+                )?,
+        };
+        let namespace = if config.namespace.is_empty() {
+            std::fs::read_to_string(service_account.join("namespace"))
+                .map(|ns| ns.trim().to_string())
+                .unwrap_or_else(|_| "default".to_string())
+        } else {
+            config.namespace.clone()
+        };
+        let ca_path = if config.ca_cert.is_empty() {
+            Some(service_account.join("ca.crt")).filter(|path| path.exists())
+        } else {
+            Some(Path::new(&config.ca_cert).to_path_buf())
+        };
+        let ca = ca_path
+            .map(|path| {
+                std::fs::read(&path)
+                    .with_context(|| format!("Failed to read cluster CA {}", path.display()))
+            })
+            .transpose()?;
+
+        let mut http = reqwest::Client::builder()
+            .connect_timeout(Duration::from_secs(10))
+            .timeout(Duration::from_secs(60));
+        let mut roots: rustls::RootCertStore =
+            webpki_roots::TLS_SERVER_ROOTS.iter().cloned().collect();
+        if let Some(pem) = &ca {
+            for cert in CertificateDer::pem_slice_iter(pem) {
+                let cert = cert.context("Invalid cluster CA certificate")?;
+                http = http.add_root_certificate(reqwest::Certificate::from_der(&cert)?);
+                roots.add(cert).context("Invalid cluster CA certificate")?;
+            }
+        }
+        let tls = rustls::ClientConfig::builder()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        Ok(Self {
+            api_url,
+            token,
+            namespace,
+            http: http.build()?,
+            tls: Arc::new(tls),
+        })
+    }
+
+    /// URL of `path` (e.g. `pods/zb-1`) in the sandbox namespace of `api`
+    /// (`api/v1` or `apis/networking.k8s.io/v1`).
+    fn url(&self, api: &str, path: &str) -> String {
+        format!(
+            "{}/{api}/namespaces/{}/{path}",
+            self.api_url, self.namespace
+        )
+    }
+
+    /// Send a request; `Ok(None)` when the object doesn't exist.
+    async fn send(
+        &self,
+        method: reqwest::Method,
+        url: &str,
+        body: Option<&Value>,
+    ) -> anyhow::Result<Option<Value>> {
+        let mut request = self
+            .http
+            .request(method, url)
+            .bearer_auth(&self.token)
+            .header("Accept", "application/json");
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| ZerobuildError::Network(format!("Kubernetes API: {e}")))?;
+        let status = response.status().as_u16();
+        let text = response.text().await.unwrap_or_default();
+        if status == 404 {
+            return Ok(None);
+        }
+        if status >= 400 {
+            let detail = format!("Kubernetes API returned {status}: {}", api_message(&text));
+            return Err(match status {
+                401 => ZerobuildError::AuthFailed(detail).into(),
+                403 => ZerobuildError::PermissionDenied(detail).into(),
+                409 => ZerobuildError::Conflict(detail).into(),
+                429 => ZerobuildError::RateLimited(detail).into(),
+                502..=504 => ZerobuildError::Network(detail).into(),
+                _ => anyhow::anyhow!(detail),
+            });
+        }
+        Ok(Some(serde_json::from_str(&text).unwrap_or(Value::Null)))
+    }
+}
+
+/// The `message` of a Kubernetes `Status` body, else the body itself.
+fn api_message(body: &str) -> String {
+    serde_json::from_str::<Value>(body)
+        .ok()
+        .and_then(|status| status["message"].as_str().map(str::to_string))
+        .unwrap_or_else(|| body.trim().to_string())
+}
+
+/// `relative` inside the sandbox root, rejecting `..` components.
+fn sandbox_path(relative: &str) -> anyhow::Result<String> {
+    shell_ops::sandbox_path(SANDBOX_ROOT, relative)
+}
+
+/// Pod of sandbox `name`: one container idling until commands are exec'd
+/// into it, without access to the cluster API.
+fn pod_manifest(name: &str, image: &str, config: &KubernetesSandboxConfig) -> Value {
+    json!({
+        "apiVersion": "v1",
+        "kind": "Pod",
+        "metadata": {
+            "name": name,
+            "labels": {
+                SANDBOX_LABEL: name,
+                "app.kubernetes.io/managed-by": "zerobuild",
+            },
+        },
+        "spec": {
+            "restartPolicy": "Never",
+            "automountServiceAccountToken": false,
+            "enableServiceLinks": false,
+            "terminationGracePeriodSeconds": 0,
+            "containers": [{
+                "name": CONTAINER,
+                "image": image,
+                "command": ["sh", "-c", format!("mkdir -p {SANDBOX_ROOT} && exec tail -f /dev/null")],
+                "workingDir": SANDBOX_ROOT,
+                "env": [{"name": "HOME", "value": SANDBOX_ROOT}],
+                "resources": {
+                    "limits": {"cpu": config.cpu, "memory": config.memory},
+                },
+            }],
+        },
+    })
+}
+
+/// Owner reference to pod `name`, so objects created for it are deleted
+/// with it.
+fn owned_by(name: &str, uid: &str) -> Value {
+    json!([{
+        "apiVersion": "v1",
+        "kind": "Pod",
+        "name": name,
+        "uid": uid,
+    }])
+}
+
+/// Service and (with a preview domain) Ingress exposing `port` of pod
+/// `name`, and the preview URL they serve.
+fn preview_manifests(
+    name: &str,
+    uid: &str,
+    port: u16,
+    namespace: &str,
+    config: &KubernetesSandboxConfig,
+) -> (Value, Option<Value>, String) {
+    let service_name = format!("{name}-{port}");
+    let metadata = json!({
+        "name": service_name,
+        "labels": {SANDBOX_LABEL: name},
+        "ownerReferences": owned_by(name, uid),
+    });
+    let service = json!({
+        "apiVersion": "v1",
+        "kind": "Service",
+        "metadata": metadata,
+        "spec": {
+            "selector": {SANDBOX_LABEL: name},
+            "ports": [{"port": port, "targetPort": port}],
+        },
+    });
+    if config.preview_domain.is_empty() {
+        let url = format!("http://{service_name}.{namespace}.svc.cluster.local:{port}");
+        return (service, None, url);
+    }
+    let host = format!("{port}-{name}.{}", config.preview_domain);
+    let mut spec = json!({
+        "rules": [{
+            "host": host,
+            "http": {"paths": [{
+                "path": "/",
+                "pathType": "Prefix",
+                "backend": {"service": {"name": service_name, "port": {"number": port}}},
+            }]},
+        }],
+    });
+    if !config.ingress_class.is_empty() {
+        spec["ingressClassName"] = json!(config.ingress_class);
+    }
+    let ingress = json!({
+        "apiVersion": "networking.k8s.io/v1",
+        "kind": "Ingress",
+        "metadata": metadata,
+        "spec": spec,
+    });
+    (service, Some(ingress), format!("https://{host}"))
+}
+
+/// Exit code reported by the exec status frame.
+fn exit_code(status: &Value) -> Result<i64, String> {
+    if status["status"] == "Success" {
+        return Ok(0);
+    }
+    let code = status["details"]["causes"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|cause| cause["reason"] == "ExitCode")
+        .and_then(|cause| cause["message"].as_str()?.parse().ok());
+    code.ok_or_else(|| {
+        status["message"]
+            .as_str()
+            .unwrap_or("command failed")
+            .to_string()
+    })
+}
+
+/// Sandbox provider running each sandbox as a Kubernetes pod.
+pub struct K8sSandboxClient {
+    config: KubernetesSandboxConfig,
+    templates: HashMap<String, SandboxTemplateConfig>,
+    cluster: tokio::sync::OnceCell<Cluster>,
+    /// Name of the active pod.
+    pod: Mutex<Option<String>>,
+    /// UID of the active pod, for the owner references of its previews.
+    pod_uid: Mutex<Option<String>>,
+    package_manager: Mutex<PackageManager>,
+}
+
+impl K8sSandboxClient {
+    pub fn new(
+        config: KubernetesSandboxConfig,
+        templates: HashMap<String, SandboxTemplateConfig>,
+    ) -> Self {
+        Self {
+            config,
+            templates,
+            cluster: tokio::sync::OnceCell::new(),
+            pod: Mutex::new(None),
+            pod_uid: Mutex::new(None),
+            package_manager: Mutex::new(PackageManager::default()),
+        }
+    }
+
+    /// The API server connection, set up on first use so a misconfigured
+    /// cluster fails the first sandbox call rather than startup.
+    async fn cluster(&self) -> anyhow::Result<&Cluster> {
+        self.cluster
+            .get_or_try_init(|| async { Cluster::from_config(&self.config) })
+            .await
+    }
+
+    fn active_pod(&self) -> anyhow::Result<String> {
+        let pod = self
+            .pod
+            .lock()
+            .clone()
+            .ok_or(ZerobuildError::SandboxMissing)?;
+        tracing::Span::current().record("sandbox_id", pod.as_str());
+        Ok(pod)
+    }
+
+    /// Phase of pod `name`, with the reason its container can't start if
+    /// it is stuck (e.g. `ErrImagePull`); `None` when the pod is gone.
+    async fn pod_phase(&self, name: &str) -> anyhow::Result<Option<(String, Option<String>)>> {
+        let cluster = self.cluster().await?;
+        let Some(pod) = cluster
+            .send(
+                reqwest::Method::GET,
+                &cluster.url("api/v1", &format!("pods/{name}")),
+                None,
+            )
+            .await?
+        else {
+            return Ok(None);
+        };
+        let phase = pod["status"]["phase"].as_str().unwrap_or("Pending");
+        let waiting = pod["status"]["containerStatuses"][0]["state"]["waiting"]["reason"]
+            .as_str()
+            .filter(|reason| {
+                matches!(
+                    *reason,
+                    "ErrImagePull"
+                        | "ImagePullBackOff"
+                        | "InvalidImageName"
+                        | "CreateContainerError"
+                )
+            })
+            .map(str::to_string);
+        Ok(Some((phase.to_string(), waiting)))
+    }
+
+    async fn delete_pod(&self, name: &str) -> anyhow::Result<()> {
+        let cluster = self.cluster().await?;
+        let url = cluster.url("api/v1", &format!("pods/{name}?gracePeriodSeconds=0"));
+        cluster.send(reqwest::Method::DELETE, &url, None).await?;
+        Ok(())
+    }
+
+    /// Run `script` under `sh -c` in the active pod, killed after
+    /// `timeout_ms`, sending output lines to `lines` as they arrive.
+    async fn exec(
+        &self,
+        script: &str,
+        timeout_ms: u64,
+        lines: Option<&tokio::sync::mpsc::UnboundedSender<String>>,
+    ) -> anyhow::Result<CommandOutput> {
+        let pod = self.active_pod()?;
+        let cluster = self.cluster().await?;
+        let ws_base = match cluster.api_url.split_once("://") {
+            Some(("http", rest)) => format!("ws://{rest}"),
+            Some((_, rest)) => format!("wss://{rest}"),
+            None => anyhow::bail!("Invalid Kubernetes API URL {}", cluster.api_url),
+        };
+        let timeout_secs = timeout_ms.div_ceil(1000).max(1).to_string();
+        let mut url = reqwest::Url::parse(&format!(
+            "{ws_base}/api/v1/namespaces/{}/pods/{pod}/exec",
+            cluster.namespace
+        ))?;
+        {
+            let mut query = url.query_pairs_mut();
+            query
+                .append_pair("container", CONTAINER)
+                .append_pair("stdout", "true")
+                .append_pair("stderr", "true");
+            for arg in ["timeout", "-s", "KILL", &timeout_secs, "sh", "-c", script] {
+                query.append_pair("command", arg);
+            }
+        }
+        let mut request = url.as_str().into_client_request()?;
+        let headers = request.headers_mut();
+        headers.insert(
+            "Authorization",
+            HeaderValue::from_str(&format!("Bearer {}", cluster.token))?,
+        );
+        headers.insert(
+            "Sec-WebSocket-Protocol",
+            HeaderValue::from_static(EXEC_PROTOCOL),
+        );
+        let connector = url
+            .scheme()
+            .eq("wss")
+            .then(|| Connector::Rustls(Arc::clone(&cluster.tls)));
+        let (mut socket, _) =
+            tokio_tungstenite::connect_async_tls_with_config(request, None, false, connector)
+                .await
+                .map_err(|e| match e {
+                    tokio_tungstenite::tungstenite::Error::Http(response)
+                        if response.status() == 404 =>
+                    {
+                        anyhow::Error::new(ZerobuildError::SandboxExpired(pod.clone()))
+                    }
+                    e => ZerobuildError::Network(format!("Kubernetes exec: {e}")).into(),
+                })?;
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        let mut status = None;
+        let read = async {
+            while let Some(frame) = socket.next().await {
+                let data = match frame? {
+                    Message::Binary(data) => data,
+                    Message::Close(_) => break,
+                    _ => continue,
+                };
+                let Some((&stream, payload)) = data.split_first() else {
+                    continue;
+                };
+                match stream {
+                    1 | 2 => {
+                        if let Some(lines) = lines {
+                            for line in String::from_utf8_lossy(payload).split(['\r', '\n']) {
+                                let line = super::ansi::normalize(line);
+                                if !line.trim().is_empty() {
+                                    let _ = lines.send(line);
+                                }
+                            }
+                        }
+                        if stream == 1 {
+                            stdout.extend_from_slice(payload);
+                        } else {
+                            stderr.extend_from_slice(payload);
+                        }
+                    }
+                    3 => status = serde_json::from_slice::<Value>(payload).ok(),
+                    _ => {}
+                }
+            }
+            Ok::<_, tokio_tungstenite::tungstenite::Error>(())
+        };
+        let limit = Duration::from_millis(timeout_ms) + EXEC_GRACE;
+        match tokio::time::timeout(limit, read).await {
+            Ok(result) => {
+                result.map_err(|e| ZerobuildError::Network(format!("Kubernetes exec: {e}")))?;
+            }
+            Err(_) => {
+                return Ok(CommandOutput {
+                    stdout: String::from_utf8_lossy(&stdout).into_owned(),
+                    stderr: format!("Command timed out after {timeout_ms}ms"),
+                    exit_code: -1,
+                })
+            }
+        }
+
+        let mut stderr = String::from_utf8_lossy(&stderr).into_owned();
+        let exit_code = match status.as_ref().map(exit_code) {
+            // `timeout -s KILL` ends the command with 128 + SIGKILL.
+            Some(Ok(137)) => {
+                stderr = format!("Command timed out after {timeout_ms}ms");
+                -1
+            }
+            Some(Ok(code)) => code,
+            Some(Err(message)) => {
+                if !stderr.is_empty() {
+                    stderr.push('\n');
+                }
+                stderr.push_str(&message);
+                -1
+            }
+            None => -1,
+        };
+        Ok(CommandOutput {
+            stdout: String::from_utf8_lossy(&stdout).into_owned(),
+            stderr,
+            exit_code,
+        })
+    }
+
+    /// Run a file operation `script`, failing with its stderr.
+    async fn file_op(&self, script: &str, what: &str) -> anyhow::Result<String> {
+        let output = self.exec(script, 60_000, None).await?;
+        if output.exit_code != 0 {
+            let detail = output.stderr.trim();
+            anyhow::bail!(
+                "{what}: {}",
+                if detail.is_empty() { "failed" } else { detail }
+            );
+        }
+        Ok(output.stdout)
+    }
+
+    /// Check a move or copy like the local provider does, then run `verb`
+    /// (`mv` or `cp -R`).
+    async fn transfer(
+        &self,
+        verb: &str,
+        from: &str,
+        to: &str,
+        overwrite: bool,
+    ) -> anyhow::Result<()> {
+        let script = shell_ops::transfer_script(SANDBOX_ROOT, verb, from, to, overwrite)?;
+        let action = if verb == "mv" { "move" } else { "copy" };
+        self.file_op(&script, &format!("Failed to {action} '{from}' to '{to}'"))
+            .await
+            .map(|_| ())
+    }
+
+    /// Create (or reset) a sandbox pod running `image`.
+    async fn create(&self, reset: bool, image: &str, timeout_ms: u64) -> anyhow::Result<String> {
+        let existing = self.pod.lock().clone();
+        if let Some(name) = existing {
+            if !reset
+                && matches!(self.pod_phase(&name).await?, Some((phase, _)) if phase == "Running")
+            {
+                tracing::Span::current().record("sandbox_id", name.as_str());
+                return Ok(name);
+            }
+            if let Err(e) = self.delete_pod(&name).await {
+                tracing::warn!(pod = %name, "Failed to delete the previous sandbox pod: {e:#}");
+            }
+            self.clear_id();
+        }
+
+        let name = format!("zb-{}", &Uuid::new_v4().simple().to_string()[..12]);
+        let cluster = self.cluster().await?;
+        let created = cluster
+            .send(
+                reqwest::Method::POST,
+                &cluster.url("api/v1", "pods"),
+                Some(&pod_manifest(&name, image, &self.config)),
+            )
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Namespace '{}' does not exist", cluster.namespace))?;
+        let uid = created["metadata"]["uid"].as_str().map(str::to_string);
+
+        let deadline = tokio::time::Instant::now() + Duration::from_millis(timeout_ms);
+        loop {
+            match self.pod_phase(&name).await? {
+                Some((phase, _)) if phase == "Running" => break,
+                Some((_, Some(reason))) => {
+                    let _ = self.delete_pod(&name).await;
+                    anyhow::bail!("Sandbox pod {name} cannot start image {image}: {reason}");
+                }
+                Some((phase, None)) if phase == "Failed" || phase == "Succeeded" => {
+                    anyhow::bail!("Sandbox pod {name} stopped while starting ({phase})");
+                }
+                None => anyhow::bail!("Sandbox pod {name} was deleted while starting"),
+                Some(_) => {}
+            }
+            if tokio::time::Instant::now() >= deadline {
+                let _ = self.delete_pod(&name).await;
+                return Err(ZerobuildError::Timeout(format!(
+                    "sandbox pod {name} did not start within {timeout_ms}ms"
+                ))
+                .into());
+            }
+            tokio::time::sleep(START_POLL).await;
+        }
+
+        *self.pod.lock() = Some(name.clone());
+        *self.pod_uid.lock() = uid;
+        tracing::Span::current().record("sandbox_id", name.as_str());
+        tracing::info!(pod = %name, namespace = %cluster.namespace, "Kubernetes sandbox created");
+        Ok(name)
+    }
+}
+
+#[async_trait]
+impl SandboxClient for K8sSandboxClient {
+    async fn create_sandbox(
+        &self,
+        reset: bool,
+        template: &str,
+        timeout_ms: u64,
+    ) -> anyhow::Result<String> {
+        let image = self
+            .templates
+            .get(template)
+            .map(|t| t.image.as_str())
+            .filter(|image| !image.is_empty())
+            .unwrap_or(&self.config.image);
+        self.create(reset, image, timeout_ms).await
+    }
+
+    async fn create_sandbox_from_image(
+        &self,
+        image: &str,
+        timeout_ms: u64,
+    ) -> anyhow::Result<String> {
+        self.create(true, image, timeout_ms).await
+    }
+
+    fn runs_images(&self) -> bool {
+        true
+    }
+
+    async fn kill_sandbox(&self) -> anyhow::Result<String> {
+        let Some(name) = self.pod.lock().clone() else {
+            return Ok("No active Kubernetes sandbox to kill.".to_string());
+        };
+        self.delete_pod(&name).await?;
+        self.clear_id();
+        tracing::info!(pod = %name, "Kubernetes sandbox deleted");
+        Ok(format!("Kubernetes sandbox pod {name} deleted."))
+    }
+
+    async fn run_command(
+        &self,
+        command: &str,
+        workdir: &str,
+        timeout_ms: u64,
+    ) -> anyhow::Result<CommandOutput> {
+        let dir = sandbox_path(workdir)?;
+        self.exec(
+            &format!("cd {} && {command}", quote(&dir)),
+            timeout_ms,
+            None,
+        )
+        .await
+    }
+
+    async fn run_command_streaming(
+        &self,
+        command: &str,
+        workdir: &str,
+        timeout_ms: u64,
+        lines: tokio::sync::mpsc::UnboundedSender<String>,
+    ) -> anyhow::Result<CommandOutput> {
+        let dir = sandbox_path(workdir)?;
+        self.exec(
+            &format!("cd {} && {command}", quote(&dir)),
+            timeout_ms,
+            Some(&lines),
+        )
+        .await
+    }
+
+    async fn write_file(&self, path: &str, content: &str) -> anyhow::Result<()> {
+        for script in shell_ops::write_scripts(SANDBOX_ROOT, path, content)? {
+            self.file_op(&script, &format!("Failed to write file '{path}'"))
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn move_path(&self, from: &str, to: &str, overwrite: bool) -> anyhow::Result<()> {
+        self.transfer("mv", from, to, overwrite).await
+    }
+
+    async fn copy_path(&self, from: &str, to: &str, overwrite: bool) -> anyhow::Result<()> {
+        self.transfer("cp -R", from, to, overwrite).await
+    }
+
+    async fn delete_path(&self, path: &str, recursive: bool) -> anyhow::Result<()> {
+        let script = shell_ops::delete_script(SANDBOX_ROOT, path, recursive)?;
+        self.file_op(&script, &format!("Failed to delete '{path}'"))
+            .await
+            .map(|_| ())
+    }
+
+    async fn read_file(&self, path: &str) -> anyhow::Result<String> {
+        let script = shell_ops::read_script(SANDBOX_ROOT, path)?;
+        let output = self.exec(&script, 60_000, None).await?;
+        match output.exit_code {
+            0 => Ok(output.stdout),
+            shell_ops::READ_MISSING => Err(ZerobuildError::FileNotFound(path.to_string()).into()),
+            _ => anyhow::bail!("Failed to read file '{path}': {}", output.stderr.trim()),
+        }
+    }
+
+    async fn list_files(&self, path: &str) -> anyhow::Result<String> {
+        let script = shell_ops::list_script(SANDBOX_ROOT, path)?;
+        let output = self
+            .file_op(&script, &format!("Failed to list directory '{path}'"))
+            .await?;
+        Ok(shell_ops::list_entries(&output))
+    }
+
+    async fn get_preview_url(&self, port: u16) -> anyhow::Result<String> {
+        let name = self.active_pod()?;
+        let cluster = self.cluster().await?;
+        let cached_uid = self.pod_uid.lock().clone();
+        let uid = match cached_uid {
+            Some(uid) => uid,
+            None => {
+                // The ID was restored with `set_id`; look the pod up.
+                let pod = cluster
+                    .send(
+                        reqwest::Method::GET,
+                        &cluster.url("api/v1", &format!("pods/{name}")),
+                        None,
+                    )
+                    .await?
+                    .ok_or_else(|| ZerobuildError::SandboxExpired(name.clone()))?;
+                let uid = pod["metadata"]["uid"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string();
+                *self.pod_uid.lock() = Some(uid.clone());
+                uid
+            }
+        };
+        let (service, ingress, url) =
+            preview_manifests(&name, &uid, port, &cluster.namespace, &self.config);
+        let created = [
+            ("api/v1", "services", Some(service)),
+            ("apis/networking.k8s.io/v1", "ingresses", ingress),
+        ];
+        for (api, kind, manifest) in created {
+            let Some(manifest) = manifest else { continue };
+            if let Err(e) = cluster
+                .send(
+                    reqwest::Method::POST,
+                    &cluster.url(api, kind),
+                    Some(&manifest),
+                )
+                .await
+            {
+                // The preview of this port was exposed before.
+                if crate::error::code_of(&e) != ErrorCode::Conflict {
+                    return Err(e.context(format!("Failed to expose port {port}")));
+                }
+            }
+        }
+        Ok(url)
+    }
+
+    async fn collect_snapshot_files(
+        &self,
+        workdir: &str,
+    ) -> anyhow::Result<HashMap<String, String>> {
+        let script = shell_ops::snapshot_script(SANDBOX_ROOT, workdir)?;
+        let output = self
+            .file_op(&script, &format!("Failed to collect files of '{workdir}'"))
+            .await?;
+        Ok(shell_ops::parse_snapshot(&output))
+    }
+
+    fn current_id(&self) -> Option<String> {
+        self.pod.lock().clone()
+    }
+
+    fn set_id(&self, id: String) {
+        *self.pod.lock() = Some(id);
+        *self.pod_uid.lock() = None;
+    }
+
+    fn clear_id(&self) {
+        *self.pod.lock() = None;
+        *self.pod_uid.lock() = None;
+    }
+
+    fn package_manager(&self) -> PackageManager {
+        *self.package_manager.lock()
+    }
+
+    fn set_package_manager(&self, pm: PackageManager) {
+        *self.package_manager.lock() = pm;
+    }
+
+    /// Detect the package manager installed in the pod's image.
+    async fn detect_package_manager(&self) -> PackageManager {
+        let pm = match self
+            .exec(shell_ops::DETECT_PACKAGE_MANAGER, 30_000, None)
+            .await
+        {
+            Ok(output) => PackageManager::from_name(output.stdout.trim()).unwrap_or_default(),
+            Err(e) => {
+                tracing::warn!("Failed to detect the sandbox's package manager: {e:#}");
+                PackageManager::default()
+            }
+        };
+        self.set_package_manager(pm);
+        pm
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_stay_inside_the_sandbox_root() {
+        assert_eq!(sandbox_path(".").unwrap(), "/workspace");
+        assert_eq!(
+            sandbox_path("/project/src/").unwrap(),
+            "/workspace/project/src"
+        );
+        assert!(sandbox_path("project/../../etc").is_err());
+        assert_eq!(quote("it's"), r"'it'\''s'");
+    }
+
+    #[test]
+    fn exec_status_gives_the_exit_code() {
+        assert_eq!(exit_code(&json!({"status": "Success"})), Ok(0));
+        let failed = json!({
+            "status": "Failure",
+            "reason": "NonZeroExitCode",
+            "details": {"causes": [{"reason": "ExitCode", "message": "2"}]},
+        });
+        assert_eq!(exit_code(&failed), Ok(2));
+        let error = json!({"status": "Failure", "message": "container not found"});
+        assert_eq!(exit_code(&error), Err("container not found".to_string()));
+    }
+
+    #[test]
+    fn previews_get_an_ingress_with_a_preview_domain() {
+        let mut config = KubernetesSandboxConfig::default();
+        let (service, ingress, url) = preview_manifests("zb-1", "uid-1", 3000, "builds", &config);
+        assert_eq!(service["metadata"]["name"], "zb-1-3000");
+        assert_eq!(service["metadata"]["ownerReferences"][0]["uid"], "uid-1");
+        assert!(ingress.is_none());
+        assert_eq!(url, "http://zb-1-3000.builds.svc.cluster.local:3000");
+
+        config.preview_domain = "preview.example.com".into();
+        config.ingress_class = "nginx".into();
+        let (_, ingress, url) = preview_manifests("zb-1", "uid-1", 3000, "builds", &config);
+        let ingress = ingress.unwrap();
+        assert_eq!(ingress["spec"]["ingressClassName"], "nginx");
+        assert_eq!(
+            ingress["spec"]["rules"][0]["host"],
+            "3000-zb-1.preview.example.com"
+        );
+        assert_eq!(url, "https://3000-zb-1.preview.example.com");
+    }
+}
@@ -12,13 +12,21 @@
 //!   (`HOME`, `TMPDIR`, `NPM_CONFIG_CACHE`, `NPM_CONFIG_PREFIX` → sandbox dir).
 //! - Network: unrestricted (npm downloads must work).
 //! - Timeout: `tokio::time::timeout` + `kill_on_drop(true)` for child cleanup.
+//! - Terminal: output goes to pipes; `run_command_in_pty` runs the command
+//!   under `script(1)` for a pseudo-terminal. All output is passed through
+//!   [`CommandOutput::normalized`].
 //!
 //! **Trade-off:** processes run as the same OS user — no kernel-level syscall
 //! filtering. Acceptable for a trusted LLM building its own code. Main
 //! protection: prevents accidental writes outside sandbox dir and credential
 //! leaks via HOME.
 
-use super::{CommandOutput, PackageManager, SandboxClient};
+use super::shell_session::ShellSession;
+use super::{
+    procfs, CommandOutput, ListeningPort, PackageManager, SandboxClient, SandboxProcess,
+    SessionOutput, ShellSessionInfo,
+};
+use crate::error::ZerobuildError;
 use anyhow::Context as _;
 use async_trait::async_trait;
 use parking_lot::Mutex;
@@ -30,7 +38,7 @@ use std::sync::Arc;
 use uuid::Uuid;
 
 /// Directories skipped when collecting a snapshot.
-const SKIP_DIRS: &[&str] = &[
+pub(crate) const SKIP_DIRS: &[&str] = &[
     "node_modules",
     ".next",
     ".git",
@@ -40,6 +48,13 @@ const SKIP_DIRS: &[&str] = &[
     ".npm-cache",
 ];
 
+/// Terminal size reported to commands run under a PTY.
+const PTY_COLUMNS: &str = "120";
+const PTY_LINES: &str = "40";
+
+/// Most shell sessions open at once.
+const MAX_SHELL_SESSIONS: usize = 8;
+
 /// State for an active Cloudflare Quick Tunnel.
 struct TunnelHandle {
     child: tokio::process::Child,
@@ -57,6 +72,8 @@ pub struct LocalProcessSandboxClient {
     tunnel_process: Arc<Mutex<Option<TunnelHandle>>>,
     /// Detected package manager for this sandbox.
     package_manager: Arc<Mutex<PackageManager>>,
+    /// Open `sandbox_shell` sessions by name.
+    shell_sessions: Arc<Mutex<HashMap<String, Arc<tokio::sync::Mutex<ShellSession>>>>>,
 }
 
 impl LocalProcessSandboxClient {
@@ -66,7 +83,25 @@ impl LocalProcessSandboxClient {
             sandbox_id: Arc::new(Mutex::new(None)),
             tunnel_process: Arc::new(Mutex::new(None)),
             package_manager: Arc::new(Mutex::new(PackageManager::Npm)),
+            shell_sessions: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Directory of the active sandbox. Fails with
+    /// [`ZerobuildError::SandboxMissing`] when none was created and with
+    /// [`ZerobuildError::SandboxExpired`] when its directory has since been
+    /// removed (temp-dir cleanup, a reboot).
+    fn active_dir(&self) -> anyhow::Result<String> {
+        let sandbox_dir = self
+            .sandbox_id
+            .lock()
+            .clone()
+            .ok_or(ZerobuildError::SandboxMissing)?;
+        if !Path::new(&sandbox_dir).is_dir() {
+            return Err(ZerobuildError::SandboxExpired(sandbox_dir).into());
         }
+        record_sandbox(&sandbox_dir);
+        Ok(sandbox_dir)
     }
 
     /// Resolve `relative` against `sandbox_dir`, rejecting any `..` components.
@@ -95,6 +130,280 @@ impl LocalProcessSandboxClient {
 
         Ok(result)
     }
+
+    /// Active sandbox directory and `path` resolved inside it.
+    fn resolve(&self, path: &str) -> anyhow::Result<(PathBuf, PathBuf)> {
+        let sandbox_dir = self.active_dir()?;
+        let root = PathBuf::from(&sandbox_dir);
+        let target = Self::safe_join(&root, path)?;
+        Ok((root, target))
+    }
+
+    /// Resolve the source and destination of a move or copy, checking that
+    /// the source exists, the destination is free (or may be replaced) and
+    /// isn't inside the source.
+    fn resolve_transfer(
+        &self,
+        from: &str,
+        to: &str,
+        overwrite: bool,
+    ) -> anyhow::Result<(PathBuf, PathBuf)> {
+        let (root, source) = self.resolve(from)?;
+        let (_, dest) = self.resolve(to)?;
+        if source == root || dest == root {
+            anyhow::bail!("Cannot move or copy the sandbox root");
+        }
+        if !source.exists() {
+            anyhow::bail!("'{from}' does not exist");
+        }
+        if dest.starts_with(&source) {
+            anyhow::bail!("Cannot move or copy '{from}' into itself ('{to}')");
+        }
+        if dest.exists() {
+            if !overwrite {
+                anyhow::bail!("'{to}' already exists; pass overwrite=true to replace it");
+            }
+            remove_path(&dest).with_context(|| format!("Failed to replace '{to}'"))?;
+        }
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create parent dirs for '{to}'"))?;
+        }
+        Ok((source, dest))
+    }
+
+    /// Start `command` under `sh -c` in `workdir` of the active sandbox with
+    /// the restricted environment and piped output. With `pty`, the command
+    /// runs under `script(1)`, which gives it a pseudo-terminal and copies
+    /// what it writes there to stdout; without `script` it falls back to
+    /// pipes.
+    fn spawn_command(
+        &self,
+        command: &str,
+        workdir: &str,
+        pty: bool,
+    ) -> anyhow::Result<tokio::process::Child> {
+        let sandbox_dir = self.active_dir()?;
+
+        let sandbox_path = PathBuf::from(&sandbox_dir);
+
+        // Resolve workdir inside sandbox, creating it if necessary
+        let resolved_workdir = if workdir.is_empty() || workdir == "/" {
+            sandbox_path.clone()
+        } else {
+            Self::safe_join(&sandbox_path, workdir)?
+        };
+        std::fs::create_dir_all(&resolved_workdir)
+            .map_err(|e| anyhow::anyhow!("Failed to create workdir: {e}"))?;
+
+        let use_script = pty && on_path("script");
+        if pty && !use_script {
+            tracing::warn!("`script` is not installed; running the command without a PTY");
+        }
+        let mut process = if use_script {
+            let mut process = Self::restricted_command("script", &sandbox_path, &resolved_workdir);
+            if cfg!(target_os = "linux") {
+                // util-linux: quiet, flush, pass the exit code through.
+                process.args(["-qfec", command, "/dev/null"]);
+            } else {
+                process.args(["-q", "/dev/null", "sh", "-c", command]);
+            }
+            process
+                .env("COLUMNS", PTY_COLUMNS)
+                .env("LINES", PTY_LINES)
+                .stdin(std::process::Stdio::null());
+            process
+        } else {
+            let mut process = Self::restricted_command("sh", &sandbox_path, &resolved_workdir);
+            process.arg("-c").arg(command);
+            process
+        };
+        process
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| anyhow::anyhow!("Failed to spawn command: {e}"))
+    }
+
+    /// `program` set up to run in `workdir` of the sandbox at `sandbox_path`
+    /// with the restricted environment.
+    fn restricted_command(
+        program: &str,
+        sandbox_path: &Path,
+        workdir: &Path,
+    ) -> tokio::process::Command {
+        let path_val =
+            std::env::var("PATH").unwrap_or_else(|_| "/usr/local/bin:/usr/bin:/bin".to_string());
+        let lang_val = std::env::var("LANG").unwrap_or_else(|_| "en_US.UTF-8".to_string());
+        let npm_cache = sandbox_path.join(".npm-cache");
+        let npm_global = sandbox_path.join(".npm-global");
+        let tmp_dir = sandbox_path.join("tmp");
+
+        let mut command = tokio::process::Command::new(program);
+        command
+            .current_dir(workdir)
+            .env_clear()
+            .env("PATH", &path_val)
+            .env("HOME", sandbox_path)
+            .env("TMPDIR", &tmp_dir)
+            .env("NPM_CONFIG_CACHE", &npm_cache)
+            .env("NPM_CONFIG_PREFIX", &npm_global)
+            .env("NPM_CONFIG_UPDATE_NOTIFIER", "false")
+            .env("NEXT_TELEMETRY_DISABLED", "1")
+            .env("CI", "1")
+            .env("LANG", &lang_val)
+            .env("TERM", "xterm-256color");
+        command
+    }
+
+    /// Start a shell session in the root of `sandbox_dir`: bash without
+    /// startup files when it is installed, else `sh`.
+    fn start_shell(sandbox_dir: &str) -> anyhow::Result<ShellSession> {
+        let sandbox_path = Path::new(sandbox_dir);
+        let shell = if on_path("bash") {
+            let mut shell = Self::restricted_command("bash", sandbox_path, sandbox_path);
+            shell.args(["--noprofile", "--norc"]);
+            shell
+        } else {
+            Self::restricted_command("sh", sandbox_path, sandbox_path)
+        };
+        ShellSession::start(shell, sandbox_dir, sandbox_dir)
+    }
+
+    /// Drop every shell session, killing the shells.
+    fn close_all_sessions(&self) {
+        self.shell_sessions.lock().clear();
+    }
+}
+
+/// Whether `program` is installed in a directory of `PATH`.
+fn on_path(program: &str) -> bool {
+    std::env::var_os("PATH")
+        .is_some_and(|path| std::env::split_paths(&path).any(|dir| dir.join(program).is_file()))
+}
+
+/// Wait up to `timeout_ms` for `child` and collect its normalized output.
+async fn wait_for_output(
+    child: tokio::process::Child,
+    timeout_ms: u64,
+) -> anyhow::Result<CommandOutput> {
+    let timeout_result = tokio::time::timeout(
+        std::time::Duration::from_millis(timeout_ms),
+        child.wait_with_output(),
+    )
+    .await;
+
+    match timeout_result {
+        Err(_elapsed) => {
+            // kill_on_drop handles the process; return a timeout indicator
+            Ok(timed_out(timeout_ms))
+        }
+        Ok(Err(e)) => Err(anyhow::anyhow!("Command execution failed: {e}")),
+        Ok(Ok(output)) => {
+            let exit_code = output.status.code().map(i64::from).unwrap_or(-1);
+            Ok(CommandOutput {
+                stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+                stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+                exit_code,
+            }
+            .normalized())
+        }
+    }
+}
+
+/// `cwd` relative to `sandbox_dir`, `.` for the root itself; paths outside
+/// the sandbox stay absolute.
+fn relative_cwd(sandbox_dir: &str, cwd: &str) -> String {
+    match Path::new(cwd).strip_prefix(sandbox_dir) {
+        Ok(rel) if rel.as_os_str().is_empty() => ".".to_string(),
+        Ok(rel) => rel.to_string_lossy().into_owned(),
+        Err(_) => cwd.to_string(),
+    }
+}
+
+/// Remove a file or a whole directory tree.
+fn remove_path(path: &Path) -> std::io::Result<()> {
+    if path.is_dir() && !path.is_symlink() {
+        std::fs::remove_dir_all(path)
+    } else {
+        std::fs::remove_file(path)
+    }
+}
+
+/// Copy a file, or a directory tree recursively. Symlinks are copied as the
+/// files they point to.
+fn copy_recursive(source: &Path, dest: &Path) -> std::io::Result<()> {
+    if source.is_dir() {
+        std::fs::create_dir_all(dest)?;
+        for entry in std::fs::read_dir(source)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &dest.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        std::fs::copy(source, dest).map(|_| ())
+    }
+}
+
+/// Output of a command that hit its timeout.
+fn timed_out(timeout_ms: u64) -> CommandOutput {
+    CommandOutput {
+        stdout: String::new(),
+        stderr: format!("Command timed out after {timeout_ms}ms"),
+        exit_code: -1,
+    }
+}
+
+/// Read `pipe` to the end, sending each line (split on `\n` and the `\r` of
+/// progress bars) to `lines`, and return everything read.
+async fn pump_lines(
+    pipe: Option<impl tokio::io::AsyncRead + Unpin>,
+    lines: tokio::sync::mpsc::UnboundedSender<String>,
+) -> Vec<u8> {
+    use tokio::io::AsyncBufReadExt;
+
+    let mut all = Vec::new();
+    let Some(pipe) = pipe else {
+        return all;
+    };
+    let mut reader = tokio::io::BufReader::new(pipe);
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf).await {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {
+                all.extend_from_slice(&buf);
+                for line in String::from_utf8_lossy(&buf).split(['\r', '\n']) {
+                    let line = super::ansi::normalize(line);
+                    if !line.trim().is_empty() {
+                        let _ = lines.send(line);
+                    }
+                }
+            }
+        }
+    }
+    all
+}
+
+/// Note the sandbox on the current span (the agent's `tool` span), so every
+/// log line of the tool call carries `sandbox_id`.
+/// `err` as a [`ZerobuildError`] when its kind has a typed form, else
+/// `message`.
+fn io_failure(path: &str, err: &std::io::Error, message: String) -> anyhow::Error {
+    match ZerobuildError::from_io(path, err) {
+        Some(typed) => typed.into(),
+        None => anyhow::anyhow!(message),
+    }
+}
+
+fn record_sandbox(sandbox_dir: &str) {
+    let id = Path::new(sandbox_dir)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(sandbox_dir);
+    tracing::Span::current().record("sandbox_id", id);
 }
 
 impl Default for LocalProcessSandboxClient {
@@ -115,12 +424,14 @@ impl SandboxClient for LocalProcessSandboxClient {
         if !reset {
             if let Some(id) = self.sandbox_id.lock().clone() {
                 if std::path::Path::new(&id).exists() {
+                    record_sandbox(&id);
                     return Ok(id);
                 }
             }
         }
 
         // Remove old sandbox dir if present
+        self.close_all_sessions();
         let old_id = self.sandbox_id.lock().clone();
         if let Some(ref old_path) = old_id {
             let _ = std::fs::remove_dir_all(old_path);
@@ -183,7 +494,8 @@ impl SandboxClient for LocalProcessSandboxClient {
             .to_string();
 
         *self.sandbox_id.lock() = Some(id.clone());
-        tracing::info!("Local sandbox created at {id}");
+        record_sandbox(&id);
+        tracing::info!(path = %id, "Local sandbox created");
         Ok(id)
     }
 
@@ -193,6 +505,7 @@ impl SandboxClient for LocalProcessSandboxClient {
         if let Some(mut child) = tunnel_child {
             let _ = child.kill().await;
         }
+        self.close_all_sessions();
 
         let id = match self.sandbox_id.lock().clone() {
             Some(id) => id,
@@ -201,7 +514,7 @@ impl SandboxClient for LocalProcessSandboxClient {
 
         *self.sandbox_id.lock() = None;
         let _ = std::fs::remove_dir_all(&id);
-        tracing::info!("Local sandbox removed: {id}");
+        tracing::info!(path = %id, "Local sandbox removed");
         Ok(format!("Local sandbox {id} removed."))
     }
 
@@ -211,114 +524,123 @@ impl SandboxClient for LocalProcessSandboxClient {
         workdir: &str,
         timeout_ms: u64,
     ) -> anyhow::Result<CommandOutput> {
-        let sandbox_dir = self.sandbox_id.lock().clone().ok_or_else(|| {
-            anyhow::anyhow!("No active local sandbox. Call sandbox_create first.")
-        })?;
+        let child = self.spawn_command(command, workdir, false)?;
+        wait_for_output(child, timeout_ms).await
+    }
 
-        let sandbox_path = PathBuf::from(&sandbox_dir);
+    async fn run_command_in_pty(
+        &self,
+        command: &str,
+        workdir: &str,
+        timeout_ms: u64,
+    ) -> anyhow::Result<CommandOutput> {
+        let child = self.spawn_command(command, workdir, true)?;
+        wait_for_output(child, timeout_ms).await
+    }
 
-        // Resolve workdir inside sandbox, creating it if necessary
-        let resolved_workdir = if workdir.is_empty() || workdir == "/" {
-            sandbox_path.clone()
-        } else {
-            Self::safe_join(&sandbox_path, workdir)?
+    async fn run_command_streaming(
+        &self,
+        command: &str,
+        workdir: &str,
+        timeout_ms: u64,
+        lines: tokio::sync::mpsc::UnboundedSender<String>,
+    ) -> anyhow::Result<CommandOutput> {
+        let mut child = self.spawn_command(command, workdir, false)?;
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+
+        let run = async {
+            let (stdout, stderr, status) = tokio::join!(
+                pump_lines(stdout, lines.clone()),
+                pump_lines(stderr, lines.clone()),
+                child.wait()
+            );
+            (stdout, stderr, status)
         };
-        std::fs::create_dir_all(&resolved_workdir)
-            .map_err(|e| anyhow::anyhow!("Failed to create workdir: {e}"))?;
-
-        // Build restricted environment
-        let path_val =
-            std::env::var("PATH").unwrap_or_else(|_| "/usr/local/bin:/usr/bin:/bin".to_string());
-        let lang_val = std::env::var("LANG").unwrap_or_else(|_| "en_US.UTF-8".to_string());
-        let npm_cache = sandbox_path.join(".npm-cache");
-        let npm_global = sandbox_path.join(".npm-global");
-        let tmp_dir = sandbox_path.join("tmp");
-
-        let child = tokio::process::Command::new("sh")
-            .arg("-c")
-            .arg(command)
-            .current_dir(&resolved_workdir)
-            .env_clear()
-            .env("PATH", &path_val)
-            .env("HOME", &sandbox_path)
-            .env("TMPDIR", &tmp_dir)
-            .env("NPM_CONFIG_CACHE", &npm_cache)
-            .env("NPM_CONFIG_PREFIX", &npm_global)
-            .env("NPM_CONFIG_UPDATE_NOTIFIER", "false")
-            .env("NEXT_TELEMETRY_DISABLED", "1")
-            .env("CI", "1")
-            .env("LANG", &lang_val)
-            .env("TERM", "xterm-256color")
-            .stdout(std::process::Stdio::piped())
-            .stderr(std::process::Stdio::piped())
-            .kill_on_drop(true)
-            .spawn()
-            .map_err(|e| anyhow::anyhow!("Failed to spawn command: {e}"))?;
-
-        let timeout_result = tokio::time::timeout(
-            std::time::Duration::from_millis(timeout_ms),
-            child.wait_with_output(),
-        )
-        .await;
-
-        match timeout_result {
-            Err(_elapsed) => {
-                // kill_on_drop handles the process; return a timeout indicator
-                Ok(CommandOutput {
-                    stdout: String::new(),
-                    stderr: format!("Command timed out after {timeout_ms}ms"),
-                    exit_code: -1,
-                })
-            }
-            Ok(Err(e)) => Err(anyhow::anyhow!("Command execution failed: {e}")),
-            Ok(Ok(output)) => {
-                let exit_code = output.status.code().map(i64::from).unwrap_or(-1);
-                Ok(CommandOutput {
-                    stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
-                    stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
-                    exit_code,
-                })
+        match tokio::time::timeout(std::time::Duration::from_millis(timeout_ms), run).await {
+            // kill_on_drop handles the process when `child` goes out of scope
+            Err(_elapsed) => Ok(timed_out(timeout_ms)),
+            Ok((_, _, Err(e))) => Err(anyhow::anyhow!("Command execution failed: {e}")),
+            Ok((stdout, stderr, Ok(status))) => Ok(CommandOutput {
+                stdout: String::from_utf8_lossy(&stdout).into_owned(),
+                stderr: String::from_utf8_lossy(&stderr).into_owned(),
+                exit_code: status.code().map(i64::from).unwrap_or(-1),
             }
+            .normalized()),
         }
     }
 
     async fn write_file(&self, path: &str, content: &str) -> anyhow::Result<()> {
-        let sandbox_dir = self
-            .sandbox_id
-            .lock()
-            .clone()
-            .ok_or_else(|| anyhow::anyhow!("No active local sandbox."))?;
+        let sandbox_dir = self.active_dir()?;
 
         let target = Self::safe_join(Path::new(&sandbox_dir), path)?;
 
         if let Some(parent) = target.parent() {
-            std::fs::create_dir_all(parent)
-                .map_err(|e| anyhow::anyhow!("Failed to create parent dirs for '{path}': {e}"))?;
+            std::fs::create_dir_all(parent).map_err(|e| {
+                io_failure(
+                    path,
+                    &e,
+                    format!("Failed to create parent dirs for '{path}': {e}"),
+                )
+            })?;
         }
 
         std::fs::write(&target, content)
-            .map_err(|e| anyhow::anyhow!("Failed to write file '{path}': {e}"))
+            .map_err(|e| io_failure(path, &e, format!("Failed to write file '{path}': {e}")))
+    }
+
+    async fn move_path(&self, from: &str, to: &str, overwrite: bool) -> anyhow::Result<()> {
+        let (source, dest) = self.resolve_transfer(from, to, overwrite)?;
+        std::fs::rename(&source, &dest)
+            .map_err(|e| anyhow::anyhow!("Failed to move '{from}' to '{to}': {e}"))
+    }
+
+    async fn copy_path(&self, from: &str, to: &str, overwrite: bool) -> anyhow::Result<()> {
+        let (source, dest) = self.resolve_transfer(from, to, overwrite)?;
+        copy_recursive(&source, &dest)
+            .map_err(|e| anyhow::anyhow!("Failed to copy '{from}' to '{to}': {e}"))
+    }
+
+    async fn delete_path(&self, path: &str, recursive: bool) -> anyhow::Result<()> {
+        let (root, target) = self.resolve(path)?;
+        if target == root {
+            anyhow::bail!("Refusing to delete the sandbox root");
+        }
+        if target.is_dir() && !target.is_symlink() {
+            let result = if recursive {
+                std::fs::remove_dir_all(&target)
+            } else {
+                std::fs::remove_dir(&target)
+            };
+            result.map_err(|e| {
+                if recursive {
+                    anyhow::anyhow!("Failed to delete directory '{path}': {e}")
+                } else {
+                    anyhow::anyhow!(
+                        "Failed to delete directory '{path}': {e} (pass recursive=true to delete \
+                         a non-empty directory)"
+                    )
+                }
+            })
+        } else if target.symlink_metadata().is_ok() {
+            std::fs::remove_file(&target)
+                .map_err(|e| anyhow::anyhow!("Failed to delete '{path}': {e}"))
+        } else {
+            anyhow::bail!("'{path}' does not exist")
+        }
     }
 
     async fn read_file(&self, path: &str) -> anyhow::Result<String> {
-        let sandbox_dir = self
-            .sandbox_id
-            .lock()
-            .clone()
-            .ok_or_else(|| anyhow::anyhow!("No active local sandbox."))?;
+        let sandbox_dir = self.active_dir()?;
 
         let target = Self::safe_join(Path::new(&sandbox_dir), path)?;
 
         std::fs::read_to_string(&target)
-            .map_err(|e| anyhow::anyhow!("Failed to read file '{path}': {e}"))
+            .map_err(|e| io_failure(path, &e, format!("Failed to read file '{path}': {e}")))
     }
 
     async fn list_files(&self, path: &str) -> anyhow::Result<String> {
-        let sandbox_dir = self
-            .sandbox_id
-            .lock()
-            .clone()
-            .ok_or_else(|| anyhow::anyhow!("No active local sandbox."))?;
+        let sandbox_dir = self.active_dir()?;
 
         let target = Self::safe_join(Path::new(&sandbox_dir), path)?;
 
@@ -344,6 +666,97 @@ impl SandboxClient for LocalProcessSandboxClient {
         Ok(format!("http://localhost:{port}"))
     }
 
+    async fn run_in_session(
+        &self,
+        session: &str,
+        command: &str,
+        timeout_ms: u64,
+    ) -> anyhow::Result<SessionOutput> {
+        let sandbox_dir = self.active_dir()?;
+        let shell = {
+            let mut sessions = self.shell_sessions.lock();
+            match sessions.get(session) {
+                Some(shell) => Arc::clone(shell),
+                None => {
+                    if sessions.len() >= MAX_SHELL_SESSIONS {
+                        anyhow::bail!(
+                            "{MAX_SHELL_SESSIONS} shell sessions are open; close one first"
+                        );
+                    }
+                    let shell = Arc::new(tokio::sync::Mutex::new(Self::start_shell(&sandbox_dir)?));
+                    sessions.insert(session.to_string(), Arc::clone(&shell));
+                    shell
+                }
+            }
+        };
+
+        let mut guard = shell.lock().await;
+        if guard.sandbox_dir() != sandbox_dir || !guard.is_alive() {
+            *guard = Self::start_shell(&sandbox_dir)?;
+        }
+        let output = guard
+            .run(command, std::time::Duration::from_millis(timeout_ms))
+            .await;
+        let cwd = relative_cwd(&sandbox_dir, guard.cwd());
+        if !guard.is_alive() {
+            drop(guard);
+            let mut sessions = self.shell_sessions.lock();
+            if sessions
+                .get(session)
+                .is_some_and(|open| Arc::ptr_eq(open, &shell))
+            {
+                sessions.remove(session);
+            }
+        }
+        Ok(SessionOutput {
+            output: output?.normalized(),
+            cwd,
+        })
+    }
+
+    async fn close_session(&self, session: &str) -> anyhow::Result<bool> {
+        let shell = self.shell_sessions.lock().remove(session);
+        match shell {
+            Some(shell) => {
+                shell.lock().await.kill().await;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn shell_sessions(&self) -> Vec<ShellSessionInfo> {
+        let sessions: Vec<(String, Arc<tokio::sync::Mutex<ShellSession>>)> = self
+            .shell_sessions
+            .lock()
+            .iter()
+            .map(|(name, shell)| (name.clone(), Arc::clone(shell)))
+            .collect();
+        let mut infos: Vec<ShellSessionInfo> = sessions
+            .into_iter()
+            .map(|(name, shell)| ShellSessionInfo {
+                name,
+                cwd: shell
+                    .try_lock()
+                    .ok()
+                    .map(|shell| relative_cwd(shell.sandbox_dir(), shell.cwd())),
+            })
+            .collect();
+        infos.sort_by(|a, b| a.name.cmp(&b.name));
+        infos
+    }
+
+    async fn processes(&self) -> anyhow::Result<Vec<SandboxProcess>> {
+        // The host's /proc lists every process; keep those started here.
+        let sandbox_dir = self.active_dir()?;
+        Ok(procfs::read(self, Some(&sandbox_dir)).await?.processes)
+    }
+
+    async fn listening_ports(&self) -> anyhow::Result<Vec<ListeningPort>> {
+        let sandbox_dir = self.active_dir()?;
+        Ok(procfs::read(self, Some(&sandbox_dir)).await?.ports)
+    }
+
     async fn start_tunnel(&self, port: u16) -> anyhow::Result<String> {
         // Return cached URL if same port is already tunnelled
         {
@@ -394,11 +807,7 @@ impl SandboxClient for LocalProcessSandboxClient {
         &self,
         workdir: &str,
     ) -> anyhow::Result<HashMap<String, String>> {
-        let sandbox_dir = self
-            .sandbox_id
-            .lock()
-            .clone()
-            .ok_or_else(|| anyhow::anyhow!("No active local sandbox."))?;
+        let sandbox_dir = self.active_dir()?;
 
         let base = Self::safe_join(Path::new(&sandbox_dir), workdir)?;
 
@@ -628,6 +1037,43 @@ mod tests {
         client.kill_sandbox().await.unwrap();
     }
 
+    #[tokio::test]
+    async fn move_copy_and_delete_stay_inside_sandbox() {
+        let client = LocalProcessSandboxClient::new();
+        client.create_sandbox(false, "", 30_000).await.unwrap();
+        client.write_file("project/src/a.ts", "a").await.unwrap();
+
+        client
+            .copy_path("project/src", "project/lib", false)
+            .await
+            .unwrap();
+        assert_eq!(client.read_file("project/lib/a.ts").await.unwrap(), "a");
+        client
+            .move_path("project/lib/a.ts", "project/lib/util/b.ts", false)
+            .await
+            .unwrap();
+        assert_eq!(
+            client.read_file("project/lib/util/b.ts").await.unwrap(),
+            "a"
+        );
+
+        let exists = client
+            .copy_path("project/src/a.ts", "project/lib/util/b.ts", false)
+            .await
+            .unwrap_err();
+        assert!(exists.to_string().contains("already exists"));
+        assert!(client
+            .move_path("project/src", "project/src/nested", true)
+            .await
+            .is_err());
+        assert!(client.delete_path("project/lib", false).await.is_err());
+        client.delete_path("project/lib", true).await.unwrap();
+        assert!(client.read_file("project/lib/util/b.ts").await.is_err());
+        assert!(client.delete_path("/", true).await.is_err());
+        assert!(client.delete_path("../x", true).await.is_err());
+        client.kill_sandbox().await.unwrap();
+    }
+
     #[tokio::test]
     async fn run_command_captures_output() {
         let client = LocalProcessSandboxClient::new();
@@ -648,6 +1094,67 @@ mod tests {
         client.kill_sandbox().await.unwrap();
     }
 
+    #[tokio::test]
+    async fn pty_commands_see_a_terminal() {
+        if !on_path("script") {
+            return;
+        }
+        let client = LocalProcessSandboxClient::new();
+        client.create_sandbox(false, "", 30_000).await.unwrap();
+        let command = "test -t 1 && printf '\\033[32mtty\\033[0m\\n'; exit 3";
+
+        let out = client
+            .run_command_in_pty(command, "", 10_000)
+            .await
+            .unwrap();
+        assert_eq!((out.stdout.as_str(), out.exit_code), ("tty\n", 3));
+        let out = client.run_command(command, "", 10_000).await.unwrap();
+        assert_eq!((out.stdout.as_str(), out.exit_code), ("", 3));
+
+        client.kill_sandbox().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn shell_sessions_keep_their_state() {
+        let client = LocalProcessSandboxClient::new();
+        client.create_sandbox(false, "", 30_000).await.unwrap();
+
+        let out = client
+            .run_in_session("dev", "mkdir -p project && cd project && FOO=bar", 10_000)
+            .await
+            .unwrap();
+        assert_eq!((out.output.exit_code, out.cwd.as_str()), (0, "project"));
+        let out = client
+            .run_in_session("dev", "echo $FOO; pwd", 10_000)
+            .await
+            .unwrap();
+        assert!(out.output.stdout.starts_with("bar\n"));
+        assert!(out.output.stdout.trim_end().ends_with("/project"));
+
+        // Other sessions start fresh in the sandbox root.
+        let out = client
+            .run_in_session("other", "echo \"[$FOO]\"", 10_000)
+            .await
+            .unwrap();
+        assert_eq!(
+            (out.output.stdout.as_str(), out.cwd.as_str()),
+            ("[]\n", ".")
+        );
+
+        let names: Vec<String> = client
+            .shell_sessions()
+            .await
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        assert_eq!(names, ["dev", "other"]);
+        assert!(client.close_session("other").await.unwrap());
+        assert!(!client.close_session("other").await.unwrap());
+
+        client.kill_sandbox().await.unwrap();
+        assert!(client.shell_sessions().await.is_empty());
+    }
+
     #[tokio::test]
     async fn list_files_returns_entries() {
         let client = LocalProcessSandboxClient::new();
@@ -660,6 +1167,24 @@ mod tests {
         client.kill_sandbox().await.unwrap();
     }
 
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn processes_lists_only_sandbox_processes() {
+        let client = LocalProcessSandboxClient::new();
+        client.create_sandbox(false, "", 10_000).await.unwrap();
+        client
+            .run_command("sleep 7 >/dev/null 2>&1 &", ".", 10_000)
+            .await
+            .unwrap();
+
+        let processes = client.processes().await.unwrap();
+        let sleep = processes.iter().find(|p| p.command == "sleep 7");
+        assert!(sleep.is_some(), "{processes:?}");
+        assert!(!processes.iter().any(|p| p.pid == std::process::id()));
+
+        client.kill_sandbox().await.unwrap();
+    }
+
     #[tokio::test]
     async fn get_preview_url_returns_localhost() {
         let client = LocalProcessSandboxClient::new();
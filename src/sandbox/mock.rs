@@ -0,0 +1,443 @@
+//! In-memory [`SandboxClient`] for hermetic tests.
+//!
+//! Files live in a map keyed by sandbox-relative path, and commands never run:
+//! each one answers with the output scripted for it (see
+//! [`MockSandboxClient::on_command`]), or exits 0 with no output. Every command
+//! is recorded so tests can assert what the agent ran. Available to unit tests
+//! and, with the `test-utils` feature, to downstream crates.
+
+use super::{CommandOutput, PackageManager, SandboxClient};
+use crate::error::ZerobuildError;
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use std::collections::{BTreeMap, HashMap};
+
+/// Sandbox ID reported by [`MockSandboxClient`].
+pub const MOCK_SANDBOX_ID: &str = "mock-sandbox";
+
+/// A command run in a [`MockSandboxClient`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedCommand {
+    pub command: String,
+    pub workdir: String,
+}
+
+struct ScriptedCommand {
+    pattern: String,
+    stdout: String,
+    stderr: String,
+    exit_code: i64,
+}
+
+/// In-memory sandbox with scripted command outputs.
+#[derive(Default)]
+pub struct MockSandboxClient {
+    id: Mutex<Option<String>>,
+    expired: Mutex<bool>,
+    outage: Mutex<Option<ZerobuildError>>,
+    files: Mutex<BTreeMap<String, String>>,
+    scripts: Mutex<Vec<ScriptedCommand>>,
+    commands: Mutex<Vec<RecordedCommand>>,
+    package_manager: Mutex<PackageManager>,
+}
+
+/// Sandbox-relative form of `path`: no leading `/`, `.` or empty segments.
+fn normalize(path: &str) -> anyhow::Result<String> {
+    let mut parts = Vec::new();
+    for part in path.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => anyhow::bail!("Path traversal rejected: '{path}' contains '..' components"),
+            part => parts.push(part),
+        }
+    }
+    Ok(parts.join("/"))
+}
+
+/// Whether `path` is `dir` or lies below it.
+fn is_within(path: &str, dir: &str) -> bool {
+    dir.is_empty() || path == dir || path.starts_with(&format!("{dir}/"))
+}
+
+impl MockSandboxClient {
+    /// Mock with no active sandbox, no files and no scripted commands.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer commands containing `pattern` with `stdout`, `stderr` and
+    /// `exit_code`. Later scripts win when several match.
+    pub fn on_command(&self, pattern: &str, stdout: &str, stderr: &str, exit_code: i64) {
+        self.scripts.lock().push(ScriptedCommand {
+            pattern: pattern.to_string(),
+            stdout: stdout.to_string(),
+            stderr: stderr.to_string(),
+            exit_code,
+        });
+    }
+
+    /// Add a file without going through the sandbox API (and without an
+    /// active sandbox).
+    pub fn with_file(self, path: &str, content: &str) -> Self {
+        let path = normalize(path).expect("mock file paths must stay inside the sandbox");
+        self.files.lock().insert(path, content.to_string());
+        self
+    }
+
+    /// Commands run so far, oldest first.
+    pub fn commands(&self) -> Vec<RecordedCommand> {
+        self.commands.lock().clone()
+    }
+
+    /// Content of every file, keyed by sandbox-relative path.
+    pub fn files(&self) -> BTreeMap<String, String> {
+        self.files.lock().clone()
+    }
+
+    /// Make the active sandbox behave like one that expired: its files are
+    /// gone and every operation fails with
+    /// [`ZerobuildError::SandboxExpired`] until the next `create_sandbox`.
+    pub fn expire(&self) {
+        *self.expired.lock() = true;
+        self.files.lock().clear();
+    }
+
+    /// Make every operation, `create_sandbox` included, fail with `error`
+    /// the way a provider outage would, until called with `None`.
+    pub fn set_outage(&self, error: Option<ZerobuildError>) {
+        *self.outage.lock() = error;
+    }
+
+    fn check_outage(&self) -> anyhow::Result<()> {
+        match self.outage.lock().clone() {
+            Some(error) => Err(error.into()),
+            None => Ok(()),
+        }
+    }
+
+    fn require_sandbox(&self) -> anyhow::Result<()> {
+        self.check_outage()?;
+        if self.id.lock().is_none() {
+            return Err(ZerobuildError::SandboxMissing.into());
+        }
+        if *self.expired.lock() {
+            return Err(ZerobuildError::SandboxExpired(MOCK_SANDBOX_ID.to_string()).into());
+        }
+        Ok(())
+    }
+
+    fn is_dir(files: &BTreeMap<String, String>, path: &str) -> bool {
+        path.is_empty()
+            || files
+                .keys()
+                .any(|file| file.starts_with(&format!("{path}/")))
+    }
+
+    /// Files under `from` and where they go under `to`, after the same checks
+    /// as the local sandbox.
+    fn transfer_plan(
+        &self,
+        from: &str,
+        to: &str,
+        overwrite: bool,
+    ) -> anyhow::Result<Vec<(String, String)>> {
+        self.require_sandbox()?;
+        let source = normalize(from)?;
+        let dest = normalize(to)?;
+        if source.is_empty() || dest.is_empty() {
+            anyhow::bail!("Cannot move or copy the sandbox root");
+        }
+        if is_within(&dest, &source) {
+            anyhow::bail!("Cannot move or copy '{from}' into itself ('{to}')");
+        }
+        let mut files = self.files.lock();
+        let plan: Vec<(String, String)> = files
+            .keys()
+            .filter(|path| is_within(path, &source))
+            .map(|path| (path.clone(), format!("{dest}{}", &path[source.len()..])))
+            .collect();
+        if plan.is_empty() {
+            anyhow::bail!("'{from}' does not exist");
+        }
+        if files.contains_key(&dest) || Self::is_dir(&files, &dest) {
+            if !overwrite {
+                anyhow::bail!("'{to}' already exists; pass overwrite=true to replace it");
+            }
+            files.retain(|path, _| !is_within(path, &dest));
+        }
+        Ok(plan)
+    }
+}
+
+#[async_trait]
+impl SandboxClient for MockSandboxClient {
+    async fn create_sandbox(
+        &self,
+        reset: bool,
+        _template: &str,
+        _timeout_ms: u64,
+    ) -> anyhow::Result<String> {
+        self.check_outage()?;
+        if reset {
+            self.files.lock().clear();
+        }
+        *self.expired.lock() = false;
+        *self.id.lock() = Some(MOCK_SANDBOX_ID.to_string());
+        Ok(MOCK_SANDBOX_ID.to_string())
+    }
+
+    async fn kill_sandbox(&self) -> anyhow::Result<String> {
+        if self.id.lock().take().is_none() {
+            return Ok("No active mock sandbox to kill.".to_string());
+        }
+        self.files.lock().clear();
+        Ok(format!("Mock sandbox {MOCK_SANDBOX_ID} removed."))
+    }
+
+    async fn run_command(
+        &self,
+        command: &str,
+        workdir: &str,
+        _timeout_ms: u64,
+    ) -> anyhow::Result<CommandOutput> {
+        self.require_sandbox()?;
+        self.commands.lock().push(RecordedCommand {
+            command: command.to_string(),
+            workdir: workdir.to_string(),
+        });
+        let scripts = self.scripts.lock();
+        Ok(scripts
+            .iter()
+            .rev()
+            .find(|script| command.contains(&script.pattern))
+            .map_or_else(
+                || CommandOutput {
+                    stdout: String::new(),
+                    stderr: String::new(),
+                    exit_code: 0,
+                },
+                |script| CommandOutput {
+                    stdout: script.stdout.clone(),
+                    stderr: script.stderr.clone(),
+                    exit_code: script.exit_code,
+                },
+            ))
+    }
+
+    async fn write_file(&self, path: &str, content: &str) -> anyhow::Result<()> {
+        self.require_sandbox()?;
+        let path = normalize(path)?;
+        if path.is_empty() {
+            anyhow::bail!("Failed to write file '': path is the sandbox root");
+        }
+        self.files.lock().insert(path, content.to_string());
+        Ok(())
+    }
+
+    async fn move_path(&self, from: &str, to: &str, overwrite: bool) -> anyhow::Result<()> {
+        let plan = self.transfer_plan(from, to, overwrite)?;
+        let mut files = self.files.lock();
+        for (source, dest) in plan {
+            if let Some(content) = files.remove(&source) {
+                files.insert(dest, content);
+            }
+        }
+        Ok(())
+    }
+
+    async fn copy_path(&self, from: &str, to: &str, overwrite: bool) -> anyhow::Result<()> {
+        let plan = self.transfer_plan(from, to, overwrite)?;
+        let mut files = self.files.lock();
+        for (source, dest) in plan {
+            if let Some(content) = files.get(&source).cloned() {
+                files.insert(dest, content);
+            }
+        }
+        Ok(())
+    }
+
+    async fn delete_path(&self, path: &str, recursive: bool) -> anyhow::Result<()> {
+        self.require_sandbox()?;
+        let target = normalize(path)?;
+        if target.is_empty() {
+            anyhow::bail!("Refusing to delete the sandbox root");
+        }
+        let mut files = self.files.lock();
+        if files.remove(&target).is_some() {
+            return Ok(());
+        }
+        if !Self::is_dir(&files, &target) {
+            anyhow::bail!("'{path}' does not exist");
+        }
+        if !recursive {
+            anyhow::bail!(
+                "Failed to delete directory '{path}': directory not empty (pass recursive=true \
+                 to delete a non-empty directory)"
+            );
+        }
+        files.retain(|file, _| !is_within(file, &target));
+        Ok(())
+    }
+
+    async fn read_file(&self, path: &str) -> anyhow::Result<String> {
+        self.require_sandbox()?;
+        let target = normalize(path)?;
+        self.files
+            .lock()
+            .get(&target)
+            .cloned()
+            .ok_or_else(|| ZerobuildError::FileNotFound(path.to_string()).into())
+    }
+
+    async fn list_files(&self, path: &str) -> anyhow::Result<String> {
+        self.require_sandbox()?;
+        let dir = normalize(path)?;
+        let files = self.files.lock();
+        if !Self::is_dir(&files, &dir) {
+            anyhow::bail!("Failed to list directory '{path}': No such directory");
+        }
+        let prefix = if dir.is_empty() {
+            String::new()
+        } else {
+            format!("{dir}/")
+        };
+        let mut entries: Vec<String> = files
+            .keys()
+            .filter_map(|file| file.strip_prefix(&prefix))
+            .map(|rest| match rest.split_once('/') {
+                Some((subdir, _)) => format!("dir\t{subdir}"),
+                None => format!("file\t{rest}"),
+            })
+            .collect();
+        entries.sort();
+        entries.dedup();
+        Ok(entries.join("\n"))
+    }
+
+    async fn get_preview_url(&self, port: u16) -> anyhow::Result<String> {
+        Ok(format!("http://localhost:{port}"))
+    }
+
+    async fn collect_snapshot_files(
+        &self,
+        workdir: &str,
+    ) -> anyhow::Result<HashMap<String, String>> {
+        self.require_sandbox()?;
+        let dir = normalize(workdir)?;
+        let prefix = if dir.is_empty() {
+            String::new()
+        } else {
+            format!("{dir}/")
+        };
+        Ok(self
+            .files
+            .lock()
+            .iter()
+            .filter_map(|(path, content)| {
+                let relative = path.strip_prefix(&prefix)?;
+                let skipped = relative
+                    .split('/')
+                    .any(|part| super::local::SKIP_DIRS.contains(&part));
+                (!skipped).then(|| (relative.to_string(), content.clone()))
+            })
+            .collect())
+    }
+
+    fn current_id(&self) -> Option<String> {
+        self.id.lock().clone()
+    }
+
+    fn set_id(&self, id: String) {
+        *self.id.lock() = Some(id);
+    }
+
+    fn clear_id(&self) {
+        *self.id.lock() = None;
+    }
+
+    fn package_manager(&self) -> PackageManager {
+        *self.package_manager.lock()
+    }
+
+    fn set_package_manager(&self, pm: PackageManager) {
+        *self.package_manager.lock() = pm;
+    }
+
+    /// Keeps the configured manager instead of probing the host's `PATH`.
+    async fn detect_package_manager(&self) -> PackageManager {
+        self.package_manager()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn files_move_copy_delete_and_list() {
+        let sandbox = MockSandboxClient::new();
+        assert!(sandbox.write_file("a.txt", "x").await.is_err());
+        sandbox.create_sandbox(false, "", 0).await.unwrap();
+
+        sandbox
+            .write_file("/project/src/app.ts", "app")
+            .await
+            .unwrap();
+        sandbox
+            .write_file("project/package.json", "{}")
+            .await
+            .unwrap();
+        assert_eq!(
+            sandbox.list_files("project").await.unwrap(),
+            "dir\tsrc\nfile\tpackage.json"
+        );
+        assert!(sandbox.read_file("../etc/passwd").await.is_err());
+
+        sandbox
+            .copy_path("project/src", "project/lib", false)
+            .await
+            .unwrap();
+        sandbox
+            .move_path("project/package.json", "project/src/app.ts", false)
+            .await
+            .unwrap_err();
+        sandbox
+            .move_path("project/lib", "project/out", false)
+            .await
+            .unwrap();
+        assert_eq!(
+            sandbox.read_file("project/out/app.ts").await.unwrap(),
+            "app"
+        );
+
+        sandbox.delete_path("project/src", false).await.unwrap_err();
+        sandbox.delete_path("project/src", true).await.unwrap();
+        let snapshot = sandbox.collect_snapshot_files("project").await.unwrap();
+        let mut paths: Vec<_> = snapshot.keys().cloned().collect();
+        paths.sort();
+        assert_eq!(paths, ["out/app.ts", "package.json"]);
+    }
+
+    #[tokio::test]
+    async fn commands_answer_with_scripts_and_are_recorded() {
+        let sandbox = MockSandboxClient::new();
+        sandbox.create_sandbox(false, "", 0).await.unwrap();
+        sandbox.on_command("npm run build", "", "Type error", 1);
+        sandbox.on_command("npm run build", "built", "", 0);
+
+        let output = sandbox
+            .run_command("npm run build", "project", 1000)
+            .await
+            .unwrap();
+        assert_eq!((output.stdout.as_str(), output.exit_code), ("built", 0));
+        let output = sandbox.run_command("ls", "project", 1000).await.unwrap();
+        assert_eq!(output.exit_code, 0);
+        assert_eq!(
+            sandbox.commands()[0],
+            RecordedCommand {
+                command: "npm run build".into(),
+                workdir: "project".into(),
+            }
+        );
+    }
+}
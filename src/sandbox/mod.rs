@@ -1,28 +1,63 @@
 //! Sandbox abstraction layer for ZeroBuild.
 //!
 //! Defines the [`SandboxClient`] trait and [`CommandOutput`] type that all
-//! sandbox providers must implement. Currently one provider exists:
+//! sandbox providers must implement. Providers:
 //!
 //! - [`local::LocalProcessSandboxClient`] — native process sandbox (no external deps)
+//! - [`kubernetes::K8sSandboxClient`] — one pod per sandbox on a Kubernetes cluster
+//! - [`firecracker::FirecrackerSandboxClient`] — one microVM per sandbox, driven
+//!   through the [`guest_agent`]
+//! - [`fly::FlySandboxClient`] — one Fly.io Machine per sandbox, with public previews
+//!
+//! [`failover::FailoverSandboxClient`] puts several providers behind one
+//! client and moves to the next when the active one keeps failing.
+//! [`http_capture::HttpCapture`] records the requests made through preview
+//! URLs.
+//!
+//! Tests use `mock::MockSandboxClient` (in-memory, scripted command outputs),
+//! which downstream crates get with the `test-utils` feature.
 //!
 //! The factory in [`crate::tools::mod`] selects the provider at startup.
 
+pub mod ansi;
+pub mod devcontainer;
+pub mod docs;
+pub mod failover;
+pub mod firecracker;
+pub mod fly;
+pub mod guest_agent;
+pub mod http_capture;
+pub mod install_progress;
+pub mod kubernetes;
 pub mod local;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod mock;
+pub mod monorepo;
+pub mod package_manager;
+pub mod procfs;
+pub mod project_config;
+pub mod shell_ops;
+pub mod shell_session;
+pub mod stack;
+pub mod templates;
 
 use async_trait::async_trait;
 use std::collections::HashMap;
 use std::fmt;
 
 /// Package manager types supported by the sandbox, ordered by priority.
-/// Priority: pnpm > yarn > npm
+/// Priority: pnpm > yarn > bun > npm
 ///
-/// Note: Default is Npm as the safe fallback, but detection prioritizes pnpm/yarn.
+/// Note: Default is Npm as the safe fallback, but detection prioritizes pnpm/yarn/bun.
+/// A project's own lockfile takes precedence, see [`package_manager`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum PackageManager {
     /// pnpm - fastest, most disk efficient (highest priority)
     Pnpm,
     /// yarn - good performance, widely adopted
     Yarn,
+    /// bun - fast all-in-one runtime and package manager
+    Bun,
     /// npm - default fallback, always available
     #[default]
     Npm,
@@ -34,24 +69,69 @@ impl PackageManager {
         match self {
             Self::Pnpm => "pnpm",
             Self::Yarn => "yarn",
+            Self::Bun => "bun",
             Self::Npm => "npm",
         }
     }
 
+    /// Parse a manager name as used in `package.json` `packageManager`
+    /// (`pnpm@9.1.0`) or by the agent.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.split('@').next().unwrap_or("").trim() {
+            "pnpm" => Some(Self::Pnpm),
+            "yarn" => Some(Self::Yarn),
+            "bun" => Some(Self::Bun),
+            "npm" => Some(Self::Npm),
+            _ => None,
+        }
+    }
+
+    /// Lockfile names this manager writes, newest format first.
+    pub fn lockfiles(&self) -> &'static [&'static str] {
+        match self {
+            Self::Pnpm => &["pnpm-lock.yaml"],
+            Self::Yarn => &["yarn.lock"],
+            Self::Bun => &["bun.lock", "bun.lockb"],
+            Self::Npm => &["package-lock.json", "npm-shrinkwrap.json"],
+        }
+    }
+
+    /// Install exactly what the lockfile pins, failing instead of updating it.
+    pub fn frozen_install_cmd(&self) -> &'static str {
+        match self {
+            Self::Pnpm => "pnpm install --frozen-lockfile",
+            Self::Yarn => "yarn install --frozen-lockfile",
+            Self::Bun => "bun install --frozen-lockfile",
+            Self::Npm => "npm ci",
+        }
+    }
+
     /// Get the install command for this package manager.
     pub fn install_cmd(&self) -> &'static str {
         match self {
             Self::Pnpm => "pnpm install",
             Self::Yarn => "yarn install",
+            Self::Bun => "bun install",
             Self::Npm => "npm install",
         }
     }
 
+    /// Update dependencies to the newest versions their ranges allow.
+    pub fn update_cmd(&self) -> &'static str {
+        match self {
+            Self::Pnpm => "pnpm update",
+            Self::Yarn => "yarn upgrade",
+            Self::Bun => "bun update",
+            Self::Npm => "npm update",
+        }
+    }
+
     /// Get the add command prefix for this package manager.
     pub fn add_cmd(&self) -> &'static str {
         match self {
             Self::Pnpm => "pnpm add",
             Self::Yarn => "yarn add",
+            Self::Bun => "bun add",
             Self::Npm => "npm install",
         }
     }
@@ -61,6 +141,7 @@ impl PackageManager {
         match self {
             Self::Pnpm => "pnpm",
             Self::Yarn => "yarn",
+            Self::Bun => "bun run",
             Self::Npm => "npm run",
         }
     }
@@ -76,10 +157,19 @@ impl PackageManager {
         if Self::is_available("yarn").await {
             return Self::Yarn;
         }
+        // Then bun
+        if Self::is_available("bun").await {
+            return Self::Bun;
+        }
         // Fallback to npm
         Self::Npm
     }
 
+    /// Whether this manager's binary is on PATH.
+    pub async fn is_installed(&self) -> bool {
+        Self::is_available(self.as_str()).await
+    }
+
     /// Check if a command is available in PATH.
     async fn is_available(cmd: &str) -> bool {
         match tokio::process::Command::new("which")
@@ -99,6 +189,27 @@ impl fmt::Display for PackageManager {
     }
 }
 
+/// Sandbox providers `[zerobuild.sandbox] providers` may name.
+pub const PROVIDERS: &[&str] = &["local", "kubernetes", "firecracker", "fly"];
+
+/// Project templates a sandbox can start from, as `(name, description)`.
+pub const PROJECT_TEMPLATES: &[(&str, &str)] = &[
+    ("nextjs", "Next.js + Tailwind CSS web app"),
+    ("vite-react", "Vite + React single-page app"),
+    ("static", "Plain HTML, CSS and JavaScript site"),
+    ("node-api", "Node.js HTTP API"),
+    ("fastapi", "Python FastAPI service"),
+    ("rust", "Rust binary crate"),
+];
+
+/// Description of the template called `name`, if it exists.
+pub fn project_template(name: &str) -> Option<&'static str> {
+    PROJECT_TEMPLATES
+        .iter()
+        .find(|(template, _)| *template == name)
+        .map(|(_, description)| *description)
+}
+
 /// Output from a command executed inside a sandbox.
 pub struct CommandOutput {
     pub stdout: String,
@@ -106,6 +217,63 @@ pub struct CommandOutput {
     pub exit_code: i64,
 }
 
+impl CommandOutput {
+    /// This output as plain text: escape sequences, progress bar redraws
+    /// and `\r\n` line endings cleaned up by [`ansi::normalize`].
+    #[must_use]
+    pub fn normalized(self) -> Self {
+        Self {
+            stdout: ansi::normalize(&self.stdout),
+            stderr: ansi::normalize(&self.stderr),
+            exit_code: self.exit_code,
+        }
+    }
+}
+
+/// Output of a command run in a shell session.
+pub struct SessionOutput {
+    /// The command's output; stderr arrives merged into `stdout`.
+    pub output: CommandOutput,
+    /// The session's working directory afterwards, relative to the sandbox
+    /// root (`.` for the root itself).
+    pub cwd: String,
+}
+
+/// An open shell session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShellSessionInfo {
+    pub name: String,
+    /// Working directory, relative to the sandbox root; `None` while a
+    /// command runs.
+    pub cwd: Option<String>,
+}
+
+/// A process running in a sandbox.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SandboxProcess {
+    pub pid: u32,
+    pub ppid: u32,
+    /// State letter of `ps` (`R` running, `S` sleeping, `Z` zombie, ...).
+    pub state: char,
+    /// Seconds since the process started.
+    pub elapsed_secs: u64,
+    /// Resident memory in KiB.
+    pub rss_kib: u64,
+    /// Command line, or the `[name]` of processes without one.
+    pub command: String,
+}
+
+/// A TCP socket listening in a sandbox.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListeningPort {
+    /// Address it is bound to, e.g. `0.0.0.0`, `127.0.0.1` or `::`.
+    pub address: String,
+    pub port: u16,
+    /// Owning process, when it could be told.
+    pub pid: Option<u32>,
+    pub command: Option<String>,
+}
+
 /// Provider-agnostic sandbox interface.
 ///
 /// All methods are async and require an active sandbox (created via
@@ -121,6 +289,25 @@ pub trait SandboxClient: Send + Sync {
         timeout_ms: u64,
     ) -> anyhow::Result<String>;
 
+    /// Replace the active sandbox with a new one running the container
+    /// `image` instead of the template's, e.g. the image a repository's
+    /// dev container names. Returns the sandbox ID.
+    /// Default impl bails — only the image-based providers (`kubernetes`,
+    /// `fly`) implement this.
+    async fn create_sandbox_from_image(
+        &self,
+        _image: &str,
+        _timeout_ms: u64,
+    ) -> anyhow::Result<String> {
+        anyhow::bail!("Container images not supported by this sandbox provider")
+    }
+
+    /// Whether [`create_sandbox_from_image`](Self::create_sandbox_from_image)
+    /// is implemented.
+    fn runs_images(&self) -> bool {
+        false
+    }
+
     /// Terminate the active sandbox. Returns a status message.
     async fn kill_sandbox(&self) -> anyhow::Result<String>;
 
@@ -132,9 +319,63 @@ pub trait SandboxClient: Send + Sync {
         timeout_ms: u64,
     ) -> anyhow::Result<CommandOutput>;
 
+    /// Like [`run_command`](Self::run_command), but sends each line of output
+    /// to `lines` as the command produces it, for progress reporting. The
+    /// default runs the command and sends its output afterwards.
+    async fn run_command_streaming(
+        &self,
+        command: &str,
+        workdir: &str,
+        timeout_ms: u64,
+        lines: tokio::sync::mpsc::UnboundedSender<String>,
+    ) -> anyhow::Result<CommandOutput> {
+        let output = self.run_command(command, workdir, timeout_ms).await?;
+        for line in output.stdout.lines().chain(output.stderr.lines()) {
+            let _ = lines.send(line.to_string());
+        }
+        Ok(output)
+    }
+
+    /// Like [`run_command`](Self::run_command), but with the command's
+    /// output attached to a pseudo-terminal, for tools that behave
+    /// differently without one (dev servers, interactive installers).
+    /// Stderr arrives merged into `stdout`. The default runs the command
+    /// with pipes.
+    async fn run_command_in_pty(
+        &self,
+        command: &str,
+        workdir: &str,
+        timeout_ms: u64,
+    ) -> anyhow::Result<CommandOutput> {
+        self.run_command(command, workdir, timeout_ms).await
+    }
+
     /// Write content to a file path inside the sandbox.
     async fn write_file(&self, path: &str, content: &str) -> anyhow::Result<()>;
 
+    /// Write several `(path, content)` files in one round trip, returning one
+    /// result per file in input order. The default writes them one by one;
+    /// remote providers override it with a single batched upload.
+    async fn write_files(&self, files: &[(String, String)]) -> Vec<anyhow::Result<()>> {
+        let mut results = Vec::with_capacity(files.len());
+        for (path, content) in files {
+            results.push(self.write_file(path, content).await);
+        }
+        results
+    }
+
+    /// Move or rename the file or directory `from` to `to`, creating parent
+    /// directories. Fails if `to` exists, unless `overwrite`.
+    async fn move_path(&self, from: &str, to: &str, overwrite: bool) -> anyhow::Result<()>;
+
+    /// Copy the file or directory tree `from` to `to`, creating parent
+    /// directories. Fails if `to` exists, unless `overwrite`.
+    async fn copy_path(&self, from: &str, to: &str, overwrite: bool) -> anyhow::Result<()>;
+
+    /// Delete a file, or a directory (non-empty ones only when `recursive`).
+    /// The sandbox root itself is never deleted.
+    async fn delete_path(&self, path: &str, recursive: bool) -> anyhow::Result<()>;
+
     /// Read a file from the sandbox and return its content as a UTF-8 string.
     async fn read_file(&self, path: &str) -> anyhow::Result<String>;
 
@@ -151,6 +392,43 @@ pub trait SandboxClient: Send + Sync {
         anyhow::bail!("Public tunnel not supported by this sandbox provider")
     }
 
+    /// Run `command` in the persistent shell `session`, starting it in the
+    /// sandbox root on first use. Unlike [`run_command`](Self::run_command),
+    /// the working directory, exported variables and shell functions carry
+    /// over to the next command of the session. A session whose command
+    /// times out is reset.
+    /// Default impl bails — only LocalProcessSandboxClient implements this.
+    async fn run_in_session(
+        &self,
+        _session: &str,
+        _command: &str,
+        _timeout_ms: u64,
+    ) -> anyhow::Result<SessionOutput> {
+        anyhow::bail!("Shell sessions not supported by this sandbox provider")
+    }
+
+    /// Close the shell `session`. Returns whether it was open.
+    async fn close_session(&self, _session: &str) -> anyhow::Result<bool> {
+        Ok(false)
+    }
+
+    /// Open shell sessions, by name.
+    async fn shell_sessions(&self) -> Vec<ShellSessionInfo> {
+        Vec::new()
+    }
+
+    /// Processes running in the sandbox, by pid. The default reads `/proc`
+    /// with [`procfs::read`].
+    async fn processes(&self) -> anyhow::Result<Vec<SandboxProcess>> {
+        Ok(procfs::read(self, None).await?.processes)
+    }
+
+    /// TCP sockets listening in the sandbox with their owning process, by
+    /// port. The default reads `/proc` with [`procfs::read`].
+    async fn listening_ports(&self) -> anyhow::Result<Vec<ListeningPort>> {
+        Ok(procfs::read(self, None).await?.ports)
+    }
+
     /// Walk `workdir` (skipping build artifacts) and return a map of
     /// `path → content` for all source files.
     async fn collect_snapshot_files(
@@ -170,7 +448,7 @@ pub trait SandboxClient: Send + Sync {
     /// Return the current ID or an error message suitable for `ToolResult`.
     fn require_id(&self) -> Result<String, String> {
         self.current_id()
-            .ok_or_else(|| "No active sandbox. Call sandbox_create first.".to_string())
+            .ok_or_else(|| crate::error::ZerobuildError::SandboxMissing.to_string())
     }
 
     /// Get the detected package manager for this sandbox.
@@ -0,0 +1,396 @@
+//! Monorepo workspace detection: `pnpm-workspace.yaml`, `package.json`
+//! workspaces, `turbo.json` and Cargo `[workspace]` members.
+//!
+//! A snapshot can hold several apps (`apps/web`, `apps/api`, a shared
+//! `packages/ui`). The member globs of the workspace files are expanded
+//! against the sandbox directories, and every member with a manifest is an
+//! app the sandbox and deploy tools can target by name or path with their
+//! `app` argument. `[apps.<name>]` of `.zerobuild.toml` adds an app's own
+//! commands and deploy target, or an app outside the workspace globs.
+
+use super::project_config::{ProjectConfig, PROJECT_DIR};
+use super::{stack, SandboxClient};
+use std::collections::BTreeSet;
+
+/// Files that declare a workspace, with the tool each one belongs to.
+pub const WORKSPACE_FILES: [(&str, &str); 4] = [
+    ("pnpm-workspace.yaml", "pnpm"),
+    ("package.json", "npm/yarn workspaces"),
+    ("turbo.json", "turbo"),
+    ("Cargo.toml", "cargo"),
+];
+
+/// Directory levels searched below the literal part of a `**` glob.
+const MAX_GLOB_DEPTH: usize = 3;
+
+/// Directories never searched for members.
+const SKIPPED_DIRS: [&str; 4] = ["node_modules", "target", ".git", "dist"];
+
+/// A workspace member with a manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceApp {
+    /// Manifest `name`, else the directory name.
+    pub name: String,
+    /// Directory relative to [`PROJECT_DIR`].
+    pub path: String,
+    pub manifest: &'static str,
+}
+
+/// A detected monorepo.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Workspace {
+    /// Tools of [`WORKSPACE_FILES`] found in the project root.
+    pub tools: Vec<&'static str>,
+    /// Member globs; `!` marks an exclusion.
+    pub patterns: Vec<String>,
+    /// Members sorted by path.
+    pub apps: Vec<WorkspaceApp>,
+}
+
+impl Workspace {
+    /// The member named `name`, or the one in directory `name`.
+    pub fn app(&self, name: &str) -> Option<&WorkspaceApp> {
+        let path = name.trim().trim_start_matches("./").trim_matches('/');
+        self.apps
+            .iter()
+            .find(|app| app.name == name)
+            .or_else(|| self.apps.iter().find(|app| app.path == path))
+    }
+}
+
+/// An app resolved for a tool call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedApp {
+    pub name: String,
+    /// Directory relative to [`PROJECT_DIR`].
+    pub path: String,
+    /// Project settings with the app as workdir, see [`ProjectConfig::for_app`].
+    pub project: ProjectConfig,
+}
+
+/// Member globs declared by workspace file `file`; `None` when the file
+/// doesn't declare a workspace. `turbo.json` has no members of its own (it
+/// runs the package manager's workspace), so it yields an empty list.
+pub fn member_patterns(file: &str, text: &str) -> Option<Vec<String>> {
+    match file {
+        "pnpm-workspace.yaml" => Some(pnpm_packages(text)),
+        "package.json" => {
+            let json: serde_json::Value = serde_json::from_str(text).ok()?;
+            let workspaces = &json["workspaces"];
+            let list = workspaces
+                .as_array()
+                .or_else(|| workspaces["packages"].as_array())?;
+            Some(
+                list.iter()
+                    .filter_map(|v| v.as_str().map(str::to_string))
+                    .collect(),
+            )
+        }
+        "turbo.json" => serde_json::from_str::<serde_json::Value>(text)
+            .ok()
+            .map(|_| Vec::new()),
+        "Cargo.toml" => {
+            let table: toml::Table = toml::from_str(text).ok()?;
+            let workspace = table.get("workspace")?;
+            let list = |key: &str| {
+                workspace
+                    .get(key)
+                    .and_then(toml::Value::as_array)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(toml::Value::as_str)
+                    .map(str::to_string)
+                    .collect::<Vec<_>>()
+            };
+            let excluded = list("exclude").into_iter().map(|p| format!("!{p}"));
+            Some(list("members").into_iter().chain(excluded).collect())
+        }
+        _ => None,
+    }
+}
+
+/// Entries of the `packages:` list in `pnpm-workspace.yaml`.
+fn pnpm_packages(text: &str) -> Vec<String> {
+    let mut packages = Vec::new();
+    let mut in_packages = false;
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if !line.starts_with([' ', '\t', '-']) {
+            in_packages = trimmed.starts_with("packages:");
+            continue;
+        }
+        if let Some(item) = trimmed.strip_prefix('-').filter(|_| in_packages) {
+            let item = item.split(" #").next().unwrap_or_default().trim();
+            let item = item.trim_matches(|c| c == '"' || c == '\'');
+            if !item.is_empty() {
+                packages.push(item.to_string());
+            }
+        }
+    }
+    packages
+}
+
+/// `pattern` without leading `./` and surrounding `/`.
+fn normalize(pattern: &str) -> &str {
+    let mut pattern = pattern.trim().trim_matches('/');
+    while let Some(rest) = pattern.strip_prefix("./") {
+        pattern = rest;
+    }
+    pattern
+}
+
+/// Whether directory `path` matches member glob `pattern`.
+fn matches(pattern: &str, path: &str) -> bool {
+    let Ok(glob) = glob::Pattern::new(pattern) else {
+        return false;
+    };
+    glob.matches_with(
+        path,
+        glob::MatchOptions {
+            require_literal_separator: !pattern.contains("**"),
+            ..glob::MatchOptions::default()
+        },
+    )
+}
+
+/// Subdirectories of `dir` (relative to the sandbox root).
+async fn subdirs(client: &dyn SandboxClient, dir: &str) -> Vec<String> {
+    let Ok(listing) = client.list_files(dir).await else {
+        return Vec::new();
+    };
+    listing
+        .lines()
+        .filter_map(|line| line.strip_prefix("dir\t"))
+        .filter(|name| !SKIPPED_DIRS.contains(name))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Directories under `root` matching `pattern`, relative to `root`.
+async fn expand(client: &dyn SandboxClient, root: &str, pattern: &str) -> Vec<String> {
+    let parts: Vec<&str> = pattern.split('/').collect();
+    let literal = parts
+        .iter()
+        .take_while(|part| !part.contains(['*', '?', '[']))
+        .count();
+    if literal == parts.len() {
+        return vec![pattern.to_string()];
+    }
+    let max_depth = if pattern.contains("**") {
+        MAX_GLOB_DEPTH
+    } else {
+        parts.len() - literal
+    };
+    let base = parts[..literal].join("/");
+    let mut found = Vec::new();
+    let mut level = vec![base];
+    for _ in 0..max_depth {
+        let mut next = Vec::new();
+        for dir in &level {
+            let full = if dir.is_empty() {
+                root.to_string()
+            } else {
+                format!("{root}/{dir}")
+            };
+            for name in subdirs(client, &full).await {
+                let path = if dir.is_empty() {
+                    name
+                } else {
+                    format!("{dir}/{name}")
+                };
+                if matches(pattern, &path) {
+                    found.push(path.clone());
+                }
+                next.push(path);
+            }
+        }
+        level = next;
+    }
+    found
+}
+
+/// The first manifest in project directory `path`, with its `name`.
+async fn read_manifest(
+    client: &dyn SandboxClient,
+    root: &str,
+    path: &str,
+) -> Option<(&'static str, Option<String>)> {
+    for file in stack::MANIFESTS {
+        if let Ok(text) = client.read_file(&format!("{root}/{path}/{file}")).await {
+            let name = stack::parse_manifest(file, &text).and_then(|m| m.name);
+            return Some((file, name));
+        }
+    }
+    None
+}
+
+/// The workspace of the project in `root` (relative to the sandbox root),
+/// or `None` when it has no workspace files.
+pub async fn detect(client: &dyn SandboxClient, root: &str) -> Option<Workspace> {
+    let root = root.trim_end_matches('/');
+    let mut workspace = Workspace::default();
+    for (file, tool) in WORKSPACE_FILES {
+        let Ok(text) = client.read_file(&format!("{root}/{file}")).await else {
+            continue;
+        };
+        if let Some(patterns) = member_patterns(file, &text) {
+            workspace.tools.push(tool);
+            workspace
+                .patterns
+                .extend(patterns.iter().map(|p| normalize(p).to_string()));
+        }
+    }
+    if workspace.tools.is_empty() {
+        return None;
+    }
+
+    let (excluded, included): (Vec<&String>, Vec<&String>) = workspace
+        .patterns
+        .iter()
+        .partition(|pattern| pattern.starts_with('!'));
+    let mut paths = BTreeSet::new();
+    for pattern in included.into_iter().filter(|p| !p.is_empty()) {
+        paths.extend(expand(client, root, pattern).await);
+    }
+    for path in paths {
+        if excluded
+            .iter()
+            .any(|pattern| matches(normalize(&pattern[1..]), &path))
+        {
+            continue;
+        }
+        if let Some((manifest, name)) = read_manifest(client, root, &path).await {
+            workspace.apps.push(WorkspaceApp {
+                name: name.unwrap_or_else(|| path.rsplit('/').next().unwrap_or(&path).into()),
+                path,
+                manifest,
+            });
+        }
+    }
+    Some(workspace)
+}
+
+/// Resolve the `app` argument of a tool: a `[apps.<name>]` entry of
+/// `project`, else a detected workspace member by name or path. The error
+/// lists the apps there are.
+pub async fn resolve_app(
+    client: &dyn SandboxClient,
+    project: &ProjectConfig,
+    app: &str,
+) -> Result<ResolvedApp, String> {
+    let name = app.trim();
+    if let Some(path) = project.apps.get(name).and_then(|a| a.path.as_deref()) {
+        return Ok(ResolvedApp {
+            name: name.to_string(),
+            path: normalize(path).to_string(),
+            project: project.for_app(path, project.apps.get(name)),
+        });
+    }
+    let workspace = detect(client, PROJECT_DIR).await.unwrap_or_default();
+    let Some(member) = workspace.app(name) else {
+        let mut known: Vec<String> = workspace
+            .apps
+            .iter()
+            .map(|a| format!("{} ({})", a.name, a.path))
+            .collect();
+        known.extend(
+            project
+                .apps
+                .keys()
+                .filter(|key| workspace.app(key).is_none())
+                .cloned(),
+        );
+        return Err(if known.is_empty() {
+            format!("Unknown app '{name}': the project has no workspace apps")
+        } else {
+            format!("Unknown app '{name}'; apps: {}", known.join(", "))
+        });
+    };
+    let settings = project.apps.get(&member.name).or_else(|| {
+        project
+            .apps
+            .iter()
+            .find(|(key, _)| key.as_str() == name || key.as_str() == member.path)
+            .map(|(_, app)| app)
+    });
+    Ok(ResolvedApp {
+        name: member.name.clone(),
+        path: member.path.clone(),
+        project: project.for_app(&member.path, settings),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sandbox::mock::MockSandboxClient;
+    use crate::sandbox::SandboxClient;
+
+    #[test]
+    fn reads_member_globs_of_each_workspace_file() {
+        let pnpm = "packages:\n  - 'apps/*'\n  - \"packages/**\" # shared\n  - '!**/test/**'\ncatalog:\n  - nope\n";
+        assert_eq!(
+            member_patterns("pnpm-workspace.yaml", pnpm).unwrap(),
+            ["apps/*", "packages/**", "!**/test/**"]
+        );
+        assert_eq!(
+            member_patterns(
+                "package.json",
+                r#"{"workspaces": {"packages": ["apps/*"]}}"#
+            ),
+            Some(vec!["apps/*".to_string()])
+        );
+        assert_eq!(member_patterns("package.json", r#"{"name": "web"}"#), None);
+        assert_eq!(member_patterns("turbo.json", "{}"), Some(Vec::new()));
+        assert_eq!(
+            member_patterns(
+                "Cargo.toml",
+                "[workspace]\nmembers = [\"crates/*\"]\nexclude = [\"crates/old\"]\n"
+            )
+            .unwrap(),
+            ["crates/*", "!crates/old"]
+        );
+        assert_eq!(
+            member_patterns("Cargo.toml", "[package]\nname = \"a\"\n"),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn detects_apps_and_resolves_them_by_name_or_path() {
+        let client = MockSandboxClient::new()
+            .with_file("project/pnpm-workspace.yaml", "packages:\n  - apps/*\n")
+            .with_file("project/turbo.json", "{}")
+            .with_file("project/package.json", r#"{"name": "root"}"#)
+            .with_file("project/apps/web/package.json", r#"{"name": "@acme/web"}"#)
+            .with_file("project/apps/api/Cargo.toml", "[package]\nname = \"api\"\n")
+            .with_file("project/apps/notes/README.md", "not an app")
+            .with_file(
+                "project/.zerobuild.toml",
+                "[apps.api]\ndeploy.repo = \"acme/api\"\n",
+            );
+        client.create_sandbox(false, "", 1000).await.unwrap();
+
+        let workspace = detect(&client, PROJECT_DIR).await.unwrap();
+        assert_eq!(workspace.tools, ["pnpm", "turbo"]);
+        let apps: Vec<(&str, &str)> = workspace
+            .apps
+            .iter()
+            .map(|a| (a.name.as_str(), a.path.as_str()))
+            .collect();
+        assert_eq!(apps, [("api", "apps/api"), ("@acme/web", "apps/web")]);
+
+        let project = ProjectConfig::load(&client).await.unwrap().unwrap();
+        let api = resolve_app(&client, &project, "api").await.unwrap();
+        assert_eq!(api.project.workdir(), "project/apps/api");
+        assert_eq!(api.project.deploy.repo.as_deref(), Some("acme/api"));
+        let web = resolve_app(&client, &project, "apps/web/").await.unwrap();
+        assert_eq!(web.name, "@acme/web");
+        assert_eq!(web.project.deploy.repo, None);
+        let err = resolve_app(&client, &project, "docs").await.unwrap_err();
+        assert!(err.contains("api (apps/api)"), "{err}");
+    }
+}
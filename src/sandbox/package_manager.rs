@@ -0,0 +1,342 @@
+//! Per-project package manager detection and lockfile-aware command rewriting.
+//!
+//! A project's own choice wins over whatever is fastest on the machine: the
+//! `packageManager` field of `package.json` first, then the lockfile in the
+//! project directory, and only for projects with neither the best installed
+//! manager ([`PackageManager::detect`]). Generic `npm install` / `npm run`
+//! commands from the agent are then rewritten to that manager, with a frozen
+//! lockfile when one exists, so installs stay fast and the lockfile doesn't
+//! churn.
+
+use super::{PackageManager, SandboxClient};
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// Timeout for the directory listing used to find lockfiles.
+const LIST_TIMEOUT_MS: u64 = 10_000;
+
+/// Shell operators between the commands of a command line.
+static COMMAND_SEPARATORS: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"&&|\|\||;|\n").unwrap());
+
+/// How the project's package manager was chosen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DetectionSource {
+    /// `packageManager` field of `package.json` (corepack).
+    PackageJson(String),
+    /// A lockfile in the project directory.
+    Lockfile(&'static str),
+    /// No project preference; the best installed manager.
+    Installed,
+}
+
+/// The package manager a project uses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectPackageManager {
+    pub manager: PackageManager,
+    /// Lockfile of `manager` present in the project, if any.
+    pub lockfile: Option<&'static str>,
+    pub source: DetectionSource,
+    /// The project's preferred manager when it isn't installed and another
+    /// one was picked instead.
+    pub unavailable: Option<PackageManager>,
+}
+
+impl ProjectPackageManager {
+    /// Install command honouring the lockfile.
+    pub fn install_cmd(&self) -> &'static str {
+        if self.lockfile.is_some() {
+            self.manager.frozen_install_cmd()
+        } else {
+            self.manager.install_cmd()
+        }
+    }
+
+    /// One-line explanation of the choice, e.g. `pnpm (pnpm-lock.yaml)`.
+    pub fn describe(&self) -> String {
+        let source = match &self.source {
+            DetectionSource::PackageJson(spec) => format!("package.json packageManager \"{spec}\""),
+            DetectionSource::Lockfile(name) => (*name).to_string(),
+            DetectionSource::Installed => "no lockfile; best installed manager".to_string(),
+        };
+        match self.unavailable {
+            Some(wanted) => format!(
+                "{} ({source}; {wanted} is preferred but not installed)",
+                self.manager
+            ),
+            None => format!("{} ({source})", self.manager),
+        }
+    }
+}
+
+/// Pick the manager from a project's `package.json` text and file names.
+/// Returns `None` when the project expresses no preference.
+fn preferred(
+    package_json: Option<&str>,
+    files: &[String],
+) -> Option<(PackageManager, DetectionSource)> {
+    let field = package_json
+        .and_then(|text| serde_json::from_str::<serde_json::Value>(text).ok())
+        .and_then(|json| json["packageManager"].as_str().map(str::to_string));
+    if let Some(spec) = field {
+        if let Some(manager) = PackageManager::from_name(&spec) {
+            return Some((manager, DetectionSource::PackageJson(spec)));
+        }
+    }
+
+    [
+        PackageManager::Pnpm,
+        PackageManager::Yarn,
+        PackageManager::Bun,
+        PackageManager::Npm,
+    ]
+    .into_iter()
+    .find_map(|manager| {
+        manager
+            .lockfiles()
+            .iter()
+            .find(|lockfile| files.iter().any(|file| file == *lockfile))
+            .map(|lockfile| (manager, DetectionSource::Lockfile(lockfile)))
+    })
+}
+
+fn lockfile_of(manager: PackageManager, files: &[String]) -> Option<&'static str> {
+    manager
+        .lockfiles()
+        .iter()
+        .copied()
+        .find(|lockfile| files.iter().any(|file| file == lockfile))
+}
+
+/// Detect the package manager of the project in `workdir` and remember it as
+/// the sandbox's manager.
+pub async fn detect_project_package_manager(
+    client: &dyn SandboxClient,
+    workdir: &str,
+) -> ProjectPackageManager {
+    let files: Vec<String> = match client.run_command("ls -1A", workdir, LIST_TIMEOUT_MS).await {
+        Ok(output) if output.exit_code == 0 => output
+            .stdout
+            .lines()
+            .map(|line| line.trim().to_string())
+            .collect(),
+        _ => Vec::new(),
+    };
+    let package_json = if files.iter().any(|file| file == "package.json") {
+        client
+            .read_file(&format!("{}/package.json", workdir.trim_end_matches('/')))
+            .await
+            .ok()
+    } else {
+        None
+    };
+
+    let detected = match preferred(package_json.as_deref(), &files) {
+        Some((manager, source))
+            if manager == PackageManager::Npm || manager.is_installed().await =>
+        {
+            ProjectPackageManager {
+                manager,
+                lockfile: lockfile_of(manager, &files),
+                source,
+                unavailable: None,
+            }
+        }
+        Some((wanted, _)) => ProjectPackageManager {
+            manager: PackageManager::detect().await,
+            lockfile: None,
+            source: DetectionSource::Installed,
+            unavailable: Some(wanted),
+        },
+        None => ProjectPackageManager {
+            manager: PackageManager::detect().await,
+            lockfile: None,
+            source: DetectionSource::Installed,
+            unavailable: None,
+        },
+    };
+    client.set_package_manager(detected.manager);
+    detected
+}
+
+/// npm flags with the same meaning for `pnpm add`, `yarn add` and `bun add`,
+/// mapped to their shared spelling (empty = default behaviour, drop it).
+fn add_flag(flag: &str) -> Option<&'static str> {
+    match flag {
+        "-D" | "--save-dev" => Some("-D"),
+        "-E" | "--save-exact" => Some("-E"),
+        "-S" | "--save" | "--save-prod" | "-P" => Some(""),
+        _ => None,
+    }
+}
+
+/// Rewrite one command of a command line, or `None` to keep it.
+fn rewrite_segment(segment: &str, project: &ProjectPackageManager) -> Option<String> {
+    let manager = project.manager;
+    let tokens: Vec<&str> = segment.split_whitespace().collect();
+    let (&"npm", rest) = tokens.split_first()? else {
+        return None;
+    };
+    match rest {
+        // `npm ci` is already frozen; only a different manager changes it.
+        ["ci"] if manager == PackageManager::Npm => None,
+        ["install" | "i" | "ci"] if manager == PackageManager::Npm => project
+            .lockfile
+            .map(|_| manager.frozen_install_cmd().to_string()),
+        ["install" | "i" | "ci"] => Some(project.install_cmd().to_string()),
+        ["install" | "i" | "add", args @ ..] if manager != PackageManager::Npm => {
+            let mut out = vec![manager.add_cmd().to_string()];
+            for arg in args {
+                if arg.starts_with('-') {
+                    let flag = add_flag(arg)?;
+                    if !flag.is_empty() {
+                        out.push(flag.to_string());
+                    }
+                } else {
+                    out.push((*arg).to_string());
+                }
+            }
+            Some(out.join(" "))
+        }
+        ["run" | "run-script", script, args @ ..] | [script @ ("test" | "start"), args @ ..]
+            if manager != PackageManager::Npm =>
+        {
+            // pnpm, yarn and bun pass extra arguments on without npm's `--`.
+            let mut out = vec![manager.run_cmd(), *script];
+            out.extend(args.iter().copied().filter(|arg| *arg != "--"));
+            Some(out.join(" "))
+        }
+        _ => None,
+    }
+}
+
+/// `command` with generic npm invocations replaced by the project's manager,
+/// or `None` when nothing changes. Commands naming a manager explicitly
+/// (`pnpm install`) are left alone, so the agent can always update the
+/// lockfile on purpose.
+pub fn rewrite_command(command: &str, project: &ProjectPackageManager) -> Option<String> {
+    let mut out = String::with_capacity(command.len());
+    let mut changed = false;
+    let mut start = 0;
+    let separators = COMMAND_SEPARATORS
+        .find_iter(command)
+        .map(|m| (m.start(), m.end()))
+        .chain(std::iter::once((command.len(), command.len())));
+    for (sep_start, sep_end) in separators {
+        let segment = &command[start..sep_start];
+        match rewrite_segment(segment, project) {
+            Some(rewritten) => {
+                changed = true;
+                out.push_str(&segment[..segment.len() - segment.trim_start().len()]);
+                out.push_str(&rewritten);
+                out.push_str(&segment[segment.trim_end().len()..]);
+            }
+            None => out.push_str(segment),
+        }
+        out.push_str(&command[sep_start..sep_end]);
+        start = sep_end;
+    }
+    changed.then_some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(manager: PackageManager, lockfile: Option<&'static str>) -> ProjectPackageManager {
+        ProjectPackageManager {
+            manager,
+            lockfile,
+            source: DetectionSource::Installed,
+            unavailable: None,
+        }
+    }
+
+    #[test]
+    fn package_json_field_beats_lockfiles() {
+        let files = vec!["package-lock.json".to_string(), "yarn.lock".to_string()];
+        assert_eq!(
+            preferred(Some(r#"{"packageManager": "pnpm@9.1.0"}"#), &files),
+            Some((
+                PackageManager::Pnpm,
+                DetectionSource::PackageJson("pnpm@9.1.0".into())
+            ))
+        );
+        assert_eq!(
+            preferred(Some("{}"), &files),
+            Some((PackageManager::Yarn, DetectionSource::Lockfile("yarn.lock")))
+        );
+        assert_eq!(
+            preferred(None, &["bun.lockb".to_string()]),
+            Some((PackageManager::Bun, DetectionSource::Lockfile("bun.lockb")))
+        );
+        assert_eq!(preferred(None, &[]), None);
+    }
+
+    #[test]
+    fn rewrites_generic_npm_commands_to_project_manager() {
+        let pnpm = project(PackageManager::Pnpm, Some("pnpm-lock.yaml"));
+        assert_eq!(
+            rewrite_command("npm install", &pnpm).as_deref(),
+            Some("pnpm install --frozen-lockfile")
+        );
+        assert_eq!(
+            rewrite_command(
+                "npm i --save-dev vitest && npm run build -- --mode prod",
+                &pnpm
+            )
+            .as_deref(),
+            Some("pnpm add -D vitest && pnpm build --mode prod")
+        );
+        assert_eq!(
+            rewrite_command("npm test", &project(PackageManager::Bun, None)).as_deref(),
+            Some("bun run test")
+        );
+        assert_eq!(
+            rewrite_command("npm install", &project(PackageManager::Yarn, None)).as_deref(),
+            Some("yarn install")
+        );
+        // Unknown npm flags and explicit managers are left alone.
+        assert_eq!(
+            rewrite_command("npm install --legacy-peer-deps react", &pnpm),
+            None
+        );
+        assert_eq!(rewrite_command("pnpm install", &pnpm), None);
+        assert_eq!(rewrite_command("npx create-next-app .", &pnpm), None);
+    }
+
+    #[test]
+    fn npm_projects_only_freeze_installs() {
+        let locked = project(PackageManager::Npm, Some("package-lock.json"));
+        assert_eq!(
+            rewrite_command("npm install", &locked).as_deref(),
+            Some("npm ci")
+        );
+        assert_eq!(rewrite_command("npm ci", &locked), None);
+        assert_eq!(rewrite_command("npm run dev", &locked), None);
+        assert_eq!(
+            rewrite_command("npm install", &project(PackageManager::Npm, None)),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn detects_lockfile_in_sandbox_project() {
+        let client = super::super::local::LocalProcessSandboxClient::new();
+        client.create_sandbox(false, "", 30_000).await.unwrap();
+        client
+            .write_file("project/package.json", r#"{"name": "app"}"#)
+            .await
+            .unwrap();
+        client
+            .write_file("project/package-lock.json", "{}")
+            .await
+            .unwrap();
+
+        let detected = detect_project_package_manager(&client, "project").await;
+        assert_eq!(detected.manager, PackageManager::Npm);
+        assert_eq!(detected.lockfile, Some("package-lock.json"));
+        assert_eq!(detected.install_cmd(), "npm ci");
+        assert_eq!(client.package_manager(), PackageManager::Npm);
+        client.kill_sandbox().await.unwrap();
+    }
+}
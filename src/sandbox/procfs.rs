@@ -0,0 +1,247 @@
+//! Processes and listening TCP ports of a sandbox, read from Linux `/proc`.
+//!
+//! [`read`] runs [`script`] in the sandbox with
+//! [`run_command`](super::SandboxClient::run_command) and parses what it
+//! prints: `/proc/uptime`, `/proc/net/tcp` and `tcp6`, and per process its
+//! `stat`, command line and socket inodes. Matching socket inodes to
+//! processes tells which process owns each listening port. Providers whose
+//! sandbox shares the host's `/proc` (the local one) pass the sandbox's
+//! `HOME` so only its own processes are listed.
+
+use super::{CommandOutput, ListeningPort, SandboxClient, SandboxProcess};
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+/// How long reading `/proc` may take.
+const TIMEOUT_MS: u64 = 15_000;
+
+/// Clock ticks per second of `/proc/<pid>/stat` times (`CLK_TCK`).
+const CLOCK_TICKS: u64 = 100;
+
+/// Size of a memory page in KiB.
+const PAGE_KIB: u64 = 4;
+
+/// `/proc/net/tcp` state of a listening socket.
+const TCP_LISTEN: &str = "0A";
+
+/// Processes and listening ports read at one moment.
+#[derive(Debug, Default)]
+pub struct ProcessTable {
+    pub processes: Vec<SandboxProcess>,
+    pub ports: Vec<ListeningPort>,
+}
+
+/// Shell script printing what [`parse`] reads. With `home`, only processes
+/// whose environment has `HOME=<home>` are printed.
+pub fn script(home: Option<&str>) -> String {
+    let home = home.map_or_else(String::new, |h| format!("'{}'", h.replace('\'', r"'\''")));
+    format!(
+        r#"only_home={home}
+echo '## uptime'; cat /proc/uptime || exit 3
+echo '## tcp'; cat /proc/net/tcp 2>/dev/null
+echo '## tcp6'; cat /proc/net/tcp6 2>/dev/null
+echo '## procs'
+for d in /proc/[0-9]*; do
+  pid=${{d#/proc/}}
+  [ "$pid" = "$$" ] && continue
+  if [ -n "$only_home" ]; then
+    tr '\0' '\n' < "$d/environ" 2>/dev/null | grep -qxF "HOME=$only_home" || continue
+  fi
+  stat=$(cat "$d/stat" 2>/dev/null) || continue
+  cmd=$(tr '\0' ' ' < "$d/cmdline" 2>/dev/null)
+  socks=$(ls -l "$d/fd" 2>/dev/null | sed -n 's/.*socket:\[\([0-9]*\)\].*/\1/p' | tr '\n' ',')
+  printf '%s\t%s\t%s\t%s\n' "$pid" "$socks" "$cmd" "$stat"
+done"#
+    )
+}
+
+/// Run [`script`] in the sandbox of `client` and parse its output. With
+/// `home`, ports not owned by a listed process are left out as well.
+pub async fn read<C: SandboxClient + ?Sized>(
+    client: &C,
+    home: Option<&str>,
+) -> anyhow::Result<ProcessTable> {
+    let CommandOutput {
+        stdout,
+        stderr,
+        exit_code,
+    } = client.run_command(&script(home), ".", TIMEOUT_MS).await?;
+    if exit_code != 0 || !stdout.contains("## procs") {
+        anyhow::bail!(
+            "Could not read /proc in the sandbox (processes and ports are only listed on \
+             Linux): {}",
+            stderr.trim()
+        );
+    }
+    Ok(parse(&stdout, home.is_some()))
+}
+
+/// Parse the output of [`script`]. With `owned_only`, ports no listed
+/// process owns are dropped.
+pub fn parse(output: &str, owned_only: bool) -> ProcessTable {
+    let mut uptime = 0.0_f64;
+    let mut sockets = Vec::new();
+    let mut processes = Vec::new();
+    let mut owners: HashMap<u64, usize> = HashMap::new();
+    let mut section = "";
+    for line in output.lines() {
+        if let Some(name) = line.strip_prefix("## ") {
+            section = name;
+            continue;
+        }
+        match section {
+            "uptime" => {
+                uptime = line
+                    .split_whitespace()
+                    .next()
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(0.0);
+            }
+            "tcp" | "tcp6" => sockets.extend(parse_socket(line, section == "tcp6")),
+            "procs" => {
+                if let Some((process, inodes)) = parse_process(line, uptime) {
+                    for inode in inodes {
+                        owners.insert(inode, processes.len());
+                    }
+                    processes.push(process);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut ports: Vec<ListeningPort> = sockets
+        .into_iter()
+        .filter_map(|(address, port, inode)| {
+            let owner = owners.get(&inode).map(|&i| &processes[i]);
+            if owned_only && owner.is_none() {
+                return None;
+            }
+            Some(ListeningPort {
+                address,
+                port,
+                pid: owner.map(|p| p.pid),
+                command: owner.map(|p| p.command.clone()),
+            })
+        })
+        .collect();
+    ports.sort_by(|a, b| (a.port, &a.address).cmp(&(b.port, &b.address)));
+    ports.dedup_by(|a, b| a.port == b.port && a.address == b.address);
+    processes.sort_by_key(|p| p.pid);
+    ProcessTable { processes, ports }
+}
+
+/// `(address, port, inode)` of a listening socket line of `/proc/net/tcp`.
+fn parse_socket(line: &str, ipv6: bool) -> Option<(String, u16, u64)> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if fields.len() < 10 || fields[3] != TCP_LISTEN {
+        return None;
+    }
+    let (address, port) = fields[1].split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+    let address = if ipv6 {
+        if address.len() != 32 {
+            return None;
+        }
+        // Four 32-bit words, each in host (little-endian) byte order.
+        let mut octets = [0u8; 16];
+        for (word, chunk) in address.as_bytes().chunks(8).enumerate() {
+            let value = u32::from_str_radix(std::str::from_utf8(chunk).ok()?, 16).ok()?;
+            octets[word * 4..word * 4 + 4].copy_from_slice(&value.to_le_bytes());
+        }
+        Ipv6Addr::from(octets).to_string()
+    } else {
+        let value = u32::from_str_radix(address, 16).ok()?;
+        Ipv4Addr::from(value.to_le_bytes()).to_string()
+    };
+    Some((address, port, fields[9].parse().ok()?))
+}
+
+/// A process line of [`script`]: pid, socket inodes, command line and
+/// `/proc/<pid>/stat`.
+fn parse_process(line: &str, uptime: f64) -> Option<(SandboxProcess, Vec<u64>)> {
+    let mut fields = line.splitn(4, '\t');
+    let pid = fields.next()?.parse().ok()?;
+    let inodes = fields
+        .next()?
+        .split(',')
+        .filter_map(|inode| inode.parse().ok())
+        .collect();
+    let cmdline = fields.next()?.trim();
+    let stat = fields.next()?;
+    // The name in parentheses may itself contain spaces and parentheses.
+    let (name, rest) = stat.rsplit_once(')')?;
+    let name = name.split_once('(').map_or("", |(_, name)| name);
+    let rest: Vec<&str> = rest.split_whitespace().collect();
+    let field = |index: usize| rest.get(index).and_then(|v| v.parse::<u64>().ok());
+    let started_secs = field(19).unwrap_or(0) / CLOCK_TICKS;
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let uptime_secs = uptime as u64;
+    Some((
+        SandboxProcess {
+            pid,
+            ppid: field(1).and_then(|v| u32::try_from(v).ok()).unwrap_or(0),
+            state: rest.first().and_then(|s| s.chars().next()).unwrap_or('?'),
+            elapsed_secs: uptime_secs.saturating_sub(started_secs),
+            rss_kib: field(21).unwrap_or(0) * PAGE_KIB,
+            command: if cmdline.is_empty() {
+                format!("[{name}]")
+            } else {
+                cmdline.to_string()
+            },
+        },
+        inodes,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OUTPUT: &str = "## uptime
+1000.50 3000.00
+## tcp
+  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000:0BB8 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 4242 1 0
+   1: 0100007F:1538 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 777 1 0
+   2: 0100007F:A2C4 0100007F:0BB8 01 00000000:00000000 00:00000000 00000000  1000        0 99 1 0
+## tcp6
+   0: 00000000000000000000000001000000:1F90 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 5151 1 0
+## procs
+1\t\t\t1 (sleep) S 0 1 1 0 -1 4194560 0 0 0 0 0 0 0 0 20 0 1 0 100 1000 50
+57\t4242,13,\tnode node_modules/.bin/next dev \t57 (next-server (v1) S 1 57 1 0 -1 4194560 0 0 0 0 5 1 0 0 20 0 11 0 60050 1000 20000
+";
+
+    #[test]
+    fn parses_processes_and_port_owners() {
+        let table = parse(OUTPUT, false);
+        assert_eq!(table.processes.len(), 2);
+        let next = &table.processes[1];
+        assert_eq!((next.pid, next.ppid, next.state), (57, 1, 'S'));
+        assert_eq!(next.command, "node node_modules/.bin/next dev");
+        assert_eq!(next.elapsed_secs, 400);
+        assert_eq!(next.rss_kib, 80_000);
+        assert_eq!(table.processes[0].command, "[sleep]");
+
+        let ports: Vec<_> = table
+            .ports
+            .iter()
+            .map(|p| (p.port, p.address.as_str(), p.pid))
+            .collect();
+        assert_eq!(
+            ports,
+            [
+                (3000, "0.0.0.0", Some(57)),
+                (5432, "127.0.0.1", None),
+                (8080, "::1", None)
+            ]
+        );
+
+        let owned = parse(OUTPUT, true);
+        assert_eq!(owned.ports.len(), 1);
+        assert_eq!(
+            owned.ports[0].command.as_deref(),
+            Some("node node_modules/.bin/next dev")
+        );
+    }
+}
@@ -0,0 +1,391 @@
+//! Per-project settings from `project/.zerobuild.toml`.
+//!
+//! A project can pin how the agent works on it:
+//!
+//! ```toml
+//! workdir = "web"                       # commands run in project/web
+//! protected = ["contracts/**", ".env*"] # never modified by the agent
+//!
+//! [commands]
+//! build = "pnpm build"
+//! test = "pnpm test -- --run"
+//!
+//! [deploy]
+//! target = "vercel"
+//! branch = "main"
+//! path = "apps/web"                     # directory of a monorepo to deploy into
+//!
+//! [apps.api]                            # one app of a monorepo snapshot
+//! path = "apps/api"
+//! commands.test = "cargo test -p api"
+//! deploy.repo = "acme/api"
+//! ```
+//!
+//! Values may be SOPS/age-encrypted (see [`crate::security::sops`]); they
+//! are decrypted with the host's age keys when the file is loaded.
+//!
+//! The sandbox file tools refuse to change protected paths,
+//! `sandbox_run_command` and `project_info` default to the configured
+//! workdir, and the factory's Developer, Tester and DevOps stages get the
+//! commands and deploy target in their task prompts. The file itself is
+//! always protected so the agent cannot lift its own restrictions.
+
+use super::SandboxClient;
+use anyhow::Context;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Project directory, relative to the sandbox root.
+pub const PROJECT_DIR: &str = "project";
+
+/// Config file name inside [`PROJECT_DIR`].
+pub const CONFIG_FILE: &str = ".zerobuild.toml";
+
+/// Commands the project is built, tested and run with.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectCommands {
+    pub install: Option<String>,
+    pub build: Option<String>,
+    pub test: Option<String>,
+    pub lint: Option<String>,
+    pub start: Option<String>,
+    /// Dependency update of the refresh job (`[zerobuild.maintenance]`).
+    pub update: Option<String>,
+}
+
+/// Where the DevOps stage deploys to.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectDeploy {
+    /// Free-form target, e.g. `github`, `vercel`, `fly`.
+    pub target: Option<String>,
+    pub branch: Option<String>,
+    /// `owner/name` of the repository to push to.
+    pub repo: Option<String>,
+    /// Directory of the repository the project lives in (monorepos).
+    pub path: Option<String>,
+    /// `owner/name` of the template a new repository is created from.
+    pub template: Option<String>,
+}
+
+/// One app of a monorepo, targeted with the `app` argument of the sandbox
+/// and deploy tools (see [`super::monorepo`]).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectApp {
+    /// App directory relative to [`PROJECT_DIR`]. Default: the detected
+    /// workspace member of the same name.
+    pub path: Option<String>,
+    pub commands: ProjectCommands,
+    pub deploy: ProjectDeploy,
+}
+
+/// Parsed `.zerobuild.toml`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectConfig {
+    /// Working directory for commands, relative to [`PROJECT_DIR`].
+    pub workdir: Option<String>,
+    pub commands: ProjectCommands,
+    pub deploy: ProjectDeploy,
+    /// Glob patterns relative to [`PROJECT_DIR`]. Patterns without a `/`
+    /// match a name at any depth, like `.gitignore`.
+    pub protected: Vec<String>,
+    /// Monorepo apps by name.
+    pub apps: BTreeMap<String, ProjectApp>,
+}
+
+/// Whether `path` is relative and doesn't leave the project.
+fn inside_project(path: &str) -> bool {
+    !path.starts_with('/') && !path.split('/').any(|part| part == "..")
+}
+
+/// `path` without surrounding `/` and leading `./`.
+fn normalize(path: &str) -> &str {
+    let mut path = path.trim().trim_matches('/');
+    while let Some(rest) = path.strip_prefix("./") {
+        path = rest.trim_start_matches('/');
+    }
+    path
+}
+
+impl ProjectConfig {
+    /// Parse and validate the file contents.
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let config: Self = match crate::security::sops::decrypt_document(text)
+            .with_context(|| format!("Invalid {CONFIG_FILE}"))?
+        {
+            Some(document) => toml::Value::Table(document)
+                .try_into()
+                .with_context(|| format!("Invalid {CONFIG_FILE}"))?,
+            None => toml::from_str(text).with_context(|| format!("Invalid {CONFIG_FILE}"))?,
+        };
+        for pattern in &config.protected {
+            glob::Pattern::new(normalize(pattern)).with_context(|| {
+                format!("Invalid protected pattern in {CONFIG_FILE}: {pattern}")
+            })?;
+        }
+        if let Some(workdir) = &config.workdir {
+            if !inside_project(workdir) {
+                anyhow::bail!("{CONFIG_FILE} workdir must stay inside the project: {workdir}");
+            }
+        }
+        for (name, app) in &config.apps {
+            if let Some(path) = &app.path {
+                if !inside_project(path) || normalize(path).is_empty() {
+                    anyhow::bail!(
+                        "{CONFIG_FILE} apps.{name}.path must be a directory inside the project: \
+                         {path}"
+                    );
+                }
+            }
+        }
+        Ok(config)
+    }
+
+    /// Load the config of the active sandbox's project. `Ok(None)` when the
+    /// project has no config file.
+    pub async fn load(client: &dyn SandboxClient) -> anyhow::Result<Option<Self>> {
+        match client
+            .read_file(&format!("{PROJECT_DIR}/{CONFIG_FILE}"))
+            .await
+        {
+            Ok(text) => Self::parse(&text).map(Some),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Command working directory relative to the sandbox root.
+    pub fn workdir(&self) -> String {
+        match self.workdir.as_deref().map(normalize) {
+            Some(dir) if !dir.is_empty() => format!("{PROJECT_DIR}/{dir}"),
+            _ => PROJECT_DIR.to_string(),
+        }
+    }
+
+    /// Settings of the app in `path` (relative to [`PROJECT_DIR`]): its
+    /// directory as workdir, the commands and deploy settings of `app`, and
+    /// the project's protected paths.
+    pub fn for_app(&self, path: &str, app: Option<&ProjectApp>) -> Self {
+        let app = app.cloned().unwrap_or_default();
+        Self {
+            workdir: Some(normalize(path).to_string()),
+            commands: app.commands,
+            deploy: app.deploy,
+            protected: self.protected.clone(),
+            apps: BTreeMap::new(),
+        }
+    }
+
+    /// The rule protecting `path` (relative to the sandbox root), if any.
+    /// A path is protected when it, a directory above it, or anything
+    /// below it matches a rule, so protected files cannot be removed by
+    /// deleting or moving their directory either.
+    pub fn protecting_rule(&self, path: &str) -> Option<&str> {
+        let path = normalize(path);
+        let relative = if path == PROJECT_DIR {
+            ""
+        } else {
+            normalize(path.strip_prefix(PROJECT_DIR)?.strip_prefix('/')?)
+        };
+        std::iter::once(CONFIG_FILE)
+            .chain(self.protected.iter().map(String::as_str))
+            .find(|rule| rule_matches(rule, relative))
+    }
+
+    /// Settings as indented `key: value` lines.
+    pub fn describe(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "  workdir: {}", self.workdir());
+        let commands = [
+            ("install", &self.commands.install),
+            ("build", &self.commands.build),
+            ("test", &self.commands.test),
+            ("lint", &self.commands.lint),
+            ("start", &self.commands.start),
+            ("update", &self.commands.update),
+        ];
+        for (name, command) in commands {
+            if let Some(command) = command {
+                let _ = writeln!(out, "  {name}: {command}");
+            }
+        }
+        if let Some(target) = &self.deploy.target {
+            let _ = writeln!(out, "  deploy_target: {target}");
+        }
+        if let Some(repo) = &self.deploy.repo {
+            let _ = writeln!(out, "  deploy_repo: {repo}");
+        }
+        if let Some(branch) = &self.deploy.branch {
+            let _ = writeln!(out, "  deploy_branch: {branch}");
+        }
+        if let Some(path) = &self.deploy.path {
+            let _ = writeln!(out, "  deploy_path: {path}");
+        }
+        if let Some(template) = &self.deploy.template {
+            let _ = writeln!(out, "  deploy_template: {template}");
+        }
+        let _ = writeln!(out, "  protected: {}", self.protected_list());
+        for (name, app) in &self.apps {
+            let mut settings = Vec::new();
+            if let Some(path) = &app.path {
+                settings.push(format!("path {path}"));
+            }
+            for (key, value) in [
+                ("build", &app.commands.build),
+                ("test", &app.commands.test),
+                ("deploy_target", &app.deploy.target),
+                ("deploy_repo", &app.deploy.repo),
+                ("deploy_path", &app.deploy.path),
+            ] {
+                if let Some(value) = value {
+                    settings.push(format!("{key} {value}"));
+                }
+            }
+            let _ = writeln!(out, "  app {name}: {}", settings.join(", "));
+        }
+        out
+    }
+
+    /// Protected patterns including the config file, comma separated.
+    pub fn protected_list(&self) -> String {
+        std::iter::once(CONFIG_FILE)
+            .chain(self.protected.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Whether `rule` covers `relative` (a path relative to the project), an
+/// ancestor of it, or something inside it.
+fn rule_matches(rule: &str, relative: &str) -> bool {
+    let rule = normalize(rule);
+    let Ok(pattern) = glob::Pattern::new(rule) else {
+        return false;
+    };
+    let options = glob::MatchOptions {
+        require_literal_separator: true,
+        ..glob::MatchOptions::default()
+    };
+    let anywhere = !rule.contains('/');
+
+    // The path itself or one of its ancestors.
+    let mut prefix = String::new();
+    for part in relative.split('/').filter(|part| !part.is_empty()) {
+        if !prefix.is_empty() {
+            prefix.push('/');
+        }
+        prefix.push_str(part);
+        if pattern.matches_with(&prefix, options) || (anywhere && pattern.matches(part)) {
+            return true;
+        }
+    }
+
+    // A directory that contains what the rule names literally.
+    let literal = rule
+        .split('/')
+        .take_while(|part| !part.contains(['*', '?', '[']))
+        .collect::<Vec<_>>()
+        .join("/");
+    relative.is_empty() || (!anywhere && format!("{literal}/").starts_with(&format!("{relative}/")))
+}
+
+/// Error message when any of `paths` is protected by the project's
+/// `.zerobuild.toml`. An unreadable config protects only itself.
+pub async fn check_writable(client: &dyn SandboxClient, paths: &[&str]) -> Result<(), String> {
+    let config = match ProjectConfig::load(client).await {
+        Ok(config) => config.unwrap_or_default(),
+        Err(e) => {
+            tracing::warn!("{e:#}");
+            ProjectConfig::default()
+        }
+    };
+    for path in paths {
+        if let Some(rule) = config.protecting_rule(path) {
+            return Err(format!(
+                "'{path}' is protected by {PROJECT_DIR}/{CONFIG_FILE} (rule '{rule}'); leave it \
+                 unchanged or ask the user to edit it"
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_validates() {
+        let config = ProjectConfig::parse(
+            r#"
+            workdir = "web"
+            protected = ["contracts/**", ".env*"]
+            [commands]
+            test = "pnpm test"
+            [deploy]
+            target = "vercel"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.workdir(), "project/web");
+        assert_eq!(config.commands.test.as_deref(), Some("pnpm test"));
+        assert_eq!(config.deploy.target.as_deref(), Some("vercel"));
+
+        assert!(ProjectConfig::parse("workdir = \"../outside\"").is_err());
+        assert!(ProjectConfig::parse("unknown = 1").is_err());
+        assert!(ProjectConfig::parse("protected = [\"[\"]").is_err());
+        assert!(ProjectConfig::parse("[apps.api]\npath = \"../api\"").is_err());
+    }
+
+    #[test]
+    fn app_settings_replace_the_project_ones() {
+        let config = ProjectConfig::parse(
+            r#"
+            protected = [".env*"]
+            [commands]
+            test = "pnpm turbo test"
+            [apps.api]
+            path = "./apps/api/"
+            commands.test = "cargo test -p api"
+            deploy.repo = "acme/api"
+            "#,
+        )
+        .unwrap();
+        let api = config.for_app("./apps/api/", config.apps.get("api"));
+        assert_eq!(api.workdir(), "project/apps/api");
+        assert_eq!(api.commands.test.as_deref(), Some("cargo test -p api"));
+        assert_eq!(api.deploy.repo.as_deref(), Some("acme/api"));
+        assert_eq!(api.protected, vec![".env*".to_string()]);
+
+        let web = config.for_app("apps/web", None);
+        assert_eq!(web.workdir(), "project/apps/web");
+        assert_eq!(web.commands, ProjectCommands::default());
+    }
+
+    #[test]
+    fn protects_matches_ancestors_and_containing_directories() {
+        let config = ProjectConfig {
+            protected: vec![
+                "contracts/**".into(),
+                ".env*".into(),
+                "src/db/schema.sql".into(),
+            ],
+            ..ProjectConfig::default()
+        };
+        let rule = |path| config.protecting_rule(path);
+
+        assert_eq!(rule("project/.zerobuild.toml"), Some(CONFIG_FILE));
+        assert_eq!(rule("project/contracts/Token.sol"), Some("contracts/**"));
+        assert_eq!(rule("./project/web/.env.local"), Some(".env*"));
+        assert_eq!(rule("project/src/db/schema.sql"), Some("src/db/schema.sql"));
+        // Deleting or moving a directory holding a protected file.
+        assert_eq!(rule("project/src/db"), Some("src/db/schema.sql"));
+        assert_eq!(rule("project/contracts"), Some("contracts/**"));
+        assert_eq!(rule("project/src/db/migrations/001.sql"), None);
+        assert_eq!(rule("project/src/app/page.tsx"), None);
+        assert_eq!(rule("scratch/notes.md"), None);
+    }
+}
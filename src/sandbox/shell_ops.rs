@@ -0,0 +1,225 @@
+//! Shell scripts for file operations of providers that only run commands
+//! in their sandbox (the `kubernetes`, `firecracker` and `fly` providers).
+//!
+//! Each builder checks its paths against the sandbox root the way the local
+//! provider does and returns a `sh` script that fails with a readable
+//! message on stderr.
+
+use base64::Engine as _;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Prints the best package manager on `PATH`: pnpm > yarn > bun > npm.
+pub const DETECT_PACKAGE_MANAGER: &str = "for pm in pnpm yarn bun; do \
+    command -v $pm >/dev/null 2>&1 && { echo $pm; exit 0; }; done; echo npm";
+
+/// Exit code of [`read_script`] when the file doesn't exist.
+pub const READ_MISSING: i64 = 44;
+
+/// Base64 characters written per script of [`write_scripts`], below the
+/// kernel's 128 KiB limit on one argument.
+const WRITE_CHUNK: usize = 64 * 1024;
+
+/// Quote `value` for `sh`.
+pub fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// `relative` inside the sandbox `root`, rejecting `..` components.
+pub fn sandbox_path(root: &str, relative: &str) -> anyhow::Result<String> {
+    let mut path = root.to_string();
+    for part in relative.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => anyhow::bail!(
+                "Path traversal rejected: '{}' contains '..' components",
+                relative
+            ),
+            part => {
+                path.push('/');
+                path.push_str(part);
+            }
+        }
+    }
+    Ok(path)
+}
+
+/// Print the file `path`, exiting with [`READ_MISSING`] if it doesn't exist.
+pub fn read_script(root: &str, path: &str) -> anyhow::Result<String> {
+    let target = quote(&sandbox_path(root, path)?);
+    Ok(format!(
+        "[ -f {target} ] || exit {READ_MISSING}; cat {target}"
+    ))
+}
+
+/// Write `content` to `path`, creating parent directories: scripts to run
+/// in order, each small enough to pass as one argument.
+pub fn write_scripts(root: &str, path: &str, content: &str) -> anyhow::Result<Vec<String>> {
+    let target = quote(&sandbox_path(root, path)?);
+    let encoded = base64::engine::general_purpose::STANDARD.encode(content);
+    let mut scripts = Vec::new();
+    let mut script = format!("mkdir -p \"$(dirname {target})\" && : > {target}");
+    for chunk in encoded.as_bytes().chunks(WRITE_CHUNK) {
+        // Chunks are a multiple of 4 bytes, so each decodes on its own.
+        let chunk = std::str::from_utf8(chunk)?;
+        let _ = write!(script, " && printf %s '{chunk}' | base64 -d >> {target}");
+        if script.len() >= WRITE_CHUNK {
+            scripts.push(std::mem::replace(&mut script, ":".to_string()));
+        }
+    }
+    if script != ":" {
+        scripts.push(script);
+    }
+    Ok(scripts)
+}
+
+/// Move (`verb` `mv`) or copy (`cp -R`) `from` to `to`, failing if `to`
+/// exists unless `overwrite`.
+pub fn transfer_script(
+    root: &str,
+    verb: &str,
+    from: &str,
+    to: &str,
+    overwrite: bool,
+) -> anyhow::Result<String> {
+    let source = sandbox_path(root, from)?;
+    let dest = sandbox_path(root, to)?;
+    if source == root || dest == root {
+        anyhow::bail!("Cannot move or copy the sandbox root");
+    }
+    if dest.starts_with(&format!("{source}/")) || dest == source {
+        anyhow::bail!("Cannot move or copy '{from}' into itself ('{to}')");
+    }
+    let (s, d) = (quote(&source), quote(&dest));
+    let exists = quote(&format!(
+        "'{to}' already exists; pass overwrite=true to replace it"
+    ));
+    let missing = quote(&format!("'{from}' does not exist"));
+    let replace = if overwrite {
+        format!("rm -rf {d}")
+    } else {
+        format!("if [ -e {d} ] || [ -L {d} ]; then echo {exists} >&2; exit 1; fi")
+    };
+    Ok(format!(
+        "if [ ! -e {s} ] && [ ! -L {s} ]; then echo {missing} >&2; exit 1; fi; \
+         {replace}; mkdir -p \"$(dirname {d})\" && {verb} {s} {d}"
+    ))
+}
+
+/// Delete the file or directory `path` (non-empty directories only when
+/// `recursive`).
+pub fn delete_script(root: &str, path: &str, recursive: bool) -> anyhow::Result<String> {
+    let target = sandbox_path(root, path)?;
+    if target == root {
+        anyhow::bail!("Refusing to delete the sandbox root");
+    }
+    let t = quote(&target);
+    let remove_dir = if recursive {
+        format!("rm -rf {t}")
+    } else {
+        format!(
+            "rmdir {t} || {{ echo '(pass recursive=true to delete a non-empty directory)' >&2; exit 1; }}"
+        )
+    };
+    let missing = quote(&format!("'{path}' does not exist"));
+    Ok(format!(
+        "if [ -d {t} ] && [ ! -L {t} ]; then {remove_dir}; \
+         elif [ -e {t} ] || [ -L {t} ]; then rm -f {t}; \
+         else echo {missing} >&2; exit 1; fi"
+    ))
+}
+
+/// Print `dir\t<name>` or `file\t<name>` for every entry of `path`; sort
+/// the lines with [`list_entries`].
+pub fn list_script(root: &str, path: &str) -> anyhow::Result<String> {
+    let dir = quote(&sandbox_path(root, path)?);
+    Ok(format!(
+        "cd {dir} && for f in * .[!.]* ..?*; do \
+         [ -e \"$f\" ] || [ -L \"$f\" ] || continue; \
+         if [ -d \"$f\" ]; then printf 'dir\\t%s\\n' \"$f\"; \
+         else printf 'file\\t%s\\n' \"$f\"; fi; done"
+    ))
+}
+
+/// Sorted output of [`list_script`].
+pub fn list_entries(output: &str) -> String {
+    let mut entries: Vec<&str> = output.lines().collect();
+    entries.sort_unstable();
+    entries.join("\n")
+}
+
+/// Print `path<TAB>base64` for every file under `workdir`, skipping
+/// [`SKIP_DIRS`](super::local::SKIP_DIRS); parse it with [`parse_snapshot`].
+pub fn snapshot_script(root: &str, workdir: &str) -> anyhow::Result<String> {
+    let dir = quote(&sandbox_path(root, workdir)?);
+    let prune = super::local::SKIP_DIRS
+        .iter()
+        .map(|name| format!("-name {}", quote(name)))
+        .collect::<Vec<_>>()
+        .join(" -o ");
+    Ok(format!(
+        "cd {dir} && find . \\( {prune} \\) -prune -o -type f -print | \
+         while IFS= read -r f; do printf '%s\\t' \"${{f#./}}\"; base64 < \"$f\" | tr -d '\\n'; echo; done"
+    ))
+}
+
+/// Parse the `path<TAB>base64` lines of [`snapshot_script`], skipping files
+/// that aren't UTF-8 text.
+pub fn parse_snapshot(output: &str) -> HashMap<String, String> {
+    let engine = base64::engine::general_purpose::STANDARD;
+    output
+        .lines()
+        .filter_map(|line| {
+            let (path, encoded) = line.split_once('\t')?;
+            let content = String::from_utf8(engine.decode(encoded).ok()?).ok()?;
+            Some((path.to_string(), content))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_stay_inside_the_sandbox_root() {
+        assert_eq!(sandbox_path("/workspace", ".").unwrap(), "/workspace");
+        assert_eq!(
+            sandbox_path("/workspace", "/project/src/").unwrap(),
+            "/workspace/project/src"
+        );
+        assert!(sandbox_path("/workspace", "project/../../etc").is_err());
+        assert!(delete_script("/workspace", "/", true).is_err());
+        assert!(transfer_script("/workspace", "mv", "src", "src/nested", false).is_err());
+        assert_eq!(quote("it's"), r"'it'\''s'");
+    }
+
+    #[test]
+    fn large_writes_are_split_into_several_scripts() {
+        let small = write_scripts("/workspace", "src/a.txt", "hi").unwrap();
+        assert_eq!(
+            small,
+            [
+                "mkdir -p \"$(dirname '/workspace/src/a.txt')\" && : > '/workspace/src/a.txt' \
+              && printf %s 'aGk=' | base64 -d >> '/workspace/src/a.txt'"
+            ]
+        );
+        let large = write_scripts("/workspace", "big.txt", &"x".repeat(100_000)).unwrap();
+        assert_eq!(large.len(), 3);
+        assert!(large[1].starts_with(": && printf %s '"));
+        assert!(large.iter().all(|script| script.len() < 2 * WRITE_CHUNK));
+    }
+
+    #[test]
+    fn snapshot_output_skips_binary_files() {
+        let engine = base64::engine::general_purpose::STANDARD;
+        let output = format!(
+            "src/index.js\t{}\nlogo.png\t{}\n",
+            engine.encode("console.log(1)\n"),
+            engine.encode([0xff, 0xfe, 0x00])
+        );
+        let files = parse_snapshot(&output);
+        assert_eq!(files.len(), 1);
+        assert_eq!(files["src/index.js"], "console.log(1)\n");
+    }
+}
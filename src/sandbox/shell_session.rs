@@ -0,0 +1,193 @@
+//! Long-lived shell behind a `sandbox_shell` session.
+//!
+//! The shell reads commands from a pipe on stdin. Each command runs in a
+//! `{ ...; } </dev/null` group of the shell itself, so `cd`, `export` and
+//! sourced scripts (nvm, virtualenvs) stay in effect, and is followed by a
+//! line carrying a per-session marker with the exit code and working
+//! directory. Output is read up to that marker. Stderr is merged into stdout.
+
+use super::CommandOutput;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+
+/// A running shell and the pipes to talk to it.
+pub struct ShellSession {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    /// Prefix of the line that ends each command's output.
+    marker: String,
+    /// Sandbox directory the shell was started for.
+    sandbox_dir: String,
+    /// Working directory after the last command, absolute.
+    cwd: String,
+}
+
+impl ShellSession {
+    /// Start `shell` (a shell reading commands from stdin, already set up
+    /// with its environment and working directory) for `sandbox_dir`.
+    pub fn start(mut shell: Command, sandbox_dir: &str, cwd: &str) -> anyhow::Result<Self> {
+        let mut child = shell
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| anyhow::anyhow!("Failed to start shell session: {e}"))?;
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| anyhow::anyhow!("Shell session has no stdin"))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| anyhow::anyhow!("Shell session has no stdout"))?;
+        Ok(Self {
+            child,
+            stdin,
+            stdout: BufReader::new(stdout),
+            marker: format!("__ZEROBUILD_DONE_{}__", uuid::Uuid::new_v4().simple()),
+            sandbox_dir: sandbox_dir.to_string(),
+            cwd: cwd.to_string(),
+        })
+    }
+
+    /// Sandbox directory the shell was started for.
+    pub fn sandbox_dir(&self) -> &str {
+        &self.sandbox_dir
+    }
+
+    /// Working directory after the last command, absolute.
+    pub fn cwd(&self) -> &str {
+        &self.cwd
+    }
+
+    /// Whether the shell is still running; `exit` or a timeout ends it.
+    pub fn is_alive(&mut self) -> bool {
+        matches!(self.child.try_wait(), Ok(None))
+    }
+
+    /// Stop the shell and whatever it runs.
+    pub async fn kill(&mut self) {
+        let _ = self.child.kill().await;
+    }
+
+    /// Run `command` and return its output, or a timeout output after
+    /// `timeout` with the shell killed.
+    pub async fn run(&mut self, command: &str, timeout: Duration) -> anyhow::Result<CommandOutput> {
+        let script = format!(
+            "exec 2>&1\n{{ {command}\n}} </dev/null\nprintf '\\n{}%s %s\\n' \"$?\" \"$PWD\"\n",
+            self.marker
+        );
+        self.stdin
+            .write_all(script.as_bytes())
+            .await
+            .map_err(|e| anyhow::anyhow!("Shell session is gone: {e}"))?;
+        self.stdin.flush().await?;
+
+        match tokio::time::timeout(timeout, self.read_until_marker()).await {
+            Ok(result) => result,
+            Err(_elapsed) => {
+                self.kill().await;
+                Ok(CommandOutput {
+                    stdout: String::new(),
+                    stderr: format!(
+                        "Command timed out after {}ms; the shell session was reset",
+                        timeout.as_millis()
+                    ),
+                    exit_code: -1,
+                })
+            }
+        }
+    }
+
+    /// Collect output lines up to the marker line and parse its exit code
+    /// and working directory. If the shell exits first (the command ran
+    /// `exit`), its own exit status is the command's.
+    async fn read_until_marker(&mut self) -> anyhow::Result<CommandOutput> {
+        let mut output = Vec::new();
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            let read = self.stdout.read_until(b'\n', &mut line).await?;
+            if read == 0 {
+                let status = self.child.wait().await?;
+                return Ok(CommandOutput {
+                    stdout: String::from_utf8_lossy(&output).into_owned(),
+                    stderr: String::new(),
+                    exit_code: status.code().map(i64::from).unwrap_or(-1),
+                });
+            }
+            let text = String::from_utf8_lossy(&line);
+            if let Some(rest) = text.trim_end_matches('\n').strip_prefix(&self.marker) {
+                let (code, cwd) = rest.split_once(' ').unwrap_or((rest, ""));
+                if !cwd.is_empty() {
+                    self.cwd = cwd.to_string();
+                }
+                // The marker is printed after a newline of its own.
+                if output.last() == Some(&b'\n') {
+                    output.pop();
+                }
+                return Ok(CommandOutput {
+                    stdout: String::from_utf8_lossy(&output).into_owned(),
+                    stderr: String::new(),
+                    exit_code: code.trim().parse().unwrap_or(-1),
+                });
+            }
+            output.extend_from_slice(&line);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(dir: &std::path::Path) -> ShellSession {
+        let mut shell = Command::new("sh");
+        shell.current_dir(dir);
+        let dir = dir.to_str().unwrap();
+        ShellSession::start(shell, dir, dir).unwrap()
+    }
+
+    #[tokio::test]
+    async fn state_carries_over_between_commands() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(tmp.path().join("project")).unwrap();
+        let mut shell = session(tmp.path());
+        let timeout = Duration::from_secs(10);
+
+        let out = shell
+            .run("cd project && export GREETING=hi", timeout)
+            .await
+            .unwrap();
+        assert_eq!((out.stdout.as_str(), out.exit_code), ("", 0));
+        assert!(shell.cwd().ends_with("/project"));
+
+        let out = shell
+            .run("echo \"$GREETING\"; echo oops >&2; false", timeout)
+            .await
+            .unwrap();
+        assert_eq!(out.stdout, "hi\noops\n");
+        assert_eq!(out.exit_code, 1);
+
+        let out = shell.run("exit 3", timeout).await.unwrap();
+        assert_eq!(out.exit_code, 3);
+        assert!(!shell.is_alive());
+    }
+
+    #[tokio::test]
+    async fn timeout_kills_the_shell() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mut shell = session(tmp.path());
+        let out = shell
+            .run("sleep 10", Duration::from_millis(100))
+            .await
+            .unwrap();
+        assert_eq!(out.exit_code, -1);
+        assert!(out.stderr.contains("timed out"));
+        assert!(!shell.is_alive());
+    }
+}
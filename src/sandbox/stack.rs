@@ -0,0 +1,493 @@
+//! Project stack detection from manifests: `package.json`, `Cargo.toml` and
+//! `pyproject.toml`.
+//!
+//! Gives the agent a structured overview of a project (framework, scripts,
+//! dependencies with their version requirements, entry points) in one tool
+//! call instead of reading files one by one. Parsing is pure; the caller
+//! reads the manifests and lists candidate entry files from the sandbox.
+
+/// Manifest file names, in report order.
+pub const MANIFESTS: [&str; 3] = ["package.json", "Cargo.toml", "pyproject.toml"];
+
+/// Frameworks recognised from a dependency name, most specific first (a
+/// Next.js app also depends on React).
+const FRAMEWORKS: &[(&str, &str)] = &[
+    ("next", "Next.js"),
+    ("nuxt", "Nuxt"),
+    ("@remix-run/react", "Remix"),
+    ("@sveltejs/kit", "SvelteKit"),
+    ("astro", "Astro"),
+    ("@angular/core", "Angular"),
+    ("@nestjs/core", "NestJS"),
+    ("react-scripts", "Create React App"),
+    ("expo", "Expo"),
+    ("vite", "Vite"),
+    ("express", "Express"),
+    ("fastify", "Fastify"),
+    ("hono", "Hono"),
+    ("tauri", "Tauri"),
+    ("leptos", "Leptos"),
+    ("axum", "Axum"),
+    ("actix-web", "Actix Web"),
+    ("rocket", "Rocket"),
+    ("bevy", "Bevy"),
+    ("django", "Django"),
+    ("fastapi", "FastAPI"),
+    ("flask", "Flask"),
+    ("streamlit", "Streamlit"),
+];
+
+/// Libraries worth naming next to the framework.
+const NOTABLE: &[(&str, &str)] = &[
+    ("react", "React"),
+    ("vue", "Vue"),
+    ("svelte", "Svelte"),
+    ("solid-js", "Solid"),
+    ("typescript", "TypeScript"),
+    ("tailwindcss", "Tailwind CSS"),
+    ("prisma", "Prisma"),
+    ("drizzle-orm", "Drizzle"),
+    ("tokio", "Tokio"),
+    ("sqlx", "SQLx"),
+    ("diesel", "Diesel"),
+    ("sqlalchemy", "SQLAlchemy"),
+    ("pydantic", "Pydantic"),
+];
+
+/// Entry files looked for when the manifest names none, relative to the
+/// project directory.
+pub const ENTRY_CANDIDATES: &[&str] = &[
+    "app/page.tsx",
+    "app/page.jsx",
+    "app/layout.tsx",
+    "src/app/page.tsx",
+    "src/app/layout.tsx",
+    "pages/index.tsx",
+    "pages/index.jsx",
+    "pages/_app.tsx",
+    "src/pages/index.tsx",
+    "src/main.tsx",
+    "src/main.ts",
+    "src/main.jsx",
+    "src/main.js",
+    "src/index.tsx",
+    "src/index.ts",
+    "src/index.js",
+    "src/App.tsx",
+    "src/App.vue",
+    "src/routes/+page.svelte",
+    "index.html",
+    "index.js",
+    "index.ts",
+    "server.js",
+    "server.ts",
+    "src/server.ts",
+    "src/main.rs",
+    "src/lib.rs",
+    "main.py",
+    "app.py",
+    "manage.py",
+    "src/main.py",
+];
+
+/// Directories holding [`ENTRY_CANDIDATES`], for listing them cheaply.
+pub fn entry_candidate_dirs() -> Vec<&'static str> {
+    let mut dirs: Vec<&str> = ENTRY_CANDIDATES
+        .iter()
+        .map(|path| path.rsplit_once('/').map_or("", |(dir, _)| dir))
+        .collect();
+    dirs.sort_unstable();
+    dirs.dedup();
+    dirs
+}
+
+/// A declared dependency.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dependency {
+    pub name: String,
+    /// Version requirement as written (`^14.2.3`, `0.7`, `>=2.0`), or `*`.
+    pub version: String,
+}
+
+/// What one manifest says about the project.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProjectManifest {
+    /// Manifest file name, e.g. `Cargo.toml`.
+    pub file: &'static str,
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub scripts: Vec<(String, String)>,
+    pub dependencies: Vec<Dependency>,
+    pub dev_dependencies: Vec<Dependency>,
+    /// Entry points the manifest declares (`main`, `bin`, `[[bin]]`,
+    /// `[project.scripts]`).
+    pub entry_points: Vec<String>,
+}
+
+impl ProjectManifest {
+    fn all_dependencies(&self) -> impl Iterator<Item = &Dependency> {
+        self.dependencies.iter().chain(&self.dev_dependencies)
+    }
+
+    fn find(&self, name: &str) -> Option<&Dependency> {
+        self.all_dependencies().find(|dep| dep.name == name)
+    }
+
+    /// Framework and notable libraries, e.g. `Next.js ^14.2.3 (React,
+    /// TypeScript)`, or `None` when nothing is recognised.
+    pub fn stack(&self) -> Option<String> {
+        let framework = FRAMEWORKS
+            .iter()
+            .find_map(|(dep, label)| self.find(dep).map(|found| (*dep, *label, found)));
+        let notable: Vec<&str> = NOTABLE
+            .iter()
+            .filter(|(dep, _)| self.find(dep).is_some())
+            .map(|(_, label)| *label)
+            .collect();
+        match (framework, notable.is_empty()) {
+            (None, true) => None,
+            (None, false) => Some(notable.join(", ")),
+            (Some((_, label, dep)), true) => Some(format!("{label} {}", dep.version)),
+            (Some((_, label, dep)), false) => {
+                Some(format!("{label} {} ({})", dep.version, notable.join(", ")))
+            }
+        }
+    }
+}
+
+fn dependencies_of(table: Option<&serde_json::Value>) -> Vec<Dependency> {
+    table
+        .and_then(serde_json::Value::as_object)
+        .map(|deps| {
+            deps.iter()
+                .map(|(name, version)| Dependency {
+                    name: name.clone(),
+                    version: version.as_str().unwrap_or("*").to_string(),
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Parse `package.json`. Returns `None` for invalid JSON.
+pub fn parse_package_json(text: &str) -> Option<ProjectManifest> {
+    let json: serde_json::Value = serde_json::from_str(text).ok()?;
+    let string = |key: &str| json[key].as_str().map(str::to_string);
+
+    let mut entry_points: Vec<String> = ["main", "module"]
+        .iter()
+        .filter_map(|key| string(key))
+        .collect();
+    match &json["bin"] {
+        serde_json::Value::String(bin) => entry_points.push(bin.clone()),
+        serde_json::Value::Object(bins) => {
+            entry_points.extend(bins.values().filter_map(|v| v.as_str().map(str::to_string)));
+        }
+        _ => {}
+    }
+
+    Some(ProjectManifest {
+        file: "package.json",
+        name: string("name"),
+        version: string("version"),
+        scripts: json["scripts"]
+            .as_object()
+            .map(|scripts| {
+                scripts
+                    .iter()
+                    .map(|(name, cmd)| (name.clone(), cmd.as_str().unwrap_or_default().to_string()))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        dependencies: dependencies_of(json.get("dependencies")),
+        dev_dependencies: dependencies_of(json.get("devDependencies")),
+        entry_points,
+    })
+}
+
+/// Version requirement of a `Cargo.toml` dependency: a string, or the
+/// `version` / `path` / `git` key of a table.
+fn cargo_version(spec: &toml::Value) -> String {
+    match spec {
+        toml::Value::String(version) => version.clone(),
+        toml::Value::Table(table) => table
+            .get("version")
+            .and_then(toml::Value::as_str)
+            .map(str::to_string)
+            .or_else(|| {
+                table
+                    .get("path")
+                    .and_then(toml::Value::as_str)
+                    .map(|path| format!("path:{path}"))
+            })
+            .or_else(|| {
+                table
+                    .get("git")
+                    .and_then(toml::Value::as_str)
+                    .map(|git| format!("git:{git}"))
+            })
+            .unwrap_or_else(|| "*".to_string()),
+        _ => "*".to_string(),
+    }
+}
+
+fn cargo_dependencies(table: Option<&toml::Value>) -> Vec<Dependency> {
+    table
+        .and_then(toml::Value::as_table)
+        .map(|deps| {
+            deps.iter()
+                .map(|(name, spec)| Dependency {
+                    name: name.clone(),
+                    version: cargo_version(spec),
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Parse `Cargo.toml`. Returns `None` for invalid TOML.
+pub fn parse_cargo_toml(text: &str) -> Option<ProjectManifest> {
+    let manifest: toml::Table = toml::from_str(text).ok()?;
+    let package = manifest.get("package");
+    let string = |key: &str| {
+        package
+            .and_then(|p| p.get(key))
+            .and_then(toml::Value::as_str)
+            .map(str::to_string)
+    };
+
+    let mut entry_points = Vec::new();
+    for bin in manifest
+        .get("bin")
+        .and_then(toml::Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let name = bin.get("name").and_then(toml::Value::as_str);
+        let path = bin.get("path").and_then(toml::Value::as_str);
+        match (name, path) {
+            (_, Some(path)) => entry_points.push(path.to_string()),
+            (Some(name), None) => entry_points.push(format!("src/bin/{name}.rs")),
+            (None, None) => {}
+        }
+    }
+    if let Some(path) = manifest
+        .get("lib")
+        .and_then(|lib| lib.get("path"))
+        .and_then(toml::Value::as_str)
+    {
+        entry_points.push(path.to_string());
+    }
+
+    let mut dependencies = cargo_dependencies(manifest.get("dependencies"));
+    // Workspace roots declare shared dependencies here instead.
+    dependencies.extend(cargo_dependencies(
+        manifest
+            .get("workspace")
+            .and_then(|w| w.get("dependencies")),
+    ));
+
+    Some(ProjectManifest {
+        file: "Cargo.toml",
+        name: string("name"),
+        version: string("version"),
+        scripts: Vec::new(),
+        dependencies,
+        dev_dependencies: cargo_dependencies(manifest.get("dev-dependencies")),
+        entry_points,
+    })
+}
+
+/// Split a PEP 508 requirement like `fastapi[all]>=0.110` into name and
+/// version specifier.
+fn pep508(requirement: &str) -> Dependency {
+    let requirement = requirement.split(';').next().unwrap_or_default().trim();
+    let end = requirement
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+        .unwrap_or(requirement.len());
+    let rest = requirement[end..].trim_start();
+    let rest = match rest.strip_prefix('[') {
+        Some(extras) => extras.split_once(']').map_or("", |(_, tail)| tail).trim(),
+        None => rest,
+    };
+    Dependency {
+        name: requirement[..end].to_ascii_lowercase(),
+        version: if rest.is_empty() { "*" } else { rest }.to_string(),
+    }
+}
+
+/// Parse `pyproject.toml` (PEP 621 `[project]` or Poetry). Returns `None`
+/// for invalid TOML.
+pub fn parse_pyproject(text: &str) -> Option<ProjectManifest> {
+    let manifest: toml::Table = toml::from_str(text).ok()?;
+    let project = manifest.get("project");
+    let poetry = manifest.get("tool").and_then(|tool| tool.get("poetry"));
+    let string = |key: &str| {
+        project
+            .or(poetry)
+            .and_then(|p| p.get(key))
+            .and_then(toml::Value::as_str)
+            .map(str::to_string)
+    };
+
+    let mut dependencies: Vec<Dependency> = project
+        .and_then(|p| p.get("dependencies"))
+        .and_then(toml::Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(toml::Value::as_str)
+        .map(pep508)
+        .collect();
+    dependencies.extend(
+        cargo_dependencies(poetry.and_then(|p| p.get("dependencies")))
+            .into_iter()
+            .filter(|dep| dep.name != "python"),
+    );
+
+    let mut dev_dependencies: Vec<Dependency> = project
+        .and_then(|p| p.get("optional-dependencies"))
+        .and_then(toml::Value::as_table)
+        .into_iter()
+        .flat_map(|groups| groups.values())
+        .filter_map(toml::Value::as_array)
+        .flatten()
+        .filter_map(toml::Value::as_str)
+        .map(pep508)
+        .collect();
+    dev_dependencies.extend(cargo_dependencies(
+        poetry
+            .and_then(|p| p.get("group"))
+            .and_then(|g| g.get("dev"))
+            .and_then(|dev| dev.get("dependencies")),
+    ));
+
+    let scripts: Vec<(String, String)> = project
+        .and_then(|p| p.get("scripts"))
+        .or_else(|| poetry.and_then(|p| p.get("scripts")))
+        .and_then(toml::Value::as_table)
+        .map(|scripts| {
+            scripts
+                .iter()
+                .map(|(name, target)| {
+                    (
+                        name.clone(),
+                        target.as_str().unwrap_or_default().to_string(),
+                    )
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Some(ProjectManifest {
+        file: "pyproject.toml",
+        name: string("name"),
+        version: string("version"),
+        entry_points: scripts.iter().map(|(_, target)| target.clone()).collect(),
+        scripts,
+        dependencies,
+        dev_dependencies,
+    })
+}
+
+/// Parse the manifest named `file`.
+pub fn parse_manifest(file: &str, text: &str) -> Option<ProjectManifest> {
+    match file {
+        "package.json" => parse_package_json(text),
+        "Cargo.toml" => parse_cargo_toml(text),
+        "pyproject.toml" => parse_pyproject(text),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn package_json_reports_framework_and_entries() {
+        let manifest = parse_package_json(
+            r#"{
+                "name": "shop",
+                "main": "dist/index.js",
+                "bin": {"shop": "bin/cli.js"},
+                "scripts": {"dev": "next dev"},
+                "dependencies": {"next": "14.2.3", "react": "^18.3.1"},
+                "devDependencies": {"typescript": "^5.4.0", "tailwindcss": "^3.4.1"}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(manifest.name.as_deref(), Some("shop"));
+        assert_eq!(
+            manifest.stack().as_deref(),
+            Some("Next.js 14.2.3 (React, TypeScript, Tailwind CSS)")
+        );
+        assert_eq!(manifest.entry_points, ["dist/index.js", "bin/cli.js"]);
+        assert_eq!(
+            manifest.scripts,
+            [("dev".to_string(), "next dev".to_string())]
+        );
+    }
+
+    #[test]
+    fn cargo_toml_reads_versions_bins_and_workspace_deps() {
+        let manifest = parse_cargo_toml(
+            r#"
+            [package]
+            name = "api"
+            version = "0.1.0"
+
+            [[bin]]
+            name = "worker"
+
+            [dependencies]
+            axum = "0.7"
+            tokio = { version = "1", features = ["full"] }
+            shared = { path = "../shared" }
+
+            [dev-dependencies]
+            tempfile = "3"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(manifest.stack().as_deref(), Some("Axum 0.7 (Tokio)"));
+        assert_eq!(manifest.entry_points, ["src/bin/worker.rs"]);
+        assert!(manifest.dependencies.contains(&Dependency {
+            name: "shared".into(),
+            version: "path:../shared".into()
+        }));
+        assert_eq!(manifest.dev_dependencies.len(), 1);
+    }
+
+    #[test]
+    fn pyproject_supports_pep621_and_poetry() {
+        let pep621 = parse_pyproject(
+            r#"
+            [project]
+            name = "svc"
+            dependencies = ["FastAPI[all]>=0.110", "pydantic==2.7.0 ; python_version >= '3.9'"]
+            [project.scripts]
+            svc = "svc.main:run"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            pep621.stack().as_deref(),
+            Some("FastAPI >=0.110 (Pydantic)")
+        );
+        assert_eq!(pep621.entry_points, ["svc.main:run"]);
+
+        let poetry = parse_pyproject(
+            r#"
+            [tool.poetry]
+            name = "site"
+            [tool.poetry.dependencies]
+            python = "^3.11"
+            django = "^5.0"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(poetry.name.as_deref(), Some("site"));
+        assert_eq!(poetry.stack().as_deref(), Some("Django ^5.0"));
+        assert_eq!(poetry.dependencies.len(), 1);
+    }
+}
@@ -0,0 +1,128 @@
+//! Sandbox template registry: the image and prebuilt project of each
+//! project template (stack).
+//!
+//! The template `setup_project` scaffolds (or `sandbox_create` starts) picks
+//! the sandbox setup automatically. Container-based providers start from the
+//! template's `image`; by default the stack's official base image, replaced
+//! through `[zerobuild.templates.<name>]` with one that has the
+//! dependencies pre-installed. A template's `prebuilt_dir` is a project with
+//! its dependencies already installed that `setup_project` copies instead of
+//! running the generator, so the following install has little left to do.
+
+use crate::config::SandboxTemplateConfig;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Base image of each template when none is configured.
+const BASE_IMAGES: &[(&str, &str)] = &[
+    ("nextjs", "node:22-bookworm"),
+    ("vite-react", "node:22-bookworm"),
+    ("static", "node:22-bookworm-slim"),
+    ("node-api", "node:22-bookworm-slim"),
+    ("fastapi", "python:3.12-slim"),
+    ("rust", "rust:1-bookworm"),
+];
+
+/// Image used for an unknown or empty template.
+pub const DEFAULT_IMAGE: &str = "node:22-bookworm";
+
+/// Sandbox setup of one template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SandboxTemplate {
+    pub image: String,
+    pub prebuilt_dir: Option<PathBuf>,
+}
+
+/// Templates by name, built-in images with `[zerobuild.templates]` applied.
+#[derive(Debug, Clone)]
+pub struct TemplateRegistry {
+    templates: HashMap<String, SandboxTemplate>,
+}
+
+impl Default for TemplateRegistry {
+    fn default() -> Self {
+        Self::from_config(&HashMap::new())
+    }
+}
+
+impl TemplateRegistry {
+    pub fn from_config(config: &HashMap<String, SandboxTemplateConfig>) -> Self {
+        let mut templates: HashMap<String, SandboxTemplate> = BASE_IMAGES
+            .iter()
+            .map(|(name, image)| {
+                (
+                    (*name).to_string(),
+                    SandboxTemplate {
+                        image: (*image).to_string(),
+                        prebuilt_dir: None,
+                    },
+                )
+            })
+            .collect();
+        for (name, configured) in config {
+            let template = templates
+                .entry(name.clone())
+                .or_insert_with(|| SandboxTemplate {
+                    image: DEFAULT_IMAGE.to_string(),
+                    prebuilt_dir: None,
+                });
+            if !configured.image.trim().is_empty() {
+                template.image = configured.image.trim().to_string();
+            }
+            let prebuilt_dir = configured.prebuilt_dir.trim();
+            if !prebuilt_dir.is_empty() {
+                template.prebuilt_dir = Some(PathBuf::from(prebuilt_dir));
+            }
+        }
+        Self { templates }
+    }
+
+    pub fn get(&self, template: &str) -> Option<&SandboxTemplate> {
+        self.templates.get(template)
+    }
+
+    /// Image a sandbox for `template` starts from.
+    pub fn image(&self, template: &str) -> &str {
+        self.get(template)
+            .map_or(DEFAULT_IMAGE, |template| template.image.as_str())
+    }
+
+    /// Prebuilt project of `template`, if one is configured.
+    pub fn prebuilt_dir(&self, template: &str) -> Option<&Path> {
+        self.get(template)?.prebuilt_dir.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_project_template_has_a_base_image() {
+        let registry = TemplateRegistry::default();
+        for (name, _) in crate::sandbox::PROJECT_TEMPLATES {
+            assert!(registry.get(name).is_some(), "{name}");
+            assert!(registry.prebuilt_dir(name).is_none());
+        }
+        assert_eq!(registry.image("fastapi"), "python:3.12-slim");
+        assert_eq!(registry.image("unknown"), DEFAULT_IMAGE);
+    }
+
+    #[test]
+    fn configured_templates_replace_the_built_in_image() {
+        let config = HashMap::from([(
+            "nextjs".to_string(),
+            SandboxTemplateConfig {
+                image: "ghcr.io/acme/next-prebuilt:15".into(),
+                prebuilt_dir: "/opt/prebuilt/nextjs".into(),
+            },
+        )]);
+        let registry = TemplateRegistry::from_config(&config);
+        assert_eq!(registry.image("nextjs"), "ghcr.io/acme/next-prebuilt:15");
+        assert_eq!(
+            registry.prebuilt_dir("nextjs"),
+            Some(Path::new("/opt/prebuilt/nextjs"))
+        );
+        assert_eq!(registry.image("rust"), "rust:1-bookworm");
+    }
+}
@@ -1,5 +1,6 @@
 //! Docker sandbox (container isolation)
 
+use crate::runtime::platform;
 use crate::security::traits::Sandbox;
 use std::process::Command;
 
@@ -72,6 +73,10 @@ impl Sandbox for DockerSandbox {
             "--network",
             "none",
         ]);
+        // Run the image variant native to the host instead of an emulated one.
+        docker_cmd
+            .arg("--platform")
+            .arg(platform::resolve_platform(""));
         docker_cmd.arg(&self.image);
         docker_cmd.arg(&program);
         docker_cmd.args(&args);
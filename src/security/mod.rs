@@ -25,12 +25,17 @@ pub mod detect;
 pub mod docker;
 #[cfg(target_os = "linux")]
 pub mod firejail;
+pub mod guardrails;
 #[cfg(feature = "sandbox-landlock")]
 pub mod landlock;
+pub mod moderation;
 pub mod pairing;
 pub mod policy;
+pub mod redaction;
 pub mod secrets;
+pub mod sops;
 pub mod traits;
+pub mod users;
 
 #[allow(unused_imports)]
 pub use audit::{AuditEvent, AuditEventType, AuditLogger};
@@ -0,0 +1,377 @@
+//! Moderation of inbound requests (`[moderation]`), for bots open to the
+//! public.
+//!
+//! Channel messages and gateway-hosted channel messages are screened before
+//! the agent sees them, so nothing that scores at or above its threshold can
+//! lead to a sandbox or GitHub action. Scoring is pluggable through
+//! [`Moderator`]:
+//!
+//! - [`LocalModerator`] matches built-in patterns per category plus the
+//!   configured `blocked_patterns`, in-process and without network access.
+//! - [`OpenAiModerator`] asks an OpenAI-compatible `/moderations` endpoint
+//!   for category scores.
+//!
+//! Every flagged request, blocked or not, is written to the audit log with
+//! its sender, categories and scores.
+
+use super::audit::{AuditEvent, AuditEventType, AuditLogger};
+use crate::config::{AuditConfig, ModerationAction, ModerationConfig, ModerationProvider};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Category of the configured `blocked_patterns`.
+pub const CUSTOM_CATEGORY: &str = "custom";
+
+/// Patterns the local classifier flags, by category.
+const BUILTIN_PATTERNS: &[(&str, &str)] = &[
+    (
+        "illicit",
+        r"(?i)\b(ransomware|keylogger|credit card skimmer|carding (site|shop)|botnet c2|crypto ?drainer|wallet drainer)\b",
+    ),
+    (
+        "illicit",
+        r"(?i)\b(phishing (page|site|kit)|fake (bank )?login page|steal(s|ing)? (passwords|credentials|cookies|seed phrases))\b",
+    ),
+    (
+        "illicit/violent",
+        r"(?i)\b(make|build|buy)\b.{0,30}\b(pipe bomb|explosives?|untraceable gun|ghost gun)\b",
+    ),
+    (
+        "sexual/minors",
+        r"(?i)\b(child|underage|minor)\b.{0,20}\b(porn|sexual|nude)",
+    ),
+    (
+        "harassment/threatening",
+        r"(?i)\b(doxx?(ing)?|swat(ting)?)\b.{0,40}\b(him|her|them|address|home)\b",
+    ),
+];
+
+/// Scores an inbound request per category.
+#[async_trait]
+pub trait Moderator: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Scores (`0.0`–`1.0`) of `text` by category; categories that don't
+    /// apply may be missing.
+    async fn scores(&self, text: &str) -> Result<HashMap<String, f64>>;
+}
+
+/// In-process classifier: a category scores `1.0` when one of its patterns
+/// matches.
+pub struct LocalModerator {
+    rules: Vec<(String, Regex)>,
+}
+
+impl LocalModerator {
+    /// The built-in patterns plus `blocked_patterns` as [`CUSTOM_CATEGORY`].
+    pub fn new(blocked_patterns: &[String]) -> Result<Self> {
+        let mut rules = Vec::new();
+        for (category, pattern) in BUILTIN_PATTERNS {
+            rules.push(((*category).to_string(), Regex::new(pattern)?));
+        }
+        for pattern in blocked_patterns {
+            let regex = Regex::new(pattern)
+                .with_context(|| format!("invalid moderation pattern '{pattern}'"))?;
+            rules.push((CUSTOM_CATEGORY.to_string(), regex));
+        }
+        Ok(Self { rules })
+    }
+}
+
+#[async_trait]
+impl Moderator for LocalModerator {
+    fn name(&self) -> &str {
+        "local"
+    }
+
+    async fn scores(&self, text: &str) -> Result<HashMap<String, f64>> {
+        Ok(self
+            .rules
+            .iter()
+            .filter(|(_, regex)| regex.is_match(text))
+            .map(|(category, _)| (category.clone(), 1.0))
+            .collect())
+    }
+}
+
+/// Classifier backed by an OpenAI-compatible `/moderations` endpoint.
+pub struct OpenAiModerator {
+    api_url: String,
+    api_key: String,
+    model: String,
+}
+
+impl OpenAiModerator {
+    pub fn new(api_url: &str, api_key: &str, model: &str) -> Self {
+        Self {
+            api_url: api_url.trim_end_matches('/').to_string(),
+            api_key: api_key.to_string(),
+            model: model.to_string(),
+        }
+    }
+}
+
+/// Category scores of the first result of a `/moderations` response.
+fn parse_openai_scores(body: &serde_json::Value) -> Result<HashMap<String, f64>> {
+    let scores = body["results"][0]["category_scores"]
+        .as_object()
+        .context("moderation response has no category_scores")?;
+    Ok(scores
+        .iter()
+        .filter_map(|(category, score)| Some((category.clone(), score.as_f64()?)))
+        .collect())
+}
+
+#[async_trait]
+impl Moderator for OpenAiModerator {
+    fn name(&self) -> &str {
+        "openai"
+    }
+
+    async fn scores(&self, text: &str) -> Result<HashMap<String, f64>> {
+        let resp = crate::config::build_runtime_proxy_client("moderation.openai")
+            .post(format!("{}/moderations", self.api_url))
+            .bearer_auth(&self.api_key)
+            .timeout(std::time::Duration::from_secs(15))
+            .json(&serde_json::json!({ "model": self.model, "input": text }))
+            .send()
+            .await
+            .context("moderation request failed")?;
+        let status = resp.status();
+        let body: serde_json::Value = resp.json().await.unwrap_or_default();
+        if !status.is_success() {
+            anyhow::bail!("moderation API returned {status}: {body}");
+        }
+        parse_openai_scores(&body)
+    }
+}
+
+/// Categories of a request at or above their thresholds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModerationVerdict {
+    /// `(category, score)`, highest score first.
+    pub flagged: Vec<(String, f64)>,
+}
+
+impl ModerationVerdict {
+    fn categories(&self) -> String {
+        self.flagged
+            .iter()
+            .map(|(category, score)| format!("{category} ({score:.2})"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Moderator with the `[moderation]` thresholds, action and audit log.
+pub struct Moderation {
+    moderator: Box<dyn Moderator>,
+    threshold: f64,
+    category_thresholds: HashMap<String, f64>,
+    action: ModerationAction,
+    fail_open: bool,
+    audit: Option<AuditLogger>,
+}
+
+impl Moderation {
+    /// Build the configured moderator. Flagged requests are audited under
+    /// `zerobuild_dir` when it is given.
+    pub fn from_config(config: &ModerationConfig, zerobuild_dir: Option<&Path>) -> Result<Self> {
+        let moderator: Box<dyn Moderator> = match config.provider {
+            ModerationProvider::Local => Box::new(LocalModerator::new(&config.blocked_patterns)?),
+            ModerationProvider::OpenAi => {
+                let api_key = config
+                    .api_key
+                    .clone()
+                    .filter(|key| !key.trim().is_empty())
+                    .or_else(|| std::env::var("OPENAI_API_KEY").ok())
+                    .context("moderation.api_key or OPENAI_API_KEY is required")?;
+                Box::new(OpenAiModerator::new(
+                    &config.api_url,
+                    &api_key,
+                    &config.model,
+                ))
+            }
+        };
+        let mut moderation = Self::with_moderator(moderator, config);
+        if let Some(dir) = zerobuild_dir {
+            moderation.audit = AuditLogger::new(AuditConfig::default(), dir.to_path_buf()).ok();
+        }
+        Ok(moderation)
+    }
+
+    /// `moderator` with the thresholds and action of `config`, unaudited.
+    pub fn with_moderator(moderator: Box<dyn Moderator>, config: &ModerationConfig) -> Self {
+        Self {
+            moderator,
+            threshold: config.threshold,
+            category_thresholds: config.category_thresholds.clone(),
+            action: config.action,
+            fail_open: config.fail_open,
+            audit: None,
+        }
+    }
+
+    fn threshold(&self, category: &str) -> f64 {
+        self.category_thresholds
+            .get(category)
+            .copied()
+            .unwrap_or(self.threshold)
+    }
+
+    /// Categories of `text` at or above their thresholds, if any.
+    pub async fn classify(&self, text: &str) -> Result<Option<ModerationVerdict>> {
+        let mut flagged = self
+            .moderator
+            .scores(text)
+            .await?
+            .into_iter()
+            .filter(|(category, score)| *score >= self.threshold(category))
+            .collect::<Vec<_>>();
+        flagged.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        Ok((!flagged.is_empty()).then_some(ModerationVerdict { flagged }))
+    }
+
+    /// Screen `text` sent by `sender` over `channel`. Returns the refusal to
+    /// send back when the request must not reach the agent.
+    pub async fn screen(&self, channel: &str, sender: &str, text: &str) -> Option<String> {
+        if text.trim().is_empty() {
+            return None;
+        }
+        let verdict = match self.classify(text).await {
+            Ok(Some(verdict)) => verdict,
+            Ok(None) => return None,
+            Err(e) => {
+                tracing::warn!(
+                    moderator = self.moderator.name(),
+                    "Moderation failed: {e:#}"
+                );
+                return (!self.fail_open).then(|| {
+                    "Your request could not be screened right now; please try again later."
+                        .to_string()
+                });
+            }
+        };
+
+        let blocked = self.action == ModerationAction::Block;
+        self.record(channel, sender, text, &verdict, blocked);
+        blocked.then(|| {
+            "Sorry, I can't help with this request: it was flagged by content moderation."
+                .to_string()
+        })
+    }
+
+    fn record(
+        &self,
+        channel: &str,
+        sender: &str,
+        text: &str,
+        verdict: &ModerationVerdict,
+        blocked: bool,
+    ) {
+        let categories = verdict.categories();
+        tracing::warn!(
+            channel,
+            sender,
+            blocked,
+            moderator = self.moderator.name(),
+            "Request flagged by moderation: {categories}"
+        );
+        let Some(audit) = &self.audit else {
+            return;
+        };
+        let preview = crate::util::truncate_with_ellipsis(text, 200);
+        let mut event = AuditEvent::new(AuditEventType::PolicyViolation)
+            .with_actor(channel.to_string(), Some(sender.to_string()), None)
+            .with_action(
+                format!("moderation: {preview}"),
+                categories,
+                false,
+                !blocked,
+            );
+        event.security.policy_violation = true;
+        if let Err(e) = audit.log(&event) {
+            tracing::warn!("Failed to write moderation audit entry: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FixedModerator(HashMap<String, f64>);
+
+    #[async_trait]
+    impl Moderator for FixedModerator {
+        fn name(&self) -> &str {
+            "fixed"
+        }
+
+        async fn scores(&self, _text: &str) -> Result<HashMap<String, f64>> {
+            Ok(self.0.clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn local_moderator_flags_builtin_and_custom_patterns() {
+        let config = ModerationConfig {
+            blocked_patterns: vec![r"(?i)\bcasino\b".into()],
+            ..ModerationConfig::default()
+        };
+        let moderation = Moderation::from_config(&config, None).unwrap();
+        let verdict = moderation
+            .classify("Build me a phishing page for my bank")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(verdict.flagged[0].0, "illicit");
+        assert!(moderation
+            .screen("telegram", "42", "An online casino landing page")
+            .await
+            .unwrap()
+            .contains("flagged"));
+        assert!(moderation
+            .screen("telegram", "42", "A todo app with dark mode")
+            .await
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn thresholds_apply_per_category_and_flag_only_audits() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let scores = HashMap::from([("violence".to_string(), 0.6), ("hate".to_string(), 0.4)]);
+        let mut config = ModerationConfig {
+            threshold: 0.5,
+            ..ModerationConfig::default()
+        };
+        config.category_thresholds.insert("violence".into(), 0.9);
+        let moderation =
+            Moderation::with_moderator(Box::new(FixedModerator(scores.clone())), &config);
+        assert_eq!(moderation.classify("text").await.unwrap(), None);
+
+        config.category_thresholds.clear();
+        config.action = ModerationAction::Flag;
+        let mut moderation = Moderation::with_moderator(Box::new(FixedModerator(scores)), &config);
+        moderation.audit = AuditLogger::new(AuditConfig::default(), tmp.path().to_path_buf()).ok();
+        assert!(moderation.screen("discord", "7", "text").await.is_none());
+        let log =
+            std::fs::read_to_string(tmp.path().join(AuditConfig::default().log_path)).unwrap();
+        assert!(log.contains("violence (0.60)"));
+        assert!(log.contains("\"user_id\":\"7\""));
+    }
+
+    #[test]
+    fn parses_openai_category_scores() {
+        let body = serde_json::json!({ "results": [{
+            "flagged": true,
+            "category_scores": { "harassment": 0.91, "violence": 0.02 }
+        }] });
+        let scores = parse_openai_scores(&body).unwrap();
+        assert_eq!(scores["harassment"], 0.91);
+        assert!(parse_openai_scores(&serde_json::json!({})).is_err());
+    }
+}
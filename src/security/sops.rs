@@ -0,0 +1,499 @@
+//! SOPS-encrypted TOML documents (`config.toml`, `.zerobuild.toml`).
+//!
+//! A document with a `[sops]` table carries values encrypted the way SOPS
+//! encrypts YAML and JSON trees: every value is
+//! `ENC[AES256_GCM,data:…,iv:…,tag:…,type:…]`, encrypted with a random data
+//! key and authenticated with its key path (`a:b:`) as additional data. The
+//! data key is age-encrypted once per recipient under `[[sops.age]]`, and
+//! `sops.mac` (the SHA-512 of all values, encrypted with the data key)
+//! detects removed, added or reordered values. Only the holder of an age
+//! identity can read the document, so configs with provider keys can be
+//! committed to a private repository.
+//!
+//! Identities are found where `sops` looks for them: `SOPS_AGE_KEY`,
+//! `SOPS_AGE_KEY_FILE`, else `$XDG_CONFIG_HOME/sops/age/keys.txt`
+//! (`~/.config/sops/age/keys.txt`). `zerobuild config encrypt` and
+//! `zerobuild config decrypt` write such documents, since `sops` itself
+//! doesn't read TOML.
+
+use aes_gcm::aead::consts::U32;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::aes::Aes256;
+use aes_gcm::AesGcm;
+use anyhow::{bail, Context, Result};
+use base64::engine::general_purpose::STANDARD as B64;
+use base64::Engine;
+use regex::Regex;
+use sha2::{Digest, Sha512};
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Table holding the encryption metadata.
+pub const METADATA_KEY: &str = "sops";
+
+/// SOPS version written to new documents.
+const SOPS_VERSION: &str = "3.9.0";
+
+/// AES-256-GCM with SOPS's 32-byte nonces.
+type SopsCipher = AesGcm<Aes256, U32>;
+
+/// Whether the parsed document carries SOPS metadata.
+pub fn is_encrypted(document: &toml::Table) -> bool {
+    document
+        .get(METADATA_KEY)
+        .and_then(toml::Value::as_table)
+        .is_some_and(|sops| sops.contains_key("age"))
+}
+
+/// Parse `text` and decrypt it with the identities of [`load_identities`].
+/// `Ok(None)` when the document isn't encrypted (or isn't valid TOML, which
+/// the caller's own parsing reports).
+pub fn decrypt_document(text: &str) -> Result<Option<toml::Table>> {
+    let Ok(document) = toml::from_str::<toml::Table>(text) else {
+        return Ok(None);
+    };
+    if !is_encrypted(&document) {
+        return Ok(None);
+    }
+    let identities = load_identities()?;
+    decrypt(document, &identities).map(Some)
+}
+
+/// age identities from `SOPS_AGE_KEY`, `SOPS_AGE_KEY_FILE` and the default
+/// SOPS key file.
+pub fn load_identities() -> Result<Vec<Box<dyn age::Identity>>> {
+    let mut identities: Vec<Box<dyn age::Identity>> = Vec::new();
+    if let Ok(keys) = std::env::var("SOPS_AGE_KEY") {
+        identities.extend(parse_identities(&keys).context("Invalid SOPS_AGE_KEY")?);
+    }
+    let file = std::env::var_os("SOPS_AGE_KEY_FILE")
+        .map(PathBuf::from)
+        .or_else(default_key_file);
+    if let Some(file) = file.filter(|file| file.exists()) {
+        let keys = std::fs::read_to_string(&file)
+            .with_context(|| format!("Failed to read age key file {}", file.display()))?;
+        identities.extend(
+            parse_identities(&keys)
+                .with_context(|| format!("Invalid age key file {}", file.display()))?,
+        );
+    }
+    if identities.is_empty() {
+        bail!(
+            "The document is SOPS-encrypted but no age key was found; set SOPS_AGE_KEY or \
+             SOPS_AGE_KEY_FILE, or put the key in ~/.config/sops/age/keys.txt"
+        );
+    }
+    Ok(identities)
+}
+
+/// `$XDG_CONFIG_HOME/sops/age/keys.txt`, else `~/.config/sops/age/keys.txt`.
+fn default_key_file() -> Option<PathBuf> {
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| directories::BaseDirs::new().map(|dirs| dirs.home_dir().join(".config")))?;
+    Some(config_dir.join("sops").join("age").join("keys.txt"))
+}
+
+/// `AGE-SECRET-KEY-1…` lines of a key file; `#` lines are comments.
+fn parse_identities(keys: &str) -> Result<Vec<Box<dyn age::Identity>>> {
+    keys.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            age::x25519::Identity::from_str(line)
+                .map(|identity| Box::new(identity) as Box<dyn age::Identity>)
+                .map_err(|e| anyhow::anyhow!("{e}"))
+        })
+        .collect()
+}
+
+/// Decrypt the values of `document` and drop its metadata.
+pub fn decrypt(
+    mut document: toml::Table,
+    identities: &[Box<dyn age::Identity>],
+) -> Result<toml::Table> {
+    let metadata = match document.remove(METADATA_KEY) {
+        Some(toml::Value::Table(metadata)) => metadata,
+        _ => bail!("Missing [{METADATA_KEY}] metadata"),
+    };
+    let key = data_key(&metadata, identities)?;
+    let mut hash = Sha512::new();
+    let mut path = Vec::new();
+    for (name, value) in &mut document {
+        path.push(name.clone());
+        decrypt_value(value, &key, &mut path, &mut hash)?;
+        path.pop();
+    }
+
+    let lastmodified = metadata
+        .get("lastmodified")
+        .and_then(toml::Value::as_str)
+        .unwrap_or_default();
+    let mac = metadata
+        .get("mac")
+        .and_then(toml::Value::as_str)
+        .context("Missing sops.mac")?;
+    let expected = decrypt_string(mac, &key, lastmodified).context("Invalid sops.mac")?;
+    let actual = hex::encode_upper(hash.finalize());
+    if !expected.eq_ignore_ascii_case(&actual) {
+        bail!("sops.mac doesn't match the values; the document was changed after encryption");
+    }
+    Ok(document)
+}
+
+/// Encrypt the values of `document` whose key path matches
+/// `encrypted_regex` (all values without one) for `recipients`, and add the
+/// metadata.
+pub fn encrypt(
+    mut document: toml::Table,
+    recipients: &[age::x25519::Recipient],
+    encrypted_regex: Option<&str>,
+) -> Result<toml::Table> {
+    if recipients.is_empty() {
+        bail!("At least one age recipient is required");
+    }
+    if is_encrypted(&document) {
+        bail!("The document is already encrypted");
+    }
+    let regex = encrypted_regex
+        .map(Regex::new)
+        .transpose()
+        .context("Invalid encrypted_regex")?;
+    let key = SopsCipher::generate_key(&mut OsRng).to_vec();
+
+    let mut hash = Sha512::new();
+    let mut path = Vec::new();
+    for (name, value) in &mut document {
+        path.push(name.clone());
+        encrypt_value(value, &key, &mut path, regex.as_ref(), &mut hash)?;
+        path.pop();
+    }
+
+    let lastmodified = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    let mac = encrypt_bytes(
+        hex::encode_upper(hash.finalize()).as_bytes(),
+        "str",
+        &key,
+        &lastmodified,
+    )?;
+    let mut metadata = toml::Table::new();
+    let mut age_keys = Vec::new();
+    for recipient in recipients {
+        let mut entry = toml::Table::new();
+        entry.insert("recipient".into(), recipient.to_string().into());
+        entry.insert("enc".into(), wrap_key(&key, recipient)?.into());
+        age_keys.push(toml::Value::Table(entry));
+    }
+    metadata.insert("age".into(), toml::Value::Array(age_keys));
+    metadata.insert("lastmodified".into(), lastmodified.into());
+    metadata.insert("mac".into(), mac.into());
+    if let Some(regex) = encrypted_regex {
+        metadata.insert("encrypted_regex".into(), regex.into());
+    }
+    metadata.insert("version".into(), SOPS_VERSION.into());
+    document.insert(METADATA_KEY.into(), toml::Value::Table(metadata));
+    Ok(document)
+}
+
+/// The data key, unwrapped by the first identity that can.
+fn data_key(metadata: &toml::Table, identities: &[Box<dyn age::Identity>]) -> Result<Vec<u8>> {
+    let entries = metadata
+        .get("age")
+        .and_then(toml::Value::as_array)
+        .context("Missing sops.age")?;
+    for entry in entries {
+        let Some(enc) = entry.get("enc").and_then(toml::Value::as_str) else {
+            continue;
+        };
+        let reader = age::armor::ArmoredReader::new(enc.trim().as_bytes());
+        let Ok(decryptor) = age::Decryptor::new(reader) else {
+            continue;
+        };
+        let Ok(mut plaintext) = decryptor.decrypt(identities.iter().map(|i| i.as_ref())) else {
+            continue;
+        };
+        let mut key = Vec::new();
+        plaintext
+            .read_to_end(&mut key)
+            .context("Failed to read the SOPS data key")?;
+        if key.len() != 32 {
+            bail!("Invalid SOPS data key length {}", key.len());
+        }
+        return Ok(key);
+    }
+    let recipients: Vec<&str> = entries
+        .iter()
+        .filter_map(|entry| entry.get("recipient").and_then(toml::Value::as_str))
+        .collect();
+    bail!(
+        "None of the age keys can decrypt the document; it is encrypted for {}",
+        recipients.join(", ")
+    )
+}
+
+/// The data key, age-encrypted and armored for `recipient`.
+fn wrap_key(key: &[u8], recipient: &age::x25519::Recipient) -> Result<String> {
+    let encryptor =
+        age::Encryptor::with_recipients(std::iter::once(recipient as &dyn age::Recipient))
+            .map_err(|e| anyhow::anyhow!("{e}"))?;
+    let mut armored = Vec::new();
+    let writer =
+        age::armor::ArmoredWriter::wrap_output(&mut armored, age::armor::Format::AsciiArmor)?;
+    let mut stream = encryptor.wrap_output(writer)?;
+    stream.write_all(key)?;
+    stream.finish()?.finish()?;
+    Ok(String::from_utf8(armored)?)
+}
+
+/// Path as SOPS authenticates it: every key followed by `:`.
+fn additional_data(path: &[String]) -> String {
+    path.iter().fold(String::new(), |mut aad, key| {
+        aad.push_str(key);
+        aad.push(':');
+        aad
+    })
+}
+
+/// What SOPS hashes into the MAC for a plain value.
+fn mac_bytes(value: &toml::Value) -> Option<String> {
+    match value {
+        toml::Value::String(s) => Some(s.clone()),
+        toml::Value::Integer(i) => Some(i.to_string()),
+        toml::Value::Float(f) => Some(f.to_string()),
+        toml::Value::Boolean(b) => Some(if *b { "True" } else { "False" }.to_string()),
+        toml::Value::Datetime(d) => Some(d.to_string()),
+        toml::Value::Array(_) | toml::Value::Table(_) => None,
+    }
+}
+
+fn decrypt_value(
+    value: &mut toml::Value,
+    key: &[u8],
+    path: &mut Vec<String>,
+    hash: &mut Sha512,
+) -> Result<()> {
+    match value {
+        toml::Value::Table(table) => {
+            for (name, value) in table {
+                path.push(name.clone());
+                decrypt_value(value, key, path, hash)?;
+                path.pop();
+            }
+        }
+        toml::Value::Array(items) => {
+            for item in items {
+                decrypt_value(item, key, path, hash)?;
+            }
+        }
+        toml::Value::String(text) if text.starts_with("ENC[") => {
+            let aad = additional_data(path);
+            *value = decrypt_typed(text, key, &aad)
+                .with_context(|| format!("Failed to decrypt {}", path.join(".")))?;
+            hash.update(mac_bytes(value).unwrap_or_default());
+        }
+        plain => hash.update(mac_bytes(plain).unwrap_or_default()),
+    }
+    Ok(())
+}
+
+fn encrypt_value(
+    value: &mut toml::Value,
+    key: &[u8],
+    path: &mut Vec<String>,
+    regex: Option<&Regex>,
+    hash: &mut Sha512,
+) -> Result<()> {
+    match value {
+        toml::Value::Table(table) => {
+            for (name, value) in table {
+                path.push(name.clone());
+                encrypt_value(value, key, path, regex, hash)?;
+                path.pop();
+            }
+        }
+        toml::Value::Array(items) => {
+            for item in items {
+                encrypt_value(item, key, path, regex, hash)?;
+            }
+        }
+        leaf => {
+            let plain = mac_bytes(leaf).unwrap_or_default();
+            hash.update(&plain);
+            let kind = match leaf {
+                toml::Value::String(_) => "str",
+                toml::Value::Integer(_) => "int",
+                toml::Value::Float(_) => "float",
+                toml::Value::Boolean(_) => "bool",
+                _ => return Ok(()),
+            };
+            if regex.is_none_or(|regex| path.iter().any(|key| regex.is_match(key))) {
+                let aad = additional_data(path);
+                *leaf = encrypt_bytes(plain.as_bytes(), kind, key, &aad)?.into();
+            }
+        }
+    }
+    Ok(())
+}
+
+fn encrypt_bytes(plain: &[u8], kind: &str, key: &[u8], aad: &str) -> Result<String> {
+    let cipher = SopsCipher::new_from_slice(key).context("Invalid SOPS data key")?;
+    let iv = SopsCipher::generate_nonce(&mut OsRng);
+    let sealed = cipher
+        .encrypt(
+            &iv,
+            Payload {
+                msg: plain,
+                aad: aad.as_bytes(),
+            },
+        )
+        .map_err(|_| anyhow::anyhow!("AES-GCM encryption failed"))?;
+    let (data, tag) = sealed.split_at(sealed.len() - 16);
+    Ok(format!(
+        "ENC[AES256_GCM,data:{},iv:{},tag:{},type:{kind}]",
+        B64.encode(data),
+        B64.encode(iv),
+        B64.encode(tag)
+    ))
+}
+
+/// The fields of an `ENC[…]` value.
+struct EncValue {
+    data: Vec<u8>,
+    iv: Vec<u8>,
+    tag: Vec<u8>,
+    kind: String,
+}
+
+fn parse_enc(text: &str) -> Result<EncValue> {
+    let inner = text
+        .strip_prefix("ENC[AES256_GCM,")
+        .and_then(|rest| rest.strip_suffix(']'))
+        .context("Unsupported encrypted value (expected ENC[AES256_GCM,...])")?;
+    let (mut data, mut iv, mut tag, mut kind) = (None, None, None, None);
+    for field in inner.split(',') {
+        match field.split_once(':') {
+            Some(("data", v)) => data = Some(B64.decode(v)?),
+            Some(("iv", v)) => iv = Some(B64.decode(v)?),
+            Some(("tag", v)) => tag = Some(B64.decode(v)?),
+            Some(("type", v)) => kind = Some(v.to_string()),
+            _ => bail!("Malformed encrypted value"),
+        }
+    }
+    match (data, iv, tag, kind) {
+        (Some(data), Some(iv), Some(tag), Some(kind)) if iv.len() == 32 && tag.len() == 16 => {
+            Ok(EncValue {
+                data,
+                iv,
+                tag,
+                kind,
+            })
+        }
+        _ => bail!("Malformed encrypted value"),
+    }
+}
+
+fn decrypt_string(text: &str, key: &[u8], aad: &str) -> Result<String> {
+    open(&parse_enc(text)?, key, aad)
+}
+
+fn open(value: &EncValue, key: &[u8], aad: &str) -> Result<String> {
+    let cipher = SopsCipher::new_from_slice(key).context("Invalid SOPS data key")?;
+    let sealed = [value.data.as_slice(), &value.tag].concat();
+    let plain = cipher
+        .decrypt(
+            value.iv.as_slice().into(),
+            Payload {
+                msg: &sealed,
+                aad: aad.as_bytes(),
+            },
+        )
+        .map_err(|_| anyhow::anyhow!("authentication failed (wrong key or tampered value)"))?;
+    String::from_utf8(plain).context("Decrypted value is not UTF-8")
+}
+
+fn decrypt_typed(text: &str, key: &[u8], aad: &str) -> Result<toml::Value> {
+    let value = parse_enc(text)?;
+    let plain = open(&value, key, aad)?;
+    Ok(match value.kind.as_str() {
+        "str" | "bytes" => plain.into(),
+        "int" => plain.parse::<i64>().context("Invalid int")?.into(),
+        "float" => plain.parse::<f64>().context("Invalid float")?.into(),
+        "bool" => plain.eq_ignore_ascii_case("true").into(),
+        other => bail!("Unsupported value type '{other}'"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document() -> toml::Table {
+        toml::from_str(
+            r#"
+            api_key = "sk-live-123"
+            default_temperature = 0.7
+            [gateway]
+            port = 3000
+            require_pairing = true
+            [zerobuild.vercel]
+            token = "vercel-token"
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn round_trips_through_age_recipients() {
+        let identity = age::x25519::Identity::generate();
+        let encrypted = encrypt(document(), &[identity.to_public()], None).unwrap();
+        assert!(is_encrypted(&encrypted));
+        let text = toml::to_string_pretty(&encrypted).unwrap();
+        assert!(!text.contains("sk-live-123"));
+        assert!(text.contains("type:bool"));
+
+        let identities: Vec<Box<dyn age::Identity>> = vec![Box::new(identity)];
+        let decrypted = decrypt(toml::from_str(&text).unwrap(), &identities).unwrap();
+        assert_eq!(decrypted, document());
+
+        let other: Vec<Box<dyn age::Identity>> = vec![Box::new(age::x25519::Identity::generate())];
+        let err = decrypt(toml::from_str(&text).unwrap(), &other).unwrap_err();
+        assert!(err.to_string().contains("None of the age keys"), "{err}");
+    }
+
+    #[test]
+    fn encrypted_regex_limits_encryption_and_tampering_is_detected() {
+        let identity = age::x25519::Identity::generate();
+        let mut encrypted = encrypt(
+            document(),
+            &[identity.to_public()],
+            Some("^(api_key|token)$"),
+        )
+        .unwrap();
+        assert_eq!(encrypted["gateway"]["port"].as_integer(), Some(3000));
+        assert!(encrypted["api_key"].as_str().unwrap().starts_with("ENC["));
+        assert!(encrypted["zerobuild"]["vercel"]["token"]
+            .as_str()
+            .unwrap()
+            .starts_with("ENC["));
+        let identities: Vec<Box<dyn age::Identity>> = vec![Box::new(identity)];
+
+        // A value moved to another key fails its authentication.
+        let mut moved = encrypted.clone();
+        let token = moved["zerobuild"]["vercel"]["token"].clone();
+        moved.insert("api_key".into(), token);
+        let err = decrypt(moved, &identities).unwrap_err();
+        assert!(
+            format!("{err:#}").contains("Failed to decrypt api_key"),
+            "{err:#}"
+        );
+
+        // A changed plain value no longer matches the MAC.
+        encrypted["gateway"]
+            .as_table_mut()
+            .unwrap()
+            .insert("port".into(), 4000.into());
+        let err = decrypt(encrypted, &identities).unwrap_err();
+        assert!(err.to_string().contains("sops.mac"), "{err}");
+    }
+}
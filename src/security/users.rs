@@ -0,0 +1,409 @@
+//! User roles and daily quotas (`[users]`) for bots open to a community.
+//!
+//! Channel and gateway messages run inside [`with_user`], which names the
+//! sender as `<channel>:<sender>`. Every tool call then passes through
+//! [`QuotaTool`]:
+//!
+//! - **Viewers** may not use build, sandbox or deploy tools.
+//! - **Builders** may, up to their daily builds, sandbox minutes and deploys.
+//! - **Admins** have no limits.
+//!
+//! Calls made outside a user scope (CLI, cron, queue workers) are not
+//! metered, nor are the tool calls a metered call makes itself (the sandbox
+//! steps of a `factory_build`), whose run time the outer call already
+//! counts. Roles and quota overrides set with the `/user` chat command and
+//! the usage counters live in [`crate::store::users`].
+
+use crate::config::{UserRole, UsersConfig};
+use crate::store::{self, users::Quota};
+use crate::tools::traits::{Tool, ToolResult};
+use async_trait::async_trait;
+use std::fmt::Write;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Tools that publish a project.
+const DEPLOY_TOOLS: &[&str] = &["github_push", "ship_it"];
+
+/// Tools besides `sandbox_*` whose run time counts as sandbox minutes.
+const SANDBOX_TOOLS: &[&str] = &["factory_build", "setup_project", "ship_it"];
+
+/// What to do after a call is refused for quota.
+const QUOTA_HINT: &str =
+    "Tell the user; the quota resets at midnight UTC, or an admin can raise it.";
+
+tokio::task_local! {
+    static CURRENT_USER: String;
+    static IN_METERED_CALL: ();
+}
+
+/// Run `fut` on behalf of `user` (`<channel>:<sender>`).
+pub async fn with_user<F: Future>(user: String, fut: F) -> F::Output {
+    CURRENT_USER.scope(user, fut).await
+}
+
+/// User set by the enclosing [`with_user`], if any.
+pub fn current_user() -> Option<String> {
+    CURRENT_USER
+        .try_with(Clone::clone)
+        .ok()
+        .filter(|user| !user.is_empty())
+}
+
+/// Whether a call of `tool_name` with `args` starts a build.
+fn is_build(tool_name: &str, args: &serde_json::Value) -> bool {
+    tool_name == "factory_build"
+        || (tool_name == "build_queue"
+            && args.get("action").and_then(serde_json::Value::as_str) == Some("enqueue"))
+}
+
+fn is_deploy(tool_name: &str) -> bool {
+    DEPLOY_TOOLS.contains(&tool_name)
+}
+
+fn uses_sandbox(tool_name: &str) -> bool {
+    tool_name.starts_with("sandbox_") || SANDBOX_TOOLS.contains(&tool_name)
+}
+
+/// Whether a call of `tool_name` with `args` counts toward any quota.
+fn is_metered(tool_name: &str, args: &serde_json::Value) -> bool {
+    is_build(tool_name, args) || is_deploy(tool_name) || uses_sandbox(tool_name)
+}
+
+/// Roles and quotas of the configured users.
+pub struct UserPolicy {
+    config: UsersConfig,
+    store: store::SharedStore,
+}
+
+/// Builds and deploys a metered call took from its user's quotas before it
+/// ran; given back by [`UserPolicy::charge`] if the call fails.
+pub struct Reservation {
+    day: String,
+    held: store::users::Usage,
+}
+
+impl UserPolicy {
+    pub fn new(config: &UsersConfig, db_path: impl Into<PathBuf>) -> Self {
+        Self {
+            config: config.clone(),
+            store: store::SharedStore::new(db_path),
+        }
+    }
+
+    fn record(&self, user: &str) -> Option<store::users::UserRecord> {
+        self.store
+            .with(|conn| store::users::get(conn, user))
+            .unwrap_or_else(|e| {
+                tracing::warn!("Failed to load user {user}: {e}");
+                None
+            })
+    }
+
+    /// `user`'s role: configured admins first, then the role an admin
+    /// assigned, then `default_role`.
+    pub fn role(&self, user: &str) -> UserRole {
+        if self.config.admins.iter().any(|admin| admin == user) {
+            return UserRole::Admin;
+        }
+        self.record(user)
+            .and_then(|record| record.role.as_deref().and_then(UserRole::parse))
+            .unwrap_or(self.config.default_role)
+    }
+
+    /// `user`'s daily limit of `quota`; `None` = unlimited.
+    pub fn limit(&self, user: &str, quota: Quota) -> Option<u32> {
+        if self.role(user) == UserRole::Admin {
+            return None;
+        }
+        let defaults = &self.config.quotas;
+        let limit = self
+            .record(user)
+            .and_then(|record| record.quota(quota))
+            .unwrap_or(match quota {
+                Quota::Builds => defaults.builds_per_day,
+                Quota::SandboxMinutes => defaults.sandbox_minutes_per_day,
+                Quota::Deploys => defaults.deploys_per_day,
+            });
+        (limit > 0).then_some(limit)
+    }
+
+    fn usage(&self, user: &str, day: &str) -> store::users::Usage {
+        self.store
+            .with(|conn| store::users::usage(conn, user, day))
+            .unwrap_or_default()
+    }
+
+    /// Check `user` may call `tool_name` with `args` and, in the same step,
+    /// take the build or deploy it would use from their quotas. `Err` holds
+    /// why the call is refused.
+    pub fn reserve(
+        &self,
+        user: &str,
+        tool_name: &str,
+        args: &serde_json::Value,
+    ) -> Result<Reservation, String> {
+        let build = is_build(tool_name, args);
+        let deploy = is_deploy(tool_name);
+        let sandbox = uses_sandbox(tool_name);
+        if self.role(user) == UserRole::Viewer {
+            return Err(format!(
+                "'{tool_name}' is not available to viewers; ask an admin for the builder role"
+            ));
+        }
+
+        let day = store::users::today();
+        let held = store::users::Usage {
+            builds: u32::from(build),
+            sandbox_secs: 0,
+            deploys: u32::from(deploy),
+        };
+        let limit = |applies: bool, quota| applies.then(|| self.limit(user, quota)).flatten();
+        let limits = store::users::Limits {
+            builds: limit(build, Quota::Builds),
+            sandbox_minutes: limit(sandbox, Quota::SandboxMinutes),
+            deploys: limit(deploy, Quota::Deploys),
+        };
+        match self
+            .store
+            .with(|conn| store::users::try_add_usage(conn, user, &day, &held, &limits))
+        {
+            Ok(true) => Ok(Reservation { day, held }),
+            Ok(false) => Err(self.quota_reached(user, &day, &limits)),
+            Err(e) => {
+                tracing::warn!("Failed to check quotas of {user}: {e}");
+                Ok(Reservation {
+                    day,
+                    held: store::users::Usage::default(),
+                })
+            }
+        }
+    }
+
+    fn quota_reached(&self, user: &str, day: &str, limits: &store::users::Limits) -> String {
+        let usage = self.usage(user, day);
+        [
+            (limits.builds, u64::from(usage.builds), "builds"),
+            (
+                limits.sandbox_minutes,
+                usage.sandbox_secs / 60,
+                "sandbox minutes",
+            ),
+            (limits.deploys, u64::from(usage.deploys), "deploys"),
+        ]
+        .into_iter()
+        .find_map(|(limit, used, label)| {
+            let limit = limit?;
+            (used >= u64::from(limit))
+                .then(|| format!("Daily quota reached: {used}/{limit} {label} used today"))
+        })
+        .unwrap_or_else(|| "Daily quota reached".to_string())
+    }
+
+    /// Settle a finished call of `tool_name` against `user`'s quotas.
+    /// Builds and deploys count when they succeed, sandbox time always.
+    pub fn charge(
+        &self,
+        user: &str,
+        reservation: &Reservation,
+        tool_name: &str,
+        succeeded: bool,
+        secs: u64,
+    ) {
+        let used = store::users::Usage {
+            sandbox_secs: if uses_sandbox(tool_name) { secs } else { 0 },
+            ..store::users::Usage::default()
+        };
+        let day = &reservation.day;
+        let result = self.store.with(|conn| {
+            if used != store::users::Usage::default() {
+                store::users::add_usage(conn, user, day, &used)?;
+            }
+            if !succeeded {
+                store::users::refund_usage(conn, user, day, &reservation.held)?;
+            }
+            Ok(())
+        });
+        if let Err(e) = result {
+            tracing::warn!("Failed to record usage of {user}: {e}");
+        }
+    }
+
+    /// `user`'s role, and today's usage against their limits.
+    pub fn describe(&self, user: &str) -> String {
+        let usage = self.usage(user, &store::users::today());
+        let mut out = format!("User `{user}`: {}\nToday:\n", self.role(user).as_str());
+        for (quota, used, label) in [
+            (Quota::Builds, u64::from(usage.builds), "Builds"),
+            (
+                Quota::SandboxMinutes,
+                usage.sandbox_secs / 60,
+                "Sandbox minutes",
+            ),
+            (Quota::Deploys, u64::from(usage.deploys), "Deploys"),
+        ] {
+            let limit = self
+                .limit(user, quota)
+                .map_or_else(|| "unlimited".to_string(), |limit| limit.to_string());
+            let _ = writeln!(out, "- {label}: {used} / {limit}");
+        }
+        out
+    }
+}
+
+/// A tool whose calls are checked against and counted toward the quotas
+/// of the user in scope.
+pub struct QuotaTool {
+    inner: Arc<dyn Tool>,
+    policy: Arc<UserPolicy>,
+}
+
+impl QuotaTool {
+    pub fn new(inner: Arc<dyn Tool>, policy: Arc<UserPolicy>) -> Self {
+        Self { inner, policy }
+    }
+}
+
+#[async_trait]
+impl Tool for QuotaTool {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn description(&self) -> &str {
+        self.inner.description()
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        self.inner.parameters_schema()
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let name = self.inner.name();
+        let user = current_user().filter(|_| IN_METERED_CALL.try_with(|()| ()).is_err());
+        let Some(user) = user.filter(|_| is_metered(name, &args)) else {
+            return self.inner.execute(args).await;
+        };
+        let reservation = match self.policy.reserve(&user, name, &args) {
+            Ok(reservation) => reservation,
+            Err(reason) => {
+                return Ok(ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some(reason),
+                    error_code: None,
+                    error_hint: Some(QUOTA_HINT.to_string()),
+                });
+            }
+        };
+
+        let started = Instant::now();
+        let result = IN_METERED_CALL.scope((), self.inner.execute(args)).await;
+        let succeeded = result.as_ref().is_ok_and(|result| result.success);
+        self.policy.charge(
+            &user,
+            &reservation,
+            name,
+            succeeded,
+            u64::try_from(started.elapsed().as_millis().div_ceil(1000)).unwrap_or(u64::MAX),
+        );
+        result
+    }
+}
+
+/// Wrap every tool in `tools` with the user quota policy.
+pub fn meter_tools(tools: Vec<Arc<dyn Tool>>, policy: &Arc<UserPolicy>) -> Vec<Arc<dyn Tool>> {
+    tools
+        .into_iter()
+        .map(|tool| Arc::new(QuotaTool::new(tool, Arc::clone(policy))) as Arc<dyn Tool>)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::UserQuotasConfig;
+    use serde_json::json;
+
+    struct NamedTool(&'static str);
+
+    #[async_trait]
+    impl Tool for NamedTool {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn description(&self) -> &str {
+            "test"
+        }
+
+        fn parameters_schema(&self) -> serde_json::Value {
+            json!({"type": "object"})
+        }
+
+        async fn execute(&self, _args: serde_json::Value) -> anyhow::Result<ToolResult> {
+            Ok(ToolResult {
+                success: true,
+                output: "done".into(),
+                error: None,
+                error_code: None,
+                error_hint: None,
+            })
+        }
+    }
+
+    fn policy(tmp: &tempfile::TempDir) -> Arc<UserPolicy> {
+        let config = UsersConfig {
+            enabled: true,
+            default_role: UserRole::Builder,
+            admins: vec!["telegram:1".into()],
+            quotas: UserQuotasConfig {
+                builds_per_day: 5,
+                sandbox_minutes_per_day: 0,
+                deploys_per_day: 1,
+            },
+        };
+        Arc::new(UserPolicy::new(&config, tmp.path().join("zerobuild.db")))
+    }
+
+    #[tokio::test]
+    async fn quotas_apply_to_the_user_in_scope() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let policy = policy(&tmp);
+        let push = QuotaTool::new(Arc::new(NamedTool("github_push")), Arc::clone(&policy));
+
+        let first = with_user("discord:2".into(), push.execute(json!({}))).await;
+        assert!(first.unwrap().success);
+        let second = with_user("discord:2".into(), push.execute(json!({})))
+            .await
+            .unwrap();
+        assert!(!second.success);
+        assert!(second.error.unwrap().contains("1/1 deploys"));
+
+        // Admins and calls outside a user scope are not limited.
+        let admin = with_user("telegram:1".into(), push.execute(json!({}))).await;
+        assert!(admin.unwrap().success);
+        assert!(push.execute(json!({})).await.unwrap().success);
+        assert!(policy.describe("discord:2").contains("Deploys: 1 / 1"));
+    }
+
+    #[tokio::test]
+    async fn viewers_only_get_tools_that_do_not_build_or_deploy() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let policy = policy(&tmp);
+        let conn = store::init_db(&tmp.path().join("zerobuild.db")).unwrap();
+        store::users::set_role(&conn, "discord:3", Some("viewer")).unwrap();
+        assert_eq!(policy.role("discord:3"), UserRole::Viewer);
+
+        let build = QuotaTool::new(Arc::new(NamedTool("sandbox_create")), Arc::clone(&policy));
+        let refused = with_user("discord:3".into(), build.execute(json!({})))
+            .await
+            .unwrap();
+        assert!(refused.error.unwrap().contains("not available to viewers"));
+        let read = QuotaTool::new(Arc::new(NamedTool("memory_recall")), policy);
+        let allowed = with_user("discord:3".into(), read.execute(json!({}))).await;
+        assert!(allowed.unwrap().success);
+    }
+}
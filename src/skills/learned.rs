@@ -0,0 +1,261 @@
+//! Learned skills — reusable procedures distilled from successful builds.
+//!
+//! Unlike workspace skills (`SKILL.md` / `SKILL.toml` on disk), learned skills
+//! are written by the agent itself after a multi-step procedure succeeds
+//! (e.g. "set up Next.js + Tailwind + shadcn"). They are stored as JSON in the
+//! global [`Memory`] under the `skill` category with a `skill:` key prefix,
+//! recalled by similarity against new requests, and injected into the prompt
+//! as few-shot guidance so the agent does not repeat the same trial-and-error.
+//!
+//! Steps may reference parameters with `{name}` placeholders; the declared
+//! parameter list tells the model which values it must fill in.
+
+use crate::memory::{Memory, MemoryCategory, MemoryEntry};
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+/// Memory key prefix for learned skill entries.
+pub const LEARNED_SKILL_KEY_PREFIX: &str = "skill:";
+
+/// Memory category name used for learned skill entries.
+pub const LEARNED_SKILL_CATEGORY: &str = "skill";
+
+/// Maximum number of learned skills injected into a single turn.
+pub const LEARNED_SKILLS_CONTEXT_LIMIT: usize = 2;
+
+/// Maximum number of steps accepted for one learned skill.
+const MAX_STEPS: usize = 50;
+
+/// A parameterized procedure the agent has learned.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LearnedSkill {
+    /// Short human-readable name (e.g. "Next.js + Tailwind + shadcn setup").
+    pub name: String,
+    /// When to apply this skill.
+    pub description: String,
+    /// Parameter names referenced in steps as `{name}` placeholders.
+    #[serde(default)]
+    pub parameters: Vec<String>,
+    /// Ordered steps (tool calls or commands) that make up the procedure.
+    pub steps: Vec<String>,
+    /// Free-form tags that improve recall (e.g. "nextjs", "tailwind").
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl LearnedSkill {
+    /// Memory key for this skill, derived from a slug of its name.
+    pub fn key(&self) -> String {
+        format!("{LEARNED_SKILL_KEY_PREFIX}{}", slugify(&self.name))
+    }
+
+    /// Validate the skill before it is persisted.
+    pub fn validate(&self) -> Result<()> {
+        if slugify(&self.name).is_empty() {
+            bail!("Skill name must contain at least one alphanumeric character");
+        }
+        if self.steps.iter().all(|step| step.trim().is_empty()) {
+            bail!("Skill must contain at least one step");
+        }
+        if self.steps.len() > MAX_STEPS {
+            bail!("Skill has {} steps (max {MAX_STEPS})", self.steps.len());
+        }
+        for param in &self.parameters {
+            let placeholder = format!("{{{param}}}");
+            if !self.steps.iter().any(|step| step.contains(&placeholder)) {
+                bail!("Parameter '{param}' is not referenced as {placeholder} in any step");
+            }
+        }
+        Ok(())
+    }
+
+    /// Text stored as the memory content. The leading description and tags
+    /// keep lexical recall useful; the JSON payload follows on the last line.
+    fn to_memory_content(&self) -> Result<String> {
+        let json = serde_json::to_string(self)?;
+        Ok(format!(
+            "{} — {} [{}]\n{json}",
+            self.name,
+            self.description,
+            self.tags.join(", ")
+        ))
+    }
+
+    /// Parse a learned skill back out of a memory entry.
+    pub fn from_memory_entry(entry: &MemoryEntry) -> Option<Self> {
+        if !is_learned_skill_key(&entry.key) {
+            return None;
+        }
+        let json = entry.content.lines().last()?;
+        serde_json::from_str(json).ok()
+    }
+}
+
+/// Whether a memory key belongs to a learned skill.
+pub fn is_learned_skill_key(key: &str) -> bool {
+    key.starts_with(LEARNED_SKILL_KEY_PREFIX)
+}
+
+/// Persist a learned skill in memory, replacing any skill with the same name.
+/// Returns the memory key used.
+pub async fn save_learned_skill(memory: &dyn Memory, skill: &LearnedSkill) -> Result<String> {
+    skill.validate()?;
+    let key = skill.key();
+    memory
+        .store(
+            &key,
+            &skill.to_memory_content()?,
+            MemoryCategory::Custom(LEARNED_SKILL_CATEGORY.to_string()),
+            None,
+        )
+        .await?;
+    Ok(key)
+}
+
+/// Recall the learned skills most similar to `query`.
+pub async fn recall_learned_skills(
+    memory: &dyn Memory,
+    query: &str,
+    limit: usize,
+    min_relevance_score: f64,
+) -> Result<Vec<LearnedSkill>> {
+    // Skills share the recall index with ordinary memories, so over-fetch
+    // and keep only skill entries.
+    let entries = memory
+        .recall(query, limit.saturating_mul(4).max(8), None)
+        .await?;
+    Ok(entries
+        .iter()
+        .filter(|entry| entry.score.map_or(true, |s| s >= min_relevance_score))
+        .filter_map(LearnedSkill::from_memory_entry)
+        .take(limit)
+        .collect())
+}
+
+/// Render learned skills as a few-shot guidance block for the prompt.
+pub fn learned_skills_to_prompt(skills: &[LearnedSkill]) -> String {
+    if skills.is_empty() {
+        return String::new();
+    }
+
+    let mut out = String::from(
+        "[Learned skills]\nProcedures that worked before for similar requests. \
+         Reuse them, substituting {parameters}, instead of rediscovering the steps.\n",
+    );
+    for skill in skills {
+        let _ = writeln!(out, "\n## {}", skill.name);
+        let _ = writeln!(out, "When: {}", skill.description);
+        if !skill.parameters.is_empty() {
+            let _ = writeln!(out, "Parameters: {}", skill.parameters.join(", "));
+        }
+        for (i, step) in skill.steps.iter().enumerate() {
+            let _ = writeln!(out, "{}. {}", i + 1, step);
+        }
+    }
+    out.push('\n');
+    out
+}
+
+/// Recall and render learned skills for `user_msg`. Returns an empty string
+/// when nothing relevant is stored or recall fails.
+pub async fn build_learned_skills_context(
+    memory: &dyn Memory,
+    user_msg: &str,
+    min_relevance_score: f64,
+) -> String {
+    match recall_learned_skills(
+        memory,
+        user_msg,
+        LEARNED_SKILLS_CONTEXT_LIMIT,
+        min_relevance_score,
+    )
+    .await
+    {
+        Ok(skills) => learned_skills_to_prompt(&skills),
+        Err(e) => {
+            tracing::debug!("Learned skill recall failed: {e}");
+            String::new()
+        }
+    }
+}
+
+fn slugify(name: &str) -> String {
+    let mut slug = String::new();
+    for ch in name.trim().chars() {
+        if ch.is_ascii_alphanumeric() {
+            slug.push(ch.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::SqliteMemory;
+    use tempfile::TempDir;
+
+    fn nextjs_skill() -> LearnedSkill {
+        LearnedSkill {
+            name: "Next.js + Tailwind setup".into(),
+            description: "Scaffold a Next.js app with Tailwind CSS".into(),
+            parameters: vec!["project_name".into()],
+            steps: vec![
+                "sandbox_run_command: npx create-next-app@latest {project_name} --tailwind --yes"
+                    .into(),
+                "sandbox_run_command: npm run build (workdir {project_name})".into(),
+            ],
+            tags: vec!["nextjs".into(), "tailwind".into()],
+        }
+    }
+
+    #[test]
+    fn key_is_slugified() {
+        assert_eq!(nextjs_skill().key(), "skill:next-js-tailwind-setup");
+    }
+
+    #[test]
+    fn validate_rejects_unreferenced_parameter() {
+        let mut skill = nextjs_skill();
+        skill.parameters.push("port".into());
+        let err = skill.validate().unwrap_err();
+        assert!(err.to_string().contains("port"));
+    }
+
+    #[test]
+    fn validate_rejects_empty_steps() {
+        let mut skill = nextjs_skill();
+        skill.parameters.clear();
+        skill.steps = vec!["  ".into()];
+        assert!(skill.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn save_and_recall_roundtrip() {
+        let tmp = TempDir::new().unwrap();
+        let mem = SqliteMemory::new(tmp.path()).unwrap();
+        mem.store("user_lang", "Prefers Rust", MemoryCategory::Core, None)
+            .await
+            .unwrap();
+
+        let key = save_learned_skill(&mem, &nextjs_skill()).await.unwrap();
+        assert!(is_learned_skill_key(&key));
+
+        let skills = recall_learned_skills(&mem, "tailwind nextjs", 2, 0.0)
+            .await
+            .unwrap();
+        assert_eq!(skills, vec![nextjs_skill()]);
+    }
+
+    #[test]
+    fn prompt_lists_parameters_and_numbered_steps() {
+        let prompt = learned_skills_to_prompt(&[nextjs_skill()]);
+        assert!(prompt.starts_with("[Learned skills]"));
+        assert!(prompt.contains("Parameters: project_name"));
+        assert!(prompt.contains("1. sandbox_run_command"));
+        assert!(learned_skills_to_prompt(&[]).is_empty());
+    }
+}
@@ -1,3 +1,5 @@
+pub mod learned;
+
 use anyhow::Result;
 use directories::UserDirs;
 use serde::{Deserialize, Serialize};
@@ -0,0 +1,184 @@
+//! Index of build artifacts (see [`crate::artifacts`]).
+//!
+//! The bytes live in the configured storage backend; this table records each
+//! object's key, project, kind, and size so retention and usage reports never
+//! have to list the backend.
+
+use anyhow::Result;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// One stored artifact.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ArtifactRecord {
+    pub key: String,
+    pub project: String,
+    pub kind: String,
+    pub size: u64,
+    pub created_at: String,
+}
+
+/// Storage used by one project's artifacts.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ProjectUsage {
+    pub project: String,
+    pub count: u64,
+    pub bytes: u64,
+    /// Bytes per artifact kind.
+    pub kinds: BTreeMap<String, u64>,
+    pub oldest: Option<String>,
+}
+
+fn size_to_sql(size: u64) -> i64 {
+    i64::try_from(size).unwrap_or(i64::MAX)
+}
+
+fn size_from_sql(size: i64) -> u64 {
+    u64::try_from(size).unwrap_or(0)
+}
+
+/// Record a stored artifact, replacing any entry with the same key.
+pub fn insert(conn: &Connection, record: &ArtifactRecord) -> Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO artifacts (key, project, kind, size, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            record.key,
+            record.project,
+            record.kind,
+            size_to_sql(record.size),
+            record.created_at
+        ],
+    )?;
+    Ok(())
+}
+
+/// Forget the artifact stored under `key`.
+pub fn remove(conn: &Connection, key: &str) -> Result<()> {
+    conn.execute("DELETE FROM artifacts WHERE key = ?1", params![key])?;
+    Ok(())
+}
+
+fn query(conn: &Connection, sql: &str, param: &str) -> Result<Vec<ArtifactRecord>> {
+    let mut stmt = conn.prepare(sql)?;
+    let rows = stmt.query_map(params![param], |row| {
+        Ok(ArtifactRecord {
+            key: row.get(0)?,
+            project: row.get(1)?,
+            kind: row.get(2)?,
+            size: size_from_sql(row.get(3)?),
+            created_at: row.get(4)?,
+        })
+    })?;
+    Ok(rows.collect::<rusqlite::Result<_>>()?)
+}
+
+/// Artifacts of `project`, oldest first.
+pub fn list(conn: &Connection, project: &str) -> Result<Vec<ArtifactRecord>> {
+    query(
+        conn,
+        "SELECT key, project, kind, size, created_at FROM artifacts
+         WHERE project = ?1 ORDER BY created_at, key",
+        project,
+    )
+}
+
+/// Artifacts created before `cutoff` (RFC 3339), oldest first.
+pub fn created_before(conn: &Connection, cutoff: &str) -> Result<Vec<ArtifactRecord>> {
+    query(
+        conn,
+        "SELECT key, project, kind, size, created_at FROM artifacts
+         WHERE created_at < ?1 ORDER BY created_at, key",
+        cutoff,
+    )
+}
+
+/// Storage used per project, largest first.
+pub fn usage(conn: &Connection) -> Result<Vec<ProjectUsage>> {
+    let mut stmt = conn.prepare(
+        "SELECT project, kind, COUNT(*), SUM(size), MIN(created_at) FROM artifacts
+         GROUP BY project, kind",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, i64>(2)?,
+            row.get::<_, i64>(3)?,
+            row.get::<_, String>(4)?,
+        ))
+    })?;
+
+    let mut projects: BTreeMap<String, ProjectUsage> = BTreeMap::new();
+    for row in rows {
+        let (project, kind, count, bytes, oldest) = row?;
+        let usage = projects
+            .entry(project.clone())
+            .or_insert_with(|| ProjectUsage {
+                project,
+                ..ProjectUsage::default()
+            });
+        usage.count += size_from_sql(count);
+        usage.bytes += size_from_sql(bytes);
+        usage.kinds.insert(kind, size_from_sql(bytes));
+        if usage.oldest.as_ref().is_none_or(|o| oldest < *o) {
+            usage.oldest = Some(oldest);
+        }
+    }
+    let mut usage: Vec<ProjectUsage> = projects.into_values().collect();
+    usage.sort_by_key(|u| std::cmp::Reverse(u.bytes));
+    Ok(usage)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn record(key: &str, project: &str, kind: &str, size: u64, created_at: &str) -> ArtifactRecord {
+        ArtifactRecord {
+            key: key.into(),
+            project: project.into(),
+            kind: kind.into(),
+            size,
+            created_at: created_at.into(),
+        }
+    }
+
+    #[test]
+    fn index_lists_ages_and_sums_artifacts() {
+        let tmp = TempDir::new().unwrap();
+        let conn = super::super::init_db(&tmp.path().join("zerobuild.db")).unwrap();
+        insert(
+            &conn,
+            &record("a/1", "a", "snapshot", 10, "2026-01-01T00:00:00Z"),
+        )
+        .unwrap();
+        insert(&conn, &record("a/2", "a", "log", 5, "2026-02-01T00:00:00Z")).unwrap();
+        insert(
+            &conn,
+            &record("b/1", "b", "log", 30, "2026-03-01T00:00:00Z"),
+        )
+        .unwrap();
+
+        let keys = |records: Vec<ArtifactRecord>| -> Vec<String> {
+            records.into_iter().map(|r| r.key).collect()
+        };
+        assert_eq!(keys(list(&conn, "a").unwrap()), ["a/1", "a/2"]);
+        assert_eq!(
+            keys(created_before(&conn, "2026-02-15T00:00:00Z").unwrap()),
+            ["a/1", "a/2"]
+        );
+
+        let usage = usage(&conn).unwrap();
+        assert_eq!(usage[0].project, "b");
+        assert_eq!(usage[1].count, 2);
+        assert_eq!(usage[1].bytes, 15);
+        assert_eq!(usage[1].kinds["snapshot"], 10);
+        assert_eq!(usage[1].oldest.as_deref(), Some("2026-01-01T00:00:00Z"));
+
+        remove(&conn, "a/1").unwrap();
+        assert_eq!(keys(list(&conn, "a").unwrap()), ["a/2"]);
+    }
+}
@@ -0,0 +1,322 @@
+//! Journal of factory blackboards, for time-travel debugging.
+//!
+//! Every artifact a build publishes on its [`Blackboard`], every read of one
+//! and every phase the workflow enters is stored with a per-build sequence
+//! number. [`state_at`] replays the journal up to any sequence number to
+//! show what the blackboard held at that point, e.g. when the Tester
+//! started. The `blackboard_history` tool reads it. Only the journals of the
+//! most recent [`MAX_BUILDS`] builds are kept.
+//!
+//! [`Blackboard`]: crate::factory::Blackboard
+
+use anyhow::Result;
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Number of builds whose journal is kept.
+pub const MAX_BUILDS: usize = 50;
+
+/// What a journal event records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    /// The workflow entered a phase; `artifact` holds its name.
+    Phase,
+    /// An artifact was published.
+    Publish,
+    /// An artifact was read; `version` is `None` when it wasn't published
+    /// yet.
+    Read,
+}
+
+impl EventKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Phase => "phase",
+            Self::Publish => "publish",
+            Self::Read => "read",
+        }
+    }
+
+    fn parse(kind: &str) -> Self {
+        match kind {
+            "phase" => Self::Phase,
+            "publish" => Self::Publish,
+            _ => Self::Read,
+        }
+    }
+}
+
+/// A recorded blackboard event.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BlackboardEvent {
+    pub seq: i64,
+    pub kind: EventKind,
+    /// Artifact key (e.g. `prd`), or the phase name for [`EventKind::Phase`].
+    pub artifact: String,
+    /// Agent that published the artifact.
+    pub agent: Option<String>,
+    /// Version published or read.
+    pub version: Option<u64>,
+    /// Value published.
+    pub value: Option<Value>,
+    pub created_at: String,
+}
+
+/// A build with a journal.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct JournaledBuild {
+    pub workflow_id: String,
+    pub idea: String,
+    pub events: i64,
+    pub started_at: String,
+}
+
+/// An artifact as the blackboard held it at some sequence number.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ArtifactState {
+    pub value: Value,
+    pub version: u64,
+    pub agent: Option<String>,
+    /// Sequence number of the publish.
+    pub seq: i64,
+}
+
+/// Register build `workflow_id`, dropping the journals of older builds
+/// beyond [`MAX_BUILDS`].
+pub fn start_build(conn: &Connection, workflow_id: &str, idea: &str) -> Result<()> {
+    conn.execute(
+        "INSERT OR IGNORE INTO blackboard_builds (workflow_id, idea, started_at)
+         VALUES (?1, ?2, ?3)",
+        params![workflow_id, idea, Utc::now().to_rfc3339()],
+    )?;
+    conn.execute(
+        "DELETE FROM blackboard_builds WHERE rowid NOT IN
+             (SELECT rowid FROM blackboard_builds ORDER BY rowid DESC LIMIT ?1)",
+        params![i64::try_from(MAX_BUILDS).unwrap_or(i64::MAX)],
+    )?;
+    conn.execute(
+        "DELETE FROM blackboard_events
+         WHERE workflow_id NOT IN (SELECT workflow_id FROM blackboard_builds)",
+        [],
+    )?;
+    Ok(())
+}
+
+/// Append an event to the journal of `workflow_id`, returning its sequence
+/// number.
+pub fn record(
+    conn: &Connection,
+    workflow_id: &str,
+    kind: EventKind,
+    artifact: &str,
+    agent: Option<&str>,
+    version: Option<u64>,
+    value: Option<&Value>,
+) -> Result<i64> {
+    let value = value.map(Value::to_string);
+    let version = version.map(|v| i64::try_from(v).unwrap_or(i64::MAX));
+    Ok(conn.query_row(
+        "INSERT INTO blackboard_events
+             (workflow_id, seq, kind, artifact, agent, version, value, created_at)
+         SELECT ?1, COALESCE(MAX(seq), 0) + 1, ?2, ?3, ?4, ?5, ?6, ?7
+         FROM blackboard_events WHERE workflow_id = ?1
+         RETURNING seq",
+        params![
+            workflow_id,
+            kind.as_str(),
+            artifact,
+            agent,
+            version,
+            value,
+            Utc::now().to_rfc3339()
+        ],
+        |row| row.get(0),
+    )?)
+}
+
+/// The most recent builds with a journal, newest first.
+pub fn builds(conn: &Connection, limit: usize) -> Result<Vec<JournaledBuild>> {
+    let mut stmt = conn.prepare(
+        "SELECT b.workflow_id, b.idea, b.started_at,
+                (SELECT COUNT(*) FROM blackboard_events e WHERE e.workflow_id = b.workflow_id)
+         FROM blackboard_builds b ORDER BY b.rowid DESC LIMIT ?1",
+    )?;
+    let rows = stmt.query_map(params![i64::try_from(limit).unwrap_or(i64::MAX)], |row| {
+        Ok(JournaledBuild {
+            workflow_id: row.get(0)?,
+            idea: row.get(1)?,
+            started_at: row.get(2)?,
+            events: row.get(3)?,
+        })
+    })?;
+    Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+}
+
+/// The build `workflow_id` (or a unique prefix of it).
+pub fn find_build(conn: &Connection, workflow_id: &str) -> Result<Option<JournaledBuild>> {
+    let matches = conn
+        .prepare(
+            "SELECT workflow_id FROM blackboard_builds
+             WHERE substr(workflow_id, 1, length(?1)) = ?1 LIMIT 2",
+        )?
+        .query_map(params![workflow_id], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    let [id] = matches.as_slice() else {
+        if matches.len() > 1 {
+            anyhow::bail!("'{workflow_id}' matches several builds; give more of the ID");
+        }
+        return Ok(None);
+    };
+    Ok(conn
+        .query_row(
+            "SELECT workflow_id, idea, started_at,
+                    (SELECT COUNT(*) FROM blackboard_events WHERE workflow_id = ?1)
+             FROM blackboard_builds WHERE workflow_id = ?1",
+            params![id],
+            |row| {
+                Ok(JournaledBuild {
+                    workflow_id: row.get(0)?,
+                    idea: row.get(1)?,
+                    started_at: row.get(2)?,
+                    events: row.get(3)?,
+                })
+            },
+        )
+        .optional()?)
+}
+
+/// Events of `workflow_id` up to and including `until` (all when `None`),
+/// in order.
+pub fn events(
+    conn: &Connection,
+    workflow_id: &str,
+    until: Option<i64>,
+) -> Result<Vec<BlackboardEvent>> {
+    let mut stmt = conn.prepare(
+        "SELECT seq, kind, artifact, agent, version, value, created_at
+         FROM blackboard_events
+         WHERE workflow_id = ?1 AND (?2 IS NULL OR seq <= ?2)
+         ORDER BY seq",
+    )?;
+    let rows = stmt.query_map(params![workflow_id, until], |row| {
+        let kind: String = row.get(1)?;
+        let version: Option<i64> = row.get(4)?;
+        let value: Option<String> = row.get(5)?;
+        Ok(BlackboardEvent {
+            seq: row.get(0)?,
+            kind: EventKind::parse(&kind),
+            artifact: row.get(2)?,
+            agent: row.get(3)?,
+            version: version.and_then(|v| u64::try_from(v).ok()),
+            value: value.and_then(|v| serde_json::from_str(&v).ok()),
+            created_at: row.get(6)?,
+        })
+    })?;
+    Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+}
+
+/// The artifacts the blackboard of `workflow_id` held right after event
+/// `seq` (at the end when `None`), by artifact key.
+pub fn state_at(
+    conn: &Connection,
+    workflow_id: &str,
+    seq: Option<i64>,
+) -> Result<BTreeMap<String, ArtifactState>> {
+    Ok(replay(&events(conn, workflow_id, seq)?))
+}
+
+/// Apply the publishes of `events` in order.
+pub fn replay(events: &[BlackboardEvent]) -> BTreeMap<String, ArtifactState> {
+    let mut state = BTreeMap::new();
+    for event in events.iter().filter(|e| e.kind == EventKind::Publish) {
+        state.insert(
+            event.artifact.clone(),
+            ArtifactState {
+                value: event.value.clone().unwrap_or(Value::Null),
+                version: event.version.unwrap_or(0),
+                agent: event.agent.clone(),
+                seq: event.seq,
+            },
+        );
+    }
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    #[test]
+    fn replays_the_blackboard_at_any_point() {
+        let tmp = TempDir::new().unwrap();
+        let conn = crate::store::init_db(&tmp.path().join("zerobuild.db")).unwrap();
+        start_build(&conn, "wf-1", "todo app").unwrap();
+        let phase = record(
+            &conn,
+            "wf-1",
+            EventKind::Phase,
+            "analysis",
+            None,
+            None,
+            None,
+        );
+        assert_eq!(phase.unwrap(), 1);
+        record(
+            &conn,
+            "wf-1",
+            EventKind::Publish,
+            "prd",
+            Some("business_analyst"),
+            Some(1),
+            Some(&json!("v1")),
+        )
+        .unwrap();
+        record(&conn, "wf-1", EventKind::Read, "prd", None, Some(1), None).unwrap();
+        let last = record(
+            &conn,
+            "wf-1",
+            EventKind::Publish,
+            "prd",
+            Some("business_analyst"),
+            Some(2),
+            Some(&json!("v2")),
+        )
+        .unwrap();
+        assert_eq!(last, 4);
+        // Another build numbers its events on its own.
+        start_build(&conn, "wf-2", "blog").unwrap();
+        assert_eq!(
+            record(
+                &conn,
+                "wf-2",
+                EventKind::Phase,
+                "analysis",
+                None,
+                None,
+                None
+            )
+            .unwrap(),
+            1
+        );
+
+        assert!(state_at(&conn, "wf-1", Some(1)).unwrap().is_empty());
+        assert_eq!(
+            state_at(&conn, "wf-1", Some(3)).unwrap()["prd"].value,
+            json!("v1")
+        );
+        let end = state_at(&conn, "wf-1", None).unwrap();
+        assert_eq!(end["prd"].version, 2);
+        assert_eq!(end["prd"].agent.as_deref(), Some("business_analyst"));
+
+        let build = find_build(&conn, "wf-1").unwrap().unwrap();
+        assert_eq!((build.idea.as_str(), build.events), ("todo app", 4));
+        assert!(find_build(&conn, "wf-").is_err());
+        assert_eq!(builds(&conn, 10).unwrap()[0].workflow_id, "wf-2");
+    }
+}
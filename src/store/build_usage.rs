@@ -0,0 +1,88 @@
+//! Estimated and actual usage of finished factory builds. The baseline
+//! estimate of each build is kept next to what it used, so later estimates
+//! can be calibrated (see [`crate::factory::estimate`]).
+
+use anyhow::Result;
+use chrono::Utc;
+use rusqlite::{params, Connection};
+
+/// Usage of one factory build.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BuildUsage {
+    /// Uncalibrated token estimate made before the build.
+    pub estimated_tokens: u64,
+    pub actual_tokens: u64,
+    /// Uncalibrated wall time estimate, in seconds.
+    pub estimated_secs: u64,
+    pub actual_secs: u64,
+}
+
+fn to_sql(value: u64) -> i64 {
+    i64::try_from(value).unwrap_or(i64::MAX)
+}
+
+fn from_sql(value: i64) -> u64 {
+    u64::try_from(value).unwrap_or(0)
+}
+
+/// Record a finished build.
+pub fn record(conn: &Connection, usage: &BuildUsage) -> Result<()> {
+    conn.execute(
+        "INSERT INTO build_usage (estimated_tokens, actual_tokens, estimated_secs, actual_secs, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            to_sql(usage.estimated_tokens),
+            to_sql(usage.actual_tokens),
+            to_sql(usage.estimated_secs),
+            to_sql(usage.actual_secs),
+            Utc::now().to_rfc3339()
+        ],
+    )?;
+    Ok(())
+}
+
+/// The `limit` most recent builds, newest first.
+pub fn recent(conn: &Connection, limit: usize) -> Result<Vec<BuildUsage>> {
+    let mut stmt = conn.prepare(
+        "SELECT estimated_tokens, actual_tokens, estimated_secs, actual_secs
+         FROM build_usage ORDER BY id DESC LIMIT ?1",
+    )?;
+    let rows = stmt
+        .query_map(params![limit as i64], |row| {
+            Ok(BuildUsage {
+                estimated_tokens: from_sql(row.get(0)?),
+                actual_tokens: from_sql(row.get(1)?),
+                estimated_secs: from_sql(row.get(2)?),
+                actual_secs: from_sql(row.get(3)?),
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn build_usage_roundtrip() {
+        let tmp = TempDir::new().unwrap();
+        let conn = crate::store::init_db(&tmp.path().join("zerobuild.db")).unwrap();
+        for actual_tokens in [100, 200, 300] {
+            let usage = BuildUsage {
+                estimated_tokens: 150,
+                actual_tokens,
+                estimated_secs: 60,
+                actual_secs: 90,
+            };
+            record(&conn, &usage).unwrap();
+        }
+
+        let recent = recent(&conn, 2).unwrap();
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].actual_tokens, 300);
+        assert_eq!(recent[1].actual_tokens, 200);
+        assert_eq!(recent[0].estimated_secs, 60);
+    }
+}
@@ -0,0 +1,398 @@
+//! Conversation checkpoints: named copies of the project snapshot plus the
+//! working conversation, so a line of work can be branched ("what if we retry
+//! the Developer stage with another model?") without losing the original.
+//!
+//! The channel runtime mirrors each user's working conversation into the
+//! `checkpoint_conversations` table, keyed like [`crate::security::users`]
+//! as `<channel>:<sender>`. A checkpoint captures the conversation of the
+//! user who created it and belongs to that user: only they can list it,
+//! branch from it or switch to it. Switching to a checkpoint writes its files
+//! back into the `snapshots` row (so `sandbox_restore_snapshot` restores that
+//! branch) and queues its conversation in `checkpoint_restores` to replace
+//! the user's live one on their next message.
+
+use crate::providers::ChatMessage;
+use crate::security::redaction::redact;
+use anyhow::{bail, Result};
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Listing entry for a checkpoint (without files or conversation).
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckpointSummary {
+    pub name: String,
+    /// Checkpoint that was active when this one was created.
+    pub parent: Option<String>,
+    pub note: Option<String>,
+    pub file_count: usize,
+    pub message_count: usize,
+    pub created_at: String,
+    pub active: bool,
+}
+
+/// Full checkpoint contents.
+#[derive(Debug, Clone)]
+pub struct Checkpoint {
+    pub name: String,
+    pub parent: Option<String>,
+    pub files: HashMap<String, String>,
+    pub project_type: Option<String>,
+    pub conversation: Vec<ChatMessage>,
+}
+
+/// Replace `user`'s mirrored working conversation. Secrets are redacted
+/// before the turns are stored.
+pub fn save_conversation(conn: &Connection, user: &str, turns: &[ChatMessage]) -> Result<()> {
+    let turns: Vec<ChatMessage> = turns
+        .iter()
+        .map(|turn| ChatMessage {
+            role: turn.role.clone(),
+            content: redact(&turn.content),
+        })
+        .collect();
+    let json = serde_json::to_string(&turns)?;
+    conn.execute(
+        "INSERT INTO checkpoint_conversations (user_id, turns, updated_at)
+         VALUES (?1, ?2, ?3)
+         ON CONFLICT(user_id) DO UPDATE SET
+             turns = excluded.turns,
+             updated_at = excluded.updated_at",
+        params![user, json, Utc::now().to_rfc3339()],
+    )?;
+    Ok(())
+}
+
+/// Load `user`'s mirrored working conversation (empty if none was recorded).
+pub fn load_conversation(conn: &Connection, user: &str) -> Result<Vec<ChatMessage>> {
+    let json: Option<String> = conn
+        .query_row(
+            "SELECT turns FROM checkpoint_conversations WHERE user_id = ?1",
+            params![user],
+            |row| row.get(0),
+        )
+        .optional()?;
+    match json {
+        Some(json) => Ok(serde_json::from_str(&json)?),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Name of the checkpoint `user`'s current line of work descends from.
+pub fn active_checkpoint(conn: &Connection, user: &str) -> Result<Option<String>> {
+    Ok(conn
+        .query_row(
+            "SELECT checkpoint FROM checkpoint_active WHERE user_id = ?1",
+            params![user],
+            |row| row.get(0),
+        )
+        .optional()?)
+}
+
+fn set_active(conn: &Connection, user: &str, active: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO checkpoint_active (user_id, checkpoint)
+         VALUES (?1, ?2)
+         ON CONFLICT(user_id) DO UPDATE SET checkpoint = excluded.checkpoint",
+        params![user, active],
+    )?;
+    Ok(())
+}
+
+/// Capture the current snapshot and `user`'s conversation as checkpoint
+/// `name`.
+///
+/// `user`'s previously active checkpoint becomes its parent and `name`
+/// becomes their active checkpoint.
+pub fn create_checkpoint(
+    conn: &Connection,
+    user: &str,
+    name: &str,
+    note: Option<&str>,
+) -> Result<CheckpointSummary> {
+    let name = name.trim();
+    if name.is_empty() {
+        bail!("Checkpoint name must not be empty");
+    }
+    let exists: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM checkpoints WHERE user_id = ?1 AND name = ?2)",
+        params![user, name],
+        |row| row.get(0),
+    )?;
+    if exists {
+        bail!("Checkpoint '{name}' already exists");
+    }
+
+    let (files, project_type) = super::snapshot::load_snapshot(conn)?.unwrap_or_default();
+    let conversation = load_conversation(conn, user)?;
+    let parent = active_checkpoint(conn, user)?;
+    let created_at = Utc::now().to_rfc3339();
+
+    conn.execute(
+        "INSERT INTO checkpoints
+             (user_id, name, parent, note, files, project_type, conversation, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            user,
+            name,
+            parent,
+            note,
+            serde_json::to_string(&files)?,
+            project_type,
+            serde_json::to_string(&conversation)?,
+            created_at
+        ],
+    )?;
+    set_active(conn, user, name)?;
+
+    Ok(CheckpointSummary {
+        name: name.to_string(),
+        parent,
+        note: note.map(str::to_string),
+        file_count: files.len(),
+        message_count: conversation.len(),
+        created_at,
+        active: true,
+    })
+}
+
+/// `user`'s checkpoints, oldest first.
+pub fn list_checkpoints(conn: &Connection, user: &str) -> Result<Vec<CheckpointSummary>> {
+    let active = active_checkpoint(conn, user)?;
+    let mut stmt = conn.prepare(
+        "SELECT name, parent, note, files, conversation, created_at
+         FROM checkpoints WHERE user_id = ?1 ORDER BY created_at, rowid",
+    )?;
+    let rows = stmt.query_map(params![user], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, Option<String>>(1)?,
+            row.get::<_, Option<String>>(2)?,
+            row.get::<_, String>(3)?,
+            row.get::<_, String>(4)?,
+            row.get::<_, String>(5)?,
+        ))
+    })?;
+
+    let mut out = Vec::new();
+    for row in rows {
+        let (name, parent, note, files, conversation, created_at) = row?;
+        let files: HashMap<String, String> = serde_json::from_str(&files)?;
+        let conversation: Vec<ChatMessage> = serde_json::from_str(&conversation)?;
+        out.push(CheckpointSummary {
+            active: active.as_deref() == Some(name.as_str()),
+            name,
+            parent,
+            note,
+            file_count: files.len(),
+            message_count: conversation.len(),
+            created_at,
+        });
+    }
+    Ok(out)
+}
+
+/// Load `user`'s checkpoint `name`, if it exists.
+pub fn load_checkpoint(conn: &Connection, user: &str, name: &str) -> Result<Option<Checkpoint>> {
+    let row = conn
+        .query_row(
+            "SELECT parent, files, project_type, conversation FROM checkpoints
+             WHERE user_id = ?1 AND name = ?2",
+            params![user, name],
+            |row| {
+                Ok((
+                    row.get::<_, Option<String>>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                    row.get::<_, String>(3)?,
+                ))
+            },
+        )
+        .optional()?;
+    let Some((parent, files, project_type, conversation)) = row else {
+        return Ok(None);
+    };
+    Ok(Some(Checkpoint {
+        name: name.to_string(),
+        parent,
+        files: serde_json::from_str(&files)?,
+        project_type,
+        conversation: serde_json::from_str(&conversation)?,
+    }))
+}
+
+/// Make `user`'s checkpoint `name` their current line of work.
+///
+/// Its files replace the saved snapshot immediately; its conversation
+/// replaces `user`'s live conversation on their next channel message.
+/// Other users' conversations are left alone.
+pub fn switch_checkpoint(conn: &Connection, user: &str, name: &str) -> Result<Checkpoint> {
+    let Some(checkpoint) = load_checkpoint(conn, user, name)? else {
+        bail!("Checkpoint '{name}' not found");
+    };
+    super::snapshot::save_snapshot(conn, &checkpoint.files, checkpoint.project_type.as_deref())?;
+    save_conversation(conn, user, &checkpoint.conversation)?;
+    set_active(conn, user, name)?;
+    conn.execute(
+        "INSERT INTO checkpoint_restores (user_id, checkpoint)
+         VALUES (?1, ?2)
+         ON CONFLICT(user_id) DO UPDATE SET checkpoint = excluded.checkpoint",
+        params![user, name],
+    )?;
+    Ok(checkpoint)
+}
+
+/// Take the conversation [`switch_checkpoint`] queued for `user`, if any.
+/// Returns `None` when that user has no switch pending.
+///
+/// The conversation is read from the checkpoint itself rather than the
+/// mirror, since the turn that performed the switch is mirrored after it.
+pub fn take_pending_conversation(
+    conn: &Connection,
+    user: &str,
+) -> Result<Option<Vec<ChatMessage>>> {
+    let pending: Option<String> = conn
+        .query_row(
+            "DELETE FROM checkpoint_restores WHERE user_id = ?1 RETURNING checkpoint",
+            params![user],
+            |row| row.get(0),
+        )
+        .optional()?;
+    let Some(name) = pending else {
+        return Ok(None);
+    };
+    let conversation = load_checkpoint(conn, user, &name)?
+        .map(|checkpoint| checkpoint.conversation)
+        .unwrap_or_default();
+    save_conversation(conn, user, &conversation)?;
+    Ok(Some(conversation))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn db() -> (TempDir, Connection) {
+        let tmp = TempDir::new().unwrap();
+        let conn = super::super::init_db(&tmp.path().join("zerobuild.db")).unwrap();
+        (tmp, conn)
+    }
+
+    fn files(content: &str) -> HashMap<String, String> {
+        HashMap::from([("/src/app.ts".to_string(), content.to_string())])
+    }
+
+    const ALICE: &str = "telegram:1";
+    const BOB: &str = "telegram:2";
+
+    #[test]
+    fn branch_and_switch_back_restores_original_line() {
+        let (_tmp, conn) = db();
+        super::super::snapshot::save_snapshot(&conn, &files("v1"), Some("nextjs")).unwrap();
+        save_conversation(&conn, ALICE, &[ChatMessage::user("build a shop")]).unwrap();
+        create_checkpoint(&conn, ALICE, "before-dev", None).unwrap();
+
+        // Work continues on the original line.
+        super::super::snapshot::save_snapshot(&conn, &files("v2"), Some("nextjs")).unwrap();
+        save_conversation(
+            &conn,
+            ALICE,
+            &[
+                ChatMessage::user("build a shop"),
+                ChatMessage::assistant("done with model A"),
+            ],
+        )
+        .unwrap();
+        let original =
+            create_checkpoint(&conn, ALICE, "model-a", Some("developer on model A")).unwrap();
+        assert_eq!(original.parent.as_deref(), Some("before-dev"));
+
+        // Branch from the earlier checkpoint.
+        switch_checkpoint(&conn, ALICE, "before-dev").unwrap();
+        // The turn that requested the switch is still mirrored afterwards.
+        save_conversation(&conn, ALICE, &[ChatMessage::user("switch please")]).unwrap();
+        let (restored, _) = super::super::snapshot::load_snapshot(&conn)
+            .unwrap()
+            .unwrap();
+        assert_eq!(restored["/src/app.ts"], "v1");
+        let pending = take_pending_conversation(&conn, ALICE).unwrap().unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].content, "build a shop");
+        assert!(take_pending_conversation(&conn, ALICE).unwrap().is_none());
+
+        // The original line is still intact.
+        let model_a = load_checkpoint(&conn, ALICE, "model-a").unwrap().unwrap();
+        assert_eq!(model_a.files["/src/app.ts"], "v2");
+        assert_eq!(model_a.conversation.len(), 2);
+
+        let listed = list_checkpoints(&conn, ALICE).unwrap();
+        assert_eq!(listed.len(), 2);
+        assert!(listed.iter().any(|c| c.name == "before-dev" && c.active));
+    }
+
+    #[test]
+    fn conversations_and_restores_belong_to_one_user() {
+        let (_tmp, conn) = db();
+        save_conversation(&conn, ALICE, &[ChatMessage::user("alice's shop")]).unwrap();
+        save_conversation(&conn, BOB, &[ChatMessage::user("bob's blog")]).unwrap();
+        let created = create_checkpoint(&conn, ALICE, "shop", None).unwrap();
+        assert_eq!(created.message_count, 1);
+        assert_eq!(
+            load_checkpoint(&conn, ALICE, "shop")
+                .unwrap()
+                .unwrap()
+                .conversation[0]
+                .content,
+            "alice's shop"
+        );
+
+        save_conversation(&conn, ALICE, &[]).unwrap();
+        switch_checkpoint(&conn, ALICE, "shop").unwrap();
+        // Bob messaging next neither receives nor consumes Alice's restore.
+        assert!(take_pending_conversation(&conn, BOB).unwrap().is_none());
+        assert_eq!(
+            load_conversation(&conn, BOB).unwrap()[0].content,
+            "bob's blog"
+        );
+        let pending = take_pending_conversation(&conn, ALICE).unwrap().unwrap();
+        assert_eq!(pending[0].content, "alice's shop");
+    }
+
+    #[test]
+    fn checkpoints_belong_to_their_creator() {
+        let (_tmp, conn) = db();
+        save_conversation(&conn, ALICE, &[ChatMessage::user("alice's shop")]).unwrap();
+        create_checkpoint(&conn, ALICE, "shop", None).unwrap();
+
+        assert!(list_checkpoints(&conn, BOB).unwrap().is_empty());
+        assert!(switch_checkpoint(&conn, BOB, "shop").is_err());
+        assert!(take_pending_conversation(&conn, BOB).unwrap().is_none());
+
+        // Bob can use the same name for his own line, branching from nothing.
+        let bobs = create_checkpoint(&conn, BOB, "shop", None).unwrap();
+        assert_eq!((bobs.parent, bobs.message_count), (None, 0));
+        assert_eq!(
+            active_checkpoint(&conn, ALICE).unwrap().as_deref(),
+            Some("shop")
+        );
+        assert_eq!(
+            load_checkpoint(&conn, ALICE, "shop")
+                .unwrap()
+                .unwrap()
+                .conversation[0]
+                .content,
+            "alice's shop"
+        );
+    }
+
+    #[test]
+    fn duplicate_and_unknown_names_are_rejected() {
+        let (_tmp, conn) = db();
+        create_checkpoint(&conn, ALICE, "a", None).unwrap();
+        assert!(create_checkpoint(&conn, ALICE, "a", None).is_err());
+        assert!(create_checkpoint(&conn, ALICE, "  ", None).is_err());
+        assert!(switch_checkpoint(&conn, ALICE, "missing").is_err());
+    }
+}
@@ -0,0 +1,384 @@
+//! Code index: chunked (and optionally embedded) project snapshot files so the
+//! agent can retrieve the parts of a large project relevant to its current
+//! task instead of reading every file into context.
+//!
+//! The index is refreshed whenever a snapshot is saved. Files whose content
+//! hash is unchanged keep their existing chunks and embeddings, so only edited
+//! files are re-embedded.
+
+use crate::memory::embeddings::EmbeddingProvider;
+use crate::memory::vector;
+use anyhow::Result;
+use rusqlite::{params, Connection};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// Lines per chunk.
+const CHUNK_LINES: usize = 60;
+
+/// Lines shared between consecutive chunks so definitions are not cut in half.
+const CHUNK_OVERLAP: usize = 10;
+
+/// Files larger than this are not indexed (generated bundles, fixtures).
+const MAX_INDEXED_FILE_BYTES: usize = 256 * 1024;
+
+/// Chunks embedded per provider request.
+const EMBED_BATCH_SIZE: usize = 32;
+
+/// Weights used when both vector and keyword scores are available.
+const VECTOR_WEIGHT: f32 = 0.7;
+const KEYWORD_WEIGHT: f32 = 0.3;
+
+/// A contiguous line range of a project file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeChunk {
+    pub path: String,
+    /// 1-based, inclusive.
+    pub start_line: usize,
+    /// 1-based, inclusive.
+    pub end_line: usize,
+    pub content: String,
+}
+
+/// A chunk returned by [`search_chunks`].
+#[derive(Debug, Clone)]
+pub struct CodeSearchHit {
+    pub chunk: CodeChunk,
+    pub score: f32,
+}
+
+/// Outcome of an index refresh.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IndexStats {
+    /// Files re-chunked because they are new or changed.
+    pub files_indexed: usize,
+    /// Files left untouched because their content hash did not change.
+    pub files_unchanged: usize,
+    /// Files dropped from the index because they left the snapshot.
+    pub files_removed: usize,
+    /// Chunks written for the re-indexed files.
+    pub chunks_written: usize,
+}
+
+/// Whether a snapshot file is worth indexing.
+fn is_indexable(path: &str, content: &str) -> bool {
+    const SKIP_FILES: &[&str] = &[
+        "package-lock.json",
+        "pnpm-lock.yaml",
+        "yarn.lock",
+        "bun.lockb",
+        "Cargo.lock",
+    ];
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    !content.trim().is_empty()
+        && content.len() <= MAX_INDEXED_FILE_BYTES
+        && !SKIP_FILES.contains(&file_name)
+        && !file_name.ends_with(".min.js")
+        && !content.contains('\0')
+}
+
+/// Split a file into overlapping line windows.
+pub fn chunk_file(path: &str, content: &str) -> Vec<CodeChunk> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut chunks = Vec::new();
+    let mut start = 0;
+
+    while start < lines.len() {
+        let end = (start + CHUNK_LINES).min(lines.len());
+        let body = lines[start..end].join("\n");
+        if !body.trim().is_empty() {
+            chunks.push(CodeChunk {
+                path: path.to_string(),
+                start_line: start + 1,
+                end_line: end,
+                content: body,
+            });
+        }
+        if end == lines.len() {
+            break;
+        }
+        start = end - CHUNK_OVERLAP;
+    }
+
+    chunks
+}
+
+fn file_hash(content: &str) -> String {
+    hex::encode(Sha256::digest(content.as_bytes()))
+}
+
+/// Content hash of every file currently in the index.
+fn indexed_file_hashes(conn: &Connection) -> Result<HashMap<String, String>> {
+    let mut stmt = conn.prepare("SELECT DISTINCT path, file_hash FROM code_chunks")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    let mut hashes = HashMap::new();
+    for row in rows {
+        let (path, hash): (String, String) = row?;
+        hashes.insert(path, hash);
+    }
+    Ok(hashes)
+}
+
+/// Refresh the code index at `db_path` from a snapshot file map.
+///
+/// Embedding failures are not fatal: affected chunks are stored without a
+/// vector and remain reachable through keyword scoring.
+#[allow(clippy::implicit_hasher)]
+pub async fn index_snapshot(
+    db_path: &Path,
+    files: &HashMap<String, String>,
+    embedder: &dyn EmbeddingProvider,
+) -> Result<IndexStats> {
+    let existing = {
+        let conn = super::init_db(db_path)?;
+        indexed_file_hashes(&conn)?
+    };
+
+    let mut stats = IndexStats::default();
+    let mut changed: Vec<(String, String, Vec<CodeChunk>)> = Vec::new();
+    for (path, content) in files {
+        if !is_indexable(path, content) {
+            continue;
+        }
+        let hash = file_hash(content);
+        if existing.get(path) == Some(&hash) {
+            stats.files_unchanged += 1;
+            continue;
+        }
+        changed.push((path.clone(), hash, chunk_file(path, content)));
+    }
+
+    let all_chunks: Vec<&CodeChunk> = changed.iter().flat_map(|(_, _, c)| c.iter()).collect();
+    let mut embeddings: Vec<Option<Vec<f32>>> = Vec::with_capacity(all_chunks.len());
+    if embedder.dimensions() > 0 {
+        for batch in all_chunks.chunks(EMBED_BATCH_SIZE) {
+            let texts: Vec<&str> = batch.iter().map(|c| c.content.as_str()).collect();
+            match embedder.embed(&texts).await {
+                Ok(vectors) if vectors.len() == batch.len() => {
+                    embeddings.extend(vectors.into_iter().map(Some));
+                }
+                Ok(_) | Err(_) => {
+                    tracing::warn!("Code index embedding failed; falling back to keyword search");
+                    embeddings.extend(std::iter::repeat_with(|| None).take(batch.len()));
+                }
+            }
+        }
+    } else {
+        embeddings.resize(all_chunks.len(), None);
+    }
+
+    let mut conn = super::init_db(db_path)?;
+    let tx = conn.transaction()?;
+    let mut embedding_iter = embeddings.into_iter();
+    for (path, hash, chunks) in &changed {
+        tx.execute("DELETE FROM code_chunks WHERE path = ?1", params![path])?;
+        for chunk in chunks {
+            let embedding = embedding_iter
+                .next()
+                .flatten()
+                .map(|v| vector::vec_to_bytes(&v));
+            tx.execute(
+                "INSERT INTO code_chunks (path, start_line, end_line, content, file_hash, embedding)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    path,
+                    chunk.start_line,
+                    chunk.end_line,
+                    chunk.content,
+                    hash,
+                    embedding
+                ],
+            )?;
+            stats.chunks_written += 1;
+        }
+        stats.files_indexed += 1;
+    }
+
+    let live: HashSet<&str> = files
+        .iter()
+        .filter(|(path, content)| is_indexable(path, content))
+        .map(|(path, _)| path.as_str())
+        .collect();
+    for path in existing.keys().filter(|p| !live.contains(p.as_str())) {
+        tx.execute("DELETE FROM code_chunks WHERE path = ?1", params![path])?;
+        stats.files_removed += 1;
+    }
+    tx.commit()?;
+
+    Ok(stats)
+}
+
+/// Lowercased alphanumeric terms of at least two characters.
+fn query_terms(query: &str) -> Vec<String> {
+    query
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|t| t.len() >= 2)
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Term-frequency score of a chunk against the query terms (path matches
+/// count double so file names like `auth.ts` surface first).
+#[allow(clippy::cast_precision_loss)]
+fn keyword_score(terms: &[String], chunk: &CodeChunk) -> f32 {
+    let content = chunk.content.to_lowercase();
+    let path = chunk.path.to_lowercase();
+    terms
+        .iter()
+        .map(|t| {
+            content.matches(t.as_str()).count() as f32
+                + 2.0 * path.matches(t.as_str()).count() as f32
+        })
+        .sum()
+}
+
+/// Retrieve the chunks most relevant to `query`.
+///
+/// Combines cosine similarity (when `query_embedding` is given and chunks
+/// were embedded) with keyword scoring via [`vector::hybrid_merge`].
+pub fn search_chunks(
+    conn: &Connection,
+    query: &str,
+    query_embedding: Option<&[f32]>,
+    limit: usize,
+) -> Result<Vec<CodeSearchHit>> {
+    let mut stmt =
+        conn.prepare("SELECT id, path, start_line, end_line, content, embedding FROM code_chunks")?;
+    let rows = stmt.query_map([], |row| {
+        let id: i64 = row.get(0)?;
+        let chunk = CodeChunk {
+            path: row.get(1)?,
+            start_line: row.get(2)?,
+            end_line: row.get(3)?,
+            content: row.get(4)?,
+        };
+        let embedding: Option<Vec<u8>> = row.get(5)?;
+        Ok((id.to_string(), chunk, embedding))
+    })?;
+
+    let terms = query_terms(query);
+    let mut chunks: HashMap<String, CodeChunk> = HashMap::new();
+    let mut vector_results = Vec::new();
+    let mut keyword_results = Vec::new();
+    for row in rows {
+        let (id, chunk, embedding) = row?;
+        if let (Some(query_vec), Some(bytes)) = (query_embedding, embedding.as_deref()) {
+            let sim = vector::cosine_similarity(query_vec, &vector::bytes_to_vec(bytes));
+            if sim > 0.0 {
+                vector_results.push((id.clone(), sim));
+            }
+        }
+        let kw = keyword_score(&terms, &chunk);
+        if kw > 0.0 {
+            keyword_results.push((id.clone(), kw));
+        }
+        chunks.insert(id, chunk);
+    }
+
+    let (vector_weight, keyword_weight) = if vector_results.is_empty() {
+        (0.0, 1.0)
+    } else {
+        (VECTOR_WEIGHT, KEYWORD_WEIGHT)
+    };
+    let merged = vector::hybrid_merge(
+        &vector_results,
+        &keyword_results,
+        vector_weight,
+        keyword_weight,
+        limit,
+    );
+
+    Ok(merged
+        .into_iter()
+        .filter_map(|r| {
+            chunks.remove(&r.id).map(|chunk| CodeSearchHit {
+                chunk,
+                score: r.final_score,
+            })
+        })
+        .collect())
+}
+
+/// Number of chunks currently indexed.
+pub fn chunk_count(conn: &Connection) -> Result<usize> {
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM code_chunks", [], |row| row.get(0))?;
+    Ok(usize::try_from(count).unwrap_or(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::embeddings::NoopEmbedding;
+    use tempfile::TempDir;
+
+    fn files(entries: &[(&str, &str)]) -> HashMap<String, String> {
+        entries
+            .iter()
+            .map(|(p, c)| ((*p).to_string(), (*c).to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn chunk_file_overlaps_windows() {
+        let content = (1..=130)
+            .map(|i| format!("line {i}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let chunks = chunk_file("src/a.ts", &content);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!((chunks[0].start_line, chunks[0].end_line), (1, 60));
+        assert_eq!((chunks[1].start_line, chunks[1].end_line), (51, 110));
+        assert_eq!((chunks[2].start_line, chunks[2].end_line), (101, 130));
+    }
+
+    #[tokio::test]
+    async fn reindex_skips_unchanged_and_drops_removed_files() {
+        let tmp = TempDir::new().unwrap();
+        let db = tmp.path().join("zerobuild.db");
+
+        let first = files(&[
+            (
+                "/src/auth.ts",
+                "export function login(user) { return token; }",
+            ),
+            ("/src/cart.ts", "export function addToCart(item) {}"),
+            ("/package-lock.json", "{}"),
+        ]);
+        let stats = index_snapshot(&db, &first, &NoopEmbedding).await.unwrap();
+        assert_eq!(stats.files_indexed, 2);
+
+        let second = files(&[(
+            "/src/auth.ts",
+            "export function login(user) { return token; }",
+        )]);
+        let stats = index_snapshot(&db, &second, &NoopEmbedding).await.unwrap();
+        assert_eq!(stats.files_unchanged, 1);
+        assert_eq!(stats.files_removed, 1);
+
+        let conn = crate::store::init_db(&db).unwrap();
+        assert_eq!(chunk_count(&conn).unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn keyword_search_ranks_matching_chunk_first() {
+        let tmp = TempDir::new().unwrap();
+        let db = tmp.path().join("zerobuild.db");
+        let snapshot = files(&[
+            (
+                "/src/auth.ts",
+                "export function login(user) { return token; }",
+            ),
+            ("/src/cart.ts", "export function addToCart(item) {}"),
+        ]);
+        index_snapshot(&db, &snapshot, &NoopEmbedding)
+            .await
+            .unwrap();
+
+        let conn = crate::store::init_db(&db).unwrap();
+        let hits = search_chunks(&conn, "login token", None, 5).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].chunk.path, "/src/auth.ts");
+        assert_eq!(hits[0].chunk.start_line, 1);
+    }
+}
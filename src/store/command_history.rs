@@ -0,0 +1,205 @@
+//! Commands the agent ran in its sandboxes.
+//!
+//! `sandbox_run_command` and `sandbox_shell` record every command here with
+//! its exit code, duration and, when its output was truncated, the
+//! [`logs`](super::logs) ID of the full output. `sandbox_command_history`
+//! reads it back so the agent can see what it already tried. Only the most
+//! recent `[zerobuild.sandbox] command_history_limit` commands are kept.
+
+use anyhow::Result;
+use chrono::Utc;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+
+/// A recorded command.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HistoryEntry {
+    pub id: i64,
+    pub sandbox_id: String,
+    /// `sandbox_shell` session the command ran in; `None` for
+    /// `sandbox_run_command`.
+    pub session: Option<String>,
+    pub command: String,
+    pub workdir: String,
+    pub exit_code: i64,
+    pub duration_ms: u64,
+    pub log_id: Option<String>,
+    pub created_at: String,
+}
+
+/// A command to record.
+#[derive(Debug, Clone, Copy)]
+pub struct NewCommand<'a> {
+    pub sandbox_id: &'a str,
+    pub session: Option<&'a str>,
+    pub command: &'a str,
+    pub workdir: &'a str,
+    pub exit_code: i64,
+    pub duration_ms: u64,
+    pub log_id: Option<&'a str>,
+}
+
+/// Which commands [`recent_commands`] returns.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HistoryQuery<'a> {
+    pub sandbox_id: Option<&'a str>,
+    pub session: Option<&'a str>,
+    pub failed_only: bool,
+    /// Only commands containing this text (case-insensitive).
+    pub contains: Option<&'a str>,
+    pub limit: usize,
+}
+
+/// Record `command`, keeping only the `keep` most recent commands.
+pub fn record_command(conn: &Connection, command: &NewCommand<'_>, keep: usize) -> Result<()> {
+    conn.execute(
+        "INSERT INTO command_history
+             (sandbox_id, session, command, workdir, exit_code, duration_ms, log_id, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            command.sandbox_id,
+            command.session,
+            command.command,
+            command.workdir,
+            command.exit_code,
+            i64::try_from(command.duration_ms).unwrap_or(i64::MAX),
+            command.log_id,
+            Utc::now().to_rfc3339()
+        ],
+    )?;
+    conn.execute(
+        "DELETE FROM command_history WHERE id NOT IN
+             (SELECT id FROM command_history ORDER BY id DESC LIMIT ?1)",
+        params![i64::try_from(keep).unwrap_or(i64::MAX)],
+    )?;
+    Ok(())
+}
+
+/// The last `query.limit` commands matching `query`, oldest first.
+pub fn recent_commands(conn: &Connection, query: &HistoryQuery<'_>) -> Result<Vec<HistoryEntry>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, sandbox_id, session, command, workdir, exit_code, duration_ms, log_id,
+                created_at
+         FROM command_history
+         WHERE (?1 IS NULL OR sandbox_id = ?1)
+           AND (?2 IS NULL OR session = ?2)
+           AND (?3 = 0 OR exit_code != 0)
+           AND (?4 IS NULL OR instr(lower(command), lower(?4)) > 0)
+         ORDER BY id DESC LIMIT ?5",
+    )?;
+    let rows = stmt.query_map(
+        params![
+            query.sandbox_id,
+            query.session,
+            query.failed_only,
+            query.contains,
+            i64::try_from(query.limit).unwrap_or(i64::MAX)
+        ],
+        |row| {
+            Ok(HistoryEntry {
+                id: row.get(0)?,
+                sandbox_id: row.get(1)?,
+                session: row.get(2)?,
+                command: row.get(3)?,
+                workdir: row.get(4)?,
+                exit_code: row.get(5)?,
+                duration_ms: u64::try_from(row.get::<_, i64>(6)?).unwrap_or(0),
+                log_id: row.get(7)?,
+                created_at: row.get(8)?,
+            })
+        },
+    )?;
+    let mut entries = rows.collect::<rusqlite::Result<Vec<_>>>()?;
+    entries.reverse();
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn command<'a>(sandbox_id: &'a str, command: &'a str, exit_code: i64) -> NewCommand<'a> {
+        NewCommand {
+            sandbox_id,
+            session: None,
+            command,
+            workdir: "project",
+            exit_code,
+            duration_ms: 12,
+            log_id: None,
+        }
+    }
+
+    #[test]
+    fn history_filters_and_prunes() {
+        let tmp = TempDir::new().unwrap();
+        let conn = super::super::init_db(&tmp.path().join("zerobuild.db")).unwrap();
+
+        record_command(&conn, &command("sb-1", "npm install", 0), 10).unwrap();
+        record_command(&conn, &command("sb-1", "npm run build", 1), 10).unwrap();
+        record_command(
+            &conn,
+            &NewCommand {
+                session: Some("dev"),
+                ..command("sb-1", "cd project && nvm use 20", 0)
+            },
+            10,
+        )
+        .unwrap();
+        record_command(&conn, &command("sb-2", "ls", 0), 10).unwrap();
+
+        let all = recent_commands(
+            &conn,
+            &HistoryQuery {
+                sandbox_id: Some("sb-1"),
+                limit: 10,
+                ..HistoryQuery::default()
+            },
+        )
+        .unwrap();
+        let commands: Vec<&str> = all.iter().map(|e| e.command.as_str()).collect();
+        assert_eq!(
+            commands,
+            ["npm install", "npm run build", "cd project && nvm use 20"]
+        );
+        assert_eq!(all[2].session.as_deref(), Some("dev"));
+        assert_eq!(all[0].duration_ms, 12);
+
+        let failed = recent_commands(
+            &conn,
+            &HistoryQuery {
+                failed_only: true,
+                limit: 10,
+                ..HistoryQuery::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].command, "npm run build");
+
+        let grep = recent_commands(
+            &conn,
+            &HistoryQuery {
+                contains: Some("NPM"),
+                limit: 1,
+                ..HistoryQuery::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(grep.len(), 1);
+        assert_eq!(grep[0].command, "npm run build");
+
+        record_command(&conn, &command("sb-2", "pwd", 0), 2).unwrap();
+        let kept = recent_commands(
+            &conn,
+            &HistoryQuery {
+                limit: 10,
+                ..HistoryQuery::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(kept.len(), 2);
+        assert_eq!(kept[1].command, "pwd");
+    }
+}
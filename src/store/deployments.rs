@@ -0,0 +1,181 @@
+//! Projects `github_push` deployed, one row per repository, with the outcome
+//! of their last dependency refresh (see [`crate::factory::maintenance`]),
+//! and the files, changelog and release tag of their last deploy (see
+//! [`crate::tools::changelog`]).
+
+use anyhow::Result;
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::BTreeMap;
+
+/// A deployed project.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Deployment {
+    /// `owner/name` of the repository.
+    pub repo: String,
+    /// Project whose snapshot versions were deployed.
+    pub project: String,
+    pub branch: String,
+    /// Repository directory the project was deployed into (monorepos).
+    pub path: Option<String>,
+    pub deployed_at: String,
+    pub refreshed_at: Option<String>,
+    /// What the last refresh did, e.g. the pull request it opened.
+    pub refresh_result: Option<String>,
+}
+
+/// Record a deploy of `project` to `repo`, replacing the previous one.
+pub fn record(
+    conn: &Connection,
+    repo: &str,
+    project: &str,
+    branch: &str,
+    path: Option<&str>,
+) -> Result<()> {
+    conn.execute(
+        "INSERT INTO deployments (repo, project, branch, path, deployed_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(repo) DO UPDATE SET
+             project = excluded.project,
+             branch = excluded.branch,
+             path = excluded.path,
+             deployed_at = excluded.deployed_at",
+        params![repo, project, branch, path, Utc::now().to_rfc3339()],
+    )?;
+    Ok(())
+}
+
+/// Every deployed project, oldest deploy first.
+pub fn list(conn: &Connection) -> Result<Vec<Deployment>> {
+    let mut stmt = conn.prepare(
+        "SELECT repo, project, branch, path, deployed_at, refreshed_at, refresh_result
+         FROM deployments ORDER BY deployed_at",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(Deployment {
+            repo: row.get(0)?,
+            project: row.get(1)?,
+            branch: row.get(2)?,
+            path: row.get(3)?,
+            deployed_at: row.get(4)?,
+            refreshed_at: row.get(5)?,
+            refresh_result: row.get(6)?,
+        })
+    })?;
+    Ok(rows.collect::<Result<_, _>>()?)
+}
+
+/// Record that `repo` was refreshed now with `result`.
+pub fn mark_refreshed(conn: &Connection, repo: &str, result: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE deployments SET refreshed_at = ?2, refresh_result = ?3 WHERE repo = ?1",
+        params![repo, Utc::now().to_rfc3339(), result],
+    )?;
+    Ok(())
+}
+
+/// What the last deploy to a repository pushed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeployedFiles {
+    /// Project files by path, before LICENSE, `.env.example` and the
+    /// changelog were added.
+    pub files: BTreeMap<String, String>,
+    /// `CHANGELOG.md` as last pushed.
+    pub changelog: Option<String>,
+    /// Tag of the last release.
+    pub release_tag: Option<String>,
+}
+
+/// Remember what a deploy to `repo` pushed. A `None` changelog or release
+/// tag keeps the previous one.
+pub fn save_files(
+    conn: &Connection,
+    repo: &str,
+    files: &BTreeMap<String, String>,
+    changelog: Option<&str>,
+    release_tag: Option<&str>,
+) -> Result<()> {
+    conn.execute(
+        "INSERT INTO deployed_files (repo, files, changelog, release_tag)
+         VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(repo) DO UPDATE SET
+             files = excluded.files,
+             changelog = COALESCE(excluded.changelog, deployed_files.changelog),
+             release_tag = COALESCE(excluded.release_tag, deployed_files.release_tag)",
+        params![repo, serde_json::to_string(files)?, changelog, release_tag],
+    )?;
+    Ok(())
+}
+
+/// What the last deploy to `repo` pushed, if it was recorded.
+pub fn load_files(conn: &Connection, repo: &str) -> Result<Option<DeployedFiles>> {
+    let row: Option<(String, Option<String>, Option<String>)> = conn
+        .query_row(
+            "SELECT files, changelog, release_tag FROM deployed_files WHERE repo = ?1",
+            params![repo],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()?;
+    row.map(|(files, changelog, release_tag)| {
+        Ok(DeployedFiles {
+            files: serde_json::from_str(&files)?,
+            changelog,
+            release_tag,
+        })
+    })
+    .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn deploys_replace_and_refreshes_are_recorded() {
+        let tmp = TempDir::new().unwrap();
+        let conn = super::super::init_db(&tmp.path().join("zerobuild.db")).unwrap();
+        record(&conn, "acme/shop", "shop", "main", None).unwrap();
+        record(&conn, "acme/site", "site", "main", Some("apps/web")).unwrap();
+        mark_refreshed(&conn, "acme/shop", "Dependencies are up to date").unwrap();
+        record(&conn, "acme/shop", "shop", "release", None).unwrap();
+
+        let deployments = list(&conn).unwrap();
+        assert_eq!(deployments.len(), 2);
+        let site = &deployments[0];
+        assert_eq!(site.repo, "acme/site");
+        assert_eq!(site.path.as_deref(), Some("apps/web"));
+        assert!(site.refreshed_at.is_none());
+        let shop = &deployments[1];
+        assert_eq!(shop.branch, "release");
+        assert_eq!(
+            shop.refresh_result.as_deref(),
+            Some("Dependencies are up to date")
+        );
+    }
+
+    #[test]
+    fn deployed_files_keep_the_last_changelog() {
+        let tmp = TempDir::new().unwrap();
+        let conn = super::super::init_db(&tmp.path().join("zerobuild.db")).unwrap();
+        assert!(load_files(&conn, "acme/shop").unwrap().is_none());
+
+        let files = BTreeMap::from([("index.html".to_string(), "<h1>Shop</h1>".to_string())]);
+        save_files(
+            &conn,
+            "acme/shop",
+            &files,
+            Some("# Changelog\n"),
+            Some("v1.0.0"),
+        )
+        .unwrap();
+        let mut changed = files.clone();
+        changed.insert("cart.js".into(), "export {}".into());
+        save_files(&conn, "acme/shop", &changed, None, None).unwrap();
+
+        let deployed = load_files(&conn, "acme/shop").unwrap().unwrap();
+        assert_eq!(deployed.files, changed);
+        assert_eq!(deployed.changelog.as_deref(), Some("# Changelog\n"));
+        assert_eq!(deployed.release_tag.as_deref(), Some("v1.0.0"));
+    }
+}
@@ -0,0 +1,69 @@
+//! Environment variables the agent set with `sandbox_set_env`, with the
+//! description it gave. Values are never stored; `.env.example` is
+//! generated from these (see [`crate::tools::env_example`]).
+
+use anyhow::Result;
+use chrono::Utc;
+use rusqlite::{params, Connection};
+
+/// A tracked environment variable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackedEnvVar {
+    pub name: String,
+    pub description: String,
+    pub required: bool,
+    pub updated_at: String,
+}
+
+/// Record that `name` was set, replacing its earlier description.
+pub fn save(conn: &Connection, name: &str, description: &str, required: bool) -> Result<()> {
+    conn.execute(
+        "INSERT INTO env_vars (name, description, required, updated_at) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(name) DO UPDATE SET
+             description = excluded.description,
+             required = excluded.required,
+             updated_at = excluded.updated_at",
+        params![name, description, required, Utc::now().to_rfc3339()],
+    )?;
+    Ok(())
+}
+
+/// All tracked variables, by name.
+pub fn list(conn: &Connection) -> Result<Vec<TrackedEnvVar>> {
+    let mut stmt =
+        conn.prepare("SELECT name, description, required, updated_at FROM env_vars ORDER BY name")?;
+    let vars = stmt
+        .query_map([], |row| {
+            Ok(TrackedEnvVar {
+                name: row.get(0)?,
+                description: row.get(1)?,
+                required: row.get(2)?,
+                updated_at: row.get(3)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(vars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn env_vars_roundtrip() {
+        let tmp = TempDir::new().unwrap();
+        let conn = crate::store::init_db(&tmp.path().join("zerobuild.db")).unwrap();
+        assert!(list(&conn).unwrap().is_empty());
+
+        save(&conn, "STRIPE_KEY", "Stripe secret key", true).unwrap();
+        save(&conn, "DATABASE_URL", "Postgres URL", true).unwrap();
+        save(&conn, "STRIPE_KEY", "Stripe test key", false).unwrap();
+
+        let vars = list(&conn).unwrap();
+        assert_eq!(vars.len(), 2);
+        assert_eq!(vars[0].name, "DATABASE_URL");
+        assert_eq!(vars[1].description, "Stripe test key");
+        assert!(!vars[1].required);
+    }
+}
@@ -0,0 +1,104 @@
+//! Builds started from labeled GitHub issues by
+//! [`crate::factory::issues`]. Each issue is built once; the row remembers
+//! the build job, the status comment on the issue and what it last said.
+
+use anyhow::Result;
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+
+/// A tracked issue build.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IssueBuild {
+    /// `owner/repo`.
+    pub repo: String,
+    pub number: u64,
+    pub job_id: i64,
+    /// Status comment on the issue, once posted.
+    pub comment_id: Option<u64>,
+    /// Last status written to the comment.
+    pub reported: String,
+    pub done: bool,
+}
+
+const COLUMNS: &str = "repo, number, job_id, comment_id, reported, done";
+
+fn from_row(row: &Row<'_>) -> rusqlite::Result<IssueBuild> {
+    Ok(IssueBuild {
+        repo: row.get(0)?,
+        number: row.get(1)?,
+        job_id: row.get(2)?,
+        comment_id: row.get(3)?,
+        reported: row.get(4)?,
+        done: row.get(5)?,
+    })
+}
+
+/// The build of `repo#number`, if the issue was picked up before.
+pub fn get(conn: &Connection, repo: &str, number: u64) -> Result<Option<IssueBuild>> {
+    Ok(conn
+        .query_row(
+            &format!("SELECT {COLUMNS} FROM issue_builds WHERE repo = ?1 AND number = ?2"),
+            params![repo, number],
+            from_row,
+        )
+        .optional()?)
+}
+
+/// Start tracking the build `job_id` of `repo#number`.
+pub fn track(conn: &Connection, repo: &str, number: u64, job_id: i64) -> Result<()> {
+    conn.execute(
+        "INSERT INTO issue_builds (repo, number, job_id, created_at) VALUES (?1, ?2, ?3, ?4)",
+        params![repo, number, job_id, Utc::now().to_rfc3339()],
+    )?;
+    Ok(())
+}
+
+/// Builds whose final status hasn't been reported yet, oldest first.
+pub fn active(conn: &Connection) -> Result<Vec<IssueBuild>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {COLUMNS} FROM issue_builds WHERE done = 0 ORDER BY created_at ASC"
+    ))?;
+    let rows = stmt.query_map([], from_row)?;
+    Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+}
+
+/// Record the status comment and what it says.
+pub fn update(
+    conn: &Connection,
+    repo: &str,
+    number: u64,
+    comment_id: Option<u64>,
+    reported: &str,
+    done: bool,
+) -> Result<()> {
+    conn.execute(
+        "UPDATE issue_builds SET comment_id = ?3, reported = ?4, done = ?5
+         WHERE repo = ?1 AND number = ?2",
+        params![repo, number, comment_id, reported, done],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn tracks_until_done() {
+        let tmp = TempDir::new().unwrap();
+        let conn = super::super::init_db(&tmp.path().join("zerobuild.db")).unwrap();
+        assert!(get(&conn, "acme/shop", 7).unwrap().is_none());
+
+        track(&conn, "acme/shop", 7, 3).unwrap();
+        assert!(track(&conn, "acme/shop", 7, 4).is_err());
+        update(&conn, "acme/shop", 7, Some(99), "queued", false).unwrap();
+        let build = get(&conn, "acme/shop", 7).unwrap().unwrap();
+        assert_eq!(build.job_id, 3);
+        assert_eq!(build.comment_id, Some(99));
+        assert_eq!(active(&conn).unwrap(), [build]);
+
+        update(&conn, "acme/shop", 7, Some(99), "succeeded", true).unwrap();
+        assert!(active(&conn).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,343 @@
+//! Build job queue: factory build requests waiting for, or handled by, the
+//! worker pool in [`crate::factory::queue`].
+//!
+//! Jobs are claimed highest priority first. Within a priority the user with
+//! the fewest running jobs goes first, then the user who was served least
+//! recently, so one person queueing five ideas doesn't starve everyone else.
+
+use anyhow::{bail, Result};
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension, Row, TransactionBehavior};
+use serde::Serialize;
+
+/// Lifecycle of a build job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Queued => "queued",
+            Self::Running => "running",
+            Self::Succeeded => "succeeded",
+            Self::Failed => "failed",
+            Self::Cancelled => "cancelled",
+        }
+    }
+
+    fn parse(value: &str) -> Result<Self> {
+        Ok(match value {
+            "queued" => Self::Queued,
+            "running" => Self::Running,
+            "succeeded" => Self::Succeeded,
+            "failed" => Self::Failed,
+            "cancelled" => Self::Cancelled,
+            other => bail!("unknown build job status '{other}'"),
+        })
+    }
+}
+
+/// A queued or processed build request.
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildJob {
+    pub id: i64,
+    pub user: String,
+    pub idea: String,
+    pub project_type: Option<String>,
+    pub project: Option<String>,
+    /// Higher runs first.
+    pub priority: i64,
+    pub status: JobStatus,
+    pub output: Option<String>,
+    pub error: Option<String>,
+    pub created_at: String,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+}
+
+/// Arguments for [`enqueue_job`].
+#[derive(Debug, Clone, Default)]
+pub struct NewJob<'a> {
+    pub user: &'a str,
+    pub idea: &'a str,
+    pub project_type: Option<&'a str>,
+    pub project: Option<&'a str>,
+    pub priority: i64,
+}
+
+const JOB_COLUMNS: &str = "id, user, idea, project_type, project, priority, status, output, \
+                           error, created_at, started_at, finished_at";
+
+fn job_from_row(row: &Row<'_>) -> rusqlite::Result<BuildJob> {
+    let status: String = row.get(6)?;
+    Ok(BuildJob {
+        id: row.get(0)?,
+        user: row.get(1)?,
+        idea: row.get(2)?,
+        project_type: row.get(3)?,
+        project: row.get(4)?,
+        priority: row.get(5)?,
+        status: JobStatus::parse(&status).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(6, rusqlite::types::Type::Text, e.into())
+        })?,
+        output: row.get(7)?,
+        error: row.get(8)?,
+        created_at: row.get(9)?,
+        started_at: row.get(10)?,
+        finished_at: row.get(11)?,
+    })
+}
+
+/// Add a job to the queue. Returns its id.
+pub fn enqueue_job(conn: &Connection, job: &NewJob<'_>) -> Result<i64> {
+    conn.execute(
+        "INSERT INTO build_jobs (user, idea, project_type, project, priority, status, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, 'queued', ?6)",
+        params![
+            job.user,
+            job.idea,
+            job.project_type,
+            job.project,
+            job.priority,
+            Utc::now().to_rfc3339()
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Queued and running jobs of `user`.
+pub fn pending_count_for_user(conn: &Connection, user: &str) -> Result<usize> {
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM build_jobs
+         WHERE user = ?1 AND status IN ('queued', 'running')",
+        params![user],
+        |row| row.get(0),
+    )?;
+    Ok(usize::try_from(count).unwrap_or(0))
+}
+
+/// Ordering of queued jobs by who should be served next (see module docs).
+const CLAIM_ORDER: &str = "ORDER BY j.priority DESC,
+             (SELECT COUNT(*) FROM build_jobs r
+              WHERE r.user = j.user AND r.status = 'running') ASC,
+             COALESCE((SELECT MAX(s.started_at) FROM build_jobs s
+                       WHERE s.user = j.user), '') ASC,
+             j.id ASC";
+
+/// Mark the next job as running and return it, or `None` when the queue is
+/// empty. Safe to call from several workers at once.
+pub fn claim_next_job(conn: &mut Connection) -> Result<Option<BuildJob>> {
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    let next: Option<i64> = tx
+        .query_row(
+            &format!(
+                "SELECT j.id FROM build_jobs j WHERE j.status = 'queued' {CLAIM_ORDER} LIMIT 1"
+            ),
+            [],
+            |row| row.get(0),
+        )
+        .optional()?;
+    let Some(id) = next else {
+        return Ok(None);
+    };
+    tx.execute(
+        "UPDATE build_jobs SET status = 'running', started_at = ?2 WHERE id = ?1",
+        params![id, Utc::now().to_rfc3339()],
+    )?;
+    tx.commit()?;
+    get_job(conn, id)
+}
+
+/// Record the outcome of a running job.
+pub fn finish_job(conn: &Connection, id: i64, outcome: Result<&str, &str>) -> Result<()> {
+    let (status, output, error) = match outcome {
+        Ok(output) => (JobStatus::Succeeded, Some(output), None),
+        Err(error) => (JobStatus::Failed, None, Some(error)),
+    };
+    conn.execute(
+        "UPDATE build_jobs SET status = ?2, output = ?3, error = ?4, finished_at = ?5
+         WHERE id = ?1",
+        params![id, status.as_str(), output, error, Utc::now().to_rfc3339()],
+    )?;
+    Ok(())
+}
+
+/// Cancel a job of `user` that hasn't started yet. Returns whether a job was
+/// cancelled.
+pub fn cancel_job(conn: &Connection, id: i64, user: &str) -> Result<bool> {
+    let changed = conn.execute(
+        "UPDATE build_jobs SET status = 'cancelled', finished_at = ?3
+         WHERE id = ?1 AND user = ?2 AND status = 'queued'",
+        params![id, user, Utc::now().to_rfc3339()],
+    )?;
+    Ok(changed > 0)
+}
+
+/// Put jobs left `running` by a previous process back in the queue. Returns
+/// the number of jobs requeued.
+pub fn requeue_interrupted(conn: &Connection) -> Result<usize> {
+    Ok(conn.execute(
+        "UPDATE build_jobs SET status = 'queued', started_at = NULL WHERE status = 'running'",
+        [],
+    )?)
+}
+
+/// Look up a job by id.
+pub fn get_job(conn: &Connection, id: i64) -> Result<Option<BuildJob>> {
+    Ok(conn
+        .query_row(
+            &format!("SELECT {JOB_COLUMNS} FROM build_jobs WHERE id = ?1"),
+            params![id],
+            job_from_row,
+        )
+        .optional()?)
+}
+
+/// Most recent jobs first, optionally only those of `user`.
+pub fn list_jobs(conn: &Connection, user: Option<&str>, limit: usize) -> Result<Vec<BuildJob>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {JOB_COLUMNS} FROM build_jobs
+         WHERE (?1 IS NULL OR user = ?1)
+         ORDER BY id DESC LIMIT ?2"
+    ))?;
+    let limit = i64::try_from(limit.max(1)).unwrap_or(i64::MAX);
+    let rows = stmt.query_map(params![user, limit], job_from_row)?;
+    Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+}
+
+/// Record the latest progress line of a running job.
+pub fn set_progress(conn: &Connection, id: i64, line: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO build_job_progress (job_id, line, updated_at) VALUES (?1, ?2, ?3)
+         ON CONFLICT(job_id) DO UPDATE SET line = excluded.line, updated_at = excluded.updated_at",
+        params![id, line, Utc::now().to_rfc3339()],
+    )?;
+    Ok(())
+}
+
+/// Latest progress line of a job, if it reported any.
+pub fn progress(conn: &Connection, id: i64) -> Result<Option<String>> {
+    Ok(conn
+        .query_row(
+            "SELECT line FROM build_job_progress WHERE job_id = ?1",
+            params![id],
+            |row| row.get(0),
+        )
+        .optional()?)
+}
+
+/// 1-based position of a queued job in claim order, or `None` when the job
+/// is not queued.
+pub fn queue_position(conn: &Connection, id: i64) -> Result<Option<usize>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT j.id FROM build_jobs j WHERE j.status = 'queued' {CLAIM_ORDER}"
+    ))?;
+    let ids = stmt
+        .query_map([], |row| row.get::<_, i64>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(ids.iter().position(|queued| *queued == id).map(|p| p + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn db() -> (TempDir, Connection) {
+        let tmp = TempDir::new().unwrap();
+        let conn = super::super::init_db(&tmp.path().join("zerobuild.db")).unwrap();
+        (tmp, conn)
+    }
+
+    fn enqueue(conn: &Connection, user: &str, priority: i64) -> i64 {
+        enqueue_job(
+            conn,
+            &NewJob {
+                user,
+                idea: "todo app",
+                priority,
+                ..NewJob::default()
+            },
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn claims_round_robin_across_users() {
+        let (_tmp, mut conn) = db();
+        let a1 = enqueue(&conn, "alice", 0);
+        let a2 = enqueue(&conn, "alice", 0);
+        let a3 = enqueue(&conn, "alice", 0);
+        let b1 = enqueue(&conn, "bob", 0);
+
+        assert_eq!(claim_next_job(&mut conn).unwrap().unwrap().id, a1);
+        // Alice already has a running job, so Bob is next.
+        assert_eq!(claim_next_job(&mut conn).unwrap().unwrap().id, b1);
+        assert_eq!(queue_position(&conn, a3).unwrap(), Some(2));
+        assert_eq!(claim_next_job(&mut conn).unwrap().unwrap().id, a2);
+        assert_eq!(claim_next_job(&mut conn).unwrap().unwrap().id, a3);
+        assert!(claim_next_job(&mut conn).unwrap().is_none());
+    }
+
+    #[test]
+    fn priority_beats_fairness() {
+        let (_tmp, mut conn) = db();
+        enqueue(&conn, "alice", 0);
+        let urgent = enqueue(&conn, "alice", 1);
+        enqueue(&conn, "bob", 0);
+
+        let job = claim_next_job(&mut conn).unwrap().unwrap();
+        assert_eq!(job.id, urgent);
+        assert_eq!(job.status, JobStatus::Running);
+        assert!(job.started_at.is_some());
+    }
+
+    #[test]
+    fn finish_cancel_and_requeue() {
+        let (_tmp, mut conn) = db();
+        let done = enqueue(&conn, "alice", 0);
+        let interrupted = enqueue(&conn, "alice", 0);
+        let cancelled = enqueue(&conn, "alice", 0);
+
+        claim_next_job(&mut conn).unwrap();
+        finish_job(&conn, done, Ok("deployed")).unwrap();
+        let job = get_job(&conn, done).unwrap().unwrap();
+        assert_eq!(job.status, JobStatus::Succeeded);
+        assert_eq!(job.output.as_deref(), Some("deployed"));
+
+        assert!(!cancel_job(&conn, cancelled, "bob").unwrap());
+        assert!(cancel_job(&conn, cancelled, "alice").unwrap());
+        assert_eq!(pending_count_for_user(&conn, "alice").unwrap(), 1);
+
+        claim_next_job(&mut conn).unwrap();
+        assert_eq!(requeue_interrupted(&conn).unwrap(), 1);
+        let job = get_job(&conn, interrupted).unwrap().unwrap();
+        assert_eq!(job.status, JobStatus::Queued);
+
+        let listed = list_jobs(&conn, Some("alice"), 10).unwrap();
+        assert_eq!(listed.len(), 3);
+        assert_eq!(listed[0].id, cancelled);
+        assert_eq!(listed[0].status, JobStatus::Cancelled);
+        assert!(list_jobs(&conn, Some("bob"), 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn keeps_latest_progress_line() {
+        let (_tmp, conn) = db();
+        let id = enqueue(&conn, "alice", 0);
+        assert_eq!(progress(&conn, id).unwrap(), None);
+        set_progress(&conn, id, "Business Analyst creating PRD").unwrap();
+        set_progress(&conn, id, "Deploying to GitHub").unwrap();
+        assert_eq!(
+            progress(&conn, id).unwrap().as_deref(),
+            Some("Deploying to GitHub")
+        );
+    }
+}
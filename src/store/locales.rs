@@ -0,0 +1,56 @@
+//! Message languages users picked with the `/lang` chat command (see
+//! [`crate::i18n`]), keyed like [`crate::security::users`] as
+//! `<channel>:<sender>`.
+
+use anyhow::Result;
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+
+/// The locale code `user` picked, if any.
+pub fn load(conn: &Connection, user: &str) -> Result<Option<String>> {
+    Ok(conn
+        .query_row(
+            "SELECT locale FROM user_locales WHERE user = ?1",
+            params![user],
+            |row| row.get(0),
+        )
+        .optional()?)
+}
+
+/// Record that `user` wants messages in `locale`.
+pub fn save(conn: &Connection, user: &str, locale: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO user_locales (user, locale, updated_at) VALUES (?1, ?2, ?3)
+         ON CONFLICT(user) DO UPDATE SET
+             locale = excluded.locale,
+             updated_at = excluded.updated_at",
+        params![user, locale, Utc::now().to_rfc3339()],
+    )?;
+    Ok(())
+}
+
+/// Forget `user`'s choice so their locale is detected again.
+pub fn clear(conn: &Connection, user: &str) -> Result<()> {
+    conn.execute("DELETE FROM user_locales WHERE user = ?1", params![user])?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn user_locales_roundtrip() {
+        let tmp = TempDir::new().unwrap();
+        let conn = crate::store::init_db(&tmp.path().join("zerobuild.db")).unwrap();
+        assert_eq!(load(&conn, "telegram:42").unwrap(), None);
+
+        save(&conn, "telegram:42", "ru").unwrap();
+        save(&conn, "telegram:42", "vi").unwrap();
+        assert_eq!(load(&conn, "telegram:42").unwrap().as_deref(), Some("vi"));
+
+        clear(&conn, "telegram:42").unwrap();
+        assert_eq!(load(&conn, "telegram:42").unwrap(), None);
+    }
+}
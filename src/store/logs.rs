@@ -0,0 +1,114 @@
+//! Full output of sandbox commands whose tool output was truncated.
+//!
+//! `sandbox_run_command` shows the model only the head and tail of long
+//! output; the complete stdout and stderr are kept here under a short ID so
+//! the `get_log` tool and the gateway can return any part of them later.
+//! Only the most recent [`MAX_LOGS`] logs are kept.
+
+use anyhow::Result;
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+
+/// Number of logs kept; older ones are deleted when a new one is saved.
+pub const MAX_LOGS: usize = 50;
+
+/// A stored command log.
+#[derive(Debug, Clone, Serialize)]
+pub struct CommandLog {
+    pub id: String,
+    pub command: String,
+    pub workdir: String,
+    pub exit_code: i64,
+    pub stdout: String,
+    pub stderr: String,
+    pub created_at: String,
+}
+
+impl CommandLog {
+    /// Text of `stream` (`stdout` or `stderr`).
+    pub fn stream(&self, stream: &str) -> Result<&str> {
+        match stream {
+            "stdout" => Ok(&self.stdout),
+            "stderr" => Ok(&self.stderr),
+            other => anyhow::bail!("Unknown stream '{other}'; expected stdout or stderr"),
+        }
+    }
+}
+
+/// Store the full output of a command and return the new log's ID.
+pub fn save_log(
+    conn: &Connection,
+    command: &str,
+    workdir: &str,
+    exit_code: i64,
+    stdout: &str,
+    stderr: &str,
+) -> Result<String> {
+    let id = format!("log-{}", &uuid::Uuid::new_v4().simple().to_string()[..12]);
+    conn.execute(
+        "INSERT INTO command_logs (id, command, workdir, exit_code, stdout, stderr, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            id,
+            command,
+            workdir,
+            exit_code,
+            stdout,
+            stderr,
+            Utc::now().to_rfc3339()
+        ],
+    )?;
+    conn.execute(
+        "DELETE FROM command_logs WHERE rowid NOT IN
+             (SELECT rowid FROM command_logs ORDER BY rowid DESC LIMIT ?1)",
+        params![i64::try_from(MAX_LOGS).unwrap_or(i64::MAX)],
+    )?;
+    Ok(id)
+}
+
+/// Load a log by ID. Returns `None` if it doesn't exist or was pruned.
+pub fn load_log(conn: &Connection, id: &str) -> Result<Option<CommandLog>> {
+    Ok(conn
+        .query_row(
+            "SELECT id, command, workdir, exit_code, stdout, stderr, created_at
+             FROM command_logs WHERE id = ?1",
+            params![id.trim()],
+            |row| {
+                Ok(CommandLog {
+                    id: row.get(0)?,
+                    command: row.get(1)?,
+                    workdir: row.get(2)?,
+                    exit_code: row.get(3)?,
+                    stdout: row.get(4)?,
+                    stderr: row.get(5)?,
+                    created_at: row.get(6)?,
+                })
+            },
+        )
+        .optional()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn logs_roundtrip_and_old_ones_are_pruned() {
+        let tmp = TempDir::new().unwrap();
+        let conn = super::super::init_db(&tmp.path().join("zerobuild.db")).unwrap();
+
+        let first = save_log(&conn, "npm run build", "project", 1, "out", "err").unwrap();
+        let log = load_log(&conn, &first).unwrap().unwrap();
+        assert_eq!(log.command, "npm run build");
+        assert_eq!(log.exit_code, 1);
+        assert_eq!(log.stream("stderr").unwrap(), "err");
+        assert!(log.stream("both").is_err());
+
+        for _ in 0..MAX_LOGS {
+            save_log(&conn, "true", "project", 0, "", "").unwrap();
+        }
+        assert!(load_log(&conn, &first).unwrap().is_none());
+    }
+}
@@ -1,16 +1,37 @@
 //! ZeroBuild store layer: SQLite-backed persistence for sandbox sessions,
-//! project snapshots, and GitHub OAuth tokens.
+//! project snapshots, and GitHub OAuth tokens. The other tables are documented
+//! in the submodule that reads and writes them.
 //!
 //! This replaces the Node.js backend's SQLite storage. All data is stored
 //! in a single database file at the path configured in `ZerobuildConfig.db_path`.
 
+pub mod artifacts;
+pub mod blackboard_log;
+pub mod build_usage;
+pub mod checkpoint;
+pub mod code_index;
+pub mod command_history;
+pub mod deployments;
+pub mod env_vars;
+pub mod history;
+pub mod issues;
+pub mod jobs;
+pub mod locales;
+pub mod logs;
+pub mod preferences;
+pub mod reviews;
+pub mod routes;
 pub mod session;
 pub mod snapshot;
 pub mod tokens;
+pub mod triage;
+pub mod user_data;
+pub mod users;
 
 use anyhow::Result;
+use parking_lot::Mutex;
 use rusqlite::Connection;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Initialize the ZeroBuild SQLite database and create tables if needed.
 pub fn init_db(db_path: &Path) -> Result<Connection> {
@@ -38,13 +59,304 @@ pub fn init_db(db_path: &Path) -> Result<Connection> {
              updated_at TEXT NOT NULL
          );
 
+         CREATE TABLE IF NOT EXISTS snapshot_project (
+             id INTEGER PRIMARY KEY CHECK (id = 1),
+             name TEXT NOT NULL,
+             updated_at TEXT NOT NULL
+         );
+
          CREATE TABLE IF NOT EXISTS tokens (
              id INTEGER PRIMARY KEY CHECK (id = 1),
              github_token TEXT,
              github_username TEXT,
              updated_at TEXT NOT NULL
+         );
+
+         CREATE TABLE IF NOT EXISTS github_token_scopes (
+             id INTEGER PRIMARY KEY CHECK (id = 1),
+             scopes TEXT,
+             checked_at TEXT NOT NULL
+         );
+
+         CREATE TABLE IF NOT EXISTS code_chunks (
+             id INTEGER PRIMARY KEY AUTOINCREMENT,
+             path TEXT NOT NULL,
+             start_line INTEGER NOT NULL,
+             end_line INTEGER NOT NULL,
+             content TEXT NOT NULL,
+             file_hash TEXT NOT NULL,
+             embedding BLOB
+         );
+         CREATE INDEX IF NOT EXISTS idx_code_chunks_path ON code_chunks(path);
+
+         CREATE TABLE IF NOT EXISTS checkpoint_conversations (
+             user_id TEXT PRIMARY KEY,
+             turns TEXT NOT NULL,
+             updated_at TEXT NOT NULL
+         );
+
+         CREATE TABLE IF NOT EXISTS checkpoints (
+             user_id TEXT NOT NULL,
+             name TEXT NOT NULL,
+             parent TEXT,
+             note TEXT,
+             files TEXT NOT NULL,
+             project_type TEXT,
+             conversation TEXT NOT NULL,
+             created_at TEXT NOT NULL,
+             PRIMARY KEY (user_id, name)
+         );
+
+         CREATE TABLE IF NOT EXISTS checkpoint_active (
+             user_id TEXT PRIMARY KEY,
+             checkpoint TEXT NOT NULL
+         );
+
+         CREATE TABLE IF NOT EXISTS checkpoint_restores (
+             user_id TEXT PRIMARY KEY,
+             checkpoint TEXT NOT NULL
+         );
+
+         CREATE TABLE IF NOT EXISTS history_messages (
+             id INTEGER PRIMARY KEY AUTOINCREMENT,
+             conversation TEXT NOT NULL,
+             role TEXT NOT NULL,
+             content TEXT NOT NULL,
+             created_at TEXT NOT NULL
+         );
+         CREATE INDEX IF NOT EXISTS idx_history_conversation
+             ON history_messages(conversation, created_at);
+         CREATE VIRTUAL TABLE IF NOT EXISTS history_fts USING fts5(
+             content, content=history_messages, content_rowid=id
+         );
+         CREATE TRIGGER IF NOT EXISTS history_messages_ai AFTER INSERT ON history_messages BEGIN
+             INSERT INTO history_fts(rowid, content) VALUES (new.id, new.content);
+         END;
+         CREATE TRIGGER IF NOT EXISTS history_messages_ad AFTER DELETE ON history_messages BEGIN
+             INSERT INTO history_fts(history_fts, rowid, content)
+             VALUES ('delete', old.id, old.content);
+         END;
+
+         CREATE TABLE IF NOT EXISTS build_jobs (
+             id INTEGER PRIMARY KEY AUTOINCREMENT,
+             user TEXT NOT NULL,
+             idea TEXT NOT NULL,
+             project_type TEXT,
+             project TEXT,
+             priority INTEGER NOT NULL DEFAULT 0,
+             status TEXT NOT NULL,
+             output TEXT,
+             error TEXT,
+             created_at TEXT NOT NULL,
+             started_at TEXT,
+             finished_at TEXT
+         );
+         CREATE INDEX IF NOT EXISTS idx_build_jobs_status
+             ON build_jobs(status, priority);
+         CREATE INDEX IF NOT EXISTS idx_build_jobs_user ON build_jobs(user, status);
+
+         CREATE TABLE IF NOT EXISTS build_job_progress (
+             job_id INTEGER PRIMARY KEY,
+             line TEXT NOT NULL,
+             updated_at TEXT NOT NULL
+         );
+
+         CREATE TABLE IF NOT EXISTS issue_builds (
+             repo TEXT NOT NULL,
+             number INTEGER NOT NULL,
+             job_id INTEGER NOT NULL,
+             comment_id INTEGER,
+             reported TEXT NOT NULL DEFAULT '',
+             done INTEGER NOT NULL DEFAULT 0,
+             created_at TEXT NOT NULL,
+             PRIMARY KEY (repo, number)
+         );
+
+         CREATE TABLE IF NOT EXISTS issue_triage (
+             repo TEXT NOT NULL,
+             number INTEGER NOT NULL,
+             kind TEXT NOT NULL,
+             labels TEXT NOT NULL,
+             asked INTEGER NOT NULL,
+             drafted INTEGER NOT NULL,
+             created_at TEXT NOT NULL,
+             PRIMARY KEY (repo, number)
+         );
+
+         CREATE TABLE IF NOT EXISTS triage_repos (
+             repo TEXT PRIMARY KEY,
+             since TEXT NOT NULL
+         );
+
+         CREATE TABLE IF NOT EXISTS pr_reviews (
+             repo TEXT NOT NULL,
+             number INTEGER NOT NULL,
+             head_sha TEXT NOT NULL,
+             event TEXT NOT NULL,
+             comments INTEGER NOT NULL,
+             created_at TEXT NOT NULL,
+             PRIMARY KEY (repo, number)
+         );
+
+         CREATE TABLE IF NOT EXISTS review_repos (
+             repo TEXT PRIMARY KEY,
+             since TEXT NOT NULL
+         );
+
+         CREATE TABLE IF NOT EXISTS command_logs (
+             id TEXT PRIMARY KEY,
+             command TEXT NOT NULL,
+             workdir TEXT NOT NULL,
+             exit_code INTEGER NOT NULL,
+             stdout TEXT NOT NULL,
+             stderr TEXT NOT NULL,
+             created_at TEXT NOT NULL
+         );
+
+         CREATE TABLE IF NOT EXISTS command_history (
+             id INTEGER PRIMARY KEY AUTOINCREMENT,
+             sandbox_id TEXT NOT NULL,
+             session TEXT,
+             command TEXT NOT NULL,
+             workdir TEXT NOT NULL,
+             exit_code INTEGER NOT NULL,
+             duration_ms INTEGER NOT NULL,
+             log_id TEXT,
+             created_at TEXT NOT NULL
+         );
+         CREATE INDEX IF NOT EXISTS idx_command_history_sandbox
+             ON command_history(sandbox_id, id);
+
+         CREATE TABLE IF NOT EXISTS blackboard_builds (
+             workflow_id TEXT PRIMARY KEY,
+             idea TEXT NOT NULL,
+             started_at TEXT NOT NULL
+         );
+
+         CREATE TABLE IF NOT EXISTS blackboard_events (
+             workflow_id TEXT NOT NULL,
+             seq INTEGER NOT NULL,
+             kind TEXT NOT NULL,
+             artifact TEXT NOT NULL,
+             agent TEXT,
+             version INTEGER,
+             value TEXT,
+             created_at TEXT NOT NULL,
+             PRIMARY KEY (workflow_id, seq)
+         );
+
+         CREATE TABLE IF NOT EXISTS model_routes (
+             hint TEXT PRIMARY KEY,
+             provider TEXT NOT NULL,
+             model TEXT NOT NULL,
+             updated_at TEXT NOT NULL
+         );
+
+         CREATE TABLE IF NOT EXISTS user_preferences (
+             user_id TEXT NOT NULL,
+             key TEXT NOT NULL,
+             value TEXT NOT NULL,
+             updated_at TEXT NOT NULL,
+             PRIMARY KEY (user_id, key)
+         );
+
+         CREATE TABLE IF NOT EXISTS env_vars (
+             name TEXT PRIMARY KEY,
+             description TEXT NOT NULL,
+             required INTEGER NOT NULL,
+             updated_at TEXT NOT NULL
+         );
+
+         CREATE TABLE IF NOT EXISTS user_locales (
+             user TEXT PRIMARY KEY,
+             locale TEXT NOT NULL,
+             updated_at TEXT NOT NULL
+         );
+
+         CREATE TABLE IF NOT EXISTS artifacts (
+             key TEXT PRIMARY KEY,
+             project TEXT NOT NULL,
+             kind TEXT NOT NULL,
+             size INTEGER NOT NULL,
+             created_at TEXT NOT NULL
+         );
+         CREATE INDEX IF NOT EXISTS idx_artifacts_project
+             ON artifacts(project, created_at);
+
+         CREATE TABLE IF NOT EXISTS build_usage (
+             id INTEGER PRIMARY KEY AUTOINCREMENT,
+             estimated_tokens INTEGER NOT NULL,
+             actual_tokens INTEGER NOT NULL,
+             estimated_secs INTEGER NOT NULL,
+             actual_secs INTEGER NOT NULL,
+             created_at TEXT NOT NULL
+         );
+
+         CREATE TABLE IF NOT EXISTS users (
+             user TEXT PRIMARY KEY,
+             role TEXT,
+             builds_per_day INTEGER,
+             sandbox_minutes_per_day INTEGER,
+             deploys_per_day INTEGER,
+             updated_at TEXT NOT NULL
+         );
+
+         CREATE TABLE IF NOT EXISTS user_usage (
+             user TEXT NOT NULL,
+             day TEXT NOT NULL,
+             builds INTEGER NOT NULL DEFAULT 0,
+             sandbox_secs INTEGER NOT NULL DEFAULT 0,
+             deploys INTEGER NOT NULL DEFAULT 0,
+             PRIMARY KEY (user, day)
+         );
+
+         CREATE TABLE IF NOT EXISTS deployments (
+             repo TEXT PRIMARY KEY,
+             project TEXT NOT NULL,
+             branch TEXT NOT NULL,
+             path TEXT,
+             deployed_at TEXT NOT NULL,
+             refreshed_at TEXT,
+             refresh_result TEXT
+         );
+
+         CREATE TABLE IF NOT EXISTS deployed_files (
+             repo TEXT PRIMARY KEY,
+             files TEXT NOT NULL,
+             changelog TEXT,
+             release_tag TEXT
          );",
     )?;
 
     Ok(conn)
 }
+
+/// The ZeroBuild store at one path, opened (and its tables created) on first
+/// use and then reused, for callers that touch it on every message.
+pub struct SharedStore {
+    path: PathBuf,
+    conn: Mutex<Option<Connection>>,
+}
+
+impl SharedStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            conn: Mutex::new(None),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Run `f` on the shared connection, opening it first if needed.
+    pub fn with<T>(&self, f: impl FnOnce(&Connection) -> Result<T>) -> Result<T> {
+        let mut guard = self.conn.lock();
+        let conn = match &mut *guard {
+            Some(conn) => conn,
+            slot @ None => slot.insert(init_db(&self.path)?),
+        };
+        f(conn)
+    }
+}
@@ -0,0 +1,270 @@
+//! User preferences set with the `preferences` tool or the `/prefs` chat
+//! command: the package manager, CSS framework, language, code style,
+//! license, and deploy target new projects should use. They are added to
+//! every system prompt and to the `sandbox_create` output, so users don't
+//! repeat them in each request.
+//!
+//! Preferences belong to one user, keyed like [`crate::security::users`]
+//! as `<channel>:<sender>`; the CLI and other calls outside a user scope
+//! share [`LOCAL_USER`].
+
+use crate::sandbox::PackageManager;
+use crate::tools::licensing;
+use anyhow::Result;
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::fmt::Write;
+use std::path::Path;
+
+/// Preference keys as `(key, label, accepted values)`, in prompt order.
+pub const PREFERENCES: &[(&str, &str, &str)] = &[
+    (
+        "package_manager",
+        "Package manager",
+        "npm, pnpm, yarn or bun",
+    ),
+    (
+        "css_framework",
+        "CSS framework",
+        "e.g. tailwind, css-modules, bootstrap",
+    ),
+    ("language", "Language", "e.g. typescript, javascript"),
+    (
+        "code_style",
+        "Code style",
+        "free text, e.g. functional components, 2-space indent",
+    ),
+    ("license", "License", "an SPDX id such as MIT or Apache-2.0"),
+    (
+        "deploy_target",
+        "Deploy target",
+        "e.g. vercel, netlify, github-pages",
+    ),
+];
+
+/// User recorded for preferences set, and build jobs enqueued, outside a
+/// channel or gateway user scope.
+pub const LOCAL_USER: &str = "local";
+
+/// Longest accepted preference value.
+const MAX_VALUE_CHARS: usize = 200;
+
+/// A stored preference.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Preference {
+    pub key: String,
+    pub value: String,
+    pub updated_at: String,
+}
+
+/// The known key for `key` (case-insensitive, `-` accepted for `_`).
+pub fn canonical_key(key: &str) -> Option<&'static str> {
+    let key = key.trim().to_ascii_lowercase().replace('-', "_");
+    PREFERENCES
+        .iter()
+        .map(|(known, _, _)| *known)
+        .find(|known| *known == key)
+}
+
+fn label(key: &str) -> &str {
+    PREFERENCES
+        .iter()
+        .find(|(known, _, _)| *known == key)
+        .map_or(key, |(_, label, _)| *label)
+}
+
+/// Validate `value` for `key`, returning the known key and the value as
+/// stored: package managers and licenses are canonicalized, free-text values
+/// are trimmed.
+pub fn normalize(key: &str, value: &str) -> Result<(&'static str, String)> {
+    let Some(key) = canonical_key(key) else {
+        anyhow::bail!(
+            "Unknown preference '{key}'. Valid: {}",
+            PREFERENCES
+                .iter()
+                .map(|(key, _, _)| *key)
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    };
+    let value = value.trim();
+    if value.is_empty() {
+        anyhow::bail!("Missing value for '{key}'");
+    }
+    if value.chars().count() > MAX_VALUE_CHARS || value.contains('\n') {
+        anyhow::bail!(
+            "The value for '{key}' must be one line of at most {MAX_VALUE_CHARS} characters"
+        );
+    }
+    let value = match key {
+        "package_manager" => PackageManager::from_name(&value.to_ascii_lowercase())
+            .ok_or_else(|| {
+                anyhow::anyhow!("Unknown package manager '{value}'. Valid: npm, pnpm, yarn, bun")
+            })?
+            .as_str()
+            .to_string(),
+        "license" => licensing::canonical(value)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Unsupported license '{value}'. Available: {}",
+                    licensing::SUPPORTED_LICENSES.join(", ")
+                )
+            })?
+            .to_string(),
+        _ => value.to_string(),
+    };
+    Ok((key, value))
+}
+
+/// The user whose preferences a tool call reads and writes: the sender of
+/// the message being handled, else [`LOCAL_USER`].
+pub fn current_user() -> String {
+    crate::security::users::current_user().unwrap_or_else(|| LOCAL_USER.to_string())
+}
+
+/// All preferences of `user`, in [`PREFERENCES`] order.
+pub fn list(conn: &Connection, user: &str) -> Result<Vec<Preference>> {
+    let mut stmt =
+        conn.prepare("SELECT key, value, updated_at FROM user_preferences WHERE user_id = ?1")?;
+    let mut prefs = stmt
+        .query_map(params![user], |row| {
+            Ok(Preference {
+                key: row.get(0)?,
+                value: row.get(1)?,
+                updated_at: row.get(2)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    prefs.sort_by_key(|pref| {
+        PREFERENCES
+            .iter()
+            .position(|(key, _, _)| *key == pref.key)
+            .unwrap_or(usize::MAX)
+    });
+    Ok(prefs)
+}
+
+/// `user`'s value of `key`, if any.
+pub fn get(conn: &Connection, user: &str, key: &str) -> Result<Option<String>> {
+    Ok(conn
+        .query_row(
+            "SELECT value FROM user_preferences WHERE user_id = ?1 AND key = ?2",
+            params![user, key],
+            |row| row.get(0),
+        )
+        .optional()?)
+}
+
+/// Validate and store `user`'s `value` for `key`, returning the stored key
+/// and value.
+pub fn save(
+    conn: &Connection,
+    user: &str,
+    key: &str,
+    value: &str,
+) -> Result<(&'static str, String)> {
+    let (key, value) = normalize(key, value)?;
+    conn.execute(
+        "INSERT INTO user_preferences (user_id, key, value, updated_at) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(user_id, key) DO UPDATE SET
+             value = excluded.value,
+             updated_at = excluded.updated_at",
+        params![user, key, value, Utc::now().to_rfc3339()],
+    )?;
+    Ok((key, value))
+}
+
+/// Delete `user`'s preference `key`. Returns `false` if it was not set.
+pub fn delete(conn: &Connection, user: &str, key: &str) -> Result<bool> {
+    let key = canonical_key(key).unwrap_or(key);
+    Ok(conn.execute(
+        "DELETE FROM user_preferences WHERE user_id = ?1 AND key = ?2",
+        params![user, key],
+    )? > 0)
+}
+
+/// `- Label: value` lines for `prefs`.
+pub fn format_lines(prefs: &[Preference]) -> String {
+    let mut lines = String::new();
+    for pref in prefs {
+        let _ = writeln!(lines, "- {}: {}", label(&pref.key), pref.value);
+    }
+    lines
+}
+
+/// System prompt section for `prefs`; empty when there are none.
+pub fn prompt_section(prefs: &[Preference]) -> String {
+    if prefs.is_empty() {
+        return String::new();
+    }
+    format!(
+        "## User Preferences\n\n\
+         Defaults the user chose for new projects. Follow them when scaffolding, \
+         installing, writing code and deploying unless the user asks for something \
+         else in this conversation:\n{}",
+        format_lines(prefs)
+    )
+}
+
+/// [`prompt_section`] for `user`'s preferences in the database at
+/// `db_path`; empty when there are none or the store can't be read.
+pub fn load_prompt_section(db_path: &Path, user: &str) -> String {
+    match super::init_db(db_path).and_then(|conn| list(&conn, user)) {
+        Ok(prefs) => prompt_section(&prefs),
+        Err(e) => {
+            tracing::debug!("Failed to load user preferences: {e}");
+            String::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const USER: &str = "telegram:42";
+
+    #[test]
+    fn preferences_are_validated_per_user_and_rendered_in_order() {
+        let tmp = TempDir::new().unwrap();
+        let db_path = tmp.path().join("zerobuild.db");
+        let conn = crate::store::init_db(&db_path).unwrap();
+        assert_eq!(load_prompt_section(&db_path, USER), "");
+
+        assert_eq!(
+            save(&conn, USER, "license", "apache-2.0").unwrap(),
+            ("license", "Apache-2.0".into())
+        );
+        assert_eq!(
+            save(&conn, USER, "Package-Manager", "pnpm@9").unwrap(),
+            ("package_manager", "pnpm".into())
+        );
+        save(&conn, USER, "css_framework", " tailwind ").unwrap();
+        assert!(save(&conn, USER, "package_manager", "cargo").is_err());
+        assert!(save(&conn, USER, "license", "WTFPL").is_err());
+        assert!(save(&conn, USER, "editor", "vim").is_err());
+        assert!(save(&conn, USER, "language", " ").is_err());
+
+        let keys: Vec<String> = list(&conn, USER)
+            .unwrap()
+            .into_iter()
+            .map(|p| p.key)
+            .collect();
+        assert_eq!(keys, ["package_manager", "css_framework", "license"]);
+        assert!(list(&conn, "discord:7").unwrap().is_empty());
+        assert_eq!(get(&conn, "discord:7", "license").unwrap(), None);
+        assert_eq!(
+            get(&conn, USER, "css_framework").unwrap().as_deref(),
+            Some("tailwind")
+        );
+
+        let section = load_prompt_section(&db_path, USER);
+        assert!(section.starts_with("## User Preferences"));
+        assert!(section.contains("- Package manager: pnpm\n- CSS framework: tailwind\n"));
+
+        assert!(delete(&conn, USER, "css-framework").unwrap());
+        assert!(!delete(&conn, USER, "css_framework").unwrap());
+        assert_eq!(get(&conn, USER, "css_framework").unwrap(), None);
+    }
+}
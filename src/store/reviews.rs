@@ -0,0 +1,93 @@
+//! Pull requests reviewed by [`crate::factory::review`], and since when each
+//! repository is watched. Each pull request is reviewed once.
+
+use anyhow::Result;
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+
+/// The review posted on one pull request.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReviewedPr {
+    /// `owner/repo`.
+    pub repo: String,
+    pub number: u64,
+    /// Commit the review was posted on.
+    pub head_sha: String,
+    /// `COMMENT` or `REQUEST_CHANGES`.
+    pub event: String,
+    /// Inline comments posted.
+    pub comments: usize,
+}
+
+/// Whether `repo#number` was reviewed before.
+pub fn is_reviewed(conn: &Connection, repo: &str, number: u64) -> Result<bool> {
+    Ok(conn
+        .query_row(
+            "SELECT 1 FROM pr_reviews WHERE repo = ?1 AND number = ?2",
+            params![repo, number],
+            |_| Ok(()),
+        )
+        .optional()?
+        .is_some())
+}
+
+/// Record the review of a pull request.
+pub fn record(conn: &Connection, review: &ReviewedPr) -> Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO pr_reviews
+             (repo, number, head_sha, event, comments, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            review.repo,
+            review.number,
+            review.head_sha,
+            review.event,
+            review.comments,
+            Utc::now().to_rfc3339()
+        ],
+    )?;
+    Ok(())
+}
+
+/// Since when `repo` is watched, as RFC 3339. The first call starts
+/// watching it now, so pull requests opened before are left alone.
+pub fn watched_since(conn: &Connection, repo: &str) -> Result<String> {
+    conn.execute(
+        "INSERT OR IGNORE INTO review_repos (repo, since) VALUES (?1, ?2)",
+        params![repo, Utc::now().to_rfc3339()],
+    )?;
+    Ok(conn.query_row(
+        "SELECT since FROM review_repos WHERE repo = ?1",
+        params![repo],
+        |row| row.get(0),
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn pull_requests_are_reviewed_once() {
+        let tmp = TempDir::new().unwrap();
+        let conn = super::super::init_db(&tmp.path().join("zerobuild.db")).unwrap();
+        let since = watched_since(&conn, "acme/shop").unwrap();
+        assert_eq!(watched_since(&conn, "acme/shop").unwrap(), since);
+
+        assert!(!is_reviewed(&conn, "acme/shop", 12).unwrap());
+        record(
+            &conn,
+            &ReviewedPr {
+                repo: "acme/shop".into(),
+                number: 12,
+                head_sha: "abc123".into(),
+                event: "COMMENT".into(),
+                comments: 2,
+            },
+        )
+        .unwrap();
+        assert!(is_reviewed(&conn, "acme/shop", 12).unwrap());
+        assert!(!is_reviewed(&conn, "acme/shop", 13).unwrap());
+    }
+}
@@ -0,0 +1,133 @@
+//! Model routes set by the user with the `model_routing` tool. A stored
+//! route replaces the `[[model_routes]]` entry with the same hint from the
+//! config file, so users can change routing from a chat without editing
+//! `config.toml`.
+
+use crate::config::ModelRouteConfig;
+use anyhow::Result;
+use chrono::Utc;
+use rusqlite::{params, Connection};
+
+/// A route from a task hint to a provider and model.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredRoute {
+    pub hint: String,
+    pub provider: String,
+    pub model: String,
+    pub updated_at: String,
+}
+
+/// All stored routes, ordered by hint.
+pub fn list_routes(conn: &Connection) -> Result<Vec<StoredRoute>> {
+    let mut stmt =
+        conn.prepare("SELECT hint, provider, model, updated_at FROM model_routes ORDER BY hint")?;
+    let routes = stmt
+        .query_map([], |row| {
+            Ok(StoredRoute {
+                hint: row.get(0)?,
+                provider: row.get(1)?,
+                model: row.get(2)?,
+                updated_at: row.get(3)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(routes)
+}
+
+/// Route `hint` to `provider` and `model`, replacing any stored route for it.
+pub fn save_route(conn: &Connection, hint: &str, provider: &str, model: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO model_routes (hint, provider, model, updated_at) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(hint) DO UPDATE SET
+             provider = excluded.provider,
+             model = excluded.model,
+             updated_at = excluded.updated_at",
+        params![hint, provider, model, Utc::now().to_rfc3339()],
+    )?;
+    Ok(())
+}
+
+/// Delete the stored route for `hint`. Returns `false` if there was none.
+pub fn delete_route(conn: &Connection, hint: &str) -> Result<bool> {
+    Ok(conn.execute("DELETE FROM model_routes WHERE hint = ?1", params![hint])? > 0)
+}
+
+/// `config_routes` with `stored` routes applied on top: a stored route
+/// replaces the configured one with the same hint, keeps its sampling
+/// settings, and keeps its API key override when the provider is unchanged.
+pub fn merge_routes(
+    config_routes: &[ModelRouteConfig],
+    stored: &[StoredRoute],
+) -> Vec<ModelRouteConfig> {
+    let mut routes = config_routes.to_vec();
+    for route in stored {
+        let configured = config_routes.iter().find(|r| r.hint == route.hint);
+        let api_key = configured
+            .filter(|r| r.provider == route.provider)
+            .and_then(|r| r.api_key.clone());
+        let merged = ModelRouteConfig {
+            hint: route.hint.clone(),
+            provider: route.provider.clone(),
+            model: route.model.clone(),
+            api_key,
+            temperature: configured.and_then(|r| r.temperature),
+            top_p: configured.and_then(|r| r.top_p),
+            seed: configured.and_then(|r| r.seed),
+        };
+        match routes.iter_mut().find(|r| r.hint == route.hint) {
+            Some(existing) => *existing = merged,
+            None => routes.push(merged),
+        }
+    }
+    routes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn stored_routes_override_configured_hints() {
+        let tmp = TempDir::new().unwrap();
+        let conn = crate::store::init_db(&tmp.path().join("zerobuild.db")).unwrap();
+        save_route(&conn, "code", "openai", "gpt-4o").unwrap();
+        save_route(&conn, "fast", "groq", "llama-3.3-70b-versatile").unwrap();
+        save_route(&conn, "code", "anthropic", "claude-sonnet-4-20250514").unwrap();
+
+        let stored = list_routes(&conn).unwrap();
+        assert_eq!(stored.len(), 2);
+        assert_eq!(stored[0].provider, "anthropic");
+
+        let configured = vec![
+            ModelRouteConfig {
+                hint: "code".into(),
+                provider: "anthropic".into(),
+                model: "claude-3-5-sonnet".into(),
+                api_key: Some("sk-route".into()),
+                temperature: None,
+                top_p: None,
+                seed: None,
+            },
+            ModelRouteConfig {
+                hint: "reasoning".into(),
+                provider: "openrouter".into(),
+                model: "anthropic/claude-opus-4".into(),
+                api_key: None,
+                temperature: None,
+                top_p: None,
+                seed: None,
+            },
+        ];
+        let merged = merge_routes(&configured, &stored);
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged[0].model, "claude-sonnet-4-20250514");
+        assert_eq!(merged[0].api_key.as_deref(), Some("sk-route"));
+        assert_eq!(merged[1].hint, "reasoning");
+        assert_eq!(merged[2].provider, "groq");
+
+        assert!(delete_route(&conn, "fast").unwrap());
+        assert!(!delete_route(&conn, "fast").unwrap());
+        assert_eq!(list_routes(&conn).unwrap().len(), 1);
+    }
+}
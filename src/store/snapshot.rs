@@ -1,5 +1,9 @@
 //! Project snapshot persistence: save/load source files as a JSON map
 //! `{"/path": "content"}` so the project can be restored after sandbox expiry.
+//!
+//! The snapshot's project name keys project-scoped memory
+//! (`memory/project_<id>/`), so facts about a project come back whenever
+//! work on it resumes.
 
 use anyhow::Result;
 use chrono::Utc;
@@ -53,3 +57,157 @@ pub fn save_snapshot(
 
     Ok(files.len())
 }
+
+/// When the snapshot was last saved, as RFC 3339; `None` before the first save.
+pub fn snapshot_saved_at(conn: &Connection) -> Result<Option<String>> {
+    Ok(conn
+        .query_row("SELECT updated_at FROM snapshots WHERE id = 1", [], |row| {
+            row.get(0)
+        })
+        .optional()?)
+}
+
+/// Project type (stack) of the saved snapshot, if one was recorded.
+pub fn load_project_type(conn: &Connection) -> Result<Option<String>> {
+    Ok(conn
+        .query_row(
+            "SELECT project_type FROM snapshots WHERE id = 1",
+            [],
+            |row| row.get(0),
+        )
+        .optional()?
+        .flatten())
+}
+
+/// Name of the project the snapshot belongs to, if one was recorded.
+pub fn load_project(conn: &Connection) -> Result<Option<String>> {
+    Ok(conn
+        .query_row(
+            "SELECT name FROM snapshot_project WHERE id = 1",
+            [],
+            |row| row.get(0),
+        )
+        .optional()?)
+}
+
+/// Record the project the snapshot belongs to (upsert — always row id=1).
+pub fn save_project(conn: &Connection, name: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO snapshot_project (id, name, updated_at)
+         VALUES (1, ?1, ?2)
+         ON CONFLICT(id) DO UPDATE SET
+             name = excluded.name,
+             updated_at = excluded.updated_at",
+        params![name.trim(), Utc::now().to_rfc3339()],
+    )?;
+    Ok(())
+}
+
+/// The snapshot and project rows as saved, for [`restore_stash`].
+#[derive(Debug, Clone)]
+pub struct SnapshotStash {
+    /// files, project type, saved at
+    snapshot: Option<(String, Option<String>, String)>,
+    /// name, saved at
+    project: Option<(String, String)>,
+}
+
+/// Set the saved snapshot and project aside, to put them back unchanged
+/// (save times included) after work that replaces them for a while.
+pub fn stash(conn: &Connection) -> Result<SnapshotStash> {
+    let snapshot = conn
+        .query_row(
+            "SELECT files, project_type, updated_at FROM snapshots WHERE id = 1",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()?;
+    let project = conn
+        .query_row(
+            "SELECT name, updated_at FROM snapshot_project WHERE id = 1",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    Ok(SnapshotStash { snapshot, project })
+}
+
+/// Put back the snapshot and project [`stash`] set aside, removing any saved
+/// since when there were none.
+pub fn restore_stash(conn: &Connection, stash: &SnapshotStash) -> Result<()> {
+    match &stash.snapshot {
+        Some((files, project_type, saved_at)) => conn.execute(
+            "INSERT INTO snapshots (id, files, project_type, updated_at)
+             VALUES (1, ?1, ?2, ?3)
+             ON CONFLICT(id) DO UPDATE SET
+                 files = excluded.files,
+                 project_type = excluded.project_type,
+                 updated_at = excluded.updated_at",
+            params![files, project_type, saved_at],
+        )?,
+        None => conn.execute("DELETE FROM snapshots WHERE id = 1", [])?,
+    };
+    match &stash.project {
+        Some((name, saved_at)) => conn.execute(
+            "INSERT INTO snapshot_project (id, name, updated_at)
+             VALUES (1, ?1, ?2)
+             ON CONFLICT(id) DO UPDATE SET
+                 name = excluded.name,
+                 updated_at = excluded.updated_at",
+            params![name, saved_at],
+        )?,
+        None => conn.execute("DELETE FROM snapshot_project WHERE id = 1", [])?,
+    };
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn project_name_roundtrips() {
+        let tmp = TempDir::new().unwrap();
+        let conn = super::super::init_db(&tmp.path().join("zerobuild.db")).unwrap();
+        assert!(load_project(&conn).unwrap().is_none());
+        save_project(&conn, " Todo App ").unwrap();
+        save_project(&conn, "Invoice Tracker").unwrap();
+        assert_eq!(
+            load_project(&conn).unwrap().as_deref(),
+            Some("Invoice Tracker")
+        );
+    }
+
+    #[test]
+    fn stash_puts_the_snapshot_and_project_back() {
+        let tmp = TempDir::new().unwrap();
+        let conn = super::super::init_db(&tmp.path().join("zerobuild.db")).unwrap();
+        let files = HashMap::from([("/src/app.ts".to_string(), "mine".to_string())]);
+        save_snapshot(&conn, &files, Some("nextjs")).unwrap();
+        save_project(&conn, "Shop").unwrap();
+        let saved_at = snapshot_saved_at(&conn).unwrap();
+
+        let stashed = stash(&conn).unwrap();
+        let theirs = HashMap::from([("/api/main.rs".to_string(), "theirs".to_string())]);
+        save_snapshot(&conn, &theirs, None).unwrap();
+        save_project(&conn, "Peer API").unwrap();
+        restore_stash(&conn, &stashed).unwrap();
+
+        let (restored, project_type) = load_snapshot(&conn).unwrap().unwrap();
+        assert_eq!(restored, files);
+        assert_eq!(project_type.as_deref(), Some("nextjs"));
+        assert_eq!(load_project(&conn).unwrap().as_deref(), Some("Shop"));
+        assert_eq!(snapshot_saved_at(&conn).unwrap(), saved_at);
+
+        // Nothing saved before: nothing left after.
+        let tmp = TempDir::new().unwrap();
+        let conn = super::super::init_db(&tmp.path().join("zerobuild.db")).unwrap();
+        let stashed = stash(&conn).unwrap();
+        save_snapshot(&conn, &theirs, None).unwrap();
+        save_project(&conn, "Peer API").unwrap();
+        restore_stash(&conn, &stashed).unwrap();
+        assert!(load_snapshot(&conn).unwrap().is_none());
+        assert!(load_project(&conn).unwrap().is_none());
+    }
+}
@@ -1,6 +1,7 @@
 //! GitHub connector - OAuth token persistence.
 //!
-//! Stores GitHub connector tokens in the local SQLite database.
+//! Stores GitHub connector tokens, and the OAuth scopes GitHub reported for
+//! them, in the local SQLite database.
 //! The database should only be accessible to the local user running ZeroBuild.
 
 use anyhow::Result;
@@ -12,30 +13,55 @@ use rusqlite::{params, Connection, OptionalExtension};
 pub struct GitHubToken {
     pub token: String,
     pub username: Option<String>,
+    /// OAuth scopes GitHub reported for the token; `None` when they haven't
+    /// been verified or GitHub doesn't report them (fine-grained tokens).
+    pub scopes: Option<Vec<String>>,
 }
 
 /// Load the stored GitHub token, if any.
 pub fn load_github_token(conn: &Connection) -> Result<Option<GitHubToken>> {
-    let mut stmt = conn.prepare("SELECT github_token, github_username FROM tokens WHERE id = 1")?;
+    let mut stmt = conn.prepare(
+        "SELECT t.github_token, t.github_username, s.scopes
+         FROM tokens t LEFT JOIN github_token_scopes s ON s.id = 1
+         WHERE t.id = 1",
+    )?;
 
     let result = stmt
         .query_row([], |row| {
             let token: Option<String> = row.get(0)?;
             let username: Option<String> = row.get(1)?;
-            Ok((token, username))
+            let scopes: Option<String> = row.get(2)?;
+            Ok((token, username, scopes))
         })
         .optional()?;
 
     match result {
-        None => Ok(None),
-        Some((None, _)) => Ok(None),
-        Some((Some(token), username)) => Ok(Some(GitHubToken { token, username })),
+        None | Some((None, _, _)) => Ok(None),
+        Some((Some(token), username, scopes)) => {
+            crate::security::redaction::register_secret(&token);
+            let scopes = scopes.map(|scopes| {
+                scopes
+                    .split(',')
+                    .filter(|scope| !scope.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            });
+            Ok(Some(GitHubToken {
+                token,
+                username,
+                scopes,
+            }))
+        }
     }
 }
 
-/// Persist a GitHub token (upsert — always row id=1).
+/// Persist a GitHub token (upsert — always row id=1). The scopes of the
+/// previous token are forgotten until [`save_github_scopes`] records the
+/// new token's.
 pub fn save_github_token(conn: &Connection, token: &str, username: Option<&str>) -> Result<()> {
+    crate::security::redaction::register_secret(token);
     let now = Utc::now().to_rfc3339();
+    conn.execute("DELETE FROM github_token_scopes", [])?;
     conn.execute(
         "INSERT INTO tokens (id, github_token, github_username, updated_at)
          VALUES (1, ?1, ?2, ?3)
@@ -48,11 +74,49 @@ pub fn save_github_token(conn: &Connection, token: &str, username: Option<&str>)
     Ok(())
 }
 
+/// Record the OAuth scopes GitHub reported for the stored token; `None`
+/// when it reports none (fine-grained tokens).
+pub fn save_github_scopes(conn: &Connection, scopes: Option<&[String]>) -> Result<()> {
+    let now = Utc::now().to_rfc3339();
+    conn.execute(
+        "INSERT INTO github_token_scopes (id, scopes, checked_at) VALUES (1, ?1, ?2)
+         ON CONFLICT(id) DO UPDATE SET
+             scopes = excluded.scopes,
+             checked_at = excluded.checked_at",
+        params![scopes.map(|scopes| scopes.join(",")), now],
+    )?;
+    Ok(())
+}
+
 /// Clear the stored GitHub token.
 pub fn clear_github_token(conn: &Connection) -> Result<()> {
+    conn.execute("DELETE FROM github_token_scopes", [])?;
     conn.execute(
         "UPDATE tokens SET github_token = NULL, github_username = NULL WHERE id = 1",
         [],
     )?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn scopes_belong_to_the_saved_token() {
+        let tmp = TempDir::new().unwrap();
+        let conn = crate::store::init_db(&tmp.path().join("zerobuild.db")).unwrap();
+        save_github_token(&conn, "gho_one", Some("alice")).unwrap();
+        assert_eq!(load_github_token(&conn).unwrap().unwrap().scopes, None);
+
+        save_github_scopes(&conn, Some(&["repo".to_string(), "gist".to_string()])).unwrap();
+        let tok = load_github_token(&conn).unwrap().unwrap();
+        assert_eq!(tok.scopes.unwrap(), ["repo", "gist"]);
+
+        save_github_token(&conn, "gho_two", Some("alice")).unwrap();
+        assert_eq!(load_github_token(&conn).unwrap().unwrap().scopes, None);
+        clear_github_token(&conn).unwrap();
+        assert!(load_github_token(&conn).unwrap().is_none());
+    }
+}
@@ -0,0 +1,97 @@
+//! Issues triaged by [`crate::factory::triage`], and since when each
+//! repository is watched. Each issue is triaged once.
+
+use anyhow::Result;
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+
+/// The outcome of triaging one issue.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TriagedIssue {
+    /// `owner/repo`.
+    pub repo: String,
+    pub number: u64,
+    /// `bug`, `feature`, `question`, or `skipped` for issues left alone.
+    pub kind: String,
+    /// Labels added, comma separated.
+    pub labels: String,
+    /// Whether reproduction details were asked for.
+    pub asked: bool,
+    /// Whether the issue was handed to the issue-to-build trigger.
+    pub drafted: bool,
+}
+
+/// Whether `repo#number` was triaged before.
+pub fn is_triaged(conn: &Connection, repo: &str, number: u64) -> Result<bool> {
+    Ok(conn
+        .query_row(
+            "SELECT 1 FROM issue_triage WHERE repo = ?1 AND number = ?2",
+            params![repo, number],
+            |_| Ok(()),
+        )
+        .optional()?
+        .is_some())
+}
+
+/// Record the triage of an issue.
+pub fn record(conn: &Connection, issue: &TriagedIssue) -> Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO issue_triage
+             (repo, number, kind, labels, asked, drafted, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            issue.repo,
+            issue.number,
+            issue.kind,
+            issue.labels,
+            issue.asked,
+            issue.drafted,
+            Utc::now().to_rfc3339()
+        ],
+    )?;
+    Ok(())
+}
+
+/// Since when `repo` is watched, as RFC 3339. The first call starts
+/// watching it now, so issues opened before are left alone.
+pub fn watched_since(conn: &Connection, repo: &str) -> Result<String> {
+    conn.execute(
+        "INSERT OR IGNORE INTO triage_repos (repo, since) VALUES (?1, ?2)",
+        params![repo, Utc::now().to_rfc3339()],
+    )?;
+    Ok(conn.query_row(
+        "SELECT since FROM triage_repos WHERE repo = ?1",
+        params![repo],
+        |row| row.get(0),
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn issues_are_triaged_once_and_watching_starts_on_first_sight() {
+        let tmp = TempDir::new().unwrap();
+        let conn = super::super::init_db(&tmp.path().join("zerobuild.db")).unwrap();
+        let since = watched_since(&conn, "acme/shop").unwrap();
+        assert_eq!(watched_since(&conn, "acme/shop").unwrap(), since);
+
+        assert!(!is_triaged(&conn, "acme/shop", 7).unwrap());
+        record(
+            &conn,
+            &TriagedIssue {
+                repo: "acme/shop".into(),
+                number: 7,
+                kind: "bug".into(),
+                labels: "bug,ui".into(),
+                asked: true,
+                drafted: false,
+            },
+        )
+        .unwrap();
+        assert!(is_triaged(&conn, "acme/shop", 7).unwrap());
+        assert!(!is_triaged(&conn, "acme/site", 7).unwrap());
+    }
+}
@@ -0,0 +1,110 @@
+//! Per-user wipe: deletes everything the store holds about one user in a
+//! single transaction.
+//!
+//! Tables key a user in one of two forms, both built from the same channel
+//! and sender: `<channel>:<sender>` (see [`crate::security::users`]) and the
+//! memory user id (see [`crate::memory::user_scoped::memory_user_id`]).
+
+use anyhow::Result;
+use rusqlite::{params, Connection};
+use std::collections::BTreeMap;
+
+/// Tables keyed by `<channel>:<sender>`, with their user column.
+const CHANNEL_USER_TABLES: [(&str, &str); 8] = [
+    ("user_preferences", "user_id"),
+    ("user_locales", "user"),
+    ("users", "user"),
+    ("user_usage", "user"),
+    ("checkpoint_conversations", "user_id"),
+    ("checkpoints", "user_id"),
+    ("checkpoint_active", "user_id"),
+    ("checkpoint_restores", "user_id"),
+];
+
+/// Delete `sender`'s rows on `channel` from every per-user table. Build jobs
+/// that are running are kept so their worker can finish them.
+///
+/// Returns the number of rows deleted per table.
+pub fn wipe_user(
+    conn: &mut Connection,
+    channel: &str,
+    sender: &str,
+) -> Result<BTreeMap<&'static str, usize>> {
+    let channel_user = format!("{channel}:{sender}");
+    let memory_user = crate::memory::user_scoped::memory_user_id(channel, sender);
+
+    let tx = conn.transaction()?;
+    let mut deleted = BTreeMap::new();
+    for (table, column) in CHANNEL_USER_TABLES {
+        let rows = tx.execute(
+            &format!("DELETE FROM {table} WHERE {column} = ?1"),
+            params![channel_user],
+        )?;
+        deleted.insert(table, rows);
+    }
+    deleted.insert(
+        "history_messages",
+        super::history::delete_conversation(&tx, &memory_user)?,
+    );
+    tx.execute(
+        "DELETE FROM build_job_progress WHERE job_id IN
+             (SELECT id FROM build_jobs WHERE user = ?1 AND status != 'running')",
+        params![memory_user],
+    )?;
+    deleted.insert(
+        "build_jobs",
+        tx.execute(
+            "DELETE FROM build_jobs WHERE user = ?1 AND status != 'running'",
+            params![memory_user],
+        )?,
+    );
+    tx.commit()?;
+    Ok(deleted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::ChatMessage;
+    use tempfile::TempDir;
+
+    #[test]
+    fn wipe_removes_only_that_users_rows() {
+        let tmp = TempDir::new().unwrap();
+        let mut conn = super::super::init_db(&tmp.path().join("zerobuild.db")).unwrap();
+        for sender in ["alice@x.io", "bob@x.io"] {
+            let user = format!("email:{sender}");
+            super::super::preferences::save(&conn, &user, "license", "mit").unwrap();
+            super::super::locales::save(&conn, &user, "vi").unwrap();
+            super::super::checkpoint::save_conversation(&conn, &user, &[ChatMessage::user("hi")])
+                .unwrap();
+            super::super::checkpoint::create_checkpoint(&conn, &user, "start", None).unwrap();
+            super::super::history::record_message(
+                &conn,
+                &crate::memory::user_scoped::memory_user_id("email", sender),
+                "user",
+                "hi",
+            )
+            .unwrap();
+        }
+
+        let deleted = wipe_user(&mut conn, "email", "alice@x.io").unwrap();
+        assert_eq!(deleted["user_preferences"], 1);
+        assert_eq!(deleted["user_locales"], 1);
+        assert_eq!(deleted["checkpoint_conversations"], 1);
+        assert_eq!(deleted["checkpoints"], 1);
+        assert_eq!(deleted["checkpoint_active"], 1);
+        assert_eq!(deleted["history_messages"], 1);
+        assert_eq!(deleted["users"], 0);
+
+        assert!(super::super::preferences::list(&conn, "email:alice@x.io")
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            super::super::locales::load(&conn, "email:bob@x.io")
+                .unwrap()
+                .as_deref(),
+            Some("vi")
+        );
+    }
+}
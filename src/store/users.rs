@@ -0,0 +1,313 @@
+//! Roles, per-user quota overrides and daily usage counters of channel and
+//! gateway users (see [`crate::security::users`]). Users are keyed as
+//! `<channel>:<sender>`; days are UTC dates (`YYYY-MM-DD`).
+
+use anyhow::Result;
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+
+/// A quota an admin can override per user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quota {
+    Builds,
+    SandboxMinutes,
+    Deploys,
+}
+
+impl Quota {
+    pub const ALL: [Self; 3] = [Self::Builds, Self::SandboxMinutes, Self::Deploys];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Builds => "builds",
+            Self::SandboxMinutes => "sandbox_minutes",
+            Self::Deploys => "deploys",
+        }
+    }
+
+    /// The quota named `name` (`-` accepted for `_`).
+    pub fn parse(name: &str) -> Option<Self> {
+        let name = name.trim().to_ascii_lowercase().replace('-', "_");
+        Self::ALL.into_iter().find(|quota| quota.as_str() == name)
+    }
+
+    fn column(self) -> &'static str {
+        match self {
+            Self::Builds => "builds_per_day",
+            Self::SandboxMinutes => "sandbox_minutes_per_day",
+            Self::Deploys => "deploys_per_day",
+        }
+    }
+}
+
+/// Role and quota overrides an admin set for a user. `None` fields fall
+/// back to the `[users]` config.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UserRecord {
+    pub user: String,
+    pub role: Option<String>,
+    pub builds_per_day: Option<u32>,
+    pub sandbox_minutes_per_day: Option<u32>,
+    pub deploys_per_day: Option<u32>,
+}
+
+impl UserRecord {
+    /// The override of `quota`, if any.
+    pub fn quota(&self, quota: Quota) -> Option<u32> {
+        match quota {
+            Quota::Builds => self.builds_per_day,
+            Quota::SandboxMinutes => self.sandbox_minutes_per_day,
+            Quota::Deploys => self.deploys_per_day,
+        }
+    }
+}
+
+/// Usage of one user on one day.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Usage {
+    pub builds: u32,
+    pub sandbox_secs: u64,
+    pub deploys: u32,
+}
+
+/// Today's UTC date, the key of the usage counters.
+pub fn today() -> String {
+    Utc::now().format("%Y-%m-%d").to_string()
+}
+
+fn to_u32(value: Option<i64>) -> Option<u32> {
+    value.map(|value| u32::try_from(value).unwrap_or(0))
+}
+
+fn read_record(row: &rusqlite::Row<'_>) -> rusqlite::Result<UserRecord> {
+    Ok(UserRecord {
+        user: row.get(0)?,
+        role: row.get(1)?,
+        builds_per_day: to_u32(row.get(2)?),
+        sandbox_minutes_per_day: to_u32(row.get(3)?),
+        deploys_per_day: to_u32(row.get(4)?),
+    })
+}
+
+/// The overrides stored for `user`, if any.
+pub fn get(conn: &Connection, user: &str) -> Result<Option<UserRecord>> {
+    Ok(conn
+        .query_row(
+            "SELECT user, role, builds_per_day, sandbox_minutes_per_day, deploys_per_day
+             FROM users WHERE user = ?1",
+            params![user],
+            read_record,
+        )
+        .optional()?)
+}
+
+/// Every user with stored overrides, by user ID.
+pub fn list(conn: &Connection) -> Result<Vec<UserRecord>> {
+    let mut stmt = conn.prepare(
+        "SELECT user, role, builds_per_day, sandbox_minutes_per_day, deploys_per_day
+         FROM users ORDER BY user",
+    )?;
+    let records = stmt
+        .query_map([], read_record)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(records)
+}
+
+fn ensure_user(conn: &Connection, user: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO users (user, updated_at) VALUES (?1, ?2)
+         ON CONFLICT(user) DO UPDATE SET updated_at = excluded.updated_at",
+        params![user, Utc::now().to_rfc3339()],
+    )?;
+    Ok(())
+}
+
+/// Set `user`'s role, or go back to the configured role with `None`.
+pub fn set_role(conn: &Connection, user: &str, role: Option<&str>) -> Result<()> {
+    ensure_user(conn, user)?;
+    conn.execute(
+        "UPDATE users SET role = ?2 WHERE user = ?1",
+        params![user, role],
+    )?;
+    Ok(())
+}
+
+/// Override `user`'s daily `quota` (`0` = unlimited), or go back to the
+/// configured limit with `None`.
+pub fn set_quota(conn: &Connection, user: &str, quota: Quota, limit: Option<u32>) -> Result<()> {
+    ensure_user(conn, user)?;
+    conn.execute(
+        &format!("UPDATE users SET {} = ?2 WHERE user = ?1", quota.column()),
+        params![user, limit.map(i64::from)],
+    )?;
+    Ok(())
+}
+
+/// What `user` used on `day`.
+pub fn usage(conn: &Connection, user: &str, day: &str) -> Result<Usage> {
+    Ok(conn
+        .query_row(
+            "SELECT builds, sandbox_secs, deploys FROM user_usage WHERE user = ?1 AND day = ?2",
+            params![user, day],
+            |row| {
+                Ok(Usage {
+                    builds: to_u32(Some(row.get(0)?)).unwrap_or(0),
+                    sandbox_secs: u64::try_from(row.get::<_, i64>(1)?).unwrap_or(0),
+                    deploys: to_u32(Some(row.get(2)?)).unwrap_or(0),
+                })
+            },
+        )
+        .optional()?
+        .unwrap_or_default())
+}
+
+/// Add `used` to `user`'s counters for `day`.
+pub fn add_usage(conn: &Connection, user: &str, day: &str, used: &Usage) -> Result<()> {
+    conn.execute(
+        "INSERT INTO user_usage (user, day, builds, sandbox_secs, deploys)
+         VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(user, day) DO UPDATE SET
+             builds = builds + excluded.builds,
+             sandbox_secs = sandbox_secs + excluded.sandbox_secs,
+             deploys = deploys + excluded.deploys",
+        params![
+            user,
+            day,
+            i64::from(used.builds),
+            i64::try_from(used.sandbox_secs).unwrap_or(i64::MAX),
+            i64::from(used.deploys)
+        ],
+    )?;
+    Ok(())
+}
+
+/// Daily limits a call is checked against; `None` = not checked.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Limits {
+    pub builds: Option<u32>,
+    pub sandbox_minutes: Option<u32>,
+    pub deploys: Option<u32>,
+}
+
+/// Add `used` to `user`'s counters for `day` if each counter with a limit
+/// is still below it. The check and the increment are one conditional
+/// `UPDATE`, so concurrent calls cannot both take the last unit of a quota.
+/// Returns whether `used` was added.
+pub fn try_add_usage(
+    conn: &Connection,
+    user: &str,
+    day: &str,
+    used: &Usage,
+    limits: &Limits,
+) -> Result<bool> {
+    conn.execute(
+        "INSERT INTO user_usage (user, day) VALUES (?1, ?2) ON CONFLICT(user, day) DO NOTHING",
+        params![user, day],
+    )?;
+    let changed = conn.execute(
+        "UPDATE user_usage SET
+             builds = builds + ?3,
+             sandbox_secs = sandbox_secs + ?4,
+             deploys = deploys + ?5
+         WHERE user = ?1 AND day = ?2
+           AND (?6 IS NULL OR builds < ?6)
+           AND (?7 IS NULL OR sandbox_secs < ?7 * 60)
+           AND (?8 IS NULL OR deploys < ?8)",
+        params![
+            user,
+            day,
+            i64::from(used.builds),
+            i64::try_from(used.sandbox_secs).unwrap_or(i64::MAX),
+            i64::from(used.deploys),
+            limits.builds.map(i64::from),
+            limits.sandbox_minutes.map(i64::from),
+            limits.deploys.map(i64::from),
+        ],
+    )?;
+    Ok(changed > 0)
+}
+
+/// Take back builds and deploys added by [`try_add_usage`] for a call that
+/// did not succeed.
+pub fn refund_usage(conn: &Connection, user: &str, day: &str, used: &Usage) -> Result<()> {
+    conn.execute(
+        "UPDATE user_usage SET
+             builds = MAX(builds - ?3, 0),
+             deploys = MAX(deploys - ?4, 0)
+         WHERE user = ?1 AND day = ?2",
+        params![user, day, i64::from(used.builds), i64::from(used.deploys)],
+    )?;
+    Ok(())
+}
+
+/// Zero `user`'s counters for `day`.
+pub fn reset_usage(conn: &Connection, user: &str, day: &str) -> Result<()> {
+    conn.execute(
+        "DELETE FROM user_usage WHERE user = ?1 AND day = ?2",
+        params![user, day],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn user_overrides_and_usage_roundtrip() {
+        let tmp = TempDir::new().unwrap();
+        let conn = crate::store::init_db(&tmp.path().join("zerobuild.db")).unwrap();
+        assert_eq!(get(&conn, "telegram:42").unwrap(), None);
+
+        set_role(&conn, "telegram:42", Some("viewer")).unwrap();
+        set_quota(&conn, "telegram:42", Quota::Deploys, Some(1)).unwrap();
+        let record = get(&conn, "telegram:42").unwrap().unwrap();
+        assert_eq!(record.role.as_deref(), Some("viewer"));
+        assert_eq!(record.quota(Quota::Deploys), Some(1));
+        assert_eq!(record.quota(Quota::Builds), None);
+        set_quota(&conn, "telegram:42", Quota::Deploys, None).unwrap();
+        assert_eq!(list(&conn).unwrap()[0].deploys_per_day, None);
+
+        let used = Usage {
+            builds: 1,
+            sandbox_secs: 90,
+            deploys: 0,
+        };
+        add_usage(&conn, "telegram:42", "2026-01-01", &used).unwrap();
+        add_usage(&conn, "telegram:42", "2026-01-01", &used).unwrap();
+        let usage = usage(&conn, "telegram:42", "2026-01-01").unwrap();
+        assert_eq!(usage.builds, 2);
+        assert_eq!(usage.sandbox_secs, 180);
+        reset_usage(&conn, "telegram:42", "2026-01-01").unwrap();
+        assert_eq!(
+            super::usage(&conn, "telegram:42", "2026-01-01").unwrap(),
+            Usage::default()
+        );
+        assert_eq!(Quota::parse("sandbox-minutes"), Some(Quota::SandboxMinutes));
+    }
+
+    #[test]
+    fn try_add_usage_stops_at_the_limit() {
+        let tmp = TempDir::new().unwrap();
+        let conn = crate::store::init_db(&tmp.path().join("zerobuild.db")).unwrap();
+        let deploy = Usage {
+            deploys: 1,
+            ..Usage::default()
+        };
+        let limits = Limits {
+            deploys: Some(2),
+            ..Limits::default()
+        };
+        let day = "2026-01-01";
+        assert!(try_add_usage(&conn, "discord:2", day, &deploy, &limits).unwrap());
+        assert!(try_add_usage(&conn, "discord:2", day, &deploy, &limits).unwrap());
+        assert!(!try_add_usage(&conn, "discord:2", day, &deploy, &limits).unwrap());
+        assert_eq!(usage(&conn, "discord:2", day).unwrap().deploys, 2);
+
+        refund_usage(&conn, "discord:2", day, &deploy).unwrap();
+        assert!(try_add_usage(&conn, "discord:2", day, &deploy, &limits).unwrap());
+        assert!(try_add_usage(&conn, "discord:2", day, &deploy, &Limits::default()).unwrap());
+        assert_eq!(usage(&conn, "discord:2", day).unwrap().deploys, 3);
+    }
+}
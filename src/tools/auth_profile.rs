@@ -105,6 +105,7 @@ impl ManageAuthProfileTool {
             success: true,
             output,
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
@@ -117,6 +118,7 @@ impl ManageAuthProfileTool {
             success: true,
             output: format!("Switched active profile for {provider} to: {profile_id}"),
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
@@ -134,6 +136,7 @@ impl ManageAuthProfileTool {
                         success: false,
                         output: String::new(),
                         error: Some(format!("OpenAI token refresh failed: {e}")),
+                        error_code: None,
                         error_hint: None,
                     })
                 }
@@ -146,6 +149,7 @@ impl ManageAuthProfileTool {
                         success: false,
                         output: String::new(),
                         error: Some(format!("Gemini token refresh failed: {e}")),
+                        error_code: None,
                         error_hint: None,
                     })
                 }
@@ -159,6 +163,7 @@ impl ManageAuthProfileTool {
                         success: false,
                         output: String::new(),
                         error: Some(format!("Token check failed for '{other}': {e}")),
+                        error_code: None,
                         error_hint: None,
                     }),
                 }
@@ -169,6 +174,7 @@ impl ManageAuthProfileTool {
             success: true,
             output: result,
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
@@ -224,6 +230,7 @@ impl Tool for ManageAuthProfileTool {
                         success: false,
                         output: String::new(),
                         error: Some("'provider' is required for switch action".into()),
+                        error_code: None,
                         error_hint: None,
                     });
                 };
@@ -239,6 +246,7 @@ impl Tool for ManageAuthProfileTool {
                         success: false,
                         output: String::new(),
                         error: Some("'provider' is required for refresh action".into()),
+                        error_code: None,
                         error_hint: None,
                     });
                 };
@@ -250,6 +258,7 @@ impl Tool for ManageAuthProfileTool {
                 error: Some(format!(
                     "Unknown action '{other}'. Valid: list, switch, refresh"
                 )),
+                error_code: None,
                 error_hint: None,
             }),
         };
@@ -260,6 +269,7 @@ impl Tool for ManageAuthProfileTool {
                 success: false,
                 output: String::new(),
                 error: Some(e.to_string()),
+                error_code: None,
                 error_hint: None,
             }),
         }
@@ -288,6 +288,7 @@ impl Tool for BgRunTool {
                 success: false,
                 output: String::new(),
                 error: Some("'arguments' must be an object".to_string()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -300,6 +301,7 @@ impl Tool for BgRunTool {
                     success: false,
                     output: String::new(),
                     error: Some(format!("unknown tool: {tool_name}")),
+                    error_code: None,
                     error_hint: None,
                 });
             }
@@ -311,6 +313,7 @@ impl Tool for BgRunTool {
                 success: false,
                 output: String::new(),
                 error: Some("cannot run bg_run or bg_status in background".to_string()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -325,6 +328,7 @@ impl Tool for BgRunTool {
                     "Maximum concurrent background jobs reached ({MAX_CONCURRENT_JOBS}). \
                      Wait for existing jobs to complete."
                 )),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -411,6 +415,7 @@ impl Tool for BgRunTool {
             success: true,
             output: serde_json::to_string_pretty(&output).unwrap_or_default(),
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
@@ -468,6 +473,7 @@ impl Tool for BgStatusTool {
                         success: false,
                         output: String::new(),
                         error: Some(format!("job not found: {id}")),
+                        error_code: None,
                         error_hint: None,
                     });
                 }
@@ -487,6 +493,7 @@ impl Tool for BgStatusTool {
             success: true,
             output,
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
@@ -0,0 +1,285 @@
+//! `blackboard_history` tool — time-travel through a factory build's
+//! blackboard.
+//!
+//! See [`crate::store::blackboard_log`] for what is journaled.
+
+use crate::store::{self, blackboard_log};
+use crate::tools::traits::{Tool, ToolResult};
+use crate::util::truncate_with_ellipsis;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::fmt::Write;
+use std::path::PathBuf;
+
+const TOOL_NAME: &str = "blackboard_history";
+
+/// Builds listed when no `workflow_id` is given.
+const MAX_BUILDS_LISTED: usize = 10;
+
+/// Longest artifact value shown in a state.
+const MAX_VALUE_CHARS: usize = 2000;
+
+/// Longest value preview shown in a timeline.
+const MAX_PREVIEW_CHARS: usize = 80;
+
+pub struct BlackboardHistoryTool {
+    db_path: PathBuf,
+}
+
+impl BlackboardHistoryTool {
+    pub fn new(db_path: impl Into<PathBuf>) -> Self {
+        Self {
+            db_path: db_path.into(),
+        }
+    }
+
+    fn run(&self, args: &Value) -> anyhow::Result<String> {
+        let conn = store::init_db(&self.db_path)?;
+        let Some(prefix) = args["workflow_id"]
+            .as_str()
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+        else {
+            return list_builds(&conn);
+        };
+        let build = blackboard_log::find_build(&conn, prefix)?.ok_or_else(|| {
+            anyhow::anyhow!(
+                "No journal for build '{prefix}'; only the last {} builds are kept",
+                blackboard_log::MAX_BUILDS
+            )
+        })?;
+        let artifact = args["artifact"].as_str().filter(|a| !a.is_empty());
+        let seq = args["seq"].as_i64();
+
+        let mut out = format!(
+            "Build {} ({}, started {}): {}\n",
+            build.workflow_id,
+            pluralize(build.events, "event"),
+            build.started_at,
+            truncate_with_ellipsis(&build.idea, MAX_PREVIEW_CHARS)
+        );
+        let events = blackboard_log::events(&conn, &build.workflow_id, seq)?;
+
+        let Some(seq) = seq else {
+            for event in events
+                .iter()
+                .filter(|e| artifact.is_none_or(|a| e.artifact == a))
+            {
+                let _ = writeln!(out, "{}", describe_event(event));
+            }
+            return Ok(out.trim_end().to_string());
+        };
+
+        let phase = events
+            .iter()
+            .rev()
+            .find(|e| e.kind == blackboard_log::EventKind::Phase)
+            .map_or("(none)", |e| e.artifact.as_str());
+        let _ = writeln!(out, "Blackboard after event #{seq}, phase {phase}:");
+        let state = blackboard_log::replay(&events);
+        let shown: Vec<_> = state
+            .iter()
+            .filter(|(key, _)| artifact.is_none_or(|a| key.as_str() == a))
+            .collect();
+        if shown.is_empty() {
+            let _ = write!(
+                out,
+                "{} was not published yet.",
+                artifact.unwrap_or("Nothing")
+            );
+            return Ok(out);
+        }
+        for (key, entry) in shown {
+            let value = match &entry.value {
+                Value::String(text) => text.clone(),
+                other => serde_json::to_string_pretty(other)?,
+            };
+            let _ = writeln!(
+                out,
+                "\n## {key} v{} by {} (#{})\n{}",
+                entry.version,
+                entry.agent.as_deref().unwrap_or("unknown"),
+                entry.seq,
+                truncate_with_ellipsis(&value, MAX_VALUE_CHARS)
+            );
+        }
+        Ok(out.trim_end().to_string())
+    }
+}
+
+fn list_builds(conn: &rusqlite::Connection) -> anyhow::Result<String> {
+    let builds = blackboard_log::builds(conn, MAX_BUILDS_LISTED)?;
+    if builds.is_empty() {
+        return Ok("No factory build has been journaled yet.".into());
+    }
+    let mut out = String::from("Recent factory builds (newest first):\n");
+    for build in builds {
+        let _ = writeln!(
+            out,
+            "- {} {} ({}): {}",
+            build.workflow_id,
+            build.started_at,
+            pluralize(build.events, "event"),
+            truncate_with_ellipsis(&build.idea, MAX_PREVIEW_CHARS)
+        );
+    }
+    Ok(out.trim_end().to_string())
+}
+
+fn describe_event(event: &blackboard_log::BlackboardEvent) -> String {
+    use blackboard_log::EventKind;
+    match event.kind {
+        EventKind::Phase => format!("#{} ── phase {}", event.seq, event.artifact),
+        EventKind::Publish => {
+            let preview = match &event.value {
+                Some(Value::String(text)) => text.clone(),
+                Some(other) => other.to_string(),
+                None => String::new(),
+            };
+            format!(
+                "#{} publish {} v{} by {}: {}",
+                event.seq,
+                event.artifact,
+                event.version.unwrap_or(0),
+                event.agent.as_deref().unwrap_or("unknown"),
+                truncate_with_ellipsis(&preview.replace('\n', " "), MAX_PREVIEW_CHARS)
+            )
+        }
+        EventKind::Read => match event.version {
+            Some(version) => format!("#{} read {} v{version}", event.seq, event.artifact),
+            None => format!("#{} read {} (not published yet)", event.seq, event.artifact),
+        },
+    }
+}
+
+fn pluralize(count: i64, noun: &str) -> String {
+    if count == 1 {
+        format!("1 {noun}")
+    } else {
+        format!("{count} {noun}s")
+    }
+}
+
+#[async_trait]
+impl Tool for BlackboardHistoryTool {
+    fn name(&self) -> &str {
+        TOOL_NAME
+    }
+
+    fn description(&self) -> &str {
+        "Time-travel through the blackboard of a factory build to debug it. Without arguments, \
+         lists recent builds. With workflow_id (or a unique prefix), lists every phase, publish \
+         and read in order, each with a sequence number. Add seq to see what the blackboard held \
+         right after that event, e.g. the phase marker where the Tester started, and artifact \
+         to focus on one artifact (e.g. 'prd')."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "workflow_id": {
+                    "type": "string",
+                    "description": "Build ID, or a unique prefix of it. Omit to list recent builds."
+                },
+                "seq": {
+                    "type": "integer",
+                    "description": "Show the blackboard right after this event instead of the timeline."
+                },
+                "artifact": {
+                    "type": "string",
+                    "description": "Only this artifact, e.g. 'prd', 'design_spec', 'test_results'."
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, args: Value) -> anyhow::Result<ToolResult> {
+        match self.run(&args) {
+            Ok(output) => Ok(ToolResult {
+                success: true,
+                output,
+                error: None,
+                error_code: None,
+                error_hint: None,
+            }),
+            Err(e) => Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(e.to_string()),
+                error_code: None,
+                error_hint: None,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blackboard_log::EventKind;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn shows_timeline_and_state_at_a_point() {
+        let tmp = TempDir::new().unwrap();
+        let db_path = tmp.path().join("zerobuild.db");
+        {
+            let conn = store::init_db(&db_path).unwrap();
+            blackboard_log::start_build(&conn, "3f2a9c1d-build", "todo app").unwrap();
+            let record = |kind, artifact: &str, agent, version, value: Option<Value>| {
+                blackboard_log::record(
+                    &conn,
+                    "3f2a9c1d-build",
+                    kind,
+                    artifact,
+                    agent,
+                    version,
+                    value.as_ref(),
+                )
+                .unwrap()
+            };
+            record(EventKind::Phase, "analysis", None, None, None);
+            record(
+                EventKind::Publish,
+                "prd",
+                Some("business_analyst"),
+                Some(1),
+                Some(json!("# PRD\nTodos")),
+            );
+            record(EventKind::Phase, "testing", None, None, None);
+            record(EventKind::Read, "design_spec", None, None, None);
+        }
+        let tool = BlackboardHistoryTool::new(&db_path);
+
+        let list = tool.execute(json!({})).await.unwrap();
+        assert!(list.output.contains("- 3f2a9c1d-build"), "{}", list.output);
+        assert!(list.output.contains("(4 events): todo app"));
+
+        let timeline = tool.execute(json!({"workflow_id": "3f2a"})).await.unwrap();
+        assert!(timeline
+            .output
+            .contains("#2 publish prd v1 by business_analyst: # PRD Todos"));
+        assert!(timeline
+            .output
+            .ends_with("#4 read design_spec (not published yet)"));
+
+        let state = tool
+            .execute(json!({"workflow_id": "3f2a", "seq": 3}))
+            .await
+            .unwrap();
+        assert!(state.output.contains("after event #3, phase testing"));
+        assert!(state
+            .output
+            .contains("## prd v1 by business_analyst (#2)\n# PRD\nTodos"));
+
+        let missing = tool
+            .execute(json!({"workflow_id": "3f2a", "seq": 1, "artifact": "prd"}))
+            .await
+            .unwrap();
+        assert!(missing.output.ends_with("prd was not published yet."));
+
+        let unknown = tool.execute(json!({"workflow_id": "nope"})).await.unwrap();
+        assert!(!unknown.success);
+    }
+}
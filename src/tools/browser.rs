@@ -11,6 +11,7 @@ use anyhow::Context;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use std::fmt::Write;
 use std::net::ToSocketAddrs;
 use std::process::Stdio;
 use std::sync::Arc;
@@ -129,6 +130,29 @@ struct ComputerUseResponse {
     error: Option<String>,
 }
 
+/// Loads axe-core into the page when missing, runs it and resolves to the
+/// violations as a JSON string (`id`, `impact`, `help`, `helpUrl`, and the
+/// offending `nodes` with their `target` selectors).
+const AXE_AUDIT_SCRIPT: &str = r#"(async () => {
+  if (!window.axe) {
+    await new Promise((resolve, reject) => {
+      const script = document.createElement('script');
+      script.src = 'https://cdn.jsdelivr.net/npm/axe-core@4/axe.min.js';
+      script.onload = resolve;
+      script.onerror = () => reject(new Error('failed to load axe-core'));
+      document.head.appendChild(script);
+    });
+  }
+  const results = await window.axe.run(document, { resultTypes: ['violations'] });
+  return JSON.stringify(results.violations.map((v) => ({
+    id: v.id,
+    impact: v.impact,
+    help: v.help,
+    helpUrl: v.helpUrl,
+    nodes: v.nodes.map((n) => ({ target: n.target, failureSummary: n.failureSummary })),
+  })));
+})()"#;
+
 /// Supported browser actions
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -194,6 +218,8 @@ pub enum BrowserAction {
         #[serde(default)]
         fill_value: Option<String>,
     },
+    /// Run an axe-core accessibility audit on the current page
+    A11yAudit,
 }
 
 impl BrowserTool {
@@ -558,7 +584,12 @@ impl BrowserTool {
                     args.push("--full");
                 }
                 let resp = self.run_command(&args).await?;
-                self.to_result(resp)
+                let mut result = self.to_result(resp)?;
+                // Let vision models see the capture (see agent loop tool images).
+                if let (true, Some(p)) = (result.success, path.as_ref()) {
+                    let _ = write!(result.output, "\n[IMAGE:{p}]");
+                }
+                Ok(result)
             }
 
             BrowserAction::Wait { selector, ms, text } => {
@@ -621,6 +652,11 @@ impl BrowserTool {
                 let resp = self.run_command(&args).await?;
                 self.to_result(resp)
             }
+
+            BrowserAction::A11yAudit => {
+                let resp = self.run_command(&["eval", AXE_AUDIT_SCRIPT]).await?;
+                self.to_result(resp)
+            }
         }
     }
 
@@ -646,6 +682,7 @@ impl BrowserTool {
                 success: true,
                 output: serde_json::to_string_pretty(&output).unwrap_or_default(),
                 error: None,
+                error_code: None,
                 error_hint: None,
             })
         }
@@ -794,6 +831,7 @@ impl BrowserTool {
                     success: true,
                     output,
                     error: None,
+                    error_code: None,
                     error_hint: None,
                 });
             }
@@ -812,6 +850,7 @@ impl BrowserTool {
                 success: false,
                 output: String::new(),
                 error,
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -821,6 +860,7 @@ impl BrowserTool {
                 success: true,
                 output: body,
                 error: None,
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -832,6 +872,7 @@ impl BrowserTool {
                 "computer-use sidecar request failed with status {status}: {}",
                 body.trim()
             )),
+            error_code: None,
             error_hint: None,
         })
     }
@@ -861,6 +902,7 @@ impl BrowserTool {
                 success: true,
                 output,
                 error: None,
+                error_code: None,
                 error_hint: None,
             })
         } else {
@@ -868,6 +910,7 @@ impl BrowserTool {
                 success: false,
                 output: String::new(),
                 error: resp.error,
+                error_code: None,
                 error_hint: None,
             })
         }
@@ -885,7 +928,8 @@ impl Tool for BrowserTool {
             "Web/browser automation with pluggable backends (agent-browser, rust-native, computer_use). ",
             "Supports DOM actions plus optional OS-level actions (mouse_move, mouse_click, mouse_drag, ",
             "key_type, key_press, screen_capture) through a computer-use sidecar. Use 'snapshot' to map ",
-            "interactive elements to refs (@e1, @e2). Enforces browser.allowed_domains for open actions."
+            "interactive elements to refs (@e1, @e2) and 'a11y_audit' to list axe-core accessibility ",
+            "violations on the open page. Enforces browser.allowed_domains for open actions."
         )
     }
 
@@ -897,7 +941,7 @@ impl Tool for BrowserTool {
                     "type": "string",
                     "enum": ["open", "snapshot", "click", "fill", "type", "get_text",
                              "get_title", "get_url", "screenshot", "wait", "press",
-                             "hover", "scroll", "is_visible", "close", "find",
+                             "hover", "scroll", "is_visible", "close", "find", "a11y_audit",
                              "mouse_move", "mouse_click", "mouse_drag", "key_type",
                              "key_press", "screen_capture"],
                     "description": "Browser action to perform (OS-level actions require backend=computer_use)"
@@ -1010,6 +1054,7 @@ impl Tool for BrowserTool {
                 success: false,
                 output: String::new(),
                 error: Some("Action blocked: autonomy is read-only".into()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -1019,6 +1064,7 @@ impl Tool for BrowserTool {
                 success: false,
                 output: String::new(),
                 error: Some("Action blocked: rate limit exceeded".into()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -1030,6 +1076,7 @@ impl Tool for BrowserTool {
                     success: false,
                     output: String::new(),
                     error: Some(error.to_string()),
+                    error_code: None,
                     error_hint: None,
                 });
             }
@@ -1046,6 +1093,7 @@ impl Tool for BrowserTool {
                 success: false,
                 output: String::new(),
                 error: Some(format!("Unknown action: {action_str}")),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -1059,6 +1107,7 @@ impl Tool for BrowserTool {
                 success: false,
                 output: String::new(),
                 error: Some(unavailable_action_for_backend_error(action_str, backend)),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -1070,6 +1119,7 @@ impl Tool for BrowserTool {
                     success: false,
                     output: String::new(),
                     error: Some(e.to_string()),
+                    error_code: None,
                     error_hint: None,
                 });
             }
@@ -1081,7 +1131,7 @@ impl Tool for BrowserTool {
 
 #[cfg(feature = "browser-native")]
 mod native_backend {
-    use super::BrowserAction;
+    use super::{BrowserAction, AXE_AUDIT_SCRIPT};
     use anyhow::{Context, Result};
     use base64::Engine;
     use fantoccini::actions::{InputSource, MouseActions, PointerAction};
@@ -1240,6 +1290,7 @@ mod native_backend {
                         tokio::fs::write(&path_str, &png)
                             .await
                             .with_context(|| format!("Failed to write screenshot to {path_str}"))?;
+                        payload["image"] = Value::String(format!("[IMAGE:{path_str}]"));
                         payload["path"] = Value::String(path_str);
                     } else {
                         payload["png_base64"] =
@@ -1428,6 +1479,23 @@ mod native_backend {
                         "data": payload,
                     }))
                 }
+                BrowserAction::A11yAudit => {
+                    let client = self.active_client()?;
+                    let script = format!(
+                        "const done = arguments[arguments.length - 1];\n\
+                         {AXE_AUDIT_SCRIPT}.then(done, (e) => done(JSON.stringify({{ error: String(e) }})));"
+                    );
+                    let violations = client
+                        .execute_async(&script, vec![])
+                        .await
+                        .context("Failed to run the axe-core audit")?;
+
+                    Ok(json!({
+                        "backend": "rust_native",
+                        "action": "a11y_audit",
+                        "data": violations,
+                    }))
+                }
             }
         }
 
@@ -1881,6 +1949,7 @@ fn parse_browser_action(action_str: &str, args: &Value) -> anyhow::Result<Browse
             })
         }
         "close" => Ok(BrowserAction::Close),
+        "a11y_audit" => Ok(BrowserAction::A11yAudit),
         "find" => {
             let by = args
                 .get("by")
@@ -1929,6 +1998,7 @@ fn is_supported_browser_action(action: &str) -> bool {
             | "is_visible"
             | "close"
             | "find"
+            | "a11y_audit"
             | "mouse_move"
             | "mouse_click"
             | "mouse_drag"
@@ -2414,6 +2484,16 @@ mod tests {
         assert!(!is_computer_use_only_action("snapshot"));
     }
 
+    #[test]
+    fn a11y_audit_is_a_dom_action() {
+        assert!(is_supported_browser_action("a11y_audit"));
+        assert!(!is_computer_use_only_action("a11y_audit"));
+        assert!(matches!(
+            parse_browser_action("a11y_audit", &json!({})).unwrap(),
+            BrowserAction::A11yAudit
+        ));
+    }
+
     #[test]
     fn unavailable_action_error_preserves_backend_context() {
         assert_eq!(
@@ -87,6 +87,7 @@ impl Tool for BrowserOpenTool {
                 success: false,
                 output: String::new(),
                 error: Some("Action blocked: autonomy is read-only".into()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -96,6 +97,7 @@ impl Tool for BrowserOpenTool {
                 success: false,
                 output: String::new(),
                 error: Some("Action blocked: rate limit exceeded".into()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -107,6 +109,7 @@ impl Tool for BrowserOpenTool {
                     success: false,
                     output: String::new(),
                     error: Some(e.to_string()),
+                    error_code: None,
                     error_hint: None,
                 })
             }
@@ -117,12 +120,14 @@ impl Tool for BrowserOpenTool {
                 success: true,
                 output: format!("Opened in Brave: {url}"),
                 error: None,
+                error_code: None,
                 error_hint: None,
             }),
             Err(e) => Ok(ToolResult {
                 success: false,
                 output: String::new(),
                 error: Some(format!("Failed to open Brave Browser: {e}")),
+                error_code: None,
                 error_hint: None,
             }),
         }
@@ -0,0 +1,474 @@
+//! Changelog of deploys (`[zerobuild.changelog]`).
+//!
+//! `github_push` remembers the project files of every deploy per repository
+//! (see [`store::deployments::save_files`]). Before the next deploy,
+//! `deploy_changes` lists what changed since then: files added, removed and
+//! modified, with their added and removed lines, so the DevOps agent can
+//! summarize the user-visible changes. Given to `github_push` as
+//! `changelog`, the summary becomes a new entry at the top of `CHANGELOG.md`
+//! and the notes of a GitHub release of the deployed commit.
+//!
+//! `CHANGELOG.md` lives in the repository, not in the sandbox, so the one
+//! last pushed is kept in the database and carried over to later deploys
+//! unless the project changed its own copy since.
+
+use super::traits::{Tool, ToolResult};
+use crate::config::ZerobuildConfig;
+use crate::store::{self, deployments::DeployedFiles};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+pub const CHANGELOG_PATH: &str = "CHANGELOG.md";
+
+const CHANGELOG_HEADER: &str =
+    "# Changelog\n\nAll notable changes to this project are documented in this file.\n";
+
+/// Files listed with their line changes.
+const MAX_FILES_LISTED: usize = 40;
+
+/// Added or removed lines shown per file.
+const MAX_LINES_PER_FILE: usize = 20;
+
+/// Longer lines are cut to this many characters.
+const MAX_LINE_CHARS: usize = 160;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Modified,
+    Removed,
+}
+
+/// How one file changed between two deploys.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChange {
+    pub path: String,
+    pub kind: ChangeKind,
+    /// Lines the new version has and the old one doesn't.
+    pub added: Vec<String>,
+    /// Lines the old version has and the new one doesn't.
+    pub removed: Vec<String>,
+}
+
+/// Lines of `after` missing from `before` and lines of `before` missing
+/// from `after`, each in file order. Lines are compared as a multiset, so
+/// moved lines don't count as changed.
+fn line_changes(before: &str, after: &str) -> (Vec<String>, Vec<String>) {
+    let mut unmatched: HashMap<&str, usize> = HashMap::new();
+    for line in before.lines() {
+        *unmatched.entry(line).or_default() += 1;
+    }
+    let mut added = Vec::new();
+    for line in after.lines() {
+        match unmatched.get_mut(line) {
+            Some(count) if *count > 0 => *count -= 1,
+            _ => added.push(line.to_string()),
+        }
+    }
+    let mut removed = Vec::new();
+    for line in before.lines() {
+        if let Some(count) = unmatched.get_mut(line).filter(|count| **count > 0) {
+            *count -= 1;
+            removed.push(line.to_string());
+        }
+    }
+    (added, removed)
+}
+
+/// Files that differ between the `before` and `after` deploys, by path.
+/// `CHANGELOG.md` itself is left out.
+pub fn changes(
+    before: &BTreeMap<String, String>,
+    after: &BTreeMap<String, String>,
+) -> Vec<FileChange> {
+    let mut paths: Vec<&String> = before.keys().chain(after.keys()).collect();
+    paths.sort();
+    paths.dedup();
+    paths
+        .into_iter()
+        .filter(|path| *path != CHANGELOG_PATH)
+        .filter_map(|path| {
+            let (kind, old, new) = match (before.get(path), after.get(path)) {
+                (None, Some(new)) => (ChangeKind::Added, "", new.as_str()),
+                (Some(old), None) => (ChangeKind::Removed, old.as_str(), ""),
+                (Some(old), Some(new)) if old != new => {
+                    (ChangeKind::Modified, old.as_str(), new.as_str())
+                }
+                _ => return None,
+            };
+            let (added, removed) = line_changes(old, new);
+            Some(FileChange {
+                path: path.clone(),
+                kind,
+                added,
+                removed,
+            })
+        })
+        .collect()
+}
+
+/// Changes listed for the agent: a summary line, then every file with its
+/// added (`+`) and removed (`-`) lines.
+pub fn describe(changes: &[FileChange]) -> String {
+    let count = |kind| changes.iter().filter(|c| c.kind == kind).count();
+    let mut out = format!(
+        "{} files added, {} modified, {} removed.\n",
+        count(ChangeKind::Added),
+        count(ChangeKind::Modified),
+        count(ChangeKind::Removed)
+    );
+    for change in changes.iter().take(MAX_FILES_LISTED) {
+        let kind = match change.kind {
+            ChangeKind::Added => "added",
+            ChangeKind::Modified => "modified",
+            ChangeKind::Removed => "removed",
+        };
+        let _ = writeln!(
+            out,
+            "\n=== {} [{kind}] (+{} / -{}) ===",
+            change.path,
+            change.added.len(),
+            change.removed.len()
+        );
+        let lines = change
+            .added
+            .iter()
+            .map(|line| ('+', line))
+            .chain(change.removed.iter().map(|line| ('-', line)))
+            .filter(|(_, line)| !line.trim().is_empty());
+        for (shown, (sign, line)) in lines.enumerate() {
+            if shown == MAX_LINES_PER_FILE {
+                out.push_str("…\n");
+                break;
+            }
+            let line: String = line.chars().take(MAX_LINE_CHARS).collect();
+            let _ = writeln!(out, "{sign}{line}");
+        }
+    }
+    if changes.len() > MAX_FILES_LISTED {
+        let _ = writeln!(
+            out,
+            "\n…and {} more files.",
+            changes.len() - MAX_FILES_LISTED
+        );
+    }
+    out
+}
+
+/// The `CHANGELOG.md` a deploy starts from: the one pushed last time,
+/// unless the project changed its own copy since then.
+pub fn current_changelog(
+    files: &BTreeMap<String, String>,
+    previous: Option<&DeployedFiles>,
+) -> Option<String> {
+    let own = files.get(CHANGELOG_PATH);
+    match previous {
+        Some(previous)
+            if previous.changelog.is_some() && previous.files.get(CHANGELOG_PATH) == own =>
+        {
+            previous.changelog.clone()
+        }
+        _ => own.cloned(),
+    }
+}
+
+/// `existing` changelog with an entry for `notes` under `heading` above the
+/// previous entries.
+pub fn add_entry(existing: Option<&str>, heading: &str, notes: &str) -> String {
+    let existing = existing
+        .filter(|text| !text.trim().is_empty())
+        .unwrap_or(CHANGELOG_HEADER);
+    let entry = format!("## {heading}\n\n{}\n", notes.trim());
+    // Entries go below the title and intro, before the first entry.
+    let first_entry = if existing.starts_with("## ") {
+        0
+    } else {
+        existing.find("\n## ").map_or(existing.len(), |at| at + 1)
+    };
+    let (intro, entries) = existing.split_at(first_entry);
+    let intro = intro.trim_end();
+    if intro.is_empty() {
+        format!("{entry}\n{entries}")
+    } else if entries.is_empty() {
+        format!("{intro}\n\n{entry}")
+    } else {
+        format!("{intro}\n\n{entry}\n{entries}")
+    }
+}
+
+/// Version in the project's `package.json`, `Cargo.toml` or
+/// `pyproject.toml`.
+pub fn manifest_version(files: &BTreeMap<String, String>) -> Option<String> {
+    if let Some(version) = files
+        .get("package.json")
+        .and_then(|text| serde_json::from_str::<serde_json::Value>(text).ok())
+        .and_then(|manifest| manifest["version"].as_str().map(String::from))
+    {
+        return Some(version);
+    }
+    [
+        ("Cargo.toml", "package"),
+        ("pyproject.toml", "project"),
+        ("pyproject.toml", "tool.poetry"),
+    ]
+    .into_iter()
+    .find_map(|(path, table)| {
+        let manifest: toml::Value = toml::from_str(files.get(path)?).ok()?;
+        let table = table
+            .split('.')
+            .try_fold(&manifest, |value, key| value.get(key))?;
+        table.get("version")?.as_str().map(String::from)
+    })
+}
+
+/// Tag of the release of a deploy: `v<version>` of the manifest when that
+/// was not released yet, else one named after the time of the deploy.
+pub fn release_tag(
+    files: &BTreeMap<String, String>,
+    last_tag: Option<&str>,
+    now: DateTime<Utc>,
+) -> String {
+    manifest_version(files)
+        .map(|version| format!("v{}", version.trim_start_matches('v')))
+        .filter(|tag| Some(tag.as_str()) != last_tag)
+        .unwrap_or_else(|| format!("deploy-{}", now.format("%Y%m%d-%H%M%S")))
+}
+
+// ── deploy_changes ────────────────────────────────────────────────────────────
+
+pub struct DeployChangesTool {
+    config: Arc<ZerobuildConfig>,
+}
+
+impl DeployChangesTool {
+    pub fn new(config: Arc<ZerobuildConfig>) -> Self {
+        Self { config }
+    }
+}
+
+#[async_trait]
+impl Tool for DeployChangesTool {
+    fn name(&self) -> &str {
+        "deploy_changes"
+    }
+
+    fn description(&self) -> &str {
+        "List what changed in the project snapshot since it was last deployed to a GitHub \
+         repository with github_push: files added, modified and removed, with their added \
+         (+) and removed (-) lines. Use it before deploying an update to write the \
+         user-visible changes as github_push's changelog."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "project_name": {
+                    "type": "string",
+                    "description": "GitHub repo name the project is deployed to, as given to github_push"
+                },
+                "owner": {
+                    "type": "string",
+                    "description": "Repository owner. Default: authenticated GitHub user."
+                }
+            },
+            "required": ["project_name"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let conn = store::init_db(&PathBuf::from(&self.config.db_path))?;
+        let project_name = args["project_name"]
+            .as_str()
+            .unwrap_or_default()
+            .trim()
+            .to_lowercase()
+            .replace(' ', "-");
+        let owner = match args["owner"].as_str().map(str::trim) {
+            Some(owner) if !owner.is_empty() => owner.to_string(),
+            _ => store::tokens::load_github_token(&conn)?
+                .and_then(|token| token.username)
+                .unwrap_or_default(),
+        };
+        if project_name.is_empty() || owner.is_empty() {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(
+                    "project_name is required, and owner when GitHub is not connected".into(),
+                ),
+                error_code: None,
+                error_hint: None,
+            });
+        }
+        let Some((files, project_type)) = store::snapshot::load_snapshot(&conn)? else {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some("No project snapshot found. Save a snapshot first.".into()),
+                error_code: None,
+                error_hint: None,
+            });
+        };
+
+        let repo = format!("{owner}/{project_name}");
+        let files = super::github_push::deployable_files(files, project_type.as_deref());
+        let output = match store::deployments::load_files(&conn, &repo)? {
+            None => format!(
+                "{repo} has no recorded deploy: this is its first release, so summarize the \
+                 project's features for the changelog."
+            ),
+            Some(previous) => {
+                let changes = changes(&previous.files, &files);
+                if changes.is_empty() {
+                    format!("Nothing changed since the last deploy to {repo}.")
+                } else {
+                    format!(
+                        "Changes since the last deploy to {repo}: {}",
+                        describe(&changes)
+                    )
+                }
+            }
+        };
+        Ok(ToolResult {
+            success: true,
+            output,
+            error: None,
+            error_code: None,
+            error_hint: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn files(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+        entries
+            .iter()
+            .map(|(path, content)| (path.to_string(), content.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn changes_list_files_and_lines() {
+        let before = files(&[
+            ("index.html", "<h1>Shop</h1>\n<p>Cart</p>\n"),
+            ("old.js", "legacy();\n"),
+            ("CHANGELOG.md", "# Changelog\n"),
+        ]);
+        let after = files(&[
+            (
+                "index.html",
+                "<p>Cart</p>\n<h1>Shop</h1>\n<button>Checkout</button>\n",
+            ),
+            ("checkout.js", "pay();\n"),
+        ]);
+        let changes = changes(&before, &after);
+        assert_eq!(changes.len(), 3);
+        assert_eq!(changes[0].path, "checkout.js");
+        assert_eq!(changes[1].kind, ChangeKind::Modified);
+        assert_eq!(changes[1].added, ["<button>Checkout</button>"]);
+        assert!(changes[1].removed.is_empty());
+        assert_eq!(changes[2].kind, ChangeKind::Removed);
+
+        let described = describe(&changes);
+        assert!(described.starts_with("1 files added, 1 modified, 1 removed."));
+        assert!(described.contains("=== index.html [modified] (+1 / -0) ===\n+<button>"));
+        assert!(described.contains("=== old.js [removed] (+0 / -1) ===\n-legacy();"));
+    }
+
+    #[test]
+    fn entries_go_above_the_previous_ones() {
+        let first = add_entry(None, "v1.0.0 - 2026-01-02", "- Checkout");
+        assert!(first.starts_with("# Changelog\n\nAll notable changes"));
+        assert!(first.ends_with("\n\n## v1.0.0 - 2026-01-02\n\n- Checkout\n"));
+        let second = add_entry(Some(&first), "v1.1.0 - 2026-02-03", "- Coupons\n");
+        let newer = second.find("## v1.1.0").unwrap();
+        assert!(newer < second.find("## v1.0.0").unwrap());
+        assert!(second.contains("- Coupons\n\n## v1.0.0"));
+        let bare = add_entry(Some("## v1\n\n- A\n"), "v2", "- B");
+        assert_eq!(bare, "## v2\n\n- B\n\n## v1\n\n- A\n");
+    }
+
+    #[test]
+    fn the_pushed_changelog_is_kept_until_the_project_edits_its_own() {
+        let pushed = "# Changelog\n\n## v1\n\n- A\n";
+        let previous = DeployedFiles {
+            files: files(&[("CHANGELOG.md", "# Changelog\n")]),
+            changelog: Some(pushed.into()),
+            release_tag: Some("v1".into()),
+        };
+        let unchanged = files(&[("CHANGELOG.md", "# Changelog\n")]);
+        assert_eq!(
+            current_changelog(&unchanged, Some(&previous)).as_deref(),
+            Some(pushed)
+        );
+        let edited = files(&[("CHANGELOG.md", "# Changes\n")]);
+        assert_eq!(
+            current_changelog(&edited, Some(&previous)).as_deref(),
+            Some("# Changes\n")
+        );
+        assert_eq!(
+            current_changelog(&unchanged, None).as_deref(),
+            Some("# Changelog\n")
+        );
+    }
+
+    #[test]
+    fn release_tags_follow_the_manifest_version() {
+        let now = DateTime::parse_from_rfc3339("2026-03-04T05:06:07Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let node = files(&[("package.json", r#"{"name": "shop", "version": "1.2.0"}"#)]);
+        assert_eq!(release_tag(&node, None, now), "v1.2.0");
+        assert_eq!(
+            release_tag(&node, Some("v1.2.0"), now),
+            "deploy-20260304-050607"
+        );
+        let python = files(&[(
+            "pyproject.toml",
+            "[project]\nname = \"api\"\nversion = \"0.3.1\"\n",
+        )]);
+        assert_eq!(manifest_version(&python).as_deref(), Some("0.3.1"));
+        assert_eq!(
+            release_tag(&BTreeMap::new(), None, now),
+            "deploy-20260304-050607"
+        );
+    }
+
+    #[tokio::test]
+    async fn deploy_changes_compares_the_snapshot_with_the_last_deploy() {
+        let tmp = TempDir::new().unwrap();
+        let config = ZerobuildConfig {
+            db_path: tmp.path().join("zerobuild.db").display().to_string(),
+            ..ZerobuildConfig::default()
+        };
+        let conn = store::init_db(&PathBuf::from(&config.db_path)).unwrap();
+        store::tokens::save_github_token(&conn, "gho_test", Some("acme")).unwrap();
+        let snapshot = HashMap::from([("index.html".to_string(), "<h1>Shop</h1>\n".to_string())]);
+        store::snapshot::save_snapshot(&conn, &snapshot, Some("static")).unwrap();
+        let tool = DeployChangesTool::new(Arc::new(config));
+
+        let first = tool.execute(json!({"project_name": "shop"})).await.unwrap();
+        assert!(first.output.contains("acme/shop has no recorded deploy"));
+
+        store::deployments::save_files(
+            &conn,
+            "acme/shop",
+            &files(&[("index.html", "<h1>Store</h1>\n")]),
+            None,
+            None,
+        )
+        .unwrap();
+        let update = tool.execute(json!({"project_name": "shop"})).await.unwrap();
+        assert!(update.output.contains("+<h1>Shop</h1>\n-<h1>Store</h1>"));
+    }
+}
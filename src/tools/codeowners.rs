@@ -0,0 +1,254 @@
+//! CODEOWNERS parsing and reviewer selection for `github_create_pr`.
+//!
+//! Follows GitHub's rules: the last matching line wins, patterns use
+//! `.gitignore` syntax, and a line without owners leaves its paths unowned.
+//! `@user` owners become reviewers and `@org/team` owners team reviewers;
+//! e-mail owners can't be requested through the API and are reported
+//! instead.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Where GitHub looks for the file, in order.
+pub const CODEOWNERS_PATHS: &[&str] = &[".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+/// One CODEOWNERS line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pub pattern: String,
+    pub owners: Vec<String>,
+    glob: glob::Pattern,
+    /// Pattern ended in `/`: matches what is inside a directory only.
+    dir_only: bool,
+}
+
+impl Rule {
+    fn parse(pattern: &str, owners: Vec<String>) -> Option<Self> {
+        let dir_only = pattern.ends_with('/');
+        let trimmed = pattern.trim_end_matches('/');
+        let anchored = trimmed.starts_with('/') || trimmed.contains('/');
+        let trimmed = trimmed.trim_start_matches('/');
+        if trimmed.is_empty() {
+            return None;
+        }
+        let glob = if anchored || trimmed.starts_with("**") {
+            trimmed.to_string()
+        } else {
+            format!("**/{trimmed}")
+        };
+        Some(Self {
+            pattern: pattern.to_string(),
+            owners,
+            glob: glob::Pattern::new(&glob).ok()?,
+            dir_only,
+        })
+    }
+
+    /// Whether the rule covers `path`, directly or through a directory
+    /// above it.
+    fn matches(&self, path: &str) -> bool {
+        let options = glob::MatchOptions {
+            require_literal_separator: true,
+            ..glob::MatchOptions::default()
+        };
+        let mut prefix = String::new();
+        let parts = path.split('/').collect::<Vec<_>>();
+        for (index, part) in parts.iter().enumerate() {
+            if !prefix.is_empty() {
+                prefix.push('/');
+            }
+            prefix.push_str(part);
+            let is_file = index + 1 == parts.len();
+            if (!is_file || !self.dir_only) && self.glob.matches_with(&prefix, options) {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// Parsed CODEOWNERS file.
+#[derive(Debug, Clone, Default)]
+pub struct CodeOwners {
+    pub rules: Vec<Rule>,
+    /// Line numbers with patterns that didn't parse.
+    pub invalid_lines: Vec<usize>,
+}
+
+impl CodeOwners {
+    pub fn parse(text: &str) -> Self {
+        let mut parsed = Self::default();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.split(" #").next().unwrap_or_default().trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            let Some(pattern) = fields.next() else {
+                continue;
+            };
+            let owners = fields.map(str::to_string).collect();
+            match Rule::parse(pattern, owners) {
+                Some(rule) => parsed.rules.push(rule),
+                None => parsed.invalid_lines.push(index + 1),
+            }
+        }
+        parsed
+    }
+
+    /// The rule deciding who owns `path`: the last one that matches.
+    pub fn rule_for(&self, path: &str) -> Option<&Rule> {
+        let path = path.trim_start_matches('/');
+        self.rules.iter().rev().find(|rule| rule.matches(path))
+    }
+}
+
+/// Reviewers to request for a set of changed paths.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReviewPlan {
+    /// GitHub logins, without `@`.
+    pub reviewers: Vec<String>,
+    /// Team slugs of the repository's organization.
+    pub team_reviewers: Vec<String>,
+    /// Owners that can't be requested, with the reason.
+    pub skipped: Vec<(String, String)>,
+    /// Changed paths per owner as written in CODEOWNERS.
+    pub owned: BTreeMap<String, Vec<String>>,
+    pub unowned: Vec<String>,
+}
+
+impl ReviewPlan {
+    /// Reviewers for `paths`. `org` is the repository owner (teams of other
+    /// organizations can't review) and `author` the PR author, who can't
+    /// review their own PR.
+    pub fn new(codeowners: &CodeOwners, paths: &[String], org: &str, author: &str) -> Self {
+        let mut plan = Self::default();
+        for path in paths {
+            match codeowners.rule_for(path) {
+                Some(rule) if !rule.owners.is_empty() => {
+                    for owner in &rule.owners {
+                        plan.owned
+                            .entry(owner.clone())
+                            .or_default()
+                            .push(path.clone());
+                    }
+                }
+                _ => plan.unowned.push(path.clone()),
+            }
+        }
+        for owner in plan.owned.keys() {
+            let skip = |reason: &str| (owner.clone(), reason.to_string());
+            match owner.strip_prefix('@').map(|name| name.split_once('/')) {
+                None => plan.skipped.push(skip("e-mail owners can't be requested")),
+                Some(Some((team_org, slug))) => {
+                    if team_org.eq_ignore_ascii_case(org) {
+                        plan.team_reviewers.push(slug.to_string());
+                    } else {
+                        plan.skipped
+                            .push(skip("team belongs to another organization"));
+                    }
+                }
+                Some(None) => {
+                    let login = &owner[1..];
+                    if login.eq_ignore_ascii_case(author) {
+                        plan.skipped.push(skip("PR author"));
+                    } else {
+                        plan.reviewers.push(login.to_string());
+                    }
+                }
+            }
+        }
+        plan
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.reviewers.is_empty() && self.team_reviewers.is_empty()
+    }
+
+    /// Owners, the changed paths they own, and skipped owners.
+    pub fn describe(&self) -> String {
+        const SAMPLE: usize = 3;
+        let mut out = String::new();
+        for (owner, paths) in &self.owned {
+            let sample = paths.iter().take(SAMPLE).cloned().collect::<Vec<_>>();
+            let more = paths.len().saturating_sub(SAMPLE);
+            let _ = write!(out, "\n- {owner}: {}", sample.join(", "));
+            if more > 0 {
+                let _ = write!(out, " (+{more} more)");
+            }
+            if let Some((_, reason)) = self.skipped.iter().find(|(skipped, _)| skipped == owner) {
+                let _ = write!(out, " — not requested: {reason}");
+            }
+        }
+        if !self.unowned.is_empty() {
+            let _ = write!(out, "\n- no owner: {} changed file(s)", self.unowned.len());
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = "\
+# Default owners
+*       @acme/core
+*.js    @js-owner   # frontend
+/docs/  docs@acme.com
+apps/   @apps-owner
+/build/logs/ @ops
+src/generated/
+scripts/*.sh @acme/infra @other-org/ops
+";
+
+    fn owners(path: &str) -> Vec<String> {
+        CodeOwners::parse(FILE)
+            .rule_for(path)
+            .map(|rule| rule.owners.clone())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn last_matching_rule_wins() {
+        assert_eq!(owners("README.md"), ["@acme/core"]);
+        assert_eq!(owners("web/index.js"), ["@js-owner"]);
+        assert_eq!(owners("docs/guide/intro.md"), ["docs@acme.com"]);
+        assert_eq!(owners("nested/docs/intro.md"), ["@acme/core"]);
+        assert_eq!(owners("services/apps/main.js"), ["@apps-owner"]);
+        assert_eq!(owners("build/logs/today.log"), ["@ops"]);
+        assert!(owners("src/generated/api.ts").is_empty());
+        assert_eq!(
+            owners("scripts/deploy.sh"),
+            ["@acme/infra", "@other-org/ops"]
+        );
+        assert_eq!(owners("scripts/ci/deploy.sh"), ["@acme/core"]);
+    }
+
+    #[test]
+    fn plans_reviewers_and_skips_what_github_rejects() {
+        let paths = [
+            "README.md",
+            "web/app.js",
+            "docs/a.md",
+            "scripts/x.sh",
+            "src/generated/api.ts",
+        ]
+        .map(String::from);
+        let plan = ReviewPlan::new(&CodeOwners::parse(FILE), &paths, "acme", "JS-Owner");
+        assert!(plan.reviewers.is_empty());
+        assert_eq!(plan.team_reviewers, ["core", "infra"]);
+        assert_eq!(plan.unowned, ["src/generated/api.ts"]);
+        let skipped = plan
+            .skipped
+            .iter()
+            .map(|(owner, _)| owner.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(skipped, ["@js-owner", "@other-org/ops", "docs@acme.com"]);
+
+        let described = plan.describe();
+        assert!(described.contains("- @acme/core: README.md"));
+        assert!(described.contains("- @js-owner: web/app.js — not requested: PR author"));
+        assert!(described.contains("- no owner: 1 changed file(s)"));
+    }
+}
@@ -651,6 +651,7 @@ impl Tool for ComposioTool {
                             success: true,
                             output,
                             error: None,
+                            error_code: None,
                             error_hint: None,
                         })
                     }
@@ -658,6 +659,7 @@ impl Tool for ComposioTool {
                         success: false,
                         output: String::new(),
                         error: Some(format!("Failed to list actions: {e}")),
+                        error_code: None,
                         error_hint: None,
                     }),
                 }
@@ -678,6 +680,7 @@ impl Tool for ComposioTool {
                                     "No connected accounts found{app_hint} for entity '{entity_id}'. Run action='connect' first."
                                 ),
                                 error: None,
+                                error_code: None,
                                 error_hint: None,
                             });
                         }
@@ -704,6 +707,7 @@ impl Tool for ComposioTool {
                             success: true,
                             output,
                             error: None,
+                            error_code: None,
                             error_hint: None,
                         })
                     }
@@ -711,6 +715,7 @@ impl Tool for ComposioTool {
                         success: false,
                         output: String::new(),
                         error: Some(format!("Failed to list connected accounts: {e}")),
+                        error_code: None,
                         error_hint: None,
                     }),
                 }
@@ -725,6 +730,7 @@ impl Tool for ComposioTool {
                         success: false,
                         output: String::new(),
                         error: Some(error),
+                        error_code: None,
                         error_hint: None,
                     });
                 }
@@ -752,6 +758,7 @@ impl Tool for ComposioTool {
                             success: true,
                             output,
                             error: None,
+                            error_code: None,
                             error_hint: None,
                         })
                     }
@@ -759,6 +766,7 @@ impl Tool for ComposioTool {
                         success: false,
                         output: String::new(),
                         error: Some(format!("Action execution failed: {e}")),
+                        error_code: None,
                         error_hint: None,
                     }),
                 }
@@ -773,6 +781,7 @@ impl Tool for ComposioTool {
                         success: false,
                         output: String::new(),
                         error: Some(error),
+                        error_code: None,
                         error_hint: None,
                     });
                 }
@@ -805,6 +814,7 @@ impl Tool for ComposioTool {
                             success: true,
                             output,
                             error: None,
+                            error_code: None,
                             error_hint: None,
                         })
                     }
@@ -812,6 +822,7 @@ impl Tool for ComposioTool {
                         success: false,
                         output: String::new(),
                         error: Some(format!("Failed to get connection URL: {e}")),
+                        error_code: None,
                         error_hint: None,
                     }),
                 }
@@ -823,6 +834,7 @@ impl Tool for ComposioTool {
                 error: Some(format!(
                     "Unknown action '{action}'. Use 'list', 'list_accounts', 'execute', or 'connect'."
                 )),
+                error_code: None,
                 error_hint: None,
             }),
         }
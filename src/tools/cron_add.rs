@@ -21,6 +21,7 @@ impl CronAddTool {
             return Some(ToolResult {
                 success: false,
                 output: String::new(),
+                error_code: None,
                 error_hint: None,
                 error: Some(format!(
                     "Security policy: read-only mode, cannot perform '{action}'"
@@ -32,6 +33,7 @@ impl CronAddTool {
             return Some(ToolResult {
                 success: false,
                 output: String::new(),
+                error_code: None,
                 error_hint: None,
                 error: Some("Rate limit exceeded: too many actions in the last hour".to_string()),
             });
@@ -41,6 +43,7 @@ impl CronAddTool {
             return Some(ToolResult {
                 success: false,
                 output: String::new(),
+                error_code: None,
                 error_hint: None,
                 error: Some("Rate limit exceeded: action budget exhausted".to_string()),
             });
@@ -91,6 +94,7 @@ impl Tool for CronAddTool {
             return Ok(ToolResult {
                 success: false,
                 output: String::new(),
+                error_code: None,
                 error_hint: None,
                 error: Some("cron is disabled by config (cron.enabled=false)".to_string()),
             });
@@ -103,6 +107,7 @@ impl Tool for CronAddTool {
                     return Ok(ToolResult {
                         success: false,
                         output: String::new(),
+                        error_code: None,
                         error_hint: None,
                         error: Some(format!("Invalid schedule: {e}")),
                     });
@@ -112,6 +117,7 @@ impl Tool for CronAddTool {
                 return Ok(ToolResult {
                     success: false,
                     output: String::new(),
+                    error_code: None,
                     error_hint: None,
                     error: Some("Missing 'schedule' parameter".to_string()),
                 });
@@ -130,6 +136,7 @@ impl Tool for CronAddTool {
                 return Ok(ToolResult {
                     success: false,
                     output: String::new(),
+                    error_code: None,
                     error_hint: None,
                     error: Some(format!("Invalid job_type: {other}")),
                 });
@@ -161,6 +168,7 @@ impl Tool for CronAddTool {
                         return Ok(ToolResult {
                             success: false,
                             output: String::new(),
+                            error_code: None,
                             error_hint: None,
                             error: Some("Missing 'command' for shell job".to_string()),
                         });
@@ -171,6 +179,7 @@ impl Tool for CronAddTool {
                     return Ok(ToolResult {
                         success: false,
                         output: String::new(),
+                        error_code: None,
                         error_hint: None,
                         error: Some(reason),
                     });
@@ -189,6 +198,7 @@ impl Tool for CronAddTool {
                         return Ok(ToolResult {
                             success: false,
                             output: String::new(),
+                            error_code: None,
                             error_hint: None,
                             error: Some("Missing 'prompt' for agent job".to_string()),
                         });
@@ -202,6 +212,7 @@ impl Tool for CronAddTool {
                             return Ok(ToolResult {
                                 success: false,
                                 output: String::new(),
+                                error_code: None,
                                 error_hint: None,
                                 error: Some(format!("Invalid session_target: {e}")),
                             });
@@ -222,6 +233,7 @@ impl Tool for CronAddTool {
                             return Ok(ToolResult {
                                 success: false,
                                 output: String::new(),
+                                error_code: None,
                                 error_hint: None,
                                 error: Some(format!("Invalid delivery config: {e}")),
                             });
@@ -258,12 +270,14 @@ impl Tool for CronAddTool {
                     "next_run": job.next_run,
                     "enabled": job.enabled
                 }))?,
+                error_code: None,
                 error_hint: None,
                 error: None,
             }),
             Err(e) => Ok(ToolResult {
                 success: false,
                 output: String::new(),
+                error_code: None,
                 error_hint: None,
                 error: Some(e.to_string()),
             }),
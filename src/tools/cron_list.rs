@@ -38,6 +38,7 @@ impl Tool for CronListTool {
             return Ok(ToolResult {
                 success: false,
                 output: String::new(),
+                error_code: None,
                 error_hint: None,
                 error: Some("cron is disabled by config (cron.enabled=false)".to_string()),
             });
@@ -47,12 +48,14 @@ impl Tool for CronListTool {
             Ok(jobs) => Ok(ToolResult {
                 success: true,
                 output: serde_json::to_string_pretty(&jobs)?,
+                error_code: None,
                 error_hint: None,
                 error: None,
             }),
             Err(e) => Ok(ToolResult {
                 success: false,
                 output: String::new(),
+                error_code: None,
                 error_hint: None,
                 error: Some(e.to_string()),
             }),
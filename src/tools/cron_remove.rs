@@ -21,6 +21,7 @@ impl CronRemoveTool {
             return Some(ToolResult {
                 success: false,
                 output: String::new(),
+                error_code: None,
                 error_hint: None,
                 error: Some(format!(
                     "Security policy: read-only mode, cannot perform '{action}'"
@@ -32,6 +33,7 @@ impl CronRemoveTool {
             return Some(ToolResult {
                 success: false,
                 output: String::new(),
+                error_code: None,
                 error_hint: None,
                 error: Some("Rate limit exceeded: too many actions in the last hour".to_string()),
             });
@@ -41,6 +43,7 @@ impl CronRemoveTool {
             return Some(ToolResult {
                 success: false,
                 output: String::new(),
+                error_code: None,
                 error_hint: None,
                 error: Some("Rate limit exceeded: action budget exhausted".to_string()),
             });
@@ -75,6 +78,7 @@ impl Tool for CronRemoveTool {
             return Ok(ToolResult {
                 success: false,
                 output: String::new(),
+                error_code: None,
                 error_hint: None,
                 error: Some("cron is disabled by config (cron.enabled=false)".to_string()),
             });
@@ -86,6 +90,7 @@ impl Tool for CronRemoveTool {
                 return Ok(ToolResult {
                     success: false,
                     output: String::new(),
+                    error_code: None,
                     error_hint: None,
                     error: Some("Missing 'job_id' parameter".to_string()),
                 });
@@ -100,12 +105,14 @@ impl Tool for CronRemoveTool {
             Ok(()) => Ok(ToolResult {
                 success: true,
                 output: format!("Removed cron job {job_id}"),
+                error_code: None,
                 error_hint: None,
                 error: None,
             }),
             Err(e) => Ok(ToolResult {
                 success: false,
                 output: String::new(),
+                error_code: None,
                 error_hint: None,
                 error: Some(e.to_string()),
             }),
@@ -48,6 +48,7 @@ impl Tool for CronRunTool {
             return Ok(ToolResult {
                 success: false,
                 output: String::new(),
+                error_code: None,
                 error_hint: None,
                 error: Some("cron is disabled by config (cron.enabled=false)".to_string()),
             });
@@ -59,6 +60,7 @@ impl Tool for CronRunTool {
                 return Ok(ToolResult {
                     success: false,
                     output: String::new(),
+                    error_code: None,
                     error_hint: None,
                     error: Some("Missing 'job_id' parameter".to_string()),
                 });
@@ -73,6 +75,7 @@ impl Tool for CronRunTool {
             return Ok(ToolResult {
                 success: false,
                 output: String::new(),
+                error_code: None,
                 error_hint: None,
                 error: Some("Security policy: read-only mode, cannot perform 'cron_run'".into()),
             });
@@ -82,6 +85,7 @@ impl Tool for CronRunTool {
             return Ok(ToolResult {
                 success: false,
                 output: String::new(),
+                error_code: None,
                 error_hint: None,
                 error: Some("Rate limit exceeded: too many actions in the last hour".into()),
             });
@@ -93,6 +97,7 @@ impl Tool for CronRunTool {
                 return Ok(ToolResult {
                     success: false,
                     output: String::new(),
+                    error_code: None,
                     error_hint: None,
                     error: Some(e.to_string()),
                 });
@@ -107,6 +112,7 @@ impl Tool for CronRunTool {
                 return Ok(ToolResult {
                     success: false,
                     output: String::new(),
+                    error_code: None,
                     error_hint: None,
                     error: Some(reason),
                 });
@@ -117,6 +123,7 @@ impl Tool for CronRunTool {
             return Ok(ToolResult {
                 success: false,
                 output: String::new(),
+                error_code: None,
                 error_hint: None,
                 error: Some("Rate limit exceeded: action budget exhausted".into()),
             });
@@ -147,6 +154,7 @@ impl Tool for CronRunTool {
                 "duration_ms": duration_ms,
                 "output": output
             }))?,
+            error_code: None,
             error_hint: None,
             error: if success {
                 None
@@ -55,6 +55,7 @@ impl Tool for CronRunsTool {
             return Ok(ToolResult {
                 success: false,
                 output: String::new(),
+                error_code: None,
                 error_hint: None,
                 error: Some("cron is disabled by config (cron.enabled=false)".to_string()),
             });
@@ -66,6 +67,7 @@ impl Tool for CronRunsTool {
                 return Ok(ToolResult {
                     success: false,
                     output: String::new(),
+                    error_code: None,
                     error_hint: None,
                     error: Some("Missing 'job_id' parameter".to_string()),
                 });
@@ -95,6 +97,7 @@ impl Tool for CronRunsTool {
                 Ok(ToolResult {
                     success: true,
                     output: serde_json::to_string_pretty(&runs)?,
+                    error_code: None,
                     error_hint: None,
                     error: None,
                 })
@@ -102,6 +105,7 @@ impl Tool for CronRunsTool {
             Err(e) => Ok(ToolResult {
                 success: false,
                 output: String::new(),
+                error_code: None,
                 error_hint: None,
                 error: Some(e.to_string()),
             }),
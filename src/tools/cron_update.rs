@@ -21,6 +21,7 @@ impl CronUpdateTool {
             return Some(ToolResult {
                 success: false,
                 output: String::new(),
+                error_code: None,
                 error_hint: None,
                 error: Some(format!(
                     "Security policy: read-only mode, cannot perform '{action}'"
@@ -32,6 +33,7 @@ impl CronUpdateTool {
             return Some(ToolResult {
                 success: false,
                 output: String::new(),
+                error_code: None,
                 error_hint: None,
                 error: Some("Rate limit exceeded: too many actions in the last hour".to_string()),
             });
@@ -41,6 +43,7 @@ impl CronUpdateTool {
             return Some(ToolResult {
                 success: false,
                 output: String::new(),
+                error_code: None,
                 error_hint: None,
                 error: Some("Rate limit exceeded: action budget exhausted".to_string()),
             });
@@ -81,6 +84,7 @@ impl Tool for CronUpdateTool {
             return Ok(ToolResult {
                 success: false,
                 output: String::new(),
+                error_code: None,
                 error_hint: None,
                 error: Some("cron is disabled by config (cron.enabled=false)".to_string()),
             });
@@ -92,6 +96,7 @@ impl Tool for CronUpdateTool {
                 return Ok(ToolResult {
                     success: false,
                     output: String::new(),
+                    error_code: None,
                     error_hint: None,
                     error: Some("Missing 'job_id' parameter".to_string()),
                 });
@@ -104,6 +109,7 @@ impl Tool for CronUpdateTool {
                 return Ok(ToolResult {
                     success: false,
                     output: String::new(),
+                    error_code: None,
                     error_hint: None,
                     error: Some("Missing 'patch' parameter".to_string()),
                 });
@@ -116,6 +122,7 @@ impl Tool for CronUpdateTool {
                 return Ok(ToolResult {
                     success: false,
                     output: String::new(),
+                    error_code: None,
                     error_hint: None,
                     error: Some(format!("Invalid patch payload: {e}")),
                 });
@@ -131,6 +138,7 @@ impl Tool for CronUpdateTool {
                 return Ok(ToolResult {
                     success: false,
                     output: String::new(),
+                    error_code: None,
                     error_hint: None,
                     error: Some(reason),
                 });
@@ -145,12 +153,14 @@ impl Tool for CronUpdateTool {
             Ok(job) => Ok(ToolResult {
                 success: true,
                 output: serde_json::to_string_pretty(&job)?,
+                error_code: None,
                 error_hint: None,
                 error: None,
             }),
             Err(e) => Ok(ToolResult {
                 success: false,
                 output: String::new(),
+                error_code: None,
                 error_hint: None,
                 error: Some(e.to_string()),
             }),
@@ -2,6 +2,7 @@ use super::traits::{Tool, ToolResult};
 use crate::agent::loop_::run_tool_call_loop;
 use crate::config::DelegateAgentConfig;
 use crate::observability::traits::{Observer, ObserverEvent, ObserverMetric};
+use crate::providers::sampling::{self, SamplingParams};
 use crate::providers::{self, ChatMessage, Provider};
 use crate::security::policy::ToolOperation;
 use crate::security::SecurityPolicy;
@@ -33,6 +34,8 @@ pub struct DelegateTool {
     parent_tools: Arc<Vec<Arc<dyn Tool>>>,
     /// Inherited multimodal handling config for sub-agent loops.
     multimodal_config: crate::config::MultimodalConfig,
+    /// Receives the LLM events of agentic sub-agent loops.
+    observer: Option<Arc<dyn Observer>>,
 }
 
 impl DelegateTool {
@@ -63,6 +66,7 @@ impl DelegateTool {
             depth: 0,
             parent_tools: Arc::new(Vec::new()),
             multimodal_config: crate::config::MultimodalConfig::default(),
+            observer: None,
         }
     }
 
@@ -99,6 +103,7 @@ impl DelegateTool {
             depth,
             parent_tools: Arc::new(Vec::new()),
             multimodal_config: crate::config::MultimodalConfig::default(),
+            observer: None,
         }
     }
 
@@ -113,6 +118,12 @@ impl DelegateTool {
         self.multimodal_config = config;
         self
     }
+
+    /// Report the LLM calls of agentic sub-agent loops (token usage) to `observer`.
+    pub fn with_observer(mut self, observer: Arc<dyn Observer>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
 }
 
 #[async_trait]
@@ -171,6 +182,7 @@ impl Tool for DelegateTool {
                 success: false,
                 output: String::new(),
                 error: Some("'agent' parameter must not be empty".into()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -186,6 +198,7 @@ impl Tool for DelegateTool {
                 success: false,
                 output: String::new(),
                 error: Some("'prompt' parameter must not be empty".into()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -213,6 +226,7 @@ impl Tool for DelegateTool {
                             available.join(", ")
                         }
                     )),
+                    error_code: None,
                     error_hint: None,
                 });
             }
@@ -229,6 +243,7 @@ impl Tool for DelegateTool {
                     depth = self.depth,
                     max = agent_config.max_depth
                 )),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -241,6 +256,7 @@ impl Tool for DelegateTool {
                 success: false,
                 output: String::new(),
                 error: Some(error),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -267,6 +283,7 @@ impl Tool for DelegateTool {
                         "Failed to create provider '{}' for agent '{agent_name}': {e}",
                         agent_config.provider
                     )),
+                    error_code: None,
                     error_hint: None,
                 });
             }
@@ -297,11 +314,14 @@ impl Tool for DelegateTool {
         // Wrap the provider call in a timeout to prevent indefinite blocking
         let result = tokio::time::timeout(
             Duration::from_secs(DELEGATE_TIMEOUT_SECS),
-            provider.chat_with_system(
-                agent_config.system_prompt.as_deref(),
-                &full_prompt,
-                &agent_config.model,
-                temperature,
+            sampling::with_sampling(
+                sampling_params(agent_config),
+                provider.chat_with_system(
+                    agent_config.system_prompt.as_deref(),
+                    &full_prompt,
+                    &agent_config.model,
+                    temperature,
+                ),
             ),
         )
         .await;
@@ -315,6 +335,7 @@ impl Tool for DelegateTool {
                     error: Some(format!(
                         "Agent '{agent_name}' timed out after {DELEGATE_TIMEOUT_SECS}s"
                     )),
+                    error_code: None,
                     error_hint: None,
                 });
             }
@@ -335,6 +356,7 @@ impl Tool for DelegateTool {
                         model = agent_config.model
                     ),
                     error: None,
+                    error_code: None,
                     error_hint: None,
                 })
             }
@@ -342,6 +364,7 @@ impl Tool for DelegateTool {
                 success: false,
                 output: String::new(),
                 error: Some(format!("Agent '{agent_name}' failed: {e}",)),
+                error_code: None,
                 error_hint: None,
             }),
         }
@@ -364,6 +387,7 @@ impl DelegateTool {
                 error: Some(format!(
                     "Agent '{agent_name}' has agentic=true but allowed_tools is empty"
                 )),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -391,6 +415,7 @@ impl DelegateTool {
                     "Agent '{agent_name}' has no executable tools after filtering allowlist ({})",
                     agent_config.allowed_tools.join(", ")
                 )),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -402,26 +427,33 @@ impl DelegateTool {
         history.push(ChatMessage::user(full_prompt.to_string()));
 
         let noop_observer = NoopObserver;
+        let observer: &dyn Observer = match &self.observer {
+            Some(observer) => observer.as_ref(),
+            None => &noop_observer,
+        };
 
         let result = tokio::time::timeout(
             Duration::from_secs(DELEGATE_AGENTIC_TIMEOUT_SECS),
-            run_tool_call_loop(
-                provider,
-                &mut history,
-                &sub_tools,
-                &noop_observer,
-                &agent_config.provider,
-                &agent_config.model,
-                temperature,
-                true,
-                None,
-                "delegate",
-                &self.multimodal_config,
-                agent_config.max_iterations,
-                None,
-                None,
-                None,
-                &[],
+            sampling::with_sampling(
+                sampling_params(agent_config),
+                run_tool_call_loop(
+                    provider,
+                    &mut history,
+                    &sub_tools,
+                    observer,
+                    &agent_config.provider,
+                    &agent_config.model,
+                    temperature,
+                    true,
+                    None,
+                    "delegate",
+                    &self.multimodal_config,
+                    agent_config.max_iterations,
+                    None,
+                    None,
+                    None,
+                    &[],
+                ),
             ),
         )
         .await;
@@ -442,6 +474,7 @@ impl DelegateTool {
                         model = agent_config.model
                     ),
                     error: None,
+                    error_code: None,
                     error_hint: None,
                 })
             }
@@ -449,6 +482,7 @@ impl DelegateTool {
                 success: false,
                 output: String::new(),
                 error: Some(format!("Agent '{agent_name}' failed: {e}")),
+                error_code: None,
                 error_hint: None,
             }),
             Err(_) => Ok(ToolResult {
@@ -457,12 +491,20 @@ impl DelegateTool {
                 error: Some(format!(
                     "Agent '{agent_name}' timed out after {DELEGATE_AGENTIC_TIMEOUT_SECS}s"
                 )),
+                error_code: None,
                 error_hint: None,
             }),
         }
     }
 }
 
+fn sampling_params(agent_config: &DelegateAgentConfig) -> SamplingParams {
+    SamplingParams {
+        top_p: agent_config.top_p,
+        seed: agent_config.seed,
+    }
+}
+
 struct ToolArcRef {
     inner: Arc<dyn Tool>,
 }
@@ -529,6 +571,8 @@ mod tests {
                 system_prompt: Some("You are a research assistant.".to_string()),
                 api_key: None,
                 temperature: Some(0.3),
+                top_p: None,
+                seed: None,
                 max_depth: 3,
                 agentic: false,
                 allowed_tools: Vec::new(),
@@ -543,6 +587,8 @@ mod tests {
                 system_prompt: None,
                 api_key: Some("delegate-test-credential".to_string()),
                 temperature: None,
+                top_p: None,
+                seed: None,
                 max_depth: 2,
                 agentic: false,
                 allowed_tools: Vec::new(),
@@ -585,6 +631,7 @@ mod tests {
                 success: true,
                 output: format!("echo:{value}"),
                 error: None,
+                error_code: None,
                 error_hint: None,
             })
         }
@@ -697,6 +744,8 @@ mod tests {
             system_prompt: Some("You are agentic.".to_string()),
             api_key: Some("delegate-test-credential".to_string()),
             temperature: Some(0.2),
+            top_p: None,
+            seed: None,
             max_depth: 3,
             agentic: true,
             allowed_tools,
@@ -805,6 +854,8 @@ mod tests {
                 system_prompt: None,
                 api_key: None,
                 temperature: None,
+                top_p: None,
+                seed: None,
                 max_depth: 3,
                 agentic: false,
                 allowed_tools: Vec::new(),
@@ -911,6 +962,8 @@ mod tests {
                 system_prompt: None,
                 api_key: None,
                 temperature: None,
+                top_p: None,
+                seed: None,
                 max_depth: 3,
                 agentic: false,
                 allowed_tools: Vec::new(),
@@ -946,6 +999,8 @@ mod tests {
                 system_prompt: None,
                 api_key: None,
                 temperature: None,
+                top_p: None,
+                seed: None,
                 max_depth: 3,
                 agentic: false,
                 allowed_tools: Vec::new(),
@@ -0,0 +1,380 @@
+//! `.env.example` generation.
+//!
+//! Before a snapshot is saved and before `github_push` deploys, the project's
+//! environment variables are collected from three places: variables the
+//! agent set with `sandbox_set_env` (tracked in the database with a
+//! description), keys of the project's `.env` files, and variables the code
+//! reads (`process.env.X`, `os.environ["X"]`, `std::env::var("X")`, ...).
+//! Every variable `.env.example` doesn't list yet is appended with a comment
+//! saying what it is for and whether it is required. Existing lines are kept
+//! as they are, and values are never copied.
+
+use super::secret_scan::ENV_EXAMPLE;
+use crate::store::env_vars::TrackedEnvVar;
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::sync::LazyLock;
+
+/// Larger files are not scanned.
+const MAX_SCAN_BYTES: usize = 512 * 1024;
+
+/// Source files scanned for variable reads.
+const SOURCE_EXTENSIONS: &[&str] = &[
+    "js", "jsx", "mjs", "cjs", "ts", "tsx", "mts", "cts", "vue", "svelte", "astro", "py", "rs",
+    "go", "rb", "php",
+];
+
+/// Directories of dependencies and build output.
+const SKIPPED_DIRS: &[&str] = &[
+    "node_modules",
+    "dist",
+    "build",
+    ".next",
+    ".nuxt",
+    ".svelte-kit",
+    "target",
+    "vendor",
+    "__pycache__",
+    ".venv",
+    "venv",
+];
+
+/// Variables the runtime or hosting platform provides; never documented.
+const PLATFORM_VARS: &[&str] = &[
+    "BASE_URL",
+    "CI",
+    "DEV",
+    "HOME",
+    "HOST",
+    "HOSTNAME",
+    "MODE",
+    "NEXT_RUNTIME",
+    "NODE_ENV",
+    "PATH",
+    "PORT",
+    "PROD",
+    "PWD",
+    "SSR",
+    "TZ",
+    "VERCEL",
+    "VERCEL_ENV",
+    "VERCEL_URL",
+];
+
+/// Reads of an environment variable, with the variable name in group 1.
+static READS: LazyLock<Vec<Regex>> = LazyLock::new(|| {
+    [
+        // JavaScript / TypeScript
+        r#"process\.env\.([A-Z][A-Z0-9_]*)\b"#,
+        r#"process\.env\[\s*["'`]([A-Z][A-Z0-9_]*)["'`]\s*\]"#,
+        r#"import\.meta\.env\.([A-Z][A-Z0-9_]*)\b"#,
+        r#"Deno\.env\.get\(\s*["']([A-Z][A-Z0-9_]*)["']"#,
+        // Python
+        r#"os\.environ\[\s*["']([A-Z][A-Z0-9_]*)["']\s*\]"#,
+        r#"os\.(?:environ\.get|getenv)\(\s*["']([A-Z][A-Z0-9_]*)["']"#,
+        // Rust
+        r#"env::var(?:_os)?\(\s*"([A-Z][A-Z0-9_]*)"\s*\)"#,
+        r#"\b(?:option_)?env!\(\s*"([A-Z][A-Z0-9_]*)""#,
+        // Go, PHP, C
+        r#"\b(?:os\.Getenv|os\.LookupEnv|getenv)\(\s*["']([A-Z][A-Z0-9_]*)["']"#,
+        // Ruby, PHP
+        r#"(?:ENV|\$_ENV|\$_SERVER)\[\s*["']([A-Z][A-Z0-9_]*)["']\s*\]"#,
+        r#"ENV\.fetch\(\s*["']([A-Z][A-Z0-9_]*)["']"#,
+    ]
+    .iter()
+    .map(|pattern| Regex::new(pattern).unwrap())
+    .collect()
+});
+
+/// A default right after a read makes the variable optional:
+/// `process.env.X ?? "..."`, `os.getenv("X", "...")`, `ENV.fetch("X", ...)`.
+static FALLBACK: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*(?:\?\?|\|\||,|\.unwrap_or|\.or\b)").unwrap());
+
+/// A `NAME=` assignment in an env file.
+static ASSIGNMENT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*(?:export\s+)?([A-Za-z_][A-Za-z0-9_]*)\s*=").unwrap());
+
+/// An environment variable of the project.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvVar {
+    pub name: String,
+    /// Description given to `sandbox_set_env`, if any.
+    pub description: Option<String>,
+    pub required: bool,
+    /// Files that read or set it.
+    pub sources: Vec<String>,
+}
+
+impl EnvVar {
+    /// Comment written above the variable in `.env.example`.
+    fn comment(&self) -> String {
+        let what = match &self.description {
+            Some(description) => description.trim_end_matches('.').to_string(),
+            None => match self.sources.as_slice() {
+                [] => "Set by the agent".to_string(),
+                [only] => format!("Used in {only}"),
+                [first, rest @ ..] => format!("Used in {first} and {} more", rest.len()),
+            },
+        };
+        let required = if self.required {
+            "required"
+        } else {
+            "optional"
+        };
+        format!("# {what} ({required})")
+    }
+}
+
+/// Whether `path` is a `.env` file with real values (`.env`, `.env.local`,
+/// `.env.production`, ...), as opposed to a template.
+pub fn is_env_file(path: &str) -> bool {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    name == ".env"
+        || name.strip_prefix(".env.").is_some_and(|suffix| {
+            !matches!(
+                suffix,
+                "example" | "sample" | "template" | "dist" | "defaults"
+            )
+        })
+}
+
+/// Whether `path` is project source worth scanning: a source file outside
+/// dependency and build directories, and not too large.
+pub fn is_source(path: &str, content: &str) -> bool {
+    content.len() <= MAX_SCAN_BYTES
+        && !path
+            .split('/')
+            .any(|segment| SKIPPED_DIRS.contains(&segment))
+        && path
+            .rsplit_once('.')
+            .is_some_and(|(_, ext)| SOURCE_EXTENSIONS.contains(&ext))
+}
+
+/// Names listed in `.env.example` text.
+fn listed(example: &str) -> Vec<String> {
+    example
+        .lines()
+        .filter_map(|line| ASSIGNMENT.captures(line).map(|c| c[1].to_string()))
+        .collect()
+}
+
+/// The environment variables of the project in `files`, plus the `tracked`
+/// ones, by name.
+pub fn collect<'a>(
+    files: impl IntoIterator<Item = (&'a String, &'a String)>,
+    tracked: &[TrackedEnvVar],
+) -> Vec<EnvVar> {
+    let mut vars: BTreeMap<String, EnvVar> = BTreeMap::new();
+    let mut note = |name: &str, path: &str, required: bool| {
+        let var = vars.entry(name.to_string()).or_insert_with(|| EnvVar {
+            name: name.to_string(),
+            description: None,
+            required: false,
+            sources: Vec::new(),
+        });
+        var.required |= required;
+        if !var.sources.iter().any(|source| source == path) {
+            var.sources.push(path.to_string());
+        }
+    };
+
+    for (path, content) in files {
+        if is_env_file(path) {
+            for name in listed(content) {
+                note(&name, path, true);
+            }
+            continue;
+        }
+        if !is_source(path, content) {
+            continue;
+        }
+        for regex in READS.iter() {
+            for captures in regex.captures_iter(content) {
+                let name = captures.get(1).expect("group 1");
+                if PLATFORM_VARS.contains(&name.as_str()) {
+                    continue;
+                }
+                let rest = &content[captures.get(0).expect("match").end()..];
+                let optional = FALLBACK.is_match(rest) || captures[0].starts_with("option_env!");
+                note(name.as_str(), path, !optional);
+            }
+        }
+    }
+
+    for var in vars.values_mut() {
+        var.sources.sort();
+    }
+    for tracked in tracked {
+        let var = vars.entry(tracked.name.clone()).or_insert_with(|| EnvVar {
+            name: tracked.name.clone(),
+            description: None,
+            required: false,
+            sources: Vec::new(),
+        });
+        if !tracked.description.trim().is_empty() {
+            var.description = Some(tracked.description.trim().to_string());
+        }
+        // The agent said whether it is required; that beats the guess.
+        var.required = tracked.required;
+    }
+    vars.into_values().collect()
+}
+
+/// What [`apply`] changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EnvExampleReport {
+    /// Whether `.env.example` was created.
+    pub created: bool,
+    /// Variables appended to it.
+    pub added: Vec<String>,
+}
+
+/// `existing` `.env.example` text with the `vars` it doesn't list appended,
+/// or `None` when it already lists them all.
+pub fn update(existing: Option<&str>, vars: &[EnvVar]) -> Option<(String, EnvExampleReport)> {
+    let present = existing.map(listed).unwrap_or_default();
+    let missing: Vec<&EnvVar> = vars
+        .iter()
+        .filter(|var| !present.contains(&var.name))
+        .collect();
+    if missing.is_empty() {
+        return None;
+    }
+
+    let mut text = match existing {
+        Some(text) => text.to_string(),
+        None => "# Environment variables of this project. Copy to .env and fill in the values.\n"
+            .to_string(),
+    };
+    for var in &missing {
+        if !text.is_empty() && !text.ends_with('\n') {
+            text.push('\n');
+        }
+        if !text.is_empty() && !text.ends_with("\n\n") {
+            text.push('\n');
+        }
+        text.push_str(&var.comment());
+        text.push('\n');
+        text.push_str(&var.name);
+        text.push_str("=\n");
+    }
+    let report = EnvExampleReport {
+        created: existing.is_none(),
+        added: missing.iter().map(|var| var.name.clone()).collect(),
+    };
+    Some((text, report))
+}
+
+/// Add the `vars` `.env.example` in `files` doesn't list yet, creating it
+/// when needed.
+pub fn apply(files: &mut BTreeMap<String, String>, vars: &[EnvVar]) -> EnvExampleReport {
+    match update(files.get(ENV_EXAMPLE).map(String::as_str), vars) {
+        Some((text, report)) => {
+            files.insert(ENV_EXAMPLE.to_string(), text);
+            report
+        }
+        None => EnvExampleReport::default(),
+    }
+}
+
+/// One line per required variable, e.g. `  DATABASE_URL — Postgres URL`.
+pub fn required_list(vars: &[EnvVar]) -> Vec<String> {
+    vars.iter()
+        .filter(|var| var.required)
+        .map(|var| match &var.description {
+            Some(description) => format!("  {} — {description}", var.name),
+            None => format!("  {}", var.name),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+        entries
+            .iter()
+            .map(|(path, content)| ((*path).to_string(), (*content).to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn collects_reads_env_files_and_tracked_vars() {
+        let project = files(&[
+            (
+                "src/db.ts",
+                "const url = process.env.DATABASE_URL;\n\
+                 const port = process.env.PORT ?? 3000;\n\
+                 const level = process.env.LOG_LEVEL ?? 'info';\n\
+                 const key = process.env['STRIPE_KEY'];",
+            ),
+            (
+                "app/main.py",
+                "import os\nA = os.environ[\"OPENAI_API_KEY\"]\nB = os.getenv(\"REGION\", \"us\")\n",
+            ),
+            ("src/main.rs", "let t = std::env::var(\"BOT_TOKEN\")?;"),
+            ("node_modules/x/index.js", "process.env.IGNORED"),
+            (".env", "SESSION_SECRET=abc\n"),
+        ]);
+        let tracked = [TrackedEnvVar {
+            name: "STRIPE_KEY".into(),
+            description: "Stripe secret key".into(),
+            required: true,
+            updated_at: String::new(),
+        }];
+        let vars = collect(&project, &tracked);
+        let names: Vec<(&str, bool)> = vars.iter().map(|v| (v.name.as_str(), v.required)).collect();
+        assert_eq!(
+            names,
+            [
+                ("BOT_TOKEN", true),
+                ("DATABASE_URL", true),
+                ("LOG_LEVEL", false),
+                ("OPENAI_API_KEY", true),
+                ("REGION", false),
+                ("SESSION_SECRET", true),
+                ("STRIPE_KEY", true),
+            ]
+        );
+        let stripe = vars.iter().find(|v| v.name == "STRIPE_KEY").unwrap();
+        assert_eq!(stripe.description.as_deref(), Some("Stripe secret key"));
+        assert_eq!(stripe.sources, ["src/db.ts"]);
+        assert_eq!(
+            required_list(&vars[5..]),
+            ["  SESSION_SECRET", "  STRIPE_KEY — Stripe secret key"]
+        );
+    }
+
+    #[test]
+    fn appends_missing_vars_and_keeps_existing_lines() {
+        let vars = collect(
+            &files(&[("src/a.js", "process.env.API_URL; process.env.API_KEY || ''")]),
+            &[],
+        );
+
+        let mut project = files(&[(".env.example", "# Backend\nAPI_URL=http://localhost\n")]);
+        let report = apply(&mut project, &vars);
+        assert!(!report.created);
+        assert_eq!(report.added, ["API_KEY"]);
+        assert_eq!(
+            project[".env.example"],
+            "# Backend\nAPI_URL=http://localhost\n\n# Used in src/a.js (optional)\nAPI_KEY=\n"
+        );
+        assert_eq!(apply(&mut project, &vars), EnvExampleReport::default());
+
+        let mut fresh = BTreeMap::new();
+        let report = apply(&mut fresh, &vars);
+        assert!(report.created);
+        assert!(fresh[".env.example"].contains("# Used in src/a.js (required)\nAPI_URL=\n"));
+    }
+
+    #[test]
+    fn recognizes_env_files() {
+        assert!(is_env_file(".env"));
+        assert!(is_env_file("web/.env.local"));
+        assert!(!is_env_file(".env.example"));
+        assert!(!is_env_file("src/env.ts"));
+    }
+}
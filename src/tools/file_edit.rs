@@ -73,6 +73,7 @@ impl Tool for FileEditTool {
                 success: false,
                 output: String::new(),
                 error: Some("old_string must not be empty".into()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -83,6 +84,7 @@ impl Tool for FileEditTool {
                 success: false,
                 output: String::new(),
                 error: Some("Action blocked: autonomy is read-only".into()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -93,6 +95,7 @@ impl Tool for FileEditTool {
                 success: false,
                 output: String::new(),
                 error: Some("Rate limit exceeded: too many actions in the last hour".into()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -103,6 +106,7 @@ impl Tool for FileEditTool {
                 success: false,
                 output: String::new(),
                 error: Some(format!("Path not allowed by security policy: {path}")),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -115,6 +119,7 @@ impl Tool for FileEditTool {
                 success: false,
                 output: String::new(),
                 error: Some("Invalid path: missing parent directory".into()),
+                error_code: None,
                 error_hint: None,
             });
         };
@@ -126,6 +131,7 @@ impl Tool for FileEditTool {
                     success: false,
                     output: String::new(),
                     error: Some(format!("Failed to resolve file path: {e}")),
+                    error_code: None,
                     error_hint: None,
                 });
             }
@@ -140,6 +146,7 @@ impl Tool for FileEditTool {
                     self.security
                         .resolved_path_violation_message(&resolved_parent),
                 ),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -149,6 +156,7 @@ impl Tool for FileEditTool {
                 success: false,
                 output: String::new(),
                 error: Some("Invalid path: missing file name".into()),
+                error_code: None,
                 error_hint: None,
             });
         };
@@ -165,6 +173,7 @@ impl Tool for FileEditTool {
                         "Refusing to edit through symlink: {}",
                         resolved_target.display()
                     )),
+                    error_code: None,
                     error_hint: None,
                 });
             }
@@ -176,6 +185,7 @@ impl Tool for FileEditTool {
                 success: false,
                 output: String::new(),
                 error: Some("Rate limit exceeded: action budget exhausted".into()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -188,6 +198,7 @@ impl Tool for FileEditTool {
                     success: false,
                     output: String::new(),
                     error: Some(format!("Failed to read file: {e}")),
+                    error_code: None,
                     error_hint: None,
                 });
             }
@@ -200,6 +211,7 @@ impl Tool for FileEditTool {
                 success: false,
                 output: String::new(),
                 error: Some("old_string not found in file".into()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -211,6 +223,7 @@ impl Tool for FileEditTool {
                 error: Some(format!(
                     "old_string matches {match_count} times; must match exactly once"
                 )),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -225,12 +238,14 @@ impl Tool for FileEditTool {
                     new_content.len()
                 ),
                 error: None,
+                error_code: None,
                 error_hint: None,
             }),
             Err(e) => Ok(ToolResult {
                 success: false,
                 output: String::new(),
                 error: Some(format!("Failed to write file: {e}")),
+                error_code: None,
                 error_hint: None,
             }),
         }
@@ -58,6 +58,7 @@ impl Tool for FileWriteTool {
                 success: false,
                 output: String::new(),
                 error: Some("Action blocked: autonomy is read-only".into()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -67,6 +68,7 @@ impl Tool for FileWriteTool {
                 success: false,
                 output: String::new(),
                 error: Some("Rate limit exceeded: too many actions in the last hour".into()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -77,6 +79,7 @@ impl Tool for FileWriteTool {
                 success: false,
                 output: String::new(),
                 error: Some(format!("Path not allowed by security policy: {path}")),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -88,6 +91,7 @@ impl Tool for FileWriteTool {
                 success: false,
                 output: String::new(),
                 error: Some("Invalid path: missing parent directory".into()),
+                error_code: None,
                 error_hint: None,
             });
         };
@@ -103,6 +107,7 @@ impl Tool for FileWriteTool {
                     success: false,
                     output: String::new(),
                     error: Some(format!("Failed to resolve file path: {e}")),
+                    error_code: None,
                     error_hint: None,
                 });
             }
@@ -116,6 +121,7 @@ impl Tool for FileWriteTool {
                     self.security
                         .resolved_path_violation_message(&resolved_parent),
                 ),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -125,6 +131,7 @@ impl Tool for FileWriteTool {
                 success: false,
                 output: String::new(),
                 error: Some("Invalid path: missing file name".into()),
+                error_code: None,
                 error_hint: None,
             });
         };
@@ -141,6 +148,7 @@ impl Tool for FileWriteTool {
                         "Refusing to write through symlink: {}",
                         resolved_target.display()
                     )),
+                    error_code: None,
                     error_hint: None,
                 });
             }
@@ -151,6 +159,7 @@ impl Tool for FileWriteTool {
                 success: false,
                 output: String::new(),
                 error: Some("Rate limit exceeded: action budget exhausted".into()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -160,12 +169,14 @@ impl Tool for FileWriteTool {
                 success: true,
                 output: format!("Written {} bytes to {path}", content.len()),
                 error: None,
+                error_code: None,
                 error_hint: None,
             }),
             Err(e) => Ok(ToolResult {
                 success: false,
                 output: String::new(),
                 error: Some(format!("Failed to write file: {e}")),
+                error_code: None,
                 error_hint: None,
             }),
         }
@@ -128,6 +128,7 @@ impl GitOperationsTool {
             success: true,
             output: serde_json::to_string_pretty(&result).unwrap_or_default(),
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
@@ -208,6 +209,7 @@ impl GitOperationsTool {
             success: true,
             output: serde_json::to_string_pretty(&result).unwrap_or_default(),
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
@@ -246,6 +248,7 @@ impl GitOperationsTool {
             output: serde_json::to_string_pretty(&json!({ "commits": commits }))
                 .unwrap_or_default(),
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
@@ -279,6 +282,7 @@ impl GitOperationsTool {
             }))
             .unwrap_or_default(),
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
@@ -319,12 +323,14 @@ impl GitOperationsTool {
                 success: true,
                 output: format!("Committed: {message}"),
                 error: None,
+                error_code: None,
                 error_hint: None,
             }),
             Err(e) => Ok(ToolResult {
                 success: false,
                 output: String::new(),
                 error: Some(format!("Commit failed: {e}")),
+                error_code: None,
                 error_hint: None,
             }),
         }
@@ -346,12 +352,14 @@ impl GitOperationsTool {
                 success: true,
                 output: format!("Staged: {paths}"),
                 error: None,
+                error_code: None,
                 error_hint: None,
             }),
             Err(e) => Ok(ToolResult {
                 success: false,
                 output: String::new(),
                 error: Some(format!("Add failed: {e}")),
+                error_code: None,
                 error_hint: None,
             }),
         }
@@ -384,12 +392,14 @@ impl GitOperationsTool {
                 success: true,
                 output: format!("Switched to branch: {branch_name}"),
                 error: None,
+                error_code: None,
                 error_hint: None,
             }),
             Err(e) => Ok(ToolResult {
                 success: false,
                 output: String::new(),
                 error: Some(format!("Checkout failed: {e}")),
+                error_code: None,
                 error_hint: None,
             }),
         }
@@ -423,12 +433,14 @@ impl GitOperationsTool {
                 success: true,
                 output: out,
                 error: None,
+                error_code: None,
                 error_hint: None,
             }),
             Err(e) => Ok(ToolResult {
                 success: false,
                 output: String::new(),
                 error: Some(format!("Stash {action} failed: {e}")),
+                error_code: None,
                 error_hint: None,
             }),
         }
@@ -500,6 +512,7 @@ impl Tool for GitOperationsTool {
                     success: false,
                     output: String::new(),
                     error: Some("Missing 'operation' parameter".into()),
+                    error_code: None,
                     error_hint: None,
                 });
             }
@@ -523,6 +536,7 @@ impl Tool for GitOperationsTool {
                     success: false,
                     output: String::new(),
                     error: Some("Not in a git repository".into()),
+                    error_code: None,
                     error_hint: None,
                 });
             }
@@ -537,6 +551,7 @@ impl Tool for GitOperationsTool {
                     error: Some(
                         "Action blocked: git write operations require higher autonomy level".into(),
                     ),
+                    error_code: None,
                     error_hint: None,
                 });
             }
@@ -547,6 +562,7 @@ impl Tool for GitOperationsTool {
                         success: false,
                         output: String::new(),
                         error: Some("Action blocked: read-only mode".into()),
+                        error_code: None,
                         error_hint: None,
                     });
                 }
@@ -560,6 +576,7 @@ impl Tool for GitOperationsTool {
                 success: false,
                 output: String::new(),
                 error: Some("Action blocked: rate limit exceeded".into()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -578,6 +595,7 @@ impl Tool for GitOperationsTool {
                 success: false,
                 output: String::new(),
                 error: Some(format!("Unknown operation: {operation}")),
+                error_code: None,
                 error_hint: None,
             }),
         }
@@ -0,0 +1,166 @@
+//! Repositories and organizations the GitHub tools may touch: the
+//! `[zerobuild.repos]` deny list and, while `[guardrails]` denies deploys
+//! outside it, `deploy_repo_allowlist`.
+//!
+//! Every request [`super::github_ops`] sends, the image commits of
+//! `github_upload_image`, the issue watcher's comments and pull requests,
+//! and the repository `github_push` (and so `ship_it`) deploys to are
+//! checked before they go out. A denied entry wins over an allowed one, so a
+//! confused or prompt-injected agent cannot push to, or open issues and PRs
+//! against, repositories outside the sanctioned set.
+
+use super::github_transport::GITHUB_API_BASE;
+use crate::config::RepoAccessConfig;
+
+/// Allow and deny lists, lowercased.
+#[derive(Debug, Clone, Default)]
+pub struct RepoAccess {
+    /// `deploy_repo_allowlist` entries: `repo`, `owner/repo` or `owner/*`.
+    allow: Vec<String>,
+    deny: Vec<(String, Option<String>)>,
+}
+
+/// `owner`, `owner/*` or `owner/repo` as `(owner, repo)`; `None` matches
+/// every repository of the owner.
+fn parse_entry(entry: &str) -> (String, Option<String>) {
+    let entry = entry.trim().to_ascii_lowercase();
+    match entry.split_once('/') {
+        Some((owner, "*" | "")) => (owner.to_string(), None),
+        Some((owner, repo)) => (owner.to_string(), Some(repo.to_string())),
+        None => (entry, None),
+    }
+}
+
+impl RepoAccess {
+    pub fn from_config(config: &RepoAccessConfig) -> Self {
+        Self {
+            allow: config
+                .allow
+                .iter()
+                .map(|entry| entry.trim().to_ascii_lowercase())
+                .filter(|entry| !entry.is_empty())
+                .collect(),
+            deny: config.deny.iter().map(|e| parse_entry(e)).collect(),
+        }
+    }
+
+    /// Whether `owner` (and `repo`, when given) is on the deny list.
+    /// Without a repo only whole-owner entries match.
+    fn denied(&self, owner: &str, repo: Option<&str>) -> bool {
+        self.deny.iter().any(|(entry_owner, entry_repo)| {
+            entry_owner == owner
+                && match (entry_repo, repo) {
+                    (None, _) => true,
+                    (Some(entry_repo), Some(repo)) => entry_repo == repo,
+                    (Some(_), None) => false,
+                }
+        })
+    }
+
+    /// Why `owner/repo` (or, without `repo`, the account `owner`) may not be
+    /// touched, if it may not.
+    pub fn check(&self, owner: &str, repo: Option<&str>) -> Result<(), String> {
+        let owner = owner.trim().to_ascii_lowercase();
+        let repo = repo.map(|repo| repo.trim().to_ascii_lowercase());
+        let target = match &repo {
+            Some(repo) => format!("{owner}/{repo}"),
+            None => owner.clone(),
+        };
+        if self.denied(&owner, repo.as_deref()) {
+            return Err(format!(
+                "'{target}' is on the [zerobuild.repos] deny list; the agent may not touch it"
+            ));
+        }
+        if !self.allowed(&owner, repo.as_deref()) {
+            return Err(format!(
+                "'{target}' is not on the [guardrails] deploy_repo_allowlist; the agent may not \
+                 touch it"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Whether the allow list admits `owner/repo`: a bare `repo` entry
+    /// matches that name under any owner. An owner alone is reachable when
+    /// an entry names it.
+    fn allowed(&self, owner: &str, repo: Option<&str>) -> bool {
+        self.allow.is_empty()
+            || self
+                .allow
+                .iter()
+                .any(|entry| match (entry.split_once('/'), repo) {
+                    (None, Some(repo)) => entry == repo,
+                    (None, None) => false,
+                    (Some((entry_owner, entry_repo)), Some(repo)) => {
+                        entry_owner == owner && (entry_repo == "*" || entry_repo == repo)
+                    }
+                    (Some((entry_owner, _)), None) => entry_owner == owner,
+                })
+    }
+
+    /// Check the repository or organization a GitHub API `url` addresses
+    /// (`/repos/{owner}/{repo}/...`, `/orgs/{org}/...`). Other endpoints
+    /// pass.
+    pub fn check_url(&self, url: &str) -> Result<(), String> {
+        let Some(path) = url.strip_prefix(GITHUB_API_BASE) else {
+            return Ok(());
+        };
+        let path = path.split(['?', '#']).next().unwrap_or_default();
+        let mut segments = path.split('/').filter(|segment| !segment.is_empty());
+        match (segments.next(), segments.next(), segments.next()) {
+            (Some("repos"), Some(owner), Some(repo)) => self.check(owner, Some(repo)),
+            (Some("orgs"), Some(org), _) => self.check(org, None),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn access(allow: &[&str], deny: &[&str]) -> RepoAccess {
+        RepoAccess::from_config(&RepoAccessConfig {
+            allow: allow.iter().map(|e| (*e).to_string()).collect(),
+            deny: deny.iter().map(|e| (*e).to_string()).collect(),
+        })
+    }
+
+    #[test]
+    fn deny_wins_over_allow() {
+        let access = access(&["acme/*", "me/site", "landing-page"], &["acme/payroll"]);
+        assert!(access.check("Acme", Some("shop")).is_ok());
+        assert!(access.check("me", Some("site")).is_ok());
+        assert!(access.check("anyone", Some("landing-page")).is_ok());
+        assert!(access
+            .check("acme", Some("payroll"))
+            .unwrap_err()
+            .contains("deny list"));
+        assert!(access
+            .check("me", Some("blog"))
+            .unwrap_err()
+            .contains("deploy_repo_allowlist"));
+        assert!(access.check("me", None).is_ok());
+        assert!(access.check("other", None).is_err());
+    }
+
+    #[test]
+    fn empty_lists_allow_everything() {
+        let access = access(&[], &["evil"]);
+        assert!(access.check("anyone", Some("anything")).is_ok());
+        assert!(access.check("evil", Some("repo")).is_err());
+    }
+
+    #[test]
+    fn urls_are_checked_by_their_repository_or_org() {
+        let access = access(&["acme/*"], &[]);
+        let url = |path: &str| format!("{GITHUB_API_BASE}{path}");
+        assert!(access.check_url(&url("/repos/acme/shop/issues")).is_ok());
+        assert!(access
+            .check_url(&url("/repos/other/shop/pulls?x=1"))
+            .is_err());
+        assert!(access.check_url(&url("/orgs/other/repos")).is_err());
+        assert!(access.check_url(&url("/user/repos")).is_ok());
+        assert!(access.check_url(&url("/gists")).is_ok());
+    }
+}
@@ -0,0 +1,213 @@
+//! GitHub organization support for `github_list_orgs` and `github_push`.
+//!
+//! Deploy repositories can be created under an organization the user
+//! belongs to instead of their personal account, and teams of that
+//! organization can be granted access to them. Organization endpoints need
+//! OAuth scopes beyond `repo` (`read:org` to list memberships, `write:org`
+//! to manage team access); callers check them with [`super::github_scopes`].
+
+use serde_json::json;
+
+const GITHUB_API_BASE: &str = "https://api.github.com";
+
+/// Team permissions GitHub accepts on a repository.
+pub const TEAM_PERMISSIONS: &[&str] = &["pull", "triage", "push", "maintain", "admin"];
+
+/// Permission granted when a team is named without one.
+pub const DEFAULT_TEAM_PERMISSION: &str = "push";
+
+/// An organization the user is an active member of.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Org {
+    pub login: String,
+    pub description: String,
+    /// `admin` or `member`.
+    pub role: String,
+}
+
+/// Organizations the user is an active member of.
+pub async fn list_orgs(client: &reqwest::Client, token: &str) -> anyhow::Result<Vec<Org>> {
+    let resp = client
+        .get(format!(
+            "{GITHUB_API_BASE}/user/memberships/orgs?state=active&per_page=100"
+        ))
+        .header("Authorization", format!("Bearer {token}"))
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await?;
+    let status = resp.status();
+    let data: serde_json::Value = resp.json().await.unwrap_or_default();
+    if !status.is_success() {
+        anyhow::bail!(
+            "GitHub returned {status}: {}",
+            data["message"].as_str().unwrap_or("unknown error")
+        );
+    }
+    Ok(parse_memberships(&data))
+}
+
+fn parse_memberships(data: &serde_json::Value) -> Vec<Org> {
+    data.as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|membership| {
+            Some(Org {
+                login: membership["organization"]["login"].as_str()?.to_string(),
+                description: membership["organization"]["description"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                role: membership["role"].as_str().unwrap_or("member").to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Whether `owner` is an organization rather than a user account.
+pub async fn is_org(client: &reqwest::Client, token: &str, owner: &str) -> anyhow::Result<bool> {
+    let resp = client
+        .get(format!("{GITHUB_API_BASE}/users/{owner}"))
+        .header("Authorization", format!("Bearer {token}"))
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await?;
+    if !resp.status().is_success() {
+        anyhow::bail!("GitHub account '{owner}' not found ({})", resp.status());
+    }
+    let data: serde_json::Value = resp.json().await.unwrap_or_default();
+    Ok(data["type"] == "Organization")
+}
+
+/// `(team slug, permission)` grants from a list of `"slug"` or
+/// `"slug:permission"` entries.
+pub fn parse_team_grants(value: &serde_json::Value) -> Result<Vec<(String, String)>, String> {
+    let Some(entries) = value.as_array() else {
+        return Ok(Vec::new());
+    };
+    let mut grants: Vec<(String, String)> = Vec::new();
+    for entry in entries {
+        let entry = entry.as_str().unwrap_or_default().trim();
+        let (slug, permission) = entry
+            .split_once(':')
+            .map_or((entry, DEFAULT_TEAM_PERMISSION), |(slug, permission)| {
+                (slug.trim(), permission.trim())
+            });
+        if slug.is_empty() {
+            return Err("Team entries must be 'team-slug' or 'team-slug:permission'".into());
+        }
+        if !TEAM_PERMISSIONS.contains(&permission) {
+            return Err(format!(
+                "Unknown permission '{permission}' for team '{slug}'. Use one of: {}",
+                TEAM_PERMISSIONS.join(", ")
+            ));
+        }
+        grants.retain(|(existing, _)| existing != slug);
+        grants.push((slug.to_string(), permission.to_string()));
+    }
+    Ok(grants)
+}
+
+/// Create repository `name` under organization `org`.
+pub async fn create_org_repo(
+    client: &reqwest::Client,
+    token: &str,
+    org: &str,
+    name: &str,
+    private: bool,
+) -> anyhow::Result<()> {
+    let resp = client
+        .post(format!("{GITHUB_API_BASE}/orgs/{org}/repos"))
+        .header("Authorization", format!("Bearer {token}"))
+        .header("Accept", "application/vnd.github+json")
+        .json(&json!({
+            "name": name,
+            "private": private,
+            "auto_init": false,
+            "description": "Built with ZeroBuild",
+        }))
+        .send()
+        .await?;
+    let status = resp.status();
+    if !status.is_success() {
+        let body = resp.text().await.unwrap_or_default();
+        if status == reqwest::StatusCode::FORBIDDEN || status == reqwest::StatusCode::NOT_FOUND {
+            anyhow::bail!(
+                "GitHub refused to create '{org}/{name}' ({status}): {body}\n\
+                 Check that you may create repositories in '{org}' and that the organization \
+                 allows ZeroBuild under Settings → Third-party access."
+            );
+        }
+        anyhow::bail!("GitHub returned {status}: {body}");
+    }
+    Ok(())
+}
+
+/// Give team `slug` of `org` `permission` on repository `org/repo`.
+pub async fn grant_team(
+    client: &reqwest::Client,
+    token: &str,
+    org: &str,
+    slug: &str,
+    repo: &str,
+    permission: &str,
+) -> anyhow::Result<()> {
+    let resp = client
+        .put(format!(
+            "{GITHUB_API_BASE}/orgs/{org}/teams/{slug}/repos/{org}/{repo}"
+        ))
+        .header("Authorization", format!("Bearer {token}"))
+        .header("Accept", "application/vnd.github+json")
+        .json(&json!({ "permission": permission }))
+        .send()
+        .await?;
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let body = resp.text().await.unwrap_or_default();
+        anyhow::bail!("GitHub returned {status}: {body}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_team_grants() {
+        assert_eq!(
+            parse_team_grants(&json!(["frontend", "ops:admin", "frontend:maintain"])).unwrap(),
+            [
+                ("ops".to_string(), "admin".to_string()),
+                ("frontend".to_string(), "maintain".to_string()),
+            ]
+        );
+        assert_eq!(parse_team_grants(&json!(null)).unwrap(), []);
+        assert!(parse_team_grants(&json!(["ops:owner"])).is_err());
+        assert!(parse_team_grants(&json!([":push"])).is_err());
+    }
+
+    #[test]
+    fn parses_memberships() {
+        let orgs = parse_memberships(&json!([
+            { "role": "admin", "organization": { "login": "potlock", "description": "Funding" } },
+            { "role": "member", "organization": { "login": "acme", "description": null } },
+            { "role": "member" }
+        ]));
+        assert_eq!(
+            orgs,
+            [
+                Org {
+                    login: "potlock".into(),
+                    description: "Funding".into(),
+                    role: "admin".into()
+                },
+                Org {
+                    login: "acme".into(),
+                    description: String::new(),
+                    role: "member".into()
+                },
+            ]
+        );
+    }
+}
@@ -9,7 +9,9 @@
 
 use super::traits::{Tool, ToolResult};
 use crate::config::ZerobuildConfig;
+use crate::sandbox::devcontainer::{self, DevContainer};
 use crate::sandbox::SandboxClient;
+use crate::security::guardrails::{self, GuardrailPolicy};
 use crate::store;
 use async_trait::async_trait;
 use base64::Engine as _;
@@ -18,6 +20,8 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 const TOOL_NAME: &str = "github_read_repo";
+/// Name the user approves a switch to a dev container image under.
+const IMAGE_SWITCH: &str = "devcontainer_image";
 const GITHUB_API_BASE: &str = "https://api.github.com";
 const MAX_FILES: usize = 500;
 
@@ -46,11 +50,106 @@ const SKIP_DIRS: &[&str] = &[
 pub struct GitHubReadRepoTool {
     client: Arc<dyn SandboxClient>,
     config: Arc<ZerobuildConfig>,
+    guardrails: Option<Arc<GuardrailPolicy>>,
 }
 
 impl GitHubReadRepoTool {
     pub fn new(client: Arc<dyn SandboxClient>, config: Arc<ZerobuildConfig>) -> Self {
-        Self { client, config }
+        Self {
+            client,
+            config,
+            guardrails: None,
+        }
+    }
+
+    /// Check the dev container's lifecycle commands against `guardrails`,
+    /// as the `sandbox_run_command` calls they would be.
+    pub fn with_guardrails(mut self, guardrails: Option<Arc<GuardrailPolicy>>) -> Self {
+        self.guardrails = guardrails;
+        self
+    }
+
+    /// Save the files under `workdir` as the stored snapshot before the
+    /// sandbox is replaced. Returns how many were saved; an empty `workdir`
+    /// leaves the stored snapshot alone.
+    async fn snapshot_current(&self, workdir: &str) -> anyhow::Result<usize> {
+        let files = self.client.collect_snapshot_files(workdir).await?;
+        if files.is_empty() {
+            return Ok(0);
+        }
+        let conn = store::init_db(&PathBuf::from(&self.config.db_path))?;
+        store::snapshot::save_snapshot(&conn, &files, None)?;
+        Ok(files.len())
+    }
+
+    /// Replace the sandbox with one running the dev container's `image`:
+    /// the repository names the image, so the user approves the switch
+    /// first, and the files under `workdir` are snapshotted so nothing in
+    /// the live sandbox is lost. Returns a note for the report.
+    async fn switch_image(&self, image: &str, workdir: &str) -> String {
+        if let Err(code) = guardrails::require_approval(IMAGE_SWITCH, &json!({ "image": image })) {
+            return format!(
+                "Dev container image {image} not used: replacing the sandbox with an image the \
+                 repository names needs the user's approval. Tell the user which image it is \
+                 and that they can allow the switch once by sending `/approve {code}`; after \
+                 they do, call {TOOL_NAME} again."
+            );
+        }
+        let saved = match self.snapshot_current(workdir).await {
+            Ok(saved) => saved,
+            Err(e) => {
+                return format!(
+                    "Dev container image {image} not used: the current sandbox could not be \
+                     snapshotted first: {e}."
+                )
+            }
+        };
+        let timeout_ms = self.config.sandbox.create_timeout_secs.saturating_mul(1000);
+        match self
+            .client
+            .create_sandbox_from_image(image, timeout_ms)
+            .await
+        {
+            Ok(_) => {
+                self.client.detect_package_manager().await;
+                let snapshot = if saved == 0 {
+                    String::new()
+                } else {
+                    format!(" ({saved} files of {workdir}/ were snapshotted first)")
+                };
+                format!("Sandbox recreated from the dev container image {image}{snapshot}.")
+            }
+            Err(e) => format!("Dev container image {image} not used: {e}."),
+        }
+    }
+
+    /// The repository's dev container settings, if it has a readable
+    /// `devcontainer.json`.
+    async fn read_devcontainer(
+        &self,
+        http: &reqwest::Client,
+        token: &str,
+        owner: &str,
+        repo: &str,
+        entries: &[serde_json::Value],
+    ) -> Option<DevContainer> {
+        let sha = devcontainer::PATHS.iter().find_map(|path| {
+            entries
+                .iter()
+                .find(|entry| entry["type"] == "blob" && entry["path"] == *path)
+                .and_then(|entry| entry["sha"].as_str())
+        })?;
+        let parsed = fetch_blob(http, token, owner, repo, sha)
+            .await
+            .map_err(|reason| anyhow::anyhow!(reason))
+            .and_then(|text| DevContainer::parse(&text));
+        match parsed {
+            Ok(container) => Some(container),
+            Err(e) => {
+                tracing::warn!("github_read_repo: devcontainer.json ignored — {e}");
+                None
+            }
+        }
     }
 }
 
@@ -65,7 +164,10 @@ impl Tool for GitHubReadRepoTool {
          Fetches the full file tree and writes each file under the specified workdir. \
          Use this before applying a bug fix to an existing repo. \
          Requires an active sandbox and GitHub authentication (use github_connect first). \
-         Skips binary files and large dependency directories automatically."
+         Skips binary files and large dependency directories automatically. If the repo has a \
+         devcontainer.json, the sandbox is recreated from its image where the provider runs \
+         images, once the user approves it and after the current files are snapshotted; its \
+         setup commands then run under the guardrails and its forwardPorts get preview URLs."
     }
 
     fn parameters_schema(&self) -> serde_json::Value {
@@ -100,6 +202,7 @@ impl Tool for GitHubReadRepoTool {
                 success: false,
                 output: String::new(),
                 error: Some(e),
+                error_code: None,
                 error_hint: Some(
                     "Create a sandbox first with sandbox_create, then call github_read_repo."
                         .to_string(),
@@ -114,6 +217,7 @@ impl Tool for GitHubReadRepoTool {
                     success: false,
                     output: String::new(),
                     error: Some("Missing required parameter: owner".to_string()),
+                    error_code: None,
                     error_hint: None,
                 })
             }
@@ -126,6 +230,7 @@ impl Tool for GitHubReadRepoTool {
                     success: false,
                     output: String::new(),
                     error: Some("Missing required parameter: repo".to_string()),
+                    error_code: None,
                     error_hint: None,
                 })
             }
@@ -157,6 +262,7 @@ impl Tool for GitHubReadRepoTool {
                         "GitHub is not connected. Use github_connect to authenticate first."
                             .to_string(),
                     ),
+                    error_code: None,
                     error_hint: None,
                 })
             }
@@ -165,6 +271,7 @@ impl Tool for GitHubReadRepoTool {
                     success: false,
                     output: String::new(),
                     error: Some(format!("Failed to load GitHub token: {e}")),
+                    error_code: None,
                     error_hint: None,
                 })
             }
@@ -197,6 +304,7 @@ impl Tool for GitHubReadRepoTool {
                 error: Some(format!(
                     "Failed to fetch repo tree ({status}): {body}"
                 )),
+                error_code: None,
                 error_hint: Some(format!(
                     "Make sure the repo '{owner}/{repo}' exists and the branch '{branch}' is correct."
                 )),
@@ -215,6 +323,7 @@ impl Tool for GitHubReadRepoTool {
                     success: false,
                     output: String::new(),
                     error: Some("Unexpected tree response format from GitHub API.".to_string()),
+                    error_code: None,
                     error_hint: None,
                 })
             }
@@ -244,6 +353,7 @@ impl Tool for GitHubReadRepoTool {
                 error: Some(format!(
                     "No readable text files found in '{owner}/{repo}' on branch '{branch}'."
                 )),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -252,64 +362,28 @@ impl Tool for GitHubReadRepoTool {
         let mut written = 0usize;
         let mut skipped = 0usize;
 
-        // 4. Fetch each blob and write to sandbox
-        for (path, sha) in &blobs {
-            let blob_url = format!("{GITHUB_API_BASE}/repos/{owner}/{repo}/git/blobs/{sha}");
-
-            let blob_resp = match http
-                .get(&blob_url)
-                .header("Authorization", format!("Bearer {token}"))
-                .header("Accept", "application/vnd.github+json")
-                .send()
-                .await
-            {
-                Ok(r) => r,
-                Err(e) => {
-                    tracing::warn!("github_read_repo: skip {path} — fetch error: {e}");
-                    skipped += 1;
-                    continue;
-                }
-            };
-
-            if !blob_resp.status().is_success() {
-                tracing::warn!(
-                    "github_read_repo: skip {path} — blob API returned {}",
-                    blob_resp.status()
-                );
-                skipped += 1;
-                continue;
+        // 4. Start the sandbox from the dev container's image, if any
+        let devcontainer = self
+            .read_devcontainer(&http, token, &owner, &repo, &entries)
+            .await;
+        let mut notes = Vec::new();
+        if let Some(image) = devcontainer.as_ref().and_then(|c| c.image.as_deref()) {
+            if self.client.runs_images() {
+                notes.push(self.switch_image(image, &workdir).await);
+            } else {
+                notes.push(format!(
+                    "Dev container image {image} not used: container images not supported by \
+                     this sandbox provider."
+                ));
             }
+        }
 
-            let blob_data: serde_json::Value = match blob_resp.json().await {
-                Ok(v) => v,
-                Err(e) => {
-                    tracing::warn!("github_read_repo: skip {path} — parse error: {e}");
-                    skipped += 1;
-                    continue;
-                }
-            };
-
-            let encoded = match blob_data["content"].as_str() {
-                Some(c) => c.replace('\n', ""),
-                None => {
-                    skipped += 1;
-                    continue;
-                }
-            };
-
-            let bytes = match base64::engine::general_purpose::STANDARD.decode(&encoded) {
-                Ok(b) => b,
-                Err(e) => {
-                    tracing::warn!("github_read_repo: skip {path} — base64 decode error: {e}");
-                    skipped += 1;
-                    continue;
-                }
-            };
-
-            let content = match String::from_utf8(bytes) {
-                Ok(s) => s,
-                Err(_) => {
-                    tracing::warn!("github_read_repo: skip {path} — not valid UTF-8 (binary)");
+        // 5. Fetch each blob and write to sandbox
+        for (path, sha) in &blobs {
+            let content = match fetch_blob(&http, token, &owner, &repo, sha).await {
+                Ok(content) => content,
+                Err(reason) => {
+                    tracing::warn!("github_read_repo: skip {path} — {reason}");
                     skipped += 1;
                     continue;
                 }
@@ -325,13 +399,39 @@ impl Tool for GitHubReadRepoTool {
             }
         }
 
+        // 6. Run the dev container's setup in the project
+        if let Some(container) = &devcontainer {
+            let timeout_ms = self
+                .config
+                .sandbox
+                .max_command_timeout_secs
+                .saturating_mul(1000);
+            let report = devcontainer::setup(
+                self.client.as_ref(),
+                self.guardrails.as_deref(),
+                &workdir,
+                container,
+                timeout_ms,
+            )
+            .await;
+            if !report.is_empty() {
+                notes.push(report);
+            }
+        }
+        let notes = if notes.is_empty() {
+            String::new()
+        } else {
+            format!("\n\nDev container:\n{}", notes.join("\n"))
+        };
+
         Ok(ToolResult {
             success: true,
             output: format!(
                 "Read repo '{owner}/{repo}' (branch: {branch}) into sandbox '{workdir}/'.\n\
-                 Files found: {total} | Written: {written} | Skipped: {skipped}"
+                 Files found: {total} | Written: {written} | Skipped: {skipped}{notes}"
             ),
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
@@ -339,6 +439,39 @@ impl Tool for GitHubReadRepoTool {
 
 // ── Helpers ───────────────────────────────────────────────────────────────────
 
+/// Text content of blob `sha`, or why it was skipped.
+async fn fetch_blob(
+    http: &reqwest::Client,
+    token: &str,
+    owner: &str,
+    repo: &str,
+    sha: &str,
+) -> Result<String, String> {
+    let blob_url = format!("{GITHUB_API_BASE}/repos/{owner}/{repo}/git/blobs/{sha}");
+    let blob_resp = http
+        .get(&blob_url)
+        .header("Authorization", format!("Bearer {token}"))
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await
+        .map_err(|e| format!("fetch error: {e}"))?;
+    if !blob_resp.status().is_success() {
+        return Err(format!("blob API returned {}", blob_resp.status()));
+    }
+    let blob_data: serde_json::Value = blob_resp
+        .json()
+        .await
+        .map_err(|e| format!("parse error: {e}"))?;
+    let encoded = blob_data["content"]
+        .as_str()
+        .ok_or("blob has no content")?
+        .replace('\n', "");
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(&encoded)
+        .map_err(|e| format!("base64 decode error: {e}"))?;
+    String::from_utf8(bytes).map_err(|_| "not valid UTF-8 (binary)".to_string())
+}
+
 /// Returns true if the file at `path` should be skipped.
 fn should_skip(path: &str) -> bool {
     // Skip known large/binary directories
@@ -0,0 +1,226 @@
+//! OAuth scopes of the GitHub connector token.
+//!
+//! After connecting, the token's scopes are read from the `X-OAuth-Scopes`
+//! header GitHub sends with every API response and stored next to the
+//! token. GitHub tools check the scopes they need against the stored ones
+//! before calling the API, so a token without a scope fails with a
+//! "reconnect and grant X" message instead of the 404 GitHub answers
+//! requests it doesn't authorize with. Fine-grained and GitHub App tokens
+//! don't report scopes; for them GitHub has the final say.
+
+use crate::store;
+use crate::store::tokens::GitHubToken;
+use rusqlite::Connection;
+
+/// Full access to public and private repositories.
+pub const REPO: &str = "repo";
+
+/// Issues, pull requests and pushes on public repositories only.
+pub const PUBLIC_REPO: &str = "public_repo";
+
+/// Creating gists.
+pub const GIST: &str = "gist";
+
+/// Listing the user's organizations.
+pub const READ_ORG: &str = "read:org";
+
+/// Granting organization teams access to repositories.
+pub const WRITE_ORG: &str = "write:org";
+
+/// Reading the user's profile.
+pub const READ_USER: &str = "read:user";
+
+/// Reading the user's email addresses.
+pub const USER_EMAIL: &str = "user:email";
+
+/// Scopes the connector asks for, with what each is used for. A token with
+/// fewer works for the tools whose scopes it has: `public_repo` instead of
+/// `repo` limits ZeroBuild to public repositories.
+pub const REQUESTED: &[(&str, &str)] = &[
+    (
+        REPO,
+        "deploy to and manage issues and pull requests of private repositories",
+    ),
+    (GIST, "share logs and snippets with github_create_gist"),
+    (READ_USER, "read your GitHub username"),
+    (USER_EMAIL, "read your email address"),
+    (
+        WRITE_ORG,
+        "list organizations and grant teams access to deployed repositories",
+    ),
+];
+
+/// `REQUESTED` as the comma-separated `scope` of an OAuth authorize URL.
+pub fn requested_scopes() -> String {
+    REQUESTED
+        .iter()
+        .map(|(scope, _)| *scope)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Scopes that include `scope` besides itself.
+fn covering_scopes(scope: &str) -> &'static [&'static str] {
+    match scope {
+        PUBLIC_REPO => &[REPO],
+        READ_ORG => &["admin:org", WRITE_ORG],
+        WRITE_ORG => &["admin:org"],
+        READ_USER | USER_EMAIL => &["user"],
+        _ => &[],
+    }
+}
+
+/// Whether `granted` includes `needed`, directly or through a broader
+/// scope (`repo` covers `public_repo`, `admin:org` covers `write:org`,
+/// which covers `read:org`).
+pub fn has_scope(granted: &[String], needed: &str) -> bool {
+    granted
+        .iter()
+        .any(|scope| scope == needed || covering_scopes(needed).contains(&scope.as_str()))
+}
+
+/// Scopes among `needed` that `granted` lacks.
+pub fn missing<'a>(granted: &[String], needed: &[&'a str]) -> Vec<&'a str> {
+    needed
+        .iter()
+        .copied()
+        .filter(|scope| !has_scope(granted, scope))
+        .collect()
+}
+
+/// Error for a token without `scopes`, with how to fix it.
+pub fn missing_scope_error(scopes: &[&str]) -> String {
+    let list = scopes
+        .iter()
+        .map(|scope| format!("'{scope}'"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        "The GitHub token is missing the {list} scope this needs, so GitHub would reject the \
+         request. Reconnect GitHub via github_connect and grant {list} (a personal access \
+         token needs it added in GitHub settings). For organization repositories, also check \
+         that the organization allows ZeroBuild under Settings → Third-party access."
+    )
+}
+
+/// Check `tok` for the scopes in `needed`. Tokens whose scopes aren't known
+/// pass.
+pub fn check(tok: &GitHubToken, needed: &[&str]) -> Result<(), String> {
+    match &tok.scopes {
+        Some(granted) => {
+            let lacking = missing(granted, needed);
+            if lacking.is_empty() {
+                Ok(())
+            } else {
+                Err(missing_scope_error(&lacking))
+            }
+        }
+        None => Ok(()),
+    }
+}
+
+/// `X-OAuth-Scopes` value as a list: `"repo, read:org"`.
+pub fn parse_scopes(header: &str) -> Vec<String> {
+    header
+        .split(',')
+        .map(str::trim)
+        .filter(|scope| !scope.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Scopes granted to `token`; `None` when GitHub doesn't report them.
+pub async fn fetch_scopes(
+    client: &reqwest::Client,
+    token: &str,
+) -> anyhow::Result<Option<Vec<String>>> {
+    let resp = client
+        .get("https://api.github.com/user")
+        .header("Authorization", format!("Bearer {token}"))
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await?;
+    if !resp.status().is_success() {
+        anyhow::bail!("GitHub returned {}", resp.status());
+    }
+    Ok(resp
+        .headers()
+        .get("x-oauth-scopes")
+        .and_then(|value| value.to_str().ok())
+        .map(parse_scopes))
+}
+
+/// Store the scopes GitHub reported for the stored token and return the
+/// requested scopes it lacks.
+pub fn record(conn: &Connection, scopes: Option<&[String]>) -> anyhow::Result<Vec<&'static str>> {
+    store::tokens::save_github_scopes(conn, scopes)?;
+    Ok(scopes.map_or_else(Vec::new, missing_requested))
+}
+
+/// Requested scopes `granted` lacks.
+pub fn missing_requested(granted: &[String]) -> Vec<&'static str> {
+    let requested: Vec<&'static str> = REQUESTED.iter().map(|(scope, _)| *scope).collect();
+    missing(granted, &requested)
+}
+
+/// What a token lacking `scopes` can't do, one line per scope.
+pub fn describe_missing(scopes: &[&str]) -> String {
+    scopes
+        .iter()
+        .map(|scope| {
+            let purpose = REQUESTED
+                .iter()
+                .find(|(requested, _)| requested == scope)
+                .map_or("", |(_, purpose)| purpose);
+            format!("• {scope}: needed to {purpose}")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(scopes: Option<&str>) -> GitHubToken {
+        GitHubToken {
+            token: "gho_test".into(),
+            username: Some("alice".into()),
+            scopes: scopes.map(parse_scopes),
+        }
+    }
+
+    #[test]
+    fn broader_scopes_cover_narrower_ones() {
+        let granted = parse_scopes("repo, admin:org ,gist");
+        assert_eq!(granted, ["repo", "admin:org", "gist"]);
+        assert!(has_scope(&granted, PUBLIC_REPO));
+        assert!(has_scope(&granted, READ_ORG));
+        assert!(has_scope(&granted, WRITE_ORG));
+
+        let granted = parse_scopes("public_repo, read:org");
+        assert_eq!(
+            missing(&granted, &[PUBLIC_REPO, READ_ORG]),
+            Vec::<&str>::new()
+        );
+        assert_eq!(missing(&granted, &[REPO, WRITE_ORG]), [REPO, WRITE_ORG]);
+    }
+
+    #[test]
+    fn check_names_the_missing_scopes() {
+        assert!(check(&token(Some("repo")), &[PUBLIC_REPO]).is_ok());
+        assert!(check(&token(None), &[GIST]).is_ok());
+        let err = check(&token(Some("repo, read:user")), &[GIST]).unwrap_err();
+        assert!(err.contains("missing the 'gist' scope"));
+        assert!(err.contains("github_connect"));
+    }
+
+    #[test]
+    fn requests_every_scope_the_tools_need() {
+        let requested = parse_scopes(&requested_scopes());
+        for needed in [REPO, PUBLIC_REPO, GIST, READ_ORG, WRITE_ORG] {
+            assert!(has_scope(&requested, needed), "{needed}");
+        }
+        assert!(describe_missing(&[GIST]).contains("github_create_gist"));
+    }
+}
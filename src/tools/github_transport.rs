@@ -0,0 +1,183 @@
+//! HTTP transport behind the GitHub REST and GraphQL calls of
+//! [`super::github_ops`].
+//!
+//! Requests go out through [`HttpTransport`] unless a future runs inside
+//! [`with_transport`], which swaps in another [`GitHubTransport`] for every
+//! GitHub call that future makes. Tests use `MockGitHubTransport` (available
+//! to downstream crates with the `test-utils` feature) to answer with canned
+//! responses and inspect what the tools sent.
+
+use async_trait::async_trait;
+use reqwest::{Method, StatusCode};
+use std::future::Future;
+use std::sync::Arc;
+
+pub const GITHUB_API_BASE: &str = "https://api.github.com";
+
+/// A request to the GitHub API.
+#[derive(Debug, Clone)]
+pub struct GitHubRequest {
+    pub method: Method,
+    /// Absolute URL, starting with [`GITHUB_API_BASE`].
+    pub url: String,
+    pub token: String,
+    /// `Accept` header value.
+    pub accept: &'static str,
+    pub body: Option<serde_json::Value>,
+}
+
+/// Status and body of a GitHub API response.
+#[derive(Debug, Clone)]
+pub struct GitHubResponse {
+    pub status: StatusCode,
+    pub body: String,
+}
+
+/// Sends GitHub API requests.
+#[async_trait]
+pub trait GitHubTransport: Send + Sync {
+    /// Send `request`. Errors are for requests that got no response; HTTP
+    /// error statuses come back as a [`GitHubResponse`].
+    async fn send(&self, request: GitHubRequest) -> anyhow::Result<GitHubResponse>;
+}
+
+/// Transport that sends requests to GitHub over HTTPS.
+pub struct HttpTransport;
+
+#[async_trait]
+impl GitHubTransport for HttpTransport {
+    async fn send(&self, request: GitHubRequest) -> anyhow::Result<GitHubResponse> {
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            .user_agent("ZeroBuild/0.1")
+            .build()
+            .map_err(|e| anyhow::anyhow!("Failed to build HTTP client: {e}"))?;
+        let mut builder = client
+            .request(request.method, &request.url)
+            .header("Authorization", format!("Bearer {}", request.token))
+            .header("Accept", request.accept);
+        if let Some(body) = &request.body {
+            builder = builder.json(body);
+        }
+        let resp = builder
+            .send()
+            .await
+            .map_err(|e| anyhow::anyhow!("GitHub API request failed: {e}"))?;
+        let status = resp.status();
+        let body = resp
+            .text()
+            .await
+            .unwrap_or_else(|_| "<unreadable>".to_string());
+        Ok(GitHubResponse { status, body })
+    }
+}
+
+tokio::task_local! {
+    static TRANSPORT: Arc<dyn GitHubTransport>;
+}
+
+/// Run `future` with its GitHub calls sent through `transport`.
+pub async fn with_transport<F: Future>(
+    transport: Arc<dyn GitHubTransport>,
+    future: F,
+) -> F::Output {
+    TRANSPORT.scope(transport, future).await
+}
+
+/// Transport for the current task: the one set by [`with_transport`], else
+/// [`HttpTransport`].
+pub fn current() -> Arc<dyn GitHubTransport> {
+    TRANSPORT
+        .try_with(Arc::clone)
+        .unwrap_or_else(|_| Arc::new(HttpTransport))
+}
+
+#[cfg(any(test, feature = "test-utils"))]
+#[allow(unused_imports)]
+pub use mock::MockGitHubTransport;
+
+#[cfg(any(test, feature = "test-utils"))]
+mod mock {
+    use super::{GitHubRequest, GitHubResponse, GitHubTransport, GITHUB_API_BASE};
+    use async_trait::async_trait;
+    use parking_lot::Mutex;
+    use reqwest::{Method, StatusCode};
+
+    struct Route {
+        method: Method,
+        path: String,
+        status: StatusCode,
+        body: String,
+    }
+
+    /// Transport answering with canned responses and recording every request.
+    ///
+    /// Routes match on method and the path after [`GITHUB_API_BASE`], with or
+    /// without the query string; later routes win. Unrouted requests get
+    /// `404 Not Found`.
+    #[derive(Default)]
+    pub struct MockGitHubTransport {
+        routes: Mutex<Vec<Route>>,
+        requests: Mutex<Vec<GitHubRequest>>,
+    }
+
+    impl MockGitHubTransport {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Answer `method` requests to `path` (e.g. `/repos/o/r/pulls`) with
+        /// `status` and the JSON `body`.
+        pub fn on(&self, method: Method, path: &str, status: u16, body: serde_json::Value) {
+            self.on_text(method, path, status, &body.to_string());
+        }
+
+        /// Like [`on`](Self::on), with a raw body.
+        pub fn on_text(&self, method: Method, path: &str, status: u16, body: &str) {
+            self.routes.lock().push(Route {
+                method,
+                path: path.to_string(),
+                status: StatusCode::from_u16(status).expect("valid HTTP status"),
+                body: body.to_string(),
+            });
+        }
+
+        /// Requests sent so far, oldest first.
+        pub fn requests(&self) -> Vec<GitHubRequest> {
+            self.requests.lock().clone()
+        }
+    }
+
+    #[async_trait]
+    impl GitHubTransport for MockGitHubTransport {
+        async fn send(&self, request: GitHubRequest) -> anyhow::Result<GitHubResponse> {
+            let path = request
+                .url
+                .strip_prefix(GITHUB_API_BASE)
+                .unwrap_or(&request.url)
+                .to_string();
+            let without_query = path.split('?').next().unwrap_or_default();
+            let response = self
+                .routes
+                .lock()
+                .iter()
+                .rev()
+                .find(|route| {
+                    route.method == request.method
+                        && (route.path == path || route.path == without_query)
+                })
+                .map_or_else(
+                    || GitHubResponse {
+                        status: StatusCode::NOT_FOUND,
+                        body: r#"{"message":"Not Found"}"#.to_string(),
+                    },
+                    |route| GitHubResponse {
+                        status: route.status,
+                        body: route.body.clone(),
+                    },
+                );
+            self.requests.lock().push(request);
+            Ok(response)
+        }
+    }
+}
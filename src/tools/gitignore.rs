@@ -0,0 +1,423 @@
+//! `.gitignore` handling for deployed repos.
+//!
+//! Before `github_push` uploads a project it adds a `.gitignore` for the
+//! project's stack when the project has none, drops every file an ignore
+//! file excludes (root and nested `.gitignore`s, with `!` negation and
+//! directory rules), and refuses to upload files that look like secrets
+//! (`.env`, private keys, credential files) even when no ignore rule
+//! excludes them.
+
+use std::collections::BTreeMap;
+
+/// Ignore file name.
+pub const GITIGNORE: &str = ".gitignore";
+
+/// Entries every generated `.gitignore` starts with.
+const COMMON: &str = "\
+# Environment and secrets
+.env
+.env.*
+!.env.example
+*.pem
+*.key
+
+# Editors and OS
+.vscode/
+.idea/
+*.swp
+*~
+.DS_Store
+Thumbs.db
+
+# Logs
+*.log
+";
+
+const NODE: &str = "\
+# Node
+node_modules/
+dist/
+build/
+.next/
+.nuxt/
+.svelte-kit/
+.turbo/
+.vercel/
+coverage/
+npm-debug.log*
+yarn-error.log*
+";
+
+const RUST: &str = "\
+# Rust
+target/
+";
+
+const PYTHON: &str = "\
+# Python
+__pycache__/
+*.py[cod]
+.venv/
+venv/
+.pytest_cache/
+.mypy_cache/
+*.egg-info/
+dist/
+build/
+";
+
+const GO: &str = "\
+# Go
+bin/
+*.exe
+*.test
+";
+
+/// Stacks a generated `.gitignore` covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stack {
+    Node,
+    Rust,
+    Python,
+    Go,
+}
+
+impl Stack {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Node => "node",
+            Self::Rust => "rust",
+            Self::Python => "python",
+            Self::Go => "go",
+        }
+    }
+
+    fn entries(self) -> &'static str {
+        match self {
+            Self::Node => NODE,
+            Self::Rust => RUST,
+            Self::Python => PYTHON,
+            Self::Go => GO,
+        }
+    }
+}
+
+/// Stacks of a project, from its root manifests and the snapshot's
+/// project type.
+pub fn detect_stacks(files: &BTreeMap<String, String>, project_type: Option<&str>) -> Vec<Stack> {
+    let has = |name: &str| files.contains_key(name);
+    let project_type = project_type.unwrap_or_default().to_ascii_lowercase();
+    let mut stacks = Vec::new();
+    if has("package.json")
+        || ["next", "react", "vue", "svelte", "node", "vite"]
+            .iter()
+            .any(|kind| project_type.contains(kind))
+    {
+        stacks.push(Stack::Node);
+    }
+    if has("Cargo.toml") || project_type.contains("rust") {
+        stacks.push(Stack::Rust);
+    }
+    if has("pyproject.toml")
+        || has("requirements.txt")
+        || has("setup.py")
+        || project_type.contains("python")
+    {
+        stacks.push(Stack::Python);
+    }
+    if has("go.mod") || project_type == "go" {
+        stacks.push(Stack::Go);
+    }
+    stacks
+}
+
+/// `.gitignore` contents for `stacks`.
+pub fn generate(stacks: &[Stack]) -> String {
+    let mut out = COMMON.to_string();
+    for stack in stacks {
+        out.push('\n');
+        out.push_str(stack.entries());
+    }
+    out
+}
+
+/// One pattern line of an ignore file.
+#[derive(Debug, Clone)]
+struct Rule {
+    pattern: glob::Pattern,
+    negated: bool,
+    /// Trailing `/`: matches directories only.
+    dir_only: bool,
+    /// Contains a `/` before the end: relative to the ignore file's
+    /// directory rather than matching a name at any depth.
+    anchored: bool,
+}
+
+impl Rule {
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line.strip_prefix('\\').unwrap_or(line)),
+        };
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let anchored = line.contains('/');
+        let line = line.trim_start_matches('/');
+        if line.is_empty() {
+            return None;
+        }
+        Some(Self {
+            pattern: glob::Pattern::new(line).ok()?,
+            negated,
+            dir_only,
+            anchored,
+        })
+    }
+
+    /// Whether the rule matches `candidate` (a path relative to the ignore
+    /// file's directory) whose last component is `name`.
+    fn matches(&self, candidate: &str, name: &str) -> bool {
+        let options = glob::MatchOptions {
+            require_literal_separator: true,
+            ..glob::MatchOptions::default()
+        };
+        if self.anchored {
+            self.pattern.matches_with(candidate, options)
+        } else {
+            self.pattern.matches_with(name, options)
+        }
+    }
+}
+
+/// The ignore rules of a project: every `.gitignore` in it, each applying
+/// to its own directory.
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreRules {
+    /// `(directory, rules)`; the root directory is `""`. Parents come
+    /// before their subdirectories.
+    scopes: Vec<(String, Vec<Rule>)>,
+}
+
+impl IgnoreRules {
+    /// Rules from the `.gitignore` files among `files`.
+    pub fn from_files(files: &BTreeMap<String, String>) -> Self {
+        let mut scopes: Vec<(String, Vec<Rule>)> = files
+            .iter()
+            .filter_map(|(path, content)| {
+                let dir = match path.rsplit_once('/') {
+                    Some((dir, GITIGNORE)) => dir,
+                    None if path == GITIGNORE => "",
+                    _ => return None,
+                };
+                Some((
+                    dir.to_string(),
+                    content.lines().filter_map(Rule::parse).collect(),
+                ))
+            })
+            .collect();
+        scopes.sort_by_key(|(dir, _)| dir.matches('/').count() + usize::from(!dir.is_empty()));
+        Self { scopes }
+    }
+
+    /// Whether `path` (a file) is ignored. A file is ignored when it or one
+    /// of its directories is; like git, a file inside an ignored directory
+    /// can't be re-included.
+    pub fn is_ignored(&self, path: &str) -> bool {
+        let parts: Vec<&str> = path.split('/').collect();
+        (1..=parts.len()).any(|depth| {
+            let is_dir = depth < parts.len();
+            self.matches(&parts[..depth], is_dir)
+        })
+    }
+
+    /// Last-match-wins verdict for one path, deeper ignore files overriding
+    /// shallower ones.
+    fn matches(&self, parts: &[&str], is_dir: bool) -> bool {
+        let path = parts.join("/");
+        let name = parts.last().copied().unwrap_or_default();
+        let mut ignored = false;
+        for (dir, rules) in &self.scopes {
+            let candidate = if dir.is_empty() {
+                path.as_str()
+            } else {
+                match path
+                    .strip_prefix(dir.as_str())
+                    .and_then(|rest| rest.strip_prefix('/'))
+                {
+                    Some(rest) => rest,
+                    None => continue,
+                }
+            };
+            for rule in rules {
+                if (!rule.dir_only || is_dir) && rule.matches(candidate, name) {
+                    ignored = !rule.negated;
+                }
+            }
+        }
+        ignored
+    }
+}
+
+/// Reason `path` must never be pushed, if it looks like a secret.
+pub fn secret_reason(path: &str) -> Option<&'static str> {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    let lower = name.to_ascii_lowercase();
+    if lower == ".env" || lower.starts_with(".env.") {
+        let template = [".example", ".sample", ".template", ".dist"]
+            .iter()
+            .any(|suffix| lower.ends_with(suffix));
+        return (!template).then_some("environment file");
+    }
+    if lower.ends_with(".pem")
+        || lower.ends_with(".key")
+        || lower.ends_with(".p12")
+        || lower.ends_with(".pfx")
+        || lower.ends_with(".keystore")
+        || lower.ends_with(".jks")
+        || matches!(
+            lower.as_str(),
+            "id_rsa" | "id_dsa" | "id_ecdsa" | "id_ed25519"
+        )
+    {
+        return Some("private key or certificate store");
+    }
+    if matches!(
+        lower.as_str(),
+        "credentials.json" | "client_secret.json" | ".netrc" | ".pgpass" | ".htpasswd"
+    ) || path.ends_with(".aws/credentials")
+        || (lower.starts_with("service-account") && lower.ends_with(".json"))
+    {
+        return Some("credentials file");
+    }
+    None
+}
+
+/// What [`apply`] changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GitignoreReport {
+    /// Stacks of the generated `.gitignore`; `None` when the project had one.
+    pub generated: Option<Vec<&'static str>>,
+    /// Files left out because an ignore rule excludes them.
+    pub ignored: Vec<String>,
+    /// Files that look like secrets, with the reason. The deploy is refused
+    /// when this is not empty.
+    pub secrets: Vec<(String, &'static str)>,
+}
+
+/// Add a `.gitignore` when the project has none, then remove the files it
+/// (and any nested ignore file) excludes from `files`, and list remaining
+/// files that look like secrets.
+pub fn apply(files: &mut BTreeMap<String, String>, project_type: Option<&str>) -> GitignoreReport {
+    let mut report = GitignoreReport::default();
+    if !files.contains_key(GITIGNORE) {
+        let stacks = detect_stacks(files, project_type);
+        files.insert(GITIGNORE.to_string(), generate(&stacks));
+        report.generated = Some(stacks.iter().map(|stack| stack.name()).collect());
+    }
+
+    let rules = IgnoreRules::from_files(files);
+    files.retain(|path, _| {
+        let ignored = rules.is_ignored(path);
+        if ignored {
+            report.ignored.push(path.clone());
+        }
+        !ignored
+    });
+    report.secrets = files
+        .keys()
+        .filter_map(|path| secret_reason(path).map(|reason| (path.clone(), reason)))
+        .collect();
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+        entries
+            .iter()
+            .map(|(path, content)| ((*path).to_string(), (*content).to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn generates_for_the_detected_stack() {
+        let project = files(&[("package.json", "{}"), ("src/index.ts", "")]);
+        let stacks = detect_stacks(&project, None);
+        assert_eq!(stacks, [Stack::Node]);
+        let text = generate(&stacks);
+        assert!(text.contains("\n.env\n"));
+        assert!(text.contains("node_modules/"));
+        assert!(!text.contains("target/"));
+        assert_eq!(detect_stacks(&BTreeMap::new(), Some("rust")), [Stack::Rust]);
+    }
+
+    #[test]
+    fn honors_nested_rules_and_negation() {
+        let project = files(&[
+            (".gitignore", "*.log\n/build\ncache/\n!keep.log\n"),
+            ("web/.gitignore", "*.tmp\n!important.log\n"),
+        ]);
+        let rules = IgnoreRules::from_files(&project);
+        assert!(rules.is_ignored("debug.log"));
+        assert!(!rules.is_ignored("keep.log"));
+        assert!(rules.is_ignored("build/out.js"));
+        assert!(!rules.is_ignored("web/build/out.js"));
+        assert!(rules.is_ignored("web/cache/a.json"));
+        assert!(!rules.is_ignored("cache"));
+        assert!(rules.is_ignored("web/a.tmp"));
+        assert!(!rules.is_ignored("a.tmp"));
+        assert!(!rules.is_ignored("web/important.log"));
+        assert!(rules.is_ignored("web/other.log"));
+    }
+
+    #[test]
+    fn apply_drops_ignored_files_and_reports_secrets() {
+        let mut project = files(&[
+            ("package.json", "{}"),
+            (".env", "KEY=1"),
+            (".env.example", "KEY="),
+            ("node_modules/x/index.js", ""),
+            ("src/index.js", ""),
+        ]);
+        let report = apply(&mut project, None);
+        assert_eq!(report.generated, Some(vec!["node"]));
+        assert_eq!(report.ignored, [".env", "node_modules/x/index.js"]);
+        assert!(report.secrets.is_empty());
+        assert!(project.contains_key(".gitignore"));
+        assert!(project.contains_key(".env.example"));
+
+        let mut project = files(&[(".gitignore", "dist/\n"), ("config/.env.production", "")]);
+        let report = apply(&mut project, None);
+        assert_eq!(report.generated, None);
+        assert_eq!(
+            report.secrets,
+            [("config/.env.production".to_string(), "environment file")]
+        );
+    }
+
+    #[test]
+    fn recognizes_secret_files() {
+        assert_eq!(
+            secret_reason("deploy/id_rsa"),
+            Some("private key or certificate store")
+        );
+        assert_eq!(
+            secret_reason("certs/server.pem"),
+            Some("private key or certificate store")
+        );
+        assert_eq!(
+            secret_reason("home/.aws/credentials"),
+            Some("credentials file")
+        );
+        assert_eq!(secret_reason(".env.sample"), None);
+        assert_eq!(secret_reason("src/keys.ts"), None);
+    }
+}
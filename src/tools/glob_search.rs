@@ -54,6 +54,7 @@ impl Tool for GlobSearchTool {
                 success: false,
                 output: String::new(),
                 error: Some("Rate limit exceeded: too many actions in the last hour".into()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -64,6 +65,7 @@ impl Tool for GlobSearchTool {
                 success: false,
                 output: String::new(),
                 error: Some("Absolute paths are not allowed. Use a relative glob pattern.".into()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -74,6 +76,7 @@ impl Tool for GlobSearchTool {
                 success: false,
                 output: String::new(),
                 error: Some("Path traversal ('..') is not allowed in glob patterns.".into()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -84,6 +87,7 @@ impl Tool for GlobSearchTool {
                 success: false,
                 output: String::new(),
                 error: Some("Rate limit exceeded: action budget exhausted".into()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -99,6 +103,7 @@ impl Tool for GlobSearchTool {
                     success: false,
                     output: String::new(),
                     error: Some(format!("Invalid glob pattern: {e}")),
+                    error_code: None,
                     error_hint: None,
                 });
             }
@@ -111,6 +116,7 @@ impl Tool for GlobSearchTool {
                     success: false,
                     output: String::new(),
                     error: Some(format!("Cannot resolve workspace directory: {e}")),
+                    error_code: None,
                     error_hint: None,
                 });
             }
@@ -172,6 +178,7 @@ impl Tool for GlobSearchTool {
             success: true,
             output,
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
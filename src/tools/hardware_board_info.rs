@@ -103,6 +103,7 @@ impl Tool for HardwareBoardInfoTool {
                     "No peripherals configured. Add boards to config.toml [peripherals.boards]."
                         .into(),
                 ),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -122,6 +123,7 @@ impl Tool for HardwareBoardInfoTool {
                         success: true,
                         output: info,
                         error: None,
+                        error_code: None,
                         error_hint: None,
                     });
                 }
@@ -153,6 +155,7 @@ impl Tool for HardwareBoardInfoTool {
             success: true,
             output,
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
@@ -89,6 +89,7 @@ impl Tool for HardwareMemoryMapTool {
                     "No peripherals configured. Add boards to config.toml [peripherals.boards]."
                         .into(),
                 ),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -140,6 +141,7 @@ impl Tool for HardwareMemoryMapTool {
             success: true,
             output,
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
@@ -68,6 +68,7 @@ impl Tool for HardwareMemoryReadTool {
                     "No peripherals configured. Add nucleo-f401re to config.toml [peripherals.boards]."
                         .into(),
                 ),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -88,6 +89,7 @@ impl Tool for HardwareMemoryReadTool {
                     "Memory read only supports nucleo-f401re, nucleo-f411re. Got: {}",
                     board
                 )),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -111,6 +113,7 @@ impl Tool for HardwareMemoryReadTool {
                         success: true,
                         output,
                         error: None,
+                        error_code: None,
                         error_hint: None,
                     });
                 }
@@ -122,6 +125,7 @@ impl Tool for HardwareMemoryReadTool {
                             "probe-rs read failed: {}. Ensure Nucleo is connected via USB and built with --features probe.",
                             e
                         )),
+                        error_code: None,
                         error_hint: None,
                     });
                 }
@@ -137,6 +141,7 @@ impl Tool for HardwareMemoryReadTool {
                     "Memory read requires probe feature. Build with: cargo build --features hardware,probe"
                         .into(),
                 ),
+                error_code: None,
                 error_hint: None,
             })
         }
@@ -0,0 +1,197 @@
+use super::traits::{Tool, ToolResult};
+use crate::memory::Memory;
+use crate::store::history::{search_history, HistoryQuery};
+use async_trait::async_trait;
+use serde_json::json;
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Exact-term search over past conversation messages and memory entries.
+///
+/// Inside a channel conversation only the current sender's messages are
+/// searched (see [`crate::memory::user_scoped::with_memory_user`]).
+pub struct HistorySearchTool {
+    db_path: PathBuf,
+    memory: Arc<dyn Memory>,
+}
+
+impl HistorySearchTool {
+    pub fn new(db_path: impl Into<PathBuf>, memory: Arc<dyn Memory>) -> Self {
+        Self {
+            db_path: db_path.into(),
+            memory,
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for HistorySearchTool {
+    fn name(&self) -> &str {
+        "history_search"
+    }
+
+    fn description(&self) -> &str {
+        "Full-text search over past conversation messages and memory. Every term must match, so use distinctive words (error codes, file names, package names). Optionally bound by date to find e.g. 'the error we hit last Tuesday'."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "description": "Terms that must all appear in the message"
+                },
+                "since": {
+                    "type": "string",
+                    "description": "Only messages on or after this date (YYYY-MM-DD or RFC 3339)"
+                },
+                "until": {
+                    "type": "string",
+                    "description": "Only messages before this date (YYYY-MM-DD or RFC 3339, exclusive)"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Max results per source (default: 10)"
+                }
+            },
+            "required": ["query"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let query = args
+            .get("query")
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|q| !q.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'query' parameter"))?;
+        let since = args.get("since").and_then(|v| v.as_str());
+        let until = args.get("until").and_then(|v| v.as_str());
+        #[allow(clippy::cast_possible_truncation)]
+        let limit = args
+            .get("limit")
+            .and_then(serde_json::Value::as_u64)
+            .map_or(10, |v| v as usize);
+
+        let conversation = crate::memory::user_scoped::current_memory_user();
+        let messages = crate::store::init_db(&self.db_path).and_then(|conn| {
+            search_history(
+                &conn,
+                query,
+                &HistoryQuery {
+                    conversation: conversation.as_deref(),
+                    since,
+                    until,
+                    limit,
+                },
+            )
+        });
+        let messages = match messages {
+            Ok(messages) => messages,
+            Err(e) => {
+                return Ok(ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some(format!("History search failed: {e}")),
+                    error_code: None,
+                    error_hint: None,
+                })
+            }
+        };
+        // Memory entries carry no date filter; skip them for bounded searches.
+        let memories = if since.is_none() && until.is_none() {
+            self.memory
+                .recall(query, limit, None)
+                .await
+                .unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+
+        if messages.is_empty() && memories.is_empty() {
+            return Ok(ToolResult {
+                success: true,
+                output: "No messages or memories found matching that query.".into(),
+                error: None,
+                error_code: None,
+                error_hint: None,
+            });
+        }
+
+        let mut output = String::new();
+        if !messages.is_empty() {
+            let _ = writeln!(output, "Found {} messages:", messages.len());
+            for hit in &messages {
+                let _ = writeln!(output, "- {} {}: {}", hit.created_at, hit.role, hit.snippet);
+            }
+        }
+        if !memories.is_empty() {
+            let _ = writeln!(output, "Found {} memories:", memories.len());
+            for entry in &memories {
+                let _ = writeln!(
+                    output,
+                    "- [{}] {}: {}",
+                    entry.category, entry.key, entry.content
+                );
+            }
+        }
+        Ok(ToolResult {
+            success: true,
+            output,
+            error: None,
+            error_code: None,
+            error_hint: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::user_scoped::with_memory_user;
+    use crate::memory::{MemoryCategory, SqliteMemory};
+    use crate::store::history::record_message;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn finds_messages_for_current_user_and_memories() {
+        let tmp = TempDir::new().unwrap();
+        let db_path = tmp.path().join("zerobuild.db");
+        let conn = crate::store::init_db(&db_path).unwrap();
+        record_message(&conn, "slack_U1", "user", "vite failed: EADDRINUSE :5173").unwrap();
+        record_message(&conn, "slack_U2", "user", "EADDRINUSE on port 3000").unwrap();
+
+        let mem: Arc<dyn Memory> = Arc::new(SqliteMemory::new(tmp.path()).unwrap());
+        mem.store(
+            "port_fix",
+            "EADDRINUSE fixed by killing the stale dev server",
+            MemoryCategory::Core,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let tool = HistorySearchTool::new(&db_path, mem);
+        let result = with_memory_user(
+            "slack_U1".into(),
+            tool.execute(json!({"query": "EADDRINUSE"})),
+        )
+        .await
+        .unwrap();
+        assert!(result.success);
+        assert!(result.output.contains("Found 1 messages"));
+        assert!(result.output.contains(":5173"));
+        assert!(!result.output.contains("3000"));
+        assert!(result.output.contains("port_fix"));
+    }
+
+    #[tokio::test]
+    async fn missing_query_is_an_error() {
+        let tmp = TempDir::new().unwrap();
+        let mem: Arc<dyn Memory> = Arc::new(SqliteMemory::new(tmp.path()).unwrap());
+        let tool = HistorySearchTool::new(tmp.path().join("zerobuild.db"), mem);
+        assert!(tool.execute(json!({"query": "  "})).await.is_err());
+    }
+}
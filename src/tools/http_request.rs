@@ -207,6 +207,7 @@ impl Tool for HttpRequestTool {
                 success: false,
                 output: String::new(),
                 error: Some("Action blocked: autonomy is read-only".into()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -216,6 +217,7 @@ impl Tool for HttpRequestTool {
                 success: false,
                 output: String::new(),
                 error: Some("Action blocked: rate limit exceeded".into()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -227,6 +229,7 @@ impl Tool for HttpRequestTool {
                     success: false,
                     output: String::new(),
                     error: Some(e.to_string()),
+                    error_code: None,
                     error_hint: None,
                 })
             }
@@ -239,6 +242,7 @@ impl Tool for HttpRequestTool {
                     success: false,
                     output: String::new(),
                     error: Some(e.to_string()),
+                    error_code: None,
                     error_hint: None,
                 })
             }
@@ -290,6 +294,7 @@ impl Tool for HttpRequestTool {
                     } else {
                         None
                     },
+                    error_code: None,
                     error_hint: None,
                 })
             }
@@ -297,6 +302,7 @@ impl Tool for HttpRequestTool {
                 success: false,
                 output: String::new(),
                 error: Some(format!("HTTP request failed: {e}")),
+                error_code: None,
                 error_hint: None,
             }),
         }
@@ -0,0 +1,375 @@
+//! Image hosting for `github_upload_image`.
+//!
+//! Images embedded in issues and pull requests are stored in one of three
+//! places (`[zerobuild.images]`):
+//!
+//! - `repo` (default): committed to `.github/assets/` of the repository the
+//!   issue or PR belongs to, so screenshots stay with the project;
+//! - `s3`: uploaded to an S3-compatible bucket, signed with AWS SigV4;
+//! - `imgur`: anonymous Imgur upload, only with a user-supplied client ID,
+//!   since it hands the image to a third party.
+
+use super::github_access::RepoAccess;
+use super::github_transport::{self, GitHubRequest, GITHUB_API_BASE};
+use crate::config::ImageS3Config;
+use crate::providers::bedrock::{derive_signing_key, hmac_sha256, sha256_hex};
+use base64::Engine as _;
+use serde_json::json;
+use std::fmt::Write;
+
+/// Repository directory images are committed to.
+pub const ASSETS_DIR: &str = ".github/assets";
+
+/// Largest image accepted (GitHub renders up to 10 MB in Markdown).
+pub const MAX_IMAGE_BYTES: usize = 10 * 1024 * 1024;
+
+/// MIME type of an image file, by extension.
+pub fn content_type(filename: &str) -> Option<&'static str> {
+    let ext = filename.rsplit_once('.')?.1.to_ascii_lowercase();
+    Some(match ext.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        _ => return None,
+    })
+}
+
+/// Decode base64 image data, with or without a `data:image/...;base64,`
+/// prefix.
+pub fn decode(image_data: &str) -> Result<Vec<u8>, String> {
+    let data = image_data
+        .split_once(";base64,")
+        .map_or(image_data, |(_, data)| data);
+    let data: String = data.chars().filter(|c| !c.is_whitespace()).collect();
+    base64::engine::general_purpose::STANDARD
+        .decode(data)
+        .map_err(|e| format!("image_data is not valid base64: {e}"))
+}
+
+/// Unique, URL-safe object name for `filename`:
+/// `20261016-153000-login-screen.png`.
+pub fn asset_name(filename: &str, now: chrono::DateTime<chrono::Utc>) -> String {
+    let base = filename.rsplit(['/', '\\']).next().unwrap_or(filename);
+    let mut name = String::new();
+    for c in base.chars() {
+        if c.is_ascii_alphanumeric() || c == '.' || c == '_' || c == '-' {
+            name.push(c.to_ascii_lowercase());
+        } else if !name.ends_with('-') {
+            name.push('-');
+        }
+    }
+    let name = name.replace("-.", ".");
+    let name = name.trim_matches(['-', '.']);
+    let name = if name.is_empty() { "image" } else { name };
+    format!("{}-{name}", now.format("%Y%m%d-%H%M%S"))
+}
+
+/// Commit `bytes` to [`ASSETS_DIR`]`/name` of `owner/repo` and return a
+/// link that renders in the repository's issues and pull requests. The
+/// commit goes through the task's GitHub transport, and only to a
+/// repository `access` admits.
+pub async fn upload_to_repo(
+    access: &RepoAccess,
+    token: &str,
+    owner: &str,
+    repo: &str,
+    branch: Option<&str>,
+    name: &str,
+    bytes: &[u8],
+) -> anyhow::Result<String> {
+    let path = format!("{ASSETS_DIR}/{name}");
+    let mut body = json!({
+        "message": format!("Add image {name}"),
+        "content": base64::engine::general_purpose::STANDARD.encode(bytes),
+    });
+    if let Some(branch) = branch {
+        body["branch"] = json!(branch);
+    }
+    access
+        .check(owner, Some(repo))
+        .map_err(anyhow::Error::msg)?;
+    let resp = github_transport::current()
+        .send(GitHubRequest {
+            method: reqwest::Method::PUT,
+            url: format!("{GITHUB_API_BASE}/repos/{owner}/{repo}/contents/{path}"),
+            token: token.to_string(),
+            accept: "application/vnd.github+json",
+            body: Some(body),
+        })
+        .await?;
+    let data: serde_json::Value = serde_json::from_str(&resp.body).unwrap_or_default();
+    if !resp.status.is_success() {
+        anyhow::bail!(
+            "GitHub returned {}: {}",
+            resp.status,
+            data["message"].as_str().unwrap_or("unknown error")
+        );
+    }
+    let html_url = data["content"]["html_url"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("GitHub response without a file URL"))?;
+    Ok(format!("{html_url}?raw=true"))
+}
+
+/// Public URL of object `key` in the configured bucket.
+pub fn s3_public_url(config: &ImageS3Config, key: &str) -> String {
+    let base = if config.public_url.trim().is_empty() {
+        format!(
+            "{}/{}",
+            config.endpoint.trim().trim_end_matches('/'),
+            config.bucket.trim()
+        )
+    } else {
+        config.public_url.trim().trim_end_matches('/').to_string()
+    };
+    format!("{base}/{key}")
+}
+
+/// Path-style object URL and SigV4-signed headers for a `method` request
+/// on `key` with `body`. Also used by the artifact store
+/// ([`crate::artifacts`]) for `GET` and `DELETE`.
+pub fn s3_request(
+    config: &ImageS3Config,
+    method: &str,
+    key: &str,
+    body: &[u8],
+    content_type: Option<&str>,
+    now: chrono::DateTime<chrono::Utc>,
+) -> anyhow::Result<(String, Vec<(String, String)>)> {
+    let secret = config
+        .secret_access_key
+        .as_deref()
+        .filter(|secret| !secret.is_empty())
+        .ok_or_else(|| anyhow::anyhow!("S3 secret_access_key is not set"))?;
+    let endpoint = reqwest::Url::parse(config.endpoint.trim())?;
+    let host = match (endpoint.host_str(), endpoint.port()) {
+        (Some(host), Some(port)) => format!("{host}:{port}"),
+        (Some(host), None) => host.to_string(),
+        (None, _) => anyhow::bail!("S3 endpoint has no host"),
+    };
+    let canonical_uri = format!(
+        "{}/{}/{key}",
+        endpoint.path().trim_end_matches('/'),
+        config.bucket.trim()
+    );
+    let url = format!("{}://{host}{canonical_uri}", endpoint.scheme());
+
+    let date_stamp = now.format("%Y%m%d").to_string();
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let payload_hash = sha256_hex(body);
+    // Sorted by name, as SigV4 requires.
+    let mut headers = Vec::new();
+    if let Some(content_type) = content_type {
+        headers.push(("content-type".to_string(), content_type.to_string()));
+    }
+    headers.extend([
+        ("host".to_string(), host),
+        ("x-amz-content-sha256".to_string(), payload_hash.clone()),
+        ("x-amz-date".to_string(), amz_date.clone()),
+    ]);
+    let mut canonical_headers = String::new();
+    for (name, value) in &headers {
+        let _ = writeln!(canonical_headers, "{name}:{value}");
+    }
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>()
+        .join(";");
+    let canonical_request = format!(
+        "{method}\n{canonical_uri}\n\n{canonical_headers}\n{signed_headers}\n{payload_hash}"
+    );
+    let region = config.region.trim();
+    let scope = format!("{date_stamp}/{region}/s3/aws4_request");
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+        sha256_hex(canonical_request.as_bytes())
+    );
+    let signing_key = derive_signing_key(secret, &date_stamp, region, "s3");
+    let signature = hex::encode(hmac_sha256(&signing_key, string_to_sign.as_bytes()));
+    headers.push((
+        "authorization".to_string(),
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+            config.access_key_id.trim()
+        ),
+    ));
+    Ok((url, headers))
+}
+
+/// Upload `bytes` to the configured bucket and return its public URL.
+pub async fn upload_to_s3(
+    client: &reqwest::Client,
+    config: &ImageS3Config,
+    name: &str,
+    bytes: Vec<u8>,
+    content_type: &str,
+) -> anyhow::Result<String> {
+    let key = format!("{}{name}", config.prefix.trim_start_matches('/'));
+    let (url, headers) = s3_request(
+        config,
+        "PUT",
+        &key,
+        &bytes,
+        Some(content_type),
+        chrono::Utc::now(),
+    )?;
+    let mut request = client.put(&url);
+    for (name, value) in headers.into_iter().filter(|(name, _)| name != "host") {
+        request = request.header(name, value);
+    }
+    let resp = request.body(bytes).send().await?;
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let body = resp.text().await.unwrap_or_default();
+        anyhow::bail!("bucket returned {status}: {body}");
+    }
+    Ok(s3_public_url(config, &key))
+}
+
+/// Upload base64 `image_data` to Imgur with the user's client ID and return
+/// the image link.
+pub async fn upload_to_imgur(
+    client: &reqwest::Client,
+    client_id: &str,
+    image_data: &str,
+    title: &str,
+) -> anyhow::Result<String> {
+    let resp = client
+        .post("https://api.imgur.com/3/image")
+        .header("Authorization", format!("Client-ID {client_id}"))
+        .json(&json!({
+            "image": image_data,
+            "type": "base64",
+            "title": title,
+        }))
+        .send()
+        .await?;
+    let status = resp.status();
+    let body = resp.text().await.unwrap_or_default();
+    if !status.is_success() {
+        anyhow::bail!("Imgur API returned {status}: {body}");
+    }
+    let parsed: serde_json::Value = serde_json::from_str(&body).unwrap_or_default();
+    parsed["data"]["link"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| anyhow::anyhow!("Imgur response without a link"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn now() -> chrono::DateTime<chrono::Utc> {
+        chrono::Utc
+            .with_ymd_and_hms(2026, 10, 16, 15, 30, 0)
+            .unwrap()
+    }
+
+    fn s3() -> ImageS3Config {
+        ImageS3Config {
+            endpoint: "https://acct.r2.cloudflarestorage.com".into(),
+            bucket: "shots".into(),
+            region: "auto".into(),
+            access_key_id: "AKID".into(),
+            secret_access_key: Some("secret".into()),
+            public_url: "https://img.example.com/".into(),
+            ..ImageS3Config::default()
+        }
+    }
+
+    #[test]
+    fn names_are_unique_and_url_safe() {
+        assert_eq!(
+            asset_name("C:\\shots\\Login Screen (1).PNG", now()),
+            "20261016-153000-login-screen-1.png"
+        );
+        assert_eq!(asset_name("???", now()), "20261016-153000-image");
+        assert_eq!(content_type("a.JPG"), Some("image/jpeg"));
+        assert_eq!(content_type("notes.txt"), None);
+    }
+
+    #[test]
+    fn decodes_plain_and_data_url_base64() {
+        assert_eq!(decode("aGk=").unwrap(), b"hi");
+        assert_eq!(decode("data:image/png;base64,aGk=\n").unwrap(), b"hi");
+        assert!(decode("not base64!").is_err());
+    }
+
+    #[test]
+    fn signs_path_style_s3_requests() {
+        let (url, headers) = s3_request(
+            &s3(),
+            "PUT",
+            "zerobuild/a.png",
+            b"png",
+            Some("image/png"),
+            now(),
+        )
+        .unwrap();
+        assert_eq!(
+            url,
+            "https://acct.r2.cloudflarestorage.com/shots/zerobuild/a.png"
+        );
+        let auth = &headers.last().unwrap().1;
+        assert!(auth.starts_with(
+            "AWS4-HMAC-SHA256 Credential=AKID/20261016/auto/s3/aws4_request, \
+             SignedHeaders=content-type;host;x-amz-content-sha256;x-amz-date, Signature="
+        ));
+        assert!(headers.contains(&("x-amz-date".into(), "20261016T153000Z".into())));
+        assert_eq!(
+            s3_public_url(&s3(), "zerobuild/a.png"),
+            "https://img.example.com/zerobuild/a.png"
+        );
+
+        let unsigned = ImageS3Config {
+            secret_access_key: None,
+            ..s3()
+        };
+        assert!(s3_request(&unsigned, "PUT", "a.png", b"", Some("image/png"), now()).is_err());
+
+        let (_, headers) = s3_request(&s3(), "DELETE", "a.png", b"", None, now()).unwrap();
+        assert!(headers
+            .last()
+            .unwrap()
+            .1
+            .contains("SignedHeaders=host;x-amz-content-sha256;x-amz-date,"));
+    }
+
+    #[tokio::test]
+    async fn repo_uploads_go_through_the_transport_and_repo_access() {
+        use super::super::github_transport::{with_transport, MockGitHubTransport};
+        use crate::config::RepoAccessConfig;
+        use std::sync::Arc;
+
+        let mock = Arc::new(MockGitHubTransport::new());
+        mock.on(
+            reqwest::Method::PUT,
+            "/repos/acme/shop/contents/.github/assets/a.png",
+            201,
+            json!({"content": {"html_url": "https://github.com/acme/shop/blob/main/.github/assets/a.png"}}),
+        );
+        let access = RepoAccess::from_config(&RepoAccessConfig {
+            allow: vec!["acme/*".into()],
+            deny: Vec::new(),
+        });
+        let (allowed, refused) = with_transport(mock.clone(), async {
+            (
+                upload_to_repo(&access, "t", "acme", "shop", None, "a.png", b"png").await,
+                upload_to_repo(&access, "t", "other", "shop", None, "a.png", b"png").await,
+            )
+        })
+        .await;
+
+        assert!(allowed.unwrap().ends_with("a.png?raw=true"));
+        assert!(refused
+            .unwrap_err()
+            .to_string()
+            .contains("deploy_repo_allowlist"));
+        assert_eq!(mock.requests().len(), 1);
+    }
+}
@@ -166,6 +166,7 @@ impl Tool for ImageInfoTool {
                 error: Some(format!(
                     "Path not allowed: {path_str} (must be within workspace)"
                 )),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -175,6 +176,7 @@ impl Tool for ImageInfoTool {
                 success: false,
                 output: String::new(),
                 error: Some(format!("File not found: {path_str}")),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -192,6 +194,7 @@ impl Tool for ImageInfoTool {
                 error: Some(format!(
                     "Image too large: {file_size} bytes (max {MAX_IMAGE_BYTES} bytes)"
                 )),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -227,6 +230,7 @@ impl Tool for ImageInfoTool {
             success: true,
             output,
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
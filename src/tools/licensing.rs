@@ -0,0 +1,672 @@
+//! License and attribution handling for deployed repos.
+//!
+//! Before `github_push` uploads a project it adds a `LICENSE` file when the
+//! project has none, fills the `license`/`repository`/`author` fields of the
+//! root `package.json` and `Cargo.toml`, and scans the sources for code that
+//! carries someone else's copyright or license without being credited.
+//! Configured under `[zerobuild.license]`.
+//!
+//! Manifests are edited textually so key order and formatting survive; an
+//! edit that would not parse back is dropped.
+
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::sync::LazyLock;
+
+/// SPDX identifiers accepted by `[zerobuild.license].spdx`.
+pub const SUPPORTED_LICENSES: &[&str] = &[
+    "MIT",
+    "Apache-2.0",
+    "BSD-2-Clause",
+    "BSD-3-Clause",
+    "ISC",
+    "0BSD",
+    "MPL-2.0",
+    "GPL-3.0",
+    "LGPL-3.0",
+    "AGPL-3.0",
+    "Unlicense",
+];
+
+/// Files a project's credits for third-party code live in.
+const CREDIT_FILES: &[&str] = &[
+    "notice",
+    "third_party",
+    "third-party",
+    "attribution",
+    "credits",
+];
+
+/// Most attribution issues reported per deploy.
+pub const MAX_ISSUES: usize = 20;
+
+/// Larger files are not scanned for attribution.
+const MAX_SCAN_BYTES: usize = 512 * 1024;
+
+/// Canonical SPDX id for `spdx` (case-insensitive, `-only`/`-or-later`
+/// accepted for the GNU licenses).
+pub fn canonical(spdx: &str) -> Option<&'static str> {
+    let spdx = spdx.trim();
+    let base = spdx
+        .strip_suffix("-only")
+        .or_else(|| spdx.strip_suffix("-or-later"))
+        .unwrap_or(spdx);
+    SUPPORTED_LICENSES
+        .iter()
+        .find(|id| id.eq_ignore_ascii_case(base))
+        .copied()
+}
+
+/// GitHub license API key (`GET /licenses/{key}`) for `spdx`.
+pub fn license_key(spdx: &str) -> Option<String> {
+    canonical(spdx).map(str::to_ascii_lowercase)
+}
+
+/// Full text of licenses short enough to ship with the binary, with the
+/// year and holder filled in. Others come from the GitHub license API.
+pub fn builtin_text(spdx: &str, year: i32, holder: &str) -> Option<String> {
+    let template = match canonical(spdx)? {
+        "MIT" => MIT,
+        "ISC" => ISC,
+        "0BSD" => ZERO_BSD,
+        "BSD-2-Clause" => BSD_2_CLAUSE,
+        "BSD-3-Clause" => BSD_3_CLAUSE,
+        _ => return None,
+    };
+    Some(fill_placeholders(template, year, holder))
+}
+
+/// Replace the `[year]` and `[fullname]` placeholders GitHub's license
+/// templates use.
+pub fn fill_placeholders(template: &str, year: i32, holder: &str) -> String {
+    template
+        .replace("[year]", &year.to_string())
+        .replace("[fullname]", holder)
+}
+
+/// Whether the project root already has a license file.
+pub fn has_license_file(files: &BTreeMap<String, String>) -> bool {
+    files.keys().any(|path| {
+        let name = path.to_ascii_lowercase();
+        !name.contains('/')
+            && ["license", "licence", "copying", "unlicense"]
+                .iter()
+                .any(|prefix| name.starts_with(prefix))
+    })
+}
+
+/// Values written into manifests that lack them.
+#[derive(Debug, Clone)]
+pub struct Metadata {
+    pub license: String,
+    pub repository: String,
+    /// Written to `package.json` only.
+    pub author: Option<String>,
+}
+
+/// `package.json` with missing `license`, `repository` and `author` keys
+/// added, or `None` when nothing is missing or the file isn't a JSON
+/// object.
+pub fn fill_package_json(text: &str, meta: &Metadata) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(text).ok()?;
+    let object = value.as_object()?;
+    let mut missing = Vec::new();
+    if !object.contains_key("license") {
+        missing.push(("license", serde_json::Value::from(meta.license.as_str())));
+    }
+    if !object.contains_key("repository") {
+        missing.push((
+            "repository",
+            serde_json::json!({ "type": "git", "url": format!("git+{}.git", meta.repository) }),
+        ));
+    }
+    if let Some(author) = meta.author.as_deref().filter(|a| !a.is_empty()) {
+        if !object.contains_key("author") {
+            missing.push(("author", serde_json::Value::from(author)));
+        }
+    }
+    if missing.is_empty() {
+        return None;
+    }
+
+    let open = text.find('{')?;
+    let after_open = &text[open + 1..];
+    let multiline = after_open
+        .trim_start_matches([' ', '\t', '\r'])
+        .starts_with('\n');
+    let updated = if multiline {
+        let indent = after_open
+            .lines()
+            .skip(1)
+            .find(|line| !line.trim().is_empty())
+            .map_or("  ", |line| &line[..line.len() - line.trim_start().len()]);
+        let entries = missing
+            .iter()
+            .map(|(key, value)| format!("{indent}\"{key}\": {value}"))
+            .collect::<Vec<_>>();
+        // After a top-level `"name"` line when it is followed by more keys,
+        // otherwise first in the object.
+        let name_line = format!("{indent}\"name\"");
+        let mut offset = open + 1;
+        let mut anchor = None;
+        for line in text[open + 1..].split_inclusive('\n') {
+            if line.starts_with(&name_line) && line.trim_end().ends_with(',') {
+                anchor = Some(offset + line.len());
+                break;
+            }
+            offset += line.len();
+        }
+        match anchor {
+            Some(at) => format!("{}{},\n{}", &text[..at], entries.join(",\n"), &text[at..]),
+            None => {
+                let at = open + 1 + after_open.find('\n')? + 1;
+                let separator = if text[at..].trim_start().starts_with('}') {
+                    "\n"
+                } else {
+                    ",\n"
+                };
+                format!(
+                    "{}{}{separator}{}",
+                    &text[..at],
+                    entries.join(",\n"),
+                    &text[at..]
+                )
+            }
+        }
+    } else {
+        let entries = missing
+            .iter()
+            .map(|(key, value)| format!("\"{key}\": {value}"))
+            .collect::<Vec<_>>();
+        let separator = if after_open.trim_start().starts_with('}') {
+            ""
+        } else {
+            ", "
+        };
+        format!(
+            "{}{}{separator}{}",
+            &text[..=open],
+            entries.join(", "),
+            after_open
+        )
+    };
+
+    let parsed: serde_json::Value = serde_json::from_str(&updated).ok()?;
+    missing
+        .iter()
+        .all(|(key, value)| parsed.get(key) == Some(value))
+        .then_some(updated)
+}
+
+/// `Cargo.toml` with missing `license` and `repository` keys added to
+/// `[package]`, or `None` when nothing is missing or there is no
+/// `[package]` table.
+pub fn fill_cargo_toml(text: &str, meta: &Metadata) -> Option<String> {
+    let table: toml::Table = toml::from_str(text).ok()?;
+    let package = table.get("package")?.as_table()?;
+    let mut missing = Vec::new();
+    if !package.contains_key("license") && !package.contains_key("license-file") {
+        missing.push(format!("license = {:?}", meta.license));
+    }
+    if !package.contains_key("repository") {
+        missing.push(format!("repository = {:?}", meta.repository));
+    }
+    if missing.is_empty() {
+        return None;
+    }
+
+    // Insert after the last key line of the `[package]` section.
+    let mut in_package = false;
+    let mut offset = 0;
+    let mut insert_at = None;
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            if in_package {
+                break;
+            }
+            in_package = trimmed == "[package]";
+            if in_package {
+                insert_at = Some(offset + line.len());
+            }
+        } else if in_package && !trimmed.is_empty() && !trimmed.starts_with('#') {
+            insert_at = Some(offset + line.len());
+        }
+        offset += line.len();
+    }
+    let at = insert_at?;
+    let newline = if text[..at].ends_with('\n') { "" } else { "\n" };
+    let updated = format!(
+        "{}{newline}{}\n{}",
+        &text[..at],
+        missing.join("\n"),
+        &text[at..]
+    );
+    toml::from_str::<toml::Table>(&updated).ok()?;
+    Some(updated)
+}
+
+/// A file that looks like it contains uncredited third-party code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttributionIssue {
+    pub path: String,
+    pub line: usize,
+    pub reason: String,
+}
+
+static COPYRIGHT_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\bcopyright\b(?:\s*(?:\(c\)|©))?\s*(?:\d{4}(?:\s*[-–,]\s*\d{4})*)?[\s,]*(.*)")
+        .unwrap()
+});
+static SPDX_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"SPDX-License-Identifier:\s*([A-Za-z0-9.+-]+)").unwrap());
+static COPIED_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(?:copied|taken|adapted|borrowed|ported|lifted)\s+(?:verbatim\s+)?from\b")
+        .unwrap()
+});
+
+/// Whether `line` is (the start of) a comment.
+fn is_comment(line: &str) -> bool {
+    let line = line.trim_start();
+    ["//", "#", "/*", "*", "<!--", "--", "{/*", "\"\"\"", ";"]
+        .iter()
+        .any(|marker| line.starts_with(marker))
+}
+
+/// Whether `path` is worth scanning for third-party notices.
+fn scannable(path: &str, content: &str) -> bool {
+    let lower = path.to_ascii_lowercase();
+    let name = lower.rsplit('/').next().unwrap_or(&lower);
+    content.len() <= MAX_SCAN_BYTES
+        && !lower.split('/').any(|part| {
+            matches!(
+                part,
+                "node_modules" | "vendor" | "third_party" | "dist" | "build" | "target"
+            )
+        })
+        && !name.contains(".min.")
+        && !name.ends_with(".lock")
+        && !name.ends_with("-lock.json")
+        && !name.ends_with("-lock.yaml")
+        && ![
+            "license", "licence", "copying", "notice", "readme", "credits",
+        ]
+        .iter()
+        .any(|prefix| name.starts_with(prefix))
+}
+
+/// Files with third-party copyright or license notices, GPL text in a
+/// non-GPL project, or "copied from" comments without a source, that are
+/// not credited in a NOTICE/THIRD_PARTY/ATTRIBUTION/CREDITS file or the
+/// README. Notices naming `holder` are the project's own.
+pub fn check_attribution(
+    files: &BTreeMap<String, String>,
+    license: Option<&str>,
+    holder: &str,
+) -> Vec<AttributionIssue> {
+    let credits = files
+        .iter()
+        .filter(|(path, _)| {
+            let name = path.to_ascii_lowercase();
+            !name.contains('/')
+                && (name.starts_with("readme")
+                    || CREDIT_FILES.iter().any(|prefix| name.starts_with(prefix)))
+        })
+        .map(|(_, content)| content.to_lowercase())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let credited = |needle: &str| {
+        let needle = needle.trim().to_lowercase();
+        !needle.is_empty() && credits.contains(&needle)
+    };
+    let holder = holder.trim().to_lowercase();
+    let license = license.and_then(canonical);
+    let gpl_project = license.is_some_and(|id| id.contains("GPL"));
+
+    let mut issues = Vec::new();
+    for (path, content) in files {
+        if !scannable(path, content) || credited(path) {
+            continue;
+        }
+        for (index, line) in content.lines().enumerate() {
+            if !is_comment(line) {
+                continue;
+            }
+            let reason = if let Some(caps) = SPDX_RE.captures(line) {
+                let id = &caps[1];
+                match (canonical(id), license) {
+                    (Some(found), Some(project)) if found == project => None,
+                    _ if credited(id) => None,
+                    _ => Some(format!(
+                        "SPDX-License-Identifier {id} differs from the project license"
+                    )),
+                }
+            } else if let Some(caps) = COPYRIGHT_RE.captures(line) {
+                let owner = caps[1]
+                    .trim_end_matches(['*', '/', '>', '-', ' '])
+                    .trim()
+                    .trim_start_matches(['(', '©'])
+                    .trim();
+                let own = owner.is_empty()
+                    || (!holder.is_empty() && owner.to_lowercase().contains(&holder));
+                (!own && !credited(owner)).then(|| format!("copyright notice for '{owner}'"))
+            } else if !gpl_project && line.contains("GNU General Public License") {
+                Some("GPL license text in a project that isn't GPL licensed".to_string())
+            } else if COPIED_RE.is_match(line) && !line.contains("http") {
+                Some("says the code was copied but names no source".to_string())
+            } else {
+                None
+            };
+            if let Some(reason) = reason {
+                issues.push(AttributionIssue {
+                    path: path.clone(),
+                    line: index + 1,
+                    reason,
+                });
+                break;
+            }
+        }
+    }
+    issues
+}
+
+/// Settings for [`apply`].
+#[derive(Debug, Clone)]
+pub struct LicenseOptions {
+    /// Canonical SPDX id, `None` to add no license.
+    pub spdx: Option<String>,
+    /// License text for a project without a license file.
+    pub text: Option<String>,
+    pub metadata: Option<Metadata>,
+    pub check_attribution: bool,
+    pub holder: String,
+}
+
+/// What [`apply`] changed and found.
+#[derive(Debug, Clone, Default)]
+pub struct LicenseReport {
+    pub license_added: bool,
+    /// Manifests whose metadata was filled in.
+    pub filled: Vec<String>,
+    pub issues: Vec<AttributionIssue>,
+}
+
+/// Add the license file and manifest metadata to `files` (paths relative
+/// to the repository root) and check attribution.
+pub fn apply(files: &mut BTreeMap<String, String>, options: &LicenseOptions) -> LicenseReport {
+    let mut report = LicenseReport::default();
+    if let Some(text) = &options.text {
+        if options.spdx.is_some() && !has_license_file(files) {
+            files.insert("LICENSE".to_string(), text.clone());
+            report.license_added = true;
+        }
+    }
+    if let Some(meta) = &options.metadata {
+        for (manifest, fill) in [
+            (
+                "package.json",
+                fill_package_json as fn(&str, &Metadata) -> Option<String>,
+            ),
+            ("Cargo.toml", fill_cargo_toml),
+        ] {
+            if let Some(updated) = files.get(manifest).and_then(|text| fill(text, meta)) {
+                files.insert(manifest.to_string(), updated);
+                report.filled.push(manifest.to_string());
+            }
+        }
+    }
+    if options.check_attribution {
+        report.issues = check_attribution(files, options.spdx.as_deref(), &options.holder);
+    }
+    report
+}
+
+const MIT: &str = "MIT License
+
+Copyright (c) [year] [fullname]
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the \"Software\"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED \"AS IS\", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+";
+
+const ISC: &str = "ISC License
+
+Copyright (c) [year] [fullname]
+
+Permission to use, copy, modify, and/or distribute this software for any
+purpose with or without fee is hereby granted, provided that the above
+copyright notice and this permission notice appear in all copies.
+
+THE SOFTWARE IS PROVIDED \"AS IS\" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+";
+
+const ZERO_BSD: &str = "BSD Zero Clause License
+
+Copyright (c) [year] [fullname]
+
+Permission to use, copy, modify, and/or distribute this software for any
+purpose with or without fee is hereby granted.
+
+THE SOFTWARE IS PROVIDED \"AS IS\" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY
+AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+PERFORMANCE OF THIS SOFTWARE.
+";
+
+const BSD_2_CLAUSE: &str = "BSD 2-Clause License
+
+Copyright (c) [year], [fullname]
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice, this
+   list of conditions and the following disclaimer.
+
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS \"AS IS\"
+AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+";
+
+const BSD_3_CLAUSE: &str = "BSD 3-Clause License
+
+Copyright (c) [year], [fullname]
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice, this
+   list of conditions and the following disclaimer.
+
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+3. Neither the name of the copyright holder nor the names of its
+   contributors may be used to endorse or promote products derived from
+   this software without specific prior written permission.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS \"AS IS\"
+AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meta() -> Metadata {
+        Metadata {
+            license: "MIT".into(),
+            repository: "https://github.com/acme/shop".into(),
+            author: Some("Acme Inc.".into()),
+        }
+    }
+
+    #[test]
+    fn resolves_ids_and_builtin_texts() {
+        assert_eq!(canonical("apache-2.0"), Some("Apache-2.0"));
+        assert_eq!(canonical("GPL-3.0-or-later"), Some("GPL-3.0"));
+        assert_eq!(license_key("AGPL-3.0-only").as_deref(), Some("agpl-3.0"));
+        assert_eq!(canonical("WTFPL"), None);
+
+        let text = builtin_text("mit", 2026, "Acme Inc.").unwrap();
+        assert!(text.contains("Copyright (c) 2026 Acme Inc."));
+        assert!(builtin_text("Apache-2.0", 2026, "Acme").is_none());
+    }
+
+    #[test]
+    fn fills_package_json_keeping_layout() {
+        let text = "{\n  \"name\": \"shop\",\n  \"version\": \"1.0.0\",\n  \"private\": true\n}\n";
+        let updated = fill_package_json(text, &meta()).unwrap();
+        assert!(updated.starts_with("{\n  \"name\": \"shop\",\n  \"license\": \"MIT\",\n"));
+        assert!(updated.ends_with("  \"version\": \"1.0.0\",\n  \"private\": true\n}\n"));
+        let parsed: serde_json::Value = serde_json::from_str(&updated).unwrap();
+        assert_eq!(
+            parsed["repository"]["url"],
+            "git+https://github.com/acme/shop.git"
+        );
+        assert_eq!(parsed["author"], "Acme Inc.");
+        assert!(fill_package_json(&updated, &meta()).is_none());
+
+        let inline = fill_package_json("{}", &meta()).unwrap();
+        assert!(serde_json::from_str::<serde_json::Value>(&inline).is_ok());
+        assert!(fill_package_json("[1, 2]", &meta()).is_none());
+    }
+
+    #[test]
+    fn fills_cargo_package_only_when_missing() {
+        let text =
+            "[package]\nname = \"shop\"\nversion = \"0.1.0\"\n\n[dependencies]\nserde = \"1\"\n";
+        let updated = fill_cargo_toml(text, &meta()).unwrap();
+        assert_eq!(
+            updated,
+            "[package]\nname = \"shop\"\nversion = \"0.1.0\"\nlicense = \"MIT\"\n\
+             repository = \"https://github.com/acme/shop\"\n\n[dependencies]\nserde = \"1\"\n"
+        );
+        assert!(fill_cargo_toml(&updated, &meta()).is_none());
+        let inherited =
+            "[package]\nname = \"shop\"\nlicense.workspace = true\nrepository = \"x\"\n";
+        assert!(fill_cargo_toml(inherited, &meta()).is_none());
+        assert!(fill_cargo_toml("[workspace]\nmembers = []\n", &meta()).is_none());
+    }
+
+    #[test]
+    fn flags_uncredited_third_party_code() {
+        let mut files = BTreeMap::new();
+        files.insert(
+            "src/own.ts".to_string(),
+            "// Copyright (c) 2026 Acme Inc.\n// SPDX-License-Identifier: MIT\n".to_string(),
+        );
+        files.insert(
+            "src/debounce.ts".to_string(),
+            "export const x = 1;\n// Copyright 2015 Jane Roe\n".to_string(),
+        );
+        files.insert(
+            "src/sort.py".to_string(),
+            "# Adapted from a StackOverflow answer\n".to_string(),
+        );
+        files.insert(
+            "src/link.py".to_string(),
+            "# Adapted from https://example.com/snippet\n".to_string(),
+        );
+        files.insert(
+            "src/parser.c".to_string(),
+            "/* SPDX-License-Identifier: GPL-2.0-only */\n".to_string(),
+        );
+        files.insert(
+            "src/footer.tsx".to_string(),
+            "<p>Copyright 2026 Someone</p>\n".to_string(),
+        );
+
+        let issues = check_attribution(&files, Some("MIT"), "acme inc.");
+        let flagged = issues
+            .iter()
+            .map(|issue| (issue.path.as_str(), issue.line))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            flagged,
+            [
+                ("src/debounce.ts", 2),
+                ("src/parser.c", 1),
+                ("src/sort.py", 1)
+            ]
+        );
+        assert!(issues[0].reason.contains("Jane Roe"));
+
+        files.insert(
+            "NOTICE".to_string(),
+            "debounce by Jane Roe (MIT)\nsrc/parser.c is GPL-2.0-only\n".to_string(),
+        );
+        let issues = check_attribution(&files, Some("MIT"), "acme inc.");
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].path, "src/sort.py");
+    }
+
+    #[test]
+    fn apply_adds_license_only_without_one() {
+        let mut files = BTreeMap::new();
+        files.insert(
+            "package.json".to_string(),
+            "{\"name\": \"shop\"}".to_string(),
+        );
+        let options = LicenseOptions {
+            spdx: Some("MIT".into()),
+            text: builtin_text("MIT", 2026, "acme"),
+            metadata: Some(meta()),
+            check_attribution: true,
+            holder: "acme".into(),
+        };
+        let report = apply(&mut files, &options);
+        assert!(report.license_added);
+        assert_eq!(report.filled, ["package.json"]);
+        assert!(files["package.json"].contains("\"license\": \"MIT\""));
+
+        let mut licensed = BTreeMap::new();
+        licensed.insert("LICENSE.md".to_string(), "Apache".to_string());
+        assert!(!apply(&mut licensed, &options).license_added);
+        assert!(!licensed.contains_key("LICENSE"));
+    }
+}
@@ -0,0 +1,308 @@
+//! Per-tool execution timeouts and circuit breaker (`[reliability.tools]`).
+//!
+//! Every registry tool is wrapped in a [`LimitedTool`] so a hung GitHub call
+//! or sandbox exec cannot stall an agent turn indefinitely. Timeouts and
+//! execution errors count towards a per-tool circuit breaker; once it trips,
+//! calls fail fast with an explanation the model can act on until the
+//! cooldown passes. Tool-reported failures (`success: false`, e.g. a failing
+//! test command) don't count, since the tool itself is working.
+
+use super::traits::{Tool, ToolResult};
+use crate::config::{SandboxLimitsConfig, ToolReliabilityConfig};
+use crate::error::ErrorCode;
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Headroom added to limits the sandbox enforces itself, so its own timeout
+/// error reaches the model first.
+const SANDBOX_GRACE: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Default)]
+struct Breaker {
+    consecutive_failures: u32,
+    last_error: String,
+    open_until: Option<Instant>,
+}
+
+/// Timeouts and breaker state shared by every wrapped tool of a registry.
+pub struct ToolLimits {
+    default_timeout: Option<Duration>,
+    timeouts: HashMap<String, Option<Duration>>,
+    failure_threshold: u32,
+    cooldown: Duration,
+    breakers: Mutex<HashMap<String, Breaker>>,
+}
+
+fn secs(value: u64) -> Option<Duration> {
+    (value > 0).then(|| Duration::from_secs(value))
+}
+
+impl ToolLimits {
+    /// Limits from `[reliability.tools]`. Sandbox commands and `wait_for`
+    /// default to the `[zerobuild.sandbox]` limits, and composite tools (`setup_project`,
+    /// `ship_it`) and sub-agent tools (`delegate`, `factory_build`,
+    /// `a2a_delegate`) to no timeout, since their own steps, tool calls or
+    /// waits are bounded.
+    pub fn from_config(config: &ToolReliabilityConfig, sandbox: &SandboxLimitsConfig) -> Self {
+        let mut timeouts: HashMap<String, Option<Duration>> = HashMap::from([
+            (
+                "sandbox_run_command".to_string(),
+                Some(Duration::from_secs(sandbox.max_command_timeout_secs) + SANDBOX_GRACE),
+            ),
+            (
+                "sandbox_shell".to_string(),
+                Some(Duration::from_secs(sandbox.max_command_timeout_secs) + SANDBOX_GRACE),
+            ),
+            (
+                "wait_for".to_string(),
+                Some(Duration::from_secs(sandbox.max_command_timeout_secs) + SANDBOX_GRACE),
+            ),
+            (
+                "sandbox_create".to_string(),
+                Some(Duration::from_secs(sandbox.create_timeout_secs) + SANDBOX_GRACE),
+            ),
+            ("setup_project".to_string(), None),
+            ("ship_it".to_string(), None),
+            ("delegate".to_string(), None),
+            ("factory_build".to_string(), None),
+            ("a2a_delegate".to_string(), None),
+        ]);
+        for (tool, timeout) in &config.timeouts {
+            timeouts.insert(tool.clone(), secs(*timeout));
+        }
+
+        Self {
+            default_timeout: secs(config.timeout_secs),
+            timeouts,
+            failure_threshold: config.failure_threshold,
+            cooldown: Duration::from_secs(config.cooldown_secs),
+            breakers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn timeout_for(&self, tool: &str) -> Option<Duration> {
+        self.timeouts
+            .get(tool)
+            .copied()
+            .unwrap_or(self.default_timeout)
+    }
+
+    /// Why `tool` is disabled, or `None` when it may run. After the cooldown
+    /// one trial call is let through; its outcome closes or re-opens the
+    /// breaker.
+    fn unavailable(&self, tool: &str) -> Option<String> {
+        let mut breakers = self.breakers.lock();
+        let breaker = breakers.get_mut(tool)?;
+        let open_until = breaker.open_until?;
+        let now = Instant::now();
+        if now >= open_until {
+            breaker.open_until = None;
+            return None;
+        }
+        Some(format!(
+            "Tool '{tool}' is temporarily disabled after {} consecutive failures (last: {}). \
+             It will be retried automatically in {}s; use a different approach meanwhile.",
+            breaker.consecutive_failures,
+            breaker.last_error,
+            (open_until - now).as_secs().max(1),
+        ))
+    }
+
+    fn record_success(&self, tool: &str) {
+        self.breakers.lock().remove(tool);
+    }
+
+    fn record_failure(&self, tool: &str, error: &str) {
+        if self.failure_threshold == 0 {
+            return;
+        }
+        let mut breakers = self.breakers.lock();
+        let breaker = breakers.entry(tool.to_string()).or_default();
+        breaker.consecutive_failures += 1;
+        breaker.last_error = crate::util::truncate_with_ellipsis(error, 200);
+        if breaker.consecutive_failures >= self.failure_threshold {
+            tracing::warn!(
+                tool,
+                failures = breaker.consecutive_failures,
+                cooldown_secs = self.cooldown.as_secs(),
+                "Tool circuit breaker opened"
+            );
+            breaker.open_until = Some(Instant::now() + self.cooldown);
+        }
+    }
+}
+
+/// A tool whose calls are bounded by [`ToolLimits`].
+pub struct LimitedTool {
+    inner: Arc<dyn Tool>,
+    limits: Arc<ToolLimits>,
+}
+
+impl LimitedTool {
+    pub fn new(inner: Arc<dyn Tool>, limits: Arc<ToolLimits>) -> Self {
+        Self { inner, limits }
+    }
+}
+
+#[async_trait]
+impl Tool for LimitedTool {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn description(&self) -> &str {
+        self.inner.description()
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        self.inner.parameters_schema()
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let name = self.inner.name();
+        if let Some(reason) = self.limits.unavailable(name) {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(reason),
+                error_code: None,
+                error_hint: None,
+            });
+        }
+
+        let result = match self.limits.timeout_for(name) {
+            Some(timeout) => match tokio::time::timeout(timeout, self.inner.execute(args)).await {
+                Ok(result) => result,
+                Err(_) => {
+                    let error = format!("timed out after {}s", timeout.as_secs());
+                    self.limits.record_failure(name, &error);
+                    return Ok(ToolResult {
+                        success: false,
+                        output: String::new(),
+                        error: Some(format!("Tool '{name}' {error} and was cancelled")),
+                        error_code: Some(ErrorCode::Timeout),
+                        error_hint: None,
+                    });
+                }
+            },
+            None => self.inner.execute(args).await,
+        };
+
+        match &result {
+            Ok(_) => self.limits.record_success(name),
+            Err(e) => self.limits.record_failure(name, &e.to_string()),
+        }
+        result
+    }
+}
+
+/// Wrap every tool in `tools` with `limits`.
+pub fn limit_tools(tools: Vec<Arc<dyn Tool>>, limits: &Arc<ToolLimits>) -> Vec<Arc<dyn Tool>> {
+    tools
+        .into_iter()
+        .map(|tool| Arc::new(LimitedTool::new(tool, Arc::clone(limits))) as Arc<dyn Tool>)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    struct SlowTool;
+
+    #[async_trait]
+    impl Tool for SlowTool {
+        fn name(&self) -> &str {
+            "github_slow"
+        }
+
+        fn description(&self) -> &str {
+            "sleeps for the requested milliseconds"
+        }
+
+        fn parameters_schema(&self) -> serde_json::Value {
+            json!({"type": "object"})
+        }
+
+        async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+            let ms = args["ms"].as_u64().unwrap_or(0);
+            tokio::time::sleep(Duration::from_millis(ms)).await;
+            Ok(ToolResult {
+                success: true,
+                output: "done".into(),
+                error: None,
+                error_code: None,
+                error_hint: None,
+            })
+        }
+    }
+
+    fn limits(threshold: u32, cooldown: Duration) -> Arc<ToolLimits> {
+        let mut limits = ToolLimits::from_config(
+            &ToolReliabilityConfig {
+                failure_threshold: threshold,
+                ..ToolReliabilityConfig::default()
+            },
+            &SandboxLimitsConfig::default(),
+        );
+        limits.default_timeout = Some(Duration::from_millis(20));
+        limits.cooldown = cooldown;
+        Arc::new(limits)
+    }
+
+    #[test]
+    fn timeouts_follow_overrides_and_sandbox_limits() {
+        let limits = ToolLimits::from_config(
+            &ToolReliabilityConfig {
+                timeouts: HashMap::from([("github_push".to_string(), 30), ("delegate".into(), 60)]),
+                ..ToolReliabilityConfig::default()
+            },
+            &SandboxLimitsConfig::default(),
+        );
+        assert_eq!(limits.timeout_for("shell"), Some(Duration::from_secs(600)));
+        assert_eq!(
+            limits.timeout_for("github_push"),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            limits.timeout_for("delegate"),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(limits.timeout_for("factory_build"), None);
+        assert_eq!(
+            limits.timeout_for("sandbox_run_command"),
+            Some(Duration::from_secs(1830))
+        );
+    }
+
+    #[tokio::test]
+    async fn hung_call_times_out_and_trips_breaker() {
+        let tool = LimitedTool::new(Arc::new(SlowTool), limits(2, Duration::from_secs(60)));
+
+        let first = tool.execute(json!({"ms": 1000})).await.unwrap();
+        assert!(!first.success);
+        assert!(first.error.unwrap().contains("timed out"));
+
+        tool.execute(json!({"ms": 1000})).await.unwrap();
+        let blocked = tool.execute(json!({"ms": 0})).await.unwrap();
+        assert!(!blocked.success);
+        let reason = blocked.error.unwrap();
+        assert!(reason.contains("temporarily disabled after 2 consecutive failures"));
+        assert!(reason.contains("timed out"));
+    }
+
+    #[tokio::test]
+    async fn breaker_half_opens_after_cooldown() {
+        let tool = LimitedTool::new(Arc::new(SlowTool), limits(1, Duration::ZERO));
+
+        tool.execute(json!({"ms": 1000})).await.unwrap();
+        let retried = tool.execute(json!({"ms": 0})).await.unwrap();
+        assert!(retried.success, "trial call after cooldown runs");
+        let again = tool.execute(json!({"ms": 0})).await.unwrap();
+        assert!(again.success, "success closes the breaker");
+    }
+}
@@ -78,6 +78,7 @@ impl Tool for MemoryObserveTool {
                     success: false,
                     output: String::new(),
                     error: Some("'confidence' must be within [0.0, 1.0]".to_string()),
+                    error_code: None,
                     error_hint: None,
                 });
             }
@@ -129,6 +130,7 @@ impl Tool for MemoryObserveTool {
                 success: false,
                 output: String::new(),
                 error: Some(error),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -138,12 +140,14 @@ impl Tool for MemoryObserveTool {
                 success: true,
                 output: format!("Stored observation memory: {key}"),
                 error: None,
+                error_code: None,
                 error_hint: None,
             }),
             Err(e) => Ok(ToolResult {
                 success: false,
                 output: String::new(),
                 error: Some(format!("Failed to store observation memory: {e}")),
+                error_code: None,
                 error_hint: None,
             }),
         }
@@ -60,6 +60,7 @@ impl Tool for MemoryRecallTool {
                 success: true,
                 output: "No memories found matching that query.".into(),
                 error: None,
+                error_code: None,
                 error_hint: None,
             }),
             Ok(entries) => {
@@ -78,6 +79,7 @@ impl Tool for MemoryRecallTool {
                     success: true,
                     output,
                     error: None,
+                    error_code: None,
                     error_hint: None,
                 })
             }
@@ -85,6 +87,7 @@ impl Tool for MemoryRecallTool {
                 success: false,
                 output: String::new(),
                 error: Some(format!("Memory recall failed: {e}")),
+                error_code: None,
                 error_hint: None,
             }),
         }
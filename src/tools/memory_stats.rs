@@ -0,0 +1,190 @@
+use super::traits::{Tool, ToolResult};
+use crate::memory::stats::{collect_stats, inspect, store_names, InspectedEntry};
+use async_trait::async_trait;
+use serde_json::json;
+use std::fmt::Write;
+use std::path::PathBuf;
+
+/// Memory statistics and per-entry inspection.
+///
+/// Inside a channel conversation only shared stores and the current
+/// sender's own store are visible.
+pub struct MemoryStatsTool {
+    workspace_dir: PathBuf,
+}
+
+impl MemoryStatsTool {
+    pub fn new(workspace_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            workspace_dir: workspace_dir.into(),
+        }
+    }
+
+    fn visible(store: &str, user: Option<&str>) -> bool {
+        match (store.strip_prefix("user_"), user) {
+            (Some(owner), Some(user)) => owner == user,
+            _ => true,
+        }
+    }
+
+    fn render_entry(out: &mut String, entry: &InspectedEntry) {
+        let _ = writeln!(out, "[{}] {} ({})", entry.store, entry.key, entry.category);
+        let _ = writeln!(
+            out,
+            "  written {} (created {}), recalled {} times, last {}",
+            entry.updated_at,
+            entry.created_at,
+            entry.access_count,
+            entry.accessed_at.as_deref().unwrap_or("never")
+        );
+        if let Some(expires_at) = &entry.expires_at {
+            let _ = writeln!(out, "  expires {expires_at}");
+        }
+        let _ = writeln!(out, "  {}", entry.content);
+    }
+}
+
+#[async_trait]
+impl Tool for MemoryStatsTool {
+    fn name(&self) -> &str {
+        "memory_stats"
+    }
+
+    fn description(&self) -> &str {
+        "Inspect long-term memory. 'stats' shows entries per store and category, storage size and last write/access; 'inspect' shows where a key is stored, when it was written and how often it was recalled — use it to find out why a wrong fact keeps coming back."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["stats", "inspect"],
+                    "description": "Default: stats"
+                },
+                "key": {
+                    "type": "string",
+                    "description": "Memory key to inspect (inspect only)"
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let user = crate::memory::user_scoped::current_memory_user();
+        let action = args
+            .get("action")
+            .and_then(|v| v.as_str())
+            .unwrap_or("stats");
+        let mut output = String::new();
+
+        match action {
+            "stats" => {
+                let stats = collect_stats(&self.workspace_dir);
+                for store in stats
+                    .stores
+                    .iter()
+                    .filter(|s| Self::visible(&s.store, user.as_deref()))
+                {
+                    let _ = writeln!(
+                        output,
+                        "{}: {} entries, {} bytes on disk, last write {}, last access {}",
+                        store.store,
+                        store.entries,
+                        store.disk_bytes,
+                        store.last_write.as_deref().unwrap_or("never"),
+                        store.last_access.as_deref().unwrap_or("never")
+                    );
+                    for category in &store.categories {
+                        let _ = writeln!(
+                            output,
+                            "  {}: {} entries, {} bytes",
+                            category.category, category.entries, category.bytes
+                        );
+                    }
+                }
+                if output.is_empty() {
+                    output = "No memory stores found.".into();
+                }
+            }
+            "inspect" => {
+                let key = args
+                    .get("key")
+                    .and_then(|v| v.as_str())
+                    .map(str::trim)
+                    .filter(|k| !k.is_empty())
+                    .ok_or_else(|| anyhow::anyhow!("Missing 'key' parameter"))?;
+                for store in store_names(&self.workspace_dir)
+                    .iter()
+                    .filter(|s| Self::visible(s, user.as_deref()))
+                {
+                    match inspect(&self.workspace_dir, store, key) {
+                        Ok(Some(entry)) => Self::render_entry(&mut output, &entry),
+                        Ok(None) => {}
+                        Err(e) => tracing::debug!(store = %store, "memory inspect skipped: {e}"),
+                    }
+                }
+                if output.is_empty() {
+                    output = format!("No memory stored under key '{key}'.");
+                }
+            }
+            other => {
+                return Ok(ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some(format!("Unknown action '{other}'")),
+                    error_code: None,
+                    error_hint: None,
+                })
+            }
+        }
+
+        Ok(ToolResult {
+            success: true,
+            output,
+            error: None,
+            error_code: None,
+            error_hint: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::user_scoped::with_memory_user;
+    use crate::memory::{Memory, MemoryCategory, SqliteMemory};
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn hides_other_users_stores() {
+        let tmp = TempDir::new().unwrap();
+        for user in ["alice", "bob"] {
+            let mem =
+                SqliteMemory::new(&tmp.path().join("memory").join(format!("user_{user}"))).unwrap();
+            mem.store(
+                "editor",
+                &format!("{user} uses vim"),
+                MemoryCategory::Core,
+                None,
+            )
+            .await
+            .unwrap();
+        }
+        let tool = MemoryStatsTool::new(tmp.path());
+
+        let all = tool.execute(json!({})).await.unwrap();
+        assert!(all.output.contains("user_alice: 1 entries"));
+        assert!(all.output.contains("user_bob"));
+
+        let alice = with_memory_user(
+            "alice".into(),
+            tool.execute(json!({"action": "inspect", "key": "editor"})),
+        )
+        .await
+        .unwrap();
+        assert!(alice.output.contains("alice uses vim"));
+        assert!(!alice.output.contains("bob"));
+    }
+}
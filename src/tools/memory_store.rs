@@ -25,7 +25,7 @@ impl Tool for MemoryStoreTool {
     }
 
     fn description(&self) -> &str {
-        "Store a fact, preference, or note in long-term memory. Use category 'core' for permanent facts, 'daily' for session notes, 'conversation' for chat context, or a custom category name."
+        "Store a fact, preference, or note in long-term memory. Use category 'core' for permanent facts, 'daily' for session notes, 'conversation' for chat context, 'project' for decisions, libraries and known issues of the current project (shared with everyone working on it), or a custom category name."
     }
 
     fn parameters_schema(&self) -> serde_json::Value {
@@ -42,7 +42,12 @@ impl Tool for MemoryStoreTool {
                 },
                 "category": {
                     "type": "string",
-                    "description": "Memory category: 'core' (permanent), 'daily' (session), 'conversation' (chat), or a custom category name. Defaults to 'core'."
+                    "description": "Memory category: 'core' (permanent), 'daily' (session), 'conversation' (chat), 'project' (current project), or a custom category name. Defaults to 'core'."
+                },
+                "ttl_hours": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": "Optional: forget this memory after N hours (e.g. sandbox IDs, temporary preferences)."
                 }
             },
             "required": ["key", "content"]
@@ -75,21 +80,38 @@ impl Tool for MemoryStoreTool {
                 success: false,
                 output: String::new(),
                 error: Some(error),
+                error_code: None,
                 error_hint: None,
             });
         }
 
-        match self.memory.store(key, content, category, None).await {
+        let ttl = args
+            .get("ttl_hours")
+            .and_then(serde_json::Value::as_u64)
+            .filter(|hours| *hours > 0)
+            .map(|hours| std::time::Duration::from_secs(hours.saturating_mul(3600)));
+        let result = match ttl {
+            Some(ttl) => {
+                self.memory
+                    .store_with_ttl(key, content, category, None, ttl)
+                    .await
+            }
+            None => self.memory.store(key, content, category, None).await,
+        };
+
+        match result {
             Ok(()) => Ok(ToolResult {
                 success: true,
                 output: format!("Stored memory: {key}"),
                 error: None,
+                error_code: None,
                 error_hint: None,
             }),
             Err(e) => Ok(ToolResult {
                 success: false,
                 output: String::new(),
                 error: Some(format!("Failed to store memory: {e}")),
+                error_code: None,
                 error_hint: None,
             }),
         }
@@ -167,6 +189,27 @@ mod tests {
         assert_eq!(entry.category, MemoryCategory::Custom("project".into()));
     }
 
+    #[tokio::test]
+    async fn store_with_ttl_sets_expiry() {
+        let (tmp, mem) = test_mem();
+        let tool = MemoryStoreTool::new(mem.clone(), test_security());
+        let result = tool
+            .execute(json!({"key": "sandbox", "content": "sbx-42", "ttl_hours": 2}))
+            .await
+            .unwrap();
+        assert!(result.success);
+
+        let conn = rusqlite::Connection::open(tmp.path().join("memory").join("brain.db")).unwrap();
+        let expires_at: Option<String> = conn
+            .query_row(
+                "SELECT expires_at FROM memories WHERE key = 'sandbox'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(expires_at.is_some());
+    }
+
     #[tokio::test]
     async fn store_missing_key() {
         let (_tmp, mem) = test_mem();
@@ -17,9 +17,12 @@
 
 pub mod auth_profile;
 pub mod bg_run;
+pub mod blackboard_history;
 pub mod browser;
 pub mod browser_open;
+pub mod changelog;
 pub mod cli_discovery;
+pub mod codeowners;
 pub mod composio;
 pub mod cron_add;
 pub mod cron_list;
@@ -28,35 +31,56 @@ pub mod cron_run;
 pub mod cron_runs;
 pub mod cron_update;
 pub mod delegate;
+pub mod env_example;
 pub mod file_edit;
 pub mod file_read;
 pub mod file_write;
 pub mod git_operations;
+pub mod github_access;
 pub mod github_ops;
+pub mod github_orgs;
 pub mod github_push;
 pub mod github_read_repo;
+pub mod github_scopes;
+pub mod github_transport;
+pub mod gitignore;
 pub mod glob_search;
 pub mod hardware_board_info;
 pub mod hardware_memory_map;
 pub mod hardware_memory_read;
+pub mod history_search;
 pub mod http_request;
+pub mod image_hosting;
 pub mod image_info;
+pub mod licensing;
+pub mod limits;
 pub mod memory_forget;
 pub mod memory_observe;
 pub mod memory_recall;
+pub mod memory_stats;
 pub mod memory_store;
+pub mod model_routing;
 pub mod model_routing_config;
 pub mod pdf_read;
 pub mod pptx_read;
+pub mod preferences;
 pub mod product_advisor;
+pub mod progress;
 pub mod proxy_config;
 pub mod pushover;
+pub mod recovery;
 pub mod sandbox;
 pub mod schedule;
 pub mod schema;
 pub mod screenshot;
+pub mod secret_scan;
 pub mod shell;
+pub mod ship_it;
+pub mod skill_save;
+pub mod switch_phase;
+pub mod think;
 pub mod traits;
+pub mod vercel;
 pub mod web_search_tool;
 pub mod xlsx_read;
 
@@ -64,8 +88,10 @@ pub use auth_profile::ManageAuthProfileTool;
 pub use bg_run::{
     format_bg_result_for_injection, BgJob, BgJobStatus, BgJobStore, BgRunTool, BgStatusTool,
 };
+pub use blackboard_history::BlackboardHistoryTool;
 pub use browser::{BrowserTool, ComputerUseConfig};
 pub use browser_open::BrowserOpenTool;
+pub use changelog::DeployChangesTool;
 pub use composio::ComposioTool;
 pub use cron_add::CronAddTool;
 pub use cron_list::CronListTool;
@@ -80,11 +106,13 @@ pub use file_write::FileWriteTool;
 pub use git_operations::GitOperationsTool;
 pub use github_ops::{
     GitHubAnalyzePRTool, GitHubCloseIssueTool, GitHubCommentIssueTool, GitHubCommentPRTool,
-    GitHubConnectTool, GitHubCreateIssueTool, GitHubCreateIssueWithHashtagsTool,
-    GitHubCreatePRTool, GitHubEditIssueTool, GitHubGetIssueTool, GitHubGetPRDiffTool,
-    GitHubGetPRTool, GitHubListIssuesTool, GitHubListPRsTool, GitHubListReposTool,
-    GitHubPostInlineCommentsTool, GitHubReplyCommentTool, GitHubReviewPRTool,
-    GitHubReviewPRWithChecklistTool, GitHubUploadImageTool,
+    GitHubConnectTool, GitHubCreateGistTool, GitHubCreateIssueTool,
+    GitHubCreateIssueWithHashtagsTool, GitHubCreatePRTool, GitHubEditIssueTool, GitHubGetIssueTool,
+    GitHubGetPRDiffTool, GitHubGetPRTool, GitHubListIssuesTool, GitHubListOrgsTool,
+    GitHubListPRsTool, GitHubListReposTool, GitHubListReviewThreadsTool,
+    GitHubPostInlineCommentsTool, GitHubReplyCommentTool, GitHubReplyToReviewCommentsTool,
+    GitHubResolveReviewThreadTool, GitHubReviewPRTool, GitHubReviewPRWithChecklistTool,
+    GitHubUploadImageTool,
 };
 pub use github_push::GitHubPushTool;
 pub use github_read_repo::GitHubReadRepoTool;
@@ -95,29 +123,40 @@ pub use hardware_board_info::HardwareBoardInfoTool;
 pub use hardware_memory_map::HardwareMemoryMapTool;
 #[allow(unused_imports)]
 pub use hardware_memory_read::HardwareMemoryReadTool;
+pub use history_search::HistorySearchTool;
 pub use http_request::HttpRequestTool;
 pub use image_info::ImageInfoTool;
 pub use memory_forget::MemoryForgetTool;
 pub use memory_observe::MemoryObserveTool;
 pub use memory_recall::MemoryRecallTool;
+pub use memory_stats::MemoryStatsTool;
 pub use memory_store::MemoryStoreTool;
+pub use model_routing::ModelRoutingTool;
 pub use model_routing_config::ModelRoutingConfigTool;
 pub use pdf_read::PdfReadTool;
 pub use pptx_read::PptxReadTool;
+pub use preferences::PreferencesTool;
 pub use product_advisor::ProductAdvisorTool;
 pub use proxy_config::ProxyConfigTool;
 pub use pushover::PushoverTool;
 pub use sandbox::{
-    SandboxCreateTool, SandboxGetPackageManagerTool, SandboxGetPreviewUrlTool,
-    SandboxGetPublicUrlTool, SandboxKillTool, SandboxListFilesTool, SandboxReadFileTool,
-    SandboxRestoreSnapshotTool, SandboxRunCommandTool, SandboxSaveSnapshotTool,
-    SandboxWriteFileTool,
+    CheckpointTool, CodebaseSearchTool, GenerateDocsTool, GetLogTool, ProjectInfoTool,
+    SandboxCommandHistoryTool, SandboxCopyFileTool, SandboxCreateTool, SandboxDeletePathTool,
+    SandboxEditFileTool, SandboxGetPackageManagerTool, SandboxGetPreviewUrlTool,
+    SandboxGetPublicUrlTool, SandboxHttpLogTool, SandboxKillTool, SandboxListFilesTool,
+    SandboxMoveFileTool, SandboxPortsTool, SandboxPsTool, SandboxReadFileTool,
+    SandboxRestoreSnapshotTool, SandboxRunCommandTool, SandboxSaveSnapshotTool, SandboxSetEnvTool,
+    SandboxShellTool, SandboxWriteFileTool, SandboxWriteFilesTool, SetupProjectTool, WaitForTool,
 };
 pub use schedule::ScheduleTool;
 #[allow(unused_imports)]
 pub use schema::{CleaningStrategy, SchemaCleanr};
 pub use screenshot::ScreenshotTool;
 pub use shell::ShellTool;
+pub use ship_it::ShipItTool;
+pub use skill_save::SkillSaveTool;
+pub use switch_phase::SwitchPhaseTool;
+pub use think::ThinkTool;
 pub use traits::Tool;
 #[allow(unused_imports)]
 pub use traits::{ToolResult, ToolSpec};
@@ -128,6 +167,8 @@ use crate::config::{Config, DelegateAgentConfig};
 use crate::factory::FactoryOrchestratorTool;
 use crate::memory::Memory;
 use crate::runtime::{NativeRuntime, RuntimeAdapter};
+use crate::security::guardrails::{guard_tools, GuardrailPolicy};
+use crate::security::users::{meter_tools, UserPolicy};
 use crate::security::SecurityPolicy;
 use async_trait::async_trait;
 use std::collections::HashMap;
@@ -202,44 +243,174 @@ pub fn default_tools_with_runtime(
     ]
 }
 
-/// Create sandbox and deployment tools (local process sandbox + GitHub).
+/// Create the sandbox client shared by the sandbox tools and the factory.
 ///
 /// Uses `LocalProcessSandboxClient` — no external API key or Docker daemon required.
+pub fn sandbox_client() -> Arc<dyn crate::sandbox::SandboxClient> {
+    tracing::info!("Using local process sandbox provider");
+    Arc::new(crate::sandbox::local::LocalProcessSandboxClient::new())
+}
+
+/// Create the sandbox client for `config`: the first of `[zerobuild.sandbox]
+/// providers`, failing over to the next ones in order when it keeps failing.
+pub fn configured_sandbox_client(
+    config: &crate::config::ZerobuildConfig,
+) -> Arc<dyn crate::sandbox::SandboxClient> {
+    let providers: Vec<(String, Arc<dyn crate::sandbox::SandboxClient>)> = config
+        .sandbox
+        .providers
+        .iter()
+        .filter_map(|name| match name.as_str() {
+            "local" => Some((name.clone(), sandbox_client())),
+            "kubernetes" => Some((
+                name.clone(),
+                Arc::new(crate::sandbox::kubernetes::K8sSandboxClient::new(
+                    (*config.sandbox.kubernetes).clone(),
+                    config.templates.clone(),
+                )) as Arc<dyn crate::sandbox::SandboxClient>,
+            )),
+            "firecracker" => Some((
+                name.clone(),
+                Arc::new(crate::sandbox::firecracker::FirecrackerSandboxClient::new(
+                    (*config.sandbox.firecracker).clone(),
+                )) as Arc<dyn crate::sandbox::SandboxClient>,
+            )),
+            "fly" => Some((
+                name.clone(),
+                Arc::new(crate::sandbox::fly::FlySandboxClient::new(
+                    (*config.sandbox.fly).clone(),
+                    config.templates.clone(),
+                )) as Arc<dyn crate::sandbox::SandboxClient>,
+            )),
+            _ => {
+                tracing::warn!("Unknown sandbox provider '{name}' skipped");
+                None
+            }
+        })
+        .collect();
+    if providers.len() < 2 {
+        return providers
+            .into_iter()
+            .next()
+            .map_or_else(sandbox_client, |(_, client)| client);
+    }
+    Arc::new(crate::sandbox::failover::FailoverSandboxClient::new(
+        providers,
+        config.sandbox.failover_after_errors,
+        &config.db_path,
+    ))
+}
+
+/// Create sandbox and deployment tools (sandbox tools on `sandbox` + GitHub).
+///
+/// `embedder` is used for the snapshot code index behind `codebase_search`;
+/// `guardrails` also check the commands a repository's dev container runs.
 pub fn sandbox_tools(
+    sandbox: Arc<dyn crate::sandbox::SandboxClient>,
     zerobuild_config: Arc<crate::config::ZerobuildConfig>,
     _gateway_base_url: String,
+    embedder: Arc<dyn crate::memory::embeddings::EmbeddingProvider>,
+    guardrails: Option<Arc<GuardrailPolicy>>,
 ) -> Vec<Box<dyn Tool>> {
-    use crate::sandbox::local::LocalProcessSandboxClient;
-
-    tracing::info!("Using local process sandbox provider");
-    let sandbox: std::sync::Arc<dyn crate::sandbox::SandboxClient> =
-        Arc::new(LocalProcessSandboxClient::new());
-
     let db_path = std::path::PathBuf::from(&zerobuild_config.db_path);
+    let limits = zerobuild_config.sandbox.clone();
+    let artifacts = Arc::new(crate::artifacts::Artifacts::from_config(&zerobuild_config));
+    let templates = Arc::new(crate::sandbox::templates::TemplateRegistry::from_config(
+        &zerobuild_config.templates,
+    ));
+    let create_tool = || {
+        SandboxCreateTool::new(
+            sandbox.clone(),
+            zerobuild_config.default_template.trim(),
+            limits.create_timeout_secs.saturating_mul(1000),
+        )
+        .with_preferences(db_path.clone())
+    };
+    let command_tool = || {
+        SandboxRunCommandTool::with_limits(sandbox.clone(), &limits)
+            .with_log_store(db_path.clone())
+            .with_artifacts(artifacts.clone())
+    };
+    let http_capture = limits
+        .http_capture
+        .then(|| Arc::new(crate::sandbox::http_capture::HttpCapture::new()));
+    let shell_tool = limits.shell_sessions.then(|| {
+        SandboxShellTool::with_limits(sandbox.clone(), &limits)
+            .with_log_store(db_path.clone())
+            .with_artifacts(artifacts.clone())
+    });
 
-    vec![
-        Box::new(SandboxCreateTool::new(sandbox.clone(), "", 600_000)),
-        Box::new(SandboxRunCommandTool::new(sandbox.clone())),
+    let mut tools: Vec<Box<dyn Tool>> = vec![
+        Box::new(create_tool()),
+        Box::new(
+            SetupProjectTool::new(
+                sandbox.clone(),
+                create_tool(),
+                command_tool(),
+                SandboxSaveSnapshotTool::new(sandbox.clone(), db_path.clone())
+                    .with_embedder(embedder.clone())
+                    .with_artifacts(artifacts.clone()),
+            )
+            .with_templates(templates),
+        ),
+        Box::new(command_tool()),
+        Box::new(GetLogTool::new(db_path.clone())),
+        Box::new(SandboxCommandHistoryTool::new(
+            sandbox.clone(),
+            db_path.clone(),
+        )),
         Box::new(SandboxWriteFileTool::new(sandbox.clone())),
+        Box::new(SandboxWriteFilesTool::new(sandbox.clone())),
         Box::new(SandboxReadFileTool::new(sandbox.clone())),
+        Box::new(SandboxEditFileTool::new(sandbox.clone())),
+        Box::new(SandboxSetEnvTool::new(sandbox.clone(), db_path.clone())),
         Box::new(SandboxListFilesTool::new(sandbox.clone())),
-        Box::new(SandboxGetPreviewUrlTool::new(sandbox.clone())),
+        Box::new(SandboxMoveFileTool::new(sandbox.clone())),
+        Box::new(SandboxCopyFileTool::new(sandbox.clone())),
+        Box::new(SandboxDeletePathTool::new(sandbox.clone())),
+        Box::new(match &http_capture {
+            Some(capture) => {
+                SandboxGetPreviewUrlTool::new(sandbox.clone()).with_http_capture(capture.clone())
+            }
+            None => SandboxGetPreviewUrlTool::new(sandbox.clone()),
+        }),
         Box::new(SandboxGetPublicUrlTool::new(sandbox.clone())),
+        Box::new(SandboxPortsTool::new(sandbox.clone())),
+        Box::new(SandboxPsTool::new(sandbox.clone())),
+        Box::new(WaitForTool::new(sandbox.clone()).with_limits(&limits)),
         Box::new(SandboxGetPackageManagerTool::new(sandbox.clone())),
-        Box::new(SandboxSaveSnapshotTool::new(
-            sandbox.clone(),
-            db_path.clone(),
-        )),
-        Box::new(SandboxRestoreSnapshotTool::new(
-            sandbox.clone(),
-            db_path.clone(),
-        )),
-        Box::new(GitHubReadRepoTool::new(
-            sandbox.clone(),
-            zerobuild_config.clone(),
-        )),
+        Box::new(ProjectInfoTool::new(sandbox.clone())),
+        Box::new(GenerateDocsTool::new(sandbox.clone())),
+        Box::new(
+            SandboxSaveSnapshotTool::new(sandbox.clone(), db_path.clone())
+                .with_embedder(embedder.clone())
+                .with_artifacts(artifacts.clone()),
+        ),
+        Box::new(CodebaseSearchTool::new(db_path.clone()).with_embedder(embedder.clone())),
+        Box::new(
+            SandboxRestoreSnapshotTool::new(sandbox.clone(), db_path.clone())
+                .with_artifacts(artifacts.clone())
+                .with_limits(&limits),
+        ),
+        Box::new(CheckpointTool::new(db_path.clone())),
+        Box::new(
+            GitHubReadRepoTool::new(sandbox.clone(), zerobuild_config.clone())
+                .with_guardrails(guardrails.clone()),
+        ),
+        Box::new(
+            ShipItTool::new(
+                sandbox.clone(),
+                zerobuild_config.clone(),
+                command_tool(),
+                SandboxSaveSnapshotTool::new(sandbox.clone(), db_path.clone())
+                    .with_embedder(embedder)
+                    .with_artifacts(artifacts.clone()),
+            )
+            .with_guardrails(guardrails),
+        ),
         Box::new(SandboxKillTool::new(sandbox)),
         Box::new(GitHubPushTool::new(zerobuild_config.clone())),
+        Box::new(DeployChangesTool::new(zerobuild_config.clone())),
         Box::new(GitHubCreateIssueTool::new(zerobuild_config.clone())),
         Box::new(GitHubCreateIssueWithHashtagsTool::new(
             zerobuild_config.clone(),
@@ -252,6 +423,7 @@ pub fn sandbox_tools(
             zerobuild_config.clone(),
         )),
         Box::new(GitHubListReposTool::new(zerobuild_config.clone())),
+        Box::new(GitHubListOrgsTool::new(zerobuild_config.clone())),
         Box::new(GitHubListIssuesTool::new(zerobuild_config.clone())),
         Box::new(GitHubListPRsTool::new(zerobuild_config.clone())),
         Box::new(GitHubGetIssueTool::new(zerobuild_config.clone())),
@@ -262,9 +434,23 @@ pub fn sandbox_tools(
         Box::new(GitHubCommentIssueTool::new(zerobuild_config.clone())),
         Box::new(GitHubCommentPRTool::new(zerobuild_config.clone())),
         Box::new(GitHubReplyCommentTool::new(zerobuild_config.clone())),
+        Box::new(GitHubListReviewThreadsTool::new(zerobuild_config.clone())),
+        Box::new(GitHubReplyToReviewCommentsTool::new(
+            zerobuild_config.clone(),
+        )),
+        Box::new(GitHubResolveReviewThreadTool::new(zerobuild_config.clone())),
         Box::new(GitHubUploadImageTool::new(zerobuild_config.clone())),
+        Box::new(GitHubCreateGistTool::new(zerobuild_config.clone())),
         Box::new(GitHubConnectTool::new(zerobuild_config)),
-    ]
+    ];
+    if let Some(shell_tool) = shell_tool {
+        // Next to sandbox_run_command.
+        tools.insert(3, Box::new(shell_tool));
+    }
+    if let Some(capture) = http_capture {
+        tools.push(Box::new(SandboxHttpLogTool::new(capture)));
+    }
+    tools
 }
 
 /// Create full tool registry including memory tools and optional Composio
@@ -286,6 +472,7 @@ pub fn all_tools(
         config,
         security,
         Arc::new(NativeRuntime::new()),
+        Arc::clone(&memory),
         memory,
         composio_key,
         composio_entity_id,
@@ -299,12 +486,16 @@ pub fn all_tools(
 }
 
 /// Create full tool registry including memory tools and optional Composio.
+///
+/// `skill_memory` is where learned skills are saved. It must not be routed
+/// per user like a channel's `memory`, since skills are shared by everyone.
 #[allow(clippy::implicit_hasher, clippy::too_many_arguments)]
 pub fn all_tools_with_runtime(
     config: Arc<Config>,
     security: &Arc<SecurityPolicy>,
     runtime: Arc<dyn RuntimeAdapter>,
     memory: Arc<dyn Memory>,
+    skill_memory: Arc<dyn Memory>,
     composio_key: Option<&str>,
     composio_entity_id: Option<&str>,
     browser_config: &crate::config::BrowserConfig,
@@ -329,11 +520,22 @@ pub fn all_tools_with_runtime(
         Arc::new(MemoryStoreTool::new(memory.clone(), security.clone())),
         Arc::new(MemoryRecallTool::new(memory.clone())),
         Arc::new(MemoryForgetTool::new(Arc::clone(&memory), security.clone())),
+        Arc::new(HistorySearchTool::new(
+            &root_config.zerobuild.db_path,
+            Arc::clone(&memory),
+        )),
+        Arc::new(MemoryStatsTool::new(workspace_dir)),
+        Arc::new(SkillSaveTool::new(skill_memory, security.clone())),
         Arc::new(ScheduleTool::new(security.clone(), root_config.clone())),
         Arc::new(ModelRoutingConfigTool::new(
             config.clone(),
             security.clone(),
         )),
+        Arc::new(ModelRoutingTool::new(config.clone(), security.clone())),
+        Arc::new(PreferencesTool::new(
+            &root_config.zerobuild.db_path,
+            security.clone(),
+        )),
         Arc::new(ProxyConfigTool::new(config.clone(), security.clone())),
         Arc::new(GitOperationsTool::new(
             security.clone(),
@@ -410,7 +612,11 @@ pub fn all_tools_with_runtime(
     ))));
 
     // Vision tools are always available
-    tool_arcs.push(Arc::new(ScreenshotTool::new(security.clone())));
+    tool_arcs.push(Arc::new(
+        ScreenshotTool::new(security.clone()).with_artifacts(Arc::new(
+            crate::artifacts::Artifacts::from_config(&root_config.zerobuild),
+        )),
+    ));
     tool_arcs.push(Arc::new(ImageInfoTool::new(security.clone())));
 
     if let Some(key) = composio_key {
@@ -423,6 +629,60 @@ pub fn all_tools_with_runtime(
         }
     }
 
+    // Guardrails and execution limits wrap every tool, including the parent
+    // tools handed to delegate and factory sub-agents.
+    let guardrails = if root_config.guardrails.enabled {
+        match GuardrailPolicy::from_config(&root_config.guardrails) {
+            Ok(policy) => {
+                let policy = match root_config.config_path.parent() {
+                    Some(dir) => policy.with_audit_log(dir),
+                    None => policy,
+                };
+                Some(Arc::new(policy))
+            }
+            Err(e) => {
+                tracing::error!("Guardrails disabled: {e:#}");
+                None
+            }
+        }
+    } else {
+        None
+    };
+    // Timeouts and circuit breakers bound every call, sub-agents' included.
+    let limits = Arc::new(limits::ToolLimits::from_config(
+        &root_config.reliability.tools,
+        &root_config.zerobuild.sandbox,
+    ));
+    // Recovery playbooks run on the sandbox shared with the sandbox tools.
+    let zb_cfg = Arc::new(root_config.zerobuild.clone());
+    let sandbox = configured_sandbox_client(&zb_cfg);
+    let recovery = root_config.reliability.recovery.enabled.then(|| {
+        Arc::new(recovery::Recovery::new(
+            &root_config.reliability.recovery,
+            Arc::clone(&sandbox),
+            Arc::clone(&zb_cfg),
+        ))
+    });
+    // User roles and quotas apply to calls made on behalf of channel users.
+    let users = root_config.users.enabled.then(|| {
+        Arc::new(UserPolicy::new(
+            &root_config.users,
+            &root_config.zerobuild.db_path,
+        ))
+    });
+    let guarded = |tools: Vec<Arc<dyn Tool>>| {
+        let tools = match &guardrails {
+            Some(policy) => guard_tools(tools, policy),
+            None => tools,
+        };
+        let tools = recovery::recover_tools(limits::limit_tools(tools, &limits), recovery.as_ref());
+        let tools = crate::security::redaction::redact_tools(tools);
+        match &users {
+            Some(policy) => meter_tools(tools, policy),
+            None => tools,
+        }
+    };
+
     // Add delegation tool when agents are configured
     if !agents.is_empty() {
         let delegate_agents: HashMap<String, DelegateAgentConfig> = agents
@@ -433,7 +693,7 @@ pub fn all_tools_with_runtime(
             let trimmed_value = value.trim();
             (!trimmed_value.is_empty()).then(|| trimmed_value.to_owned())
         });
-        let parent_tools = Arc::new(tool_arcs.clone());
+        let parent_tools = Arc::new(guarded(tool_arcs.clone()));
         let delegate_tool = DelegateTool::new_with_options(
             delegate_agents,
             delegate_fallback_credential,
@@ -446,6 +706,8 @@ pub fn all_tools_with_runtime(
                     .map(std::path::PathBuf::from),
                 secrets_encrypt: root_config.secrets.encrypt,
                 reasoning_enabled: root_config.runtime.reasoning_enabled,
+                local_provider: root_config.local_provider.clone(),
+                prompt_caching: root_config.agent.prompt_caching,
             },
         )
         .with_parent_tools(parent_tools)
@@ -455,12 +717,24 @@ pub fn all_tools_with_runtime(
 
     // Add sandbox tools (E2B/Docker) and GitHub tools
     // These are available when user wants to build web apps or deploy
-    let zb_cfg = Arc::new(root_config.zerobuild.clone());
     let gateway_base_url = format!(
         "http://{}:{}",
         root_config.gateway.host, root_config.gateway.port
     );
-    let sandbox_and_deploy = sandbox_tools(zb_cfg, gateway_base_url);
+    let code_index_embedder: Arc<dyn crate::memory::embeddings::EmbeddingProvider> =
+        Arc::from(crate::memory::embeddings::create_embedding_provider(
+            &root_config.memory.embedding_provider,
+            root_config.api_key.as_deref(),
+            &root_config.memory.embedding_model,
+            root_config.memory.embedding_dimensions,
+        ));
+    let sandbox_and_deploy = sandbox_tools(
+        Arc::clone(&sandbox),
+        zb_cfg,
+        gateway_base_url,
+        code_index_embedder,
+        guardrails.clone(),
+    );
     for tool in sandbox_and_deploy {
         tool_arcs.push(Arc::from(tool));
     }
@@ -468,9 +742,16 @@ pub fn all_tools_with_runtime(
     // Add product advisor tool for generating improvement suggestions
     tool_arcs.push(Arc::new(ProductAdvisorTool::new(security.clone())));
 
+    // Phase-aware exposure: the planning phase reasons with `think`, and
+    // the model moves between phases with `switch_phase`.
+    if root_config.agent.phases.enabled {
+        tool_arcs.push(Arc::new(ThinkTool::new()));
+        tool_arcs.push(Arc::new(SwitchPhaseTool::new(&root_config.agent.phases)));
+    }
+
     // Add factory orchestrator tool when factory mode is enabled
     if root_config.factory.enabled {
-        let factory_parent_tools = Arc::new(tool_arcs.clone());
+        let factory_parent_tools = Arc::new(guarded(tool_arcs.clone()));
         let factory_fallback = root_config.api_key.as_deref().and_then(|k| {
             let trimmed = k.trim();
             (!trimmed.is_empty()).then(|| trimmed.to_owned())
@@ -478,7 +759,7 @@ pub fn all_tools_with_runtime(
 
         tracing::info!("Factory workflow enabled with dynamic spawning and progress streaming");
 
-        tool_arcs.push(Arc::new(crate::factory::FactoryOrchestratorTool::new(
+        let mut factory = crate::factory::FactoryOrchestratorTool::new(
             root_config.factory.max_ping_pong_iterations,
             root_config.factory.provider_overrides.clone(),
             crate::providers::ProviderRuntimeOptions {
@@ -489,6 +770,8 @@ pub fn all_tools_with_runtime(
                     .map(std::path::PathBuf::from),
                 secrets_encrypt: root_config.secrets.encrypt,
                 reasoning_enabled: root_config.runtime.reasoning_enabled,
+                local_provider: root_config.local_provider.clone(),
+                prompt_caching: root_config.agent.prompt_caching,
             },
             factory_fallback,
             root_config
@@ -502,10 +785,106 @@ pub fn all_tools_with_runtime(
             factory_parent_tools,
             root_config.multimodal.clone(),
             root_config.factory.enable_streaming,
+        )
+        .with_prompt_templates(
+            crate::agent::prompt_templates::PromptTemplates::for_workspace(workspace_dir)
+                .with_store(Arc::new(crate::store::SharedStore::new(
+                    &root_config.zerobuild.db_path,
+                ))),
+        )
+        .with_shared_memory(Arc::clone(&memory), root_config.factory.memory.clone())
+        .with_stages(root_config.factory.stages.clone())
+        .with_sandbox(Arc::clone(&sandbox))
+        .with_cost_estimate(
+            root_config.cost.prices.clone(),
+            root_config.factory.confirm_above_usd,
+            &root_config.zerobuild.db_path,
+        )
+        .with_journal(&root_config.zerobuild.db_path);
+
+        // A shared bus lets other processes see the blackboard and run stages.
+        let bus_config = &root_config.factory.bus;
+        let mut stage_bus = None;
+        if bus_config.backend.trim() != "memory" {
+            match crate::coordination::bus::connect(bus_config) {
+                Ok(bus) => {
+                    factory = factory.with_bus(Arc::clone(&bus), bus_config.clone());
+                    stage_bus = Some(bus);
+                }
+                Err(e) => tracing::warn!("Factory bus not used: {e:#}"),
+            }
+        }
+        let factory = Arc::new(factory);
+        if let Some(bus) = stage_bus.filter(|_| bus_config.workers > 0) {
+            if let Err(e) = crate::factory::StageWorkers::start_global(
+                bus,
+                bus_config.workers,
+                Arc::clone(&factory),
+            ) {
+                tracing::warn!("Factory stage workers not started: {e:#}");
+            }
+        }
+        let factory_tool: Arc<dyn Tool> = factory;
+        tool_arcs.push(Arc::clone(&factory_tool));
+        tool_arcs.push(Arc::new(BlackboardHistoryTool::new(
+            &root_config.zerobuild.db_path,
         )));
+
+        if !root_config.factory.a2a.peers.is_empty() {
+            tool_arcs.push(Arc::new(crate::factory::A2aDelegateTool::new(
+                &root_config.factory.a2a,
+                sandbox,
+                &root_config.zerobuild.db_path,
+            )));
+        }
+
+        let queue_config = &root_config.factory.queue;
+        if queue_config.enabled {
+            let db_path = std::path::PathBuf::from(&root_config.zerobuild.db_path);
+            let queue = match crate::factory::BuildQueue::start_global(
+                db_path.clone(),
+                queue_config.workers,
+                factory_tool,
+            ) {
+                Ok(queue) => Some(queue),
+                Err(e) => {
+                    tracing::warn!("Build queue workers not started: {e:#}");
+                    None
+                }
+            };
+            let issues_config = &root_config.factory.issues;
+            if issues_config.enabled {
+                match &queue {
+                    Some(queue) => {
+                        if let Err(e) = crate::factory::IssueWatcher::start_global(
+                            db_path.clone(),
+                            issues_config.clone(),
+                            Arc::clone(queue),
+                            crate::tools::github_access::RepoAccess::from_config(
+                                &root_config.zerobuild.repos,
+                            ),
+                        ) {
+                            tracing::warn!("Issue watcher not started: {e:#}");
+                        }
+                    }
+                    None => {
+                        tracing::warn!("Issue watcher not started: the build queue is not running");
+                    }
+                }
+            }
+            tool_arcs.push(Arc::new(crate::factory::BuildQueueTool::new(
+                db_path,
+                queue,
+                queue_config,
+            )));
+        } else if root_config.factory.issues.enabled {
+            tracing::warn!(
+                "[factory.issues] needs [factory.queue] enabled; issues are not watched"
+            );
+        }
     }
 
-    boxed_registry_from_arcs(tool_arcs)
+    boxed_registry_from_arcs(guarded(tool_arcs))
 }
 
 #[cfg(test)]
@@ -565,7 +944,10 @@ mod tests {
         let names: Vec<&str> = tools.iter().map(|t| t.name()).collect();
         assert!(!names.contains(&"browser_open"));
         assert!(names.contains(&"schedule"));
+        assert!(names.contains(&"skill_save"));
         assert!(names.contains(&"model_routing_config"));
+        assert!(names.contains(&"model_routing"));
+        assert!(names.contains(&"preferences"));
         assert!(names.contains(&"pushover"));
         assert!(names.contains(&"proxy_config"));
     }
@@ -606,6 +988,8 @@ mod tests {
         let names: Vec<&str> = tools.iter().map(|t| t.name()).collect();
         assert!(names.contains(&"browser_open"));
         assert!(names.contains(&"model_routing_config"));
+        assert!(names.contains(&"model_routing"));
+        assert!(names.contains(&"preferences"));
         assert!(names.contains(&"pushover"));
         assert!(names.contains(&"proxy_config"));
     }
@@ -672,6 +1056,7 @@ mod tests {
             success: true,
             output: "hello".into(),
             error: None,
+            error_code: None,
             error_hint: None,
         };
         let json = serde_json::to_string(&result).unwrap();
@@ -687,6 +1072,7 @@ mod tests {
             success: false,
             output: String::new(),
             error: Some("boom".into()),
+            error_code: None,
             error_hint: None,
         };
         let json = serde_json::to_string(&result).unwrap();
@@ -732,6 +1118,8 @@ mod tests {
                 system_prompt: None,
                 api_key: None,
                 temperature: None,
+                top_p: None,
+                seed: None,
                 max_depth: 3,
                 agentic: false,
                 allowed_tools: Vec::new(),
@@ -792,7 +1180,13 @@ mod tests {
     fn sandbox_tools_includes_get_package_manager() {
         use crate::config::ZerobuildConfig;
         let zb_cfg = Arc::new(ZerobuildConfig::default());
-        let tools = sandbox_tools(zb_cfg, "http://localhost:8080".into());
+        let tools = sandbox_tools(
+            sandbox_client(),
+            zb_cfg,
+            "http://localhost:8080".into(),
+            Arc::new(crate::memory::embeddings::NoopEmbedding),
+            None,
+        );
         let names: Vec<&str> = tools.iter().map(|t| t.name()).collect();
         assert!(
             names.contains(&"sandbox_get_package_manager"),
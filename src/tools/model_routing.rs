@@ -0,0 +1,291 @@
+use super::traits::{Tool, ToolResult};
+use crate::config::Config;
+use crate::security::SecurityPolicy;
+use crate::store::routes;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Per-hint model routes kept in the ZeroBuild store.
+///
+/// Stored routes replace `[[model_routes]]` entries with the same hint when
+/// providers are created (see [`crate::providers::effective_model_routes`]),
+/// so routing preferences survive restarts without editing `config.toml`.
+pub struct ModelRoutingTool {
+    config: Arc<Config>,
+    security: Arc<SecurityPolicy>,
+}
+
+impl ModelRoutingTool {
+    pub fn new(config: Arc<Config>, security: Arc<SecurityPolicy>) -> Self {
+        Self { config, security }
+    }
+
+    fn db_path(&self) -> PathBuf {
+        PathBuf::from(&self.config.zerobuild.db_path)
+    }
+
+    fn require_write_access(&self) -> Option<ToolResult> {
+        if !self.security.can_act() {
+            return Some(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some("Action blocked: autonomy is read-only".into()),
+                error_code: None,
+                error_hint: None,
+            });
+        }
+
+        if !self.security.record_action() {
+            return Some(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some("Action blocked: rate limit exceeded".into()),
+                error_code: None,
+                error_hint: None,
+            });
+        }
+
+        None
+    }
+
+    fn required_str<'a>(args: &'a Value, field: &str) -> anyhow::Result<&'a str> {
+        args.get(field)
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("Missing '{field}'"))
+    }
+
+    fn handle_list(&self) -> anyhow::Result<String> {
+        let conn = crate::store::init_db(&self.db_path())?;
+        let stored = routes::list_routes(&conn)?;
+        let effective = routes::merge_routes(&self.config.model_routes, &stored);
+        if effective.is_empty() {
+            return Ok(format!(
+                "No model routes. Every request uses the default model `{}`. \
+                 Add one with action 'set'.",
+                self.config.default_model.as_deref().unwrap_or("(unset)")
+            ));
+        }
+
+        let mut output = String::from("Model routes (request a route with model `hint:<name>`):\n");
+        for route in &effective {
+            let source = if stored.iter().any(|s| s.hint == route.hint) {
+                "user"
+            } else {
+                "config"
+            };
+            let _ = writeln!(
+                output,
+                "- {}: {} / {} ({source})",
+                route.hint, route.provider, route.model
+            );
+        }
+        Ok(output)
+    }
+
+    fn handle_set(&self, args: &Value) -> anyhow::Result<String> {
+        let hint = Self::required_str(args, "hint")?;
+        let raw_provider = Self::required_str(args, "provider")?;
+        let model = Self::required_str(args, "model")?;
+        let provider = crate::providers::resolve_provider_name(raw_provider).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Unknown provider '{raw_provider}'. Use a supported provider name or a \
+                 custom:<url> endpoint."
+            )
+        })?;
+
+        let conn = crate::store::init_db(&self.db_path())?;
+        routes::save_route(&conn, hint, &provider, model)?;
+        Ok(format!(
+            "Route '{hint}' now uses {provider} / {model}. It applies to agent sessions \
+             started from now on."
+        ))
+    }
+
+    fn handle_delete(&self, args: &Value) -> anyhow::Result<String> {
+        let hint = Self::required_str(args, "hint")?;
+        let conn = crate::store::init_db(&self.db_path())?;
+        if !routes::delete_route(&conn, hint)? {
+            anyhow::bail!(
+                "No stored route for '{hint}'. Routes in config.toml are changed with \
+                 model_routing_config."
+            );
+        }
+        let output = match self.config.model_routes.iter().find(|r| r.hint == hint) {
+            Some(route) => format!(
+                "Deleted the stored route '{hint}'; config.toml routes it to {} / {} again.",
+                route.provider, route.model
+            ),
+            None => format!("Deleted the stored route '{hint}'."),
+        };
+        Ok(output)
+    }
+}
+
+#[async_trait]
+impl Tool for ModelRoutingTool {
+    fn name(&self) -> &str {
+        "model_routing"
+    }
+
+    fn description(&self) -> &str {
+        "List, set or delete the user's model routes: which provider and model handle a task hint (e.g. code, reasoning, fast). Stored routes override config.toml routes with the same hint and persist across restarts."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["list", "set", "delete"],
+                    "default": "list"
+                },
+                "hint": {
+                    "type": "string",
+                    "description": "Task hint the route applies to (for set/delete)"
+                },
+                "provider": {
+                    "type": "string",
+                    "description": "Provider name or alias, or custom:<url> (for set)"
+                },
+                "model": {
+                    "type": "string",
+                    "description": "Model ID for that provider (for set)"
+                }
+            },
+            "additionalProperties": false
+        })
+    }
+
+    async fn execute(&self, args: Value) -> anyhow::Result<ToolResult> {
+        let action = args
+            .get("action")
+            .and_then(Value::as_str)
+            .unwrap_or("list")
+            .to_ascii_lowercase();
+
+        let result = match action.as_str() {
+            "list" => self.handle_list(),
+            "set" | "delete" => {
+                if let Some(blocked) = self.require_write_access() {
+                    return Ok(blocked);
+                }
+                if action == "set" {
+                    self.handle_set(&args)
+                } else {
+                    self.handle_delete(&args)
+                }
+            }
+            _ => anyhow::bail!("Unknown action '{action}'. Valid: list, set, delete"),
+        };
+
+        match result {
+            Ok(output) => Ok(ToolResult {
+                success: true,
+                output,
+                error: None,
+                error_code: None,
+                error_hint: None,
+            }),
+            Err(error) => Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(error.to_string()),
+                error_code: None,
+                error_hint: None,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ModelRouteConfig;
+    use crate::security::{AutonomyLevel, SecurityPolicy};
+    use tempfile::TempDir;
+
+    fn test_tool(tmp: &TempDir, autonomy: AutonomyLevel) -> ModelRoutingTool {
+        let mut config = Config {
+            workspace_dir: tmp.path().join("workspace"),
+            config_path: tmp.path().join("config.toml"),
+            model_routes: vec![ModelRouteConfig {
+                hint: "reasoning".into(),
+                provider: "openrouter".into(),
+                model: "anthropic/claude-opus-4".into(),
+                api_key: None,
+                temperature: None,
+                top_p: None,
+                seed: None,
+            }],
+            ..Config::default()
+        };
+        config.zerobuild.db_path = tmp.path().join("zerobuild.db").display().to_string();
+        let security = SecurityPolicy {
+            autonomy,
+            workspace_dir: std::env::temp_dir(),
+            ..SecurityPolicy::default()
+        };
+        ModelRoutingTool::new(Arc::new(config), Arc::new(security))
+    }
+
+    #[tokio::test]
+    async fn set_list_and_delete_routes() {
+        let tmp = TempDir::new().unwrap();
+        let tool = test_tool(&tmp, AutonomyLevel::Supervised);
+
+        let result = tool
+            .execute(json!({ "action": "set", "hint": "reasoning", "provider": "OpenAI", "model": "o3" }))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+
+        let listed = tool.execute(json!({})).await.unwrap().output;
+        assert!(listed.contains("- reasoning: openai / o3 (user)"));
+        let config = Config {
+            model_routes: tool.config.model_routes.clone(),
+            zerobuild: tool.config.zerobuild.clone(),
+            ..Config::default()
+        };
+        let effective = crate::providers::effective_model_routes(&config);
+        assert_eq!(effective.len(), 1);
+        assert_eq!(effective[0].model, "o3");
+
+        let result = tool
+            .execute(json!({ "action": "delete", "hint": "reasoning" }))
+            .await
+            .unwrap();
+        assert!(result
+            .output
+            .contains("openrouter / anthropic/claude-opus-4"));
+        let listed = tool
+            .execute(json!({ "action": "list" }))
+            .await
+            .unwrap()
+            .output;
+        assert!(listed.contains("(config)"));
+    }
+
+    #[tokio::test]
+    async fn rejects_unknown_providers_and_read_only_writes() {
+        let tmp = TempDir::new().unwrap();
+        let tool = test_tool(&tmp, AutonomyLevel::Supervised);
+        let result = tool
+            .execute(json!({ "action": "set", "hint": "code", "provider": "nope", "model": "x" }))
+            .await
+            .unwrap();
+        assert!(result.error.unwrap().contains("Unknown provider"));
+
+        let tool = test_tool(&tmp, AutonomyLevel::ReadOnly);
+        let result = tool
+            .execute(json!({ "action": "delete", "hint": "code" }))
+            .await
+            .unwrap();
+        assert!(result.error.unwrap().contains("read-only"));
+    }
+}
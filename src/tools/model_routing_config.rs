@@ -46,6 +46,7 @@ impl ModelRoutingConfigTool {
                 success: false,
                 output: String::new(),
                 error: Some("Action blocked: autonomy is read-only".into()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -55,6 +56,7 @@ impl ModelRoutingConfigTool {
                 success: false,
                 output: String::new(),
                 error: Some("Action blocked: rate limit exceeded".into()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -328,6 +330,7 @@ impl ModelRoutingConfigTool {
             success: true,
             output: serde_json::to_string_pretty(&Self::snapshot(&cfg))?,
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
@@ -374,6 +377,7 @@ impl ModelRoutingConfigTool {
                 }
             }))?,
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
@@ -427,6 +431,7 @@ impl ModelRoutingConfigTool {
                 "config": Self::snapshot(&cfg),
             }))?,
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
@@ -472,6 +477,9 @@ impl ModelRoutingConfigTool {
             provider: provider.clone(),
             model: model.clone(),
             api_key: None,
+            temperature: None,
+            top_p: None,
+            seed: None,
         });
 
         next_route.hint = hint.clone();
@@ -561,6 +569,7 @@ impl ModelRoutingConfigTool {
                 "config": Self::snapshot(&cfg),
             }))?,
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
@@ -607,6 +616,7 @@ impl ModelRoutingConfigTool {
                 "config": Self::snapshot(&cfg),
             }))?,
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
@@ -641,6 +651,8 @@ impl ModelRoutingConfigTool {
                 system_prompt: None,
                 api_key: None,
                 temperature: None,
+                top_p: None,
+                seed: None,
                 max_depth: DEFAULT_AGENT_MAX_DEPTH,
                 agentic: false,
                 allowed_tools: Vec::new(),
@@ -718,6 +730,7 @@ impl ModelRoutingConfigTool {
                 "config": Self::snapshot(&cfg),
             }))?,
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
@@ -740,6 +753,7 @@ impl ModelRoutingConfigTool {
                 "config": Self::snapshot(&cfg),
             }))?,
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
@@ -901,6 +915,7 @@ impl Tool for ModelRoutingConfigTool {
                 success: false,
                 output: String::new(),
                 error: Some(error.to_string()),
+                error_code: None,
                 error_hint: None,
             }),
         }
@@ -80,6 +80,7 @@ impl Tool for PdfReadTool {
                 success: false,
                 output: String::new(),
                 error: Some("Rate limit exceeded: too many actions in the last hour".into()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -89,6 +90,7 @@ impl Tool for PdfReadTool {
                 success: false,
                 output: String::new(),
                 error: Some(format!("Path not allowed by security policy: {path}")),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -99,6 +101,7 @@ impl Tool for PdfReadTool {
                 success: false,
                 output: String::new(),
                 error: Some("Rate limit exceeded: action budget exhausted".into()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -112,6 +115,7 @@ impl Tool for PdfReadTool {
                     success: false,
                     output: String::new(),
                     error: Some(format!("Failed to resolve file path: {e}")),
+                    error_code: None,
                     error_hint: None,
                 });
             }
@@ -125,6 +129,7 @@ impl Tool for PdfReadTool {
                     self.security
                         .resolved_path_violation_message(&resolved_path),
                 ),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -141,6 +146,7 @@ impl Tool for PdfReadTool {
                             "PDF too large: {} bytes (limit: {MAX_PDF_BYTES} bytes)",
                             meta.len()
                         )),
+                        error_code: None,
                         error_hint: None,
                     });
                 }
@@ -150,6 +156,7 @@ impl Tool for PdfReadTool {
                     success: false,
                     output: String::new(),
                     error: Some(format!("Failed to read file metadata: {e}")),
+                    error_code: None,
                     error_hint: None,
                 });
             }
@@ -162,6 +169,7 @@ impl Tool for PdfReadTool {
                     success: false,
                     output: String::new(),
                     error: Some(format!("Failed to read PDF file: {e}")),
+                    error_code: None,
                     error_hint: None,
                 });
             }
@@ -181,6 +189,7 @@ impl Tool for PdfReadTool {
                         success: false,
                         output: String::new(),
                         error: Some(format!("PDF extraction failed: {e}")),
+                        error_code: None,
                         error_hint: None,
                     });
                 }
@@ -189,6 +198,7 @@ impl Tool for PdfReadTool {
                         success: false,
                         output: String::new(),
                         error: Some(format!("PDF extraction task panicked: {e}")),
+                        error_code: None,
                         error_hint: None,
                     });
                 }
@@ -202,6 +212,7 @@ impl Tool for PdfReadTool {
                     output: "PDF contains no extractable text (may be image-only or encrypted)"
                         .into(),
                     error: None,
+                    error_code: None,
                     error_hint: None,
                 });
             }
@@ -219,6 +230,7 @@ impl Tool for PdfReadTool {
                 success: true,
                 output,
                 error: None,
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -235,6 +247,7 @@ impl Tool for PdfReadTool {
                      Rebuild with: cargo build --features rag-pdf"
                         .into(),
                 ),
+                error_code: None,
                 error_hint: None,
             })
         }
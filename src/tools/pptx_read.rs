@@ -350,6 +350,7 @@ impl Tool for PptxReadTool {
                     success: false,
                     output: String::new(),
                     error: Some(err.to_string()),
+                    error_code: None,
                     error_hint: None,
                 })
             }
@@ -360,6 +361,7 @@ impl Tool for PptxReadTool {
                 success: false,
                 output: String::new(),
                 error: Some("Rate limit exceeded: too many actions in the last hour".into()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -369,6 +371,7 @@ impl Tool for PptxReadTool {
                 success: false,
                 output: String::new(),
                 error: Some(format!("Path not allowed by security policy: {path}")),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -378,6 +381,7 @@ impl Tool for PptxReadTool {
                 success: false,
                 output: String::new(),
                 error: Some("Rate limit exceeded: action budget exhausted".into()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -391,6 +395,7 @@ impl Tool for PptxReadTool {
                     success: false,
                     output: String::new(),
                     error: Some(format!("Failed to resolve file path: {e}")),
+                    error_code: None,
                     error_hint: None,
                 });
             }
@@ -404,6 +409,7 @@ impl Tool for PptxReadTool {
                     self.security
                         .resolved_path_violation_message(&resolved_path),
                 ),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -420,6 +426,7 @@ impl Tool for PptxReadTool {
                             "PPTX too large: {} bytes (limit: {MAX_PPTX_BYTES} bytes)",
                             meta.len()
                         )),
+                        error_code: None,
                         error_hint: None,
                     });
                 }
@@ -429,6 +436,7 @@ impl Tool for PptxReadTool {
                     success: false,
                     output: String::new(),
                     error: Some(format!("Failed to read file metadata: {e}")),
+                    error_code: None,
                     error_hint: None,
                 });
             }
@@ -441,6 +449,7 @@ impl Tool for PptxReadTool {
                     success: false,
                     output: String::new(),
                     error: Some(format!("Failed to read PPTX file: {e}")),
+                    error_code: None,
                     error_hint: None,
                 });
             }
@@ -453,6 +462,7 @@ impl Tool for PptxReadTool {
                     success: false,
                     output: String::new(),
                     error: Some(format!("PPTX extraction failed: {e}")),
+                    error_code: None,
                     error_hint: None,
                 });
             }
@@ -461,6 +471,7 @@ impl Tool for PptxReadTool {
                     success: false,
                     output: String::new(),
                     error: Some(format!("PPTX extraction task panicked: {e}")),
+                    error_code: None,
                     error_hint: None,
                 });
             }
@@ -471,6 +482,7 @@ impl Tool for PptxReadTool {
                 success: true,
                 output: "PPTX contains no extractable text".into(),
                 error: None,
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -488,6 +500,7 @@ impl Tool for PptxReadTool {
             success: true,
             output,
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
@@ -0,0 +1,229 @@
+use super::traits::{Tool, ToolResult};
+use crate::security::SecurityPolicy;
+use crate::store::preferences;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// The user's generation defaults kept in the ZeroBuild store.
+///
+/// Stored preferences are added to the system prompt of every conversation
+/// and to the `sandbox_create` output (see [`crate::store::preferences`]), so
+/// they apply to new projects without being repeated in each request.
+pub struct PreferencesTool {
+    db_path: PathBuf,
+    security: Arc<SecurityPolicy>,
+}
+
+impl PreferencesTool {
+    pub fn new(db_path: impl Into<PathBuf>, security: Arc<SecurityPolicy>) -> Self {
+        Self {
+            db_path: db_path.into(),
+            security,
+        }
+    }
+
+    fn require_write_access(&self) -> Option<ToolResult> {
+        if !self.security.can_act() {
+            return Some(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some("Action blocked: autonomy is read-only".into()),
+                error_code: None,
+                error_hint: None,
+            });
+        }
+
+        if !self.security.record_action() {
+            return Some(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some("Action blocked: rate limit exceeded".into()),
+                error_code: None,
+                error_hint: None,
+            });
+        }
+
+        None
+    }
+
+    fn required_str<'a>(args: &'a Value, field: &str) -> anyhow::Result<&'a str> {
+        args.get(field)
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("Missing '{field}'"))
+    }
+
+    fn handle_list(&self) -> anyhow::Result<String> {
+        let conn = crate::store::init_db(&self.db_path)?;
+        let stored = preferences::list(&conn, &preferences::current_user())?;
+        let mut output = if stored.is_empty() {
+            String::from("No preferences set.\n")
+        } else {
+            format!("Preferences:\n{}", preferences::format_lines(&stored))
+        };
+        output.push_str("\nAvailable keys:\n");
+        for (key, _, values) in preferences::PREFERENCES {
+            let _ = writeln!(output, "- {key}: {values}");
+        }
+        Ok(output)
+    }
+
+    fn handle_set(&self, args: &Value) -> anyhow::Result<String> {
+        let key = Self::required_str(args, "key")?;
+        let value = Self::required_str(args, "value")?;
+        let conn = crate::store::init_db(&self.db_path)?;
+        let (key, value) = preferences::save(&conn, &preferences::current_user(), key, value)?;
+        Ok(format!(
+            "Preference '{key}' set to '{value}'. It applies from the next message on."
+        ))
+    }
+
+    fn handle_delete(&self, args: &Value) -> anyhow::Result<String> {
+        let key = Self::required_str(args, "key")?;
+        let conn = crate::store::init_db(&self.db_path)?;
+        if !preferences::delete(&conn, &preferences::current_user(), key)? {
+            anyhow::bail!("Preference '{key}' is not set.");
+        }
+        Ok(format!("Deleted the preference '{key}'."))
+    }
+}
+
+#[async_trait]
+impl Tool for PreferencesTool {
+    fn name(&self) -> &str {
+        "preferences"
+    }
+
+    fn description(&self) -> &str {
+        "List, set or delete the user's generation defaults: package_manager, css_framework, language, code_style, license and deploy_target. Stored preferences are applied to every new project and persist across restarts. Use when the user states a lasting preference (\"always use pnpm\")."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        let keys: Vec<&str> = preferences::PREFERENCES
+            .iter()
+            .map(|(key, _, _)| *key)
+            .collect();
+        json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["list", "set", "delete"],
+                    "default": "list"
+                },
+                "key": {
+                    "type": "string",
+                    "enum": keys,
+                    "description": "Preference to change (for set/delete)"
+                },
+                "value": {
+                    "type": "string",
+                    "description": "New value (for set)"
+                }
+            },
+            "additionalProperties": false
+        })
+    }
+
+    async fn execute(&self, args: Value) -> anyhow::Result<ToolResult> {
+        let action = args
+            .get("action")
+            .and_then(Value::as_str)
+            .unwrap_or("list")
+            .to_ascii_lowercase();
+
+        let result = match action.as_str() {
+            "list" => self.handle_list(),
+            "set" | "delete" => {
+                if let Some(blocked) = self.require_write_access() {
+                    return Ok(blocked);
+                }
+                if action == "set" {
+                    self.handle_set(&args)
+                } else {
+                    self.handle_delete(&args)
+                }
+            }
+            _ => anyhow::bail!("Unknown action '{action}'. Valid: list, set, delete"),
+        };
+
+        match result {
+            Ok(output) => Ok(ToolResult {
+                success: true,
+                output,
+                error: None,
+                error_code: None,
+                error_hint: None,
+            }),
+            Err(error) => Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(error.to_string()),
+                error_code: None,
+                error_hint: None,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::AutonomyLevel;
+    use tempfile::TempDir;
+
+    fn test_tool(tmp: &TempDir, autonomy: AutonomyLevel) -> PreferencesTool {
+        let security = SecurityPolicy {
+            autonomy,
+            workspace_dir: std::env::temp_dir(),
+            ..SecurityPolicy::default()
+        };
+        PreferencesTool::new(tmp.path().join("zerobuild.db"), Arc::new(security))
+    }
+
+    #[tokio::test]
+    async fn set_list_and_delete_preferences() {
+        let tmp = TempDir::new().unwrap();
+        let tool = test_tool(&tmp, AutonomyLevel::Supervised);
+
+        let result = tool
+            .execute(json!({ "action": "set", "key": "package_manager", "value": "PNPM" }))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+
+        let listed = tool.execute(json!({})).await.unwrap().output;
+        assert!(listed.contains("- Package manager: pnpm"));
+        assert!(listed.contains("- deploy_target:"));
+
+        let result = tool
+            .execute(json!({ "action": "set", "key": "license", "value": "WTFPL" }))
+            .await
+            .unwrap();
+        assert!(result.error.unwrap().contains("Unsupported license"));
+
+        let result = tool
+            .execute(json!({ "action": "delete", "key": "package_manager" }))
+            .await
+            .unwrap();
+        assert!(result.success);
+        let listed = tool.execute(json!({})).await.unwrap().output;
+        assert!(listed.starts_with("No preferences set."));
+    }
+
+    #[tokio::test]
+    async fn read_only_autonomy_blocks_changes() {
+        let tmp = TempDir::new().unwrap();
+        let tool = test_tool(&tmp, AutonomyLevel::ReadOnly);
+        let result = tool
+            .execute(json!({ "action": "set", "key": "language", "value": "typescript" }))
+            .await
+            .unwrap();
+        assert!(result.error.unwrap().contains("read-only"));
+        assert!(tool.execute(json!({})).await.unwrap().success);
+    }
+}
@@ -239,6 +239,7 @@ impl Tool for ProductAdvisorTool {
             success: true,
             output,
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
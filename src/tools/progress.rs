@@ -0,0 +1,181 @@
+//! Progress messages from long-running tools to the user's channel.
+//!
+//! The agent loop already reports when a tool starts and finishes. A tool
+//! that runs for minutes (a dependency install, a production build) can send
+//! intermediate lines with [`report_progress`] while it works; inside
+//! [`with_tool_progress`] they go to the same draft or progress channel, so
+//! users see it isn't stuck and channel webhooks see activity.
+//!
+//! Composite tools that run several steps report each one through
+//! [`Steps`].
+//!
+//! Factory builds also report structured [`BuildStatus`] changes inside
+//! [`with_build_status`], for channels that keep a live status message.
+//!
+//! Status changes, snapshot diffs and deploys are also published
+//! process-wide as [`Activity`], which observer channels
+//! (`[[channels_config.observers]]`) subscribe to.
+
+use crate::tools::traits::ToolResult;
+use std::fmt::Write;
+use std::future::Future;
+use std::sync::OnceLock;
+use std::time::Instant;
+use tokio::sync::broadcast;
+use tokio::sync::mpsc::Sender;
+
+tokio::task_local! {
+    static TOOL_PROGRESS: Sender<String>;
+    static BUILD_STATUS: Sender<BuildStatus>;
+}
+
+/// A change in a running build's status.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuildStatus {
+    /// A stage started, e.g. `Integration Loop — Developer fixing tests`.
+    Stage(String),
+    /// The app's preview is served at this URL.
+    Preview(String),
+    /// The build finished.
+    Finished { success: bool },
+}
+
+/// Build activity mirrored to observer channels.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Activity {
+    /// A build status change.
+    Status(BuildStatus),
+    /// Files of `project` changed since its previous snapshot, as listed by
+    /// [`crate::tools::changelog::describe`].
+    Diff { project: String, changes: String },
+    /// A deploy to `target` (e.g. `GitHub`, `Vercel`) reachable at `url`.
+    Deploy { target: String, url: String },
+}
+
+/// Activity events kept for a slow subscriber before it starts missing them.
+const ACTIVITY_BUFFER: usize = 64;
+
+fn activity() -> &'static broadcast::Sender<Activity> {
+    static ACTIVITY: OnceLock<broadcast::Sender<Activity>> = OnceLock::new();
+    ACTIVITY.get_or_init(|| broadcast::channel(ACTIVITY_BUFFER).0)
+}
+
+/// Receive the activity published from now on.
+pub fn subscribe_activity() -> broadcast::Receiver<Activity> {
+    activity().subscribe()
+}
+
+/// Whether anyone receives [`report_activity`] events, so callers can skip
+/// preparing them.
+pub fn has_activity_subscribers() -> bool {
+    activity().receiver_count() > 0
+}
+
+/// Publish an activity event; dropped when nobody subscribes.
+pub fn report_activity(event: Activity) {
+    let _ = activity().send(event);
+}
+
+/// Run `fut` with `tx` receiving the progress lines its tools report.
+pub async fn with_tool_progress<F: Future>(tx: Sender<String>, fut: F) -> F::Output {
+    TOOL_PROGRESS.scope(tx, fut).await
+}
+
+/// Whether a caller up the stack is listening for progress.
+pub fn is_reporting() -> bool {
+    TOOL_PROGRESS.try_with(|_| ()).is_ok()
+}
+
+/// Send one progress line. Dropped when nobody listens or the channel is
+/// full; progress is best effort and must never slow the tool down.
+pub fn report_progress(line: impl Into<String>) {
+    let mut line = line.into();
+    if !line.ends_with('\n') {
+        line.push('\n');
+    }
+    let _ = TOOL_PROGRESS.try_with(|tx| tx.try_send(line));
+}
+
+/// Run `fut` with `tx`, when given, receiving the build status changes its
+/// tools report.
+pub async fn with_build_status<F: Future>(tx: Option<Sender<BuildStatus>>, fut: F) -> F::Output {
+    match tx {
+        Some(tx) => BUILD_STATUS.scope(tx, fut).await,
+        None => fut.await,
+    }
+}
+
+/// Send a build status change. Dropped like [`report_progress`] lines when
+/// nobody listens or the channel is full.
+///
+/// Also published as [`Activity::Status`].
+pub fn report_build_status(status: BuildStatus) {
+    if has_activity_subscribers() {
+        report_activity(Activity::Status(status.clone()));
+    }
+    let _ = BUILD_STATUS.try_with(|tx| tx.try_send(status));
+}
+
+/// Numbered steps of a composite tool, reported as `<tool> [n/total] …`
+/// when they start and end and listed with their outcome.
+pub struct Steps {
+    tool: &'static str,
+    total: usize,
+    lines: Vec<String>,
+}
+
+impl Steps {
+    pub fn new(tool: &'static str, total: usize) -> Self {
+        Self {
+            tool,
+            total,
+            lines: Vec::new(),
+        }
+    }
+
+    /// Run step `label`, reporting when it starts and how it ended.
+    pub async fn run(&mut self, label: &str, step: impl Future<Output = ToolResult>) -> ToolResult {
+        let number = self.lines.len() + 1;
+        report_progress(format!("{} [{number}/{}] {label}…", self.tool, self.total));
+        let started = Instant::now();
+        let result = step.await;
+        let mark = if result.success { "✓" } else { "✗" };
+        let line = format!("{mark} {label} ({:.1}s)", started.elapsed().as_secs_f64());
+        report_progress(format!("{} [{number}/{}] {line}", self.tool, self.total));
+        self.lines.push(line);
+        result
+    }
+
+    /// Record step `label` as skipped for `reason`.
+    pub fn skip(&mut self, label: &str, reason: &str) {
+        let number = self.lines.len() + 1;
+        let line = format!("- {label}: skipped, {reason}");
+        report_progress(format!("{} [{number}/{}] {line}", self.tool, self.total));
+        self.lines.push(line);
+    }
+
+    /// One line per step so far.
+    pub fn summary(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    /// The tool stopped at step `label` with `result`; `hint` says how to
+    /// go on.
+    pub fn stopped(self, label: &str, result: ToolResult, hint: &str) -> ToolResult {
+        let mut output = self.summary();
+        if !result.output.is_empty() {
+            let _ = write!(output, "\n\n{label} output:\n{}", result.output);
+        }
+        ToolResult {
+            success: false,
+            output,
+            error: Some(format!(
+                "{} stopped at '{label}': {}",
+                self.tool,
+                result.error.as_deref().unwrap_or("step failed")
+            )),
+            error_code: result.error_code,
+            error_hint: Some(hint.to_string()),
+        }
+    }
+}
@@ -44,6 +44,7 @@ impl ProxyConfigTool {
                 success: false,
                 output: String::new(),
                 error: Some("Action blocked: autonomy is read-only".into()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -53,6 +54,7 @@ impl ProxyConfigTool {
                 success: false,
                 output: String::new(),
                 error: Some("Action blocked: rate limit exceeded".into()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -151,6 +153,7 @@ impl ProxyConfigTool {
                 "environment": Self::env_snapshot(),
             }))?,
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
@@ -168,6 +171,7 @@ impl ProxyConfigTool {
                 }
             }))?,
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
@@ -266,6 +270,7 @@ impl ProxyConfigTool {
                 "environment": Self::env_snapshot(),
             }))?,
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
@@ -294,6 +299,7 @@ impl ProxyConfigTool {
                 "environment": Self::env_snapshot(),
             }))?,
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
@@ -325,6 +331,7 @@ impl ProxyConfigTool {
                 "environment": Self::env_snapshot(),
             }))?,
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
@@ -338,6 +345,7 @@ impl ProxyConfigTool {
                 "environment": Self::env_snapshot(),
             }))?,
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
@@ -438,6 +446,7 @@ impl Tool for ProxyConfigTool {
                 success: false,
                 output: String::new(),
                 error: Some(error.to_string()),
+                error_code: None,
                 error_hint: None,
             }),
         }
@@ -117,6 +117,7 @@ impl Tool for PushoverTool {
                 success: false,
                 output: String::new(),
                 error: Some("Action blocked: autonomy is read-only".into()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -126,6 +127,7 @@ impl Tool for PushoverTool {
                 success: false,
                 output: String::new(),
                 error: Some("Action blocked: rate limit exceeded".into()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -149,6 +151,7 @@ impl Tool for PushoverTool {
                     error: Some(format!(
                         "Invalid 'priority': {value}. Expected integer in range -2..=2"
                     )),
+                    error_code: None,
                     error_hint: None,
                 })
             }
@@ -191,6 +194,7 @@ impl Tool for PushoverTool {
                 success: false,
                 output: body,
                 error: Some(format!("Pushover API returned status {}", status)),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -207,6 +211,7 @@ impl Tool for PushoverTool {
                     body
                 ),
                 error: None,
+                error_code: None,
                 error_hint: None,
             })
         } else {
@@ -214,6 +219,7 @@ impl Tool for PushoverTool {
                 success: false,
                 output: body,
                 error: Some("Pushover API returned an application-level error".into()),
+                error_code: None,
                 error_hint: None,
             })
         }
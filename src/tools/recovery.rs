@@ -0,0 +1,346 @@
+//! Automatic recovery playbooks (`[reliability.recovery]`).
+//!
+//! Every registry tool is wrapped in a [`RecoveringTool`]. When a call fails
+//! with an error code that has a playbook, the playbook runs before the
+//! failure reaches the model:
+//!
+//! | Code              | Tools                     | Playbook                                       |
+//! |-------------------|---------------------------|------------------------------------------------|
+//! | `sandbox_expired` | sandbox-backed tools      | recreate the sandbox, restore the last snapshot, retry |
+//! | `disk_full`       | `sandbox_*`               | clean package-manager and build caches, retry  |
+//! | `auth_failed`     | `github_*`                | clear the rejected token, ask the user to reconnect |
+//!
+//! A call is retried at most once. When the playbook itself fails, or cannot
+//! fix the failure on its own, what it did is appended to the error so the
+//! model can explain it to the user instead of retrying blindly.
+
+use super::traits::{Tool, ToolResult};
+use crate::config::{RecoveryConfig, ZerobuildConfig};
+use crate::error::{self, ErrorCode};
+use crate::sandbox::project_config::PROJECT_DIR;
+use crate::sandbox::SandboxClient;
+use async_trait::async_trait;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Frees disk space in the project without touching sources or lockfiles.
+/// Each step may fail when its package manager is not installed.
+const CLEAN_CACHES_COMMAND: &str = "npm cache clean --force 2>/dev/null; \
+     pnpm store prune 2>/dev/null; \
+     yarn cache clean 2>/dev/null; \
+     rm -rf node_modules/.cache .next/cache .turbo; true";
+
+const CLEAN_CACHES_TIMEOUT_MS: u64 = 120_000;
+
+/// Remediation for one kind of failure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Playbook {
+    RecreateSandbox,
+    CleanCaches,
+    ReconnectGitHub,
+}
+
+impl Playbook {
+    /// Playbook for a `tool` call that failed with `code`.
+    fn for_failure(code: ErrorCode, tool: &str) -> Option<Self> {
+        match code {
+            ErrorCode::SandboxExpired if !matches!(tool, "sandbox_create" | "sandbox_kill") => {
+                Some(Self::RecreateSandbox)
+            }
+            ErrorCode::DiskFull if tool.starts_with("sandbox_") => Some(Self::CleanCaches),
+            ErrorCode::AuthFailed if tool.starts_with("github_") && tool != "github_connect" => {
+                Some(Self::ReconnectGitHub)
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether the failed call can succeed once the playbook ran. A GitHub
+    /// reconnect needs the user, so that call is not retried.
+    fn retries(self) -> bool {
+        !matches!(self, Self::ReconnectGitHub)
+    }
+}
+
+/// Runs playbooks against the session's sandbox and GitHub connection.
+pub struct Recovery {
+    sandbox: Arc<dyn SandboxClient>,
+    zerobuild: Arc<ZerobuildConfig>,
+    disabled: Vec<ErrorCode>,
+}
+
+impl Recovery {
+    pub fn new(
+        config: &RecoveryConfig,
+        sandbox: Arc<dyn SandboxClient>,
+        zerobuild: Arc<ZerobuildConfig>,
+    ) -> Self {
+        Self {
+            sandbox,
+            zerobuild,
+            disabled: config.disabled.clone(),
+        }
+    }
+
+    fn playbook(&self, code: ErrorCode, tool: &str) -> Option<Playbook> {
+        if self.disabled.contains(&code) {
+            return None;
+        }
+        Playbook::for_failure(code, tool)
+    }
+
+    /// Run `playbook` for a call with `args`, returning what it did.
+    async fn run(&self, playbook: Playbook, args: &serde_json::Value) -> anyhow::Result<String> {
+        match playbook {
+            Playbook::RecreateSandbox => self.recreate_sandbox().await,
+            Playbook::CleanCaches => {
+                let workdir = args["workdir"].as_str().unwrap_or(PROJECT_DIR);
+                self.clean_caches(workdir).await
+            }
+            Playbook::ReconnectGitHub => self.reconnect_github().await,
+        }
+    }
+
+    async fn recreate_sandbox(&self) -> anyhow::Result<String> {
+        let id = self
+            .sandbox
+            .create_sandbox(
+                true,
+                self.zerobuild.default_template.trim(),
+                self.zerobuild
+                    .sandbox
+                    .create_timeout_secs
+                    .saturating_mul(1000),
+            )
+            .await?;
+        let restore = super::sandbox::SandboxRestoreSnapshotTool::new(
+            Arc::clone(&self.sandbox),
+            PathBuf::from(&self.zerobuild.db_path),
+        )
+        .execute(serde_json::json!({}))
+        .await?;
+        Ok(if restore.success {
+            format!("the sandbox had expired; created sandbox {id} and restored the last snapshot")
+        } else {
+            format!(
+                "the sandbox had expired; created sandbox {id} without restoring files ({})",
+                restore.error.unwrap_or_default()
+            )
+        })
+    }
+
+    async fn clean_caches(&self, workdir: &str) -> anyhow::Result<String> {
+        self.sandbox
+            .run_command(CLEAN_CACHES_COMMAND, workdir, CLEAN_CACHES_TIMEOUT_MS)
+            .await?;
+        Ok("the sandbox ran out of disk space; cleaned package-manager and build caches".into())
+    }
+
+    async fn reconnect_github(&self) -> anyhow::Result<String> {
+        let conn = crate::store::init_db(&PathBuf::from(&self.zerobuild.db_path))?;
+        crate::store::tokens::clear_github_token(&conn)?;
+        drop(conn);
+        let connect = super::github_ops::GitHubConnectTool::new(Arc::clone(&self.zerobuild))
+            .execute(serde_json::json!({}))
+            .await?;
+        Ok(format!(
+            "GitHub rejected the stored token, so it was removed. {}",
+            connect.error.unwrap_or(connect.output)
+        ))
+    }
+}
+
+/// A tool whose failures are remediated by [`Recovery`] playbooks.
+pub struct RecoveringTool {
+    inner: Arc<dyn Tool>,
+    recovery: Arc<Recovery>,
+}
+
+impl RecoveringTool {
+    pub fn new(inner: Arc<dyn Tool>, recovery: Arc<Recovery>) -> Self {
+        Self { inner, recovery }
+    }
+}
+
+/// `result` as a failed [`ToolResult`] with `note` appended to its error.
+fn with_note(result: anyhow::Result<ToolResult>, code: ErrorCode, note: &str) -> ToolResult {
+    let mut result = result.unwrap_or_else(|e| ToolResult {
+        success: false,
+        output: String::new(),
+        error: Some(format!("{e:#}")),
+        error_code: Some(code),
+        error_hint: None,
+    });
+    let error = result.error.take().unwrap_or_default();
+    result.error = Some(format!("{error}\n[automatic recovery: {note}]"));
+    result
+}
+
+#[async_trait]
+impl Tool for RecoveringTool {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn description(&self) -> &str {
+        self.inner.description()
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        self.inner.parameters_schema()
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let name = self.inner.name();
+        let result = self.inner.execute(args.clone()).await;
+        let code = match &result {
+            Ok(r) if r.success => None,
+            Ok(r) => r
+                .error_code
+                .or_else(|| r.error.as_deref().and_then(ErrorCode::classify)),
+            Err(e) => Some(error::code_of(e)),
+        };
+        let Some((code, playbook)) =
+            code.and_then(|code| Some((code, self.recovery.playbook(code, name)?)))
+        else {
+            return result;
+        };
+
+        tracing::info!(tool = name, code = %code, ?playbook, "Running recovery playbook");
+        let note = match self.recovery.run(playbook, &args).await {
+            Ok(note) => note,
+            Err(e) => {
+                tracing::warn!(tool = name, ?playbook, "Recovery playbook failed: {e:#}");
+                return Ok(with_note(
+                    result,
+                    code,
+                    &format!("{playbook:?} failed: {e}"),
+                ));
+            }
+        };
+        if !playbook.retries() {
+            return Ok(with_note(result, code, &note));
+        }
+
+        match self.inner.execute(args).await {
+            Ok(mut retried) if retried.success => {
+                retried.output = format!("[recovered: {note}]\n{}", retried.output);
+                Ok(retried)
+            }
+            retried => Ok(with_note(retried, code, &note)),
+        }
+    }
+}
+
+/// Wrap every tool in `tools` with `recovery`, or return them unchanged when
+/// recovery is off.
+pub fn recover_tools(
+    tools: Vec<Arc<dyn Tool>>,
+    recovery: Option<&Arc<Recovery>>,
+) -> Vec<Arc<dyn Tool>> {
+    let Some(recovery) = recovery else {
+        return tools;
+    };
+    tools
+        .into_iter()
+        .map(|tool| Arc::new(RecoveringTool::new(tool, Arc::clone(recovery))) as Arc<dyn Tool>)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sandbox::mock::MockSandboxClient;
+    use crate::tools::sandbox::{SandboxRunCommandTool, SandboxWriteFileTool};
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn recovery(sandbox: &Arc<MockSandboxClient>, tmp: &TempDir) -> Arc<Recovery> {
+        let zerobuild = ZerobuildConfig {
+            db_path: tmp.path().join("zerobuild.db").display().to_string(),
+            ..ZerobuildConfig::default()
+        };
+        Arc::new(Recovery::new(
+            &RecoveryConfig::default(),
+            Arc::clone(sandbox) as Arc<dyn SandboxClient>,
+            Arc::new(zerobuild),
+        ))
+    }
+
+    #[tokio::test]
+    async fn expired_sandbox_is_recreated_and_the_call_retried() {
+        let tmp = TempDir::new().unwrap();
+        let sandbox = Arc::new(MockSandboxClient::new());
+        sandbox.create_sandbox(false, "", 0).await.unwrap();
+        sandbox.expire();
+        let tool = RecoveringTool::new(
+            Arc::new(SandboxWriteFileTool::new(Arc::clone(&sandbox) as _)),
+            recovery(&sandbox, &tmp),
+        );
+
+        let result = tool
+            .execute(json!({"path": "project/a.txt", "content": "hi"}))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(result
+            .output
+            .contains("[recovered: the sandbox had expired"));
+        assert!(result.output.contains("without restoring files"));
+        assert_eq!(sandbox.files()["project/a.txt"], "hi");
+    }
+
+    #[tokio::test]
+    async fn full_disk_cleans_caches_before_retrying() {
+        let tmp = TempDir::new().unwrap();
+        let sandbox = Arc::new(MockSandboxClient::new());
+        sandbox.create_sandbox(false, "", 0).await.unwrap();
+        sandbox.on_command("npm install", "", "npm ERR! code ENOSPC", 1);
+        let tool = RecoveringTool::new(
+            Arc::new(SandboxRunCommandTool::new(Arc::clone(&sandbox) as _)),
+            recovery(&sandbox, &tmp),
+        );
+
+        let result = tool
+            .execute(json!({"command": "npm install", "workdir": "project"}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert_eq!(result.error_code, Some(ErrorCode::DiskFull));
+        assert!(result
+            .error
+            .unwrap()
+            .contains("[automatic recovery: the sandbox ran out of disk space"));
+        let commands: Vec<String> = sandbox
+            .commands()
+            .into_iter()
+            .map(|c| c.command)
+            .filter(|command| !command.starts_with("ls "))
+            .collect();
+        assert_eq!(
+            commands,
+            ["npm install", CLEAN_CACHES_COMMAND, "npm install"]
+        );
+    }
+
+    #[test]
+    fn playbooks_match_code_and_tool() {
+        assert_eq!(
+            Playbook::for_failure(ErrorCode::SandboxExpired, "sandbox_read_file"),
+            Some(Playbook::RecreateSandbox)
+        );
+        assert_eq!(
+            Playbook::for_failure(ErrorCode::SandboxExpired, "sandbox_create"),
+            None
+        );
+        assert_eq!(
+            Playbook::for_failure(ErrorCode::AuthFailed, "github_push"),
+            Some(Playbook::ReconnectGitHub)
+        );
+        assert_eq!(
+            Playbook::for_failure(ErrorCode::AuthFailed, "github_connect"),
+            None
+        );
+        assert_eq!(Playbook::for_failure(ErrorCode::DiskFull, "shell"), None);
+    }
+}
@@ -0,0 +1,186 @@
+//! `checkpoint` tool — create, list and switch conversation checkpoints.
+//!
+//! See [`crate::store::checkpoint`] for what a checkpoint captures.
+
+use crate::store;
+use crate::tools::traits::{Tool, ToolResult};
+use async_trait::async_trait;
+use serde_json::json;
+use std::fmt::Write;
+use std::path::PathBuf;
+
+const TOOL_NAME: &str = "checkpoint";
+
+pub struct CheckpointTool {
+    db_path: PathBuf,
+}
+
+impl CheckpointTool {
+    pub fn new(db_path: impl Into<PathBuf>) -> Self {
+        Self {
+            db_path: db_path.into(),
+        }
+    }
+
+    fn run(&self, action: &str, args: &serde_json::Value) -> anyhow::Result<String> {
+        let conn = store::init_db(&self.db_path)?;
+        // Checkpoints belong to, and rewind the conversation of, whoever asked.
+        let user = store::preferences::current_user();
+        let name = args
+            .get("name")
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|n| !n.is_empty());
+
+        match action {
+            "create" => {
+                let name = name.ok_or_else(|| anyhow::anyhow!("Missing 'name' parameter"))?;
+                let note = args.get("note").and_then(|v| v.as_str());
+                let created = store::checkpoint::create_checkpoint(&conn, &user, name, note)?;
+                Ok(format!(
+                    "Checkpoint '{}' created ({} files, {} messages{}).",
+                    created.name,
+                    created.file_count,
+                    created.message_count,
+                    created
+                        .parent
+                        .map(|p| format!(", branched from '{p}'"))
+                        .unwrap_or_default()
+                ))
+            }
+            "list" => {
+                let checkpoints = store::checkpoint::list_checkpoints(&conn, &user)?;
+                if checkpoints.is_empty() {
+                    return Ok("No checkpoints yet.".into());
+                }
+                let mut out = String::from("Checkpoints (oldest first):\n");
+                for c in checkpoints {
+                    let _ = write!(
+                        out,
+                        "{} {} — {} files, {} messages, {}",
+                        if c.active { "*" } else { "-" },
+                        c.name,
+                        c.file_count,
+                        c.message_count,
+                        c.created_at
+                    );
+                    if let Some(parent) = c.parent {
+                        let _ = write!(out, ", parent '{parent}'");
+                    }
+                    if let Some(note) = c.note {
+                        let _ = write!(out, " ({note})");
+                    }
+                    out.push('\n');
+                }
+                Ok(out)
+            }
+            "switch" => {
+                let name = name.ok_or_else(|| anyhow::anyhow!("Missing 'name' parameter"))?;
+                let checkpoint = store::checkpoint::switch_checkpoint(&conn, &user, name)?;
+                Ok(format!(
+                    "Switched to checkpoint '{}'. The saved snapshot now holds its {} files — \
+                     run sandbox_restore_snapshot to load them into the sandbox. Your \
+                     conversation is rewound to this checkpoint from your next message.",
+                    checkpoint.name,
+                    checkpoint.files.len()
+                ))
+            }
+            other => anyhow::bail!("Unknown action '{other}'. Use create, list or switch."),
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for CheckpointTool {
+    fn name(&self) -> &str {
+        TOOL_NAME
+    }
+
+    fn description(&self) -> &str {
+        "Manage named checkpoints of the saved project snapshot plus the conversation. \
+         'create' captures the current state (run sandbox_save_snapshot first), 'list' shows \
+         the user's checkpoints, 'switch' rewinds to a checkpoint so work can branch from it \
+         (e.g. retry a stage with a different model) without losing other checkpoints."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["create", "list", "switch"],
+                    "description": "Operation to perform"
+                },
+                "name": {
+                    "type": "string",
+                    "description": "Checkpoint name (required for create and switch)"
+                },
+                "note": {
+                    "type": "string",
+                    "description": "Optional description stored with a new checkpoint"
+                }
+            },
+            "required": ["action"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let action = args
+            .get("action")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'action' parameter"))?;
+
+        match self.run(action, &args) {
+            Ok(output) => Ok(ToolResult {
+                success: true,
+                output,
+                error: None,
+                error_code: None,
+                error_hint: None,
+            }),
+            Err(e) => Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(e.to_string()),
+                error_code: None,
+                error_hint: None,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn create_list_switch_round_trip() {
+        let tmp = TempDir::new().unwrap();
+        let tool = CheckpointTool::new(tmp.path().join("zerobuild.db"));
+
+        let created = tool
+            .execute(json!({"action": "create", "name": "v1", "note": "first pass"}))
+            .await
+            .unwrap();
+        assert!(created.success, "{:?}", created.error);
+
+        let listed = tool.execute(json!({"action": "list"})).await.unwrap();
+        assert!(listed.output.contains("* v1"));
+        assert!(listed.output.contains("first pass"));
+
+        let switched = tool
+            .execute(json!({"action": "switch", "name": "v1"}))
+            .await
+            .unwrap();
+        assert!(switched.success);
+
+        let missing = tool
+            .execute(json!({"action": "switch", "name": "nope"}))
+            .await
+            .unwrap();
+        assert!(!missing.success);
+        assert!(missing.error.unwrap().contains("not found"));
+    }
+}
@@ -0,0 +1,183 @@
+//! `codebase_search` tool — retrieve the snapshot chunks most relevant to a query.
+
+use crate::memory::embeddings::{EmbeddingProvider, NoopEmbedding};
+use crate::store;
+use crate::tools::traits::{Tool, ToolResult};
+use async_trait::async_trait;
+use serde_json::json;
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const TOOL_NAME: &str = "codebase_search";
+
+const DEFAULT_LIMIT: usize = 5;
+const MAX_LIMIT: usize = 20;
+
+pub struct CodebaseSearchTool {
+    db_path: PathBuf,
+    embedder: Arc<dyn EmbeddingProvider>,
+}
+
+impl CodebaseSearchTool {
+    pub fn new(db_path: impl Into<PathBuf>) -> Self {
+        Self {
+            db_path: db_path.into(),
+            embedder: Arc::new(NoopEmbedding),
+        }
+    }
+
+    /// Embed queries with `embedder` (keyword-only by default).
+    pub fn with_embedder(mut self, embedder: Arc<dyn EmbeddingProvider>) -> Self {
+        self.embedder = embedder;
+        self
+    }
+}
+
+#[async_trait]
+impl Tool for CodebaseSearchTool {
+    fn name(&self) -> &str {
+        TOOL_NAME
+    }
+
+    fn description(&self) -> &str {
+        "Search the indexed project snapshot for the code most relevant to a task \
+         (e.g. 'where is the login form validated'). Returns file paths, line ranges \
+         and the matching code. Prefer this over reading many files in large projects. \
+         The index is refreshed by sandbox_save_snapshot."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "description": "What you are looking for, in natural language or identifiers"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Maximum number of chunks to return (default: 5, max: 20)"
+                }
+            },
+            "required": ["query"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let query = args
+            .get("query")
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|q| !q.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'query' parameter"))?;
+
+        #[allow(clippy::cast_possible_truncation)]
+        let limit = args
+            .get("limit")
+            .and_then(serde_json::Value::as_u64)
+            .map_or(DEFAULT_LIMIT, |v| v as usize)
+            .clamp(1, MAX_LIMIT);
+
+        let query_embedding = if self.embedder.dimensions() > 0 {
+            match self.embedder.embed_one(query).await {
+                Ok(v) => Some(v),
+                Err(e) => {
+                    tracing::debug!("codebase_search query embedding failed: {e}");
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let hits = match store::init_db(&self.db_path).and_then(|conn| {
+            store::code_index::search_chunks(&conn, query, query_embedding.as_deref(), limit)
+        }) {
+            Ok(hits) => hits,
+            Err(e) => {
+                return Ok(ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some(format!("Failed to search code index: {e}")),
+                    error_code: None,
+                    error_hint: None,
+                })
+            }
+        };
+
+        if hits.is_empty() {
+            return Ok(ToolResult {
+                success: true,
+                output: "No indexed code matched. If the project changed recently, \
+                         run sandbox_save_snapshot to refresh the index."
+                    .into(),
+                error: None,
+                error_code: None,
+                error_hint: None,
+            });
+        }
+
+        let mut output = format!("Found {} relevant chunks:\n", hits.len());
+        for hit in &hits {
+            let _ = write!(
+                output,
+                "\n--- {}:{}-{} (score {:.2}) ---\n{}\n",
+                hit.chunk.path,
+                hit.chunk.start_line,
+                hit.chunk.end_line,
+                hit.score,
+                hit.chunk.content
+            );
+        }
+
+        Ok(ToolResult {
+            success: true,
+            output,
+            error: None,
+            error_code: None,
+            error_hint: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn returns_matching_chunk_with_line_range() {
+        let tmp = TempDir::new().unwrap();
+        let db = tmp.path().join("zerobuild.db");
+        let files = HashMap::from([
+            (
+                "/src/auth.ts".to_string(),
+                "export function login(user) { return token; }".to_string(),
+            ),
+            (
+                "/src/cart.ts".to_string(),
+                "export function addToCart(item) {}".to_string(),
+            ),
+        ]);
+        store::code_index::index_snapshot(&db, &files, &NoopEmbedding)
+            .await
+            .unwrap();
+
+        let tool = CodebaseSearchTool::new(&db);
+        let result = tool.execute(json!({"query": "login"})).await.unwrap();
+        assert!(result.success);
+        assert!(result.output.contains("/src/auth.ts:1-1"));
+        assert!(!result.output.contains("cart.ts"));
+    }
+
+    #[tokio::test]
+    async fn empty_index_suggests_saving_snapshot() {
+        let tmp = TempDir::new().unwrap();
+        let tool = CodebaseSearchTool::new(tmp.path().join("zerobuild.db"));
+        let result = tool.execute(json!({"query": "login"})).await.unwrap();
+        assert!(result.success);
+        assert!(result.output.contains("sandbox_save_snapshot"));
+    }
+}
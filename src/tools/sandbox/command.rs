@@ -1,20 +1,233 @@
 //! `sandbox_run_command` tool — execute a shell command in the sandbox.
 
-use crate::sandbox::SandboxClient;
+use super::failure_context::gather_failure_context;
+use crate::artifacts::{ArtifactKind, Artifacts};
+use crate::config::SandboxLimitsConfig;
+use crate::error::{self, ErrorCode};
+use crate::sandbox::install_progress::{install_label, is_install_command, InstallProgress};
+use crate::sandbox::monorepo;
+use crate::sandbox::package_manager::{detect_project_package_manager, rewrite_command};
+use crate::sandbox::project_config::{ProjectConfig, PROJECT_DIR};
+use crate::sandbox::{CommandOutput, SandboxClient};
+use crate::security::redaction::redact;
+use crate::store;
+use crate::tools::progress::{is_reporting, report_progress};
 use crate::tools::traits::{Tool, ToolResult};
 use async_trait::async_trait;
 use serde_json::json;
+use std::fmt::Write;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
 const TOOL_NAME: &str = "sandbox_run_command";
 
+/// Longest line shown in truncated output; minified bundles and progress
+/// bars can put megabytes on one line.
+const MAX_LINE_CHARS: usize = 1000;
+
+/// `duration` in whole milliseconds.
+pub(super) fn duration_ms(duration: Duration) -> u64 {
+    u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)
+}
+
 pub struct SandboxRunCommandTool {
     client: Arc<dyn SandboxClient>,
+    default_timeout_ms: u64,
+    max_timeout_ms: u64,
+    rewrite_package_commands: bool,
+    pty: bool,
+    head_lines: usize,
+    tail_lines: usize,
+    log_db_path: Option<PathBuf>,
+    history_limit: usize,
+    artifacts: Option<Arc<Artifacts>>,
+    install_heartbeat: Option<Duration>,
 }
 
 impl SandboxRunCommandTool {
     pub fn new(client: Arc<dyn SandboxClient>) -> Self {
-        Self { client }
+        Self::with_limits(client, &SandboxLimitsConfig::default())
+    }
+
+    /// Apply `[zerobuild.sandbox]` command timeouts.
+    pub fn with_limits(client: Arc<dyn SandboxClient>, limits: &SandboxLimitsConfig) -> Self {
+        Self {
+            client,
+            default_timeout_ms: limits.command_timeout_secs.saturating_mul(1000),
+            max_timeout_ms: limits.max_command_timeout_secs.saturating_mul(1000),
+            rewrite_package_commands: limits.rewrite_package_commands,
+            pty: limits.pty,
+            head_lines: limits.output_head_lines,
+            tail_lines: limits.output_tail_lines,
+            log_db_path: None,
+            history_limit: limits.command_history_limit,
+            artifacts: None,
+            install_heartbeat: (limits.install_heartbeat_secs > 0)
+                .then(|| Duration::from_secs(limits.install_heartbeat_secs)),
+        }
+    }
+
+    /// Run `command`, under a pseudo-terminal with `pty`, else reporting
+    /// install progress every heartbeat while it runs when it installs
+    /// dependencies and someone listens for progress.
+    async fn run(
+        &self,
+        command: &str,
+        workdir: &str,
+        timeout_ms: u64,
+        pty: bool,
+    ) -> anyhow::Result<CommandOutput> {
+        if pty {
+            return self
+                .client
+                .run_command_in_pty(command, workdir, timeout_ms)
+                .await;
+        }
+        let heartbeat = match self.install_heartbeat {
+            Some(heartbeat) if is_reporting() && is_install_command(command) => heartbeat,
+            _ => return self.client.run_command(command, workdir, timeout_ms).await,
+        };
+
+        let label = install_label(command);
+        let (tx, mut lines) = tokio::sync::mpsc::unbounded_channel();
+        let run = self
+            .client
+            .run_command_streaming(command, workdir, timeout_ms, tx);
+        tokio::pin!(run);
+        let mut progress = InstallProgress::new();
+        let mut ticker =
+            tokio::time::interval_at(tokio::time::Instant::now() + heartbeat, heartbeat);
+        loop {
+            tokio::select! {
+                result = &mut run => return result,
+                Some(line) = lines.recv() => progress.observe(&line),
+                _ = ticker.tick() => report_progress(progress.summary(&label)),
+            }
+        }
+    }
+
+    /// Store the full output of truncated commands in the ZeroBuild database
+    /// so `get_log` can return it, and record every command for
+    /// `sandbox_command_history`.
+    pub fn with_log_store(mut self, db_path: impl Into<PathBuf>) -> Self {
+        self.log_db_path = Some(db_path.into());
+        self
+    }
+
+    /// Also keep stored logs in `artifacts`, where they outlive the
+    /// database's last [`store::logs::MAX_LOGS`] until the retention policy
+    /// deletes them.
+    pub fn with_artifacts(mut self, artifacts: Arc<Artifacts>) -> Self {
+        self.artifacts = Some(artifacts);
+        self
+    }
+
+    /// Timeout used when the agent asks for none and the largest it may ask
+    /// for, in milliseconds.
+    pub(super) fn timeouts_ms(&self) -> (u64, u64) {
+        (self.default_timeout_ms, self.max_timeout_ms)
+    }
+
+    /// Whether `text` is too long to show in full.
+    pub(super) fn needs_truncation(&self, text: &str) -> bool {
+        text.lines().count() > self.head_lines + self.tail_lines
+            || text
+                .lines()
+                .any(|line| line.chars().count() > MAX_LINE_CHARS)
+    }
+
+    /// Add `entry` to the command history, when a store is configured.
+    pub(super) fn record_history(&self, entry: store::command_history::NewCommand<'_>) {
+        let Some(db_path) = self.log_db_path.as_ref() else {
+            return;
+        };
+        let command = redact(entry.command);
+        let entry = store::command_history::NewCommand {
+            command: &command,
+            ..entry
+        };
+        let recorded = store::init_db(db_path).and_then(|conn| {
+            store::command_history::record_command(&conn, &entry, self.history_limit)
+        });
+        if let Err(e) = recorded {
+            tracing::warn!("Failed to record command history: {e}");
+        }
+    }
+
+    /// Save the full output, returning the log ID or `None` when no store is
+    /// configured or saving failed.
+    pub(super) async fn store_log(
+        &self,
+        command: &str,
+        workdir: &str,
+        output: &CommandOutput,
+    ) -> Option<String> {
+        let db_path = self.log_db_path.as_ref()?;
+        let (command, stdout, stderr) = (
+            redact(command),
+            redact(&output.stdout),
+            redact(&output.stderr),
+        );
+        let saved = store::init_db(db_path).and_then(|conn| {
+            store::logs::save_log(&conn, &command, workdir, output.exit_code, &stdout, &stderr)
+        });
+        let id = match saved {
+            Ok(id) => id,
+            Err(e) => {
+                tracing::warn!("Failed to store command log: {e}");
+                return None;
+            }
+        };
+        if let Some(artifacts) = &self.artifacts {
+            let log = format!(
+                "$ {command}\n# workdir: {workdir}, exit code: {}\n\n--- stdout ---\n{stdout}\n--- stderr ---\n{stderr}",
+                output.exit_code
+            );
+            if let Err(e) = artifacts
+                .save(
+                    None,
+                    ArtifactKind::Log,
+                    &format!("{id}.log"),
+                    log.into_bytes(),
+                )
+                .await
+            {
+                tracing::warn!("Failed to store command log artifact: {e:#}");
+            }
+        }
+        Some(id)
+    }
+
+    /// Head and tail of `text` around a line saying what was left out and how
+    /// to get it.
+    pub(super) fn truncate(&self, text: &str, stream: &str, log_id: Option<&str>) -> String {
+        if !self.needs_truncation(text) {
+            return text.to_string();
+        }
+        let lines: Vec<&str> = text.lines().collect();
+        let total = lines.len();
+        let clip = |line: &&str| crate::util::truncate_with_ellipsis(line, MAX_LINE_CHARS);
+        if total <= self.head_lines + self.tail_lines {
+            return lines.iter().map(clip).collect::<Vec<_>>().join("\n");
+        }
+
+        let omitted_start = self.head_lines + 1;
+        let omitted_end = total - self.tail_lines;
+        let how = match log_id {
+            Some(id) => format!(
+                "get_log log_id=\"{id}\" stream=\"{stream}\" start_line={omitted_start} \
+                 end_line={omitted_end} (or grep=...) to see them"
+            ),
+            None => "full log not stored".to_string(),
+        };
+        let mut out: Vec<String> = lines[..self.head_lines].iter().map(clip).collect();
+        out.push(format!(
+            "... [{} lines omitted ({omitted_start}-{omitted_end} of {total}); {how}] ...",
+            omitted_end - omitted_start + 1
+        ));
+        out.extend(lines[omitted_end..].iter().map(clip));
+        out.join("\n")
     }
 }
 
@@ -29,16 +242,19 @@ impl Tool for SandboxRunCommandTool {
          \
          ✅ REQUIRED for: npm install, npx create-next-app, npm run build, npm run dev, npx, node, python \
          \
-         📦 PACKAGE MANAGER PRIORITY (faster installs): \
-         1. Use `pnpm install` if available (fastest) \
-         2. Use `yarn install` if pnpm unavailable \
-         3. Use `npm install` as fallback \
+         📦 PACKAGE MANAGER: generic `npm install`, `npm install <pkg>` and `npm run <script>` \
+         are rewritten to the project's package manager (package.json packageManager, then \
+         lockfile, then pnpm > yarn > bun > npm), with a frozen lockfile when one exists. \
          \
-         💡 Call `sandbox_get_package_manager` to check which is available. \
+         💡 Call `project_info` to see the project's manager, commands and scripts. \
          \
          ❌ DO NOT use `shell` tool for build operations — it runs locally, not in sandbox! \
          \
-         Returns stdout, stderr, and exit_code. \
+         Returns stdout, stderr, and exit_code, with colors and progress bar redraws \
+         cleaned up; pass pty=true for tools that need a terminal. Long output shows only its first and last \
+         lines plus a log_id; call `get_log` for the omitted part. On failure, also returns the source lines \
+         around referenced errors plus package.json/tsconfig.json. \
+         In a monorepo, pass 'app' to run the command in one app's directory. \
          Requires an active sandbox (call sandbox_create first)."
     }
 
@@ -52,11 +268,26 @@ impl Tool for SandboxRunCommandTool {
                 },
                 "workdir": {
                     "type": "string",
-                    "description": "Working directory relative to sandbox root (e.g. 'project'). Default: 'project'. NEVER use absolute paths like /home/user/project."
+                    "description": "Working directory relative to sandbox root (e.g. 'project'). Default: the workdir from project/.zerobuild.toml, else 'project'. NEVER use absolute paths like /home/user/project."
+                },
+                "app": {
+                    "type": "string",
+                    "description": "Monorepo app to run the command in, by name or path (see project_info), e.g. 'web' or 'apps/web'. Ignored when workdir is set."
                 },
                 "timeout_ms": {
                     "type": "integer",
-                    "description": "Timeout in milliseconds. Default: 120000 (2 minutes)."
+                    "description": format!(
+                        "Timeout in milliseconds. Default: {}, maximum: {}.",
+                        self.default_timeout_ms, self.max_timeout_ms
+                    )
+                },
+                "pty": {
+                    "type": "boolean",
+                    "description": format!(
+                        "Run under a pseudo-terminal, for tools that need a TTY or behave \
+                         differently without one; stderr is then merged into stdout. Default: {}.",
+                        self.pty
+                    )
                 }
             },
             "required": ["command"]
@@ -64,13 +295,14 @@ impl Tool for SandboxRunCommandTool {
     }
 
     async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
-        let _sandbox_id = match self.client.require_id() {
+        let sandbox_id = match self.client.require_id() {
             Ok(id) => id,
             Err(e) => {
                 return Ok(ToolResult {
                     success: false,
                     output: String::new(),
                     error: Some(e),
+                    error_code: None,
                     error_hint: None,
                 })
             }
@@ -85,24 +317,92 @@ impl Tool for SandboxRunCommandTool {
                 success: false,
                 output: String::new(),
                 error: Some("command cannot be empty".to_string()),
+                error_code: None,
                 error_hint: None,
             });
         }
 
-        let workdir = args["workdir"].as_str().unwrap_or("project");
-        let timeout_ms = args["timeout_ms"].as_u64().unwrap_or(300_000);
+        let workdir = match (args["workdir"].as_str(), args["app"].as_str()) {
+            (Some(dir), _) => dir.to_string(),
+            (None, Some(app)) => {
+                let project = ProjectConfig::load(self.client.as_ref())
+                    .await
+                    .ok()
+                    .flatten()
+                    .unwrap_or_default();
+                match monorepo::resolve_app(self.client.as_ref(), &project, app).await {
+                    Ok(app) => app.project.workdir(),
+                    Err(e) => {
+                        return Ok(ToolResult {
+                            success: false,
+                            output: String::new(),
+                            error: Some(e),
+                            error_code: None,
+                            error_hint: None,
+                        })
+                    }
+                }
+            }
+            (None, None) => ProjectConfig::load(self.client.as_ref())
+                .await
+                .ok()
+                .flatten()
+                .map_or_else(|| PROJECT_DIR.to_string(), |config| config.workdir()),
+        };
+        let workdir = workdir.as_str();
+        let timeout_ms = args["timeout_ms"]
+            .as_u64()
+            .unwrap_or(self.default_timeout_ms)
+            .min(self.max_timeout_ms);
+
+        let mut command = command.to_string();
+        let mut rewrite_note = String::new();
+        if self.rewrite_package_commands && command.contains("npm ") {
+            let project = detect_project_package_manager(self.client.as_ref(), workdir).await;
+            if let Some(rewritten) = rewrite_command(&command, &project) {
+                rewrite_note = format!(
+                    "note: ran `{rewritten}` instead of `{command}`; project uses {}\n",
+                    project.describe()
+                );
+                command = rewritten;
+            }
+        }
 
-        match self.client.run_command(command, workdir, timeout_ms).await {
+        let started = std::time::Instant::now();
+        let pty = args["pty"].as_bool().unwrap_or(self.pty);
+        match self.run(&command, workdir, timeout_ms, pty).await {
             Ok(output) => {
                 let exit_code = output.exit_code;
                 let success = exit_code == 0;
 
-                let mut out = format!("exit_code: {exit_code}");
+                let log_id = if self.needs_truncation(&output.stdout)
+                    || self.needs_truncation(&output.stderr)
+                {
+                    self.store_log(&command, workdir, &output).await
+                } else {
+                    None
+                };
+                self.record_history(store::command_history::NewCommand {
+                    sandbox_id: &sandbox_id,
+                    session: None,
+                    command: &command,
+                    workdir,
+                    exit_code,
+                    duration_ms: duration_ms(started.elapsed()),
+                    log_id: log_id.as_deref(),
+                });
+
+                let mut out = format!("{rewrite_note}exit_code: {exit_code}");
+                if let Some(id) = &log_id {
+                    let _ = write!(out, "\nlog_id: {id}");
+                }
                 if !output.stdout.is_empty() {
-                    out.push_str(&format!("\n\nstdout:\n{}", output.stdout));
+                    let stdout = self.truncate(&output.stdout, "stdout", log_id.as_deref());
+                    let _ = write!(out, "\n\nstdout:\n{stdout}");
                 }
                 if !output.stderr.is_empty() {
-                    out.push_str(&format!("\n\nstderr:\n{}", output.stderr));
+                    let stderr = self.truncate(&output.stderr, "stderr", log_id.as_deref());
+                    let _ = write!(out, "\n\nstderr:\n{stderr}");
                 }
 
                 if success {
@@ -110,13 +410,31 @@ impl Tool for SandboxRunCommandTool {
                         success: true,
                         output: out,
                         error: None,
+                        error_code: None,
                         error_hint: None,
                     })
                 } else {
+                    if let Some(context) = gather_failure_context(
+                        self.client.as_ref(),
+                        workdir,
+                        &output.stdout,
+                        &output.stderr,
+                    )
+                    .await
+                    {
+                        out.push_str("\n\nfailure context (referenced files and manifests):");
+                        out.push_str(&context);
+                    }
+                    // A full disk is worth telling apart from ordinary
+                    // build failures: it has an automatic remedy.
+                    let disk_full = [&output.stderr, &output.stdout].iter().any(|text| {
+                        ErrorCode::classify(text.as_str()) == Some(ErrorCode::DiskFull)
+                    });
                     Ok(ToolResult {
                         success: false,
                         output: out,
                         error: Some(format!("Command exited with code {exit_code}")),
+                        error_code: disk_full.then_some(ErrorCode::DiskFull),
                         error_hint: None,
                     })
                 }
@@ -142,6 +460,7 @@ impl Tool for SandboxRunCommandTool {
                     success: false,
                     output: String::new(),
                     error: Some(err_msg),
+                    error_code: Some(error::code_of(&e)),
                     error_hint: Some(error_hint),
                 })
             }
@@ -158,4 +477,130 @@ mod tests {
         let client = Arc::new(crate::sandbox::local::LocalProcessSandboxClient::new());
         assert_eq!(SandboxRunCommandTool::new(client).name(), TOOL_NAME);
     }
+
+    #[tokio::test]
+    async fn failed_command_includes_failure_context() {
+        let client = Arc::new(crate::sandbox::local::LocalProcessSandboxClient::new());
+        client.create_sandbox(false, "", 30_000).await.unwrap();
+        client
+            .write_file(
+                "project/src/main.js",
+                "let x = 1;\nthrow new Error('boom');\n",
+            )
+            .await
+            .unwrap();
+        let tool = SandboxRunCommandTool::new(client.clone());
+
+        let result = tool
+            .execute(json!({"command": "echo 'src/main.js:2:1 boom' >&2; exit 1"}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.output.contains("failure context"));
+        assert!(result.output.contains(">    2 | throw new Error('boom');"));
+
+        client.kill_sandbox().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn generic_npm_install_uses_frozen_lockfile() {
+        let client = Arc::new(crate::sandbox::local::LocalProcessSandboxClient::new());
+        client.create_sandbox(false, "", 30_000).await.unwrap();
+        client
+            .write_file("project/package-lock.json", "{}")
+            .await
+            .unwrap();
+        let tool = SandboxRunCommandTool::new(client.clone());
+
+        let result = tool
+            .execute(json!({"command": "true || npm install"}))
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert!(result
+            .output
+            .starts_with("note: ran `true || npm ci` instead of `true || npm install`"));
+
+        client.kill_sandbox().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn long_output_is_truncated_and_stored() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let db_path = tmp.path().join("zerobuild.db");
+        let client = Arc::new(crate::sandbox::local::LocalProcessSandboxClient::new());
+        client.create_sandbox(false, "", 30_000).await.unwrap();
+        let limits = SandboxLimitsConfig {
+            output_head_lines: 2,
+            output_tail_lines: 2,
+            ..SandboxLimitsConfig::default()
+        };
+        let tool =
+            SandboxRunCommandTool::with_limits(client.clone(), &limits).with_log_store(&db_path);
+
+        let result = tool
+            .execute(json!({"command": "seq 1 10", "workdir": "."}))
+            .await
+            .unwrap();
+        assert!(result.success);
+        let log_id = result
+            .output
+            .lines()
+            .find_map(|line| line.strip_prefix("log_id: "))
+            .unwrap()
+            .to_string();
+        assert!(result.output.contains(&format!(
+            "stdout:\n1\n2\n... [6 lines omitted (3-8 of 10); get_log log_id=\"{log_id}\" \
+             stream=\"stdout\" start_line=3 end_line=8"
+        )));
+        assert!(result.output.ends_with("] ...\n9\n10"));
+
+        let conn = store::init_db(&db_path).unwrap();
+        let log = store::logs::load_log(&conn, &log_id).unwrap().unwrap();
+        assert_eq!(log.stdout.lines().count(), 10);
+
+        let short = tool
+            .execute(json!({"command": "seq 1 4", "workdir": "."}))
+            .await
+            .unwrap();
+        assert!(!short.output.contains("log_id"));
+
+        let history = store::command_history::recent_commands(
+            &conn,
+            &store::command_history::HistoryQuery {
+                limit: 10,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].log_id.as_deref(), Some(log_id.as_str()));
+        assert_eq!(history[1].command, "seq 1 4");
+        client.kill_sandbox().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn installs_report_progress_heartbeats() {
+        let client = Arc::new(crate::sandbox::local::LocalProcessSandboxClient::new());
+        client.create_sandbox(false, "", 30_000).await.unwrap();
+        let mut tool = SandboxRunCommandTool::new(client.clone());
+        tool.install_heartbeat = Some(Duration::from_millis(100));
+
+        // A shell function stands in for pnpm so the test needs no network.
+        let command = "pnpm() { echo 'Progress: resolved 12, reused 0, downloaded 5, added 0'; \
+                       sleep 0.5; }; pnpm install";
+        let (tx, mut rx) = tokio::sync::mpsc::channel(16);
+        let result = crate::tools::progress::with_tool_progress(
+            tx,
+            tool.execute(json!({"command": command, "workdir": "."})),
+        )
+        .await
+        .unwrap();
+        assert!(result.success);
+        let heartbeat = rx.try_recv().unwrap();
+        assert!(heartbeat.starts_with("\u{23f3} pnpm install: "));
+        assert!(heartbeat.contains("12 resolved, 5 downloaded"));
+
+        client.kill_sandbox().await.unwrap();
+    }
 }
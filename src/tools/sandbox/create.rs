@@ -1,9 +1,11 @@
 //! `sandbox_create` tool — create or reset a local process sandbox.
 
-use crate::sandbox::SandboxClient;
+use crate::sandbox::{PackageManager, SandboxClient};
+use crate::store::preferences::{self, Preference};
 use crate::tools::traits::{Tool, ToolResult};
 use async_trait::async_trait;
 use serde_json::json;
+use std::path::PathBuf;
 use std::sync::Arc;
 
 const TOOL_NAME: &str = "sandbox_create";
@@ -12,6 +14,7 @@ pub struct SandboxCreateTool {
     client: Arc<dyn SandboxClient>,
     template: String,
     timeout_ms: u64,
+    preferences_db_path: Option<PathBuf>,
 }
 
 impl SandboxCreateTool {
@@ -24,68 +27,86 @@ impl SandboxCreateTool {
             client,
             template: template.into(),
             timeout_ms,
+            preferences_db_path: None,
         }
     }
-}
 
-#[async_trait]
-impl Tool for SandboxCreateTool {
-    fn name(&self) -> &str {
-        TOOL_NAME
+    /// Scaffold with the user's preferences from the ZeroBuild database:
+    /// their package manager replaces the detected one when installed, and
+    /// the rest are listed in the output.
+    pub fn with_preferences(mut self, db_path: impl Into<PathBuf>) -> Self {
+        self.preferences_db_path = Some(db_path.into());
+        self
     }
 
-    fn description(&self) -> &str {
-        "🚀 Create a new SANDBOX (isolated temp directory) for running code. \
-         \
-         ⚠️ This creates an ISOLATED environment — NOT your local machine. \
-         All build operations (npm, npx, node) must run INSIDE this sandbox via `sandbox_run_command`. \
-         \
-         Pass reset=true to kill any existing sandbox and start fresh. \
-         Returns the sandbox ID — all other sandbox_* tools use this automatically. \
-         Call this before any file or command operations. \
-         \
-         ❌ DO NOT use `shell` tool for npm/npx/node — it runs LOCALLY, not in sandbox!"
+    /// Template new sandboxes start from unless a caller picks another.
+    pub(super) fn template(&self) -> &str {
+        &self.template
     }
 
-    fn parameters_schema(&self) -> serde_json::Value {
-        json!({
-            "type": "object",
-            "properties": {
-                "reset": {
-                    "type": "boolean",
-                    "description": "If true, kill any existing sandbox and create a fresh one. Default: false (resume if possible)."
-                }
-            },
-            "required": []
-        })
+    fn load_preferences(&self) -> Vec<Preference> {
+        let Some(db_path) = &self.preferences_db_path else {
+            return Vec::new();
+        };
+        match crate::store::init_db(db_path)
+            .and_then(|conn| preferences::list(&conn, &preferences::current_user()))
+        {
+            Ok(prefs) => prefs,
+            Err(e) => {
+                tracing::debug!("Failed to load user preferences: {e}");
+                Vec::new()
+            }
+        }
     }
 
-    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
-        let reset = args["reset"].as_bool().unwrap_or(false);
+    /// The user's package manager when set and installed, else the best
+    /// installed one.
+    async fn package_manager(&self, prefs: &[Preference]) -> (PackageManager, bool) {
+        let preferred = prefs
+            .iter()
+            .find(|pref| pref.key == "package_manager")
+            .and_then(|pref| PackageManager::from_name(&pref.value));
+        if let Some(pm) = preferred {
+            if pm == PackageManager::Npm || pm.is_installed().await {
+                self.client.set_package_manager(pm);
+                return (pm, true);
+            }
+        }
+        (self.client.detect_package_manager().await, false)
+    }
 
+    /// Create (or with `reset`, recreate) the sandbox from `template`, or
+    /// report the running one.
+    pub(super) async fn create(&self, reset: bool, template: &str) -> ToolResult {
         if !reset {
             if let Some(existing_id) = self.client.current_id() {
-                return Ok(ToolResult {
+                return ToolResult {
                     success: true,
                     output: format!(
                         "Reusing existing sandbox.\nsandbox_id: {existing_id}\nstatus: running"
                     ),
                     error: None,
+                    error_code: None,
                     error_hint: None,
-                });
+                };
             }
         }
 
         match self
             .client
-            .create_sandbox(reset, &self.template, self.timeout_ms)
+            .create_sandbox(reset, template, self.timeout_ms)
             .await
         {
             Ok(id) => {
-                // Auto-detect package manager after sandbox creation
-                let pm = self.client.detect_package_manager().await;
+                let prefs = self.load_preferences();
+                let (pm, preferred) = self.package_manager(&prefs).await;
                 // Build tip based on detected package manager
-                let tip = if pm == crate::sandbox::PackageManager::Npm {
+                let tip = if preferred {
+                    format!(
+                        "💡 Tip: The user prefers {pm}; install with '{install}'",
+                        install = pm.install_cmd()
+                    )
+                } else if pm == PackageManager::Npm {
                     "💡 Tip: Using npm as package manager. Consider installing pnpm for faster installs.".to_string()
                 } else {
                     format!(
@@ -93,33 +114,87 @@ impl Tool for SandboxCreateTool {
                         install = pm.install_cmd()
                     )
                 };
-                Ok(ToolResult {
-                    success: true,
-                    output: format!(
-                        "Sandbox created.\nsandbox_id: {id}\ntemplate: {}\nstatus: running\npackage_manager: {pm}\n\n{tip}",
-                        self.template,
-                        pm = pm,
-                        tip = tip
+                let template = match crate::sandbox::project_template(template) {
+                    Some(description) => format!(
+                        "{} ({description}) — scaffold new projects from this template unless the user asks for another stack",
+                        template
                     ),
+                    None if template.is_empty() => "none".to_string(),
+                    None => template.to_string(),
+                };
+                let mut output = format!(
+                    "Sandbox created.\nsandbox_id: {id}\ntemplate: {template}\nstatus: running\npackage_manager: {pm}\n\n{tip}",
+                    pm = pm,
+                    tip = tip
+                );
+                if !prefs.is_empty() {
+                    output.push_str(
+                        "\n\nUser preferences — scaffold with these unless the user asks otherwise:\n",
+                    );
+                    output.push_str(&preferences::format_lines(&prefs));
+                }
+                ToolResult {
+                    success: true,
+                    output,
                     error: None,
+                    error_code: None,
                     error_hint: None,
-                })
+                }
             }
             Err(e) => {
                 let err_msg = format!("{e}");
-                Ok(ToolResult {
+                ToolResult {
                     success: false,
                     output: String::new(),
                     error: Some(format!("🚨 SANDBOX CREATION FAILED: {err_msg}")),
+                    error_code: None,
                     error_hint: Some(
                         "Sandbox creation failed. STOP: Do not proceed with file_write or shell. Fix the sandbox issue first.".to_string(),
                     ),
-                })
+                }
             }
         }
     }
 }
 
+#[async_trait]
+impl Tool for SandboxCreateTool {
+    fn name(&self) -> &str {
+        TOOL_NAME
+    }
+
+    fn description(&self) -> &str {
+        "🚀 Create a new SANDBOX (isolated temp directory) for running code. \
+         \
+         ⚠️ This creates an ISOLATED environment — NOT your local machine. \
+         All build operations (npm, npx, node) must run INSIDE this sandbox via `sandbox_run_command`. \
+         \
+         Pass reset=true to kill any existing sandbox and start fresh. \
+         Returns the sandbox ID — all other sandbox_* tools use this automatically. \
+         Call this before any file or command operations. \
+         \
+         ❌ DO NOT use `shell` tool for npm/npx/node — it runs LOCALLY, not in sandbox!"
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "reset": {
+                    "type": "boolean",
+                    "description": "If true, kill any existing sandbox and create a fresh one. Default: false (resume if possible)."
+                }
+            },
+            "required": []
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let reset = args["reset"].as_bool().unwrap_or(false);
+        Ok(self.create(reset, &self.template).await)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -131,4 +206,24 @@ mod tests {
         let tool = SandboxCreateTool::new(client, "code-interpreter-v1", 600_000);
         assert_eq!(tool.name(), TOOL_NAME);
     }
+
+    #[tokio::test]
+    async fn preferences_pick_the_package_manager_and_are_listed() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let db_path = tmp.path().join("zerobuild.db");
+        let conn = crate::store::init_db(&db_path).unwrap();
+        preferences::save(&conn, preferences::LOCAL_USER, "package_manager", "npm").unwrap();
+        preferences::save(&conn, preferences::LOCAL_USER, "css_framework", "tailwind").unwrap();
+
+        let client = Arc::new(crate::sandbox::mock::MockSandboxClient::new());
+        client.set_package_manager(PackageManager::Pnpm);
+        let tool =
+            SandboxCreateTool::new(client.clone(), "nextjs", 1_000).with_preferences(&db_path);
+        let result = tool.execute(json!({})).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(result.output.contains("package_manager: npm"));
+        assert!(result.output.contains("The user prefers npm"));
+        assert!(result.output.contains("- CSS framework: tailwind"));
+        assert_eq!(client.package_manager(), PackageManager::Npm);
+    }
 }
@@ -0,0 +1,157 @@
+//! `generate_docs` tool — write or refresh the project's README, API
+//! reference and architecture overview from its source.
+//!
+//! See [`crate::sandbox::docs`] for what is generated and how edits outside
+//! the generated blocks are kept.
+
+use crate::sandbox::docs::{update_project_docs, DocStatus};
+use crate::sandbox::project_config::{ProjectConfig, PROJECT_DIR};
+use crate::sandbox::SandboxClient;
+use crate::tools::traits::{Tool, ToolResult};
+use async_trait::async_trait;
+use serde_json::json;
+use std::fmt::Write;
+use std::sync::Arc;
+
+const TOOL_NAME: &str = "generate_docs";
+
+pub struct GenerateDocsTool {
+    client: Arc<dyn SandboxClient>,
+}
+
+impl GenerateDocsTool {
+    pub fn new(client: Arc<dyn SandboxClient>) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl Tool for GenerateDocsTool {
+    fn name(&self) -> &str {
+        TOOL_NAME
+    }
+
+    fn description(&self) -> &str {
+        "Generate or refresh the project's README.md, docs/API.md (every HTTP route found in \
+         Next.js, Express, FastAPI, Flask, axum or actix handlers) and docs/ARCHITECTURE.md \
+         (stack, entry points, pages, directory layout) from the current source. Only the \
+         block between the zerobuild:generated markers is rewritten, so hand-written text \
+         around it is kept. Run it before deploying and again after changing routes or \
+         structure. Requires an active sandbox."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "workdir": {
+                    "type": "string",
+                    "description": "Project directory relative to sandbox root. Default: the workdir from project/.zerobuild.toml, else 'project'."
+                }
+            },
+            "required": []
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        if let Err(e) = self.client.require_id() {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(e),
+                error_code: None,
+                error_hint: None,
+            });
+        }
+
+        let workdir = match args["workdir"].as_str() {
+            Some(dir) => dir.to_string(),
+            None => ProjectConfig::load(self.client.as_ref())
+                .await
+                .ok()
+                .flatten()
+                .map_or_else(|| PROJECT_DIR.to_string(), |config| config.workdir()),
+        };
+
+        match update_project_docs(self.client.as_ref(), &workdir).await {
+            Ok(report) => {
+                let mut output = String::new();
+                for (path, status) in &report {
+                    let _ = match status {
+                        DocStatus::Created => writeln!(output, "created {path}"),
+                        DocStatus::Updated => writeln!(output, "updated {path}"),
+                        DocStatus::Unchanged => writeln!(output, "unchanged {path}"),
+                        DocStatus::Skipped(reason) => writeln!(output, "skipped {path}: {reason}"),
+                    };
+                }
+                Ok(ToolResult {
+                    success: true,
+                    output: output.trim_end().to_string(),
+                    error: None,
+                    error_code: None,
+                    error_hint: None,
+                })
+            }
+            Err(e) => Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(format!("Failed to generate docs: {e}")),
+                error_code: None,
+                error_hint: None,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn generates_then_refreshes_keeping_edits() {
+        let client = Arc::new(crate::sandbox::local::LocalProcessSandboxClient::new());
+        client.create_sandbox(false, "", 30_000).await.unwrap();
+        client
+            .write_file(
+                "project/package.json",
+                r#"{"name": "api", "dependencies": {"express": "^4.19.0"}}"#,
+            )
+            .await
+            .unwrap();
+        client
+            .write_file("project/server.js", "app.get('/health', ok);")
+            .await
+            .unwrap();
+        let tool = GenerateDocsTool::new(client.clone());
+
+        let first = tool.execute(json!({})).await.unwrap();
+        assert!(first.success);
+        assert!(first.output.contains("created project/README.md"));
+        assert!(first.output.contains("created project/docs/API.md"));
+
+        let readme = client.read_file("project/README.md").await.unwrap();
+        client
+            .write_file(
+                "project/README.md",
+                &format!("{readme}\n## Notes\nKeep me.\n"),
+            )
+            .await
+            .unwrap();
+        client
+            .write_file(
+                "project/server.js",
+                "app.get('/health', ok);\napp.post('/orders', create);",
+            )
+            .await
+            .unwrap();
+
+        let second = tool.execute(json!({})).await.unwrap();
+        assert!(second.output.contains("updated project/docs/API.md"));
+        assert!(second.output.contains("unchanged project/README.md"));
+        let api = client.read_file("project/docs/API.md").await.unwrap();
+        assert!(api.contains("| POST | `/orders` | `server.js:2` |"));
+        let readme = client.read_file("project/README.md").await.unwrap();
+        assert!(readme.contains("Keep me."));
+        client.kill_sandbox().await.unwrap();
+    }
+}
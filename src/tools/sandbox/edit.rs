@@ -0,0 +1,398 @@
+//! `sandbox_edit_file` tool — change part of a sandbox file without
+//! rewriting it: replace a line range, or replace an exact string with
+//! occurrence checks.
+
+use crate::sandbox::project_config::check_writable;
+use crate::sandbox::SandboxClient;
+use crate::tools::traits::{Tool, ToolResult};
+use async_trait::async_trait;
+use serde_json::json;
+use std::fmt::Write;
+use std::sync::Arc;
+
+const TOOL_NAME: &str = "sandbox_edit_file";
+
+/// Lines of context shown around an edit.
+const CONTEXT_LINES: usize = 2;
+
+/// Most lines of the edited region echoed back.
+const MAX_ECHO_LINES: usize = 40;
+
+/// Lines `start..=end` (1-based, clamped to the file) prefixed with their
+/// line numbers.
+pub(super) fn numbered_lines(content: &str, start: usize, end: usize) -> String {
+    let mut out = String::new();
+    for (i, line) in content
+        .lines()
+        .enumerate()
+        .skip(start.saturating_sub(1))
+        .take((end + 1).saturating_sub(start.max(1)))
+    {
+        let _ = writeln!(out, "{:>6} | {line}", i + 1);
+    }
+    out.trim_end_matches('\n').to_string()
+}
+
+/// Number of the line containing byte offset `offset`.
+fn line_of(content: &str, offset: usize) -> usize {
+    content[..offset].matches('\n').count() + 1
+}
+
+/// `content` with lines `start..=end` (1-based) replaced by `replacement`.
+/// An empty `replacement` deletes the lines.
+fn replace_lines(
+    content: &str,
+    start: usize,
+    end: usize,
+    replacement: &str,
+) -> Result<String, String> {
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let total = lines.len();
+    if start == 0 || end < start {
+        return Err(format!(
+            "Invalid line range {start}-{end}: lines are 1-based and end_line must be >= start_line"
+        ));
+    }
+    if end > total {
+        return Err(format!(
+            "Line range {start}-{end} is past the end of the file ({total} lines)"
+        ));
+    }
+
+    let mut out = lines[..start - 1].concat();
+    if !replacement.is_empty() {
+        out.push_str(replacement);
+        // Keep the line break that ended the replaced range.
+        if lines[end - 1].ends_with('\n') && !replacement.ends_with('\n') {
+            out.push('\n');
+        }
+    }
+    out.push_str(&lines[end..].concat());
+    Ok(out)
+}
+
+/// What a string replacement should touch.
+enum Occurrence {
+    /// The only match; more than one is an error.
+    Only,
+    /// The n-th match (1-based).
+    Nth(usize),
+    /// Every match.
+    All,
+}
+
+/// `content` with `old` replaced by `new`, plus the number of replacements
+/// and the byte offset of the first one.
+fn replace_string(
+    content: &str,
+    old: &str,
+    new: &str,
+    occurrence: &Occurrence,
+) -> Result<(String, usize, usize), String> {
+    if old.is_empty() {
+        return Err("old_string cannot be empty".into());
+    }
+    let offsets: Vec<usize> = content.match_indices(old).map(|(i, _)| i).collect();
+    let lines = || {
+        offsets
+            .iter()
+            .map(|offset| line_of(content, *offset).to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    match (occurrence, offsets.len()) {
+        (_, 0) => {
+            Err("old_string not found in file; read the file again, it may have changed".into())
+        }
+        (Occurrence::Only, 1) => Ok((content.replacen(old, new, 1), 1, offsets[0])),
+        (Occurrence::Only, n) => Err(format!(
+            "old_string matches {n} times (lines {}); include more surrounding text, or pass \
+             occurrence or replace_all",
+            lines()
+        )),
+        (Occurrence::Nth(n), count) if *n == 0 || *n > count => Err(format!(
+            "occurrence {n} requested but old_string matches {count} times (lines {})",
+            lines()
+        )),
+        (Occurrence::Nth(n), _) => {
+            let offset = offsets[n - 1];
+            let mut out = String::with_capacity(content.len() + new.len());
+            out.push_str(&content[..offset]);
+            out.push_str(new);
+            out.push_str(&content[offset + old.len()..]);
+            Ok((out, 1, offset))
+        }
+        (Occurrence::All, count) => Ok((content.replace(old, new), count, offsets[0])),
+    }
+}
+
+pub struct SandboxEditFileTool {
+    client: Arc<dyn SandboxClient>,
+}
+
+impl SandboxEditFileTool {
+    pub fn new(client: Arc<dyn SandboxClient>) -> Self {
+        Self { client }
+    }
+}
+
+fn failure(error: String) -> ToolResult {
+    ToolResult {
+        success: false,
+        output: String::new(),
+        error: Some(error),
+        error_code: None,
+        error_hint: None,
+    }
+}
+
+fn line_arg(args: &serde_json::Value, key: &str) -> Option<usize> {
+    args[key].as_u64().and_then(|n| usize::try_from(n).ok())
+}
+
+#[async_trait]
+impl Tool for SandboxEditFileTool {
+    fn name(&self) -> &str {
+        TOOL_NAME
+    }
+
+    fn description(&self) -> &str {
+        "Edit part of a file in the sandbox instead of rewriting all of it. Either replace \
+         lines start_line..end_line (from sandbox_read_file with line numbers) with \
+         new_content, or replace old_string with new_string: old_string must match exactly \
+         once unless occurrence (1-based) or replace_all is given. Returns the edited lines \
+         with line numbers. Requires an active sandbox."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "File path relative to sandbox root (e.g. 'project/src/app/page.tsx')"
+                },
+                "start_line": {
+                    "type": "integer",
+                    "description": "Line range mode: first line to replace (1-based)"
+                },
+                "end_line": {
+                    "type": "integer",
+                    "description": "Line range mode: last line to replace (inclusive)"
+                },
+                "new_content": {
+                    "type": "string",
+                    "description": "Line range mode: replacement lines (empty string deletes the range)"
+                },
+                "expected_content": {
+                    "type": "string",
+                    "description": "Line range mode, optional: current text of the range; the edit is refused if the file no longer matches"
+                },
+                "old_string": {
+                    "type": "string",
+                    "description": "String mode: exact text to replace, with enough surrounding lines to be unique"
+                },
+                "new_string": {
+                    "type": "string",
+                    "description": "String mode: replacement text (empty string deletes the match)"
+                },
+                "occurrence": {
+                    "type": "integer",
+                    "description": "String mode: replace only this match (1-based) when old_string matches several times"
+                },
+                "replace_all": {
+                    "type": "boolean",
+                    "description": "String mode: replace every match. Default: false."
+                }
+            },
+            "required": ["path"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        if let Err(e) = self.client.require_id() {
+            return Ok(failure(e));
+        }
+        let path = args["path"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Missing required parameter: path"))?;
+        if let Err(e) = check_writable(self.client.as_ref(), &[path]).await {
+            return Ok(failure(e));
+        }
+        let content = match self.client.read_file(path).await {
+            Ok(content) => content,
+            Err(e) => return Ok(failure(format!("Failed to read file: {e}"))),
+        };
+
+        let (updated, first_line, summary) = if let Some(old) = args["old_string"].as_str() {
+            let new = args["new_string"].as_str().unwrap_or_default();
+            let occurrence = if args["replace_all"].as_bool().unwrap_or(false) {
+                Occurrence::All
+            } else if let Some(n) = line_arg(&args, "occurrence") {
+                Occurrence::Nth(n)
+            } else {
+                Occurrence::Only
+            };
+            match replace_string(&content, old, new, &occurrence) {
+                Ok((updated, count, offset)) => {
+                    let noun = if count == 1 {
+                        "occurrence"
+                    } else {
+                        "occurrences"
+                    };
+                    let line = line_of(&content, offset);
+                    (
+                        updated,
+                        line,
+                        format!("replaced {count} {noun} (first at line {line})"),
+                    )
+                }
+                Err(e) => return Ok(failure(e)),
+            }
+        } else if let (Some(start), Some(end)) =
+            (line_arg(&args, "start_line"), line_arg(&args, "end_line"))
+        {
+            let Some(new) = args["new_content"].as_str() else {
+                return Ok(failure("Line range mode needs new_content".into()));
+            };
+            if let Some(expected) = args["expected_content"].as_str() {
+                let current: String = content
+                    .split_inclusive('\n')
+                    .skip(start.saturating_sub(1))
+                    .take((end + 1).saturating_sub(start))
+                    .collect();
+                if current.trim_end_matches('\n') != expected.trim_end_matches('\n') {
+                    return Ok(failure(format!(
+                        "Lines {start}-{end} no longer match expected_content; read them again \
+                         before editing. Current text:\n{}",
+                        numbered_lines(&content, start, end)
+                    )));
+                }
+            }
+            match replace_lines(&content, start, end, new) {
+                Ok(updated) => {
+                    let added = new.lines().count();
+                    (
+                        updated,
+                        start,
+                        format!(
+                            "replaced lines {start}-{end} ({} lines) with {added} lines",
+                            end - start + 1
+                        ),
+                    )
+                }
+                Err(e) => return Ok(failure(e)),
+            }
+        } else {
+            return Ok(failure(
+                "Pass either old_string (and new_string) or start_line, end_line and \
+                 new_content"
+                    .into(),
+            ));
+        };
+
+        if let Err(e) = self.client.write_file(path, &updated).await {
+            return Ok(failure(format!("Failed to write file: {e}")));
+        }
+
+        let changed_lines = updated
+            .lines()
+            .count()
+            .saturating_sub(content.lines().count());
+        let echo_start = first_line.saturating_sub(CONTEXT_LINES).max(1);
+        let echo_end =
+            (first_line + changed_lines + CONTEXT_LINES).min(echo_start + MAX_ECHO_LINES - 1);
+        Ok(ToolResult {
+            success: true,
+            output: format!(
+                "Edited {path}: {summary}\n{}",
+                numbered_lines(&updated, echo_start, echo_end)
+            ),
+            error: None,
+            error_code: None,
+            error_hint: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = "a\nb\nc\nd\n";
+
+    #[test]
+    fn line_ranges_replace_and_delete() {
+        assert_eq!(replace_lines(FILE, 2, 3, "X").unwrap(), "a\nX\nd\n");
+        assert_eq!(
+            replace_lines(FILE, 2, 2, "X\nY\n").unwrap(),
+            "a\nX\nY\nc\nd\n"
+        );
+        assert_eq!(replace_lines(FILE, 1, 4, "").unwrap(), "");
+        assert_eq!(replace_lines("a\nb", 2, 2, "c").unwrap(), "a\nc");
+        assert!(replace_lines(FILE, 3, 9, "X")
+            .unwrap_err()
+            .contains("4 lines"));
+        assert!(replace_lines(FILE, 0, 1, "X").is_err());
+        assert_eq!(numbered_lines(FILE, 3, 9), "     3 | c\n     4 | d");
+    }
+
+    #[test]
+    fn string_replacement_checks_occurrences() {
+        let text = "let x = 1;\nlet y = 1;\nlet z = 2;\n";
+        let err = replace_string(text, "= 1", "= 3", &Occurrence::Only).unwrap_err();
+        assert!(err.contains("matches 2 times (lines 1, 2)"));
+        let (out, count, _) = replace_string(text, "= 1", "= 3", &Occurrence::Nth(2)).unwrap();
+        assert_eq!(
+            (out.as_str(), count),
+            ("let x = 1;\nlet y = 3;\nlet z = 2;\n", 1)
+        );
+        let (out, count, _) = replace_string(text, "= 1", "= 3", &Occurrence::All).unwrap();
+        assert_eq!((out.matches("= 3").count(), count), (2, 2));
+        assert!(replace_string(text, "= 9", "", &Occurrence::Only)
+            .unwrap_err()
+            .contains("not found"));
+        assert!(replace_string(text, "= 1", "", &Occurrence::Nth(3)).is_err());
+    }
+
+    #[tokio::test]
+    async fn edits_sandbox_file_and_echoes_lines() {
+        let client = Arc::new(crate::sandbox::local::LocalProcessSandboxClient::new());
+        client.create_sandbox(false, "", 30_000).await.unwrap();
+        client.write_file("project/app.ts", FILE).await.unwrap();
+        let tool = SandboxEditFileTool::new(client.clone());
+
+        let result = tool
+            .execute(
+                json!({"path": "project/app.ts", "start_line": 2, "end_line": 2,
+                            "new_content": "B", "expected_content": "b"}),
+            )
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(result
+            .output
+            .starts_with("Edited project/app.ts: replaced lines 2-2"));
+        assert!(result.output.contains("     2 | B"));
+
+        let stale = tool
+            .execute(
+                json!({"path": "project/app.ts", "start_line": 2, "end_line": 2,
+                            "new_content": "Z", "expected_content": "b"}),
+            )
+            .await
+            .unwrap();
+        assert!(!stale.success);
+
+        let result = tool
+            .execute(json!({"path": "project/app.ts", "old_string": "c\n", "new_string": "C\n"}))
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert_eq!(
+            client.read_file("project/app.ts").await.unwrap(),
+            "a\nB\nC\nd\n"
+        );
+        client.kill_sandbox().await.unwrap();
+    }
+}
@@ -0,0 +1,241 @@
+//! `sandbox_set_env` tool — set an environment variable in the project's
+//! `.env` and track it for `.env.example`.
+
+use crate::sandbox::project_config::PROJECT_DIR;
+use crate::sandbox::SandboxClient;
+use crate::store;
+use crate::tools::traits::{Tool, ToolResult};
+use async_trait::async_trait;
+use serde_json::json;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const TOOL_NAME: &str = "sandbox_set_env";
+
+/// Whether `name` is a valid environment variable name.
+fn valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// `value` as written after `NAME=`, quoted when dotenv parsers would
+/// otherwise cut it short.
+fn dotenv_value(value: &str) -> String {
+    if value
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "-_./:@+,=".contains(c))
+    {
+        value.to_string()
+    } else {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+}
+
+/// `env` with `name` set to `value`, replacing an existing assignment.
+fn set_line(env: &str, name: &str, value: &str) -> String {
+    let line = format!("{name}={}", dotenv_value(value));
+    let mut replaced = false;
+    let mut lines: Vec<String> = env
+        .lines()
+        .map(|existing| {
+            let assigned = existing
+                .trim_start()
+                .trim_start_matches("export ")
+                .split_once('=')
+                .is_some_and(|(key, _)| key.trim() == name);
+            if assigned && !replaced {
+                replaced = true;
+                line.clone()
+            } else {
+                existing.to_string()
+            }
+        })
+        .collect();
+    if !replaced {
+        lines.push(line);
+    }
+    lines.join("\n") + "\n"
+}
+
+pub struct SandboxSetEnvTool {
+    client: Arc<dyn SandboxClient>,
+    db_path: PathBuf,
+}
+
+impl SandboxSetEnvTool {
+    pub fn new(client: Arc<dyn SandboxClient>, db_path: impl Into<PathBuf>) -> Self {
+        Self {
+            client,
+            db_path: db_path.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for SandboxSetEnvTool {
+    fn name(&self) -> &str {
+        TOOL_NAME
+    }
+
+    fn description(&self) -> &str {
+        "Set an environment variable the project needs (API keys, database URLs, feature \
+         flags) in project/.env. Always use this instead of writing .env by hand: the \
+         variable and its description are recorded and listed in .env.example on the next \
+         snapshot and deploy, without the value. Describe what the variable is for."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "name": {
+                    "type": "string",
+                    "description": "Variable name, e.g. 'DATABASE_URL'."
+                },
+                "value": {
+                    "type": "string",
+                    "description": "Value for the sandbox. Never copied into .env.example."
+                },
+                "description": {
+                    "type": "string",
+                    "description": "What the variable is for, e.g. 'Postgres connection string'."
+                },
+                "required": {
+                    "type": "boolean",
+                    "description": "Whether the app fails without it. Default: true."
+                }
+            },
+            "required": ["name", "value", "description"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        if let Err(e) = self.client.require_id() {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(e),
+                error_code: None,
+                error_hint: Some("Call sandbox_create before sandbox_set_env.".to_string()),
+            });
+        }
+
+        let name = args["name"].as_str().unwrap_or("").trim();
+        let value = args["value"].as_str().unwrap_or("");
+        let description = args["description"].as_str().unwrap_or("").trim();
+        let required = args["required"].as_bool().unwrap_or(true);
+        let invalid = if !valid_name(name) {
+            Some(format!(
+                "Invalid variable name '{name}': use letters, digits and '_', not starting with a digit."
+            ))
+        } else if value.contains('\n') {
+            Some("The value must be a single line.".to_string())
+        } else if description.is_empty() || description.contains('\n') {
+            Some("Give a one-line description of what the variable is for.".to_string())
+        } else {
+            None
+        };
+        if let Some(error) = invalid {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(error),
+                error_code: None,
+                error_hint: None,
+            });
+        }
+
+        let path = format!("{PROJECT_DIR}/.env");
+        let env = self.client.read_file(&path).await.unwrap_or_default();
+        if let Err(e) = self
+            .client
+            .write_file(&path, &set_line(&env, name, value))
+            .await
+        {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(format!("Failed to write {path}: {e}")),
+                error_code: None,
+                error_hint: None,
+            });
+        }
+
+        let tracked = store::init_db(&self.db_path)
+            .and_then(|conn| store::env_vars::save(&conn, name, description, required));
+        if let Err(e) = tracked {
+            tracing::warn!("Failed to track environment variable {name}: {e:#}");
+        }
+
+        Ok(ToolResult {
+            success: true,
+            output: format!(
+                "Set {name} in {path} (value hidden). It is listed in .env.example as {} \
+                 on the next snapshot or deploy.",
+                if required { "required" } else { "optional" }
+            ),
+            error: None,
+            error_code: None,
+            error_hint: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sandbox::mock::MockSandboxClient;
+    use tempfile::TempDir;
+
+    #[test]
+    fn set_line_replaces_or_appends() {
+        assert_eq!(set_line("", "A", "1"), "A=1\n");
+        assert_eq!(
+            set_line("# db\nA=1\nexport B=2", "B", "two words"),
+            "# db\nA=1\nB=\"two words\"\n"
+        );
+        assert_eq!(
+            dotenv_value("postgres://u@h:5432/db"),
+            "postgres://u@h:5432/db"
+        );
+        assert_eq!(dotenv_value("a\"b#"), "\"a\\\"b#\"");
+        assert!(valid_name("_API_KEY2"));
+        assert!(!valid_name("2FA") && !valid_name("A-B") && !valid_name(""));
+    }
+
+    #[tokio::test]
+    async fn writes_dotenv_and_tracks_the_variable() {
+        let tmp = TempDir::new().unwrap();
+        let db_path = tmp.path().join("zerobuild.db");
+        let client = Arc::new(MockSandboxClient::new());
+        client.create_sandbox(false, "", 1000).await.unwrap();
+        let tool = SandboxSetEnvTool::new(client.clone(), &db_path);
+
+        let result = tool
+            .execute(json!({
+                "name": "STRIPE_KEY",
+                "value": "sk_test_123",
+                "description": "Stripe secret key"
+            }))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(!result.output.contains("sk_test_123"));
+        assert_eq!(client.files()["project/.env"], "STRIPE_KEY=sk_test_123\n");
+
+        let conn = store::init_db(&db_path).unwrap();
+        let tracked = store::env_vars::list(&conn).unwrap();
+        assert_eq!(tracked[0].name, "STRIPE_KEY");
+        assert_eq!(tracked[0].description, "Stripe secret key");
+        assert!(tracked[0].required);
+
+        let invalid = tool
+            .execute(json!({"name": "BAD-NAME", "value": "x", "description": "y"}))
+            .await
+            .unwrap();
+        assert!(!invalid.success);
+    }
+}
@@ -0,0 +1,229 @@
+//! Failure analyzer for `sandbox_run_command`.
+//!
+//! When a command exits nonzero the agent usually only sees the tail of
+//! stderr. This module pulls `file:line` references out of the output
+//! (tsc, eslint, vite/webpack, Next.js, Node stack traces), reads the
+//! surrounding source lines from the sandbox, and adds the project
+//! manifests (`package.json`, `tsconfig.json`) so the next turn can fix
+//! the failure without extra read round-trips.
+
+use crate::sandbox::SandboxClient;
+use regex::Regex;
+use std::fmt::Write;
+use std::sync::OnceLock;
+
+/// Source lines shown on each side of a referenced error line.
+const CONTEXT_LINES: usize = 6;
+/// Maximum number of distinct error locations expanded per failure.
+const MAX_LOCATIONS: usize = 3;
+/// Manifest files attached when present in the working directory.
+const MANIFESTS: &[&str] = &["package.json", "tsconfig.json"];
+/// Cap on each attached manifest, in characters.
+const MAX_MANIFEST_CHARS: usize = 3_000;
+
+/// A source location referenced by compiler or runtime output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorLocation {
+    pub path: String,
+    pub line: usize,
+}
+
+fn location_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        // `src/a.ts:12:5`, `./src/a.tsx:12`, `src/a.ts(12,5)` (tsc).
+        Regex::new(
+            r"(?P<path>[\w@./\-\[\]]+\.(?:tsx?|jsx?|mjs|cjs|vue|svelte|astro|css|scss|json|py))(?::(?P<line>\d+)(?::\d+)?|\((?P<tsc_line>\d+),\d+\))",
+        )
+        .expect("valid error location regex")
+    })
+}
+
+/// Extract up to [`MAX_LOCATIONS`] distinct project file locations from
+/// command output, in order of appearance. Dependencies under
+/// `node_modules` are skipped since the fix is never there.
+pub fn parse_error_locations(output: &str) -> Vec<ErrorLocation> {
+    let mut locations: Vec<ErrorLocation> = Vec::new();
+    for caps in location_regex().captures_iter(output) {
+        let path = caps["path"].trim_start_matches("./");
+        if path.contains("node_modules/") {
+            continue;
+        }
+        let Some(line) = caps
+            .name("line")
+            .or_else(|| caps.name("tsc_line"))
+            .and_then(|m| m.as_str().parse::<usize>().ok())
+            .filter(|line| *line > 0)
+        else {
+            continue;
+        };
+        if locations.iter().any(|l| l.path == path) {
+            continue;
+        }
+        locations.push(ErrorLocation {
+            path: path.to_string(),
+            line,
+        });
+        if locations.len() == MAX_LOCATIONS {
+            break;
+        }
+    }
+    locations
+}
+
+/// Render `line` of `content` with [`CONTEXT_LINES`] of context, numbered
+/// and with the referenced line marked by `>`.
+fn excerpt(content: &str, line: usize) -> String {
+    let start = line.saturating_sub(CONTEXT_LINES).max(1);
+    let end = line + CONTEXT_LINES;
+    let mut out = String::new();
+    for (number, text) in content
+        .lines()
+        .enumerate()
+        .map(|(i, text)| (i + 1, text))
+        .skip(start - 1)
+        .take_while(|(number, _)| *number <= end)
+    {
+        let marker = if number == line { '>' } else { ' ' };
+        let _ = writeln!(out, "{marker}{number:>5} | {text}");
+    }
+    out
+}
+
+/// Map a path from command output to a path relative to the sandbox root.
+fn sandbox_path(client: &dyn SandboxClient, workdir: &str, path: &str) -> String {
+    if path.starts_with('/') {
+        // Absolute paths inside the sandbox directory (Node stack traces).
+        if let Some(root) = client.current_id() {
+            if let Some(rest) = path.strip_prefix(root.as_str()) {
+                return rest.trim_start_matches('/').to_string();
+            }
+        }
+        return path.to_string();
+    }
+    let workdir = workdir.trim_matches('/');
+    if workdir.is_empty() || workdir == "." {
+        path.to_string()
+    } else {
+        format!("{workdir}/{path}")
+    }
+}
+
+/// Gather source excerpts and manifests relevant to a failed command.
+/// Returns `None` when nothing useful could be read.
+pub async fn gather_failure_context(
+    client: &dyn SandboxClient,
+    workdir: &str,
+    stdout: &str,
+    stderr: &str,
+) -> Option<String> {
+    let mut out = String::new();
+
+    // Errors are usually on stderr, but tsc and Next.js print to stdout.
+    let combined = format!("{stderr}\n{stdout}");
+    for location in parse_error_locations(&combined) {
+        let path = sandbox_path(client, workdir, &location.path);
+        let Ok(content) = client.read_file(&path).await else {
+            continue;
+        };
+        let snippet = excerpt(&content, location.line);
+        if snippet.is_empty() {
+            continue;
+        }
+        let _ = write!(
+            out,
+            "\n--- {} (line {}) ---\n{snippet}",
+            location.path, location.line
+        );
+    }
+
+    for manifest in MANIFESTS {
+        let path = sandbox_path(client, workdir, manifest);
+        let Ok(content) = client.read_file(&path).await else {
+            continue;
+        };
+        let mut content = content.trim_end().to_string();
+        if content.chars().count() > MAX_MANIFEST_CHARS {
+            content = content.chars().take(MAX_MANIFEST_CHARS).collect();
+            content.push_str("\n... (truncated)");
+        }
+        let _ = write!(out, "\n--- {manifest} ---\n{content}\n");
+    }
+
+    (!out.is_empty()).then_some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sandbox::local::LocalProcessSandboxClient;
+
+    #[test]
+    fn parses_common_compiler_formats() {
+        let output = "\
+src/app/page.tsx(12,5): error TS2322: Type 'string' is not assignable to type 'number'.
+./src/components/Cart.tsx:40:7
+Type error: Property 'total' does not exist
+    at Object.<anonymous> (node_modules/next/dist/server.js:10:3)
+src/app/page.tsx:13:1 duplicate of the first file
+src/lib/api.js:8";
+        assert_eq!(
+            parse_error_locations(output),
+            vec![
+                ErrorLocation {
+                    path: "src/app/page.tsx".into(),
+                    line: 12
+                },
+                ErrorLocation {
+                    path: "src/components/Cart.tsx".into(),
+                    line: 40
+                },
+                ErrorLocation {
+                    path: "src/lib/api.js".into(),
+                    line: 8
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn excerpt_marks_error_line_and_clamps_to_file() {
+        let content = "a\nb\nc\nd";
+        let snippet = excerpt(content, 2);
+        assert!(snippet.starts_with("     1 | a\n>    2 | b\n"));
+        assert!(snippet.ends_with("     4 | d\n"));
+        assert!(excerpt(content, 40).is_empty());
+    }
+
+    #[tokio::test]
+    async fn gathers_source_excerpt_and_manifests() {
+        let client = LocalProcessSandboxClient::new();
+        client.create_sandbox(false, "", 30_000).await.unwrap();
+        client
+            .write_file(
+                "project/src/index.ts",
+                "const a = 1;\nconst b: number = 'x';\n",
+            )
+            .await
+            .unwrap();
+        client
+            .write_file("project/package.json", r#"{"scripts":{"build":"tsc"}}"#)
+            .await
+            .unwrap();
+
+        let context = gather_failure_context(
+            &client,
+            "project",
+            "src/index.ts(2,7): error TS2322: Type 'string' is not assignable",
+            "",
+        )
+        .await
+        .unwrap();
+        assert!(context.contains("--- src/index.ts (line 2) ---"));
+        assert!(context.contains(">    2 | const b: number = 'x';"));
+        assert!(context.contains("--- package.json ---"));
+        assert!(!context.contains("tsconfig.json"));
+
+        client.kill_sandbox().await.unwrap();
+    }
+}
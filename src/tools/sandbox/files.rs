@@ -1,5 +1,9 @@
-//! Sandbox file operation tools: write_file, read_file, list_files.
+//! Sandbox file operation tools: write_file, write_files, read_file, list_files,
+//! move_file, copy_file, delete_path.
 
+use super::edit::numbered_lines;
+use crate::error;
+use crate::sandbox::project_config::check_writable;
 use crate::sandbox::SandboxClient;
 use crate::tools::traits::{Tool, ToolResult};
 use async_trait::async_trait;
@@ -53,6 +57,7 @@ impl Tool for SandboxWriteFileTool {
                 success: false,
                 output: String::new(),
                 error: Some(e),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -64,24 +69,153 @@ impl Tool for SandboxWriteFileTool {
         let content = args["content"]
             .as_str()
             .ok_or_else(|| anyhow::anyhow!("Missing required parameter: content"))?;
+        if let Err(e) = check_writable(self.client.as_ref(), &[path]).await {
+            return Ok(failure(e));
+        }
 
         match self.client.write_file(path, content).await {
             Ok(()) => Ok(ToolResult {
                 success: true,
                 output: format!("File written: {path}"),
                 error: None,
+                error_code: None,
                 error_hint: None,
             }),
             Err(e) => Ok(ToolResult {
                 success: false,
                 output: String::new(),
                 error: Some(format!("Failed to write file: {e}")),
+                error_code: Some(error::code_of(&e)),
                 error_hint: None,
             }),
         }
     }
 }
 
+// ── sandbox_write_files ───────────────────────────────────────────────────────────
+
+/// Most files accepted by one `sandbox_write_files` call.
+const MAX_BATCH_FILES: usize = 100;
+
+pub struct SandboxWriteFilesTool {
+    client: Arc<dyn SandboxClient>,
+}
+
+impl SandboxWriteFilesTool {
+    pub fn new(client: Arc<dyn SandboxClient>) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl Tool for SandboxWriteFilesTool {
+    fn name(&self) -> &str {
+        "sandbox_write_files"
+    }
+
+    fn description(&self) -> &str {
+        "Write several files to the sandbox in one call. Creates parent directories as needed \
+         and reports success per file. Prefer this over repeated sandbox_write_file calls when \
+         scaffolding a project or changing many files. Requires an active sandbox."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "files": {
+                    "type": "array",
+                    "description": "Files to write (at most 100)",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "path": {
+                                "type": "string",
+                                "description": "File path relative to sandbox root (e.g. 'project/src/app/page.tsx'). NEVER use absolute paths like /home/user/project/..."
+                            },
+                            "content": {
+                                "type": "string",
+                                "description": "Full file content to write"
+                            }
+                        },
+                        "required": ["path", "content"]
+                    }
+                }
+            },
+            "required": ["files"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        if let Err(e) = self.client.require_id() {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(e),
+                error_code: None,
+                error_hint: None,
+            });
+        }
+
+        let entries = args["files"]
+            .as_array()
+            .ok_or_else(|| anyhow::anyhow!("Missing required parameter: files"))?;
+        if entries.is_empty() || entries.len() > MAX_BATCH_FILES {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(format!(
+                    "files must contain between 1 and {MAX_BATCH_FILES} entries, got {}",
+                    entries.len()
+                )),
+                error_code: None,
+                error_hint: None,
+            });
+        }
+
+        // Malformed entries are reported in place; the rest are still written.
+        let mut lines = vec![String::new(); entries.len()];
+        let mut batch = Vec::new();
+        let mut batch_index = Vec::new();
+        for (i, entry) in entries.iter().enumerate() {
+            match (entry["path"].as_str(), entry["content"].as_str()) {
+                (Some(path), Some(content)) => {
+                    if let Err(e) = check_writable(self.client.as_ref(), &[path]).await {
+                        lines[i] = format!("✗ {path}: {e}");
+                        continue;
+                    }
+                    batch.push((path.to_string(), content.to_string()));
+                    batch_index.push(i);
+                }
+                _ => lines[i] = format!("✗ files[{i}]: missing path or content"),
+            }
+        }
+
+        let results = self.client.write_files(&batch).await;
+        for ((i, (path, _)), result) in batch_index.into_iter().zip(&batch).zip(results) {
+            lines[i] = match result {
+                Ok(()) => format!("✓ {path}"),
+                Err(e) => format!("✗ {path}: {e}"),
+            };
+        }
+
+        let failed = lines.iter().filter(|line| line.starts_with('✗')).count();
+        let written = entries.len() - failed;
+        let output = format!(
+            "Wrote {written}/{} files:\n{}",
+            entries.len(),
+            lines.join("\n")
+        );
+        Ok(ToolResult {
+            success: failed == 0,
+            output,
+            error: (failed > 0).then(|| format!("{failed} of {} files failed", entries.len())),
+            error_code: None,
+            error_hint: None,
+        })
+    }
+}
+
 // ── sandbox_read_file ─────────────────────────────────────────────────────────────
 
 pub struct SandboxReadFileTool {
@@ -102,8 +236,9 @@ impl Tool for SandboxReadFileTool {
 
     fn description(&self) -> &str {
         "Read the content of a file from the sandbox. \
-         Returns the file content as a string. \
-         Requires an active sandbox."
+         Returns the file content as a string; with start_line/end_line, only those lines \
+         prefixed with their line numbers (use them with sandbox_edit_file). Prefer a line \
+         range for large files. Requires an active sandbox."
     }
 
     fn parameters_schema(&self) -> serde_json::Value {
@@ -113,6 +248,14 @@ impl Tool for SandboxReadFileTool {
                 "path": {
                     "type": "string",
                     "description": "Absolute file path to read from the sandbox"
+                },
+                "start_line": {
+                    "type": "integer",
+                    "description": "First line to return (1-based). Default: 1 when end_line is set."
+                },
+                "end_line": {
+                    "type": "integer",
+                    "description": "Last line to return (inclusive). Default: end of file when start_line is set."
                 }
             },
             "required": ["path"]
@@ -125,6 +268,7 @@ impl Tool for SandboxReadFileTool {
                 success: false,
                 output: String::new(),
                 error: Some(e),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -133,17 +277,42 @@ impl Tool for SandboxReadFileTool {
             .as_str()
             .ok_or_else(|| anyhow::anyhow!("Missing required parameter: path"))?;
 
+        let line = |key: &str| args[key].as_u64().and_then(|n| usize::try_from(n).ok());
+        let range = match (line("start_line"), line("end_line")) {
+            (None, None) => None,
+            (start, end) => Some((start.unwrap_or(1).max(1), end.unwrap_or(usize::MAX))),
+        };
+
         match self.client.read_file(path).await {
-            Ok(content) => Ok(ToolResult {
-                success: true,
-                output: content,
-                error: None,
-                error_hint: None,
-            }),
+            Ok(content) => {
+                let output = match range {
+                    None => content,
+                    Some((start, end)) => {
+                        let total = content.lines().count();
+                        let end = end.min(total);
+                        if start > total {
+                            format!("{path}: start_line {start} is past the end ({total} lines)")
+                        } else {
+                            format!(
+                                "{path} lines {start}-{end} of {total}:\n{}",
+                                numbered_lines(&content, start, end)
+                            )
+                        }
+                    }
+                };
+                Ok(ToolResult {
+                    success: true,
+                    output,
+                    error: None,
+                    error_code: None,
+                    error_hint: None,
+                })
+            }
             Err(e) => Ok(ToolResult {
                 success: false,
                 output: String::new(),
                 error: Some(format!("Failed to read file: {e}")),
+                error_code: Some(error::code_of(&e)),
                 error_hint: None,
             }),
         }
@@ -193,6 +362,7 @@ impl Tool for SandboxListFilesTool {
                 success: false,
                 output: String::new(),
                 error: Some(e),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -206,14 +376,249 @@ impl Tool for SandboxListFilesTool {
                 success: true,
                 output: listing,
                 error: None,
+                error_code: None,
                 error_hint: None,
             }),
             Err(e) => Ok(ToolResult {
                 success: false,
                 output: String::new(),
                 error: Some(format!("Failed to list files: {e}")),
+                error_code: Some(error::code_of(&e)),
+                error_hint: None,
+            }),
+        }
+    }
+}
+
+// ── sandbox_move_file / sandbox_copy_file / sandbox_delete_path ──────────────────
+
+/// Directories the path tools refuse to move or delete: the sandbox root and
+/// the default project workdir.
+const PROTECTED_PATHS: &[&str] = &["", ".", "project"];
+
+/// `path` without leading `/` or `./` and trailing `/`, for comparing against
+/// [`PROTECTED_PATHS`].
+fn normalize_path(path: &str) -> &str {
+    let mut path = path.trim().trim_matches('/');
+    while let Some(rest) = path.strip_prefix("./") {
+        path = rest.trim_start_matches('/');
+    }
+    path
+}
+
+fn protected(path: &str) -> bool {
+    PROTECTED_PATHS.contains(&normalize_path(path))
+}
+
+fn failure(error: String) -> ToolResult {
+    ToolResult {
+        success: false,
+        output: String::new(),
+        error: Some(error),
+        error_code: None,
+        error_hint: None,
+    }
+}
+
+fn transfer_schema(verb: &str) -> serde_json::Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "from": {
+                "type": "string",
+                "description": format!("File or directory to {verb}, relative to sandbox root (e.g. 'project/src/old.tsx')")
+            },
+            "to": {
+                "type": "string",
+                "description": "Destination path relative to sandbox root (e.g. 'project/src/new.tsx'); parent directories are created"
+            },
+            "overwrite": {
+                "type": "boolean",
+                "description": "Replace the destination if it exists. Default: false."
+            }
+        },
+        "required": ["from", "to"]
+    })
+}
+
+/// `(from, to, overwrite)` arguments of a move or copy.
+fn transfer_args(args: &serde_json::Value) -> anyhow::Result<(&str, &str, bool)> {
+    let from = args["from"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("Missing required parameter: from"))?;
+    let to = args["to"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("Missing required parameter: to"))?;
+    Ok((from, to, args["overwrite"].as_bool().unwrap_or(false)))
+}
+
+pub struct SandboxMoveFileTool {
+    client: Arc<dyn SandboxClient>,
+}
+
+impl SandboxMoveFileTool {
+    pub fn new(client: Arc<dyn SandboxClient>) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl Tool for SandboxMoveFileTool {
+    fn name(&self) -> &str {
+        "sandbox_move_file"
+    }
+
+    fn description(&self) -> &str {
+        "Move or rename a file or directory in the sandbox. Use this instead of `mv` in \
+         sandbox_run_command. Refuses to overwrite unless overwrite=true. Requires an active \
+         sandbox."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        transfer_schema("move")
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        if let Err(e) = self.client.require_id() {
+            return Ok(failure(e));
+        }
+        let (from, to, overwrite) = transfer_args(&args)?;
+        if protected(from) {
+            return Ok(failure(format!(
+                "Refusing to move '{from}': it is the sandbox or project root"
+            )));
+        }
+        if let Err(e) = check_writable(self.client.as_ref(), &[from, to]).await {
+            return Ok(failure(e));
+        }
+
+        match self.client.move_path(from, to, overwrite).await {
+            Ok(()) => Ok(ToolResult {
+                success: true,
+                output: format!("Moved: {from} -> {to}"),
+                error: None,
+                error_code: None,
                 error_hint: None,
             }),
+            Err(e) => Ok(failure(format!("Failed to move: {e}"))),
+        }
+    }
+}
+
+pub struct SandboxCopyFileTool {
+    client: Arc<dyn SandboxClient>,
+}
+
+impl SandboxCopyFileTool {
+    pub fn new(client: Arc<dyn SandboxClient>) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl Tool for SandboxCopyFileTool {
+    fn name(&self) -> &str {
+        "sandbox_copy_file"
+    }
+
+    fn description(&self) -> &str {
+        "Copy a file or a whole directory in the sandbox. Use this instead of `cp` in \
+         sandbox_run_command. Refuses to overwrite unless overwrite=true. Requires an active \
+         sandbox."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        transfer_schema("copy")
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        if let Err(e) = self.client.require_id() {
+            return Ok(failure(e));
+        }
+        let (from, to, overwrite) = transfer_args(&args)?;
+        if let Err(e) = check_writable(self.client.as_ref(), &[to]).await {
+            return Ok(failure(e));
+        }
+
+        match self.client.copy_path(from, to, overwrite).await {
+            Ok(()) => Ok(ToolResult {
+                success: true,
+                output: format!("Copied: {from} -> {to}"),
+                error: None,
+                error_code: None,
+                error_hint: None,
+            }),
+            Err(e) => Ok(failure(format!("Failed to copy: {e}"))),
+        }
+    }
+}
+
+pub struct SandboxDeletePathTool {
+    client: Arc<dyn SandboxClient>,
+}
+
+impl SandboxDeletePathTool {
+    pub fn new(client: Arc<dyn SandboxClient>) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl Tool for SandboxDeletePathTool {
+    fn name(&self) -> &str {
+        "sandbox_delete_path"
+    }
+
+    fn description(&self) -> &str {
+        "Delete a file or directory in the sandbox. Use this instead of `rm` in \
+         sandbox_run_command. Non-empty directories need recursive=true; the sandbox and \
+         project roots cannot be deleted. Requires an active sandbox."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "File or directory to delete, relative to sandbox root (e.g. 'project/src/unused.tsx')"
+                },
+                "recursive": {
+                    "type": "boolean",
+                    "description": "Delete a non-empty directory with everything in it. Default: false."
+                }
+            },
+            "required": ["path"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        if let Err(e) = self.client.require_id() {
+            return Ok(failure(e));
+        }
+        let path = args["path"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Missing required parameter: path"))?;
+        if protected(path) {
+            return Ok(failure(format!(
+                "Refusing to delete '{path}': it is the sandbox or project root. Delete the \
+                 files inside it instead, or recreate the sandbox with sandbox_create reset=true."
+            )));
+        }
+        if let Err(e) = check_writable(self.client.as_ref(), &[path]).await {
+            return Ok(failure(e));
+        }
+        let recursive = args["recursive"].as_bool().unwrap_or(false);
+
+        match self.client.delete_path(path, recursive).await {
+            Ok(()) => Ok(ToolResult {
+                success: true,
+                output: format!("Deleted: {path}"),
+                error: None,
+                error_code: None,
+                error_hint: None,
+            }),
+            Err(e) => Ok(failure(format!("Failed to delete: {e}"))),
         }
     }
 }
@@ -229,6 +634,10 @@ mod tests {
             SandboxWriteFileTool::new(client.clone()).name(),
             "sandbox_write_file"
         );
+        assert_eq!(
+            SandboxWriteFilesTool::new(client.clone()).name(),
+            "sandbox_write_files"
+        );
         assert_eq!(
             SandboxReadFileTool::new(client.clone()).name(),
             "sandbox_read_file"
@@ -238,4 +647,146 @@ mod tests {
             "sandbox_list_files"
         );
     }
+
+    #[tokio::test]
+    async fn read_file_returns_numbered_line_range() {
+        let client = Arc::new(crate::sandbox::local::LocalProcessSandboxClient::new());
+        client.create_sandbox(false, "", 30_000).await.unwrap();
+        client
+            .write_file("project/a.ts", "one\ntwo\nthree\n")
+            .await
+            .unwrap();
+        let tool = SandboxReadFileTool::new(client.clone());
+
+        let range = tool
+            .execute(json!({"path": "project/a.ts", "start_line": 2}))
+            .await
+            .unwrap();
+        assert_eq!(
+            range.output,
+            "project/a.ts lines 2-3 of 3:\n     2 | two\n     3 | three"
+        );
+        let whole = tool.execute(json!({"path": "project/a.ts"})).await.unwrap();
+        assert_eq!(whole.output, "one\ntwo\nthree\n");
+        client.kill_sandbox().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn path_tools_refuse_roots_and_report_results() {
+        let client = Arc::new(crate::sandbox::local::LocalProcessSandboxClient::new());
+        client.create_sandbox(false, "", 30_000).await.unwrap();
+        client
+            .write_file("project/src/old.ts", "export {}")
+            .await
+            .unwrap();
+
+        let moved = SandboxMoveFileTool::new(client.clone())
+            .execute(json!({"from": "project/src/old.ts", "to": "project/src/new.ts"}))
+            .await
+            .unwrap();
+        assert!(moved.success);
+        assert_eq!(
+            moved.output,
+            "Moved: project/src/old.ts -> project/src/new.ts"
+        );
+
+        let copied = SandboxCopyFileTool::new(client.clone())
+            .execute(json!({"from": "project/src/new.ts", "to": "project/src/new.ts.bak"}))
+            .await
+            .unwrap();
+        assert!(copied.success);
+
+        let delete = SandboxDeletePathTool::new(client.clone());
+        for root in ["project", "./project/", "/", "."] {
+            let refused = delete.execute(json!({"path": root})).await.unwrap();
+            assert!(!refused.success, "{root} must be protected");
+            assert!(refused.error.unwrap().contains("Refusing to delete"));
+        }
+        let missing = delete
+            .execute(json!({"path": "project/nope.ts"}))
+            .await
+            .unwrap();
+        assert_eq!(
+            missing.error.as_deref(),
+            Some("Failed to delete: 'project/nope.ts' does not exist")
+        );
+        let deleted = delete
+            .execute(json!({"path": "project/src", "recursive": true}))
+            .await
+            .unwrap();
+        assert!(deleted.success);
+        assert!(client.read_file("project/src/new.ts").await.is_err());
+        client.kill_sandbox().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn write_files_reports_each_file() {
+        let client = Arc::new(crate::sandbox::local::LocalProcessSandboxClient::new());
+        client.create_sandbox(false, "", 30_000).await.unwrap();
+        let tool = SandboxWriteFilesTool::new(client.clone());
+
+        let result = tool
+            .execute(json!({"files": [
+                {"path": "project/src/a.ts", "content": "export const a = 1;"},
+                {"path": "../escape.txt", "content": "bad"},
+                {"path": "project/b.txt"},
+                {"path": "project/c.txt", "content": "c"},
+            ]}))
+            .await
+            .unwrap();
+
+        assert!(!result.success);
+        assert_eq!(result.error.as_deref(), Some("2 of 4 files failed"));
+        let lines: Vec<&str> = result.output.lines().collect();
+        assert_eq!(lines[0], "Wrote 2/4 files:");
+        assert_eq!(lines[1], "✓ project/src/a.ts");
+        assert!(lines[2].starts_with("✗ ../escape.txt"));
+        assert_eq!(lines[3], "✗ files[2]: missing path or content");
+        assert_eq!(lines[4], "✓ project/c.txt");
+        assert_eq!(
+            client.read_file("project/src/a.ts").await.unwrap(),
+            "export const a = 1;"
+        );
+        client.kill_sandbox().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn tools_refuse_paths_protected_by_project_config() {
+        let client = Arc::new(crate::sandbox::local::LocalProcessSandboxClient::new());
+        client.create_sandbox(false, "", 30_000).await.unwrap();
+        client
+            .write_file("project/.zerobuild.toml", "protected = [\"contracts/**\"]")
+            .await
+            .unwrap();
+        client
+            .write_file("project/contracts/Token.sol", "contract Token {}")
+            .await
+            .unwrap();
+
+        let write = SandboxWriteFileTool::new(client.clone())
+            .execute(json!({"path": "project/contracts/Token.sol", "content": ""}))
+            .await
+            .unwrap();
+        assert!(!write.success);
+        assert!(write.error.unwrap().contains("rule 'contracts/**'"));
+
+        let config = SandboxWriteFileTool::new(client.clone())
+            .execute(json!({"path": "project/.zerobuild.toml", "content": ""}))
+            .await
+            .unwrap();
+        assert!(!config.success);
+
+        let delete = SandboxDeletePathTool::new(client.clone())
+            .execute(json!({"path": "project/contracts", "recursive": true}))
+            .await
+            .unwrap();
+        assert!(!delete.success);
+
+        let allowed = SandboxWriteFileTool::new(client.clone())
+            .execute(json!({"path": "project/src/app.ts", "content": "ok"}))
+            .await
+            .unwrap();
+        assert!(allowed.success);
+        client.kill_sandbox().await.unwrap();
+    }
 }
@@ -0,0 +1,196 @@
+//! `sandbox_command_history` tool — list the commands already run in the
+//! sandbox.
+//!
+//! See [`crate::store::command_history`] for what is recorded.
+
+use crate::sandbox::SandboxClient;
+use crate::store;
+use crate::store::command_history::{HistoryEntry, HistoryQuery};
+use crate::tools::traits::{Tool, ToolResult};
+use async_trait::async_trait;
+use serde_json::json;
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const TOOL_NAME: &str = "sandbox_command_history";
+
+/// Commands listed when the agent asks for no particular number.
+const DEFAULT_LIMIT: usize = 20;
+
+/// Most commands listed by one call.
+const MAX_LIMIT: usize = 100;
+
+/// Longest command shown; longer ones are cut.
+const MAX_COMMAND_CHARS: usize = 300;
+
+pub struct SandboxCommandHistoryTool {
+    client: Arc<dyn SandboxClient>,
+    db_path: PathBuf,
+}
+
+impl SandboxCommandHistoryTool {
+    pub fn new(client: Arc<dyn SandboxClient>, db_path: impl Into<PathBuf>) -> Self {
+        Self {
+            client,
+            db_path: db_path.into(),
+        }
+    }
+
+    fn run(&self, args: &serde_json::Value) -> anyhow::Result<String> {
+        let sandbox_id = self.client.current_id();
+        let limit = args["limit"]
+            .as_u64()
+            .and_then(|n| usize::try_from(n).ok())
+            .unwrap_or(DEFAULT_LIMIT)
+            .clamp(1, MAX_LIMIT);
+        let query = HistoryQuery {
+            sandbox_id: sandbox_id.as_deref(),
+            session: args["session"].as_str().filter(|s| !s.is_empty()),
+            failed_only: args["failed_only"].as_bool().unwrap_or(false),
+            contains: args["grep"].as_str().filter(|p| !p.is_empty()),
+            limit,
+        };
+
+        let conn = store::init_db(&self.db_path)?;
+        let entries = store::command_history::recent_commands(&conn, &query)?;
+        if entries.is_empty() {
+            return Ok("No matching commands recorded for this sandbox.".to_string());
+        }
+        let mut out = format!("Last {} commands, oldest first:", entries.len());
+        for entry in &entries {
+            let _ = write!(out, "\n{}", format_entry(entry));
+        }
+        Ok(out)
+    }
+}
+
+/// One history line: where and how long it ran, its exit code, the command
+/// and, when truncated, where its full output is.
+fn format_entry(entry: &HistoryEntry) -> String {
+    let time = entry.created_at.get(11..19).unwrap_or(&entry.created_at);
+    let place = match &entry.session {
+        Some(session) => format!("session {session}, {}", entry.workdir),
+        None => entry.workdir.clone(),
+    };
+    let mut line = format!(
+        "{time} [{place}] exit {} in {:.1}s: {}",
+        entry.exit_code,
+        std::time::Duration::from_millis(entry.duration_ms).as_secs_f64(),
+        crate::util::truncate_with_ellipsis(&entry.command, MAX_COMMAND_CHARS)
+    );
+    if let Some(log_id) = &entry.log_id {
+        let _ = write!(line, " (log_id: {log_id})");
+    }
+    line
+}
+
+#[async_trait]
+impl Tool for SandboxCommandHistoryTool {
+    fn name(&self) -> &str {
+        TOOL_NAME
+    }
+
+    fn description(&self) -> &str {
+        "List commands already run in the current sandbox (sandbox_run_command and \
+         sandbox_shell), oldest first, with exit code, duration, working directory and the \
+         log_id of truncated output. Use it to see what was tried before instead of repeating \
+         it. Filter with failed_only, grep (text in the command) or session."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "limit": {
+                    "type": "integer",
+                    "description": format!(
+                        "Number of most recent commands to list. Default: {DEFAULT_LIMIT}, maximum: {MAX_LIMIT}."
+                    )
+                },
+                "failed_only": {
+                    "type": "boolean",
+                    "description": "Only commands with a non-zero exit code. Default: false."
+                },
+                "grep": {
+                    "type": "string",
+                    "description": "Only commands containing this text (case-insensitive)."
+                },
+                "session": {
+                    "type": "string",
+                    "description": "Only commands of this sandbox_shell session."
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        match self.run(&args) {
+            Ok(output) => Ok(ToolResult {
+                success: true,
+                output,
+                error: None,
+                error_code: None,
+                error_hint: None,
+            }),
+            Err(e) => Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(e.to_string()),
+                error_code: None,
+                error_hint: None,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sandbox::mock::{MockSandboxClient, MOCK_SANDBOX_ID};
+    use crate::store::command_history::NewCommand;
+
+    #[tokio::test]
+    async fn lists_the_current_sandboxs_commands() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let db_path = tmp.path().join("zerobuild.db");
+        let client = Arc::new(MockSandboxClient::new());
+        client.create_sandbox(false, "", 1000).await.unwrap();
+        {
+            let conn = store::init_db(&db_path).unwrap();
+            let entry = |sandbox_id, command, exit_code, log_id| NewCommand {
+                sandbox_id,
+                session: None,
+                command,
+                workdir: "project",
+                exit_code,
+                duration_ms: 1500,
+                log_id,
+            };
+            for command in [
+                entry(MOCK_SANDBOX_ID, "npm install", 0, None),
+                entry(MOCK_SANDBOX_ID, "npm run build", 2, Some("log-1")),
+                entry("old-sandbox", "npm test", 1, None),
+            ] {
+                store::command_history::record_command(&conn, &command, 10).unwrap();
+            }
+        }
+        let tool = SandboxCommandHistoryTool::new(client, &db_path);
+
+        let all = tool.execute(json!({})).await.unwrap();
+        assert!(all.output.starts_with("Last 2 commands, oldest first:"));
+        assert!(all
+            .output
+            .ends_with("[project] exit 2 in 1.5s: npm run build (log_id: log-1)"));
+        assert!(!all.output.contains("npm test"));
+
+        let failed = tool.execute(json!({"failed_only": true})).await.unwrap();
+        assert!(!failed.output.contains("npm install"));
+
+        let none = tool.execute(json!({"grep": "lint"})).await.unwrap();
+        assert_eq!(
+            none.output,
+            "No matching commands recorded for this sandbox."
+        );
+    }
+}
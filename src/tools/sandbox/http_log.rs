@@ -0,0 +1,258 @@
+//! `sandbox_http_log` tool — requests captured through preview URLs.
+//!
+//! See [`crate::sandbox::http_capture`] for how they are captured.
+
+use crate::sandbox::http_capture::{Exchange, HttpCapture, MAX_EXCHANGES};
+use crate::tools::traits::{Tool, ToolResult};
+use crate::util::truncate_with_ellipsis;
+use async_trait::async_trait;
+use serde_json::json;
+use std::fmt::Write;
+use std::sync::Arc;
+
+const TOOL_NAME: &str = "sandbox_http_log";
+
+/// Exchanges listed when the agent passes no `limit`.
+const DEFAULT_LIMIT: usize = 20;
+
+/// Longest response body preview in a listing.
+const MAX_PREVIEW_CHARS: usize = 160;
+
+pub struct SandboxHttpLogTool {
+    capture: Arc<HttpCapture>,
+}
+
+impl SandboxHttpLogTool {
+    pub fn new(capture: Arc<HttpCapture>) -> Self {
+        Self { capture }
+    }
+
+    fn run(&self, args: &serde_json::Value) -> anyhow::Result<String> {
+        if let Some(id) = args["id"].as_u64() {
+            let exchange = self.capture.exchange(id).ok_or_else(|| {
+                anyhow::anyhow!("No request #{id}; only the last {MAX_EXCHANGES} are kept")
+            })?;
+            return Ok(describe(&exchange));
+        }
+        if args["clear"].as_bool() == Some(true) {
+            self.capture.clear();
+            return Ok("Captured requests cleared.".into());
+        }
+
+        let errors_only = args["errors_only"].as_bool().unwrap_or(false);
+        let include_assets = args["include_assets"].as_bool().unwrap_or(false);
+        let path = args["path"].as_str().filter(|p| !p.is_empty());
+        let limit = args["limit"]
+            .as_u64()
+            .and_then(|n| usize::try_from(n).ok())
+            .unwrap_or(DEFAULT_LIMIT)
+            .clamp(1, MAX_EXCHANGES);
+
+        let exchanges = self.capture.exchanges();
+        let matching: Vec<&Exchange> = exchanges
+            .iter()
+            .filter(|e| include_assets || !e.is_asset())
+            .filter(|e| !errors_only || e.failed())
+            .filter(|e| path.is_none_or(|p| e.path.contains(p)))
+            .collect();
+        if matching.is_empty() {
+            return Ok(if exchanges.is_empty() {
+                "No requests captured yet. Open the app through the URL sandbox_get_preview_url \
+                 returns so its requests are captured."
+                    .into()
+            } else {
+                "No captured request matches.".into()
+            });
+        }
+
+        let shown = &matching[matching.len().saturating_sub(limit)..];
+        let mut out = format!(
+            "{} of {} matching requests (oldest first; id gives details):\n",
+            shown.len(),
+            matching.len()
+        );
+        for exchange in shown {
+            let outcome = match (exchange.status, &exchange.error) {
+                (Some(status), _) => status.to_string(),
+                (None, Some(error)) => format!("failed: {error}"),
+                (None, None) => "failed".into(),
+            };
+            let _ = writeln!(
+                out,
+                "#{} {} {} {} → {outcome} ({} ms, port {})",
+                exchange.id,
+                exchange.at.format("%H:%M:%S"),
+                exchange.method,
+                exchange.path,
+                exchange.duration_ms,
+                exchange.port
+            );
+            if exchange.failed() && !exchange.response_body.is_empty() {
+                let _ = writeln!(
+                    out,
+                    "    {}",
+                    truncate_with_ellipsis(
+                        &exchange.response_body.replace('\n', " "),
+                        MAX_PREVIEW_CHARS
+                    )
+                );
+            }
+        }
+        Ok(out.trim_end().to_string())
+    }
+}
+
+/// Full request and response of `exchange`.
+fn describe(exchange: &Exchange) -> String {
+    let mut out = format!(
+        "#{} {} {} (port {}, {} ms, {})\n\n> {} {}\n",
+        exchange.id,
+        exchange.method,
+        exchange.path,
+        exchange.port,
+        exchange.duration_ms,
+        exchange.at.to_rfc3339(),
+        exchange.method,
+        exchange.path
+    );
+    for (name, value) in &exchange.request_headers {
+        let _ = writeln!(out, "> {name}: {value}");
+    }
+    if !exchange.request_body.is_empty() {
+        let _ = writeln!(out, "\n{}", exchange.request_body);
+    }
+    match exchange.status {
+        Some(status) => {
+            let _ = writeln!(out, "\n< {status}");
+            for (name, value) in &exchange.response_headers {
+                let _ = writeln!(out, "< {name}: {value}");
+            }
+            if !exchange.response_body.is_empty() {
+                let _ = writeln!(out, "\n{}", exchange.response_body);
+            }
+        }
+        None => {
+            let _ = writeln!(out, "\n< no response");
+        }
+    }
+    if let Some(error) = &exchange.error {
+        let _ = writeln!(out, "\nError: {error}");
+    }
+    out.trim_end().to_string()
+}
+
+#[async_trait]
+impl Tool for SandboxHttpLogTool {
+    fn name(&self) -> &str {
+        TOOL_NAME
+    }
+
+    fn description(&self) -> &str {
+        "Show the HTTP requests made through sandbox preview URLs (pages and the frontend's API \
+         calls) with their status codes, so failing API calls can be debugged from what was \
+         actually sent and returned. Lists the most recent requests (static assets hidden), \
+         optionally only failed ones or those whose path contains some text; pass id for the full \
+         headers and bodies of one request. Requests are captured only when the app is opened \
+         through the URL sandbox_get_preview_url returns."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "id": {
+                    "type": "integer",
+                    "description": "Show the full request and response with this id."
+                },
+                "errors_only": {
+                    "type": "boolean",
+                    "description": "Only requests that failed or got a 4xx/5xx response. Default: false."
+                },
+                "path": {
+                    "type": "string",
+                    "description": "Only requests whose path contains this text, e.g. '/api/'."
+                },
+                "include_assets": {
+                    "type": "boolean",
+                    "description": "Also list scripts, styles, images and fonts. Default: false."
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Most recent requests to list. Default: 20."
+                },
+                "clear": {
+                    "type": "boolean",
+                    "description": "Forget all captured requests, e.g. before reproducing a bug."
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        match self.run(&args) {
+            Ok(output) => Ok(ToolResult {
+                success: true,
+                output,
+                error: None,
+                error_code: None,
+                error_hint: None,
+            }),
+            Err(e) => Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(e.to_string()),
+                error_code: None,
+                error_hint: None,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+    use axum::routing::get;
+    use axum::Router;
+
+    #[tokio::test]
+    async fn lists_failed_api_calls_and_shows_details() {
+        let app = Router::new()
+            .route(
+                "/api/users",
+                get(|| async { (StatusCode::NOT_FOUND, "no such route") }),
+            )
+            .route("/", get(|| async { "<html></html>" }))
+            .route("/main.css", get(|| async { "body {}" }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let app_url = format!("http://127.0.0.1:{}", listener.local_addr().unwrap().port());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let capture = Arc::new(HttpCapture::new());
+        let tool = SandboxHttpLogTool::new(capture.clone());
+        let empty = tool.execute(json!({})).await.unwrap();
+        assert!(empty.output.starts_with("No requests captured yet"));
+
+        let url = capture.proxy(3000, &app_url).await.unwrap();
+        for path in ["/", "/main.css", "/api/users"] {
+            reqwest::get(format!("{url}{path}")).await.unwrap();
+        }
+
+        let all = tool.execute(json!({})).await.unwrap();
+        assert!(all.output.starts_with("2 of 2 matching requests"));
+        assert!(!all.output.contains("main.css"));
+
+        let errors = tool.execute(json!({"errors_only": true})).await.unwrap();
+        assert!(errors.output.contains("#3 "), "{}", errors.output);
+        assert!(errors.output.contains("GET /api/users → 404"));
+        assert!(errors.output.ends_with("    no such route"));
+
+        let detail = tool.execute(json!({"id": 3})).await.unwrap();
+        assert!(detail.output.contains("> GET /api/users"));
+        assert!(detail.output.contains("\n< 404\n"));
+
+        tool.execute(json!({"clear": true})).await.unwrap();
+        let gone = tool.execute(json!({"id": 3})).await.unwrap();
+        assert!(!gone.success);
+    }
+}
@@ -49,6 +49,7 @@ impl Tool for SandboxKillTool {
                 success: true,
                 output: "No active sandbox to kill.".to_string(),
                 error: None,
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -64,12 +65,14 @@ impl Tool for SandboxKillTool {
                     msg
                 ),
                 error: None,
+                error_code: None,
                 error_hint: None,
             }),
             Err(e) => Ok(ToolResult {
                 success: false,
                 output: String::new(),
                 error: Some(format!("Failed to kill sandbox: {e}")),
+                error_code: None,
                 error_hint: None,
             }),
         }
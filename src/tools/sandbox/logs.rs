@@ -0,0 +1,204 @@
+//! `get_log` tool — read part of a stored `sandbox_run_command` log.
+//!
+//! See [`crate::store::logs`] for which logs are kept.
+
+use crate::store;
+use crate::tools::traits::{Tool, ToolResult};
+use async_trait::async_trait;
+use serde_json::json;
+use std::fmt::Write;
+use std::path::PathBuf;
+
+const TOOL_NAME: &str = "get_log";
+
+/// Most lines returned by one call.
+const MAX_LINES: usize = 400;
+
+pub struct GetLogTool {
+    db_path: PathBuf,
+}
+
+impl GetLogTool {
+    pub fn new(db_path: impl Into<PathBuf>) -> Self {
+        Self {
+            db_path: db_path.into(),
+        }
+    }
+
+    fn run(&self, args: &serde_json::Value) -> anyhow::Result<String> {
+        let log_id = args["log_id"]
+            .as_str()
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'log_id' parameter"))?;
+        let stream = args["stream"].as_str().unwrap_or("stdout");
+
+        let conn = store::init_db(&self.db_path)?;
+        let log = store::logs::load_log(&conn, log_id)?.ok_or_else(|| {
+            anyhow::anyhow!(
+                "No log '{log_id}'; only the last {} logs are kept",
+                store::logs::MAX_LOGS
+            )
+        })?;
+        let lines: Vec<&str> = log.stream(stream)?.lines().collect();
+        let total = lines.len();
+
+        let mut out = format!("{log_id} {stream} of `{}` ({total} lines)\n", log.command);
+        if let Some(pattern) = args["grep"].as_str().filter(|p| !p.is_empty()) {
+            let needle = pattern.to_lowercase();
+            let matches: Vec<(usize, &str)> = lines
+                .iter()
+                .enumerate()
+                .filter(|(_, line)| line.to_lowercase().contains(&needle))
+                .map(|(i, line)| (i + 1, *line))
+                .collect();
+            if matches.is_empty() {
+                let _ = write!(out, "No lines contain '{pattern}'.");
+                return Ok(out);
+            }
+            for (number, line) in matches.iter().take(MAX_LINES) {
+                let _ = writeln!(out, "{number:>6} | {line}");
+            }
+            if matches.len() > MAX_LINES {
+                let _ = write!(
+                    out,
+                    "... {} more matching lines; narrow the pattern",
+                    matches.len() - MAX_LINES
+                );
+            }
+            return Ok(out.trim_end().to_string());
+        }
+
+        let start = args["start_line"]
+            .as_u64()
+            .and_then(|n| usize::try_from(n).ok())
+            .unwrap_or(1)
+            .max(1);
+        if start > total {
+            let _ = write!(out, "start_line {start} is past the end of the log.");
+            return Ok(out);
+        }
+        let requested_end = args["end_line"]
+            .as_u64()
+            .and_then(|n| usize::try_from(n).ok())
+            .unwrap_or(total)
+            .min(total);
+        let end = requested_end.min(start + MAX_LINES - 1);
+        for (i, line) in lines[start - 1..end.max(start - 1)].iter().enumerate() {
+            let _ = writeln!(out, "{:>6} | {line}", start + i);
+        }
+        if end < requested_end {
+            let _ = write!(
+                out,
+                "... stopped after {MAX_LINES} lines; continue with start_line={}",
+                end + 1
+            );
+        }
+        Ok(out.trim_end().to_string())
+    }
+}
+
+#[async_trait]
+impl Tool for GetLogTool {
+    fn name(&self) -> &str {
+        TOOL_NAME
+    }
+
+    fn description(&self) -> &str {
+        "Read the full output of a sandbox_run_command whose output was truncated. Pass the \
+         log_id it returned and either a line range (start_line/end_line, 1-based, at most 400 \
+         lines per call) or grep to list matching lines with their line numbers."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "log_id": {
+                    "type": "string",
+                    "description": "log_id from sandbox_run_command output, e.g. 'log-3f2a9c1d7b4e'"
+                },
+                "stream": {
+                    "type": "string",
+                    "enum": ["stdout", "stderr"],
+                    "description": "Which output to read. Default: 'stdout'."
+                },
+                "start_line": {
+                    "type": "integer",
+                    "description": "First line to return (1-based). Default: 1."
+                },
+                "end_line": {
+                    "type": "integer",
+                    "description": "Last line to return (inclusive). Default: end of log."
+                },
+                "grep": {
+                    "type": "string",
+                    "description": "Return only lines containing this text (case-insensitive) instead of a range."
+                }
+            },
+            "required": ["log_id"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        match self.run(&args) {
+            Ok(output) => Ok(ToolResult {
+                success: true,
+                output,
+                error: None,
+                error_code: None,
+                error_hint: None,
+            }),
+            Err(e) => Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(e.to_string()),
+                error_code: None,
+                error_hint: None,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn reads_ranges_and_grep_matches() {
+        let tmp = TempDir::new().unwrap();
+        let db_path = tmp.path().join("zerobuild.db");
+        let stdout: String = (1..=1000).map(|i| format!("line {i}\n")).collect();
+        let id = {
+            let conn = store::init_db(&db_path).unwrap();
+            store::logs::save_log(&conn, "npm run build", "project", 1, &stdout, "").unwrap()
+        };
+        let tool = GetLogTool::new(&db_path);
+
+        let range = tool
+            .execute(json!({"log_id": id, "start_line": 500, "end_line": 501}))
+            .await
+            .unwrap();
+        assert!(range.success);
+        assert!(range
+            .output
+            .ends_with("   500 | line 500\n   501 | line 501"));
+
+        let capped = tool.execute(json!({"log_id": id})).await.unwrap();
+        assert!(capped
+            .output
+            .ends_with("stopped after 400 lines; continue with start_line=401"));
+
+        let grep = tool
+            .execute(json!({"log_id": id, "grep": "LINE 99"}))
+            .await
+            .unwrap();
+        assert!(grep.output.contains("    99 | line 99"));
+        assert!(grep.output.contains("   999 | line 999"));
+        assert!(!grep.output.contains("line 98\n"));
+
+        let missing = tool.execute(json!({"log_id": "log-nope"})).await.unwrap();
+        assert!(!missing.success);
+    }
+}
@@ -4,35 +4,83 @@
 //! — a native process sandbox that requires no external API key or Docker daemon.
 //! The tools are thin delegators to the [`SandboxClient`] trait.
 
+pub mod checkpoint;
+pub mod codebase_search;
 pub mod command;
 pub mod create;
+pub mod docs;
+pub mod edit;
+pub mod env;
+pub mod failure_context;
 pub mod files;
+pub mod history;
+pub mod http_log;
 pub mod kill;
+pub mod logs;
 pub mod package_manager;
 pub mod preview;
+pub mod processes;
+pub mod project_info;
 pub mod restore;
+pub mod setup_project;
+pub mod shell;
 pub mod snapshot;
 pub mod tunnel;
+pub mod wait;
 
+pub use checkpoint::CheckpointTool;
+pub use codebase_search::CodebaseSearchTool;
 pub use command::SandboxRunCommandTool;
 pub use create::SandboxCreateTool;
-pub use files::{SandboxListFilesTool, SandboxReadFileTool, SandboxWriteFileTool};
+pub use docs::GenerateDocsTool;
+pub use edit::SandboxEditFileTool;
+pub use env::SandboxSetEnvTool;
+pub use files::{
+    SandboxCopyFileTool, SandboxDeletePathTool, SandboxListFilesTool, SandboxMoveFileTool,
+    SandboxReadFileTool, SandboxWriteFileTool, SandboxWriteFilesTool,
+};
+pub use history::SandboxCommandHistoryTool;
+pub use http_log::SandboxHttpLogTool;
 pub use kill::SandboxKillTool;
+pub use logs::GetLogTool;
 pub use package_manager::SandboxGetPackageManagerTool;
 pub use preview::SandboxGetPreviewUrlTool;
+pub use processes::{SandboxPortsTool, SandboxPsTool};
+pub use project_info::ProjectInfoTool;
 pub use restore::SandboxRestoreSnapshotTool;
+pub use setup_project::SetupProjectTool;
+pub use shell::SandboxShellTool;
 pub use snapshot::SandboxSaveSnapshotTool;
 pub use tunnel::SandboxGetPublicUrlTool;
+pub use wait::WaitForTool;
 
 /// Tool name constants for reference.
 pub const TOOL_CREATE: &str = "sandbox_create";
+pub const TOOL_SETUP_PROJECT: &str = "setup_project";
 pub const TOOL_RUN_COMMAND: &str = "sandbox_run_command";
+pub const TOOL_SHELL: &str = "sandbox_shell";
+pub const TOOL_COMMAND_HISTORY: &str = "sandbox_command_history";
 pub const TOOL_WRITE_FILE: &str = "sandbox_write_file";
+pub const TOOL_WRITE_FILES: &str = "sandbox_write_files";
 pub const TOOL_READ_FILE: &str = "sandbox_read_file";
+pub const TOOL_EDIT_FILE: &str = "sandbox_edit_file";
+pub const TOOL_SET_ENV: &str = "sandbox_set_env";
 pub const TOOL_LIST_FILES: &str = "sandbox_list_files";
+pub const TOOL_MOVE_FILE: &str = "sandbox_move_file";
+pub const TOOL_COPY_FILE: &str = "sandbox_copy_file";
+pub const TOOL_DELETE_PATH: &str = "sandbox_delete_path";
 pub const TOOL_GET_PREVIEW_URL: &str = "sandbox_get_preview_url";
+pub const TOOL_HTTP_LOG: &str = "sandbox_http_log";
+pub const TOOL_PORTS: &str = "sandbox_ports";
+pub const TOOL_PS: &str = "sandbox_ps";
+pub const TOOL_WAIT_FOR: &str = "wait_for";
 pub const TOOL_GET_PACKAGE_MANAGER: &str = "sandbox_get_package_manager";
 pub const TOOL_SAVE_SNAPSHOT: &str = "sandbox_save_snapshot";
 pub const TOOL_RESTORE_SNAPSHOT: &str = "sandbox_restore_snapshot";
 pub const TOOL_KILL: &str = "sandbox_kill";
 pub const TOOL_TUNNEL: &str = "sandbox_get_public_url";
+pub const TOOL_CODEBASE_SEARCH: &str = "codebase_search";
+pub const TOOL_CHECKPOINT: &str = "checkpoint";
+pub const TOOL_PROJECT_INFO: &str = "project_info";
+pub const TOOL_GET_LOG: &str = "get_log";
+pub const TOOL_GENERATE_DOCS: &str = "generate_docs";
@@ -1,6 +1,7 @@
 //! `sandbox_get_package_manager` tool — get the detected package manager for the sandbox.
 
-use crate::sandbox::{PackageManager, SandboxClient};
+use crate::sandbox::package_manager::detect_project_package_manager;
+use crate::sandbox::SandboxClient;
 use crate::tools::traits::{Tool, ToolResult};
 use async_trait::async_trait;
 use serde_json::json;
@@ -25,9 +26,10 @@ impl Tool for SandboxGetPackageManagerTool {
     }
 
     fn description(&self) -> &str {
-        "📦 Get the detected package manager for the sandbox. \
+        "📦 Get the package manager of the sandbox project. \
          \
-         Returns the best available package manager in priority order: pnpm > yarn > npm. \
+         Follows the project's package.json packageManager field or lockfile, otherwise the \
+         best available manager in priority order: pnpm > yarn > bun > npm. \
          Use this to determine which package manager to use for install commands. \
          \
          Example: If this returns 'pnpm', use 'pnpm install' instead of 'npm install'."
@@ -49,21 +51,23 @@ impl Tool for SandboxGetPackageManagerTool {
                     success: false,
                     output: String::new(),
                     error: Some(e),
+                    error_code: None,
                     error_hint: None,
                 })
             }
         };
 
         // Actively detect package manager instead of using cached state
-        let pm = self.client.detect_package_manager().await;
+        let detected = detect_project_package_manager(self.client.as_ref(), "project").await;
+        let pm = detected.manager;
 
         let output = format!(
-            "Detected package manager: {pm}\n\
+            "Detected package manager: {described}\n\
              Install command: {install}\n\
              Add command: {add}\n\
              Run command: {run}",
-            pm = pm,
-            install = pm.install_cmd(),
+            described = detected.describe(),
+            install = detected.install_cmd(),
             add = pm.add_cmd(),
             run = pm.run_cmd()
         );
@@ -72,6 +76,7 @@ impl Tool for SandboxGetPackageManagerTool {
             success: true,
             output,
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
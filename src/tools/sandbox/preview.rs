@@ -1,5 +1,6 @@
 //! `sandbox_get_preview_url` tool — get the preview URL for a port.
 
+use crate::sandbox::http_capture::HttpCapture;
 use crate::sandbox::SandboxClient;
 use crate::tools::traits::{Tool, ToolResult};
 use async_trait::async_trait;
@@ -10,11 +11,22 @@ const TOOL_NAME: &str = "sandbox_get_preview_url";
 
 pub struct SandboxGetPreviewUrlTool {
     client: Arc<dyn SandboxClient>,
+    capture: Option<Arc<HttpCapture>>,
 }
 
 impl SandboxGetPreviewUrlTool {
     pub fn new(client: Arc<dyn SandboxClient>) -> Self {
-        Self { client }
+        Self {
+            client,
+            capture: None,
+        }
+    }
+
+    /// Return URLs of `capture` proxies, so `sandbox_http_log` sees the
+    /// app's requests.
+    pub fn with_http_capture(mut self, capture: Arc<HttpCapture>) -> Self {
+        self.capture = Some(capture);
+        self
     }
 }
 
@@ -55,6 +67,7 @@ impl Tool for SandboxGetPreviewUrlTool {
                     success: false,
                     output: String::new(),
                     error: Some(e),
+                    error_code: None,
                     error_hint: None,
                 })
             }
@@ -62,11 +75,34 @@ impl Tool for SandboxGetPreviewUrlTool {
 
         let port = args["port"].as_u64().map(|p| p as u16).unwrap_or(3000);
 
-        match self.client.get_preview_url(port).await {
+        let url = self.client.get_preview_url(port).await;
+        if let (Ok(direct), Some(capture)) = (&url, &self.capture) {
+            return Ok(match capture.proxy(port, direct).await {
+                Ok(url) => ToolResult {
+                    success: true,
+                    output: format!(
+                        "Preview URL (port {port}): {url}\nRequests through this URL are \
+                         captured for sandbox_http_log. Direct URL: {direct}\n(sandbox: {sandbox_id})"
+                    ),
+                    error: None,
+                    error_code: None,
+                    error_hint: None,
+                },
+                Err(e) => ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some(format!("Failed to start the HTTP capture proxy: {e}")),
+                    error_code: None,
+                    error_hint: Some(format!("The app is reachable directly at {direct}.")),
+                },
+            });
+        }
+        match url {
             Ok(url) => Ok(ToolResult {
                 success: true,
                 output: format!("Preview URL (port {port}): {url}\n(sandbox: {sandbox_id})"),
                 error: None,
+                error_code: None,
                 error_hint: None,
             }),
             Err(e) => Ok(ToolResult {
@@ -75,6 +111,7 @@ impl Tool for SandboxGetPreviewUrlTool {
                 error: Some(format!(
                     "Failed to get preview URL: {e}\nMake sure the dev server is running on port {port}."
                 )),
+                error_code: None,
                 error_hint: None,
             }),
         }
@@ -90,4 +127,18 @@ mod tests {
         let client = Arc::new(crate::sandbox::local::LocalProcessSandboxClient::new());
         assert_eq!(SandboxGetPreviewUrlTool::new(client).name(), TOOL_NAME);
     }
+
+    #[tokio::test]
+    async fn captured_previews_go_through_a_proxy() {
+        let client = Arc::new(crate::sandbox::mock::MockSandboxClient::new());
+        client.set_id("sb-1".into());
+        let tool =
+            SandboxGetPreviewUrlTool::new(client).with_http_capture(Arc::new(HttpCapture::new()));
+        let result = tool.execute(json!({"port": 5173})).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(result.output.contains("captured for sandbox_http_log"));
+        assert!(!result
+            .output
+            .starts_with("Preview URL (port 5173): http://localhost:5173"));
+    }
 }
@@ -0,0 +1,292 @@
+//! `sandbox_ports` and `sandbox_ps` tools — what listens and what runs in
+//! the sandbox, for diagnosing a dead preview in one call.
+
+use crate::sandbox::{SandboxClient, SandboxProcess};
+use crate::tools::traits::{Tool, ToolResult};
+use crate::util::truncate_with_ellipsis;
+use async_trait::async_trait;
+use serde_json::json;
+use std::fmt::Write;
+use std::sync::Arc;
+
+const TOOL_PORTS: &str = "sandbox_ports";
+const TOOL_PS: &str = "sandbox_ps";
+
+/// Longest command line shown.
+const MAX_COMMAND_CHARS: usize = 200;
+
+fn failure(error: String) -> ToolResult {
+    ToolResult {
+        success: false,
+        output: String::new(),
+        error: Some(error),
+        error_code: None,
+        error_hint: None,
+    }
+}
+
+fn success(output: String) -> ToolResult {
+    ToolResult {
+        success: true,
+        output,
+        error: None,
+        error_code: None,
+        error_hint: None,
+    }
+}
+
+/// `3m 20s`-style duration.
+fn format_elapsed(secs: u64) -> String {
+    match secs {
+        0..=59 => format!("{secs}s"),
+        60..=3599 => format!("{}m {}s", secs / 60, secs % 60),
+        _ => format!("{}h {}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+fn command_line(process: &SandboxProcess) -> String {
+    truncate_with_ellipsis(&process.command, MAX_COMMAND_CHARS)
+}
+
+pub struct SandboxPortsTool {
+    client: Arc<dyn SandboxClient>,
+}
+
+impl SandboxPortsTool {
+    pub fn new(client: Arc<dyn SandboxClient>) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl Tool for SandboxPortsTool {
+    fn name(&self) -> &str {
+        TOOL_PORTS
+    }
+
+    fn description(&self) -> &str {
+        "List the TCP ports listening in the sandbox, with the address each is bound to and the \
+         process that owns it. Use it when a preview URL does not respond, to tell whether the \
+         dev server crashed, is still starting, bound another port or listens only on \
+         127.0.0.1. Pass port to check the one the preview expects."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "port": {
+                    "type": "integer",
+                    "description": "Port the app should listen on, e.g. 3000; the answer says whether it does."
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        if let Err(e) = self.client.require_id() {
+            return Ok(failure(e));
+        }
+        let ports = match self.client.listening_ports().await {
+            Ok(ports) => ports,
+            Err(e) => return Ok(failure(format!("Failed to list ports: {e}"))),
+        };
+        let expected = args["port"].as_u64().and_then(|p| u16::try_from(p).ok());
+
+        let mut out = String::new();
+        if let Some(expected) = expected {
+            let bound: Vec<_> = ports.iter().filter(|p| p.port == expected).collect();
+            if bound.is_empty() {
+                let _ = writeln!(
+                    out,
+                    "Nothing listens on port {expected}: the server crashed, is still starting \
+                     or uses another port (see below and sandbox_ps).\n"
+                );
+            } else if bound
+                .iter()
+                .all(|p| p.address.starts_with("127.") || p.address == "::1")
+            {
+                let _ = writeln!(
+                    out,
+                    "Port {expected} listens on loopback only; if the preview cannot reach it, \
+                     bind the server to 0.0.0.0.\n"
+                );
+            } else {
+                let _ = writeln!(out, "Port {expected} is listening.\n");
+            }
+        }
+        if ports.is_empty() {
+            out.push_str("No TCP port is listening in the sandbox.");
+            return Ok(success(out));
+        }
+        out.push_str("Listening TCP ports:\n");
+        for port in &ports {
+            let owner = match (port.pid, &port.command) {
+                (Some(pid), Some(command)) => format!(
+                    "pid {pid} {}",
+                    truncate_with_ellipsis(command, MAX_COMMAND_CHARS)
+                ),
+                _ => "owner unknown".to_string(),
+            };
+            let _ = writeln!(out, "- {} on {} — {owner}", port.port, port.address);
+        }
+        Ok(success(out.trim_end().to_string()))
+    }
+}
+
+pub struct SandboxPsTool {
+    client: Arc<dyn SandboxClient>,
+}
+
+impl SandboxPsTool {
+    pub fn new(client: Arc<dyn SandboxClient>) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl Tool for SandboxPsTool {
+    fn name(&self) -> &str {
+        TOOL_PS
+    }
+
+    fn description(&self) -> &str {
+        "List the processes running in the sandbox with their pid, parent pid, state, run time, \
+         memory and command line, e.g. to check whether a dev server started with \
+         sandbox_run_command is still alive. Pass filter to list only those whose command line \
+         contains some text."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "filter": {
+                    "type": "string",
+                    "description": "Only processes whose command line contains this text (case-insensitive), e.g. 'next' or 'vite'."
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        if let Err(e) = self.client.require_id() {
+            return Ok(failure(e));
+        }
+        let processes = match self.client.processes().await {
+            Ok(processes) => processes,
+            Err(e) => return Ok(failure(format!("Failed to list processes: {e}"))),
+        };
+        let filter = args["filter"]
+            .as_str()
+            .filter(|f| !f.is_empty())
+            .map(str::to_lowercase);
+        let shown: Vec<_> = processes
+            .iter()
+            .filter(|p| {
+                filter
+                    .as_ref()
+                    .is_none_or(|f| p.command.to_lowercase().contains(f))
+            })
+            .collect();
+        if shown.is_empty() {
+            return Ok(success(match filter {
+                Some(filter) => format!("No process matches '{filter}'."),
+                None => "No process is running in the sandbox.".into(),
+            }));
+        }
+
+        let mut out = format!(
+            "{} {}:\n{:>7} {:>7} S {:>8} {:>9}  COMMAND\n",
+            shown.len(),
+            if shown.len() == 1 {
+                "process"
+            } else {
+                "processes"
+            },
+            "PID",
+            "PPID",
+            "TIME",
+            "RSS"
+        );
+        for process in shown {
+            let _ = writeln!(
+                out,
+                "{:>7} {:>7} {} {:>8} {:>7}MB  {}",
+                process.pid,
+                process.ppid,
+                process.state,
+                format_elapsed(process.elapsed_secs),
+                process.rss_kib / 1024,
+                command_line(process)
+            );
+        }
+        if processes.iter().any(|p| p.state == 'Z') {
+            out.push_str("Z = exited (zombie) process not yet reaped by its parent.");
+        }
+        Ok(success(out.trim_end().to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sandbox::mock::MockSandboxClient;
+
+    const PROC_OUTPUT: &str = "## uptime
+500.00 1000.00
+## tcp
+   0: 0100007F:0BB8 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 31 1 0
+   1: 00000000:1538 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 32 1 0
+## tcp6
+## procs
+40\t31,\tnode server.js \t40 (node) S 1 40 1 0 -1 0 0 0 0 0 0 0 0 0 20 0 7 0 20000 0 12800
+41\t\t\t41 (sh) Z 40 40 1 0 -1 0 0 0 0 0 0 0 0 0 20 0 1 0 49000 0 0
+";
+
+    fn client() -> Arc<MockSandboxClient> {
+        let client = Arc::new(MockSandboxClient::new());
+        client.set_id("sb-1".into());
+        client.on_command("/proc/net/tcp", PROC_OUTPUT, "", 0);
+        client
+    }
+
+    #[tokio::test]
+    async fn ports_tell_why_the_preview_is_dead() {
+        let tool = SandboxPortsTool::new(client());
+        let result = tool.execute(json!({"port": 3000})).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(result
+            .output
+            .starts_with("Port 3000 listens on loopback only"));
+        assert!(result
+            .output
+            .contains("- 3000 on 127.0.0.1 — pid 40 node server.js"));
+        assert!(result.output.ends_with("- 5432 on 0.0.0.0 — owner unknown"));
+
+        let missing = tool.execute(json!({"port": 5173})).await.unwrap();
+        assert!(missing.output.starts_with("Nothing listens on port 5173"));
+    }
+
+    #[tokio::test]
+    async fn ps_lists_and_filters_processes() {
+        let tool = SandboxPsTool::new(client());
+        let all = tool.execute(json!({})).await.unwrap();
+        assert!(all.output.starts_with("2 processes:"), "{}", all.output);
+        assert!(all
+            .output
+            .contains("     40       1 S    5m 0s      50MB  node server.js"));
+        assert!(all.output.ends_with("not yet reaped by its parent."));
+
+        let filtered = tool.execute(json!({"filter": "NODE"})).await.unwrap();
+        assert!(filtered.output.starts_with("1 process:"));
+        let none = tool.execute(json!({"filter": "vite"})).await.unwrap();
+        assert_eq!(none.output, "No process matches 'vite'.");
+    }
+
+    #[tokio::test]
+    async fn requires_a_sandbox() {
+        let tool = SandboxPsTool::new(Arc::new(MockSandboxClient::new()));
+        assert!(!tool.execute(json!({})).await.unwrap().success);
+    }
+}
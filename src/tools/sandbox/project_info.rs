@@ -0,0 +1,320 @@
+//! `project_info` tool — stack, dependencies, entry points, package manager
+//! and scripts of the sandbox project.
+
+use crate::sandbox::monorepo;
+use crate::sandbox::package_manager::detect_project_package_manager;
+use crate::sandbox::project_config::{ProjectConfig, CONFIG_FILE, PROJECT_DIR};
+use crate::sandbox::{stack, SandboxClient};
+use crate::tools::traits::{Tool, ToolResult};
+use async_trait::async_trait;
+use serde_json::json;
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::sync::Arc;
+
+const TOOL_NAME: &str = "project_info";
+
+/// Dependencies listed per section; the count is always shown in full.
+const MAX_DEPENDENCIES: usize = 60;
+
+pub struct ProjectInfoTool {
+    client: Arc<dyn SandboxClient>,
+}
+
+impl ProjectInfoTool {
+    pub fn new(client: Arc<dyn SandboxClient>) -> Self {
+        Self { client }
+    }
+
+    /// Conventional entry files that exist in `dir`, in
+    /// [`stack::ENTRY_CANDIDATES`] order.
+    async fn existing_entry_files(&self, dir: &str) -> Vec<&'static str> {
+        let mut existing = HashSet::new();
+        for sub in stack::entry_candidate_dirs() {
+            let path = if sub.is_empty() {
+                dir.to_string()
+            } else {
+                format!("{dir}/{sub}")
+            };
+            let Ok(listing) = self.client.list_files(&path).await else {
+                continue;
+            };
+            for name in listing.lines().filter_map(|l| l.strip_prefix("file\t")) {
+                existing.insert(if sub.is_empty() {
+                    name.to_string()
+                } else {
+                    format!("{sub}/{name}")
+                });
+            }
+        }
+        stack::ENTRY_CANDIDATES
+            .iter()
+            .copied()
+            .filter(|path| existing.contains(*path))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl Tool for ProjectInfoTool {
+    fn name(&self) -> &str {
+        TOOL_NAME
+    }
+
+    fn description(&self) -> &str {
+        "Describe the project in the sandbox from its .zerobuild.toml, package.json, \
+         Cargo.toml and pyproject.toml: the project's own build/test commands, deploy target \
+         and protected files, the detected framework and stack, scripts, dependencies with versions, \
+         entry points and conventional entry files; for JavaScript projects also the package \
+         manager (from package.json packageManager, the lockfile, or the best installed one) and \
+         the exact install, add and run commands to use. For monorepos (pnpm, npm/yarn \
+         workspaces, turbo, cargo workspaces) lists the apps; pass 'app' to describe one of them. \
+         Call this first when working on an \
+         existing project and before installing dependencies. Requires an active sandbox."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "workdir": {
+                    "type": "string",
+                    "description": "Project directory relative to sandbox root. Default: the workdir from project/.zerobuild.toml, else 'project'."
+                },
+                "app": {
+                    "type": "string",
+                    "description": "Monorepo app to describe, by name or path, e.g. 'web' or 'apps/web'. Ignored when workdir is set."
+                }
+            },
+            "required": []
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        if let Err(e) = self.client.require_id() {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(e),
+                error_code: None,
+                error_hint: None,
+            });
+        }
+
+        let project_config = ProjectConfig::load(self.client.as_ref()).await;
+        let workdir = match (args["workdir"].as_str(), args["app"].as_str()) {
+            (Some(dir), _) => dir.to_string(),
+            (None, Some(app)) => {
+                let config = project_config.as_ref().ok().cloned().flatten();
+                match monorepo::resolve_app(self.client.as_ref(), &config.unwrap_or_default(), app)
+                    .await
+                {
+                    Ok(app) => app.project.workdir(),
+                    Err(e) => {
+                        return Ok(ToolResult {
+                            success: false,
+                            output: String::new(),
+                            error: Some(e),
+                            error_code: None,
+                            error_hint: None,
+                        })
+                    }
+                }
+            }
+            (None, None) => match &project_config {
+                Ok(Some(config)) => config.workdir(),
+                _ => PROJECT_DIR.to_string(),
+            },
+        };
+        let workdir = workdir.as_str();
+        let dir = workdir.trim_end_matches('/');
+        let mut manifests = Vec::new();
+        for file in stack::MANIFESTS {
+            if let Ok(text) = self.client.read_file(&format!("{dir}/{file}")).await {
+                match stack::parse_manifest(file, &text) {
+                    Some(manifest) => manifests.push(manifest),
+                    None => manifests.push(stack::ProjectManifest {
+                        file,
+                        name: Some(format!("(unparseable {file})")),
+                        ..Default::default()
+                    }),
+                }
+            }
+        }
+        let entry_files = self.existing_entry_files(dir).await;
+
+        let mut out = format!("workdir: {workdir}\n");
+        match &project_config {
+            Ok(Some(config)) => {
+                let _ = write!(out, "{PROJECT_DIR}/{CONFIG_FILE}:\n{}", config.describe());
+            }
+            Ok(None) => {}
+            Err(e) => {
+                let _ = writeln!(out, "{PROJECT_DIR}/{CONFIG_FILE}: {e:#} (ignored)");
+            }
+        }
+        if let Some(workspace) = monorepo::detect(self.client.as_ref(), PROJECT_DIR).await {
+            let _ = writeln!(out, "workspace: {}", workspace.tools.join(", "));
+            if workspace.apps.is_empty() {
+                out.push_str("apps: none found\n");
+            } else {
+                out.push_str("apps:\n");
+                for app in &workspace.apps {
+                    let _ = writeln!(out, "  {}: {} ({})", app.name, app.path, app.manifest);
+                }
+            }
+        }
+        if manifests.is_empty() {
+            out.push_str("package.json: not found (new project)\n");
+        } else {
+            let files: Vec<&str> = manifests.iter().map(|m| m.file).collect();
+            let _ = writeln!(out, "manifests: {}", files.join(", "));
+        }
+        if !entry_files.is_empty() {
+            let _ = writeln!(out, "entry_files: {}", entry_files.join(", "));
+        }
+
+        let is_node = manifests.is_empty() || manifests.iter().any(|m| m.file == "package.json");
+        for manifest in &manifests {
+            let _ = writeln!(out, "\n[{}]", manifest.file);
+            if let Some(name) = &manifest.name {
+                let _ = writeln!(out, "name: {name}");
+            }
+            if let Some(version) = &manifest.version {
+                let _ = writeln!(out, "version: {version}");
+            }
+            if let Some(stack) = manifest.stack() {
+                let _ = writeln!(out, "stack: {stack}");
+            }
+            if !manifest.entry_points.is_empty() {
+                let _ = writeln!(out, "entry_points: {}", manifest.entry_points.join(", "));
+            }
+            if !manifest.scripts.is_empty() {
+                out.push_str("scripts:\n");
+                for (name, command) in &manifest.scripts {
+                    let _ = writeln!(out, "  {name}: {command}");
+                }
+            }
+            write_dependencies(&mut out, "dependencies", &manifest.dependencies);
+            write_dependencies(&mut out, "dev_dependencies", &manifest.dev_dependencies);
+        }
+
+        if is_node {
+            let detected = detect_project_package_manager(self.client.as_ref(), workdir).await;
+            let manager = detected.manager;
+            out.push('\n');
+            let _ = writeln!(out, "package_manager: {}", detected.describe());
+            let _ = writeln!(
+                out,
+                "lockfile: {}",
+                detected
+                    .lockfile
+                    .unwrap_or("none (first install creates it)")
+            );
+            let _ = writeln!(out, "install: {}", detected.install_cmd());
+            let _ = writeln!(out, "add: {} <package>", manager.add_cmd());
+            let _ = writeln!(out, "run: {} <script>", manager.run_cmd());
+            if detected.lockfile.is_some() {
+                let _ = write!(
+                    out,
+                    "\nInstalls keep the lockfile frozen; run `{}` directly to update it.",
+                    manager.install_cmd()
+                );
+            }
+        }
+
+        Ok(ToolResult {
+            success: true,
+            output: out.trim_end().to_string(),
+            error: None,
+            error_code: None,
+            error_hint: None,
+        })
+    }
+}
+
+/// Append `deps` under `label`, at most [`MAX_DEPENDENCIES`] of them.
+fn write_dependencies(out: &mut String, label: &str, deps: &[stack::Dependency]) {
+    if deps.is_empty() {
+        return;
+    }
+    let _ = writeln!(out, "{label} ({}):", deps.len());
+    for dep in deps.iter().take(MAX_DEPENDENCIES) {
+        let _ = writeln!(out, "  {} {}", dep.name, dep.version);
+    }
+    if deps.len() > MAX_DEPENDENCIES {
+        let _ = writeln!(out, "  ... {} more", deps.len() - MAX_DEPENDENCIES);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn reports_manager_and_scripts() {
+        let client = Arc::new(crate::sandbox::local::LocalProcessSandboxClient::new());
+        client.create_sandbox(false, "", 30_000).await.unwrap();
+        client
+            .write_file(
+                "project/package.json",
+                r#"{"name": "shop", "packageManager": "npm@10.2.0", "scripts": {"dev": "vite"},
+                    "dependencies": {"react": "^18.3.1"}, "devDependencies": {"vite": "^5.2.0"}}"#,
+            )
+            .await
+            .unwrap();
+        client
+            .write_file("project/package-lock.json", "{}")
+            .await
+            .unwrap();
+        client
+            .write_file("project/src/main.tsx", "export {}")
+            .await
+            .unwrap();
+
+        let result = ProjectInfoTool::new(client.clone())
+            .execute(json!({}))
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert!(result.output.contains("name: shop"));
+        assert!(result
+            .output
+            .contains("package_manager: npm (package.json packageManager \"npm@10.2.0\")"));
+        assert!(result.output.contains("install: npm ci"));
+        assert!(result.output.contains("  dev: vite"));
+        assert!(result.output.contains("stack: Vite ^5.2.0 (React)"));
+        assert!(result
+            .output
+            .contains("dev_dependencies (1):\n  vite ^5.2.0"));
+        assert!(result.output.contains("entry_files: src/main.tsx"));
+        client.kill_sandbox().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn reports_rust_stack_without_package_manager() {
+        let client = Arc::new(crate::sandbox::local::LocalProcessSandboxClient::new());
+        client.create_sandbox(false, "", 30_000).await.unwrap();
+        client
+            .write_file(
+                "project/Cargo.toml",
+                "[package]\nname = \"api\"\n[dependencies]\naxum = \"0.7\"\n",
+            )
+            .await
+            .unwrap();
+        client
+            .write_file("project/src/main.rs", "fn main() {}")
+            .await
+            .unwrap();
+
+        let result = ProjectInfoTool::new(client.clone())
+            .execute(json!({}))
+            .await
+            .unwrap();
+        assert!(result.output.contains("manifests: Cargo.toml"));
+        assert!(result.output.contains("stack: Axum 0.7"));
+        assert!(result.output.contains("entry_files: src/main.rs"));
+        assert!(!result.output.contains("package_manager:"));
+        client.kill_sandbox().await.unwrap();
+    }
+}
@@ -1,18 +1,60 @@
-//! `sandbox_restore_snapshot` tool — restore project files from SQLite snapshot into sandbox.
+//! `sandbox_restore_snapshot` tool — restore a saved project snapshot into a
+//! sandbox, install its dependencies, and report whether it is ready.
+//!
+//! The latest snapshot comes from SQLite; older versions and other projects
+//! come from the snapshot artifacts `sandbox_save_snapshot` keeps.
 
+use crate::artifacts::{ArtifactKind, Artifacts};
+use crate::config::SandboxLimitsConfig;
+use crate::sandbox::package_manager::detect_project_package_manager;
+use crate::sandbox::project_config::{ProjectConfig, PROJECT_DIR};
 use crate::sandbox::SandboxClient;
 use crate::store;
 use crate::tools::traits::{Tool, ToolResult};
 use async_trait::async_trait;
+use serde::Deserialize;
 use serde_json::json;
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Instant;
 
 const TOOL_NAME: &str = "sandbox_restore_snapshot";
 
+/// Defaults when the tool is built without [`SandboxLimitsConfig`].
+const DEFAULT_CREATE_TIMEOUT_MS: u64 = 120_000;
+const DEFAULT_INSTALL_TIMEOUT_MS: u64 = 600_000;
+
+/// Output lines of a failed install shown in the report.
+const INSTALL_TAIL_LINES: usize = 20;
+
+/// A snapshot version as stored by `sandbox_save_snapshot`.
+#[derive(Deserialize)]
+struct StoredSnapshot {
+    project_type: Option<String>,
+    files: HashMap<String, String>,
+}
+
+/// A snapshot to restore and where it came from.
+struct Snapshot {
+    files: HashMap<String, String>,
+    project_type: Option<String>,
+    source: String,
+}
+
+/// How the install step went.
+enum Install {
+    Skipped(&'static str),
+    Succeeded { command: String, secs: f64 },
+    Failed { command: String, error: String },
+}
+
 pub struct SandboxRestoreSnapshotTool {
     client: Arc<dyn SandboxClient>,
     db_path: PathBuf,
+    artifacts: Option<Arc<Artifacts>>,
+    create_timeout_ms: u64,
+    install_timeout_ms: u64,
 }
 
 impl SandboxRestoreSnapshotTool {
@@ -20,6 +62,133 @@ impl SandboxRestoreSnapshotTool {
         Self {
             client,
             db_path: db_path.into(),
+            artifacts: None,
+            create_timeout_ms: DEFAULT_CREATE_TIMEOUT_MS,
+            install_timeout_ms: DEFAULT_INSTALL_TIMEOUT_MS,
+        }
+    }
+
+    /// Allow restoring older versions and other projects from the snapshot
+    /// artifacts.
+    pub fn with_artifacts(mut self, artifacts: Arc<Artifacts>) -> Self {
+        self.artifacts = Some(artifacts);
+        self
+    }
+
+    /// Create sandboxes within `create_timeout_secs` and give the install
+    /// up to `max_command_timeout_secs`.
+    pub fn with_limits(mut self, limits: &SandboxLimitsConfig) -> Self {
+        self.create_timeout_ms = limits.create_timeout_secs.saturating_mul(1000);
+        self.install_timeout_ms = limits.max_command_timeout_secs.saturating_mul(1000);
+        self
+    }
+
+    /// The latest snapshot, or version `version` (1 = newest) of `project`.
+    async fn load(&self, project: Option<&str>, version: Option<u64>) -> Result<Snapshot, String> {
+        if project.is_none() && version.is_none() {
+            let conn = store::init_db(&self.db_path)
+                .map_err(|e| format!("Failed to open store DB: {e}"))?;
+            return match store::snapshot::load_snapshot(&conn) {
+                Ok(Some((files, project_type))) => Ok(Snapshot {
+                    files,
+                    project_type,
+                    source: "latest snapshot".to_string(),
+                }),
+                Ok(None) => Err("No snapshot found. Run sandbox_save_snapshot first.".to_string()),
+                Err(e) => Err(format!("Failed to load snapshot: {e}")),
+            };
+        }
+
+        let artifacts = self
+            .artifacts
+            .as_ref()
+            .ok_or("Snapshot versions are not stored; omit project and version.")?;
+        let versions = artifacts
+            .versions(project, ArtifactKind::Snapshot)
+            .map_err(|e| format!("Failed to list snapshot versions: {e}"))?;
+        let version = version.unwrap_or(1);
+        let Some(record) = usize::try_from(version)
+            .ok()
+            .and_then(|v| v.checked_sub(1))
+            .and_then(|index| versions.get(index))
+        else {
+            let project = project.unwrap_or("the current project");
+            return Err(match versions.len() {
+                0 => format!("No saved snapshot versions for {project}."),
+                n => format!("Version {version} not found: {project} has {n} snapshot versions (1 = newest)."),
+            });
+        };
+        let bytes = artifacts
+            .load(&record.key)
+            .await
+            .map_err(|e| format!("Failed to load snapshot version: {e:#}"))?;
+        let stored: StoredSnapshot = serde_json::from_slice(&bytes)
+            .map_err(|e| format!("Snapshot version {} is corrupt: {e}", record.key))?;
+        Ok(Snapshot {
+            files: stored.files,
+            project_type: stored.project_type,
+            source: format!(
+                "version {version} of {} of project '{}', saved {}",
+                versions.len(),
+                record.project,
+                record.created_at
+            ),
+        })
+    }
+
+    /// Run the project's install command in `workdir`: the one in
+    /// `.zerobuild.toml`, else the detected package manager's for projects
+    /// with a `package.json`.
+    async fn install(&self, workdir: &str) -> Install {
+        let mut command_workdir = workdir.to_string();
+        let mut command = None;
+        if workdir == PROJECT_DIR {
+            if let Ok(Some(config)) = ProjectConfig::load(self.client.as_ref()).await {
+                command_workdir = config.workdir();
+                command = config.commands.install;
+            }
+        }
+        let command = match command {
+            Some(command) => command,
+            None => {
+                if self
+                    .client
+                    .read_file(&format!("{command_workdir}/package.json"))
+                    .await
+                    .is_err()
+                {
+                    return Install::Skipped("no package.json or install command");
+                }
+                detect_project_package_manager(self.client.as_ref(), &command_workdir)
+                    .await
+                    .install_cmd()
+                    .to_string()
+            }
+        };
+
+        let started = Instant::now();
+        match self
+            .client
+            .run_command(&command, &command_workdir, self.install_timeout_ms)
+            .await
+        {
+            Ok(output) if output.exit_code == 0 => Install::Succeeded {
+                command,
+                secs: started.elapsed().as_secs_f64(),
+            },
+            Ok(output) => {
+                let combined = format!("{}\n{}", output.stdout, output.stderr);
+                let lines: Vec<&str> = combined.trim().lines().collect();
+                let tail = lines[lines.len().saturating_sub(INSTALL_TAIL_LINES)..].join("\n");
+                Install::Failed {
+                    command,
+                    error: format!("exit code {}:\n{tail}", output.exit_code),
+                }
+            }
+            Err(e) => Install::Failed {
+                command,
+                error: format!("{e:#}"),
+            },
         }
     }
 }
@@ -31,10 +200,12 @@ impl Tool for SandboxRestoreSnapshotTool {
     }
 
     fn description(&self) -> &str {
-        "Restore project files from the last saved SQLite snapshot into the active sandbox. \
-         Use this after sandbox_create when resuming work on a previously built project. \
-         Writes every file from the snapshot back into the sandbox under the given workdir. \
-         Returns the number of files restored."
+        "Restore a saved project snapshot into the sandbox and get it ready to run. \
+         Use this when resuming work on a previously built project. Creates a sandbox \
+         if none is active (fresh=true always starts a new one), writes every snapshot \
+         file under the workdir, runs the project's install command, and reports whether \
+         the project is ready. Restores the latest snapshot by default; pass project \
+         and/or version (1 = newest) to restore an older version or another project."
     }
 
     fn parameters_schema(&self) -> serde_json::Value {
@@ -44,6 +215,23 @@ impl Tool for SandboxRestoreSnapshotTool {
                 "workdir": {
                     "type": "string",
                     "description": "Project root relative to sandbox root (e.g. 'project'). Default: 'project'. Must match the workdir used when the snapshot was saved."
+                },
+                "project": {
+                    "type": "string",
+                    "description": "Project whose snapshot versions to restore from. Default: the project of the last snapshot."
+                },
+                "version": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": "Snapshot version to restore, 1 = newest. Default: the latest snapshot."
+                },
+                "fresh": {
+                    "type": "boolean",
+                    "description": "Restore into a new, empty sandbox even if one is active. Default: false."
+                },
+                "install": {
+                    "type": "boolean",
+                    "description": "Run the install command after restoring. Default: true."
                 }
             },
             "required": []
@@ -51,93 +239,119 @@ impl Tool for SandboxRestoreSnapshotTool {
     }
 
     async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
-        if let Err(e) = self.client.require_id() {
-            return Ok(ToolResult {
-                success: false,
-                output: String::new(),
-                error: Some(e),
-                error_hint: Some(
-                    "Call sandbox_create before sandbox_restore_snapshot.".to_string(),
-                ),
-            });
-        }
+        let workdir = args["workdir"].as_str().unwrap_or(PROJECT_DIR);
+        let project = args["project"].as_str().filter(|p| !p.trim().is_empty());
+        let version = args["version"].as_u64();
+        let fresh = args["fresh"].as_bool().unwrap_or(false);
+        let install = args["install"].as_bool().unwrap_or(true);
 
-        let workdir = args["workdir"].as_str().unwrap_or("project");
-
-        let conn = match store::init_db(&self.db_path) {
-            Ok(c) => c,
+        let snapshot = match self.load(project, version).await {
+            Ok(snapshot) => snapshot,
             Err(e) => {
                 return Ok(ToolResult {
                     success: false,
                     output: String::new(),
-                    error: Some(format!("Failed to open store DB: {e}")),
+                    error: Some(e),
+                    error_code: None,
                     error_hint: None,
                 })
             }
         };
 
-        let snapshot = match store::snapshot::load_snapshot(&conn) {
-            Ok(Some(s)) => s,
-            Ok(None) => {
-                return Ok(ToolResult {
-                    success: false,
-                    output: String::new(),
-                    error: Some("No snapshot found. Run sandbox_save_snapshot first.".to_string()),
-                    error_hint: None,
-                })
-            }
-            Err(e) => {
-                return Ok(ToolResult {
-                    success: false,
-                    output: String::new(),
-                    error: Some(format!("Failed to load snapshot: {e}")),
-                    error_hint: None,
-                })
+        let mut report = Vec::new();
+        if fresh || self.client.current_id().is_none() {
+            match self
+                .client
+                .create_sandbox(fresh, "", self.create_timeout_ms)
+                .await
+            {
+                Ok(id) => report.push(format!("Sandbox created: {id}")),
+                Err(e) => {
+                    return Ok(ToolResult {
+                        success: false,
+                        output: String::new(),
+                        error: Some(format!("Failed to create a sandbox: {e:#}")),
+                        error_code: None,
+                        error_hint: None,
+                    })
+                }
             }
-        };
-
-        let (files, project_type) = snapshot;
-        let total = files.len();
-        let mut failed: Vec<String> = Vec::new();
+        }
 
-        for (rel_path, content) in &files {
+        let files: Vec<(String, String)> = snapshot
+            .files
+            .into_iter()
             // Restore under workdir — e.g. "project/src/pages/index.tsx"
-            let dest = format!("{workdir}/{rel_path}");
-            if let Err(e) = self.client.write_file(&dest, content).await {
+            .map(|(rel_path, content)| (format!("{workdir}/{rel_path}"), content))
+            .collect();
+        let total = files.len();
+        let mut failed: Vec<&str> = Vec::new();
+        for ((dest, _), result) in files.iter().zip(self.client.write_files(&files).await) {
+            if let Err(e) = result {
                 tracing::warn!("Failed to restore {dest}: {e}");
                 failed.push(dest);
             }
         }
-
         let restored = total - failed.len();
+        report.push(format!(
+            "Snapshot restored: {restored}/{total} files written to '{workdir}' ({}, project_type: {}).",
+            snapshot.source,
+            snapshot.project_type.as_deref().unwrap_or("unknown")
+        ));
 
-        if failed.is_empty() {
-            Ok(ToolResult {
-                success: true,
-                output: format!(
-                    "Snapshot restored: {restored}/{total} files written to '{workdir}' (project_type: {}).",
-                    project_type.as_deref().unwrap_or("unknown")
-                ),
-                error: None,
-                error_hint: None,
-            })
-        } else {
-            Ok(ToolResult {
+        if !failed.is_empty() {
+            return Ok(ToolResult {
                 success: false,
-                output: format!(
-                    "Restored {restored}/{total} files. {} failed.",
-                    failed.len()
-                ),
+                output: report.join("\n"),
                 error: Some(format!("Failed to restore: {}", failed.join(", "))),
+                error_code: None,
                 error_hint: None,
-            })
+            });
+        }
+
+        let install = if install {
+            self.client.detect_package_manager().await;
+            self.install(workdir).await
+        } else {
+            Install::Skipped("install=false")
+        };
+        match install {
+            Install::Skipped(reason) => report.push(format!("Install: skipped ({reason}).")),
+            Install::Succeeded { command, secs } => {
+                report.push(format!("Install: `{command}` succeeded in {secs:.1}s."));
+            }
+            Install::Failed { command, error } => {
+                report.push(format!("Install: `{command}` failed ({error})"));
+                return Ok(ToolResult {
+                    success: false,
+                    output: report.join("\n"),
+                    error: Some(format!("Dependencies did not install with `{command}`.")),
+                    error_code: None,
+                    error_hint: Some(
+                        "The files are restored; fix the install error and rerun the install with sandbox_run_command."
+                            .to_string(),
+                    ),
+                });
+            }
         }
+        report.push("Ready: the project is restored and can be built or started.".to_string());
+
+        Ok(ToolResult {
+            success: true,
+            output: report.join("\n"),
+            error: None,
+            error_code: None,
+            error_hint: None,
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::artifacts::LocalStorage;
+    use crate::config::ArtifactsConfig;
+    use crate::sandbox::mock::MockSandboxClient;
     use tempfile::TempDir;
 
     #[test]
@@ -149,4 +363,80 @@ mod tests {
             TOOL_NAME
         );
     }
+
+    #[tokio::test]
+    async fn restores_the_latest_snapshot_into_a_new_sandbox_and_installs() {
+        let tmp = TempDir::new().unwrap();
+        let db_path = tmp.path().join("zerobuild.db");
+        let conn = store::init_db(&db_path).unwrap();
+        let files = HashMap::from([
+            ("package.json".to_string(), "{}".to_string()),
+            ("package-lock.json".to_string(), "{}".to_string()),
+        ]);
+        store::snapshot::save_snapshot(&conn, &files, Some("nextjs")).unwrap();
+
+        let client = Arc::new(MockSandboxClient::new());
+        client.on_command("ls -1A", "package.json\npackage-lock.json\n", "", 0);
+        let tool = SandboxRestoreSnapshotTool::new(client.clone(), &db_path);
+
+        let result = tool.execute(json!({})).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(result.output.contains("Sandbox created"));
+        assert!(result.output.contains("2/2 files"));
+        assert!(result.output.contains("Ready"));
+        assert_eq!(client.files()["project/package.json"], "{}");
+        let commands = client.commands();
+        let install = commands.last().unwrap();
+        assert_eq!(install.command, "npm ci");
+        assert_eq!(install.workdir, "project");
+    }
+
+    #[tokio::test]
+    async fn restores_an_older_version_and_reports_a_failed_install() {
+        let tmp = TempDir::new().unwrap();
+        let db_path = tmp.path().join("zerobuild.db");
+        let artifacts = Arc::new(Artifacts::new(
+            Arc::new(LocalStorage::new(tmp.path().join("artifacts"))),
+            &db_path,
+            &ArtifactsConfig::default(),
+        ));
+        for (app, install) in [("v1", "npm ci"), ("v2", "make deps")] {
+            let version = json!({
+                "project_type": "static",
+                "files": {
+                    "app.txt": app,
+                    ".zerobuild.toml": format!("[commands]\ninstall = \"{install}\"\n"),
+                },
+            });
+            artifacts
+                .save(
+                    Some("todo"),
+                    ArtifactKind::Snapshot,
+                    "snapshot.json",
+                    serde_json::to_vec(&version).unwrap(),
+                )
+                .await
+                .unwrap();
+        }
+
+        let client = Arc::new(MockSandboxClient::new());
+        client.on_command("npm ci", "", "npm ERR! missing lockfile", 1);
+        let tool =
+            SandboxRestoreSnapshotTool::new(client.clone(), &db_path).with_artifacts(artifacts);
+
+        let result = tool
+            .execute(json!({"project": "todo", "version": 2}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.output.contains("version 2 of 2 of project 'todo'"));
+        assert!(result.output.contains("npm ERR! missing lockfile"));
+        assert_eq!(client.files()["project/app.txt"], "v1");
+
+        let missing = tool
+            .execute(json!({"project": "todo", "version": 3}))
+            .await
+            .unwrap();
+        assert!(missing.error.unwrap().contains("has 2 snapshot versions"));
+    }
 }
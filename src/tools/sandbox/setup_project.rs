@@ -0,0 +1,616 @@
+//! `setup_project` tool — create a sandbox and a ready-to-build project in
+//! one call.
+//!
+//! Starting a project otherwise takes a turn per step: `sandbox_create`, a
+//! scaffold generator, the dependency install, `git init` and the first
+//! `sandbox_save_snapshot`. This tool runs those steps in order through the
+//! same tools, so commands land in the command history and installs send
+//! heartbeats, and reports each step through [`Steps`]. The first
+//! failing step stops the setup and leaves the sandbox as it is.
+
+use super::command::SandboxRunCommandTool;
+use super::create::SandboxCreateTool;
+use super::snapshot::SandboxSaveSnapshotTool;
+use crate::error;
+use crate::sandbox::project_config::PROJECT_DIR;
+use crate::sandbox::templates::TemplateRegistry;
+use crate::sandbox::{PackageManager, SandboxClient};
+use crate::tools::progress::Steps;
+use crate::tools::traits::{Tool, ToolResult};
+use async_trait::async_trait;
+use serde_json::json;
+use std::fmt::Write;
+use std::sync::Arc;
+
+const TOOL_NAME: &str = "setup_project";
+
+/// Longest project name.
+const MAX_NAME: usize = 64;
+
+/// Author of the initial commit.
+const GIT_IDENTITY: &str = "-c user.name=ZeroBuild -c user.email=zerobuild@localhost";
+
+/// How a template's project comes into being.
+struct Starter {
+    /// Generator creating the project directory, run from the sandbox root.
+    /// `{dir}` is the project directory and `{pm}` the package manager.
+    generator: Option<&'static str>,
+    /// Files written into the project directory instead; `{name}` is the
+    /// project name.
+    files: &'static [(&'static str, &'static str)],
+    /// Dependency install run in the project directory; `{install}` is the
+    /// package manager's install command.
+    install: Option<&'static str>,
+    /// Renames a generated or prebuilt project; `{name}` is the project name.
+    rename: Option<&'static str>,
+    /// Command serving the app during development; `{run}` is the package
+    /// manager's script runner.
+    dev: &'static str,
+}
+
+const STATIC_FILES: &[(&str, &str)] = &[
+    (
+        "index.html",
+        r#"<!doctype html>
+<html lang="en">
+  <head>
+    <meta charset="utf-8" />
+    <meta name="viewport" content="width=device-width, initial-scale=1" />
+    <title>{name}</title>
+    <link rel="stylesheet" href="styles.css" />
+  </head>
+  <body>
+    <main>
+      <h1>{name}</h1>
+    </main>
+    <script src="script.js"></script>
+  </body>
+</html>
+"#,
+    ),
+    (
+        "styles.css",
+        "body {\n  margin: 0;\n  font-family: system-ui, sans-serif;\n}\n\nmain {\n  max-width: 60rem;\n  margin: 0 auto;\n  padding: 2rem;\n}\n",
+    ),
+    ("script.js", "// Page behaviour goes here.\n"),
+    (".gitignore", ".DS_Store\n"),
+];
+
+const NODE_API_FILES: &[(&str, &str)] = &[
+    (
+        "package.json",
+        r#"{
+  "name": "{name}",
+  "version": "0.1.0",
+  "private": true,
+  "type": "module",
+  "scripts": {
+    "dev": "node --watch src/index.js",
+    "start": "node src/index.js"
+  }
+}
+"#,
+    ),
+    (
+        "src/index.js",
+        r#"import { createServer } from "node:http";
+
+const port = Number(process.env.PORT ?? 3000);
+
+const server = createServer((req, res) => {
+  if (req.url === "/health") {
+    res.writeHead(200, { "content-type": "application/json" });
+    res.end(JSON.stringify({ status: "ok" }));
+    return;
+  }
+  res.writeHead(404, { "content-type": "application/json" });
+  res.end(JSON.stringify({ error: "Not found" }));
+});
+
+server.listen(port, () => {
+  console.log(`{name} listening on http://localhost:${port}`);
+});
+"#,
+    ),
+    (".gitignore", "node_modules\n.env\n"),
+];
+
+const FASTAPI_FILES: &[(&str, &str)] = &[
+    ("requirements.txt", "fastapi\nuvicorn[standard]\n"),
+    (
+        "main.py",
+        r#"from fastapi import FastAPI
+
+app = FastAPI(title="{name}")
+
+
+@app.get("/health")
+def health() -> dict[str, str]:
+    return {"status": "ok"}
+"#,
+    ),
+    (".gitignore", ".venv\n__pycache__/\n.env\n"),
+];
+
+const RUST_FILES: &[(&str, &str)] = &[
+    (
+        "Cargo.toml",
+        "[package]\nname = \"{name}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\n",
+    ),
+    (
+        "src/main.rs",
+        "fn main() {\n    println!(\"Hello from {name}!\");\n}\n",
+    ),
+    (".gitignore", "target\n"),
+];
+
+/// Renames a Node.js project.
+const NPM_RENAME: &str = "npm pkg set name={name}";
+
+/// How to start a project from `template`, one of
+/// [`crate::sandbox::PROJECT_TEMPLATES`].
+fn starter(template: &str) -> Option<Starter> {
+    let starter = match template {
+        "nextjs" => Starter {
+            generator: Some(
+                "npx --yes create-next-app@latest {dir} --typescript --tailwind --eslint --app \
+                 --src-dir --import-alias '@/*' --use-{pm} --skip-install --disable-git --yes",
+            ),
+            files: &[],
+            install: Some("{install}"),
+            rename: Some(NPM_RENAME),
+            dev: "{run} dev",
+        },
+        "vite-react" => Starter {
+            generator: Some("npm create vite@latest {dir} -- --template react-ts"),
+            files: &[],
+            install: Some("{install}"),
+            rename: Some(NPM_RENAME),
+            dev: "{run} dev",
+        },
+        "static" => Starter {
+            generator: None,
+            files: STATIC_FILES,
+            install: None,
+            rename: None,
+            dev: "npx --yes serve -l 3000 .",
+        },
+        "node-api" => Starter {
+            generator: None,
+            files: NODE_API_FILES,
+            install: Some("{install}"),
+            rename: Some(NPM_RENAME),
+            dev: "{run} dev",
+        },
+        "fastapi" => Starter {
+            generator: None,
+            files: FASTAPI_FILES,
+            install: Some(
+                "python3 -m venv .venv && .venv/bin/python -m pip install -q -r requirements.txt",
+            ),
+            rename: None,
+            dev: ".venv/bin/python -m uvicorn main:app --reload --host 0.0.0.0 --port 3000",
+        },
+        "rust" => Starter {
+            generator: None,
+            files: RUST_FILES,
+            install: Some("cargo fetch"),
+            rename: None,
+            dev: "cargo run",
+        },
+        _ => return None,
+    };
+    Some(starter)
+}
+
+/// Check a project name from the agent: it becomes the package name.
+fn valid_name(name: &str) -> Result<(), String> {
+    if name.is_empty()
+        || name.len() > MAX_NAME
+        || name.starts_with(['.', '_', '-'])
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '_' | '.'))
+    {
+        return Err(format!(
+            "Invalid project name '{name}': use up to {MAX_NAME} lowercase letters, digits, \
+             '-', '_' or '.', starting with a letter or digit"
+        ));
+    }
+    Ok(())
+}
+
+fn failure(error: String) -> ToolResult {
+    ToolResult {
+        success: false,
+        output: String::new(),
+        error: Some(error),
+        error_code: None,
+        error_hint: None,
+    }
+}
+
+/// What to do after a failed step.
+const STOPPED_HINT: &str = "The sandbox keeps everything done so far. Fix the problem with \
+     sandbox_run_command and finish the remaining steps one by one; call setup_project with \
+     reset=true only to start over.";
+
+pub struct SetupProjectTool {
+    client: Arc<dyn SandboxClient>,
+    create: SandboxCreateTool,
+    commands: SandboxRunCommandTool,
+    snapshot: SandboxSaveSnapshotTool,
+    templates: Arc<TemplateRegistry>,
+}
+
+impl SetupProjectTool {
+    /// Set projects up through these tools; `create` also provides the
+    /// default template.
+    pub fn new(
+        client: Arc<dyn SandboxClient>,
+        create: SandboxCreateTool,
+        commands: SandboxRunCommandTool,
+        snapshot: SandboxSaveSnapshotTool,
+    ) -> Self {
+        Self {
+            client,
+            create,
+            commands,
+            snapshot,
+            templates: Arc::new(TemplateRegistry::default()),
+        }
+    }
+
+    /// Copy the prebuilt projects of `templates` instead of scaffolding
+    /// from scratch where one is configured.
+    pub fn with_templates(mut self, templates: Arc<TemplateRegistry>) -> Self {
+        self.templates = templates;
+        self
+    }
+
+    /// Run `command` in `workdir` with the longest allowed timeout.
+    async fn run_command(&self, command: &str, workdir: &str) -> ToolResult {
+        let (_, max_timeout_ms) = self.commands.timeouts_ms();
+        let args = json!({"command": command, "workdir": workdir, "timeout_ms": max_timeout_ms});
+        match self.commands.execute(args).await {
+            Ok(result) => result,
+            Err(e) => failure(e.to_string()),
+        }
+    }
+
+    /// Create the project directory from `starter`.
+    async fn scaffold(
+        &self,
+        template: &str,
+        starter: &Starter,
+        name: &str,
+        pm: PackageManager,
+    ) -> ToolResult {
+        if let Ok(listing) = self.client.list_files(PROJECT_DIR).await {
+            if !listing.trim().is_empty() {
+                return failure(format!(
+                    "{PROJECT_DIR}/ already exists in this sandbox; pass reset=true to start \
+                     from a fresh sandbox"
+                ));
+            }
+        }
+        let rename = starter
+            .rename
+            .map(|rename| format!(" && cd {PROJECT_DIR} && {}", rename.replace("{name}", name)))
+            .unwrap_or_default();
+        if let Some(prebuilt) = self.templates.prebuilt_dir(template) {
+            let prebuilt = prebuilt.display().to_string().replace('\'', r"'\''");
+            let command =
+                format!("mkdir -p {PROJECT_DIR} && cp -a '{prebuilt}/.' {PROJECT_DIR}/{rename}");
+            let mut result = self.run_command(&command, ".").await;
+            if result.success {
+                result.output =
+                    format!("Copied the prebuilt {template} project.\n{}", result.output);
+            }
+            return result;
+        }
+        if let Some(generator) = starter.generator {
+            let command = format!(
+                "{}{rename}",
+                generator
+                    .replace("{dir}", PROJECT_DIR)
+                    .replace("{pm}", pm.as_str())
+            );
+            return self.run_command(&command, ".").await;
+        }
+        let files: Vec<(String, String)> = starter
+            .files
+            .iter()
+            .map(|(path, content)| {
+                (
+                    format!("{PROJECT_DIR}/{path}"),
+                    content.replace("{name}", name),
+                )
+            })
+            .collect();
+        let results = self.client.write_files(&files).await;
+        if let Some(e) = results.into_iter().find_map(Result::err) {
+            return ToolResult {
+                error_code: Some(error::code_of(&e)),
+                ..failure(format!("Failed to write starter files: {e}"))
+            };
+        }
+        ToolResult {
+            success: true,
+            output: format!("Wrote {} starter files.", files.len()),
+            error: None,
+            error_code: None,
+            error_hint: None,
+        }
+    }
+
+    async fn save_snapshot(&self, template: &str, name: &str) -> ToolResult {
+        let args = json!({"workdir": PROJECT_DIR, "project_type": template, "project": name});
+        match self.snapshot.execute(args).await {
+            Ok(result) => result,
+            Err(e) => failure(e.to_string()),
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for SetupProjectTool {
+    fn name(&self) -> &str {
+        TOOL_NAME
+    }
+
+    fn description(&self) -> &str {
+        "Start a NEW project in one call: creates the sandbox, scaffolds the project from a \
+         template into project/, installs dependencies, initializes git with a first commit \
+         and saves the first snapshot. Use it instead of calling sandbox_create, a scaffold \
+         command, the install and sandbox_save_snapshot one by one. Templates: nextjs \
+         (Next.js + Tailwind), vite-react, static (HTML/CSS/JS), node-api, fastapi (Python) \
+         and rust. If a step fails the setup stops there and the sandbox is kept, so continue by hand from that step. Not \
+         for existing projects: restore those with sandbox_restore_snapshot."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        let templates: Vec<&str> = crate::sandbox::PROJECT_TEMPLATES
+            .iter()
+            .map(|(name, _)| *name)
+            .collect();
+        let default = match self.create.template() {
+            "" => String::new(),
+            template => format!(" Default: '{template}'."),
+        };
+        json!({
+            "type": "object",
+            "properties": {
+                "name": {
+                    "type": "string",
+                    "description": "Project name, used as the package name and to key project memory (lowercase letters, digits, '-', '_', '.'), e.g. 'todo-app'."
+                },
+                "template": {
+                    "type": "string",
+                    "enum": templates,
+                    "description": format!("Project template.{default}")
+                },
+                "reset": {
+                    "type": "boolean",
+                    "description": "If true, kill any existing sandbox and start from a fresh one. Default: false."
+                }
+            },
+            "required": ["name"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let name = args["name"].as_str().map_or("", str::trim);
+        if let Err(e) = valid_name(name) {
+            return Ok(failure(e));
+        }
+        let template = args["template"]
+            .as_str()
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .unwrap_or(self.create.template());
+        let Some(starter) = starter(template) else {
+            let templates: Vec<&str> = crate::sandbox::PROJECT_TEMPLATES
+                .iter()
+                .map(|(name, _)| *name)
+                .collect();
+            return Ok(failure(format!(
+                "Unknown template '{template}'; expected one of: {}",
+                templates.join(", ")
+            )));
+        };
+        let reset = args["reset"].as_bool().unwrap_or(false);
+
+        let mut steps = Steps::new(TOOL_NAME, if starter.install.is_some() { 5 } else { 4 });
+        let created = steps
+            .run("Create sandbox", self.create.create(reset, template))
+            .await;
+        if !created.success {
+            return Ok(steps.stopped("Create sandbox", created, STOPPED_HINT));
+        }
+        let sandbox_id = self.client.current_id().unwrap_or_default();
+        let pm = self.client.package_manager();
+
+        let scaffolded = steps
+            .run("Scaffold", self.scaffold(template, &starter, name, pm))
+            .await;
+        if !scaffolded.success {
+            return Ok(steps.stopped("Scaffold", scaffolded, STOPPED_HINT));
+        }
+
+        if let Some(install) = starter.install {
+            let install = install.replace("{install}", pm.install_cmd());
+            let installed = steps
+                .run(
+                    "Install dependencies",
+                    self.run_command(&install, PROJECT_DIR),
+                )
+                .await;
+            if !installed.success {
+                return Ok(steps.stopped("Install dependencies", installed, STOPPED_HINT));
+            }
+        }
+
+        // Without git the project is still usable; note it and go on.
+        let git = format!(
+            "git init -q && git add -A && git {GIT_IDENTITY} commit -q -m 'Initial commit'"
+        );
+        let committed = steps
+            .run("Initialize git", self.run_command(&git, PROJECT_DIR))
+            .await;
+        let snapshot = steps
+            .run("Save snapshot", self.save_snapshot(template, name))
+            .await;
+
+        let description = crate::sandbox::project_template(template).unwrap_or_default();
+        let mut output = format!(
+            "Project '{name}' set up from template {template} ({description}) in {PROJECT_DIR}/.\n\
+             sandbox_id: {sandbox_id}\npackage_manager: {pm}\n\n{}",
+            steps.summary()
+        );
+        if !committed.success {
+            let _ = write!(
+                output,
+                "\n\nwarning: git was not initialized ({}); the project works without it.",
+                committed.error.as_deref().unwrap_or("git failed")
+            );
+        }
+        if snapshot.success {
+            let _ = write!(output, "\n\n{}", snapshot.output);
+        } else {
+            let _ = write!(
+                output,
+                "\n\nwarning: no snapshot was saved ({}); call sandbox_save_snapshot later.",
+                snapshot.error.as_deref().unwrap_or("snapshot failed")
+            );
+        }
+        let dev = starter.dev.replace("{run}", pm.run_cmd());
+        let _ = write!(
+            output,
+            "\n\nNext: implement the app in {PROJECT_DIR}/, then serve it with `{dev}` \
+             (sandbox_run_command, workdir {PROJECT_DIR}) and open it with sandbox_get_preview_url."
+        );
+        Ok(ToolResult {
+            success: true,
+            output,
+            error: None,
+            error_code: None,
+            error_hint: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sandbox::mock::MockSandboxClient;
+    use crate::store;
+
+    fn tool(client: &Arc<MockSandboxClient>, db_path: &std::path::Path) -> SetupProjectTool {
+        SetupProjectTool::new(
+            client.clone(),
+            SandboxCreateTool::new(client.clone(), "nextjs", 1000),
+            SandboxRunCommandTool::new(client.clone()).with_log_store(db_path),
+            SandboxSaveSnapshotTool::new(client.clone(), db_path),
+        )
+    }
+
+    #[tokio::test]
+    async fn sets_up_a_static_site_in_one_call() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let db_path = tmp.path().join("zerobuild.db");
+        let client = Arc::new(MockSandboxClient::new());
+
+        let result = tool(&client, &db_path)
+            .execute(json!({"name": "landing", "template": "static"}))
+            .await
+            .unwrap();
+        assert!(result.success, "{result:?}");
+        assert!(result
+            .output
+            .starts_with("Project 'landing' set up from template static"));
+        for step in [
+            "Create sandbox",
+            "Scaffold",
+            "Initialize git",
+            "Save snapshot",
+        ] {
+            assert!(result.output.contains(&format!("✓ {step}")), "{step}");
+        }
+        assert!(!result.output.contains("Install dependencies"));
+        assert!(result
+            .output
+            .contains("Snapshot saved: 4 files from project"));
+        assert!(client.files()["project/index.html"].contains("<title>landing</title>"));
+
+        let commands = client.commands();
+        assert_eq!(commands.len(), 1);
+        assert!(commands[0].command.starts_with("git init -q"));
+        assert_eq!(commands[0].workdir, "project");
+        let conn = store::init_db(&db_path).unwrap();
+        assert!(store::snapshot::load_snapshot(&conn).unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn stops_at_the_first_failing_step() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let db_path = tmp.path().join("zerobuild.db");
+        let client = Arc::new(MockSandboxClient::new());
+        client.on_command("create-next-app", "", "npm ERR! network", 1);
+        let tool = tool(&client, &db_path);
+
+        let bad = tool.execute(json!({"name": "My App"})).await.unwrap();
+        assert!(bad.error.unwrap().contains("Invalid project name"));
+
+        let result = tool.execute(json!({"name": "shop"})).await.unwrap();
+        assert!(!result.success);
+        assert!(result.output.starts_with("✓ Create sandbox"));
+        assert!(result.output.contains("✗ Scaffold"));
+        assert!(result
+            .error
+            .unwrap()
+            .starts_with("setup_project stopped at 'Scaffold'"));
+        let commands = client.commands();
+        let scaffold = commands
+            .iter()
+            .find(|c| c.command.contains("create-next-app"))
+            .unwrap();
+        assert!(scaffold.command.contains("--use-npm --skip-install"));
+        assert_eq!(scaffold.workdir, ".");
+        assert!(!commands.iter().any(|c| c.command == "npm install"));
+    }
+
+    #[tokio::test]
+    async fn copies_the_prebuilt_project_of_the_template() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let db_path = tmp.path().join("zerobuild.db");
+        let client = Arc::new(MockSandboxClient::new());
+        let templates = TemplateRegistry::from_config(&std::collections::HashMap::from([(
+            "node-api".to_string(),
+            crate::config::SandboxTemplateConfig {
+                prebuilt_dir: "/opt/prebuilt/node-api".into(),
+                ..Default::default()
+            },
+        )]));
+
+        let result = tool(&client, &db_path)
+            .with_templates(Arc::new(templates))
+            .execute(json!({"name": "api", "template": "node-api"}))
+            .await
+            .unwrap();
+        assert!(result.success, "{result:?}");
+        assert!(!client.files().contains_key("project/package.json"));
+        let commands: Vec<_> = client
+            .commands()
+            .into_iter()
+            .map(|c| c.command)
+            .filter(|c| c != "ls -1A")
+            .collect();
+        assert_eq!(
+            commands[0],
+            "mkdir -p project && cp -a '/opt/prebuilt/node-api/.' project/ && cd project \
+             && npm pkg set name=api"
+        );
+        assert_eq!(commands[1], "npm install");
+    }
+}
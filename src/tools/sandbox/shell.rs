@@ -0,0 +1,325 @@
+//! `sandbox_shell` tool — run commands in a persistent shell session.
+//!
+//! Each `sandbox_run_command` call starts a fresh shell, so `cd`, exported
+//! variables and `nvm use` are gone by the next call. A named session keeps
+//! one shell running per name (see [`SandboxClient::run_in_session`]).
+//! Output is truncated, stored and recorded in the command history like
+//! `sandbox_run_command` output.
+
+use super::command::{duration_ms, SandboxRunCommandTool};
+use crate::artifacts::Artifacts;
+use crate::config::SandboxLimitsConfig;
+use crate::error;
+use crate::sandbox::SandboxClient;
+use crate::store;
+use crate::tools::traits::{Tool, ToolResult};
+use async_trait::async_trait;
+use serde_json::json;
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const TOOL_NAME: &str = "sandbox_shell";
+
+/// Session used when the agent names none.
+const DEFAULT_SESSION: &str = "default";
+
+/// Longest session name.
+const MAX_SESSION_NAME: usize = 32;
+
+pub struct SandboxShellTool {
+    client: Arc<dyn SandboxClient>,
+    /// Timeouts, truncation, log storage and history, shared with
+    /// `sandbox_run_command`.
+    commands: SandboxRunCommandTool,
+}
+
+impl SandboxShellTool {
+    pub fn new(client: Arc<dyn SandboxClient>) -> Self {
+        Self::with_limits(client, &SandboxLimitsConfig::default())
+    }
+
+    /// Apply `[zerobuild.sandbox]` command timeouts and output limits.
+    pub fn with_limits(client: Arc<dyn SandboxClient>, limits: &SandboxLimitsConfig) -> Self {
+        Self {
+            commands: SandboxRunCommandTool::with_limits(client.clone(), limits),
+            client,
+        }
+    }
+
+    /// Store the full output of truncated commands for `get_log` and record
+    /// every command for `sandbox_command_history`.
+    pub fn with_log_store(mut self, db_path: impl Into<PathBuf>) -> Self {
+        self.commands = self.commands.with_log_store(db_path);
+        self
+    }
+
+    /// Also keep stored logs in `artifacts`.
+    pub fn with_artifacts(mut self, artifacts: Arc<Artifacts>) -> Self {
+        self.commands = self.commands.with_artifacts(artifacts);
+        self
+    }
+
+    async fn run(
+        &self,
+        sandbox_id: &str,
+        session: &str,
+        args: &serde_json::Value,
+    ) -> anyhow::Result<ToolResult> {
+        let command = args["command"]
+            .as_str()
+            .map(str::trim)
+            .filter(|command| !command.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'command' parameter for action 'run'"))?;
+        let (default_timeout_ms, max_timeout_ms) = self.commands.timeouts_ms();
+        let timeout_ms = args["timeout_ms"]
+            .as_u64()
+            .unwrap_or(default_timeout_ms)
+            .min(max_timeout_ms);
+
+        let started = std::time::Instant::now();
+        let result = self
+            .client
+            .run_in_session(session, command, timeout_ms)
+            .await?;
+        let output = result.output;
+        let exit_code = output.exit_code;
+
+        let log_id = if self.commands.needs_truncation(&output.stdout) {
+            self.commands.store_log(command, &result.cwd, &output).await
+        } else {
+            None
+        };
+        self.commands
+            .record_history(store::command_history::NewCommand {
+                sandbox_id,
+                session: Some(session),
+                command,
+                workdir: &result.cwd,
+                exit_code,
+                duration_ms: duration_ms(started.elapsed()),
+                log_id: log_id.as_deref(),
+            });
+
+        let mut out = format!(
+            "session: {session} (cwd: {})\nexit_code: {exit_code}",
+            result.cwd
+        );
+        if let Some(id) = &log_id {
+            let _ = write!(out, "\nlog_id: {id}");
+        }
+        if !output.stdout.is_empty() {
+            let text = self
+                .commands
+                .truncate(&output.stdout, "stdout", log_id.as_deref());
+            let _ = write!(out, "\n\noutput:\n{text}");
+        }
+        if !output.stderr.is_empty() {
+            let _ = write!(out, "\n\n{}", output.stderr);
+        }
+        Ok(ToolResult {
+            success: exit_code == 0,
+            output: out,
+            error: (exit_code != 0).then(|| format!("Command exited with code {exit_code}")),
+            error_code: None,
+            error_hint: None,
+        })
+    }
+
+    async fn list(&self) -> ToolResult {
+        let sessions = self.client.shell_sessions().await;
+        let output = if sessions.is_empty() {
+            "No shell sessions are open.".to_string()
+        } else {
+            let mut out = String::from("Open shell sessions:");
+            for session in sessions {
+                let cwd = session.cwd.as_deref().unwrap_or("(running a command)");
+                let _ = write!(out, "\n- {} (cwd: {cwd})", session.name);
+            }
+            out
+        };
+        ToolResult {
+            success: true,
+            output,
+            error: None,
+            error_code: None,
+            error_hint: None,
+        }
+    }
+}
+
+/// Check a session name from the agent.
+fn valid_session(name: &str) -> Result<(), String> {
+    if name.is_empty()
+        || name.len() > MAX_SESSION_NAME
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "Invalid session '{name}': use up to {MAX_SESSION_NAME} letters, digits, '-' or '_'"
+        ));
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl Tool for SandboxShellTool {
+    fn name(&self) -> &str {
+        TOOL_NAME
+    }
+
+    fn description(&self) -> &str {
+        "Run a command in a PERSISTENT shell session inside the sandbox. Unlike \
+         sandbox_run_command, state carries over between calls of the same session: the \
+         working directory (cd), exported variables, shell functions and activated tools \
+         (source ~/.nvm/nvm.sh && nvm use 20, source .venv/bin/activate). A new session starts \
+         in the sandbox root, so cd into the project first. Commands run as written (no \
+         package manager rewriting); stderr is merged into the output. \
+         action: 'run' (default) runs `command` in `session` (default 'default'), 'list' shows \
+         open sessions and their directories, 'close' ends `session`. A command that times out \
+         resets its session. Requires an active sandbox."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        let (default_timeout_ms, max_timeout_ms) = self.commands.timeouts_ms();
+        json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["run", "list", "close"],
+                    "description": "What to do. Default: 'run'."
+                },
+                "session": {
+                    "type": "string",
+                    "description": "Session name (letters, digits, '-', '_'). Default: 'default'."
+                },
+                "command": {
+                    "type": "string",
+                    "description": "Shell command to run (action 'run'), e.g. 'cd project && nvm use 20'"
+                },
+                "timeout_ms": {
+                    "type": "integer",
+                    "description": format!(
+                        "Timeout in milliseconds. Default: {default_timeout_ms}, maximum: {max_timeout_ms}."
+                    )
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let sandbox_id = match self.client.require_id() {
+            Ok(id) => id,
+            Err(e) => {
+                return Ok(ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some(e),
+                    error_code: None,
+                    error_hint: None,
+                })
+            }
+        };
+        let session = args["session"]
+            .as_str()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .unwrap_or(DEFAULT_SESSION);
+        let action = args["action"].as_str().unwrap_or("run");
+        if action != "list" {
+            if let Err(e) = valid_session(session) {
+                return Ok(ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some(e),
+                    error_code: None,
+                    error_hint: None,
+                });
+            }
+        }
+
+        let result = match action {
+            "run" => self.run(&sandbox_id, session, &args).await,
+            "list" => Ok(self.list().await),
+            "close" => self
+                .client
+                .close_session(session)
+                .await
+                .map(|closed| ToolResult {
+                    success: true,
+                    output: if closed {
+                        format!("Closed shell session '{session}'.")
+                    } else {
+                        format!("No shell session '{session}' was open.")
+                    },
+                    error: None,
+                    error_code: None,
+                    error_hint: None,
+                }),
+            other => Err(anyhow::anyhow!(
+                "Unknown action '{other}'; expected run, list or close"
+            )),
+        };
+        Ok(result.unwrap_or_else(|e| ToolResult {
+            success: false,
+            output: String::new(),
+            error: Some(e.to_string()),
+            error_code: Some(error::code_of(&e)),
+            error_hint: None,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn session_keeps_directory_and_records_history() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let db_path = tmp.path().join("zerobuild.db");
+        let client = Arc::new(crate::sandbox::local::LocalProcessSandboxClient::new());
+        client.create_sandbox(false, "", 30_000).await.unwrap();
+        let tool = SandboxShellTool::new(client.clone()).with_log_store(&db_path);
+
+        let result = tool
+            .execute(json!({"command": "mkdir -p project && cd project"}))
+            .await
+            .unwrap();
+        assert!(result.success, "{result:?}");
+        assert!(result.output.starts_with("session: default (cwd: project)"));
+
+        let result = tool
+            .execute(json!({"command": "echo in $(basename $PWD); exit 4"}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.output.contains("exit_code: 4"));
+        assert!(result.output.contains("output:\nin project"));
+
+        let list = tool.execute(json!({"action": "list"})).await.unwrap();
+        assert_eq!(list.output, "No shell sessions are open.");
+
+        let bad = tool
+            .execute(json!({"session": "../x", "command": "ls"}))
+            .await
+            .unwrap();
+        assert!(bad.error.unwrap().contains("Invalid session"));
+
+        let conn = store::init_db(&db_path).unwrap();
+        let history = store::command_history::recent_commands(
+            &conn,
+            &store::command_history::HistoryQuery {
+                limit: 10,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[1].session.as_deref(), Some("default"));
+        assert_eq!(history[1].exit_code, 4);
+        client.kill_sandbox().await.unwrap();
+    }
+}
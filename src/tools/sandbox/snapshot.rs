@@ -1,18 +1,58 @@
 //! `sandbox_save_snapshot` tool — extract files from sandbox to SQLite for persistence.
+//!
+//! Saving a snapshot also refreshes the code index used by `codebase_search`,
+//! adds the project's undocumented environment variables to `.env.example`
+//! and, with an artifact store, keeps the files as a snapshot version.
 
+use crate::artifacts::{ArtifactKind, Artifacts};
+use crate::memory::embeddings::{EmbeddingProvider, NoopEmbedding};
 use crate::sandbox::SandboxClient;
 use crate::store;
+use crate::tools::secret_scan::ENV_EXAMPLE;
 use crate::tools::traits::{Tool, ToolResult};
+use crate::tools::{changelog, env_example, progress};
 use async_trait::async_trait;
+use rusqlite::Connection;
 use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 use std::sync::Arc;
 
 const TOOL_NAME: &str = "sandbox_save_snapshot";
 
+/// Publish what changed since the stored snapshot to observer channels.
+fn report_diff(conn: &Connection, files: &HashMap<String, String>, project: Option<&str>) {
+    let before: BTreeMap<String, String> = match store::snapshot::load_snapshot(conn) {
+        Ok(Some((before, _))) => before.into_iter().collect(),
+        Ok(None) => BTreeMap::new(),
+        Err(e) => {
+            tracing::warn!("Failed to load the previous snapshot: {e:#}");
+            return;
+        }
+    };
+    let after: BTreeMap<String, String> = files.clone().into_iter().collect();
+    let changes = changelog::changes(&before, &after);
+    if changes.is_empty() {
+        return;
+    }
+    let project = match project {
+        Some(project) => project.to_string(),
+        None => store::snapshot::load_project(conn)
+            .ok()
+            .flatten()
+            .unwrap_or_else(|| "project".to_string()),
+    };
+    progress::report_activity(progress::Activity::Diff {
+        project,
+        changes: changelog::describe(&changes),
+    });
+}
+
 pub struct SandboxSaveSnapshotTool {
     client: Arc<dyn SandboxClient>,
     db_path: PathBuf,
+    embedder: Arc<dyn EmbeddingProvider>,
+    artifacts: Option<Arc<Artifacts>>,
 }
 
 impl SandboxSaveSnapshotTool {
@@ -20,8 +60,22 @@ impl SandboxSaveSnapshotTool {
         Self {
             client,
             db_path: db_path.into(),
+            embedder: Arc::new(NoopEmbedding),
+            artifacts: None,
         }
     }
+
+    /// Embed indexed code chunks with `embedder` (keyword-only by default).
+    pub fn with_embedder(mut self, embedder: Arc<dyn EmbeddingProvider>) -> Self {
+        self.embedder = embedder;
+        self
+    }
+
+    /// Keep every saved snapshot as a version in `artifacts`.
+    pub fn with_artifacts(mut self, artifacts: Arc<Artifacts>) -> Self {
+        self.artifacts = Some(artifacts);
+        self
+    }
 }
 
 #[async_trait]
@@ -48,6 +102,10 @@ impl Tool for SandboxSaveSnapshotTool {
                 "project_type": {
                     "type": "string",
                     "description": "Project type hint (e.g. 'nextjs', 'react'). Optional."
+                },
+                "project": {
+                    "type": "string",
+                    "description": "Project name. Project memory (decisions, libraries, known issues) is keyed to it and recalled whenever work on the project resumes. Optional; keeps the previously recorded name."
                 }
             },
             "required": []
@@ -60,39 +118,66 @@ impl Tool for SandboxSaveSnapshotTool {
                 success: false,
                 output: String::new(),
                 error: Some(e),
+                error_code: None,
                 error_hint: None,
             });
         }
 
         let workdir = args["workdir"].as_str().unwrap_or("project");
         let project_type = args["project_type"].as_str();
+        let project = args["project"]
+            .as_str()
+            .map(str::trim)
+            .filter(|p| !p.is_empty());
 
         // Collect snapshot files from the sandbox
-        let files = match self.client.collect_snapshot_files(workdir).await {
+        let mut files = match self.client.collect_snapshot_files(workdir).await {
             Ok(f) => f,
             Err(e) => {
                 return Ok(ToolResult {
                     success: false,
                     output: String::new(),
                     error: Some(format!("Failed to collect snapshot files: {e}")),
+                    error_code: None,
                     error_hint: None,
                 })
             }
         };
 
-        let files_count = files.len();
-
-        if files_count == 0 {
+        if files.is_empty() {
             return Ok(ToolResult {
                 success: false,
                 output: String::new(),
                 error: Some(format!(
                     "No files found in {workdir}. Make sure the project has been created."
                 )),
+                error_code: None,
                 error_hint: None,
             });
         }
 
+        // Document environment variables before the files are stored
+        let tracked = store::init_db(&self.db_path)
+            .and_then(|conn| store::env_vars::list(&conn))
+            .unwrap_or_else(|e| {
+                tracing::warn!("Failed to load tracked environment variables: {e:#}");
+                Vec::new()
+            });
+        let vars = env_example::collect(&files, &tracked);
+        let existing = files.get(ENV_EXAMPLE).map(String::as_str);
+        let env_note = match env_example::update(existing, &vars) {
+            Some((text, report)) => {
+                let path = format!("{workdir}/{ENV_EXAMPLE}");
+                if let Err(e) = self.client.write_file(&path, &text).await {
+                    tracing::warn!("Failed to write {path}: {e}");
+                }
+                files.insert(ENV_EXAMPLE.to_string(), text);
+                format!("\n{ENV_EXAMPLE}: added {}", report.added.join(", "))
+            }
+            None => String::new(),
+        };
+        let files_count = files.len();
+
         // Open DB and save snapshot
         let conn = match store::init_db(&self.db_path) {
             Ok(c) => c,
@@ -101,24 +186,69 @@ impl Tool for SandboxSaveSnapshotTool {
                     success: false,
                     output: String::new(),
                     error: Some(format!("Failed to open store DB: {e}")),
+                    error_code: None,
                     error_hint: None,
                 })
             }
         };
 
+        if progress::has_activity_subscribers() {
+            report_diff(&conn, &files, project);
+        }
+
         if let Err(e) = store::snapshot::save_snapshot(&conn, &files, project_type) {
             return Ok(ToolResult {
                 success: false,
                 output: String::new(),
                 error: Some(format!("Failed to save snapshot: {e}")),
+                error_code: None,
                 error_hint: None,
             });
         }
 
+        if let Some(project) = project {
+            if let Err(e) = store::snapshot::save_project(&conn, project) {
+                tracing::warn!("Failed to record snapshot project: {e}");
+            }
+        }
+
+        drop(conn);
+
+        if let Some(artifacts) = &self.artifacts {
+            let version = serde_json::to_vec(&json!({
+                "project_type": project_type,
+                "files": &files,
+            }))?;
+            if let Err(e) = artifacts
+                .save(project, ArtifactKind::Snapshot, "snapshot.json", version)
+                .await
+            {
+                tracing::warn!("Failed to store snapshot version: {e:#}");
+            }
+        }
+
+        // Index failures must not fail the snapshot itself.
+        let index_note =
+            match store::code_index::index_snapshot(&self.db_path, &files, self.embedder.as_ref())
+                .await
+            {
+                Ok(stats) => format!(
+                    " (code index: {} files updated, {} unchanged)",
+                    stats.files_indexed, stats.files_unchanged
+                ),
+                Err(e) => {
+                    tracing::warn!("Code index refresh failed: {e}");
+                    String::new()
+                }
+            };
+
         Ok(ToolResult {
             success: true,
-            output: format!("Snapshot saved: {files_count} files from {workdir}"),
+            output: format!(
+                "Snapshot saved: {files_count} files from {workdir}{index_note}{env_note}"
+            ),
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
@@ -138,4 +268,29 @@ mod tests {
             TOOL_NAME
         );
     }
+
+    #[tokio::test]
+    async fn snapshot_documents_environment_variables() {
+        let tmp = TempDir::new().unwrap();
+        let db_path = tmp.path().join("zerobuild.db");
+        let conn = store::init_db(&db_path).unwrap();
+        store::env_vars::save(&conn, "STRIPE_KEY", "Stripe secret key", true).unwrap();
+        let client = Arc::new(crate::sandbox::mock::MockSandboxClient::new().with_file(
+            "project/src/db.ts",
+            "export const url = process.env.DATABASE_URL;",
+        ));
+        client.create_sandbox(false, "", 1000).await.unwrap();
+        let tool = SandboxSaveSnapshotTool::new(client.clone(), &db_path);
+
+        let result = tool.execute(json!({})).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(result
+            .output
+            .contains(".env.example: added DATABASE_URL, STRIPE_KEY"));
+        let example = &client.files()["project/.env.example"];
+        assert!(example.contains("# Used in src/db.ts (required)\nDATABASE_URL=\n"));
+        assert!(example.contains("# Stripe secret key (required)\nSTRIPE_KEY=\n"));
+        let (files, _) = store::snapshot::load_snapshot(&conn).unwrap().unwrap();
+        assert_eq!(files[".env.example"], *example);
+    }
 }
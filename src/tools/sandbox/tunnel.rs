@@ -63,6 +63,7 @@ impl Tool for SandboxGetPublicUrlTool {
                     success: false,
                     output: String::new(),
                     error: Some(e),
+                    error_code: None,
                     error_hint: None,
                 })
             }
@@ -75,6 +76,7 @@ impl Tool for SandboxGetPublicUrlTool {
                 success: true,
                 output: format!("Public URL (port {port}): {url}\n(sandbox: {sandbox_id})"),
                 error: None,
+                error_code: None,
                 error_hint: None,
             }),
             Err(e) => {
@@ -88,6 +90,7 @@ impl Tool for SandboxGetPublicUrlTool {
                     success: false,
                     output: String::new(),
                     error: Some(format!("Failed to start tunnel: {msg}")),
+                    error_code: None,
                     error_hint: hint,
                 })
             }
@@ -0,0 +1,340 @@
+//! `wait_for` tool — block until the sandbox reaches a state (a port
+//! listens, a file exists, a URL answers, a log line appears) instead of
+//! polling with `sleep` from the agent, one turn per try.
+
+use crate::config::SandboxLimitsConfig;
+use crate::sandbox::shell_ops::{quote, sandbox_path};
+use crate::sandbox::SandboxClient;
+use crate::tools::traits::{Tool, ToolResult};
+use crate::util::truncate_with_ellipsis;
+use async_trait::async_trait;
+use regex::Regex;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const TOOL_NAME: &str = "wait_for";
+
+/// Wait when the agent passes no `timeout_secs`.
+const DEFAULT_TIMEOUT_SECS: u64 = 60;
+
+/// Interval between checks.
+const POLL: Duration = Duration::from_millis(500);
+
+/// Limit of one URL request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Last lines of a log searched for the pattern.
+const LOG_LINES: usize = 500;
+
+/// Last lines of a log quoted when the wait times out.
+const LOG_TAIL_LINES: usize = 15;
+
+/// What to wait for.
+enum Condition {
+    Port(u16),
+    File(String),
+    Url(String),
+    Log { file: String, pattern: Regex },
+}
+
+impl Condition {
+    fn parse(args: &serde_json::Value) -> anyhow::Result<Self> {
+        let text = |key: &str| args[key].as_str().map(str::trim).filter(|s| !s.is_empty());
+        let mut conditions = Vec::new();
+        if let Some(port) = args["port"].as_u64() {
+            let port = u16::try_from(port)
+                .ok()
+                .filter(|&p| p > 0)
+                .ok_or_else(|| anyhow::anyhow!("Invalid port {port}"))?;
+            conditions.push(Self::Port(port));
+        }
+        if let Some(file) = text("file") {
+            conditions.push(Self::File(file.to_string()));
+        }
+        if let Some(url) = text("url") {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                anyhow::bail!("url must start with http:// or https://");
+            }
+            conditions.push(Self::Url(url.to_string()));
+        }
+        match (text("log_file"), text("pattern")) {
+            (Some(file), Some(pattern)) => conditions.push(Self::Log {
+                file: file.to_string(),
+                pattern: Regex::new(pattern)
+                    .map_err(|e| anyhow::anyhow!("Invalid pattern: {e}"))?,
+            }),
+            (None, None) => {}
+            _ => anyhow::bail!("log_file and pattern go together"),
+        }
+        match conditions.len() {
+            1 => Ok(conditions.remove(0)),
+            0 => anyhow::bail!("Pass one of port, file, url or log_file with pattern"),
+            _ => anyhow::bail!("Pass only one of port, file, url or log_file with pattern"),
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Self::Port(port) => format!("port {port} to listen"),
+            Self::File(file) => format!("'{file}' to exist"),
+            Self::Url(url) => format!("{url} to answer 2xx"),
+            Self::Log { file, pattern } => format!("a line of '{file}' to match /{pattern}/"),
+        }
+    }
+}
+
+/// Result of one check: `Ok` with what was seen once the condition holds,
+/// `Err` with the current state otherwise.
+type Check = Result<String, String>;
+
+pub struct WaitForTool {
+    client: Arc<dyn SandboxClient>,
+    http: reqwest::Client,
+    max_timeout_secs: u64,
+}
+
+impl WaitForTool {
+    pub fn new(client: Arc<dyn SandboxClient>) -> Self {
+        Self {
+            client,
+            http: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+            max_timeout_secs: SandboxLimitsConfig::default().max_command_timeout_secs,
+        }
+    }
+
+    /// Cap waits at `[zerobuild.sandbox] max_command_timeout_secs`.
+    #[must_use]
+    pub fn with_limits(mut self, limits: &SandboxLimitsConfig) -> Self {
+        self.max_timeout_secs = limits.max_command_timeout_secs;
+        self
+    }
+
+    /// Run `command` at the sandbox root.
+    async fn shell(&self, command: &str) -> anyhow::Result<crate::sandbox::CommandOutput> {
+        self.client.run_command(command, ".", 15_000).await
+    }
+
+    async fn check(&self, condition: &Condition) -> anyhow::Result<Check> {
+        Ok(match condition {
+            Condition::Port(port) => {
+                let ports = self.client.listening_ports().await?;
+                match ports.iter().find(|p| p.port == *port) {
+                    Some(bound) => Ok(format!("Port {port} is listening on {}", bound.address)),
+                    None if ports.is_empty() => Err("No port is listening.".into()),
+                    None => {
+                        let mut listening: Vec<String> =
+                            ports.iter().map(|p| p.port.to_string()).collect();
+                        listening.dedup();
+                        Err(format!("Listening instead: {}.", listening.join(", ")))
+                    }
+                }
+            }
+            Condition::File(file) => {
+                let path = quote(&sandbox_path(".", file)?);
+                if self.shell(&format!("test -e {path}")).await?.exit_code == 0 {
+                    Ok(format!("'{file}' exists"))
+                } else {
+                    Err(format!("'{file}' does not exist."))
+                }
+            }
+            Condition::Url(url) => match self.http.get(url).send().await {
+                Ok(response) if response.status().is_success() => {
+                    Ok(format!("{url} answered {}", response.status().as_u16()))
+                }
+                Ok(response) => Err(format!("Last answer: {}.", response.status())),
+                Err(e) => Err(format!("Last request failed: {e}.")),
+            },
+            Condition::Log { file, pattern } => {
+                let path = quote(&sandbox_path(".", file)?);
+                let output = self.shell(&format!("tail -n {LOG_LINES} {path}")).await?;
+                if output.exit_code != 0 {
+                    Err(format!("'{file}' does not exist."))
+                } else if let Some(line) = output.stdout.lines().find(|l| pattern.is_match(l)) {
+                    Ok(format!(
+                        "'{file}' has a matching line: {}",
+                        truncate_with_ellipsis(line.trim(), 300)
+                    ))
+                } else {
+                    let lines: Vec<&str> = output.stdout.lines().collect();
+                    let tail = &lines[lines.len().saturating_sub(LOG_TAIL_LINES)..];
+                    Err(if tail.is_empty() {
+                        format!("'{file}' is empty.")
+                    } else {
+                        format!("Last lines of '{file}':\n{}", tail.join("\n"))
+                    })
+                }
+            }
+        })
+    }
+
+    async fn run(&self, args: &serde_json::Value) -> anyhow::Result<Check> {
+        let condition = Condition::parse(args)?;
+        // URLs may point anywhere; the other conditions look into the sandbox.
+        if !matches!(condition, Condition::Url(_)) {
+            self.client.require_id().map_err(|e| anyhow::anyhow!(e))?;
+        }
+        let timeout_secs = args["timeout_secs"]
+            .as_u64()
+            .unwrap_or(DEFAULT_TIMEOUT_SECS)
+            .clamp(1, self.max_timeout_secs.max(1));
+        let timeout = Duration::from_secs(timeout_secs);
+
+        let start = Instant::now();
+        loop {
+            let state = match self.check(&condition).await? {
+                Ok(seen) => {
+                    return Ok(Ok(format!(
+                        "{seen} (after {:.1}s).",
+                        start.elapsed().as_secs_f64()
+                    )))
+                }
+                Err(state) => state,
+            };
+            let elapsed = start.elapsed();
+            if elapsed >= timeout {
+                return Ok(Err(format!(
+                    "Timed out after {timeout_secs}s waiting for {}. {state}",
+                    condition.describe()
+                )));
+            }
+            tokio::time::sleep(POLL.min(timeout.saturating_sub(elapsed))).await;
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for WaitForTool {
+    fn name(&self) -> &str {
+        TOOL_NAME
+    }
+
+    fn description(&self) -> &str {
+        "Wait until something is ready instead of polling with sleep: a port listens in the \
+         sandbox, a file exists, a URL answers with a 2xx status, or a line of a log file matches \
+         a regex (e.g. the log a dev server started in the background writes). Pass exactly one \
+         of port, file, url, or log_file with pattern. Returns as soon as the condition holds, \
+         with the time it took; on timeout, says what was seen last."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "port": {
+                    "type": "integer",
+                    "description": "Wait until a server listens on this port in the sandbox, e.g. 3000."
+                },
+                "file": {
+                    "type": "string",
+                    "description": "Wait until this path exists, relative to the sandbox root, e.g. 'project/dist/index.html'."
+                },
+                "url": {
+                    "type": "string",
+                    "description": "Wait until a GET of this URL answers 2xx, e.g. the URL from sandbox_get_preview_url plus '/api/health'."
+                },
+                "log_file": {
+                    "type": "string",
+                    "description": "Log file relative to the sandbox root to search with pattern, e.g. 'project/dev.log'."
+                },
+                "pattern": {
+                    "type": "string",
+                    "description": "Regex a line of log_file must match, e.g. 'ready|compiled|listening'."
+                },
+                "timeout_secs": {
+                    "type": "integer",
+                    "description": "Longest wait in seconds. Default: 60."
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let (success, output, error) = match self.run(&args).await {
+            Ok(Ok(output)) => (true, output, None),
+            Ok(Err(timed_out)) => (false, String::new(), Some(timed_out)),
+            Err(e) => (false, String::new(), Some(e.to_string())),
+        };
+        Ok(ToolResult {
+            success,
+            output,
+            error,
+            error_code: None,
+            error_hint: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sandbox::mock::MockSandboxClient;
+
+    fn client() -> Arc<MockSandboxClient> {
+        let client = Arc::new(MockSandboxClient::new());
+        client.set_id("sb-1".into());
+        client
+    }
+
+    #[tokio::test]
+    async fn waits_for_a_log_line_and_files() {
+        let client = client();
+        client.on_command(
+            "tail -n 500 './project/dev.log'",
+            "> next dev\n  ▲ Next.js 14.2.3\n ✓ Ready in 2.1s\n",
+            "",
+            0,
+        );
+        client.on_command("test -e './project/dist'", "", "", 1);
+        let tool = WaitForTool::new(client);
+
+        let ready = tool
+            .execute(json!({"log_file": "project/dev.log", "pattern": "Ready in"}))
+            .await
+            .unwrap();
+        assert!(ready.success, "{:?}", ready.error);
+        assert!(ready
+            .output
+            .starts_with("'project/dev.log' has a matching line: ✓ Ready in 2.1s (after "));
+
+        let missing = tool
+            .execute(json!({"file": "project/dist", "timeout_secs": 1}))
+            .await
+            .unwrap();
+        assert!(!missing.success);
+        assert_eq!(
+            missing.error.as_deref(),
+            Some("Timed out after 1s waiting for 'project/dist' to exist. 'project/dist' does not exist.")
+        );
+    }
+
+    #[tokio::test]
+    async fn waits_for_a_url_and_rejects_bad_arguments() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let tool = WaitForTool::new(Arc::new(MockSandboxClient::new()));
+        let app = axum::Router::new().route("/", axum::routing::get(|| async { "ok" }));
+        let server = tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        let up = tool
+            .execute(json!({"url": url, "timeout_secs": 10}))
+            .await
+            .unwrap();
+        assert!(up.success, "{:?}", up.error);
+        assert!(up.output.contains("answered 200 (after "));
+        server.abort();
+
+        for args in [
+            json!({}),
+            json!({"port": 3000, "file": "a"}),
+            json!({"log_file": "dev.log"}),
+            json!({"log_file": "dev.log", "pattern": "("}),
+        ] {
+            assert!(!tool.execute(args).await.unwrap().success);
+        }
+        let no_sandbox = tool.execute(json!({"port": 3000})).await.unwrap();
+        assert!(!no_sandbox.success);
+    }
+}
@@ -130,6 +130,7 @@ impl Tool for ScheduleTool {
                 error: Some(format!(
                     "Unknown action '{other}'. Use create/add/once/list/get/cancel/remove/pause/resume."
                 )),
+                error_code: None,
                 error_hint: None,
             }),
         }
@@ -145,6 +146,7 @@ impl ScheduleTool {
                 error: Some(format!(
                     "cron is disabled by config (cron.enabled=false); cannot perform '{action}'"
                 )),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -156,6 +158,7 @@ impl ScheduleTool {
                 error: Some(format!(
                     "Security policy: read-only mode, cannot perform '{action}'"
                 )),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -165,6 +168,7 @@ impl ScheduleTool {
                 success: false,
                 output: String::new(),
                 error: Some("Rate limit exceeded: action budget exhausted".to_string()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -179,6 +183,7 @@ impl ScheduleTool {
                 success: true,
                 output: "No scheduled jobs.".to_string(),
                 error: None,
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -213,6 +218,7 @@ impl ScheduleTool {
             success: true,
             output: format!("Scheduled jobs ({}):\n{}", lines.len(), lines.join("\n")),
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
@@ -234,6 +240,7 @@ impl ScheduleTool {
                     success: true,
                     output: serde_json::to_string_pretty(&detail)?,
                     error: None,
+                    error_code: None,
                     error_hint: None,
                 })
             }
@@ -241,6 +248,7 @@ impl ScheduleTool {
                 success: false,
                 output: String::new(),
                 error: Some(format!("Job '{id}' not found")),
+                error_code: None,
                 error_hint: None,
             }),
         }
@@ -263,6 +271,7 @@ impl ScheduleTool {
                 success: false,
                 output: String::new(),
                 error: Some(reason),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -278,6 +287,7 @@ impl ScheduleTool {
                         success: false,
                         output: String::new(),
                         error: Some("'add' requires 'expression' and forbids delay/run_at".into()),
+                        error_code: None,
                         error_hint: None,
                     });
                 }
@@ -288,6 +298,7 @@ impl ScheduleTool {
                         success: false,
                         output: String::new(),
                         error: Some("'once' requires exactly one of 'delay' or 'run_at'".into()),
+                        error_code: None,
                         error_hint: None,
                     });
                 }
@@ -296,6 +307,7 @@ impl ScheduleTool {
                         success: false,
                         output: String::new(),
                         error: Some("'once' supports either delay or run_at, not both".into()),
+                        error_code: None,
                         error_hint: None,
                     });
                 }
@@ -313,6 +325,7 @@ impl ScheduleTool {
                             "Exactly one of 'expression', 'delay', or 'run_at' must be provided"
                                 .into(),
                         ),
+                        error_code: None,
                         error_hint: None,
                     });
                 }
@@ -331,6 +344,7 @@ impl ScheduleTool {
                     job.command
                 ),
                 error: None,
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -346,6 +360,7 @@ impl ScheduleTool {
                     job.command
                 ),
                 error: None,
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -365,6 +380,7 @@ impl ScheduleTool {
                 job.command
             ),
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
@@ -375,12 +391,14 @@ impl ScheduleTool {
                 success: true,
                 output: format!("Cancelled job {id}"),
                 error: None,
+                error_code: None,
                 error_hint: None,
             },
             Err(error) => ToolResult {
                 success: false,
                 output: String::new(),
                 error: Some(error.to_string()),
+                error_code: None,
                 error_hint: None,
             },
         }
@@ -402,12 +420,14 @@ impl ScheduleTool {
                     format!("Resumed job {id}")
                 },
                 error: None,
+                error_code: None,
                 error_hint: None,
             },
             Err(error) => ToolResult {
                 success: false,
                 output: String::new(),
                 error: Some(error.to_string()),
+                error_code: None,
                 error_hint: None,
             },
         }
@@ -1,16 +1,14 @@
 use super::traits::{Tool, ToolResult};
+use crate::artifacts::{ArtifactKind, Artifacts};
 use crate::security::SecurityPolicy;
 use async_trait::async_trait;
 use serde_json::json;
-use std::fmt::Write;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 
 /// Maximum time to wait for a screenshot command to complete.
 const SCREENSHOT_TIMEOUT_SECS: u64 = 15;
-/// Maximum base64 payload size to return (2 MB of base64 ≈ 1.5 MB image).
-const MAX_BASE64_BYTES: usize = 2_097_152;
 
 /// Tool for capturing screenshots using platform-native commands.
 ///
@@ -18,11 +16,38 @@ const MAX_BASE64_BYTES: usize = 2_097_152;
 /// Linux: tries `gnome-screenshot`, `scrot`, `import` (`ImageMagick`) in order.
 pub struct ScreenshotTool {
     security: Arc<SecurityPolicy>,
+    artifacts: Option<Arc<Artifacts>>,
 }
 
 impl ScreenshotTool {
     pub fn new(security: Arc<SecurityPolicy>) -> Self {
-        Self { security }
+        Self {
+            security,
+            artifacts: None,
+        }
+    }
+
+    /// Keep a copy of every screenshot in `artifacts`.
+    pub fn with_artifacts(mut self, artifacts: Arc<Artifacts>) -> Self {
+        self.artifacts = Some(artifacts);
+        self
+    }
+
+    /// Copy the screenshot at `path` to the artifact store, if any.
+    async fn keep_artifact(&self, path: &std::path::Path, name: &str) {
+        let Some(artifacts) = &self.artifacts else {
+            return;
+        };
+        let saved = match tokio::fs::read(path).await {
+            Ok(bytes) => artifacts
+                .save(None, ArtifactKind::Screenshot, name, bytes)
+                .await
+                .map(|_| ()),
+            Err(e) => Err(e.into()),
+        };
+        if let Err(e) = saved {
+            tracing::warn!("Failed to store screenshot artifact: {e:#}");
+        }
     }
 
     /// Determine the screenshot command for the current platform.
@@ -77,6 +102,7 @@ impl ScreenshotTool {
                 success: false,
                 output: String::new(),
                 error: Some("Filename contains characters unsafe for shell execution".into()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -89,6 +115,7 @@ impl ScreenshotTool {
                 success: false,
                 output: String::new(),
                 error: Some("Screenshot not supported on this platform".into()),
+                error_code: None,
                 error_hint: None,
             });
         };
@@ -125,6 +152,7 @@ impl ScreenshotTool {
                                 "No screenshot tool found. Install gnome-screenshot, scrot, or ImageMagick."
                                     .into(),
                             ),
+                            error_code: None,
                             error_hint: None,
                         });
                     }
@@ -132,16 +160,22 @@ impl ScreenshotTool {
                         success: false,
                         output: String::new(),
                         error: Some(format!("Screenshot command failed: {stderr}")),
+                        error_code: None,
                         error_hint: None,
                     });
                 }
 
-                Self::read_and_encode(&output_path).await
+                let result = Self::describe_capture(&output_path).await?;
+                if result.success {
+                    self.keep_artifact(&output_path, &safe_name).await;
+                }
+                Ok(result)
             }
             Ok(Err(e)) => Ok(ToolResult {
                 success: false,
                 output: String::new(),
                 error: Some(format!("Failed to execute screenshot command: {e}")),
+                error_code: None,
                 error_hint: None,
             }),
             Err(_) => Ok(ToolResult {
@@ -150,70 +184,35 @@ impl ScreenshotTool {
                 error: Some(format!(
                     "Screenshot timed out after {SCREENSHOT_TIMEOUT_SECS}s"
                 )),
+                error_code: None,
                 error_hint: None,
             }),
         }
     }
 
-    /// Read the screenshot file and return base64-encoded result.
-    async fn read_and_encode(output_path: &std::path::Path) -> anyhow::Result<ToolResult> {
-        // Check file size before reading to prevent OOM on large screenshots
-        const MAX_RAW_BYTES: u64 = 1_572_864; // ~1.5 MB (base64 expands ~33%)
-        if let Ok(meta) = tokio::fs::metadata(output_path).await {
-            if meta.len() > MAX_RAW_BYTES {
-                return Ok(ToolResult {
-                    success: true,
-                    output: format!(
-                        "Screenshot saved to: {}\nSize: {} bytes (too large to base64-encode inline)",
-                        output_path.display(),
-                        meta.len(),
-                    ),
-                    error: None,
-                    error_hint: None,
-                });
-            }
-        }
-
-        match tokio::fs::read(output_path).await {
-            Ok(bytes) => {
-                use base64::Engine;
-                let size = bytes.len();
-                let mut encoded = base64::engine::general_purpose::STANDARD.encode(&bytes);
-                let truncated = if encoded.len() > MAX_BASE64_BYTES {
-                    encoded.truncate(encoded.floor_char_boundary(MAX_BASE64_BYTES));
-                    true
-                } else {
-                    false
-                };
-
-                let mut output_msg = format!(
-                    "Screenshot saved to: {}\nSize: {size} bytes\nBase64 length: {}",
-                    output_path.display(),
-                    encoded.len(),
-                );
-                if truncated {
-                    output_msg.push_str(" (truncated)");
-                }
-                let mime = match output_path.extension().and_then(|e| e.to_str()) {
-                    Some("jpg" | "jpeg") => "image/jpeg",
-                    Some("bmp") => "image/bmp",
-                    Some("gif") => "image/gif",
-                    Some("webp") => "image/webp",
-                    _ => "image/png",
-                };
-                let _ = write!(output_msg, "\ndata:{mime};base64,{encoded}");
-
-                Ok(ToolResult {
-                    success: true,
-                    output: output_msg,
-                    error: None,
-                    error_hint: None,
-                })
-            }
+    /// Report the saved screenshot with an `[IMAGE:]` marker.
+    ///
+    /// The image itself is not inlined: the agent loop attaches marked images
+    /// to the conversation for vision-capable providers, downscaled to the
+    /// `[multimodal]` budget.
+    async fn describe_capture(output_path: &std::path::Path) -> anyhow::Result<ToolResult> {
+        match tokio::fs::metadata(output_path).await {
+            Ok(meta) => Ok(ToolResult {
+                success: true,
+                output: format!(
+                    "Screenshot saved to: {path}\nSize: {size} bytes\n[IMAGE:{path}]",
+                    path = output_path.display(),
+                    size = meta.len(),
+                ),
+                error: None,
+                error_code: None,
+                error_hint: None,
+            }),
             Err(e) => Ok(ToolResult {
                 success: false,
                 output: format!("Screenshot saved to: {}", output_path.display()),
                 error: Some(format!("Failed to read screenshot file: {e}")),
+                error_code: None,
                 error_hint: None,
             }),
         }
@@ -227,7 +226,7 @@ impl Tool for ScreenshotTool {
     }
 
     fn description(&self) -> &str {
-        "Capture a screenshot of the current screen. Returns the file path and base64-encoded PNG data."
+        "Capture a screenshot of the current screen. Returns the file path and an image marker; vision-capable models see the image itself."
     }
 
     fn parameters_schema(&self) -> serde_json::Value {
@@ -252,6 +251,7 @@ impl Tool for ScreenshotTool {
                 success: false,
                 output: String::new(),
                 error: Some("Action blocked: autonomy is read-only".into()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -330,4 +330,18 @@ mod tests {
             "Command should contain the output path"
         );
     }
+
+    #[tokio::test]
+    async fn screenshot_output_marks_image_instead_of_inlining_it() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("shot.png");
+        std::fs::write(&path, [0x89, b'P', b'N', b'G']).unwrap();
+
+        let result = ScreenshotTool::describe_capture(&path).await.unwrap();
+        assert!(result.success);
+        assert!(result
+            .output
+            .contains(&format!("[IMAGE:{}]", path.display())));
+        assert!(!result.output.contains("base64"));
+    }
 }
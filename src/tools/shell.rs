@@ -185,6 +185,7 @@ impl Tool for ShellTool {
                         Make sure to call sandbox_create first if no sandbox is active.",
                         command, command
                     )),
+                    error_code: None,
                     error_hint: Some("Use sandbox_run_command, not shell".to_string()),
                 });
             }
@@ -195,6 +196,7 @@ impl Tool for ShellTool {
                 success: false,
                 output: String::new(),
                 error: Some("Rate limit exceeded: too many actions in the last hour".into()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -206,6 +208,7 @@ impl Tool for ShellTool {
                     success: false,
                     output: String::new(),
                     error: Some(reason),
+                    error_code: None,
                     error_hint: None,
                 });
             }
@@ -216,6 +219,7 @@ impl Tool for ShellTool {
                 success: false,
                 output: String::new(),
                 error: Some(format!("Path blocked by security policy: {path}")),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -225,6 +229,7 @@ impl Tool for ShellTool {
                 success: false,
                 output: String::new(),
                 error: Some("Rate limit exceeded: action budget exhausted".into()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -242,6 +247,7 @@ impl Tool for ShellTool {
                     success: false,
                     output: String::new(),
                     error: Some(format!("Failed to build runtime command: {e}")),
+                    error_code: None,
                     error_hint: None,
                 });
             }
@@ -280,6 +286,7 @@ impl Tool for ShellTool {
                     } else {
                         Some(stderr)
                     },
+                    error_code: None,
                     error_hint: None,
                 })
             }
@@ -287,6 +294,7 @@ impl Tool for ShellTool {
                 success: false,
                 output: String::new(),
                 error: Some(format!("Failed to execute command: {e}")),
+                error_code: None,
                 error_hint: None,
             }),
             Err(_) => Ok(ToolResult {
@@ -295,6 +303,7 @@ impl Tool for ShellTool {
                 error: Some(format!(
                     "Command timed out after {SHELL_TIMEOUT_SECS}s and was killed"
                 )),
+                error_code: None,
                 error_hint: None,
             }),
         }
@@ -0,0 +1,192 @@
+use super::traits::{Tool, ToolResult};
+use crate::memory::Memory;
+use crate::security::policy::ToolOperation;
+use crate::security::SecurityPolicy;
+use crate::skills::learned::{save_learned_skill, LearnedSkill};
+use async_trait::async_trait;
+use serde_json::json;
+use std::sync::Arc;
+
+/// Let the agent record a procedure that worked as a reusable learned skill
+pub struct SkillSaveTool {
+    memory: Arc<dyn Memory>,
+    security: Arc<SecurityPolicy>,
+}
+
+impl SkillSaveTool {
+    pub fn new(memory: Arc<dyn Memory>, security: Arc<SecurityPolicy>) -> Self {
+        Self { memory, security }
+    }
+}
+
+fn string_array(args: &serde_json::Value, field: &str) -> Vec<String> {
+    args.get(field)
+        .and_then(serde_json::Value::as_array)
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(serde_json::Value::as_str)
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[async_trait]
+impl Tool for SkillSaveTool {
+    fn name(&self) -> &str {
+        "skill_save"
+    }
+
+    fn description(&self) -> &str {
+        "Save a multi-step procedure that just succeeded as a reusable learned skill (e.g. 'set up Next.js + Tailwind + shadcn'). Use {placeholders} in steps for values that change between projects and list them in 'parameters'. Saved skills are recalled automatically for similar future requests. Saving a skill with an existing name replaces it."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "name": {
+                    "type": "string",
+                    "description": "Short skill name (e.g. 'Next.js + Tailwind + shadcn setup')"
+                },
+                "description": {
+                    "type": "string",
+                    "description": "When this skill applies"
+                },
+                "steps": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Ordered steps, e.g. 'sandbox_run_command: npx create-next-app@latest {project_name} --yes'"
+                },
+                "parameters": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Parameter names used as {name} placeholders in steps"
+                },
+                "tags": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Keywords that help recall (e.g. 'nextjs', 'tailwind')"
+                }
+            },
+            "required": ["name", "description", "steps"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let name = args
+            .get("name")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'name' parameter"))?;
+
+        let description = args
+            .get("description")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'description' parameter"))?;
+
+        if let Err(error) = self
+            .security
+            .enforce_tool_operation(ToolOperation::Act, "skill_save")
+        {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(error),
+                error_code: None,
+                error_hint: None,
+            });
+        }
+
+        let skill = LearnedSkill {
+            name: name.trim().to_string(),
+            description: description.trim().to_string(),
+            parameters: string_array(&args, "parameters"),
+            steps: string_array(&args, "steps"),
+            tags: string_array(&args, "tags"),
+        };
+
+        match save_learned_skill(self.memory.as_ref(), &skill).await {
+            Ok(key) => Ok(ToolResult {
+                success: true,
+                output: format!(
+                    "Saved learned skill '{}' ({} steps) as {key}",
+                    skill.name,
+                    skill.steps.len()
+                ),
+                error: None,
+                error_code: None,
+                error_hint: None,
+            }),
+            Err(e) => Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(format!("Failed to save skill: {e}")),
+                error_code: None,
+                error_hint: None,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::SqliteMemory;
+    use crate::security::{AutonomyLevel, SecurityPolicy};
+    use tempfile::TempDir;
+
+    fn test_mem() -> (TempDir, Arc<dyn Memory>) {
+        let tmp = TempDir::new().unwrap();
+        let mem = SqliteMemory::new(tmp.path()).unwrap();
+        (tmp, Arc::new(mem))
+    }
+
+    #[tokio::test]
+    async fn saves_skill_into_memory() {
+        let (_tmp, mem) = test_mem();
+        let tool = SkillSaveTool::new(mem.clone(), Arc::new(SecurityPolicy::default()));
+        let result = tool
+            .execute(json!({
+                "name": "Vite React setup",
+                "description": "Scaffold a Vite React app",
+                "steps": ["sandbox_run_command: npm create vite@latest {name} -- --template react"],
+                "parameters": ["name"]
+            }))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+
+        let entry = mem.get("skill:vite-react-setup").await.unwrap().unwrap();
+        let skill = LearnedSkill::from_memory_entry(&entry).unwrap();
+        assert_eq!(skill.parameters, vec!["name".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn rejects_skill_without_steps() {
+        let (_tmp, mem) = test_mem();
+        let tool = SkillSaveTool::new(mem, Arc::new(SecurityPolicy::default()));
+        let result = tool
+            .execute(json!({"name": "Empty", "description": "nothing", "steps": []}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("at least one step"));
+    }
+
+    #[tokio::test]
+    async fn blocked_in_readonly_mode() {
+        let (_tmp, mem) = test_mem();
+        let readonly = Arc::new(SecurityPolicy {
+            autonomy: AutonomyLevel::ReadOnly,
+            ..SecurityPolicy::default()
+        });
+        let tool = SkillSaveTool::new(mem, readonly);
+        let result = tool
+            .execute(json!({"name": "x", "description": "y", "steps": ["z"]}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+    }
+}
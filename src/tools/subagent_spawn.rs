@@ -520,6 +520,8 @@ mod tests {
                 system_prompt: Some("You are a research assistant.".to_string()),
                 api_key: None,
                 temperature: Some(0.3),
+                top_p: None,
+                seed: None,
                 max_depth: 3,
                 agentic: false,
                 allowed_tools: Vec::new(),
@@ -0,0 +1,95 @@
+//! `switch_phase` tool — move the session to another phase of
+//! `[agent.phases]` so the tool schemas of that phase are sent (see
+//! [`crate::agent::phases`]).
+
+use super::traits::{Tool, ToolResult};
+use crate::agent::phases::{self, SWITCH_PHASE_TOOL};
+use crate::config::{AgentPhasesConfig, SessionPhase};
+use async_trait::async_trait;
+use serde_json::json;
+
+pub struct SwitchPhaseTool {
+    config: AgentPhasesConfig,
+}
+
+impl SwitchPhaseTool {
+    pub fn new(config: &AgentPhasesConfig) -> Self {
+        Self {
+            config: config.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for SwitchPhaseTool {
+    fn name(&self) -> &str {
+        SWITCH_PHASE_TOOL
+    }
+
+    fn description(&self) -> &str {
+        "Switch the session phase to change which tools you have. 'planning' has thinking and research tools, 'build' has the sandbox and file tools, 'delivery' has the GitHub and deploy tools. Switch when the work moves on, e.g. to 'build' once the plan is agreed and to 'delivery' when the project is ready to push or deploy. The new tools are available from your next step."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "phase": {
+                    "type": "string",
+                    "enum": SessionPhase::ALL.map(SessionPhase::as_str),
+                    "description": "Phase to switch to"
+                },
+                "reason": {
+                    "type": "string",
+                    "description": "Why the work moves to this phase"
+                }
+            },
+            "required": ["phase"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let raw = args.get("phase").and_then(|v| v.as_str()).unwrap_or("");
+        let Some(phase) = SessionPhase::parse(raw) else {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(format!(
+                    "Unknown phase '{raw}'; use planning, build or delivery"
+                )),
+                error_code: None,
+                error_hint: None,
+            });
+        };
+        let Some(policy) = phases::policy() else {
+            return Ok(ToolResult {
+                success: true,
+                output: "Phases are disabled; every tool is already available.".into(),
+                error: None,
+                error_code: None,
+                error_hint: None,
+            });
+        };
+        let previous = phases::switch(&policy, phase);
+        if let Some(reason) = args.get("reason").and_then(|v| v.as_str()) {
+            tracing::info!(
+                from = previous.as_str(),
+                to = phase.as_str(),
+                reason,
+                "Session phase switched"
+            );
+        }
+        Ok(ToolResult {
+            success: true,
+            output: format!(
+                "Switched from the {} phase to the {} phase. Tools of this phase: {}.",
+                previous.as_str(),
+                phase.as_str(),
+                self.config.tools(phase).join(", ")
+            ),
+            error: None,
+            error_code: None,
+            error_hint: None,
+        })
+    }
+}
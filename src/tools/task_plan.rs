@@ -138,6 +138,7 @@ impl TaskPlanTool {
                 success: false,
                 output: String::new(),
                 error: Some(msg),
+                error_code: None,
                 error_hint: None,
             })
     }
@@ -152,6 +153,7 @@ impl TaskPlanTool {
                     error: Some(
                         "Parameter 'tasks' must be a non-empty array of {title, status?}".into(),
                     ),
+                    error_code: None,
                     error_hint: None,
                 };
             }
@@ -167,6 +169,7 @@ impl TaskPlanTool {
                         success: false,
                         output: String::new(),
                         error: Some("Each task must have a non-empty 'title' string".into()),
+                        error_code: None,
                         error_hint: None,
                     };
                 }
@@ -188,6 +191,7 @@ impl TaskPlanTool {
             success: true,
             output: format!("Created {count} task(s)."),
             error: None,
+            error_code: None,
             error_hint: None,
         }
     }
@@ -198,6 +202,7 @@ impl TaskPlanTool {
                 success: false,
                 output: String::new(),
                 error: Some("Parameter 'title' must be a non-empty string".into()),
+                error_code: None,
                 error_hint: None,
             };
         }
@@ -216,6 +221,7 @@ impl TaskPlanTool {
             success: true,
             output: format!("Added task [{id}] \"{title}\"."),
             error: None,
+            error_code: None,
             error_hint: None,
         }
     }
@@ -230,6 +236,7 @@ impl TaskPlanTool {
                     error: Some(format!(
                         "Invalid status '{status_str}'. Must be: pending, in_progress, completed"
                     )),
+                    error_code: None,
                     error_hint: None,
                 };
             }
@@ -243,6 +250,7 @@ impl TaskPlanTool {
                     success: true,
                     output: format!("Task [{id}] updated to {status}."),
                     error: None,
+                    error_code: None,
                     error_hint: None,
                 }
             }
@@ -250,6 +258,7 @@ impl TaskPlanTool {
                 success: false,
                 output: String::new(),
                 error: Some(format!("Task with id {id} not found")),
+                error_code: None,
                 error_hint: None,
             },
         }
@@ -262,6 +271,7 @@ impl TaskPlanTool {
                 success: true,
                 output: "No tasks.".into(),
                 error: None,
+                error_code: None,
                 error_hint: None,
             };
         }
@@ -281,6 +291,7 @@ impl TaskPlanTool {
             success: true,
             output: lines.join("\n"),
             error: None,
+            error_code: None,
             error_hint: None,
         }
     }
@@ -293,6 +304,7 @@ impl TaskPlanTool {
             success: true,
             output: "Task list cleared.".into(),
             error: None,
+            error_code: None,
             error_hint: None,
         }
     }
@@ -333,6 +345,7 @@ impl TaskPlanTool {
                 success: false,
                 output: String::new(),
                 error: Some("Plan must have at least one step".into()),
+                error_code: None,
                 error_hint: None,
             };
         }
@@ -352,6 +365,7 @@ impl TaskPlanTool {
             success: true,
             output: formatted,
             error: None,
+            error_code: None,
             error_hint: None,
         }
     }
@@ -366,6 +380,7 @@ impl TaskPlanTool {
                         success: true,
                         output: "Plan was already approved. You can proceed with the build.".into(),
                         error: None,
+                        error_code: None,
                         error_hint: None,
                     };
                 }
@@ -377,6 +392,7 @@ impl TaskPlanTool {
                         error: Some(
                             "This plan was previously rejected. Please propose a new plan.".into(),
                         ),
+                        error_code: None,
                         error_hint: None,
                     };
                 }
@@ -403,6 +419,7 @@ impl TaskPlanTool {
                         plan.steps.len()
                     ),
                     error: None,
+                    error_code: None,
                     error_hint: None,
                 }
             }
@@ -410,6 +427,7 @@ impl TaskPlanTool {
                 success: false,
                 output: String::new(),
                 error: Some("No plan has been proposed. Use 'propose' action first.".into()),
+                error_code: None,
                 error_hint: None,
             },
         }
@@ -426,6 +444,7 @@ impl TaskPlanTool {
                     success: true,
                     output: "Plan rejected. Let me know what you'd like to change and I'll create a new plan.".into(),
                     error: None,
+                    error_code: None,
                     error_hint: None,
                 }
             }
@@ -433,6 +452,7 @@ impl TaskPlanTool {
                 success: false,
                 output: String::new(),
                 error: Some("No plan has been proposed. Use 'propose' action first.".into()),
+                error_code: None,
                 error_hint: None,
             },
         }
@@ -570,6 +590,7 @@ impl Tool for TaskPlanTool {
                         success: false,
                         output: String::new(),
                         error: Some("Parameter 'id' is required for update".into()),
+                        error_code: None,
                         error_hint: None,
                     });
                 }
@@ -578,6 +599,7 @@ impl Tool for TaskPlanTool {
                         success: false,
                         output: String::new(),
                         error: Some("Parameter 'status' is required for update".into()),
+                        error_code: None,
                         error_hint: None,
                     });
                 }
@@ -615,6 +637,7 @@ impl Tool for TaskPlanTool {
                 error: Some(format!(
                     "Unknown action '{other}'. Valid: create, add, update, list, delete, propose, confirm, reject"
                 )),
+                error_code: None,
                 error_hint: None,
             }),
         }
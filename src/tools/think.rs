@@ -50,13 +50,9 @@ impl Tool for ThinkTool {
     }
 
     async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
-        let thoughts = args["thoughts"]
-            .as_str()
-            .unwrap_or("No thoughts provided");
-        
-        let reasoning_type = args["reasoning_type"]
-            .as_str()
-            .unwrap_or("analysis");
+        let thoughts = args["thoughts"].as_str().unwrap_or("No thoughts provided");
+
+        let reasoning_type = args["reasoning_type"].as_str().unwrap_or("analysis");
 
         let output = format!(
             "[{}] Thinking through the problem:\n\n{}",
@@ -68,6 +64,8 @@ impl Tool for ThinkTool {
             success: true,
             output,
             error: None,
+            error_code: None,
+            error_hint: None,
         })
     }
 }
@@ -1,3 +1,4 @@
+use crate::error::{ErrorCode, ZerobuildError};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
@@ -7,11 +8,26 @@ pub struct ToolResult {
     pub success: bool,
     pub output: String,
     pub error: Option<String>,
+    /// Machine-readable category of the failure, when known
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub error_code: Option<ErrorCode>,
     /// Optional hint for error recovery, providing suggested remediation
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub error_hint: Option<String>,
 }
 
+impl From<ZerobuildError> for ToolResult {
+    fn from(err: ZerobuildError) -> Self {
+        Self {
+            success: false,
+            output: String::new(),
+            error: Some(err.to_string()),
+            error_code: Some(err.code()),
+            error_hint: None,
+        }
+    }
+}
+
 /// Description of a tool for the LLM
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolSpec {
@@ -79,6 +95,7 @@ mod tests {
                     .unwrap_or_default()
                     .to_string(),
                 error: None,
+                error_code: None,
                 error_hint: None,
             })
         }
@@ -114,6 +131,7 @@ mod tests {
             success: false,
             output: String::new(),
             error: Some("boom".into()),
+            error_code: None,
             error_hint: Some("Try checking your dependencies".into()),
         };
 
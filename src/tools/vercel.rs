@@ -0,0 +1,209 @@
+//! Vercel deployments over the REST API.
+//!
+//! [`deploy`] uploads the project files inline with the deployment request
+//! and waits until Vercel has built it. Files the project's ignore rules
+//! exclude and files that look like secrets are left out, as for
+//! `github_push` (see [`super::gitignore`]).
+
+use super::gitignore;
+use crate::config::VercelConfig;
+use anyhow::Context;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::Duration;
+
+const VERCEL_API_BASE: &str = "https://api.vercel.com";
+
+/// Delay between deployment status checks.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Status checks before a deployment that is still building is given up on.
+const MAX_POLLS: usize = 180;
+
+/// A finished production deployment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deployment {
+    pub id: String,
+    /// Production URL: the project's first alias, else the deployment's own.
+    pub url: String,
+    /// Dashboard page with the build logs.
+    pub inspector_url: Option<String>,
+    /// Files uploaded.
+    pub files: usize,
+}
+
+/// Query parameter selecting the configured team, if any.
+fn team_param(config: &VercelConfig) -> Option<String> {
+    match config.team.trim() {
+        "" => None,
+        team if team.starts_with("team_") => Some(format!("teamId={team}")),
+        team => Some(format!("slug={team}")),
+    }
+}
+
+/// Body of the deployment request for project `name`.
+fn request_body(name: &str, files: &BTreeMap<String, String>) -> Value {
+    let files: Vec<Value> = files
+        .iter()
+        .map(|(path, content)| json!({"file": path, "data": content, "encoding": "utf-8"}))
+        .collect();
+    json!({
+        "name": name,
+        "target": "production",
+        "files": files,
+    })
+}
+
+/// `https://` URL of a host name Vercel returns without a scheme.
+fn https(host: &str) -> String {
+    if host.starts_with("http://") || host.starts_with("https://") {
+        host.to_string()
+    } else {
+        format!("https://{host}")
+    }
+}
+
+/// The deployment described by `body` once it is ready, `None` while it
+/// still builds.
+fn ready_deployment(body: &Value, files: usize) -> anyhow::Result<Option<Deployment>> {
+    let state = body["readyState"]
+        .as_str()
+        .or_else(|| body["status"].as_str())
+        .unwrap_or_default();
+    match state {
+        "READY" => {
+            let host = body["alias"][0]
+                .as_str()
+                .or_else(|| body["url"].as_str())
+                .context("Vercel returned a ready deployment without a URL")?;
+            Ok(Some(Deployment {
+                id: body["id"].as_str().unwrap_or_default().to_string(),
+                url: https(host),
+                inspector_url: body["inspectorUrl"].as_str().map(str::to_string),
+                files,
+            }))
+        }
+        "ERROR" | "CANCELED" => anyhow::bail!(
+            "Vercel deployment {}: {}",
+            state.to_lowercase(),
+            body["errorMessage"]
+                .as_str()
+                .unwrap_or("see the build logs on Vercel")
+        ),
+        _ => Ok(None),
+    }
+}
+
+/// Deploy `files` (project paths to contents) to production as Vercel
+/// project `name`.
+pub async fn deploy(
+    config: &VercelConfig,
+    name: &str,
+    files: &BTreeMap<String, String>,
+) -> anyhow::Result<Deployment> {
+    let token = config
+        .token
+        .as_deref()
+        .filter(|token| !token.trim().is_empty())
+        .context("Vercel is not configured: set [zerobuild.vercel] token")?;
+
+    let mut files = files.clone();
+    let report = gitignore::apply(&mut files, None);
+    for (path, _) in &report.secrets {
+        files.remove(path);
+    }
+    if files.is_empty() {
+        anyhow::bail!("No files to deploy to Vercel");
+    }
+
+    let client = crate::config::build_runtime_proxy_client("tool.vercel");
+    let team = team_param(config);
+    let mut create_url =
+        format!("{VERCEL_API_BASE}/v13/deployments?skipAutoDetectionConfirmation=1");
+    let mut status_query = String::new();
+    if let Some(team) = &team {
+        let _ = write!(create_url, "&{team}");
+        let _ = write!(status_query, "?{team}");
+    }
+    let response = client
+        .post(create_url)
+        .bearer_auth(token)
+        .json(&request_body(name, &files))
+        .send()
+        .await
+        .context("Failed to reach Vercel")?;
+    let status = response.status();
+    let body: Value = response.json().await.unwrap_or_default();
+    if !status.is_success() {
+        anyhow::bail!(
+            "Vercel rejected the deployment ({status}): {}",
+            body["error"]["message"].as_str().unwrap_or("no details")
+        );
+    }
+    if let Some(deployment) = ready_deployment(&body, files.len())? {
+        return Ok(deployment);
+    }
+    let id = body["id"]
+        .as_str()
+        .context("Vercel returned a deployment without an ID")?;
+
+    for _ in 0..MAX_POLLS {
+        tokio::time::sleep(POLL_INTERVAL).await;
+        let response = client
+            .get(format!(
+                "{VERCEL_API_BASE}/v13/deployments/{id}{status_query}"
+            ))
+            .bearer_auth(token)
+            .send()
+            .await
+            .context("Failed to reach Vercel")?;
+        if !response.status().is_success() {
+            continue;
+        }
+        let body: Value = response.json().await.unwrap_or_default();
+        if let Some(deployment) = ready_deployment(&body, files.len())? {
+            return Ok(deployment);
+        }
+    }
+    anyhow::bail!(
+        "Vercel deployment {id} is still building after {} minutes",
+        POLL_INTERVAL.as_secs() * MAX_POLLS as u64 / 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_requests_and_reads_ready_deployments() {
+        let files = BTreeMap::from([("index.html".to_string(), "<h1>hi</h1>".to_string())]);
+        let body = request_body("shop", &files);
+        assert_eq!(body["target"], "production");
+        assert_eq!(body["files"][0]["file"], "index.html");
+
+        let config = VercelConfig {
+            token: None,
+            team: "acme".into(),
+        };
+        assert_eq!(team_param(&config).as_deref(), Some("slug=acme"));
+
+        let building =
+            json!({"id": "dpl_1", "readyState": "BUILDING", "url": "shop-x1.vercel.app"});
+        assert_eq!(ready_deployment(&building, 1).unwrap(), None);
+        let ready = json!({
+            "id": "dpl_1",
+            "readyState": "READY",
+            "url": "shop-x1.vercel.app",
+            "alias": ["shop.vercel.app"]
+        });
+        let deployment = ready_deployment(&ready, 1).unwrap().unwrap();
+        assert_eq!(deployment.url, "https://shop.vercel.app");
+        let failed = json!({"readyState": "ERROR", "errorMessage": "Build failed"});
+        assert_eq!(
+            ready_deployment(&failed, 1).unwrap_err().to_string(),
+            "Vercel deployment error: Build failed"
+        );
+    }
+}
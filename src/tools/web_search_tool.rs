@@ -229,6 +229,7 @@ impl Tool for WebSearchTool {
             success: true,
             output: result,
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
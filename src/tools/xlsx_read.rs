@@ -536,6 +536,7 @@ impl Tool for XlsxReadTool {
                     success: false,
                     output: String::new(),
                     error: Some(err.to_string()),
+                    error_code: None,
                     error_hint: None,
                 })
             }
@@ -546,6 +547,7 @@ impl Tool for XlsxReadTool {
                 success: false,
                 output: String::new(),
                 error: Some("Rate limit exceeded: too many actions in the last hour".into()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -555,6 +557,7 @@ impl Tool for XlsxReadTool {
                 success: false,
                 output: String::new(),
                 error: Some(format!("Path not allowed by security policy: {path}")),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -564,6 +567,7 @@ impl Tool for XlsxReadTool {
                 success: false,
                 output: String::new(),
                 error: Some("Rate limit exceeded: action budget exhausted".into()),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -577,6 +581,7 @@ impl Tool for XlsxReadTool {
                     success: false,
                     output: String::new(),
                     error: Some(format!("Failed to resolve file path: {e}")),
+                    error_code: None,
                     error_hint: None,
                 });
             }
@@ -590,6 +595,7 @@ impl Tool for XlsxReadTool {
                     self.security
                         .resolved_path_violation_message(&resolved_path),
                 ),
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -606,6 +612,7 @@ impl Tool for XlsxReadTool {
                             "XLSX too large: {} bytes (limit: {MAX_XLSX_BYTES} bytes)",
                             meta.len()
                         )),
+                        error_code: None,
                         error_hint: None,
                     });
                 }
@@ -615,6 +622,7 @@ impl Tool for XlsxReadTool {
                     success: false,
                     output: String::new(),
                     error: Some(format!("Failed to read file metadata: {e}")),
+                    error_code: None,
                     error_hint: None,
                 });
             }
@@ -627,6 +635,7 @@ impl Tool for XlsxReadTool {
                     success: false,
                     output: String::new(),
                     error: Some(format!("Failed to read XLSX file: {e}")),
+                    error_code: None,
                     error_hint: None,
                 });
             }
@@ -639,6 +648,7 @@ impl Tool for XlsxReadTool {
                     success: false,
                     output: String::new(),
                     error: Some(format!("XLSX extraction failed: {e}")),
+                    error_code: None,
                     error_hint: None,
                 });
             }
@@ -647,6 +657,7 @@ impl Tool for XlsxReadTool {
                     success: false,
                     output: String::new(),
                     error: Some(format!("XLSX extraction task panicked: {e}")),
+                    error_code: None,
                     error_hint: None,
                 });
             }
@@ -657,6 +668,7 @@ impl Tool for XlsxReadTool {
                 success: true,
                 output: "XLSX contains no extractable text".into(),
                 error: None,
+                error_code: None,
                 error_hint: None,
             });
         }
@@ -674,6 +686,7 @@ impl Tool for XlsxReadTool {
             success: true,
             output,
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
@@ -100,6 +100,7 @@ impl Tool for EchoTool {
             success: true,
             output: msg,
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
@@ -140,6 +141,7 @@ impl Tool for CountingTool {
             success: true,
             output: format!("call #{}", *c),
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
@@ -95,6 +95,7 @@ impl Tool for EchoTool {
             success: true,
             output: msg,
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
@@ -119,6 +120,7 @@ impl Tool for FailingTool {
             success: false,
             output: String::new(),
             error: Some("Service unavailable: connection timeout".into()),
+            error_code: None,
             error_hint: None,
         })
     }
@@ -159,6 +161,7 @@ impl Tool for CountingTool {
             success: true,
             output: format!("call #{}", *c),
             error: None,
+            error_code: None,
             error_hint: None,
         })
     }
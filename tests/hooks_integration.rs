@@ -88,6 +88,7 @@ async fn hook_runner_full_pipeline() {
         success: true,
         output: "ok".into(),
         error: None,
+        error_code: None,
         error_hint: None,
     };
     runner
@@ -10,6 +10,7 @@ import {
   DollarSign,
   Activity,
   Stethoscope,
+  Rocket,
 } from 'lucide-react';
 import { t } from '@/lib/i18n';
 
@@ -24,6 +25,7 @@ const navItems = [
   { to: '/cost', icon: DollarSign, labelKey: 'nav.cost' },
   { to: '/logs', icon: Activity, labelKey: 'nav.logs' },
   { to: '/doctor', icon: Stethoscope, labelKey: 'nav.doctor' },
+  { to: '/setup', icon: Rocket, labelKey: 'nav.setup' },
 ];
 
 export default function Sidebar() {
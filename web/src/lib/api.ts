@@ -8,6 +8,9 @@ import type {
   CostSummary,
   CliTool,
   HealthSnapshot,
+  InitStatus,
+  InitCheck,
+  InitAnswers,
 } from '../types/api';
 import { clearToken, getToken, setToken } from './auth';
 
@@ -235,3 +238,27 @@ export function getCliTools(): Promise<CliTool[]> {
     unwrapField(data, 'cli_tools'),
   );
 }
+
+// ---------------------------------------------------------------------------
+// Setup (zerobuild init)
+// ---------------------------------------------------------------------------
+
+export function getInitStatus(): Promise<InitStatus> {
+  return apiFetch<InitStatus>('/api/init');
+}
+
+export function testProviderKey(
+  body: Pick<InitAnswers, 'provider' | 'api_key' | 'api_url' | 'model'>,
+): Promise<InitCheck> {
+  return apiFetch<{ check: InitCheck }>('/api/init/test', {
+    method: 'POST',
+    body: JSON.stringify(body),
+  }).then((data) => data.check);
+}
+
+export function applyInit(answers: InitAnswers): Promise<void> {
+  return apiFetch<void>('/api/init', {
+    method: 'POST',
+    body: JSON.stringify(answers),
+  });
+}
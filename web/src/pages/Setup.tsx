@@ -0,0 +1,242 @@
+import { useState, useEffect } from 'react';
+import {
+  Rocket,
+  CheckCircle,
+  AlertTriangle,
+  Loader2,
+  Github,
+  Save,
+  FlaskConical,
+} from 'lucide-react';
+import type { InitCheck, InitStatus } from '@/types/api';
+import { getInitStatus, testProviderKey, applyInit } from '@/lib/api';
+
+function CheckRow({ check }: { check: InitCheck }) {
+  return (
+    <div className="flex items-start gap-3 py-2">
+      {check.ok ? (
+        <CheckCircle className="h-4 w-4 text-green-400 flex-shrink-0 mt-0.5" />
+      ) : (
+        <AlertTriangle className="h-4 w-4 text-yellow-400 flex-shrink-0 mt-0.5" />
+      )}
+      <div>
+        <p className="text-sm text-white font-medium capitalize">{check.name}</p>
+        <p className="text-sm text-gray-400">{check.detail}</p>
+      </div>
+    </div>
+  );
+}
+
+export default function Setup() {
+  const [status, setStatus] = useState<InitStatus | null>(null);
+  const [loading, setLoading] = useState(true);
+  const [error, setError] = useState<string | null>(null);
+  const [success, setSuccess] = useState<string | null>(null);
+
+  const [provider, setProvider] = useState('');
+  const [apiKey, setApiKey] = useState('');
+  const [apiUrl, setApiUrl] = useState('');
+  const [model, setModel] = useState('');
+  const [template, setTemplate] = useState('');
+  const [useDocker, setUseDocker] = useState(false);
+
+  const [keyCheck, setKeyCheck] = useState<InitCheck | null>(null);
+  const [testing, setTesting] = useState(false);
+  const [saving, setSaving] = useState(false);
+
+  useEffect(() => {
+    getInitStatus()
+      .then((data) => {
+        setStatus(data);
+        setProvider(data.current.provider ?? 'openrouter');
+        setModel(data.current.model ?? '');
+        setApiUrl(data.current.api_url ?? '');
+        setTemplate(data.current.template);
+        setUseDocker(data.current.use_docker);
+      })
+      .catch((err) => setError(err.message))
+      .finally(() => setLoading(false));
+  }, []);
+
+  const handleTest = async () => {
+    setTesting(true);
+    setKeyCheck(null);
+    try {
+      setKeyCheck(
+        await testProviderKey({ provider, api_key: apiKey, api_url: apiUrl, model }),
+      );
+    } catch (err: unknown) {
+      setError(err instanceof Error ? err.message : 'Failed to test provider');
+    } finally {
+      setTesting(false);
+    }
+  };
+
+  const handleSave = async () => {
+    setSaving(true);
+    setError(null);
+    setSuccess(null);
+    try {
+      await applyInit({
+        provider,
+        api_key: apiKey,
+        api_url: apiUrl,
+        model,
+        template,
+        use_docker: useDocker,
+      });
+      setSuccess('Configuration written and validated.');
+    } catch (err: unknown) {
+      setError(err instanceof Error ? err.message : 'Failed to write configuration');
+    } finally {
+      setSaving(false);
+    }
+  };
+
+  if (loading) {
+    return (
+      <div className="flex items-center justify-center h-64">
+        <div className="animate-spin rounded-full h-8 w-8 border-2 border-blue-500 border-t-transparent" />
+      </div>
+    );
+  }
+
+  const dockerReady = status?.checks.some((c) => c.name === 'docker' && c.ok) ?? false;
+  const inputClass =
+    'w-full bg-gray-800 border border-gray-700 rounded-lg px-3 py-2 text-sm text-white focus:outline-none focus:border-blue-500';
+
+  return (
+    <div className="p-6 space-y-6">
+      {/* Header */}
+      <div className="flex items-center justify-between">
+        <div className="flex items-center gap-2">
+          <Rocket className="h-5 w-5 text-blue-400" />
+          <h2 className="text-base font-semibold text-white">Setup</h2>
+        </div>
+        <button
+          onClick={handleSave}
+          disabled={saving || !provider}
+          className="flex items-center gap-2 bg-blue-600 hover:bg-blue-700 text-white text-sm font-medium px-4 py-2 rounded-lg transition-colors disabled:opacity-50"
+        >
+          <Save className="h-4 w-4" />
+          {saving ? 'Writing...' : 'Write Config'}
+        </button>
+      </div>
+
+      {error && (
+        <div className="rounded-lg bg-red-900/30 border border-red-700 p-4 text-red-300">
+          {error}
+        </div>
+      )}
+      {success && (
+        <div className="flex items-center gap-2 rounded-lg bg-green-900/30 border border-green-700 p-4 text-green-300">
+          <CheckCircle className="h-4 w-4" />
+          {success}
+        </div>
+      )}
+
+      {/* Environment */}
+      <div className="bg-gray-900 rounded-xl border border-gray-800 p-5">
+        <h3 className="text-sm font-semibold text-white mb-2">Environment</h3>
+        {status?.checks.map((check) => <CheckRow key={check.name} check={check} />)}
+      </div>
+
+      {/* Provider */}
+      <div className="bg-gray-900 rounded-xl border border-gray-800 p-5 space-y-3">
+        <h3 className="text-sm font-semibold text-white">AI Provider</h3>
+        <div className="grid grid-cols-1 md:grid-cols-2 gap-3">
+          <input
+            value={provider}
+            onChange={(e) => setProvider(e.target.value)}
+            placeholder="Provider (e.g. openrouter, anthropic, ollama)"
+            className={inputClass}
+          />
+          <input
+            value={model}
+            onChange={(e) => setModel(e.target.value)}
+            placeholder="Model (blank for the provider default)"
+            className={inputClass}
+          />
+          <input
+            type="password"
+            value={apiKey}
+            onChange={(e) => setApiKey(e.target.value)}
+            placeholder={status?.current.has_api_key ? 'API key (blank keeps the saved key)' : 'API key'}
+            className={inputClass}
+          />
+          <input
+            value={apiUrl}
+            onChange={(e) => setApiUrl(e.target.value)}
+            placeholder="Custom API URL (optional)"
+            className={inputClass}
+          />
+        </div>
+        <div className="flex items-center gap-3">
+          <button
+            onClick={handleTest}
+            disabled={testing || !provider}
+            className="flex items-center gap-2 bg-gray-800 hover:bg-gray-700 text-white text-sm px-3 py-2 rounded-lg transition-colors disabled:opacity-50"
+          >
+            {testing ? (
+              <Loader2 className="h-4 w-4 animate-spin" />
+            ) : (
+              <FlaskConical className="h-4 w-4" />
+            )}
+            Test Key
+          </button>
+          {keyCheck && <CheckRow check={keyCheck} />}
+        </div>
+      </div>
+
+      {/* GitHub */}
+      <div className="bg-gray-900 rounded-xl border border-gray-800 p-5 flex items-center justify-between">
+        <div className="flex items-center gap-3">
+          <Github className="h-5 w-5 text-gray-300" />
+          <div>
+            <h3 className="text-sm font-semibold text-white">GitHub</h3>
+            <p className="text-sm text-gray-400">
+              {status?.github_connected ? 'Connected' : 'Optional — needed to push code and open PRs'}
+            </p>
+          </div>
+        </div>
+        {!status?.github_connected && (
+          <a
+            href="/auth/github"
+            target="_blank"
+            rel="noreferrer"
+            className="bg-gray-800 hover:bg-gray-700 text-white text-sm px-3 py-2 rounded-lg transition-colors"
+          >
+            Connect
+          </a>
+        )}
+      </div>
+
+      {/* Template & runtime */}
+      <div className="bg-gray-900 rounded-xl border border-gray-800 p-5 space-y-3">
+        <h3 className="text-sm font-semibold text-white">Default Project Template</h3>
+        <select
+          value={template}
+          onChange={(e) => setTemplate(e.target.value)}
+          className={inputClass}
+        >
+          <option value="">No default — let the agent choose per project</option>
+          {status?.templates.map((t) => (
+            <option key={t.name} value={t.name}>
+              {t.name} — {t.description}
+            </option>
+          ))}
+        </select>
+        <label className="flex items-center gap-2 text-sm text-gray-300">
+          <input
+            type="checkbox"
+            checked={useDocker}
+            disabled={!dockerReady && !useDocker}
+            onChange={(e) => setUseDocker(e.target.checked)}
+          />
+          Run shell commands in Docker containers
+          {!dockerReady && <span className="text-gray-500">(Docker not available)</span>}
+        </label>
+      </div>
+    </div>
+  );
+}
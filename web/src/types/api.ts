@@ -88,6 +88,40 @@ export interface CliTool {
   category: string;
 }
 
+export interface InitCheck {
+  name: string;
+  ok: boolean;
+  detail: string;
+}
+
+export interface ProjectTemplate {
+  name: string;
+  description: string;
+}
+
+export interface InitStatus {
+  checks: InitCheck[];
+  templates: ProjectTemplate[];
+  github_connected: boolean;
+  current: {
+    provider: string | null;
+    model: string | null;
+    api_url: string | null;
+    has_api_key: boolean;
+    template: string;
+    use_docker: boolean;
+  };
+}
+
+export interface InitAnswers {
+  provider: string;
+  api_key?: string;
+  api_url?: string;
+  model?: string;
+  template?: string;
+  use_docker: boolean;
+}
+
 export interface SSEEvent {
   type: string;
   timestamp?: string;